@@ -0,0 +1,20 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Generates the gRPC server and client from proto/ when the `grpc` feature
+//! is enabled
+
+const PROTO: &str = "proto/conflow/v1/conflow.proto";
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed={}", PROTO);
+
+    #[cfg(feature = "grpc")]
+    {
+        let descriptors = protox::compile([PROTO], ["proto"]).expect("invalid gRPC definition");
+        tonic_prost_build::configure()
+            .compile_fds(descriptors)
+            .expect("failed to generate the gRPC service");
+    }
+}
@@ -36,9 +36,21 @@ package conflow
 	// Input specification
 	input: #Input
 
+	// Input files to leave out (.gitignore patterns)
+	exclude?: [...string]
+
 	// Output specification
 	output?: #Output
 
+	// Schema the output must satisfy: registry id or CUE file
+	contract?: string | {
+		schema:      string
+		definition?: string
+	}
+
+	// Compare output with its snapshot under .conflow/snapshots/
+	snapshot?: bool | *false
+
 	// Dependencies (other stage names)
 	depends_on?: [...string]
 
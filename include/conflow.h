@@ -0,0 +1,45 @@
+/* SPDX-License-Identifier: MIT OR Apache-2.0 */
+/* Copyright (c) 2025 conflow contributors */
+
+/*
+ * conflow C ABI
+ *
+ * Build the shared library with:
+ *
+ *     cargo rustc --lib --release --features ffi --crate-type cdylib
+ *
+ * Calls return a JSON string owned by the caller, to be released with
+ * conflow_string_free, or NULL on failure. conflow_last_error then
+ * describes the error until the next call on the same thread.
+ */
+
+#ifndef CONFLOW_H
+#define CONFLOW_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Library version (static, do not free) */
+const char *conflow_version(void);
+
+/* Analyze a configuration file; returns an "analysis" report */
+char *conflow_analyze(const char *path);
+
+/* Validate a pipeline file; returns {"valid", "errors", "warnings"} */
+char *conflow_validate(const char *path);
+
+/* Check RSR compliance of a project; returns a "compliance" report */
+char *conflow_check_compliance(const char *project_root);
+
+/* Message of the last error on this thread, or NULL (do not free) */
+const char *conflow_last_error(void);
+
+/* Release a string returned by conflow */
+void conflow_string_free(char *s);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* CONFLOW_H */
@@ -0,0 +1,415 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! YAML anchor, alias, and merge-key analysis
+//!
+//! Anchors (`&name`), aliases (`*name`) and merge keys (`<<:`) are YAML's
+//! only reuse mechanism. Heavy use means the config is reaching for
+//! abstraction, and nested aliases can expand a small file into a huge
+//! document (the "billion laughs" attack). Sizes are measured in bytes of
+//! text, as if every alias were replaced by a copy of its anchored node.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+use std::sync::LazyLock;
+
+static ANCHOR: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?m)(?:^|[\s\[\{,])&([^\s\[\]\{\},]+)").unwrap());
+
+static ALIAS: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?m)(?:^|[\s\[\{,])(\*([^\s\[\]\{\},]+))").unwrap());
+
+static MERGE_KEY: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?m)(?:^|[\s\{,])<<\s*:").unwrap());
+
+static BLOCK_SCALAR: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?:^|[:\-]\s)[|>][0-9+\-]*\s*$").unwrap());
+
+/// Expansion ratio above which aliases are worth a warning
+const WARN_RATIO: u64 = 10;
+
+/// Expanded size above which aliases are worth a warning
+const WARN_BYTES: u64 = 64 * 1024;
+
+/// Anchor and alias usage in a YAML document
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AliasUsage {
+    /// Anchors defined (`&name`)
+    pub anchors: usize,
+    /// Alias references (`*name`)
+    pub aliases: usize,
+    /// Merge keys (`<<:`)
+    pub merge_keys: usize,
+    /// Size as written, in bytes
+    pub source_bytes: u64,
+    /// Size with every alias expanded, in bytes
+    pub expanded_bytes: u64,
+}
+
+impl AliasUsage {
+    /// How many times larger the document is once expanded
+    pub fn expansion_ratio(&self) -> f64 {
+        if self.source_bytes == 0 {
+            1.0
+        } else {
+            self.expanded_bytes as f64 / self.source_bytes as f64
+        }
+    }
+
+    /// Whether anchors are used enough to suggest a real abstraction
+    pub fn is_heavy(&self) -> bool {
+        self.aliases >= 3 || self.merge_keys >= 2
+    }
+
+    /// Whether alias expansion blows the document up
+    pub fn is_explosive(&self) -> bool {
+        self.expanded_bytes >= WARN_BYTES
+            && self.expanded_bytes >= self.source_bytes.saturating_mul(WARN_RATIO)
+    }
+
+    /// Combine the usage of documents from the same file
+    pub fn merge(self, other: Self) -> Self {
+        Self {
+            anchors: self.anchors + other.anchors,
+            aliases: self.aliases + other.aliases,
+            merge_keys: self.merge_keys + other.merge_keys,
+            source_bytes: self.source_bytes + other.source_bytes,
+            expanded_bytes: self.expanded_bytes.saturating_add(other.expanded_bytes),
+        }
+    }
+}
+
+/// Analyze anchors, aliases and merge keys in a single YAML document
+pub fn analyze_aliases(content: &str) -> AliasUsage {
+    let masked = mask(content);
+
+    let anchors: Vec<(&str, Range<usize>)> = ANCHOR
+        .captures_iter(&masked)
+        .map(|c| {
+            let name = c.get(1).unwrap();
+            (name.as_str(), node_span(&masked, name.end()))
+        })
+        .collect();
+    let aliases: Vec<(&str, Range<usize>)> = ALIAS
+        .captures_iter(&masked)
+        .map(|c| (c.get(2).unwrap().as_str(), c.get(1).unwrap().range()))
+        .collect();
+
+    let mut sizes = vec![None; anchors.len()];
+    let expansion: u64 = aliases
+        .iter()
+        .map(|alias| expanded_alias(alias, &anchors, &aliases, &mut sizes, &mut Vec::new()))
+        .fold(0, u64::saturating_add);
+
+    AliasUsage {
+        anchors: anchors.len(),
+        aliases: aliases.len(),
+        merge_keys: MERGE_KEY.find_iter(&masked).count(),
+        source_bytes: content.len() as u64,
+        expanded_bytes: (content.len() as u64).saturating_add(expansion),
+    }
+}
+
+/// Bytes an alias adds when replaced by its anchored node
+fn expanded_alias(
+    alias: &(&str, Range<usize>),
+    anchors: &[(&str, Range<usize>)],
+    aliases: &[(&str, Range<usize>)],
+    sizes: &mut Vec<Option<u64>>,
+    visiting: &mut Vec<usize>,
+) -> u64 {
+    // The most recent anchor with that name before the alias
+    let target = anchors
+        .iter()
+        .rposition(|(name, span)| *name == alias.0 && span.start <= alias.1.start);
+    let Some(target) = target.filter(|t| !visiting.contains(t)) else {
+        return 0;
+    };
+
+    let size = match sizes[target] {
+        Some(size) => size,
+        None => {
+            visiting.push(target);
+            let span = &anchors[target].1;
+            let nested: u64 = aliases
+                .iter()
+                .filter(|(_, range)| span.contains(&range.start))
+                .map(|a| expanded_alias(a, anchors, aliases, sizes, visiting))
+                .fold(0, u64::saturating_add);
+            visiting.pop();
+
+            let size = (span.len() as u64).saturating_add(nested);
+            sizes[target] = Some(size);
+            size
+        }
+    };
+
+    size.saturating_sub(alias.1.len() as u64)
+}
+
+/// Byte range of the node following an anchor
+fn node_span(masked: &str, from: usize) -> Range<usize> {
+    let bytes = masked.as_bytes();
+    let mut start = from;
+    while start < bytes.len() && matches!(bytes[start], b' ' | b'\t') {
+        start += 1;
+    }
+
+    // Flow collection: up to the matching bracket
+    if start < bytes.len() && matches!(bytes[start], b'[' | b'{') {
+        let mut depth = 0usize;
+        for (i, &b) in bytes.iter().enumerate().skip(start) {
+            match b {
+                b'[' | b'{' => depth += 1,
+                b']' | b'}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return start..i + 1;
+                    }
+                }
+                _ => {}
+            }
+        }
+        return start..bytes.len();
+    }
+
+    // Scalar inside a flow collection: up to the next separator
+    if flow_depth(&bytes[..start]) > 0 {
+        let end = bytes[start..]
+            .iter()
+            .position(|b| matches!(b, b',' | b']' | b'}' | b'\n'))
+            .map_or(bytes.len(), |p| start + p);
+        return start..end;
+    }
+
+    // Block node: the rest of the line and every more-indented line after it
+    let line_start = masked[..start].rfind('\n').map_or(0, |i| i + 1);
+    let indent = indentation(&masked[line_start..]);
+    let head = masked[line_start..start].trim_end();
+    let head = match head.rfind(char::is_whitespace) {
+        Some(i) if head[i + 1..].starts_with('&') => head[..i].trim_end(),
+        None if head.starts_with('&') => "",
+        _ => head,
+    };
+    let is_key = head.ends_with(':');
+    let rest_empty = masked[start..]
+        .lines()
+        .next()
+        .unwrap_or("")
+        .trim()
+        .is_empty();
+
+    let mut end = masked[start..]
+        .find('\n')
+        .map_or(masked.len(), |i| start + i);
+    let mut cursor = (end + 1).min(masked.len());
+    for line in masked[cursor..].split_inclusive('\n') {
+        let line_start = cursor;
+        cursor += line.len();
+
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        let child = indentation(line) > indent
+            // A key's block sequence may sit at the key's own indentation
+            || (is_key && rest_empty && indentation(line) == indent && trimmed.starts_with("- "));
+        if !child {
+            break;
+        }
+        end = line_start + line.trim_end_matches('\n').len();
+    }
+    start..end
+}
+
+/// Unclosed flow brackets in `before`
+fn flow_depth(before: &[u8]) -> usize {
+    let line_start = before
+        .iter()
+        .rposition(|&b| b == b'\n')
+        .map_or(0, |i| i + 1);
+    // Flow collections rarely span lines; look back a bounded distance
+    let scan = &before[line_start.saturating_sub(4096)..];
+    scan.iter().fold(0usize, |depth, &b| match b {
+        b'[' | b'{' => depth + 1,
+        b']' | b'}' => depth.saturating_sub(1),
+        _ => depth,
+    })
+}
+
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+/// Blank out quoted strings, comments and block scalars
+///
+/// Offsets are preserved, so spans found in the result apply to the
+/// original text.
+fn mask(content: &str) -> String {
+    let mut out = content.as_bytes().to_vec();
+    let bytes = content.as_bytes();
+    let mut quote: Option<u8> = None;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let b = bytes[i];
+        let prev = if i == 0 { b'\n' } else { bytes[i - 1] };
+        match quote {
+            Some(q) => {
+                if q == b'"' && b == b'\\' {
+                    out[i] = b' ';
+                    if i + 1 < bytes.len() && bytes[i + 1] != b'\n' {
+                        out[i + 1] = b' ';
+                    }
+                    i += 2;
+                    continue;
+                }
+                if b == q {
+                    if q == b'\'' && bytes.get(i + 1) == Some(&b'\'') {
+                        out[i] = b' ';
+                        out[i + 1] = b' ';
+                        i += 2;
+                        continue;
+                    }
+                    quote = None;
+                } else if b != b'\n' {
+                    out[i] = b' ';
+                }
+            }
+            None => {
+                let token_start = matches!(prev, b' ' | b'\t' | b'\n' | b'[' | b'{' | b',' | b':');
+                if (b == b'"' || b == b'\'') && token_start {
+                    quote = Some(b);
+                } else if b == b'#' && matches!(prev, b' ' | b'\t' | b'\n') {
+                    while i < bytes.len() && bytes[i] != b'\n' {
+                        out[i] = b' ';
+                        i += 1;
+                    }
+                    continue;
+                }
+            }
+        }
+        i += 1;
+    }
+
+    // Block scalar bodies are literal text
+    let masked = String::from_utf8_lossy(&out).into_owned();
+    let mut result = masked.clone().into_bytes();
+    let mut offset = 0;
+    let mut scalar_indent: Option<usize> = None;
+    for line in masked.split_inclusive('\n') {
+        let trimmed = line.trim();
+        match scalar_indent {
+            Some(indent) if trimmed.is_empty() || indentation(line) > indent => {
+                for b in &mut result[offset..offset + line.trim_end_matches('\n').len()] {
+                    *b = b' ';
+                }
+            }
+            _ => {
+                scalar_indent = BLOCK_SCALAR
+                    .is_match(line.trim_end())
+                    .then(|| indentation(line));
+            }
+        }
+        offset += line.len();
+    }
+
+    String::from_utf8_lossy(&result).into_owned()
+}
+
+/// Human-readable byte size
+pub fn format_bytes(bytes: u64) -> String {
+    const KB: u64 = 1024;
+    const MB: u64 = KB * 1024;
+    const GB: u64 = MB * 1024;
+
+    if bytes >= GB {
+        format!("{:.1} GB", bytes as f64 / GB as f64)
+    } else if bytes >= MB {
+        format!("{:.1} MB", bytes as f64 / MB as f64)
+    } else if bytes >= KB {
+        format!("{:.1} KB", bytes as f64 / KB as f64)
+    } else {
+        format!("{} bytes", bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counts_anchors_aliases_and_merge_keys() {
+        let content = r#"defaults: &defaults
+  image: nginx
+  replicas: 2
+web:
+  <<: *defaults
+  name: web
+api:
+  <<: *defaults
+  name: "*not-an-alias &nor-an-anchor"
+# *commented
+"#;
+        let usage = analyze_aliases(content);
+
+        assert_eq!(usage.anchors, 1);
+        assert_eq!(usage.aliases, 2);
+        assert_eq!(usage.merge_keys, 2);
+        assert!(usage.is_heavy());
+
+        // Each alias adds the anchored block minus the alias itself
+        let block = "\n  image: nginx\n  replicas: 2".len() as u64;
+        let alias = "*defaults".len() as u64;
+        assert_eq!(
+            usage.expanded_bytes,
+            content.len() as u64 + 2 * (block - alias)
+        );
+        assert!(!usage.is_explosive());
+    }
+
+    #[test]
+    fn test_billion_laughs() {
+        let content = r#"a: &a ["lol","lol","lol","lol","lol","lol","lol","lol","lol"]
+b: &b [*a,*a,*a,*a,*a,*a,*a,*a,*a]
+c: &c [*b,*b,*b,*b,*b,*b,*b,*b,*b]
+d: &d [*c,*c,*c,*c,*c,*c,*c,*c,*c]
+e: &e [*d,*d,*d,*d,*d,*d,*d,*d,*d]
+f: &f [*e,*e,*e,*e,*e,*e,*e,*e,*e]
+g: &g [*f,*f,*f,*f,*f,*f,*f,*f,*f]
+h: &h [*g,*g,*g,*g,*g,*g,*g,*g,*g]
+i: &i [*h,*h,*h,*h,*h,*h,*h,*h,*h]
+"#;
+        let usage = analyze_aliases(content);
+
+        assert_eq!(usage.anchors, 9);
+        assert_eq!(usage.aliases, 72);
+        assert!(usage.expanded_bytes > 1_000_000_000);
+        assert!(usage.is_explosive());
+    }
+
+    #[test]
+    fn test_node_spans() {
+        let content = "list: &l\n- a\n- b\nnext: 1\nflow: [&x 1, *x]\nnested: &n\n  k: v\n";
+        let masked = mask(content);
+        let span = |name: &str| {
+            let at = masked.find(&format!("&{}", name)).unwrap() + name.len() + 1;
+            content[node_span(&masked, at)].to_string()
+        };
+
+        assert_eq!(span("l"), "\n- a\n- b");
+        assert_eq!(span("x"), "1");
+        assert_eq!(span("n"), "\n  k: v");
+    }
+
+    #[test]
+    fn test_mask_block_scalars() {
+        let content = "script: |\n  echo *x &y\nafter: *z\n";
+        let masked = mask(content);
+        assert_eq!(masked.len(), content.len());
+        assert!(!masked.contains("*x"));
+        assert!(masked.contains("*z"));
+    }
+}
@@ -3,10 +3,13 @@
 
 //! Complexity analysis for configuration files
 
+use serde::{Deserialize, Serialize};
+
+use super::aliases::{analyze_aliases, AliasUsage};
 use super::ConfigFormat;
 
 /// Complexity analysis result
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Complexity {
     /// Contains conditional logic (if/else, match, etc.)
     pub has_logic: bool,
@@ -24,6 +27,26 @@ pub struct Complexity {
     pub nesting_depth: usize,
     /// Contains repeated similar structures
     pub has_repetition: bool,
+    /// YAML anchor, alias, and merge-key usage
+    #[serde(default)]
+    pub aliases: AliasUsage,
+}
+
+impl Complexity {
+    /// Combine the analyses of documents from the same file
+    pub fn merge(self, other: Self) -> Self {
+        Self {
+            has_logic: self.has_logic || other.has_logic,
+            has_functions: self.has_functions || other.has_functions,
+            has_constraints: self.has_constraints || other.has_constraints,
+            has_validation: self.has_validation || other.has_validation,
+            has_generation: self.has_generation || other.has_generation,
+            line_count: self.line_count + other.line_count,
+            nesting_depth: self.nesting_depth.max(other.nesting_depth),
+            has_repetition: self.has_repetition || other.has_repetition,
+            aliases: self.aliases.merge(other.aliases),
+        }
+    }
 }
 
 /// Analyze the complexity of configuration content
@@ -37,6 +60,7 @@ pub fn analyze_complexity(content: &str, format: ConfigFormat) -> Complexity {
         line_count: content.lines().count(),
         nesting_depth: 0,
         has_repetition: false,
+        aliases: AliasUsage::default(),
     };
 
     // Check for logic patterns
@@ -57,6 +81,11 @@ pub fn analyze_complexity(content: &str, format: ConfigFormat) -> Complexity {
     // Check for repetition
     complexity.has_repetition = check_repetition(content);
 
+    // Anchors are YAML syntax; `&` and `*` mean other things elsewhere
+    if format == ConfigFormat::Yaml {
+        complexity.aliases = analyze_aliases(content);
+    }
+
     // Infer validation if constraints are present
     complexity.has_validation = complexity.has_constraints;
 
@@ -201,4 +230,25 @@ mod tests {
         assert!(!complexity.has_functions);
         assert!(!complexity.has_constraints);
     }
+
+    #[test]
+    fn test_merge() {
+        let a = analyze_complexity("{ a: { b: 1 } }", ConfigFormat::Yaml);
+        let b = analyze_complexity("x: if y", ConfigFormat::Yaml);
+        let merged = a.merge(b);
+
+        assert!(merged.has_logic);
+        assert_eq!(merged.nesting_depth, 2);
+        assert_eq!(merged.line_count, 2);
+    }
+
+    #[test]
+    fn test_aliases_only_for_yaml() {
+        let content = "a: &x 1\nb: *x\nc: *x\nd: *x\n";
+        assert!(analyze_complexity(content, ConfigFormat::Yaml).aliases.is_heavy());
+        assert_eq!(
+            analyze_complexity(content, ConfigFormat::Cue).aliases,
+            AliasUsage::default()
+        );
+    }
 }
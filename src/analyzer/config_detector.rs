@@ -3,12 +3,14 @@
 
 //! Configuration format detection
 
+use serde::{Deserialize, Serialize};
 use std::path::Path;
 
 use crate::errors::ConflowError;
 
 /// Detected configuration format
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum ConfigFormat {
     Json,
     Yaml,
@@ -36,23 +38,25 @@ pub fn detect_format(content: &str, path: &Path) -> Result<ConfigFormat, Conflow
     let trimmed = content.trim();
 
     // JSON detection
-    if (trimmed.starts_with('{') && trimmed.ends_with('}'))
-        || (trimmed.starts_with('[') && trimmed.ends_with(']'))
+    if ((trimmed.starts_with('{') && trimmed.ends_with('}'))
+        || (trimmed.starts_with('[') && trimmed.ends_with(']')))
+        && serde_json::from_str::<serde_json::Value>(content).is_ok()
     {
-        if serde_json::from_str::<serde_json::Value>(content).is_ok() {
-            return Ok(ConfigFormat::Json);
-        }
+        return Ok(ConfigFormat::Json);
     }
 
     // TOML detection (look for = assignments and [sections])
-    if trimmed.contains(" = ") || trimmed.contains("\n[") {
-        if toml::from_str::<toml::Value>(content).is_ok() {
-            return Ok(ConfigFormat::Toml);
-        }
+    if (trimmed.contains(" = ") || trimmed.contains("\n["))
+        && toml::from_str::<toml::Value>(content).is_ok()
+    {
+        return Ok(ConfigFormat::Toml);
     }
 
-    // YAML detection
-    if serde_yaml::from_str::<serde_yaml::Value>(content).is_ok() {
+    // YAML detection (each document of a stream must parse)
+    if crate::utils::yaml::documents(content)
+        .iter()
+        .all(|d| serde_yaml::from_str::<serde_yaml::Value>(d.content).is_ok())
+    {
         // Could be YAML (most formats are valid YAML)
         // Check for YAML-specific patterns
         if trimmed.contains(": ") || trimmed.starts_with("---") || trimmed.contains("\n- ") {
@@ -93,6 +97,13 @@ mod tests {
         assert_eq!(format, ConfigFormat::Yaml);
     }
 
+    #[test]
+    fn test_detect_yaml_stream() {
+        let content = "---\nkind: Service\n---\nkind: Deployment\n";
+        let format = detect_format(content, &PathBuf::from("manifests")).unwrap();
+        assert_eq!(format, ConfigFormat::Yaml);
+    }
+
     #[test]
     fn test_detect_toml() {
         let content = "[package]\nname = \"test\"";
@@ -0,0 +1,549 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Config dependency graph
+//!
+//! Finds which config files reference which across a project: Helm chart
+//! templates and the chart's values, kustomize overlays and their bases,
+//! Compose env files and includes, CUE package imports and Nickel imports.
+//! Following the references backwards answers "what does changing this
+//! file affect?".
+
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::path::{Component, Path, PathBuf};
+use std::sync::LazyLock;
+
+use super::parse_roots;
+use crate::errors::ConflowError;
+use crate::utils::walk::Walker;
+
+/// How one config file refers to another
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ReferenceKind {
+    /// A chart template renders the chart's values
+    HelmValues,
+    /// A chart depends on a local chart (`file://`)
+    HelmDependency,
+    /// A kustomization lists a base, resource, component or patch
+    KustomizeResource,
+    /// A kustomization generates a ConfigMap or Secret from a file
+    KustomizeGenerator,
+    /// A Compose service reads an env file
+    ComposeEnvFile,
+    /// A Compose file includes or extends another, or mounts a config
+    ComposeFile,
+    /// A CUE file imports a package of the module
+    CueImport,
+    /// A Nickel file imports another
+    NickelImport,
+}
+
+impl std::fmt::Display for ReferenceKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::HelmValues => "helm values",
+            Self::HelmDependency => "helm dependency",
+            Self::KustomizeResource => "kustomize resource",
+            Self::KustomizeGenerator => "kustomize generator",
+            Self::ComposeEnvFile => "compose env_file",
+            Self::ComposeFile => "compose file",
+            Self::CueImport => "cue import",
+            Self::NickelImport => "nickel import",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A reference from one config file to another
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct ConfigReference {
+    /// The referring file, relative to the project root
+    pub from: PathBuf,
+    /// The referenced file, relative to the project root
+    pub to: PathBuf,
+    pub kind: ReferenceKind,
+}
+
+/// References between the config files of a project
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ConfigGraph {
+    pub references: Vec<ConfigReference>,
+}
+
+/// `import "pkg"` and the entries of `import ( ... )` blocks
+static CUE_IMPORT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?m)^\s*import\s+(?:[\w.]+\s+)?"([^"]+)"|^\s*import\s*\(([^)]*)\)"#).unwrap()
+});
+static QUOTED: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#""([^"]+)""#).unwrap());
+static CUE_MODULE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"(?m)^\s*module:\s*"([^"@]+)"#).unwrap());
+static NICKEL_IMPORT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"\bimport\s+"([^"]+)""#).unwrap());
+
+impl ConfigGraph {
+    /// Scan the project's files under `root`, as its `walk:` settings and
+    /// `.conflowignore` select them
+    ///
+    /// Files that don't parse and references to files that don't exist are
+    /// left out; the graph only records what it can resolve.
+    pub fn scan(root: &Path) -> Result<Self, ConflowError> {
+        std::fs::read_dir(root).map_err(|e| ConflowError::Io {
+            message: format!("Failed to read '{}': {}", root.display(), e),
+        })?;
+        let files = Walker::load(root).files(root)?;
+
+        let modules: Vec<(String, PathBuf)> = files
+            .iter()
+            .filter(|f| f.ends_with("cue.mod/module.cue"))
+            .filter_map(|f| {
+                let content = std::fs::read_to_string(f).ok()?;
+                let module = CUE_MODULE.captures(&content)?[1].to_string();
+                Some((module, f.parent()?.parent()?.to_path_buf()))
+            })
+            .collect();
+
+        let mut found = Vec::new();
+        for file in &files {
+            let name = file
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or_default();
+            let extension = file.extension().and_then(|e| e.to_str());
+            let dir = file.parent().unwrap_or(root);
+
+            match (name, extension) {
+                ("kustomization.yaml" | "kustomization.yml" | "Kustomization", _) => {
+                    kustomization(file, dir, &mut found)
+                }
+                ("Chart.yaml", _) => helm_chart(file, dir, &files, &mut found),
+                (_, Some("yaml" | "yml"))
+                    if name.starts_with("compose") || name.starts_with("docker-compose") =>
+                {
+                    compose(file, dir, &mut found)
+                }
+                (_, Some("cue")) if !file.components().any(|c| c.as_os_str() == "cue.mod") => {
+                    cue_imports(file, &modules, &mut found)
+                }
+                (_, Some("ncl")) => {
+                    let content = std::fs::read_to_string(file).unwrap_or_default();
+                    for import in NICKEL_IMPORT.captures_iter(&content) {
+                        found.push(reference(
+                            file,
+                            dir.join(&import[1]),
+                            ReferenceKind::NickelImport,
+                        ));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let mut references: Vec<ConfigReference> = found
+            .into_iter()
+            .filter(|r| r.to.is_file())
+            .filter_map(|r| {
+                Some(ConfigReference {
+                    from: r.from.strip_prefix(root).ok()?.to_path_buf(),
+                    to: normalize(r.to.strip_prefix(root).ok()?),
+                    kind: r.kind,
+                })
+            })
+            .filter(|r| r.from != r.to)
+            .collect();
+        references.sort();
+        references.dedup();
+        Ok(Self { references })
+    }
+
+    /// Files affected by a change to `file`: those that reference it,
+    /// directly or through other files, in path order
+    pub fn affected_by(&self, file: &Path) -> Vec<PathBuf> {
+        let mut referrers: HashMap<&Path, Vec<&Path>> = HashMap::new();
+        for reference in &self.references {
+            referrers
+                .entry(&reference.to)
+                .or_default()
+                .push(&reference.from);
+        }
+
+        let mut affected = BTreeSet::new();
+        let mut queue = VecDeque::from([file]);
+        while let Some(next) = queue.pop_front() {
+            for &from in referrers.get(next).into_iter().flatten() {
+                if from != file && affected.insert(from.to_path_buf()) {
+                    queue.push_back(from);
+                }
+            }
+        }
+        affected.into_iter().collect()
+    }
+
+    /// Each referring file with the files it references
+    pub fn to_text(&self) -> String {
+        let mut by_file: BTreeMap<&Path, Vec<&ConfigReference>> = BTreeMap::new();
+        for reference in &self.references {
+            by_file.entry(&reference.from).or_default().push(reference);
+        }
+
+        let mut out = String::new();
+        for (file, references) in by_file {
+            out.push_str(&format!("{}\n", file.display()));
+            for reference in references {
+                out.push_str(&format!(
+                    "  → {} ({})\n",
+                    reference.to.display(),
+                    reference.kind
+                ));
+            }
+        }
+        out
+    }
+
+    /// Generate DOT diagram of the references
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph configs {\n");
+        out.push_str("    rankdir=LR;\n");
+        out.push_str("    node [shape=note];\n\n");
+        for reference in &self.references {
+            out.push_str(&format!(
+                "    \"{}\" -> \"{}\" [label=\"{}\"];\n",
+                reference.from.display(),
+                reference.to.display(),
+                reference.kind
+            ));
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Generate Mermaid diagram of the references
+    pub fn to_mermaid(&self) -> String {
+        let mut ids: BTreeMap<&Path, usize> = BTreeMap::new();
+        for reference in &self.references {
+            for path in [&reference.from, &reference.to] {
+                let next = ids.len();
+                ids.entry(path).or_insert(next);
+            }
+        }
+
+        let mut out = String::from("graph LR\n");
+        for (path, id) in &ids {
+            out.push_str(&format!("    n{}[\"{}\"]\n", id, path.display()));
+        }
+        for reference in &self.references {
+            out.push_str(&format!(
+                "    n{} -->|{}| n{}\n",
+                ids[reference.from.as_path()],
+                reference.kind,
+                ids[reference.to.as_path()]
+            ));
+        }
+        out
+    }
+}
+
+fn reference(from: &Path, to: PathBuf, kind: ReferenceKind) -> ConfigReference {
+    ConfigReference {
+        from: from.to_path_buf(),
+        to,
+        kind,
+    }
+}
+
+/// Parsed documents of a YAML file
+fn documents(file: &Path) -> Vec<Value> {
+    std::fs::read_to_string(file)
+        .map(|content| {
+            parse_roots(file, &content)
+                .into_iter()
+                .map(|(_, v)| v)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Strings of a value that is a string or a list of strings
+fn strings(value: &Value) -> Vec<&str> {
+    match value {
+        Value::String(s) => vec![s.as_str()],
+        Value::Array(items) => items.iter().filter_map(Value::as_str).collect(),
+        _ => vec![],
+    }
+}
+
+/// A kustomization entry: a file, or a directory holding a kustomization
+fn kustomize_target(dir: &Path, entry: &str) -> PathBuf {
+    let path = dir.join(entry);
+    if path.is_dir() {
+        ["kustomization.yaml", "kustomization.yml", "Kustomization"]
+            .iter()
+            .map(|name| path.join(name))
+            .find(|p| p.is_file())
+            .unwrap_or(path)
+    } else {
+        path
+    }
+}
+
+fn kustomization(file: &Path, dir: &Path, out: &mut Vec<ConfigReference>) {
+    let mut add = |to, kind| out.push(reference(file, to, kind));
+    for document in documents(file) {
+        for field in ["resources", "bases", "components", "patchesStrategicMerge"] {
+            for entry in strings(&document[field]) {
+                add(
+                    kustomize_target(dir, entry),
+                    ReferenceKind::KustomizeResource,
+                );
+            }
+        }
+        for patch in document["patches"].as_array().into_iter().flatten() {
+            if let Some(path) = patch["path"].as_str() {
+                add(dir.join(path), ReferenceKind::KustomizeResource);
+            }
+        }
+        for field in ["configMapGenerator", "secretGenerator"] {
+            for generator in document[field].as_array().into_iter().flatten() {
+                for entry in strings(&generator["files"])
+                    .into_iter()
+                    .chain(strings(&generator["envs"]))
+                    .chain(generator["env"].as_str())
+                {
+                    // `files` entries may be `key=path`
+                    let path = entry.split_once('=').map_or(entry, |(_, p)| p);
+                    add(dir.join(path), ReferenceKind::KustomizeGenerator);
+                }
+            }
+        }
+    }
+}
+
+fn helm_chart(file: &Path, dir: &Path, files: &[PathBuf], out: &mut Vec<ConfigReference>) {
+    let templates = dir.join("templates");
+    for template in files.iter().filter(|f| f.starts_with(&templates)) {
+        for values in ["values.yaml", "values.schema.json"] {
+            out.push(reference(
+                template,
+                dir.join(values),
+                ReferenceKind::HelmValues,
+            ));
+        }
+    }
+
+    for document in documents(file) {
+        for dependency in document["dependencies"].as_array().into_iter().flatten() {
+            if let Some(path) = dependency["repository"]
+                .as_str()
+                .and_then(|r| r.strip_prefix("file://"))
+            {
+                let chart = dir.join(path).join("Chart.yaml");
+                out.push(reference(file, chart, ReferenceKind::HelmDependency));
+            }
+        }
+    }
+}
+
+fn compose(file: &Path, dir: &Path, out: &mut Vec<ConfigReference>) {
+    let mut add = |to, kind| out.push(reference(file, to, kind));
+    let path_of = |entry: &Value| {
+        entry
+            .as_str()
+            .or_else(|| entry["path"].as_str())
+            .map(String::from)
+    };
+    let entries = |value: &Value| -> Vec<String> {
+        match value {
+            Value::Array(items) => items.iter().filter_map(path_of).collect(),
+            other => path_of(other).into_iter().collect(),
+        }
+    };
+
+    for document in documents(file) {
+        for entry in entries(&document["include"]) {
+            add(dir.join(entry), ReferenceKind::ComposeFile);
+        }
+        for service in document["services"]
+            .as_object()
+            .into_iter()
+            .flat_map(|s| s.values())
+        {
+            for entry in entries(&service["env_file"]) {
+                add(dir.join(entry), ReferenceKind::ComposeEnvFile);
+            }
+            if let Some(extends) = service["extends"]["file"].as_str() {
+                add(dir.join(extends), ReferenceKind::ComposeFile);
+            }
+        }
+        for section in ["configs", "secrets"] {
+            for item in document[section]
+                .as_object()
+                .into_iter()
+                .flat_map(|s| s.values())
+            {
+                if let Some(path) = item["file"].as_str() {
+                    add(dir.join(path), ReferenceKind::ComposeFile);
+                }
+            }
+        }
+    }
+}
+
+fn cue_imports(file: &Path, modules: &[(String, PathBuf)], out: &mut Vec<ConfigReference>) {
+    let Ok(content) = std::fs::read_to_string(file) else {
+        return;
+    };
+
+    let mut imports = Vec::new();
+    for captures in CUE_IMPORT.captures_iter(&content) {
+        match (captures.get(1), captures.get(2)) {
+            (Some(single), _) => imports.push(single.as_str().to_string()),
+            (None, Some(block)) => imports.extend(
+                QUOTED
+                    .captures_iter(block.as_str())
+                    .map(|c| c[1].to_string()),
+            ),
+            _ => {}
+        }
+    }
+
+    for import in imports {
+        // Drop a `:package` qualifier
+        let import = import.split(':').next().unwrap_or_default();
+        for (module, module_dir) in modules {
+            let Some(package) = import.strip_prefix(module.as_str()) else {
+                continue;
+            };
+            let package_dir = module_dir.join(package.trim_start_matches('/'));
+            let Ok(entries) = std::fs::read_dir(&package_dir) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.extension().is_some_and(|e| e == "cue") {
+                    out.push(reference(file, path, ReferenceKind::CueImport));
+                }
+            }
+        }
+    }
+}
+
+/// Resolve `.` and `..` without touching the filesystem
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(root: &Path, path: &str, content: &str) {
+        let path = root.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_scan_and_affected() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let root = temp.path();
+        write(
+            root,
+            "base/kustomization.yaml",
+            "resources: [deployment.yaml]\n",
+        );
+        write(root, "base/deployment.yaml", "kind: Deployment\n");
+        write(
+            root,
+            "overlays/prod/kustomization.yaml",
+            "resources: [../../base]\nconfigMapGenerator:\n  - name: app\n    files: [app=app.env]\n",
+        );
+        write(root, "overlays/prod/app.env", "A=1\n");
+        write(
+            root,
+            "compose.yaml",
+            "services:\n  web:\n    env_file: [web.env, { path: missing.env }]\n",
+        );
+        write(root, "web.env", "PORT=80\n");
+        write(root, "chart/Chart.yaml", "name: chart\n");
+        write(root, "chart/values.yaml", "replicas: 1\n");
+        write(
+            root,
+            "chart/templates/deployment.yaml",
+            "replicas: {{ .Values.replicas }}\n",
+        );
+        write(root, "cue.mod/module.cue", "module: \"example.com/cfg\"\n");
+        write(root, "schemas/base.cue", "package schemas\n");
+        write(
+            root,
+            "app.cue",
+            "package app\n\nimport (\n\t\"strings\"\n\t\"example.com/cfg/schemas\"\n)\n",
+        );
+        write(root, "lib.ncl", "{ port = 80 }\n");
+        write(root, "main.ncl", "let lib = import \"lib.ncl\" in lib\n");
+
+        let graph = ConfigGraph::scan(root).unwrap();
+        let edges: Vec<String> = graph
+            .references
+            .iter()
+            .map(|r| format!("{} -> {} ({})", r.from.display(), r.to.display(), r.kind))
+            .collect();
+        assert_eq!(
+            edges,
+            vec![
+                "app.cue -> schemas/base.cue (cue import)",
+                "base/kustomization.yaml -> base/deployment.yaml (kustomize resource)",
+                "chart/templates/deployment.yaml -> chart/values.yaml (helm values)",
+                "compose.yaml -> web.env (compose env_file)",
+                "main.ncl -> lib.ncl (nickel import)",
+                "overlays/prod/kustomization.yaml -> base/kustomization.yaml (kustomize resource)",
+                "overlays/prod/kustomization.yaml -> overlays/prod/app.env (kustomize generator)",
+            ]
+        );
+
+        assert_eq!(
+            graph.affected_by(Path::new("base/deployment.yaml")),
+            vec![
+                PathBuf::from("base/kustomization.yaml"),
+                PathBuf::from("overlays/prod/kustomization.yaml"),
+            ]
+        );
+        assert!(graph.affected_by(Path::new("compose.yaml")).is_empty());
+        assert!(graph.to_mermaid().contains("-->|cue import|"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_scan_walks_like_the_project() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let root = temp.path();
+        write(root, "main.ncl", "let lib = import \"lib.ncl\" in lib\n");
+        write(root, "lib.ncl", "{ port = 80 }\n");
+        write(
+            root,
+            "old/main.ncl",
+            "let lib = import \"../lib.ncl\" in lib\n",
+        );
+        write(root, ".conflowignore", "old/\n");
+        // Links back up the tree end the walk rather than hanging it
+        std::os::unix::fs::symlink(".", root.join("l1")).unwrap();
+        std::os::unix::fs::symlink("l1", root.join("l2")).unwrap();
+        std::os::unix::fs::symlink("l2", root.join("l3")).unwrap();
+
+        let graph = ConfigGraph::scan(root).unwrap();
+        let from: Vec<_> = graph.references.iter().map(|r| r.from.clone()).collect();
+        assert_eq!(from, vec![PathBuf::from("main.ncl")]);
+    }
+}
@@ -0,0 +1,679 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Cost estimation
+//!
+//! Estimates the monthly cost of a Terraform plan, or of the resources
+//! Kubernetes manifests request, and how much a change adds or saves. The
+//! built-in pricing table holds approximate on-demand list prices for
+//! common resources; pipelines can override or extend it. Infracost output
+//! is read into the same estimate.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::errors::ConflowError;
+
+/// Built-in prices in USD per month
+const BUILTIN_PRICING: &str = r#"
+kubernetes:
+  cpu: 23.07
+  memory: 3.10
+  storage: 0.08
+  nodes: 1
+resources:
+  aws_instance:
+    attribute: instance_type
+    prices:
+      t3.nano: 3.80
+      t3.micro: 7.59
+      t3.small: 15.18
+      t3.medium: 30.37
+      t3.large: 60.74
+      t3.xlarge: 121.47
+      m5.large: 70.08
+      m5.xlarge: 140.16
+      m5.2xlarge: 280.32
+      m6i.large: 70.08
+      m6i.xlarge: 140.16
+      c5.large: 62.05
+      c5.xlarge: 124.10
+      r5.large: 91.98
+      r5.xlarge: 183.96
+  aws_db_instance:
+    attribute: instance_class
+    prices:
+      db.t3.micro: 12.41
+      db.t3.small: 24.82
+      db.t3.medium: 49.64
+      db.t3.large: 99.28
+      db.m5.large: 124.10
+      db.m5.xlarge: 248.20
+      db.r5.large: 175.20
+  aws_elasticache_cluster:
+    attribute: node_type
+    prices:
+      cache.t3.micro: 12.41
+      cache.t3.small: 24.82
+      cache.t3.medium: 49.64
+      cache.m5.large: 113.88
+  aws_ebs_volume: { attribute: size, per_unit: 0.08 }
+  aws_nat_gateway: 32.85
+  aws_lb: 16.43
+  aws_alb: 16.43
+  aws_eip: 3.65
+  aws_eks_cluster: 73.00
+  aws_kms_key: 1.00
+  aws_secretsmanager_secret: 0.40
+  aws_route53_zone: 0.50
+  google_compute_instance:
+    attribute: machine_type
+    prices:
+      e2-micro: 6.11
+      e2-small: 12.23
+      e2-medium: 24.46
+      e2-standard-2: 48.92
+      e2-standard-4: 97.83
+      n1-standard-1: 24.27
+      n1-standard-2: 48.55
+      n2-standard-2: 70.90
+      n2-standard-4: 141.80
+  google_compute_disk: { attribute: size, per_unit: 0.04 }
+  google_container_cluster: 73.00
+  google_sql_database_instance:
+    attribute: settings.0.tier
+    prices:
+      db-f1-micro: 7.67
+      db-g1-small: 25.55
+  azurerm_linux_virtual_machine:
+    attribute: size
+    prices:
+      Standard_B1s: 7.59
+      Standard_B2s: 30.37
+      Standard_D2s_v3: 70.08
+      Standard_D4s_v3: 140.16
+"#;
+
+/// Largest change considered no change at all
+const EPSILON: f64 = 0.005;
+
+/// Prices used for estimates, in USD per month
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Pricing {
+    /// Prices of Kubernetes resource requests
+    #[serde(default)]
+    pub kubernetes: KubernetesPrices,
+
+    /// Prices of Terraform resources, by resource type
+    #[serde(default)]
+    pub resources: BTreeMap<String, ResourcePrice>,
+}
+
+/// Prices of Kubernetes resource requests
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct KubernetesPrices {
+    /// Per vCPU requested
+    #[serde(default)]
+    pub cpu: Option<f64>,
+    /// Per GiB of memory requested
+    #[serde(default)]
+    pub memory: Option<f64>,
+    /// Per GiB of persistent volume claimed
+    #[serde(default)]
+    pub storage: Option<f64>,
+    /// Nodes a DaemonSet runs on
+    #[serde(default)]
+    pub nodes: Option<f64>,
+}
+
+/// Monthly price of a Terraform resource type
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum ResourcePrice {
+    /// The same price for every resource
+    Flat(f64),
+    /// A price for each value of an attribute, such as `instance_type`
+    ByAttribute {
+        /// Dotted path of the attribute; list indices are numbers
+        attribute: String,
+        prices: BTreeMap<String, f64>,
+    },
+    /// A price per unit of a numeric attribute, such as a disk's `size`
+    PerUnit { attribute: String, per_unit: f64 },
+}
+
+impl ResourcePrice {
+    /// Monthly price of a resource with these attributes, if known
+    pub fn monthly(&self, values: &Value) -> Option<f64> {
+        match self {
+            Self::Flat(price) => Some(*price),
+            Self::ByAttribute { attribute, prices } => prices
+                .get(attribute_at(values, attribute)?.as_str()?)
+                .copied(),
+            Self::PerUnit {
+                attribute,
+                per_unit,
+            } => Some(attribute_at(values, attribute)?.as_f64()? * per_unit),
+        }
+    }
+}
+
+fn attribute_at<'a>(values: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.').try_fold(values, |value, key| match value {
+        Value::Array(items) => items.get(key.parse::<usize>().ok()?),
+        _ => value.get(key),
+    })
+}
+
+impl Pricing {
+    /// The built-in pricing table
+    pub fn builtin() -> Self {
+        serde_yaml::from_str(BUILTIN_PRICING).expect("built-in pricing table is valid")
+    }
+
+    /// The built-in table with the prices in a YAML or JSON file on top
+    pub fn load(path: &Path) -> Result<Self, ConflowError> {
+        let content = std::fs::read_to_string(path).map_err(|e| ConflowError::FileReadError {
+            path: path.to_path_buf(),
+            error: e.to_string(),
+        })?;
+        let overrides = crate::utils::yaml::parse(&content, &path.display().to_string())?;
+        Ok(Self::builtin().merge(overrides))
+    }
+
+    /// These prices, with `other` taking precedence
+    pub fn merge(mut self, other: Pricing) -> Self {
+        let k8s = &mut self.kubernetes;
+        k8s.cpu = other.kubernetes.cpu.or(k8s.cpu);
+        k8s.memory = other.kubernetes.memory.or(k8s.memory);
+        k8s.storage = other.kubernetes.storage.or(k8s.storage);
+        k8s.nodes = other.kubernetes.nodes.or(k8s.nodes);
+        self.resources.extend(other.resources);
+        self
+    }
+}
+
+/// Estimated monthly cost of a stage's inputs, in USD
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CostEstimate {
+    /// Monthly cost after the change
+    pub monthly: f64,
+    /// Monthly cost before the change, when there is something to compare
+    #[serde(default)]
+    pub previous: Option<f64>,
+    /// Cost of each priced resource
+    #[serde(default)]
+    pub resources: Vec<ResourceCost>,
+    /// Resource types without a price, counted as free
+    #[serde(default)]
+    pub unpriced: Vec<String>,
+}
+
+/// Monthly cost of one resource
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResourceCost {
+    /// Terraform address, or `kind/name` for Kubernetes
+    pub name: String,
+    /// Cost after the change
+    pub monthly: f64,
+    /// Cost before the change; `None` for new resources
+    #[serde(default)]
+    pub previous: Option<f64>,
+}
+
+impl ResourceCost {
+    /// How much the change adds (or, when negative, saves)
+    pub fn delta(&self) -> f64 {
+        self.monthly - self.previous.unwrap_or(0.0)
+    }
+}
+
+impl CostEstimate {
+    /// Where a cost stage records its estimate
+    pub fn path(working_dir: &Path, stage: &str) -> PathBuf {
+        working_dir
+            .join(".conflow")
+            .join("costs")
+            .join(format!("{}.json", stage))
+    }
+
+    /// The estimate a cost stage recorded, if any
+    pub fn load(working_dir: &Path, stage: &str) -> Option<Self> {
+        let content = std::fs::read_to_string(Self::path(working_dir, stage)).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    /// How much the change adds (or, when negative, saves)
+    pub fn delta(&self) -> Option<f64> {
+        self.previous.map(|previous| self.monthly - previous)
+    }
+
+    /// Resources whose cost changed, largest change first
+    pub fn changed(&self) -> Vec<&ResourceCost> {
+        let mut changed: Vec<_> = self
+            .resources
+            .iter()
+            .filter(|r| r.delta().abs() >= EPSILON)
+            .collect();
+        changed.sort_by(|a, b| b.delta().abs().total_cmp(&a.delta().abs()));
+        changed
+    }
+
+    /// Add another estimate's resources to this one
+    pub fn add(&mut self, other: CostEstimate) {
+        self.monthly += other.monthly;
+        self.previous = self.previous.zip(other.previous).map(|(a, b)| a + b);
+        self.resources.extend(other.resources);
+        self.unpriced.extend(other.unpriced);
+        self.unpriced.sort();
+        self.unpriced.dedup();
+    }
+
+    /// One-line summary, e.g. "adds ~$420/mo ($1,000 → $1,420/mo)"
+    pub fn summary(&self) -> String {
+        let monthly = money(self.monthly);
+        match (self.previous, self.delta()) {
+            (Some(previous), Some(delta)) if delta >= EPSILON => format!(
+                "adds ~{}/mo ({} → {}/mo)",
+                money(delta),
+                money(previous),
+                monthly
+            ),
+            (Some(previous), Some(delta)) if delta <= -EPSILON => format!(
+                "saves ~{}/mo ({} → {}/mo)",
+                money(-delta),
+                money(previous),
+                monthly
+            ),
+            (Some(_), _) => format!("doesn't change the estimated {}/mo", monthly),
+            _ => format!("is estimated at ~{}/mo", monthly),
+        }
+    }
+}
+
+/// Format dollars: whole dollars from $100, cents below
+pub fn money(amount: f64) -> String {
+    let sign = if amount < 0.0 { "-" } else { "" };
+    let amount = amount.abs();
+    if amount < 100.0 {
+        return format!("{}${:.2}", sign, amount);
+    }
+
+    let digits = format!("{:.0}", amount);
+    let mut grouped = String::new();
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    format!("{}${}", sign, grouped)
+}
+
+/// Estimate the cost of a plan from `terraform show -json`
+///
+/// Data sources are skipped; managed resources without a price are listed
+/// in [`CostEstimate::unpriced`].
+pub fn estimate_terraform_plan(plan: &Value, pricing: &Pricing) -> CostEstimate {
+    let mut estimate = CostEstimate::default();
+    let mut previous = 0.0;
+    let changes = plan["resource_changes"].as_array().into_iter().flatten();
+
+    for change in changes {
+        if change["mode"].as_str() == Some("data") {
+            continue;
+        }
+        let Some(kind) = change["type"].as_str() else {
+            continue;
+        };
+        let (before, after) = (&change["change"]["before"], &change["change"]["after"]);
+        let Some(price) = pricing.resources.get(kind) else {
+            if !before.is_null() || !after.is_null() {
+                estimate.unpriced.push(kind.to_string());
+            }
+            continue;
+        };
+
+        let cost =
+            |values: &Value| (!values.is_null()).then(|| price.monthly(values).unwrap_or(0.0));
+        let resource = ResourceCost {
+            name: change["address"].as_str().unwrap_or(kind).to_string(),
+            monthly: cost(after).unwrap_or(0.0),
+            previous: cost(before),
+        };
+        estimate.monthly += resource.monthly;
+        previous += resource.previous.unwrap_or(0.0);
+        estimate.resources.push(resource);
+    }
+
+    estimate.previous = Some(previous);
+    estimate.unpriced.sort();
+    estimate.unpriced.dedup();
+    estimate
+}
+
+/// Read `infracost breakdown --format json` output into an estimate
+pub fn estimate_infracost(output: &Value) -> CostEstimate {
+    let amount = |value: &Value| match value {
+        Value::String(s) => s.parse::<f64>().ok(),
+        value => value.as_f64(),
+    };
+    let resources = |breakdown: &str| -> BTreeMap<String, f64> {
+        output["projects"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .flat_map(|p| p[breakdown]["resources"].as_array().into_iter().flatten())
+            .filter_map(|r| Some((r["name"].as_str()?.to_string(), amount(&r["monthlyCost"])?)))
+            .collect()
+    };
+
+    let mut previous = resources("pastBreakdown");
+    let mut estimate = CostEstimate {
+        monthly: amount(&output["totalMonthlyCost"]).unwrap_or(0.0),
+        previous: amount(&output["pastTotalMonthlyCost"]),
+        resources: resources("breakdown")
+            .into_iter()
+            .map(|(name, monthly)| ResourceCost {
+                previous: previous.remove(&name),
+                name,
+                monthly,
+            })
+            .collect(),
+        unpriced: output["summary"]["unsupportedResourceCounts"]
+            .as_object()
+            .map(|counts| counts.keys().cloned().collect())
+            .unwrap_or_default(),
+    };
+    // Resources the change removes
+    estimate
+        .resources
+        .extend(previous.into_iter().map(|(name, previous)| ResourceCost {
+            name,
+            monthly: 0.0,
+            previous: Some(previous),
+        }));
+    estimate
+}
+
+/// Estimate the cost of the resources Kubernetes manifests request
+///
+/// With `previous` manifests the estimate compares against them. Pods
+/// count their containers' requests (or limits, when there are no
+/// requests) once per replica; DaemonSets once per node.
+pub fn estimate_kubernetes(
+    documents: &[Value],
+    previous: Option<&[Value]>,
+    pricing: &Pricing,
+) -> CostEstimate {
+    let current = kubernetes_costs(documents, pricing);
+    let mut before = previous.map(|documents| kubernetes_costs(documents, pricing));
+
+    let mut resources: Vec<ResourceCost> = current
+        .into_iter()
+        .map(|(name, monthly)| ResourceCost {
+            previous: before.as_mut().and_then(|b| b.remove(&name)),
+            name,
+            monthly,
+        })
+        .collect();
+    if let Some(removed) = &before {
+        resources.extend(removed.iter().map(|(name, previous)| ResourceCost {
+            name: name.clone(),
+            monthly: 0.0,
+            previous: Some(*previous),
+        }));
+    }
+
+    CostEstimate {
+        monthly: resources.iter().map(|r| r.monthly).sum(),
+        previous: previous.map(|_| resources.iter().filter_map(|r| r.previous).sum()),
+        resources,
+        unpriced: vec![],
+    }
+}
+
+/// Monthly cost of each workload and volume claim, by `namespace/kind/name`
+fn kubernetes_costs(documents: &[Value], pricing: &Pricing) -> BTreeMap<String, f64> {
+    let mut costs = BTreeMap::new();
+    for document in documents {
+        for (_, manifest) in super::kubernetes::manifests(document) {
+            let Some(cost) = manifest_cost(manifest, pricing) else {
+                continue;
+            };
+            let kind = manifest["kind"].as_str().unwrap_or_default();
+            let name = manifest["metadata"]["name"].as_str().unwrap_or_default();
+            let name = match manifest["metadata"]["namespace"].as_str() {
+                Some(namespace) => format!("{}/{}/{}", namespace, kind, name),
+                None => format!("{}/{}", kind, name),
+            };
+            *costs.entry(name).or_insert(0.0) += cost;
+        }
+    }
+    costs
+}
+
+fn manifest_cost(manifest: &Value, pricing: &Pricing) -> Option<f64> {
+    let prices = &pricing.kubernetes;
+    let (pod, replicas) = match manifest["kind"].as_str()? {
+        "Pod" => (&manifest["spec"], 1.0),
+        "Deployment" | "StatefulSet" | "ReplicaSet" | "ReplicationController" => (
+            &manifest["spec"]["template"]["spec"],
+            manifest["spec"]["replicas"].as_f64().unwrap_or(1.0),
+        ),
+        "DaemonSet" => (
+            &manifest["spec"]["template"]["spec"],
+            prices.nodes.unwrap_or(1.0),
+        ),
+        "PersistentVolumeClaim" => return Some(claim_cost(manifest, pricing)),
+        _ => return None,
+    };
+
+    let mut cost = 0.0;
+    for container in pod["containers"].as_array().into_iter().flatten() {
+        let resources = &container["resources"];
+        let quantity = |name: &str| {
+            let requests = &resources["requests"][name];
+            if requests.is_null() {
+                &resources["limits"][name]
+            } else {
+                requests
+            }
+        };
+        cost += cpu(quantity("cpu")).unwrap_or(0.0) * prices.cpu.unwrap_or(0.0);
+        cost += gibibytes(quantity("memory")).unwrap_or(0.0) * prices.memory.unwrap_or(0.0);
+    }
+    for claim in manifest["spec"]["volumeClaimTemplates"]
+        .as_array()
+        .into_iter()
+        .flatten()
+    {
+        cost += claim_cost(claim, pricing);
+    }
+
+    Some(cost * replicas)
+}
+
+fn claim_cost(claim: &Value, pricing: &Pricing) -> f64 {
+    let size = gibibytes(&claim["spec"]["resources"]["requests"]["storage"]);
+    size.unwrap_or(0.0) * pricing.kubernetes.storage.unwrap_or(0.0)
+}
+
+/// CPUs in a quantity such as `500m` or `2`
+fn cpu(quantity: &Value) -> Option<f64> {
+    match quantity {
+        Value::String(s) => match s.strip_suffix('m') {
+            Some(millis) => millis.parse::<f64>().ok().map(|m| m / 1000.0),
+            None => s.parse().ok(),
+        },
+        value => value.as_f64(),
+    }
+}
+
+/// GiB in a quantity such as `512Mi`, `1G` or a number of bytes
+fn gibibytes(quantity: &Value) -> Option<f64> {
+    const SUFFIXES: &[(&str, f64)] = &[
+        ("Ki", 1024.0),
+        ("Mi", 1048576.0),
+        ("Gi", 1073741824.0),
+        ("Ti", 1099511627776.0),
+        ("k", 1e3),
+        ("M", 1e6),
+        ("G", 1e9),
+        ("T", 1e12),
+    ];
+
+    let bytes = match quantity {
+        Value::String(s) => SUFFIXES
+            .iter()
+            .find_map(|(suffix, factor)| {
+                Some(s.strip_suffix(suffix)?.parse::<f64>().ok()? * factor)
+            })
+            .or_else(|| s.parse().ok())?,
+        value => value.as_f64()?,
+    };
+    Some(bytes / 1073741824.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_terraform_plan_estimate() {
+        let plan = json!({
+            "resource_changes": [
+                {
+                    "address": "aws_instance.web",
+                    "type": "aws_instance",
+                    "mode": "managed",
+                    "change": {
+                        "before": { "instance_type": "t3.micro" },
+                        "after": { "instance_type": "t3.large" }
+                    }
+                },
+                {
+                    "address": "aws_nat_gateway.main",
+                    "type": "aws_nat_gateway",
+                    "mode": "managed",
+                    "change": { "before": null, "after": {} }
+                },
+                {
+                    "address": "aws_ebs_volume.data",
+                    "type": "aws_ebs_volume",
+                    "mode": "managed",
+                    "change": { "before": { "size": 100 }, "after": null }
+                },
+                {
+                    "address": "aws_security_group.web",
+                    "type": "aws_security_group",
+                    "mode": "managed",
+                    "change": { "before": {}, "after": {} }
+                },
+                {
+                    "address": "data.aws_ami.ubuntu",
+                    "type": "aws_ami",
+                    "mode": "data",
+                    "change": { "before": null, "after": {} }
+                }
+            ]
+        });
+
+        let estimate = estimate_terraform_plan(&plan, &Pricing::builtin());
+        assert_eq!(estimate.resources.len(), 3);
+        assert_eq!(estimate.unpriced, vec!["aws_security_group"]);
+        assert!((estimate.monthly - (60.74 + 32.85)).abs() < 1e-9);
+        assert!((estimate.previous.unwrap() - (7.59 + 8.0)).abs() < 1e-9);
+
+        let changed = estimate.changed();
+        assert_eq!(changed[0].name, "aws_instance.web");
+        assert_eq!(changed[1].name, "aws_nat_gateway.main");
+        assert_eq!(changed[2].name, "aws_ebs_volume.data");
+        assert_eq!(estimate.summary(), "adds ~$78.00/mo ($15.59 → $93.59/mo)");
+    }
+
+    #[test]
+    fn test_kubernetes_estimate() {
+        let deployment = |replicas: u64, cpu: &str| {
+            json!({
+                "apiVersion": "apps/v1",
+                "kind": "Deployment",
+                "metadata": { "name": "web", "namespace": "prod" },
+                "spec": {
+                    "replicas": replicas,
+                    "template": { "spec": { "containers": [{
+                        "name": "web",
+                        "resources": { "requests": { "cpu": cpu, "memory": "512Mi" } }
+                    }] } }
+                }
+            })
+        };
+        let claim = json!({
+            "apiVersion": "v1",
+            "kind": "PersistentVolumeClaim",
+            "metadata": { "name": "data" },
+            "spec": { "resources": { "requests": { "storage": "50Gi" } } }
+        });
+        let pricing = Pricing::builtin()
+            .merge(serde_yaml::from_str("kubernetes: { cpu: 20, memory: 2 }").unwrap());
+
+        let before = [deployment(2, "500m")];
+        let after = [deployment(4, "1"), claim];
+        let estimate = estimate_kubernetes(&after, Some(&before), &pricing);
+
+        // 2 × (0.5 × $20 + 0.5 × $2) before; 4 × (1 × $20 + 0.5 × $2) and 50 × $0.08 after
+        assert_eq!(estimate.previous, Some(22.0));
+        assert!((estimate.monthly - 88.0).abs() < 1e-9);
+        assert_eq!(estimate.changed()[0].name, "prod/Deployment/web");
+        assert_eq!(estimate.changed()[1].previous, None);
+        assert_eq!(estimate.summary(), "adds ~$66.00/mo ($22.00 → $88.00/mo)");
+
+        let unchanged = estimate_kubernetes(&before, Some(&before), &pricing);
+        assert!(unchanged.summary().starts_with("doesn't change"));
+        assert_eq!(
+            estimate_kubernetes(&before, None, &pricing).summary(),
+            "is estimated at ~$22.00/mo"
+        );
+    }
+
+    #[test]
+    fn test_infracost_estimate() {
+        let output = json!({
+            "totalMonthlyCost": "1420.4",
+            "pastTotalMonthlyCost": "1000",
+            "projects": [{
+                "breakdown": { "resources": [
+                    { "name": "aws_instance.web", "monthlyCost": "1420.4" }
+                ] },
+                "pastBreakdown": { "resources": [
+                    { "name": "aws_instance.web", "monthlyCost": "900" },
+                    { "name": "aws_eip.old", "monthlyCost": "100" }
+                ] }
+            }],
+            "summary": { "unsupportedResourceCounts": { "aws_foo": 1 } }
+        });
+
+        let estimate = estimate_infracost(&output);
+        assert!((estimate.delta().unwrap() - 420.4).abs() < 1e-9);
+        assert_eq!(estimate.unpriced, vec!["aws_foo"]);
+        assert_eq!(estimate.changed()[0].name, "aws_instance.web");
+        assert_eq!(estimate.changed()[1].delta(), -100.0);
+        assert_eq!(estimate.summary(), "adds ~$420/mo ($1,000 → $1,420/mo)");
+    }
+
+    #[test]
+    fn test_quantities() {
+        assert_eq!(cpu(&json!("250m")), Some(0.25));
+        assert_eq!(cpu(&json!(2)), Some(2.0));
+        assert_eq!(gibibytes(&json!("2Gi")), Some(2.0));
+        assert_eq!(gibibytes(&json!("512Mi")), Some(0.5));
+        assert_eq!(gibibytes(&json!(1073741824u64)), Some(1.0));
+        assert_eq!(money(1234567.0), "$1,234,567");
+        assert_eq!(money(-12.5), "-$12.50");
+    }
+}
@@ -0,0 +1,801 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Near-duplicate block detection
+//!
+//! Finds mappings that share a shape (the same set of key paths) and most
+//! of their values across files and documents, such as a dozen
+//! Deployments differing only in name and image. Each group becomes a CUE
+//! definition or Nickel function taking the varying values as parameters.
+
+#[cfg(feature = "native")]
+use rayon::prelude::*;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use super::templates::detect_template;
+use crate::cache::ParseCache;
+
+/// Smallest group worth abstracting
+pub const MIN_DUPLICATES: usize = 3;
+
+/// Smallest block (in leaf values) worth abstracting
+const MIN_LEAVES: usize = 4;
+
+/// Share of values a block must have in common with the group
+const MIN_AGREEMENT: f64 = 0.5;
+
+/// A step in a path through a config value
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+/// Render segments as `a.b[0].c`
+pub fn dotted(path: &[Segment]) -> String {
+    let mut out = String::new();
+    for segment in path {
+        match segment {
+            Segment::Key(key) => {
+                if !out.is_empty() {
+                    out.push('.');
+                }
+                out.push_str(key);
+            }
+            Segment::Index(i) => out.push_str(&format!("[{}]", i)),
+        }
+    }
+    out
+}
+
+/// Where a block lives
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockLocation {
+    pub file: PathBuf,
+    /// Document index in a multi-document YAML stream
+    pub document: Option<usize>,
+    /// Path to the block inside the document
+    pub path: Vec<Segment>,
+}
+
+impl std::fmt::Display for BlockLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.file.display())?;
+        if let Some(document) = self.document {
+            write!(f, " (document {})", document + 1)?;
+        }
+        if !self.path.is_empty() {
+            write!(f, ": {}", dotted(&self.path))?;
+        }
+        Ok(())
+    }
+}
+
+/// A value that differs between the blocks of a group
+#[derive(Debug, Clone)]
+pub struct Parameter {
+    /// Identifier used in the generated abstraction
+    pub name: String,
+    /// Path inside the block
+    pub path: Vec<Segment>,
+    /// Value in each block, in member order
+    pub values: Vec<Value>,
+}
+
+/// A group of near-identical blocks
+#[derive(Debug, Clone)]
+pub struct DuplicateGroup {
+    /// Name for the abstraction, e.g. `Deployment`
+    pub name: String,
+    pub members: Vec<BlockLocation>,
+    /// Shared structure; parameter positions hold the first member's value
+    pub template: Value,
+    pub parameters: Vec<Parameter>,
+    /// Leaf values identical in every block
+    pub common: usize,
+}
+
+impl DuplicateGroup {
+    /// Total leaf values per block
+    pub fn leaves(&self) -> usize {
+        self.common + self.parameters.len()
+    }
+
+    /// CUE definition with the varying values left open
+    pub fn cue_definition(&self) -> String {
+        let mut out = format!("#{}: ", self.name);
+        render_cue(
+            &self.template,
+            &mut Vec::new(),
+            &self.param_types(),
+            0,
+            &mut out,
+        );
+        out
+    }
+
+    /// CUE definition plus one instance per block
+    pub fn to_cue(&self) -> String {
+        let mut out = format!(
+            "// Generated by conflow from {} near-identical blocks\n\n{}\n\n{}: [\n",
+            self.members.len(),
+            self.cue_definition(),
+            self.file_stem()
+        );
+        for i in 0..self.members.len() {
+            out.push_str(&format!("\t#{} & ", self.name));
+            render_cue(
+                &self.instance(i),
+                &mut Vec::new(),
+                &HashMap::new(),
+                1,
+                &mut out,
+            );
+            out.push_str(",\n");
+        }
+        out.push_str("]\n");
+        out
+    }
+
+    /// Nickel function taking the varying values as `args`
+    pub fn nickel_function(&self) -> String {
+        let names: HashMap<Vec<Segment>, String> = self
+            .parameters
+            .iter()
+            .map(|p| (p.path.clone(), format!("args.{}", p.name)))
+            .collect();
+
+        let mut out = format!("let mk{} = fun args =>\n  ", self.name);
+        render_nickel(&self.template, &mut Vec::new(), &names, 1, &mut out);
+        out
+    }
+
+    /// Nickel function plus one call per block
+    pub fn to_nickel(&self) -> String {
+        let mut out = format!(
+            "# Generated by conflow from {} near-identical blocks\n{}\nin\n[\n",
+            self.members.len(),
+            self.nickel_function()
+        );
+        for i in 0..self.members.len() {
+            let args: serde_json::Map<String, Value> = self
+                .parameters
+                .iter()
+                .map(|p| (p.name.clone(), p.values[i].clone()))
+                .collect();
+            out.push_str(&format!("  mk{} ", self.name));
+            render_nickel(
+                &Value::Object(args),
+                &mut Vec::new(),
+                &HashMap::new(),
+                1,
+                &mut out,
+            );
+            out.push_str(",\n");
+        }
+        out.push_str("]\n");
+        out
+    }
+
+    /// Plural name for the instances, e.g. `deployments`
+    pub fn file_stem(&self) -> String {
+        let mut chars = self.name.chars();
+        let first = chars
+            .next()
+            .map(|c| c.to_ascii_lowercase().to_string())
+            .unwrap_or_default();
+        format!("{}{}s", first, chars.as_str())
+    }
+
+    /// Varying values of one member, as a nested value
+    fn instance(&self, member: usize) -> Value {
+        let mut root = Value::Object(Default::default());
+        for parameter in &self.parameters {
+            insert(&mut root, &parameter.path, parameter.values[member].clone());
+        }
+        root
+    }
+
+    /// CUE type for each parameter
+    fn param_types(&self) -> HashMap<Vec<Segment>, String> {
+        self.parameters
+            .iter()
+            .map(|p| {
+                let kinds: HashSet<&str> = p.values.iter().map(cue_type).collect();
+                let kind = match kinds.len() {
+                    1 => kinds.into_iter().next().unwrap(),
+                    _ if kinds.iter().all(|k| matches!(*k, "int" | "number")) => "number",
+                    _ => "_",
+                };
+                (p.path.clone(), kind.to_string())
+            })
+            .collect()
+    }
+}
+
+/// Parse JSON and YAML files and find near-duplicate blocks across them
+///
+/// Other formats and unparseable files are skipped. Files are parsed in
+/// parallel with `native`; blocks keep the order of `paths`.
+pub fn find_duplicates_in_files(paths: &[PathBuf]) -> Vec<DuplicateGroup> {
+    #[cfg(feature = "native")]
+    let paths = paths.par_iter();
+    #[cfg(not(feature = "native"))]
+    let paths = paths.iter();
+    let parsed: Vec<Vec<(BlockLocation, Value)>> = paths
+        .map(|path| match std::fs::read_to_string(path) {
+            Ok(content) => parse_roots(path, &content),
+            Err(_) => vec![],
+        })
+        .collect();
+    let roots: Vec<(BlockLocation, Value)> = parsed.into_iter().flatten().collect();
+    find_duplicates(&roots)
+}
+
+pub(crate) fn parse_roots(path: &Path, content: &str) -> Vec<(BlockLocation, Value)> {
+    let location = |document| BlockLocation {
+        file: path.to_path_buf(),
+        document,
+        path: vec![],
+    };
+
+    // Templates repeat blocks on purpose and aren't data until rendered
+    if detect_template(content, path).is_some() {
+        return vec![];
+    }
+    let documents = match ParseCache::global().documents(path, content) {
+        Ok(documents) => documents,
+        Err(_) => return vec![],
+    };
+    let multiple = documents.len() > 1;
+    documents
+        .iter()
+        .enumerate()
+        .map(|(i, v)| (location(Some(i).filter(|_| multiple)), v.clone()))
+        .collect()
+}
+
+struct Candidate<'a> {
+    location: BlockLocation,
+    value: &'a Value,
+    leaves: Vec<(Vec<Segment>, &'a Value)>,
+}
+
+/// Find groups of near-duplicate mappings
+///
+/// Larger blocks are grouped first, and blocks inside a grouped block are
+/// not reported again.
+pub fn find_duplicates(roots: &[(BlockLocation, Value)]) -> Vec<DuplicateGroup> {
+    let mut candidates = Vec::new();
+    for (location, value) in roots {
+        collect(location.clone(), value, &mut candidates);
+    }
+
+    let mut by_shape: HashMap<Vec<&Vec<Segment>>, Vec<usize>> = HashMap::new();
+    for (i, candidate) in candidates.iter().enumerate() {
+        let shape = candidate.leaves.iter().map(|(path, _)| path).collect();
+        by_shape.entry(shape).or_default().push(i);
+    }
+    let mut shapes: Vec<Vec<usize>> = by_shape
+        .into_values()
+        .filter(|members| members.len() >= MIN_DUPLICATES)
+        .collect();
+    shapes.sort_by_key(|members| {
+        let first = &candidates[members[0]];
+        (
+            std::cmp::Reverse(first.leaves.len()),
+            first.location.to_string(),
+        )
+    });
+
+    let mut groups = Vec::new();
+    let mut grouped: Vec<BlockLocation> = Vec::new();
+    for members in shapes {
+        let mut remaining: Vec<usize> = members
+            .into_iter()
+            .filter(|&i| !grouped.iter().any(|g| contains(g, &candidates[i].location)))
+            .collect();
+
+        while remaining.len() >= MIN_DUPLICATES {
+            let seed = &candidates[remaining[0]];
+            let (cluster, rest): (Vec<usize>, Vec<usize>) = remaining
+                .iter()
+                .partition(|&&i| agreement(seed, &candidates[i]) >= MIN_AGREEMENT);
+
+            if cluster.len() >= MIN_DUPLICATES {
+                let members: Vec<&Candidate> = cluster.iter().map(|&i| &candidates[i]).collect();
+                grouped.extend(members.iter().map(|c| c.location.clone()));
+                groups.push(build_group(&members));
+                remaining = rest;
+            } else {
+                remaining.remove(0);
+            }
+        }
+    }
+
+    groups
+}
+
+fn collect<'a>(location: BlockLocation, value: &'a Value, out: &mut Vec<Candidate<'a>>) {
+    let children: Vec<(Segment, &Value)> = match value {
+        Value::Object(map) => map
+            .iter()
+            .map(|(k, v)| (Segment::Key(k.clone()), v))
+            .collect(),
+        Value::Array(items) => items
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (Segment::Index(i), v))
+            .collect(),
+        _ => return,
+    };
+
+    if value.is_object() {
+        let mut leaves = Vec::new();
+        flatten(value, &mut Vec::new(), &mut leaves);
+        if leaves.len() >= MIN_LEAVES {
+            out.push(Candidate {
+                location: location.clone(),
+                value,
+                leaves,
+            });
+        }
+    }
+
+    for (segment, child) in children {
+        let mut child_location = location.clone();
+        child_location.path.push(segment);
+        collect(child_location, child, out);
+    }
+}
+
+/// Leaf values with their paths, in sorted path order
+fn flatten<'a>(
+    value: &'a Value,
+    path: &mut Vec<Segment>,
+    out: &mut Vec<(Vec<Segment>, &'a Value)>,
+) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            let sorted: BTreeMap<&String, &Value> = map.iter().collect();
+            for (key, child) in sorted {
+                path.push(Segment::Key(key.clone()));
+                flatten(child, path, out);
+                path.pop();
+            }
+        }
+        Value::Array(items) if !items.is_empty() => {
+            for (i, child) in items.iter().enumerate() {
+                path.push(Segment::Index(i));
+                flatten(child, path, out);
+                path.pop();
+            }
+        }
+        leaf => out.push((path.clone(), leaf)),
+    }
+}
+
+/// Whether `inner` is `outer` or inside it
+fn contains(outer: &BlockLocation, inner: &BlockLocation) -> bool {
+    outer.file == inner.file
+        && outer.document == inner.document
+        && inner.path.starts_with(&outer.path)
+}
+
+/// Share of leaves with equal values (same shape assumed)
+fn agreement(a: &Candidate, b: &Candidate) -> f64 {
+    let same = a
+        .leaves
+        .iter()
+        .zip(&b.leaves)
+        .filter(|((_, x), (_, y))| x == y)
+        .count();
+    same as f64 / a.leaves.len() as f64
+}
+
+fn build_group(members: &[&Candidate]) -> DuplicateGroup {
+    let first = members[0];
+    let mut parameters = Vec::new();
+    let mut common = 0;
+
+    for (i, (path, value)) in first.leaves.iter().enumerate() {
+        let values: Vec<Value> = members.iter().map(|m| m.leaves[i].1.clone()).collect();
+        if values.iter().all(|v| v == *value) {
+            common += 1;
+        } else {
+            parameters.push(Parameter {
+                name: String::new(),
+                path: path.clone(),
+                values,
+            });
+        }
+    }
+    name_parameters(&mut parameters);
+
+    DuplicateGroup {
+        name: group_name(members),
+        members: members.iter().map(|m| m.location.clone()).collect(),
+        template: first.value.clone(),
+        parameters,
+        common,
+    }
+}
+
+/// Name the group after a shared `kind`, or the key the blocks sit under
+fn group_name(members: &[&Candidate]) -> String {
+    let kind = members[0].value.get("kind").and_then(Value::as_str);
+    if let Some(kind) = kind.filter(|k| {
+        members
+            .iter()
+            .all(|m| m.value.get("kind") == Some(&Value::from(*k)))
+    }) {
+        return pascal_case(kind);
+    }
+
+    let key = |m: &Candidate| match m.location.path.last() {
+        Some(Segment::Key(key)) => Some(key.clone()),
+        _ => None,
+    };
+    match key(members[0]) {
+        Some(k)
+            if members
+                .iter()
+                .all(|m| key(m).as_deref() == Some(k.as_str())) =>
+        {
+            pascal_case(&k)
+        }
+        _ => "Block".to_string(),
+    }
+}
+
+/// Use the last key of each path, adding parent keys until names are unique
+fn name_parameters(parameters: &mut [Parameter]) {
+    let keys: Vec<Vec<String>> = parameters
+        .iter()
+        .map(|p| {
+            p.path
+                .iter()
+                .filter_map(|s| match s {
+                    Segment::Key(k) => Some(identifier(k)),
+                    Segment::Index(_) => None,
+                })
+                .collect()
+        })
+        .collect();
+
+    let mut depth = vec![1; parameters.len()];
+    loop {
+        let names: Vec<String> = keys
+            .iter()
+            .zip(&depth)
+            .map(|(k, &d)| k[k.len().saturating_sub(d)..].join("_"))
+            .collect();
+        let mut counts: HashMap<&String, usize> = HashMap::new();
+        for name in &names {
+            *counts.entry(name).or_default() += 1;
+        }
+
+        let mut changed = false;
+        for (i, name) in names.iter().enumerate() {
+            if counts[name] > 1 && depth[i] < keys[i].len() {
+                depth[i] += 1;
+                changed = true;
+            }
+        }
+        if !changed {
+            let mut seen: HashMap<String, usize> = HashMap::new();
+            for (parameter, name) in parameters.iter_mut().zip(names) {
+                let n = seen.entry(name.clone()).or_default();
+                *n += 1;
+                parameter.name = match *n {
+                    1 if !name.is_empty() => name,
+                    _ => format!("{}{}", if name.is_empty() { "value" } else { &name }, n),
+                };
+            }
+            return;
+        }
+    }
+}
+
+/// Keywords that cannot be Nickel identifiers
+const NICKEL_KEYWORDS: &[&str] = &[
+    "default", "doc", "else", "false", "force", "forall", "fun", "if", "import", "in", "let",
+    "match", "null", "optional", "priority", "rec", "then", "true",
+];
+
+fn identifier(key: &str) -> String {
+    let mut out: String = key
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if out.starts_with(|c: char| c.is_ascii_digit()) {
+        out.insert(0, '_');
+    }
+    if NICKEL_KEYWORDS.contains(&out.as_str()) {
+        out.push('_');
+    }
+    out
+}
+
+fn pascal_case(name: &str) -> String {
+    // Identifiers cannot start with a digit
+    let name: String = name
+        .trim_start_matches(|c: char| !c.is_ascii_alphabetic())
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .flat_map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|c| c.to_ascii_uppercase())
+                .into_iter()
+                .chain(chars)
+        })
+        .collect();
+    if name.is_empty() {
+        "Block".to_string()
+    } else {
+        name
+    }
+}
+
+fn insert(root: &mut Value, path: &[Segment], value: Value) {
+    let Some((head, rest)) = path.split_first() else {
+        *root = value;
+        return;
+    };
+    match head {
+        Segment::Key(key) => {
+            if !root.is_object() {
+                *root = Value::Object(Default::default());
+            }
+            let child = root
+                .as_object_mut()
+                .unwrap()
+                .entry(key.clone())
+                .or_insert(Value::Null);
+            insert(child, rest, value);
+        }
+        Segment::Index(i) => {
+            if !root.is_array() {
+                *root = Value::Array(vec![]);
+            }
+            let items = root.as_array_mut().unwrap();
+            // Earlier elements are unconstrained in the instance
+            while items.len() <= *i {
+                items.push(Value::Object(Default::default()));
+            }
+            insert(&mut items[*i], rest, value);
+        }
+    }
+}
+
+fn cue_type(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "bool",
+        Value::Number(n) if n.is_i64() || n.is_u64() => "int",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "[...]",
+        Value::Object(_) => "{...}",
+    }
+}
+
+fn render_cue(
+    value: &Value,
+    path: &mut Vec<Segment>,
+    params: &HashMap<Vec<Segment>, String>,
+    indent: usize,
+    out: &mut String,
+) {
+    if let Some(kind) = params.get(path.as_slice()) {
+        out.push_str(kind);
+        return;
+    }
+
+    let pad = "\t".repeat(indent + 1);
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            out.push_str("{\n");
+            for (key, child) in map {
+                out.push_str(&format!("{}{}: ", pad, cue_label(key)));
+                path.push(Segment::Key(key.clone()));
+                render_cue(child, path, params, indent + 1, out);
+                path.pop();
+                out.push('\n');
+            }
+            out.push_str(&format!("{}}}", "\t".repeat(indent)));
+        }
+        Value::Array(items) if !items.is_empty() => {
+            out.push_str("[\n");
+            for (i, child) in items.iter().enumerate() {
+                out.push_str(&pad);
+                path.push(Segment::Index(i));
+                render_cue(child, path, params, indent + 1, out);
+                path.pop();
+                out.push_str(",\n");
+            }
+            out.push_str(&format!("{}]", "\t".repeat(indent)));
+        }
+        leaf => out.push_str(&leaf.to_string()),
+    }
+}
+
+fn render_nickel(
+    value: &Value,
+    path: &mut Vec<Segment>,
+    params: &HashMap<Vec<Segment>, String>,
+    indent: usize,
+    out: &mut String,
+) {
+    if let Some(name) = params.get(path.as_slice()) {
+        out.push_str(name);
+        return;
+    }
+
+    let pad = "  ".repeat(indent + 1);
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            out.push_str("{\n");
+            for (key, child) in map {
+                out.push_str(&format!("{}{} = ", pad, nickel_label(key)));
+                path.push(Segment::Key(key.clone()));
+                render_nickel(child, path, params, indent + 1, out);
+                path.pop();
+                out.push_str(",\n");
+            }
+            out.push_str(&format!("{}}}", "  ".repeat(indent)));
+        }
+        Value::Array(items) if !items.is_empty() => {
+            out.push_str("[\n");
+            for (i, child) in items.iter().enumerate() {
+                out.push_str(&pad);
+                path.push(Segment::Index(i));
+                render_nickel(child, path, params, indent + 1, out);
+                path.pop();
+                out.push_str(",\n");
+            }
+            out.push_str(&format!("{}]", "  ".repeat(indent)));
+        }
+        // `%{` starts an interpolation in Nickel strings
+        Value::String(_) => out.push_str(&value.to_string().replace("%{", "\\%{")),
+        leaf => out.push_str(&leaf.to_string()),
+    }
+}
+
+pub(super) fn cue_label(key: &str) -> String {
+    let plain = key.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if plain {
+        key.to_string()
+    } else {
+        Value::from(key).to_string()
+    }
+}
+
+pub(super) fn nickel_label(key: &str) -> String {
+    let plain = key.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '\''))
+        && !NICKEL_KEYWORDS.contains(&key);
+    if plain {
+        key.to_string()
+    } else {
+        Value::from(key).to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn deployment(name: &str, replicas: u32) -> Value {
+        json!({
+            "apiVersion": "apps/v1",
+            "kind": "Deployment",
+            "metadata": { "name": name, "labels": { "team": "web" } },
+            "spec": {
+                "replicas": replicas,
+                "template": { "spec": { "containers": [{
+                    "name": name,
+                    "image": format!("registry.example.com/{}:1.0", name),
+                    "ports": [{ "containerPort": 8080 }],
+                }]}}
+            }
+        })
+    }
+
+    fn root(file: &str, value: Value) -> (BlockLocation, Value) {
+        let location = BlockLocation {
+            file: file.into(),
+            document: None,
+            path: vec![],
+        };
+        (location, value)
+    }
+
+    #[test]
+    fn test_groups_near_identical_blocks() {
+        let roots = vec![
+            root("web.yaml", deployment("web", 2)),
+            root("api.yaml", deployment("api", 3)),
+            root("jobs.yaml", deployment("jobs", 2)),
+            root("other.yaml", json!({ "a": 1, "b": 2, "c": 3, "d": 4 })),
+        ];
+        let groups = find_duplicates(&roots);
+
+        // Nested blocks of the grouped Deployments are not reported again
+        assert_eq!(groups.len(), 1);
+        let group = &groups[0];
+        assert_eq!(group.name, "Deployment");
+        assert_eq!(group.members.len(), 3);
+        assert_eq!(group.members[1].to_string(), "api.yaml");
+        assert_eq!(group.leaves(), 8);
+
+        let names: Vec<&str> = group.parameters.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["metadata_name", "replicas", "image", "containers_name"]
+        );
+        assert_eq!(
+            group.parameters[1].values,
+            vec![json!(2), json!(3), json!(2)]
+        );
+    }
+
+    #[test]
+    fn test_dissimilar_blocks_are_not_grouped() {
+        let block = |a: u32| json!({ "a": a, "b": a, "c": a, "d": a });
+        let roots: Vec<_> = (0..4).map(|i| root("x.json", block(i))).collect();
+        assert!(find_duplicates(&roots).is_empty());
+
+        let roots: Vec<_> = (0..2).map(|i| root("x.json", deployment("a", i))).collect();
+        assert!(find_duplicates(&roots).is_empty());
+    }
+
+    #[test]
+    fn test_generated_abstractions() {
+        let roots: Vec<_> = ["web", "api", "jobs"]
+            .iter()
+            .map(|n| root("k8s.yaml", deployment(n, 2)))
+            .collect();
+        let group = &find_duplicates(&roots)[0];
+
+        let cue = group.to_cue();
+        assert!(cue.contains("#Deployment: {\n\tapiVersion: \"apps/v1\"\n"));
+        assert!(cue.contains("\t\tname: string\n"));
+        assert!(cue.contains("\t\treplicas: 2\n"));
+        assert!(cue.contains("deployments: [\n\t#Deployment & {\n"));
+        assert_eq!(cue.matches("#Deployment & ").count(), 3);
+
+        let nickel = group.to_nickel();
+        assert!(
+            nickel.contains("let mkDeployment = fun args =>\n  {\n    apiVersion = \"apps/v1\",\n")
+        );
+        assert!(nickel.contains("name = args.metadata_name,"));
+        assert!(nickel.contains("  mkDeployment {\n"));
+        assert!(nickel.contains("image = \"registry.example.com/jobs:1.0\","));
+        assert_eq!(group.file_stem(), "deployments");
+    }
+
+    #[test]
+    fn test_names() {
+        assert_eq!(pascal_case("stateful-set"), "StatefulSet");
+        assert_eq!(pascal_case("9lives"), "Lives");
+        assert_eq!(pascal_case("--"), "Block");
+        assert_eq!(
+            identifier("app.kubernetes.io/name"),
+            "app_kubernetes_io_name"
+        );
+        assert_eq!(identifier("default"), "default_");
+        assert_eq!(
+            cue_label("app.kubernetes.io/name"),
+            "\"app.kubernetes.io/name\""
+        );
+        assert_eq!(nickel_label("in"), "\"in\"");
+    }
+}
@@ -0,0 +1,647 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Deprecated Kubernetes API versions
+//!
+//! Kubernetes removes beta API versions a few releases after the stable
+//! version ships, and manifests still using them fail to apply once the
+//! cluster is upgraded. Like kube-no-trouble, this checks each manifest's
+//! `apiVersion` and `kind` against the known deprecations and reports the
+//! ones that are deprecated or already removed in the target version,
+//! with the version to move to.
+
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use super::templates::{detect_template, stub_render};
+use crate::cache::ParseCache;
+use crate::errors::ConflowError;
+use crate::utils::yaml;
+
+/// A Kubernetes minor release, e.g. `1.29`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct KubeVersion {
+    pub major: u32,
+    pub minor: u32,
+}
+
+impl KubeVersion {
+    const fn new(minor: u32) -> Self {
+        Self { major: 1, minor }
+    }
+
+    /// The newest release with a known removal, the default target
+    pub fn latest() -> Self {
+        DEPRECATED_APIS
+            .iter()
+            .filter_map(|api| api.removed_in)
+            .max()
+            .unwrap_or(Self::new(0))
+    }
+}
+
+impl FromStr for KubeVersion {
+    type Err = String;
+
+    /// Parse `1.29`, `v1.29` or `v1.29.3`; the patch release is ignored
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid Kubernetes version '{}' (expected e.g. 1.29)", s);
+        let mut parts = s.trim().trim_start_matches('v').split('.');
+        let major = parts
+            .next()
+            .and_then(|p| p.parse().ok())
+            .ok_or_else(invalid)?;
+        let minor = parts
+            .next()
+            .and_then(|p| p.parse().ok())
+            .ok_or_else(invalid)?;
+        match parts.next() {
+            Some(patch) if patch.parse::<u32>().is_err() => Err(invalid()),
+            _ if parts.next().is_some() => Err(invalid()),
+            _ => Ok(Self { major, minor }),
+        }
+    }
+}
+
+impl fmt::Display for KubeVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+impl Serialize for KubeVersion {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// An API version of a kind that Kubernetes deprecates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeprecatedApi {
+    pub api_version: &'static str,
+    pub kind: &'static str,
+    pub deprecated_in: KubeVersion,
+    /// Release that stops serving it, if one has been announced
+    pub removed_in: Option<KubeVersion>,
+    /// API version to migrate to, if there is one
+    pub replacement: Option<&'static str>,
+    /// What else changes when migrating
+    #[serde(skip_serializing_if = "str::is_empty")]
+    pub note: &'static str,
+}
+
+/// Whether an API is deprecated or already gone in the target release
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ApiStatus {
+    Deprecated,
+    Removed,
+}
+
+const fn api(
+    api_version: &'static str,
+    kind: &'static str,
+    deprecated_in: u32,
+    removed_in: u32,
+    replacement: &'static str,
+    note: &'static str,
+) -> DeprecatedApi {
+    DeprecatedApi {
+        api_version,
+        kind,
+        deprecated_in: KubeVersion::new(deprecated_in),
+        removed_in: Some(KubeVersion::new(removed_in)),
+        replacement: if replacement.is_empty() {
+            None
+        } else {
+            Some(replacement)
+        },
+        note,
+    }
+}
+
+const WORKLOAD_SELECTOR: &str = "spec.selector is required and can't be changed after creation";
+const INGRESS: &str = "backend serviceName/servicePort become service.name/service.port; \
+                       each path needs a pathType";
+const WEBHOOK: &str = "sideEffects and admissionReviewVersions are required";
+
+/// Known deprecations, by the release that removes them
+pub const DEPRECATED_APIS: &[DeprecatedApi] = &[
+    api(
+        "extensions/v1beta1",
+        "Deployment",
+        9,
+        16,
+        "apps/v1",
+        WORKLOAD_SELECTOR,
+    ),
+    api(
+        "extensions/v1beta1",
+        "DaemonSet",
+        9,
+        16,
+        "apps/v1",
+        WORKLOAD_SELECTOR,
+    ),
+    api(
+        "extensions/v1beta1",
+        "ReplicaSet",
+        9,
+        16,
+        "apps/v1",
+        WORKLOAD_SELECTOR,
+    ),
+    api(
+        "extensions/v1beta1",
+        "NetworkPolicy",
+        9,
+        16,
+        "networking.k8s.io/v1",
+        "",
+    ),
+    api(
+        "extensions/v1beta1",
+        "PodSecurityPolicy",
+        10,
+        16,
+        "policy/v1beta1",
+        "",
+    ),
+    api(
+        "apps/v1beta1",
+        "Deployment",
+        9,
+        16,
+        "apps/v1",
+        WORKLOAD_SELECTOR,
+    ),
+    api(
+        "apps/v1beta1",
+        "StatefulSet",
+        9,
+        16,
+        "apps/v1",
+        WORKLOAD_SELECTOR,
+    ),
+    api("apps/v1beta2", "Deployment", 9, 16, "apps/v1", ""),
+    api("apps/v1beta2", "DaemonSet", 9, 16, "apps/v1", ""),
+    api("apps/v1beta2", "ReplicaSet", 9, 16, "apps/v1", ""),
+    api("apps/v1beta2", "StatefulSet", 9, 16, "apps/v1", ""),
+    api(
+        "extensions/v1beta1",
+        "Ingress",
+        14,
+        22,
+        "networking.k8s.io/v1",
+        INGRESS,
+    ),
+    api(
+        "networking.k8s.io/v1beta1",
+        "Ingress",
+        19,
+        22,
+        "networking.k8s.io/v1",
+        INGRESS,
+    ),
+    api(
+        "networking.k8s.io/v1beta1",
+        "IngressClass",
+        19,
+        22,
+        "networking.k8s.io/v1",
+        "",
+    ),
+    api(
+        "apiextensions.k8s.io/v1beta1",
+        "CustomResourceDefinition",
+        16,
+        22,
+        "apiextensions.k8s.io/v1",
+        "each version needs a structural schema; spec.validation moves under spec.versions",
+    ),
+    api(
+        "admissionregistration.k8s.io/v1beta1",
+        "MutatingWebhookConfiguration",
+        16,
+        22,
+        "admissionregistration.k8s.io/v1",
+        WEBHOOK,
+    ),
+    api(
+        "admissionregistration.k8s.io/v1beta1",
+        "ValidatingWebhookConfiguration",
+        16,
+        22,
+        "admissionregistration.k8s.io/v1",
+        WEBHOOK,
+    ),
+    api(
+        "rbac.authorization.k8s.io/v1beta1",
+        "ClusterRole",
+        17,
+        22,
+        "rbac.authorization.k8s.io/v1",
+        "",
+    ),
+    api(
+        "rbac.authorization.k8s.io/v1beta1",
+        "ClusterRoleBinding",
+        17,
+        22,
+        "rbac.authorization.k8s.io/v1",
+        "",
+    ),
+    api(
+        "rbac.authorization.k8s.io/v1beta1",
+        "Role",
+        17,
+        22,
+        "rbac.authorization.k8s.io/v1",
+        "",
+    ),
+    api(
+        "rbac.authorization.k8s.io/v1beta1",
+        "RoleBinding",
+        17,
+        22,
+        "rbac.authorization.k8s.io/v1",
+        "",
+    ),
+    api(
+        "scheduling.k8s.io/v1beta1",
+        "PriorityClass",
+        14,
+        22,
+        "scheduling.k8s.io/v1",
+        "",
+    ),
+    api(
+        "storage.k8s.io/v1beta1",
+        "CSIDriver",
+        19,
+        22,
+        "storage.k8s.io/v1",
+        "",
+    ),
+    api(
+        "storage.k8s.io/v1beta1",
+        "CSINode",
+        17,
+        22,
+        "storage.k8s.io/v1",
+        "",
+    ),
+    api(
+        "storage.k8s.io/v1beta1",
+        "StorageClass",
+        19,
+        22,
+        "storage.k8s.io/v1",
+        "",
+    ),
+    api(
+        "storage.k8s.io/v1beta1",
+        "VolumeAttachment",
+        19,
+        22,
+        "storage.k8s.io/v1",
+        "",
+    ),
+    api(
+        "certificates.k8s.io/v1beta1",
+        "CertificateSigningRequest",
+        19,
+        22,
+        "certificates.k8s.io/v1",
+        "spec.signerName is required",
+    ),
+    api(
+        "coordination.k8s.io/v1beta1",
+        "Lease",
+        14,
+        22,
+        "coordination.k8s.io/v1",
+        "",
+    ),
+    api(
+        "apiregistration.k8s.io/v1beta1",
+        "APIService",
+        19,
+        22,
+        "apiregistration.k8s.io/v1",
+        "",
+    ),
+    api("batch/v1beta1", "CronJob", 21, 25, "batch/v1", ""),
+    api(
+        "discovery.k8s.io/v1beta1",
+        "EndpointSlice",
+        21,
+        25,
+        "discovery.k8s.io/v1",
+        "",
+    ),
+    api(
+        "events.k8s.io/v1beta1",
+        "Event",
+        22,
+        25,
+        "events.k8s.io/v1",
+        "",
+    ),
+    api(
+        "autoscaling/v2beta1",
+        "HorizontalPodAutoscaler",
+        22,
+        25,
+        "autoscaling/v2",
+        "",
+    ),
+    api(
+        "policy/v1beta1",
+        "PodDisruptionBudget",
+        21,
+        25,
+        "policy/v1",
+        "an empty spec.selector now selects every pod in the namespace",
+    ),
+    api(
+        "policy/v1beta1",
+        "PodSecurityPolicy",
+        21,
+        25,
+        "",
+        "use Pod Security Admission or a policy engine instead",
+    ),
+    api(
+        "node.k8s.io/v1beta1",
+        "RuntimeClass",
+        22,
+        25,
+        "node.k8s.io/v1",
+        "",
+    ),
+    api(
+        "autoscaling/v2beta2",
+        "HorizontalPodAutoscaler",
+        23,
+        26,
+        "autoscaling/v2",
+        "",
+    ),
+    api(
+        "flowcontrol.apiserver.k8s.io/v1beta1",
+        "FlowSchema",
+        23,
+        26,
+        "flowcontrol.apiserver.k8s.io/v1",
+        "",
+    ),
+    api(
+        "flowcontrol.apiserver.k8s.io/v1beta1",
+        "PriorityLevelConfiguration",
+        23,
+        26,
+        "flowcontrol.apiserver.k8s.io/v1",
+        "",
+    ),
+    api(
+        "storage.k8s.io/v1beta1",
+        "CSIStorageCapacity",
+        24,
+        27,
+        "storage.k8s.io/v1",
+        "",
+    ),
+    api(
+        "flowcontrol.apiserver.k8s.io/v1beta2",
+        "FlowSchema",
+        26,
+        29,
+        "flowcontrol.apiserver.k8s.io/v1",
+        "",
+    ),
+    api(
+        "flowcontrol.apiserver.k8s.io/v1beta2",
+        "PriorityLevelConfiguration",
+        26,
+        29,
+        "flowcontrol.apiserver.k8s.io/v1",
+        "",
+    ),
+    api(
+        "flowcontrol.apiserver.k8s.io/v1beta3",
+        "FlowSchema",
+        29,
+        32,
+        "flowcontrol.apiserver.k8s.io/v1",
+        "",
+    ),
+    api(
+        "flowcontrol.apiserver.k8s.io/v1beta3",
+        "PriorityLevelConfiguration",
+        29,
+        32,
+        "flowcontrol.apiserver.k8s.io/v1",
+        "",
+    ),
+];
+
+/// The deprecation a manifest's `apiVersion` and `kind` fall under
+pub fn deprecation(api_version: &str, kind: &str) -> Option<&'static DeprecatedApi> {
+    DEPRECATED_APIS
+        .iter()
+        .find(|api| api.api_version == api_version && api.kind == kind)
+}
+
+/// A manifest using a deprecated API
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiFinding {
+    pub file: PathBuf,
+    pub line: usize,
+    /// `metadata.name`, if set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub status: ApiStatus,
+    pub api: &'static DeprecatedApi,
+}
+
+impl ApiFinding {
+    /// One line describing the problem and the way out
+    pub fn message(&self) -> String {
+        let name = match &self.name {
+            Some(name) => format!(" '{}'", name),
+            None => String::new(),
+        };
+        let when = match (self.status, self.api.removed_in) {
+            (ApiStatus::Removed, Some(removed)) => format!("removed in Kubernetes {}", removed),
+            (_, Some(removed)) => format!(
+                "deprecated since Kubernetes {}, removed in {}",
+                self.api.deprecated_in, removed
+            ),
+            (_, None) => format!("deprecated since Kubernetes {}", self.api.deprecated_in),
+        };
+        let mut message = format!(
+            "{}{} uses {}, {}",
+            self.api.kind, name, self.api.api_version, when
+        );
+        if let Some(replacement) = self.api.replacement {
+            message.push_str(&format!("; use {}", replacement));
+        }
+        if !self.api.note.is_empty() {
+            message.push_str(&format!(" ({})", self.api.note));
+        }
+        message
+    }
+}
+
+/// Manifests in `files` (relative to `root`) using APIs deprecated or
+/// removed in `target`
+///
+/// Files that don't parse are skipped; `conflow lint` reports them.
+pub fn find_deprecated_apis(
+    files: &[PathBuf],
+    root: &Path,
+    target: KubeVersion,
+) -> Result<Vec<ApiFinding>, ConflowError> {
+    let mut findings = Vec::new();
+    for file in files {
+        let path = root.join(file);
+        let content = std::fs::read_to_string(&path).map_err(|e| ConflowError::FileReadError {
+            path: path.clone(),
+            error: e.to_string(),
+        })?;
+        // Templates are scanned as the manifests around their tags
+        let content = match detect_template(&content, &path) {
+            Some(_) => stub_render(&content),
+            None => content,
+        };
+        let Ok(documents) = ParseCache::global().documents(file, &content) else {
+            continue;
+        };
+        let offsets: Vec<usize> = match file.extension().and_then(|e| e.to_str()) {
+            Some("json") => vec![0],
+            _ => yaml::documents(&content).iter().map(|d| d.offset).collect(),
+        };
+
+        for (document, offset) in documents.iter().zip(offsets) {
+            for (key, manifest) in manifests(document) {
+                let Some((api, status)) = check(manifest, target) else {
+                    continue;
+                };
+                findings.push(ApiFinding {
+                    file: file.clone(),
+                    line: yaml::key_line(&content, offset, key).unwrap_or(1),
+                    name: manifest
+                        .pointer("/metadata/name")
+                        .and_then(Value::as_str)
+                        .map(String::from),
+                    status,
+                    api,
+                });
+            }
+        }
+    }
+    Ok(findings)
+}
+
+/// Whether a manifest uses an API deprecated in `target`
+pub fn check(manifest: &Value, target: KubeVersion) -> Option<(&'static DeprecatedApi, ApiStatus)> {
+    let api_version = manifest.get("apiVersion")?.as_str()?;
+    let kind = manifest.get("kind")?.as_str()?;
+    let api = deprecation(api_version, kind)?;
+    match api.removed_in {
+        Some(removed) if removed <= target => Some((api, ApiStatus::Removed)),
+        _ if api.deprecated_in <= target => Some((api, ApiStatus::Deprecated)),
+        _ => None,
+    }
+}
+
+/// A document, or the items of a `kind: List`, with the path to the
+/// `apiVersion` key to report
+///
+/// Paths skip list indices, so every item of a list is reported at the
+/// first item's `apiVersion`.
+pub(super) fn manifests(document: &Value) -> Vec<(&'static [&'static str], &Value)> {
+    match document.get("items").and_then(Value::as_array) {
+        Some(items) if document.get("kind").and_then(Value::as_str) == Some("List") => {
+            let key: &[&str] = &["items", "apiVersion"];
+            items.iter().map(|item| (key, item)).collect()
+        }
+        _ => vec![(&["apiVersion"][..], document)],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_versions() {
+        let version: KubeVersion = "v1.29.3".parse().unwrap();
+        assert_eq!(version, KubeVersion::new(29));
+        assert_eq!(version.to_string(), "1.29");
+        assert!("1.x".parse::<KubeVersion>().is_err());
+        assert!("1.29.3.1".parse::<KubeVersion>().is_err());
+        assert!(KubeVersion::new(9) < KubeVersion::new(16));
+        assert_eq!(KubeVersion::latest(), KubeVersion::new(32));
+    }
+
+    #[test]
+    fn test_deprecated_apis() {
+        let cronjob = json!({"apiVersion": "batch/v1beta1", "kind": "CronJob"});
+        assert_eq!(check(&cronjob, KubeVersion::new(20)), None);
+        let (api, status) = check(&cronjob, KubeVersion::new(23)).unwrap();
+        assert_eq!(
+            (api.replacement, status),
+            (Some("batch/v1"), ApiStatus::Deprecated)
+        );
+        assert_eq!(
+            check(&cronjob, KubeVersion::new(25)).unwrap().1,
+            ApiStatus::Removed
+        );
+
+        let temp = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            temp.path().join("app.yaml"),
+            "\
+apiVersion: apps/v1
+kind: Deployment
+---
+# legacy ingress
+apiVersion: extensions/v1beta1
+kind: Ingress
+metadata:
+  name: web
+---
+kind: List
+apiVersion: v1
+items:
+  - apiVersion: policy/v1beta1
+    kind: PodDisruptionBudget
+",
+        )
+        .unwrap();
+        let files = vec![PathBuf::from("app.yaml")];
+        let found = find_deprecated_apis(&files, temp.path(), KubeVersion::new(22)).unwrap();
+
+        let summary: Vec<_> = found
+            .iter()
+            .map(|f| (f.line, f.api.kind, f.status))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (5, "Ingress", ApiStatus::Removed),
+                (13, "PodDisruptionBudget", ApiStatus::Deprecated),
+            ]
+        );
+        assert!(found[0]
+            .message()
+            .starts_with("Ingress 'web' uses extensions/v1beta1, removed in Kubernetes 1.22; use networking.k8s.io/v1 ("));
+    }
+}
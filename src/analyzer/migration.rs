@@ -0,0 +1,667 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Migration planning
+//!
+//! Turns the per-file recommendation into a plan for a whole set of
+//! configs: which tool, how much there is to move (files, lines,
+//! duplication an abstraction removes, schema lines to write) and a phased
+//! order that keeps every phase shippable on its own. The first phase only
+//! adds validation, against schemas inferred from the documents themselves
+//! so they pass from day one and can be tightened afterwards.
+
+#[cfg(feature = "native")]
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use super::duplicates::{cue_label, nickel_label, parse_roots};
+use super::recommender::recommend_tool;
+use super::{
+    find_duplicates, BlockLocation, Complexity, ConfigAnalyzer, ConfigFormat, RecommendedTool,
+    ToolRecommendation,
+};
+
+/// Schema lines reviewed and tightened into constraints an hour
+const SCHEMA_LINES_PER_HOUR: f64 = 40.0;
+
+/// Duplicated lines folded into a definition or function an hour
+const DEDUP_LINES_PER_HOUR: f64 = 150.0;
+
+/// Lines with conditionals or generated values ported an hour
+const LOGIC_LINES_PER_HOUR: f64 = 60.0;
+
+/// Hours to wire one file into a stage, or convert one without logic
+const HOURS_PER_FILE: f64 = 0.1;
+
+/// Nesting below which inferred schemas leave values open
+const MAX_DEPTH: usize = 8;
+
+/// A phased plan for moving configs to CUE or Nickel
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationPlan {
+    /// Tool for the whole set, from the files' combined complexity
+    pub recommendation: ToolRecommendation,
+    /// JSON, YAML and TOML files to migrate
+    pub files: Vec<PathBuf>,
+    /// Their lines
+    pub lines: usize,
+    /// Files already in CUE or Nickel
+    pub migrated: Vec<PathBuf>,
+    /// Templates, validated once a `render` stage renders them
+    pub templated: Vec<PathBuf>,
+    /// Files with conditionals, functions or generated values
+    pub logic: Vec<PathBuf>,
+    /// Groups of near-identical blocks
+    pub duplicate_groups: usize,
+    /// Lines those groups' abstractions remove
+    pub removable_lines: usize,
+    /// Skeleton schemas for the first phase
+    pub schemas: Vec<SchemaSkeleton>,
+    pub phases: Vec<Phase>,
+}
+
+impl MigrationPlan {
+    /// Lines of the skeleton schemas, in the recommended tool
+    pub fn schema_lines(&self) -> usize {
+        self.schemas
+            .iter()
+            .map(|s| s.render(self.recommendation.primary).lines().count())
+            .sum()
+    }
+
+    /// Estimated hours for every phase
+    pub fn hours(&self) -> f64 {
+        self.phases.iter().map(|p| p.hours).sum()
+    }
+}
+
+/// One shippable step of a migration
+#[derive(Debug, Clone, Serialize)]
+pub struct Phase {
+    pub name: String,
+    /// What the phase achieves
+    pub goal: String,
+    pub steps: Vec<String>,
+    /// Files the phase touches
+    pub files: usize,
+    /// Rough estimate, in half hours
+    pub hours: f64,
+}
+
+/// A schema inferred from documents of one kind
+#[derive(Debug, Clone, Serialize)]
+pub struct SchemaSkeleton {
+    /// Definition or contract name, e.g. `Deployment`
+    pub name: String,
+    /// Kubernetes kind the documents share
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    /// Files holding the documents
+    pub files: Vec<PathBuf>,
+    pub documents: usize,
+    #[serde(skip)]
+    shape: Shape,
+}
+
+impl SchemaSkeleton {
+    /// File name for the schema, e.g. `deployment.cue`
+    pub fn file_name(&self, tool: RecommendedTool) -> String {
+        let mut stem = String::new();
+        for (i, c) in self.name.chars().enumerate() {
+            if c.is_ascii_uppercase() && i > 0 {
+                stem.push('-');
+            }
+            stem.push(c.to_ascii_lowercase());
+        }
+        match tool {
+            RecommendedTool::Cue => format!("{}.cue", stem),
+            RecommendedTool::Nickel => format!("{}.ncl", stem),
+        }
+    }
+
+    /// The schema as a CUE definition or Nickel contract
+    pub fn render(&self, tool: RecommendedTool) -> String {
+        match tool {
+            RecommendedTool::Cue => self.to_cue(),
+            RecommendedTool::Nickel => self.to_nickel(),
+        }
+    }
+
+    /// CUE definition in package `schemas`
+    pub fn to_cue(&self) -> String {
+        let mut out = format!(
+            "// Inferred by conflow from {}.\n\
+             // Types are the values seen; tighten them into constraints\n\
+             // (ranges, enums, patterns).\n\
+             package schemas\n\n#{}: ",
+            self.source(),
+            self.name
+        );
+        render_cue(&self.shape, self.kind.as_deref(), 0, &mut out);
+        out.push('\n');
+        out
+    }
+
+    /// Nickel contract; apply it with `(import "config.yaml") | (import "schema.ncl")`
+    pub fn to_nickel(&self) -> String {
+        let mut out = format!(
+            "# Inferred by conflow from {}.\n\
+             # Types are the values seen; tighten them with contracts of your own.\n",
+            self.source()
+        );
+        render_nickel(&self.shape, 0, &mut out);
+        out.push('\n');
+        out
+    }
+
+    fn source(&self) -> String {
+        let documents = match self.documents {
+            1 => "1 document".to_string(),
+            n => format!("{} documents", n),
+        };
+        match self.files.as_slice() {
+            [file] => format!("{} in {}", documents, file.display()),
+            [file, rest @ ..] => format!(
+                "{} in {} and {} more file(s)",
+                documents,
+                file.display(),
+                rest.len()
+            ),
+            [] => documents,
+        }
+    }
+}
+
+/// Plan the migration of `paths` to the tool their combined complexity
+/// calls for
+///
+/// Files are read and analyzed in parallel with `native`; unreadable files and formats
+/// other than JSON, YAML, TOML, CUE and Nickel are left out.
+pub fn plan_migration(paths: &[PathBuf]) -> MigrationPlan {
+    let analyzer = ConfigAnalyzer::new();
+    #[cfg(feature = "native")]
+    let paths = paths.par_iter();
+    #[cfg(not(feature = "native"))]
+    let paths = paths.iter();
+    let analyzed: Vec<_> = paths
+        .filter_map(|path| {
+            let content = std::fs::read_to_string(path).ok()?;
+            let analysis = analyzer.analyze_content(path, &content).ok()?;
+            Some((path, analysis, parse_roots(path, &content)))
+        })
+        .collect();
+
+    let mut files = Vec::new();
+    let mut migrated = Vec::new();
+    let mut templated = Vec::new();
+    let mut logic = Vec::new();
+    let mut logic_lines = 0;
+    let mut complexity: Option<Complexity> = None;
+    let mut roots = Vec::new();
+    for (path, analysis, parsed) in analyzed {
+        match analysis.format {
+            ConfigFormat::Cue | ConfigFormat::Nickel => {
+                migrated.push(path.clone());
+                continue;
+            }
+            ConfigFormat::Unknown => continue,
+            _ => {}
+        }
+        files.push(path.clone());
+        if analysis.template.is_some() {
+            templated.push(path.clone());
+        }
+        let c = &analysis.complexity;
+        if c.has_logic || c.has_functions || c.has_generation {
+            logic.push(path.clone());
+            logic_lines += c.line_count;
+        }
+        complexity = Some(match complexity {
+            Some(all) => all.merge(analysis.complexity),
+            None => analysis.complexity,
+        });
+        roots.extend(parsed);
+    }
+    let complexity =
+        complexity.unwrap_or_else(|| super::complexity::analyze_complexity("", ConfigFormat::Yaml));
+    let lines = complexity.line_count;
+    let recommendation = recommend_tool(&complexity);
+    let tool = recommendation.primary;
+
+    let groups = find_duplicates(&roots);
+    let removable_lines: usize = groups
+        .iter()
+        .map(|g| (g.members.len() - 1) * g.common)
+        .sum();
+    let schemas = infer_schemas(&roots);
+
+    let mut plan = MigrationPlan {
+        recommendation,
+        files,
+        lines,
+        migrated,
+        templated,
+        logic,
+        duplicate_groups: groups.len(),
+        removable_lines,
+        schemas,
+        phases: Vec::new(),
+    };
+
+    // 1. Validate what exists, changing no config
+    let schema_lines = plan.schema_lines();
+    let (language, stage) = match tool {
+        RecommendedTool::Cue => (
+            "CUE",
+            "Add a `cue vet` stage per schema, with `flags: [-d, \"#Name\"]`",
+        ),
+        RecommendedTool::Nickel => (
+            "Nickel",
+            "Apply each contract in a `nickel export` stage: \
+             `(import \"config.yaml\") | (import \"schemas/name.ncl\")`",
+        ),
+    };
+    let mut steps = vec![
+        format!(
+            "Review the {} skeleton schema(s) ({} lines) written by `conflow recommend -o schemas`",
+            plan.schemas.len(),
+            schema_lines
+        ),
+        stage.to_string(),
+        "Tighten inferred types into constraints: ranges, enums, patterns".to_string(),
+    ];
+    if !plan.templated.is_empty() {
+        steps.push(format!(
+            "Render the {} template(s) in a `render` stage and validate its output \
+             (`conflow init --template helm`)",
+            plan.templated.len()
+        ));
+    }
+    plan.phases.push(Phase {
+        name: "Validate".into(),
+        goal: format!("Check the existing files against {} schemas", language),
+        steps,
+        files: plan.files.len(),
+        hours: half_hours(
+            schema_lines as f64 / SCHEMA_LINES_PER_HOUR + plan.files.len() as f64 * HOURS_PER_FILE,
+        ),
+    });
+
+    // 2. Fold near-identical blocks into abstractions
+    if !groups.is_empty() {
+        let members: BTreeSet<&Path> = groups
+            .iter()
+            .flat_map(|g| g.members.iter().map(|m: &BlockLocation| m.file.as_path()))
+            .collect();
+        let (abstraction, emit) = match tool {
+            RecommendedTool::Cue => ("definitions", "cue"),
+            RecommendedTool::Nickel => ("functions", "nickel"),
+        };
+        plan.phases.push(Phase {
+            name: "Deduplicate".into(),
+            goal: format!(
+                "Replace {} group(s) of near-identical blocks with {}, removing ~{} lines",
+                groups.len(),
+                abstraction,
+                removable_lines
+            ),
+            steps: vec![
+                format!(
+                    "Generate them with `conflow analyze <files> --emit {}`",
+                    emit
+                ),
+                format!(
+                    "Export the instances in a {} stage in place of the copies",
+                    language
+                ),
+            ],
+            files: members.len(),
+            hours: half_hours(groups.len() as f64 + removable_lines as f64 / DEDUP_LINES_PER_HOUR),
+        });
+    }
+
+    // 3. Make the new language the source
+    let (goal, steps) = match tool {
+        RecommendedTool::Cue => (
+            "Write the configs in CUE and export the YAML and JSON from it",
+            vec![
+                "Convert the files with `cue import`, a directory at a time".to_string(),
+                "Replace the copies with a `cue export` stage".to_string(),
+            ],
+        ),
+        RecommendedTool::Nickel => (
+            "Generate the configs from Nickel",
+            vec![
+                format!(
+                    "Port the {} file(s) with conditionals or generated values first",
+                    plan.logic.len()
+                ),
+                "Replace the copies with a `nickel export` stage".to_string(),
+            ],
+        ),
+    };
+    plan.phases.push(Phase {
+        name: "Adopt".into(),
+        goal: goal.into(),
+        steps,
+        files: plan.files.len(),
+        hours: half_hours(
+            plan.files.len() as f64 * HOURS_PER_FILE + logic_lines as f64 / LOGIC_LINES_PER_HOUR,
+        ),
+    });
+
+    plan
+}
+
+/// Round up to a half hour, so no phase looks free
+fn half_hours(hours: f64) -> f64 {
+    ((hours * 2.0).ceil() / 2.0).max(0.5)
+}
+
+/// One skeleton per Kubernetes kind, and per directory (or file, at the
+/// top level) for other documents
+fn infer_schemas(roots: &[(BlockLocation, Value)]) -> Vec<SchemaSkeleton> {
+    let mut families: BTreeMap<(String, Option<String>), SchemaSkeleton> = BTreeMap::new();
+    for (location, value) in roots {
+        let kind = match (value.get("apiVersion"), value.get("kind")) {
+            (Some(Value::String(_)), Some(Value::String(kind))) => Some(kind.clone()),
+            _ => None,
+        };
+        let family = match kind {
+            Some(ref kind) => kind.clone(),
+            None => {
+                let dir = location
+                    .file
+                    .parent()
+                    .and_then(|d| d.file_name())
+                    .filter(|d| !d.is_empty() && *d != ".");
+                let stem = location.file.file_stem();
+                dir.or(stem)
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_default()
+            }
+        };
+        let skeleton = families
+            .entry((pascal_case(&family), kind.clone()))
+            .or_insert_with(|| SchemaSkeleton {
+                name: pascal_case(&family),
+                kind,
+                files: Vec::new(),
+                documents: 0,
+                shape: Shape::Unknown,
+            });
+        if !skeleton.files.contains(&location.file) {
+            skeleton.files.push(location.file.clone());
+        }
+        skeleton.documents += 1;
+        skeleton.shape = std::mem::take(&mut skeleton.shape).merge(Shape::of(value));
+    }
+    families.into_values().collect()
+}
+
+/// `app-settings` as `AppSettings`
+fn pascal_case(name: &str) -> String {
+    let name: String = name
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word[..1].to_ascii_uppercase() + &word[1..])
+        .collect();
+    match name.chars().next() {
+        Some(c) if c.is_ascii_alphabetic() => name,
+        _ => format!("Config{}", name),
+    }
+}
+
+/// Type inferred from the values seen at one place in the documents
+#[derive(Debug, Clone, Default, PartialEq)]
+enum Shape {
+    /// Nothing seen yet, as in the items of empty lists
+    #[default]
+    Unknown,
+    Null,
+    Bool,
+    Int,
+    Number,
+    String,
+    List(Box<Shape>),
+    /// Fields with the number of objects holding each, and the number of
+    /// objects seen
+    Object(BTreeMap<String, (Shape, usize)>, usize),
+    /// Values of different types
+    Either(Vec<Shape>),
+}
+
+impl Shape {
+    fn of(value: &Value) -> Self {
+        match value {
+            Value::Null => Self::Null,
+            Value::Bool(_) => Self::Bool,
+            Value::Number(n) if n.is_i64() || n.is_u64() => Self::Int,
+            Value::Number(_) => Self::Number,
+            Value::String(_) => Self::String,
+            Value::Array(items) => Self::List(Box::new(
+                items
+                    .iter()
+                    .fold(Self::Unknown, |shape, item| shape.merge(Self::of(item))),
+            )),
+            Value::Object(map) => Self::Object(
+                map.iter()
+                    .map(|(key, value)| (key.clone(), (Self::of(value), 1)))
+                    .collect(),
+                1,
+            ),
+        }
+    }
+
+    /// Types of the same kind merge; others become alternatives
+    fn merge(self, other: Self) -> Self {
+        let mut alternatives = Vec::new();
+        for shape in self.alternatives().into_iter().chain(other.alternatives()) {
+            match alternatives
+                .iter()
+                .position(|a: &Shape| a.kind() == shape.kind())
+            {
+                Some(i) => {
+                    let existing = std::mem::take(&mut alternatives[i]);
+                    alternatives[i] = existing.merge_same(shape);
+                }
+                None => alternatives.push(shape),
+            }
+        }
+        match alternatives.len() {
+            0 => Self::Unknown,
+            1 => alternatives.remove(0),
+            _ => Self::Either(alternatives),
+        }
+    }
+
+    fn alternatives(self) -> Vec<Self> {
+        match self {
+            Self::Unknown => Vec::new(),
+            Self::Either(shapes) => shapes,
+            shape => vec![shape],
+        }
+    }
+
+    /// Alternatives of one kind merge; ints and numbers are both numbers
+    fn kind(&self) -> u8 {
+        match self {
+            Self::Unknown | Self::Either(_) => 0,
+            Self::Null => 1,
+            Self::Bool => 2,
+            Self::Int | Self::Number => 3,
+            Self::String => 4,
+            Self::List(_) => 5,
+            Self::Object(..) => 6,
+        }
+    }
+
+    fn merge_same(self, other: Self) -> Self {
+        match (self, other) {
+            (Self::List(a), Self::List(b)) => Self::List(Box::new(a.merge(*b))),
+            (Self::Object(mut fields, seen), Self::Object(more, more_seen)) => {
+                for (key, (shape, count)) in more {
+                    let field = fields.entry(key).or_insert((Self::Unknown, 0));
+                    field.0 = std::mem::take(&mut field.0).merge(shape);
+                    field.1 += count;
+                }
+                Self::Object(fields, seen + more_seen)
+            }
+            (Self::Int, Self::Int) => Self::Int,
+            (Self::Int | Self::Number, Self::Int | Self::Number) => Self::Number,
+            (shape, _) => shape,
+        }
+    }
+}
+
+fn render_cue(shape: &Shape, kind: Option<&str>, depth: usize, out: &mut String) {
+    match shape {
+        Shape::Unknown => out.push('_'),
+        Shape::Null => out.push_str("null"),
+        Shape::Bool => out.push_str("bool"),
+        Shape::Int => out.push_str("int"),
+        Shape::Number => out.push_str("number"),
+        Shape::String => out.push_str("string"),
+        Shape::List(item) if **item == Shape::Unknown => out.push_str("[...]"),
+        Shape::List(item) => {
+            out.push_str("[...");
+            render_cue(item, None, depth, out);
+            out.push(']');
+        }
+        Shape::Object(fields, _) if fields.is_empty() || depth >= MAX_DEPTH => {
+            out.push_str("{...}")
+        }
+        Shape::Object(fields, seen) => {
+            out.push_str("{\n");
+            let pad = "\t".repeat(depth + 1);
+            for (key, (field, count)) in fields {
+                let optional = if count < seen { "?" } else { "" };
+                out.push_str(&format!("{}{}{}: ", pad, cue_label(key), optional));
+                match kind {
+                    Some(kind) if key == "kind" => out.push_str(&Value::from(kind).to_string()),
+                    _ => render_cue(field, None, depth + 1, out),
+                }
+                out.push('\n');
+            }
+            out.push_str(&format!("{}}}", "\t".repeat(depth)));
+        }
+        Shape::Either(shapes) => {
+            for (i, shape) in shapes.iter().enumerate() {
+                if i > 0 {
+                    out.push_str(" | ");
+                }
+                render_cue(shape, None, depth, out);
+            }
+        }
+    }
+}
+
+fn render_nickel(shape: &Shape, depth: usize, out: &mut String) {
+    match shape {
+        Shape::Bool => out.push_str("Bool"),
+        Shape::Int | Shape::Number => out.push_str("Number"),
+        Shape::String => out.push_str("String"),
+        Shape::List(item) => {
+            out.push_str("Array ");
+            match **item {
+                Shape::Object(..) | Shape::List(_) => {
+                    out.push('(');
+                    render_nickel(item, depth, out);
+                    out.push(')');
+                }
+                _ => render_nickel(item, depth, out),
+            }
+        }
+        Shape::Object(fields, seen) if !fields.is_empty() && depth < MAX_DEPTH => {
+            out.push_str("{\n");
+            let pad = "  ".repeat(depth + 1);
+            for (key, (field, count)) in fields {
+                out.push_str(&format!("{}{} | ", pad, nickel_label(key)));
+                render_nickel(field, depth + 1, out);
+                if count < seen {
+                    out.push_str(" | optional");
+                }
+                out.push_str(",\n");
+            }
+            out.push_str(&format!("{}}}", "  ".repeat(depth)));
+        }
+        // Nulls, mixed types and open records have no simple contract
+        _ => out.push_str("Dyn"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_migration() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let mut paths = Vec::new();
+        let deployments = [
+            ("api", 2, false),
+            ("web", 3, false),
+            ("worker", 1, false),
+            ("debug", 1, true),
+        ];
+        for (name, replicas, debug) in deployments {
+            let path = temp.path().join(format!("{}.yaml", name));
+            let debug = if debug { "\n          debug: true" } else { "" };
+            let manifest = format!(
+                "apiVersion: apps/v1\nkind: Deployment\n\
+                 metadata:\n  name: {name}\n  labels: {{team: platform, tier: backend}}\n\
+                 spec:\n  replicas: {replicas}\n  strategy: {{type: RollingUpdate}}\n  \
+                 selector: {{matchLabels: {{app: {name}}}}}\n  \
+                 template:\n    spec:\n      containers:\n        - name: {name}\n          \
+                 image: registry/{name}:1.0\n          \
+                 ports: [{{containerPort: 8080}}]{debug}\n"
+            );
+            std::fs::write(&path, manifest).unwrap();
+            paths.push(path);
+        }
+        let schema = temp.path().join("schema.cue");
+        std::fs::write(&schema, "#App: {name: string}\n").unwrap();
+        paths.push(schema.clone());
+
+        let plan = plan_migration(&paths);
+        assert_eq!(plan.files.len(), 4);
+        assert_eq!(plan.migrated, [schema]);
+        assert_eq!(plan.duplicate_groups, 1);
+        assert!(plan.removable_lines > 0);
+        let names: Vec<&str> = plan.phases.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["Validate", "Deduplicate", "Adopt"]);
+        assert!(plan.hours() >= 1.5);
+
+        let [deployment] = plan.schemas.as_slice() else {
+            panic!("expected one schema, got {:?}", plan.schemas);
+        };
+        assert_eq!(deployment.name, "Deployment");
+        assert_eq!(deployment.documents, 4);
+        assert_eq!(deployment.file_name(RecommendedTool::Cue), "deployment.cue");
+        let cue = deployment.to_cue();
+        assert!(cue.contains("#Deployment: {\n\tapiVersion: string\n\tkind: \"Deployment\"\n"));
+        assert!(cue.contains("\t\treplicas: int\n"));
+        assert!(cue.contains("ports: [...{\n"));
+        assert!(cue.contains("debug?: bool"), "{}", cue);
+        let nickel = deployment.to_nickel();
+        assert!(nickel.contains("  kind | String,\n"));
+        assert!(nickel.contains("debug | Bool | optional,"));
+        assert!(nickel.contains("containers | Array ({"));
+    }
+
+    #[test]
+    fn test_shape_merge() {
+        let shape = Shape::of(&serde_json::json!([1, 2.5, "x", null, []]));
+        let mut cue = String::new();
+        render_cue(&shape, None, 0, &mut cue);
+        assert_eq!(cue, "[...number | string | null | [...]]");
+        let mut nickel = String::new();
+        render_nickel(&shape, 0, &mut nickel);
+        assert_eq!(nickel, "Array Dyn");
+        assert_eq!(pascal_case("app-settings"), "AppSettings");
+        assert_eq!(pascal_case("2024"), "Config2024");
+    }
+}
@@ -5,28 +5,75 @@
 //!
 //! Analyzes configuration files and recommends appropriate tools.
 
+mod aliases;
 mod complexity;
 mod config_detector;
+mod config_graph;
+mod cost;
+mod duplicates;
+mod kubernetes;
+mod migration;
 mod patterns;
+mod provenance;
 mod recommender;
+mod render;
+mod templates;
+mod unused;
 
+pub use aliases::{format_bytes, AliasUsage};
 pub use complexity::Complexity;
-pub use config_detector::ConfigFormat;
+pub use config_detector::{detect_format, ConfigFormat};
+pub use config_graph::{ConfigGraph, ConfigReference, ReferenceKind};
+pub use cost::{
+    estimate_infracost, estimate_kubernetes, estimate_terraform_plan, money, CostEstimate,
+    KubernetesPrices, Pricing, ResourceCost, ResourcePrice,
+};
+pub(crate) use duplicates::parse_roots;
+pub use duplicates::{
+    dotted, find_duplicates, find_duplicates_in_files, BlockLocation, DuplicateGroup, Parameter,
+    Segment, MIN_DUPLICATES,
+};
+pub use kubernetes::{
+    check as check_kubernetes_api, deprecation, find_deprecated_apis, ApiFinding, ApiStatus,
+    DeprecatedApi, KubeVersion, DEPRECATED_APIS,
+};
+pub use migration::{plan_migration, MigrationPlan, Phase, SchemaSkeleton};
+pub use patterns::{detect_patterns, ConfigPattern};
+pub use provenance::{stage_layers, trace, Layer, MergeStrategy, Origin, Role, Trace};
 pub use recommender::{Alternative, RecommendedTool, ToolRecommendation};
+pub use render::{is_chart_helper, render_inputs, render_template, SampleValues, DEFAULT_RELEASE};
+pub use templates::{
+    chart_root, detect_template, stub_render, untemplated_path, TemplateDialect, PLACEHOLDER,
+};
+pub use unused::{
+    find_unused, pipeline_bindings, Schema, SchemaBinding, UnsetField, UnusedKey, UnusedReport,
+};
 
-use std::path::Path;
+#[cfg(feature = "native")]
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
 
 use crate::errors::ConflowError;
 
 /// Result of analyzing a configuration file
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Analysis {
     /// Detected configuration format
     pub format: ConfigFormat,
+    /// Number of YAML documents (1 for other formats)
+    pub documents: usize,
     /// Complexity analysis
     pub complexity: Complexity,
     /// Tool recommendation
     pub recommendation: ToolRecommendation,
+    /// Problems worth flagging regardless of the tool choice
+    #[serde(default)]
+    pub warnings: Vec<String>,
+    /// Template dialect; the file was analyzed as rendered with its chart's
+    /// defaults, or failing that with its tags stubbed out
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<TemplateDialect>,
 }
 
 /// Configuration analyzer
@@ -39,6 +86,7 @@ impl ConfigAnalyzer {
     }
 
     /// Analyze a configuration file
+    #[cfg(feature = "native")]
     pub async fn analyze(&self, path: &Path) -> Result<Analysis, ConflowError> {
         // Read file content
         let content = tokio::fs::read_to_string(path).await.map_err(|e| {
@@ -47,26 +95,173 @@ impl ConfigAnalyzer {
                 error: e.to_string(),
             }
         })?;
+        self.analyze_content(path, &content)
+    }
+
+    /// Analyze many files on the rayon pool (in order, without `native`),
+    /// keeping results in input order
+    pub fn analyze_files(&self, paths: &[PathBuf]) -> Vec<Result<Analysis, ConflowError>> {
+        #[cfg(feature = "native")]
+        let paths = paths.par_iter();
+        #[cfg(not(feature = "native"))]
+        let paths = paths.iter();
+        paths
+            .map(|path| {
+                let content =
+                    std::fs::read_to_string(path).map_err(|e| ConflowError::FileReadError {
+                        path: path.to_path_buf(),
+                        error: e.to_string(),
+                    })?;
+                self.analyze_content(path, &content)
+            })
+            .collect()
+    }
+
+    /// Analyze configuration content read from `path`
+    pub fn analyze_content(&self, path: &Path, content: &str) -> Result<Analysis, ConflowError> {
+        // Chart templates are analyzed as rendered with the chart's default
+        // values, other templates as the structure around their tags
+        let template = templates::detect_template(content, path);
+        let mut rendered = false;
+        let (untemplated, expanded);
+        let (path, content) = match template {
+            Some(_) => {
+                untemplated = untemplated_path(path);
+                expanded = match render_with_defaults(path, content) {
+                    Some(output) => {
+                        rendered = true;
+                        output
+                    }
+                    None => templates::stub_render(content),
+                };
+                (untemplated.as_path(), expanded.as_str())
+            }
+            None => (path, content),
+        };
 
         // Detect format
-        let format = config_detector::detect_format(&content, path)?;
+        let format = config_detector::detect_format(content, path)?;
 
-        // Analyze complexity
-        let complexity = complexity::analyze_complexity(&content, format);
+        // Analyze complexity, each document of a YAML stream on its own so
+        // lines shared by every manifest don't count as repetition
+        let documents = match format {
+            ConfigFormat::Yaml => crate::utils::yaml::documents(content)
+                .into_iter()
+                .map(|d| d.content)
+                .collect(),
+            _ => vec![content],
+        };
+        let complexity = documents
+            .iter()
+            .map(|d| complexity::analyze_complexity(d, format))
+            .reduce(Complexity::merge)
+            .unwrap_or_else(|| complexity::analyze_complexity(content, format));
 
         // Generate recommendation
         let recommendation = recommender::recommend_tool(&complexity);
 
+        let mut warnings = Vec::new();
+        let aliases = &complexity.aliases;
+        if aliases.is_explosive() {
+            warnings.push(format!(
+                "Aliases expand {} to {} ({:.0}x); parsers that expand aliases are \
+                 exposed to \"billion laughs\" blow-ups",
+                format_bytes(aliases.source_bytes),
+                format_bytes(aliases.expanded_bytes),
+                aliases.expansion_ratio()
+            ));
+        }
+
+        if let (Some(dialect), true) = (template, rendered) {
+            warnings.push(format!(
+                "{} template: analyzed as rendered with its chart's default values. Add a \
+                 `render` stage before validating (`conflow init --template helm` sets one up) \
+                 so schemas check what it renders",
+                dialect
+            ));
+        } else if let Some(dialect) = template {
+            let mut warning = match dialect {
+                TemplateDialect::Jinja => format!(
+                    "{} template: analyzed with its expressions stubbed out. Render it in a \
+                     stage before validating (e.g. `{}`) and validate the rendered output",
+                    dialect,
+                    dialect.render_command()
+                ),
+                _ => format!(
+                    "{} template: analyzed with its expressions stubbed out. Add a `render` \
+                     stage to render it with sample values before validating (try `{}`)",
+                    dialect,
+                    dialect.render_command()
+                ),
+            };
+            let parses = match format {
+                ConfigFormat::Yaml => documents
+                    .iter()
+                    .all(|d| serde_yaml::from_str::<serde_yaml::Value>(d).is_ok()),
+                ConfigFormat::Json => serde_json::from_str::<serde_json::Value>(content).is_ok(),
+                _ => true,
+            };
+            if !parses {
+                warning.push_str(&format!(
+                    "; even stubbed it isn't valid {:?}, so only text patterns were checked",
+                    format
+                ));
+            }
+            warnings.push(warning);
+        }
+
         Ok(Analysis {
             format,
+            documents: documents.len().max(1),
             complexity,
             recommendation,
+            warnings,
+            template,
         })
     }
 }
 
+/// A chart template rendered with the chart's default values, if that
+/// renders something that parses
+fn render_with_defaults(path: &Path, content: &str) -> Option<String> {
+    chart_root(path)?;
+    let output = render_template(path, content, &SampleValues::default()).ok()?;
+    let parses = crate::utils::yaml::documents(&output)
+        .iter()
+        .all(|d| serde_yaml::from_str::<serde_yaml::Value>(d.content).is_ok());
+    (parses && !output.trim().is_empty()).then_some(output)
+}
+
 impl Default for ConfigAnalyzer {
     fn default() -> Self {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_analyze_files_keeps_order() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let mut paths = Vec::new();
+        for i in 0..16 {
+            let path = temp.path().join(format!("{:02}.yaml", i));
+            let docs = vec!["a: 1"; i + 1].join("\n---\n");
+            std::fs::write(&path, docs).unwrap();
+            paths.push(path);
+        }
+        paths.insert(3, temp.path().join("missing.yaml"));
+
+        let results = ConfigAnalyzer::new().analyze_files(&paths);
+        assert_eq!(results.len(), 17);
+        assert!(matches!(results[3], Err(ConflowError::FileReadError { .. })));
+        let documents: Vec<usize> = results
+            .iter()
+            .filter_map(|r| r.as_ref().ok())
+            .map(|a| a.documents)
+            .collect();
+        assert_eq!(documents, (1..=16).collect::<Vec<_>>());
+    }
+}
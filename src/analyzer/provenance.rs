@@ -0,0 +1,516 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Value provenance
+//!
+//! When a stage combines several files, such as CUE unifying schemas with
+//! data or Helm layering values files, traces where the final value at a
+//! path came from: the file that set it, the files it overrode, and the
+//! CUE defaults and constraints that applied.
+
+use regex::Regex;
+use serde_json::Value;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use super::unused::{cue_expressions, ELEMENT};
+use crate::cache::ParseCache;
+use crate::errors::ConflowError;
+use crate::executors::resolve_inputs;
+use crate::pipeline::{stage_workdir, Stage, Tool};
+use crate::utils::yaml;
+
+/// `*value` default marker in a CUE expression
+static DEFAULT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"\*\s*("(?:[^"\\]|\\.)*"|[^\s|&,)}]+)"#).unwrap());
+
+/// Definition reference in a CUE expression
+static REFERENCE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"#[A-Za-z_]\w*").unwrap());
+
+/// How a stage combines its files
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeStrategy {
+    /// Later files override earlier ones and mappings merge (Helm values)
+    Override,
+    /// Values must agree and defaults fill the gaps (CUE)
+    Unify,
+}
+
+impl std::fmt::Display for MergeStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Override => write!(f, "override"),
+            Self::Unify => write!(f, "unify"),
+        }
+    }
+}
+
+/// One file of a merge, in order
+#[derive(Debug, Clone)]
+pub struct Layer {
+    pub file: PathBuf,
+    content: String,
+    source: Source,
+}
+
+#[derive(Debug, Clone)]
+enum Source {
+    /// JSON or YAML documents, with their offsets
+    Data(Vec<(usize, Value)>),
+    /// CUE field expressions
+    Cue(Vec<(Vec<String>, Range<usize>)>),
+}
+
+impl Layer {
+    /// Load a CUE, JSON or YAML file
+    pub fn load(path: &Path) -> Result<Self, ConflowError> {
+        let content = std::fs::read_to_string(path).map_err(|e| ConflowError::FileReadError {
+            path: path.to_path_buf(),
+            error: e.to_string(),
+        })?;
+        Self::parse(path, content)
+    }
+
+    /// A layer from file content
+    pub fn parse(path: &Path, content: String) -> Result<Self, ConflowError> {
+        let source = match path.extension().and_then(|e| e.to_str()) {
+            Some("cue") => Source::Cue(cue_expressions(&content)),
+            Some("json") => {
+                let values = ParseCache::global().documents(path, &content)?;
+                Source::Data(values.iter().map(|v| (0, v.clone())).collect())
+            }
+            Some("yaml" | "yml") => {
+                let values = ParseCache::global().documents(path, &content)?;
+                let offsets = yaml::documents(&content).into_iter().map(|d| d.offset);
+                Source::Data(offsets.zip(values.iter().cloned()).collect())
+            }
+            _ => {
+                return Err(ConflowError::FormatDetectionFailed {
+                    path: path.to_path_buf(),
+                })
+            }
+        };
+        Ok(Self {
+            file: path.to_path_buf(),
+            content,
+            source,
+        })
+    }
+
+    /// What this layer says about the value at `keys`
+    fn contributions(&self, keys: &[String]) -> Vec<(Option<usize>, Contribution)> {
+        match &self.source {
+            Source::Data(documents) => {
+                let path: Vec<&str> = keys.iter().map(String::as_str).collect();
+                documents
+                    .iter()
+                    .filter_map(|(offset, document)| {
+                        let value = lookup(document, keys)?;
+                        let line = yaml::key_line(&self.content, *offset, &path);
+                        Some((line, Contribution::Value(value.clone())))
+                    })
+                    .collect()
+            }
+            Source::Cue(exprs) => cue_lookup(&self.content, exprs, keys, 0)
+                .map(|span| {
+                    let line = self.content[..span.start].matches('\n').count() + 1;
+                    (Some(line), classify(self.content[span].trim()))
+                })
+                .into_iter()
+                .collect(),
+        }
+    }
+}
+
+/// Files a stage merges, and how
+///
+/// CUE stages unify their schemas with their inputs; Helm stages layer
+/// their values files. Other tools don't merge files.
+pub fn stage_layers(stage: &Stage, working_dir: &Path) -> Option<(Vec<PathBuf>, MergeStrategy)> {
+    let dir = stage_workdir(stage, working_dir);
+    match &stage.tool {
+        Tool::Cue { schemas, .. } => {
+            let mut files: Vec<PathBuf> = schemas.iter().map(|s| dir.join(s)).collect();
+            files.extend(resolve_inputs(stage, &dir).unwrap_or_default());
+            Some((files, MergeStrategy::Unify))
+        }
+        Tool::Helm { values, .. } if !values.is_empty() => Some((
+            values.iter().map(|v| dir.join(v)).collect(),
+            MergeStrategy::Override,
+        )),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Contribution {
+    Value(Value),
+    Default(Value, String),
+    Constraint(String),
+}
+
+/// What a file did to the final value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    /// Set the final value
+    Set,
+    /// Contributed keys to a merged mapping
+    Merged,
+    /// Set a value a later file replaced
+    Overridden,
+    /// Set a value that disagrees with another file
+    Conflicting,
+    /// Supplied the default that became the final value
+    Default,
+    /// Constrained the value without setting it
+    Constraint,
+}
+
+impl std::fmt::Display for Role {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Set => write!(f, "set"),
+            Self::Merged => write!(f, "merged"),
+            Self::Overridden => write!(f, "overridden"),
+            Self::Conflicting => write!(f, "conflict"),
+            Self::Default => write!(f, "default"),
+            Self::Constraint => write!(f, "constraint"),
+        }
+    }
+}
+
+/// A file's part in the final value
+#[derive(Debug, Clone)]
+pub struct Origin {
+    pub file: PathBuf,
+    pub line: Option<usize>,
+    pub role: Role,
+    /// The value or expression the file gives
+    pub text: String,
+}
+
+/// Where the value at a path came from
+#[derive(Debug, Clone)]
+pub struct Trace {
+    pub path: String,
+    /// Final value, if the files agree on one
+    pub value: Option<Value>,
+    /// Contributing files, in merge order
+    pub origins: Vec<Origin>,
+}
+
+/// Trace the value at a dotted path (`spec.ports[0].name`) through layers
+pub fn trace(layers: &[Layer], path: &str, strategy: MergeStrategy) -> Trace {
+    let keys: Vec<String> = path
+        .replace('[', ".")
+        .replace(']', "")
+        .split('.')
+        .filter(|k| !k.is_empty())
+        .map(str::to_string)
+        .collect();
+
+    let found: Vec<(&Layer, Option<usize>, Contribution)> = layers
+        .iter()
+        .flat_map(|layer| {
+            layer
+                .contributions(&keys)
+                .into_iter()
+                .map(move |(line, c)| (layer, line, c))
+        })
+        .collect();
+
+    let values: Vec<&Value> = found
+        .iter()
+        .filter_map(|(_, _, c)| match c {
+            Contribution::Value(v) => Some(v),
+            _ => None,
+        })
+        .collect();
+
+    // Role of each value, in order, and the final value
+    let (mut roles, value) = match strategy {
+        MergeStrategy::Override => override_roles(&values),
+        MergeStrategy::Unify => unify_roles(&values),
+    };
+    roles.reverse();
+
+    let default = found.iter().find_map(|(_, _, c)| match c {
+        Contribution::Default(v, _) => Some(v.clone()),
+        _ => None,
+    });
+    let unset = values.is_empty();
+    let value = if unset { default } else { value };
+
+    let origins = found
+        .into_iter()
+        .map(|(layer, line, contribution)| {
+            let (role, text) = match contribution {
+                Contribution::Value(v) => (roles.pop().unwrap_or(Role::Set), render(&v)),
+                Contribution::Default(_, expr) if unset => (Role::Default, expr),
+                Contribution::Default(_, expr) => (Role::Overridden, expr),
+                Contribution::Constraint(expr) => (Role::Constraint, expr),
+            };
+            Origin {
+                file: layer.file.clone(),
+                line,
+                role,
+                text,
+            }
+        })
+        .collect();
+
+    Trace {
+        path: path.to_string(),
+        value,
+        origins,
+    }
+}
+
+/// Later values win; mappings merge until a non-mapping replaces them
+fn override_roles(values: &[&Value]) -> (Vec<Role>, Option<Value>) {
+    let Some((last, earlier)) = values.split_last() else {
+        return (vec![], None);
+    };
+
+    let mut merging = last.is_object();
+    let mut merged = vec![*last];
+    let mut roles = vec![Role::Set];
+    for value in earlier.iter().rev() {
+        if merging && value.is_object() {
+            roles.push(Role::Merged);
+            merged.push(value);
+        } else {
+            roles.push(Role::Overridden);
+            merging = false;
+        }
+    }
+    roles.reverse();
+
+    let mut value = Value::Null;
+    for layer in merged.into_iter().rev() {
+        deep_merge(&mut value, layer);
+    }
+    if roles.len() > 1 && roles.iter().all(|r| *r != Role::Overridden) {
+        // Every layer contributed keys to the mapping
+        roles.iter_mut().for_each(|r| *r = Role::Merged);
+    }
+    (roles, Some(value))
+}
+
+/// Values must agree; mappings combine
+fn unify_roles(values: &[&Value]) -> (Vec<Role>, Option<Value>) {
+    let mut value = Value::Null;
+    let mut conflict = false;
+    for layer in values {
+        conflict |= !unify(&mut value, layer);
+    }
+
+    let role = match (conflict, values.iter().all(|v| v.is_object())) {
+        (true, _) => Role::Conflicting,
+        (false, true) if values.len() > 1 => Role::Merged,
+        _ => Role::Set,
+    };
+    let value = (!conflict && !values.is_empty()).then_some(value);
+    (vec![role; values.len()], value)
+}
+
+fn deep_merge(target: &mut Value, layer: &Value) {
+    match (target, layer) {
+        (Value::Object(target), Value::Object(layer)) => {
+            for (key, value) in layer {
+                deep_merge(target.entry(key.clone()).or_insert(Value::Null), value);
+            }
+        }
+        (target, layer) => *target = layer.clone(),
+    }
+}
+
+/// CUE-style unification; false if the values conflict
+fn unify(target: &mut Value, layer: &Value) -> bool {
+    match (target, layer) {
+        (target @ Value::Null, layer) => {
+            *target = layer.clone();
+            true
+        }
+        (Value::Object(target), Value::Object(layer)) => layer.iter().fold(true, |ok, (k, v)| {
+            unify(target.entry(k.clone()).or_insert(Value::Null), v) && ok
+        }),
+        (target, layer) => *target == *layer,
+    }
+}
+
+fn lookup<'a>(value: &'a Value, keys: &[String]) -> Option<&'a Value> {
+    keys.iter().try_fold(value, |value, key| match value {
+        Value::Object(map) => map.get(key),
+        Value::Array(items) => items.get(key.parse::<usize>().ok()?),
+        _ => None,
+    })
+}
+
+/// The expression for `keys`, following definition references
+///
+/// `service: #Service` sends `service.replicas` to `#Service.replicas`.
+fn cue_lookup(
+    content: &str,
+    exprs: &[(Vec<String>, Range<usize>)],
+    keys: &[String],
+    depth: usize,
+) -> Option<Range<usize>> {
+    let keys: Vec<String> = keys
+        .iter()
+        .map(|k| match k.parse::<usize>() {
+            Ok(_) => ELEMENT.to_string(),
+            Err(_) => k.clone(),
+        })
+        .collect();
+    let find = |keys: &[String]| {
+        exprs
+            .iter()
+            .rev()
+            .find(|(path, _)| path.as_slice() == keys)
+            .map(|(_, span)| span.clone())
+    };
+
+    if let Some(span) = find(&keys) {
+        return Some(span);
+    }
+    if depth > 8 {
+        return None;
+    }
+    (1..keys.len()).rev().find_map(|i| {
+        let span = find(&keys[..i])?;
+        REFERENCE.find_iter(&content[span]).find_map(|name| {
+            let mut path = vec![name.as_str().to_string()];
+            path.extend_from_slice(&keys[i..]);
+            cue_lookup(content, exprs, &path, depth + 1)
+        })
+    })
+}
+
+fn classify(expr: &str) -> Contribution {
+    if let Ok(value) = serde_json::from_str(expr) {
+        return Contribution::Value(value);
+    }
+    match DEFAULT.captures(expr) {
+        Some(caps) => {
+            let default = &caps[1];
+            let value = serde_json::from_str(default).unwrap_or_else(|_| default.into());
+            Contribution::Default(value, expr.to_string())
+        }
+        None => Contribution::Constraint(expr.to_string()),
+    }
+}
+
+/// One-line rendering of a value
+fn render(value: &Value) -> String {
+    match value {
+        Value::Object(map) if map.len() > 4 => {
+            let keys: Vec<&str> = map.keys().take(4).map(String::as_str).collect();
+            format!("{{{}, …}}", keys.join(", "))
+        }
+        Value::Object(map) => {
+            let keys: Vec<&str> = map.keys().map(String::as_str).collect();
+            format!("{{{}}}", keys.join(", "))
+        }
+        Value::Array(items) => format!("[{} item(s)]", items.len()),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layer(file: &str, content: &str) -> Layer {
+        Layer::parse(Path::new(file), content.to_string()).unwrap()
+    }
+
+    fn roles(trace: &Trace) -> Vec<(String, Option<usize>, Role)> {
+        trace
+            .origins
+            .iter()
+            .map(|o| (o.file.display().to_string(), o.line, o.role))
+            .collect()
+    }
+
+    #[test]
+    fn test_override_layers() {
+        let layers = vec![
+            layer(
+                "base.yaml",
+                "image:\n  tag: \"1.0\"\n  pullPolicy: Always\nreplicas: 1\n",
+            ),
+            layer("prod.yaml", "replicas: 3\nimage:\n  tag: \"2.0\"\n"),
+        ];
+
+        let trace = trace(&layers, "replicas", MergeStrategy::Override);
+        assert_eq!(trace.value, Some(3.into()));
+        assert_eq!(
+            roles(&trace),
+            vec![
+                ("base.yaml".into(), Some(4), Role::Overridden),
+                ("prod.yaml".into(), Some(1), Role::Set),
+            ]
+        );
+
+        let image = super::trace(&layers, "image", MergeStrategy::Override);
+        assert_eq!(
+            image.value,
+            Some(serde_json::json!({ "tag": "2.0", "pullPolicy": "Always" }))
+        );
+        assert!(image.origins.iter().all(|o| o.role == Role::Merged));
+
+        let missing = super::trace(&layers, "image.digest", MergeStrategy::Override);
+        assert!(missing.value.is_none() && missing.origins.is_empty());
+    }
+
+    #[test]
+    fn test_unify_with_cue_defaults() {
+        let schema = "#Service: {\n\tname: string\n\treplicas: int & >0 | *1\n\tport: 8080\n}\nservice: #Service\n";
+        let layers = vec![
+            layer("schema.cue", schema),
+            layer("a.yaml", "service:\n  name: web\n"),
+            layer("b.json", "{ \"service\": { \"name\": \"web\" } }"),
+        ];
+
+        let replicas = trace(&layers, "service.replicas", MergeStrategy::Unify);
+        assert_eq!(replicas.value, Some(1.into()));
+        assert_eq!(replicas.origins.len(), 1);
+        assert_eq!(replicas.origins[0].role, Role::Default);
+        assert_eq!(replicas.origins[0].line, Some(3));
+        assert_eq!(replicas.origins[0].text, "int & >0 | *1");
+
+        let port = trace(&layers, "service.port", MergeStrategy::Unify);
+        assert_eq!(port.value, Some(8080.into()));
+        assert_eq!(port.origins[0].role, Role::Set);
+
+        let name = trace(&layers, "service.name", MergeStrategy::Unify);
+        assert_eq!(name.value, Some("web".into()));
+        assert_eq!(
+            roles(&name),
+            vec![
+                ("schema.cue".into(), Some(2), Role::Constraint),
+                ("a.yaml".into(), Some(2), Role::Set),
+                ("b.json".into(), Some(1), Role::Set),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unify_conflict() {
+        let layers = vec![
+            layer("a.yaml", "replicas: 2\n"),
+            layer("b.yaml", "---\nname: x\n---\nreplicas: 3\n"),
+        ];
+        let trace = trace(&layers, "replicas", MergeStrategy::Unify);
+        assert_eq!(trace.value, None);
+        assert_eq!(
+            roles(&trace),
+            vec![
+                ("a.yaml".into(), Some(1), Role::Conflicting),
+                ("b.yaml".into(), Some(4), Role::Conflicting),
+            ]
+        );
+    }
+}
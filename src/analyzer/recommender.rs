@@ -5,17 +5,21 @@
 //!
 //! Recommends the appropriate tool (CUE or Nickel) based on complexity analysis.
 
+use serde::{Deserialize, Serialize};
+
 use super::Complexity;
+use crate::i18n::{tr, tr_with};
 
 /// Recommended tool
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum RecommendedTool {
     Cue,
     Nickel,
 }
 
 /// Tool recommendation with rationale
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolRecommendation {
     /// Primary recommended tool
     pub primary: RecommendedTool,
@@ -24,11 +28,12 @@ pub struct ToolRecommendation {
     /// Alternative options
     pub alternatives: Vec<Alternative>,
     /// Suggested combined approach (if applicable)
+    #[serde(default)]
     pub combined_approach: Option<String>,
 }
 
 /// An alternative tool option
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Alternative {
     pub tool: RecommendedTool,
     pub reason: String,
@@ -60,49 +65,67 @@ pub fn recommend_tool(complexity: &Complexity) -> ToolRecommendation {
     // Logic patterns strongly favor Nickel
     if complexity.has_logic {
         nickel_score += 3;
-        nickel_reasons.push("Complex logic detected (conditionals, branching)".to_string());
+        nickel_reasons.push(tr("analyze.logic"));
     }
 
     // Functions strongly favor Nickel
     if complexity.has_functions {
         nickel_score += 3;
-        nickel_reasons.push("Function definitions detected".to_string());
+        nickel_reasons.push(tr("analyze.functions"));
     }
 
     // Generation patterns favor Nickel
     if complexity.has_generation {
         nickel_score += 2;
-        nickel_reasons.push("Configuration generation patterns detected".to_string());
+        nickel_reasons.push(tr("analyze.generation"));
     }
 
     // Repetition suggests Nickel for DRY
     if complexity.has_repetition {
         nickel_score += 1;
-        nickel_reasons.push("Repetitive patterns could benefit from abstraction".to_string());
+        nickel_reasons.push(tr("analyze.repetition"));
+    }
+
+    // Anchors emulate abstraction YAML lacks; heavy use is a strong signal
+    let aliases = &complexity.aliases;
+    if aliases.aliases >= 3 {
+        nickel_score += 2;
+        nickel_reasons.push(tr_with("analyze.aliases", &[("count", &aliases.aliases)]));
+    }
+    if aliases.merge_keys >= 2 {
+        cue_score += 2;
+        cue_reasons.push(tr_with("analyze.merge_keys", &[("count", &aliases.merge_keys)]));
+    }
+    if aliases.is_explosive() {
+        nickel_score += 1;
+        cue_score += 1;
+        let reason = tr("analyze.alias_explosion");
+        nickel_reasons.push(reason.clone());
+        cue_reasons.push(reason);
     }
 
     // Constraints favor CUE
     if complexity.has_constraints {
         cue_score += 3;
-        cue_reasons.push("Constraint validation patterns detected".to_string());
+        cue_reasons.push(tr("analyze.constraints"));
     }
 
     // Validation patterns favor CUE
     if complexity.has_validation {
         cue_score += 2;
-        cue_reasons.push("Schema validation requirements detected".to_string());
+        cue_reasons.push(tr("analyze.validation"));
     }
 
     // Deep nesting slightly favors CUE (unification handles it well)
     if complexity.nesting_depth > 3 {
         cue_score += 1;
-        cue_reasons.push("Deep nesting works well with CUE unification".to_string());
+        cue_reasons.push(tr("analyze.nesting"));
     }
 
     // Simple configs slightly favor CUE
     if !complexity.has_logic && !complexity.has_functions && complexity.line_count < 50 {
         cue_score += 1;
-        cue_reasons.push("Simple configuration structure".to_string());
+        cue_reasons.push(tr("analyze.simple"));
     }
 
     // Make recommendation
@@ -122,8 +145,8 @@ pub fn recommend_tool(complexity: &Complexity) -> ToolRecommendation {
     // Add default reason if none
     if rationale.is_empty() {
         rationale.push(match primary {
-            RecommendedTool::Cue => "CUE provides good validation defaults".to_string(),
-            RecommendedTool::Nickel => "Nickel provides flexible configuration".to_string(),
+            RecommendedTool::Cue => tr("analyze.default_cue"),
+            RecommendedTool::Nickel => tr("analyze.default_nickel"),
         });
     }
 
@@ -135,7 +158,7 @@ pub fn recommend_tool(complexity: &Complexity) -> ToolRecommendation {
             if complexity.has_repetition || complexity.line_count > 100 {
                 alternatives.push(Alternative {
                     tool: RecommendedTool::Nickel,
-                    reason: "Consider Nickel if you need more abstraction".to_string(),
+                    reason: tr("analyze.alternative_nickel"),
                 });
             }
         }
@@ -143,7 +166,7 @@ pub fn recommend_tool(complexity: &Complexity) -> ToolRecommendation {
             if complexity.has_constraints {
                 alternatives.push(Alternative {
                     tool: RecommendedTool::Cue,
-                    reason: "CUE provides stronger constraint validation".to_string(),
+                    reason: tr("analyze.alternative_cue"),
                 });
             }
         }
@@ -151,7 +174,7 @@ pub fn recommend_tool(complexity: &Complexity) -> ToolRecommendation {
 
     // Suggest combined approach if both have strong signals
     let combined_approach = if nickel_score >= 2 && cue_score >= 2 {
-        Some("Use Nickel to generate configurations, then CUE to validate them".to_string())
+        Some(tr("analyze.combined"))
     } else {
         None
     };
@@ -167,6 +190,7 @@ pub fn recommend_tool(complexity: &Complexity) -> ToolRecommendation {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::analyzer::AliasUsage;
 
     #[test]
     fn test_recommend_cue_for_constraints() {
@@ -179,6 +203,7 @@ mod tests {
             line_count: 50,
             nesting_depth: 2,
             has_repetition: false,
+            aliases: AliasUsage::default(),
         };
 
         let rec = recommend_tool(&complexity);
@@ -196,6 +221,7 @@ mod tests {
             line_count: 100,
             nesting_depth: 3,
             has_repetition: true,
+            aliases: AliasUsage::default(),
         };
 
         let rec = recommend_tool(&complexity);
@@ -213,9 +239,40 @@ mod tests {
             line_count: 100,
             nesting_depth: 3,
             has_repetition: false,
+            aliases: AliasUsage::default(),
         };
 
         let rec = recommend_tool(&complexity);
         assert!(rec.combined_approach.is_some());
     }
+
+    #[test]
+    fn test_aliases_are_a_strong_signal() {
+        let mut complexity = Complexity {
+            has_logic: false,
+            has_functions: false,
+            has_constraints: false,
+            has_validation: false,
+            has_generation: false,
+            line_count: 40,
+            nesting_depth: 2,
+            has_repetition: false,
+            aliases: AliasUsage {
+                aliases: 6,
+                ..Default::default()
+            },
+        };
+
+        let rec = recommend_tool(&complexity);
+        assert_eq!(rec.primary, RecommendedTool::Nickel);
+        assert!(rec.rationale[0].contains("6 YAML aliases"));
+
+        complexity.aliases = AliasUsage {
+            merge_keys: 4,
+            ..Default::default()
+        };
+        let rec = recommend_tool(&complexity);
+        assert_eq!(rec.primary, RecommendedTool::Cue);
+        assert!(rec.rationale[0].contains("merge keys"));
+    }
 }
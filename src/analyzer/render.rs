@@ -0,0 +1,358 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Sample-values rendering
+//!
+//! Templated configuration can only be validated once rendered.
+//! [`render_template`] renders Helm chart templates and Go-templated files
+//! with sample values: the chart's `values.yaml` defaults, then values
+//! files, then `key=value` overrides, so schemas and policies see the YAML a
+//! release would get. Chart templates also get `.Release`, `.Chart`,
+//! `.Capabilities` and `.Template`, and the blocks the chart's `.tpl`
+//! helpers define.
+
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use super::templates::{chart_root, detect_template, TemplateDialect};
+use super::KubeVersion;
+use crate::errors::ConflowError;
+use crate::utils::gotemplate::TemplateSet;
+
+/// Release name charts are rendered for, as with `helm template`
+pub const DEFAULT_RELEASE: &str = "release-name";
+
+/// API versions `.Capabilities.APIVersions.Has` finds
+const API_VERSIONS: &[&str] = &[
+    "v1",
+    "admissionregistration.k8s.io/v1",
+    "apiextensions.k8s.io/v1",
+    "apps/v1",
+    "autoscaling/v1",
+    "autoscaling/v2",
+    "batch/v1",
+    "certificates.k8s.io/v1",
+    "coordination.k8s.io/v1",
+    "discovery.k8s.io/v1",
+    "events.k8s.io/v1",
+    "networking.k8s.io/v1",
+    "node.k8s.io/v1",
+    "policy/v1",
+    "rbac.authorization.k8s.io/v1",
+    "scheduling.k8s.io/v1",
+    "storage.k8s.io/v1",
+];
+
+/// Values and release settings templates are rendered with
+#[derive(Debug, Clone, Default)]
+pub struct SampleValues {
+    /// YAML or JSON values files, each layered over the ones before
+    pub files: Vec<PathBuf>,
+    /// Values set by dotted key, applied last
+    pub set: BTreeMap<String, Value>,
+    /// Release name (default: [`DEFAULT_RELEASE`])
+    pub release: Option<String>,
+    /// Release namespace (default: `default`)
+    pub namespace: Option<String>,
+    /// Kubernetes release for `.Capabilities` (default: the newest known)
+    pub kube_version: Option<KubeVersion>,
+}
+
+impl SampleValues {
+    /// Set `key.path` to `value` parsed as YAML: `3` is a number, `true` a
+    /// bool, and anything that doesn't parse a string
+    pub fn set_str(&mut self, key: &str, value: &str) {
+        let parsed = match value {
+            "" => Value::String(String::new()),
+            value => serde_yaml::from_str(value).unwrap_or_else(|_| Value::String(value.into())),
+        };
+        self.set.insert(key.to_string(), parsed);
+    }
+}
+
+/// `content` rendered with sample values
+///
+/// Content that isn't a Go template is returned unchanged. Jinja templates
+/// can't be rendered here.
+pub fn render_template(
+    path: &Path,
+    content: &str,
+    values: &SampleValues,
+) -> Result<String, ConflowError> {
+    let failed = |message: String, help: Option<&str>| ConflowError::Template {
+        template: path.display().to_string(),
+        message,
+        help: help.map(str::to_string),
+    };
+    let dialect = match detect_template(content, path) {
+        None => return Ok(content.to_string()),
+        Some(TemplateDialect::Jinja) => {
+            return Err(failed(
+                "Jinja templates can't be rendered with sample values".into(),
+                Some("Render it in a shell stage, e.g. `j2 <file> <values>`"),
+            ))
+        }
+        Some(dialect) => dialect,
+    };
+
+    let chart = chart_root(path);
+    let mut merged = match chart.map(|chart| chart.join("values.yaml")) {
+        Some(defaults) if defaults.is_file() => read_values(&defaults)?,
+        _ => Value::Object(Map::new()),
+    };
+    for file in &values.files {
+        merge(&mut merged, read_values(file)?);
+    }
+    for (key, value) in &values.set {
+        set(&mut merged, key, value);
+    }
+
+    let mut templates = TemplateSet::new();
+    let data = match dialect {
+        TemplateDialect::Helm => {
+            if let Some(chart) = chart {
+                for helper in helpers(chart) {
+                    let source = read(&helper)?;
+                    templates
+                        .define(&source)
+                        .map_err(|e| ConflowError::Template {
+                            template: helper.display().to_string(),
+                            message: e.to_string(),
+                            help: None,
+                        })?;
+                }
+            }
+            helm_data(chart, path, merged, values)?
+        }
+        _ => merged,
+    };
+
+    templates
+        .render(content, &data)
+        .map_err(|e| failed(e.to_string(), None))
+}
+
+/// Files other than `path` itself that rendering it reads: for chart
+/// templates, the chart's `Chart.yaml`, `values.yaml` and `.tpl` helpers
+pub fn render_inputs(path: &Path) -> Vec<PathBuf> {
+    let Some(chart) = chart_root(path) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = ["Chart.yaml", "values.yaml"]
+        .iter()
+        .map(|name| chart.join(name))
+        .filter(|file| file.is_file())
+        .collect();
+    files.extend(helpers(chart));
+    files
+}
+
+/// Whether a file under a chart's `templates` only holds helpers or notes,
+/// and renders no manifest
+pub fn is_chart_helper(path: &Path) -> bool {
+    let name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default();
+    chart_root(path).is_some() && (name.ends_with(".tpl") || name == "NOTES.txt")
+}
+
+/// The `.tpl` files under a chart's `templates`, sorted
+fn helpers(chart: &Path) -> Vec<PathBuf> {
+    let pattern = chart.join("templates").join("**").join("*.tpl");
+    let mut files: Vec<PathBuf> = glob::glob(&pattern.to_string_lossy())
+        .map(|paths| paths.flatten().collect())
+        .unwrap_or_default();
+    files.sort();
+    files
+}
+
+fn read(path: &Path) -> Result<String, ConflowError> {
+    std::fs::read_to_string(path).map_err(|e| ConflowError::FileReadError {
+        path: path.to_path_buf(),
+        error: e.to_string(),
+    })
+}
+
+/// A values file; an empty one sets nothing
+fn read_values(path: &Path) -> Result<Value, ConflowError> {
+    let value: Value = serde_yaml::from_str(&read(path)?).map_err(|e| ConflowError::Yaml {
+        message: format!("{}: {}", path.display(), e),
+    })?;
+    Ok(match value {
+        Value::Null => Value::Object(Map::new()),
+        value => value,
+    })
+}
+
+/// Layer `overlay` over `base`, merging maps key by key
+fn merge(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// Set the value at a dotted key, making maps along the way
+fn set(values: &mut Value, key: &str, value: &Value) {
+    let mut target = values;
+    for field in key.split('.') {
+        if !target.is_object() {
+            *target = Value::Object(Map::new());
+        }
+        let Value::Object(map) = target else {
+            unreachable!("just made an object")
+        };
+        target = map.entry(field).or_insert(Value::Null);
+    }
+    *target = value.clone();
+}
+
+/// `.Values`, `.Release`, `.Chart`, `.Capabilities` and `.Template`
+fn helm_data(
+    chart: Option<&Path>,
+    path: &Path,
+    values: Value,
+    sample: &SampleValues,
+) -> Result<Value, ConflowError> {
+    // Chart.yaml keys are capitalized, as in Helm's chart metadata
+    let mut metadata = Map::new();
+    if let Some(chart) = chart {
+        if let Value::Object(fields) = read_values(&chart.join("Chart.yaml"))? {
+            for (key, value) in fields {
+                let key = match key.as_str() {
+                    "apiVersion" => "APIVersion".to_string(),
+                    _ => key[..1].to_uppercase() + &key[1..],
+                };
+                metadata.insert(key, value);
+            }
+        }
+    }
+    let chart_name = metadata
+        .get("Name")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .or_else(|| {
+            let dir = chart?.canonicalize().ok()?;
+            Some(dir.file_name()?.to_string_lossy().to_string())
+        })
+        .unwrap_or_default();
+    let relative = chart
+        .and_then(|chart| path.strip_prefix(chart).ok())
+        .unwrap_or(path);
+    let relative = relative.to_string_lossy().replace('\\', "/");
+
+    let kube = sample.kube_version.unwrap_or_else(KubeVersion::latest);
+    let kube_version = format!("v{}.{}.0", kube.major, kube.minor);
+    Ok(json!({
+        "Values": values,
+        "Release": {
+            "Name": sample.release.as_deref().unwrap_or(DEFAULT_RELEASE),
+            "Namespace": sample.namespace.as_deref().unwrap_or("default"),
+            "Service": "Helm",
+            "IsInstall": true,
+            "IsUpgrade": false,
+            "Revision": 1,
+        },
+        "Chart": metadata,
+        "Capabilities": {
+            "KubeVersion": {
+                "Version": kube_version,
+                "GitVersion": kube_version,
+                "Major": kube.major.to_string(),
+                "Minor": kube.minor.to_string(),
+            },
+            "APIVersions": API_VERSIONS,
+        },
+        "Template": {
+            "Name": format!("{}/{}", chart_name, relative),
+            "BasePath": format!("{}/templates", chart_name),
+        },
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_chart_template() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let chart = temp.path().join("web");
+        let write = |path: &str, content: &str| {
+            let path = chart.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        };
+        write("Chart.yaml", "apiVersion: v2\nname: web\nversion: 1.2.0\n");
+        write(
+            "values.yaml",
+            "replicas: 1\nimage: {repository: nginx, tag: \"1.25\"}\n",
+        );
+        write(
+            "templates/_helpers.tpl",
+            "{{- define \"web.fullname\" -}}\n{{ .Release.Name }}-{{ .Chart.Name }}\n{{- end }}\n",
+        );
+        let template = r#"apiVersion: apps/v1
+kind: Deployment
+metadata:
+  name: {{ include "web.fullname" . }}
+  namespace: {{ .Release.Namespace }}
+spec:
+  replicas: {{ .Values.replicas }}
+  template:
+    spec:
+      containers:
+        - image: "{{ .Values.image.repository }}:{{ .Values.image.tag }}"
+          {{- if .Values.debug }}
+          args: [--debug]
+          {{- end }}
+"#;
+        write("templates/deployment.yaml", template);
+        let path = chart.join("templates/deployment.yaml");
+
+        let overrides = temp.path().join("ci.yaml");
+        std::fs::write(&overrides, "image: {tag: \"1.26\"}\n").unwrap();
+        let mut values = SampleValues {
+            files: vec![overrides],
+            ..SampleValues::default()
+        };
+        values.set_str("debug", "true");
+        let rendered = render_template(&path, template, &values).unwrap();
+        let manifest: Value = serde_yaml::from_str(&rendered).unwrap();
+        assert_eq!(manifest["metadata"]["name"], "release-name-web");
+        assert_eq!(manifest["metadata"]["namespace"], "default");
+        assert_eq!(manifest["spec"]["replicas"], 1);
+        let container = &manifest["spec"]["template"]["spec"]["containers"][0];
+        assert_eq!(container["image"], "nginx:1.26");
+        assert_eq!(container["args"][0], "--debug");
+
+        assert_eq!(render_inputs(&path).len(), 3);
+        assert!(is_chart_helper(&chart.join("templates/_helpers.tpl")));
+
+        let required = "tag: {{ required \"image.digest is required\" .Values.image.digest }}";
+        let err = render_template(&path, required, &SampleValues::default()).unwrap_err();
+        assert!(err.to_string().contains("image.digest is required"));
+
+        // Plain Go templates get the values as `.`
+        let go = "port: {{ .port }}\n";
+        let mut values = SampleValues::default();
+        values.set_str("port", "8080");
+        let rendered = render_template(Path::new("app.yaml.tmpl"), go, &values).unwrap();
+        assert_eq!(rendered, "port: 8080\n");
+        assert_eq!(
+            render_template(Path::new("a.yaml"), "a: 1\n", &values).unwrap(),
+            "a: 1\n"
+        );
+    }
+}
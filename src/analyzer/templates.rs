@@ -0,0 +1,268 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Templated configuration
+//!
+//! Helm charts, Go templates and Jinja files hold `{{ ... }}` expressions and
+//! control tags, so they aren't the YAML they look like until rendered: they
+//! fail to parse, or parse into nonsense (`{{ .Values.name }}` is a flow
+//! mapping). [`detect_template`] recognises the dialect, and [`stub_render`]
+//! swaps expressions for a placeholder and drops control tags so the
+//! structure around them can still be analyzed.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Value substituted for template expressions by [`stub_render`]
+pub const PLACEHOLDER: &str = "TEMPLATED";
+
+/// Template syntax a configuration file is written in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TemplateDialect {
+    /// Go templates in a Helm chart
+    Helm,
+    /// Go `text/template`, e.g. gomplate or consul-template
+    GoTemplate,
+    /// Jinja, e.g. Ansible templates or Salt states
+    Jinja,
+}
+
+impl TemplateDialect {
+    /// Display name
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Helm => "Helm",
+            Self::GoTemplate => "Go template",
+            Self::Jinja => "Jinja",
+        }
+    }
+
+    /// How files of this dialect are usually rendered
+    pub fn render_command(&self) -> &'static str {
+        match self {
+            Self::Helm => "conflow render <chart>",
+            Self::GoTemplate => "conflow render <file> --values <values>",
+            Self::Jinja => "j2 <file> <values>",
+        }
+    }
+}
+
+impl std::fmt::Display for TemplateDialect {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Go template actions that render nothing themselves
+const GO_CONTROL: &[&str] = &[
+    "if", "else", "end", "range", "with", "define", "block", "break", "continue",
+];
+
+/// Jinja file extensions, as in `values.yaml.j2`
+const JINJA_EXTENSIONS: &[&str] = &["j2", "jinja", "jinja2"];
+
+/// Go template file extensions
+const GO_TEMPLATE_EXTENSIONS: &[&str] = &["gotmpl", "tmpl"];
+
+/// The template dialect `content` is written in, if any
+///
+/// GitHub Actions `${{ ... }}` expressions are not templates.
+pub fn detect_template(content: &str, path: &Path) -> Option<TemplateDialect> {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_lowercase)
+        .unwrap_or_default();
+    let jinja_tags = content.contains("{%") || content.contains("{#");
+    if JINJA_EXTENSIONS.contains(&extension.as_str()) || jinja_tags {
+        return Some(TemplateDialect::Jinja);
+    }
+
+    let expressions: Vec<&str> = tags(content)
+        .into_iter()
+        .filter(|tag| tag.open == "{{")
+        .map(|tag| tag.body)
+        .collect();
+    let go_extension = GO_TEMPLATE_EXTENSIONS.contains(&extension.as_str());
+    if expressions.is_empty() && !go_extension {
+        return None;
+    }
+
+    let helm_values = [".Values", ".Release", ".Chart", ".Capabilities", "include "];
+    let helm = expressions
+        .iter()
+        .any(|e| helm_values.iter().any(|v| e.contains(v)));
+    if chart_root(path).is_some() || helm {
+        return Some(TemplateDialect::Helm);
+    }
+    let go = go_extension
+        || expressions.iter().any(|e| {
+            e.starts_with(['.', '$']) || e.starts_with("/*") || is_go_control(e)
+        });
+    Some(if go {
+        TemplateDialect::GoTemplate
+    } else {
+        TemplateDialect::Jinja
+    })
+}
+
+/// The path without a template extension: `values.yaml` for `values.yaml.j2`
+pub fn untemplated_path(path: &Path) -> PathBuf {
+    let extension = path.extension().and_then(|e| e.to_str()).map(str::to_lowercase);
+    match extension {
+        Some(ext) if JINJA_EXTENSIONS.contains(&ext.as_str())
+            || GO_TEMPLATE_EXTENSIONS.contains(&ext.as_str()) =>
+        {
+            path.with_extension("")
+        }
+        _ => path.to_path_buf(),
+    }
+}
+
+/// `content` with expressions replaced by [`PLACEHOLDER`] and control tags
+/// and comments removed
+///
+/// Lines holding nothing but tags become blank, so line numbers still match
+/// the template. The result may still not parse, e.g. where a loop emits
+/// list items.
+pub fn stub_render(content: &str) -> String {
+    // Expressions alone on a line usually emit whole blocks, so a marker
+    // tells them apart from inline values until lines are looked at
+    const MARK: char = '\u{0}';
+
+    let mut stubbed = String::with_capacity(content.len());
+    let mut rest = content;
+    for tag in tags(content) {
+        let start = tag.start - (content.len() - rest.len());
+        stubbed.push_str(&rest[..start]);
+        let text = &rest[start..start + tag.len];
+        if tag.open == "{{" && !is_go_control(tag.body) && !tag.body.starts_with("/*") {
+            stubbed.push(MARK);
+        }
+        // Keep the lines a multi-line tag spans
+        stubbed.extend(text.chars().filter(|&c| c == '\n'));
+        rest = &rest[start + tag.len..];
+    }
+    stubbed.push_str(rest);
+
+    let mut lines = Vec::new();
+    for (stub, original) in stubbed.split('\n').zip(content.split('\n')) {
+        let tags_only = stub.trim().chars().all(|c| c == MARK) && !original.trim().is_empty();
+        lines.push(match tags_only {
+            true => String::new(),
+            false => stub.replace(MARK, PLACEHOLDER),
+        });
+    }
+    lines.join("\n")
+}
+
+/// A template tag in the source
+struct Tag<'a> {
+    start: usize,
+    len: usize,
+    open: &'static str,
+    /// Text between the delimiters, without whitespace trim markers
+    body: &'a str,
+}
+
+/// Template tags in `content`, in order
+fn tags(content: &str) -> Vec<Tag<'_>> {
+    let mut tags = Vec::new();
+    let mut from = 0;
+    while let Some(found) = content[from..].find('{') {
+        let start = from + found;
+        let rest = &content[start..];
+        let (open, close) = match rest.get(..2) {
+            Some("{{") => ("{{", "}}"),
+            Some("{%") => ("{%", "%}"),
+            Some("{#") => ("{#", "#}"),
+            _ => {
+                from = start + 1;
+                continue;
+            }
+        };
+        // `${{ ... }}` is a GitHub Actions expression
+        if open == "{{" && content[..start].ends_with('$') {
+            from = start + 2;
+            continue;
+        }
+        let Some(end) = rest[2..].find(close) else {
+            break;
+        };
+        let len = end + 4;
+        tags.push(Tag {
+            start,
+            len,
+            open,
+            body: rest[2..2 + end].trim_matches(|c: char| c == '-' || c.is_whitespace()),
+        });
+        from = start + len;
+    }
+    tags
+}
+
+fn is_go_control(body: &str) -> bool {
+    let word = body.split_whitespace().next().unwrap_or_default();
+    GO_CONTROL.contains(&word) || (body.starts_with('$') && body.contains(":="))
+}
+
+/// The Helm chart whose `templates` directory holds `path`
+pub fn chart_root(path: &Path) -> Option<&Path> {
+    path.ancestors()
+        .skip(1)
+        .take_while(|dir| !dir.as_os_str().is_empty())
+        .find(|dir| {
+            dir.file_name().is_some_and(|name| name == "templates")
+                && dir.parent().is_some_and(|chart| chart.join("Chart.yaml").is_file())
+        })
+        .and_then(Path::parent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_templates() {
+        let helm = r#"apiVersion: apps/v1
+kind: Deployment
+metadata:
+  name: {{ include "app.fullname" . }}
+  labels:
+    {{- include "app.labels" . | nindent 4 }}
+    tier: web
+spec:
+  {{- if not .Values.autoscaling.enabled }}
+  replicas: {{ .Values.replicaCount }}
+  {{- end }}
+  {{/* image
+       settings */}}
+  image: "{{ .Values.image.repository }}:{{ .Values.image.tag }}"
+"#;
+        let path = Path::new("deployment.yaml");
+        assert_eq!(detect_template(helm, path), Some(TemplateDialect::Helm));
+
+        let stub = stub_render(helm);
+        assert_eq!(stub.lines().count(), helm.lines().count());
+        let value: serde_yaml::Value = serde_yaml::from_str(&stub).unwrap();
+        assert_eq!(value["metadata"]["name"], PLACEHOLDER);
+        assert_eq!(value["metadata"]["labels"]["tier"], "web");
+        assert_eq!(value["spec"]["replicas"], PLACEHOLDER);
+        assert_eq!(value["spec"]["image"], "TEMPLATED:TEMPLATED");
+
+        let go = "port: {{ .port }}\n{{ range $i, $e := .items }}\n{{ end }}\n";
+        assert_eq!(detect_template(go, path), Some(TemplateDialect::GoTemplate));
+        let jinja = "host: {{ host }}\n{% if tls %}\ntls: true\n{% endif %}\n";
+        assert_eq!(detect_template(jinja, path), Some(TemplateDialect::Jinja));
+        assert_eq!(stub_render(jinja), "host: TEMPLATED\n\ntls: true\n\n");
+        let values = Path::new("values.yaml.j2");
+        assert_eq!(detect_template("a: 1\n", values), Some(TemplateDialect::Jinja));
+        assert_eq!(untemplated_path(values), Path::new("values.yaml"));
+
+        // Not templates
+        let workflow = "run: echo ${{ secrets.TOKEN }}\n";
+        assert_eq!(detect_template(workflow, path), None);
+        assert_eq!(detect_template("a: {b: 1}\n", path), None);
+    }
+}
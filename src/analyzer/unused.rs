@@ -0,0 +1,983 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Unused key detection
+//!
+//! Compares config files with the schemas that vet them. Regular CUE
+//! structs are open, so a misspelled or leftover key passes `cue vet`
+//! silently; this finds keys no schema declares, and schema fields no
+//! config sets. Schemas come from the CUE stages of a pipeline (each
+//! stage binds its `schemas` to its inputs) or are given explicitly as
+//! CUE files or JSON Schema.
+
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use super::duplicates::parse_roots;
+use super::{BlockLocation, Segment};
+use crate::errors::ConflowError;
+use crate::executors::resolve_inputs;
+use crate::pipeline::{stage_workdir, Pipeline, Tool};
+use crate::utils::yaml;
+
+/// Top-level keys read by tools rather than validated by the schema
+const TOOL_KEYS: &[&str] = &["$schema"];
+
+/// Config files read by conflow itself, whose keys its own schemas check
+const TOOL_FILES: &[&str] = &[".conflow.yaml", ".conflow.yml", ".rsr.yaml", ".rsr.yml"];
+
+/// Child key matching any key (`[string]: T` in CUE)
+const WILDCARD: &str = "*";
+
+/// Child key for list elements
+pub(super) const ELEMENT: &str = "[]";
+
+/// Keys declared by a schema (or set by configs), as a tree
+#[derive(Debug, Clone, Default)]
+struct Node {
+    children: BTreeMap<String, Node>,
+    /// Accepts keys beyond `children`
+    open: bool,
+    /// Definitions unified into this node, resolved after parsing
+    refs: Vec<String>,
+}
+
+impl Node {
+    fn child(&mut self, key: &str) -> &mut Node {
+        self.children.entry(key.to_string()).or_default()
+    }
+
+    fn merge(&mut self, other: &Node) {
+        self.open |= other.open;
+        self.refs.extend(other.refs.iter().cloned());
+        for (key, child) in &other.children {
+            self.child(key).merge(child);
+        }
+    }
+
+    /// Whether the schema says nothing about the keys below this node,
+    /// e.g. a scalar, `_` or a type imported from another package
+    fn accepts_any(&self) -> bool {
+        self.open || self.children.keys().all(|k| k == ELEMENT)
+    }
+
+    fn lookup(&self, key: &str) -> Option<&Node> {
+        self.children
+            .get(key)
+            .filter(|_| key != WILDCARD && key != ELEMENT)
+            .or_else(|| self.children.get(WILDCARD))
+    }
+
+    /// Keys a config sets, with list elements merged
+    fn from_value(value: &Value) -> Self {
+        let mut node = Node::default();
+        match value {
+            Value::Object(map) => {
+                for (key, value) in map {
+                    node.child(key).merge(&Node::from_value(value));
+                }
+            }
+            Value::Array(items) => {
+                for item in items {
+                    node.child(ELEMENT).merge(&Node::from_value(item));
+                }
+            }
+            _ => {}
+        }
+        node
+    }
+
+    /// Replace references with the definitions they name
+    ///
+    /// Recursive definitions and references to other packages can't be
+    /// expanded, so they accept any key.
+    fn resolve(&mut self, defs: &BTreeMap<String, Node>, stack: &mut Vec<String>) {
+        for name in std::mem::take(&mut self.refs) {
+            match defs.get(&name) {
+                Some(def) if !stack.contains(&name) => {
+                    let mut def = def.clone();
+                    stack.push(name);
+                    def.resolve(defs, stack);
+                    stack.pop();
+                    self.merge(&def);
+                }
+                _ => self.open = true,
+            }
+        }
+        for child in self.children.values_mut() {
+            child.resolve(defs, stack);
+        }
+    }
+}
+
+/// Render a field path as `spec.containers[].image`
+fn field_path(path: &[String]) -> String {
+    let mut out = String::new();
+    for key in path {
+        if key != ELEMENT && !out.is_empty() {
+            out.push('.');
+        }
+        out.push_str(key);
+    }
+    out
+}
+
+/// Fields declared by a schema file
+#[derive(Debug, Clone)]
+pub struct Schema {
+    pub file: PathBuf,
+    root: Node,
+}
+
+impl Schema {
+    /// Load a CUE file or JSON Schema
+    ///
+    /// `definition` names the CUE definition configs are vetted against
+    /// (`cue vet -d`); without it, configs unify with the top-level fields.
+    pub fn load(path: &Path, definition: Option<&str>) -> Result<Self, ConflowError> {
+        let content = std::fs::read_to_string(path).map_err(|e| ConflowError::FileReadError {
+            path: path.to_path_buf(),
+            error: e.to_string(),
+        })?;
+
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("cue") => Ok(Self::from_cue(path, &content, definition)),
+            Some("json") => {
+                let schema: Value = serde_json::from_str(&content)?;
+                Ok(Self::from_json_schema(path, &schema))
+            }
+            _ => Err(ConflowError::UnsupportedSchema {
+                path: path.to_path_buf(),
+            }),
+        }
+    }
+
+    /// Fields declared by CUE source
+    pub fn from_cue(file: &Path, content: &str, definition: Option<&str>) -> Self {
+        let mut parser = CueParser::new(content);
+        let mut top = Node::default();
+        parser.skip_preamble();
+        parser.fields(&mut top);
+        let defs = parser.defs;
+
+        // A schema of definitions only is meant to be used with `-d`
+        let only = (top.children.is_empty() && defs.len() == 1)
+            .then(|| defs.keys().next().cloned())
+            .flatten();
+        let mut root = match definition.map(str::to_string).or(only) {
+            Some(name) => {
+                let mut root = Node::default();
+                root.refs.push(name);
+                root
+            }
+            None => top,
+        };
+        root.resolve(&defs, &mut Vec::new());
+
+        Self {
+            file: file.to_path_buf(),
+            root,
+        }
+    }
+
+    /// Fields declared by a JSON Schema document
+    pub fn from_json_schema(file: &Path, schema: &Value) -> Self {
+        Self {
+            file: file.to_path_buf(),
+            root: json_schema_node(schema, schema, 0),
+        }
+    }
+
+    /// Declared field paths, e.g. `spec.containers[].image`
+    pub fn fields(&self) -> Vec<String> {
+        fn walk(node: &Node, path: &mut Vec<String>, out: &mut Vec<String>) {
+            for (key, child) in &node.children {
+                path.push(key.clone());
+                if key != ELEMENT {
+                    out.push(field_path(path));
+                }
+                walk(child, path, out);
+                path.pop();
+            }
+        }
+        let mut out = Vec::new();
+        walk(&self.root, &mut Vec::new(), &mut out);
+        out
+    }
+}
+
+fn json_schema_node(schema: &Value, root: &Value, depth: usize) -> Node {
+    let mut node = Node::default();
+    if depth > 32 {
+        node.open = true;
+        return node;
+    }
+
+    if let Some(reference) = schema["$ref"].as_str() {
+        match reference.strip_prefix('#').and_then(|p| root.pointer(p)) {
+            Some(target) => node.merge(&json_schema_node(target, root, depth + 1)),
+            None => node.open = true,
+        }
+    }
+    if let Some(properties) = schema["properties"].as_object() {
+        for (key, property) in properties {
+            node.child(key)
+                .merge(&json_schema_node(property, root, depth + 1));
+        }
+    }
+    if let Some(patterns) = schema["patternProperties"].as_object() {
+        for property in patterns.values() {
+            node.child(WILDCARD)
+                .merge(&json_schema_node(property, root, depth + 1));
+        }
+    }
+    match &schema["additionalProperties"] {
+        Value::Bool(true) => node.open = true,
+        additional @ Value::Object(_) => {
+            node.child(WILDCARD)
+                .merge(&json_schema_node(additional, root, depth + 1))
+        }
+        _ => {}
+    }
+    match &schema["items"] {
+        items @ Value::Object(_) => {
+            node.child(ELEMENT)
+                .merge(&json_schema_node(items, root, depth + 1))
+        }
+        Value::Array(items) => {
+            for item in items {
+                node.child(ELEMENT)
+                    .merge(&json_schema_node(item, root, depth + 1));
+            }
+        }
+        _ => {}
+    }
+    for keyword in ["allOf", "anyOf", "oneOf"] {
+        for branch in schema[keyword].as_array().into_iter().flatten() {
+            node.merge(&json_schema_node(branch, root, depth + 1));
+        }
+    }
+    for keyword in ["then", "else"] {
+        if schema[keyword].is_object() {
+            node.merge(&json_schema_node(&schema[keyword], root, depth + 1));
+        }
+    }
+    node
+}
+
+/// Just enough of a CUE parser to collect field labels and where their
+/// values are
+///
+/// Values are skipped except for the structs, lists and definition
+/// references inside them; conditional fields (`if`, `for`) count as
+/// declared.
+struct CueParser<'a> {
+    src: &'a str,
+    pos: usize,
+    defs: BTreeMap<String, Node>,
+    /// Labels of the fields being parsed
+    path: Vec<String>,
+    /// Span of each field's value, by label path
+    exprs: Vec<(Vec<String>, Range<usize>)>,
+}
+
+impl<'a> CueParser<'a> {
+    fn new(src: &'a str) -> Self {
+        Self {
+            src,
+            pos: 0,
+            defs: BTreeMap::new(),
+            path: Vec::new(),
+            exprs: Vec::new(),
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.src.as_bytes().get(self.pos).copied()
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.src[self.pos..]
+    }
+
+    /// Skip blanks and comments, and newlines too if `lines`
+    fn skip_space(&mut self, lines: bool) {
+        while let Some(c) = self.peek() {
+            match c {
+                b' ' | b'\t' | b'\r' => self.pos += 1,
+                b'\n' if lines => self.pos += 1,
+                b'/' if self.rest().starts_with("//") => {
+                    self.pos += self.rest().find('\n').unwrap_or(self.rest().len());
+                }
+                _ => break,
+            }
+        }
+    }
+
+    fn skip_line(&mut self) {
+        self.pos += self.rest().find('\n').unwrap_or(self.rest().len());
+    }
+
+    /// `package` and `import` clauses
+    fn skip_preamble(&mut self) {
+        loop {
+            self.skip_space(true);
+            if self.keyword("package") {
+                self.skip_line();
+            } else if self.keyword("import") {
+                self.skip_space(false);
+                if self.peek() == Some(b'(') {
+                    self.pos += self.rest().find(')').map_or(self.rest().len(), |i| i + 1);
+                } else {
+                    self.skip_line();
+                }
+            } else {
+                return;
+            }
+        }
+    }
+
+    /// Consume `word` if it is the next token
+    fn keyword(&mut self, word: &str) -> bool {
+        let rest = self.rest();
+        let followed = rest[word.len().min(rest.len())..]
+            .bytes()
+            .next()
+            .is_none_or(|c| !is_ident(c));
+        if rest.starts_with(word) && followed {
+            self.pos += word.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn identifier(&mut self) -> &'a str {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| is_ident(c) || c == b'.') {
+            self.pos += 1;
+        }
+        &self.src[start..self.pos]
+    }
+
+    fn skip_string(&mut self) {
+        let quote = &self.rest()[..1];
+        let triple = quote.repeat(3);
+        if self.rest().starts_with(&triple) {
+            self.pos += 3;
+            self.pos += self
+                .rest()
+                .find(&triple)
+                .map_or(self.rest().len(), |i| i + 3);
+            return;
+        }
+        self.pos += 1;
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            match c {
+                b'\\' => self.pos += 1,
+                b'\n' => return,
+                c if c == quote.as_bytes()[0] => return,
+                _ => {}
+            }
+        }
+        self.pos = self.pos.min(self.src.len());
+    }
+
+    fn string_label(&mut self) -> String {
+        let start = self.pos;
+        self.skip_string();
+        let raw = &self.src[start..self.pos];
+        serde_json::from_str(raw).unwrap_or_else(|_| raw.trim_matches('"').to_string())
+    }
+
+    /// Fields of a struct body, up to its closing brace
+    fn fields(&mut self, node: &mut Node) {
+        loop {
+            self.skip_space(true);
+            let start = self.pos;
+            match self.peek() {
+                None | Some(b'}') => return,
+                Some(b',') => self.pos += 1,
+                Some(b'.') if self.rest().starts_with("...") => {
+                    self.pos += 3;
+                    node.open = true;
+                    self.value(&mut Node::default(), false);
+                }
+                _ if self.keyword("if") || self.keyword("for") => {
+                    // The clause's condition, then its struct
+                    while !matches!(self.peek(), None | Some(b'{') | Some(b'}')) {
+                        if matches!(self.peek(), Some(b'"' | b'\'')) {
+                            self.skip_string();
+                        } else {
+                            self.pos += 1;
+                        }
+                    }
+                    if self.peek() == Some(b'{') {
+                        self.pos += 1;
+                        self.fields(node);
+                        self.pos += 1;
+                    }
+                }
+                _ if self.keyword("let") => self.value(&mut Node::default(), false),
+                _ => self.field(node),
+            }
+            // Step over what nothing parses, such as a stray `)` or `]`
+            if self.pos == start {
+                self.pos += 1;
+            }
+        }
+    }
+
+    /// A label; `None` if the next token isn't one
+    fn label(&mut self) -> Option<String> {
+        let label = match self.peek()? {
+            b'"' => self.string_label(),
+            b'[' | b'(' => {
+                // Pattern constraint or dynamic field
+                self.pos += 1;
+                self.value(&mut Node::default(), true);
+                if self.peek() == Some(b']') || self.peek() == Some(b')') {
+                    self.pos += 1;
+                }
+                WILDCARD.to_string()
+            }
+            c if is_ident(c) => {
+                let ident = self.identifier();
+                // `Alias=label`
+                if self.rest().starts_with('=') && !self.rest().starts_with("==") {
+                    self.pos += 1;
+                    return self.label();
+                }
+                ident.to_string()
+            }
+            _ => return None,
+        };
+        if matches!(self.peek(), Some(b'?' | b'!')) {
+            self.pos += 1;
+        }
+        Some(label)
+    }
+
+    /// Parse `label: value`, or an embedded expression
+    fn field(&mut self, node: &mut Node) {
+        let start = self.pos;
+        let label = self.label();
+        self.skip_space(false);
+
+        let Some(label) = label.filter(|_| self.peek() == Some(b':')) else {
+            // An embedding such as `#Base` or `close({...})`
+            self.pos = start;
+            self.value(node, false);
+            return;
+        };
+        self.pos += 1;
+        self.skip_space(true);
+
+        let mut value = Node::default();
+        // `a: b: c: 1` shorthand
+        let checkpoint = self.pos;
+        let nested = self.label().is_some() && {
+            self.skip_space(false);
+            self.peek() == Some(b':')
+        };
+        self.pos = checkpoint;
+        self.path.push(label.clone());
+        if nested {
+            self.field(&mut value);
+        } else {
+            self.value(&mut value, false);
+            self.exprs.push((self.path.clone(), checkpoint..self.pos));
+        }
+        self.path.pop();
+        self.declare(node, &label, value);
+    }
+
+    /// Record a field; definitions are kept apart and hidden fields dropped
+    fn declare(&mut self, node: &mut Node, label: &str, value: Node) {
+        if label.starts_with('#') {
+            self.defs
+                .entry(label.to_string())
+                .or_default()
+                .merge(&value);
+        } else if !label.starts_with('_') {
+            node.child(label).merge(&value);
+        }
+    }
+
+    /// An expression, collecting the structs, lists and definition
+    /// references it contains into `node`
+    fn value(&mut self, node: &mut Node, in_list: bool) {
+        let mut depth = 0usize;
+        let mut last = b':';
+        loop {
+            self.skip_space(false);
+            let Some(c) = self.peek() else { return };
+            match c {
+                b'\n' if depth == 0 && !in_list && !b"|&=(:+-*/<>~,".contains(&last) => return,
+                b'\n' => self.pos += 1,
+                b',' | b'}' | b']' | b')' if depth == 0 => return,
+                b'(' => depth += 1,
+                b')' => depth = depth.saturating_sub(1),
+                b'{' => {
+                    self.pos += 1;
+                    self.fields(node);
+                    self.pos = (self.pos + 1).min(self.src.len());
+                    last = b'}';
+                    continue;
+                }
+                b'[' => {
+                    self.pos += 1;
+                    self.path.push(ELEMENT.to_string());
+                    self.list(node.child(ELEMENT));
+                    self.path.pop();
+                    self.pos = (self.pos + 1).min(self.src.len());
+                    last = b']';
+                    continue;
+                }
+                b'"' | b'\'' => {
+                    self.skip_string();
+                    last = b'"';
+                    continue;
+                }
+                c if is_ident(c) => {
+                    let ident = self.identifier();
+                    if ident.contains('.') {
+                        // A type from another package
+                        node.open = true;
+                    } else if ident.len() > 1 && ident.starts_with('#') {
+                        node.refs.push(ident.to_string());
+                    }
+                    last = b'a';
+                    continue;
+                }
+                _ => {}
+            }
+            last = c;
+            self.pos += 1;
+        }
+    }
+
+    /// List elements, up to the closing bracket
+    fn list(&mut self, element: &mut Node) {
+        loop {
+            self.skip_space(true);
+            match self.peek() {
+                None | Some(b']') => return,
+                Some(b',') => self.pos += 1,
+                Some(b'.') if self.rest().starts_with("...") => self.pos += 3,
+                _ => {
+                    let before = self.pos;
+                    self.value(element, true);
+                    if self.pos == before {
+                        self.pos += 1;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The value expression of every field in CUE source, by label path
+///
+/// Definitions start their paths with the `#Name` label, and list elements
+/// with `[]`.
+pub(super) fn cue_expressions(content: &str) -> Vec<(Vec<String>, Range<usize>)> {
+    let mut parser = CueParser::new(content);
+    parser.skip_preamble();
+    parser.fields(&mut Node::default());
+    parser.exprs
+}
+
+fn is_ident(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, b'_' | b'#' | b'$')
+}
+
+/// Schemas and the config files they vet
+#[derive(Debug, Clone)]
+pub struct SchemaBinding {
+    pub schemas: Vec<Schema>,
+    pub configs: Vec<PathBuf>,
+}
+
+/// Bindings for each CUE stage of a pipeline with schemas
+///
+/// Inputs that don't exist yet (e.g. generated by an earlier stage) are
+/// skipped rather than reported.
+pub fn pipeline_bindings(
+    pipeline: &Pipeline,
+    working_dir: &Path,
+) -> Result<Vec<SchemaBinding>, ConflowError> {
+    let mut bindings = Vec::new();
+    for stage in &pipeline.stages {
+        let Tool::Cue { schemas, flags, .. } = &stage.tool else {
+            continue;
+        };
+        if schemas.is_empty() {
+            continue;
+        }
+
+        let dir = stage_workdir(stage, working_dir);
+        let definition = definition_flag(flags);
+        let schemas = schemas
+            .iter()
+            .map(|s| Schema::load(&dir.join(s), definition))
+            .collect::<Result<_, _>>()?;
+        let configs = resolve_inputs(stage, &dir).unwrap_or_default();
+
+        bindings.push(SchemaBinding { schemas, configs });
+    }
+    Ok(bindings)
+}
+
+/// The definition named by `-d`/`--schema` in CUE flags
+fn definition_flag(flags: &[String]) -> Option<&str> {
+    let mut flags = flags.iter().map(String::as_str);
+    while let Some(flag) = flags.next() {
+        if flag == "-d" || flag == "--schema" {
+            return flags.next();
+        }
+        if let Some(value) = flag
+            .strip_prefix("-d=")
+            .or_else(|| flag.strip_prefix("--schema="))
+        {
+            return Some(value);
+        }
+    }
+    None
+}
+
+/// A config key no schema declares
+#[derive(Debug, Clone)]
+pub struct UnusedKey {
+    pub location: BlockLocation,
+    /// A declared key with a similar name
+    pub suggestion: Option<String>,
+}
+
+/// A schema field no config sets
+#[derive(Debug, Clone)]
+pub struct UnsetField {
+    pub schema: PathBuf,
+    pub field: String,
+}
+
+/// Result of [`find_unused`]
+#[derive(Debug, Clone, Default)]
+pub struct UnusedReport {
+    pub unused_keys: Vec<UnusedKey>,
+    pub unset_fields: Vec<UnsetField>,
+    /// Config files that were checked
+    pub files: usize,
+}
+
+impl UnusedReport {
+    pub fn is_empty(&self) -> bool {
+        self.unused_keys.is_empty() && self.unset_fields.is_empty()
+    }
+}
+
+/// Find keys no schema declares and schema fields no config sets
+///
+/// A config vetted by several stages may use a key declared by any of
+/// their schemas. Only JSON and YAML configs are read.
+pub fn find_unused(bindings: &[SchemaBinding]) -> UnusedReport {
+    let mut declared: BTreeMap<&Path, Node> = BTreeMap::new();
+    let mut used: HashMap<&Path, Node> = HashMap::new();
+    let mut schemas: BTreeMap<&Path, &Schema> = BTreeMap::new();
+    let mut vets: HashMap<&Path, Vec<&Path>> = HashMap::new();
+
+    for binding in bindings {
+        for config in &binding.configs {
+            let name = config
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or_default();
+            if TOOL_FILES.contains(&name) {
+                continue;
+            }
+            let node = declared.entry(config).or_default();
+            for schema in &binding.schemas {
+                node.merge(&schema.root);
+                schemas.insert(&schema.file, schema);
+                vets.entry(&schema.file).or_default().push(config);
+            }
+        }
+    }
+
+    let mut report = UnusedReport::default();
+    for (config, schema) in &declared {
+        let Ok(content) = std::fs::read_to_string(config) else {
+            continue;
+        };
+        let roots = parse_roots(config, &content);
+        if roots.is_empty() {
+            continue;
+        }
+        report.files += 1;
+
+        let set = used.entry(config).or_default();
+        for (location, value) in roots {
+            set.merge(&Node::from_value(&value));
+            unknown_keys(
+                &value,
+                schema,
+                &location,
+                &mut Vec::new(),
+                &mut report.unused_keys,
+            );
+        }
+    }
+
+    for (file, schema) in schemas {
+        let mut set = Node::default();
+        let mut any = false;
+        for config in &vets[file] {
+            if let Some(node) = used.get(config) {
+                set.merge(node);
+                any = true;
+            }
+        }
+        if any {
+            unset_fields(
+                schema,
+                &schema.root,
+                &set,
+                &mut Vec::new(),
+                &mut report.unset_fields,
+            );
+        }
+    }
+
+    report
+}
+
+fn unknown_keys(
+    value: &Value,
+    schema: &Node,
+    location: &BlockLocation,
+    path: &mut Vec<Segment>,
+    out: &mut Vec<UnusedKey>,
+) {
+    match value {
+        Value::Object(_) if schema.accepts_any() => {}
+        Value::Object(map) => {
+            for (key, value) in map {
+                path.push(Segment::Key(key.clone()));
+                match schema.lookup(key) {
+                    Some(child) => unknown_keys(value, child, location, path, out),
+                    None if path.len() == 1 && TOOL_KEYS.contains(&key.as_str()) => {}
+                    None => {
+                        let candidates = schema
+                            .children
+                            .keys()
+                            .map(String::as_str)
+                            .filter(|k| *k != WILDCARD && *k != ELEMENT);
+                        out.push(UnusedKey {
+                            location: BlockLocation {
+                                path: path.clone(),
+                                ..location.clone()
+                            },
+                            suggestion: yaml::suggest(key, candidates).map(str::to_string),
+                        });
+                    }
+                }
+                path.pop();
+            }
+        }
+        Value::Array(items) => {
+            let Some(element) = schema.children.get(ELEMENT).filter(|_| !schema.open) else {
+                return;
+            };
+            for (i, item) in items.iter().enumerate() {
+                path.push(Segment::Index(i));
+                unknown_keys(item, element, location, path, out);
+                path.pop();
+            }
+        }
+        _ => {}
+    }
+}
+
+fn unset_fields(
+    schema: &Schema,
+    declared: &Node,
+    set: &Node,
+    path: &mut Vec<String>,
+    out: &mut Vec<UnsetField>,
+) {
+    for (key, child) in &declared.children {
+        if key == WILDCARD {
+            continue;
+        }
+        path.push(key.clone());
+        match set.children.get(key) {
+            Some(set) => unset_fields(schema, child, set, path, out),
+            // An always-empty list is a value, not a missing field
+            None if key == ELEMENT => {}
+            None => out.push(UnsetField {
+                schema: schema.file.clone(),
+                field: field_path(path),
+            }),
+        }
+        path.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const SCHEMA: &str = r#"package config
+
+import "strings"
+
+#Port: {
+	name?:         string
+	containerPort: int & >0
+}
+
+// Service settings
+service: {
+	name:      string & strings.MinRunes(1)
+	replicas?: int | *1
+	ports: [...#Port]
+	labels: [string]: string
+	if replicas > 1 {
+		strategy?: "rolling" | "recreate"
+	}
+	extra: {...}
+	_internal: true
+}
+owner: team: string
+"#;
+
+    fn schema() -> Schema {
+        Schema::from_cue(Path::new("schema.cue"), SCHEMA, None)
+    }
+
+    #[test]
+    fn test_cue_fields() {
+        let fields = schema().fields();
+        assert_eq!(
+            fields,
+            vec![
+                "owner",
+                "owner.team",
+                "service",
+                "service.extra",
+                "service.labels",
+                "service.labels.*",
+                "service.name",
+                "service.ports",
+                "service.ports[].containerPort",
+                "service.ports[].name",
+                "service.replicas",
+                "service.strategy",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cue_definitions() {
+        let source = "#Config: {\n\tname: string\n\tnested: #Nested\n}\n#Nested: {\n\tvalue: int\n\tself?: #Nested\n}\n";
+        let schema = Schema::from_cue(Path::new("s.cue"), source, Some("#Config"));
+        assert_eq!(
+            schema.fields(),
+            vec!["name", "nested", "nested.self", "nested.value"]
+        );
+
+        // A lone definition is used without `-d`
+        let source = "#Only: { a: int, b?: [...string] }\n";
+        let schema = Schema::from_cue(Path::new("s.cue"), source, None);
+        assert_eq!(schema.fields(), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_stray_closers() {
+        // Parsing goes on past what doesn't parse instead of spinning
+        for source in ["a: 1)\nb: int\n", "a: 1]\nb: int\n", ")\n]\nb: int\n"] {
+            let schema = Schema::from_cue(Path::new("s.cue"), source, None);
+            assert!(schema.fields().contains(&"b".to_string()), "{}", source);
+            assert!(!cue_expressions(source).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_json_schema_fields() {
+        let value = json!({
+            "type": "object",
+            "properties": {
+                "name": { "type": "string" },
+                "steps": { "type": "array", "items": { "$ref": "#/$defs/step" } }
+            },
+            "$defs": {
+                "step": { "properties": { "run": {}, "env": { "additionalProperties": true } } }
+            }
+        });
+        let schema = Schema::from_json_schema(Path::new("s.json"), &value);
+        assert_eq!(
+            schema.fields(),
+            vec!["name", "steps", "steps[].env", "steps[].run"]
+        );
+    }
+
+    #[test]
+    fn test_find_unused() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let config = temp.path().join("web.yaml");
+        std::fs::write(
+            &config,
+            "$schema: ./schema.json\nservice:\n  name: web\n  replica: 3\n  ports:\n    \
+             - containerPort: 80\n      protocol: TCP\n  labels: {app: web}\n  \
+             extra: {anything: true}\nowner: {team: web}\n",
+        )
+        .unwrap();
+
+        let binding = SchemaBinding {
+            schemas: vec![schema()],
+            configs: vec![config.clone(), temp.path().join("missing.yaml")],
+        };
+        let report = find_unused(&[binding]);
+        assert_eq!(report.files, 1);
+
+        let unused: Vec<String> = report
+            .unused_keys
+            .iter()
+            .map(|k| format!("{} {:?}", k.location, k.suggestion))
+            .collect();
+        let file = config.display();
+        assert_eq!(
+            unused,
+            vec![
+                format!("{}: service.ports[0].protocol None", file),
+                format!("{}: service.replica Some(\"replicas\")", file),
+            ]
+        );
+
+        let unset: Vec<&str> = report
+            .unset_fields
+            .iter()
+            .map(|f| f.field.as_str())
+            .collect();
+        assert_eq!(
+            unset,
+            vec![
+                "service.ports[].name",
+                "service.replicas",
+                "service.strategy"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_definition_flag() {
+        let flags = |f: &[&str]| f.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(definition_flag(&flags(&["-d", "#Config"])), Some("#Config"));
+        assert_eq!(definition_flag(&flags(&["--schema=#App"])), Some("#App"));
+        assert_eq!(definition_flag(&flags(&["--strict"])), None);
+    }
+}
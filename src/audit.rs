@@ -0,0 +1,542 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Audit log
+//!
+//! Every change conflow makes to a project's own files (auto-remediation,
+//! generated templates, `lint --fix`, `plugin --fix`, `yaml split` and
+//! `join`, `init`, `learn`, migrations, waivers) is
+//! appended to `.conflow/audit.jsonl`, one JSON line per file: when, who,
+//! which action and command, and BLAKE3 digests of the content before and
+//! after. Changes made by one conflow command share an action id, counting
+//! up from 1 per project. Each entry also carries a digest chained from the
+//! entry before it, so `conflow audit --verify` notices edited or removed
+//! lines.
+//!
+//! The content a change replaces is kept under `.conflow/audit/objects`,
+//! named by its digest, so `conflow undo` can put back the files of an
+//! action. Undoing is an action too, and can itself be undone.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::errors::ConflowError;
+use crate::rsr::diff::now_rfc3339;
+#[cfg(feature = "native")]
+use crate::utils::git;
+
+/// The audit log, relative to the project root
+pub const AUDIT_LOG: &str = ".conflow/audit.jsonl";
+
+/// Content replaced by changes, by digest, relative to the project root
+pub const OBJECTS_DIR: &str = ".conflow/audit/objects";
+
+/// Action of an undo, followed by the id undone
+const UNDO: &str = "undo #";
+
+/// Names who made changes, overriding CI and git identities
+pub const ACTOR_ENV: &str = "CONFLOW_ACTOR";
+
+/// Action ids of this process, by project root
+static ACTIONS: Mutex<Option<HashMap<PathBuf, u64>>> = Mutex::new(None);
+
+/// How a file changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Change {
+    Created,
+    Modified,
+    Deleted,
+}
+
+impl std::fmt::Display for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Created => "created",
+            Self::Modified => "modified",
+            Self::Deleted => "deleted",
+        })
+    }
+}
+
+/// One change to one file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// Action id, shared by the changes of one command
+    pub id: u64,
+    /// When, in RFC 3339
+    pub timestamp: String,
+    /// Who ran the command
+    pub actor: String,
+    /// What made the change, e.g. `remediate RSR-CONFIG-004` or `lint --fix`
+    pub action: String,
+    /// The command line
+    pub command: String,
+    /// The file, relative to the project root
+    pub path: PathBuf,
+    pub change: Change,
+    /// Digest of the content before the change
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub before: Option<String>,
+    /// Digest of the content after it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub after: Option<String>,
+    /// Digest of the previous entry's chain and this entry
+    #[serde(default)]
+    pub chain: String,
+}
+
+impl AuditEntry {
+    /// The chain digest following `previous`
+    fn chain_from(&self, previous: &str) -> String {
+        let mut entry = self.clone();
+        entry.chain = String::new();
+        let json = serde_json::to_string(&entry).unwrap_or_default();
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(previous.as_bytes());
+        hasher.update(json.as_bytes());
+        format!("blake3:{}", hasher.finalize().to_hex())
+    }
+}
+
+/// Write a project file, recording the change
+///
+/// `path` is relative to `root`, or absolute.
+pub fn write(
+    root: &Path,
+    path: &Path,
+    content: impl AsRef<[u8]>,
+    action: &str,
+) -> std::io::Result<()> {
+    let full = root.join(path);
+    let before = match std::fs::read(&full) {
+        Ok(previous) => Some(keep(root, &previous)?),
+        Err(_) => None,
+    };
+    std::fs::write(&full, content.as_ref())?;
+    let change = match before {
+        Some(_) => Change::Modified,
+        None => Change::Created,
+    };
+    let after = Some(digest(content.as_ref()));
+    record(root, path, action, change, before, after)
+}
+
+/// Remove a project file or directory, recording the change of each file
+///
+/// Symbolic links are removed, never followed.
+pub fn remove(root: &Path, path: &Path, action: &str) -> std::io::Result<()> {
+    let full = root.join(path);
+    let metadata = std::fs::symlink_metadata(&full)?;
+    if metadata.is_dir() {
+        let mut entries: Vec<PathBuf> = std::fs::read_dir(&full)?
+            .filter_map(|e| Some(e.ok()?.path()))
+            .collect();
+        entries.sort();
+        for entry in entries {
+            remove(root, &entry, action)?;
+        }
+        return std::fs::remove_dir(&full);
+    }
+    // A dangling link has no content to keep
+    let content = match std::fs::read(&full) {
+        Err(_) if metadata.is_symlink() => Vec::new(),
+        read => read?,
+    };
+    let before = keep(root, &content)?;
+    std::fs::remove_file(&full)?;
+    record(root, path, action, Change::Deleted, Some(before), None)
+}
+
+/// Keep content a change replaces, returning its digest
+fn keep(root: &Path, content: &[u8]) -> std::io::Result<String> {
+    let digest = digest(content);
+    let object = object_path(root, &digest);
+    if !object.exists() {
+        if let Some(parent) = object.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&object, content)?;
+    }
+    Ok(digest)
+}
+
+fn object_path(root: &Path, digest: &str) -> PathBuf {
+    let name = digest.split_once(':').map_or(digest, |(_, hex)| hex);
+    root.join(OBJECTS_DIR).join(name)
+}
+
+fn record(
+    root: &Path,
+    path: &Path,
+    action: &str,
+    change: Change,
+    before: Option<String>,
+    after: Option<String>,
+) -> std::io::Result<()> {
+    let log = root.join(AUDIT_LOG);
+    if let Some(parent) = log.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let entries = read_log(&log)?;
+    let mut entry = AuditEntry {
+        id: action_id(root, &entries),
+        timestamp: now_rfc3339(),
+        actor: actor(root),
+        action: action.to_string(),
+        command: command_line(),
+        path: path.strip_prefix(root).unwrap_or(path).to_path_buf(),
+        change,
+        before,
+        after,
+        chain: String::new(),
+    };
+    let previous = entries.last().map(|e| e.chain.as_str()).unwrap_or_default();
+    entry.chain = entry.chain_from(previous);
+
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&log)?;
+    let line = serde_json::to_string(&entry).map_err(std::io::Error::other)?;
+    writeln!(file, "{}", line)
+}
+
+/// The action id of this process's changes to the project in `root`
+fn action_id(root: &Path, entries: &[AuditEntry]) -> u64 {
+    let mut actions = ACTIONS.lock().unwrap_or_else(|e| e.into_inner());
+    let actions = actions.get_or_insert_with(HashMap::new);
+    let key = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+    *actions
+        .entry(key)
+        .or_insert_with(|| entries.iter().map(|e| e.id).max().unwrap_or(0) + 1)
+}
+
+/// Give the next changes to the project in `root` a new action id
+fn new_action(root: &Path) {
+    let mut actions = ACTIONS.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(actions) = actions.as_mut() {
+        actions.remove(&root.canonicalize().unwrap_or_else(|_| root.to_path_buf()));
+    }
+}
+
+/// The audit log of the project in `root`, oldest first
+pub fn entries(root: &Path) -> Result<Vec<AuditEntry>, ConflowError> {
+    let log = root.join(AUDIT_LOG);
+    read_log(&log).map_err(|e| ConflowError::FileReadError {
+        path: log,
+        error: e.to_string(),
+    })
+}
+
+fn read_log(log: &Path) -> std::io::Result<Vec<AuditEntry>> {
+    let content = match std::fs::read_to_string(log) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .enumerate()
+        .map(|(i, line)| {
+            serde_json::from_str(line).map_err(|e| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("line {}: {}", i + 1, e),
+                )
+            })
+        })
+        .collect()
+}
+
+/// The first entry whose chain digest doesn't follow from those before it,
+/// counting from 1, or `None` when the log is intact
+pub fn verify(entries: &[AuditEntry]) -> Option<usize> {
+    let mut previous = "";
+    for (i, entry) in entries.iter().enumerate() {
+        if entry.chain != entry.chain_from(previous) {
+            return Some(i + 1);
+        }
+        previous = &entry.chain;
+    }
+    None
+}
+
+/// Who is making changes: `CONFLOW_ACTOR`, the CI user, the git user or
+/// the login name
+pub fn actor(root: &Path) -> String {
+    let vars = [ACTOR_ENV, "GITHUB_ACTOR", "GITLAB_USER_LOGIN"];
+    if let Some(actor) = vars.iter().find_map(|v| std::env::var(v).ok()) {
+        return actor;
+    }
+    #[cfg(feature = "native")]
+    if let Ok(email) = git::user_email(root) {
+        return email;
+    }
+    #[cfg(not(feature = "native"))]
+    let _ = root;
+    ["USER", "USERNAME"]
+        .iter()
+        .find_map(|v| std::env::var(v).ok())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Files an undo put back
+#[derive(Debug, Clone, Serialize)]
+pub struct Undone {
+    /// The action undone
+    pub id: u64,
+    /// What it did to them
+    pub action: String,
+    /// Each file, with the change the undo made to it
+    pub files: Vec<(PathBuf, Change)>,
+}
+
+/// Put back the files an action changed, as they were before it
+///
+/// Without an id, the newest action not undone yet is undone. A file
+/// changed again since is only put back with `force`.
+pub fn undo(root: &Path, id: Option<u64>, force: bool) -> Result<Undone, ConflowError> {
+    let log = entries(root)?;
+    let undone: HashSet<u64> = log
+        .iter()
+        .filter_map(|e| e.action.strip_prefix(UNDO)?.parse().ok())
+        .collect();
+    let failed = |message: String| ConflowError::ExecutionFailed {
+        message,
+        help: Some("'conflow audit' lists the actions".into()),
+    };
+    let id = match id {
+        Some(id) if undone.contains(&id) => {
+            return Err(failed(format!("Action #{} was already undone", id)))
+        }
+        Some(id) => id,
+        None => log
+            .iter()
+            .rev()
+            .find(|e| !undone.contains(&e.id) && !e.action.starts_with(UNDO))
+            .map(|e| e.id)
+            .ok_or_else(|| failed(format!("Nothing to undo in {}", AUDIT_LOG)))?,
+    };
+    let changes: Vec<&AuditEntry> = log.iter().filter(|e| e.id == id).collect();
+    let Some(first) = changes.first() else {
+        return Err(failed(format!("No action #{} in {}", id, AUDIT_LOG)));
+    };
+    let action = first.action.clone();
+
+    // Each file goes back to before its first change; it must still be as
+    // the last change left it
+    let mut files: Vec<(PathBuf, Option<String>, Option<String>)> = Vec::new();
+    for change in changes {
+        match files.iter_mut().find(|(path, ..)| *path == change.path) {
+            Some(file) => file.2 = change.after.clone(),
+            None => files.push((
+                change.path.clone(),
+                change.before.clone(),
+                change.after.clone(),
+            )),
+        }
+    }
+    let changed: Vec<String> = files
+        .iter()
+        .filter(|(path, _, after)| {
+            std::fs::read(root.join(path)).ok().map(|c| digest(&c)) != *after
+        })
+        .map(|(path, ..)| path.display().to_string())
+        .collect();
+    if !changed.is_empty() && !force {
+        return Err(ConflowError::UndoConflict { id, files: changed });
+    }
+    let missing: Vec<String> = files
+        .iter()
+        .filter_map(|(_, before, _)| before.as_ref())
+        .filter(|digest| !object_path(root, digest).is_file())
+        .cloned()
+        .collect();
+    if !missing.is_empty() {
+        return Err(failed(format!(
+            "The content action #{} replaced is gone from {}: {}",
+            id,
+            OBJECTS_DIR,
+            missing.join(", ")
+        )));
+    }
+
+    new_action(root);
+    let label = format!("{}{}", UNDO, id);
+    let mut restored = Vec::new();
+    for (path, before, _) in files.into_iter().rev() {
+        let full = root.join(&path);
+        match before {
+            Some(before) => {
+                let content = std::fs::read(object_path(root, &before))?;
+                if let Some(parent) = full.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                let change = match full.exists() {
+                    true => Change::Modified,
+                    false => Change::Created,
+                };
+                write(root, &path, content, &label)?;
+                restored.push((path, change));
+            }
+            None if full.exists() => {
+                remove(root, &path, &label)?;
+                restored.push((path, Change::Deleted));
+            }
+            None => {}
+        }
+    }
+    new_action(root);
+    restored.reverse();
+    Ok(Undone {
+        id,
+        action,
+        files: restored,
+    })
+}
+
+/// BLAKE3 digest of content, as written in the log
+pub fn digest(content: &[u8]) -> String {
+    format!("blake3:{}", blake3::hash(content).to_hex())
+}
+
+fn command_line() -> String {
+    let args: Vec<String> = std::env::args().collect();
+    let mut args = args.iter().map(String::as_str);
+    let program = args.next().map(|p| {
+        Path::new(p)
+            .file_name()
+            .map_or(p.to_string(), |n| n.to_string_lossy().to_string())
+    });
+    program
+        .into_iter()
+        .chain(args.map(String::from))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_audit_log() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let root = temp.path();
+
+        write(root, Path::new("app.yaml"), "replicas: 1\n", "init").unwrap();
+        write(root, &root.join("app.yaml"), "replicas: 2\n", "lint --fix").unwrap();
+        remove(root, Path::new("app.yaml"), "remediate RSR-X").unwrap();
+
+        let log = entries(root).unwrap();
+        let changes: Vec<_> = log.iter().map(|e| (e.path.clone(), e.change)).collect();
+        assert_eq!(
+            changes,
+            [
+                (PathBuf::from("app.yaml"), Change::Created),
+                (PathBuf::from("app.yaml"), Change::Modified),
+                (PathBuf::from("app.yaml"), Change::Deleted),
+            ]
+        );
+        // One process makes one action
+        assert!(log.iter().all(|e| e.id == log[0].id));
+        assert_eq!(log[1].before, log[0].after);
+        assert_eq!(
+            log[1].after.as_deref(),
+            Some(digest(b"replicas: 2\n").as_str())
+        );
+        assert_eq!(verify(&log), None);
+
+        // Editing an entry breaks the chain from there on
+        let mut tampered = log.clone();
+        tampered[1].actor = "someone-else".into();
+        assert_eq!(verify(&tampered), Some(2));
+        assert_eq!(verify(&log[1..]), Some(1));
+    }
+
+    #[test]
+    fn test_undo() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let root = temp.path();
+        std::fs::create_dir_all(root.join("k8s")).unwrap();
+        std::fs::write(root.join("k8s/app.yaml"), "image: nginx\n").unwrap();
+
+        // One action edits a file twice and creates another
+        write(
+            root,
+            Path::new("k8s/app.yaml"),
+            "image: nginx:1\n",
+            "lint --fix",
+        )
+        .unwrap();
+        write(
+            root,
+            Path::new("k8s/app.yaml"),
+            "image: nginx:2\n",
+            "lint --fix",
+        )
+        .unwrap();
+        write(
+            root,
+            Path::new("k8s/new.yaml"),
+            "kind: Service\n",
+            "lint --fix",
+        )
+        .unwrap();
+        let id = entries(root).unwrap()[0].id;
+
+        // A file changed since is only put back with force
+        std::fs::write(root.join("k8s/new.yaml"), "edited\n").unwrap();
+        assert!(matches!(
+            undo(root, None, false),
+            Err(ConflowError::UndoConflict { .. })
+        ));
+        let undone = undo(root, None, true).unwrap();
+        assert_eq!(undone.id, id);
+        assert_eq!(
+            undone.files,
+            [
+                (PathBuf::from("k8s/app.yaml"), Change::Modified),
+                (PathBuf::from("k8s/new.yaml"), Change::Deleted),
+            ]
+        );
+        assert_eq!(
+            std::fs::read_to_string(root.join("k8s/app.yaml")).unwrap(),
+            "image: nginx\n"
+        );
+        assert!(!root.join("k8s/new.yaml").exists());
+
+        // The undo is an action of its own, and can be undone in turn
+        let log = entries(root).unwrap();
+        let last = log.last().unwrap();
+        assert_eq!((last.id, last.action.as_str()), (id + 1, "undo #1"));
+        assert!(undo(root, Some(id), false).is_err());
+        assert_eq!(undo(root, Some(id + 1), false).unwrap().files.len(), 2);
+        assert_eq!(
+            std::fs::read_to_string(root.join("k8s/app.yaml")).unwrap(),
+            "image: nginx:2\n"
+        );
+        assert_eq!(verify(&entries(root).unwrap()), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_remove_keeps_link_targets() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let (root, outside) = (temp.path().join("project"), temp.path().join("outside"));
+        std::fs::create_dir_all(root.join("old")).unwrap();
+        std::fs::create_dir_all(&outside).unwrap();
+        std::fs::write(outside.join("keep.txt"), "kept\n").unwrap();
+        std::os::unix::fs::symlink(&outside, root.join("old/link")).unwrap();
+        std::os::unix::fs::symlink(root.join("old"), root.join("old/loop")).unwrap();
+
+        remove(&root, Path::new("old"), "rsr fix").unwrap();
+        assert!(!root.join("old").exists());
+        assert!(outside.join("keep.txt").is_file());
+    }
+}
@@ -0,0 +1,657 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Air-gapped bundles
+//!
+//! `conflow bundle create` fetches everything a project needs from the
+//! network (pinned tool binaries, `.rsr.yaml` profiles and git template
+//! repositories) and packs it into one gzipped tarball. `conflow bundle
+//! load` unpacks it into the toolchain, profile and template caches of a
+//! machine without network access, where `--offline` runs find them.
+//!
+//! `conflow bundle export` packs one pipeline run instead: the pipeline
+//! file, every file its stages read, its templates and the versions of the
+//! tools it runs, so the run can be repeated elsewhere with `conflow run
+//! --from-bundle` or archived for audit.
+//!
+//! ```text
+//! bundle.json
+//! toolchain/<tool>/<version>/<binary>
+//! profiles/<org>/<name>/<version>/...
+//! templates/<repo>/<version>/...
+//! project/...
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::File;
+use std::path::{Component, Path, PathBuf};
+
+use crate::analyzer::render_inputs;
+use crate::cache::cue_import_closure;
+use crate::errors::ConflowError;
+use crate::executors::{create_executors, resolve_inputs};
+use crate::pipeline::{stage_workdir, Pipeline, Tool};
+use crate::rsr::config::RsrConfig;
+use crate::rsr::profiles::{self, ProfileStore};
+use crate::rsr::templates::{self, TEMPLATE_DIR_ENV};
+use crate::toolchain::{self, Toolchain};
+use crate::utils::ignore::IGNORE_FILE;
+use crate::utils::walk::Walker;
+
+/// Name of the manifest at the root of a bundle
+pub const MANIFEST: &str = "bundle.json";
+
+/// Current version of the bundle layout
+pub const BUNDLE_VERSION: u32 = 1;
+
+const TOOLCHAIN_DIR: &str = "toolchain";
+const PROFILES_DIR: &str = "profiles";
+const TEMPLATES_DIR: &str = "templates";
+const PROJECT_DIR: &str = "project";
+
+/// Project files a run reads besides its pipeline and stage files
+const PROJECT_FILES: &[&str] = &[".rsr.yaml", IGNORE_FILE];
+
+/// What a bundle holds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleManifest {
+    pub format_version: u32,
+    /// When the bundle was made
+    pub created: String,
+    /// conflow version that made it
+    pub conflow_version: String,
+    /// Platform the tool binaries run on, e.g. `linux-x86_64`
+    pub platform: String,
+    /// Tool binaries, as `tool@version`
+    #[serde(default)]
+    pub tools: Vec<String>,
+    /// Profiles, as `name@version`
+    #[serde(default)]
+    pub profiles: Vec<String>,
+    /// Git template repositories
+    #[serde(default)]
+    pub templates: Vec<String>,
+    /// Pipeline file of a run bundle, relative to the project
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pipeline: Option<PathBuf>,
+    /// Project files of a run bundle
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<PathBuf>,
+    /// Versions of the tools a run bundle's stages use
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub versions: BTreeMap<String, String>,
+}
+
+impl BundleManifest {
+    fn new() -> Self {
+        Self {
+            format_version: BUNDLE_VERSION,
+            created: crate::report::timestamp(),
+            conflow_version: crate::VERSION.to_string(),
+            platform: toolchain::platform(),
+            tools: vec![],
+            profiles: vec![],
+            templates: vec![],
+            pipeline: None,
+            files: vec![],
+            versions: BTreeMap::new(),
+        }
+    }
+
+    /// Whether the tool binaries run on this machine
+    pub fn runs_here(&self) -> bool {
+        self.tools.is_empty() || self.platform == toolchain::platform()
+    }
+
+    /// Tools whose local version differs from the bundled run's, as
+    /// `(tool, bundled, local)`; a missing tool has local version "missing"
+    pub fn version_drift(&self, local: &BTreeMap<String, String>) -> Vec<(String, String, String)> {
+        self.versions
+            .iter()
+            .filter_map(|(tool, bundled)| {
+                let here = local.get(tool).map_or("missing", String::as_str);
+                (here != bundled).then(|| (tool.clone(), bundled.clone(), here.to_string()))
+            })
+            .collect()
+    }
+}
+
+/// The local caches bundles are made from and unpacked into
+#[derive(Debug, Clone)]
+pub struct Caches {
+    pub toolchain: PathBuf,
+    pub profiles: PathBuf,
+    pub templates: PathBuf,
+}
+
+impl Caches {
+    /// This user's caches
+    pub fn open() -> Result<Self, ConflowError> {
+        let no_home = |env: &str| ConflowError::Io {
+            message: format!("Could not find a home directory; set {}", env),
+        };
+        Ok(Self {
+            toolchain: toolchain::default_root().ok_or_else(|| no_home("CONFLOW_TOOLCHAIN_DIR"))?,
+            profiles: profiles::default_root().ok_or_else(|| no_home(profiles::PROFILE_DIR_ENV))?,
+            templates: templates::default_cache().ok_or_else(|| no_home(TEMPLATE_DIR_ENV))?,
+        })
+    }
+
+    fn root(&self, kind: &str) -> &Path {
+        match kind {
+            TOOLCHAIN_DIR => &self.toolchain,
+            PROFILES_DIR => &self.profiles,
+            _ => &self.templates,
+        }
+    }
+}
+
+/// Fetch what the project in `root` needs and pack it into `output`
+///
+/// Tools already installed and profiles and repositories already cached
+/// are packed as they are.
+pub async fn create(
+    root: &Path,
+    pipeline: Option<&Pipeline>,
+    output: &Path,
+    caches: &Caches,
+) -> Result<BundleManifest, ConflowError> {
+    let mut archive = new_archive(output)?;
+    let mut manifest = BundleManifest::new();
+
+    // Pinned tools
+    let mut pins: Vec<_> = pipeline.iter().flat_map(|p| &p.toolchain).collect();
+    pins.sort_by(|a, b| a.0.cmp(b.0));
+    if !pins.is_empty() {
+        let toolchain = Toolchain::new(&caches.toolchain);
+        for (tool, pin) in pins {
+            let binary = match toolchain.installed(tool, &pin.version) {
+                Some(binary) => binary,
+                None => {
+                    let expected = pin.checksums.get(&toolchain::platform());
+                    let expected = expected.map(String::as_str);
+                    toolchain.install(tool, &pin.version, expected).await?.path
+                }
+            };
+            let dir = binary.parent().unwrap_or(toolchain.root());
+            let version = dir.file_name().unwrap_or_default().to_string_lossy();
+            let name = Path::new(TOOLCHAIN_DIR).join(tool).join(&*version);
+            append_dir(&mut archive, &name, dir)?;
+            manifest.tools.push(format!("{}@{}", tool, version));
+        }
+    }
+
+    // Profiles
+    let config = RsrConfig::load_from_project(root)?;
+    let store = ProfileStore::new(&caches.profiles);
+    for source in &config.profiles {
+        store.fetch(source, false).await?;
+        let dir = store.dir(&source.profile);
+        let name = Path::new(PROFILES_DIR).join(relative(&dir, store.root()));
+        append_dir(&mut archive, &name, &dir)?;
+        manifest.profiles.push(source.profile.to_string());
+    }
+
+    let mut repos: Vec<_> = pipeline.iter().flat_map(|p| p.templates.clone()).collect();
+    repos.extend(config.templates);
+    append_templates(&mut archive, &mut manifest, root, &repos, caches)?;
+
+    finish(archive, &manifest)?;
+    Ok(manifest)
+}
+
+/// Pack a run of the pipeline in `pipeline_path` into `output`
+///
+/// Input files that don't exist yet, written by earlier stages, are left
+/// out; so are files outside `root`.
+pub async fn export(
+    root: &Path,
+    pipeline_path: &Path,
+    output: &Path,
+    caches: &Caches,
+) -> Result<BundleManifest, ConflowError> {
+    let pipeline = Pipeline::from_file(&root.join(pipeline_path))?;
+    let mut archive = new_archive(output)?;
+    let mut manifest = BundleManifest::new();
+    manifest.pipeline = Some(relative(pipeline_path, root));
+    manifest.versions = tool_versions(&pipeline).await;
+
+    let mut files = run_files(root, &pipeline);
+    files.insert(relative(pipeline_path, root));
+    for file in files {
+        let path = root.join(&file);
+        archive
+            .append_path_with_name(&path, Path::new(PROJECT_DIR).join(&file))
+            .map_err(|e| ConflowError::FileReadError {
+                path,
+                error: e.to_string(),
+            })?;
+        manifest.files.push(file);
+    }
+    let config = RsrConfig::load_from_project(root)?;
+    let mut repos = pipeline.templates.clone();
+    repos.extend(config.templates);
+    append_templates(&mut archive, &mut manifest, root, &repos, caches)?;
+
+    finish(archive, &manifest)?;
+    Ok(manifest)
+}
+
+/// Versions of the tools the pipeline's stages use, by tool name
+pub async fn tool_versions(pipeline: &Pipeline) -> BTreeMap<String, String> {
+    let executors = create_executors(pipeline);
+    let tools: BTreeSet<&str> = pipeline.stages.iter().map(|s| s.tool_name()).collect();
+    let mut versions = BTreeMap::new();
+    for tool in tools {
+        if let Some(executor) = executors.get(tool) {
+            if let Ok(version) = executor.version().await {
+                versions.insert(tool.to_string(), version);
+            }
+        }
+    }
+    versions
+}
+
+/// The files under `root` a run of the pipeline reads, relative to it
+fn run_files(root: &Path, pipeline: &Pipeline) -> BTreeSet<PathBuf> {
+    let mut read: Vec<PathBuf> = PROJECT_FILES.iter().map(|f| root.join(f)).collect();
+    let mut dirs: Vec<PathBuf> = vec![root.join(templates::PROJECT_TEMPLATE_DIR)];
+    for stage in &pipeline.stages {
+        let dir = stage_workdir(stage, root);
+        read.extend(stage.env_file.iter().map(|f| root.join(f)));
+        let inputs = resolve_inputs(stage, &dir).unwrap_or_default();
+        read.extend(inputs.iter().cloned());
+        if let Some(ref contract) = stage.contract {
+            if contract.is_file() {
+                read.push(dir.join(contract.schema()));
+            }
+        }
+        match &stage.tool {
+            Tool::Cue { schemas, .. } => {
+                let mut loaded: Vec<PathBuf> = schemas.iter().map(|s| dir.join(s)).collect();
+                loaded.extend(inputs.iter().cloned());
+                read.extend(cue_import_closure(&loaded));
+            }
+            Tool::Nickel { file, .. } => read.extend(file.iter().map(|f| dir.join(f))),
+            Tool::Helm { chart, values, .. } => {
+                read.extend(values.iter().map(|v| dir.join(v)));
+                dirs.push(dir.join(chart));
+            }
+            Tool::Terraform { dir: tf, .. } => dirs.extend(tf.iter().map(|d| dir.join(d))),
+            Tool::Cost { pricing, .. } => read.extend(pricing.iter().map(|p| dir.join(p))),
+            Tool::Render { values, .. } => {
+                read.extend(values.iter().map(|v| dir.join(v)));
+                read.extend(inputs.iter().flat_map(|f| render_inputs(f)));
+            }
+            _ => {}
+        }
+    }
+    dirs.extend(
+        pipeline
+            .templates
+            .iter()
+            .filter(|repo| repo.git.is_none())
+            .filter_map(|repo| Some(root.join(repo.path.as_ref()?))),
+    );
+    for dir in dirs.iter().filter(|d| d.is_dir()) {
+        read.extend(Walker::default().files(dir).unwrap_or_default());
+    }
+
+    read.into_iter()
+        .filter(|f| f.is_file())
+        .filter_map(|f| Some(f.strip_prefix(root).ok()?.to_path_buf()))
+        .filter(|f| f.components().all(|c| matches!(c, Component::Normal(_))))
+        .collect()
+}
+
+/// Unpack a bundle into this machine's toolchain, profile and template caches
+pub fn load(bundle: &Path, caches: &Caches) -> Result<BundleManifest, ConflowError> {
+    unpack(bundle, caches, None)
+}
+
+/// Unpack a run bundle's project into `dir`, and its templates into the
+/// template cache
+pub fn unpack_run(
+    bundle: &Path,
+    dir: &Path,
+    caches: &Caches,
+) -> Result<BundleManifest, ConflowError> {
+    let manifest = unpack(bundle, caches, Some(dir))?;
+    if manifest.pipeline.is_none() {
+        return Err(ConflowError::Io {
+            message: format!(
+                "{} holds no pipeline run; load it with 'conflow bundle load', or make one with 'conflow bundle export'",
+                bundle.display()
+            ),
+        });
+    }
+    Ok(manifest)
+}
+
+fn unpack(
+    bundle: &Path,
+    caches: &Caches,
+    project: Option<&Path>,
+) -> Result<BundleManifest, ConflowError> {
+    let invalid = |message: String| ConflowError::Io {
+        message: format!("Invalid bundle {}: {}", bundle.display(), message),
+    };
+    let file = File::open(bundle).map_err(|e| ConflowError::FileReadError {
+        path: bundle.to_path_buf(),
+        error: e.to_string(),
+    })?;
+    let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(file));
+
+    let mut manifest: Option<BundleManifest> = None;
+    for entry in archive.entries().map_err(|e| invalid(e.to_string()))? {
+        let mut entry = entry.map_err(|e| invalid(e.to_string()))?;
+        let path = entry
+            .path()
+            .map_err(|e| invalid(e.to_string()))?
+            .into_owned();
+        if path == Path::new(MANIFEST) {
+            let parsed = serde_json::from_reader(&mut entry).map_err(|e| invalid(e.to_string()))?;
+            manifest = Some(parsed);
+            continue;
+        }
+        if path
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_)))
+        {
+            return Err(invalid(format!("unsafe path {}", path.display())));
+        }
+
+        let kinds = [TOOLCHAIN_DIR, PROFILES_DIR, TEMPLATES_DIR, PROJECT_DIR];
+        let Some(kind) = kinds.into_iter().find(|kind| path.starts_with(kind)) else {
+            continue;
+        };
+        let root = match (kind, project) {
+            (PROJECT_DIR, Some(project)) => project,
+            (PROJECT_DIR, None) => continue,
+            _ => caches.root(kind),
+        };
+        // Only files and directories: a link could point the entries after
+        // it anywhere on the machine
+        let entry_type = entry.header().entry_type();
+        if !entry_type.is_file() && !entry_type.is_dir() {
+            return Err(invalid(format!(
+                "{} is a {:?}; bundles may only hold files and directories",
+                path.display(),
+                entry_type
+            )));
+        }
+
+        let dest = root.join(path.strip_prefix(kind).unwrap_or(&path));
+        let parent = match entry_type.is_dir() {
+            true => dest.as_path(),
+            false => dest.parent().unwrap_or(root),
+        };
+        std::fs::create_dir_all(root)?;
+        std::fs::create_dir_all(parent)?;
+        // Directories already on disk may be links themselves
+        if !parent.canonicalize()?.starts_with(root.canonicalize()?) {
+            return Err(invalid(format!("{} leaves {}", path.display(), root.display())));
+        }
+        if entry_type.is_file() {
+            // Keep executable bits for tools, but never setuid or
+            // group/world-writable modes
+            entry.set_mask(0o022);
+            entry.unpack(&dest).map_err(|e| invalid(e.to_string()))?;
+        }
+    }
+
+    let manifest = manifest.ok_or_else(|| invalid(format!("no {}", MANIFEST)))?;
+    if manifest.format_version > BUNDLE_VERSION {
+        return Err(invalid(format!(
+            "format version {} is newer than this conflow supports ({})",
+            manifest.format_version, BUNDLE_VERSION
+        )));
+    }
+    Ok(manifest)
+}
+
+fn new_archive(
+    output: &Path,
+) -> Result<tar::Builder<flate2::write::GzEncoder<File>>, ConflowError> {
+    let file = File::create(output).map_err(|e| ConflowError::FileWriteError {
+        path: output.to_path_buf(),
+        error: e.to_string(),
+    })?;
+    let encoder = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+    Ok(tar::Builder::new(encoder))
+}
+
+/// Write the manifest and close the archive
+fn finish(
+    mut archive: tar::Builder<flate2::write::GzEncoder<File>>,
+    manifest: &BundleManifest,
+) -> Result<(), ConflowError> {
+    let json = serde_json::to_vec_pretty(manifest)?;
+    let mut header = tar::Header::new_gnu();
+    header.set_size(json.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    archive.append_data(&mut header, MANIFEST, json.as_slice())?;
+    archive.into_inner()?.finish()?;
+    Ok(())
+}
+
+/// Fetch git template repositories and pack them
+fn append_templates<W: std::io::Write>(
+    archive: &mut tar::Builder<W>,
+    manifest: &mut BundleManifest,
+    root: &Path,
+    repos: &[templates::TemplateRepo],
+    caches: &Caches,
+) -> Result<(), ConflowError> {
+    for repo in repos {
+        let Some(dir) = repo.cache_dir(&caches.templates) else {
+            continue;
+        };
+        repo.fetch(root, &caches.templates, false)?;
+        let name = Path::new(TEMPLATES_DIR).join(relative(&dir, &caches.templates));
+        append_dir(archive, &name, &dir)?;
+        manifest.templates.push(repo.to_string());
+    }
+    Ok(())
+}
+
+fn relative(path: &Path, root: &Path) -> PathBuf {
+    path.strip_prefix(root).unwrap_or(path).to_path_buf()
+}
+
+fn append_dir<W: std::io::Write>(
+    archive: &mut tar::Builder<W>,
+    name: &Path,
+    dir: &Path,
+) -> Result<(), ConflowError> {
+    archive
+        .append_dir_all(name, dir)
+        .map_err(|e| ConflowError::FileReadError {
+            path: dir.to_path_buf(),
+            error: e.to_string(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_bundle_round_trip() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let root = temp.path();
+
+        // A cached template repository, and a project that uses it
+        let caches = Caches {
+            toolchain: root.join("toolchain"),
+            profiles: root.join("profiles"),
+            templates: root.join("templates"),
+        };
+        let repo = crate::rsr::templates::TemplateRepo {
+            git: Some("https://example.com/templates.git".into()),
+            version: Some("1.0".into()),
+            path: None,
+        };
+        let dir = repo.cache_dir(&caches.templates).unwrap();
+        std::fs::create_dir_all(dir.join("service")).unwrap();
+        std::fs::write(dir.join("service/template.yaml"), "name: service\n").unwrap();
+        let pipeline = Pipeline::from_yaml(
+            r#"
+name: app
+templates: [{ git: "https://example.com/templates.git", version: "1.0" }]
+stages:
+  - name: vet
+    tool: { type: cue, command: vet, schemas: [schema.cue] }
+    input: "*.yaml"
+"#,
+        )
+        .unwrap();
+
+        let project = root.join("project");
+        std::fs::create_dir_all(&project).unwrap();
+        let output = root.join("bundle.tar.gz");
+        let created = create(&project, Some(&pipeline), &output, &caches)
+            .await
+            .unwrap();
+        assert_eq!(created.templates, ["https://example.com/templates.git@1.0"]);
+        assert!(created.tools.is_empty() && created.runs_here());
+
+        std::fs::remove_dir_all(&caches.templates).unwrap();
+        let loaded = load(&output, &caches).unwrap();
+        assert_eq!(loaded.templates, created.templates);
+        assert!(dir.join("service/template.yaml").is_file());
+    }
+
+    #[tokio::test]
+    async fn test_run_bundle_round_trip() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let root = temp.path().join("project");
+        let caches = Caches {
+            toolchain: temp.path().join("toolchain"),
+            profiles: temp.path().join("profiles"),
+            templates: temp.path().join("templates"),
+        };
+        std::fs::create_dir_all(root.join("config")).unwrap();
+        std::fs::write(root.join("config/app.yaml"), "replicas: 2\n").unwrap();
+        std::fs::write(root.join("schema.cue"), "replicas: int\n").unwrap();
+        std::fs::write(root.join("notes.txt"), "not read by the run\n").unwrap();
+        std::fs::write(
+            root.join(".conflow.yaml"),
+            r#"
+name: app
+stages:
+  - name: vet
+    tool: { type: cue, command: vet, schemas: [schema.cue] }
+    input: "config/*.yaml"
+  - name: check
+    tool: { type: shell, command: "cat generated/*.json" }
+    input: "generated/*.json"
+"#,
+        )
+        .unwrap();
+
+        let output = temp.path().join("run.tar.gz");
+        let manifest = export(&root, Path::new(".conflow.yaml"), &output, &caches)
+            .await
+            .unwrap();
+        assert_eq!(
+            manifest.pipeline.as_deref(),
+            Some(Path::new(".conflow.yaml"))
+        );
+        assert_eq!(
+            manifest.files,
+            [".conflow.yaml", "config/app.yaml", "schema.cue"].map(PathBuf::from)
+        );
+
+        // Loading only fills caches; running unpacks the project
+        assert!(load(&output, &caches).unwrap().pipeline.is_some());
+        let dir = temp.path().join("unpacked");
+        unpack_run(&output, &dir, &caches).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(dir.join("config/app.yaml")).unwrap(),
+            "replicas: 2\n"
+        );
+        assert!(!dir.join("notes.txt").exists());
+
+        let mut bundled = manifest.clone();
+        bundled.versions = BTreeMap::from([("cue".to_string(), "v0.9.0".to_string())]);
+        assert_eq!(
+            bundled.version_drift(&BTreeMap::new()),
+            [("cue".into(), "v0.9.0".into(), "missing".into())]
+        );
+        let local = BTreeMap::from([("cue".to_string(), "v0.9.0".to_string())]);
+        assert!(bundled.version_drift(&local).is_empty());
+    }
+
+    /// A bundle with a manifest and one extra entry
+    fn bundle_with(output: &Path, mut header: tar::Header, path: &str, data: &[u8]) {
+        let mut archive = new_archive(output).unwrap();
+        header.set_size(data.len() as u64);
+        header.set_cksum();
+        archive.append_data(&mut header, path, data).unwrap();
+        finish(archive, &BundleManifest::new()).unwrap();
+    }
+
+    #[test]
+    fn test_unpack_rejects_links() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let outside = temp.path().join("outside");
+        std::fs::create_dir(&outside).unwrap();
+        let caches = Caches {
+            toolchain: temp.path().join("toolchain"),
+            profiles: temp.path().join("profiles"),
+            templates: temp.path().join("templates"),
+        };
+        let output = temp.path().join("evil.tar.gz");
+
+        for entry_type in [tar::EntryType::Symlink, tar::EntryType::Link] {
+            let mut header = tar::Header::new_gnu();
+            header.set_entry_type(entry_type);
+            header.set_link_name(&outside).unwrap();
+            bundle_with(&output, header, "templates/repo", b"");
+
+            let err = load(&output, &caches).unwrap_err().to_string();
+            assert!(err.contains("only hold files and directories"), "{}", err);
+            assert!(!caches.templates.join("repo").exists());
+        }
+
+        // A link already in the cache is not followed either
+        #[cfg(unix)]
+        {
+            std::fs::create_dir_all(&caches.profiles).unwrap();
+            std::os::unix::fs::symlink(&outside, caches.profiles.join("org")).unwrap();
+            let mut header = tar::Header::new_gnu();
+            header.set_mode(0o644);
+            bundle_with(&output, header, "profiles/org/evil.yaml", b"x");
+            let err = load(&output, &caches).unwrap_err().to_string();
+            assert!(err.contains("leaves"), "{}", err);
+            assert!(!outside.join("evil.yaml").exists());
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_unpack_masks_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp = tempfile::TempDir::new().unwrap();
+        let caches = Caches {
+            toolchain: temp.path().join("toolchain"),
+            profiles: temp.path().join("profiles"),
+            templates: temp.path().join("templates"),
+        };
+        let output = temp.path().join("tools.tar.gz");
+        let mut header = tar::Header::new_gnu();
+        header.set_mode(0o4777);
+        bundle_with(&output, header, "toolchain/cue/v0.9.0/cue", b"#!/bin/sh\n");
+
+        load(&output, &caches).unwrap();
+        let mode = std::fs::metadata(caches.toolchain.join("cue/v0.9.0/cue"))
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o7777, 0o755);
+    }
+}
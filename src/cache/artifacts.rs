@@ -0,0 +1,198 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Content-addressed artifact store
+//!
+//! Holds files under the BLAKE3 hash of their content. Stages run on remote
+//! agents exchange their input and output files through it: a file already in
+//! the receiving store is not sent again, so a runner and agents sharing
+//! `CONFLOW_CACHE_DIR` (e.g. on a network volume) transfer nothing at all.
+
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::errors::ConflowError;
+
+/// Directory of the store in the shared cache
+pub const ARTIFACTS_DIR: &str = "artifacts";
+
+/// Files by content hash
+#[derive(Debug, Clone)]
+pub struct ArtifactStore {
+    root: PathBuf,
+}
+
+impl ArtifactStore {
+    /// A store in `root`, created on first write
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    /// The store in the shared cache directory
+    pub fn shared() -> Result<Self, ConflowError> {
+        let root = super::global_root().ok_or_else(|| ConflowError::CacheError {
+            message: "No cache directory for artifacts; set CONFLOW_CACHE_DIR".into(),
+        })?;
+        Ok(Self::new(root.join(ARTIFACTS_DIR)))
+    }
+
+    /// Where an artifact is kept, or `None` if `hash` isn't a BLAKE3 hash
+    pub fn path(&self, hash: &str) -> Option<PathBuf> {
+        let valid =
+            hash.len() == 64 && hash.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'));
+        valid.then(|| self.root.join(hash))
+    }
+
+    /// Whether the store holds `hash`
+    pub fn contains(&self, hash: &str) -> bool {
+        self.path(hash).is_some_and(|path| path.is_file())
+    }
+
+    /// Add a file, returning its hash
+    pub fn put_file(&self, path: &Path) -> Result<String, ConflowError> {
+        let hash = super::hash_file(path)?;
+        if !self.contains(&hash) {
+            let content = std::fs::read(path).map_err(|e| ConflowError::FileReadError {
+                path: path.to_path_buf(),
+                error: e.to_string(),
+            })?;
+            let mut writer = self.writer(&hash)?;
+            writer.write(&content)?;
+            writer.finish()?;
+        }
+        Ok(hash)
+    }
+
+    /// Start adding the artifact `hash`, received in pieces
+    pub fn writer(&self, hash: &str) -> Result<ArtifactWriter, ConflowError> {
+        static NEXT: AtomicU64 = AtomicU64::new(0);
+
+        let target = self.path(hash).ok_or_else(|| ConflowError::CacheError {
+            message: format!("Invalid artifact hash '{}'", hash),
+        })?;
+        std::fs::create_dir_all(&self.root)?;
+        let temp = self.root.join(format!(
+            "{}.{}.{}.tmp",
+            hash,
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        ));
+        Ok(ArtifactWriter {
+            file: File::create(&temp)?,
+            temp,
+            target,
+            expected: hash.to_string(),
+            hasher: blake3::Hasher::new(),
+        })
+    }
+
+    /// Copy the artifact `hash` to `dest`, creating its directory
+    pub fn copy_to(&self, hash: &str, dest: &Path) -> Result<(), ConflowError> {
+        let source = self
+            .path(hash)
+            .filter(|path| path.is_file())
+            .ok_or_else(|| ConflowError::CacheError {
+                message: format!("Artifact {} is not in {}", hash, self.root.display()),
+            })?;
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::copy(&source, dest).map_err(|e| ConflowError::FileWriteError {
+            path: dest.to_path_buf(),
+            error: e.to_string(),
+        })?;
+        Ok(())
+    }
+}
+
+/// An artifact being added; it only enters the store when its content
+/// matches its hash
+pub struct ArtifactWriter {
+    file: File,
+    temp: PathBuf,
+    target: PathBuf,
+    expected: String,
+    hasher: blake3::Hasher,
+}
+
+impl ArtifactWriter {
+    /// Append content
+    pub fn write(&mut self, data: &[u8]) -> Result<(), ConflowError> {
+        self.hasher.update(data);
+        self.file.write_all(data)?;
+        Ok(())
+    }
+
+    /// Check the content against the hash and add it to the store
+    pub fn finish(mut self) -> Result<(), ConflowError> {
+        let actual = self.hasher.finalize().to_hex().to_string();
+        if actual != self.expected {
+            return Err(ConflowError::CacheError {
+                message: format!(
+                    "Artifact {} arrived with content hashing to {}",
+                    self.expected, actual
+                ),
+            });
+        }
+        self.file.flush()?;
+        std::fs::rename(&self.temp, &self.target)?;
+        Ok(())
+    }
+}
+
+impl Drop for ArtifactWriter {
+    fn drop(&mut self) {
+        // Left over unless finish() moved it into place
+        let _ = std::fs::remove_file(&self.temp);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_round_trip() {
+        let temp = TempDir::new().unwrap();
+        let store = ArtifactStore::new(temp.path().join("store"));
+        let file = temp.path().join("input.yaml");
+        std::fs::write(&file, "replicas: 3\n").unwrap();
+
+        let hash = store.put_file(&file).unwrap();
+        assert_eq!(hash, crate::cache::hash_string("replicas: 3\n"));
+        assert!(store.contains(&hash));
+        // Adding it again is a no-op
+        assert_eq!(store.put_file(&file).unwrap(), hash);
+
+        let copy = temp.path().join("out/copy.yaml");
+        store.copy_to(&hash, &copy).unwrap();
+        assert_eq!(std::fs::read_to_string(copy).unwrap(), "replicas: 3\n");
+
+        // Pieces are checked against the hash before they are kept
+        let other = ArtifactStore::new(temp.path().join("other"));
+        let mut writer = other.writer(&hash).unwrap();
+        writer.write(b"replicas: ").unwrap();
+        writer.write(b"3\n").unwrap();
+        writer.finish().unwrap();
+        assert!(other.contains(&hash));
+
+        let forged = crate::cache::hash_string("replicas: 4\n");
+        let mut writer = other.writer(&forged).unwrap();
+        writer.write(b"replicas: 3\n").unwrap();
+        assert!(writer.finish().is_err());
+        assert!(!other.contains(&forged));
+        assert_eq!(
+            std::fs::read_dir(temp.path().join("other"))
+                .unwrap()
+                .count(),
+            1
+        );
+
+        // Hashes name files in the store and nothing else
+        assert!(store.path("../input.yaml").is_none());
+        assert!(store.writer("abc").is_err());
+    }
+}
@@ -6,10 +6,14 @@
 //! Stores cache entries as JSON files in a cache directory.
 
 use async_trait::async_trait;
+use std::fs::{File, TryLockError};
 use std::path::{Path, PathBuf};
 use std::time::SystemTime;
 
-use super::{Cache, CacheStats, CachedEntry, CachedResult, ContentHasher};
+use super::{
+    Cache, CacheLimits, CacheStats, CachedEntry, ContentHasher, CorruptEntry, Corruption, GcReport,
+    VerifyReport,
+};
 use crate::errors::ConflowError;
 use crate::executors::ExecutionResult;
 use crate::pipeline::Stage;
@@ -20,6 +24,19 @@ pub struct FilesystemCache {
     cache_dir: PathBuf,
     /// Base directory for resolving relative paths
     base_dir: PathBuf,
+    /// Limits enforced after each store
+    limits: CacheLimits,
+}
+
+/// Lock file in the cache directory
+const LOCK_FILE: &str = ".lock";
+
+/// A cache entry file on disk
+struct EntryFile {
+    path: PathBuf,
+    size: u64,
+    /// Modification time, refreshed on every cache hit
+    last_used: SystemTime,
 }
 
 impl FilesystemCache {
@@ -32,7 +49,17 @@ impl FilesystemCache {
             })?;
         }
 
-        Ok(Self { cache_dir, base_dir })
+        Ok(Self {
+            cache_dir,
+            base_dir,
+            limits: CacheLimits::default(),
+        })
+    }
+
+    /// Evict entries beyond these limits whenever a result is stored
+    pub fn with_limits(mut self, limits: CacheLimits) -> Self {
+        self.limits = limits;
+        self
     }
 
     /// Create cache with default directory
@@ -54,12 +81,12 @@ impl FilesystemCache {
         self.cache_dir.join(prefix).join(format!("{}.json", rest))
     }
 
-    /// List all cache entries
-    async fn list_entries(&self) -> Result<Vec<CachedEntry>, ConflowError> {
-        let mut entries = Vec::new();
+    /// List all cache entry files
+    fn entry_files(&self) -> Result<Vec<EntryFile>, ConflowError> {
+        let mut files = Vec::new();
 
         if !self.cache_dir.exists() {
-            return Ok(entries);
+            return Ok(files);
         }
 
         // Walk cache directory
@@ -93,16 +120,142 @@ impl FilesystemCache {
                     continue;
                 }
 
-                // Read and parse entry
-                if let Ok(content) = std::fs::read_to_string(&entry_file) {
-                    if let Ok(entry) = serde_json::from_str::<CachedEntry>(&content) {
-                        entries.push(entry);
-                    }
+                if let Ok(metadata) = entry_file.metadata() {
+                    files.push(EntryFile {
+                        size: metadata.len(),
+                        last_used: metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+                        path: entry_file,
+                    });
                 }
             }
         }
 
-        Ok(entries)
+        Ok(files)
+    }
+
+    /// List all cache entries with their files
+    fn list_entries(&self) -> Result<Vec<(EntryFile, CachedEntry)>, ConflowError> {
+        Ok(self
+            .entry_files()?
+            .into_iter()
+            .filter_map(|file| {
+                // Read and parse entry
+                let content = std::fs::read_to_string(&file.path).ok()?;
+                let entry = serde_json::from_str::<CachedEntry>(&content).ok()?;
+                Some((file, entry))
+            })
+            .collect())
+    }
+
+    /// Remove expired entries, then least recently used ones until the
+    /// cache fits
+    fn evict(
+        &self,
+        mut files: Vec<EntryFile>,
+        limits: &CacheLimits,
+        report: &mut GcReport,
+    ) -> Result<(), ConflowError> {
+        files.sort_by_key(|f| f.last_used);
+        let now = SystemTime::now();
+        let mut total: u64 = files.iter().map(|f| f.size).sum();
+
+        for file in files {
+            let age = now.duration_since(file.last_used).unwrap_or_default();
+            if limits.max_age.is_some_and(|max| age > max) {
+                report.expired += 1;
+            } else if limits.max_size_bytes.is_some_and(|max| total > max) {
+                report.evicted += 1;
+            } else {
+                report.remaining += 1;
+                report.remaining_bytes += file.size;
+                continue;
+            }
+            self.remove_entry(&file.path)?;
+            total -= file.size;
+            report.freed_bytes += file.size;
+        }
+        Ok(())
+    }
+
+    /// Lock the cache against other processes until the file is dropped
+    ///
+    /// Stores share the lock; gc, clear and repair hold it exclusively.
+    fn lock(&self, exclusive: bool) -> Result<File, ConflowError> {
+        let file = self.lock_file()?;
+        let locked = if exclusive {
+            file.lock()
+        } else {
+            file.lock_shared()
+        };
+        locked.map_err(|e| ConflowError::CacheError {
+            message: format!("Failed to lock cache: {}", e),
+        })?;
+        Ok(file)
+    }
+
+    /// The exclusive lock, unless another process holds the lock
+    fn try_lock_exclusive(&self) -> Result<Option<File>, ConflowError> {
+        let file = self.lock_file()?;
+        match file.try_lock() {
+            Ok(()) => Ok(Some(file)),
+            Err(TryLockError::WouldBlock) => Ok(None),
+            Err(TryLockError::Error(e)) => Err(ConflowError::CacheError {
+                message: format!("Failed to lock cache: {}", e),
+            }),
+        }
+    }
+
+    fn lock_file(&self) -> Result<File, ConflowError> {
+        File::options()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(self.cache_dir.join(LOCK_FILE))
+            .map_err(|e| ConflowError::CacheError {
+                message: format!("Failed to open cache lock: {}", e),
+            })
+    }
+
+    /// Read an entry and check it against its key and checksums
+    fn read_entry(path: &Path, key: &str) -> Result<CachedEntry, Corruption> {
+        let content =
+            std::fs::read_to_string(path).map_err(|e| Corruption::Unreadable(e.to_string()))?;
+        let entry: CachedEntry =
+            serde_json::from_str(&content).map_err(|e| Corruption::Unreadable(e.to_string()))?;
+
+        if entry.cache_key != key {
+            return Err(Corruption::KeyMismatch);
+        }
+        match entry.verify() {
+            Some(problem) => Err(problem),
+            None => Ok(entry),
+        }
+    }
+
+    /// The key an entry file is stored under
+    fn key_of(path: &Path) -> String {
+        let prefix = path
+            .parent()
+            .and_then(|p| p.file_name())
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let rest = path
+            .file_stem()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        prefix + &rest
+    }
+
+    fn remove_entry(&self, path: &Path) -> Result<(), ConflowError> {
+        std::fs::remove_file(path).map_err(|e| ConflowError::CacheError {
+            message: format!("Failed to remove cache entry: {}", e),
+        })?;
+        // Drop the prefix directory once it is empty
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::remove_dir(parent);
+        }
+        tracing::debug!(path = %path.display(), "removed cache entry");
+        Ok(())
     }
 }
 
@@ -113,35 +266,44 @@ impl Cache for FilesystemCache {
         let path = self.cache_path(&key);
 
         if !path.exists() {
+            tracing::debug!(key = %key, "cache miss");
             return Ok(None);
         }
 
-        // Read cached entry
-        let content = tokio::fs::read_to_string(&path).await.map_err(|e| {
-            ConflowError::CacheError {
-                message: format!("Failed to read cache entry: {}", e),
-            }
-        })?;
-
-        let entry: CachedEntry = serde_json::from_str(&content).map_err(|e| {
-            ConflowError::CacheError {
-                message: format!("Failed to parse cache entry: {}", e),
+        // A damaged entry is recomputed rather than trusted
+        let entry = match Self::read_entry(&path, &key) {
+            Ok(entry) => entry,
+            Err(problem) => {
+                tracing::warn!(key = %key, problem = %problem, "corrupt cache entry, recomputing");
+                let _ = self.remove_entry(&path);
+                return Ok(None);
             }
-        })?;
+        };
 
         // Verify outputs still exist
         for output in &entry.result.outputs {
             if !output.exists() {
                 // Cache invalid - outputs missing
                 // Delete the stale entry
+                tracing::debug!(
+                    key = %key,
+                    output = %output.display(),
+                    "stale cache entry, output missing"
+                );
                 let _ = tokio::fs::remove_file(&path).await;
                 return Ok(None);
             }
         }
 
+        // Mark the entry as recently used for LRU eviction
+        if let Ok(file) = std::fs::File::options().write(true).open(&path) {
+            let _ = file.set_modified(SystemTime::now());
+        }
+
         // Convert to ExecutionResult
         let mut result: ExecutionResult = entry.result.into();
         result.cache_hit = true;
+        tracing::debug!(key = %key, "cache hit");
 
         Ok(Some(result))
     }
@@ -159,20 +321,39 @@ impl Cache for FilesystemCache {
             })?;
         }
 
-        let entry = CachedEntry {
-            timestamp: SystemTime::now(),
-            stage_name: stage.name.clone(),
-            cache_key: key,
-            result: CachedResult::from(result),
-        };
+        let entry = CachedEntry::new(stage.name.clone(), key, result);
 
         let json = serde_json::to_string_pretty(&entry).map_err(|e| ConflowError::CacheError {
             message: format!("Failed to serialize cache entry: {}", e),
         })?;
 
-        tokio::fs::write(&path, json).await.map_err(|e| ConflowError::CacheError {
+        // Write beside the entry and rename, so concurrent readers never
+        // see a partial entry; the shared lock keeps gc out meanwhile
+        let lock = self.lock(false)?;
+        let temp = path.with_extension(format!("{}.tmp", std::process::id()));
+        tokio::fs::write(&temp, json).await.map_err(|e| ConflowError::CacheError {
+            message: format!("Failed to write cache entry: {}", e),
+        })?;
+        tokio::fs::rename(&temp, &path).await.map_err(|e| ConflowError::CacheError {
             message: format!("Failed to write cache entry: {}", e),
         })?;
+        drop(lock);
+        tracing::debug!(path = %path.display(), "stored cache entry");
+
+        // Evict only if no other process is using the cache right now
+        if !self.limits.is_unlimited() {
+            if let Some(_lock) = self.try_lock_exclusive()? {
+                let mut report = GcReport::default();
+                self.evict(self.entry_files()?, &self.limits, &mut report)?;
+                if report.removed() > 0 {
+                    tracing::debug!(
+                        removed = report.removed(),
+                        freed_bytes = report.freed_bytes,
+                        "evicted cache entries"
+                    );
+                }
+            }
+        }
 
         Ok(())
     }
@@ -194,33 +375,45 @@ impl Cache for FilesystemCache {
 
     async fn clear(&self) -> Result<(), ConflowError> {
         if self.cache_dir.exists() {
-            tokio::fs::remove_dir_all(&self.cache_dir).await.map_err(|e| {
+            let _lock = self.lock(true)?;
+
+            // Remove the entry directories, keeping the lock file
+            for prefix_dir in std::fs::read_dir(&self.cache_dir).map_err(|e| {
                 ConflowError::CacheError {
                     message: format!("Failed to clear cache: {}", e),
                 }
-            })?;
-
-            tokio::fs::create_dir_all(&self.cache_dir).await.map_err(|e| {
-                ConflowError::CacheError {
-                    message: format!("Failed to recreate cache directory: {}", e),
+            })? {
+                let path = prefix_dir
+                    .map_err(|e| ConflowError::CacheError {
+                        message: format!("Failed to clear cache: {}", e),
+                    })?
+                    .path();
+                if path.is_dir() {
+                    tokio::fs::remove_dir_all(&path).await.map_err(|e| {
+                        ConflowError::CacheError {
+                            message: format!("Failed to clear cache: {}", e),
+                        }
+                    })?;
                 }
-            })?;
+            }
         }
 
         Ok(())
     }
 
     async fn stats(&self) -> Result<CacheStats, ConflowError> {
-        let entries = self.list_entries().await?;
+        let entries = self.list_entries()?;
 
         let mut stats = CacheStats {
             entries: entries.len(),
-            size_bytes: 0,
-            oldest_entry: None,
-            newest_entry: None,
+            ..CacheStats::default()
         };
 
-        for entry in &entries {
+        for (file, entry) in &entries {
+            let usage = stats.stages.entry(entry.stage_name.clone()).or_default();
+            usage.entries += 1;
+            usage.size_bytes += file.size;
+
             // Update timestamps
             match stats.oldest_entry {
                 None => stats.oldest_entry = Some(entry.timestamp),
@@ -246,6 +439,54 @@ impl Cache for FilesystemCache {
 
         Ok(stats)
     }
+
+    async fn gc(&self, limits: &CacheLimits) -> Result<GcReport, ConflowError> {
+        let _lock = self.lock(true)?;
+        let mut report = GcReport::default();
+        let mut live = Vec::new();
+
+        for (file, entry) in self.list_entries()? {
+            if entry.result.outputs.iter().all(|o| o.exists()) {
+                live.push(file);
+            } else {
+                self.remove_entry(&file.path)?;
+                report.stale += 1;
+                report.freed_bytes += file.size;
+            }
+        }
+
+        self.evict(live, limits, &mut report)?;
+        Ok(report)
+    }
+
+    async fn verify(&self, repair: bool) -> Result<VerifyReport, ConflowError> {
+        let _lock = self.lock(repair)?;
+        let mut report = VerifyReport {
+            repaired: repair,
+            ..VerifyReport::default()
+        };
+
+        for file in self.entry_files()? {
+            report.checked += 1;
+            let Err(problem) = Self::read_entry(&file.path, &Self::key_of(&file.path)) else {
+                continue;
+            };
+            // Name the stage when the entry is at least readable
+            let stage = std::fs::read_to_string(&file.path)
+                .ok()
+                .and_then(|c| serde_json::from_str::<CachedEntry>(&c).ok())
+                .map(|e| e.stage_name);
+            if repair {
+                self.remove_entry(&file.path)?;
+            }
+            report.corrupt.push(CorruptEntry {
+                path: file.path,
+                stage,
+                problem,
+            });
+        }
+        Ok(report)
+    }
 }
 
 impl FilesystemCache {
@@ -294,11 +535,19 @@ mod tests {
                 out_format: None,
             },
             input: Input::Single("*.json".into()),
+            exclude: vec![],
             output: None,
+            snapshot: false,
+            contract: None,
             depends_on: vec![],
             allow_failure: false,
             env: HashMap::new(),
             condition: None,
+            env_file: None,
+            workdir: None,
+            shard: None,
+            runs_on: Vec::new(),
+            manual: false,
         }
     }
 
@@ -379,4 +628,158 @@ mod tests {
         let stats = cache.stats().await.unwrap();
         assert_eq!(stats.entries, 0);
     }
+
+    fn age(cache: &FilesystemCache, stage: &Stage, days: u64) {
+        let path = cache.cache_path(&cache.cache_key(stage).unwrap());
+        let file = std::fs::File::options().write(true).open(path).unwrap();
+        let then = SystemTime::now() - std::time::Duration::from_secs(days * 24 * 60 * 60);
+        file.set_modified(then).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_cache_gc() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache =
+            FilesystemCache::new(temp_dir.path().to_path_buf(), temp_dir.path().to_path_buf())
+                .unwrap();
+
+        let result = |outputs| {
+            ExecutionResult::success("x".repeat(1000), std::time::Duration::ZERO, outputs)
+        };
+        let stages: Vec<Stage> = ["a", "b", "c", "d"].map(make_test_stage).into();
+        for stage in &stages[..3] {
+            cache.store(stage, &result(vec![])).await.unwrap();
+        }
+        cache
+            .store(&stages[3], &result(vec![temp_dir.path().join("gone.json")]))
+            .await
+            .unwrap();
+        age(&cache, &stages[0], 40);
+        age(&cache, &stages[1], 3);
+        age(&cache, &stages[2], 2);
+
+        let stats = cache.stats().await.unwrap();
+        assert_eq!(stats.stages.len(), 4);
+        assert_eq!(stats.stages["a"].entries, 1);
+        assert!(stats.stages["a"].size_bytes > 1000);
+
+        // b was used longest ago of what's left after the age limit
+        let size = stats.stages["b"].size_bytes;
+        let limits = CacheLimits {
+            max_size_bytes: Some(size + size / 2),
+            max_age: Some(std::time::Duration::from_secs(30 * 24 * 60 * 60)),
+        };
+        let report = cache.gc(&limits).await.unwrap();
+        assert_eq!((report.stale, report.expired, report.evicted), (1, 1, 1));
+        assert_eq!(report.remaining, 1);
+
+        assert!(cache.get(&stages[2]).await.unwrap().is_some());
+        for stage in [&stages[0], &stages[1], &stages[3]] {
+            assert!(cache.get(stage).await.unwrap().is_none());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cache_limits_enforced_on_store() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache =
+            FilesystemCache::new(temp_dir.path().to_path_buf(), temp_dir.path().to_path_buf())
+                .unwrap();
+        let result = ExecutionResult::success("output".into(), std::time::Duration::ZERO, vec![]);
+
+        cache.store(&make_test_stage("old"), &result).await.unwrap();
+        age(&cache, &make_test_stage("old"), 10);
+        let cache = cache.with_limits(CacheLimits {
+            max_size_bytes: None,
+            max_age: Some(std::time::Duration::from_secs(24 * 60 * 60)),
+        });
+        cache.store(&make_test_stage("new"), &result).await.unwrap();
+
+        assert!(cache.get(&make_test_stage("old")).await.unwrap().is_none());
+        assert!(cache.get(&make_test_stage("new")).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_cache_corruption_recomputed() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache =
+            FilesystemCache::new(temp_dir.path().join("cache"), temp_dir.path().to_path_buf())
+                .unwrap();
+        // Outside the stages' `*.json` input, so their keys don't change
+        let output = temp_dir.path().join("cache").join("out.json");
+        std::fs::write(&output, "{}").unwrap();
+
+        let stages: Vec<Stage> = ["tampered", "truncated", "edited", "fine"]
+            .map(make_test_stage)
+            .into();
+        for stage in &stages {
+            let result =
+                ExecutionResult::success("ok".into(), std::time::Duration::ZERO, vec![]);
+            let result = match stage.name.as_str() {
+                "edited" => ExecutionResult {
+                    outputs: vec![output.clone()],
+                    ..result
+                },
+                _ => result,
+            };
+            cache.store(stage, &result).await.unwrap();
+        }
+        let path = |stage: &Stage| cache.cache_path(&cache.cache_key(stage).unwrap());
+
+        let tampered = std::fs::read_to_string(path(&stages[0])).unwrap();
+        std::fs::write(path(&stages[0]), tampered.replace("\"ok\"", "\"no\"")).unwrap();
+        let truncated = std::fs::read_to_string(path(&stages[1])).unwrap();
+        std::fs::write(path(&stages[1]), &truncated[..truncated.len() / 2]).unwrap();
+        std::fs::write(&output, "{\"changed\": true}").unwrap();
+
+        let report = cache.verify(false).await.unwrap();
+        assert_eq!(report.checked, 4);
+        let mut problems: Vec<(Option<String>, String)> = report
+            .corrupt
+            .iter()
+            .map(|c| (c.stage.clone(), c.problem.to_string()))
+            .collect();
+        problems.sort();
+        assert_eq!(problems.len(), 3);
+        assert_eq!(problems[0].0, None);
+        assert!(problems[0].1.starts_with("unreadable"));
+        assert_eq!(problems[1].0.as_deref(), Some("edited"));
+        assert!(problems[1].1.ends_with("out.json changed"));
+        assert_eq!(
+            problems[2],
+            (
+                Some("tampered".into()),
+                "result doesn't match its checksum".into()
+            )
+        );
+
+        // Reads treat every corrupt entry as a miss and drop it
+        for stage in &stages[..3] {
+            assert!(cache.get(stage).await.unwrap().is_none());
+            assert!(!path(stage).exists());
+        }
+        assert!(cache.get(&stages[3]).await.unwrap().is_some());
+        assert!(cache.verify(true).await.unwrap().corrupt.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_cache_lock() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache =
+            FilesystemCache::new(temp_dir.path().to_path_buf(), temp_dir.path().to_path_buf())
+                .unwrap();
+
+        // Another process storing holds the shared lock
+        let other = cache.lock(false).unwrap();
+        assert!(cache.try_lock_exclusive().unwrap().is_none());
+        drop(other);
+        assert!(cache.try_lock_exclusive().unwrap().is_some());
+
+        // Clearing keeps the lock file
+        let result = ExecutionResult::success("output".into(), std::time::Duration::ZERO, vec![]);
+        cache.store(&make_test_stage("a"), &result).await.unwrap();
+        cache.clear().await.unwrap();
+        assert!(temp_dir.path().join(LOCK_FILE).exists());
+        assert_eq!(cache.stats().await.unwrap().entries, 0);
+    }
 }
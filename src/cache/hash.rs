@@ -6,10 +6,13 @@
 //! Uses BLAKE3 for fast, secure content hashing.
 
 use blake3::Hasher;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
+use super::cue_import_closure;
+use crate::analyzer::render_inputs;
 use crate::errors::ConflowError;
-use crate::pipeline::{Input, Stage};
+use crate::pipeline::{stage_workdir, Stage, Tool};
+use crate::utils::ignore::Ignore;
 
 /// Content hasher for generating cache keys
 pub struct ContentHasher {
@@ -58,10 +61,48 @@ impl ContentHasher {
             self.hasher.update(v.as_bytes());
         }
 
+        // Hash the env file and working directory
+        if let Some(ref env_file) = stage.env_file {
+            self.hash_file(&base_dir.join(env_file))?;
+        }
+        if let Some(ref workdir) = stage.workdir {
+            // `a\b` and `a/b` name the same directory on Windows
+            let workdir: Vec<_> = workdir.components().map(|c| c.as_os_str()).collect();
+            self.hasher
+                .update(workdir.join(std::ffi::OsStr::new("/")).as_encoded_bytes());
+        }
+        let base_dir = &stage_workdir(stage, base_dir);
+
         // Hash input file contents
         let input_files = self.collect_input_files(stage, base_dir)?;
-        for file in input_files {
-            self.hash_file(&file)?;
+        for file in &input_files {
+            self.hash_file(file)?;
+        }
+
+        // CUE stages also load their schemas and everything those import,
+        // but no other CUE file
+        if let Tool::Cue { ref schemas, .. } = stage.tool {
+            let mut loaded: Vec<PathBuf> = schemas.iter().map(|s| base_dir.join(s)).collect();
+            loaded.extend(input_files.iter().cloned());
+            for file in cue_import_closure(&loaded) {
+                if !input_files.contains(&file) {
+                    self.hash_file(&file)?;
+                }
+            }
+        }
+
+        // Render stages read their values files, and chart templates their
+        // chart's defaults and helpers
+        if let Tool::Render { ref values, .. } = stage.tool {
+            let mut read: Vec<PathBuf> = values.iter().map(|v| base_dir.join(v)).collect();
+            for file in input_files.iter().flat_map(|f| render_inputs(f)) {
+                if !read.contains(&file) && !input_files.contains(&file) {
+                    read.push(file);
+                }
+            }
+            for file in &read {
+                self.hash_file(file)?;
+            }
         }
 
         Ok(self.hasher.finalize().to_hex().to_string())
@@ -87,7 +128,7 @@ impl ContentHasher {
         &self,
         stage: &Stage,
         base_dir: &Path,
-    ) -> Result<Vec<std::path::PathBuf>, ConflowError> {
+    ) -> Result<Vec<PathBuf>, ConflowError> {
         let patterns = stage.input.patterns();
 
         if patterns.is_empty() {
@@ -106,12 +147,10 @@ impl ContentHasher {
             let matches = glob::glob(&full_pattern)
                 .map_err(|e| ConflowError::GlobPattern { message: e.to_string() })?;
 
-            for entry in matches {
-                if let Ok(path) = entry {
-                    files.push(path);
-                }
-            }
+            files.extend(matches.flatten());
         }
+        let exclude = Ignore::new(base_dir, stage.exclude.iter().map(String::as_str));
+        files.retain(|f| !exclude.is_ignored(f));
 
         // Sort for consistent ordering
         files.sort();
@@ -181,4 +220,45 @@ mod tests {
 
         assert_eq!(hash1, hash2);
     }
+
+    #[test]
+    fn test_cue_stage_key_covers_imports() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let root = temp.path();
+        let write = |path: &str, content: &str| {
+            let path = root.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        };
+        write("cue.mod/module.cue", "module: \"example.com/app\"\n");
+        write("schema.cue", "package app\n\nimport \"example.com/app/defs\"\n");
+        write("defs/defs.cue", "package defs\n\n#Port: int\n");
+        write("other/other.cue", "package other\n");
+        write("data.yaml", "port: 80\n");
+
+        let pipeline = crate::pipeline::Pipeline::from_yaml(
+            r#"
+version: "1"
+name: app
+stages:
+  - name: vet
+    tool: { type: cue, command: vet, schemas: [schema.cue] }
+    input: "*.yaml"
+"#,
+        )
+        .unwrap();
+        let key = || ContentHasher::new().hash_stage(&pipeline.stages[0], root).unwrap();
+        let before = key();
+
+        write("other/other.cue", "package other\n\nx: 1\n");
+        assert_eq!(key(), before);
+
+        write("defs/defs.cue", "package defs\n\n#Port: int & >0\n");
+        let imported = key();
+        assert_ne!(imported, before);
+
+        let schema = "package app\n\nimport \"example.com/app/defs\"\n\nport: defs.#Port\n";
+        write("schema.cue", schema);
+        assert_ne!(key(), imported);
+    }
 }
@@ -0,0 +1,243 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! CUE import closure
+//!
+//! A CUE stage's result depends on its schema files and on every file of
+//! the packages they import, transitively. [`cue_import_closure`] finds those
+//! files by reading `import` declarations and resolving them the way `cue`
+//! does: paths under the module path (from `cue.mod/module.cue`) to the
+//! module's own directories, anything else to `cue.mod/pkg`, `cue.mod/gen`
+//! or `cue.mod/usr`. Standard library imports have no files.
+
+use std::collections::{BTreeSet, HashSet};
+use std::path::{Path, PathBuf};
+
+/// A CUE module: the directory holding `cue.mod`, and its import path
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Module {
+    root: PathBuf,
+    path: Option<String>,
+}
+
+/// The `.cue` files among `files`, plus every file they import, sorted
+///
+/// Files that can't be read are kept but not followed.
+pub fn cue_import_closure(files: &[PathBuf]) -> Vec<PathBuf> {
+    let mut closure = BTreeSet::new();
+    let mut modules = Vec::new();
+    let mut queue: Vec<PathBuf> = files
+        .iter()
+        .filter(|f| f.extension().is_some_and(|ext| ext == "cue"))
+        .cloned()
+        .collect();
+
+    while let Some(file) = queue.pop() {
+        if !closure.insert(file.clone()) {
+            continue;
+        }
+        let Ok(source) = std::fs::read_to_string(&file) else {
+            continue;
+        };
+        let Some(module) = file.parent().and_then(|dir| find_module(dir, &mut modules)) else {
+            continue;
+        };
+        for import in imports(&source) {
+            queue.extend(package_files(&import, &module));
+        }
+    }
+    closure.into_iter().collect()
+}
+
+/// Import paths declared in a CUE file
+fn imports(source: &str) -> Vec<String> {
+    let mut imports = Vec::new();
+    let mut in_block = false;
+    for line in source.lines() {
+        let line = line.split("//").next().unwrap_or_default().trim();
+        let spec = if in_block {
+            if line.starts_with(')') {
+                in_block = false;
+                continue;
+            }
+            line
+        } else if let Some(rest) = line
+            .strip_prefix("import")
+            .filter(|rest| rest.starts_with([' ', '\t', '(', '"']))
+        {
+            let rest = rest.trim_start();
+            if let Some(block) = rest.strip_prefix('(') {
+                in_block = true;
+                block
+            } else {
+                rest
+            }
+        } else {
+            continue;
+        };
+        // `"path"` or `alias "path"`
+        if let Some(path) = spec.split('"').nth(1).filter(|p| !p.is_empty()) {
+            imports.push(path.to_string());
+        }
+    }
+    imports
+}
+
+/// The `package` a CUE file belongs to
+fn package_name(source: &str) -> Option<&str> {
+    source.lines().find_map(|line| {
+        let line = line.split("//").next().unwrap_or_default().trim();
+        line.strip_prefix("package ")
+            .map(|name| name.trim())
+            .filter(|name| !name.is_empty())
+    })
+}
+
+/// The module containing `dir`, found by walking up to a `cue.mod` directory
+fn find_module(dir: &Path, known: &mut Vec<Module>) -> Option<Module> {
+    if let Some(module) = known.iter().find(|m| dir.starts_with(&m.root)) {
+        return Some(module.clone());
+    }
+    let root = dir.ancestors().find(|d| d.join("cue.mod").is_dir())?;
+    let path = std::fs::read_to_string(root.join("cue.mod/module.cue"))
+        .ok()
+        .and_then(|source| module_path(&source));
+    let module = Module {
+        root: root.to_path_buf(),
+        path,
+    };
+    known.push(module.clone());
+    Some(module)
+}
+
+/// `module: "example.com/app"` from `cue.mod/module.cue`, without a major
+/// version suffix
+fn module_path(source: &str) -> Option<String> {
+    source.lines().find_map(|line| {
+        let value = line.trim().strip_prefix("module:")?;
+        let path = value.split('"').nth(1)?;
+        Some(path.split('@').next().unwrap_or(path).to_string())
+    })
+}
+
+/// Files of the package an import path names
+///
+/// Besides the package directory itself, files of the same package in its
+/// parent directories up to the module root are part of the package.
+fn package_files(import: &str, module: &Module) -> Vec<PathBuf> {
+    let (path, qualifier) = match import.split_once(':') {
+        Some((path, qualifier)) => (path, Some(qualifier)),
+        None => (import, None),
+    };
+    let path = path.split('@').next().unwrap_or(path);
+    let name = qualifier.unwrap_or_else(|| path.rsplit('/').next().unwrap_or(path));
+
+    let local = module.path.as_deref().and_then(|module_path| {
+        let rest = path.strip_prefix(module_path)?;
+        (rest.is_empty() || rest.starts_with('/')).then(|| rest.trim_start_matches('/'))
+    });
+    let (dir, top) = match local {
+        Some(rest) => (module.root.join(rest), module.root.clone()),
+        None => {
+            // Standard library packages have no dot in their first element
+            if !path.split('/').next().unwrap_or_default().contains('.') {
+                return Vec::new();
+            }
+            let Some(dir) = ["pkg", "gen", "usr"]
+                .iter()
+                .map(|tree| module.root.join("cue.mod").join(tree).join(path))
+                .find(|dir| dir.is_dir())
+            else {
+                return Vec::new();
+            };
+            (dir.clone(), dir)
+        }
+    };
+
+    let mut files = Vec::new();
+    let mut seen = HashSet::new();
+    for ancestor in dir.ancestors().take_while(|d| d.starts_with(&top)) {
+        let Ok(entries) = std::fs::read_dir(ancestor) else {
+            continue;
+        };
+        for entry in entries.filter_map(|e| e.ok()) {
+            let file = entry.path();
+            if file.extension().is_none_or(|ext| ext != "cue") || !seen.insert(file.clone()) {
+                continue;
+            }
+            let in_package = std::fs::read_to_string(&file)
+                .is_ok_and(|source| package_name(&source) == Some(name));
+            if in_package {
+                files.push(file);
+            }
+        }
+    }
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cue_import_closure() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let root = temp.path();
+        let write = |path: &str, content: &str| {
+            let path = root.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        };
+        write("cue.mod/module.cue", "module: \"example.com/app@v0\"\n");
+        write(
+            "schema.cue",
+            r#"package app
+
+import "strings"
+
+import (
+    "example.com/app/defs" // shared definitions
+    core "k8s.io/api/core/v1"
+)
+
+#Name: strings.MinRunes(1)
+"#,
+        );
+        write("base.cue", "package defs\n\n#Base: {}\n");
+        write(
+            "defs/defs.cue",
+            "package defs\n\nimport \"example.com/app/shared:common\"\n",
+        );
+        write("defs/other.cue", "package other\n");
+        write("shared/common.cue", "package common\n");
+        write("unrelated/u.cue", "package unrelated\n");
+        write("cue.mod/pkg/k8s.io/api/core/v1/types.cue", "package v1\n");
+
+        let closure = cue_import_closure(&[root.join("schema.cue"), root.join("data.yaml")]);
+        let relative: Vec<_> = closure
+            .iter()
+            .map(|f| {
+                f.strip_prefix(root)
+                    .unwrap()
+                    .to_string_lossy()
+                    .replace('\\', "/")
+            })
+            .collect();
+        assert_eq!(
+            relative,
+            [
+                "base.cue",
+                "cue.mod/pkg/k8s.io/api/core/v1/types.cue",
+                "defs/defs.cue",
+                "schema.cue",
+                "shared/common.cue",
+            ]
+        );
+
+        assert_eq!(
+            imports("import \"list\"\nimporter: \"x\"\nx: 1 // import \"y\""),
+            ["list"]
+        );
+        assert_eq!(package_name("// c\npackage  app \n"), Some("app"));
+    }
+}
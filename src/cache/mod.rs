@@ -3,23 +3,72 @@
 
 //! Caching layer for pipeline results
 //!
-//! Provides file-based caching to avoid redundant stage executions.
+//! Provides file-based caching to avoid redundant stage executions, and a
+//! parse cache so the same config file isn't parsed twice.
 
+#[cfg(feature = "native")]
+mod artifacts;
+#[cfg(feature = "native")]
 mod filesystem;
 mod hash;
+mod imports;
+mod parse;
+mod timings;
 
+#[cfg(feature = "native")]
+pub use artifacts::{ArtifactStore, ArtifactWriter, ARTIFACTS_DIR};
+#[cfg(feature = "native")]
 pub use filesystem::FilesystemCache;
-pub use hash::ContentHasher;
+pub use hash::{hash_file, hash_string, ContentHasher};
+pub use imports::cue_import_closure;
+pub use parse::{DataFormat, ParseCache, ParseStats, PARSE_CACHE_ENV};
+pub use timings::{StageTiming, StageTimings, TIMINGS_FILE};
 
+#[cfg(feature = "native")]
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
-use std::time::SystemTime;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
 
+#[cfg(feature = "native")]
 use crate::errors::ConflowError;
 use crate::executors::ExecutionResult;
+#[cfg(feature = "native")]
 use crate::pipeline::Stage;
 
+/// Shared cache directory (`CONFLOW_CACHE_DIR` or the user cache, e.g.
+/// `~/.cache/conflow`)
+pub fn global_root() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os("CONFLOW_CACHE_DIR") {
+        return Some(PathBuf::from(dir));
+    }
+    directories::BaseDirs::new().map(|d| d.cache_dir().join("conflow"))
+}
+
+/// Namespace for a project in the shared cache: its directory name and a
+/// hash of its full path
+pub fn project_namespace(working_dir: &Path) -> String {
+    let path = working_dir
+        .canonicalize()
+        .unwrap_or_else(|_| working_dir.to_path_buf());
+    let name: String = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    let hash = hash_string(&path.display().to_string());
+    if name.is_empty() {
+        hash[..12].to_string()
+    } else {
+        format!("{}-{}", name, &hash[..12])
+    }
+}
+
 /// Trait for cache implementations
+#[cfg(feature = "native")]
 #[async_trait]
 pub trait Cache: Send + Sync {
     /// Get cached result for a stage
@@ -36,6 +85,53 @@ pub trait Cache: Send + Sync {
 
     /// Get cache statistics
     async fn stats(&self) -> Result<CacheStats, ConflowError>;
+
+    /// Remove stale entries, then evict entries beyond the limits
+    async fn gc(&self, limits: &CacheLimits) -> Result<GcReport, ConflowError>;
+
+    /// Check every entry against its checksums, removing corrupt ones if
+    /// `repair` is set
+    async fn verify(&self, repair: bool) -> Result<VerifyReport, ConflowError>;
+}
+
+/// Size and age limits for a cache
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheLimits {
+    /// Evict least recently used entries until the cache fits
+    pub max_size_bytes: Option<u64>,
+    /// Evict entries not used for this long
+    pub max_age: Option<Duration>,
+}
+
+impl CacheLimits {
+    /// Whether no limit is set
+    pub fn is_unlimited(&self) -> bool {
+        self.max_size_bytes.is_none() && self.max_age.is_none()
+    }
+}
+
+/// What a garbage collection removed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GcReport {
+    /// Entries whose outputs no longer exist
+    pub stale: usize,
+    /// Entries older than the age limit
+    pub expired: usize,
+    /// Least recently used entries removed to fit the size limit
+    pub evicted: usize,
+    /// Bytes freed
+    pub freed_bytes: u64,
+    /// Entries left
+    pub remaining: usize,
+    /// Bytes left
+    pub remaining_bytes: u64,
+}
+
+impl GcReport {
+    /// Entries removed for any reason
+    pub fn removed(&self) -> usize {
+        self.stale + self.expired + self.evicted
+    }
 }
 
 /// Cache statistics
@@ -49,6 +145,18 @@ pub struct CacheStats {
     pub oldest_entry: Option<SystemTime>,
     /// Newest entry timestamp
     pub newest_entry: Option<SystemTime>,
+    /// Disk usage by stage name
+    #[serde(default)]
+    pub stages: BTreeMap<String, StageUsage>,
+}
+
+/// Disk usage of one stage's cache entries
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StageUsage {
+    /// Number of cached entries
+    pub entries: usize,
+    /// Total size in bytes
+    pub size_bytes: u64,
 }
 
 impl CacheStats {
@@ -81,6 +189,97 @@ pub struct CachedEntry {
     pub cache_key: String,
     /// The execution result
     pub result: CachedResult,
+    /// BLAKE3 of the serialized result (absent in older entries)
+    #[serde(default)]
+    pub checksum: Option<String>,
+    /// BLAKE3 of each output file when the result was stored
+    #[serde(default)]
+    pub output_checksums: BTreeMap<PathBuf, String>,
+}
+
+impl CachedEntry {
+    /// Create an entry, recording checksums of the result and its outputs
+    pub fn new(stage_name: String, cache_key: String, result: &ExecutionResult) -> Self {
+        let result = CachedResult::from(result);
+        let output_checksums = result
+            .outputs
+            .iter()
+            .filter_map(|o| Some((o.clone(), hash_file(o).ok()?)))
+            .collect();
+        Self {
+            timestamp: SystemTime::now(),
+            stage_name,
+            cache_key,
+            checksum: Some(result.checksum()),
+            result,
+            output_checksums,
+        }
+    }
+
+    /// Why this entry can't be trusted, if it can't
+    ///
+    /// Outputs that no longer exist are stale rather than corrupt, and are
+    /// left to the caller.
+    pub fn verify(&self) -> Option<Corruption> {
+        if self
+            .checksum
+            .as_ref()
+            .is_some_and(|c| *c != self.result.checksum())
+        {
+            return Some(Corruption::ChecksumMismatch);
+        }
+        self.output_checksums
+            .iter()
+            .filter(|(path, _)| path.exists())
+            .find(|(path, expected)| hash_file(path).ok().as_ref() != Some(*expected))
+            .map(|(path, _)| Corruption::OutputChanged(path.clone()))
+    }
+}
+
+/// Why a cache entry can't be trusted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Corruption {
+    /// Not a readable cache entry
+    Unreadable(String),
+    /// The entry is filed under another key
+    KeyMismatch,
+    /// The result doesn't match its checksum
+    ChecksumMismatch,
+    /// An output file changed since the result was cached
+    OutputChanged(PathBuf),
+}
+
+impl std::fmt::Display for Corruption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unreadable(e) => write!(f, "unreadable: {}", e),
+            Self::KeyMismatch => write!(f, "filed under the wrong key"),
+            Self::ChecksumMismatch => write!(f, "result doesn't match its checksum"),
+            Self::OutputChanged(path) => write!(f, "output {} changed", path.display()),
+        }
+    }
+}
+
+/// A cache entry that failed verification
+#[derive(Debug, Clone)]
+pub struct CorruptEntry {
+    /// Entry file
+    pub path: PathBuf,
+    /// Stage the entry belongs to, if it could be read
+    pub stage: Option<String>,
+    /// What is wrong with it
+    pub problem: Corruption,
+}
+
+/// Result of verifying a cache
+#[derive(Debug, Clone, Default)]
+pub struct VerifyReport {
+    /// Entries checked
+    pub checked: usize,
+    /// Entries that failed verification
+    pub corrupt: Vec<CorruptEntry>,
+    /// Whether the corrupt entries were removed
+    pub repaired: bool,
 }
 
 /// Serializable execution result
@@ -94,6 +293,13 @@ pub struct CachedResult {
     pub duration_ms: u64,
 }
 
+impl CachedResult {
+    /// BLAKE3 of the serialized result
+    pub fn checksum(&self) -> String {
+        hash_string(&serde_json::to_string(self).unwrap_or_default())
+    }
+}
+
 impl From<&ExecutionResult> for CachedResult {
     fn from(result: &ExecutionResult) -> Self {
         Self {
@@ -0,0 +1,350 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Parse cache
+//!
+//! Keeps parsed JSON and YAML documents keyed by a hash of the file
+//! content, so duplicate detection, unused-key checks, reference rules and
+//! value tracing that read the same file parse it once per process (or once
+//! for the life of `conflow watch`). At most [`DEFAULT_CAPACITY`] files
+//! are kept in memory, dropping the least recently used first. When
+//! `CONFLOW_PARSE_CACHE` names a directory, parsed documents are also kept
+//! there between runs, under a subdirectory for the entry format so a
+//! conflow that parses differently never reads them back.
+
+use blake3::Hasher;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+
+use crate::errors::ConflowError;
+use crate::utils::yaml;
+
+/// Directory for on-disk parse cache entries
+pub const PARSE_CACHE_ENV: &str = "CONFLOW_PARSE_CACHE";
+
+/// Files kept in memory by default
+pub const DEFAULT_CAPACITY: usize = 1024;
+
+/// Version of the on-disk entries; bump it when parsing or the stored
+/// layout changes
+pub const FORMAT_VERSION: u32 = 1;
+
+static GLOBAL: LazyLock<ParseCache> = LazyLock::new(|| match std::env::var_os(PARSE_CACHE_ENV) {
+    Some(dir) if !dir.is_empty() => ParseCache::with_directory(PathBuf::from(dir)),
+    _ => ParseCache::new(),
+});
+
+/// Data formats the parse cache understands
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataFormat {
+    /// A single JSON document
+    Json,
+    /// A stream of one or more YAML documents
+    Yaml,
+}
+
+impl DataFormat {
+    /// Format from a file extension
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => Some(Self::Json),
+            Some("yaml" | "yml") => Some(Self::Yaml),
+            _ => None,
+        }
+    }
+
+    fn tag(self) -> &'static [u8] {
+        match self {
+            Self::Json => b"json\0",
+            Self::Yaml => b"yaml\0",
+        }
+    }
+}
+
+/// Parse cache hit and miss counts
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ParseStats {
+    /// Documents found in memory
+    pub hits: usize,
+    /// Documents read back from the cache directory
+    pub disk_hits: usize,
+    /// Files that had to be parsed
+    pub misses: usize,
+}
+
+/// Parsed documents keyed by content hash
+#[derive(Debug)]
+pub struct ParseCache {
+    entries: Mutex<Entries>,
+    capacity: usize,
+    directory: Option<PathBuf>,
+    hits: AtomicUsize,
+    disk_hits: AtomicUsize,
+    misses: AtomicUsize,
+}
+
+/// In-memory entries, each with the tick it was last used at
+#[derive(Debug, Default)]
+struct Entries {
+    documents: HashMap<String, (Arc<Vec<Value>>, u64)>,
+    tick: u64,
+}
+
+impl Entries {
+    fn get(&mut self, key: &str) -> Option<Arc<Vec<Value>>> {
+        self.tick += 1;
+        let (documents, used) = self.documents.get_mut(key)?;
+        *used = self.tick;
+        Some(Arc::clone(documents))
+    }
+
+    /// Insert an entry, dropping the least recently used beyond `capacity`
+    fn insert(&mut self, key: String, documents: Arc<Vec<Value>>, capacity: usize) {
+        self.tick += 1;
+        self.documents.insert(key, (documents, self.tick));
+        while self.documents.len() > capacity {
+            let oldest = self
+                .documents
+                .iter()
+                .min_by_key(|(_, (_, used))| *used)
+                .map(|(key, _)| key.clone());
+            let Some(oldest) = oldest else {
+                break;
+            };
+            self.documents.remove(&oldest);
+        }
+    }
+}
+
+impl Default for ParseCache {
+    fn default() -> Self {
+        Self {
+            entries: Mutex::default(),
+            capacity: DEFAULT_CAPACITY,
+            directory: None,
+            hits: AtomicUsize::new(0),
+            disk_hits: AtomicUsize::new(0),
+            misses: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl ParseCache {
+    /// Create an in-memory parse cache
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep at most `capacity` files in memory
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    /// Create a parse cache that also keeps entries in `directory`
+    pub fn with_directory(directory: PathBuf) -> Self {
+        Self {
+            directory: Some(directory),
+            ..Self::default()
+        }
+    }
+
+    /// The process-wide parse cache
+    pub fn global() -> &'static Self {
+        &GLOBAL
+    }
+
+    /// Documents of a JSON or YAML file, parsing it only if this content
+    /// hasn't been seen before
+    ///
+    /// Parse errors aren't cached, so they always name the file passed in.
+    pub fn documents(&self, path: &Path, content: &str) -> Result<Arc<Vec<Value>>, ConflowError> {
+        let format =
+            DataFormat::from_path(path).ok_or_else(|| ConflowError::FormatDetectionFailed {
+                path: path.to_path_buf(),
+            })?;
+        let key = cache_key(format, content);
+
+        if let Some(documents) = self.lock().get(&key) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(documents);
+        }
+
+        let documents = match self.read(&key) {
+            Some(documents) => {
+                self.disk_hits.fetch_add(1, Ordering::Relaxed);
+                tracing::debug!(file = %path.display(), "parse cache hit on disk");
+                documents
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                let documents = parse(format, path, content)?;
+                self.write(&key, &documents);
+                documents
+            }
+        };
+
+        crate::report::learn(&documents);
+        let documents = Arc::new(documents);
+        self.lock()
+            .insert(key, Arc::clone(&documents), self.capacity);
+        Ok(documents)
+    }
+
+    /// Hit and miss counts so far
+    pub fn stats(&self) -> ParseStats {
+        ParseStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            disk_hits: self.disk_hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Files kept in memory
+    pub fn len(&self) -> usize {
+        self.lock().documents.len()
+    }
+
+    /// Whether no file is kept in memory
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drop every in-memory entry
+    pub fn clear(&self) {
+        self.lock().documents.clear();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Entries> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn entry_path(&self, key: &str) -> Option<PathBuf> {
+        let (prefix, rest) = key.split_at(2);
+        Some(
+            self.directory
+                .as_ref()?
+                .join(format!("v{}", FORMAT_VERSION))
+                .join(prefix)
+                .join(format!("{}.json", rest)),
+        )
+    }
+
+    fn read(&self, key: &str) -> Option<Vec<Value>> {
+        let content = std::fs::read_to_string(self.entry_path(key)?).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    /// Store an entry on disk; failures only cost a re-parse next time
+    fn write(&self, key: &str, documents: &[Value]) {
+        let Some(path) = self.entry_path(key) else {
+            return;
+        };
+        let stored = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(&path, serde_json::to_vec(documents)?));
+        if let Err(e) = stored {
+            tracing::debug!(path = %path.display(), error = %e, "failed to store parse cache entry");
+        }
+    }
+}
+
+fn cache_key(format: DataFormat, content: &str) -> String {
+    let mut hasher = Hasher::new();
+    hasher.update(format.tag());
+    hasher.update(content.as_bytes());
+    hasher.finalize().to_hex().to_string()
+}
+
+fn parse(format: DataFormat, path: &Path, content: &str) -> Result<Vec<Value>, ConflowError> {
+    match format {
+        DataFormat::Json => Ok(vec![serde_json::from_str(content)?]),
+        DataFormat::Yaml => yaml::parse_documents(content, &path.display().to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_content_parsed_once() {
+        let cache = ParseCache::new();
+        let a = cache
+            .documents(Path::new("a.yaml"), "name: web\n---\nname: db\n")
+            .unwrap();
+        let b = cache
+            .documents(Path::new("copy/a.yml"), "name: web\n---\nname: db\n")
+            .unwrap();
+
+        assert!(Arc::ptr_eq(&a, &b));
+        assert_eq!(a.len(), 2);
+        assert_eq!(
+            cache.stats(),
+            ParseStats {
+                hits: 1,
+                disk_hits: 0,
+                misses: 1
+            }
+        );
+
+        // The same text as JSON is a different entry
+        cache.documents(Path::new("a.json"), "[1, 2]").unwrap();
+        cache.documents(Path::new("b.yaml"), "[1, 2]").unwrap();
+        assert_eq!(cache.stats().misses, 3);
+    }
+
+    #[test]
+    fn test_errors_are_not_cached() {
+        let cache = ParseCache::new();
+        assert!(cache.documents(Path::new("bad.json"), "{").is_err());
+        assert!(cache.documents(Path::new("bad.json"), "{").is_err());
+        assert_eq!(cache.stats().misses, 2);
+        assert!(matches!(
+            cache.documents(Path::new("a.toml"), "a = 1"),
+            Err(ConflowError::FormatDetectionFailed { .. })
+        ));
+    }
+
+    #[test]
+    fn test_least_recently_used_dropped() {
+        let cache = ParseCache::new().with_capacity(2);
+        cache.documents(Path::new("a.yaml"), "a: 1\n").unwrap();
+        cache.documents(Path::new("b.yaml"), "b: 1\n").unwrap();
+        cache.documents(Path::new("a.yaml"), "a: 1\n").unwrap();
+        cache.documents(Path::new("c.yaml"), "c: 1\n").unwrap();
+        assert_eq!(cache.len(), 2);
+
+        // b was used least recently, so only it is parsed again
+        cache.documents(Path::new("a.yaml"), "a: 1\n").unwrap();
+        cache.documents(Path::new("c.yaml"), "c: 1\n").unwrap();
+        assert_eq!(cache.stats().misses, 3);
+        cache.documents(Path::new("b.yaml"), "b: 1\n").unwrap();
+        assert_eq!(cache.stats().misses, 4);
+    }
+
+    #[test]
+    fn test_directory_survives_process() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let content = "replicas: 3\n";
+
+        let first = ParseCache::with_directory(temp.path().to_path_buf());
+        first.documents(Path::new("values.yaml"), content).unwrap();
+        assert_eq!(first.stats().misses, 1);
+
+        let second = ParseCache::with_directory(temp.path().to_path_buf());
+        let documents = second.documents(Path::new("values.yaml"), content).unwrap();
+        assert_eq!(documents[0]["replicas"], 3);
+        assert_eq!(second.stats().disk_hits, 1);
+        assert_eq!(second.stats().misses, 0);
+
+        // Entries are kept by format version
+        let versioned = temp.path().join(format!("v{}", FORMAT_VERSION));
+        assert_eq!(std::fs::read_dir(temp.path()).unwrap().count(), 1);
+        assert!(versioned.is_dir());
+    }
+}
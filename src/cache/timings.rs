@@ -0,0 +1,136 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Stage timing history
+//!
+//! Remembers how long each stage took on recent runs, next to the cache
+//! entries, so progress output can estimate how long a run has left.
+//! Cache hits aren't recorded; they say nothing about the stage's real cost.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::errors::ConflowError;
+
+/// Timing history file, relative to the cache directory
+pub const TIMINGS_FILE: &str = "timings.json";
+
+/// Weight of the newest run in the moving average
+const SMOOTHING: f64 = 0.3;
+
+/// How long one stage has taken
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct StageTiming {
+    /// Runs recorded
+    pub runs: u32,
+    /// Exponential moving average, in milliseconds
+    pub average_ms: f64,
+    /// Most recent run, in milliseconds
+    pub last_ms: u64,
+}
+
+/// Durations of past stage runs, by stage name
+#[derive(Debug, Clone, Default)]
+pub struct StageTimings {
+    path: Option<PathBuf>,
+    stages: BTreeMap<String, StageTiming>,
+}
+
+impl StageTimings {
+    /// Load the history kept in `cache_dir`, starting empty if there is none
+    pub fn load(cache_dir: &Path) -> Self {
+        let path = cache_dir.join(TIMINGS_FILE);
+        let stages = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        Self {
+            path: Some(path),
+            stages,
+        }
+    }
+
+    /// Expected duration of a stage, if it has run before
+    pub fn estimate(&self, stage: &str) -> Option<Duration> {
+        self.stages
+            .get(stage)
+            .map(|t| Duration::from_secs_f64(t.average_ms / 1000.0))
+    }
+
+    /// History of one stage
+    pub fn get(&self, stage: &str) -> Option<&StageTiming> {
+        self.stages.get(stage)
+    }
+
+    /// Add a run of `stage` to the history
+    pub fn record(&mut self, stage: &str, duration: Duration) {
+        let ms = duration.as_secs_f64() * 1000.0;
+        let timing = self
+            .stages
+            .entry(stage.to_string())
+            .and_modify(|t| {
+                t.runs += 1;
+                t.average_ms += SMOOTHING * (ms - t.average_ms);
+            })
+            .or_insert(StageTiming {
+                runs: 1,
+                average_ms: ms,
+                last_ms: 0,
+            });
+        timing.last_ms = duration.as_millis() as u64;
+    }
+
+    /// Write the history back to the cache directory
+    ///
+    /// Written to a temporary file first, so concurrent readers never see a
+    /// partial file.
+    pub fn save(&self) -> Result<(), ConflowError> {
+        let Some(ref path) = self.path else {
+            return Ok(());
+        };
+        let error = |e: std::io::Error| ConflowError::CacheError {
+            message: format!("Failed to save stage timings: {}", e),
+        };
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(error)?;
+        }
+        let json =
+            serde_json::to_string_pretty(&self.stages).map_err(|e| ConflowError::CacheError {
+                message: format!("Failed to serialize stage timings: {}", e),
+            })?;
+        let tmp = path.with_extension(format!("{}.tmp", std::process::id()));
+        std::fs::write(&tmp, json).map_err(error)?;
+        std::fs::rename(&tmp, path).map_err(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timings_average_and_persist() {
+        let temp = tempfile::TempDir::new().unwrap();
+
+        let mut timings = StageTimings::load(temp.path());
+        assert_eq!(timings.estimate("build"), None);
+
+        timings.record("build", Duration::from_secs(10));
+        timings.record("build", Duration::from_secs(20));
+        let build = timings.get("build").unwrap();
+        assert_eq!(build.runs, 2);
+        assert_eq!(build.last_ms, 20_000);
+        assert_eq!(timings.estimate("build"), Some(Duration::from_secs(13)));
+        timings.save().unwrap();
+
+        let loaded = StageTimings::load(temp.path());
+        assert_eq!(loaded.estimate("build"), Some(Duration::from_secs(13)));
+
+        // A damaged file is treated as no history
+        std::fs::write(temp.path().join(TIMINGS_FILE), "{").unwrap();
+        assert_eq!(StageTimings::load(temp.path()).estimate("build"), None);
+    }
+}
@@ -0,0 +1,131 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Agent command - runs stages sent by `conflow run --coordinator` to agents
+//! offering their `runs_on` labels
+
+use miette::Result;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+/// Default cap on the bytes a runner may upload for one stage
+pub const DEFAULT_MAX_UPLOAD: u64 = 1 << 30;
+
+/// Options for the agent command
+#[derive(Debug, Clone)]
+pub struct AgentOptions {
+    /// Address to listen on
+    pub listen: SocketAddr,
+    /// URL runners reach the agent at
+    pub advertise: Option<String>,
+    /// PEM certificate the agent serves TLS with
+    pub tls_cert: PathBuf,
+    /// PEM private key of `tls_cert`
+    pub tls_key: PathBuf,
+    /// Labels stages can ask for with runs_on
+    pub labels: Vec<String>,
+    /// Coordinator to register with
+    pub coordinator: Option<String>,
+    /// Key to register with the coordinator by
+    pub agent_key: Option<String>,
+    /// Keys runners must send
+    pub runner_keys: Vec<String>,
+    /// Largest upload of files for one stage, in bytes
+    pub max_upload: u64,
+}
+
+/// Run the agent command
+pub async fn run(opts: AgentOptions) -> Result<()> {
+    #[cfg(not(feature = "grpc"))]
+    {
+        let _ = opts;
+        Err(miette::miette!(
+            help = "rebuild with `cargo install conflow --features grpc`",
+            "This conflow was built without gRPC support"
+        ))
+    }
+
+    #[cfg(feature = "grpc")]
+    {
+        use crate::cache::ArtifactStore;
+        use crate::grpc::{self, AgentConfig};
+        use colored::Colorize;
+
+        let AgentOptions {
+            listen,
+            advertise,
+            tls_cert,
+            tls_key,
+            labels,
+            coordinator,
+            agent_key,
+            runner_keys,
+            max_upload,
+        } = opts;
+
+        let runner_keys: Vec<String> = runner_keys
+            .into_iter()
+            .map(|key| key.trim().to_string())
+            .filter(|key| !key.is_empty())
+            .collect();
+        if runner_keys.is_empty() {
+            return Err(miette::miette!(
+                help = "pass --runner-key or set CONFLOW_RUNNER_KEYS",
+                "No runner key configured"
+            ));
+        }
+        let agent_key = agent_key
+            .map(|key| key.trim().to_string())
+            .filter(|key| !key.is_empty());
+        if coordinator.is_some() && agent_key.is_none() {
+            return Err(miette::miette!(
+                help = "pass --agent-key or set CONFLOW_AGENT_KEY",
+                "No agent key to register with the coordinator"
+            ));
+        }
+
+        let read = |path: &PathBuf| {
+            std::fs::read(path)
+                .map_err(|e| miette::miette!("Failed to read {}: {}", path.display(), e))
+        };
+        let identity = tonic::transport::Identity::from_pem(read(&tls_cert)?, read(&tls_key)?);
+
+        let config = AgentConfig {
+            labels,
+            runner_keys,
+            identity,
+            max_upload,
+            store: ArtifactStore::shared()?,
+        };
+        let advertise = advertise.unwrap_or_else(|| format!("https://{}", listen));
+        let info = grpc::describe(&config, &advertise);
+
+        let listener = tokio::net::TcpListener::bind(listen)
+            .await
+            .map_err(|e| miette::miette!("Failed to listen on {}: {}", listen, e))?;
+
+        println!("{} Agent serving on {}", "✓".green(), advertise.cyan());
+        println!("  Labels: {}", config.labels.join(", ").bold());
+        println!("  Tools: {}", info.tools.join(", "));
+        println!("  {} runner key(s) accepted", config.runner_keys.len());
+        println!("  Uploads are limited to {} bytes per stage", max_upload);
+        match &coordinator {
+            Some(url) => println!("  Registering with {}", url.cyan()),
+            None => println!(
+                "{} No --coordinator; runners won't find this agent",
+                "⚠".yellow()
+            ),
+        }
+        println!("Press {} to stop.", "Ctrl+C".cyan());
+
+        let registration = coordinator
+            .zip(agent_key)
+            .map(|(url, key)| tokio::spawn(grpc::keep_registered(url, info, key)));
+        let served = grpc::serve_agent(listener, config).await;
+        if let Some(registration) = registration {
+            registration.abort();
+        }
+        served?;
+        Ok(())
+    }
+}
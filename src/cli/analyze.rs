@@ -5,49 +5,221 @@
 
 use colored::Colorize;
 use miette::Result;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use super::OutputFormat;
-use crate::analyzer::ConfigAnalyzer;
+use super::{AbstractionFormat, OutputFormat};
+use crate::analyzer::{
+    dotted, find_duplicates_in_files, ConfigAnalyzer, DuplicateGroup, TemplateDialect,
+};
+use crate::utils::walk::Walker;
 
 /// Run the analyze command
-pub async fn run(files: Vec<PathBuf>, format: OutputFormat, verbose: bool) -> Result<()> {
+pub async fn run(
+    files: Vec<PathBuf>,
+    format: OutputFormat,
+    emit: Option<AbstractionFormat>,
+    emit_dir: PathBuf,
+    jobs: Option<usize>,
+    verbose: bool,
+) -> Result<()> {
     if files.is_empty() {
         return Err(miette::miette!(
             "No files specified.\n\n\
-             Usage: conflow analyze <file>..."
+             Usage: conflow analyze <file|dir>..."
         ));
     }
 
-    let analyzer = ConfigAnalyzer::new();
-
-    for file in &files {
-        if !file.exists() {
+    let walker = Walker::load(Path::new("."));
+    let mut existing = Vec::new();
+    for file in files {
+        if file.is_dir() {
+            existing.extend(config_files(&file, &walker)?);
+        } else if file.exists() {
+            existing.push(file);
+        } else {
             eprintln!("{}: File not found: {}", "Warning".yellow(), file.display());
-            continue;
         }
+    }
 
-        match analyzer.analyze(file).await {
-            Ok(analysis) => {
-                match format {
-                    OutputFormat::Text => print_text_analysis(file, &analysis, verbose),
-                    OutputFormat::Json => print_json_analysis(file, &analysis)?,
-                }
-            }
+    // Parse and score on a work-stealing pool; results come back in order
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(jobs.unwrap_or(0))
+        .build()
+        .map_err(|e| miette::miette!("Failed to start analysis threads: {}", e))?;
+    let analyzer = ConfigAnalyzer::new();
+    let (analyses, groups) = pool.install(|| {
+        (
+            analyzer.analyze_files(&existing),
+            find_duplicates_in_files(&existing),
+        )
+    });
+
+    for (file, result) in existing.iter().zip(analyses) {
+        match result {
+            Ok(analysis) => match format {
+                OutputFormat::Text => print_text_analysis(file, &analysis, verbose),
+                OutputFormat::Json => print_json_analysis(file, &analysis)?,
+            },
             Err(e) => {
-                eprintln!("{}: Failed to analyze {}: {}", "Error".red(), file.display(), e);
+                eprintln!(
+                    "{}: Failed to analyze {}: {}",
+                    "Error".red(),
+                    file.display(),
+                    e
+                );
             }
         }
     }
 
+    // Near-duplicate blocks across all the files
+    match format {
+        OutputFormat::Text => print_text_duplicates(&groups, emit),
+        OutputFormat::Json if !groups.is_empty() => print_json_duplicates(&groups)?,
+        OutputFormat::Json => {}
+    }
+
+    if let Some(emit) = emit {
+        emit_abstractions(&groups, emit, &emit_dir, verbose)?;
+    }
+
     Ok(())
 }
 
-fn print_text_analysis(
-    file: &PathBuf,
-    analysis: &crate::analyzer::Analysis,
+/// Config files under `dir` in path order, as the project's walk settings allow
+pub(super) fn config_files(dir: &Path, walker: &Walker) -> Result<Vec<PathBuf>> {
+    let files = walker.files(dir)?;
+    Ok(files
+        .into_iter()
+        .filter(|path| {
+            matches!(
+                path.extension().and_then(|e| e.to_str()),
+                Some("json" | "yaml" | "yml" | "toml" | "cue" | "ncl")
+            )
+        })
+        .collect())
+}
+
+fn print_text_duplicates(groups: &[DuplicateGroup], emit: Option<AbstractionFormat>) {
+    if groups.is_empty() {
+        return;
+    }
+
+    println!("{}", "Duplicate blocks".bold());
+    println!("{}", "═".repeat(50));
+
+    for group in groups {
+        println!();
+        println!(
+            "{} {} near-identical {} blocks, {} of {} values shared",
+            "⚠".yellow(),
+            group.members.len(),
+            group.name.bold(),
+            group.common,
+            group.leaves()
+        );
+        for member in group.members.iter().take(5) {
+            println!("    • {}", member);
+        }
+        if group.members.len() > 5 {
+            println!("    • … and {} more", group.members.len() - 5);
+        }
+        let varying: Vec<String> = group.parameters.iter().map(|p| dotted(&p.path)).collect();
+        println!("  Varying: {}", varying.join(", "));
+        println!();
+
+        let suggestion = match emit {
+            Some(AbstractionFormat::Nickel) => group.nickel_function(),
+            _ => group.cue_definition(),
+        };
+        for line in suggestion.lines() {
+            println!("    {}", line.dimmed());
+        }
+    }
+
+    println!();
+    if emit.is_none() {
+        println!(
+            "  {} write the abstractions with {}",
+            "→".blue(),
+            "conflow analyze --emit cue|nickel".cyan()
+        );
+    }
+    println!();
+}
+
+fn print_json_duplicates(groups: &[DuplicateGroup]) -> Result<()> {
+    let json = serde_json::json!({
+        "duplicates": groups.iter().map(|g| {
+            serde_json::json!({
+                "name": g.name,
+                "members": g.members.iter().map(|m| m.to_string()).collect::<Vec<_>>(),
+                "common_values": g.common,
+                "parameters": g.parameters.iter().map(|p| {
+                    serde_json::json!({
+                        "name": p.name,
+                        "path": dotted(&p.path),
+                        "values": p.values,
+                    })
+                }).collect::<Vec<_>>(),
+                "cue": g.cue_definition(),
+                "nickel": g.nickel_function(),
+            })
+        }).collect::<Vec<_>>(),
+    });
+
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&json)
+            .map_err(|e| { miette::miette!("Failed to serialize JSON: {}", e) })?
+    );
+
+    Ok(())
+}
+
+fn emit_abstractions(
+    groups: &[DuplicateGroup],
+    emit: AbstractionFormat,
+    dir: &Path,
     verbose: bool,
-) {
+) -> Result<()> {
+    if groups.is_empty() {
+        if verbose {
+            println!("{}", "No duplicate blocks to abstract.".dimmed());
+        }
+        return Ok(());
+    }
+
+    std::fs::create_dir_all(dir)
+        .map_err(|e| miette::miette!("Failed to create {}: {}", dir.display(), e))?;
+
+    let mut used = std::collections::HashSet::new();
+    for group in groups {
+        let (ext, content) = match emit {
+            AbstractionFormat::Cue => ("cue", group.to_cue()),
+            AbstractionFormat::Nickel => ("ncl", group.to_nickel()),
+        };
+        let stem = group.file_stem();
+        let mut path = dir.join(format!("{}.{}", stem, ext));
+        let mut n = 1;
+        while !used.insert(path.clone()) {
+            n += 1;
+            path = dir.join(format!("{}-{}.{}", stem, n, ext));
+        }
+
+        std::fs::write(&path, content)
+            .map_err(|e| miette::miette!("Failed to write {}: {}", path.display(), e))?;
+        println!(
+            "{} Wrote {} ({} blocks)",
+            "✓".green(),
+            path.display().to_string().cyan(),
+            group.members.len()
+        );
+    }
+
+    Ok(())
+}
+
+fn print_text_analysis(file: &Path, analysis: &crate::analyzer::Analysis, verbose: bool) {
     println!();
     println!("{}: {}", "Analyzing".bold(), file.display());
     println!("{}", "═".repeat(50));
@@ -60,6 +232,12 @@ fn print_text_analysis(
         "Size".bold(),
         analysis.complexity.line_count
     );
+    if analysis.documents > 1 {
+        println!("{}: {}", "Documents".bold(), analysis.documents);
+    }
+    if let Some(dialect) = analysis.template {
+        println!("{}: {}", "Template".bold(), dialect);
+    }
     println!();
 
     // Complexity analysis
@@ -68,17 +246,34 @@ fn print_text_analysis(
     print_check("Functions", analysis.complexity.has_functions);
     print_check("Constraints", analysis.complexity.has_constraints);
     print_check("Nested structures", analysis.complexity.nesting_depth > 2);
-    println!(
-        "  Nesting depth: {}",
-        analysis.complexity.nesting_depth
-    );
+    println!("  Nesting depth: {}", analysis.complexity.nesting_depth);
+    let aliases = &analysis.complexity.aliases;
+    if aliases.anchors > 0 || aliases.merge_keys > 0 {
+        print_check("Anchors/aliases", aliases.is_heavy());
+        println!(
+            "  {} anchor(s), {} alias(es), {} merge key(s); expands to {}",
+            aliases.anchors,
+            aliases.aliases,
+            aliases.merge_keys,
+            crate::analyzer::format_bytes(aliases.expanded_bytes)
+        );
+    }
     println!();
 
+    for warning in &analysis.warnings {
+        println!("{} {}", "⚠".yellow(), warning);
+    }
+    if !analysis.warnings.is_empty() {
+        println!();
+    }
+
     // Recommendation
     println!(
         "{}: Use {}",
         "Recommendation".bold(),
-        format!("{:?}", analysis.recommendation.primary).green().bold()
+        format!("{:?}", analysis.recommendation.primary)
+            .green()
+            .bold()
     );
     println!("{}", "═".repeat(50));
     println!();
@@ -134,26 +329,37 @@ fn print_text_analysis(
 
     println!();
     println!("{}:", "Next steps".bold());
-    println!("  1. {}", "conflow init --template <template>".cyan());
+    let init = match analysis.template {
+        Some(TemplateDialect::Helm | TemplateDialect::GoTemplate) => "conflow init --template helm",
+        _ => "conflow init --template <template>",
+    };
+    println!("  1. {}", init.cyan());
     println!("  2. Add your configuration files");
     println!("  3. {}", "conflow run".cyan());
     println!();
 }
 
-fn print_json_analysis(
-    file: &PathBuf,
-    analysis: &crate::analyzer::Analysis,
-) -> Result<()> {
+fn print_json_analysis(file: &Path, analysis: &crate::analyzer::Analysis) -> Result<()> {
     let json = serde_json::json!({
         "file": file.display().to_string(),
         "format": format!("{:?}", analysis.format),
+        "documents": analysis.documents,
         "complexity": {
             "has_logic": analysis.complexity.has_logic,
             "has_functions": analysis.complexity.has_functions,
             "has_constraints": analysis.complexity.has_constraints,
             "line_count": analysis.complexity.line_count,
             "nesting_depth": analysis.complexity.nesting_depth,
+            "aliases": {
+                "anchors": analysis.complexity.aliases.anchors,
+                "aliases": analysis.complexity.aliases.aliases,
+                "merge_keys": analysis.complexity.aliases.merge_keys,
+                "source_bytes": analysis.complexity.aliases.source_bytes,
+                "expanded_bytes": analysis.complexity.aliases.expanded_bytes,
+            },
         },
+        "warnings": analysis.warnings,
+        "template": analysis.template,
         "recommendation": {
             "primary": format!("{:?}", analysis.recommendation.primary),
             "rationale": analysis.recommendation.rationale,
@@ -167,9 +373,11 @@ fn print_json_analysis(
         }
     });
 
-    println!("{}", serde_json::to_string_pretty(&json).map_err(|e| {
-        miette::miette!("Failed to serialize JSON: {}", e)
-    })?);
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&json)
+            .map_err(|e| { miette::miette!("Failed to serialize JSON: {}", e) })?
+    );
 
     Ok(())
 }
@@ -0,0 +1,103 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Audit command - query the log of file changes conflow made
+
+use colored::Colorize;
+use miette::Result;
+use std::path::PathBuf;
+
+use super::OutputFormat;
+use crate::audit::{self, AuditEntry, Change, AUDIT_LOG};
+use crate::ConflowError;
+
+/// Run the audit command
+pub async fn run(
+    id: Option<u64>,
+    path: Option<PathBuf>,
+    actor: Option<String>,
+    since: Option<String>,
+    verify: bool,
+    format: OutputFormat,
+) -> Result<()> {
+    let working_dir = std::env::current_dir().map_err(|e| ConflowError::Io {
+        message: e.to_string(),
+    })?;
+    let entries = audit::entries(&working_dir)?;
+
+    if verify {
+        return match audit::verify(&entries) {
+            None => {
+                println!(
+                    "{} {} is intact ({} entries)",
+                    "✓".green(),
+                    AUDIT_LOG,
+                    entries.len()
+                );
+                Ok(())
+            }
+            Some(line) => Err(miette::miette!(
+                help = "the log is append-only; restore it from version control or a backup",
+                "{} was altered at entry {}",
+                AUDIT_LOG,
+                line
+            )),
+        };
+    }
+
+    let entries: Vec<&AuditEntry> = entries
+        .iter()
+        .filter(|e| id.is_none_or(|id| e.id == id))
+        .filter(|e| path.as_ref().is_none_or(|p| e.path.starts_with(p)))
+        .filter(|e| actor.as_ref().is_none_or(|a| &e.actor == a))
+        .filter(|e| {
+            since
+                .as_ref()
+                .is_none_or(|s| e.timestamp.as_str() >= s.as_str())
+        })
+        .collect();
+
+    if format == OutputFormat::Json {
+        let json = serde_json::to_string_pretty(&entries).map_err(ConflowError::from)?;
+        println!("{}", json);
+        return Ok(());
+    }
+    if entries.is_empty() {
+        println!("{} No changes recorded in {}", "⚠".yellow(), AUDIT_LOG);
+        return Ok(());
+    }
+
+    let mut last = None;
+    for entry in entries {
+        if last != Some(entry.id) {
+            println!(
+                "{} {}  {}  {}",
+                format!("#{}", entry.id).bold(),
+                entry.timestamp.dimmed(),
+                entry.actor.cyan(),
+                entry.action
+            );
+            last = Some(entry.id);
+        }
+        let change = match entry.change {
+            Change::Created => entry.change.to_string().green(),
+            Change::Modified => entry.change.to_string().yellow(),
+            Change::Deleted => entry.change.to_string().red(),
+        };
+        let digest = entry.after.as_ref().or(entry.before.as_ref());
+        let digest = digest.map(|d| short(d)).unwrap_or_default();
+        println!(
+            "    {:<8}  {}  {}",
+            change,
+            entry.path.display(),
+            digest.dimmed()
+        );
+    }
+    Ok(())
+}
+
+/// A digest cut to its first 12 hex digits
+fn short(digest: &str) -> String {
+    let (algorithm, hex) = digest.split_once(':').unwrap_or(("", digest));
+    format!("{}:{}", algorithm, &hex[..hex.len().min(12)])
+}
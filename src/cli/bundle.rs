@@ -0,0 +1,91 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Bundle command - pack what a project fetches for air-gapped machines
+
+use colored::Colorize;
+use miette::Result;
+
+use super::BundleAction;
+use crate::bundle::{self, BundleManifest, Caches};
+use crate::errors::ConflowError;
+use crate::pipeline::Pipeline;
+use crate::toolchain;
+
+/// Run the bundle command
+pub async fn run(action: BundleAction) -> Result<()> {
+    let caches = Caches::open()?;
+    match action {
+        BundleAction::Create { output, pipeline } => {
+            let working_dir = std::env::current_dir().map_err(|e| ConflowError::Io {
+                message: e.to_string(),
+            })?;
+            let pipeline = match pipeline.exists() {
+                true => Some(Pipeline::from_file(&pipeline)?),
+                false => None,
+            };
+            let manifest =
+                bundle::create(&working_dir, pipeline.as_ref(), &output, &caches).await?;
+            print_contents(&manifest);
+            println!("{} Wrote {}", "✓".green(), output.display());
+        }
+        BundleAction::Export { output, pipeline } => {
+            let working_dir = std::env::current_dir().map_err(|e| ConflowError::Io {
+                message: e.to_string(),
+            })?;
+            if !pipeline.exists() {
+                return Err(ConflowError::PipelineNotFound { path: pipeline }.into());
+            }
+            let manifest = bundle::export(&working_dir, &pipeline, &output, &caches).await?;
+            print_contents(&manifest);
+            println!(
+                "{} Wrote {} ({} files); run it with 'conflow run --from-bundle {}'",
+                "✓".green(),
+                output.display(),
+                manifest.files.len(),
+                output.display()
+            );
+        }
+        BundleAction::Load { bundle } => {
+            let manifest = bundle::load(&bundle, &caches)?;
+            print_contents(&manifest);
+            if !manifest.runs_here() {
+                println!(
+                    "{} Tools in this bundle are built for {}, not {}",
+                    "⚠".yellow(),
+                    manifest.platform,
+                    toolchain::platform()
+                );
+            }
+            println!(
+                "{} Loaded {}; runs with --offline use it",
+                "✓".green(),
+                bundle.display()
+            );
+        }
+    }
+    Ok(())
+}
+
+fn print_contents(manifest: &BundleManifest) {
+    let sections = [
+        ("Tools", &manifest.tools),
+        ("Profiles", &manifest.profiles),
+        ("Templates", &manifest.templates),
+    ];
+    let versions: Vec<String> = manifest
+        .versions
+        .iter()
+        .map(|(tool, version)| format!("{}: {}", tool, version))
+        .collect();
+    let sections = sections.into_iter().chain([("Tool versions", &versions)]);
+    for (title, items) in sections {
+        if items.is_empty() {
+            continue;
+        }
+        println!("{}:", title.bold());
+        for item in items {
+            println!("  {}", item);
+        }
+    }
+}
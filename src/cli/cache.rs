@@ -8,15 +8,20 @@ use miette::Result;
 use std::io::{self, Write};
 
 use super::CacheAction;
+use crate::analyzer::format_bytes;
 use crate::cache::{Cache, FilesystemCache};
+use crate::pipeline::{CacheConfig, CacheScope, Pipeline};
 
 /// Run the cache command
-pub async fn run(action: CacheAction, _verbose: bool) -> Result<()> {
-    let working_dir = std::env::current_dir().map_err(|e| {
-        miette::miette!("Failed to get current directory: {}", e)
-    })?;
-
-    let cache_dir = working_dir.join(".conflow").join("cache");
+pub async fn run(action: CacheAction, verbose: bool) -> Result<()> {
+    let working_dir = std::env::current_dir()
+        .map_err(|e| miette::miette!("Failed to get current directory: {}", e))?;
+
+    // The pipeline's cache settings, if there is a readable pipeline
+    let config = Pipeline::from_file(&working_dir.join(".conflow.yaml"))
+        .map(|p| p.cache)
+        .unwrap_or_default();
+    let cache_dir = config.path(&working_dir)?;
     let cache = FilesystemCache::new(cache_dir.clone(), working_dir)?;
 
     match action {
@@ -26,6 +31,9 @@ pub async fn run(action: CacheAction, _verbose: bool) -> Result<()> {
             println!("{}", "Cache Statistics".bold());
             println!("{}", "═".repeat(40));
             println!("  Location: {}", cache_dir.display());
+            if config.scope == CacheScope::Global {
+                println!("  Scope:    shared user cache");
+            }
             println!("  Entries:  {}", stats.entries);
             println!("  Size:     {}", stats.formatted_size());
 
@@ -41,6 +49,74 @@ pub async fn run(action: CacheAction, _verbose: bool) -> Result<()> {
                 }
             }
 
+            if let Some(limit) = config.max_size_mb {
+                println!("  Limit:    {} MB", limit);
+            }
+
+            if !stats.stages.is_empty() {
+                println!();
+                println!("{}", "By stage".bold());
+                let mut stages: Vec<_> = stats.stages.iter().collect();
+                stages.sort_by(|a, b| b.1.size_bytes.cmp(&a.1.size_bytes).then(a.0.cmp(b.0)));
+                for (stage, usage) in stages {
+                    println!(
+                        "  {:<24} {:>10}  {} entries",
+                        stage,
+                        format_bytes(usage.size_bytes),
+                        usage.entries
+                    );
+                }
+            }
+
+            Ok(())
+        }
+
+        CacheAction::Gc {
+            max_size_mb,
+            max_age_days,
+        } => {
+            let limits = CacheConfig {
+                max_size_mb: max_size_mb.or(config.max_size_mb),
+                max_age_days: max_age_days.or(config.max_age_days),
+                ..config
+            }
+            .limits();
+            let report = cache.gc(&limits).await?;
+
+            if report.removed() == 0 {
+                println!(
+                    "{} Nothing to collect ({} entries, {})",
+                    "✓".green(),
+                    report.remaining,
+                    format_bytes(report.remaining_bytes)
+                );
+                return Ok(());
+            }
+
+            println!(
+                "{} Removed {} entries, freed {}",
+                "✓".green(),
+                report.removed(),
+                format_bytes(report.freed_bytes)
+            );
+            if verbose {
+                println!("  {} stale (outputs missing)", report.stale);
+                println!("  {} expired", report.expired);
+                println!("  {} evicted to fit the size limit", report.evicted);
+            }
+            println!(
+                "  {} entries left ({})",
+                report.remaining,
+                format_bytes(report.remaining_bytes)
+            );
+            if limits.is_unlimited() {
+                println!(
+                    "{}",
+                    "  Set cache.max_size_mb or cache.max_age_days to evict automatically."
+                        .dimmed()
+                );
+            }
+
             Ok(())
         }
 
@@ -75,6 +151,47 @@ pub async fn run(action: CacheAction, _verbose: bool) -> Result<()> {
             Ok(())
         }
 
+        CacheAction::Verify { repair } => {
+            let report = cache.verify(repair).await?;
+
+            if report.corrupt.is_empty() {
+                println!("{} {} entries verified", "✓".green(), report.checked);
+                return Ok(());
+            }
+
+            for entry in &report.corrupt {
+                let name = entry
+                    .path
+                    .strip_prefix(&cache_dir)
+                    .unwrap_or(&entry.path)
+                    .display()
+                    .to_string();
+                let stage = entry
+                    .stage
+                    .as_ref()
+                    .map(|s| format!(" (stage '{}')", s))
+                    .unwrap_or_default();
+                println!("  {} {}{}: {}", "✗".red(), name, stage, entry.problem);
+            }
+            println!();
+
+            if report.repaired {
+                println!(
+                    "{} Removed {} corrupt of {} entries; they will be recomputed",
+                    "✓".green(),
+                    report.corrupt.len(),
+                    report.checked
+                );
+                return Ok(());
+            }
+            Err(miette::miette!(
+                help = "Run 'conflow cache verify --repair' to remove them",
+                "{} of {} cache entries are corrupt",
+                report.corrupt.len(),
+                report.checked
+            ))
+        }
+
         CacheAction::List => {
             // For now, just show stats since we don't expose entry listing in the trait
             let stats = cache.stats().await?;
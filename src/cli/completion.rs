@@ -0,0 +1,753 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Completion command - generate shell completion scripts
+//!
+//! Scripts are generated from the clap command tree. Arguments whose value
+//! name is `STAGE`, `SCHEMA` or `REQUIREMENT` are completed at runtime by
+//! calling back into `conflow __complete`, so stage names follow the
+//! project's `.conflow.yaml` and IDs follow the registries.
+
+use clap::{Arg, ArgAction, Command, CommandFactory};
+use miette::Result;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use super::{Cli, CompletionShell, DynamicValues};
+use crate::pipeline::Pipeline;
+use crate::rsr::{RsrRequirementRegistry, RsrSchemaRegistry};
+
+/// Hidden subcommand the scripts call for dynamic values
+const VALUES_COMMAND: &str = "__complete";
+
+/// Print the completion script for a shell
+pub async fn run(shell: CompletionShell, _verbose: bool) -> Result<()> {
+    let mut cmd = Cli::command();
+    print!("{}", generate(shell, &mut cmd));
+    Ok(())
+}
+
+/// Print dynamic values, one per line
+///
+/// Errors are swallowed: a broken pipeline file should leave the shell
+/// without suggestions rather than print errors mid-completion.
+pub async fn values(kind: DynamicValues, pipeline: PathBuf) -> Result<()> {
+    for value in dynamic_values(kind, &pipeline) {
+        println!("{}", value);
+    }
+    Ok(())
+}
+
+/// Current values of a dynamic kind
+pub fn dynamic_values(kind: DynamicValues, pipeline: &Path) -> Vec<String> {
+    match kind {
+        DynamicValues::Stages => Pipeline::from_file(pipeline)
+            .map(|p| p.stage_names().into_iter().map(String::from).collect())
+            .unwrap_or_default(),
+        DynamicValues::Schemas => {
+            let mut ids: Vec<_> = RsrSchemaRegistry::new()
+                .list()
+                .map(|s| s.id.clone())
+                .collect();
+            ids.sort();
+            ids
+        }
+        DynamicValues::Requirements => {
+            let mut ids: Vec<_> = RsrRequirementRegistry::new()
+                .all()
+                .map(|r| r.id.clone())
+                .collect();
+            ids.sort();
+            ids
+        }
+    }
+}
+
+/// Generate the script for a shell
+pub fn generate(shell: CompletionShell, cmd: &mut Command) -> String {
+    cmd.build();
+    let bin = cmd.get_name().to_string();
+    let mut specs = Vec::new();
+    collect(cmd, String::new(), &mut specs);
+
+    match shell {
+        CompletionShell::Bash => bash(&bin, &specs),
+        CompletionShell::Zsh => zsh(&bin, &specs),
+        CompletionShell::Fish => fish(&bin, &specs),
+        CompletionShell::Powershell => powershell(&bin, &specs),
+    }
+}
+
+/// What an argument's value completes to
+#[derive(Debug, Clone, PartialEq)]
+enum Values {
+    /// Fixed choices
+    Static(Vec<String>),
+    /// Looked up at completion time
+    Dynamic(DynamicValues),
+    /// Anything; completed as a file path
+    Any,
+}
+
+#[derive(Debug)]
+struct OptionSpec {
+    short: Option<char>,
+    long: Option<String>,
+    help: String,
+    /// `None` for flags
+    values: Option<Values>,
+}
+
+impl OptionSpec {
+    fn names(&self) -> Vec<String> {
+        let short = self.short.map(|s| format!("-{}", s));
+        let long = self.long.as_ref().map(|l| format!("--{}", l));
+        short.into_iter().chain(long).collect()
+    }
+}
+
+/// Completions at one point in the subcommand tree
+#[derive(Debug)]
+struct CommandSpec {
+    /// Subcommand names from the root, space separated ("" for the root)
+    path: String,
+    subcommands: Vec<(String, String)>,
+    options: Vec<OptionSpec>,
+    /// Values of the first positional argument
+    positional: Option<Values>,
+}
+
+fn collect(cmd: &Command, path: String, specs: &mut Vec<CommandSpec>) {
+    let visible = |a: &&Arg| !a.is_hide_set();
+
+    let options = cmd
+        .get_arguments()
+        .filter(visible)
+        .filter(|a| !a.is_positional())
+        .map(|a| OptionSpec {
+            short: a.get_short(),
+            long: a.get_long().map(String::from),
+            help: help_text(a.get_help()),
+            values: takes_value(a).then(|| arg_values(a)),
+        })
+        .collect();
+    let positional = cmd
+        .get_positionals()
+        .find(|a| !a.is_hide_set())
+        .map(arg_values);
+    let subcommands = cmd
+        .get_subcommands()
+        .filter(|s| !s.is_hide_set() && s.get_name() != "help")
+        .map(|s| (s.get_name().to_string(), help_text(s.get_about())))
+        .collect();
+
+    specs.push(CommandSpec {
+        path: path.clone(),
+        subcommands,
+        options,
+        positional,
+    });
+
+    for sub in cmd.get_subcommands() {
+        if sub.is_hide_set() || sub.get_name() == "help" {
+            continue;
+        }
+        let sub_path = if path.is_empty() {
+            sub.get_name().to_string()
+        } else {
+            format!("{} {}", path, sub.get_name())
+        };
+        collect(sub, sub_path, specs);
+    }
+}
+
+fn takes_value(arg: &Arg) -> bool {
+    matches!(arg.get_action(), ArgAction::Set | ArgAction::Append)
+}
+
+fn arg_values(arg: &Arg) -> Values {
+    let name = arg
+        .get_value_names()
+        .and_then(|names| names.first())
+        .map(|n| n.as_str());
+    match name {
+        Some("STAGE") => return Values::Dynamic(DynamicValues::Stages),
+        Some("SCHEMA") => return Values::Dynamic(DynamicValues::Schemas),
+        Some("REQUIREMENT") => return Values::Dynamic(DynamicValues::Requirements),
+        _ => {}
+    }
+
+    let choices: Vec<String> = arg
+        .get_possible_values()
+        .iter()
+        .filter(|v| !v.is_hide_set())
+        .map(|v| v.get_name().to_string())
+        .collect();
+    if choices.is_empty() {
+        Values::Any
+    } else {
+        Values::Static(choices)
+    }
+}
+
+/// First line of a help text
+fn help_text(help: Option<&clap::builder::StyledStr>) -> String {
+    help.map(|h| h.to_string().lines().next().unwrap_or_default().to_string())
+        .unwrap_or_default()
+}
+
+/// Nested `path:word` transitions into subcommands
+fn transitions(specs: &[CommandSpec]) -> Vec<(String, String)> {
+    specs
+        .iter()
+        .flat_map(|spec| {
+            spec.subcommands.iter().map(move |(name, _)| {
+                let child = if spec.path.is_empty() {
+                    name.clone()
+                } else {
+                    format!("{} {}", spec.path, name)
+                };
+                (format!("{}:{}", spec.path, name), child)
+            })
+        })
+        .collect()
+}
+
+fn dynamic_arg(kind: DynamicValues) -> &'static str {
+    match kind {
+        DynamicValues::Stages => "stages",
+        DynamicValues::Schemas => "schemas",
+        DynamicValues::Requirements => "requirements",
+    }
+}
+
+fn bash(bin: &str, specs: &[CommandSpec]) -> String {
+    let func = format!("_{}", bin.replace('-', "_"));
+    let words = |values: &Values| match values {
+        Values::Static(choices) => format!("\"{}\"", choices.join(" ")),
+        Values::Dynamic(kind) => format!("\"$({}_values {})\"", func, dynamic_arg(*kind)),
+        Values::Any => String::new(),
+    };
+    let reply = |values: &Values| match values {
+        Values::Any => "COMPREPLY=($(compgen -f -- \"$cur\"))".to_string(),
+        values => format!("COMPREPLY=($(compgen -W {} -- \"$cur\"))", words(values)),
+    };
+
+    let mut out = String::new();
+    let _ = writeln!(out, "# bash completion for {}", bin);
+    let _ = writeln!(out);
+    let _ = writeln!(out, "{}_values() {{", func);
+    let _ = writeln!(
+        out,
+        "    {} {} \"$1\" ${{pipeline:+--pipeline \"$pipeline\"}} 2>/dev/null",
+        bin, VALUES_COMMAND
+    );
+    let _ = writeln!(out, "}}\n");
+    let _ = writeln!(out, "{}() {{", func);
+    out.push_str(
+        "    local cur prev cmdpath=\"\" pipeline=\"\" opts i word\n    \
+         COMPREPLY=()\n    \
+         cur=\"${COMP_WORDS[COMP_CWORD]}\"\n    \
+         prev=\"${COMP_WORDS[COMP_CWORD-1]}\"\n\n    \
+         for ((i = 1; i < COMP_CWORD; i++)); do\n        \
+         word=\"${COMP_WORDS[i]}\"\n        \
+         case \"$word\" in\n            \
+         -p|--pipeline) pipeline=\"${COMP_WORDS[i+1]}\" ;;\n        \
+         esac\n        \
+         case \"$cmdpath:$word\" in\n",
+    );
+    for (from, to) in transitions(specs) {
+        let _ = writeln!(out, "            \"{}\") cmdpath=\"{}\" ;;", from, to);
+    }
+    out.push_str("        esac\n    done\n\n    case \"$cmdpath:$prev\" in\n");
+    for spec in specs {
+        for opt in &spec.options {
+            let Some(ref values) = opt.values else {
+                continue;
+            };
+            let labels: Vec<String> = opt
+                .names()
+                .iter()
+                .map(|n| format!("\"{}:{}\"", spec.path, n))
+                .collect();
+            let _ = writeln!(
+                out,
+                "        {}) {}; return ;;",
+                labels.join("|"),
+                reply(values)
+            );
+        }
+    }
+    out.push_str("    esac\n\n    if [[ \"$cur\" == -* ]]; then\n        case \"$cmdpath\" in\n");
+    for spec in specs {
+        let names: Vec<String> = spec.options.iter().flat_map(|o| o.names()).collect();
+        let _ = writeln!(
+            out,
+            "            \"{}\") opts=\"{}\" ;;",
+            spec.path,
+            names.join(" ")
+        );
+    }
+    out.push_str(
+        "        esac\n        \
+         COMPREPLY=($(compgen -W \"$opts\" -- \"$cur\"))\n        \
+         return\n    fi\n\n    \
+         case \"$cmdpath\" in\n",
+    );
+    for spec in specs {
+        if !spec.subcommands.is_empty() {
+            let names: Vec<&str> = spec.subcommands.iter().map(|(n, _)| n.as_str()).collect();
+            let _ = writeln!(
+                out,
+                "        \"{}\") COMPREPLY=($(compgen -W \"{}\" -- \"$cur\")) ;;",
+                spec.path,
+                names.join(" ")
+            );
+        } else if let Some(ref values) = spec.positional {
+            let _ = writeln!(out, "        \"{}\") {} ;;", spec.path, reply(values));
+        }
+    }
+    out.push_str("        *) COMPREPLY=($(compgen -f -- \"$cur\")) ;;\n    esac\n}\n\n");
+    let _ = writeln!(out, "complete -F {} {}", func, bin);
+    out
+}
+
+fn zsh(bin: &str, specs: &[CommandSpec]) -> String {
+    let func = format!("_{}", bin.replace('-', "_"));
+    let quote = |s: &str| format!("'{}'", s.replace('\'', "'\\''"));
+    let item = |name: &str, help: &str| quote(&format!("{}:{}", name.replace(':', "\\:"), help));
+    let complete = |values: &Values| match values {
+        Values::Static(choices) => format!("values=({}); compadd -a values", choices.join(" ")),
+        Values::Dynamic(kind) => format!(
+            "values=(${{(f)\"$({}_values {})\"}}); compadd -a values",
+            func,
+            dynamic_arg(*kind)
+        ),
+        Values::Any => "_files".to_string(),
+    };
+
+    let mut out = String::new();
+    let _ = writeln!(out, "#compdef {}\n", bin);
+    let _ = writeln!(out, "{}_values() {{", func);
+    let _ = writeln!(
+        out,
+        "    {} {} \"$1\" ${{pipeline:+--pipeline \"$pipeline\"}} 2>/dev/null",
+        bin, VALUES_COMMAND
+    );
+    let _ = writeln!(out, "}}\n");
+    let _ = writeln!(out, "{}() {{", func);
+    out.push_str(
+        "    local cur prev cmdpath=\"\" pipeline=\"\" i word\n    \
+         local -a values\n    \
+         cur=\"${words[CURRENT]}\"\n    \
+         prev=\"${words[CURRENT-1]}\"\n\n    \
+         for ((i = 2; i < CURRENT; i++)); do\n        \
+         word=\"${words[i]}\"\n        \
+         case \"$word\" in\n            \
+         -p|--pipeline) pipeline=\"${words[i+1]}\" ;;\n        \
+         esac\n        \
+         case \"$cmdpath:$word\" in\n",
+    );
+    for (from, to) in transitions(specs) {
+        let _ = writeln!(out, "            \"{}\") cmdpath=\"{}\" ;;", from, to);
+    }
+    out.push_str("        esac\n    done\n\n    case \"$cmdpath:$prev\" in\n");
+    for spec in specs {
+        for opt in &spec.options {
+            let Some(ref values) = opt.values else {
+                continue;
+            };
+            let labels: Vec<String> = opt
+                .names()
+                .iter()
+                .map(|n| format!("\"{}:{}\"", spec.path, n))
+                .collect();
+            let _ = writeln!(
+                out,
+                "        {}) {}; return ;;",
+                labels.join("|"),
+                complete(values)
+            );
+        }
+    }
+    out.push_str("    esac\n\n    if [[ \"$cur\" == -* ]]; then\n        case \"$cmdpath\" in\n");
+    for spec in specs {
+        let items: Vec<String> = spec
+            .options
+            .iter()
+            .flat_map(|o| o.names().into_iter().map(|n| item(&n, &o.help)))
+            .collect();
+        let _ = writeln!(
+            out,
+            "            \"{}\") values=({}) ;;",
+            spec.path,
+            items.join(" ")
+        );
+    }
+    out.push_str(
+        "        esac\n        \
+         _describe 'option' values\n        \
+         return\n    fi\n\n    \
+         case \"$cmdpath\" in\n",
+    );
+    for spec in specs {
+        if !spec.subcommands.is_empty() {
+            let items: Vec<String> = spec.subcommands.iter().map(|(n, h)| item(n, h)).collect();
+            let _ = writeln!(
+                out,
+                "        \"{}\") values=({}); _describe 'command' values ;;",
+                spec.path,
+                items.join(" ")
+            );
+        } else if let Some(ref values) = spec.positional {
+            let _ = writeln!(out, "        \"{}\") {} ;;", spec.path, complete(values));
+        }
+    }
+    out.push_str("        *) _files ;;\n    esac\n}\n\n");
+    let _ = writeln!(out, "if [ \"$funcstack[1]\" = \"{}\" ]; then", func);
+    let _ = writeln!(
+        out,
+        "    {} \"$@\"\nelse\n    compdef {} {}\nfi",
+        func, func, bin
+    );
+    out
+}
+
+fn fish(bin: &str, specs: &[CommandSpec]) -> String {
+    let func = format!("__{}", bin.replace('-', "_"));
+    let quote = |s: &str| format!("'{}'", s.replace('\\', "\\\\").replace('\'', "\\'"));
+
+    let mut out = String::new();
+    let _ = writeln!(out, "# fish completion for {}\n", bin);
+    let _ = writeln!(out, "function {}_path", func);
+    out.push_str(
+        "    set -l cmdpath \"\"\n    \
+         for token in (commandline -opc)[2..-1]\n        \
+         switch \"$cmdpath:$token\"\n",
+    );
+    for (from, to) in transitions(specs) {
+        let _ = writeln!(
+            out,
+            "            case {}\n                set cmdpath {}",
+            quote(&from),
+            quote(&to)
+        );
+    }
+    out.push_str("        end\n    end\n    echo $cmdpath\nend\n\n");
+    let _ = writeln!(out, "function {}_using", func);
+    let _ = writeln!(out, "    test \"({}_path)\" = \"$argv[1]\"\nend\n", func);
+    let _ = writeln!(out, "function {}_values", func);
+    let _ = writeln!(
+        out,
+        "    set -l tokens (commandline -opc)\n    \
+         set -l args {} $argv[1]\n    \
+         for i in (seq (math (count $tokens) - 1))\n        \
+         if contains -- $tokens[$i] -p --pipeline\n            \
+         set args $args --pipeline $tokens[(math $i + 1)]\n        \
+         end\n    \
+         end\n    \
+         {} $args 2>/dev/null\nend\n",
+        VALUES_COMMAND, bin
+    );
+
+    for spec in specs {
+        let condition = format!("-n {}", quote(&format!("{}_using \"{}\"", func, spec.path)));
+        for (name, help) in &spec.subcommands {
+            let _ = writeln!(
+                out,
+                "complete -c {} {} -f -a {} -d {}",
+                bin,
+                condition,
+                quote(name),
+                quote(help)
+            );
+        }
+        for opt in &spec.options {
+            let mut line = format!("complete -c {} {}", bin, condition);
+            if let Some(short) = opt.short {
+                let _ = write!(line, " -s {}", short);
+            }
+            if let Some(ref long) = opt.long {
+                let _ = write!(line, " -l {}", long);
+            }
+            match opt.values {
+                Some(Values::Static(ref choices)) => {
+                    let _ = write!(line, " -x -a {}", quote(&choices.join(" ")));
+                }
+                Some(Values::Dynamic(kind)) => {
+                    let _ = write!(line, " -x -a '({}_values {})'", func, dynamic_arg(kind));
+                }
+                Some(Values::Any) => line.push_str(" -r"),
+                None => {}
+            }
+            let _ = writeln!(out, "{} -d {}", line, quote(&opt.help));
+        }
+        if spec.subcommands.is_empty() {
+            match spec.positional {
+                Some(Values::Static(ref choices)) => {
+                    let _ = writeln!(
+                        out,
+                        "complete -c {} {} -f -a {}",
+                        bin,
+                        condition,
+                        quote(&choices.join(" "))
+                    );
+                }
+                Some(Values::Dynamic(kind)) => {
+                    let _ = writeln!(
+                        out,
+                        "complete -c {} {} -f -a '({}_values {})'",
+                        bin,
+                        condition,
+                        func,
+                        dynamic_arg(kind)
+                    );
+                }
+                _ => {}
+            }
+        }
+    }
+    out
+}
+
+fn powershell(bin: &str, specs: &[CommandSpec]) -> String {
+    let quote = |s: &str| format!("'{}'", s.replace('\'', "''"));
+    let values = |values: &Values| match values {
+        Values::Static(choices) => {
+            let items: Vec<String> = choices.iter().map(|c| quote(c)).collect();
+            format!("@({})", items.join(", "))
+        }
+        Values::Dynamic(kind) => format!("@(Get-Values {})", quote(dynamic_arg(*kind))),
+        Values::Any => "$null".to_string(),
+    };
+
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "Register-ArgumentCompleter -Native -CommandName {} -ScriptBlock {{",
+        quote(bin)
+    );
+    out.push_str(
+        "    param($wordToComplete, $commandAst, $cursorPosition)\n\n    \
+         $tokens = @($commandAst.CommandElements | Select-Object -Skip 1 |\n        \
+         Where-Object { $_.Extent.EndOffset -lt $cursorPosition } |\n        \
+         ForEach-Object { $_.ToString() })\n    \
+         $cmdpath = ''\n    \
+         $pipeline = $null\n    \
+         $prev = ''\n    \
+         foreach ($token in $tokens) {\n        \
+         if ($prev -eq '-p' -or $prev -eq '--pipeline') { $pipeline = $token }\n        \
+         switch -CaseSensitive (\"${cmdpath}:${token}\") {\n",
+    );
+    for (from, to) in transitions(specs) {
+        let _ = writeln!(
+            out,
+            "            {} {{ $cmdpath = {} }}",
+            quote(&from),
+            quote(&to)
+        );
+    }
+    let _ = writeln!(
+        out,
+        "        }}\n        \
+         $prev = $token\n    \
+         }}\n\n    \
+         function Get-Values($kind) {{\n        \
+         $extra = if ($pipeline) {{ @('--pipeline', $pipeline) }} else {{ @() }}\n        \
+         & {} {} $kind @extra 2>$null\n    \
+         }}\n",
+        quote(bin),
+        VALUES_COMMAND
+    );
+    // Case-sensitive, so -v and -V stay distinct
+    out.push_str(
+        "    $candidates = [System.Collections.Specialized.OrderedDictionary]::new(\
+         [StringComparer]::Ordinal)\n    \
+         $choices = switch -CaseSensitive (\"${cmdpath}:${prev}\") {\n",
+    );
+    for spec in specs {
+        for opt in &spec.options {
+            let Some(ref opt_values) = opt.values else {
+                continue;
+            };
+            for name in opt.names() {
+                let _ = writeln!(
+                    out,
+                    "        {} {{ {} }}",
+                    quote(&format!("{}:{}", spec.path, name)),
+                    values(opt_values)
+                );
+            }
+        }
+    }
+    out.push_str(
+        "        default { $null }\n    }\n\n    \
+         if ($null -ne $choices) {\n        \
+         foreach ($choice in $choices) { $candidates[$choice] = $choice }\n    \
+         } elseif ($wordToComplete.StartsWith('-')) {\n        \
+         switch ($cmdpath) {\n",
+    );
+    for spec in specs {
+        let items: Vec<String> = spec
+            .options
+            .iter()
+            .flat_map(|o| {
+                o.names()
+                    .into_iter()
+                    .map(|n| format!("$candidates[{}] = {}", quote(&n), quote(&o.help)))
+            })
+            .collect();
+        let _ = writeln!(
+            out,
+            "            {} {{ {} }}",
+            quote(&spec.path),
+            items.join("; ")
+        );
+    }
+    out.push_str("        }\n    } else {\n        switch ($cmdpath) {\n");
+    for spec in specs {
+        if !spec.subcommands.is_empty() {
+            let items: Vec<String> = spec
+                .subcommands
+                .iter()
+                .map(|(n, h)| format!("$candidates[{}] = {}", quote(n), quote(h)))
+                .collect();
+            let _ = writeln!(
+                out,
+                "            {} {{ {} }}",
+                quote(&spec.path),
+                items.join("; ")
+            );
+        } else if let Some(ref positional) = spec.positional {
+            if *positional != Values::Any {
+                let _ = writeln!(
+                    out,
+                    "            {} {{ foreach ($v in {}) {{ $candidates[$v] = $v }} }}",
+                    quote(&spec.path),
+                    values(positional)
+                );
+            }
+        }
+    }
+    out.push_str(
+        "        }\n    }\n\n    \
+         $candidates.GetEnumerator() |\n        \
+         Where-Object { $_.Key -like \"$wordToComplete*\" } |\n        \
+         ForEach-Object {\n            \
+         $tip = if ($_.Value) { $_.Value } else { $_.Key }\n            \
+         [System.Management.Automation.CompletionResult]::new(\
+         $_.Key, $_.Key, 'ParameterValue', $tip)\n        \
+         }\n}\n",
+    );
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn specs() -> Vec<CommandSpec> {
+        let mut cmd = Cli::command();
+        cmd.build();
+        let mut specs = Vec::new();
+        collect(&cmd, String::new(), &mut specs);
+        specs
+    }
+
+    fn option<'a>(specs: &'a [CommandSpec], path: &str, long: &str) -> &'a OptionSpec {
+        specs
+            .iter()
+            .find(|s| s.path == path)
+            .and_then(|s| s.options.iter().find(|o| o.long.as_deref() == Some(long)))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_dynamic_and_static_values() {
+        let specs = specs();
+
+        assert_eq!(
+            option(&specs, "run", "stage").values,
+            Some(Values::Dynamic(DynamicValues::Stages))
+        );
+        assert_eq!(
+            option(&specs, "rsr check", "requirement").values,
+            Some(Values::Dynamic(DynamicValues::Requirements))
+        );
+        assert_eq!(
+            option(&specs, "run", "report-format").values,
+            Some(Values::Static(vec![
+                "json".into(),
+                "sarif".into(),
+                "code-quality".into()
+            ]))
+        );
+        assert_eq!(option(&specs, "run", "dry-run").values, None);
+
+        let schema = specs.iter().find(|s| s.path == "rsr schema").unwrap();
+        assert_eq!(
+            schema.positional,
+            Some(Values::Dynamic(DynamicValues::Schemas))
+        );
+        assert!(!specs.iter().any(|s| s.path.contains(VALUES_COMMAND)));
+    }
+
+    #[test]
+    fn test_scripts_call_back_for_values() {
+        for shell in [
+            CompletionShell::Bash,
+            CompletionShell::Zsh,
+            CompletionShell::Fish,
+            CompletionShell::Powershell,
+        ] {
+            let script = generate(shell, &mut Cli::command());
+            assert!(script.contains("__complete"), "{:?}", shell);
+            assert!(script.contains("stages"), "{:?}", shell);
+            assert!(script.contains("rsr schema"), "{:?}", shell);
+        }
+    }
+
+    #[test]
+    fn test_bash_script_completes() {
+        let script = generate(CompletionShell::Bash, &mut Cli::command());
+        let check = |line: &str| {
+            let output = std::process::Command::new("bash")
+                .arg("-c")
+                .arg(format!(
+                    "{}\nconflow() {{ echo build; echo test; }}\n{}\n_conflow\n\
+                     printf '%s\\n' \"${{COMPREPLY[@]}}\"",
+                    script, line
+                ))
+                .output();
+            output.map(|o| String::from_utf8_lossy(&o.stdout).to_string())
+        };
+
+        let Ok(stages) = check("COMP_WORDS=(conflow run --stage ''); COMP_CWORD=3") else {
+            return;
+        };
+        assert_eq!(stages, "build\ntest\n");
+
+        let actions = check("COMP_WORDS=(conflow rsr sch); COMP_CWORD=2").unwrap();
+        assert_eq!(actions, "schemas\nschema\n");
+
+        let formats = check("COMP_WORDS=(conflow run --report-format s); COMP_CWORD=3").unwrap();
+        assert_eq!(formats, "sarif\n");
+    }
+
+    #[test]
+    fn test_dynamic_values() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join(".conflow.yaml");
+        std::fs::write(
+            &path,
+            "name: demo\nstages:\n  - name: lint\n    tool: { type: shell, command: \"true\" }\n    input: \"*\"\n",
+        )
+        .unwrap();
+
+        assert_eq!(dynamic_values(DynamicValues::Stages, &path), vec!["lint"]);
+        assert!(dynamic_values(DynamicValues::Stages, &temp.path().join("missing")).is_empty());
+        assert!(dynamic_values(DynamicValues::Requirements, &path)
+            .iter()
+            .any(|id| id.starts_with("RSR-")));
+    }
+}
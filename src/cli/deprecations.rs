@@ -0,0 +1,197 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Deprecations command - find manifests using deprecated Kubernetes APIs
+
+use colored::Colorize;
+use miette::Result;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use super::graph::project_relative;
+use super::OutputFormat;
+use crate::analyzer::{find_deprecated_apis, ApiFinding, ApiStatus, KubeVersion};
+use crate::diagnostics::{annotations, Diagnostic};
+use crate::errors::{ConflowError, FailOn};
+use crate::executors::parsers::{to_json, Location, Severity, ToolDiagnostic};
+use crate::pipeline::{KubernetesConfig, Pipeline};
+
+/// Run the deprecations command
+pub async fn run(
+    files: Vec<PathBuf>,
+    target: Option<String>,
+    pipeline_path: PathBuf,
+    fail_on: FailOn,
+    format: OutputFormat,
+    verbose: bool,
+) -> Result<()> {
+    let cwd = std::env::current_dir().map_err(|e| ConflowError::Io {
+        message: e.to_string(),
+    })?;
+
+    let config = if pipeline_path.exists() {
+        Pipeline::from_file(&pipeline_path)?.kubernetes
+    } else {
+        KubernetesConfig::default()
+    };
+    let target = match target.or(config.version) {
+        Some(version) => version
+            .parse::<KubeVersion>()
+            .map_err(|message| ConflowError::CheckFailed { message })?,
+        None => {
+            if verbose {
+                println!(
+                    "{} No kubernetes.version in {}; checking against {}",
+                    "→".blue(),
+                    pipeline_path.display(),
+                    KubeVersion::latest()
+                );
+            }
+            KubeVersion::latest()
+        }
+    };
+
+    let files = if files.is_empty() {
+        crate::lint::matching_files(&config.files, &cwd)?
+    } else {
+        let mut relative = Vec::new();
+        for file in files {
+            let file = project_relative(&file, &cwd);
+            if !cwd.join(&file).is_file() {
+                return Err(ConflowError::FileNotFound {
+                    path: file,
+                    help: None,
+                }
+                .into());
+            }
+            relative.push(file);
+        }
+        relative
+    };
+
+    let findings = find_deprecated_apis(&files, &cwd, target)?;
+    let diagnostics: Vec<ToolDiagnostic> = findings.iter().map(diagnostic).collect();
+    for diagnostic in &diagnostics {
+        annotations::emit(&Diagnostic::from(diagnostic));
+    }
+    match format {
+        OutputFormat::Text => print_text(target, files.len(), &findings, &diagnostics),
+        OutputFormat::Json => {
+            let json = serde_json::json!({
+                "target": target,
+                "files": files.len(),
+                "findings": findings,
+                "diagnostics": to_json(&diagnostics),
+            });
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&json).map_err(ConflowError::from)?
+            );
+        }
+    }
+
+    let removed = count(&findings, ApiStatus::Removed);
+    let deprecated = count(&findings, ApiStatus::Deprecated);
+    if removed > 0 && fail_on.fails_on_errors() {
+        return Err(ConflowError::CheckFailed {
+            message: format!(
+                "{} manifest(s) use APIs Kubernetes {} no longer serves",
+                removed, target
+            ),
+        }
+        .into());
+    }
+    if deprecated > 0 && fail_on.fails_on_warnings() {
+        return Err(ConflowError::WarningsFound { count: deprecated }.into());
+    }
+    Ok(())
+}
+
+fn count(findings: &[ApiFinding], status: ApiStatus) -> usize {
+    findings.iter().filter(|f| f.status == status).count()
+}
+
+fn diagnostic(finding: &ApiFinding) -> ToolDiagnostic {
+    let (severity, rule) = match finding.status {
+        ApiStatus::Removed => (Severity::Error, "removed-api"),
+        ApiStatus::Deprecated => (Severity::Warning, "deprecated-api"),
+    };
+    ToolDiagnostic {
+        tool: "deprecations".into(),
+        severity,
+        message: finding.message(),
+        rule: Some(rule.into()),
+        location: Some(Location {
+            file: finding.file.clone(),
+            line: Some(finding.line),
+            column: None,
+        }),
+        related: vec![],
+    }
+}
+
+fn print_text(
+    target: KubeVersion,
+    files: usize,
+    findings: &[ApiFinding],
+    diagnostics: &[ToolDiagnostic],
+) {
+    if findings.is_empty() {
+        println!(
+            "{} {} file(s) checked, no APIs deprecated in Kubernetes {}",
+            "✓".green(),
+            files,
+            target
+        );
+        return;
+    }
+
+    for diagnostic in diagnostics {
+        println!("{}", diagnostic.render());
+    }
+    println!();
+
+    // Upgrade report: what has to change before each release
+    println!(
+        "{}",
+        format!("Upgrade report for Kubernetes {}", target).bold()
+    );
+    let mut by_release: BTreeMap<Option<KubeVersion>, Vec<&ApiFinding>> = BTreeMap::new();
+    for finding in findings {
+        by_release
+            .entry(finding.api.removed_in)
+            .or_default()
+            .push(finding);
+    }
+    for (release, findings) in &by_release {
+        let heading = match release {
+            Some(release) if *release <= target => format!("Removed in {}", release).red(),
+            Some(release) => format!("Removed in {}", release).yellow(),
+            None => "Deprecated, no removal announced".yellow(),
+        };
+        println!();
+        println!("  {} ({})", heading.bold(), findings.len());
+        for finding in findings {
+            println!(
+                "    {} {} → {}  {}",
+                finding.api.kind,
+                finding.api.api_version.dimmed(),
+                finding.api.replacement.unwrap_or("no replacement"),
+                format!("{}:{}", finding.file.display(), finding.line).cyan()
+            );
+        }
+    }
+    println!();
+
+    let removed = count(findings, ApiStatus::Removed);
+    let deprecated = count(findings, ApiStatus::Deprecated);
+    let icon = if removed > 0 {
+        "✗".red()
+    } else {
+        "⚠".yellow()
+    };
+    println!(
+        "{} {} removed, {} deprecated API use(s) in {} file(s)",
+        icon, removed, deprecated, files
+    );
+}
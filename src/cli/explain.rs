@@ -0,0 +1,219 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Explain command - describe an error code
+
+use colored::Colorize;
+use miette::Result;
+use std::collections::HashMap;
+use std::path::Path;
+
+use super::OutputFormat;
+use crate::diagnostics::{reproduce, Repro};
+use crate::errors::{ConflowError, ErrorCode, ERROR_CODES};
+use crate::executors::parsers::parse_output;
+use crate::pipeline::{Pipeline, RunHistory, RunRecord, StageOutcome};
+
+/// Run the explain command
+pub async fn run(
+    code: Option<String>,
+    show_fix: bool,
+    format: OutputFormat,
+    _verbose: bool,
+) -> Result<()> {
+    let error = match code {
+        Some(code) => Some(ErrorCode::find(&code).ok_or_else(|| {
+            miette::miette!(
+                help = "Run 'conflow explain' to list all codes",
+                "Unknown error code: {}",
+                code
+            )
+        })?),
+        None if show_fix => None,
+        None => return list(format),
+    };
+
+    let fixes = if show_fix {
+        let cwd = std::env::current_dir().map_err(|e| ConflowError::Io {
+            message: e.to_string(),
+        })?;
+        Some(last_run_fixes(&cwd)?)
+    } else {
+        None
+    };
+
+    match format {
+        OutputFormat::Text => {
+            if let Some(error) = error {
+                println!("{} {}", error.code.bold(), error.name.dimmed());
+                println!();
+                println!("{}", error.summary.bold());
+                println!();
+                println!("{}", error.explanation);
+            }
+            if let Some((run, ref fixes)) = fixes {
+                if error.is_some() {
+                    println!();
+                }
+                print_fixes(run.as_ref(), fixes);
+            }
+        }
+        OutputFormat::Json => {
+            let mut json = error.map_or_else(|| serde_json::json!({}), to_json);
+            if let Some((run, fixes)) = fixes {
+                json["run"] = serde_json::json!(run.map(|r| r.id));
+                json["fixes"] = fixes
+                    .iter()
+                    .map(|(stage, repro)| {
+                        let mut fix = serde_json::json!(repro);
+                        fix["stage"] = serde_json::json!(stage);
+                        fix
+                    })
+                    .collect();
+            }
+            print_json(&json)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Reproductions of failed constraints, with the stage that failed each
+type Fixes = Vec<(String, Repro)>;
+
+/// The latest recorded run, and reproductions of the constraints it failed
+fn last_run_fixes(cwd: &Path) -> Result<(Option<RunRecord>, Fixes)> {
+    let Some(run) = RunHistory::new(cwd).latest()? else {
+        return Ok((None, vec![]));
+    };
+
+    // The stage's tool picks the parser; without the pipeline, detect it
+    let tools: HashMap<String, String> = Pipeline::from_file(&cwd.join(".conflow.yaml"))
+        .map(|pipeline| {
+            pipeline
+                .stages
+                .iter()
+                .map(|s| (s.name.clone(), s.tool_name().to_string()))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut fixes = Vec::new();
+    for stage in &run.stages {
+        let Some(ref error) = stage.error else {
+            continue;
+        };
+        if stage.outcome != StageOutcome::Failed {
+            continue;
+        }
+        let tool = tools.get(&stage.name).map_or("", String::as_str);
+        for repro in reproduce(&parse_output(tool, error), cwd) {
+            fixes.push((stage.name.clone(), repro));
+        }
+    }
+    Ok((Some(run), fixes))
+}
+
+fn print_fixes(run: Option<&RunRecord>, fixes: &[(String, Repro)]) {
+    let Some(run) = run else {
+        println!("No recorded runs to show fixes for; run the pipeline with 'conflow run'.");
+        return;
+    };
+    let failed: Vec<&str> = run
+        .stages
+        .iter()
+        .filter(|s| s.outcome == StageOutcome::Failed)
+        .map(|s| s.name.as_str())
+        .collect();
+    if failed.is_empty() {
+        println!("Run #{} passed: there is nothing to fix.", run.id);
+        return;
+    }
+    if fixes.is_empty() {
+        println!(
+            "Run #{} failed in {}, but not on a CUE or Nickel constraint conflow can reproduce.",
+            run.id,
+            failed.join(", ")
+        );
+        println!("See the stage output with 'conflow runs show {}'.", run.id);
+        return;
+    }
+
+    println!("{}", format!("Fixes for run #{}", run.id).bold());
+    println!("{}", "═".repeat(50));
+    for (stage, repro) in fixes {
+        println!();
+        println!(
+            "{} {} {}",
+            repro.field.cyan().bold(),
+            repro.constraint.bold(),
+            format!("(stage {}, {})", stage, repro.tool).dimmed()
+        );
+        println!("  {}", repro.lesson);
+        println!();
+        print_side_by_side(&repro.failing, &repro.fixed);
+    }
+}
+
+/// The failing config and its fix in two columns, changed lines colored
+fn print_side_by_side(failing: &str, fixed: &str) {
+    let left: Vec<&str> = failing.lines().collect();
+    let right: Vec<&str> = fixed.lines().collect();
+    let width = left
+        .iter()
+        .map(|l| l.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max(10)
+        + 4;
+
+    println!(
+        "  {}{}{}",
+        "✗ Fails".red().bold(),
+        " ".repeat(width - 7),
+        "✓ Passes".green().bold()
+    );
+    for i in 0..left.len().max(right.len()) {
+        let l = left.get(i).copied().unwrap_or("");
+        let r = right.get(i).copied().unwrap_or("");
+        let padding = " ".repeat(width - l.chars().count());
+        if l == r {
+            println!("  {}{}{}", l, padding, r);
+        } else {
+            println!("  {}{}{}", l.red(), padding, r.green());
+        }
+    }
+}
+
+fn list(format: OutputFormat) -> Result<()> {
+    match format {
+        OutputFormat::Text => {
+            for error in ERROR_CODES {
+                println!("{}  {}", error.code.bold(), error.summary);
+            }
+        }
+        OutputFormat::Json => {
+            let all: Vec<_> = ERROR_CODES.iter().map(to_json).collect();
+            print_json(&serde_json::Value::Array(all))?;
+        }
+    }
+    Ok(())
+}
+
+fn to_json(error: &ErrorCode) -> serde_json::Value {
+    serde_json::json!({
+        "code": error.code,
+        "name": error.name,
+        "summary": error.summary,
+        "explanation": error.explanation,
+    })
+}
+
+fn print_json(value: &serde_json::Value) -> Result<()> {
+    println!(
+        "{}",
+        serde_json::to_string_pretty(value)
+            .map_err(|e| miette::miette!("Failed to serialize JSON: {}", e))?
+    );
+    Ok(())
+}
@@ -0,0 +1,509 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! GitOps command - check a revision range for newly introduced violations
+//!
+//! Both revisions are exported to scratch directories and checked the same
+//! way: changed YAML and JSON files must parse, the pipeline must be valid
+//! with no broken references, and the stages affected by the changed files
+//! run. Only violations found at `--to` but not at `--from` fail the check,
+//! so legacy repositories can adopt conflow without fixing everything first.
+
+use colored::Colorize;
+use miette::Result;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+use super::{GitopsAction, OutputFormat};
+use crate::analyzer::dotted;
+use crate::diagnostics::{annotations, Diagnostic, Label};
+use crate::errors::ConflowError;
+use crate::executors::parsers::{self, Location, Severity};
+use crate::executors::{create_executors, DEPLOY_TOOLS};
+use crate::pipeline::{
+    affected_stages, check_refs, ExecutionOptions, Pipeline, PipelineExecutor, PipelineValidator,
+};
+use crate::utils::{git, stream};
+
+/// Something wrong with a revision's configuration
+#[derive(Debug, Clone, Serialize)]
+struct Violation {
+    /// File relative to the checked directory
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    line: Option<usize>,
+    severity: Severity,
+    /// What found it: `syntax`, `pipeline`, `refs` or a stage name
+    source: String,
+    message: String,
+    /// Details that vary between revisions, e.g. parser positions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    detail: Option<String>,
+}
+
+impl Violation {
+    fn error(file: Option<&Path>, source: &str, message: impl Into<String>) -> Self {
+        Self {
+            file: file.map(Path::to_path_buf),
+            line: None,
+            severity: Severity::Error,
+            source: source.to_string(),
+            message: message.into(),
+            detail: None,
+        }
+    }
+
+    /// For annotating the violation's line in CI
+    fn diagnostic(&self) -> Diagnostic {
+        let labels = self.file.iter().map(|file| {
+            let location = Location {
+                file: file.clone(),
+                line: self.line,
+                column: None,
+            };
+            Label::primary(&location, self.detail.clone().unwrap_or_default())
+        });
+        Diagnostic {
+            severity: self.severity,
+            code: Some(self.source.clone()),
+            message: self.message.clone(),
+            labels: labels.collect(),
+            notes: vec![],
+        }
+    }
+
+    /// Identity across revisions; lines shift as files are edited
+    fn key(&self) -> (Option<&Path>, &str, &str) {
+        (self.file.as_deref(), &self.source, &self.message)
+    }
+}
+
+impl std::fmt::Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(ref file) = self.file {
+            write!(f, "{}", file.display())?;
+            if let Some(line) = self.line {
+                write!(f, ":{}", line)?;
+            }
+            write!(f, ": ")?;
+        }
+        write!(f, "[{}] {}", self.source, self.message)?;
+        if let Some(ref detail) = self.detail {
+            write!(f, " ({})", detail)?;
+        }
+        Ok(())
+    }
+}
+
+/// Outcome of checking a revision range
+#[derive(Debug, Serialize)]
+struct RangeReport {
+    from: String,
+    to: String,
+    /// Changed files, relative to the checked directory
+    changed: Vec<PathBuf>,
+    /// Violations at `to` that were not at `from`
+    new: Vec<Violation>,
+    /// Violations at `from` that are gone at `to`
+    fixed: Vec<Violation>,
+    /// Violations at both revisions
+    existing: usize,
+}
+
+/// Run the gitops command
+pub async fn run(action: GitopsAction, verbose: bool) -> Result<()> {
+    match action {
+        GitopsAction::Check {
+            from,
+            to,
+            pipeline,
+            format,
+            no_run,
+        } => {
+            let working_dir = std::env::current_dir()
+                .map_err(|e| miette::miette!("Failed to get current directory: {}", e))?;
+            let report = check_range(&working_dir, &from, &to, &pipeline, !no_run).await?;
+
+            for violation in &report.new {
+                annotations::emit(&violation.diagnostic());
+            }
+
+            match format {
+                OutputFormat::Json => println!(
+                    "{}",
+                    serde_json::to_string_pretty(&report).map_err(ConflowError::from)?
+                ),
+                OutputFormat::Text => print_report(&report, verbose),
+            }
+
+            if report.new.is_empty() {
+                Ok(())
+            } else {
+                Err(ConflowError::ViolationsIntroduced {
+                    from,
+                    count: report.new.len(),
+                }
+                .into())
+            }
+        }
+    }
+}
+
+/// Check both ends of `from..to` and compare their violations
+async fn check_range(
+    working_dir: &Path,
+    from: &str,
+    to: &str,
+    pipeline_path: &Path,
+    run_stages: bool,
+) -> Result<RangeReport, ConflowError> {
+    let base = working_dir
+        .canonicalize()
+        .unwrap_or_else(|_| working_dir.to_path_buf());
+    let changed: Vec<PathBuf> = git::changed_between(working_dir, from, to)?
+        .into_iter()
+        .filter_map(|path| Some(path.strip_prefix(&base).ok()?.to_path_buf()))
+        .collect();
+
+    let mut report = RangeReport {
+        from: from.to_string(),
+        to: to.to_string(),
+        changed,
+        new: Vec::new(),
+        fixed: Vec::new(),
+        existing: 0,
+    };
+    if report.changed.is_empty() {
+        return Ok(report);
+    }
+
+    let scratch = crate::utils::private_tempdir("conflow-gitops-")?;
+    let check = |git_ref, name| {
+        revision_violations(
+            working_dir,
+            git_ref,
+            scratch.path().join(name),
+            pipeline_path,
+            &report.changed,
+            run_stages,
+        )
+    };
+    let before = check(from, "from").await?;
+    let after = check(to, "to").await?;
+
+    let (new, fixed, existing) = compare(before, after);
+    report.new = new;
+    report.fixed = fixed;
+    report.existing = existing;
+    Ok(report)
+}
+
+/// Split violations into new, fixed and the count of those at both revisions
+fn compare(
+    before: Vec<Violation>,
+    after: Vec<Violation>,
+) -> (Vec<Violation>, Vec<Violation>, usize) {
+    let before_keys: HashSet<_> = before.iter().map(Violation::key).collect();
+    let after_keys: HashSet<_> = after.iter().map(Violation::key).collect();
+
+    let fixed: Vec<_> = before
+        .iter()
+        .filter(|v| !after_keys.contains(&v.key()))
+        .cloned()
+        .collect();
+    let (new, existing): (Vec<_>, Vec<_>) = after
+        .iter()
+        .cloned()
+        .partition(|v| !before_keys.contains(&v.key()));
+
+    (new, fixed, existing.len())
+}
+
+/// Export `git_ref` to `dest` and collect its violations
+async fn revision_violations(
+    working_dir: &Path,
+    git_ref: &str,
+    dest: PathBuf,
+    pipeline_path: &Path,
+    changed: &[PathBuf],
+    run_stages: bool,
+) -> Result<Vec<Violation>, ConflowError> {
+    let dir = git::export_tree(working_dir, git_ref, &dest)?;
+    let mut violations: Vec<Violation> = changed
+        .iter()
+        .filter(|file| dir.join(file).is_file())
+        .filter_map(|file| syntax_violation(&dir, file))
+        .collect();
+
+    // Repositories adopting conflow may not have a pipeline at `from` yet
+    if !dir.join(pipeline_path).is_file() {
+        return Ok(violations);
+    }
+    let pipeline = match Pipeline::from_file(&dir.join(pipeline_path)) {
+        Ok(pipeline) => pipeline,
+        Err(e) => {
+            let mut violation = Violation::error(Some(pipeline_path), "pipeline", "does not parse");
+            violation.detail = Some(e.to_string());
+            violations.push(violation);
+            return Ok(violations);
+        }
+    };
+
+    let validation = PipelineValidator::validate(&pipeline)?;
+    for error in &validation.errors {
+        violations.push(Violation::error(
+            Some(pipeline_path),
+            "pipeline",
+            error.clone(),
+        ));
+    }
+    for broken in check_refs(&pipeline, &dir)? {
+        let file = &broken.location.file;
+        let message = format!(
+            "{}: '{}' is not defined ({})",
+            dotted(&broken.location.path),
+            broken.value,
+            broken.rule
+        );
+        violations.push(Violation::error(
+            Some(file.strip_prefix(&dir).unwrap_or(file)),
+            "refs",
+            message,
+        ));
+    }
+
+    if run_stages && validation.is_valid() {
+        violations.extend(stage_violations(&pipeline, &dir, changed).await?);
+    }
+
+    Ok(violations)
+}
+
+/// A parse error in a changed YAML or JSON file
+fn syntax_violation(dir: &Path, file: &Path) -> Option<Violation> {
+    let reader = BufReader::new(std::fs::File::open(dir.join(file)).ok()?);
+    let name = file.display().to_string();
+    let result = match file.extension().and_then(|e| e.to_str()) {
+        Some("json") => stream::check_json(reader, &name),
+        Some("yaml" | "yml") => stream::check_yaml(reader, &name),
+        _ => return None,
+    };
+
+    let error = result.err()?;
+    let mut violation = Violation::error(Some(file), "syntax", "does not parse");
+    violation.detail = Some(match error {
+        ConflowError::ConfigParse { message, .. } => message,
+        e => e.to_string(),
+    });
+    Some(violation)
+}
+
+/// Run the stages affected by `changed` and collect their diagnostics
+///
+/// Manual and deployment stages never run against historical revisions.
+async fn stage_violations(
+    pipeline: &Pipeline,
+    dir: &Path,
+    changed: &[PathBuf],
+) -> Result<Vec<Violation>, ConflowError> {
+    let changed: Vec<PathBuf> = changed.iter().map(|file| dir.join(file)).collect();
+    let mut stages = affected_stages(pipeline, &changed, dir)?;
+    stages.retain(|name| {
+        pipeline
+            .get_stage(name)
+            .is_some_and(|stage| !stage.manual && !DEPLOY_TOOLS.contains(&stage.tool_name()))
+    });
+    if stages.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut executor = PipelineExecutor::new().quiet();
+    for (name, exec) in create_executors(pipeline) {
+        executor.register_executor(&name, exec);
+    }
+    let mut missing = executor.check_tools(pipeline).await?;
+    missing.retain(|tool| {
+        stages
+            .iter()
+            .filter_map(|name| pipeline.get_stage(name))
+            .any(|stage| stage.tool_name() == tool)
+    });
+    if !missing.is_empty() {
+        return Err(ConflowError::ToolNotFound {
+            tool: missing.join("', '"),
+            suggestion: "Install the tools or pass --no-run to skip running stages".into(),
+        });
+    }
+
+    let options = ExecutionOptions {
+        no_cache: true,
+        stages,
+        ..Default::default()
+    };
+    let result = executor.execute(pipeline, dir, &options).await?;
+
+    let mut violations = Vec::new();
+    for stage in &pipeline.stages {
+        let Some(stage_result) = result.results.get(&stage.name) else {
+            continue;
+        };
+
+        let output = format!("{}\n{}", stage_result.stdout, stage_result.stderr);
+        let diagnostics: Vec<_> = parsers::parse_output(stage.tool_name(), &output)
+            .into_iter()
+            .filter(|d| d.severity != Severity::Info)
+            .collect();
+
+        if diagnostics.is_empty() && !stage_result.success {
+            let message = format!("failed with exit code {}", stage_result.exit_code);
+            violations.push(Violation::error(None, &stage.name, message));
+        }
+        for diagnostic in diagnostics {
+            let location = diagnostic.location.as_ref();
+            violations.push(Violation {
+                file: location.map(|l| l.file.strip_prefix(dir).unwrap_or(&l.file).to_path_buf()),
+                line: location.and_then(|l| l.line),
+                severity: diagnostic.severity,
+                source: stage.name.clone(),
+                message: diagnostic.message,
+                detail: None,
+            });
+        }
+    }
+
+    Ok(violations)
+}
+
+fn print_report(report: &RangeReport, verbose: bool) {
+    println!();
+    println!(
+        "{} {}..{}",
+        "GitOps check".bold(),
+        report.from.cyan(),
+        report.to.cyan()
+    );
+    println!("{}", "═".repeat(50));
+    println!("  {} changed file(s)", report.changed.len());
+    if verbose {
+        for file in &report.changed {
+            println!("    {} {}", "·".dimmed(), file.display());
+        }
+    }
+
+    if !report.new.is_empty() {
+        println!();
+        println!("{}:", "New violations".red().bold());
+        for violation in &report.new {
+            println!("  {} {}", "✗".red(), violation);
+        }
+    }
+
+    if !report.fixed.is_empty() {
+        println!();
+        println!("{}:", "Fixed".green().bold());
+        for violation in &report.fixed {
+            println!("  {} {}", "✓".green(), violation);
+        }
+    }
+
+    println!();
+    if report.existing > 0 {
+        println!(
+            "{} {} violation(s) already present at {} were ignored",
+            "→".blue(),
+            report.existing,
+            report.from
+        );
+    }
+    if report.new.is_empty() {
+        println!("{} No new violations", "✓".green());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    fn git(dir: &Path, args: &[&str]) -> bool {
+        Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .is_ok_and(|o| o.status.success())
+    }
+
+    fn commit(dir: &Path, files: &[(&str, &str)], tag: &str) {
+        for (name, content) in files {
+            std::fs::write(dir.join(name), content).unwrap();
+        }
+        assert!(git(dir, &["add", "-A"]));
+        assert!(git(dir, &["commit", "-q", "-m", tag]));
+        assert!(git(dir, &["tag", tag]));
+    }
+
+    #[test]
+    fn test_compare_ignores_moved_lines() {
+        let at = |line| Violation {
+            line: Some(line),
+            ..Violation::error(Some(Path::new("a.yaml")), "lint", "bad indent")
+        };
+        let gone = Violation::error(None, "pipeline", "Pipeline has no stages defined");
+        let added = Violation::error(Some(Path::new("b.json")), "syntax", "does not parse");
+
+        let (new, fixed, existing) = compare(vec![at(3), gone], vec![at(7), added]);
+        assert_eq!(new.len(), 1);
+        assert_eq!(new[0].source, "syntax");
+        assert_eq!(fixed[0].source, "pipeline");
+        assert_eq!(existing, 1);
+    }
+
+    #[tokio::test]
+    async fn test_only_new_violations_are_reported() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let dir = temp.path();
+        // Skip if git is not installed
+        if !git(dir, &["init", "-q"]) {
+            return;
+        }
+        git(dir, &["config", "user.email", "test@example.com"]);
+        git(dir, &["config", "user.name", "test"]);
+
+        let pipeline =
+            "name: legacy\nstages:\n  - name: policy\n    tool:\n      type: shell\n      \
+                        command: \"! grep -l forbidden *.yaml\"\n    input: \"*.yaml\"\n";
+        commit(
+            dir,
+            &[
+                (".conflow.yaml", pipeline),
+                ("old.yaml", "a: [1\nforbidden: true\n"),
+            ],
+            "v1",
+        );
+        commit(
+            dir,
+            &[
+                ("old.yaml", "a: [1, 2\nforbidden: true\n"),
+                ("new.json", "{\"a\": }"),
+            ],
+            "v2",
+        );
+
+        let report = check_range(dir, "v1", "v2", Path::new(".conflow.yaml"), true)
+            .await
+            .unwrap();
+        assert_eq!(report.changed.len(), 2);
+        assert_eq!(report.new.len(), 1, "{:?}", report.new);
+        assert_eq!(report.new[0].file.as_deref(), Some(Path::new("new.json")));
+        // old.yaml's syntax error and the failing policy stage predate v1
+        assert_eq!(report.existing, 2);
+
+        let report = check_range(dir, "v2", "v2", Path::new(".conflow.yaml"), true)
+            .await
+            .unwrap();
+        assert!(report.changed.is_empty());
+    }
+}
@@ -3,11 +3,14 @@
 
 //! Graph command - visualize pipeline as a graph
 
+use colored::Colorize;
 use miette::Result;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use super::GraphFormat;
+use crate::analyzer::ConfigGraph;
 use crate::pipeline::{DagBuilder, Pipeline};
+use crate::ConflowError;
 
 /// Run the graph command
 pub async fn run(pipeline_path: PathBuf, format: GraphFormat, _verbose: bool) -> Result<()> {
@@ -21,9 +24,7 @@ pub async fn run(pipeline_path: PathBuf, format: GraphFormat, _verbose: bool) ->
     }
 
     // Load pipeline
-    let pipeline = Pipeline::from_file(&pipeline_path).map_err(|e| {
-        miette::miette!("Failed to load pipeline: {}", e)
-    })?;
+    let pipeline = Pipeline::from_file(&pipeline_path)?;
 
     // Build DAG
     let dag = DagBuilder::build(&pipeline)?;
@@ -39,3 +40,63 @@ pub async fn run(pipeline_path: PathBuf, format: GraphFormat, _verbose: bool) ->
 
     Ok(())
 }
+
+/// Graph the references between the project's config files
+///
+/// With `affected`, list the files a change to it affects instead.
+pub async fn run_configs(
+    format: GraphFormat,
+    affected: Option<PathBuf>,
+    verbose: bool,
+) -> Result<()> {
+    let cwd = std::env::current_dir().map_err(|e| ConflowError::Io {
+        message: e.to_string(),
+    })?;
+    let graph = ConfigGraph::scan(&cwd)?;
+
+    let Some(file) = affected else {
+        let output = match format {
+            GraphFormat::Text if graph.references.is_empty() => {
+                "No references between config files found".to_string()
+            }
+            GraphFormat::Text => graph.to_text(),
+            GraphFormat::Dot => graph.to_dot(),
+            GraphFormat::Mermaid => graph.to_mermaid(),
+        };
+        println!("{}", output.trim_end());
+        return Ok(());
+    };
+
+    let file = project_relative(&file, &cwd);
+    if !cwd.join(&file).exists() {
+        return Err(ConflowError::FileNotFound {
+            path: file,
+            help: None,
+        }
+        .into());
+    }
+
+    let affected = graph.affected_by(&file);
+    if affected.is_empty() {
+        println!("{} Nothing references {}", "✓".green(), file.display());
+        return Ok(());
+    }
+    if verbose {
+        println!("{} Changing {} affects:", "→".blue(), file.display());
+    }
+    for path in affected {
+        println!("{}", path.display());
+    }
+    Ok(())
+}
+
+/// `path` relative to `cwd`, as the graph names files
+pub(super) fn project_relative(path: &Path, cwd: &Path) -> PathBuf {
+    let absolute = cwd.join(path);
+    let canonical = absolute.canonicalize().unwrap_or(absolute);
+    let cwd = cwd.canonicalize().unwrap_or_else(|_| cwd.to_path_buf());
+    canonical
+        .strip_prefix(&cwd)
+        .map(Path::to_path_buf)
+        .unwrap_or(canonical)
+}
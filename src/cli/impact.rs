@@ -0,0 +1,99 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Impact command - list what editing a file affects
+
+use colored::Colorize;
+use miette::Result;
+use std::path::PathBuf;
+
+use super::graph::project_relative;
+use super::OutputFormat;
+use crate::pipeline::{impact, Impact, Pipeline};
+use crate::ConflowError;
+
+/// Run the impact command
+pub async fn run(
+    file: PathBuf,
+    pipeline_path: PathBuf,
+    format: OutputFormat,
+    verbose: bool,
+) -> Result<()> {
+    let cwd = std::env::current_dir().map_err(|e| ConflowError::Io {
+        message: e.to_string(),
+    })?;
+
+    let file = project_relative(&file, &cwd);
+    if !cwd.join(&file).exists() {
+        return Err(ConflowError::FileNotFound {
+            path: file,
+            help: None,
+        }
+        .into());
+    }
+
+    let pipeline = if pipeline_path.exists() {
+        Some(Pipeline::from_file(&pipeline_path)?)
+    } else {
+        if verbose {
+            println!(
+                "{} No {}; only listing configs and environments",
+                "→".blue(),
+                pipeline_path.display()
+            );
+        }
+        None
+    };
+
+    let impact = impact(&file, pipeline.as_ref(), &cwd)?;
+    match format {
+        OutputFormat::Text => print_text(&impact),
+        OutputFormat::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&impact).map_err(ConflowError::from)?
+        ),
+    }
+    Ok(())
+}
+
+fn print_text(impact: &Impact) {
+    if impact.is_empty() {
+        println!(
+            "{} Nothing references or reads {}",
+            "✓".green(),
+            impact.file.display()
+        );
+        return;
+    }
+
+    println!(
+        "{} {}",
+        "Impact of".bold(),
+        impact.file.display().to_string().bold()
+    );
+
+    let section = |title: &str, items: Vec<String>| {
+        if items.is_empty() {
+            return;
+        }
+        println!();
+        println!("{} ({})", title.bold(), items.len());
+        for item in items {
+            println!("  {} {}", "→".blue(), item);
+        }
+    };
+    let paths = |paths: &[PathBuf]| paths.iter().map(|p| p.display().to_string()).collect();
+
+    section("Configs", paths(&impact.configs));
+    section("Stages", impact.stages.clone());
+    section("Rendered artifacts", paths(&impact.artifacts));
+    section("Environments", impact.environments.clone());
+    section(
+        "Deployments",
+        impact
+            .deployments
+            .iter()
+            .map(|d| format!("{} ({})", d.stage, d.target))
+            .collect(),
+    );
+}
@@ -5,7 +5,12 @@
 
 use colored::Colorize;
 use miette::Result;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+use crate::audit;
+use crate::rsr::config::RsrConfig;
+use crate::rsr::profiles;
+use crate::utils::walk::Walker;
 
 /// Run the init command
 pub async fn run(name: Option<String>, template: Option<String>, verbose: bool) -> Result<()> {
@@ -26,29 +31,52 @@ pub async fn run(name: Option<String>, template: Option<String>, verbose: bool)
         ));
     }
 
+    // A project holding a Helm chart gets the helm template by default
+    let template = template.or_else(|| {
+        let chart = find_chart()?;
+        println!(
+            "  {} Found a Helm chart in {}; using the helm template",
+            "→".blue(),
+            chart.display()
+        );
+        Some("helm".to_string())
+    });
+
     // Generate pipeline based on template
     let pipeline_content = match template.as_deref() {
         Some("cue-validation") => generate_cue_template(&project_name),
         Some("nickel-generation") => generate_nickel_template(&project_name),
         Some("full-pipeline") => generate_full_template(&project_name),
         Some("kubernetes") | Some("k8s") => generate_k8s_template(&project_name),
-        Some(t) => {
-            return Err(miette::miette!(
-                "Unknown template: '{}'\n\nAvailable templates:\n\
-                 • cue-validation     - Simple CUE schema validation\n\
-                 • nickel-generation  - Nickel config generation\n\
-                 • full-pipeline      - Generate → validate → export\n\
-                 • kubernetes         - Kubernetes manifests pipeline",
-                t
-            ));
-        }
+        Some("helm") => generate_helm_template(
+            &project_name,
+            &find_chart().unwrap_or_else(|| PathBuf::from("chart")),
+        ),
+        Some(t) => match profile_template(t).await? {
+            Some(dir) => {
+                copy_template(&dir, Path::new("."))?;
+                std::fs::read_to_string(dir.join(".conflow.yaml"))
+                    .unwrap_or_else(|_| generate_default_template(&project_name))
+            }
+            None => {
+                return Err(miette::miette!(
+                    "Unknown template: '{}'\n\nAvailable templates:\n\
+                     • cue-validation     - Simple CUE schema validation\n\
+                     • nickel-generation  - Nickel config generation\n\
+                     • full-pipeline      - Generate → validate → export\n\
+                     • kubernetes         - Kubernetes manifests pipeline\n\
+                     • helm               - Render a Helm chart → validate\n\n\
+                     Templates from the profiles in .rsr.yaml are available too.",
+                    t
+                ));
+            }
+        },
         None => generate_default_template(&project_name),
     };
 
     // Write pipeline file
-    std::fs::write(".conflow.yaml", &pipeline_content).map_err(|e| {
-        miette::miette!("Failed to write .conflow.yaml: {}", e)
-    })?;
+    write(".conflow.yaml", &pipeline_content)
+        .map_err(|e| miette::miette!("Failed to write .conflow.yaml: {}", e))?;
 
     println!("  {} Created .conflow.yaml", "✓".green());
 
@@ -56,9 +84,8 @@ pub async fn run(name: Option<String>, template: Option<String>, verbose: bool)
     let dirs = [".conflow", "schemas", "configs"];
     for dir in dirs {
         if !Path::new(dir).exists() {
-            std::fs::create_dir_all(dir).map_err(|e| {
-                miette::miette!("Failed to create directory '{}': {}", dir, e)
-            })?;
+            std::fs::create_dir_all(dir)
+                .map_err(|e| miette::miette!("Failed to create directory '{}': {}", dir, e))?;
             println!("  {} Created {}/", "✓".green(), dir);
         }
     }
@@ -72,7 +99,10 @@ pub async fn run(name: Option<String>, template: Option<String>, verbose: bool)
     println!("{}", "Project initialized!".green().bold());
     println!();
     println!("Next steps:");
-    println!("  1. Edit {} to define your pipeline", ".conflow.yaml".cyan());
+    println!(
+        "  1. Edit {} to define your pipeline",
+        ".conflow.yaml".cyan()
+    );
     println!("  2. Add your configuration files to {}", "configs/".cyan());
     println!("  3. Run {} to execute the pipeline", "conflow run".cyan());
     println!();
@@ -269,6 +299,106 @@ stages:
     )
 }
 
+fn generate_helm_template(name: &str, chart: &Path) -> String {
+    let templates = chart.join("templates").join("**").join("*");
+    let templates = templates.to_string_lossy().replace('\\', "/");
+    let templates = templates.trim_start_matches("./");
+    format!(
+        r#"# conflow pipeline - Helm chart
+version: "1"
+name: "{name}"
+
+stages:
+  - name: "render"
+    description: "Render the chart with sample values"
+    tool:
+      type: render
+      # Values layered over the chart's values.yaml, e.g. per environment
+      # values: [ci-values.yaml]
+      # set:
+      #   image.tag: "1.0.0"
+    input: "{templates}"
+
+  - name: "validate"
+    description: "Validate the rendered manifests"
+    tool:
+      type: cue
+      command: vet
+      schemas:
+        - schemas/manifests.cue
+    input:
+      from_stage: render
+    depends_on:
+      - render
+"#
+    )
+}
+
+/// The Helm chart in the project directory, its `charts/` directory, or a
+/// directory just below it
+fn find_chart() -> Option<PathBuf> {
+    if Path::new("Chart.yaml").is_file() {
+        return Some(PathBuf::from("."));
+    }
+    let mut charts: Vec<PathBuf> = [".", "charts"]
+        .iter()
+        .filter_map(|dir| std::fs::read_dir(dir).ok())
+        .flat_map(|entries| entries.flatten())
+        .map(|entry| entry.path())
+        .filter(|dir| dir.join("Chart.yaml").is_file())
+        .map(|dir| dir.strip_prefix(".").map(Path::to_path_buf).unwrap_or(dir))
+        .collect();
+    charts.sort();
+    charts.into_iter().next()
+}
+
+/// Directory of a template from the RSR profiles the project inherits
+async fn profile_template(name: &str) -> Result<Option<PathBuf>> {
+    let config = RsrConfig::load_from_project(Path::new("."))?;
+    let profiles = profiles::fetch_all(&config, false).await?;
+    Ok(profiles
+        .iter()
+        .find_map(|p| p.template(name))
+        .map(|t| t.path.clone()))
+}
+
+/// Copy a profile template's files, keeping files that already exist
+///
+/// The template's `.conflow.yaml` is written by the caller.
+fn copy_template(from: &Path, to: &Path) -> Result<()> {
+    std::fs::read_dir(from)
+        .map_err(|e| miette::miette!("Failed to read template '{}': {}", from.display(), e))?;
+
+    for source in Walker::everything().files(from)? {
+        let Ok(relative) = source.strip_prefix(from) else {
+            continue;
+        };
+        let target = to.join(relative);
+        let is_pipeline = to == Path::new(".") && relative == Path::new(".conflow.yaml");
+        if target.exists() || is_pipeline {
+            continue;
+        }
+
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| {
+                miette::miette!("Failed to create directory '{}': {}", parent.display(), e)
+            })?;
+        }
+        std::fs::read(&source)
+            .and_then(|content| write(&target, content))
+            .map_err(|e| miette::miette!("Failed to write '{}': {}", target.display(), e))?;
+        let shown = target.strip_prefix(".").unwrap_or(&target);
+        println!("  {} Created {}", "✓".green(), shown.display());
+    }
+
+    Ok(())
+}
+
+/// Write a project file, recording it in the audit log
+fn write(path: impl AsRef<Path>, content: impl AsRef<[u8]>) -> std::io::Result<()> {
+    audit::write(Path::new("."), path.as_ref(), content, "init")
+}
+
 fn create_example_files(template: &str) -> Result<()> {
     match template {
         "cue-validation" => {
@@ -288,7 +418,7 @@ package config
     }
 }
 "#;
-            std::fs::write("schemas/config.cue", schema)
+            write("schemas/config.cue", schema)
                 .map_err(|e| miette::miette!("Failed to write schema: {}", e))?;
             println!("  {} Created schemas/config.cue", "✓".green());
 
@@ -300,7 +430,7 @@ package config
     "env": "dev"
 }
 "#;
-            std::fs::write("configs/example.json", config)
+            write("configs/example.json", config)
                 .map_err(|e| miette::miette!("Failed to write example config: {}", e))?;
             println!("  {} Created configs/example.json", "✓".green());
         }
@@ -318,7 +448,7 @@ package config
   env = "dev",
 }
 "#;
-            std::fs::write("configs/config.ncl", ncl)
+            write("configs/config.ncl", ncl)
                 .map_err(|e| miette::miette!("Failed to write Nickel file: {}", e))?;
             println!("  {} Created configs/config.ncl", "✓".green());
 
@@ -333,7 +463,7 @@ package config
     env:      string
 }
 "#;
-            std::fs::write("schemas/config.cue", schema)
+            write("schemas/config.cue", schema)
                 .map_err(|e| miette::miette!("Failed to write schema: {}", e))?;
             println!("  {} Created schemas/config.cue", "✓".green());
 
@@ -387,7 +517,7 @@ package config
   },
 }
 "#;
-            std::fs::write("k8s/base.ncl", ncl)
+            write("k8s/base.ncl", ncl)
                 .map_err(|e| miette::miette!("Failed to write k8s/base.ncl: {}", e))?;
             println!("  {} Created k8s/base.ncl", "✓".green());
 
@@ -418,7 +548,7 @@ package kubernetes
     ports: [...{containerPort: int}]
 }
 "#;
-            std::fs::write("k8s/schemas/kubernetes.cue", schema)
+            write("k8s/schemas/kubernetes.cue", schema)
                 .map_err(|e| miette::miette!("Failed to write k8s schema: {}", e))?;
             println!("  {} Created k8s/schemas/kubernetes.cue", "✓".green());
 
@@ -427,6 +557,63 @@ package kubernetes
             std::fs::create_dir_all("deploy")
                 .map_err(|e| miette::miette!("Failed to create deploy/: {}", e))?;
         }
+        "helm" => {
+            // Create an example chart unless the project has one
+            if find_chart().is_none() {
+                std::fs::create_dir_all("chart/templates")
+                    .map_err(|e| miette::miette!("Failed to create chart/templates/: {}", e))?;
+                let files = [
+                    (
+                        "chart/Chart.yaml",
+                        "apiVersion: v2\nname: my-app\nversion: 0.1.0\n",
+                    ),
+                    ("chart/values.yaml", "replicas: 2\nimage: my-app:latest\n"),
+                    (
+                        "chart/templates/deployment.yaml",
+                        r#"apiVersion: apps/v1
+kind: Deployment
+metadata:
+  name: {{ .Release.Name }}-{{ .Chart.Name }}
+spec:
+  replicas: {{ .Values.replicas }}
+  selector:
+    matchLabels:
+      app: {{ .Chart.Name }}
+  template:
+    metadata:
+      labels:
+        app: {{ .Chart.Name }}
+    spec:
+      containers:
+        - name: {{ .Chart.Name }}
+          image: {{ .Values.image | quote }}
+"#,
+                    ),
+                ];
+                for (path, content) in files {
+                    write(path, content)
+                        .map_err(|e| miette::miette!("Failed to write {}: {}", path, e))?;
+                    println!("  {} Created {}", "✓".green(), path);
+                }
+            }
+
+            // Create a schema every rendered manifest must satisfy
+            let schema = r#"// Rendered manifest schema (simplified)
+package manifests
+
+apiVersion: string
+kind:       string
+metadata: {
+    name:       =~"^[a-z0-9]([-a-z0-9.]*[a-z0-9])?$"
+    namespace?: string
+    labels?: [string]: string
+    ...
+}
+"#;
+            write("schemas/manifests.cue", schema)
+                .map_err(|e| miette::miette!("Failed to write schema: {}", e))?;
+            println!("  {} Created schemas/manifests.cue", "✓".green());
+        }
         _ => {}
     }
 
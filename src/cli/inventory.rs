@@ -0,0 +1,130 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Inventory command - list the repository's config files for governance
+
+use colored::Colorize;
+use miette::Result;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use super::OutputFormat;
+use crate::cache::{Cache, FilesystemCache};
+use crate::pipeline::{inventory, Inventory, Pipeline, Validation};
+use crate::ConflowError;
+
+/// Run the inventory command
+pub async fn run(
+    patterns: Vec<String>,
+    pipeline_path: PathBuf,
+    output: Option<PathBuf>,
+    format: OutputFormat,
+    verbose: bool,
+) -> Result<()> {
+    let cwd = std::env::current_dir().map_err(|e| ConflowError::Io {
+        message: e.to_string(),
+    })?;
+
+    let pipeline = match pipeline_path.exists() {
+        true => Some(Pipeline::from_file(&pipeline_path)?),
+        false => {
+            if verbose {
+                println!(
+                    "{} No {}; files are listed without stages or validation status",
+                    "→".blue(),
+                    pipeline_path.display()
+                );
+            }
+            None
+        }
+    };
+    let passed = match pipeline {
+        Some(ref pipeline) => passed_stages(pipeline, &cwd).await?,
+        None => HashSet::new(),
+    };
+
+    let inventory = inventory(&cwd, &patterns, pipeline.as_ref(), &passed)?;
+    let json = serde_json::to_string_pretty(&inventory).map_err(ConflowError::from)?;
+    match output {
+        Some(path) => {
+            std::fs::write(&path, json + "\n").map_err(|e| ConflowError::FileWriteError {
+                path: path.clone(),
+                error: e.to_string(),
+            })?;
+            println!(
+                "{} Inventory of {} file(s) written to {}",
+                "✓".green(),
+                inventory.summary.total,
+                path.display().to_string().cyan()
+            );
+        }
+        None => match format {
+            OutputFormat::Text => print_text(&inventory),
+            OutputFormat::Json => println!("{}", json),
+        },
+    }
+    Ok(())
+}
+
+/// Stages with a cached successful result for their current inputs
+async fn passed_stages(pipeline: &Pipeline, working_dir: &Path) -> Result<HashSet<String>> {
+    let mut passed = HashSet::new();
+    if !pipeline.cache.enabled {
+        return Ok(passed);
+    }
+    let cache = FilesystemCache::new(pipeline.cache.path(working_dir)?, working_dir.to_path_buf())?;
+    for stage in pipeline.stages.iter().filter(|s| s.is_cacheable()) {
+        if let Ok(Some(_)) = cache.get(stage).await {
+            passed.insert(stage.name.clone());
+        }
+    }
+    Ok(passed)
+}
+
+fn print_text(inventory: &Inventory) {
+    if inventory.artifacts.is_empty() {
+        println!("{} No config files found", "⚠".yellow());
+        return;
+    }
+
+    for artifact in &inventory.artifacts {
+        let icon = match artifact.validation {
+            Validation::Valid => "✓".green(),
+            Validation::Invalid => "✗".red(),
+            Validation::Pending => "•".yellow(),
+            Validation::Unvalidated => "○".dimmed(),
+        };
+        let format = serde_json::to_value(artifact.format).unwrap_or_default();
+        let mut line = format!(
+            "  {} {} {}",
+            icon,
+            artifact.path.display().to_string().bold(),
+            format!("({})", format.as_str().unwrap_or_default()).dimmed()
+        );
+        if !artifact.owners.is_empty() {
+            line.push_str(&format!(" {}", artifact.owners.join(" ").cyan()));
+        }
+        println!("{}", line);
+        if !artifact.schemas.is_empty() {
+            println!("      schema: {}", artifact.schemas.join(", "));
+        }
+        if let Some(ref error) = artifact.error {
+            println!("      {}", error.red());
+        }
+    }
+
+    let summary = &inventory.summary;
+    let count = |validation| summary.by_validation.get(&validation).copied().unwrap_or(0);
+    println!();
+    println!(
+        "{} file(s): {} valid, {} invalid, {} pending, {} unvalidated; \
+         {} without a schema, {} unowned",
+        summary.total,
+        count(Validation::Valid),
+        count(Validation::Invalid),
+        count(Validation::Pending),
+        count(Validation::Unvalidated),
+        summary.without_schema,
+        summary.unowned
+    );
+}
@@ -0,0 +1,192 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Learn command - interactive CUE and Nickel lessons
+
+use colored::Colorize;
+use miette::Result;
+use std::io::{BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+
+use crate::errors::ConflowError;
+use crate::executors::create_default_executors;
+use crate::learn::{self, Lesson, Progress, LESSONS};
+
+/// Run the learn command
+pub async fn run(lesson: Option<String>, dir: PathBuf, check: bool, reset: bool) -> Result<()> {
+    let Some(name) = lesson else {
+        print_lessons(&Progress::load(&dir)?);
+        return Ok(());
+    };
+    let lesson = learn::lesson(&name).ok_or_else(|| {
+        let names: Vec<&str> = LESSONS.iter().map(|l| l.name).collect();
+        miette::miette!(
+            help = format!("Available lessons: {}", names.join(", ")),
+            "Unknown lesson '{}'",
+            name
+        )
+    })?;
+
+    let mut progress = Progress::load(&dir)?;
+    if reset {
+        progress.lessons.remove(lesson.name);
+        let sandbox = dir.join(lesson.name);
+        if sandbox.exists() {
+            std::fs::remove_dir_all(&sandbox).map_err(|e| ConflowError::FileWriteError {
+                path: sandbox,
+                error: e.to_string(),
+            })?;
+        }
+        progress.save(&dir)?;
+    }
+
+    let interactive = !check && std::io::stdin().is_terminal();
+    let executors = create_default_executors();
+    loop {
+        let current = progress.lesson(lesson.name).current;
+        let Some(exercise) = lesson.exercises.get(current) else {
+            print_done(lesson, &progress);
+            return Ok(());
+        };
+        let exercise_dir = lesson.exercise_dir(&dir, current);
+        exercise.write(Path::new("."), &exercise_dir, false)?;
+        print_exercise(lesson, current, &exercise_dir);
+
+        let solved = loop {
+            if interactive {
+                match prompt()?.as_str() {
+                    "" => {}
+                    "h" | "hint" => {
+                        println!("  {} {}", "Hint:".yellow().bold(), exercise.hint);
+                        continue;
+                    }
+                    "s" | "skip" => break false,
+                    "q" | "quit" => return Ok(()),
+                    other => {
+                        println!("  Unknown answer '{}'", other);
+                        continue;
+                    }
+                }
+            }
+
+            let problems = exercise.check(&exercise_dir, &executors).await?;
+            if problems.is_empty() {
+                println!("  {} Solved!", "✓".green().bold());
+                break true;
+            }
+            for problem in &problems {
+                println!("  {} {}", "✗".red().bold(), problem.replace('\n', "\n    "));
+            }
+            if !interactive {
+                return Err(ConflowError::CheckFailed {
+                    message: format!("'{}' is not solved yet", exercise.title),
+                }
+                .into());
+            }
+        };
+
+        let lesson_progress = progress.lesson(lesson.name);
+        if solved {
+            lesson_progress.solve();
+        } else {
+            lesson_progress.skip();
+        }
+        progress.save(&dir)?;
+        if !interactive {
+            if let Some(next) = lesson.exercises.get(current + 1) {
+                let next_dir = lesson.exercise_dir(&dir, current + 1);
+                next.write(Path::new("."), &next_dir, false)?;
+                println!(
+                    "  Next: {} in {}",
+                    next.title.bold(),
+                    next_dir.display().to_string().cyan()
+                );
+            }
+            return Ok(());
+        }
+    }
+}
+
+/// Ask what to do next; end of input quits
+fn prompt() -> Result<String> {
+    print!("\n  [Enter] check, [h]int, [s]kip, [q]uit: ");
+    std::io::stdout().flush().ok();
+
+    let mut answer = String::new();
+    let read = std::io::stdin()
+        .lock()
+        .read_line(&mut answer)
+        .map_err(|e| ConflowError::Io {
+            message: e.to_string(),
+        })?;
+    if read == 0 {
+        return Ok("q".to_string());
+    }
+    Ok(answer.trim().to_lowercase())
+}
+
+fn print_lessons(progress: &Progress) {
+    println!();
+    println!("{}", "Lessons".bold());
+    println!("{}", "═".repeat(50));
+    for lesson in LESSONS {
+        println!();
+        println!(
+            "  {} {}",
+            lesson.name.cyan().bold(),
+            format!(
+                "({}/{} solved)",
+                progress.solved(lesson.name),
+                lesson.exercises.len()
+            )
+            .dimmed()
+        );
+        println!("    {}: {}", lesson.title, lesson.summary);
+    }
+    println!();
+    println!("Start one with {}", "conflow learn <lesson>".cyan());
+    println!();
+}
+
+fn print_exercise(lesson: &Lesson, index: usize, dir: &Path) {
+    let exercise = &lesson.exercises[index];
+    println!();
+    println!(
+        "{} {}",
+        format!("{} {}/{}:", lesson.title, index + 1, lesson.exercises.len()).bold(),
+        exercise.title.cyan().bold()
+    );
+    println!();
+    println!("  {}", exercise.task);
+    println!();
+    for (i, (name, _)) in exercise.files.iter().enumerate() {
+        let path = dir.join(name).display().to_string();
+        if i == 0 {
+            println!("  {} {}", path.cyan(), "(edit this)".dimmed());
+        } else {
+            println!("  {}", path);
+        }
+    }
+}
+
+fn print_done(lesson: &Lesson, progress: &Progress) {
+    println!();
+    println!(
+        "{} {} complete: {}/{} exercises solved",
+        "✓".green().bold(),
+        lesson.title.bold(),
+        progress.solved(lesson.name),
+        lesson.exercises.len()
+    );
+    if let Some(next) = LESSONS.iter().skip_while(|l| l.name != lesson.name).nth(1) {
+        println!(
+            "  Next lesson: {}",
+            format!("conflow learn {}", next.name).cyan()
+        );
+    }
+    println!(
+        "  Start over with {}",
+        format!("conflow learn {} --reset", lesson.name).cyan()
+    );
+    println!();
+}
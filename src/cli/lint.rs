@@ -0,0 +1,211 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Lint command - check configs against lint rules
+
+use colored::Colorize;
+use miette::Result;
+use std::path::PathBuf;
+
+use super::graph::project_relative;
+use super::migrate::print_diff;
+use super::OutputFormat;
+use crate::audit;
+use crate::diagnostics::{annotations, Diagnostic};
+use crate::errors::{ConflowError, FailOn};
+use crate::executors::parsers::{to_json, Severity};
+use crate::lint::{self, LintReport, RULES};
+use crate::pipeline::{LintConfig, Pipeline};
+
+/// Run the lint command
+pub async fn run(
+    files: Vec<PathBuf>,
+    fix: bool,
+    dry_run: bool,
+    pipeline_path: PathBuf,
+    fail_on: FailOn,
+    format: OutputFormat,
+    verbose: bool,
+) -> Result<()> {
+    let cwd = std::env::current_dir().map_err(|e| ConflowError::Io {
+        message: e.to_string(),
+    })?;
+
+    let config = if pipeline_path.exists() {
+        Pipeline::from_file(&pipeline_path)?.lint
+    } else {
+        if verbose {
+            println!(
+                "{} No {}; linting with the default rules",
+                "→".blue(),
+                pipeline_path.display()
+            );
+        }
+        LintConfig::default()
+    };
+    let problems = lint::validate(&config);
+    if !problems.is_empty() {
+        return Err(ConflowError::CheckFailed {
+            message: format!("Invalid lint configuration: {}", problems.join("; ")),
+        }
+        .into());
+    }
+
+    let files = if files.is_empty() {
+        lint::files(&config, &cwd)?
+    } else {
+        let mut relative = Vec::new();
+        for file in files {
+            let file = project_relative(&file, &cwd);
+            if !cwd.join(&file).is_file() {
+                return Err(ConflowError::FileNotFound {
+                    path: file,
+                    help: None,
+                }
+                .into());
+            }
+            relative.push(file);
+        }
+        relative
+    };
+
+    let report = lint::lint(&config, &files, &cwd, fix)?;
+    if !dry_run {
+        for fix in &report.fixes {
+            let action = format!("lint --fix {}", fix.rules.join(","));
+            audit::write(&cwd, &fix.file, &fix.fixed, &action).map_err(|e| {
+                ConflowError::FileWriteError {
+                    path: cwd.join(&fix.file),
+                    error: e.to_string(),
+                }
+            })?;
+        }
+    }
+    for diagnostic in &report.diagnostics {
+        annotations::emit(&Diagnostic::from(diagnostic));
+    }
+    match format {
+        OutputFormat::Text => print_text(&report, dry_run),
+        OutputFormat::Json => {
+            let fixes: Vec<_> = report
+                .fixes
+                .iter()
+                .map(|fix| {
+                    serde_json::json!({
+                        "file": fix.file,
+                        "rules": fix.rules,
+                        "diff": fix.diff(),
+                    })
+                })
+                .collect();
+            let json = serde_json::json!({
+                "files": report.files,
+                "fixes": fixes,
+                "written": !dry_run,
+                "diagnostics": to_json(&report.diagnostics),
+            });
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&json).map_err(ConflowError::from)?
+            );
+        }
+    }
+
+    let errors = report.count(Severity::Error);
+    let warnings = report.count(Severity::Warning);
+    if errors > 0 && fail_on.fails_on_errors() {
+        return Err(ConflowError::CheckFailed {
+            message: format!("Lint found {} error(s)", errors),
+        }
+        .into());
+    }
+    if warnings > 0 && fail_on.fails_on_warnings() {
+        return Err(ConflowError::WarningsFound { count: warnings }.into());
+    }
+    Ok(())
+}
+
+fn print_text(report: &LintReport, dry_run: bool) {
+    for fix in &report.fixes {
+        let verb = if dry_run { "Would fix" } else { "Fixed" };
+        println!(
+            "{} {} {} ({})",
+            "→".blue(),
+            verb,
+            fix.file.display().to_string().cyan(),
+            fix.rules.join(", ")
+        );
+        print_diff(&fix.diff());
+        println!();
+    }
+    for diagnostic in &report.diagnostics {
+        println!("{}", diagnostic.render());
+    }
+    if !report.diagnostics.is_empty() {
+        println!();
+    }
+    if dry_run && !report.fixes.is_empty() {
+        println!("{}", "Dry run, no changes written.".dimmed());
+    }
+
+    let errors = report.count(Severity::Error);
+    let warnings = report.count(Severity::Warning);
+    if report.diagnostics.is_empty() {
+        println!(
+            "{} {} file(s) linted, no problems",
+            "✓".green(),
+            report.files
+        );
+    } else if errors > 0 {
+        println!(
+            "{} {} error(s), {} warning(s) in {} file(s)",
+            "✗".red(),
+            errors,
+            warnings,
+            report.files
+        );
+    } else {
+        println!(
+            "{} {} warning(s) in {} file(s)",
+            "⚠".yellow(),
+            warnings,
+            report.files
+        );
+    }
+}
+
+/// List the built-in rules
+pub fn list_rules(format: OutputFormat) -> Result<()> {
+    match format {
+        OutputFormat::Text => {
+            for rule in RULES {
+                let fix = if rule.fixable() { " (fixable)" } else { "" };
+                println!(
+                    "  {:<20} {:<8} {}{}",
+                    rule.id.bold(),
+                    format!("{:?}", rule.level).to_lowercase(),
+                    rule.description,
+                    fix.dimmed()
+                );
+            }
+        }
+        OutputFormat::Json => {
+            let rules: Vec<_> = RULES
+                .iter()
+                .map(|rule| {
+                    serde_json::json!({
+                        "id": rule.id,
+                        "description": rule.description,
+                        "level": rule.level,
+                        "fixable": rule.fixable(),
+                    })
+                })
+                .collect();
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&rules).map_err(ConflowError::from)?
+            );
+        }
+    }
+    Ok(())
+}
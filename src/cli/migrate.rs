@@ -0,0 +1,112 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Migrate command - upgrade a pipeline to the current schema version
+
+use colored::Colorize;
+use miette::Result;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+use crate::audit;
+use crate::pipeline::{migrate, Pipeline};
+use crate::ConflowError;
+
+/// Run the migrate command
+pub async fn run(pipeline_path: PathBuf, dry_run: bool, yes: bool, _verbose: bool) -> Result<()> {
+    if !pipeline_path.exists() {
+        return Err(ConflowError::PipelineNotFound {
+            path: pipeline_path,
+        }
+        .into());
+    }
+
+    let content =
+        std::fs::read_to_string(&pipeline_path).map_err(|e| ConflowError::FileReadError {
+            path: pipeline_path.clone(),
+            error: e.to_string(),
+        })?;
+    let file = pipeline_path.display().to_string();
+    let migration = migrate(&content)?;
+
+    if !migration.is_needed() {
+        println!(
+            "{} {} is already at version {}",
+            "✓".green(),
+            file.cyan(),
+            migration.to
+        );
+        return Ok(());
+    }
+
+    // The rewritten file must load, or migrating would strand the user
+    Pipeline::from_yaml(&migration.migrated)?;
+
+    println!(
+        "{} Migrating {} from version {} to {}",
+        "→".blue(),
+        file.cyan(),
+        migration.from,
+        migration.to
+    );
+    for change in &migration.changes {
+        println!("  • {}", change);
+    }
+    println!();
+    print_diff(&migration.diff(&file));
+
+    if content.lines().any(|l| l.trim_start().starts_with('#')) {
+        eprintln!(
+            "{} Comments are not preserved by the migration",
+            "⚠".yellow()
+        );
+    }
+
+    if dry_run {
+        println!("{}", "Dry run, no changes written.".dimmed());
+        return Ok(());
+    }
+
+    if !yes {
+        print!("Write {}? [y/N] ", file);
+        io::stdout().flush().ok();
+
+        let mut input = String::new();
+        io::stdin().read_line(&mut input).ok();
+
+        if !input.trim().eq_ignore_ascii_case("y") {
+            println!("{}", "Cancelled.".dimmed());
+            return Ok(());
+        }
+    }
+
+    let cwd = std::env::current_dir().map_err(|e| ConflowError::Io {
+        message: e.to_string(),
+    })?;
+    audit::write(&cwd, &pipeline_path, &migration.migrated, "migrate").map_err(|e| {
+        ConflowError::FileWriteError {
+            path: pipeline_path.clone(),
+            error: e.to_string(),
+        }
+    })?;
+    println!("{} Migrated {}", "✓".green(), file.cyan());
+
+    Ok(())
+}
+
+/// Print a unified diff, colored
+pub(super) fn print_diff(diff: &str) {
+    for line in diff.lines() {
+        if line.starts_with("+++") || line.starts_with("---") {
+            println!("{}", line.bold());
+        } else if line.starts_with('+') {
+            println!("{}", line.green());
+        } else if line.starts_with('-') {
+            println!("{}", line.red());
+        } else if line.starts_with("@@") {
+            println!("{}", line.cyan());
+        } else {
+            println!("{}", line);
+        }
+    }
+}
@@ -5,18 +5,61 @@
 //!
 //! Defines the command-line interface for conflow.
 
+pub mod agent;
 pub mod analyze;
+pub mod audit;
+pub mod bundle;
 pub mod cache;
+pub mod completion;
+pub mod deprecations;
+pub mod explain;
+pub mod gitops;
 pub mod graph;
+pub mod impact;
 pub mod init;
+pub mod inventory;
+pub mod learn;
+pub mod lint;
+pub mod migrate;
+pub mod new;
+pub mod plugin;
+pub mod recommend;
+pub mod release;
+pub mod render;
 pub mod rsr;
 pub mod run;
+pub mod runs;
+pub mod scan;
+pub mod schema;
+pub mod serve;
+pub mod snapshot;
+pub mod template;
+pub mod toolchain;
+pub mod undo;
+pub mod unused;
 pub mod validate;
+pub mod version;
 pub mod watch;
+pub mod why;
+pub mod workspace;
+pub mod yaml;
 
 use clap::{Parser, Subcommand};
 use std::path::PathBuf;
 
+use crate::diagnostics::annotations::Annotations;
+use crate::errors::FailOn;
+use crate::i18n::Locale;
+use crate::learn::DEFAULT_SANDBOX;
+use crate::rsr::badges::BadgeStyle;
+use crate::rsr::compliance::DEFAULT_CHECK_TIMEOUT;
+use crate::rsr::config::CiProvider;
+use crate::server::DEFAULT_MAX_BODY;
+use crate::utils::colors::ColorChoice;
+use crate::utils::cron::Schedule;
+use crate::utils::logging::LogFormat;
+use crate::workspace::WORKSPACE_FILE;
+
 /// Configuration flow orchestrator
 ///
 /// Intelligently manage CUE, Nickel, and configuration workflows.
@@ -37,13 +80,49 @@ pub struct Cli {
     #[clap(subcommand)]
     pub command: Commands,
 
-    /// Enable verbose output
-    #[clap(short, long, global = true)]
-    pub verbose: bool,
+    /// Enable verbose output (-v for info logs, -vv for debug, -vvv for trace)
+    #[clap(short, long, global = true, action = clap::ArgAction::Count)]
+    pub verbose: u8,
+
+    /// Log format for diagnostic output on stderr
+    #[clap(long, global = true, value_enum, default_value_t = LogFormat::Pretty)]
+    pub log_format: LogFormat,
+
+    /// When to color output; `auto` honors NO_COLOR and checks for a terminal
+    #[clap(
+        long,
+        global = true,
+        value_enum,
+        value_name = "WHEN",
+        default_value_t = ColorChoice::Auto
+    )]
+    pub color: ColorChoice,
+
+    /// Language for messages; defaults to CONFLOW_LANG, then LC_ALL, LC_MESSAGES or LANG
+    #[clap(long, global = true, value_enum, value_name = "LOCALE")]
+    pub lang: Option<Locale>,
+
+    /// When to print diagnostics as CI annotations; `auto` detects GitHub Actions and Azure
+    #[clap(
+        long,
+        global = true,
+        value_enum,
+        value_name = "WHEN",
+        default_value_t = Annotations::Auto
+    )]
+    pub annotations: Annotations,
 
     /// Change to directory before executing
     #[clap(short = 'C', long, global = true, value_name = "DIR")]
     pub directory: Option<PathBuf>,
+
+    /// Zero timestamps and durations in reports, for comparing output against golden files
+    #[clap(long, global = true, env = "CONFLOW_STABLE")]
+    pub stable: bool,
+
+    /// Forbid network access: downloads, fetches, clones, notifications and telemetry
+    #[clap(long, global = true, env = "CONFLOW_OFFLINE")]
+    pub offline: bool,
 }
 
 #[derive(Subcommand, Debug)]
@@ -58,14 +137,86 @@ pub enum Commands {
         template: Option<String>,
     },
 
+    /// Scaffold a new project directory: schemas, examples, pipeline, .rsr.yaml and CI
+    New {
+        /// Project name, also the directory created
+        name: String,
+
+        /// Template or template type (e.g. full-pipeline, kubernetes, k8s-service)
+        #[clap(short, long, default_value = "full-pipeline")]
+        template: String,
+
+        /// RSR tier (1-4, higher is more strict)
+        #[clap(long, default_value_t = 2, value_parser = clap::value_parser!(u8).range(1..=4))]
+        tier: u8,
+
+        /// CI provider to write a workflow for
+        #[clap(long, value_enum, default_value_t = CiProvider::GitHub)]
+        ci: CiProvider,
+
+        /// Do not write a CI workflow
+        #[clap(long, conflicts_with = "ci")]
+        no_ci: bool,
+
+        /// Set a template variable (repeatable)
+        #[clap(long = "set", value_name = "KEY=VALUE")]
+        set: Vec<String>,
+    },
+
     /// Analyze configuration files and recommend tools
     Analyze {
-        /// Files to analyze
+        /// Files to analyze; directories are searched for config files
+        files: Vec<PathBuf>,
+
+        /// Output format
+        #[clap(short, long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+
+        /// Write a CUE definition or Nickel function for each group of duplicate blocks
+        #[clap(long, value_enum, value_name = "TOOL")]
+        emit: Option<AbstractionFormat>,
+
+        /// Directory for emitted abstractions
+        #[clap(long, value_name = "DIR", default_value = ".", requires = "emit")]
+        emit_dir: PathBuf,
+
+        /// Files to analyze at once (default: number of CPUs)
+        #[clap(short, long, value_name = "N")]
+        jobs: Option<usize>,
+    },
+
+    /// Plan a migration to CUE or Nickel: effort, phases and skeleton schemas
+    Recommend {
+        /// Files to plan for; directories are searched for config files
+        /// (default: the current directory)
         files: Vec<PathBuf>,
 
         /// Output format
-        #[clap(short, long, default_value = "text", value_parser = ["text", "json"])]
+        #[clap(short, long, value_enum, default_value_t = OutputFormat::Text)]
         format: OutputFormat,
+
+        /// Write the first phase's skeleton schemas into this directory
+        #[clap(short, long, value_name = "DIR")]
+        output: Option<PathBuf>,
+    },
+
+    /// Interactive CUE and Nickel lessons, checked by the real tools
+    Learn {
+        /// Lesson to take: cue-basics, nickel-contracts or unification
+        /// (default: list lessons and progress)
+        lesson: Option<String>,
+
+        /// Sandbox directory for exercises and progress
+        #[clap(long, value_name = "DIR", default_value = DEFAULT_SANDBOX)]
+        dir: PathBuf,
+
+        /// Check the current exercise once and exit, failing if unsolved
+        #[clap(long)]
+        check: bool,
+
+        /// Start the lesson over, restoring the exercises' starter files
+        #[clap(long)]
+        reset: bool,
     },
 
     /// Run the pipeline
@@ -75,7 +226,7 @@ pub enum Commands {
         pipeline: PathBuf,
 
         /// Run only specific stages
-        #[clap(short, long)]
+        #[clap(short, long, value_name = "STAGE")]
         stage: Vec<String>,
 
         /// Skip cache (force re-execution)
@@ -85,6 +236,116 @@ pub enum Commands {
         /// Dry run (show what would be done)
         #[clap(long)]
         dry_run: bool,
+
+        /// Run only stages affected by changes since this git ref (e.g. origin/main)
+        #[clap(long, value_name = "REF")]
+        since: Option<String>,
+
+        /// Pick up an interrupted or failed run after the stages it passed
+        #[clap(long, conflicts_with = "since")]
+        resume: bool,
+
+        /// Write a Chrome trace of stage timings (default: conflow-profile.json)
+        #[clap(long, value_name = "FILE", num_args = 0..=1, default_missing_value = "conflow-profile.json")]
+        profile: Option<PathBuf>,
+
+        /// Show an interactive dashboard while the pipeline runs
+        #[clap(long)]
+        tui: bool,
+
+        /// Only print failures, without progress or outputs
+        #[clap(short, long, conflicts_with = "tui")]
+        quiet: bool,
+
+        /// Write tool diagnostics with source locations to FILE
+        #[clap(long, value_name = "FILE")]
+        report: Option<PathBuf>,
+
+        /// Diagnostics report format
+        #[clap(long, value_enum, default_value_t = ReportFormat::Sarif)]
+        report_format: ReportFormat,
+
+        /// Group failures, and JSON reports, by CODEOWNERS owner
+        #[clap(long, value_enum, value_name = "KEY")]
+        group_by: Option<GroupBy>,
+
+        /// Run stage commands without network, with read-only access outside the project
+        #[clap(long)]
+        sandbox: bool,
+
+        /// Approve all manual stages without asking
+        #[clap(short, long)]
+        yes: bool,
+
+        /// Approve a manual stage without asking (repeatable)
+        #[clap(long, value_name = "STAGE")]
+        approve: Vec<String>,
+
+        /// Post the results as a GitHub PR or GitLab MR comment
+        #[clap(long)]
+        pr_comment: bool,
+
+        /// Don't wait for other conflow runs in this project to finish
+        #[clap(long)]
+        no_lock: bool,
+
+        /// Seconds to wait for another run before giving up
+        #[clap(
+            long,
+            value_name = "SECS",
+            default_value_t = crate::pipeline::DEFAULT_LOCK_TIMEOUT.as_secs(),
+            conflicts_with = "no_lock"
+        )]
+        lock_timeout: u64,
+
+        /// Lowest severity that fails the run; failing stages always do
+        #[clap(
+            long,
+            value_name = "SEVERITY",
+            value_parser = FailOn::severities(),
+            default_value = "error"
+        )]
+        fail_on: FailOn,
+
+        /// Run the pipeline of a bundle from 'conflow bundle export'
+        #[clap(long, value_name = "BUNDLE", conflicts_with_all = ["pipeline", "since", "resume"])]
+        from_bundle: Option<PathBuf>,
+
+        /// Send stages with runs_on labels to agents of this coordinator (experimental)
+        #[clap(long, value_name = "URL", env = "CONFLOW_COORDINATOR")]
+        coordinator: Option<String>,
+
+        /// API key for the coordinator
+        #[clap(
+            long,
+            value_name = "KEY",
+            env = "CONFLOW_API_KEY",
+            hide_env_values = true
+        )]
+        api_key: Option<String>,
+
+        /// Key agents accept from runners
+        #[clap(
+            long,
+            value_name = "KEY",
+            env = "CONFLOW_RUNNER_KEY",
+            hide_env_values = true,
+            requires = "coordinator"
+        )]
+        runner_key: Option<String>,
+
+        /// Trust agent certificates signed by this PEM CA instead of the web roots
+        #[clap(
+            long,
+            value_name = "FILE",
+            env = "CONFLOW_AGENT_CA",
+            requires = "coordinator"
+        )]
+        agent_ca: Option<PathBuf>,
+
+        /// Send variables listed under secrets: or redact.env to agents
+        #[clap(long, requires = "coordinator")]
+        send_secrets: bool,
     },
 
     /// Watch mode - re-run pipeline on file changes
@@ -96,6 +357,110 @@ pub enum Commands {
         /// Debounce delay in milliseconds
         #[clap(long, default_value = "500")]
         debounce: u64,
+
+        /// Serve Prometheus metrics on this address (e.g. 127.0.0.1:9464)
+        #[clap(long, value_name = "ADDR")]
+        metrics_addr: Option<std::net::SocketAddr>,
+    },
+
+    /// Serve validation, analysis and compliance checks over HTTP
+    Serve {
+        /// Address to listen on
+        #[clap(long, value_name = "ADDR", default_value = "127.0.0.1:8080")]
+        addr: std::net::SocketAddr,
+
+        /// Also serve the gRPC API, with remote pipeline runs, on this address
+        #[clap(long, value_name = "ADDR")]
+        grpc: Option<std::net::SocketAddr>,
+
+        /// Accepted API key (repeatable, or comma-separated in CONFLOW_API_KEYS)
+        #[clap(
+            long = "api-key",
+            value_name = "KEY",
+            env = "CONFLOW_API_KEYS",
+            value_delimiter = ',',
+            hide_env_values = true
+        )]
+        api_keys: Vec<String>,
+
+        /// Accept requests without an API key
+        #[clap(long, conflicts_with = "api_keys")]
+        no_auth: bool,
+
+        /// Largest accepted request body, in bytes
+        #[clap(long, value_name = "BYTES", default_value_t = DEFAULT_MAX_BODY)]
+        max_body: usize,
+
+        /// Check this project's compliance on its schedule (repeatable)
+        #[clap(long = "project", value_name = "DIR")]
+        projects: Vec<PathBuf>,
+
+        /// Cron schedule (UTC) for projects without compliance.schedule
+        #[clap(long, value_name = "CRON", requires = "projects")]
+        schedule: Option<Schedule>,
+
+        /// Key agents register with the gRPC coordinator by (repeatable, or
+        /// comma-separated in CONFLOW_AGENT_KEYS)
+        #[clap(
+            long = "agent-key",
+            value_name = "KEY",
+            env = "CONFLOW_AGENT_KEYS",
+            value_delimiter = ',',
+            hide_env_values = true,
+            requires = "grpc"
+        )]
+        agent_keys: Vec<String>,
+    },
+
+    /// Run stages for 'conflow run --coordinator' on this machine (experimental)
+    Agent {
+        /// Address to listen on
+        #[clap(long, value_name = "ADDR", default_value = "127.0.0.1:50052")]
+        listen: std::net::SocketAddr,
+
+        /// URL runners reach this agent at (default: https://<listen>)
+        #[clap(long, value_name = "URL")]
+        advertise: Option<String>,
+
+        /// PEM certificate the agent serves TLS with
+        #[clap(long, value_name = "FILE")]
+        tls_cert: PathBuf,
+
+        /// PEM private key of --tls-cert
+        #[clap(long, value_name = "FILE")]
+        tls_key: PathBuf,
+
+        /// Label stages can ask for with runs_on (repeatable)
+        #[clap(long = "label", value_name = "LABEL", required = true)]
+        labels: Vec<String>,
+
+        /// Register with the coordinator at this URL (e.g. http://ci:50051)
+        #[clap(long, value_name = "URL", env = "CONFLOW_COORDINATOR")]
+        coordinator: Option<String>,
+
+        /// Key to register with the coordinator by (its --agent-key)
+        #[clap(
+            long,
+            value_name = "KEY",
+            env = "CONFLOW_AGENT_KEY",
+            hide_env_values = true
+        )]
+        agent_key: Option<String>,
+
+        /// Key runners must send (repeatable, or comma-separated in CONFLOW_RUNNER_KEYS)
+        #[clap(
+            long = "runner-key",
+            value_name = "KEY",
+            env = "CONFLOW_RUNNER_KEYS",
+            value_delimiter = ',',
+            hide_env_values = true,
+            required = true
+        )]
+        runner_keys: Vec<String>,
+
+        /// Largest upload of files for one stage, in bytes
+        #[clap(long, value_name = "BYTES", default_value_t = agent::DEFAULT_MAX_UPLOAD)]
+        max_upload: u64,
     },
 
     /// Validate pipeline configuration
@@ -103,6 +468,228 @@ pub enum Commands {
         /// Pipeline file to validate
         #[clap(default_value = ".conflow.yaml")]
         pipeline: PathBuf,
+
+        /// Lowest severity that fails validation
+        #[clap(
+            long,
+            value_name = "SEVERITY",
+            value_parser = FailOn::severities(),
+            default_value = "error"
+        )]
+        fail_on: FailOn,
+    },
+
+    /// Lint config files with the built-in and custom rules under `lint:`
+    Lint {
+        /// Files to lint (default: the files selected by `lint.files`)
+        files: Vec<PathBuf>,
+
+        /// Fix what can be fixed safely, showing a diff of each file
+        #[clap(long)]
+        fix: bool,
+
+        /// Show the fixes without writing them
+        #[clap(long, requires = "fix")]
+        dry_run: bool,
+
+        /// List the built-in rules and exit
+        #[clap(long, conflicts_with_all = ["files", "fix"])]
+        list_rules: bool,
+
+        /// Pipeline file
+        #[clap(short, long, default_value = ".conflow.yaml")]
+        pipeline: PathBuf,
+
+        /// Lowest severity that fails the lint
+        #[clap(
+            long,
+            value_name = "SEVERITY",
+            value_parser = FailOn::severities(),
+            default_value = "error"
+        )]
+        fail_on: FailOn,
+
+        /// Output format
+        #[clap(short, long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+    },
+
+    /// Find manifests using Kubernetes APIs deprecated or removed in the target version
+    Deprecations {
+        /// Manifests to check (default: the files selected by `kubernetes.files`)
+        files: Vec<PathBuf>,
+
+        /// Kubernetes version to check against (default: `kubernetes.version`)
+        #[clap(long, value_name = "VERSION")]
+        target: Option<String>,
+
+        /// Pipeline file
+        #[clap(short, long, default_value = ".conflow.yaml")]
+        pipeline: PathBuf,
+
+        /// Lowest severity that fails the check: removed APIs are errors,
+        /// deprecated ones warnings
+        #[clap(
+            long,
+            value_name = "SEVERITY",
+            value_parser = FailOn::severities(),
+            default_value = "error"
+        )]
+        fail_on: FailOn,
+
+        /// Output format
+        #[clap(short, long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+    },
+
+    /// Render Helm chart templates and Go-templated files with sample values
+    Render {
+        /// Templates, or chart directories to render every template of
+        #[clap(required = true)]
+        files: Vec<PathBuf>,
+
+        /// Values file layered over the chart's values.yaml (repeatable)
+        #[clap(short = 'f', long = "values", value_name = "FILE")]
+        values: Vec<PathBuf>,
+
+        /// Set a value by dotted key (repeatable)
+        #[clap(long = "set", value_name = "KEY=VALUE")]
+        set: Vec<String>,
+
+        /// Release name
+        #[clap(long, value_name = "NAME")]
+        release: Option<String>,
+
+        /// Release namespace
+        #[clap(short, long)]
+        namespace: Option<String>,
+
+        /// Kubernetes version templates see in `.Capabilities` (default:
+        /// `kubernetes.version` in .conflow.yaml)
+        #[clap(long, value_name = "VERSION")]
+        kube_version: Option<String>,
+
+        /// Write the rendered files into this directory instead of printing them
+        #[clap(short, long, value_name = "DIR")]
+        output: Option<PathBuf>,
+    },
+
+    /// Upgrade a pipeline file to the current schema version
+    Migrate {
+        /// Pipeline file to migrate
+        #[clap(default_value = ".conflow.yaml")]
+        pipeline: PathBuf,
+
+        /// Show the changes without writing them
+        #[clap(long)]
+        dry_run: bool,
+
+        /// Write the changes without asking
+        #[clap(short, long)]
+        yes: bool,
+    },
+
+    /// Find config keys no schema declares and schema fields no config sets
+    Unused {
+        /// Config files to check (default: the inputs of the pipeline's CUE stages)
+        files: Vec<PathBuf>,
+
+        /// Check the files against this schema (CUE or JSON Schema) instead
+        #[clap(short, long, value_name = "FILE", requires = "files")]
+        schema: Vec<PathBuf>,
+
+        /// Pipeline file
+        #[clap(short, long, default_value = ".conflow.yaml")]
+        pipeline: PathBuf,
+
+        /// Output format
+        #[clap(short, long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+    },
+
+    /// List the configs, stages, artifacts and environments an edit to FILE affects
+    Impact {
+        /// The file being edited
+        file: PathBuf,
+
+        /// Pipeline file
+        #[clap(short, long, default_value = ".conflow.yaml")]
+        pipeline: PathBuf,
+
+        /// Output format
+        #[clap(short, long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+    },
+
+    /// Export an inventory of the repository's config files: format, schemas,
+    /// validation status and CODEOWNERS owners
+    Inventory {
+        /// Files to list (default: every config file git doesn't ignore)
+        #[clap(value_name = "GLOB")]
+        patterns: Vec<String>,
+
+        /// Pipeline file
+        #[clap(short, long, default_value = ".conflow.yaml")]
+        pipeline: PathBuf,
+
+        /// Write the inventory as JSON to this file
+        #[clap(short, long)]
+        output: Option<PathBuf>,
+
+        /// Output format
+        #[clap(short, long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+    },
+
+    /// Validate every recognized config file against its detected built-in
+    /// schema, without a pipeline
+    Scan {
+        /// Files to scan (default: every JSON and YAML file git doesn't ignore)
+        #[clap(value_name = "GLOB")]
+        patterns: Vec<String>,
+
+        /// Pipeline file, whose cache directory is left out
+        #[clap(short, long, default_value = ".conflow.yaml")]
+        pipeline: PathBuf,
+
+        /// Lowest severity that fails the scan
+        #[clap(
+            long,
+            value_name = "SEVERITY",
+            value_parser = FailOn::severities(),
+            default_value = "error"
+        )]
+        fail_on: FailOn,
+
+        /// Output format
+        #[clap(short, long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+    },
+
+    /// Show where the final value at a path comes from
+    Why {
+        /// Dotted path, e.g. service.replicas or spec.ports[0].name
+        path: String,
+
+        /// Stage whose files to trace (default: the only CUE or Helm stage)
+        #[clap(short, long)]
+        stage: Option<String>,
+
+        /// Trace these files, in merge order, instead of a stage's
+        #[clap(long, value_name = "FILE", num_args = 1.., conflicts_with = "stage")]
+        files: Vec<PathBuf>,
+
+        /// How the --files combine [default: override]
+        #[clap(long, value_enum, requires = "files")]
+        merge: Option<MergeMode>,
+
+        /// Pipeline file
+        #[clap(short, long, default_value = ".conflow.yaml")]
+        pipeline: PathBuf,
+
+        /// Output format
+        #[clap(short, long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
     },
 
     /// Cache management
@@ -111,6 +698,46 @@ pub enum Commands {
         action: CacheAction,
     },
 
+    /// Pack tools, profiles and templates for machines without network access
+    Bundle {
+        #[clap(subcommand)]
+        action: BundleAction,
+    },
+
+    /// Golden-file snapshots of stage output
+    Snapshot {
+        #[clap(subcommand)]
+        action: SnapshotAction,
+    },
+
+    /// Check whether the project is ready to release
+    Release {
+        #[clap(subcommand)]
+        action: ReleaseAction,
+    },
+
+    /// Keep the version the same in every file that declares it
+    Version {
+        #[clap(subcommand)]
+        action: VersionAction,
+    },
+
+    /// Explain an error code (e.g. CF0011), or list them all
+    Explain {
+        /// Error code or name
+        #[clap(value_name = "CODE")]
+        code: Option<String>,
+
+        /// Show each constraint the last run failed as a minimal failing
+        /// config next to its corrected version
+        #[clap(long)]
+        show_fix: bool,
+
+        /// Output format
+        #[clap(short, long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+    },
+
     /// Show pipeline as a graph
     Graph {
         /// Pipeline file
@@ -118,8 +745,22 @@ pub enum Commands {
         pipeline: PathBuf,
 
         /// Output format
-        #[clap(short, long, default_value = "text", value_parser = ["text", "dot", "mermaid"])]
+        #[clap(short, long, value_enum, default_value_t = GraphFormat::Text)]
         format: GraphFormat,
+
+        /// Graph which config files reference which across the project instead
+        #[clap(long)]
+        configs: bool,
+
+        /// List the config files affected by changing FILE
+        #[clap(long, value_name = "FILE", requires = "configs")]
+        affected: Option<PathBuf>,
+    },
+
+    /// Check Git revision ranges, failing only on newly introduced violations
+    Gitops {
+        #[clap(subcommand)]
+        action: GitopsAction,
     },
 
     /// RSR (Rhodium Standard Repository) integration
@@ -127,6 +768,135 @@ pub enum Commands {
         #[clap(subcommand)]
         action: RsrAction,
     },
+
+    /// List, show and compare recorded pipeline runs
+    Runs {
+        #[clap(subcommand)]
+        action: RunsAction,
+    },
+
+    /// Show the audit log of file changes conflow made
+    Audit {
+        /// Only this action
+        #[clap(long, value_name = "ID")]
+        id: Option<u64>,
+
+        /// Only changes to files under this path
+        #[clap(long)]
+        path: Option<PathBuf>,
+
+        /// Only changes by this actor
+        #[clap(long)]
+        actor: Option<String>,
+
+        /// Only changes at or after this date or RFC 3339 time
+        #[clap(long, value_name = "DATE")]
+        since: Option<String>,
+
+        /// Check that no entry was edited or removed
+        #[clap(long)]
+        verify: bool,
+
+        /// Output format
+        #[clap(short, long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+    },
+
+    /// Put back the files an action from the audit log changed
+    Undo {
+        /// Action to undo
+        #[clap(value_name = "ACTION_ID", required_unless_present = "last")]
+        id: Option<u64>,
+
+        /// Undo the newest action not undone yet
+        #[clap(long, conflicts_with = "id")]
+        last: bool,
+
+        /// Put back files changed again since the action
+        #[clap(long)]
+        force: bool,
+    },
+
+    /// Run pipelines and check compliance across the projects of a workspace
+    Workspace {
+        #[clap(subcommand)]
+        action: WorkspaceAction,
+    },
+
+    /// List and render project templates
+    Template {
+        #[clap(subcommand)]
+        action: TemplateAction,
+    },
+
+    /// Manage and run WASM plugins
+    Plugin {
+        #[clap(subcommand)]
+        action: PluginAction,
+    },
+
+    /// Install and list pinned tool versions
+    Toolchain {
+        #[clap(subcommand)]
+        action: ToolchainAction,
+    },
+
+    /// Export schemas for conflow's own config files, or detect a file's schema
+    Schema {
+        #[clap(subcommand)]
+        action: SchemaAction,
+    },
+
+    /// Split, join and check multi-document YAML streams
+    Yaml {
+        #[clap(subcommand)]
+        action: YamlAction,
+    },
+
+    /// Generate shell completions
+    Completion {
+        /// Shell to generate the script for
+        #[clap(value_enum)]
+        shell: CompletionShell,
+    },
+
+    /// Print dynamic completion values (used by completion scripts)
+    #[clap(name = "__complete", hide = true)]
+    Complete {
+        #[clap(value_enum)]
+        kind: DynamicValues,
+
+        /// Pipeline file to read stage names from
+        #[clap(short, long, default_value = ".conflow.yaml")]
+        pipeline: PathBuf,
+    },
+}
+
+/// GitOps actions
+#[derive(Subcommand, Debug, Clone)]
+pub enum GitopsAction {
+    /// Check the configs changed between two revisions; fail only on new violations
+    Check {
+        /// Revision to compare against (e.g. v1.2.0)
+        #[clap(long, value_name = "REF")]
+        from: String,
+
+        /// Revision to check
+        #[clap(long, value_name = "REF", default_value = "HEAD")]
+        to: String,
+
+        /// Pipeline file, relative to the current directory
+        #[clap(short, long, default_value = ".conflow.yaml")]
+        pipeline: PathBuf,
+
+        /// Output format
+        #[clap(short, long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+
+        /// Only check syntax, the pipeline and references, without running stages
+        #[clap(long)]
+        no_run: bool,
+    },
 }
 
 /// RSR integration actions
@@ -135,12 +905,40 @@ pub enum RsrAction {
     /// Check RSR compliance
     Check {
         /// Specific requirements to check (default: all)
-        #[clap(short, long)]
+        #[clap(short, long, value_name = "REQUIREMENT")]
         requirement: Vec<String>,
 
         /// Output format
-        #[clap(short, long, default_value = "text", value_parser = ["text", "json"])]
+        #[clap(short, long, value_enum, default_value_t = OutputFormat::Text)]
         format: OutputFormat,
+
+        /// What fails the check: unmet mandatory requirements (`error`), any unmet
+        /// requirement (`warning`), or only requirements that pass on --base (`regression`)
+        #[clap(
+            long,
+            value_enum,
+            value_name = "SEVERITY",
+            default_value_t = FailOn::Error,
+            requires_if("regression", "base")
+        )]
+        fail_on: FailOn,
+
+        /// Git ref to compare against; requirements that pass there but fail now
+        /// are regressions (e.g. origin/main)
+        #[clap(long, value_name = "REF")]
+        base: Option<String>,
+
+        /// Seconds a requirement's shell check may run before it is killed and fails
+        #[clap(
+            long,
+            value_name = "SECS",
+            default_value_t = DEFAULT_CHECK_TIMEOUT.as_secs()
+        )]
+        timeout: u64,
+
+        /// Group unmet requirements by CODEOWNERS owner
+        #[clap(long, value_enum, value_name = "KEY")]
+        group_by: Option<GroupBy>,
     },
 
     /// Show RSR requirements
@@ -150,7 +948,7 @@ pub enum RsrAction {
         tag: Option<String>,
 
         /// Show only specific requirement
-        #[clap(short, long)]
+        #[clap(short, long, value_name = "REQUIREMENT")]
         id: Option<String>,
     },
 
@@ -164,12 +962,300 @@ pub enum RsrAction {
     /// Export an RSR schema
     Schema {
         /// Schema ID to export
+        #[clap(value_name = "SCHEMA")]
         id: String,
 
         /// Output file (default: stdout)
         #[clap(short, long)]
         output: Option<PathBuf>,
     },
+
+    /// Fetch and list the profiles .rsr.yaml inherits from
+    Profiles {
+        /// Fetch every profile again, even if it is cached
+        #[clap(long)]
+        update: bool,
+    },
+
+    /// Write compliance badges as SVG and shields.io endpoint JSON
+    Badges {
+        /// Output directory (default: integrations.badges.output in .rsr.yaml)
+        #[clap(short, long, value_name = "DIR")]
+        output: Option<PathBuf>,
+
+        /// Badge style (default: integrations.badges.style in .rsr.yaml)
+        #[clap(short, long, value_enum)]
+        style: Option<BadgeStyle>,
+    },
+
+    /// List and render templates (same as 'conflow template')
+    Template {
+        #[clap(subcommand)]
+        action: TemplateAction,
+    },
+
+    /// Accept a failing requirement until a date, recorded in .rsr/waivers.yaml
+    Waive {
+        /// Requirement to waive
+        #[clap(value_name = "REQUIREMENT")]
+        requirement: String,
+
+        /// Why the failure is accepted
+        #[clap(long)]
+        reason: String,
+
+        /// Who approved the waiver
+        #[clap(long)]
+        approver: String,
+
+        /// Date the waiver expires (YYYY-MM-DD)
+        #[clap(long, value_name = "DATE")]
+        expires: String,
+    },
+}
+
+/// Template actions
+#[derive(Subcommand, Debug, Clone)]
+pub enum TemplateAction {
+    /// List built-in, repository and project templates (.rsr/templates)
+    List {
+        /// Fetch every git template repository again, even if it is cached
+        #[clap(long)]
+        update: bool,
+    },
+
+    /// Render a template into the project
+    Render {
+        /// Template name
+        name: String,
+
+        /// Set a template variable (repeatable)
+        #[clap(long = "set", value_name = "KEY=VALUE")]
+        set: Vec<String>,
+
+        /// Directory to write files into
+        #[clap(short, long, default_value = ".")]
+        output: PathBuf,
+
+        /// Print the rendered files instead of writing them
+        #[clap(long)]
+        dry_run: bool,
+
+        /// Replace files that already exist
+        #[clap(long)]
+        force: bool,
+    },
+}
+
+/// Schema actions
+#[derive(Subcommand, Debug, Clone)]
+pub enum SchemaAction {
+    /// Print a schema for editor validation and completion
+    Export {
+        /// Config file schema
+        #[clap(value_enum)]
+        id: ConfigSchema,
+
+        /// Schema format
+        #[clap(short, long, value_enum, default_value_t = SchemaFormat::Jsonschema)]
+        format: SchemaFormat,
+
+        /// Output file (default: stdout)
+        #[clap(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Show the built-in schema each config file matches
+    Detect {
+        /// JSON or YAML files
+        #[clap(required = true)]
+        files: Vec<PathBuf>,
+
+        /// Output format
+        #[clap(short, long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+    },
+}
+
+/// conflow's own config files
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ConfigSchema {
+    /// .conflow.yaml
+    ConflowPipeline,
+    /// .rsr.yaml
+    ConflowRsr,
+}
+
+/// Format of an exported config schema
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum SchemaFormat {
+    Jsonschema,
+    Cue,
+}
+
+/// YAML stream actions
+#[derive(Subcommand, Debug, Clone)]
+pub enum YamlAction {
+    /// Write each document of a stream to its own file
+    Split {
+        /// Multi-document YAML file
+        file: PathBuf,
+
+        /// Output directory (default: next to the input)
+        #[clap(short, long, value_name = "DIR")]
+        output: Option<PathBuf>,
+    },
+
+    /// Join YAML files into one `---` separated stream
+    Join {
+        /// Files to join, in order
+        #[clap(required = true)]
+        files: Vec<PathBuf>,
+
+        /// Output file (default: stdout)
+        #[clap(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Check that YAML or JSON files parse, streaming them without loading them
+    Check {
+        /// Files to check; suited to multi-hundred-MB rendered manifests
+        #[clap(required = true)]
+        files: Vec<PathBuf>,
+    },
+}
+
+/// Plugin actions
+#[derive(Subcommand, Debug, Clone)]
+pub enum PluginAction {
+    /// List discovered plugins
+    List {
+        /// Pipeline file declaring project plugins
+        #[clap(short, long, default_value = ".conflow.yaml")]
+        pipeline: PathBuf,
+    },
+
+    /// Run plugin analyzers and validators on config files
+    Check {
+        /// Files to check
+        #[clap(required = true)]
+        files: Vec<PathBuf>,
+
+        /// Pipeline file declaring project plugins
+        #[clap(short, long, default_value = ".conflow.yaml")]
+        pipeline: PathBuf,
+
+        /// Apply fixes from remediator plugins
+        #[clap(long)]
+        fix: bool,
+    },
+}
+
+/// Run record actions
+#[derive(Subcommand, Debug, Clone)]
+pub enum RunsAction {
+    /// List recent runs, newest first
+    List {
+        /// How many runs to list
+        #[clap(short = 'n', long, default_value_t = 20)]
+        limit: usize,
+
+        /// Output format
+        #[clap(short, long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+    },
+
+    /// Show a run's stages, commit and timings
+    Show {
+        /// Run ID, or `latest`
+        #[clap(default_value = "latest")]
+        id: String,
+
+        /// Output format
+        #[clap(short, long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+    },
+
+    /// Show what changed between two runs
+    Compare {
+        /// Earlier run ID
+        from: String,
+
+        /// Later run ID, or `latest`
+        #[clap(default_value = "latest")]
+        to: String,
+    },
+}
+
+/// Workspace actions
+#[derive(Subcommand, Debug, Clone)]
+pub enum WorkspaceAction {
+    /// Run every project's pipeline
+    Run {
+        /// Workspace file
+        #[clap(short, long, default_value = WORKSPACE_FILE)]
+        file: PathBuf,
+
+        /// Only these projects
+        #[clap(short, long, value_name = "NAME")]
+        project: Vec<String>,
+
+        /// Clone Git projects afresh
+        #[clap(long)]
+        update: bool,
+
+        /// Skip cache (force re-execution)
+        #[clap(long)]
+        no_cache: bool,
+
+        /// Stop at the first failing project
+        #[clap(long)]
+        fail_fast: bool,
+    },
+
+    /// Check every project's compliance and show a requirement-by-project matrix
+    Report {
+        /// Workspace file
+        #[clap(short, long, default_value = WORKSPACE_FILE)]
+        file: PathBuf,
+
+        /// Only these projects
+        #[clap(short, long, value_name = "NAME")]
+        project: Vec<String>,
+
+        /// Clone Git projects afresh
+        #[clap(long)]
+        update: bool,
+
+        /// Output format
+        #[clap(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+
+        /// Write the matrix as a JSON report to this file
+        #[clap(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+/// Toolchain actions
+#[derive(Subcommand, Debug, Clone)]
+pub enum ToolchainAction {
+    /// Download tools into the conflow-managed directory
+    Install {
+        /// Tools to install, e.g. cue@0.9 nickel@1.7 (default: all pinned)
+        tools: Vec<crate::toolchain::ToolRequest>,
+
+        /// Pipeline file with the 'toolchain:' section
+        #[clap(short, long, default_value = ".conflow.yaml")]
+        pipeline: PathBuf,
+    },
+
+    /// Show installed and pinned versions
+    List {
+        /// Pipeline file with the 'toolchain:' section
+        #[clap(short, long, default_value = ".conflow.yaml")]
+        pipeline: PathBuf,
+    },
 }
 
 /// Cache management actions
@@ -187,10 +1273,108 @@ pub enum CacheAction {
 
     /// List cached entries
     List,
+
+    /// Remove stale entries and evict entries beyond the size and age limits
+    Gc {
+        /// Keep at most this many megabytes (default: cache.max_size_mb)
+        #[clap(long, value_name = "MB")]
+        max_size_mb: Option<u64>,
+
+        /// Remove entries unused for this many days (default: cache.max_age_days)
+        #[clap(long, value_name = "DAYS")]
+        max_age_days: Option<u64>,
+    },
+
+    /// Check every entry against its checksums
+    Verify {
+        /// Remove corrupt entries so they are recomputed
+        #[clap(long)]
+        repair: bool,
+    },
+}
+
+/// Bundle actions
+#[derive(Subcommand, Debug, Clone)]
+pub enum BundleAction {
+    /// Fetch what the project needs and pack it into a bundle
+    Create {
+        /// Bundle to write
+        #[clap(short, long, default_value = "conflow-bundle.tar.gz")]
+        output: PathBuf,
+
+        /// Pipeline file
+        #[clap(short, long, default_value = ".conflow.yaml")]
+        pipeline: PathBuf,
+    },
+
+    /// Pack a pipeline run: its pipeline, the files it reads, its templates
+    /// and the versions of its tools
+    Export {
+        /// Bundle to write
+        #[clap(short, long, default_value = "conflow-run.tar.gz")]
+        output: PathBuf,
+
+        /// Pipeline file
+        #[clap(short, long, default_value = ".conflow.yaml")]
+        pipeline: PathBuf,
+    },
+
+    /// Unpack a bundle into the local caches
+    Load {
+        /// Bundle to unpack
+        bundle: PathBuf,
+    },
+}
+
+/// Snapshot actions
+#[derive(Subcommand, Debug, Clone)]
+pub enum SnapshotAction {
+    /// Accept the changed output of stages as their new snapshots
+    Update {
+        /// Stages to accept (default: all with changed output)
+        stages: Vec<String>,
+    },
+
+    /// List snapshots with changed output waiting to be accepted
+    List,
+}
+
+/// Release actions
+#[derive(Subcommand, Debug, Clone)]
+pub enum ReleaseAction {
+    /// Run the release gates (version, changelog, schemas, compliance,
+    /// pipeline) and give a go/no-go verdict
+    Check {
+        /// Previous release to compare with (default: the latest tag)
+        #[clap(long, value_name = "REF")]
+        since: Option<String>,
+
+        /// Output format
+        #[clap(short, long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+    },
+}
+
+/// Version actions
+#[derive(Subcommand, Debug, Clone)]
+pub enum VersionAction {
+    /// Check that every location under versions: holds the same version
+    Check {
+        /// Output format
+        #[clap(short, long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+    },
+
+    /// Set every location under versions: to a new version, or none if one
+    /// can't be changed
+    Bump {
+        /// New version, e.g. 1.4.0
+        version: String,
+    },
 }
 
 /// Output format for analyze command
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
 pub enum OutputFormat {
     Text,
     Json,
@@ -208,8 +1392,61 @@ impl std::str::FromStr for OutputFormat {
     }
 }
 
+/// Language for abstractions emitted by the analyze command
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum AbstractionFormat {
+    Cue,
+    Nickel,
+}
+
+/// How files passed to the why command combine
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum MergeMode {
+    /// Later files override earlier ones, like Helm values
+    Override,
+    /// Values must agree and defaults fill the gaps, like CUE
+    Unify,
+}
+
+/// Diagnostics report format for the run command
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ReportFormat {
+    Json,
+    #[default]
+    Sarif,
+    /// GitLab Code Quality, shown inline in merge requests
+    CodeQuality,
+}
+
+/// How reports group their findings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum GroupBy {
+    /// The owners CODEOWNERS assigns to each finding's file
+    Owner,
+}
+
+/// Shell for the completion command
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum CompletionShell {
+    Bash,
+    Zsh,
+    Fish,
+    Powershell,
+}
+
+/// Values completed at runtime rather than baked into the script
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum DynamicValues {
+    /// Stage names from the pipeline file
+    Stages,
+    /// RSR schema IDs
+    Schemas,
+    /// RSR requirement IDs
+    Requirements,
+}
+
 /// Graph output format
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
 pub enum GraphFormat {
     Text,
     Dot,
@@ -0,0 +1,72 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! New command - scaffold a complete project from a template
+
+use colored::Colorize;
+use miette::Result;
+
+use crate::rsr::config::CiProvider;
+use crate::rsr::templates::{Scaffold, TemplateGenerator};
+
+/// Run the new command
+pub async fn run(
+    name: String,
+    template: String,
+    tier: u8,
+    ci: Option<CiProvider>,
+    set: Vec<String>,
+    verbose: bool,
+) -> Result<()> {
+    let working_dir = std::env::current_dir()
+        .map_err(|e| miette::miette!("Failed to get current directory: {}", e))?;
+    let variables = super::template::parse_variables(&set)?;
+
+    let target = working_dir.join(&name);
+    let occupied = std::fs::read_dir(&target).is_ok_and(|mut entries| entries.next().is_some());
+    if occupied {
+        return Err(miette::miette!(
+            help = "Pick another name, or run 'conflow init' inside the directory",
+            "Directory '{}' already exists and is not empty",
+            name
+        ));
+    }
+
+    let generator = TemplateGenerator::for_project(&working_dir, false)?;
+    let scaffold = Scaffold {
+        project_name: name.clone(),
+        tier,
+        ci,
+    };
+    let result = generator.scaffold(&template, &target, &scaffold, &variables)?;
+
+    println!(
+        "{} {} {}",
+        "Created".bold(),
+        name.cyan().bold(),
+        format!("from {} (tier {})", result.template_name, tier).dimmed()
+    );
+    println!();
+    if verbose {
+        for dir in &result.directories_created {
+            println!("  {} {}/", "✓".green(), dir);
+        }
+    }
+    let mut files = result.files_created.clone();
+    files.sort();
+    for path in &files {
+        println!("  {} {}", "✓".green(), path);
+    }
+
+    println!();
+    println!("Next steps:");
+    println!("  1. {}", format!("cd {}", name).cyan());
+    println!("  2. Run {} to execute the pipeline", "conflow run".cyan());
+    println!(
+        "  3. Run {} to check RSR compliance",
+        "conflow rsr check".cyan()
+    );
+    println!();
+
+    Ok(())
+}
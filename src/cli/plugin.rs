@@ -0,0 +1,213 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Plugin command - list and run WASM plugins
+
+use colored::Colorize;
+use miette::Result;
+use std::path::{Path, PathBuf};
+
+use super::PluginAction;
+use crate::audit;
+use crate::pipeline::Pipeline;
+use crate::plugins::{self, Capability, Plugin, PluginRequest, Severity};
+use crate::utils::yaml;
+
+/// Run the plugin command
+pub async fn run(action: PluginAction, verbose: bool) -> Result<()> {
+    match action {
+        PluginAction::List { pipeline } => run_list(&pipeline, verbose),
+        PluginAction::Check {
+            files,
+            pipeline,
+            fix,
+        } => run_check(&files, &pipeline, fix, verbose),
+    }
+}
+
+/// Discover plugin paths from the pipeline and the user plugin directory
+fn discover(pipeline_path: &Path) -> Result<Vec<PathBuf>> {
+    let configured = if pipeline_path.exists() {
+        Pipeline::from_file(pipeline_path)?.plugins
+    } else {
+        vec![]
+    };
+    let base_dir = pipeline_path
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+
+    Ok(plugins::discover(
+        &configured,
+        base_dir,
+        plugins::user_plugin_dir().as_deref(),
+    ))
+}
+
+fn run_list(pipeline_path: &Path, verbose: bool) -> Result<()> {
+    let paths = discover(pipeline_path)?;
+
+    println!("{}", "Plugins".bold());
+    println!("{}", "═".repeat(50));
+
+    if paths.is_empty() {
+        println!("{}", "No plugins found.".dimmed());
+        if let Some(dir) = plugins::user_plugin_dir() {
+            println!("Install plugins into {}", dir.display().to_string().cyan());
+        }
+        return Ok(());
+    }
+
+    for path in paths {
+        match Plugin::load(&path) {
+            Ok(plugin) => {
+                let manifest = plugin.manifest();
+                let caps: Vec<String> = manifest
+                    .capabilities
+                    .iter()
+                    .map(|c| format!("{:?}", c).to_lowercase())
+                    .collect();
+                println!(
+                    "  {} {} {} [{}]",
+                    "✓".green(),
+                    manifest.name.bold(),
+                    manifest.version.dimmed(),
+                    caps.join(", ")
+                );
+                if let Some(ref description) = manifest.description {
+                    println!("    {}", description);
+                }
+                if verbose {
+                    println!("    {}", path.display().to_string().dimmed());
+                }
+            }
+            Err(e) => {
+                println!("  {} {}", "✗".red(), path.display());
+                println!("    {}", e.to_string().red());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn run_check(files: &[PathBuf], pipeline_path: &Path, fix: bool, verbose: bool) -> Result<()> {
+    let loaded: Vec<Plugin> = discover(pipeline_path)?
+        .iter()
+        .map(|p| Plugin::load(p))
+        .collect::<std::result::Result<_, _>>()?;
+
+    if loaded.is_empty() {
+        println!("{}", "No plugins found.".dimmed());
+        return Ok(());
+    }
+
+    let mut errors = 0;
+    let mut warnings = 0;
+
+    for file in files {
+        let mut requests = PluginRequest::from_file(file)?;
+        let mut index = 0;
+
+        while index < requests.len() {
+            for plugin in &loaded {
+                let name = &plugin.manifest().name;
+                let request = &requests[index];
+
+                for capability in [Capability::Validate, Capability::Analyze] {
+                    if !plugin.supports(capability) {
+                        continue;
+                    }
+
+                    let response = plugin.call(capability, request)?;
+                    for finding in &response.findings {
+                        // Finding lines are relative to the document
+                        let line = finding.line.map(|l| l + request.first_line - 1);
+                        let location = match (line, finding.column) {
+                            (Some(l), Some(c)) => format!("{}:{}:{}", file.display(), l, c),
+                            (Some(l), None) => format!("{}:{}", file.display(), l),
+                            _ => file.display().to_string(),
+                        };
+                        let document = request
+                            .document
+                            .map(|d| format!(" (document {})", d + 1))
+                            .unwrap_or_default();
+                        let label = match finding.severity {
+                            Severity::Error => {
+                                errors += 1;
+                                "error".red().bold()
+                            }
+                            Severity::Warning => {
+                                warnings += 1;
+                                "warning".yellow().bold()
+                            }
+                            Severity::Info => "info".blue().bold(),
+                        };
+                        println!(
+                            "{}: {}: {}{} {}",
+                            location,
+                            label,
+                            finding.message,
+                            document,
+                            format!("[{}]", finding.rule.as_deref().unwrap_or(name)).dimmed()
+                        );
+                    }
+                }
+
+                if fix && plugin.supports(Capability::Remediate) {
+                    let response = plugin.call(Capability::Remediate, request)?;
+                    if let Some(patch) = response.fixes.into_iter().next() {
+                        // A fix replaces one document of a stream, or the whole file
+                        let content = match request.document {
+                            Some(_) => {
+                                let mut docs: Vec<&str> =
+                                    requests.iter().map(|r| r.content.as_str()).collect();
+                                docs[index] = &patch.content;
+                                yaml::join(&docs)
+                            }
+                            None => patch.content.clone(),
+                        };
+                        let action = format!("plugin --fix {}", name);
+                        audit::write(Path::new("."), file, content, &action).map_err(|e| {
+                            miette::miette!("Failed to write {}: {}", file.display(), e)
+                        })?;
+                        println!(
+                            "{} {}: {} {}",
+                            "✓".green(),
+                            file.display(),
+                            patch.description,
+                            format!("[{}]", name).dimmed()
+                        );
+                        // Later plugins see the fixed content
+                        requests = PluginRequest::from_file(file)?;
+                        if index >= requests.len() {
+                            break;
+                        }
+                    }
+                }
+            }
+            index += 1;
+        }
+
+        if verbose {
+            println!("{} {}", "→".blue(), file.display());
+        }
+    }
+
+    println!();
+    if errors > 0 {
+        return Err(miette::miette!(
+            "Plugin checks failed: {} error(s), {} warning(s)",
+            errors,
+            warnings
+        ));
+    }
+
+    println!(
+        "{} {} file(s) checked, {} warning(s)",
+        "✓".green(),
+        files.len(),
+        warnings
+    );
+    Ok(())
+}
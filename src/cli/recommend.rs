@@ -0,0 +1,171 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Recommend command - plan a migration to CUE or Nickel
+
+use colored::Colorize;
+use miette::Result;
+use std::path::{Path, PathBuf};
+
+use super::analyze::config_files;
+use super::OutputFormat;
+use crate::analyzer::{plan_migration, MigrationPlan};
+use crate::utils::walk::Walker;
+
+/// Run the recommend command
+pub async fn run(files: Vec<PathBuf>, format: OutputFormat, output: Option<PathBuf>) -> Result<()> {
+    let files = if files.is_empty() {
+        vec![PathBuf::from(".")]
+    } else {
+        files
+    };
+    let walker = Walker::load(Path::new("."));
+    let mut existing = Vec::new();
+    for file in files {
+        if file.is_dir() {
+            let start = existing.len();
+            existing.extend(config_files(&file, &walker)?);
+            if file == Path::new(".") {
+                for path in &mut existing[start..] {
+                    *path = path.strip_prefix(".").unwrap_or(path).to_path_buf();
+                }
+            }
+        } else if file.exists() {
+            existing.push(file);
+        } else {
+            eprintln!("{}: File not found: {}", "Warning".yellow(), file.display());
+        }
+    }
+
+    let plan = plan_migration(&existing);
+    match format {
+        OutputFormat::Text => print_text(&plan, output.is_some()),
+        OutputFormat::Json => {
+            let json = serde_json::to_string_pretty(&plan)
+                .map_err(|e| miette::miette!("Failed to serialize plan: {}", e))?;
+            println!("{}", json);
+        }
+    }
+
+    if let Some(dir) = output {
+        write_schemas(&plan, &dir)?;
+    }
+    Ok(())
+}
+
+fn print_text(plan: &MigrationPlan, writing: bool) {
+    let tool = plan.recommendation.primary;
+    println!();
+    println!("{}", "Migration plan".bold());
+    println!("{}", "═".repeat(50));
+    println!();
+
+    if plan.files.is_empty() {
+        println!("No JSON, YAML or TOML files to migrate.");
+        println!();
+        return;
+    }
+
+    println!(
+        "{}: Use {}",
+        "Recommendation".bold(),
+        format!("{:?}", tool).green().bold()
+    );
+    for reason in &plan.recommendation.rationale {
+        println!("  • {}", reason);
+    }
+    println!();
+
+    println!("{}:", "Scope".bold());
+    let mut files = format!("{} ({} lines)", plan.files.len(), plan.lines);
+    if !plan.migrated.is_empty() {
+        files.push_str(&format!(
+            ", {} already in CUE or Nickel",
+            plan.migrated.len()
+        ));
+    }
+    println!("  Files:        {}", files);
+    if !plan.templated.is_empty() {
+        println!(
+            "  Templates:    {} (render before validating)",
+            plan.templated.len()
+        );
+    }
+    if !plan.logic.is_empty() {
+        println!("  With logic:   {}", plan.logic.len());
+    }
+    println!(
+        "  Duplication:  {} group(s), ~{} removable lines",
+        plan.duplicate_groups, plan.removable_lines
+    );
+    println!(
+        "  Schemas:      {} skeleton(s), ~{} lines",
+        plan.schemas.len(),
+        plan.schema_lines()
+    );
+    println!(
+        "  Effort:       ~{} hours",
+        format!("{}", plan.hours()).bold()
+    );
+    println!();
+
+    for (i, phase) in plan.phases.iter().enumerate() {
+        println!(
+            "{} {} {}",
+            format!("Phase {}:", i + 1).bold(),
+            phase.name.cyan().bold(),
+            format!("({} file(s), ~{} h)", phase.files, phase.hours).dimmed()
+        );
+        println!("  {}", phase.goal);
+        for step in &phase.steps {
+            println!("  • {}", step);
+        }
+        println!();
+    }
+
+    println!("{}:", "Skeleton schemas".bold());
+    for schema in &plan.schemas {
+        println!(
+            "  {:<28} {} document(s) in {} file(s)",
+            schema.file_name(tool),
+            schema.documents,
+            schema.files.len()
+        );
+    }
+    if !writing {
+        println!();
+        println!(
+            "Write them with {}",
+            "conflow recommend <files> -o schemas".cyan()
+        );
+    }
+    println!();
+}
+
+/// Write the skeleton schemas, keeping files that already exist
+fn write_schemas(plan: &MigrationPlan, dir: &Path) -> Result<()> {
+    std::fs::create_dir_all(dir)
+        .map_err(|e| miette::miette!("Failed to create {}: {}", dir.display(), e))?;
+
+    let tool = plan.recommendation.primary;
+    for schema in &plan.schemas {
+        let path = dir.join(schema.file_name(tool));
+        if path.exists() {
+            eprintln!(
+                "{}: {} exists, left as is",
+                "Warning".yellow(),
+                path.display()
+            );
+            continue;
+        }
+        std::fs::write(&path, schema.render(tool))
+            .map_err(|e| miette::miette!("Failed to write {}: {}", path.display(), e))?;
+        println!(
+            "{} Wrote {} ({} document(s))",
+            "✓".green(),
+            path.display().to_string().cyan(),
+            schema.documents
+        );
+    }
+    Ok(())
+}
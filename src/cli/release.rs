@@ -0,0 +1,63 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Release command - go/no-go release readiness check
+
+use colored::Colorize;
+use miette::Result;
+
+use super::{OutputFormat, ReleaseAction};
+use crate::errors::ConflowError;
+use crate::release::{GateStatus, ReleaseChecker, ReleaseReport};
+
+/// Run the release command
+pub async fn run(action: ReleaseAction) -> Result<()> {
+    let working_dir = std::env::current_dir().map_err(|e| ConflowError::Io {
+        message: e.to_string(),
+    })?;
+
+    match action {
+        ReleaseAction::Check { since, format } => {
+            let report = ReleaseChecker::new(&working_dir).with_base(since).check()?;
+            match format {
+                OutputFormat::Text => print_report(&report),
+                OutputFormat::Json => println!("{}", crate::report::to_json(&report)?),
+            }
+            if !report.ready {
+                return Err(ConflowError::ReleaseNotReady {
+                    gates: report.failed().map(|g| g.name.clone()).collect(),
+                }
+                .into());
+            }
+        }
+    }
+    Ok(())
+}
+
+fn print_report(report: &ReleaseReport) {
+    let version = report.version.as_deref().unwrap_or("unknown version");
+    match &report.base {
+        Some(base) => println!("{} {} since {}\n", "Release".bold(), version.cyan(), base),
+        None => println!("{} {}\n", "Release".bold(), version.cyan()),
+    }
+
+    for gate in &report.gates {
+        let icon = match gate.status {
+            GateStatus::Pass => "✓".green(),
+            GateStatus::Warn => "⚠".yellow(),
+            GateStatus::Fail => "✗".red(),
+            GateStatus::Skip => "○".dimmed(),
+        };
+        println!("  {} {:<11} {}", icon, gate.name, gate.summary);
+        for detail in &gate.details {
+            println!("      • {}", detail.dimmed());
+        }
+    }
+
+    println!();
+    if report.ready {
+        println!("{} Ready to release", "GO".green().bold());
+    } else {
+        println!("{} Not ready to release", "NO-GO".red().bold());
+    }
+}
@@ -0,0 +1,129 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Render command - render Helm and Go templates with sample values
+
+use colored::Colorize;
+use miette::Result;
+use std::path::{Path, PathBuf};
+
+use super::graph::project_relative;
+use super::template::parse_variables;
+use crate::analyzer::{is_chart_helper, render_template, untemplated_path, SampleValues};
+use crate::errors::ConflowError;
+use crate::pipeline::Pipeline;
+
+/// Run the render command
+pub async fn run(
+    files: Vec<PathBuf>,
+    values: Vec<PathBuf>,
+    set: Vec<String>,
+    release: Option<String>,
+    namespace: Option<String>,
+    kube_version: Option<String>,
+    output: Option<PathBuf>,
+) -> Result<()> {
+    let cwd = std::env::current_dir().map_err(|e| ConflowError::Io {
+        message: e.to_string(),
+    })?;
+
+    let pipeline = Path::new(".conflow.yaml");
+    let kube_version = match kube_version {
+        Some(version) => Some(version),
+        None if pipeline.exists() => Pipeline::from_file(pipeline)?.kubernetes.version,
+        None => None,
+    };
+    let mut sample = SampleValues {
+        files: values,
+        release,
+        namespace,
+        kube_version: kube_version
+            .map(|v| v.parse())
+            .transpose()
+            .map_err(|message| ConflowError::CheckFailed { message })?,
+        ..SampleValues::default()
+    };
+    for (key, value) in parse_variables(&set)? {
+        sample.set_str(&key, &value);
+    }
+
+    let mut rendered = 0;
+    for file in templates(&files, &cwd)? {
+        let content = std::fs::read_to_string(&file).map_err(|e| ConflowError::FileReadError {
+            path: file.clone(),
+            error: e.to_string(),
+        })?;
+        let result = render_template(&file, &content, &sample)?;
+        if result.trim().is_empty() {
+            continue;
+        }
+        rendered += 1;
+
+        let relative = project_relative(&file, &cwd);
+        match output {
+            Some(ref dir) => {
+                let path = dir.join(untemplated_path(&relative));
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent).map_err(|e| ConflowError::FileWriteError {
+                        path: parent.to_path_buf(),
+                        error: e.to_string(),
+                    })?;
+                }
+                std::fs::write(&path, result).map_err(|e| ConflowError::FileWriteError {
+                    path: path.clone(),
+                    error: e.to_string(),
+                })?;
+            }
+            None => {
+                println!("---");
+                println!("# Source: {}", relative.display());
+                print!("{}", result);
+                if !result.ends_with('\n') {
+                    println!();
+                }
+            }
+        }
+    }
+
+    if let Some(dir) = output {
+        println!(
+            "{} Rendered {} file(s) into {}",
+            "✓".green(),
+            rendered,
+            dir.display()
+        );
+    }
+    Ok(())
+}
+
+/// The files to render: templates as given, and every template of a chart
+/// directory, without helpers
+fn templates(files: &[PathBuf], cwd: &Path) -> Result<Vec<PathBuf>, ConflowError> {
+    let mut templates = Vec::new();
+    for file in files {
+        let path = cwd.join(file);
+        if path.is_file() {
+            templates.push(path);
+            continue;
+        }
+        if !path.join("Chart.yaml").is_file() {
+            return Err(ConflowError::FileNotFound {
+                path: file.clone(),
+                help: path
+                    .is_dir()
+                    .then(|| "Directories must be Helm charts, with a Chart.yaml".to_string()),
+            });
+        }
+        let pattern = path.join("templates").join("**").join("*");
+        let mut chart: Vec<PathBuf> = glob::glob(&pattern.to_string_lossy())
+            .map_err(|e| ConflowError::GlobPattern {
+                message: e.to_string(),
+            })?
+            .flatten()
+            .filter(|path| path.is_file() && !is_chart_helper(path))
+            .collect();
+        chart.sort();
+        templates.extend(chart);
+    }
+    Ok(templates)
+}
@@ -2,78 +2,319 @@
 
 use colored::Colorize;
 use miette::Result;
-use std::path::PathBuf;
-
-use super::{OutputFormat, RsrAction};
-use crate::rsr::compliance::{ComplianceChecker, ComplianceLevel};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use super::{GroupBy, OutputFormat, RsrAction};
+use crate::errors::{ConflowError, FailOn};
+use crate::i18n::{tr, tr_with};
+use crate::rsr::badges::{BadgeGenerator, BadgeStyle};
+use crate::rsr::compliance::{
+    ComplianceChecker, ComplianceLevel, ComplianceReport, RequirementResult,
+};
+use crate::rsr::config::RsrConfig;
+use crate::rsr::diff::now_rfc3339;
+use crate::rsr::hooks::{self, RsrHookResult, RsrHooks, RsrTrigger};
+use crate::rsr::profiles::{self, Profile};
 use crate::rsr::requirements::{RsrRequirementClass, RsrRequirementRegistry};
 use crate::rsr::schemas::RsrSchemaRegistry;
+use crate::rsr::waivers::{self, Waiver, WaiverStatus};
+use crate::utils::codeowners::group_by_owner;
+use crate::utils::{date, git, yaml};
 
 /// Run the RSR command
 pub async fn run(action: RsrAction, verbose: bool) -> Result<()> {
     match action {
-        RsrAction::Check { requirement, format } => {
-            run_check(requirement, format, verbose).await
-        }
-        RsrAction::Requirements { tag, id } => {
-            run_requirements(tag, id, verbose).await
-        }
-        RsrAction::Schemas { tag } => {
-            run_schemas(tag, verbose).await
-        }
-        RsrAction::Schema { id, output } => {
-            run_schema(id, output, verbose).await
+        RsrAction::Check {
+            requirement,
+            format,
+            fail_on,
+            base,
+            timeout,
+            group_by,
+        } => {
+            run_check(
+                requirement,
+                format,
+                fail_on,
+                base,
+                timeout,
+                group_by,
+                verbose,
+            )
+            .await
         }
+        RsrAction::Requirements { tag, id } => run_requirements(tag, id, verbose).await,
+        RsrAction::Schemas { tag } => run_schemas(tag, verbose).await,
+        RsrAction::Schema { id, output } => run_schema(id, output, verbose).await,
+        RsrAction::Profiles { update } => run_profiles(update, verbose).await,
+        RsrAction::Badges { output, style } => run_badges(output, style, verbose).await,
+        RsrAction::Template { action } => super::template::run(action, verbose).await,
+        RsrAction::Waive {
+            requirement,
+            reason,
+            approver,
+            expires,
+        } => run_waive(requirement, reason, approver, expires).await,
     }
 }
 
 async fn run_check(
     requirements: Vec<String>,
     format: OutputFormat,
+    fail_on: FailOn,
+    base: Option<String>,
+    timeout: u64,
+    group_by: Option<GroupBy>,
     verbose: bool,
 ) -> Result<()> {
-    let checker = ComplianceChecker::new();
     let working_dir = std::env::current_dir()
         .map_err(|e| miette::miette!("Failed to get current directory: {}", e))?;
+    fetch_profiles(&working_dir).await?;
+    let checker =
+        ComplianceChecker::for_project(&working_dir)?.with_timeout(Duration::from_secs(timeout));
+    let req_refs: Vec<&str> = requirements.iter().map(|s| s.as_str()).collect();
+
+    // Failing checks are errors when they make the project non-compliant, or
+    // when they were asked for by name; other unmet requirements are warnings
+    let (results, blocking) = if requirements.is_empty() {
+        // Check all requirements, printing each as it finishes
+        let report = match format {
+            OutputFormat::Text => {
+                print_compliance_header();
+                let report = checker.check_with(&working_dir, |result| {
+                    print_compliance_line(result, verbose)
+                })?;
+                print_compliance_summary(&report);
+                report
+            }
+            OutputFormat::Json => {
+                let report = checker.check(&working_dir)?;
+                print_compliance_json(&report, group_by)?;
+                report
+            }
+        };
 
-    if requirements.is_empty() {
-        // Check all requirements
-        let report = checker.check(&working_dir)?;
-
-        match format {
-            OutputFormat::Text => print_compliance_report(&report, verbose),
-            OutputFormat::Json => print_compliance_json(&report)?,
+        let config = RsrConfig::load_from_project(&working_dir)?;
+        if config.integrations.ci.generate_badges {
+            let written = write_badges(&checker, &report, &config, None, None)?;
+            if verbose && format == OutputFormat::Text {
+                for path in written {
+                    println!("{} {}", "→".blue(), path.display());
+                }
+            }
         }
 
-        if report.level == ComplianceLevel::NonCompliant {
-            return Err(miette::miette!("Compliance check failed"));
-        }
+        let result = hooks::compliance_result(&report, checker.registry());
+        run_hooks(&working_dir, &config, &requirements, &result, verbose).await;
+
+        let blocking = report.level == ComplianceLevel::NonCompliant;
+        (report.requirements, blocking)
     } else {
         // Check specific requirements
-        let req_refs: Vec<&str> = requirements.iter().map(|s| s.as_str()).collect();
-        let results = checker.check_requirements(&req_refs, &working_dir)?;
+        let results = match format {
+            OutputFormat::Text => {
+                print_requirement_header();
+                checker.check_requirements_with(&req_refs, &working_dir, |result| {
+                    print_requirement_result(result, verbose)
+                })?
+            }
+            OutputFormat::Json => {
+                let results = checker.check_requirements(&req_refs, &working_dir)?;
+                print_requirement_results_json(&results, group_by)?;
+                results
+            }
+        };
+
+        let config = RsrConfig::load_from_project(&working_dir)?;
+        let result = hooks::requirements_result(&results, checker.registry());
+        run_hooks(&working_dir, &config, &requirements, &result, verbose).await;
+
+        let blocking = results.iter().any(|r| !r.met && !r.waived);
+        (results, blocking)
+    };
+    if group_by.is_some() && format == OutputFormat::Text {
+        print_owner_gaps(&results);
+    }
 
-        match format {
-            OutputFormat::Text => print_requirement_results(&results, verbose),
-            OutputFormat::Json => print_requirement_results_json(&results)?,
+    // Active waivers only carry a detail when grandfathered
+    for status in checker.waivers().iter().filter(|w| w.is_active()) {
+        if let Some(ref detail) = status.detail {
+            eprintln!(
+                "{} Exception for {}: {}",
+                "⚠".yellow(),
+                status.waiver.requirement,
+                detail
+            );
         }
+    }
+    let invalid: Vec<_> = checker
+        .waivers()
+        .iter()
+        .filter(|w| !w.is_active())
+        .collect();
+    if !invalid.is_empty() {
+        let ids: Vec<_> = invalid
+            .iter()
+            .map(|w| w.waiver.requirement.as_str())
+            .collect();
+        return Err(ConflowError::CheckFailed {
+            message: format!(
+                "{} waiver(s) expired or unapproved: {}",
+                invalid.len(),
+                ids.join(", ")
+            ),
+        }
+        .into());
+    }
 
-        if results.iter().any(|r| !r.met) {
-            return Err(miette::miette!("Some requirements not met"));
+    if let Some(git_ref) = base {
+        let regressed = regressions(&checker, &req_refs, &working_dir, &git_ref, &results)?;
+        if !regressed.is_empty() {
+            return Err(ConflowError::ComplianceRegressed {
+                base: git_ref,
+                requirements: regressed,
+            }
+            .into());
         }
     }
 
+    if blocking && fail_on.fails_on_errors() {
+        let message = if requirements.is_empty() {
+            "Compliance check failed"
+        } else {
+            "Some requirements not met"
+        };
+        return Err(ConflowError::CheckFailed {
+            message: message.into(),
+        }
+        .into());
+    }
+
+    let unmet = results.iter().filter(|r| !r.met && !r.waived).count();
+    if unmet > 0 && fail_on.fails_on_warnings() {
+        return Err(ConflowError::WarningsFound { count: unmet }.into());
+    }
+
     Ok(())
 }
 
-fn print_compliance_report(
-    report: &crate::rsr::compliance::ComplianceReport,
+/// Run the hooks `.rsr.yaml` declares for compliance checks
+///
+/// Waits for async hooks too, so they finish before conflow exits. Hook
+/// failures are reported but do not fail the check.
+async fn run_hooks(
+    working_dir: &Path,
+    config: &RsrConfig,
+    requirements: &[String],
+    result: &RsrHookResult,
     verbose: bool,
 ) {
+    if config.hooks.is_empty() {
+        return;
+    }
+
+    let hooks = RsrHooks::new(working_dir.to_path_buf()).with_hooks(config.hooks.clone());
+    let trigger = RsrTrigger::CheckCompliance {
+        requirements: requirements.to_vec(),
+    };
+    let mut outcomes = hooks.fire(&trigger, result).await;
+    outcomes.extend(hooks.wait().await);
+
+    for outcome in outcomes {
+        if !outcome.success {
+            eprintln!(
+                "{} hook '{}': {}",
+                "⚠".yellow(),
+                outcome.hook,
+                outcome.message
+            );
+        } else if verbose {
+            eprintln!(
+                "{} hook '{}': {}",
+                "→".blue(),
+                outcome.hook,
+                outcome.message
+            );
+        }
+    }
+}
+
+/// Requirements that are met on `git_ref` but not in `results`
+fn regressions(
+    checker: &ComplianceChecker,
+    requirements: &[&str],
+    working_dir: &Path,
+    git_ref: &str,
+    results: &[RequirementResult],
+) -> std::result::Result<Vec<String>, ConflowError> {
+    let scratch = crate::utils::private_tempdir("conflow-base-")?;
+    let dir = git::export_tree(working_dir, git_ref, scratch.path())?;
+    let base = if requirements.is_empty() {
+        checker.check(&dir)?.requirements
+    } else {
+        checker.check_requirements(requirements, &dir)?
+    };
+
+    let passing: HashSet<String> = base
+        .into_iter()
+        .filter(|r| r.met)
+        .map(|r| r.requirement_id)
+        .collect();
+    Ok(results
+        .iter()
+        .filter(|r| !r.met && !r.waived && passing.contains(&r.requirement_id))
+        .map(|r| r.requirement_id.clone())
+        .collect())
+}
+
+fn print_compliance_header() {
     println!();
     println!("{}", "RSR Compliance Report".bold());
     println!("{}", "═".repeat(50));
     println!();
+    println!("{}:", "Requirements".bold());
+}
+
+fn print_compliance_line(result: &RequirementResult, verbose: bool) {
+    if result.waived {
+        println!(
+            "  {} {} {}",
+            "⚠".yellow(),
+            result.requirement_id,
+            "(waived)".dimmed()
+        );
+        return;
+    }
+    if result.is_blocked() {
+        println!(
+            "  {} {} {}",
+            "○".yellow(),
+            result.requirement_id,
+            format!("(blocked by {})", result.blocked_by.join(", ")).dimmed()
+        );
+        return;
+    }
+
+    let icon = if result.met {
+        "✓".green()
+    } else {
+        "✗".red()
+    };
+    println!("  {} {}", icon, result.requirement_id);
+
+    if verbose && !result.met {
+        if let Some(ref rem) = result.remediation {
+            for line in rem.lines() {
+                println!("      {}", line.dimmed());
+            }
+        }
+    }
+}
+
+fn print_compliance_summary(report: &crate::rsr::compliance::ComplianceReport) {
+    println!();
 
     // Overall level
     let level_color = match report.level {
@@ -88,6 +329,9 @@ fn print_compliance_report(
         report.level.emoji(),
         report.level.description().color(level_color)
     );
+    if let Some(ref custom_level) = report.custom_level {
+        println!("Custom level: {}", custom_level.bold());
+    }
     println!("Score: {:.0}%", report.score * 100.0);
     println!();
 
@@ -109,29 +353,27 @@ fn print_compliance_report(
         "  Advisory:    {}/{}",
         report.stats.advisory_passed, report.stats.advisory_total
     );
-    println!();
-
-    // Individual requirements
-    println!("{}:", "Requirements".bold());
-    for result in &report.requirements {
-        let icon = if result.met {
-            "✓".green()
-        } else {
-            "✗".red()
-        };
-        println!("  {} {}", icon, result.requirement_id);
+    if report.stats.blocked > 0 {
+        println!("  Blocked:     {}", report.stats.blocked);
+    }
+    if report.stats.waived > 0 {
+        println!("  Waived:      {}", report.stats.waived);
+    }
 
-        if verbose && !result.met {
-            if let Some(ref rem) = result.remediation {
-                for line in rem.lines() {
-                    println!("      {}", line.dimmed());
-                }
-            }
+    if !report.waivers.is_empty() {
+        println!();
+        println!("{}:", "Waivers".bold());
+        for status in &report.waivers {
+            print_waiver(status);
         }
     }
 
     // Suggestions for failed requirements
-    let failed: Vec<_> = report.requirements.iter().filter(|r| !r.met).collect();
+    let failed: Vec<_> = report
+        .requirements
+        .iter()
+        .filter(|r| !r.met && !r.is_blocked() && !r.waived)
+        .collect();
     if !failed.is_empty() {
         println!();
         println!("{}:", "Suggestions".bold());
@@ -152,14 +394,18 @@ fn print_compliance_report(
 
 fn print_compliance_json(
     report: &crate::rsr::compliance::ComplianceReport,
+    group_by: Option<GroupBy>,
 ) -> Result<()> {
-    let json = serde_json::json!({
+    let mut json = serde_json::json!({
         "level": format!("{:?}", report.level),
         "score": report.score,
+        "custom_level": report.custom_level,
         "stats": {
             "total": report.stats.total,
             "passed": report.stats.passed,
             "failed": report.stats.failed,
+            "blocked": report.stats.blocked,
+            "waived": report.stats.waived,
             "mandatory": {
                 "total": report.stats.mandatory_total,
                 "passed": report.stats.mandatory_passed,
@@ -177,10 +423,17 @@ fn print_compliance_json(
             serde_json::json!({
                 "id": r.requirement_id,
                 "met": r.met,
+                "blocked_by": r.blocked_by,
+                "waived": r.waived,
                 "remediation": r.remediation,
+                "owners": r.owners,
             })
         }).collect::<Vec<_>>(),
+        "waivers": report.waivers.iter().map(waiver_json).collect::<Vec<_>>(),
     });
+    if group_by.is_some() {
+        json["by_owner"] = serde_json::json!(owner_gaps(&report.requirements));
+    }
 
     println!(
         "{}",
@@ -191,58 +444,124 @@ fn print_compliance_json(
     Ok(())
 }
 
-fn print_requirement_results(
-    results: &[crate::rsr::compliance::RequirementResult],
-    verbose: bool,
-) {
+fn print_waiver(status: &WaiverStatus) {
+    let waiver = &status.waiver;
+    let icon = if status.is_active() {
+        "⚠".yellow()
+    } else {
+        "✗".red()
+    };
+    let mut terms = Vec::new();
+    if let Some(ref expires) = waiver.expires {
+        terms.push(format!("until {}", expires));
+    }
+    if let Some(ref approver) = waiver.approver {
+        terms.push(format!("approved by {}", approver));
+    }
+    if status.verified {
+        terms.push("signed".into());
+    }
+    println!(
+        "  {} {} {}",
+        icon,
+        waiver.requirement,
+        format!("({})", terms.join(", ")).dimmed()
+    );
+    println!("    {}", waiver.reason);
+    if let Some(ref detail) = status.detail {
+        let state = if status.is_active() {
+            status.state.to_string().yellow()
+        } else {
+            status.state.to_string().red()
+        };
+        println!("    {} {}", state, detail.dimmed());
+    }
+}
+
+fn waiver_json(status: &WaiverStatus) -> serde_json::Value {
+    serde_json::json!({
+        "requirement": status.waiver.requirement,
+        "reason": status.waiver.reason,
+        "approver": status.waiver.approver,
+        "expires": status.waiver.expires,
+        "state": status.state,
+        "verified": status.verified,
+        "detail": status.detail,
+    })
+}
+
+fn print_requirement_header() {
     println!();
     println!("{}", "Requirement Check Results".bold());
     println!("{}", "═".repeat(50));
     println!();
+}
 
-    for result in results {
-        let icon = if result.met {
-            "✓".green()
-        } else {
-            "✗".red()
-        };
+fn print_requirement_result(result: &RequirementResult, verbose: bool) {
+    if result.is_blocked() && !result.waived {
+        println!(
+            "{} {} {}",
+            "○".yellow(),
+            result.requirement_id.bold(),
+            format!("(blocked by {})", result.blocked_by.join(", ")).dimmed()
+        );
+        println!();
+        return;
+    }
+
+    let icon = if result.met {
+        "✓".green()
+    } else if result.waived {
+        "⚠".yellow()
+    } else {
+        "✗".red()
+    };
+    if result.waived {
+        println!(
+            "{} {} {}",
+            icon,
+            result.requirement_id.bold(),
+            "(waived)".dimmed()
+        );
+    } else {
         println!("{} {}", icon, result.requirement_id.bold());
+    }
 
-        if verbose {
-            for detail in &result.details {
-                let detail_icon = if detail.passed { "✓" } else { "✗" };
-                println!(
-                    "    {} {}",
-                    if detail.passed {
-                        detail_icon.green()
-                    } else {
-                        detail_icon.red()
-                    },
-                    detail.check
-                );
-                if let Some(ref info) = detail.info {
-                    println!("      {}", info.dimmed());
-                }
+    if verbose {
+        for detail in &result.details {
+            let detail_icon = if detail.passed { "✓" } else { "✗" };
+            println!(
+                "    {} {}",
+                if detail.passed {
+                    detail_icon.green()
+                } else {
+                    detail_icon.red()
+                },
+                detail.check
+            );
+            if let Some(ref info) = detail.info {
+                println!("      {}", info.dimmed());
             }
         }
+    }
 
-        if !result.met {
-            if let Some(ref rem) = result.remediation {
-                println!("  {}:", "Remediation".yellow());
-                for line in rem.lines() {
-                    println!("    {}", line);
-                }
+    if !result.met && !result.waived {
+        if let Some(ref rem) = result.remediation {
+            println!("  {}:", tr("rsr.remediation").yellow());
+            for line in rem.lines() {
+                println!("    {}", line);
             }
         }
-
-        println!();
     }
+
+    println!();
 }
 
 fn print_requirement_results_json(
     results: &[crate::rsr::compliance::RequirementResult],
+    group_by: Option<GroupBy>,
 ) -> Result<()> {
-    let json: Vec<_> = results
+    let requirements: Vec<_> = results
         .iter()
         .map(|r| {
             serde_json::json!({
@@ -255,10 +574,20 @@ fn print_requirement_results_json(
                         "info": d.info,
                     })
                 }).collect::<Vec<_>>(),
+                "blocked_by": r.blocked_by,
+                "waived": r.waived,
                 "remediation": r.remediation,
+                "owners": r.owners,
             })
         })
         .collect();
+    let json = match group_by {
+        Some(_) => serde_json::json!({
+            "requirements": requirements,
+            "by_owner": owner_gaps(results),
+        }),
+        None => serde_json::json!(requirements),
+    };
 
     println!(
         "{}",
@@ -269,12 +598,36 @@ fn print_requirement_results_json(
     Ok(())
 }
 
-async fn run_requirements(
-    tag: Option<String>,
-    id: Option<String>,
-    _verbose: bool,
-) -> Result<()> {
-    let registry = RsrRequirementRegistry::new();
+/// IDs of unmet, unwaived requirements by CODEOWNERS owner
+fn owner_gaps(results: &[RequirementResult]) -> BTreeMap<String, Vec<&str>> {
+    let gaps = results.iter().filter(|r| !r.met && !r.waived);
+    group_by_owner(gaps, |r| r.owners.clone())
+        .into_iter()
+        .map(|(owner, gaps)| {
+            let ids = gaps.iter().map(|r| r.requirement_id.as_str()).collect();
+            (owner, ids)
+        })
+        .collect()
+}
+
+fn print_owner_gaps(results: &[RequirementResult]) {
+    let gaps = owner_gaps(results);
+    if gaps.is_empty() {
+        return;
+    }
+    let width = gaps.keys().map(|owner| owner.len()).max().unwrap_or(0);
+    println!();
+    println!("{}:", "Unmet by owner".bold());
+    for (owner, ids) in gaps {
+        println!("  {:<width$}  {}", owner.cyan(), ids.join(", "));
+    }
+}
+
+async fn run_requirements(tag: Option<String>, id: Option<String>, _verbose: bool) -> Result<()> {
+    let working_dir = std::env::current_dir()
+        .map_err(|e| miette::miette!("Failed to get current directory: {}", e))?;
+    fetch_profiles(&working_dir).await?;
+    let registry = RsrRequirementRegistry::for_project(&working_dir)?;
 
     println!();
     println!("{}", "RSR Requirements".bold());
@@ -319,7 +672,7 @@ fn print_requirement_summary(req: &crate::rsr::requirements::RsrRequirement) {
     println!("{} [{}]", req.id.bold(), class_str);
     println!("  {}", req.name);
     if !req.tags.is_empty() {
-        println!("  Tags: {}", req.tags.join(", ").dimmed());
+        println!("  {}: {}", tr("rsr.tags"), req.tags.join(", ").dimmed());
     }
     println!();
 }
@@ -333,50 +686,59 @@ fn print_requirement(req: &crate::rsr::requirements::RsrRequirement) {
 
     println!("{} [{}]", req.id.bold(), class_str);
     println!();
-    println!("{}:", "Name".bold());
+    println!("{}:", tr("rsr.name").bold());
     println!("  {}", req.name);
     println!();
-    println!("{}:", "Description".bold());
+    println!("{}:", tr("rsr.description").bold());
     println!("  {}", req.description);
     println!();
 
     if !req.tags.is_empty() {
-        println!("{}:", "Tags".bold());
+        println!("{}:", tr("rsr.tags").bold());
         println!("  {}", req.tags.join(", "));
         println!();
     }
 
+    if !req.requires.is_empty() {
+        println!("{}:", tr("rsr.requires").bold());
+        for prerequisite in &req.requires {
+            println!("  - {}", prerequisite);
+        }
+        println!();
+    }
+
     if !req.related.is_empty() {
-        println!("{}:", "Related".bold());
+        println!("{}:", tr("rsr.related").bold());
         for rel in &req.related {
             println!("  - {}", rel);
         }
         println!();
     }
 
-    println!("{}:", "Validation".bold());
+    println!("{}:", tr("rsr.validation").bold());
     if !req.validation.file_exists.is_empty() {
-        println!("  Files required: {:?}", req.validation.file_exists);
+        let files = format!("{:?}", req.validation.file_exists);
+        println!("  {}", tr_with("rsr.files_required", &[("files", &files)]));
     }
     if req.validation.conflow_valid {
-        println!("  conflow pipeline must be valid");
+        println!("  {}", tr("rsr.pipeline_valid"));
     }
     println!();
 
-    println!("{}:", "Remediation".bold());
+    println!("{}:", tr("rsr.remediation").bold());
     if req.remediation.auto_fix {
-        println!("  Auto-fix available");
+        println!("  {}", tr("rsr.auto_fix"));
     }
     for step in &req.remediation.manual_steps {
         println!("  • {}", step);
     }
     if let Some(ref url) = req.remediation.docs_url {
-        println!("  Docs: {}", url.cyan());
+        println!("  {}", tr_with("rsr.docs", &[("url", &url.cyan())]));
     }
 }
 
 async fn run_schemas(tag: Option<String>, _verbose: bool) -> Result<()> {
-    let registry = RsrSchemaRegistry::new();
+    let registry = schema_registry().await?;
 
     println!();
     println!("{}", "RSR Schemas".bold());
@@ -406,12 +768,8 @@ async fn run_schemas(tag: Option<String>, _verbose: bool) -> Result<()> {
     Ok(())
 }
 
-async fn run_schema(
-    id: String,
-    output: Option<PathBuf>,
-    _verbose: bool,
-) -> Result<()> {
-    let registry = RsrSchemaRegistry::new();
+async fn run_schema(id: String, output: Option<PathBuf>, _verbose: bool) -> Result<()> {
+    let registry = schema_registry().await?;
 
     let content = registry.get_content(&id)?;
 
@@ -425,3 +783,189 @@ async fn run_schema(
 
     Ok(())
 }
+
+async fn run_profiles(update: bool, verbose: bool) -> Result<()> {
+    let working_dir = std::env::current_dir()
+        .map_err(|e| miette::miette!("Failed to get current directory: {}", e))?;
+    let config = RsrConfig::load_from_project(&working_dir)?;
+
+    if config.profiles.is_empty() {
+        println!("No profiles in .rsr.yaml");
+        return Ok(());
+    }
+
+    let profiles = profiles::fetch_all(&config, update).await?;
+
+    println!();
+    println!("{}", "RSR Profiles".bold());
+    println!("{}", "═".repeat(50));
+    println!();
+
+    for (source, profile) in config.profiles.iter().zip(&profiles) {
+        println!("{} {}", "✓".green(), source.profile.to_string().bold());
+        if let Some(ref description) = profile.description {
+            println!("  {}", description.dimmed());
+        }
+        println!(
+            "  {} requirement(s) · {} remediation(s) · {} schema(s) · {} template(s)",
+            profile.requirements.len(),
+            profile.remediations.len(),
+            profile.schemas.len(),
+            profile.templates.len()
+        );
+        if verbose {
+            let from = source
+                .git
+                .clone()
+                .or_else(|| source.registry.clone())
+                .unwrap_or_else(|| "registry".into());
+            println!("  {} {}", "From:".dimmed(), from);
+            println!("  {} {}", "Cached:".dimmed(), profile.dir.display());
+        }
+        println!();
+    }
+
+    Ok(())
+}
+
+async fn run_waive(
+    requirement: String,
+    reason: String,
+    approver: String,
+    expires: String,
+) -> Result<()> {
+    let working_dir = std::env::current_dir()
+        .map_err(|e| miette::miette!("Failed to get current directory: {}", e))?;
+    fetch_profiles(&working_dir).await?;
+    let registry = RsrRequirementRegistry::for_project(&working_dir)?;
+
+    if registry.get(&requirement).is_none() {
+        let suggestion = yaml::suggest(&requirement, registry.all().map(|r| r.id.as_str()));
+        return Err(miette::miette!(
+            help = match suggestion {
+                Some(id) => format!("Did you mean {}?", id),
+                None => "Run 'conflow rsr requirements' to list requirements".into(),
+            },
+            "Unknown requirement: {}",
+            requirement
+        ));
+    }
+
+    let now = now_rfc3339();
+    if !date::is_date(&expires) {
+        return Err(miette::miette!(
+            help = "Use a date like 2026-12-31",
+            "Invalid expiry date: {}",
+            expires
+        ));
+    }
+    if waivers::expired(&expires, &now) != Some(false) {
+        return Err(miette::miette!(
+            help = "Waivers must expire in the future",
+            "Expiry date {} has passed",
+            expires
+        ));
+    }
+
+    let mut waiver = Waiver {
+        requirement,
+        reason,
+        approver: Some(approver),
+        expires: Some(expires),
+        created: Some(now),
+        signature: None,
+    };
+    let key = waivers::signing_key();
+    if let Some(ref key) = key {
+        waiver.sign(key);
+    }
+
+    let mut recorded = waivers::load(&working_dir)?;
+    recorded.retain(|w| w.requirement != waiver.requirement);
+    recorded.push(waiver.clone());
+    waivers::save(&working_dir, &recorded)?;
+
+    println!(
+        "{} Waived {} until {}",
+        "✓".green(),
+        waiver.requirement.bold(),
+        waiver.expires.as_deref().unwrap_or_default()
+    );
+    println!("  {} {}", "Recorded in".dimmed(), waivers::WAIVER_FILE);
+    if key.is_none() {
+        eprintln!(
+            "{} The waiver is not signed: anyone who can edit {} can grant waivers",
+            "⚠".yellow(),
+            waivers::WAIVER_FILE
+        );
+        eprintln!(
+            "  Set {} in CI and when waiving to require signatures",
+            waivers::WAIVER_KEY_ENV
+        );
+    }
+
+    Ok(())
+}
+
+async fn run_badges(
+    output: Option<PathBuf>,
+    style: Option<BadgeStyle>,
+    verbose: bool,
+) -> Result<()> {
+    let working_dir = std::env::current_dir()
+        .map_err(|e| miette::miette!("Failed to get current directory: {}", e))?;
+    fetch_profiles(&working_dir).await?;
+    let config = RsrConfig::load_from_project(&working_dir)?;
+    let checker = ComplianceChecker::for_project(&working_dir)?;
+    let report = checker.check(&working_dir)?;
+
+    let written = write_badges(&checker, &report, &config, output, style)?;
+    for path in &written {
+        println!("{} {}", "✓".green(), path.display());
+    }
+    if verbose {
+        println!();
+        println!(
+            "{} Serve a .json file and use https://img.shields.io/endpoint?url=<its URL>",
+            "→".blue()
+        );
+    }
+
+    Ok(())
+}
+
+/// Write the report's badges, with `output` and `style` overriding `.rsr.yaml`
+fn write_badges(
+    checker: &ComplianceChecker,
+    report: &ComplianceReport,
+    config: &RsrConfig,
+    output: Option<PathBuf>,
+    style: Option<BadgeStyle>,
+) -> Result<Vec<PathBuf>> {
+    let badges = &config.integrations.badges;
+    let mut generator = BadgeGenerator::from_config(badges);
+    if let Some(style) = style {
+        generator = generator.style(style);
+    }
+    let dir = output.unwrap_or_else(|| badges.output.clone());
+    Ok(generator.write_all(report, checker.registry(), &dir)?)
+}
+
+/// Fetch the profiles `.rsr.yaml` references that are not cached yet
+async fn fetch_profiles(working_dir: &Path) -> Result<Vec<Profile>> {
+    let config = RsrConfig::load_from_project(working_dir)?;
+    Ok(profiles::fetch_all(&config, false).await?)
+}
+
+/// Built-in schemas plus those of the project's profiles
+async fn schema_registry() -> Result<RsrSchemaRegistry> {
+    let working_dir = std::env::current_dir()
+        .map_err(|e| miette::miette!("Failed to get current directory: {}", e))?;
+    let mut registry = RsrSchemaRegistry::new();
+    for profile in fetch_profiles(&working_dir).await? {
+        for schema in profile.schemas {
+            registry.register(schema);
+        }
+    }
+    Ok(registry)
+}
@@ -5,46 +5,209 @@
 
 use colored::Colorize;
 use miette::Result;
-use std::path::PathBuf;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
 
-use crate::cache::FilesystemCache;
-use crate::executors::create_default_executors;
-use crate::pipeline::{ExecutionOptions, Pipeline, PipelineExecutor, PipelineValidator};
+use super::{GroupBy, ReportFormat};
+use crate::analyzer::CostEstimate;
+use crate::bundle::{self, Caches};
+use crate::cache::{FilesystemCache, StageTimings};
+use crate::diagnostics::{annotations, Diagnostic, Renderer};
+use crate::errors::{ConflowError, FailOn};
+use crate::executors::parsers::{self, Severity, ToolDiagnostic};
+use crate::executors::{create_executors, Sandbox};
+use crate::i18n::{tr, tr_with};
+use crate::notify::{Notifier, NotifyState, PrCommenter, PrReport, PrTarget, RunSummary};
+use crate::pipeline::{
+    affected_stages, stage_workdir, with_producers, Approvals, Dispatcher, ExecutionOptions,
+    Ownership, Pipeline, PipelineExecutor, PipelineResult, PipelineValidator, Profile, RunHistory,
+    RunLock, RunRecord, SpanKind, Tool,
+};
+use crate::rsr::ComplianceChecker;
+use crate::telemetry::{OtlpExporter, OtlpSettings};
+use crate::utils::codeowners::group_by_owner;
+use crate::utils::git;
+use crate::utils::redact::Redactor;
+
+/// Options for the run command
+#[derive(Debug, Default)]
+pub struct RunOptions {
+    /// Only run these stages
+    pub stages: Vec<String>,
+    /// Skip cache
+    pub no_cache: bool,
+    /// Show what would be done
+    pub dry_run: bool,
+    /// Only run stages affected by changes since this git ref
+    pub since: Option<String>,
+    /// Skip the stages the last recorded run passed
+    pub resume: bool,
+    /// Write a Chrome trace profile here
+    pub profile: Option<PathBuf>,
+    /// Show the interactive dashboard
+    pub tui: bool,
+    /// Only print failures
+    pub quiet: bool,
+    /// Write parsed tool diagnostics here
+    pub report: Option<PathBuf>,
+    /// Format of the diagnostics report
+    pub report_format: ReportFormat,
+    /// Group failures, and JSON reports, by owner
+    pub group_by: Option<GroupBy>,
+    /// Run stage commands in the sandbox
+    pub sandbox: bool,
+    /// Approve all manual stages
+    pub yes: bool,
+    /// Approve these manual stages
+    pub approve: Vec<String>,
+    /// Post the results as a PR/MR comment
+    pub pr_comment: bool,
+    /// Don't take the project's run lock
+    pub no_lock: bool,
+    /// How long to wait for the run lock
+    pub lock_timeout: Duration,
+    /// Lowest severity that fails the run
+    pub fail_on: FailOn,
+    /// Run the pipeline of this run bundle
+    pub from_bundle: Option<PathBuf>,
+    /// Send stages with runs_on labels to agents of this coordinator
+    pub coordinator: Option<String>,
+    /// API key for the coordinator
+    pub api_key: Option<String>,
+    /// Key agents accept from runners
+    pub runner_key: Option<String>,
+    /// PEM CA agents' certificates are signed by
+    pub agent_ca: Option<PathBuf>,
+    /// Send secret variables to agents
+    pub send_secrets: bool,
+}
+
+/// How stages with runs_on labels reach agents
+#[cfg_attr(not(feature = "grpc"), allow(dead_code))]
+struct Dispatch {
+    coordinator: String,
+    api_key: Option<String>,
+    runner_key: Option<String>,
+    agent_ca: Option<PathBuf>,
+    send_secrets: bool,
+}
+
+/// Dispatcher sending stages with runs_on labels through the coordinator
+#[cfg(feature = "grpc")]
+fn dispatcher(dispatch: Dispatch) -> Result<Arc<dyn Dispatcher>> {
+    let agent_ca = match &dispatch.agent_ca {
+        Some(path) => Some(
+            std::fs::read(path).map_err(|e| ConflowError::FileReadError {
+                path: path.clone(),
+                error: e.to_string(),
+            })?,
+        ),
+        None => None,
+    };
+    let store = crate::cache::ArtifactStore::shared()?;
+    let dispatcher =
+        crate::grpc::RemoteDispatcher::new(dispatch.coordinator, dispatch.api_key, store)
+            .with_runner_key(dispatch.runner_key)
+            .with_agent_ca(agent_ca)
+            .with_secrets(dispatch.send_secrets);
+    Ok(Arc::new(dispatcher))
+}
+
+#[cfg(not(feature = "grpc"))]
+fn dispatcher(_dispatch: Dispatch) -> Result<Arc<dyn Dispatcher>> {
+    Err(miette::miette!(
+        help = "rebuild with `cargo install conflow --features grpc`",
+        "This conflow was built without gRPC support, which --coordinator needs"
+    ))
+}
 
 /// Run the pipeline
-pub async fn run(
-    pipeline_path: PathBuf,
-    stages: Vec<String>,
-    no_cache: bool,
-    dry_run: bool,
-    verbose: bool,
-) -> Result<()> {
+pub async fn run(pipeline_path: PathBuf, opts: RunOptions, verbose: bool) -> Result<()> {
+    let RunOptions {
+        stages,
+        no_cache,
+        dry_run,
+        since,
+        resume,
+        profile,
+        tui,
+        quiet,
+        report,
+        report_format,
+        group_by,
+        sandbox,
+        yes,
+        approve,
+        pr_comment,
+        no_lock,
+        lock_timeout,
+        fail_on,
+        from_bundle,
+        coordinator,
+        api_key,
+        runner_key,
+        agent_ca,
+        send_secrets,
+    } = opts;
+
+    // A bundled run is unpacked into a directory of its own, removed when
+    // the run ends; a relative report path still names the caller's directory
+    let mut report = report;
+    let (pipeline_path, _bundle_dir) = match from_bundle {
+        Some(ref bundle) => {
+            if let (Some(path), Ok(cwd)) = (&report, std::env::current_dir()) {
+                report = Some(cwd.join(path));
+            }
+            let (path, dir) = open_bundle(bundle, quiet).await?;
+            (path, Some(dir))
+        }
+        None => (pipeline_path, None),
+    };
+
     // Check pipeline exists
     if !pipeline_path.exists() {
-        return Err(miette::miette!(
-            "Pipeline file not found: {}\n\n\
-             Run 'conflow init' to create a new project.",
-            pipeline_path.display()
-        ));
+        return Err(ConflowError::PipelineNotFound {
+            path: pipeline_path,
+        }
+        .into());
     }
 
     // Load pipeline
-    let pipeline = Pipeline::from_file(&pipeline_path).map_err(|e| {
-        miette::miette!("Failed to load pipeline: {}", e)
-    })?;
+    let mut pipeline = Pipeline::from_file(&pipeline_path)?;
+
+    if sandbox {
+        pipeline.sandbox.enabled = true;
+    }
+    if pipeline.sandbox.enabled {
+        Sandbox::check_available()?;
+        if verbose {
+            let network = if pipeline.sandbox.network {
+                "allowed"
+            } else {
+                "blocked"
+            };
+            println!("{} Sandbox enabled (network {})", "→".blue(), network);
+        }
+    }
 
     // Validate pipeline
     let validation = PipelineValidator::validate(&pipeline)?;
 
     if !validation.is_valid() {
-        eprintln!("{}", "Pipeline validation failed:".red().bold());
+        eprintln!("{}", tr("run.invalid").red().bold());
         for error in &validation.errors {
             eprintln!("  {} {}", "✗".red(), error);
+            annotations::emit_message(Severity::Error, &pipeline_path, error);
+        }
+        return Err(ConflowError::CheckFailed {
+            message: "Pipeline configuration is invalid".into(),
         }
-        return Err(miette::miette!("Pipeline configuration is invalid"));
+        .into());
     }
 
-    if validation.has_warnings() && verbose {
+    if validation.has_warnings() && (verbose || fail_on.fails_on_warnings()) {
         eprintln!("{}", "Pipeline warnings:".yellow().bold());
         for warning in &validation.warnings {
             eprintln!("  {} {}", "⚠".yellow(), warning);
@@ -56,37 +219,141 @@ pub async fn run(
     let mut executor = PipelineExecutor::new();
 
     // Register executors
-    for (name, exec) in create_default_executors() {
+    for (name, exec) in create_executors(&pipeline) {
         executor.register_executor(&name, exec);
     }
+    if let Some(coordinator) = coordinator {
+        executor = executor.with_dispatcher(dispatcher(Dispatch {
+            coordinator,
+            api_key,
+            runner_key,
+            agent_ca,
+            send_secrets,
+        })?);
+    }
 
     // Check required tools are available
     let missing_tools = executor.check_tools(&pipeline).await?;
     if !missing_tools.is_empty() {
-        eprintln!("{}", "Missing required tools:".red().bold());
+        eprintln!("{}", tr("run.missing_tools").red().bold());
         for tool in &missing_tools {
             eprintln!("  {} {}", "✗".red(), tool);
-            match tool.as_str() {
-                "cue" => eprintln!("    Install: {}", "https://cuelang.org/docs/install/".cyan()),
-                "nickel" => eprintln!("    Install: {}", "https://nickel-lang.org/getting-started/".cyan()),
-                _ => {}
+            let url = match tool.as_str() {
+                "cue" => Some("https://cuelang.org/docs/install/"),
+                "nickel" => Some("https://nickel-lang.org/getting-started/"),
+                _ => None,
+            };
+            if let Some(url) = url {
+                eprintln!("    {}", tr_with("run.install", &[("url", &url.cyan())]));
             }
         }
-        return Err(miette::miette!("Required tools are not installed"));
+        return Err(ConflowError::ToolNotFound {
+            tool: missing_tools.join("', '"),
+            suggestion: "Install the tools listed above and make sure they are on PATH".into(),
+        }
+        .into());
     }
 
     // Set up cache
-    let working_dir = std::env::current_dir().map_err(|e| {
-        miette::miette!("Failed to get current directory: {}", e)
-    })?;
+    let working_dir = std::env::current_dir()
+        .map_err(|e| miette::miette!("Failed to get current directory: {}", e))?;
 
+    // Wait for any other run in this project; dry runs change nothing
+    let _lock = if no_lock || dry_run {
+        None
+    } else {
+        let dir = working_dir.clone();
+        let lock = tokio::task::spawn_blocking(move || {
+            RunLock::acquire(&dir, lock_timeout, |holder| {
+                let holder = holder
+                    .map(|pid| format!(" (pid {})", pid))
+                    .unwrap_or_default();
+                println!(
+                    "{} Waiting for another conflow run{} to finish...",
+                    "→".blue(),
+                    holder
+                );
+            })
+        })
+        .await
+        .map_err(|e| miette::miette!("Failed to wait for the run lock: {}", e))??;
+        Some(lock)
+    };
+
+    let cache_dir = pipeline.cache.path(&working_dir)?;
     if !no_cache && pipeline.cache.enabled {
-        let cache = FilesystemCache::new(
-            working_dir.join(&pipeline.cache.directory),
-            working_dir.clone(),
-        )?;
+        let cache = FilesystemCache::new(cache_dir.clone(), working_dir.clone())?
+            .with_limits(pipeline.cache.limits());
         executor = executor.with_cache(Box::new(cache));
     }
+    if !dry_run {
+        executor = executor.with_timings(StageTimings::load(&cache_dir));
+    }
+    if quiet {
+        executor = executor.quiet();
+    }
+
+    // The PR comment compares compliance against the same base
+    let base_ref = since.clone();
+
+    // Narrow stages to those affected by git changes
+    let stages = match since {
+        Some(since) => {
+            let changed = git::changed_files(&working_dir, &since)?;
+            let pipeline_changed = pipeline_path
+                .canonicalize()
+                .map(|p| changed.iter().any(|c| c == &p))
+                .unwrap_or(false);
+
+            let mut affected = if pipeline_changed {
+                pipeline
+                    .stage_names()
+                    .into_iter()
+                    .map(String::from)
+                    .collect()
+            } else {
+                affected_stages(&pipeline, &changed, &working_dir)?
+            };
+
+            if !stages.is_empty() {
+                affected.retain(|s| stages.contains(s));
+            }
+
+            if !quiet {
+                let message = tr_with(
+                    "run.changes_since",
+                    &[
+                        ("since", &since.cyan()),
+                        ("affected", &affected.len()),
+                        ("total", &pipeline.stages.len()),
+                    ],
+                );
+                println!("{} {}", "→".blue(), message);
+            }
+
+            if affected.is_empty() {
+                if !quiet {
+                    println!("{} {}", "✓".green(), tr("run.nothing_to_run"));
+                }
+                return Ok(());
+            }
+
+            affected
+        }
+        None => stages,
+    };
+
+    // Pick up after the stages the last recorded run passed
+    let (stages, resumed) = match resume {
+        true => match resume_point(&pipeline, &pipeline_path, &working_dir, stages, quiet)? {
+            Some(point) => point,
+            None => return Ok(()),
+        },
+        false => (stages, None),
+    };
+
+    // Telemetry export is enabled by env or pipeline config
+    let telemetry = OtlpSettings::resolve(&pipeline.telemetry);
 
     // Create execution options
     let options = ExecutionOptions {
@@ -94,40 +361,591 @@ pub async fn run(
         dry_run,
         stages,
         verbose,
+        profile: profile.is_some() || telemetry.is_some(),
+        approvals: Approvals {
+            all: yes,
+            stages: approve,
+        }
+        .with_env(),
+    };
+
+    // Record the run as it goes, so an interrupted run can be resumed
+    let started = match dry_run {
+        true => None,
+        false => start_record(&pipeline, &pipeline_path, &working_dir, resumed.as_ref()),
     };
+    let mut updater = None;
+    if let Some(mut record) = started.clone() {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        executor = executor.with_events(tx);
+        let history = RunHistory::new(&working_dir);
+        updater = Some(tokio::spawn(async move {
+            while let Some(event) = rx.recv().await {
+                if record.apply(&event) {
+                    if let Err(e) = history.save(&record) {
+                        tracing::warn!(error = %e, "failed to update the run record");
+                    }
+                }
+            }
+        }));
+    }
 
     // Execute
-    let result = executor.execute(&pipeline, &working_dir, &options).await?;
+    let result = if tui {
+        crate::tui::run(pipeline.clone(), executor, working_dir.clone(), options).await?
+    } else {
+        executor.execute(&pipeline, &working_dir, &options).await?
+    };
+
+    if let Some(updater) = updater {
+        updater.abort();
+        let _ = updater.await;
+    }
+    let record = started.and_then(|started| {
+        let mut record = RunRecord::new(&pipeline, &pipeline_path, &result, &working_dir);
+        record.id = started.id;
+        if let Some(ref resumed) = resumed {
+            record.resume(resumed);
+        }
+        save_record(record, &working_dir, verbose)
+    });
+
+    if let (Some(settings), Some(recorded)) = (telemetry, &result.profile) {
+        let endpoint = settings.endpoint.clone();
+        match OtlpExporter::new(settings)
+            .export(&pipeline, &result, recorded)
+            .await
+        {
+            Ok(()) => {
+                if verbose {
+                    println!("{} Exported telemetry to {}", "→".blue(), endpoint.cyan());
+                }
+            }
+            Err(e) => eprintln!("{} {}", "⚠".yellow(), e),
+        }
+    }
+
+    if let (Some(path), Some(recorded)) = (&profile, &result.profile) {
+        recorded.write_chrome_trace(path)?;
+        print_profile_summary(recorded, path);
+    }
+
+    let diagnostics = collect_diagnostics(&pipeline, &result);
+    for (_, diagnostic) in &diagnostics {
+        annotations::emit(&Diagnostic::from(diagnostic));
+    }
+    let ownership = Ownership::load(&pipeline, &pipeline_path, &working_dir);
+
+    if let Some(ref path) = report {
+        let ownership = group_by.map(|_| &ownership);
+        write_report(path, report_format, &diagnostics, ownership)?;
+        if verbose {
+            let shown = path.display().to_string();
+            println!("{} Diagnostics written to {}", "→".blue(), shown.cyan());
+        }
+    }
+
+    if !dry_run && !pipeline.notifications.is_empty() {
+        let owners = failed_stage_owners(&result, &diagnostics, &ownership);
+        send_notifications(&pipeline, &result, &owners, &working_dir, verbose).await;
+    }
+
+    if pr_comment && !dry_run {
+        let comment = post_pr_comment(&pipeline, &result, &diagnostics, &working_dir, base_ref);
+        if let Err(e) = comment.await {
+            eprintln!("{} {}", "⚠".yellow(), e);
+        }
+    }
 
     if !result.success {
-        // Find which stage failed
-        for (name, stage_result) in &result.results {
-            if !stage_result.success {
+        if let Some(GroupBy::Owner) = group_by {
+            print_failures_by_owner(&pipeline, &result, &diagnostics, &ownership, &working_dir);
+        } else {
+            // Report the first stage to fail, in pipeline order
+            let failed = pipeline.stages.iter().find_map(|stage| {
+                let stage_result = result.results.get(&stage.name)?;
+                (!stage_result.success).then_some((&stage.name, stage_result))
+            });
+            if let Some((name, stage_result)) = failed {
                 eprintln!();
-                eprintln!("{}", format!("Stage '{}' failed:", name).red().bold());
-                if !stage_result.stderr.is_empty() {
+                let header = tr_with("run.failure_header", &[("stage", name)]);
+                eprintln!("{}", header.red().bold());
+
+                let parsed: Vec<_> = diagnostics.iter().filter(|(s, _)| s == name).collect();
+                if !parsed.is_empty() {
+                    let mut renderer = Renderer::new(&working_dir);
+                    for (_, diagnostic) in parsed {
+                        eprintln!("{}", renderer.render(&Diagnostic::from(diagnostic)));
+                    }
+                } else if !stage_result.stderr.is_empty() {
                     eprintln!("{}", stage_result.stderr.dimmed());
                 }
-                break;
             }
         }
-        return Err(miette::miette!("Pipeline execution failed"));
+        if let Some(ref record) = record {
+            print_last_passing(record, &working_dir);
+        }
+        return Err(ConflowError::CheckFailed {
+            message: "Pipeline execution failed".into(),
+        }
+        .into());
     }
 
-    // Print outputs
-    let outputs: Vec<_> = result
-        .results
-        .values()
+    // Print outputs, in pipeline order
+    let outputs: Vec<_> = pipeline
+        .stages
+        .iter()
+        .filter_map(|stage| result.results.get(&stage.name))
         .flat_map(|r| r.outputs.iter())
         .collect();
 
-    if !outputs.is_empty() {
+    if !outputs.is_empty() && !quiet {
         println!();
-        println!("{}:", "Outputs".bold());
+        println!("{}:", tr("run.outputs").bold());
         for output in outputs {
             println!("  - {}", output.display());
         }
     }
 
+    if fail_on.fails_on_warnings() {
+        let warnings: Vec<_> = diagnostics
+            .iter()
+            .filter(|(_, d)| d.severity == Severity::Warning)
+            .collect();
+        let mut renderer = Renderer::new(&working_dir);
+        for (_, diagnostic) in &warnings {
+            eprintln!("{}", renderer.render(&Diagnostic::from(diagnostic)));
+        }
+        let count = warnings.len() + validation.warnings.len();
+        if count > 0 {
+            return Err(ConflowError::WarningsFound { count }.into());
+        }
+    }
+
     Ok(())
 }
+
+/// Which stages to run to resume the last recorded run
+///
+/// Returns `None` when there is nothing left to run, and no run to resume
+/// from when the pipeline file changed since.
+fn resume_point(
+    pipeline: &Pipeline,
+    pipeline_path: &Path,
+    working_dir: &Path,
+    stages: Vec<String>,
+    quiet: bool,
+) -> Result<Option<(Vec<String>, Option<RunRecord>)>> {
+    let last = RunHistory::new(working_dir).latest()?.ok_or_else(|| {
+        miette::miette!(
+            help = "runs are recorded by 'conflow run'",
+            "No recorded run to resume"
+        )
+    })?;
+
+    if last.pipeline_digest != RunRecord::digest(pipeline_path) {
+        eprintln!(
+            "{} {} changed since run {}, running all stages",
+            "⚠".yellow(),
+            pipeline_path.display(),
+            last.id
+        );
+        return Ok(Some((stages, None)));
+    }
+
+    let mut pending = last.pending();
+    if !stages.is_empty() {
+        pending.retain(|s| stages.contains(s));
+    }
+    if pending.is_empty() || (last.finished && last.success) {
+        if !quiet {
+            println!("{} Nothing to resume from run {}", "✓".green(), last.id);
+        }
+        return Ok(None);
+    }
+
+    if !quiet {
+        let state = if last.finished {
+            "failed"
+        } else {
+            "was interrupted"
+        };
+        println!(
+            "{} Resuming run {}, which {}: {} of {} stage(s) left",
+            "→".blue(),
+            last.id,
+            state,
+            pending.len(),
+            last.stages.len()
+        );
+    }
+    // Producers of `from_stage` inputs run again, usually from the cache
+    Ok(Some((with_producers(pipeline, pending), Some(last))))
+}
+
+/// Record the start of the run under `.conflow/runs`
+fn start_record(
+    pipeline: &Pipeline,
+    pipeline_path: &Path,
+    working_dir: &Path,
+    resumed: Option<&RunRecord>,
+) -> Option<RunRecord> {
+    let mut record = RunRecord::started(pipeline, pipeline_path, working_dir);
+    if let Some(resumed) = resumed {
+        record.resume(resumed);
+    }
+    match RunHistory::new(working_dir).record(record) {
+        Ok(record) => Some(record),
+        Err(e) => {
+            eprintln!("{} Failed to record the run: {}", "⚠".yellow(), e);
+            None
+        }
+    }
+}
+
+/// Save the finished run's record
+fn save_record(record: RunRecord, working_dir: &Path, verbose: bool) -> Option<RunRecord> {
+    match RunHistory::new(working_dir).save(&record) {
+        Ok(()) => {
+            if verbose {
+                println!("{} Recorded as run {}", "→".blue(), record.id);
+            }
+            Some(record)
+        }
+        Err(e) => {
+            eprintln!("{} Failed to record the run: {}", "⚠".yellow(), e);
+            None
+        }
+    }
+}
+
+/// Point a failed run at the last one that passed
+fn print_last_passing(record: &RunRecord, working_dir: &Path) {
+    let runs = RunHistory::new(working_dir).list().unwrap_or_default();
+    let Some(last) = runs.iter().find(|r| r.id < record.id && r.success) else {
+        return;
+    };
+    let command = format!("conflow runs compare {} {}", last.id, record.id);
+    let message = tr_with(
+        "run.last_passing",
+        &[
+            ("id", &record.id),
+            ("last", &last.id),
+            ("command", &command.cyan()),
+        ],
+    );
+    eprintln!("\n{} {}", "→".blue(), message);
+}
+
+/// Notify configured channels and remember the run's outcome
+async fn send_notifications(
+    pipeline: &Pipeline,
+    result: &PipelineResult,
+    stage_owners: &BTreeMap<String, Vec<String>>,
+    working_dir: &Path,
+    verbose: bool,
+) {
+    let compliance = ComplianceChecker::for_project(working_dir)
+        .and_then(|checker| checker.check(working_dir))
+        .ok();
+    let summary = RunSummary::new(&pipeline.name, result, compliance.as_ref())
+        .with_stage_owners(stage_owners);
+    let events = NotifyState::load(working_dir).events(&summary);
+
+    let (sent, errors) = Notifier::new(pipeline.notifications.clone())
+        .notify(&summary, &events)
+        .await;
+    for error in errors {
+        eprintln!("{} {}", "⚠".yellow(), error);
+    }
+    if verbose && sent > 0 {
+        println!("{} Sent {} notification(s)", "→".blue(), sent);
+    }
+
+    if let Err(e) = NotifyState::save(working_dir, &summary) {
+        eprintln!("{} {}", "⚠".yellow(), e);
+    }
+}
+
+/// Create or update the PR/MR comment with results and the compliance diff
+async fn post_pr_comment(
+    pipeline: &Pipeline,
+    result: &PipelineResult,
+    diagnostics: &[(String, ToolDiagnostic)],
+    working_dir: &Path,
+    base_ref: Option<String>,
+) -> std::result::Result<(), ConflowError> {
+    let target = PrTarget::from_env()?;
+    let checker = ComplianceChecker::for_project(working_dir)?;
+    let compliance = checker.check(working_dir).ok();
+
+    // Check the target branch from a clean export of its tree
+    let base_ref = base_ref.or_else(|| target.base_ref.clone());
+    let base = match (&base_ref, &compliance) {
+        (Some(git_ref), Some(_)) => {
+            let report = crate::utils::private_tempdir("conflow-base-")
+                .map_err(ConflowError::from)
+                .and_then(|scratch| {
+                    let dir = git::export_tree(working_dir, git_ref, scratch.path())?;
+                    checker.check(&dir)
+                });
+            match report {
+                Ok(report) => Some((git_ref.as_str(), report)),
+                Err(e) => {
+                    let skipped = format!("Skipping compliance diff against {}", git_ref);
+                    eprintln!("{} {}: {}", "⚠".yellow(), skipped, e);
+                    None
+                }
+            }
+        }
+        _ => None,
+    };
+
+    let costs = cost_estimates(pipeline, result, working_dir);
+    let report = PrReport {
+        pipeline,
+        result,
+        diagnostics,
+        costs: &costs,
+        compliance: compliance.as_ref(),
+        base: base.as_ref().map(|(r, c)| (*r, c)),
+    };
+    let updated = PrCommenter::new(target)
+        .upsert(&report.marker(), &report.markdown())
+        .await?;
+
+    let action = if updated { "Updated" } else { "Posted" };
+    println!("{} {} PR comment", "→".blue(), action);
+    Ok(())
+}
+
+/// Estimates recorded by the cost stages that ran, keyed by stage
+fn cost_estimates(
+    pipeline: &Pipeline,
+    result: &PipelineResult,
+    working_dir: &Path,
+) -> Vec<(String, CostEstimate)> {
+    pipeline
+        .stages
+        .iter()
+        .filter(|stage| matches!(stage.tool, Tool::Cost { .. }))
+        .filter(|stage| result.results.contains_key(&stage.name))
+        .filter_map(|stage| {
+            let dir = stage_workdir(stage, working_dir);
+            Some((stage.name.clone(), CostEstimate::load(&dir, &stage.name)?))
+        })
+        .collect()
+}
+
+/// Parse each executed stage's output into diagnostics, keyed by stage
+fn collect_diagnostics(
+    pipeline: &Pipeline,
+    result: &PipelineResult,
+) -> Vec<(String, ToolDiagnostic)> {
+    let mut diagnostics = Vec::new();
+
+    for stage in &pipeline.stages {
+        let Some(stage_result) = result.results.get(&stage.name) else {
+            continue;
+        };
+        if stage_result.cache_hit {
+            continue;
+        }
+
+        let output = format!("{}\n{}", stage_result.stdout, stage_result.stderr);
+        for diagnostic in parsers::parse_output(stage.tool_name(), &output) {
+            diagnostics.push((stage.name.clone(), diagnostic));
+        }
+    }
+
+    diagnostics
+}
+
+/// Owners of each failed stage
+fn failed_stage_owners(
+    result: &PipelineResult,
+    diagnostics: &[(String, ToolDiagnostic)],
+    ownership: &Ownership,
+) -> BTreeMap<String, Vec<String>> {
+    result
+        .results
+        .iter()
+        .filter(|(_, r)| !r.success)
+        .map(|(name, _)| (name.clone(), ownership.of_stage(name, diagnostics)))
+        .collect()
+}
+
+/// Print every failed stage's diagnostics under the owners of their files
+///
+/// A failed stage without parsed diagnostics is listed, with its stderr,
+/// under the owners of the stage.
+fn print_failures_by_owner(
+    pipeline: &Pipeline,
+    result: &PipelineResult,
+    diagnostics: &[(String, ToolDiagnostic)],
+    ownership: &Ownership,
+    working_dir: &Path,
+) {
+    let failed = pipeline
+        .stages
+        .iter()
+        .filter(|stage| result.results.get(&stage.name).is_some_and(|r| !r.success));
+    let mut findings: Vec<(&str, Option<&ToolDiagnostic>)> = Vec::new();
+    for stage in failed {
+        let before = findings.len();
+        findings.extend(
+            diagnostics
+                .iter()
+                .filter(|(name, _)| name == &stage.name)
+                .map(|(name, d)| (name.as_str(), Some(d))),
+        );
+        if findings.len() == before {
+            findings.push((stage.name.as_str(), None));
+        }
+    }
+
+    let groups = group_by_owner(findings, |(stage, diagnostic)| match diagnostic {
+        Some(diagnostic) => ownership.of_diagnostic(stage, diagnostic),
+        None => ownership.of_stage(stage, diagnostics),
+    });
+    let mut renderer = Renderer::new(working_dir);
+    for (owner, findings) in groups {
+        eprintln!();
+        eprintln!(
+            "{}",
+            tr_with("run.owner_header", &[("owner", &owner)]).bold()
+        );
+        let mut current = None;
+        for (stage, diagnostic) in findings {
+            if current != Some(stage) {
+                eprintln!(
+                    "{}",
+                    tr_with("run.failure_header", &[("stage", &stage)])
+                        .red()
+                        .bold()
+                );
+                current = Some(stage);
+            }
+            match diagnostic {
+                Some(diagnostic) => {
+                    eprintln!("{}", renderer.render(&Diagnostic::from(diagnostic)))
+                }
+                None => {
+                    let stderr = &result.results[stage].stderr;
+                    if !stderr.is_empty() {
+                        eprintln!("{}", stderr.dimmed());
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Write diagnostics as JSON, SARIF or a Code Quality report
+///
+/// With `ownership`, a JSON report is keyed by owner.
+fn write_report(
+    path: &Path,
+    format: ReportFormat,
+    diagnostics: &[(String, ToolDiagnostic)],
+    ownership: Option<&Ownership>,
+) -> Result<()> {
+    let by_owner = ownership.map(|ownership| {
+        group_by_owner(diagnostics, |(stage, d)| ownership.of_diagnostic(stage, d))
+    });
+    let diagnostics: Vec<ToolDiagnostic> = diagnostics.iter().map(|(_, d)| d.clone()).collect();
+    let value = match format {
+        ReportFormat::Json => match by_owner {
+            Some(groups) => serde_json::Value::Object(
+                groups
+                    .into_iter()
+                    .map(|(owner, findings)| {
+                        let findings: Vec<_> = findings.iter().map(|(_, d)| d.clone()).collect();
+                        (owner, parsers::to_json(&findings))
+                    })
+                    .collect(),
+            ),
+            None => parsers::to_json(&diagnostics),
+        },
+        ReportFormat::Sarif => parsers::to_sarif(&diagnostics),
+        ReportFormat::CodeQuality => parsers::to_code_quality(&diagnostics),
+    };
+
+    let content = serde_json::to_string_pretty(&value)
+        .map_err(|e| miette::miette!("Failed to serialize diagnostics: {}", e))?;
+    std::fs::write(path, content)
+        .map_err(|e| miette::miette!("Failed to write '{}': {}", path.display(), e))
+}
+
+/// Print the slowest stages and where time went
+fn print_profile_summary(profile: &Profile, path: &Path) {
+    println!();
+    println!("{}", "Profile".bold());
+    println!("{}", "═".repeat(50));
+
+    let slowest = profile.slowest_stages(5);
+    if !slowest.is_empty() {
+        println!("Slowest stages:");
+        for span in slowest {
+            println!(
+                "  {:>8.2}s  {}",
+                span.duration.as_secs_f64(),
+                span.name.bold()
+            );
+        }
+        println!();
+    }
+
+    println!(
+        "Subprocess: {:.2}s  Cache: {:.2}s",
+        profile.total(SpanKind::Process).as_secs_f64(),
+        profile.total(SpanKind::Cache).as_secs_f64()
+    );
+    println!(
+        "{} Trace written to {}",
+        "→".blue(),
+        path.display().to_string().cyan()
+    );
+}
+
+/// Unpack a run bundle into a fresh temporary directory and change into it
+///
+/// Returns the pipeline file and the directory, which is deleted when
+/// dropped. The bundle's own `redact:` rules replace those of the directory
+/// conflow was started in.
+async fn open_bundle(bundle: &Path, quiet: bool) -> Result<(PathBuf, tempfile::TempDir)> {
+    let dir = tempfile::Builder::new()
+        .prefix("conflow-run-")
+        .tempdir()
+        .map_err(|e| ConflowError::Io {
+            message: format!("Failed to create a directory for the bundle: {}", e),
+        })?;
+    let manifest = bundle::unpack_run(bundle, dir.path(), &Caches::open()?)?;
+    std::env::set_current_dir(dir.path()).map_err(|e| ConflowError::Io {
+        message: format!("Failed to change to {}: {}", dir.path().display(), e),
+    })?;
+    if !quiet {
+        println!(
+            "{} Running {} (made {} by conflow {}) in {}",
+            "→".blue(),
+            bundle.display(),
+            manifest.created,
+            manifest.conflow_version,
+            dir.path().display()
+        );
+    }
+
+    let pipeline_path = manifest.pipeline.clone().unwrap_or_default();
+    let pipeline = Pipeline::from_file(&pipeline_path)?;
+    crate::report::set_redactor(Redactor::new(&pipeline.redact, &HashMap::new()));
+    for (tool, bundled, local) in manifest.version_drift(&bundle::tool_versions(&pipeline).await) {
+        println!(
+            "{} {} is {} here; the bundled run used {}",
+            "⚠".yellow(),
+            tool,
+            local,
+            bundled
+        );
+    }
+    Ok((pipeline_path, dir))
+}
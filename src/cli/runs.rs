@@ -0,0 +1,277 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Runs command - list, show and compare recorded pipeline runs
+
+use colored::{ColoredString, Colorize};
+use miette::Result;
+use std::path::Path;
+use std::time::Duration;
+
+use super::{OutputFormat, RunsAction};
+use crate::pipeline::{RunHistory, RunRecord, StageOutcome, RUNS_DIR};
+use crate::ConflowError;
+
+/// Run the runs command
+pub async fn run(action: RunsAction, _verbose: bool) -> Result<()> {
+    let working_dir = std::env::current_dir().map_err(|e| ConflowError::Io {
+        message: e.to_string(),
+    })?;
+    let history = RunHistory::new(&working_dir);
+
+    match action {
+        RunsAction::List { limit, format } => list(&history, limit, format),
+        RunsAction::Show { id, format } => {
+            let record = resolve(&history, &id)?;
+            match format {
+                OutputFormat::Text => print_record(&record),
+                OutputFormat::Json => println!("{}", crate::report::to_json(&record)?),
+            }
+            Ok(())
+        }
+        RunsAction::Compare { from, to } => {
+            let from = resolve(&history, &from)?;
+            let to = resolve(&history, &to)?;
+            print_comparison(&from, &to, &working_dir);
+            Ok(())
+        }
+    }
+}
+
+/// A run by ID, or the newest for `latest`
+fn resolve(history: &RunHistory, id: &str) -> Result<RunRecord> {
+    if id == "latest" {
+        return history.latest()?.ok_or_else(|| {
+            miette::miette!(
+                help = "runs are recorded by 'conflow run'",
+                "No runs recorded in {}",
+                RUNS_DIR
+            )
+        });
+    }
+    let id = id
+        .trim_start_matches('#')
+        .parse()
+        .map_err(|_| miette::miette!("Invalid run ID '{}': expected a number or 'latest'", id))?;
+    Ok(history.get(id)?)
+}
+
+fn list(history: &RunHistory, limit: usize, format: OutputFormat) -> Result<()> {
+    let runs: Vec<RunRecord> = history.list()?.into_iter().take(limit).collect();
+
+    if format == OutputFormat::Json {
+        let json = serde_json::to_string_pretty(&runs).map_err(ConflowError::from)?;
+        println!("{}", json);
+        return Ok(());
+    }
+    if runs.is_empty() {
+        println!("{} No runs recorded in {}", "⚠".yellow(), RUNS_DIR);
+        return Ok(());
+    }
+
+    for run in &runs {
+        let count = |outcome| run.stages.iter().filter(|s| s.outcome == outcome).count();
+        let mut stages = format!(
+            "{} passed, {} cached",
+            count(StageOutcome::Passed),
+            count(StageOutcome::Cached)
+        );
+        if count(StageOutcome::Failed) > 0 {
+            stages.push_str(&format!(", {} failed", count(StageOutcome::Failed)));
+        }
+        println!(
+            "{} {:>4}  {}  {:>8}  {:<8}  {}",
+            status_icon(run),
+            run.id,
+            run.started_at,
+            seconds(run.duration),
+            commit(run),
+            stages.dimmed()
+        );
+    }
+    Ok(())
+}
+
+fn print_record(run: &RunRecord) {
+    let status = match (run.finished, run.success) {
+        (false, _) => status(run).yellow(),
+        (true, true) => status(run).green(),
+        (true, false) => status(run).red(),
+    };
+    println!(
+        "{} {} of {} {}",
+        "Run".bold(),
+        run.id.to_string().bold(),
+        run.pipeline.cyan(),
+        status
+    );
+    if let Some(resumed) = run.resumed_from {
+        println!("  Resumed:  run {}", resumed);
+    }
+    println!("  Started:  {}", run.started_at);
+    println!("  Duration: {}", seconds(run.duration));
+    println!("  Commit:   {}", commit(run));
+    println!("  conflow:  {}", run.conflow_version);
+    if let Some(ref digest) = run.pipeline_digest {
+        println!("  Pipeline: {}", &digest[..digest.len().min(12)]);
+    }
+
+    println!();
+    for stage in &run.stages {
+        let mut line = format!(
+            "  {} {} {}",
+            outcome_icon(stage.outcome),
+            stage.name.bold(),
+            stage.outcome.to_string().dimmed()
+        );
+        if stage.outcome != StageOutcome::NotRun {
+            line.push_str(&format!(" {}", seconds(stage.duration)));
+        }
+        if stage.outcome == StageOutcome::Failed {
+            line.push_str(&format!(" (exit {})", stage.exit_code));
+        }
+        println!("{}", line);
+        if let Some(ref error) = stage.error {
+            for error_line in error.lines() {
+                println!("      {}", error_line.dimmed());
+            }
+        }
+    }
+}
+
+fn print_comparison(from: &RunRecord, to: &RunRecord, working_dir: &Path) {
+    println!(
+        "{} {} → {} {}",
+        "Run".bold(),
+        format!("{} ({})", from.id, status(from)).bold(),
+        format!("{} ({})", to.id, status(to)).bold(),
+        format!("{} → {}", from.started_at, to.started_at).dimmed()
+    );
+
+    let changed = |changed: bool| match changed {
+        true => "changed".yellow(),
+        false => "unchanged".dimmed(),
+    };
+    println!(
+        "  Commit:   {} → {} {}",
+        commit(from),
+        commit(to),
+        changed(from.git_commit != to.git_commit || from.git_dirty != to.git_dirty)
+    );
+    println!(
+        "  Pipeline: {}",
+        changed(from.pipeline_digest != to.pipeline_digest)
+    );
+    println!(
+        "  conflow:  {} → {} {}",
+        from.conflow_version,
+        to.conflow_version,
+        changed(from.conflow_version != to.conflow_version)
+    );
+
+    println!();
+    let mut names: Vec<&str> = to.stages.iter().map(|s| s.name.as_str()).collect();
+    for stage in &from.stages {
+        if !names.contains(&stage.name.as_str()) {
+            names.push(&stage.name);
+        }
+    }
+    for name in names {
+        let before = from.stage(name);
+        let after = to.stage(name);
+        let outcome = |stage: Option<&crate::pipeline::StageRecord>| {
+            stage.map_or("absent".to_string(), |s| s.outcome.to_string())
+        };
+        let (icon, transition) = match (before.map(|s| s.outcome), after.map(|s| s.outcome)) {
+            (a, b) if a == b => (" ".normal(), outcome(after).dimmed()),
+            (_, Some(StageOutcome::Failed)) => (
+                "✗".red(),
+                format!("{} → {}", outcome(before), outcome(after)).red(),
+            ),
+            (Some(StageOutcome::Failed), _) => (
+                "✓".green(),
+                format!("{} → {}", outcome(before), outcome(after)).green(),
+            ),
+            _ => (
+                "~".yellow(),
+                format!("{} → {}", outcome(before), outcome(after)).yellow(),
+            ),
+        };
+
+        let mut line = format!("  {} {} {}", icon, name.bold(), transition);
+        if let (Some(before), Some(after)) = (before, after) {
+            let ran = |s: &crate::pipeline::StageRecord| s.outcome != StageOutcome::NotRun;
+            if ran(before) && ran(after) {
+                let delta = after.duration.as_secs_f64() - before.duration.as_secs_f64();
+                line.push_str(&format!(
+                    "  {} → {} ({:+.2}s)",
+                    seconds(before.duration),
+                    seconds(after.duration),
+                    delta
+                ));
+            }
+        }
+        println!("{}", line);
+
+        if let Some(error) = after.and_then(|s| s.error.as_ref()) {
+            if before.map(|s| s.outcome) != Some(StageOutcome::Failed) {
+                for error_line in error.lines() {
+                    println!("      {}", error_line.dimmed());
+                }
+            }
+        }
+    }
+
+    if let (Some(a), Some(b)) = (&from.git_commit, &to.git_commit) {
+        if a != b && crate::utils::git::repo_root(working_dir).is_ok() {
+            println!(
+                "\n{} {}",
+                "Commits in between:".dimmed(),
+                format!("git log --oneline {}..{}", short(a), short(b)).cyan()
+            );
+        }
+    }
+}
+
+/// Runs that never finished were killed part way, or are still going
+fn status(run: &RunRecord) -> &'static str {
+    match (run.finished, run.success) {
+        (false, _) => "interrupted",
+        (true, true) => "passed",
+        (true, false) => "failed",
+    }
+}
+
+fn status_icon(run: &RunRecord) -> ColoredString {
+    match (run.finished, run.success) {
+        (false, _) => "⚠".yellow(),
+        (true, true) => "✓".green(),
+        (true, false) => "✗".red(),
+    }
+}
+
+fn outcome_icon(outcome: StageOutcome) -> ColoredString {
+    match outcome {
+        StageOutcome::Passed => "✓".green(),
+        StageOutcome::Cached => "✓".blue(),
+        StageOutcome::Failed => "✗".red(),
+        StageOutcome::NotRun => "○".dimmed(),
+    }
+}
+
+/// Short commit, with `+` for uncommitted changes
+fn commit(run: &RunRecord) -> String {
+    match run.git_commit {
+        Some(ref sha) if run.git_dirty => format!("{}+", short(sha)),
+        Some(ref sha) => short(sha).to_string(),
+        None => "-".to_string(),
+    }
+}
+
+fn short(sha: &str) -> &str {
+    &sha[..sha.len().min(7)]
+}
+
+fn seconds(duration: Duration) -> String {
+    format!("{:.2}s", duration.as_secs_f64())
+}
@@ -0,0 +1,136 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Scan command - validate a repository's configs with no setup
+
+use colored::Colorize;
+use miette::Result;
+use std::path::PathBuf;
+
+use super::OutputFormat;
+use crate::diagnostics::{annotations, Diagnostic};
+use crate::errors::{ConflowError, FailOn};
+use crate::executors::parsers::Severity;
+use crate::pipeline::{scan, Pipeline, ScanReport, ScanStatus};
+
+/// Run the scan command
+pub async fn run(
+    patterns: Vec<String>,
+    pipeline_path: PathBuf,
+    fail_on: FailOn,
+    format: OutputFormat,
+    verbose: bool,
+) -> Result<()> {
+    let cwd = std::env::current_dir().map_err(|e| ConflowError::Io {
+        message: e.to_string(),
+    })?;
+    let pipeline = match pipeline_path.exists() {
+        true => Some(Pipeline::from_file(&pipeline_path)?),
+        false => None,
+    };
+
+    let report = scan(&cwd, &patterns, pipeline.as_ref())?;
+    for diagnostic in report.files.iter().flat_map(|f| &f.diagnostics) {
+        annotations::emit(&Diagnostic::from(diagnostic));
+    }
+    match format {
+        OutputFormat::Text => print_text(&report, verbose),
+        OutputFormat::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&report).map_err(ConflowError::from)?
+        ),
+    }
+
+    let summary = &report.summary;
+    if summary.errors > 0 && fail_on.fails_on_errors() {
+        return Err(ConflowError::CheckFailed {
+            message: format!(
+                "{} config file(s) failed their schema",
+                summary.count(ScanStatus::Invalid)
+            ),
+        }
+        .into());
+    }
+    if summary.warnings > 0 && fail_on.fails_on_warnings() {
+        let count = summary.warnings;
+        return Err(ConflowError::WarningsFound { count }.into());
+    }
+    Ok(())
+}
+
+fn print_text(report: &ScanReport, verbose: bool) {
+    let summary = &report.summary;
+    if summary.total == 0 {
+        println!("{} No JSON or YAML files found", "⚠".yellow());
+        return;
+    }
+
+    let mut rank = 0;
+    for file in &report.files {
+        let shown = verbose || matches!(file.status, ScanStatus::Invalid);
+        if !shown {
+            continue;
+        }
+        let icon = match file.status {
+            ScanStatus::Invalid => "✗".red(),
+            ScanStatus::Valid => "✓".green(),
+            ScanStatus::Unchecked => "•".yellow(),
+            ScanStatus::Unrecognized => "○".dimmed(),
+        };
+        let schema = match file.schema {
+            Some(ref m) => format!(" ({})", m.schema).dimmed().to_string(),
+            None => String::new(),
+        };
+        let errors = file.count(Severity::Error);
+        let mut line = format!(
+            "{} {}{}",
+            icon,
+            file.path.display().to_string().bold(),
+            schema
+        );
+        if errors > 0 {
+            rank += 1;
+            line = format!("{:>3}. {} - {} error(s)", rank, line, errors);
+        } else {
+            line = format!("     {}", line);
+        }
+        println!("{}", line);
+        for diagnostic in &file.diagnostics {
+            println!("       {}", diagnostic.render());
+        }
+    }
+    if rank > 0 || verbose {
+        println!();
+    }
+
+    let unchecked = summary.count(ScanStatus::Unchecked);
+    if unchecked > 0 {
+        println!(
+            "{} `cue` is not installed; {} recognized file(s) were only parsed",
+            "⚠".yellow(),
+            unchecked
+        );
+    }
+    if !summary.by_schema.is_empty() {
+        let schemas: Vec<String> = summary
+            .by_schema
+            .iter()
+            .map(|(schema, count)| format!("{} {}", schema, count))
+            .collect();
+        println!("Recognized: {}", schemas.join(", ").cyan());
+    }
+    let icon = match summary.errors {
+        0 => "✓".green(),
+        _ => "✗".red(),
+    };
+    println!(
+        "{} {} file(s): {} valid, {} invalid, {} unchecked, {} unrecognized; {} error(s)",
+        icon,
+        summary.total,
+        summary.count(ScanStatus::Valid),
+        summary.count(ScanStatus::Invalid),
+        unchecked,
+        summary.count(ScanStatus::Unrecognized),
+        summary.errors
+    );
+}
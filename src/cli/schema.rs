@@ -0,0 +1,338 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Schema command - export schemas for conflow's own config files
+//!
+//! The JSON Schemas let editors validate and complete `.conflow.yaml` and
+//! `.rsr.yaml` through yaml-language-server `$schema` comments. `detect`
+//! shows which built-in schema a CUE `vet` stage without `schemas:` would
+//! check a file against.
+
+use colored::Colorize;
+use miette::Result;
+use std::path::PathBuf;
+
+use super::{ConfigSchema, OutputFormat, SchemaAction, SchemaFormat};
+use crate::errors::ConflowError;
+use crate::rsr::detect::detect_file;
+use crate::rsr::RsrSchemaRegistry;
+
+/// JSON Schema for `.conflow.yaml`
+pub const PIPELINE_JSON_SCHEMA: &str = include_str!("../../schemas/conflow-pipeline.schema.json");
+
+/// JSON Schema for `.rsr.yaml`
+pub const RSR_JSON_SCHEMA: &str = include_str!("../../schemas/conflow-rsr.schema.json");
+
+/// Run the schema command
+pub async fn run(action: SchemaAction, verbose: bool) -> Result<()> {
+    match action {
+        SchemaAction::Export { id, format, output } => export(id, format, output, verbose),
+        SchemaAction::Detect { files, format } => detect(&files, format, verbose),
+    }
+}
+
+fn detect(files: &[PathBuf], format: OutputFormat, verbose: bool) -> Result<()> {
+    let mut detected = Vec::new();
+    for file in files {
+        detected.push((file, detect_file(file)?));
+    }
+
+    match format {
+        OutputFormat::Text => {
+            for (file, matched) in &detected {
+                let shown = file.display().to_string();
+                let Some(matched) = matched else {
+                    println!("{} {}", shown, "no known schema".dimmed());
+                    continue;
+                };
+                let mut schema = matched.schema.clone();
+                if let Some(definition) = &matched.definition {
+                    schema.push_str(&format!(" ({})", definition));
+                }
+                println!("{} {}", shown, schema.cyan());
+                if verbose {
+                    println!("  score {}: {}", matched.score, matched.evidence.join(", "));
+                }
+            }
+        }
+        OutputFormat::Json => {
+            let json: Vec<_> = detected
+                .iter()
+                .map(|(file, matched)| serde_json::json!({"file": file, "match": matched}))
+                .collect();
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&json).map_err(ConflowError::from)?
+            );
+        }
+    }
+    Ok(())
+}
+
+fn export(
+    id: ConfigSchema,
+    format: SchemaFormat,
+    output: Option<PathBuf>,
+    verbose: bool,
+) -> Result<()> {
+    let content = content(id, format)?;
+
+    match output {
+        Some(path) => {
+            std::fs::write(&path, content)
+                .map_err(|e| miette::miette!("Failed to write {}: {}", path.display(), e))?;
+            if verbose {
+                let shown = path.display().to_string();
+                println!("{} Schema written to {}", "✓".green(), shown.cyan());
+            }
+        }
+        None => print!("{}", content),
+    }
+
+    Ok(())
+}
+
+/// Schema text in the requested format
+pub fn content(id: ConfigSchema, format: SchemaFormat) -> Result<String> {
+    let content = match (id, format) {
+        (ConfigSchema::ConflowPipeline, SchemaFormat::Jsonschema) => PIPELINE_JSON_SCHEMA.into(),
+        (ConfigSchema::ConflowRsr, SchemaFormat::Jsonschema) => RSR_JSON_SCHEMA.into(),
+        // The CUE definitions live in the RSR registry
+        (ConfigSchema::ConflowPipeline, SchemaFormat::Cue) => {
+            RsrSchemaRegistry::new().get_content("rsr:pipeline")?
+        }
+        (ConfigSchema::ConflowRsr, SchemaFormat::Cue) => {
+            RsrSchemaRegistry::new().get_content("rsr:config")?
+        }
+    };
+    Ok(content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::Pipeline;
+    use crate::rsr::config::RsrConfig;
+    use serde_json::Value;
+
+    /// Check that every key in `value` is allowed by `schema`
+    ///
+    /// A minimal structural check, enough to catch fields added to the
+    /// config structs but not to the schema.
+    fn check(value: &Value, schema: &Value, root: &Value) -> std::result::Result<(), String> {
+        if let Some(reference) = schema["$ref"].as_str() {
+            let name = reference.trim_start_matches("#/definitions/");
+            return check(value, &root["definitions"][name], root);
+        }
+        if let Some(branches) = schema["oneOf"].as_array() {
+            if !branches.iter().any(|b| check(value, b, root).is_ok()) {
+                return Err(format!("no oneOf branch matches {}", value));
+            }
+        }
+        if let Some(allowed) = schema["enum"].as_array() {
+            if !allowed.contains(value) {
+                return Err(format!("{} not in {:?}", value, allowed));
+            }
+        }
+        if let Some(expected) = schema.get("const") {
+            if expected != value {
+                return Err(format!("{} is not {}", value, expected));
+            }
+        }
+        if let Some(types) = schema.get("type") {
+            let name = match value {
+                Value::Null => "null",
+                Value::Bool(_) => "boolean",
+                Value::Number(n) if n.is_i64() || n.is_u64() => "integer",
+                Value::Number(_) => "number",
+                Value::String(_) => "string",
+                Value::Array(_) => "array",
+                Value::Object(_) => "object",
+            };
+            let ok = match types {
+                Value::Array(types) => types.iter().any(|t| t == name),
+                t => t == name,
+            };
+            if !ok {
+                return Err(format!("{} is not {}", value, types));
+            }
+        }
+
+        match value {
+            Value::Object(map) => {
+                for (key, item) in map {
+                    let result = match (&schema["properties"][key], &schema["additionalProperties"])
+                    {
+                        (Value::Null, Value::Bool(false)) => Err(format!("unknown key '{}'", key)),
+                        (Value::Null, Value::Object(_)) => {
+                            check(item, &schema["additionalProperties"], root)
+                        }
+                        (Value::Null, _) => Ok(()),
+                        (property, _) => check(item, property, root),
+                    };
+                    result.map_err(|e| format!("{}: {}", key, e))?;
+                }
+                Ok(())
+            }
+            Value::Array(items) if schema.get("items").is_some() => items
+                .iter()
+                .try_for_each(|item| check(item, &schema["items"], root)),
+            _ => Ok(()),
+        }
+    }
+
+    fn schema(id: ConfigSchema) -> Value {
+        serde_json::from_str(&content(id, SchemaFormat::Jsonschema).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_pipeline_schema_covers_all_fields() {
+        let pipeline = Pipeline::from_yaml(
+            r##"
+name: full
+env_file: .env
+secrets: [TOKEN]
+telemetry: { endpoint: "http://localhost:4318", service_name: ci, headers: { a: b } }
+plugins: [lint.wasm]
+toolchain: { cue: { version: "0.9", checksums: { linux-x86_64: abc } } }
+sandbox: { enabled: true, writable: [out], limits: { cpu_seconds: 10, memory_mb: 512 } }
+cache: { scope: global, namespace: schemas, max_size_mb: 500, max_age_days: 30 }
+notifications:
+  - { type: slack, url: "https://hooks.example.com", headers: { a: b }, template: "{}" }
+  - { type: webhook, url: "https://hooks.example.com", owners: ["@org/platform"] }
+executors:
+  lint: { command: "lint {inputs}", requires: lint, error_pattern: "(?P<message>.+)" }
+stages:
+  - name: vet
+    tool: { type: cue, command: export, schemas: [s.cue], flags: [-c], out_format: json }
+    input: ["*.cue"]
+    output: { path: out.json, format: json }
+    manual: true
+    env_file: stage.env
+    workdir: sub
+    when: { env_equals: { var: CI, value: "true" } }
+  - name: gen
+    tool: { type: nickel, command: export, file: main.ncl, format: yaml }
+    input: { from_stage: vet }
+    output: out.yaml
+    snapshot: true
+    contract: { schema: k8s:base, definition: "#Deployment" }
+    depends_on: [vet]
+    allow_failure: true
+    condition: { file_exists: main.ncl }
+  - name: lint
+    tool: { type: custom, executor: lint, args: [--strict] }
+    input: "*.yaml"
+    exclude: [vendor/]
+    shard: auto
+  - name: sh
+    tool: { type: shell, command: "true", shell: sh }
+    input: "*"
+    env: { A: b }
+    condition: never
+  - name: k8s-plan
+    tool: { type: kubectl, action: plan, namespace: ns, context: ctx, flags: [] }
+    input: "k8s/*.yaml"
+  - name: k8s-apply
+    tool: { type: kubectl, action: apply, plan: k8s-plan }
+    input: "k8s/*.yaml"
+  - name: helm
+    tool: { type: helm, action: plan, release: r, chart: c, namespace: n, values: [v.yaml] }
+    input: "*"
+  - name: tf
+    tool: { type: terraform, action: plan, dir: infra, flags: [] }
+    input: "infra/*.tf"
+  - name: tf-cost
+    tool: { type: cost, source: terraform, plan: tf, infracost: true, budget: 500 }
+    input: "infra/*.tf"
+  - name: k8s-cost
+    tool: { type: cost, source: kubernetes, pricing: prices.yaml, base: origin/main }
+    input: "k8s/*.yaml"
+refs:
+  - name: configmap
+    from: { files: "k8s/*.yaml", path: "spec.volumes.*.configMap.name", where: { kind: Pod } }
+    to: { files: [k8s/a.yaml], path: metadata, keys: true }
+  - name: app-path
+    from: { files: "argocd/*.yaml", path: spec.source.path }
+    to: { exists: directory, repo: spec.source.repoURL }
+lint:
+  files: ["**/*.yaml"]
+  rules: { no-latest-tag: error, max-depth: { level: warning, max: 6 } }
+  paths: [{ files: ["k8s/**"], rules: { resource-limits: off }, schema: app.schema.json }]
+  custom: [{ name: replicas, rego: policy.rego, files: ["k8s/**"], level: warning }]
+kubernetes: { version: "1.29", files: ["k8s/**/*.yaml"] }
+walk: { symlinks: skip, submodules: true, vendor: [vendor] }
+redact: { paths: ["$..password"], env: [API_TOKEN], patterns: ["token=(\\S+)"] }
+templates: [{ git: "https://example.com/t.git", version: "1.0", path: templates }]
+"##,
+        )
+        .unwrap();
+        let value = serde_json::to_value(&pipeline).unwrap();
+
+        let schema = schema(ConfigSchema::ConflowPipeline);
+        check(&value, &schema, &schema).unwrap();
+
+        let mut typo = value.clone();
+        typo["stages"][0]["depend_on"] = Value::Array(vec![]);
+        assert!(check(&typo, &schema, &schema).is_err());
+    }
+
+    #[test]
+    fn test_rsr_schema_covers_all_fields() {
+        let mut config: RsrConfig = serde_yaml::from_str(
+            r#"
+project: { name: demo, tier: 2, tags: [a] }
+requirements:
+  skip: [RSR-CONFIG-001]
+  overrides: { RSR-CONFIG-002: { class: advisory, skip: false, reason: legacy } }
+  imports: [org.yaml]
+integrations:
+  ci: { provider: gitlab, config: .gitlab-ci.yml }
+  notifications: { slack_webhook: "https://hooks.example.com", emails: [a@b.c] }
+  badges:
+    output: public/badges
+    style: for-the-badge
+    label: compliance
+    thresholds: [{ min_score: 0.8, color: brightgreen }]
+    categories: [security]
+compliance:
+  target_level: good
+  exceptions: [{ requirement: RSR-CONFIG-003, reason: wip, expires: "2030-01-01" }]
+  history_file: .rsr-history.json
+  schedule: "*/30 9-17 * * mon-fri"
+  score: strict
+  weights: { RSR-CONFIG-002: 3.0 }
+  levels: [{ name: L2, min_score: 0.6, mandatory: false, requires: [RSR-CONFIG-001] }]
+schemas: [{ id: org:base, path: base.cue, schema_type: cue }]
+profiles:
+  - { profile: acme/backend@2.1, git: "https://example.com/p.git", path: backend }
+  - { profile: acme/security@1.0, registry: "https://rsr.example.com", sha256: abc }
+templates:
+  - { git: "https://example.com/t.git", version: "1.0", path: templates }
+  - { path: ../shared/templates }
+hooks:
+  - { name: alert, on: [check_compliance], tags: [security], when: failure, command: ./a.sh }
+  - name: dashboard
+    async: true
+    webhook:
+      url: "https://example.com/hook"
+      headers: { a: b }
+      retries: 2
+      backoff_ms: 10
+      timeout_secs: 5
+"#,
+        )
+        .unwrap();
+        config.requirements.custom = crate::rsr::requirements::builtin_config_requirements();
+        let value = serde_json::to_value(&config).unwrap();
+
+        let schema = schema(ConfigSchema::ConflowRsr);
+        check(&value, &schema, &schema).unwrap();
+    }
+
+    #[test]
+    fn test_cue_format() {
+        let cue = content(ConfigSchema::ConflowPipeline, SchemaFormat::Cue).unwrap();
+        assert!(cue.contains("#Pipeline"));
+    }
+}
@@ -0,0 +1,150 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Serve command - REST API for validation, analysis and compliance checks,
+//! scheduled compliance checks of local projects, and optionally the gRPC API
+
+use colored::Colorize;
+use miette::Result;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+
+use crate::rsr::schedule::{self, ScheduledProject};
+use crate::server::{self, ServerConfig};
+use crate::telemetry::Metrics;
+use crate::utils::cron::Schedule;
+
+/// Options for the serve command
+#[derive(Debug, Clone)]
+pub struct ServeOptions {
+    /// Address of the REST API
+    pub addr: SocketAddr,
+    /// Address of the gRPC API, if served
+    pub grpc: Option<SocketAddr>,
+    /// Accepted API keys
+    pub api_keys: Vec<String>,
+    /// Accept requests without an API key
+    pub no_auth: bool,
+    /// Largest accepted request body, in bytes
+    pub max_body: usize,
+    /// Projects whose compliance is checked on a schedule
+    pub projects: Vec<PathBuf>,
+    /// Schedule for projects without their own
+    pub default_schedule: Option<Schedule>,
+    /// Keys agents register with the gRPC coordinator by
+    pub agent_keys: Vec<String>,
+}
+
+/// Run the serve command
+pub async fn run(opts: ServeOptions) -> Result<()> {
+    let ServeOptions {
+        addr,
+        grpc,
+        api_keys,
+        no_auth,
+        max_body,
+        projects,
+        default_schedule,
+        agent_keys,
+    } = opts;
+    let api_keys = trimmed(api_keys);
+    let agent_keys = trimmed(agent_keys);
+
+    if api_keys.is_empty() && !no_auth {
+        return Err(miette::miette!(
+            help =
+                "pass --api-key or set CONFLOW_API_KEYS; use --no-auth only on a trusted network",
+            "No API key configured"
+        ));
+    }
+
+    let projects = projects
+        .iter()
+        .map(|root| ScheduledProject::load(root, default_schedule.as_ref()))
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    #[cfg(not(feature = "grpc"))]
+    if grpc.is_some() {
+        return Err(miette::miette!(
+            help = "rebuild with `cargo install conflow --features grpc`",
+            "This conflow was built without gRPC support"
+        ));
+    }
+
+    let listener = bind(addr).await?;
+    #[cfg(feature = "grpc")]
+    let grpc_listener = match grpc {
+        Some(addr) => Some(bind(addr).await?),
+        None => None,
+    };
+
+    println!(
+        "{} Serving the conflow API on {}",
+        "✓".green(),
+        format!("http://{}/v1", addr).cyan()
+    );
+    if api_keys.is_empty() {
+        println!("{} Authentication is disabled", "⚠".yellow());
+    } else {
+        println!("  {} API key(s) accepted", api_keys.len());
+    }
+    println!("  Request bodies are limited to {} bytes", max_body);
+    println!(
+        "  Prometheus metrics on {}",
+        format!("http://{}/metrics", addr).cyan()
+    );
+    if let Some(grpc) = grpc {
+        println!("  gRPC API on {}", format!("http://{}", grpc).cyan());
+        println!("  {} agent key(s) accepted", agent_keys.len());
+    }
+    for project in &projects {
+        println!(
+            "  Checking compliance of {} on {}",
+            project.name.bold(),
+            project.schedule.to_string().cyan()
+        );
+    }
+    println!("Press {} to stop.", "Ctrl+C".cyan());
+
+    let metrics = Arc::new(Metrics::new());
+    let scheduler = tokio::spawn(schedule::run(projects.clone(), Arc::clone(&metrics)));
+    let config = ServerConfig {
+        api_keys,
+        agent_keys,
+        max_body,
+        max_connections: server::DEFAULT_MAX_CONNECTIONS,
+        projects,
+        metrics,
+    };
+
+    #[cfg(feature = "grpc")]
+    let served = match grpc_listener {
+        Some(grpc_listener) => tokio::select! {
+            served = server::serve(listener, config.clone()) => served,
+            served = crate::grpc::serve(grpc_listener, config) => served,
+        },
+        None => server::serve(listener, config).await,
+    };
+    #[cfg(not(feature = "grpc"))]
+    let served = server::serve(listener, config).await;
+
+    scheduler.abort();
+    served?;
+    Ok(())
+}
+
+/// Keys with surrounding whitespace removed, without empty ones
+fn trimmed(keys: Vec<String>) -> Vec<String> {
+    keys.into_iter()
+        .map(|key| key.trim().to_string())
+        .filter(|key| !key.is_empty())
+        .collect()
+}
+
+async fn bind(addr: SocketAddr) -> Result<TcpListener> {
+    TcpListener::bind(addr)
+        .await
+        .map_err(|e| miette::miette!("Failed to listen on {}: {}", addr, e))
+}
@@ -0,0 +1,47 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Snapshot command - accept changed stage output
+
+use colored::Colorize;
+use miette::Result;
+
+use super::SnapshotAction;
+use crate::errors::ConflowError;
+use crate::pipeline::{accept_snapshots, pending_snapshots};
+
+/// Run the snapshot command
+pub async fn run(action: SnapshotAction) -> Result<()> {
+    let working_dir = std::env::current_dir().map_err(|e| ConflowError::Io {
+        message: e.to_string(),
+    })?;
+
+    match action {
+        SnapshotAction::Update { stages } => {
+            let accepted = accept_snapshots(&working_dir, &stages)?;
+            if accepted.is_empty() {
+                println!("{} No changed snapshots", "✓".green());
+                return Ok(());
+            }
+            for snapshot in &accepted {
+                println!("  {} {}", "✓".green(), snapshot.display());
+            }
+            println!("Accepted {} snapshot(s)", accepted.len());
+        }
+        SnapshotAction::List => {
+            let pending = pending_snapshots(&working_dir, &[]);
+            if pending.is_empty() {
+                println!("{} No changed snapshots", "✓".green());
+                return Ok(());
+            }
+            for snapshot in &pending {
+                println!("  {} {}", "≠".yellow(), snapshot.display());
+            }
+            println!(
+                "{} snapshot(s) changed; run 'conflow snapshot update' to accept them",
+                pending.len()
+            );
+        }
+    }
+    Ok(())
+}
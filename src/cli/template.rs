@@ -0,0 +1,158 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Template command - list and render project templates
+
+use colored::Colorize;
+use miette::Result;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use super::TemplateAction;
+use crate::rsr::templates::{TemplateGenerator, BUILTIN};
+
+/// Run the template command
+pub async fn run(action: TemplateAction, verbose: bool) -> Result<()> {
+    let working_dir = std::env::current_dir()
+        .map_err(|e| miette::miette!("Failed to get current directory: {}", e))?;
+
+    match action {
+        TemplateAction::List { update } => run_list(&working_dir, update, verbose),
+        TemplateAction::Render {
+            name,
+            set,
+            output,
+            dry_run,
+            force,
+        } => run_render(&working_dir, &name, &set, &output, dry_run, force),
+    }
+}
+
+fn run_list(working_dir: &Path, update: bool, verbose: bool) -> Result<()> {
+    let generator = TemplateGenerator::for_project(working_dir, update)?;
+    let mut templates: Vec<_> = generator.list().collect();
+    templates.sort_by(|a, b| a.name.cmp(&b.name));
+
+    println!();
+    println!("{}", "Templates".bold());
+    println!("{}", "═".repeat(50));
+    println!();
+    for template in templates {
+        let version = template
+            .version
+            .as_ref()
+            .map(|v| format!(" {}", v).dimmed().to_string())
+            .unwrap_or_default();
+        println!(
+            "  {}{} {}",
+            template.name.bold(),
+            version,
+            template.description.dimmed()
+        );
+
+        let source = generator.source(&template.name).unwrap_or(BUILTIN);
+        if source != BUILTIN {
+            let overridden = generator.overridden(&template.name);
+            let overrides = match overridden.last() {
+                Some(previous) => format!(", overrides {}", previous),
+                None => String::new(),
+            };
+            println!(
+                "      {} {}{}",
+                "from".dimmed(),
+                source.cyan(),
+                overrides.dimmed()
+            );
+        }
+
+        if verbose {
+            let mut variables: Vec<_> = template.variables.iter().collect();
+            variables.sort_by(|a, b| a.0.cmp(b.0));
+            for (name, var) in variables {
+                let default = if var.default.is_empty() {
+                    String::new()
+                } else {
+                    format!(" (default: {})", var.default)
+                };
+                println!("      {} {}{}", name, var.description.dimmed(), default);
+            }
+        }
+    }
+    println!();
+
+    Ok(())
+}
+
+/// Parse `--set key=value` pairs
+pub(super) fn parse_variables(set: &[String]) -> Result<HashMap<String, String>> {
+    let mut variables = HashMap::new();
+    for pair in set {
+        let Some((key, value)) = pair.split_once('=') else {
+            return Err(miette::miette!(
+                help = "Use --set key=value",
+                "Invalid variable: {}",
+                pair
+            ));
+        };
+        variables.insert(key.trim().to_string(), value.to_string());
+    }
+    Ok(variables)
+}
+
+fn run_render(
+    working_dir: &Path,
+    name: &str,
+    set: &[String],
+    output: &Path,
+    dry_run: bool,
+    force: bool,
+) -> Result<()> {
+    let variables = parse_variables(set)?;
+
+    let generator = TemplateGenerator::for_project(working_dir, false)?;
+
+    // Catch typos in --set keys
+    if let Some(template) = generator.get(name) {
+        let mut used = HashSet::new();
+        for file in &template.files {
+            for source in [&file.path, &file.content] {
+                used.extend(crate::utils::gotemplate::variables(source).unwrap_or_default());
+            }
+        }
+        for key in variables.keys().filter(|k| !used.contains(*k)) {
+            eprintln!(
+                "{} '{}' is not used by template '{}'",
+                "⚠".yellow(),
+                key,
+                name
+            );
+        }
+    }
+
+    if dry_run {
+        for (path, content) in generator.render(name, &variables)? {
+            println!("{} {}", "→".blue(), path.bold());
+            print!("{}", content);
+            if !content.ends_with('\n') {
+                println!();
+            }
+            println!();
+        }
+        return Ok(());
+    }
+
+    let result = generator.generate_with(name, output, &variables, force)?;
+    for path in &result.files_created {
+        println!("{} {}", "✓".green(), path);
+    }
+    for path in &result.files_skipped {
+        println!(
+            "{} {} {}",
+            "·".dimmed(),
+            path,
+            "(exists, use --force)".dimmed()
+        );
+    }
+
+    Ok(())
+}
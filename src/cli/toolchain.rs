@@ -0,0 +1,160 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Toolchain command - install and list pinned tool versions
+
+use colored::Colorize;
+use miette::Result;
+use std::path::Path;
+
+use super::ToolchainAction;
+use crate::pipeline::Pipeline;
+use crate::toolchain::{self, ToolRequest, Toolchain};
+
+/// Run the toolchain command
+pub async fn run(action: ToolchainAction, verbose: bool) -> Result<()> {
+    match action {
+        ToolchainAction::Install { tools, pipeline } => {
+            run_install(tools, &pipeline, verbose).await
+        }
+        ToolchainAction::List { pipeline } => run_list(&pipeline),
+    }
+}
+
+fn load_pipeline(path: &Path) -> Result<Option<Pipeline>> {
+    if path.exists() {
+        Ok(Some(Pipeline::from_file(path)?))
+    } else {
+        Ok(None)
+    }
+}
+
+async fn run_install(tools: Vec<ToolRequest>, pipeline_path: &Path, verbose: bool) -> Result<()> {
+    let pipeline = load_pipeline(pipeline_path)?;
+    let pins = pipeline.map(|p| p.toolchain).unwrap_or_default();
+
+    // Without arguments, install everything the pipeline pins
+    let tools = if tools.is_empty() {
+        let mut pinned: Vec<ToolRequest> = pins
+            .iter()
+            .map(|(tool, pin)| ToolRequest {
+                tool: tool.clone(),
+                version: Some(pin.version.clone()),
+            })
+            .collect();
+        pinned.sort_by(|a, b| a.tool.cmp(&b.tool));
+        pinned
+    } else {
+        tools
+    };
+
+    if tools.is_empty() {
+        return Err(miette::miette!(
+            "Nothing to install\n\n\
+             Pass tools like 'cue@0.9', or pin versions under 'toolchain:' in {}",
+            pipeline_path.display()
+        ));
+    }
+
+    let toolchain = Toolchain::open()?;
+    let platform = toolchain::platform();
+
+    for request in tools {
+        let pin = pins.get(&request.tool);
+        let version = match (request.version, pin) {
+            (Some(version), _) => version,
+            (None, Some(pin)) => pin.version.clone(),
+            (None, None) => {
+                return Err(miette::miette!(
+                    "No version given for '{}' and none pinned in {}",
+                    request.tool,
+                    pipeline_path.display()
+                ));
+            }
+        };
+
+        if let Some(pin) = pin {
+            if pin.version != version {
+                println!(
+                    "{} {} is pinned to {} in {}",
+                    "⚠".yellow(),
+                    request.tool,
+                    pin.version,
+                    pipeline_path.display()
+                );
+            }
+        }
+
+        let expected = pin
+            .filter(|p| p.version == version)
+            .and_then(|p| p.checksums.get(&platform))
+            .map(String::as_str);
+
+        println!("{} Installing {}@{}...", "→".blue(), request.tool, version);
+        let installed = toolchain.install(&request.tool, &version, expected).await?;
+
+        println!(
+            "  {} {} {}",
+            "✓".green(),
+            installed.tool.bold(),
+            installed.version
+        );
+        if verbose {
+            println!("    {}", installed.path.display().to_string().dimmed());
+        }
+
+        if expected.is_none() {
+            if !installed.verified {
+                println!(
+                    "    {} No upstream checksum to verify against",
+                    "⚠".yellow()
+                );
+            }
+            println!(
+                "    Pin it in .conflow.yaml: toolchain.{}.checksums.{}: {}",
+                installed.tool,
+                platform,
+                installed.sha256.cyan()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn run_list(pipeline_path: &Path) -> Result<()> {
+    let toolchain = Toolchain::open()?;
+    let pins = load_pipeline(pipeline_path)?
+        .map(|p| p.toolchain)
+        .unwrap_or_default();
+
+    println!("{}", "Toolchain".bold());
+    println!("{}", "═".repeat(50));
+    println!(
+        "Directory: {}",
+        toolchain.root().display().to_string().cyan()
+    );
+    println!();
+
+    for tool in toolchain::SUPPORTED_TOOLS {
+        let versions = toolchain.versions(tool);
+        let pin = pins.get(*tool);
+
+        let status = match pin {
+            Some(pin) if toolchain.installed(tool, &pin.version).is_some() => {
+                format!("{} pinned {}", "✓".green(), pin.version)
+            }
+            Some(pin) => format!("{} pinned {} (not installed)", "✗".red(), pin.version),
+            None => "not pinned".dimmed().to_string(),
+        };
+        println!("  {} {}", tool.bold(), status);
+
+        if versions.is_empty() {
+            println!("    {}", "no versions installed".dimmed());
+        } else {
+            println!("    installed: {}", versions.join(", "));
+        }
+    }
+
+    Ok(())
+}
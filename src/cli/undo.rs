@@ -0,0 +1,34 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Undo command - put back the files an audited action changed
+
+use colored::Colorize;
+use miette::Result;
+
+use crate::audit::{self, Change};
+use crate::ConflowError;
+
+/// Run the undo command; without an id, the newest action is undone
+pub async fn run(id: Option<u64>, force: bool) -> Result<()> {
+    let working_dir = std::env::current_dir().map_err(|e| ConflowError::Io {
+        message: e.to_string(),
+    })?;
+    let undone = audit::undo(&working_dir, id, force)?;
+
+    for (path, change) in &undone.files {
+        let verb = match change {
+            Change::Created => "restored".green(),
+            Change::Modified => "reverted".yellow(),
+            Change::Deleted => "removed".red(),
+        };
+        println!("  {} {}", verb, path.display());
+    }
+    println!(
+        "{} Undid action #{} ({})",
+        "✓".green(),
+        undone.id,
+        undone.action
+    );
+    Ok(())
+}
@@ -0,0 +1,163 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Unused command - find config keys and schema fields nothing uses
+
+use colored::Colorize;
+use miette::Result;
+use std::path::{Path, PathBuf};
+
+use super::OutputFormat;
+use crate::analyzer::{find_unused, pipeline_bindings, Schema, SchemaBinding, UnusedReport};
+use crate::pipeline::Pipeline;
+use crate::ConflowError;
+
+/// Run the unused command
+pub async fn run(
+    files: Vec<PathBuf>,
+    schemas: Vec<PathBuf>,
+    pipeline_path: PathBuf,
+    format: OutputFormat,
+    verbose: bool,
+) -> Result<()> {
+    let cwd = std::env::current_dir().map_err(|e| ConflowError::Io {
+        message: e.to_string(),
+    })?;
+
+    let bindings = if schemas.is_empty() {
+        if !pipeline_path.exists() {
+            return Err(ConflowError::PipelineNotFound {
+                path: pipeline_path,
+            }
+            .into());
+        }
+        let pipeline = Pipeline::from_file(&pipeline_path)?;
+
+        let mut bindings = pipeline_bindings(&pipeline, &cwd)?;
+        if !files.is_empty() {
+            let wanted: Vec<PathBuf> = files.iter().map(|f| canonical(f)).collect();
+            for binding in &mut bindings {
+                binding.configs.retain(|c| wanted.contains(&canonical(c)));
+            }
+        }
+        if bindings.is_empty() {
+            println!(
+                "{} No CUE stage in {} has schemas; pass them with --schema",
+                "⚠".yellow(),
+                pipeline_path.display()
+            );
+            return Ok(());
+        }
+        bindings
+    } else {
+        let schemas = schemas
+            .iter()
+            .map(|s| Schema::load(s, None))
+            .collect::<Result<_, _>>()?;
+        vec![SchemaBinding {
+            schemas,
+            configs: files,
+        }]
+    };
+
+    let mut report = find_unused(&bindings);
+    for key in &mut report.unused_keys {
+        key.location.file = relative(&key.location.file, &cwd);
+    }
+    for field in &mut report.unset_fields {
+        field.schema = relative(&field.schema, &cwd);
+    }
+
+    match format {
+        OutputFormat::Text => print_text(&report, verbose),
+        OutputFormat::Json => print_json(&report)?,
+    }
+    Ok(())
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn relative(path: &Path, base: &Path) -> PathBuf {
+    path.strip_prefix(base).unwrap_or(path).to_path_buf()
+}
+
+fn print_text(report: &UnusedReport, verbose: bool) {
+    if report.files == 0 {
+        println!("{} No JSON or YAML config files to check", "⚠".yellow());
+        return;
+    }
+    if report.is_empty() {
+        println!(
+            "{} Every key is declared and every field is set ({} file(s))",
+            "✓".green(),
+            report.files
+        );
+        return;
+    }
+
+    if !report.unused_keys.is_empty() {
+        println!("{}", "Keys no schema declares".bold());
+        for key in &report.unused_keys {
+            match &key.suggestion {
+                Some(suggestion) => println!(
+                    "  {} {} (did you mean `{}`?)",
+                    "⚠".yellow(),
+                    key.location,
+                    suggestion.cyan()
+                ),
+                None => println!("  {} {}", "⚠".yellow(), key.location),
+            }
+        }
+        println!();
+    }
+
+    if !report.unset_fields.is_empty() {
+        println!("{}", "Schema fields no config sets".bold());
+        for field in &report.unset_fields {
+            println!(
+                "  {} {}: {}",
+                "→".blue(),
+                field.schema.display(),
+                field.field
+            );
+        }
+        println!();
+    }
+
+    println!(
+        "{} unused key(s), {} unset field(s) across {} file(s)",
+        report.unused_keys.len(),
+        report.unset_fields.len(),
+        report.files
+    );
+    if verbose && !report.unset_fields.is_empty() {
+        println!(
+            "{}",
+            "Unset fields may still be needed by configs outside the checked files.".dimmed()
+        );
+    }
+}
+
+fn print_json(report: &UnusedReport) -> Result<()> {
+    let json = serde_json::json!({
+        "files": report.files,
+        "unused_keys": report.unused_keys.iter().map(|k| serde_json::json!({
+            "file": k.location.file.display().to_string(),
+            "document": k.location.document.map(|d| d + 1),
+            "key": crate::analyzer::dotted(&k.location.path),
+            "suggestion": k.suggestion,
+        })).collect::<Vec<_>>(),
+        "unset_fields": report.unset_fields.iter().map(|f| serde_json::json!({
+            "schema": f.schema.display().to_string(),
+            "field": f.field,
+        })).collect::<Vec<_>>(),
+    });
+
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&json).map_err(|e| miette::miette!("{}", e))?
+    );
+    Ok(())
+}
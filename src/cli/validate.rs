@@ -7,20 +7,22 @@ use colored::Colorize;
 use miette::Result;
 use std::path::PathBuf;
 
-use crate::pipeline::{Pipeline, PipelineValidator};
+use crate::diagnostics::annotations;
+use crate::errors::{ConflowError, FailOn};
+use crate::executors::parsers::Severity;
+use crate::pipeline::{check_refs, Pipeline, PipelineValidator};
 
 /// Run the validate command
-pub async fn run(pipeline_path: PathBuf, verbose: bool) -> Result<()> {
+pub async fn run(pipeline_path: PathBuf, fail_on: FailOn, verbose: bool) -> Result<()> {
     println!("{}", "Validating pipeline...".bold());
     println!();
 
     // Check pipeline exists
     if !pipeline_path.exists() {
-        return Err(miette::miette!(
-            "Pipeline file not found: {}\n\n\
-             Run 'conflow init' to create a new project.",
-            pipeline_path.display()
-        ));
+        return Err(ConflowError::PipelineNotFound {
+            path: pipeline_path,
+        }
+        .into());
     }
 
     // Load pipeline
@@ -29,7 +31,7 @@ pub async fn run(pipeline_path: PathBuf, verbose: bool) -> Result<()> {
         Err(e) => {
             eprintln!("  {} Failed to parse pipeline", "✗".red());
             eprintln!();
-            return Err(miette::miette!("Parse error: {}", e));
+            return Err(e.into());
         }
     };
 
@@ -39,9 +41,19 @@ pub async fn run(pipeline_path: PathBuf, verbose: bool) -> Result<()> {
     let validation = PipelineValidator::validate(&pipeline)?;
 
     // Check for file existence
-    let cwd = std::env::current_dir().map_err(|e| miette::miette!("Failed to get current directory: {}", e))?;
+    let cwd = std::env::current_dir()
+        .map_err(|e| miette::miette!("Failed to get current directory: {}", e))?;
     let missing_files = PipelineValidator::validate_files(&pipeline, &cwd)?;
 
+    // Check cross-file references
+    let broken_refs = match check_refs(&pipeline, &cwd) {
+        Ok(broken) => broken,
+        Err(e) => {
+            eprintln!("  {} Failed to check references: {}", "✗".red(), e);
+            return Err(e.into());
+        }
+    };
+
     // Report results
     let mut has_issues = false;
 
@@ -51,6 +63,7 @@ pub async fn run(pipeline_path: PathBuf, verbose: bool) -> Result<()> {
         println!("{}:", "Errors".red().bold());
         for error in &validation.errors {
             println!("  {} {}", "✗".red(), error);
+            annotations::emit_message(Severity::Error, &pipeline_path, error);
         }
     }
 
@@ -60,6 +73,22 @@ pub async fn run(pipeline_path: PathBuf, verbose: bool) -> Result<()> {
         println!("{}:", "Missing files".yellow().bold());
         for missing in &missing_files {
             println!("  {} {}", "⚠".yellow(), missing);
+            annotations::emit_message(Severity::Warning, &pipeline_path, missing);
+        }
+    }
+
+    if !broken_refs.is_empty() {
+        has_issues = true;
+        println!();
+        println!("{}:", "Broken references".red().bold());
+        for broken in &broken_refs {
+            let mut broken = broken.clone();
+            if let Ok(relative) = broken.location.file.strip_prefix(&cwd) {
+                broken.location.file = relative.to_path_buf();
+            }
+            println!("  {} {}", "✗".red(), broken);
+            let message = format!("'{}' is not defined ({})", broken.value, broken.rule);
+            annotations::emit_message(Severity::Error, &broken.location.file, &message);
         }
     }
 
@@ -68,6 +97,7 @@ pub async fn run(pipeline_path: PathBuf, verbose: bool) -> Result<()> {
         println!("{}:", "Warnings".yellow().bold());
         for warning in &validation.warnings {
             println!("  {} {}", "⚠".yellow(), warning);
+            annotations::emit_message(Severity::Warning, &pipeline_path, warning);
         }
     }
 
@@ -82,21 +112,30 @@ pub async fn run(pipeline_path: PathBuf, verbose: bool) -> Result<()> {
             } else {
                 format!(" [depends: {}]", stage.depends_on.join(", "))
             };
-            println!("    - {} ({}){}", stage.name, stage.tool_name(), deps.dimmed());
+            println!(
+                "    - {} ({}){}",
+                stage.name,
+                stage.tool_name(),
+                deps.dimmed()
+            );
         }
     }
 
     println!();
 
     if has_issues {
-        if validation.is_valid() && missing_files.is_empty() {
-            println!(
-                "{}",
-                "Pipeline is valid but has warnings.".yellow().bold()
-            );
+        if validation.is_valid() && missing_files.is_empty() && broken_refs.is_empty() {
+            println!("{}", "Pipeline is valid but has warnings.".yellow().bold());
+            if fail_on.fails_on_warnings() {
+                let count = validation.warnings.len();
+                return Err(ConflowError::WarningsFound { count }.into());
+            }
             Ok(())
         } else {
-            Err(miette::miette!("Pipeline validation failed"))
+            Err(ConflowError::CheckFailed {
+                message: "Pipeline validation failed".into(),
+            }
+            .into())
         }
     } else {
         println!("{}", "Pipeline is valid!".green().bold());
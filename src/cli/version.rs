@@ -0,0 +1,94 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Version command - check and bump the version across config files
+
+use colored::Colorize;
+use miette::Result;
+
+use super::{OutputFormat, VersionAction};
+use crate::errors::ConflowError;
+use crate::pipeline::{
+    bump_versions, check_versions, read_versions, version_locations, VersionLocation,
+};
+
+/// Run the version command
+pub async fn run(action: VersionAction) -> Result<()> {
+    let working_dir = std::env::current_dir().map_err(|e| ConflowError::Io {
+        message: e.to_string(),
+    })?;
+    let locations = version_locations(&working_dir);
+    if locations.is_empty() {
+        return Err(miette::miette!(
+            help = "map files to JSONPath locations under versions: in .conflow.yaml, \
+                    e.g. 'Cargo.toml: $.package.version'",
+            "No version locations: no versions: section and no Cargo.toml, \
+             package.json, pyproject.toml or Chart.yaml version"
+        ));
+    }
+
+    match action {
+        VersionAction::Check { format } => {
+            let found = read_versions(&working_dir, &locations)?;
+            let checked = check_versions(&found);
+            match format {
+                OutputFormat::Text => print_locations(&found, checked.as_ref().ok()),
+                OutputFormat::Json => {
+                    let json = serde_json::json!({
+                        "version": checked.as_ref().ok().cloned().flatten(),
+                        "consistent": checked.is_ok(),
+                        "locations": found,
+                    });
+                    let json = serde_json::to_string_pretty(&json).map_err(ConflowError::from)?;
+                    println!("{}", json);
+                }
+            }
+            checked?;
+        }
+        VersionAction::Bump { version } => {
+            if version.is_empty() || version.contains(|c: char| c.is_whitespace() || c == '"') {
+                return Err(miette::miette!("Invalid version '{}'", version));
+            }
+            let written = bump_versions(&working_dir, &locations, &version)?;
+            if written.is_empty() {
+                println!("{} Every location is already at {}", "✓".green(), version);
+                return Ok(());
+            }
+            for file in &written {
+                println!("  {} {}", "✓".green(), file.display());
+            }
+            println!(
+                "Set version {} in {} file(s)",
+                version.cyan(),
+                written.len()
+            );
+        }
+    }
+    Ok(())
+}
+
+fn print_locations(found: &[VersionLocation], version: Option<&Option<String>>) {
+    let file_width = found
+        .iter()
+        .map(|l| l.file.display().to_string().len())
+        .max()
+        .unwrap_or(0);
+    let path_width = found.iter().map(|l| l.path.len()).max().unwrap_or(0);
+    for location in found {
+        println!(
+            "  {:<file_width$}  {:<path_width$}  {}",
+            location.file.display(),
+            location.path.dimmed(),
+            location.version,
+        );
+    }
+    println!();
+    if let Some(Some(version)) = version {
+        println!(
+            "{} {} location(s) at {}",
+            "✓".green(),
+            found.len(),
+            version.cyan()
+        );
+    }
+}
@@ -5,18 +5,31 @@
 
 use colored::Colorize;
 use miette::Result;
-use notify::{RecursiveMode, Watcher};
+use notify::RecursiveMode;
 use notify_debouncer_mini::{new_debouncer, DebouncedEventKind};
-use std::path::PathBuf;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
 use std::sync::mpsc::channel;
+use std::sync::Arc;
 use std::time::Duration;
 
-use crate::cache::FilesystemCache;
-use crate::executors::create_default_executors;
-use crate::pipeline::{ExecutionOptions, Pipeline, PipelineExecutor};
+use crate::cache::{FilesystemCache, StageTimings};
+use crate::executors::create_executors;
+use crate::pipeline::{
+    Approvals, ExecutionOptions, Pipeline, PipelineExecutor, RunLock, DEFAULT_LOCK_TIMEOUT,
+    RUN_LOCK,
+};
+use crate::rsr::ComplianceChecker;
+use crate::telemetry::{metrics, Metrics};
+use crate::utils::ignore::Ignore;
 
 /// Run the watch command
-pub async fn run(pipeline_path: PathBuf, debounce_ms: u64, verbose: bool) -> Result<()> {
+pub async fn run(
+    pipeline_path: PathBuf,
+    debounce_ms: u64,
+    metrics_addr: Option<SocketAddr>,
+    verbose: bool,
+) -> Result<()> {
     // Check pipeline exists
     if !pipeline_path.exists() {
         return Err(miette::miette!(
@@ -27,11 +40,24 @@ pub async fn run(pipeline_path: PathBuf, debounce_ms: u64, verbose: bool) -> Res
     }
 
     println!("{}", "Starting watch mode...".bold());
-    println!(
-        "Watching for changes (debounce: {}ms)",
-        debounce_ms
-    );
+    println!("Watching for changes (debounce: {}ms)", debounce_ms);
     println!("Press {} to exit.", "Ctrl+C".cyan());
+
+    // Start metrics endpoint
+    let metrics = metrics_addr.map(|addr| {
+        let registry = Arc::new(Metrics::new());
+        let server = Arc::clone(&registry);
+        tokio::spawn(async move {
+            if let Err(e) = metrics::serve(server, addr).await {
+                eprintln!("{}: {}", "Metrics server error".red(), e);
+            }
+        });
+        println!(
+            "Serving metrics on {}",
+            format!("http://{}/metrics", addr).cyan()
+        );
+        registry
+    });
     println!();
 
     // Create channel for receiving events
@@ -44,13 +70,14 @@ pub async fn run(pipeline_path: PathBuf, debounce_ms: u64, verbose: bool) -> Res
     // Watch current directory
     debouncer
         .watcher()
-        .watch(std::path::Path::new("."), RecursiveMode::Recursive)
+        .watch(Path::new("."), RecursiveMode::Recursive)
         .map_err(|e| miette::miette!("Failed to start watching: {}", e))?;
 
     // Initial run
-    run_pipeline(&pipeline_path, verbose).await;
+    run_pipeline(&pipeline_path, metrics.as_deref(), verbose).await;
 
-    // Watch for changes
+    // Watch for changes, except to what `.conflowignore` lists
+    let ignore = Ignore::load(Path::new("."));
     loop {
         match rx.recv() {
             Ok(Ok(events)) => {
@@ -58,8 +85,10 @@ pub async fn run(pipeline_path: PathBuf, debounce_ms: u64, verbose: bool) -> Res
                 let relevant: Vec<_> = events
                     .iter()
                     .filter(|e| {
-                        // Skip cache directory
+                        // Skip cache directory, the run lock and ignored paths
                         !e.path.to_string_lossy().contains(".conflow/cache")
+                            && !e.path.ends_with(RUN_LOCK)
+                            && !ignore.is_ignored(&e.path)
                     })
                     .filter(|e| matches!(e.kind, DebouncedEventKind::Any))
                     .collect();
@@ -80,7 +109,7 @@ pub async fn run(pipeline_path: PathBuf, debounce_ms: u64, verbose: bool) -> Res
                     }
 
                     println!();
-                    run_pipeline(&pipeline_path, verbose).await;
+                    run_pipeline(&pipeline_path, metrics.as_deref(), verbose).await;
                 }
             }
             Ok(Err(e)) => {
@@ -97,21 +126,21 @@ pub async fn run(pipeline_path: PathBuf, debounce_ms: u64, verbose: bool) -> Res
     Ok(())
 }
 
-async fn run_pipeline(pipeline_path: &PathBuf, verbose: bool) {
+async fn run_pipeline(pipeline_path: &Path, metrics: Option<&Metrics>, verbose: bool) {
     let start = std::time::Instant::now();
 
     // Load pipeline
     let pipeline = match Pipeline::from_file(pipeline_path) {
         Ok(p) => p,
         Err(e) => {
-            eprintln!("{}: {}", "Failed to load pipeline".red(), e);
+            eprintln!("{:?}", miette::Report::new(e));
             return;
         }
     };
 
     // Create executor
     let mut executor = PipelineExecutor::new();
-    for (name, exec) in create_default_executors() {
+    for (name, exec) in create_executors(&pipeline) {
         executor.register_executor(&name, exec);
     }
 
@@ -124,13 +153,37 @@ async fn run_pipeline(pipeline_path: &PathBuf, verbose: bool) {
         }
     };
 
-    if pipeline.cache.enabled {
-        if let Ok(cache) = FilesystemCache::new(
-            working_dir.join(&pipeline.cache.directory),
-            working_dir.clone(),
-        ) {
-            executor = executor.with_cache(Box::new(cache));
+    // Hold off while a manual run or hook is working on the same project
+    let dir = working_dir.clone();
+    let lock = tokio::task::spawn_blocking(move || {
+        RunLock::acquire(&dir, DEFAULT_LOCK_TIMEOUT, |_| {
+            println!(
+                "{} Waiting for another conflow run to finish...",
+                "→".blue()
+            );
+        })
+    })
+    .await;
+    let _lock = match lock {
+        Ok(Ok(lock)) => lock,
+        Ok(Err(e)) => {
+            eprintln!("{:?}", miette::Report::new(e));
+            return;
         }
+        Err(e) => {
+            eprintln!("{}: {}", "Failed to wait for the run lock".red(), e);
+            return;
+        }
+    };
+
+    if let Ok(cache_dir) = pipeline.cache.path(&working_dir) {
+        if pipeline.cache.enabled {
+            if let Ok(cache) = FilesystemCache::new(cache_dir.clone(), working_dir.clone()) {
+                let cache = cache.with_limits(pipeline.cache.limits());
+                executor = executor.with_cache(Box::new(cache));
+            }
+        }
+        executor = executor.with_timings(StageTimings::load(&cache_dir));
     }
 
     let options = ExecutionOptions {
@@ -138,11 +191,22 @@ async fn run_pipeline(pipeline_path: &PathBuf, verbose: bool) {
         dry_run: false,
         stages: vec![],
         verbose,
+        profile: false,
+        approvals: Approvals::default().with_env(),
     };
 
     // Execute
     match executor.execute(&pipeline, &working_dir, &options).await {
         Ok(result) => {
+            if let Some(metrics) = metrics {
+                metrics.record_pipeline(&result);
+                if let Ok(report) = ComplianceChecker::for_project(&working_dir)
+                    .and_then(|checker| checker.check(&working_dir))
+                {
+                    metrics.record_compliance(&report);
+                }
+            }
+
             let elapsed = start.elapsed();
             if result.success {
                 println!(
@@ -0,0 +1,185 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Why command - trace where a merged value comes from
+
+use colored::Colorize;
+use miette::Result;
+use std::path::{Path, PathBuf};
+
+use super::{MergeMode, OutputFormat};
+use crate::analyzer::{stage_layers, trace, Layer, MergeStrategy, Role, Trace};
+use crate::pipeline::Pipeline;
+use crate::ConflowError;
+
+/// Run the why command
+pub async fn run(
+    path: String,
+    stage: Option<String>,
+    files: Vec<PathBuf>,
+    merge: Option<MergeMode>,
+    pipeline_path: PathBuf,
+    format: OutputFormat,
+    verbose: bool,
+) -> Result<()> {
+    let cwd = std::env::current_dir().map_err(|e| ConflowError::Io {
+        message: e.to_string(),
+    })?;
+
+    let (source, files, strategy) = if files.is_empty() {
+        let (name, files, strategy) = pipeline_layers(&pipeline_path, stage, &cwd)?;
+        (format!("stage '{}'", name), files, strategy)
+    } else {
+        let strategy = match merge {
+            None | Some(MergeMode::Override) => MergeStrategy::Override,
+            Some(MergeMode::Unify) => MergeStrategy::Unify,
+        };
+        (format!("{} file(s)", files.len()), files, strategy)
+    };
+
+    let layers = files
+        .iter()
+        .map(|f| Layer::load(f))
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    let mut trace = trace(&layers, &path, strategy);
+    for origin in &mut trace.origins {
+        if let Ok(relative) = origin.file.strip_prefix(&cwd) {
+            origin.file = relative.to_path_buf();
+        }
+    }
+
+    if trace.origins.is_empty() {
+        return Err(miette::miette!(
+            help = format!(
+                "Checked {} ({}): {}",
+                source,
+                strategy,
+                files
+                    .iter()
+                    .map(|f| f.strip_prefix(&cwd).unwrap_or(f).display().to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            "'{}' is not set by any file",
+            path
+        ));
+    }
+
+    match format {
+        OutputFormat::Text => print_text(&trace, &source, strategy, verbose),
+        OutputFormat::Json => print_json(&trace, strategy)?,
+    }
+    Ok(())
+}
+
+/// Files merged by the chosen stage, or the only stage that merges files
+fn pipeline_layers(
+    pipeline_path: &Path,
+    stage: Option<String>,
+    cwd: &Path,
+) -> Result<(String, Vec<PathBuf>, MergeStrategy)> {
+    if !pipeline_path.exists() {
+        return Err(ConflowError::PipelineNotFound {
+            path: pipeline_path.to_path_buf(),
+        }
+        .into());
+    }
+    let pipeline = Pipeline::from_file(pipeline_path)?;
+
+    let candidates: Vec<_> = pipeline
+        .stages
+        .iter()
+        .filter_map(|s| stage_layers(s, cwd).map(|(files, strategy)| (s, files, strategy)))
+        .collect();
+
+    let chosen = match stage {
+        Some(name) => {
+            if pipeline.get_stage(&name).is_none() {
+                return Err(ConflowError::StageNotFound { stage: name }.into());
+            }
+            candidates
+                .into_iter()
+                .find(|(s, ..)| s.name == name)
+                .ok_or_else(|| {
+                    miette::miette!(
+                        help = "Only CUE stages and Helm stages with values files merge files",
+                        "Stage '{}' doesn't merge files",
+                        name
+                    )
+                })?
+        }
+        None if candidates.len() == 1 => candidates.into_iter().next().unwrap(),
+        None if candidates.is_empty() => {
+            return Err(miette::miette!(
+                help = "Pass the files to trace with --files",
+                "No stage in {} merges files",
+                pipeline_path.display()
+            ));
+        }
+        None => {
+            let names: Vec<&str> = candidates.iter().map(|(s, ..)| s.name.as_str()).collect();
+            return Err(miette::miette!(
+                help = format!("Choose one with --stage: {}", names.join(", ")),
+                "Several stages merge files"
+            ));
+        }
+    };
+
+    let (stage, files, strategy) = chosen;
+    Ok((stage.name.clone(), files, strategy))
+}
+
+fn print_text(trace: &Trace, source: &str, strategy: MergeStrategy, verbose: bool) {
+    let value = match &trace.value {
+        Some(value) => serde_json::to_string(value).unwrap_or_default(),
+        None => "(conflict)".red().to_string(),
+    };
+    println!("{} = {}", trace.path.bold(), value);
+    println!("{}", format!("  from {} ({})", source, strategy).dimmed());
+    println!();
+
+    for origin in &trace.origins {
+        let icon = match origin.role {
+            Role::Set | Role::Default => "✓".green(),
+            Role::Merged => "→".blue(),
+            Role::Conflicting => "✗".red(),
+            Role::Overridden | Role::Constraint => "·".dimmed(),
+        };
+        let location = match origin.line {
+            Some(line) => format!("{}:{}", origin.file.display(), line),
+            None => origin.file.display().to_string(),
+        };
+        let text = match origin.text.lines().count() {
+            0 | 1 => origin.text.clone(),
+            _ if verbose => origin.text.clone(),
+            _ => format!("{} …", origin.text.lines().next().unwrap_or_default()),
+        };
+        println!(
+            "  {} {}  {}  {}",
+            icon,
+            location.cyan(),
+            text,
+            format!("({})", origin.role).dimmed()
+        );
+    }
+}
+
+fn print_json(trace: &Trace, strategy: MergeStrategy) -> Result<()> {
+    let json = serde_json::json!({
+        "path": trace.path,
+        "value": trace.value,
+        "strategy": strategy.to_string(),
+        "origins": trace.origins.iter().map(|o| serde_json::json!({
+            "file": o.file.display().to_string(),
+            "line": o.line,
+            "role": o.role.to_string(),
+            "text": o.text,
+        })).collect::<Vec<_>>(),
+    });
+
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&json).map_err(|e| miette::miette!("{}", e))?
+    );
+    Ok(())
+}
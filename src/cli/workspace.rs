@@ -0,0 +1,247 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Workspace command - run pipelines and check compliance across projects
+
+use colored::Colorize;
+use miette::Result;
+use std::path::{Path, PathBuf};
+
+use super::{OutputFormat, WorkspaceAction};
+use crate::workspace::{ComplianceMatrix, MatrixCell, Member, Workspace};
+use crate::ConflowError;
+
+/// Run the workspace command
+pub async fn run(action: WorkspaceAction, verbose: bool) -> Result<()> {
+    match action {
+        WorkspaceAction::Run {
+            file,
+            project,
+            update,
+            no_cache,
+            fail_fast,
+        } => {
+            let members = load(&file, &project, update)?;
+            run_projects(&members, no_cache, fail_fast, verbose).await
+        }
+        WorkspaceAction::Report {
+            file,
+            project,
+            update,
+            format,
+            output,
+        } => {
+            let members = load(&file, &project, update)?;
+            report(&members, format, output)
+        }
+    }
+}
+
+/// Load the workspace and check out the selected projects
+fn load(file: &Path, only: &[String], update: bool) -> Result<Vec<Member>> {
+    if !file.exists() {
+        return Err(miette::miette!(
+            help = "create conflow-workspace.yaml listing the projects, or pass --file",
+            "Workspace file not found: {}",
+            file.display()
+        ));
+    }
+    let mut workspace = Workspace::load(file)?;
+
+    let unknown: Vec<&str> = only
+        .iter()
+        .filter(|name| !workspace.projects.iter().any(|p| &p.name() == *name))
+        .map(String::as_str)
+        .collect();
+    if !unknown.is_empty() {
+        return Err(miette::miette!(
+            "Unknown project(s) in {}: {}",
+            file.display(),
+            unknown.join(", ")
+        ));
+    }
+    workspace
+        .projects
+        .retain(|p| only.is_empty() || only.contains(&p.name()));
+
+    let root = file
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    Ok(workspace.members(root, update)?)
+}
+
+/// Run `conflow run` in each project, one after another
+async fn run_projects(
+    members: &[Member],
+    no_cache: bool,
+    fail_fast: bool,
+    verbose: bool,
+) -> Result<()> {
+    let exe = std::env::current_exe().map_err(|e| ConflowError::Io {
+        message: e.to_string(),
+    })?;
+
+    // None for projects without a pipeline
+    let mut outcomes: Vec<(&Member, Option<bool>)> = Vec::new();
+    for member in members {
+        println!(
+            "{} {} {}",
+            "━━".blue(),
+            member.name.bold(),
+            member.root.display().to_string().dimmed()
+        );
+        if !member.root.join(&member.pipeline).exists() {
+            println!(
+                "{} No {}, skipped\n",
+                "○".dimmed(),
+                member.pipeline.display()
+            );
+            outcomes.push((member, None));
+            continue;
+        }
+
+        let mut command = tokio::process::Command::new(&exe);
+        command
+            .current_dir(&member.root)
+            .envs(&member.env)
+            .arg("run")
+            .arg("--pipeline")
+            .arg(&member.pipeline);
+        for stage in &member.stages {
+            command.arg("--stage").arg(stage);
+        }
+        if no_cache {
+            command.arg("--no-cache");
+        }
+        if verbose {
+            command.arg("--verbose");
+        }
+        let status = command.status().await.map_err(|e| ConflowError::Io {
+            message: format!("Failed to run {}: {}", member.name, e),
+        })?;
+        println!();
+
+        outcomes.push((member, Some(status.success())));
+        if fail_fast && !status.success() {
+            break;
+        }
+    }
+
+    println!("{}", "Workspace summary:".bold());
+    for (member, outcome) in &outcomes {
+        match outcome {
+            Some(true) => println!("  {} {}", "✓".green(), member.name),
+            Some(false) => println!("  {} {}", "✗".red(), member.name),
+            None => println!(
+                "  {} {} {}",
+                "○".dimmed(),
+                member.name,
+                "(no pipeline)".dimmed()
+            ),
+        }
+    }
+    let skipped = members.len() - outcomes.len();
+    if skipped > 0 {
+        println!("  {} project(s) not run after a failure", skipped);
+    }
+
+    let failed = outcomes.iter().filter(|(_, o)| *o == Some(false)).count();
+    if failed > 0 {
+        return Err(ConflowError::CheckFailed {
+            message: format!("{} of {} project(s) failed", failed, members.len()),
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// Check every project's compliance and show the matrix
+fn report(members: &[Member], format: OutputFormat, output: Option<PathBuf>) -> Result<()> {
+    let matrix = ComplianceMatrix::check(members)?;
+
+    match output {
+        Some(path) => {
+            let json = crate::report::to_json(&matrix)?;
+            std::fs::write(&path, json + "\n").map_err(|e| ConflowError::FileWriteError {
+                path: path.clone(),
+                error: e.to_string(),
+            })?;
+            println!(
+                "{} Compliance matrix of {} project(s) written to {}",
+                "✓".green(),
+                matrix.projects.len(),
+                path.display().to_string().cyan()
+            );
+        }
+        None => match format {
+            OutputFormat::Text => print_matrix(&matrix),
+            OutputFormat::Json => println!("{}", crate::report::to_json(&matrix)?),
+        },
+    }
+    Ok(())
+}
+
+fn print_matrix(matrix: &ComplianceMatrix) {
+    if matrix.projects.is_empty() {
+        println!("{} No projects", "⚠".yellow());
+        return;
+    }
+
+    let first = matrix
+        .requirements
+        .iter()
+        .map(|row| row.requirement_id.len())
+        .max()
+        .unwrap_or_default()
+        .max("Requirement".len());
+    let widths: Vec<usize> = matrix
+        .projects
+        .iter()
+        .map(|p| p.name.chars().count().max(4))
+        .collect();
+
+    let mut header = format!("{:first$}", "Requirement");
+    for (project, width) in matrix.projects.iter().zip(&widths) {
+        header.push_str(&format!("  {:^width$}", project.name));
+    }
+    println!("{}", header.bold());
+
+    for row in &matrix.requirements {
+        let mut line = format!("{:first$}", row.requirement_id);
+        for (cell, width) in row.cells.iter().zip(&widths) {
+            let symbol = format!("{:^width$}", cell.symbol());
+            let symbol = match cell {
+                MatrixCell::Met => symbol.green(),
+                MatrixCell::Unmet => symbol.red(),
+                MatrixCell::Waived => symbol.yellow(),
+                MatrixCell::NotChecked => symbol.dimmed(),
+            };
+            line.push_str(&format!("  {}", symbol));
+        }
+        println!("{}", line);
+    }
+
+    let mut score = format!("{:first$}", "Score");
+    for (project, width) in matrix.projects.iter().zip(&widths) {
+        let percent = format!("{:.0}%", project.score * 100.0);
+        score.push_str(&format!("  {:^width$}", percent));
+    }
+    println!("{}", score.bold());
+    println!();
+    for project in &matrix.projects {
+        println!(
+            "  {} {}: {}",
+            project.level.emoji(),
+            project.name.bold(),
+            project.level.description()
+        );
+    }
+    println!(
+        "\n{} met, {} unmet, {} waived, {} not checked",
+        MatrixCell::Met.symbol().green(),
+        MatrixCell::Unmet.symbol().red(),
+        MatrixCell::Waived.symbol().yellow(),
+        MatrixCell::NotChecked.symbol().dimmed()
+    );
+}
@@ -0,0 +1,207 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! YAML command - split, join and check multi-document streams
+
+use colored::Colorize;
+use miette::Result;
+use std::path::{Path, PathBuf};
+
+use super::YamlAction;
+use crate::analyzer::format_bytes;
+use crate::audit;
+use crate::utils::{stream, yaml};
+use crate::ConflowError;
+
+/// Run the yaml command
+pub async fn run(action: YamlAction, verbose: bool) -> Result<()> {
+    match action {
+        YamlAction::Split { file, output } => split(Path::new("."), &file, output, verbose),
+        YamlAction::Join { files, output } => join(Path::new("."), &files, output, verbose),
+        YamlAction::Check { files } => check(&files, verbose),
+    }
+}
+
+fn read(path: &Path) -> Result<String> {
+    std::fs::read_to_string(path).map_err(|e| {
+        ConflowError::FileReadError {
+            path: path.to_path_buf(),
+            error: e.to_string(),
+        }
+        .into()
+    })
+}
+
+fn write(root: &Path, path: &Path, content: &str, action: &str) -> Result<()> {
+    audit::write(root, path, content, action).map_err(|e| {
+        ConflowError::FileWriteError {
+            path: path.to_path_buf(),
+            error: e.to_string(),
+        }
+        .into()
+    })
+}
+
+fn split(root: &Path, file: &Path, output: Option<PathBuf>, verbose: bool) -> Result<()> {
+    let content = read(file)?;
+
+    // Refuse to split a stream with broken documents
+    yaml::parse_documents::<serde_yaml::Value>(&content, &file.display().to_string())?;
+
+    let dir = output
+        .or_else(|| file.parent().map(Path::to_path_buf))
+        .unwrap_or_default();
+    std::fs::create_dir_all(&dir).map_err(|e| ConflowError::FileWriteError {
+        path: dir.clone(),
+        error: e.to_string(),
+    })?;
+
+    let docs = yaml::documents(&content);
+    for (doc, path) in docs.iter().zip(split_paths(file, &dir, docs.len())) {
+        write(root, &path, &yaml::join(&[doc.content]), "yaml split")?;
+        if verbose {
+            println!(
+                "{} document {} (line {}) → {}",
+                "→".blue(),
+                doc.index + 1,
+                doc.line,
+                path.display()
+            );
+        }
+    }
+
+    println!(
+        "{} Split {} into {} document(s) in {}",
+        "✓".green(),
+        file.display().to_string().cyan(),
+        docs.len(),
+        dir.display()
+    );
+    Ok(())
+}
+
+/// `<stem>-<n>.<ext>` for each document, numbered from 1
+fn split_paths(file: &Path, dir: &Path, count: usize) -> Vec<PathBuf> {
+    let stem = file
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "document".into());
+    let ext = file
+        .extension()
+        .map(|e| e.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "yaml".into());
+    let width = count.to_string().len();
+
+    (1..=count)
+        .map(|n| dir.join(format!("{}-{:0width$}.{}", stem, n, ext, width = width)))
+        .collect()
+}
+
+fn join(root: &Path, files: &[PathBuf], output: Option<PathBuf>, verbose: bool) -> Result<()> {
+    let mut documents = Vec::new();
+    for file in files {
+        let content = read(file)?;
+        yaml::parse_documents::<serde_yaml::Value>(&content, &file.display().to_string())?;
+        documents.extend(
+            yaml::documents(&content)
+                .iter()
+                .map(|d| d.content.to_string()),
+        );
+    }
+
+    let stream = yaml::join(&documents);
+    match output {
+        Some(path) => {
+            write(root, &path, &stream, "yaml join")?;
+            if verbose {
+                println!(
+                    "{} Joined {} document(s) into {}",
+                    "✓".green(),
+                    documents.len(),
+                    path.display().to_string().cyan()
+                );
+            }
+        }
+        None => print!("{}", stream),
+    }
+    Ok(())
+}
+
+fn check(files: &[PathBuf], verbose: bool) -> Result<()> {
+    let mut failed = 0;
+    for file in files {
+        match stream::check_file(file) {
+            Ok(summary) => {
+                println!(
+                    "{} {}: {} document(s), {}",
+                    "✓".green(),
+                    file.display().to_string().cyan(),
+                    summary.documents,
+                    format_bytes(summary.bytes)
+                );
+                if verbose && summary.documents > 1 {
+                    println!(
+                        "  {} largest document {}",
+                        "→".blue(),
+                        format_bytes(summary.largest as u64)
+                    );
+                }
+            }
+            Err(e) => {
+                failed += 1;
+                println!("{} {}", "✗".red(), e);
+            }
+        }
+    }
+
+    if failed > 0 {
+        return Err(miette::miette!(
+            "{} of {} file(s) failed to parse",
+            failed,
+            files.len()
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_paths() {
+        let paths = split_paths(Path::new("k8s/all.yaml"), Path::new("out"), 12);
+        assert_eq!(paths[0], PathBuf::from("out/all-01.yaml"));
+        assert_eq!(paths[11], PathBuf::from("out/all-12.yaml"));
+
+        let paths = split_paths(Path::new("manifests"), Path::new(""), 1);
+        assert_eq!(paths, vec![PathBuf::from("manifests-1.yaml")]);
+    }
+
+    #[tokio::test]
+    async fn test_split_then_join_round_trips() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let stream = "kind: Service\n---\nkind: Deployment\nspec:\n  replicas: 2\n";
+        let file = temp.path().join("app.yaml");
+        std::fs::write(&file, stream).unwrap();
+
+        let out = temp.path().join("parts");
+        split(temp.path(), &file, Some(out.clone()), false).unwrap();
+        let parts = vec![out.join("app-1.yaml"), out.join("app-2.yaml")];
+        assert_eq!(
+            read(&parts[1]).unwrap(),
+            "kind: Deployment\nspec:\n  replicas: 2\n"
+        );
+
+        let joined = temp.path().join("joined.yaml");
+        join(temp.path(), &parts, Some(joined.clone()), false).unwrap();
+        assert_eq!(read(&joined).unwrap(), stream);
+
+        let actions: Vec<_> = audit::entries(temp.path())
+            .unwrap()
+            .into_iter()
+            .map(|e| e.action)
+            .collect();
+        assert_eq!(actions, ["yaml split", "yaml split", "yaml join"]);
+    }
+}
@@ -0,0 +1,291 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! CI annotations
+//!
+//! In GitHub Actions and Azure Pipelines every diagnostic is also printed
+//! as a workflow command, so it shows up on the offending line of the pull
+//! request diff. GitLab has no such commands; it annotates merge requests
+//! from a Code Quality report (`conflow run --report-format code-quality`).
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use super::{Diagnostic, Label};
+use crate::executors::parsers::{Location, Severity};
+
+/// When to print CI annotations
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum Annotations {
+    /// In GitHub Actions or Azure Pipelines, detected from the environment
+    #[default]
+    Auto,
+    /// GitHub Actions workflow commands
+    Github,
+    /// Azure Pipelines logging commands
+    Azure,
+    /// Never print annotations
+    Never,
+}
+
+impl Annotations {
+    /// Decide which annotations to print, for [`emit`]
+    pub fn apply(self) -> Option<CiPlatform> {
+        let platform = self.resolve(|name| std::env::var(name).ok());
+        let index = match platform {
+            None => 0,
+            Some(CiPlatform::GitHub) => 1,
+            Some(CiPlatform::Azure) => 2,
+        };
+        CURRENT.store(index, Ordering::Relaxed);
+        platform
+    }
+
+    fn resolve(self, env: impl Fn(&str) -> Option<String>) -> Option<CiPlatform> {
+        match self {
+            Self::Auto if env("GITHUB_ACTIONS").as_deref() == Some("true") => {
+                Some(CiPlatform::GitHub)
+            }
+            Self::Auto if env("TF_BUILD").is_some_and(|v| v.eq_ignore_ascii_case("true")) => {
+                Some(CiPlatform::Azure)
+            }
+            Self::Auto | Self::Never => None,
+            Self::Github => Some(CiPlatform::GitHub),
+            Self::Azure => Some(CiPlatform::Azure),
+        }
+    }
+}
+
+/// CI system that understands annotations on stdout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CiPlatform {
+    GitHub,
+    Azure,
+}
+
+impl CiPlatform {
+    /// The annotation for a diagnostic, with its location's file as given
+    ///
+    /// Azure has no notice level, so informational diagnostics give `None`.
+    pub fn format(self, diagnostic: &Diagnostic) -> Option<String> {
+        let label = diagnostic.labels.iter().find(|l| l.primary);
+        let mut message = diagnostic.message.clone();
+        if let Some(label) = label.filter(|l| !l.message.is_empty()) {
+            message = format!("{} ({})", message, label.message);
+        }
+
+        match self {
+            Self::GitHub => {
+                let level = match diagnostic.severity {
+                    Severity::Error => "error",
+                    Severity::Warning => "warning",
+                    Severity::Info => "notice",
+                };
+                let mut properties = Vec::new();
+                if let Some(label) = label {
+                    properties.push(format!("file={}", github_property(&slashes(&label.file))));
+                    if let Some(line) = label.line {
+                        properties.push(format!("line={}", line));
+                    }
+                    if let Some(column) = label.column {
+                        properties.push(format!("col={}", column));
+                    }
+                }
+                if let Some(ref code) = diagnostic.code {
+                    properties.push(format!("title={}", github_property(code)));
+                }
+                let command = if properties.is_empty() {
+                    level.to_string()
+                } else {
+                    format!("{} {}", level, properties.join(","))
+                };
+                Some(format!("::{}::{}", command, github_data(&message)))
+            }
+            Self::Azure => {
+                let level = match diagnostic.severity {
+                    Severity::Error => "error",
+                    Severity::Warning => "warning",
+                    Severity::Info => return None,
+                };
+                let mut properties = vec![format!("type={}", level)];
+                if let Some(label) = label {
+                    properties.push(format!("sourcepath={}", azure(&slashes(&label.file))));
+                    if let Some(line) = label.line {
+                        properties.push(format!("linenumber={}", line));
+                    }
+                    if let Some(column) = label.column {
+                        properties.push(format!("columnnumber={}", column));
+                    }
+                }
+                if let Some(ref code) = diagnostic.code {
+                    properties.push(format!("code={}", azure(code)));
+                }
+                Some(format!(
+                    "##vso[task.logissue {}]{}",
+                    properties.join(";"),
+                    azure(&message)
+                ))
+            }
+        }
+    }
+
+    /// Directory annotation paths are relative to
+    fn workspace(self) -> Option<PathBuf> {
+        let var = match self {
+            Self::GitHub => "GITHUB_WORKSPACE",
+            Self::Azure => "BUILD_SOURCESDIRECTORY",
+        };
+        std::env::var_os(var).map(PathBuf::from)
+    }
+}
+
+static CURRENT: AtomicU8 = AtomicU8::new(0);
+
+/// The platform set by [`Annotations::apply`], if annotations are on
+pub fn platform() -> Option<CiPlatform> {
+    match CURRENT.load(Ordering::Relaxed) {
+        1 => Some(CiPlatform::GitHub),
+        2 => Some(CiPlatform::Azure),
+        _ => None,
+    }
+}
+
+/// Print a diagnostic as an annotation, if annotations are on
+///
+/// Relative paths are taken from the current directory; annotations name
+/// files relative to the CI workspace, falling back to the current
+/// directory outside of it.
+pub fn emit(diagnostic: &Diagnostic) {
+    let Some(platform) = platform() else {
+        return;
+    };
+    let Ok(cwd) = std::env::current_dir() else {
+        return;
+    };
+    let root = platform.workspace().unwrap_or_else(|| cwd.clone());
+
+    let mut diagnostic = diagnostic.clone();
+    for label in &mut diagnostic.labels {
+        label.file = relative(&cwd.join(&label.file), &root);
+    }
+    if let Some(annotation) = platform.format(&diagnostic) {
+        println!("{}", crate::report::redact(&annotation));
+    }
+}
+
+/// Print a conflow finding about a file as an annotation
+pub fn emit_message(severity: Severity, file: &Path, message: &str) {
+    let location = Location {
+        file: file.to_path_buf(),
+        line: None,
+        column: None,
+    };
+    emit(&Diagnostic {
+        severity,
+        code: Some("conflow".into()),
+        message: message.to_string(),
+        labels: vec![Label::primary(&location, "")],
+        notes: vec![],
+    });
+}
+
+fn relative(path: &Path, root: &Path) -> PathBuf {
+    let canonical = |p: &Path| p.canonicalize().unwrap_or_else(|_| p.to_path_buf());
+    canonical(path)
+        .strip_prefix(canonical(root))
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn slashes(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+/// Escape a workflow command message
+fn github_data(s: &str) -> String {
+    s.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escape a workflow command property value
+fn github_property(s: &str) -> String {
+    github_data(s).replace(':', "%3A").replace(',', "%2C")
+}
+
+/// Escape a logging command property value or message
+fn azure(s: &str) -> String {
+    s.replace('%', "%AZP25")
+        .replace(';', "%3B")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+        .replace(']', "%5D")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diagnostic(severity: Severity) -> Diagnostic {
+        let location = Location {
+            file: PathBuf::from("config/app.yaml"),
+            line: Some(3),
+            column: Some(8),
+        };
+        Diagnostic {
+            severity,
+            code: Some("cue".into()),
+            message: "port: conflicting values 80 and \"80\",\nsee schema".into(),
+            labels: vec![Label::primary(&location, "")],
+            notes: vec![],
+        }
+    }
+
+    #[test]
+    fn test_formats() {
+        assert_eq!(
+            CiPlatform::GitHub
+                .format(&diagnostic(Severity::Error))
+                .unwrap(),
+            "::error file=config/app.yaml,line=3,col=8,title=cue::\
+             port: conflicting values 80 and \"80\",%0Asee schema"
+        );
+        assert_eq!(
+            CiPlatform::Azure
+                .format(&diagnostic(Severity::Warning))
+                .unwrap(),
+            "##vso[task.logissue type=warning;sourcepath=config/app.yaml;linenumber=3;\
+             columnnumber=8;code=cue]port: conflicting values 80 and \"80\",%0Asee schema"
+        );
+        assert_eq!(CiPlatform::Azure.format(&diagnostic(Severity::Info)), None);
+
+        let mut unlocated = diagnostic(Severity::Info);
+        unlocated.labels.clear();
+        unlocated.code = Some("refs: a,b".into());
+        assert!(CiPlatform::GitHub
+            .format(&unlocated)
+            .unwrap()
+            .starts_with("::notice title=refs%3A a%2Cb::"));
+    }
+
+    #[test]
+    fn test_detection() {
+        let env = |vars: &'static [(&str, &str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(k, _)| *k == name)
+                    .map(|(_, v)| v.to_string())
+            }
+        };
+        let github = env(&[("GITHUB_ACTIONS", "true")]);
+        let azure = env(&[("TF_BUILD", "True")]);
+        assert_eq!(Annotations::Auto.resolve(github), Some(CiPlatform::GitHub));
+        assert_eq!(Annotations::Auto.resolve(azure), Some(CiPlatform::Azure));
+        assert_eq!(
+            Annotations::Auto.resolve(env(&[("GITLAB_CI", "true")])),
+            None
+        );
+        assert_eq!(Annotations::Never.resolve(github), None);
+        assert_eq!(Annotations::Azure.resolve(github), Some(CiPlatform::Azure));
+    }
+}
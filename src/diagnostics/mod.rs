@@ -0,0 +1,136 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Diagnostics rendering
+//!
+//! Renders validation errors against the source they refer to, with the
+//! offending lines, caret underlines and labels. Works for any text format
+//! (YAML, JSON, CUE, Nickel, ...) since it only needs line and column.
+
+pub mod annotations;
+mod render;
+mod repro;
+
+pub use render::Renderer;
+pub use repro::{reproduce, Repro};
+
+use std::path::{Path, PathBuf};
+
+use crate::executors::parsers::{self, Location, Severity, ToolDiagnostic};
+
+/// A label pointing at a source position
+#[derive(Debug, Clone, PartialEq)]
+pub struct Label {
+    pub file: PathBuf,
+    /// 1-based line, when known
+    pub line: Option<usize>,
+    /// 1-based column, when known
+    pub column: Option<usize>,
+    /// Text printed next to the carets
+    pub message: String,
+    /// Primary labels use `^`, secondary ones `-`
+    pub primary: bool,
+}
+
+impl Label {
+    /// Create a primary label
+    pub fn primary(location: &Location, message: impl Into<String>) -> Self {
+        Self {
+            file: location.file.clone(),
+            line: location.line,
+            column: location.column,
+            message: message.into(),
+            primary: true,
+        }
+    }
+
+    /// Create a secondary label
+    pub fn secondary(location: &Location, message: impl Into<String>) -> Self {
+        Self {
+            primary: false,
+            ..Self::primary(location, message)
+        }
+    }
+}
+
+/// A diagnostic ready for rendering
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// Short code shown after the severity, e.g. the tool or rule
+    pub code: Option<String>,
+    pub message: String,
+    pub labels: Vec<Label>,
+    pub notes: Vec<String>,
+}
+
+impl From<&ToolDiagnostic> for Diagnostic {
+    fn from(diagnostic: &ToolDiagnostic) -> Self {
+        let mut labels = Vec::new();
+        if let Some(ref location) = diagnostic.location {
+            labels.push(Label::primary(
+                location,
+                diagnostic.rule.clone().unwrap_or_default(),
+            ));
+        }
+        labels.extend(
+            diagnostic
+                .related
+                .iter()
+                .map(|l| Label::secondary(l, "also involved here")),
+        );
+
+        Self {
+            severity: diagnostic.severity,
+            code: Some(diagnostic.tool.clone()),
+            message: diagnostic.message.clone(),
+            labels,
+            notes: vec![],
+        }
+    }
+}
+
+/// Render a stage's tool output as diagnostics
+///
+/// Returns `None` when nothing in the output could be located, so callers
+/// can fall back to printing the raw output.
+pub fn render_tool_output(tool: &str, output: &str, base_dir: &Path) -> Option<String> {
+    let diagnostics = parsers::parse_output(tool, output);
+    if diagnostics.is_empty() {
+        return None;
+    }
+
+    let mut renderer = Renderer::new(base_dir);
+    Some(
+        diagnostics
+            .iter()
+            .map(|d| renderer.render(&Diagnostic::from(d)))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_tool_diagnostic() {
+        let parsed = parsers::parse_output(
+            "cue",
+            "port: conflicting values:\n    ./schema.cue:3:8\n    ./config.json:2:10\n",
+        );
+        let diagnostic = Diagnostic::from(&parsed[0]);
+
+        assert_eq!(diagnostic.code.as_deref(), Some("cue"));
+        assert_eq!(diagnostic.labels.len(), 2);
+        assert!(diagnostic.labels[0].primary);
+        assert_eq!(diagnostic.labels[0].file, PathBuf::from("config.json"));
+        assert!(!diagnostic.labels[1].primary);
+    }
+
+    #[test]
+    fn test_render_tool_output_without_locations() {
+        assert!(render_tool_output("cue", "something odd", Path::new(".")).is_none());
+    }
+}
@@ -0,0 +1,270 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Source snippet renderer
+//!
+//! Output looks like:
+//!
+//! ```text
+//! error[cue]: port: conflicting values 80 and "80"
+//!   ┌─ config.json:2:11
+//!   │
+//! 2 │   "port": "80",
+//!   │           ^^^^
+//!   │
+//!   ┌─ schema.cue:3:7
+//!   │
+//! 3 │ port: int
+//!   │       --- also involved here
+//! ```
+
+use colored::{ColoredString, Colorize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use super::{Diagnostic, Label};
+use crate::executors::parsers::Severity;
+
+/// Renders diagnostics, caching source files between calls
+pub struct Renderer {
+    base_dir: PathBuf,
+    sources: HashMap<PathBuf, Option<Vec<String>>>,
+}
+
+impl Renderer {
+    /// Create a renderer resolving relative paths against `base_dir`
+    pub fn new(base_dir: &Path) -> Self {
+        Self {
+            base_dir: base_dir.to_path_buf(),
+            sources: HashMap::new(),
+        }
+    }
+
+    /// Render a diagnostic with snippets for each label
+    pub fn render(&mut self, diagnostic: &Diagnostic) -> String {
+        let mut out = String::new();
+
+        let severity = paint(diagnostic.severity, &diagnostic.severity.to_string()).bold();
+        match diagnostic.code {
+            Some(ref code) => out.push_str(&format!(
+                "{}{}",
+                severity,
+                paint(diagnostic.severity, &format!("[{}]", code)).bold()
+            )),
+            None => out.push_str(&severity.to_string()),
+        }
+        out.push_str(&format!(": {}\n", diagnostic.message.bold()));
+
+        let gutter = diagnostic
+            .labels
+            .iter()
+            .filter_map(|l| l.line)
+            .max()
+            .map(|l| l.to_string().len())
+            .unwrap_or(1);
+
+        for label in &diagnostic.labels {
+            self.render_label(&mut out, diagnostic.severity, label, gutter);
+        }
+
+        for note in &diagnostic.notes {
+            out.push_str(&format!("{} = {}\n", " ".repeat(gutter), note));
+        }
+
+        crate::report::redact(&out)
+    }
+
+    fn render_label(&mut self, out: &mut String, severity: Severity, label: &Label, gutter: usize) {
+        let pad = " ".repeat(gutter);
+        let bar = "│".blue();
+
+        let mut position = label.file.display().to_string();
+        if let Some(line) = label.line {
+            position.push_str(&format!(":{}", line));
+            if let Some(column) = label.column {
+                position.push_str(&format!(":{}", column));
+            }
+        }
+        out.push_str(&format!("{} {} {}\n", pad, "┌─".blue(), position));
+
+        let Some(line_no) = label.line else {
+            if !label.message.is_empty() {
+                out.push_str(&format!("{} {} {}\n", pad, bar, label.message));
+            }
+            return;
+        };
+
+        let Some(text) = self.line(&label.file, line_no) else {
+            if !label.message.is_empty() {
+                out.push_str(&format!("{} {} {}\n", pad, bar, label.message));
+            }
+            return;
+        };
+
+        let text = text.replace('\t', "    ");
+        out.push_str(&format!("{} {}\n", pad, bar));
+        out.push_str(&format!(
+            "{} {} {}\n",
+            format!("{:>width$}", line_no, width = gutter).blue(),
+            bar,
+            text
+        ));
+
+        let (start, width) = underline_span(&text, label.column);
+        let marker = if label.primary { "^" } else { "-" };
+        let underline = marker.repeat(width);
+        let underline = if label.primary {
+            paint(severity, &underline)
+        } else {
+            underline.blue()
+        };
+
+        let mut line = format!("{} {} {}{}", pad, bar, " ".repeat(start), underline);
+        if !label.message.is_empty() {
+            let message = if label.primary {
+                paint(severity, &label.message)
+            } else {
+                label.message.blue()
+            };
+            line.push_str(&format!(" {}", message));
+        }
+        out.push_str(&line);
+        out.push('\n');
+    }
+
+    /// Fetch a 1-based line from a source file
+    fn line(&mut self, file: &Path, line: usize) -> Option<String> {
+        let path = if file.is_absolute() {
+            file.to_path_buf()
+        } else {
+            self.base_dir.join(file)
+        };
+
+        let lines = self.sources.entry(path.clone()).or_insert_with(|| {
+            std::fs::read_to_string(&path)
+                .ok()
+                .map(|content| content.lines().map(String::from).collect())
+        });
+
+        lines.as_ref()?.get(line.checked_sub(1)?).cloned()
+    }
+}
+
+/// Column offset and width to underline
+///
+/// Without a column the whole trimmed line is marked; otherwise the token
+/// starting at the column (a quoted string or a run of non-space chars).
+fn underline_span(text: &str, column: Option<usize>) -> (usize, usize) {
+    let chars: Vec<char> = text.chars().collect();
+
+    let Some(column) = column else {
+        let start = chars.iter().take_while(|c| c.is_whitespace()).count();
+        return (start, chars.len().saturating_sub(start).max(1));
+    };
+
+    let start = column.saturating_sub(1).min(chars.len());
+    let rest = &chars[start..];
+
+    let width = match rest.first() {
+        Some(&quote @ ('"' | '\'')) => rest[1..]
+            .iter()
+            .position(|&c| c == quote)
+            .map(|end| end + 2)
+            .unwrap_or(rest.len()),
+        Some(_) => rest
+            .iter()
+            .position(|c| c.is_whitespace() || matches!(c, ',' | ':' | '}' | ']'))
+            .unwrap_or(rest.len()),
+        None => 1,
+    };
+
+    (start, width.max(1))
+}
+
+fn paint(severity: Severity, text: &str) -> ColoredString {
+    match severity {
+        Severity::Error => text.red(),
+        Severity::Warning => text.yellow(),
+        Severity::Info => text.blue(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executors::parsers::Location;
+
+    fn location(file: &str, line: usize, column: usize) -> Location {
+        Location {
+            file: file.into(),
+            line: Some(line),
+            column: Some(column),
+        }
+    }
+
+    #[test]
+    fn test_underline_span() {
+        assert_eq!(underline_span(r#"  "port": "80","#, Some(11)), (10, 4));
+        assert_eq!(underline_span("port: int", Some(7)), (6, 3));
+        assert_eq!(underline_span("  key: value", None), (2, 10));
+    }
+
+    #[test]
+    fn test_render_snippet() {
+        colored::control::set_override(false);
+        let temp = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            temp.path().join("config.json"),
+            "{\n  \"port\": \"80\",\n}\n",
+        )
+        .unwrap();
+        std::fs::write(
+            temp.path().join("schema.cue"),
+            "#C: {\n  x: 1\n}\nport: int\n",
+        )
+        .unwrap();
+
+        let diagnostic = Diagnostic {
+            severity: Severity::Error,
+            code: Some("cue".into()),
+            message: "port: conflicting values".into(),
+            labels: vec![
+                Label::primary(&location("config.json", 2, 11), ""),
+                Label::secondary(&location("schema.cue", 4, 7), "also involved here"),
+            ],
+            notes: vec![],
+        };
+
+        let rendered = Renderer::new(temp.path()).render(&diagnostic);
+        let expected = "\
+error[cue]: port: conflicting values
+  ┌─ config.json:2:11
+  │
+2 │   \"port\": \"80\",
+  │           ^^^^
+  ┌─ schema.cue:4:7
+  │
+4 │ port: int
+  │       --- also involved here
+";
+        assert_eq!(rendered, expected);
+    }
+
+    #[test]
+    fn test_render_missing_source() {
+        colored::control::set_override(false);
+        let diagnostic = Diagnostic {
+            severity: Severity::Warning,
+            code: None,
+            message: "resource invalid".into(),
+            labels: vec![Label::primary(&location("gone.yaml", 1, 1), "")],
+            notes: vec!["see the schema".into()],
+        };
+
+        let rendered = Renderer::new(Path::new("/nonexistent")).render(&diagnostic);
+        assert_eq!(
+            rendered,
+            "warning: resource invalid\n  ┌─ gone.yaml:1:1\n  = see the schema\n"
+        );
+    }
+}
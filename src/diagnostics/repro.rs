@@ -0,0 +1,544 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Minimal reproductions
+//!
+//! Rebuilds a failed CUE or Nickel constraint as the smallest config that
+//! breaks it, next to the same config corrected, so `conflow explain
+//! --show-fix` teaches the constraint rather than the error text. Values
+//! the message leaves out are read from the source lines it points at.
+
+use regex::Regex;
+use serde::Serialize;
+use std::path::Path;
+use std::sync::LazyLock;
+
+use crate::executors::parsers::{Location, ToolDiagnostic};
+
+/// A failing config and its fix, for one constraint
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Repro {
+    pub tool: String,
+    /// Path of the field that failed, e.g. `deploy.replicas`
+    pub field: String,
+    /// The constraint that failed, in the tool's syntax
+    pub constraint: String,
+    /// What the constraint asks for, and how the value missed it
+    pub lesson: String,
+    /// Smallest config that fails the constraint
+    pub failing: String,
+    /// The same config, passing
+    pub fixed: String,
+}
+
+static CUE_MESSAGE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(?P<field>[^\s:]+): (?P<rest>.+)$").unwrap());
+
+static CONFLICT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(concat!(
+        r"^conflicting values (?P<a>.+?) and (?P<b>.+?)",
+        r"(?: \(mismatched types (?P<ta>\w+) and (?P<tb>\w+)\))?$",
+    ))
+    .unwrap()
+});
+
+static OUT_OF_BOUND: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(concat!(
+        r"^invalid value (?P<value>.+) ",
+        r"\(out of bound (?P<op>>=|<=|!=|=~|!~|>|<)(?P<bound>.+)\)$",
+    ))
+    .unwrap()
+});
+
+static INCOMPLETE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^incomplete value (?P<constraint>.+)$").unwrap());
+
+static CONTRACT_BROKEN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^contract broken by (?:the value of `(?P<field>[^`]+)`|a value)").unwrap()
+});
+
+static MISSING_DEFINITION: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^missing definition for `(?P<field>[^`]+)`").unwrap());
+
+/// CUE's basic types
+const TYPES: &[&str] = &["int", "float", "number", "string", "bool", "bytes", "null"];
+
+/// Reproductions for the constraint failures among `diagnostics`
+///
+/// Diagnostics that aren't constraint failures, or whose constraint can't
+/// be satisfied mechanically (a regex nothing simple matches, a custom
+/// contract), are left out. Source locations are resolved against
+/// `base_dir`.
+pub fn reproduce(diagnostics: &[ToolDiagnostic], base_dir: &Path) -> Vec<Repro> {
+    let mut repros: Vec<Repro> = Vec::new();
+    for diagnostic in diagnostics {
+        let repro = cue(diagnostic, diagnostics, base_dir).or_else(|| nickel(diagnostic, base_dir));
+        // A disjunction reports one conflict per alternative
+        if let Some(repro) = repro.filter(|r| !repros.contains(r)) {
+            repros.push(repro);
+        }
+    }
+    repros
+}
+
+fn cue(diagnostic: &ToolDiagnostic, all: &[ToolDiagnostic], base_dir: &Path) -> Option<Repro> {
+    let caps = CUE_MESSAGE.captures(&diagnostic.message)?;
+    let path = &caps["field"];
+    let field = path.rsplit('.').next().unwrap_or(path);
+    let rest = &caps["rest"];
+
+    if let Some(conflict) = CONFLICT.captures(rest) {
+        let pairs: Vec<(String, String)> = all
+            .iter()
+            .filter_map(|d| {
+                let caps = CUE_MESSAGE.captures(&d.message)?;
+                let conflict = CONFLICT.captures(&caps["rest"])?;
+                (&caps["field"] == path).then(|| (conflict["a"].into(), conflict["b"].into()))
+            })
+            .collect();
+        let types = conflict.name("ta").zip(conflict.name("tb"));
+        return cue_conflict(diagnostic, path, field, &pairs, types, base_dir);
+    }
+
+    if let Some(caps) = OUT_OF_BOUND.captures(rest) {
+        let (op, bound, value) = (&caps["op"], caps["bound"].trim(), caps["value"].trim());
+        let fixed = satisfy_bound(op, bound, value)?;
+        let base = match op {
+            "=~" | "!~" => "string",
+            _ if is_integer(bound) && is_integer(value) => "int",
+            _ => "number",
+        };
+        let constraint = format!("{} & {}{}", base, op, bound);
+        return Some(Repro {
+            tool: "cue".into(),
+            field: path.into(),
+            lesson: format!(
+                "`{}` must be {} {}, and {} is not.",
+                field,
+                bound_words(op),
+                bound,
+                value
+            ),
+            failing: cue_config(field, &constraint, &format!("{}: {}", field, value)),
+            fixed: cue_config(field, &constraint, &format!("{}: {}", field, fixed)),
+            constraint,
+        });
+    }
+
+    if rest == "field not allowed" {
+        let value = source_value(diagnostic, field, base_dir)
+            .map_or_else(|| "\"...\"".into(), |v| cue_literal(&v));
+        let known = "name: \"example\"";
+        return Some(Repro {
+            tool: "cue".into(),
+            field: path.into(),
+            constraint: "#Config: {name: string}".into(),
+            lesson: format!(
+                "Definitions are closed: a field they don't declare is an error. Declare \
+                 `{}` in the definition, or remove it (check for a typo).",
+                field
+            ),
+            failing: cue_config(
+                "name",
+                "string",
+                &format!("{}\n  {}: {}", known, field, value),
+            ),
+            fixed: cue_config("name", "string", known),
+        });
+    }
+
+    if let Some(caps) = INCOMPLETE.captures(rest) {
+        let constraint = caps["constraint"].trim();
+        let fixed = satisfy(constraint, "")?;
+        return Some(Repro {
+            tool: "cue".into(),
+            field: path.into(),
+            constraint: constraint.into(),
+            lesson: format!(
+                "`{}` is declared as {} but never set, so it has no concrete value to export.",
+                field, constraint
+            ),
+            failing: cue_config(field, constraint, ""),
+            fixed: cue_config(field, constraint, &format!("{}: {}", field, fixed)),
+        });
+    }
+
+    None
+}
+
+/// A conflict, or several for one field when the value matched none of a
+/// disjunction's alternatives
+fn cue_conflict(
+    diagnostic: &ToolDiagnostic,
+    path: &str,
+    field: &str,
+    pairs: &[(String, String)],
+    types: Option<(regex::Match, regex::Match)>,
+    base_dir: &Path,
+) -> Option<Repro> {
+    let (a, b) = pairs.first()?;
+
+    if pairs.len() > 1 {
+        // The value is the side every conflict shares
+        let value = [a, b]
+            .into_iter()
+            .find(|v| pairs.iter().all(|(x, y)| x == *v || y == *v))?;
+        let alternatives: Vec<&str> = pairs
+            .iter()
+            .map(|(x, y)| if x == value { y.as_str() } else { x.as_str() })
+            .collect();
+        let fixed = closest(value, &alternatives);
+        let constraint = alternatives.join(" | ");
+        return Some(Repro {
+            tool: "cue".into(),
+            field: path.into(),
+            lesson: format!(
+                "`{}` must be one of {}, and {} matches none of them.",
+                field,
+                alternatives.join(", "),
+                value
+            ),
+            failing: cue_config(field, &constraint, &format!("{}: {}", field, value)),
+            fixed: cue_config(field, &constraint, &format!("{}: {}", field, fixed)),
+            constraint,
+        });
+    }
+
+    let (constraint, value) = if is_constraint(a) && !is_constraint(b) {
+        (a.clone(), b.clone())
+    } else if is_constraint(b) && !is_constraint(a) {
+        (b.clone(), a.clone())
+    } else {
+        // Two concrete values: the one in the config is the value
+        match source_value(diagnostic, field, base_dir) {
+            Some(source) if unquote(&source) == unquote(a) => (b.clone(), a.clone()),
+            _ => (a.clone(), b.clone()),
+        }
+    };
+
+    let lesson = match types {
+        Some((ta, tb)) if is_constraint(&constraint) => {
+            let kind = if value == *a {
+                ta.as_str()
+            } else {
+                tb.as_str()
+            };
+            format!(
+                "`{}` must be {}, and {} is a {}: unification never converts between types.",
+                field, constraint, value, kind
+            )
+        }
+        _ if is_constraint(&constraint) => {
+            format!("`{}` must be {}, and {} is not.", field, constraint, value)
+        }
+        _ => format!(
+            "`{}` is fixed to {}: values unify only with themselves, so {} conflicts with it.",
+            field, constraint, value
+        ),
+    };
+    let fixed = satisfy(&constraint, &value)?;
+    Some(Repro {
+        tool: "cue".into(),
+        field: path.into(),
+        lesson,
+        failing: cue_config(field, &constraint, &format!("{}: {}", field, value)),
+        fixed: cue_config(field, &constraint, &format!("{}: {}", field, fixed)),
+        constraint,
+    })
+}
+
+fn nickel(diagnostic: &ToolDiagnostic, base_dir: &Path) -> Option<Repro> {
+    if let Some(caps) = CONTRACT_BROKEN.captures(&diagnostic.message) {
+        let named = caps.name("field").map(|f| f.as_str());
+        let (field, contract, value) = source_lines(diagnostic, base_dir)
+            .into_iter()
+            .find_map(|line| contract_field(&line, named))?;
+        let fixed = satisfy(nickel_type(&contract)?, &value)?;
+        return Some(Repro {
+            tool: "nickel".into(),
+            lesson: format!(
+                "`{}` has the contract {}, and {} breaks it: contracts check values, they \
+                 don't convert them.",
+                field, contract, value
+            ),
+            failing: format!("{{\n  {} | {} = {},\n}}\n", field, contract, value),
+            fixed: format!("{{\n  {} | {} = {},\n}}\n", field, contract, fixed),
+            field,
+            constraint: contract,
+        });
+    }
+
+    if let Some(caps) = MISSING_DEFINITION.captures(&diagnostic.message) {
+        let field = caps["field"].to_string();
+        let declaration = Regex::new(&format!(
+            r"\b{}\s*\|\s*(?P<contract>[^=,]+?)\s*,?\s*$",
+            regex::escape(&field)
+        ))
+        .ok()?;
+        let contract = source_lines(diagnostic, base_dir).iter().find_map(|line| {
+            declaration
+                .captures(line)
+                .map(|c| c["contract"].to_string())
+        })?;
+        let fixed = satisfy(nickel_type(&contract)?, "")?;
+        let schema = format!("let Config = {{\n  {} | {},\n}} in\n", field, contract);
+        return Some(Repro {
+            tool: "nickel".into(),
+            lesson: format!(
+                "`{}` is declared with the contract {} but never defined, and exporting needs \
+                 a value for every field.",
+                field, contract
+            ),
+            failing: format!("{}{{\n}} | Config\n", schema),
+            fixed: format!("{}{{\n  {} = {},\n}} | Config\n", schema, field, fixed),
+            field,
+            constraint: contract,
+        });
+    }
+
+    None
+}
+
+/// A definition with one constrained field, and a config using it
+fn cue_config(field: &str, constraint: &str, value: &str) -> String {
+    let value = if value.is_empty() {
+        String::new()
+    } else {
+        format!("  {}\n", value)
+    };
+    format!(
+        "#Config: {{\n  {}: {}\n}}\n\nconfig: #Config & {{\n{}}}\n",
+        field, constraint, value
+    )
+}
+
+/// `field | Contract = value` in a line of Nickel
+fn contract_field(line: &str, field: Option<&str>) -> Option<(String, String, String)> {
+    let name = field.map_or(r"[\w-]+".to_string(), regex::escape);
+    let pattern = format!(
+        r#"(?P<field>{})\s*\|\s*(?P<contract>[^=]+?)\s*=\s*(?P<value>.+?)\s*,?\s*$"#,
+        name
+    );
+    let caps = Regex::new(&pattern).ok()?.captures(line)?;
+    Some((
+        caps["field"].to_string(),
+        caps["contract"].to_string(),
+        caps["value"].to_string(),
+    ))
+}
+
+/// The CUE type a Nickel contract checks, for the built-in ones
+fn nickel_type(contract: &str) -> Option<&'static str> {
+    match contract {
+        "Number" => Some("number"),
+        "String" => Some("string"),
+        "Bool" => Some("bool"),
+        _ => None,
+    }
+}
+
+/// The lines a diagnostic points at
+fn source_lines(diagnostic: &ToolDiagnostic, base_dir: &Path) -> Vec<String> {
+    diagnostic
+        .location
+        .iter()
+        .chain(&diagnostic.related)
+        .filter_map(|location| source_line(location, base_dir))
+        .collect()
+}
+
+fn source_line(location: &Location, base_dir: &Path) -> Option<String> {
+    let content = std::fs::read_to_string(base_dir.join(&location.file)).ok()?;
+    let line = content.lines().nth(location.line?.checked_sub(1)?)?;
+    Some(line.to_string())
+}
+
+/// The value a config line sets `field` to, in YAML, JSON or CUE
+fn source_value(diagnostic: &ToolDiagnostic, field: &str, base_dir: &Path) -> Option<String> {
+    let pattern = format!(
+        r#"\b{}"?\s*:\s*(?P<value>.+?)\s*,?\s*$"#,
+        regex::escape(field)
+    );
+    let regex = Regex::new(&pattern).ok()?;
+    source_lines(diagnostic, base_dir)
+        .iter()
+        .find_map(|line| regex.captures(line).map(|c| c["value"].to_string()))
+}
+
+/// A YAML or JSON scalar as a CUE literal, quoting bare strings
+fn cue_literal(value: &str) -> String {
+    let bare = !value.starts_with(['"', '\'', '[', '{'])
+        && value.parse::<f64>().is_err()
+        && !["true", "false", "null"].contains(&value);
+    if bare {
+        format!("\"{}\"", value)
+    } else {
+        value.to_string()
+    }
+}
+
+/// Whether a CUE value is a type or bound rather than a concrete value
+fn is_constraint(value: &str) -> bool {
+    TYPES.contains(&value)
+        || value.starts_with(['>', '<', '!', '='])
+        || value.contains(" & ")
+        || value.contains(" | ")
+}
+
+/// A value satisfying `constraint`, as close to `value` as possible
+fn satisfy(constraint: &str, value: &str) -> Option<String> {
+    let mut fixed = value.to_string();
+    for part in constraint.split(" & ") {
+        let part = part.trim();
+        fixed = if TYPES.contains(&part) {
+            convert(&fixed, part)
+        } else if let Some(op) = [">=", "<=", "!=", "=~", "!~", ">", "<"]
+            .into_iter()
+            .find(|op| part.starts_with(op))
+        {
+            satisfy_bound(op, &part[op.len()..], &fixed)?
+        } else if part.contains(" | ") {
+            closest(&fixed, &part.split(" | ").collect::<Vec<_>>())
+        } else {
+            part.to_string()
+        };
+    }
+    (!fixed.is_empty()).then_some(fixed)
+}
+
+/// `value` as a CUE `kind`, or an example of that kind
+fn convert(value: &str, kind: &str) -> String {
+    let text = unquote(value);
+    let number = text.parse::<f64>().ok();
+    match kind {
+        "int" => number.map_or("1".into(), |n| (n.trunc() as i64).to_string()),
+        "float" => number.map_or("1.0".into(), |n| format!("{:?}", n)),
+        "number" => number.map_or("1".into(), |_| text.to_string()),
+        "bool" if text == "true" || text == "false" => text.to_string(),
+        "bool" => "true".into(),
+        "null" => "null".into(),
+        "bytes" => format!("'{}'", text),
+        _ if text.is_empty() => "\"example\"".into(),
+        _ => format!("\"{}\"", text),
+    }
+}
+
+/// The nearest value to `value` a bound allows
+fn satisfy_bound(op: &str, bound: &str, value: &str) -> Option<String> {
+    if let ("=~" | "!~", Ok(pattern)) = (op, serde_json::from_str::<String>(bound)) {
+        let regex = Regex::new(&pattern).ok()?;
+        let text = unquote(value).to_string();
+        let lower = text.to_lowercase();
+        let slug: String = lower
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+            .collect();
+        return [text, lower, slug, "example".into(), "a".into(), "1".into()]
+            .into_iter()
+            .filter(|candidate| !candidate.is_empty())
+            .find(|candidate| regex.is_match(candidate) == (op == "=~"))
+            .map(|candidate| format!("\"{}\"", candidate));
+    }
+
+    let bound: f64 = bound.parse().ok()?;
+    let step = if is_integer(&bound.to_string()) {
+        1.0
+    } else {
+        0.5
+    };
+    let fixed = match op {
+        ">=" | "<=" => bound,
+        ">" | "!=" => bound + step,
+        "<" => bound - step,
+        _ => return None,
+    };
+    Some(if fixed.fract() == 0.0 {
+        (fixed as i64).to_string()
+    } else {
+        fixed.to_string()
+    })
+}
+
+fn bound_words(op: &str) -> &'static str {
+    match op {
+        ">=" => "at least",
+        ">" => "greater than",
+        "<=" => "at most",
+        "<" => "less than",
+        "!=" => "anything but",
+        "=~" => "a string matching",
+        _ => "a string not matching",
+    }
+}
+
+/// The alternative most like `value`, e.g. `"prod"` for `"production"`
+fn closest(value: &str, alternatives: &[&str]) -> String {
+    let text = unquote(value).to_lowercase();
+    alternatives
+        .iter()
+        .find(|a| {
+            let a = unquote(a).to_lowercase();
+            !a.is_empty() && (text.starts_with(&a) || a.starts_with(&text))
+        })
+        .or(alternatives.first())
+        .map_or_else(String::new, |a| a.to_string())
+}
+
+fn is_integer(value: &str) -> bool {
+    value.parse::<i64>().is_ok()
+}
+
+fn unquote(value: &str) -> &str {
+    value.trim().trim_matches('"').trim_matches('\'')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executors::parsers::parse_output;
+
+    #[test]
+    fn test_cue_reproductions() {
+        let output = concat!(
+            "port: conflicting values int and \"8080\" (mismatched types int and string):\n",
+            "    ./schema.cue:3:8\n    ./config.yaml:2:7\n",
+            "env: 3 errors in empty disjunction:\n",
+            "env: conflicting values \"dev\" and \"production\":\n",
+            "    ./schema.cue:4:8\n    ./config.yaml:3:6\n",
+            "env: conflicting values \"prod\" and \"production\":\n",
+            "    ./schema.cue:4:25\n    ./config.yaml:3:6\n",
+            "replicas: invalid value 0 (out of bound >=1):\n",
+            "    ./schema.cue:5:15\n    ./config.yaml:4:11\n",
+        );
+        let repros = reproduce(&parse_output("cue", output), Path::new("."));
+        assert_eq!(repros.len(), 3);
+
+        assert_eq!(repros[0].constraint, "int");
+        assert!(repros[0].failing.contains("port: \"8080\""));
+        assert!(repros[0].fixed.contains("port: 8080"));
+        assert!(repros[0].lesson.contains("is a string"));
+
+        assert_eq!(repros[1].constraint, "\"dev\" | \"prod\"");
+        assert!(repros[1].fixed.contains("env: \"prod\""));
+
+        assert_eq!(repros[2].constraint, "int & >=1");
+        assert!(repros[2].fixed.contains("replicas: 1"));
+    }
+
+    #[test]
+    fn test_nickel_reproduction_from_source() {
+        let temp = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            temp.path().join("config.ncl"),
+            "{\n  name | String = \"api\",\n  port | Number = \"8080\",\n}\n",
+        )
+        .unwrap();
+        let output = "error: contract broken by the value of `port`\n  ┌─ config.ncl:3:19\n";
+
+        let repros = reproduce(&parse_output("nickel", output), temp.path());
+        assert_eq!(repros.len(), 1);
+        assert_eq!(repros[0].constraint, "Number");
+        assert_eq!(repros[0].failing, "{\n  port | Number = \"8080\",\n}\n");
+        assert_eq!(repros[0].fixed, "{\n  port | Number = 8080,\n}\n");
+    }
+}
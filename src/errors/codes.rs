@@ -0,0 +1,494 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Error codes
+//!
+//! Every `ConflowError` variant carries a stable `CFnnnn` code, shown next to
+//! the message and looked up with `conflow explain`. Codes are never reused:
+//! a retired variant keeps its entry, and new variants take the next number.
+
+use miette::Diagnostic;
+
+use super::{ConflowError, ExitCode};
+
+/// A documented error code
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ErrorCode {
+    /// Stable code, e.g. `CF0011`
+    pub code: &'static str,
+    /// Short identifier, e.g. `stage_failed`
+    pub name: &'static str,
+    /// One-line description
+    pub summary: &'static str,
+    /// Causes and fixes
+    pub explanation: &'static str,
+}
+
+impl ErrorCode {
+    /// Find a code by `CF0042`, `cf42` or its name, `stage_failed`
+    pub fn find(query: &str) -> Option<&'static ErrorCode> {
+        let query = query.trim();
+        let number = query
+            .get(..2)
+            .filter(|prefix| prefix.eq_ignore_ascii_case("cf"))
+            .and_then(|_| query[2..].parse::<u32>().ok());
+
+        ERROR_CODES.iter().find(|e| match number {
+            Some(n) => e.code[2..].parse::<u32>() == Ok(n),
+            None => e.name.eq_ignore_ascii_case(query),
+        })
+    }
+}
+
+impl ConflowError {
+    /// Documentation for this error's code
+    pub fn error_code(&self) -> Option<&'static ErrorCode> {
+        let code = self.code()?.to_string();
+        ErrorCode::find(&code)
+    }
+}
+
+/// An error report as one JSON object, for `--log-format json`
+pub fn report_json(report: &miette::Report, exit: ExitCode) -> serde_json::Value {
+    let code = report.code().map(|c| c.to_string());
+    let documented = code.as_deref().and_then(ErrorCode::find);
+    serde_json::json!({
+        "level": "ERROR",
+        "code": code,
+        "name": documented.map(|e| e.name),
+        "message": report.to_string(),
+        "help": report.help().map(|h| h.to_string()),
+        "exit_code": exit.code(),
+    })
+}
+
+/// All error codes, in order
+pub const ERROR_CODES: &[ErrorCode] = &[
+    ErrorCode {
+        code: "CF0001",
+        name: "tool_not_found",
+        summary: "A tool the pipeline needs is not installed",
+        explanation: "A stage uses a tool (cue, nickel or a custom executor's command) that \
+            is not on PATH.\n\nInstall the tool, or pin it with 'conflow toolchain install' so \
+            conflow manages the version. Run 'conflow run --dry-run' to see which tools the \
+            pipeline needs.",
+    },
+    ErrorCode {
+        code: "CF0002",
+        name: "tool_execution_failed",
+        summary: "A tool could not be started or crashed",
+        explanation: "conflow found the tool but could not run it to completion, for example \
+            because it is not executable or was killed.\n\nRun the command shown in the error \
+            by hand to see the tool's own output.",
+    },
+    ErrorCode {
+        code: "CF0003",
+        name: "executor_not_found",
+        summary: "A stage names an executor that doesn't exist",
+        explanation: "The stage's 'tool' refers to an executor conflow doesn't know. Built-in \
+            executors are cue, nickel and shell; others must be declared under 'executors:' \
+            in the pipeline.\n\nCheck the spelling, or add an executor definition.",
+    },
+    ErrorCode {
+        code: "CF0004",
+        name: "pipeline_not_found",
+        summary: "The pipeline file doesn't exist",
+        explanation: "conflow looks for .conflow.yaml in the current directory, or the file \
+            given with --pipeline.\n\nRun 'conflow init' to create one, or pass -C <DIR> to run \
+            in another directory.",
+    },
+    ErrorCode {
+        code: "CF0005",
+        name: "invalid_pipeline",
+        summary: "The pipeline configuration is invalid",
+        explanation: "The pipeline parsed, but its contents break a rule, such as duplicate \
+            stage names or an unsupported schema version.\n\nRun 'conflow validate' for the \
+            full list of problems.",
+    },
+    ErrorCode {
+        code: "CF0006",
+        name: "config_parse",
+        summary: "A configuration file could not be parsed",
+        explanation: "A pipeline or RSR file is not valid YAML, or has fields in the wrong \
+            shape. The error points at the offending location.\n\nExport the schema with \
+            'conflow schema export' and let your editor check the file as you type.",
+    },
+    ErrorCode {
+        code: "CF0007",
+        name: "invalid_stage",
+        summary: "A stage definition is invalid",
+        explanation: "A stage is missing something it needs, or combines options that don't \
+            go together.\n\nThe message names the stage and the reason.",
+    },
+    ErrorCode {
+        code: "CF0008",
+        name: "circular_dependency",
+        summary: "Stages depend on each other in a cycle",
+        explanation: "'depends_on' links form a loop, so no stage in it can run first.\n\n\
+            Remove one of the dependencies listed in the error; 'conflow graph' shows the \
+            whole dependency graph.",
+    },
+    ErrorCode {
+        code: "CF0009",
+        name: "unknown_dependency",
+        summary: "A stage depends on a stage that doesn't exist",
+        explanation: "A 'depends_on' entry names no stage in the pipeline.\n\nCheck the \
+            spelling against the stage names.",
+    },
+    ErrorCode {
+        code: "CF0010",
+        name: "stage_not_found",
+        summary: "The requested stage doesn't exist",
+        explanation: "A stage given on the command line (for example with --stage) is not \
+            in the pipeline.\n\nRun 'conflow graph' to list the stages.",
+    },
+    ErrorCode {
+        code: "CF0011",
+        name: "stage_failed",
+        summary: "A stage's command failed",
+        explanation: "The tool ran and reported a problem, usually a validation or type \
+            error in your configuration.\n\nThe tool's output is printed above the error; fix \
+            what it reports and run again.",
+    },
+    ErrorCode {
+        code: "CF0012",
+        name: "execution_failed",
+        summary: "The pipeline could not be executed",
+        explanation: "Something outside a single stage stopped the run, such as an output \
+            that could not be written.\n\nThe message gives the cause.",
+    },
+    ErrorCode {
+        code: "CF0013",
+        name: "approval_required",
+        summary: "A manual stage needs approval",
+        explanation: "The stage is marked 'manual' and there is no terminal to ask on.\n\n\
+            Approve it with --approve <STAGE>, approve all with --yes, or set CONFLOW_APPROVE \
+            in CI.",
+    },
+    ErrorCode {
+        code: "CF0014",
+        name: "run_locked",
+        summary: "Another conflow run holds the project lock",
+        explanation: "Only one run per project may execute at a time, and the other run \
+            didn't finish within --lock-timeout.\n\nWait for it, raise --lock-timeout, or pass \
+            --no-lock if you are sure no other run is active.",
+    },
+    ErrorCode {
+        code: "CF0015",
+        name: "file_not_found",
+        summary: "A file the pipeline refers to doesn't exist",
+        explanation: "A stage input, schema or referenced file is missing.\n\nCheck the path \
+            relative to the pipeline's directory; 'conflow validate' lists missing files.",
+    },
+    ErrorCode {
+        code: "CF0016",
+        name: "file_read_error",
+        summary: "A file could not be read",
+        explanation: "The file exists but reading it failed, usually because of \
+            permissions.\n\nThe message includes the operating system's error.",
+    },
+    ErrorCode {
+        code: "CF0017",
+        name: "file_write_error",
+        summary: "A file could not be written",
+        explanation: "conflow could not write an output, cache or state file.\n\nCheck \
+            permissions and free space for the path in the message.",
+    },
+    ErrorCode {
+        code: "CF0018",
+        name: "no_input_files",
+        summary: "An input pattern matched no files",
+        explanation: "A stage's 'input' glob matched nothing, so the stage has nothing to \
+            check.\n\nCheck the pattern against the files in your project.",
+    },
+    ErrorCode {
+        code: "CF0019",
+        name: "cache_error",
+        summary: "The cache could not be read or written",
+        explanation: "A cache entry, index or timing file failed to load or save.\n\nRun \
+            'conflow cache verify --repair', or 'conflow cache clear' to start over.",
+    },
+    ErrorCode {
+        code: "CF0020",
+        name: "cue_validation_failed",
+        summary: "CUE validation failed",
+        explanation: "A value doesn't satisfy its CUE constraint. CUE unifies values with \
+            their constraints, and fails when they conflict.\n\nThe message shows the file, \
+            position and the conflicting values.",
+    },
+    ErrorCode {
+        code: "CF0021",
+        name: "nickel_type_error",
+        summary: "Nickel reported a type or contract error",
+        explanation: "A Nickel value doesn't match its type annotation or contract.\n\nCheck \
+            the annotation at the reported location, and the types of function arguments.",
+    },
+    ErrorCode {
+        code: "CF0022",
+        name: "check_failed",
+        summary: "A check failed",
+        explanation: "The pipeline, its validation or a compliance check failed. The details \
+            are printed above the error.\n\nExits with status 3.",
+    },
+    ErrorCode {
+        code: "CF0023",
+        name: "warnings_found",
+        summary: "Warnings fail the check",
+        explanation: "Only warnings were found, and --fail-on warning makes them fail the \
+            check.\n\nFix the warnings, or use --fail-on error. Exits with status 6.",
+    },
+    ErrorCode {
+        code: "CF0024",
+        name: "compliance_regressed",
+        summary: "Compliance got worse than on the base ref",
+        explanation: "Requirements that pass on --base fail in the working tree.\n\nFix the \
+            listed requirements; 'conflow rsr requirements --id <ID>' shows how. Exits with \
+            status 5.",
+    },
+    ErrorCode {
+        code: "CF0025",
+        name: "format_detection_failed",
+        summary: "The file's format could not be detected",
+        explanation: "conflow recognizes JSON, YAML, TOML, CUE and Nickel by extension and \
+            content.\n\nRename the file with its usual extension.",
+    },
+    ErrorCode {
+        code: "CF0026",
+        name: "unsupported_schema",
+        summary: "The schema file type is not supported",
+        explanation: "Schemas can be CUE files (.cue) or JSON Schema (.json).",
+    },
+    ErrorCode {
+        code: "CF0027",
+        name: "io_error",
+        summary: "An operating system error occurred",
+        explanation: "A file system or process operation failed.\n\nThe message includes \
+            the operating system's error.",
+    },
+    ErrorCode {
+        code: "CF0028",
+        name: "yaml_error",
+        summary: "YAML could not be parsed or written",
+        explanation: "A YAML document is malformed.\n\nThe message gives the line and \
+            column.",
+    },
+    ErrorCode {
+        code: "CF0029",
+        name: "json_error",
+        summary: "JSON could not be parsed or written",
+        explanation: "A JSON document is malformed, or a state file is damaged.\n\nThe \
+            message gives the line and column.",
+    },
+    ErrorCode {
+        code: "CF0030",
+        name: "toml_error",
+        summary: "TOML could not be parsed",
+        explanation: "A TOML document is malformed.\n\nThe message gives the location.",
+    },
+    ErrorCode {
+        code: "CF0031",
+        name: "glob_error",
+        summary: "A glob pattern is invalid",
+        explanation: "An 'input' or 'output' pattern isn't valid glob syntax, for example an \
+            unclosed '['.",
+    },
+    ErrorCode {
+        code: "CF0032",
+        name: "git_error",
+        summary: "A git command failed",
+        explanation: "Features such as --since, --base and PR comments read the git \
+            repository by running the git CLI, so git must be installed and on PATH.\n\nCheck \
+            that you are inside a repository and that the ref exists; in CI, fetch enough \
+            history.",
+    },
+    ErrorCode {
+        code: "CF0033",
+        name: "telemetry_error",
+        summary: "Traces could not be exported",
+        explanation: "The OTLP endpoint rejected or didn't answer the export.\n\nCheck \
+            OTEL_EXPORTER_OTLP_ENDPOINT and that the collector is running.",
+    },
+    ErrorCode {
+        code: "CF0034",
+        name: "notification_failed",
+        summary: "A notification could not be sent",
+        explanation: "A configured notification channel rejected the message or couldn't \
+            be reached.\n\nCheck the channel's URL and credentials.",
+    },
+    ErrorCode {
+        code: "CF0035",
+        name: "pr_comment_failed",
+        summary: "The PR comment could not be posted",
+        explanation: "--pr-comment needs the CI's PR metadata and a token with permission \
+            to comment (GITHUB_TOKEN or GITLAB_TOKEN).",
+    },
+    ErrorCode {
+        code: "CF0036",
+        name: "plugin_error",
+        summary: "A plugin failed",
+        explanation: "A WASM plugin failed to load or returned an error.\n\nRun 'conflow \
+            plugin list' to check it is installed and compatible.",
+    },
+    ErrorCode {
+        code: "CF0037",
+        name: "sandbox_unavailable",
+        summary: "The sandbox is not available",
+        explanation: "--sandbox needs bubblewrap (bwrap) on Linux.\n\nInstall it, or run \
+            without --sandbox.",
+    },
+    ErrorCode {
+        code: "CF0038",
+        name: "toolchain_error",
+        summary: "A pinned tool could not be installed",
+        explanation: "Downloading, verifying or unpacking a pinned tool version failed.\n\n\
+            Check the version exists and your network access.",
+    },
+    ErrorCode {
+        code: "CF0039",
+        name: "requirement_cycle",
+        summary: "RSR requirements depend on each other in a cycle",
+        explanation: "A requirement's 'requires' list names the requirements that must be met \
+            before it is checked. These lists form a loop, so none of the requirements in it \
+            can be checked first.\n\nRemove one of the entries to break the cycle.",
+    },
+    ErrorCode {
+        code: "CF0040",
+        name: "profile_error",
+        summary: "An RSR profile could not be fetched or loaded",
+        explanation: "A profile listed under 'profiles:' in .rsr.yaml was not cached, could \
+            not be cloned or downloaded, failed its sha256 check, or its profile.yaml does \
+            not match the requested org/name@version.\n\nRun 'conflow rsr profiles --update' \
+            to fetch it again.",
+    },
+    ErrorCode {
+        code: "CF0041",
+        name: "template_error",
+        summary: "An RSR template could not be rendered",
+        explanation: "A template used a variable that is not set, an unknown filter, or an \
+            'if'/'for' block that is not closed. The error names the line of the template \
+            file.\n\nPass variables with 'conflow rsr template render <name> --set key=value', \
+            or give them a fallback with {{ key | default(\"value\") }}.",
+    },
+    ErrorCode {
+        code: "CF0042",
+        name: "violations_introduced",
+        summary: "Changes introduced new violations",
+        explanation: "'conflow gitops check' found violations at --to that were not there at \
+            --from, in the configs changed between them.\n\nFix the listed violations; older \
+            ones can be fixed separately. Exits with status 3.",
+    },
+    ErrorCode {
+        code: "CF0043",
+        name: "symlink_rejected",
+        summary: "A symbolic link was found with 'walk.symlinks: error'",
+        explanation: "The pipeline's 'walk:' section rejects symbolic links, and a command \
+            walking the project met one.\n\nRemove the link, list it in .conflowignore, or \
+            set 'walk.symlinks' to 'follow' or 'skip'. Exits with status 3.",
+    },
+    ErrorCode {
+        code: "CF0044",
+        name: "offline",
+        summary: "A network request was refused in offline mode",
+        explanation: "With --offline or CONFLOW_OFFLINE set, conflow makes no network \
+            requests, and the command needed one: a toolchain download, a profile or \
+            template fetch, a git clone, a notification, a webhook or a telemetry \
+            export. The error lists them.\n\nRun 'conflow bundle create' on a machine \
+            that can reach them, carry the bundle over and run 'conflow bundle load', \
+            or run without --offline.",
+    },
+    ErrorCode {
+        code: "CF0045",
+        name: "undo_conflict",
+        summary: "Files an action changed have changed again since",
+        explanation: "'conflow undo' puts files back as they were before an action, \
+            but only when they still hold what the action wrote: undoing would \
+            otherwise throw away the later edits. The error lists the files that \
+            differ.\n\nCompare them with the digests 'conflow audit --id <ID>' \
+            shows, then pass --force to put them back anyway.",
+    },
+    ErrorCode {
+        code: "CF0046",
+        name: "release_not_ready",
+        summary: "A release readiness gate failed",
+        explanation: "'conflow release check' passes only when every gate does: the \
+            version is bumped alike in every manifest, the CHANGELOG has an entry for \
+            it, JSON Schemas still accept what they did at the previous release, RSR \
+            compliance meets compliance.target_level and the latest pipeline run \
+            passed.\n\nFix what the failed gates list and check again. Exits with \
+            status 3.",
+    },
+    ErrorCode {
+        code: "CF0047",
+        name: "version_mismatch",
+        summary: "Files that should share a version don't",
+        explanation: "The versions: section of .conflow.yaml lists files and JSONPath \
+            locations that hold the project's version, and they hold different ones. \
+            Without versions:, the version fields of Cargo.toml, package.json, \
+            pyproject.toml and Chart.yaml are compared.\n\nRun 'conflow version bump \
+            <VERSION>' to set them all at once. Exits with status 3.",
+    },
+    ErrorCode {
+        code: "CF0048",
+        name: "version_location",
+        summary: "A version location can't be read or rewritten",
+        explanation: "A location under versions: names a file that doesn't exist or \
+            doesn't parse, a JSONPath that is invalid or matches nothing, or a value \
+            that isn't a version string. 'conflow version bump' also refuses a \
+            location it can't rewrite in place, keeping the file's formatting, and then \
+            changes no file at all.\n\nFix the file or its path under versions:.",
+    },
+    ErrorCode {
+        code: "CF0049",
+        name: "dispatch",
+        summary: "A stage couldn't be run on a remote agent",
+        explanation: "Stages with runs_on labels run on conflow agents: 'conflow run \
+            --coordinator <URL>' asks the coordinator ('conflow serve --grpc') for a live \
+            agent offering every label, sends it the stage's files and fetches its \
+            outputs. The error says which step failed: no coordinator was given, no \
+            agent offers the labels, or the agent couldn't be reached or run the \
+            stage.\n\nStart 'conflow agent --label <LABEL> --coordinator <URL>' where \
+            the stage should run, and check 'conflow agent' logs for the details.",
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_codes_match_error_variants() {
+        // Every code used on a variant is documented, in order and only once
+        let source = include_str!("mod.rs");
+        let used: Vec<&str> = source
+            .match_indices("code(CF")
+            .map(|(i, _)| &source[i + 5..i + 11])
+            .collect();
+        let documented: Vec<&str> = ERROR_CODES.iter().map(|e| e.code).collect();
+        assert_eq!(used, documented);
+
+        let names: HashSet<_> = ERROR_CODES.iter().map(|e| e.name).collect();
+        assert_eq!(names.len(), ERROR_CODES.len());
+    }
+
+    #[test]
+    fn test_find_code() {
+        assert_eq!(ErrorCode::find("CF0011").unwrap().name, "stage_failed");
+        assert_eq!(ErrorCode::find("cf11").unwrap().name, "stage_failed");
+        assert_eq!(ErrorCode::find("stage_failed").unwrap().code, "CF0011");
+        assert!(ErrorCode::find("CF9999").is_none());
+        assert!(ErrorCode::find("nonsense").is_none());
+
+        let error = ConflowError::RunLocked {
+            path: "run.lock".into(),
+            seconds: 5,
+        };
+        assert_eq!(error.error_code().unwrap().code, "CF0014");
+
+        let report = miette::Report::new(error);
+        let json = report_json(&report, ExitCode::of_report(&report));
+        assert_eq!(json["code"], "CF0014");
+        assert_eq!(json["name"], "run_locked");
+        assert_eq!(json["exit_code"], 1);
+    }
+}
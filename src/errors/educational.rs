@@ -8,6 +8,8 @@
 
 use std::path::Path;
 
+use crate::i18n::{tr, tr_with};
+
 /// Educational message with explanation and examples
 #[derive(Debug, Clone)]
 pub struct EducationalMessage {
@@ -29,12 +31,10 @@ impl EducationalMessage {
         got: &str,
     ) -> Self {
         Self {
-            summary: format!("Constraint violation on field '{}'", field),
-            explanation: format!(
-                "The value '{}' does not satisfy the constraint '{}'.\n\n\
-                 CUE uses unification to merge values with constraints.\n\
-                 When a value doesn't match its constraint, validation fails.",
-                got, expected
+            summary: tr_with("explain.constraint.summary", &[("field", &field)]),
+            explanation: tr_with(
+                "explain.constraint.explanation",
+                &[("got", &got), ("expected", &expected)],
             ),
             example: Some(format!(
                 "// Example constraint:\n\
@@ -52,12 +52,10 @@ impl EducationalMessage {
     /// Create a message for Nickel type error
     pub fn nickel_type_error(expected: &str, got: &str) -> Self {
         Self {
-            summary: "Type mismatch".into(),
-            explanation: format!(
-                "Expected type '{}' but got '{}'.\n\n\
-                 Nickel uses gradual typing: you can add type annotations\n\
-                 to catch errors early, or leave them out for flexibility.",
-                expected, got
+            summary: tr("explain.type.summary"),
+            explanation: tr_with(
+                "explain.type.explanation",
+                &[("expected", &expected), ("got", &got)],
             ),
             example: Some(
                 "// With type annotation:\n\
@@ -85,13 +83,14 @@ impl EducationalMessage {
         };
 
         Self {
-            summary: format!("Tool '{}' not found", tool),
-            explanation: format!(
-                "conflow requires '{}' to be installed and available in your PATH.\n\n\
-                 This tool is used for configuration {} and must be installed\n\
-                 before running pipelines that use it.",
-                tool,
-                if tool == "cue" { "validation" } else { "generation" }
+            summary: tr_with("explain.tool.summary", &[("tool", &tool)]),
+            explanation: tr_with(
+                if tool == "cue" {
+                    "explain.tool.validation"
+                } else {
+                    "explain.tool.generation"
+                },
+                &[("tool", &tool)],
             ),
             example: if !install_cmd.is_empty() {
                 Some(format!("# Install {}:\n{}", tool, install_cmd))
@@ -108,34 +107,18 @@ impl EducationalMessage {
 
     /// Create a message explaining CUE vs Nickel choice
     pub fn tool_choice_explanation(file: &Path, recommended: &str) -> Self {
-        let extension = file
-            .extension()
-            .and_then(|e| e.to_str())
-            .unwrap_or("unknown");
-
-        let (explanation, alternative) = match recommended {
-            "cue" => (
-                "CUE is recommended because:\n\
-                 • Your config uses constraint validation\n\
-                 • No complex logic or functions detected\n\
-                 • CUE's unification semantics fit well",
-                "If you need to generate configs programmatically,\n\
-                 consider using Nickel for generation + CUE for validation.",
-            ),
-            "nickel" => (
-                "Nickel is recommended because:\n\
-                 • Complex logic detected (functions, conditionals)\n\
-                 • Config generation patterns found\n\
-                 • Full programming language features needed",
-                "After generating with Nickel, you can pipe\n\
-                 the output to CUE for constraint validation.",
-            ),
-            _ => ("", ""),
+        let explanation = match recommended {
+            "cue" => tr("explain.choice.cue"),
+            "nickel" => tr("explain.choice.nickel"),
+            _ => String::new(),
         };
 
         Self {
-            summary: format!("Recommendation: Use {} for {}", recommended, file.display()),
-            explanation: explanation.into(),
+            summary: tr_with(
+                "explain.choice.summary",
+                &[("tool", &recommended), ("file", &file.display())],
+            ),
+            explanation,
             example: None,
             docs_url: Some("https://conflow.dev/docs/tool-selection/".into()),
         }
@@ -150,14 +133,14 @@ impl std::fmt::Display for EducationalMessage {
 
         if let Some(ref example) = self.example {
             writeln!(f)?;
-            writeln!(f, "Example:")?;
+            writeln!(f, "{}", tr("explain.example"))?;
             writeln!(f, "────────")?;
             writeln!(f, "{}", example)?;
         }
 
         if let Some(ref url) = self.docs_url {
             writeln!(f)?;
-            writeln!(f, "Learn more: {}", url)?;
+            writeln!(f, "{}", tr_with("explain.learn_more", &[("url", url)]))?;
         }
 
         Ok(())
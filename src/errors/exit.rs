@@ -0,0 +1,182 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Exit codes
+//!
+//! CI jobs branch on conflow's exit status, so each kind of failure has its
+//! own code. The values are part of the command-line interface: they are
+//! never renumbered or reused.
+
+use super::ConflowError;
+use clap::builder::{PossibleValuesParser, TypedValueParser};
+use clap::ValueEnum;
+
+/// Process exit status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum ExitCode {
+    /// Everything passed
+    Success = 0,
+    /// conflow itself failed (I/O, git, plugins, ...)
+    Internal = 1,
+    /// The command line was invalid
+    Usage = 2,
+    /// A pipeline, config or compliance check failed
+    ValidationFailed = 3,
+    /// A required tool is not installed
+    ToolMissing = 4,
+    /// Compliance is worse than on the baseline
+    ComplianceRegression = 5,
+    /// Only warnings were found, with `--fail-on warning`
+    Warnings = 6,
+}
+
+impl ExitCode {
+    /// Numeric exit status
+    pub fn code(self) -> u8 {
+        self as u8
+    }
+
+    /// Exit status for an error
+    pub fn of(error: &ConflowError) -> Self {
+        match error {
+            ConflowError::ToolNotFound { .. } | ConflowError::ExecutorNotFound { .. } => {
+                ExitCode::ToolMissing
+            }
+            ConflowError::PipelineNotFound { .. }
+            | ConflowError::InvalidPipeline { .. }
+            | ConflowError::ConfigParse { .. }
+            | ConflowError::InvalidStage { .. }
+            | ConflowError::CircularDependency { .. }
+            | ConflowError::UnknownDependency { .. }
+            | ConflowError::StageNotFound { .. }
+            | ConflowError::StageFailed { .. }
+            | ConflowError::CueValidationFailed { .. }
+            | ConflowError::NickelTypeError { .. }
+            | ConflowError::CheckFailed { .. }
+            | ConflowError::RequirementCycle { .. }
+            | ConflowError::Template { .. }
+            | ConflowError::ViolationsIntroduced { .. }
+            | ConflowError::SymlinkRejected { .. }
+            | ConflowError::ReleaseNotReady { .. }
+            | ConflowError::VersionMismatch { .. }
+            | ConflowError::VersionLocation { .. } => ExitCode::ValidationFailed,
+            ConflowError::WarningsFound { .. } => ExitCode::Warnings,
+            ConflowError::ComplianceRegressed { .. } => ExitCode::ComplianceRegression,
+            _ => ExitCode::Internal,
+        }
+    }
+
+    /// Exit status for a reported error; untyped reports count as internal
+    pub fn of_report(report: &miette::Report) -> Self {
+        report
+            .downcast_ref::<ConflowError>()
+            .map(Self::of)
+            .unwrap_or(ExitCode::Internal)
+    }
+}
+
+impl From<ExitCode> for std::process::ExitCode {
+    fn from(code: ExitCode) -> Self {
+        std::process::ExitCode::from(code.code())
+    }
+}
+
+/// Lowest severity that makes a check fail
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum FailOn {
+    /// Fail only on compliance that got worse than on `--base`
+    Regression,
+    /// Fail on errors; warnings pass
+    #[default]
+    Error,
+    /// Fail on errors and warnings
+    Warning,
+}
+
+impl FailOn {
+    /// Whether errors that were already there on the baseline fail the check
+    pub fn fails_on_errors(self) -> bool {
+        self != FailOn::Regression
+    }
+
+    /// Whether warnings fail the check
+    pub fn fails_on_warnings(self) -> bool {
+        self == FailOn::Warning
+    }
+
+    /// `--fail-on` parser for commands with no baseline to regress from,
+    /// which accept only `error` and `warning`
+    pub fn severities() -> impl TypedValueParser<Value = FailOn> {
+        let values = FailOn::value_variants()
+            .iter()
+            .filter(|fail_on| **fail_on != FailOn::Regression)
+            .filter_map(ValueEnum::to_possible_value);
+        PossibleValuesParser::new(values)
+            .map(|name| FailOn::from_str(&name, false).expect("a possible value"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_codes() {
+        // These values are documented and relied on by CI scripts
+        let codes = [
+            (ExitCode::Success, 0),
+            (ExitCode::Internal, 1),
+            (ExitCode::Usage, 2),
+            (ExitCode::ValidationFailed, 3),
+            (ExitCode::ToolMissing, 4),
+            (ExitCode::ComplianceRegression, 5),
+            (ExitCode::Warnings, 6),
+        ];
+        for (exit, code) in codes {
+            assert_eq!(exit.code(), code);
+        }
+
+        assert_eq!(
+            ExitCode::of(&ConflowError::tool_not_found("cue")),
+            ExitCode::ToolMissing
+        );
+        let failed = ConflowError::StageFailed {
+            stage: "validate".into(),
+            stderr: String::new(),
+            help: None,
+        };
+        assert_eq!(ExitCode::of(&failed), ExitCode::ValidationFailed);
+        let regressed = miette::Report::new(ConflowError::ComplianceRegressed {
+            base: "origin/main".into(),
+            requirements: vec!["RSR-CONFIG-001".into()],
+        });
+        assert_eq!(
+            ExitCode::of_report(&regressed),
+            ExitCode::ComplianceRegression
+        );
+        assert_eq!(
+            ExitCode::of_report(&miette::miette!("untyped")),
+            ExitCode::Internal
+        );
+    }
+
+    #[test]
+    fn test_severities_reject_regression() {
+        let command = clap::Command::new("conflow").arg(
+            clap::Arg::new("fail-on")
+                .long("fail-on")
+                .value_parser(FailOn::severities()),
+        );
+        let parse = |value: &str| {
+            command
+                .clone()
+                .try_get_matches_from(["conflow", "--fail-on", value])
+                .map(|matches| *matches.get_one::<FailOn>("fail-on").unwrap())
+        };
+
+        assert_eq!(parse("error").unwrap(), FailOn::Error);
+        assert_eq!(parse("warning").unwrap(), FailOn::Warning);
+        assert!(parse("regression").is_err());
+    }
+}
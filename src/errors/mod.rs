@@ -6,10 +6,14 @@
 //! conflow provides helpful, educational error messages that guide users
 //! toward solutions rather than leaving them confused.
 
+mod codes;
 mod educational;
+mod exit;
 mod recovery;
 
+pub use codes::{report_json, ErrorCode, ERROR_CODES};
 pub use educational::EducationalMessage;
+pub use exit::{ExitCode, FailOn};
 pub use recovery::RecoverySuggestion;
 
 use miette::Diagnostic;
@@ -27,7 +31,7 @@ pub enum ConflowError {
     // ─────────────────────────────────────────────────────────────────────────
     #[error("Tool '{tool}' not found")]
     #[diagnostic(
-        code(conflow::tool_not_found),
+        code(CF0001),
         help("{suggestion}")
     )]
     ToolNotFound {
@@ -36,7 +40,7 @@ pub enum ConflowError {
     },
 
     #[error("Tool '{tool}' execution failed: {error}")]
-    #[diagnostic(code(conflow::tool_execution_failed))]
+    #[diagnostic(code(CF0002))]
     ToolExecutionFailed {
         tool: String,
         error: String,
@@ -46,7 +50,7 @@ pub enum ConflowError {
 
     #[error("Executor not found for tool: {tool}")]
     #[diagnostic(
-        code(conflow::executor_not_found),
+        code(CF0003),
         help("Available executors: cue, nickel, shell")
     )]
     ExecutorNotFound { tool: String },
@@ -56,46 +60,59 @@ pub enum ConflowError {
     // ─────────────────────────────────────────────────────────────────────────
     #[error("Pipeline file not found: {path}")]
     #[diagnostic(
-        code(conflow::pipeline_not_found),
+        code(CF0004),
         help("Create a pipeline with 'conflow init' or create .conflow.yaml manually")
     )]
     PipelineNotFound { path: PathBuf },
 
     #[error("Invalid pipeline configuration: {reason}")]
-    #[diagnostic(code(conflow::invalid_pipeline))]
+    #[diagnostic(code(CF0005))]
     InvalidPipeline {
         reason: String,
         #[help]
         help: Option<String>,
     },
 
+    #[error("Invalid {file}: {message}")]
+    #[diagnostic(code(CF0006))]
+    ConfigParse {
+        file: String,
+        message: String,
+        #[source_code]
+        source_code: std::sync::Arc<miette::NamedSource<String>>,
+        #[label("here")]
+        span: Option<miette::SourceSpan>,
+        #[help]
+        help: Option<String>,
+    },
+
     #[error("Stage '{stage}' is invalid: {reason}")]
-    #[diagnostic(code(conflow::invalid_stage))]
+    #[diagnostic(code(CF0007))]
     InvalidStage { stage: String, reason: String },
 
     #[error("Circular dependency detected")]
     #[diagnostic(
-        code(conflow::circular_dependency),
+        code(CF0008),
         help("Review your stage dependencies to remove the cycle")
     )]
     CircularDependency { stages: Vec<String> },
 
     #[error("Stage '{stage}' depends on unknown stage '{dependency}'")]
     #[diagnostic(
-        code(conflow::unknown_dependency),
+        code(CF0009),
         help("Check that '{dependency}' is defined in your pipeline")
     )]
     UnknownDependency { stage: String, dependency: String },
 
     #[error("Stage '{stage}' not found in pipeline")]
-    #[diagnostic(code(conflow::stage_not_found))]
+    #[diagnostic(code(CF0010))]
     StageNotFound { stage: String },
 
     // ─────────────────────────────────────────────────────────────────────────
     // Execution Errors
     // ─────────────────────────────────────────────────────────────────────────
     #[error("Stage '{stage}' failed")]
-    #[diagnostic(code(conflow::stage_failed))]
+    #[diagnostic(code(CF0011))]
     StageFailed {
         stage: String,
         stderr: String,
@@ -104,18 +121,33 @@ pub enum ConflowError {
     },
 
     #[error("Execution failed: {message}")]
-    #[diagnostic(code(conflow::execution_failed))]
+    #[diagnostic(code(CF0012))]
     ExecutionFailed {
         message: String,
         #[help]
         help: Option<String>,
     },
 
+    #[error("Stage '{stage}' requires approval")]
+    #[diagnostic(code(CF0013))]
+    ApprovalRequired {
+        stage: String,
+        #[help]
+        help: Option<String>,
+    },
+
+    #[error("Another conflow run is still holding {path} after {seconds}s")]
+    #[diagnostic(
+        code(CF0014),
+        help("Wait for the other run to finish, or pass --no-lock if no run is active")
+    )]
+    RunLocked { path: PathBuf, seconds: u64 },
+
     // ─────────────────────────────────────────────────────────────────────────
     // File Errors
     // ─────────────────────────────────────────────────────────────────────────
     #[error("File not found: {path}")]
-    #[diagnostic(code(conflow::file_not_found))]
+    #[diagnostic(code(CF0015))]
     FileNotFound {
         path: PathBuf,
         #[help]
@@ -123,16 +155,16 @@ pub enum ConflowError {
     },
 
     #[error("Failed to read file '{path}': {error}")]
-    #[diagnostic(code(conflow::file_read_error))]
+    #[diagnostic(code(CF0016))]
     FileReadError { path: PathBuf, error: String },
 
     #[error("Failed to write file '{path}': {error}")]
-    #[diagnostic(code(conflow::file_write_error))]
+    #[diagnostic(code(CF0017))]
     FileWriteError { path: PathBuf, error: String },
 
     #[error("No input files matched pattern: {pattern}")]
     #[diagnostic(
-        code(conflow::no_input_files),
+        code(CF0018),
         help("Check that files matching '{pattern}' exist in your project")
     )]
     NoInputFiles { pattern: String },
@@ -141,14 +173,14 @@ pub enum ConflowError {
     // Cache Errors
     // ─────────────────────────────────────────────────────────────────────────
     #[error("Cache error: {message}")]
-    #[diagnostic(code(conflow::cache_error))]
+    #[diagnostic(code(CF0019))]
     CacheError { message: String },
 
     // ─────────────────────────────────────────────────────────────────────────
     // Validation Errors
     // ─────────────────────────────────────────────────────────────────────────
     #[error("CUE validation failed")]
-    #[diagnostic(code(conflow::cue_validation_failed))]
+    #[diagnostic(code(CF0020))]
     CueValidationFailed {
         file: PathBuf,
         line: Option<usize>,
@@ -159,7 +191,7 @@ pub enum ConflowError {
     },
 
     #[error("Nickel type error")]
-    #[diagnostic(code(conflow::nickel_type_error))]
+    #[diagnostic(code(CF0021))]
     NickelTypeError {
         file: PathBuf,
         message: String,
@@ -167,38 +199,224 @@ pub enum ConflowError {
         help: Option<String>,
     },
 
+    // ─────────────────────────────────────────────────────────────────────────
+    // Check Results
+    // ─────────────────────────────────────────────────────────────────────────
+    #[error("{message}")]
+    #[diagnostic(code(CF0022))]
+    CheckFailed { message: String },
+
+    #[error("{count} warning(s) found")]
+    #[diagnostic(
+        code(CF0023),
+        help("Warnings fail the check because of --fail-on warning")
+    )]
+    WarningsFound { count: usize },
+
+    #[error("Compliance regressed against {base}: {}", requirements.join(", "))]
+    #[diagnostic(
+        code(CF0024),
+        help("These requirements pass on {base} but fail now")
+    )]
+    ComplianceRegressed {
+        base: String,
+        requirements: Vec<String>,
+    },
+
     // ─────────────────────────────────────────────────────────────────────────
     // Analysis Errors
     // ─────────────────────────────────────────────────────────────────────────
     #[error("Could not detect configuration format for: {path}")]
     #[diagnostic(
-        code(conflow::format_detection_failed),
+        code(CF0025),
         help("Supported formats: JSON, YAML, TOML, CUE, Nickel")
     )]
     FormatDetectionFailed { path: PathBuf },
 
+    #[error("Unsupported schema file: {path}")]
+    #[diagnostic(
+        code(CF0026),
+        help("Schemas can be CUE files (.cue) or JSON Schema (.json)")
+    )]
+    UnsupportedSchema { path: PathBuf },
+
     // ─────────────────────────────────────────────────────────────────────────
     // IO/System Errors
     // ─────────────────────────────────────────────────────────────────────────
     #[error("IO error: {message}")]
-    #[diagnostic(code(conflow::io_error))]
+    #[diagnostic(code(CF0027))]
     Io { message: String },
 
     #[error("YAML parsing error: {message}")]
-    #[diagnostic(code(conflow::yaml_error))]
+    #[diagnostic(code(CF0028))]
     Yaml { message: String },
 
     #[error("JSON parsing error: {message}")]
-    #[diagnostic(code(conflow::json_error))]
+    #[diagnostic(code(CF0029))]
     Json { message: String },
 
     #[error("TOML parsing error: {message}")]
-    #[diagnostic(code(conflow::toml_error))]
+    #[diagnostic(code(CF0030))]
     Toml { message: String },
 
     #[error("Glob pattern error: {message}")]
-    #[diagnostic(code(conflow::glob_error))]
+    #[diagnostic(code(CF0031))]
     GlobPattern { message: String },
+
+    #[error("Git error: {message}")]
+    #[diagnostic(
+        code(CF0032),
+        help("Change detection runs the git CLI: install git and run inside a repository with a valid base ref")
+    )]
+    Git { message: String },
+
+    #[error("Telemetry export failed: {message}")]
+    #[diagnostic(
+        code(CF0033),
+        help("Check the OTLP endpoint (OTEL_EXPORTER_OTLP_ENDPOINT or telemetry.endpoint in .conflow.yaml)")
+    )]
+    Telemetry { message: String },
+
+    #[error("Notification failed: {message}")]
+    #[diagnostic(
+        code(CF0034),
+        help("Check the webhook URL under 'notifications:' in .conflow.yaml")
+    )]
+    Notification { message: String },
+
+    #[error("PR comment failed: {message}")]
+    #[diagnostic(code(CF0035))]
+    PrComment {
+        message: String,
+        #[help]
+        help: Option<String>,
+    },
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // Plugin Errors
+    // ─────────────────────────────────────────────────────────────────────────
+    #[error("Plugin '{plugin}' failed: {message}")]
+    #[diagnostic(code(CF0036))]
+    Plugin {
+        plugin: String,
+        message: String,
+        #[help]
+        help: Option<String>,
+    },
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // Sandbox Errors
+    // ─────────────────────────────────────────────────────────────────────────
+    #[error("Sandbox unavailable: {message}")]
+    #[diagnostic(code(CF0037))]
+    Sandbox {
+        message: String,
+        #[help]
+        help: Option<String>,
+    },
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // Toolchain Errors
+    // ─────────────────────────────────────────────────────────────────────────
+    #[error("Toolchain error for '{tool}': {message}")]
+    #[diagnostic(code(CF0038))]
+    Toolchain {
+        tool: String,
+        message: String,
+        #[help]
+        help: Option<String>,
+    },
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // RSR Errors
+    // ─────────────────────────────────────────────────────────────────────────
+    #[error("RSR requirements require each other: {}", requirements.join(", "))]
+    #[diagnostic(
+        code(CF0039),
+        help("Remove one of the 'requires' entries to break the cycle")
+    )]
+    RequirementCycle { requirements: Vec<String> },
+
+    #[error("Profile '{profile}' {message}")]
+    #[diagnostic(code(CF0040))]
+    Profile {
+        profile: String,
+        message: String,
+        #[help]
+        help: Option<String>,
+    },
+
+    #[error("Template '{template}': {message}")]
+    #[diagnostic(code(CF0041))]
+    Template {
+        template: String,
+        message: String,
+        #[help]
+        help: Option<String>,
+    },
+
+    #[error("{count} new violation(s) since {from}")]
+    #[diagnostic(
+        code(CF0042),
+        help("Violations that already existed at {from} do not fail the check")
+    )]
+    ViolationsIntroduced { from: String, count: usize },
+
+    #[error("Symbolic link found while walking the project: {path}")]
+    #[diagnostic(
+        code(CF0043),
+        help("Set 'walk.symlinks' to 'follow' or 'skip' in .conflow.yaml to allow links")
+    )]
+    SymlinkRejected { path: PathBuf },
+
+    #[error("Offline mode forbids network access: {}", requests.join(", "))]
+    #[diagnostic(
+        code(CF0044),
+        help("Run 'conflow bundle create' where the network is reachable and 'conflow bundle load' here, or drop --offline")
+    )]
+    Offline { requests: Vec<String> },
+
+    #[error("Files changed since action #{id}: {}", files.join(", "))]
+    #[diagnostic(
+        code(CF0045),
+        help("Check the changes with 'conflow audit --id {id}', then pass --force to put the files back anyway")
+    )]
+    UndoConflict { id: u64, files: Vec<String> },
+
+    #[error("Not ready to release: {} failed", gates.join(", "))]
+    #[diagnostic(
+        code(CF0046),
+        help("Fix what the failed gates list, then run 'conflow release check' again")
+    )]
+    ReleaseNotReady { gates: Vec<String> },
+
+    #[error("Versions differ: {}", versions.join(", "))]
+    #[diagnostic(
+        code(CF0047),
+        help("Run 'conflow version bump <VERSION>' to set every location to one version")
+    )]
+    VersionMismatch { versions: Vec<String> },
+
+    #[error("Version at {path} in {file}: {message}")]
+    #[diagnostic(
+        code(CF0048),
+        help("Check the file and its JSONPath under versions: in .conflow.yaml")
+    )]
+    VersionLocation {
+        file: PathBuf,
+        path: String,
+        message: String,
+    },
+
+    #[error("Stage '{stage}' can't run on an agent: {message}")]
+    #[diagnostic(
+        code(CF0049),
+        help(
+            "Start 'conflow agent --label <LABEL> --coordinator <URL>' with the stage's \
+             runs_on labels, and run with --coordinator <URL>"
+        )
+    )]
+    Dispatch { stage: String, message: String },
 }
 
 impl From<std::io::Error> for ConflowError {
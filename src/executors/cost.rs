@@ -0,0 +1,506 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Cost estimate executor
+//!
+//! Estimates what a change costs a month. Terraform stages price the plan
+//! JSON from `terraform show -json`, either a plan stage's saved plan or the
+//! stage inputs, with the pricing table or infracost. Kubernetes stages
+//! price the manifests' resource requests against the same files at the
+//! base ref. The estimate is recorded under `.conflow/costs/` for the PR
+//! comment.
+
+use async_trait::async_trait;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Output;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::process::Command;
+
+use super::{resolve_inputs, DeployExecutor, ExecutionResult, Executor, Sandbox};
+use crate::analyzer::{
+    estimate_infracost, estimate_kubernetes, estimate_terraform_plan, money, CostEstimate, Pricing,
+};
+use crate::cache::ParseCache;
+use crate::errors::ConflowError;
+use crate::pipeline::{CostSource, Stage, Tool};
+use crate::utils::git;
+
+/// Most changed resources listed in the stage output
+const MAX_RESOURCES: usize = 10;
+
+/// Executor for cost estimate stages
+pub struct CostExecutor {
+    /// terraform binary, for reading saved plans
+    terraform: Option<PathBuf>,
+    /// infracost binary, when installed
+    infracost: Option<PathBuf>,
+    /// Sandbox for commands, when enabled
+    sandbox: Option<Arc<Sandbox>>,
+}
+
+impl CostExecutor {
+    /// Create an executor using terraform and infracost from PATH, if there
+    pub fn new() -> Self {
+        Self {
+            terraform: which::which("terraform").ok(),
+            infracost: which::which("infracost").ok(),
+            sandbox: None,
+        }
+    }
+
+    /// Use a specific terraform binary
+    pub fn with_terraform(mut self, bin: PathBuf) -> Self {
+        self.terraform = Some(bin);
+        self
+    }
+
+    /// Run commands in a sandbox
+    pub fn sandboxed(mut self, sandbox: Option<Arc<Sandbox>>) -> Self {
+        self.sandbox = sandbox;
+        self
+    }
+
+    /// Where a stage saves the JSON of the plan it estimates
+    fn plan_json_path(working_dir: &Path, stage: &str) -> PathBuf {
+        CostEstimate::path(working_dir, stage).with_extension("plan.json")
+    }
+
+    /// Files to estimate: the plan stage's plan as JSON, or the inputs
+    fn inputs(
+        stage: &Stage,
+        working_dir: &Path,
+        resolved_inputs: Option<&[PathBuf]>,
+    ) -> Result<Vec<PathBuf>, ConflowError> {
+        if stage.tool.plan_stage().is_some() {
+            return Ok(vec![Self::plan_json_path(working_dir, &stage.name)]);
+        }
+        match resolved_inputs {
+            Some(resolved) => Ok(resolved.to_vec()),
+            None => resolve_inputs(stage, working_dir),
+        }
+    }
+
+    /// Command converting a plan stage's saved plan to JSON
+    fn show_command(&self, plan: &str, working_dir: &Path) -> Result<Command, ConflowError> {
+        let bin = self
+            .terraform
+            .as_ref()
+            .ok_or_else(|| ConflowError::tool_not_found("terraform"))?;
+        let mut cmd = Command::new(bin);
+        cmd.current_dir(working_dir)
+            .args(["show", "-json"])
+            .arg(DeployExecutor::tfplan_path(working_dir, plan));
+        Ok(cmd)
+    }
+
+    /// Command pricing a plan JSON file with infracost
+    fn infracost_command(&self, plan: &Path, working_dir: &Path) -> Result<Command, ConflowError> {
+        let bin = self
+            .infracost
+            .as_ref()
+            .ok_or_else(|| ConflowError::tool_not_found("infracost"))?;
+        let mut cmd = Command::new(bin);
+        cmd.current_dir(working_dir)
+            .args(["breakdown", "--format", "json", "--path"])
+            .arg(plan);
+        Ok(cmd)
+    }
+
+    /// Run a command with the stage environment, returning its stdout
+    async fn run(
+        &self,
+        tool: &str,
+        mut cmd: Command,
+        working_dir: &Path,
+        env: &HashMap<String, String>,
+    ) -> Result<String, ConflowError> {
+        cmd.envs(env);
+        if let Some(ref sandbox) = self.sandbox {
+            cmd = sandbox.wrap(cmd, working_dir)?;
+        }
+
+        tracing::debug!(command = ?super::argv(&cmd), "spawning {}", tool);
+        let output: Output = cmd
+            .output()
+            .await
+            .map_err(|e| ConflowError::ToolExecutionFailed {
+                tool: tool.to_string(),
+                error: e.to_string(),
+                help: Some(format!("Ensure {} is installed and accessible", tool)),
+            })?;
+        if !output.status.success() {
+            return Err(ConflowError::ToolExecutionFailed {
+                tool: tool.to_string(),
+                error: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+                help: None,
+            });
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
+    /// Estimate Terraform plans
+    async fn estimate_terraform(
+        &self,
+        plans: &[PathBuf],
+        infracost: bool,
+        pricing: &Pricing,
+        working_dir: &Path,
+        env: &HashMap<String, String>,
+    ) -> Result<CostEstimate, ConflowError> {
+        let mut estimate = CostEstimate {
+            previous: Some(0.0),
+            ..Default::default()
+        };
+        for plan in plans {
+            let next = if infracost {
+                let cmd = self.infracost_command(plan, working_dir)?;
+                let output = self.run("infracost", cmd, working_dir, env).await?;
+                estimate_infracost(&parse_json(plan, &output)?)
+            } else {
+                let content = read_file(plan).await?;
+                estimate_terraform_plan(&parse_json(plan, &content)?, pricing)
+            };
+            estimate.add(next);
+        }
+        Ok(estimate)
+    }
+
+    /// Estimate Kubernetes manifests against the same files at `base`
+    ///
+    /// Without a usable baseline the estimate has no previous cost, and the
+    /// reason is returned alongside it.
+    async fn estimate_kubernetes(
+        &self,
+        manifests: &[PathBuf],
+        base: &str,
+        pricing: &Pricing,
+        working_dir: &Path,
+    ) -> Result<(CostEstimate, Option<String>), ConflowError> {
+        let mut current = Vec::new();
+        let mut previous = Some(Vec::new());
+        let mut problem = None;
+
+        for path in manifests {
+            let content = read_file(path).await?;
+            current.extend(documents(path, &content)?);
+
+            let Some(before) = previous.as_mut() else {
+                continue;
+            };
+            let relative = path.strip_prefix(working_dir).unwrap_or(path);
+            match git::file_at(working_dir, base, relative) {
+                Ok(Some(content)) => before.extend(documents(path, &content).unwrap_or_default()),
+                Ok(None) => {}
+                Err(e) => {
+                    problem = Some(format!("No baseline to compare against: {}", e));
+                    previous = None;
+                }
+            }
+        }
+
+        let estimate = estimate_kubernetes(&current, previous.as_deref(), pricing);
+        Ok((estimate, problem))
+    }
+}
+
+impl Default for CostExecutor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Executor for CostExecutor {
+    async fn execute(
+        &self,
+        stage: &Stage,
+        working_dir: &Path,
+        env: &HashMap<String, String>,
+        resolved_inputs: Option<&[PathBuf]>,
+    ) -> Result<ExecutionResult, ConflowError> {
+        let start = Instant::now();
+        self.validate_stage(stage)?;
+        let Tool::Cost {
+            source,
+            plan,
+            infracost,
+            pricing,
+            base,
+            budget,
+        } = &stage.tool
+        else {
+            unreachable!("validated as a cost stage");
+        };
+
+        let pricing = match pricing {
+            Some(path) => Pricing::load(&working_dir.join(path))?,
+            None => Pricing::builtin(),
+        };
+
+        if let Some(plan) = plan {
+            let cmd = self.show_command(plan, working_dir)?;
+            let json = self.run("terraform", cmd, working_dir, env).await?;
+            write_file(
+                &Self::plan_json_path(working_dir, &stage.name),
+                json.as_bytes(),
+            )
+            .await?;
+        }
+        let inputs = Self::inputs(stage, working_dir, resolved_inputs)?;
+
+        let mut stderr = String::new();
+        let estimate = match source {
+            CostSource::Terraform => {
+                self.estimate_terraform(&inputs, *infracost, &pricing, working_dir, env)
+                    .await?
+            }
+            CostSource::Kubernetes => {
+                let base = base.clone().unwrap_or_else(default_base);
+                let (estimate, problem) = self
+                    .estimate_kubernetes(&inputs, &base, &pricing, working_dir)
+                    .await?;
+                stderr.extend(problem);
+                estimate
+            }
+        };
+
+        let json = serde_json::to_string_pretty(&estimate)?;
+        let path = CostEstimate::path(working_dir, &stage.name);
+        write_file(&path, json.as_bytes()).await?;
+        let mut outputs = vec![path];
+        if let Some(ref output) = stage.output {
+            let path = working_dir.join(output.path());
+            write_file(&path, json.as_bytes()).await?;
+            outputs.push(path);
+        }
+
+        let stdout = describe(&estimate);
+        let increase = estimate.delta().unwrap_or(estimate.monthly);
+        if let Some(budget) = budget.filter(|budget| increase > *budget) {
+            let mut result = ExecutionResult::failure(
+                format!(
+                    "Estimated increase of {}/mo exceeds the budget of {}/mo",
+                    money(increase),
+                    money(budget)
+                ),
+                1,
+                start.elapsed(),
+            );
+            result.stdout = stdout;
+            return Ok(result);
+        }
+
+        let mut result = ExecutionResult::success(stdout, start.elapsed(), outputs);
+        result.stderr = stderr;
+        Ok(result)
+    }
+
+    async fn check_available(&self) -> Result<bool, ConflowError> {
+        Ok(true)
+    }
+
+    async fn version(&self) -> Result<String, ConflowError> {
+        Ok(format!("built-in {}", env!("CARGO_PKG_VERSION")))
+    }
+
+    fn validate_stage(&self, stage: &Stage) -> Result<(), ConflowError> {
+        match &stage.tool {
+            Tool::Cost { .. } => Ok(()),
+            _ => Err(ConflowError::InvalidStage {
+                stage: stage.name.clone(),
+                reason: "Not a cost stage".to_string(),
+            }),
+        }
+    }
+
+    fn command_line(
+        &self,
+        stage: &Stage,
+        working_dir: &Path,
+        resolved_inputs: Option<&[PathBuf]>,
+    ) -> Result<Vec<String>, ConflowError> {
+        let Tool::Cost {
+            source,
+            plan,
+            infracost,
+            ..
+        } = &stage.tool
+        else {
+            return Err(ConflowError::InvalidStage {
+                stage: stage.name.clone(),
+                reason: "Expected cost tool".to_string(),
+            });
+        };
+
+        if let Some(plan) = plan {
+            return Ok(super::argv(&self.show_command(plan, working_dir)?));
+        }
+        let inputs = Self::inputs(stage, working_dir, resolved_inputs)?;
+        if *infracost {
+            let plan = inputs.first().cloned().unwrap_or_default();
+            return Ok(super::argv(&self.infracost_command(&plan, working_dir)?));
+        }
+
+        // The pricing table is built in, so there is no command to show
+        Ok(std::iter::once(format!("(estimate {})", source))
+            .chain(inputs.iter().map(|i| i.display().to_string()))
+            .collect())
+    }
+}
+
+/// The pull request's target branch in CI, or HEAD
+fn default_base() -> String {
+    ["GITHUB_BASE_REF", "CI_MERGE_REQUEST_TARGET_BRANCH_NAME"]
+        .iter()
+        .find_map(|key| std::env::var(key).ok().filter(|v| !v.is_empty()))
+        .map(|branch| format!("origin/{}", branch))
+        .unwrap_or_else(|| "HEAD".to_string())
+}
+
+/// The stage output: the summary and the largest changes
+fn describe(estimate: &CostEstimate) -> String {
+    let mut out = format!("This change {}\n", estimate.summary());
+    let changed = estimate.changed();
+    for resource in changed.iter().take(MAX_RESOURCES) {
+        let sign = if resource.delta() > 0.0 { "+" } else { "" };
+        out.push_str(&format!(
+            "  {:>12}/mo  {}\n",
+            format!("{}{}", sign, money(resource.delta())),
+            resource.name
+        ));
+    }
+    if changed.len() > MAX_RESOURCES {
+        out.push_str(&format!("  ... {} more\n", changed.len() - MAX_RESOURCES));
+    }
+    if !estimate.unpriced.is_empty() {
+        out.push_str(&format!(
+            "No price for {} (counted as free)\n",
+            estimate.unpriced.join(", ")
+        ));
+    }
+    out
+}
+
+fn parse_json(path: &Path, content: &str) -> Result<Value, ConflowError> {
+    serde_json::from_str(content).map_err(|e| ConflowError::FileReadError {
+        path: path.to_path_buf(),
+        error: format!("not a JSON plan: {}", e),
+    })
+}
+
+fn documents(path: &Path, content: &str) -> Result<Vec<Value>, ConflowError> {
+    Ok(ParseCache::global().documents(path, content)?.to_vec())
+}
+
+async fn read_file(path: &Path) -> Result<String, ConflowError> {
+    tokio::fs::read_to_string(path)
+        .await
+        .map_err(|e| ConflowError::FileReadError {
+            path: path.to_path_buf(),
+            error: e.to_string(),
+        })
+}
+
+async fn write_file(path: &Path, content: &[u8]) -> Result<(), ConflowError> {
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| ConflowError::FileWriteError {
+                path: parent.to_path_buf(),
+                error: e.to_string(),
+            })?;
+    }
+
+    tokio::fs::write(path, content)
+        .await
+        .map_err(|e| ConflowError::FileWriteError {
+            path: path.to_path_buf(),
+            error: e.to_string(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stage(yaml: &str) -> Stage {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_terraform_estimate_and_budget() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let plan = serde_json::json!({
+            "resource_changes": [{
+                "address": "aws_instance.web",
+                "type": "aws_instance",
+                "mode": "managed",
+                "change": {
+                    "before": { "instance_type": "t3.micro" },
+                    "after": { "instance_type": "m5.xlarge" }
+                }
+            }]
+        });
+        std::fs::write(temp.path().join("plan.json"), plan.to_string()).unwrap();
+        let executor = CostExecutor::new();
+
+        let within = stage(
+            "name: cost\ninput: plan.json\ntool: { type: cost, source: terraform, budget: 500 }",
+        );
+        let result = executor
+            .execute(&within, temp.path(), &HashMap::new(), None)
+            .await
+            .unwrap();
+        assert!(result.success, "{}", result.stderr);
+        assert!(result
+            .stdout
+            .starts_with("This change adds ~$133/mo ($7.59 → $140/mo)"));
+        assert!(result.stdout.contains("+$133/mo  aws_instance.web"));
+        let recorded = CostEstimate::load(temp.path(), "cost").unwrap();
+        assert_eq!(recorded.resources.len(), 1);
+
+        let over = stage(
+            "name: cost\ninput: plan.json\ntool: { type: cost, source: terraform, budget: 100 }",
+        );
+        let result = executor
+            .execute(&over, temp.path(), &HashMap::new(), None)
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result.stderr.contains("exceeds the budget of $100/mo"));
+    }
+
+    #[test]
+    fn test_command_line() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let executor = CostExecutor {
+            terraform: Some("terraform".into()),
+            infracost: Some("infracost".into()),
+            sandbox: None,
+        };
+
+        let from_plan = stage(
+            "name: cost\ninput: \"*.tf\"\ntool: { type: cost, source: terraform, plan: tf-plan }",
+        );
+        let argv = executor
+            .command_line(&from_plan, temp.path(), None)
+            .unwrap();
+        assert_eq!(argv[..3], ["terraform", "show", "-json"]);
+        assert!(argv[3].ends_with(".conflow/plans/tf-plan.tfplan"));
+
+        std::fs::write(temp.path().join("plan.json"), "{}").unwrap();
+        let infracost = stage(
+            "name: cost\ninput: plan.json\ntool: { type: cost, source: terraform, infracost: true }",
+        );
+        let argv = executor
+            .command_line(&infracost, temp.path(), None)
+            .unwrap();
+        assert_eq!(
+            argv[..5],
+            ["infracost", "breakdown", "--format", "json", "--path"]
+        );
+    }
+}
@@ -8,17 +8,21 @@
 use async_trait::async_trait;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::Instant;
 use tokio::process::Command;
 
-use super::{resolve_globs, ExecutionResult, Executor};
+use super::{resolve_inputs, ExecutionResult, Executor, Sandbox};
 use crate::errors::ConflowError;
-use crate::pipeline::{CueCommand, Input, Output, Stage, Tool};
+use crate::pipeline::{CueCommand, Output, Stage, Tool};
+use crate::rsr::detect::{detect_common, schema_file};
 
 /// CUE executor
 pub struct CueExecutor {
     /// Path to cue binary
     cue_bin: PathBuf,
+    /// Sandbox for commands, when enabled
+    sandbox: Option<Arc<Sandbox>>,
 }
 
 impl CueExecutor {
@@ -27,7 +31,24 @@ impl CueExecutor {
         let cue_bin =
             which::which("cue").map_err(|_| ConflowError::tool_not_found("cue"))?;
 
-        Ok(Self { cue_bin })
+        Ok(Self {
+            cue_bin,
+            sandbox: None,
+        })
+    }
+
+    /// Create a CUE executor using a specific binary
+    pub fn with_binary(cue_bin: PathBuf) -> Self {
+        Self {
+            cue_bin,
+            sandbox: None,
+        }
+    }
+
+    /// Run commands in a sandbox
+    pub fn sandboxed(mut self, sandbox: Option<Arc<Sandbox>>) -> Self {
+        self.sandbox = sandbox;
+        self
     }
 
     /// Build the command for a stage
@@ -60,12 +81,7 @@ impl CueExecutor {
         let input_files = if let Some(resolved) = resolved_inputs {
             resolved.to_vec()
         } else {
-            let patterns = stage.input.patterns();
-            if patterns.is_empty() {
-                vec![]
-            } else {
-                resolve_globs(&patterns, working_dir)?
-            }
+            resolve_inputs(stage, working_dir)?
         };
 
         // Add schema files first (for vet/export)
@@ -78,6 +94,24 @@ impl CueExecutor {
             cmd.arg(&schema_path);
         }
 
+        // Without schemas, vet the inputs against the built-in schema they match
+        if *command == CueCommand::Vet && schemas.is_empty() {
+            match detect_common(&input_files) {
+                Ok(Some(detected)) => {
+                    let schema_path = schema_file(working_dir, &detected.schema)?;
+                    tracing::info!(stage = %stage.name, schema = %detected.schema, "detected schema");
+                    cmd.arg(&schema_path);
+                    if let Some(definition) = &detected.definition {
+                        cmd.arg("-d").arg(definition);
+                    }
+                }
+                Ok(None) => {}
+                Err(reason) => {
+                    tracing::warn!(stage = %stage.name, "no schema detected: {}", reason);
+                }
+            }
+        }
+
         // Add input files
         for input_file in &input_files {
             cmd.arg(input_file);
@@ -151,7 +185,12 @@ impl Executor for CueExecutor {
         // Add environment variables
         cmd.envs(env);
 
+        if let Some(ref sandbox) = self.sandbox {
+            cmd = sandbox.wrap(cmd, working_dir)?;
+        }
+
         // Execute
+        tracing::debug!(command = ?super::argv(&cmd), "spawning cue");
         let output = cmd.output().await.map_err(|e| ConflowError::ToolExecutionFailed {
             tool: "cue".to_string(),
             error: e.to_string(),
@@ -159,6 +198,11 @@ impl Executor for CueExecutor {
         })?;
 
         let duration = start.elapsed();
+        tracing::debug!(
+            exit_code = output.status.code(),
+            duration_ms = duration.as_millis() as u64,
+            "cue finished"
+        );
         let stdout = String::from_utf8_lossy(&output.stdout).to_string();
         let stderr = String::from_utf8_lossy(&output.stderr).to_string();
 
@@ -178,9 +222,6 @@ impl Executor for CueExecutor {
         } else {
             let exit_code = output.status.code().unwrap_or(-1);
 
-            // Generate helpful error message
-            let help = ConflowError::stage_failed_with_help(&stage.name, stderr.clone(), "cue");
-
             Ok(ExecutionResult {
                 success: false,
                 stdout,
@@ -221,7 +262,7 @@ impl Executor for CueExecutor {
     }
 
     fn validate_stage(&self, stage: &Stage) -> Result<(), ConflowError> {
-        let Tool::Cue { schemas, .. } = &stage.tool else {
+        let Tool::Cue { .. } = &stage.tool else {
             return Err(ConflowError::InvalidStage {
                 stage: stage.name.clone(),
                 reason: "Not a CUE stage".to_string(),
@@ -233,12 +274,22 @@ impl Executor for CueExecutor {
 
         Ok(())
     }
+
+    fn command_line(
+        &self,
+        stage: &Stage,
+        working_dir: &Path,
+        resolved_inputs: Option<&[PathBuf]>,
+    ) -> Result<Vec<String>, ConflowError> {
+        let (cmd, _) = self.build_command(stage, working_dir, resolved_inputs)?;
+        Ok(super::argv(&cmd))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::pipeline::CacheConfig;
+    use crate::pipeline::Input;
 
     fn make_cue_stage(name: &str, command: CueCommand) -> Stage {
         Stage {
@@ -251,11 +302,19 @@ mod tests {
                 out_format: None,
             },
             input: Input::Single("*.json".into()),
+            exclude: vec![],
             output: None,
+            snapshot: false,
+            contract: None,
             depends_on: vec![],
             allow_failure: false,
             env: HashMap::new(),
             condition: None,
+            env_file: None,
+            workdir: None,
+            shard: None,
+            runs_on: Vec::new(),
+            manual: false,
         }
     }
 
@@ -270,6 +329,36 @@ mod tests {
         assert!(executor.validate_stage(&stage).is_ok());
     }
 
+    #[test]
+    fn test_vet_detects_schema() {
+        let temp = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            temp.path().join("compose.yaml"),
+            "services:\n  web:\n    image: nginx:1.25\n",
+        )
+        .unwrap();
+        let executor = CueExecutor::with_binary(PathBuf::from("cue"));
+
+        let mut stage = make_cue_stage("vet", CueCommand::Vet);
+        stage.input = Input::Single("*.yaml".into());
+        let (cmd, _) = executor.build_command(&stage, temp.path(), None).unwrap();
+        let args = crate::executors::argv(&cmd);
+        let schema = temp.path().join(".conflow/schemas/compose-spec.cue");
+        assert!(args.contains(&schema.display().to_string()));
+        assert!(std::fs::read_to_string(schema).unwrap().contains("#Service"));
+
+        // Explicit schemas are left alone
+        stage.tool = Tool::Cue {
+            command: CueCommand::Vet,
+            schemas: vec!["app.cue".into()],
+            flags: vec![],
+            out_format: None,
+        };
+        let (cmd, _) = executor.build_command(&stage, temp.path(), None).unwrap();
+        let args = crate::executors::argv(&cmd);
+        assert!(!args.iter().any(|a| a.contains("compose-spec")));
+    }
+
     #[test]
     fn test_validate_non_cue_stage_fails() {
         let Ok(executor) = CueExecutor::new() else {
@@ -284,11 +373,19 @@ mod tests {
                 shell: "bash".into(),
             },
             input: Input::Single("*.json".into()),
+            exclude: vec![],
             output: None,
+            snapshot: false,
+            contract: None,
             depends_on: vec![],
             allow_failure: false,
             env: HashMap::new(),
             condition: None,
+            env_file: None,
+            workdir: None,
+            shard: None,
+            runs_on: Vec::new(),
+            manual: false,
         };
 
         assert!(executor.validate_stage(&stage).is_err());
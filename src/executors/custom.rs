@@ -0,0 +1,382 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Custom executor
+//!
+//! Runs tools declared under `executors:` in `.conflow.yaml`, so teams can
+//! plug in proprietary validators as first-class, cacheable stage types.
+
+use async_trait::async_trait;
+use regex::Regex;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::process::Command;
+
+use super::{
+    resolve_inputs, shell_argv, shell_command, ExecutionResult, Executor, Sandbox, ShellKind,
+};
+use crate::errors::ConflowError;
+use crate::pipeline::{ExecutorDefinition, Stage, Tool};
+
+/// Executor for a pipeline-declared tool
+pub struct CustomExecutor {
+    name: String,
+    definition: ExecutorDefinition,
+    sandbox: Option<Arc<Sandbox>>,
+}
+
+impl CustomExecutor {
+    /// Create an executor from its definition
+    pub fn new(name: impl Into<String>, definition: ExecutorDefinition) -> Self {
+        Self {
+            name: name.into(),
+            definition,
+            sandbox: None,
+        }
+    }
+
+    /// Run commands in a sandbox
+    pub fn sandboxed(mut self, sandbox: Option<Arc<Sandbox>>) -> Self {
+        self.sandbox = sandbox;
+        self
+    }
+
+    /// Executable that must be available
+    fn required_binary(&self) -> Option<&str> {
+        self.definition
+            .requires
+            .as_deref()
+            .or_else(|| self.definition.command.split_whitespace().next())
+    }
+
+    /// Substitute template placeholders
+    fn render_command(&self, stage: &Stage, args: &[String], inputs: &[PathBuf]) -> String {
+        let shell = ShellKind::of(&self.definition.shell);
+        let inputs = inputs
+            .iter()
+            .map(|p| shell.quote(&p.to_string_lossy()))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let output = stage
+            .output
+            .as_ref()
+            .map(|o| shell.quote(&o.path().to_string_lossy()))
+            .unwrap_or_default();
+        let args = args
+            .iter()
+            .map(|a| shell.quote(a))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        self.definition
+            .command
+            .replace("{inputs}", &inputs)
+            .replace("{output}", &output)
+            .replace("{args}", &args)
+            .replace("{stage}", &shell.quote(&stage.name))
+    }
+
+    /// Input files from a previous stage or the stage's globs
+    fn inputs(
+        stage: &Stage,
+        working_dir: &Path,
+        resolved_inputs: Option<&[PathBuf]>,
+    ) -> Result<Vec<PathBuf>, ConflowError> {
+        if let Some(resolved) = resolved_inputs {
+            return Ok(resolved.to_vec());
+        }
+
+        resolve_inputs(stage, working_dir)
+    }
+
+    /// Decide success from exit code and output patterns
+    fn is_success(&self, exit_code: i32, combined: &str) -> Result<bool, ConflowError> {
+        if !self.definition.success_exit_codes.contains(&exit_code) {
+            return Ok(false);
+        }
+
+        if let Some(ref pattern) = self.definition.failure_pattern {
+            if self.regex(pattern)?.is_match(combined) {
+                return Ok(false);
+            }
+        }
+
+        if let Some(ref pattern) = self.definition.success_pattern {
+            return Ok(self.regex(pattern)?.is_match(combined));
+        }
+
+        Ok(true)
+    }
+
+    /// Extract `file:line:column: message` errors using the error pattern
+    fn parse_errors(&self, combined: &str) -> Result<Vec<String>, ConflowError> {
+        let Some(ref pattern) = self.definition.error_pattern else {
+            return Ok(vec![]);
+        };
+        let re = self.regex(pattern)?;
+
+        Ok(re
+            .captures_iter(combined)
+            .filter_map(|caps| {
+                let message = caps.name("message")?.as_str().trim();
+                let location: Vec<&str> = ["file", "line", "column"]
+                    .iter()
+                    .filter_map(|g| caps.name(g).map(|m| m.as_str()))
+                    .collect();
+
+                Some(if location.is_empty() {
+                    message.to_string()
+                } else {
+                    format!("{}: {}", location.join(":"), message)
+                })
+            })
+            .collect())
+    }
+
+    fn regex(&self, pattern: &str) -> Result<Regex, ConflowError> {
+        Regex::new(pattern).map_err(|e| ConflowError::ExecutionFailed {
+            message: format!("Executor '{}': invalid pattern: {}", self.name, e),
+            help: None,
+        })
+    }
+}
+
+#[async_trait]
+impl Executor for CustomExecutor {
+    async fn execute(
+        &self,
+        stage: &Stage,
+        working_dir: &Path,
+        env: &HashMap<String, String>,
+        resolved_inputs: Option<&[PathBuf]>,
+    ) -> Result<ExecutionResult, ConflowError> {
+        let Tool::Custom { args, .. } = &stage.tool else {
+            return Err(ConflowError::InvalidStage {
+                stage: stage.name.clone(),
+                reason: "Expected custom tool".to_string(),
+            });
+        };
+
+        let start = Instant::now();
+
+        let inputs = Self::inputs(stage, working_dir, resolved_inputs)?;
+        let command = self.render_command(stage, args, &inputs);
+
+        let mut cmd = Command::from(shell_command(&self.definition.shell, &command));
+        cmd.current_dir(working_dir);
+        cmd.envs(env);
+
+        if let Some(ref sandbox) = self.sandbox {
+            cmd = sandbox.wrap(cmd, working_dir)?;
+        }
+
+        tracing::debug!(command = ?super::argv(&cmd), "spawning {}", self.name);
+        let output = cmd
+            .output()
+            .await
+            .map_err(|e| ConflowError::ToolExecutionFailed {
+                tool: self.name.clone(),
+                error: e.to_string(),
+                help: Some(format!(
+                    "Shell '{}' may not be available",
+                    self.definition.shell
+                )),
+            })?;
+
+        let duration = start.elapsed();
+        let exit_code = output.status.code().unwrap_or(-1);
+        tracing::debug!(
+            exit_code,
+            duration_ms = duration.as_millis() as u64,
+            "{} finished",
+            self.name
+        );
+
+        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        let combined = format!("{}\n{}", stdout, stderr);
+
+        if self.is_success(exit_code, &combined)? {
+            let outputs = match stage.output {
+                Some(ref out) => vec![out.path().clone()],
+                None => vec![],
+            };
+
+            return Ok(ExecutionResult {
+                success: true,
+                stdout,
+                stderr,
+                exit_code,
+                outputs,
+                duration,
+                cache_hit: false,
+            });
+        }
+
+        // Replace raw tool output with parsed errors when available
+        let errors = self.parse_errors(&combined)?;
+        let stderr = if errors.is_empty() {
+            stderr
+        } else {
+            errors.join("\n")
+        };
+
+        Ok(ExecutionResult {
+            success: false,
+            stdout,
+            stderr,
+            exit_code,
+            outputs: vec![],
+            duration,
+            cache_hit: false,
+        })
+    }
+
+    async fn check_available(&self) -> Result<bool, ConflowError> {
+        Ok(match self.required_binary() {
+            Some(binary) => which::which(binary).is_ok(),
+            None => false,
+        })
+    }
+
+    async fn version(&self) -> Result<String, ConflowError> {
+        Ok(format!("{} (custom)", self.name))
+    }
+
+    fn validate_stage(&self, stage: &Stage) -> Result<(), ConflowError> {
+        match &stage.tool {
+            Tool::Custom { executor, .. } if *executor == self.name => Ok(()),
+            _ => Err(ConflowError::InvalidStage {
+                stage: stage.name.clone(),
+                reason: format!("Not a '{}' stage", self.name),
+            }),
+        }
+    }
+
+    fn command_line(
+        &self,
+        stage: &Stage,
+        working_dir: &Path,
+        resolved_inputs: Option<&[PathBuf]>,
+    ) -> Result<Vec<String>, ConflowError> {
+        let Tool::Custom { args, .. } = &stage.tool else {
+            return Err(ConflowError::InvalidStage {
+                stage: stage.name.clone(),
+                reason: "Expected custom tool".to_string(),
+            });
+        };
+
+        let inputs = Self::inputs(stage, working_dir, resolved_inputs)?;
+        let command = self.render_command(stage, args, &inputs);
+        Ok(shell_argv(&self.definition.shell, &command))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::{Input, Output};
+
+    fn definition(command: &str) -> ExecutorDefinition {
+        ExecutorDefinition {
+            command: command.into(),
+            shell: "bash".into(),
+            requires: None,
+            success_exit_codes: vec![0],
+            success_pattern: None,
+            failure_pattern: None,
+            error_pattern: None,
+        }
+    }
+
+    fn stage(args: Vec<&str>) -> Stage {
+        Stage {
+            name: "check".into(),
+            description: None,
+            tool: Tool::Custom {
+                executor: "lint".into(),
+                args: args.into_iter().map(String::from).collect(),
+                definition: None,
+            },
+            input: Input::Single("*.yaml".into()),
+            exclude: vec![],
+            output: Some(Output::File("out/report.txt".into())),
+            snapshot: false,
+            contract: None,
+            depends_on: vec![],
+            allow_failure: false,
+            env: HashMap::new(),
+            condition: None,
+            env_file: None,
+            workdir: None,
+            shard: None,
+            runs_on: Vec::new(),
+            manual: false,
+        }
+    }
+
+    #[test]
+    fn test_render_command() {
+        let exec = CustomExecutor::new("lint", definition("lint {args} -o {output} {inputs}"));
+        let rendered = exec.render_command(
+            &stage(vec!["--strict", "a b"]),
+            &["--strict".into(), "a b".into()],
+            &[PathBuf::from("x.yaml"), PathBuf::from("it's.yaml")],
+        );
+
+        assert_eq!(
+            rendered,
+            r"lint --strict 'a b' -o out/report.txt x.yaml 'it'\''s.yaml'"
+        );
+    }
+
+    #[test]
+    fn test_success_criteria() {
+        let mut def = definition("lint");
+        def.success_exit_codes = vec![0, 2];
+        def.failure_pattern = Some("FATAL".into());
+        def.success_pattern = Some("(?m)^OK$".into());
+        let exec = CustomExecutor::new("lint", def);
+
+        assert!(exec.is_success(2, "OK\n").unwrap());
+        assert!(!exec.is_success(1, "OK\n").unwrap());
+        assert!(!exec.is_success(0, "OK\nFATAL: boom").unwrap());
+        assert!(!exec.is_success(0, "done").unwrap());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let mut def = definition("lint");
+        def.error_pattern =
+            Some(r"(?m)^(?P<file>[^:\n]+):(?P<line>\d+):(?P<column>\d+): (?P<message>.+)$".into());
+        let exec = CustomExecutor::new("lint", def);
+
+        let errors = exec
+            .parse_errors("checking...\napp.yaml:3:7: unknown key 'prot'\n")
+            .unwrap();
+        assert_eq!(errors, vec!["app.yaml:3:7: unknown key 'prot'"]);
+    }
+
+    #[tokio::test]
+    async fn test_execute_custom_stage() {
+        let temp = tempfile::TempDir::new().unwrap();
+        std::fs::write(temp.path().join("a.yaml"), "x: 1").unwrap();
+
+        let mut def = definition("echo checked {inputs}; exit 3");
+        def.success_exit_codes = vec![3];
+        let exec = CustomExecutor::new("lint", def);
+
+        let mut stage = stage(vec![]);
+        stage.output = None;
+        let result = exec
+            .execute(&stage, temp.path(), &HashMap::new(), None)
+            .await
+            .unwrap();
+
+        assert!(result.success);
+        assert!(result.stdout.contains("checked"));
+        assert!(result.stdout.contains("a.yaml"));
+    }
+}
@@ -0,0 +1,597 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Deployment executor
+//!
+//! Runs kubectl, helm, and terraform stages with plan-then-apply semantics.
+//! A `plan` stage records what would change under `.conflow/plans/`. An
+//! `apply` stage that names the plan checks it still holds before changing
+//! anything: kubectl and helm repeat the dry run and require the same
+//! result, terraform applies the saved plan file after verifying it.
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::process::Output;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::process::Command;
+
+use super::{resolve_inputs, ExecutionResult, Executor, Sandbox};
+use crate::errors::ConflowError;
+use crate::pipeline::{DeployAction, Stage, Tool};
+use crate::toolchain::sha256_hex;
+
+/// Metadata the API server changes on every request
+const VOLATILE_METADATA: &[&str] = &[
+    "uid",
+    "resourceVersion",
+    "creationTimestamp",
+    "generation",
+    "managedFields",
+];
+
+/// Most changed lines shown when a plan drifts
+const MAX_DRIFT_LINES: usize = 20;
+
+/// Executor for deployment tools
+pub struct DeployExecutor {
+    /// Tool name (kubectl, helm, or terraform)
+    tool: String,
+    /// Path to the tool binary
+    bin: PathBuf,
+    /// Sandbox for commands, when enabled
+    sandbox: Option<Arc<Sandbox>>,
+}
+
+impl DeployExecutor {
+    /// Create an executor for a deployment tool found on PATH
+    pub fn new(tool: &str) -> Result<Self, ConflowError> {
+        let bin = which::which(tool).map_err(|_| ConflowError::tool_not_found(tool))?;
+        Ok(Self::with_binary(tool, bin))
+    }
+
+    /// Create an executor using a specific binary
+    pub fn with_binary(tool: &str, bin: PathBuf) -> Self {
+        Self {
+            tool: tool.to_string(),
+            bin,
+            sandbox: None,
+        }
+    }
+
+    /// Run commands in a sandbox
+    pub fn sandboxed(mut self, sandbox: Option<Arc<Sandbox>>) -> Self {
+        self.sandbox = sandbox;
+        self
+    }
+
+    /// Where a plan stage records its plan
+    fn plan_path(working_dir: &Path, stage: &str) -> PathBuf {
+        working_dir
+            .join(".conflow")
+            .join("plans")
+            .join(format!("{}.plan", stage))
+    }
+
+    /// Where a terraform plan stage saves its plan file
+    pub(super) fn tfplan_path(working_dir: &Path, stage: &str) -> PathBuf {
+        Self::plan_path(working_dir, stage).with_extension("tfplan")
+    }
+
+    /// Build the command for a stage, as a dry run or for real
+    fn build_command(
+        &self,
+        stage: &Stage,
+        action: DeployAction,
+        working_dir: &Path,
+        resolved_inputs: Option<&[PathBuf]>,
+    ) -> Result<Command, ConflowError> {
+        let mut cmd = Command::new(&self.bin);
+        cmd.current_dir(working_dir);
+        let dry_run = action == DeployAction::Plan;
+
+        match &stage.tool {
+            Tool::Kubectl {
+                namespace,
+                context,
+                flags,
+                ..
+            } => {
+                if let Some(context) = context {
+                    cmd.arg("--context").arg(context);
+                }
+                if let Some(namespace) = namespace {
+                    cmd.arg("--namespace").arg(namespace);
+                }
+                cmd.arg("apply");
+                if dry_run {
+                    cmd.args(["--dry-run=server", "--output", "yaml"]);
+                }
+
+                let manifests = match resolved_inputs {
+                    Some(resolved) => resolved.to_vec(),
+                    None => resolve_inputs(stage, working_dir)?,
+                };
+                for manifest in manifests {
+                    cmd.arg("--filename").arg(manifest);
+                }
+                cmd.args(flags);
+            }
+            Tool::Helm {
+                release,
+                chart,
+                namespace,
+                values,
+                flags,
+                ..
+            } => {
+                cmd.args(["upgrade", "--install"]).arg(release).arg(chart);
+                if let Some(namespace) = namespace {
+                    cmd.arg("--namespace").arg(namespace);
+                }
+                for file in values {
+                    cmd.arg("--values").arg(working_dir.join(file));
+                }
+                if dry_run {
+                    cmd.arg("--dry-run");
+                }
+                cmd.args(flags);
+            }
+            Tool::Terraform {
+                dir, flags, plan, ..
+            } => {
+                if let Some(dir) = dir {
+                    cmd.arg(format!("-chdir={}", working_dir.join(dir).display()));
+                }
+                if dry_run {
+                    let out = Self::tfplan_path(working_dir, &stage.name);
+                    cmd.args(["plan", "-input=false", "-no-color"]);
+                    cmd.arg(format!("-out={}", out.display()));
+                    cmd.args(flags);
+                } else {
+                    cmd.args(["apply", "-input=false", "-no-color"]);
+                    cmd.args(flags);
+                    match plan {
+                        Some(plan) => cmd.arg(Self::tfplan_path(working_dir, plan)),
+                        None => cmd.arg("-auto-approve"),
+                    };
+                }
+            }
+            _ => {
+                return Err(ConflowError::InvalidStage {
+                    stage: stage.name.clone(),
+                    reason: format!("Expected {} tool", self.tool),
+                })
+            }
+        }
+
+        Ok(cmd)
+    }
+
+    /// Run a command with the stage environment
+    async fn run(
+        &self,
+        mut cmd: Command,
+        working_dir: &Path,
+        env: &HashMap<String, String>,
+    ) -> Result<Output, ConflowError> {
+        cmd.envs(env);
+
+        if let Some(ref sandbox) = self.sandbox {
+            cmd = sandbox.wrap(cmd, working_dir)?;
+        }
+
+        tracing::debug!(command = ?super::argv(&cmd), "spawning {}", self.tool);
+        cmd.output()
+            .await
+            .map_err(|e| ConflowError::ToolExecutionFailed {
+                tool: self.tool.clone(),
+                error: e.to_string(),
+                help: Some(format!("Ensure {} is installed and accessible", self.tool)),
+            })
+    }
+
+    /// Plan output with run-specific details removed
+    fn normalize(&self, stdout: &str) -> String {
+        match self.tool.as_str() {
+            "kubectl" => normalize_manifests(stdout),
+            "helm" => stdout
+                .lines()
+                .filter(|line| !line.starts_with("LAST DEPLOYED:"))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            _ => stdout.to_string(),
+        }
+    }
+
+    /// Record a plan for later apply stages
+    async fn record_plan(
+        &self,
+        stage: &Stage,
+        working_dir: &Path,
+        stdout: &str,
+    ) -> Result<(), ConflowError> {
+        let path = Self::plan_path(working_dir, &stage.name);
+        let recorded = if self.tool == "terraform" {
+            let tfplan = Self::tfplan_path(working_dir, &stage.name);
+            sha256_hex(&read_file(&tfplan).await?)
+        } else {
+            self.normalize(stdout)
+        };
+
+        write_file(&path, recorded.as_bytes()).await
+    }
+
+    /// Check that a recorded plan still holds, describing any drift
+    async fn check_plan(
+        &self,
+        stage: &Stage,
+        plan: &str,
+        working_dir: &Path,
+        env: &HashMap<String, String>,
+        resolved_inputs: Option<&[PathBuf]>,
+    ) -> Result<Option<String>, ConflowError> {
+        let path = Self::plan_path(working_dir, plan);
+        let Ok(recorded) = tokio::fs::read_to_string(&path).await else {
+            return Ok(Some(format!(
+                "No plan recorded by stage '{}'; run it before applying",
+                plan
+            )));
+        };
+
+        if self.tool == "terraform" {
+            let tfplan = Self::tfplan_path(working_dir, plan);
+            let current = read_file(&tfplan).await.map(|b| sha256_hex(&b)).ok();
+            return Ok((current.as_deref() != Some(recorded.as_str())).then(|| {
+                format!(
+                    "Saved plan '{}' changed since stage '{}' recorded it",
+                    tfplan.display(),
+                    plan
+                )
+            }));
+        }
+
+        let cmd = self.build_command(stage, DeployAction::Plan, working_dir, resolved_inputs)?;
+        let output = self.run(cmd, working_dir, env).await?;
+        if !output.status.success() {
+            return Ok(Some(format!(
+                "Dry run before apply failed:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            )));
+        }
+
+        let current = self.normalize(&String::from_utf8_lossy(&output.stdout));
+        Ok((current != recorded).then(|| describe_drift(plan, &recorded, &current)))
+    }
+}
+
+#[async_trait]
+impl Executor for DeployExecutor {
+    async fn execute(
+        &self,
+        stage: &Stage,
+        working_dir: &Path,
+        env: &HashMap<String, String>,
+        resolved_inputs: Option<&[PathBuf]>,
+    ) -> Result<ExecutionResult, ConflowError> {
+        let start = Instant::now();
+        let action = stage
+            .tool
+            .deploy_action()
+            .ok_or_else(|| ConflowError::InvalidStage {
+                stage: stage.name.clone(),
+                reason: format!("Expected {} tool", self.tool),
+            })?;
+
+        if action == DeployAction::Apply {
+            if let Some(plan) = stage.tool.plan_stage() {
+                if let Some(drift) = self
+                    .check_plan(stage, plan, working_dir, env, resolved_inputs)
+                    .await?
+                {
+                    return Ok(ExecutionResult::failure(drift, 1, start.elapsed()));
+                }
+            }
+        } else {
+            let plans = Self::plan_path(working_dir, &stage.name);
+            if let Some(parent) = plans.parent() {
+                create_dir(parent).await?;
+            }
+        }
+
+        let cmd = self.build_command(stage, action, working_dir, resolved_inputs)?;
+        let output = self.run(cmd, working_dir, env).await?;
+
+        let duration = start.elapsed();
+        let exit_code = output.status.code().unwrap_or(-1);
+        tracing::debug!(
+            exit_code,
+            duration_ms = duration.as_millis() as u64,
+            "{} finished",
+            self.tool
+        );
+        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+
+        if !output.status.success() {
+            return Ok(ExecutionResult {
+                success: false,
+                stdout,
+                stderr,
+                exit_code,
+                outputs: vec![],
+                duration,
+                cache_hit: false,
+            });
+        }
+
+        let mut outputs = vec![];
+        if action == DeployAction::Plan {
+            self.record_plan(stage, working_dir, &stdout).await?;
+
+            if let Some(ref output) = stage.output {
+                let path = working_dir.join(output.path());
+                write_file(&path, stdout.as_bytes()).await?;
+                outputs.push(path);
+            }
+        }
+
+        Ok(ExecutionResult {
+            success: true,
+            stdout,
+            stderr,
+            exit_code,
+            outputs,
+            duration,
+            cache_hit: false,
+        })
+    }
+
+    async fn check_available(&self) -> Result<bool, ConflowError> {
+        Ok(self.bin.exists())
+    }
+
+    async fn version(&self) -> Result<String, ConflowError> {
+        let args: &[&str] = match self.tool.as_str() {
+            "kubectl" => &["version", "--client"],
+            "helm" => &["version", "--short"],
+            _ => &["version"],
+        };
+
+        let output = Command::new(&self.bin)
+            .args(args)
+            .output()
+            .await
+            .map_err(|e| ConflowError::ToolExecutionFailed {
+                tool: self.tool.clone(),
+                error: e.to_string(),
+                help: None,
+            })?;
+
+        let version = String::from_utf8_lossy(&output.stdout);
+        Ok(version
+            .lines()
+            .next()
+            .unwrap_or("unknown")
+            .trim()
+            .to_string())
+    }
+
+    fn validate_stage(&self, stage: &Stage) -> Result<(), ConflowError> {
+        if stage.tool.deploy_action().is_none() || stage.tool_name() != self.tool {
+            return Err(ConflowError::InvalidStage {
+                stage: stage.name.clone(),
+                reason: format!("Not a {} stage", self.tool),
+            });
+        }
+
+        Ok(())
+    }
+
+    fn command_line(
+        &self,
+        stage: &Stage,
+        working_dir: &Path,
+        resolved_inputs: Option<&[PathBuf]>,
+    ) -> Result<Vec<String>, ConflowError> {
+        let action = stage.tool.deploy_action().unwrap_or(DeployAction::Plan);
+        let cmd = self.build_command(stage, action, working_dir, resolved_inputs)?;
+        Ok(super::argv(&cmd))
+    }
+}
+
+/// Strip server-assigned fields from a server-side dry run
+///
+/// Output that isn't YAML is compared as-is.
+fn normalize_manifests(output: &str) -> String {
+    let mut documents = Vec::new();
+
+    for document in serde_yaml::Deserializer::from_str(output) {
+        let Ok(mut value) = serde_yaml::Value::deserialize(document) else {
+            return output.to_string();
+        };
+        strip_volatile(&mut value);
+        documents.push(serde_yaml::to_string(&value).unwrap_or_default());
+    }
+
+    documents.join("---\n")
+}
+
+fn strip_volatile(value: &mut serde_yaml::Value) {
+    match value {
+        serde_yaml::Value::Mapping(map) => {
+            if map.contains_key("apiVersion") && map.contains_key("kind") {
+                map.remove("status");
+                if let Some(serde_yaml::Value::Mapping(metadata)) = map.get_mut("metadata") {
+                    for key in VOLATILE_METADATA {
+                        metadata.remove(*key);
+                    }
+                }
+            }
+            for (_, child) in map.iter_mut() {
+                strip_volatile(child);
+            }
+        }
+        serde_yaml::Value::Sequence(items) => items.iter_mut().for_each(strip_volatile),
+        _ => {}
+    }
+}
+
+/// Summarize lines that differ between a recorded and a fresh plan
+fn describe_drift(plan: &str, recorded: &str, current: &str) -> String {
+    let old: HashSet<&str> = recorded.lines().collect();
+    let new: HashSet<&str> = current.lines().collect();
+
+    let changes: Vec<String> = recorded
+        .lines()
+        .filter(|line| !new.contains(line))
+        .map(|line| format!("- {}", line))
+        .chain(
+            current
+                .lines()
+                .filter(|line| !old.contains(line))
+                .map(|line| format!("+ {}", line)),
+        )
+        .collect();
+
+    let mut message = format!(
+        "Plan recorded by stage '{}' no longer matches; re-run it and review the changes:",
+        plan
+    );
+    for change in changes.iter().take(MAX_DRIFT_LINES) {
+        message.push_str("\n  ");
+        message.push_str(change);
+    }
+    if changes.len() > MAX_DRIFT_LINES {
+        message.push_str(&format!("\n  ... {} more", changes.len() - MAX_DRIFT_LINES));
+    }
+
+    message
+}
+
+async fn create_dir(path: &Path) -> Result<(), ConflowError> {
+    tokio::fs::create_dir_all(path)
+        .await
+        .map_err(|e| ConflowError::FileWriteError {
+            path: path.to_path_buf(),
+            error: e.to_string(),
+        })
+}
+
+async fn read_file(path: &Path) -> Result<Vec<u8>, ConflowError> {
+    tokio::fs::read(path)
+        .await
+        .map_err(|e| ConflowError::FileReadError {
+            path: path.to_path_buf(),
+            error: e.to_string(),
+        })
+}
+
+async fn write_file(path: &Path, content: &[u8]) -> Result<(), ConflowError> {
+    if let Some(parent) = path.parent() {
+        create_dir(parent).await?;
+    }
+
+    tokio::fs::write(path, content)
+        .await
+        .map_err(|e| ConflowError::FileWriteError {
+            path: path.to_path_buf(),
+            error: e.to_string(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stage(yaml: &str) -> Stage {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn test_build_commands() {
+        let temp = tempfile::TempDir::new().unwrap();
+        std::fs::write(temp.path().join("app.yaml"), "kind: ConfigMap").unwrap();
+        let dir = temp.path();
+
+        let kubectl = DeployExecutor::with_binary("kubectl", "kubectl".into());
+        let plan = stage(
+            "name: k8s-plan\ninput: \"*.yaml\"\n\
+             tool: { type: kubectl, action: plan, namespace: prod }",
+        );
+        let argv = kubectl.command_line(&plan, dir, None).unwrap();
+        assert_eq!(
+            argv[..6],
+            [
+                "kubectl",
+                "--namespace",
+                "prod",
+                "apply",
+                "--dry-run=server",
+                "--output"
+            ]
+        );
+        assert!(argv.last().unwrap().ends_with("app.yaml"));
+
+        let helm = DeployExecutor::with_binary("helm", "helm".into());
+        let apply = stage(
+            "name: release\ninput: \"*.yaml\"\n\
+             tool: { type: helm, action: apply, release: web, chart: ./chart }",
+        );
+        assert_eq!(
+            helm.command_line(&apply, dir, None).unwrap(),
+            ["helm", "upgrade", "--install", "web", "./chart"]
+        );
+
+        let terraform = DeployExecutor::with_binary("terraform", "terraform".into());
+        let apply = stage(
+            "name: tf-apply\ninput: \"*.tf\"\n\
+             tool: { type: terraform, action: apply, plan: tf-plan }",
+        );
+        let argv = terraform.command_line(&apply, dir, None).unwrap();
+        assert_eq!(argv[1..4], ["apply", "-input=false", "-no-color"]);
+        assert!(argv[4].ends_with(".conflow/plans/tf-plan.tfplan"));
+    }
+
+    #[test]
+    fn test_normalize_manifests() {
+        let first = "apiVersion: v1\nkind: ConfigMap\nmetadata:\n  name: app\n  uid: abc\n  \
+                     resourceVersion: \"1\"\ndata:\n  status: ok\n";
+        let second = "apiVersion: v1\nkind: ConfigMap\nmetadata:\n  name: app\n  uid: def\n  \
+                      resourceVersion: \"2\"\ndata:\n  status: ok\n";
+
+        assert_eq!(normalize_manifests(first), normalize_manifests(second));
+        assert!(normalize_manifests(first).contains("status: ok"));
+        assert!(!normalize_manifests(first).contains("uid"));
+    }
+
+    #[test]
+    fn test_describe_drift() {
+        let drift = describe_drift("plan", "a\nb\nc", "a\nc\nd");
+        assert!(drift.contains("'plan'"));
+        assert!(drift.contains("- b"));
+        assert!(drift.contains("+ d"));
+        assert!(!drift.contains("- a"));
+    }
+
+    #[tokio::test]
+    async fn test_apply_without_recorded_plan_fails() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let terraform = DeployExecutor::with_binary("terraform", "terraform".into());
+        let apply = stage(
+            "name: tf-apply\ninput: \"*.tf\"\n\
+             tool: { type: terraform, action: apply, plan: tf-plan }",
+        );
+
+        let result = terraform
+            .execute(&apply, temp.path(), &HashMap::new(), None)
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result
+            .stderr
+            .contains("No plan recorded by stage 'tf-plan'"));
+    }
+}
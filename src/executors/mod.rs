@@ -4,26 +4,66 @@
 //! Tool executors
 //!
 //! This module provides the executor trait and implementations
-//! for various configuration tools (CUE, Nickel, Shell).
+//! for various configuration tools (CUE, Nickel, Shell, deployment tools,
+//! cost estimates, template rendering, and custom executors declared in the
+//! pipeline).
 
+#[cfg(feature = "native")]
+mod cost;
+#[cfg(feature = "native")]
 mod cue;
+#[cfg(feature = "native")]
+mod custom;
+#[cfg(feature = "native")]
+mod deploy;
+#[cfg(feature = "native")]
 mod nickel;
+pub mod parsers;
+#[cfg(feature = "native")]
+mod render;
+#[cfg(feature = "native")]
+mod sandbox;
+#[cfg(feature = "native")]
 mod shell;
 
+#[cfg(feature = "native")]
+pub use cost::CostExecutor;
+#[cfg(feature = "native")]
 pub use cue::CueExecutor;
+#[cfg(feature = "native")]
+pub use custom::CustomExecutor;
+#[cfg(feature = "native")]
+pub use deploy::DeployExecutor;
+#[cfg(feature = "native")]
 pub use nickel::NickelExecutor;
+#[cfg(feature = "native")]
+pub use render::RenderExecutor;
+#[cfg(feature = "native")]
+pub use sandbox::Sandbox;
+#[cfg(feature = "native")]
 pub use shell::ShellExecutor;
 
+#[cfg(feature = "native")]
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "native")]
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+#[cfg(feature = "native")]
+use std::sync::Arc;
 use std::time::Duration;
 
 use crate::errors::ConflowError;
+#[cfg(feature = "native")]
+use crate::pipeline::Pipeline;
 use crate::pipeline::Stage;
+use crate::utils::ignore::Ignore;
+
+/// Tools handled by the deployment executor
+pub const DEPLOY_TOOLS: &[&str] = &["kubectl", "helm", "terraform"];
 
 /// Result of stage execution
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExecutionResult {
     /// Whether execution succeeded
     pub success: bool,
@@ -38,12 +78,15 @@ pub struct ExecutionResult {
     pub exit_code: i32,
 
     /// Output files generated
+    #[serde(default)]
     pub outputs: Vec<PathBuf>,
 
     /// Execution duration
+    #[serde(rename = "duration_ms", with = "crate::report::duration_ms")]
     pub duration: Duration,
 
     /// Cache hit or miss
+    #[serde(default)]
     pub cache_hit: bool,
 }
 
@@ -74,6 +117,32 @@ impl ExecutionResult {
         }
     }
 
+    /// Combine the results of a stage run in shards
+    ///
+    /// Fails if any shard failed, with the first failing shard's exit code.
+    pub fn merged(shards: Vec<ExecutionResult>, duration: Duration) -> Self {
+        let join = |texts: Vec<&str>| {
+            texts
+                .into_iter()
+                .map(|text| text.trim_end_matches('\n'))
+                .filter(|text| !text.is_empty())
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+        Self {
+            success: shards.iter().all(|shard| shard.success),
+            stdout: join(shards.iter().map(|shard| shard.stdout.as_str()).collect()),
+            stderr: join(shards.iter().map(|shard| shard.stderr.as_str()).collect()),
+            exit_code: shards
+                .iter()
+                .find(|shard| !shard.success)
+                .map_or(0, |shard| shard.exit_code),
+            outputs: shards.iter().flat_map(|shard| shard.outputs.clone()).collect(),
+            duration,
+            cache_hit: false,
+        }
+    }
+
     /// Mark this result as a cache hit
     pub fn with_cache_hit(mut self) -> Self {
         self.cache_hit = true;
@@ -82,6 +151,7 @@ impl ExecutionResult {
 }
 
 /// Trait for tool executors
+#[cfg(feature = "native")]
 #[async_trait]
 pub trait Executor: Send + Sync {
     /// Execute a stage
@@ -107,6 +177,131 @@ pub trait Executor: Send + Sync {
 
     /// Validate stage configuration
     fn validate_stage(&self, stage: &Stage) -> Result<(), ConflowError>;
+
+    /// The command line `execute` would run, for dry runs
+    fn command_line(
+        &self,
+        stage: &Stage,
+        working_dir: &Path,
+        resolved_inputs: Option<&[PathBuf]>,
+    ) -> Result<Vec<String>, ConflowError>;
+}
+
+/// Quote a value for POSIX shells when needed
+pub(crate) fn shell_quote(value: &str) -> String {
+    let safe = !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./=:,+@%".contains(c));
+
+    if safe {
+        value.to_string()
+    } else {
+        format!("'{}'", value.replace('\'', r"'\''"))
+    }
+}
+
+/// How a shell takes a command string
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShellKind {
+    /// sh, bash, zsh and friends: `-c <command>`
+    Posix,
+    /// Windows PowerShell or PowerShell 7: `-Command <command>`
+    PowerShell,
+    /// cmd.exe: `/c <command>`
+    Cmd,
+}
+
+impl ShellKind {
+    /// Kind of a shell by program name, ignoring any directory and `.exe`
+    pub fn of(shell: &str) -> Self {
+        // Split on both separators so Windows paths classify the same anywhere
+        let name = shell.rsplit(['/', '\\']).next().unwrap_or(shell);
+        let name = name.to_ascii_lowercase();
+        match name.strip_suffix(".exe").unwrap_or(&name) {
+            "powershell" | "pwsh" => Self::PowerShell,
+            "cmd" => Self::Cmd,
+            _ => Self::Posix,
+        }
+    }
+
+    /// Arguments placed before the command string
+    pub fn args(self) -> &'static [&'static str] {
+        match self {
+            Self::Posix => &["-c"],
+            Self::PowerShell => &["-NoLogo", "-NoProfile", "-NonInteractive", "-Command"],
+            Self::Cmd => &["/d", "/s", "/c"],
+        }
+    }
+
+    /// Quote a value for this shell when needed
+    pub fn quote(self, value: &str) -> String {
+        match self {
+            Self::Posix => shell_quote(value),
+            Self::PowerShell => {
+                let safe = !value.is_empty()
+                    && value
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || r"-_./\=:+".contains(c));
+                if safe {
+                    value.to_string()
+                } else {
+                    format!("'{}'", value.replace('\'', "''"))
+                }
+            }
+            Self::Cmd => {
+                let safe = !value.is_empty()
+                    && value
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || r"-_./\=:+,@".contains(c));
+                if safe {
+                    value.to_string()
+                } else {
+                    format!("\"{}\"", value.replace('"', "\"\""))
+                }
+            }
+        }
+    }
+}
+
+/// Program and arguments that run `command` through `shell`, for display
+#[cfg_attr(not(feature = "native"), allow(dead_code))]
+pub(crate) fn shell_argv(shell: &str, command: &str) -> Vec<String> {
+    std::iter::once(shell)
+        .chain(ShellKind::of(shell).args().iter().copied())
+        .chain(std::iter::once(command))
+        .map(String::from)
+        .collect()
+}
+
+/// A command that runs `command` through `shell`
+#[cfg_attr(not(feature = "native"), allow(dead_code))]
+pub(crate) fn shell_command(shell: &str, command: &str) -> std::process::Command {
+    let kind = ShellKind::of(shell);
+    let mut cmd = std::process::Command::new(shell);
+    cmd.args(kind.args());
+
+    // cmd.exe parses its own command line rather than argv, so the usual
+    // quoting of arguments would mangle the command
+    #[cfg(windows)]
+    if kind == ShellKind::Cmd {
+        use std::os::windows::process::CommandExt;
+        cmd.raw_arg(format!("\"{}\"", command));
+        return cmd;
+    }
+
+    cmd.arg(command);
+    cmd
+}
+
+/// Program and arguments of a command, for display
+#[cfg(feature = "native")]
+pub(crate) fn argv(cmd: &tokio::process::Command) -> Vec<String> {
+    let cmd = cmd.as_std();
+    std::iter::once(cmd.get_program())
+        .chain(cmd.get_args())
+        .map(|a| a.to_string_lossy().to_string())
+        .collect()
 }
 
 /// Resolve glob patterns to file paths
@@ -137,7 +332,18 @@ pub fn resolve_globs(patterns: &[&str], base_dir: &Path) -> Result<Vec<PathBuf>,
     Ok(files)
 }
 
+/// Resolve a stage's input globs, leaving out files its `exclude` patterns match
+pub fn resolve_inputs(stage: &Stage, base_dir: &Path) -> Result<Vec<PathBuf>, ConflowError> {
+    let files = resolve_globs(&stage.input.patterns(), base_dir)?;
+    if stage.exclude.is_empty() {
+        return Ok(files);
+    }
+    let exclude = Ignore::new(base_dir, stage.exclude.iter().map(String::as_str));
+    Ok(files.into_iter().filter(|f| !exclude.is_ignored(f)).collect())
+}
+
 /// Create a standard executor setup with all built-in executors
+#[cfg(feature = "native")]
 pub fn create_default_executors() -> HashMap<String, Box<dyn Executor>> {
     let mut executors: HashMap<String, Box<dyn Executor>> = HashMap::new();
 
@@ -155,3 +361,62 @@ pub fn create_default_executors() -> HashMap<String, Box<dyn Executor>> {
 
     executors
 }
+
+/// Create the built-in executors plus any declared by the pipeline
+///
+/// Tools pinned under `toolchain:` use the conflow-managed binaries when
+/// they are installed. With `sandbox.enabled`, every executor that spawns
+/// commands runs them in the sandbox.
+#[cfg(feature = "native")]
+pub fn create_executors(pipeline: &Pipeline) -> HashMap<String, Box<dyn Executor>> {
+    let mut executors: HashMap<String, Box<dyn Executor>> = HashMap::new();
+    let pinned = crate::toolchain::pinned_binaries(pipeline);
+    let sandbox = pipeline
+        .sandbox
+        .enabled
+        .then(|| Arc::new(Sandbox::new(pipeline.sandbox.clone())));
+
+    let cue = match pinned.get("cue") {
+        Some(path) => Some(CueExecutor::with_binary(path.clone())),
+        None => CueExecutor::new().ok(),
+    };
+    if let Some(cue) = cue {
+        executors.insert("cue".to_string(), Box::new(cue.sandboxed(sandbox.clone())));
+    }
+
+    let nickel = match pinned.get("nickel") {
+        Some(path) => Some(NickelExecutor::with_binary(path.clone())),
+        None => NickelExecutor::new().ok(),
+    };
+    if let Some(nickel) = nickel {
+        executors.insert("nickel".to_string(), Box::new(nickel.sandboxed(sandbox.clone())));
+    }
+
+    executors.insert(
+        "shell".to_string(),
+        Box::new(ShellExecutor::new().sandboxed(sandbox.clone())),
+    );
+
+    for tool in DEPLOY_TOOLS {
+        if let Ok(deploy) = DeployExecutor::new(tool) {
+            executors.insert(tool.to_string(), Box::new(deploy.sandboxed(sandbox.clone())));
+        }
+    }
+
+    let cost = match pinned.get("terraform") {
+        Some(path) => CostExecutor::new().with_terraform(path.clone()),
+        None => CostExecutor::new(),
+    };
+    executors.insert("cost".to_string(), Box::new(cost.sandboxed(sandbox.clone())));
+
+    let kube_version = pipeline.kubernetes.version.as_deref().and_then(|v| v.parse().ok());
+    let render = RenderExecutor::new().with_kube_version(kube_version);
+    executors.insert("render".to_string(), Box::new(render));
+
+    for (name, definition) in &pipeline.executors {
+        let executor = CustomExecutor::new(name, definition.clone()).sandboxed(sandbox.clone());
+        executors.insert(name.clone(), Box::new(executor));
+    }
+
+    executors
+}
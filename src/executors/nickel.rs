@@ -8,17 +8,20 @@
 use async_trait::async_trait;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::Instant;
 use tokio::process::Command;
 
-use super::{resolve_globs, ExecutionResult, Executor};
+use super::{resolve_inputs, ExecutionResult, Executor, Sandbox};
 use crate::errors::ConflowError;
-use crate::pipeline::{Input, NickelCommand, Output, OutputFormat, Stage, Tool};
+use crate::pipeline::{NickelCommand, Output, OutputFormat, Stage, Tool};
 
 /// Nickel executor
 pub struct NickelExecutor {
     /// Path to nickel binary
     nickel_bin: PathBuf,
+    /// Sandbox for commands, when enabled
+    sandbox: Option<Arc<Sandbox>>,
 }
 
 impl NickelExecutor {
@@ -27,7 +30,24 @@ impl NickelExecutor {
         let nickel_bin =
             which::which("nickel").map_err(|_| ConflowError::tool_not_found("nickel"))?;
 
-        Ok(Self { nickel_bin })
+        Ok(Self {
+            nickel_bin,
+            sandbox: None,
+        })
+    }
+
+    /// Create a Nickel executor using a specific binary
+    pub fn with_binary(nickel_bin: PathBuf) -> Self {
+        Self {
+            nickel_bin,
+            sandbox: None,
+        }
+    }
+
+    /// Run commands in a sandbox
+    pub fn sandboxed(mut self, sandbox: Option<Arc<Sandbox>>) -> Self {
+        self.sandbox = sandbox;
+        self
     }
 
     /// Build the command for a stage
@@ -62,12 +82,7 @@ impl NickelExecutor {
         } else if let Some(f) = file {
             vec![working_dir.join(f)]
         } else {
-            let patterns = stage.input.patterns();
-            if patterns.is_empty() {
-                vec![]
-            } else {
-                resolve_globs(&patterns, working_dir)?
-            }
+            resolve_inputs(stage, working_dir)?
         };
 
         // Add input file (Nickel typically works with single files)
@@ -149,7 +164,12 @@ impl Executor for NickelExecutor {
         // Add environment variables
         cmd.envs(env);
 
+        if let Some(ref sandbox) = self.sandbox {
+            cmd = sandbox.wrap(cmd, working_dir)?;
+        }
+
         // Execute
+        tracing::debug!(command = ?super::argv(&cmd), "spawning nickel");
         let output = cmd.output().await.map_err(|e| ConflowError::ToolExecutionFailed {
             tool: "nickel".to_string(),
             error: e.to_string(),
@@ -157,6 +177,11 @@ impl Executor for NickelExecutor {
         })?;
 
         let duration = start.elapsed();
+        tracing::debug!(
+            exit_code = output.status.code(),
+            duration_ms = duration.as_millis() as u64,
+            "nickel finished"
+        );
         let stdout = String::from_utf8_lossy(&output.stdout).to_string();
         let stderr = String::from_utf8_lossy(&output.stderr).to_string();
 
@@ -219,11 +244,22 @@ impl Executor for NickelExecutor {
 
         Ok(())
     }
+
+    fn command_line(
+        &self,
+        stage: &Stage,
+        working_dir: &Path,
+        resolved_inputs: Option<&[PathBuf]>,
+    ) -> Result<Vec<String>, ConflowError> {
+        let (cmd, _) = self.build_command(stage, working_dir, resolved_inputs)?;
+        Ok(super::argv(&cmd))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::pipeline::Input;
 
     fn make_nickel_stage(name: &str, command: NickelCommand) -> Stage {
         Stage {
@@ -236,11 +272,19 @@ mod tests {
                 format: Some(OutputFormat::Json),
             },
             input: Input::Single("config.ncl".into()),
+            exclude: vec![],
             output: Some(Output::File(PathBuf::from("output.json"))),
+            snapshot: false,
+            contract: None,
             depends_on: vec![],
             allow_failure: false,
             env: HashMap::new(),
             condition: None,
+            env_file: None,
+            workdir: None,
+            shard: None,
+            runs_on: Vec::new(),
+            manual: false,
         }
     }
 
@@ -0,0 +1,73 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! GitLab Code Quality export
+//!
+//! GitLab shows the issues of a `codequality` report artifact inline in
+//! merge request diffs. Issues without a file can't be placed, so they are
+//! left out.
+
+use serde_json::{json, Value};
+
+use super::{Severity, ToolDiagnostic};
+
+/// Build a Code Quality report from diagnostics
+pub fn to_code_quality(diagnostics: &[ToolDiagnostic]) -> Value {
+    diagnostics
+        .iter()
+        .filter_map(|diagnostic| {
+            let location = diagnostic.location.as_ref()?;
+            let path = location.file.to_string_lossy().replace('\\', "/");
+            let line = location.line.unwrap_or(1);
+            let check = match diagnostic.rule {
+                Some(ref rule) => format!("{}/{}", diagnostic.tool, rule),
+                None => diagnostic.tool.clone(),
+            };
+            let severity = match diagnostic.severity {
+                Severity::Error => "major",
+                Severity::Warning => "minor",
+                Severity::Info => "info",
+            };
+
+            // Stable across runs so GitLab can tell new issues from old ones
+            let fingerprint = blake3::hash(
+                format!("{}\0{}\0{}\0{}", check, path, line, diagnostic.message).as_bytes(),
+            )
+            .to_hex()[..32]
+                .to_string();
+
+            Some(json!({
+                "description": diagnostic.message,
+                "check_name": check,
+                "fingerprint": fingerprint,
+                "severity": severity,
+                "location": { "path": path, "lines": { "begin": line } },
+            }))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::parse_output;
+    use super::*;
+
+    #[test]
+    fn test_code_quality_issues() {
+        let mut diagnostics = parse_output(
+            "yamllint",
+            "a.yaml:3:1: [warning] missing document start \"---\" (document-start)\n",
+        );
+        diagnostics.extend(parse_output("shell", "something went wrong\n"));
+
+        let report = to_code_quality(&diagnostics);
+        let issues = report.as_array().unwrap();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0]["check_name"], "yamllint/document-start");
+        assert_eq!(issues[0]["severity"], "minor");
+        assert_eq!(issues[0]["location"]["path"], "a.yaml");
+        assert_eq!(issues[0]["location"]["lines"]["begin"], 3);
+        assert_eq!(issues[0]["fingerprint"].as_str().unwrap().len(), 32);
+        assert_eq!(to_code_quality(&diagnostics), report);
+    }
+}
@@ -0,0 +1,101 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! CUE error output
+//!
+//! CUE prints each error as a message line followed by indented
+//! positions:
+//!
+//! ```text
+//! port: conflicting values 80 and "80" (mismatched types int and string):
+//!     ./schema.cue:3:8
+//!     ./config.json:2:10
+//! ```
+
+use regex::Regex;
+use std::sync::LazyLock;
+
+use super::{Location, Severity, ToolDiagnostic};
+
+static POSITION: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s+(?P<file>[^\s:][^:]*):(?P<line>\d+):(?P<column>\d+)\s*$").unwrap()
+});
+
+/// Parse `cue vet`/`cue export` errors
+pub fn parse(output: &str) -> Vec<ToolDiagnostic> {
+    let mut diagnostics: Vec<ToolDiagnostic> = Vec::new();
+    let mut current: Option<ToolDiagnostic> = None;
+
+    for line in output.lines() {
+        if let Some(caps) = POSITION.captures(line) {
+            let Some(ref mut diagnostic) = current else {
+                continue;
+            };
+            let location = Location::new(&caps["file"], Some(&caps["line"]), Some(&caps["column"]));
+            diagnostic.related.push(location);
+            continue;
+        }
+
+        if line.trim().is_empty() || line.starts_with(char::is_whitespace) {
+            continue;
+        }
+
+        if let Some(done) = current.take() {
+            diagnostics.extend(finish(done));
+        }
+        let message = line.trim().trim_end_matches(':');
+        current = Some(ToolDiagnostic::new("cue", Severity::Error, message));
+    }
+
+    if let Some(done) = current {
+        diagnostics.extend(finish(done));
+    }
+
+    diagnostics
+}
+
+/// Keep only messages that had positions, preferring a non-schema file
+/// as the primary location
+fn finish(mut diagnostic: ToolDiagnostic) -> Option<ToolDiagnostic> {
+    if diagnostic.related.is_empty() {
+        return None;
+    }
+
+    let primary = diagnostic
+        .related
+        .iter()
+        .rposition(|l| l.file.extension().is_some_and(|e| e != "cue"))
+        .unwrap_or(0);
+    diagnostic.location = Some(diagnostic.related.remove(primary));
+    Some(diagnostic)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_parse_conflict() {
+        let output = "port: conflicting values 80 and \"80\" (mismatched types int and string):\n    ./schema.cue:3:8\n    ./config.json:2:10\nname: incomplete value string:\n    ./schema.cue:2:8\n";
+        let diagnostics = parse(output);
+
+        assert_eq!(diagnostics.len(), 2);
+        let first = &diagnostics[0];
+        assert!(first.message.starts_with("port: conflicting values"));
+        let location = first.location.as_ref().unwrap();
+        assert_eq!(location.file, PathBuf::from("config.json"));
+        assert_eq!((location.line, location.column), (Some(2), Some(10)));
+        assert_eq!(first.related[0].file, PathBuf::from("schema.cue"));
+
+        assert_eq!(
+            diagnostics[1].location.as_ref().unwrap().file,
+            PathBuf::from("schema.cue")
+        );
+    }
+
+    #[test]
+    fn test_ignores_unpositioned_text() {
+        assert!(parse("some other tool output\n").is_empty());
+    }
+}
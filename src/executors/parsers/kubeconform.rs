@@ -0,0 +1,64 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! kubeconform output
+//!
+//! kubeconform reports per-resource results without line numbers:
+//!
+//! ```text
+//! deploy.yaml - Deployment web is invalid: problem validating schema. ...
+//! svc.yaml - Service api failed validation: ...
+//! ```
+
+use regex::Regex;
+use std::sync::LazyLock;
+
+use super::{Location, Severity, ToolDiagnostic};
+
+static RESULT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?m)^(?P<file>\S+) - (?:(?P<kind>\S+) (?P<name>\S+) )?(?:is invalid|failed validation|error):? (?P<message>.+)$").unwrap()
+});
+
+/// Parse kubeconform text output
+pub fn parse(output: &str) -> Vec<ToolDiagnostic> {
+    RESULT
+        .captures_iter(output)
+        .map(|caps| {
+            let message = match (caps.name("kind"), caps.name("name")) {
+                (Some(kind), Some(name)) => {
+                    format!(
+                        "{} {}: {}",
+                        kind.as_str(),
+                        name.as_str(),
+                        caps["message"].trim()
+                    )
+                }
+                _ => caps["message"].trim().to_string(),
+            };
+
+            let mut diagnostic = ToolDiagnostic::new("kubeconform", Severity::Error, message);
+            diagnostic.location = Some(Location::new(&caps["file"], None, None));
+            diagnostic
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_parse_invalid_resource() {
+        let output = "deploy.yaml - Deployment web is invalid: problem validating schema: '/spec/replicas' expected integer, but got string\nsvc.yaml - Service api is valid\n";
+        let diagnostics = parse(output);
+
+        assert_eq!(diagnostics.len(), 1);
+        assert!(diagnostics[0]
+            .message
+            .starts_with("Deployment web: problem validating"));
+        let location = diagnostics[0].location.as_ref().unwrap();
+        assert_eq!(location.file, PathBuf::from("deploy.yaml"));
+        assert_eq!(location.line, None);
+    }
+}
@@ -0,0 +1,256 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Tool output parsers
+//!
+//! Turn the error output of cue, nickel, yamllint, kubeconform and
+//! similar tools into structured diagnostics with source locations, so
+//! failures can be rendered uniformly and exported as JSON, SARIF or a
+//! GitLab Code Quality report.
+
+mod code_quality;
+mod cue;
+mod kubeconform;
+mod nickel;
+mod sarif;
+mod yamllint;
+
+pub use code_quality::to_code_quality;
+pub use sarif::to_sarif;
+
+use colored::Colorize;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::LazyLock;
+
+/// Diagnostic severity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+}
+
+impl Severity {
+    fn parse(s: &str) -> Self {
+        match s.to_lowercase().as_str() {
+            "warning" | "warn" => Self::Warning,
+            "info" | "note" | "help" => Self::Info,
+            _ => Self::Error,
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Error => write!(f, "error"),
+            Self::Warning => write!(f, "warning"),
+            Self::Info => write!(f, "info"),
+        }
+    }
+}
+
+/// A position in a source file (1-based)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Location {
+    pub file: PathBuf,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<usize>,
+}
+
+impl Location {
+    fn new(file: &str, line: Option<&str>, column: Option<&str>) -> Self {
+        Self {
+            file: PathBuf::from(file.trim().trim_start_matches("./")),
+            line: line.and_then(|l| l.parse().ok()),
+            column: column.and_then(|c| c.parse().ok()),
+        }
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.file.display())?;
+        if let Some(line) = self.line {
+            write!(f, ":{}", line)?;
+            if let Some(column) = self.column {
+                write!(f, ":{}", column)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A structured diagnostic extracted from tool output
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolDiagnostic {
+    /// Tool that produced the diagnostic
+    pub tool: String,
+    pub severity: Severity,
+    pub message: String,
+    /// Rule or check identifier, when the tool reports one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rule: Option<String>,
+    /// Primary location
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<Location>,
+    /// Other locations involved (e.g. the schema side of a CUE conflict)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub related: Vec<Location>,
+}
+
+impl ToolDiagnostic {
+    fn new(tool: &str, severity: Severity, message: impl Into<String>) -> Self {
+        Self {
+            tool: tool.to_string(),
+            severity,
+            message: message.into(),
+            rule: None,
+            location: None,
+            related: vec![],
+        }
+    }
+
+    /// Render as a colorized one-line summary plus related locations
+    pub fn render(&self) -> String {
+        let severity = match self.severity {
+            Severity::Error => self.severity.to_string().red().bold(),
+            Severity::Warning => self.severity.to_string().yellow().bold(),
+            Severity::Info => self.severity.to_string().blue().bold(),
+        };
+
+        let mut out = String::new();
+        if let Some(ref location) = self.location {
+            out.push_str(&format!("{}: ", location.to_string().bold()));
+        }
+        out.push_str(&format!("{}[{}]: {}", severity, self.tool, self.message));
+        if let Some(ref rule) = self.rule {
+            out.push_str(&format!(" {}", format!("({})", rule).dimmed()));
+        }
+        for related in &self.related {
+            out.push_str(&format!("\n    {} {}", "→".blue(), related));
+        }
+        crate::report::redact(&out)
+    }
+}
+
+/// Parse tool output into diagnostics
+///
+/// `tool` selects the parser; unknown tools (shell stages, custom
+/// executors) try every parser and fall back to `file:line:col: message`.
+pub fn parse_output(tool: &str, output: &str) -> Vec<ToolDiagnostic> {
+    match tool {
+        "cue" => cue::parse(output),
+        "nickel" => nickel::parse(output),
+        "yamllint" => yamllint::parse(output),
+        "kubeconform" => kubeconform::parse(output),
+        _ => detect(tool, output),
+    }
+}
+
+/// A tool-specific output parser
+type Parser = fn(&str) -> Vec<ToolDiagnostic>;
+
+/// Try each known format, keeping the first that recognises anything
+fn detect(tool: &str, output: &str) -> Vec<ToolDiagnostic> {
+    let parsers: [(&str, Parser); 4] = [
+        ("nickel", nickel::parse),
+        ("yamllint", yamllint::parse),
+        ("kubeconform", kubeconform::parse),
+        ("cue", cue::parse),
+    ];
+
+    for (name, parser) in parsers {
+        let diagnostics = parser(output);
+        if !diagnostics.is_empty() {
+            tracing::debug!(tool, format = name, "detected tool output format");
+            return diagnostics;
+        }
+    }
+
+    generic(tool, output)
+}
+
+static GENERIC: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?m)^(?P<file>[^\s:][^:\n]*):(?P<line>\d+)(?::(?P<column>\d+))?:\s*(?:(?P<severity>error|warning|info|note)\s*:\s*)?(?P<message>.+)$",
+    )
+    .unwrap()
+});
+
+/// Parse the common `file:line[:column]: [severity:] message` format
+pub fn generic(tool: &str, output: &str) -> Vec<ToolDiagnostic> {
+    GENERIC
+        .captures_iter(output)
+        .map(|caps| {
+            let severity = caps
+                .name("severity")
+                .map(|s| Severity::parse(s.as_str()))
+                .unwrap_or(Severity::Error);
+            let mut diagnostic =
+                ToolDiagnostic::new(tool, severity, caps["message"].trim().to_string());
+            diagnostic.location = Some(Location::new(
+                &caps["file"],
+                caps.name("line").map(|m| m.as_str()),
+                caps.name("column").map(|m| m.as_str()),
+            ));
+            diagnostic
+        })
+        .collect()
+}
+
+/// Serialize diagnostics as a JSON array
+pub fn to_json(diagnostics: &[ToolDiagnostic]) -> serde_json::Value {
+    let json = serde_json::to_string(diagnostics).unwrap_or_default();
+    serde_json::from_str(&crate::report::redact(&json)).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generic_parser() {
+        let output =
+            "checking\nconfig/app.yaml:12:4: error: unknown key\nlib.rs:3: warning: unused\n";
+        let diagnostics = generic("custom", output);
+
+        assert_eq!(diagnostics.len(), 2);
+        let location = diagnostics[0].location.as_ref().unwrap();
+        assert_eq!(location.file, PathBuf::from("config/app.yaml"));
+        assert_eq!(location.line, Some(12));
+        assert_eq!(location.column, Some(4));
+        assert_eq!(diagnostics[0].message, "unknown key");
+        assert_eq!(diagnostics[1].severity, Severity::Warning);
+        assert_eq!(diagnostics[1].location.as_ref().unwrap().column, None);
+    }
+
+    #[test]
+    fn test_detect_format_for_shell_stages() {
+        let output = "deploy.yaml:3:1: [error] wrong indentation (indentation)\n";
+        let diagnostics = parse_output("shell", output);
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].tool, "yamllint");
+        assert_eq!(diagnostics[0].rule.as_deref(), Some("indentation"));
+    }
+
+    #[test]
+    fn test_render_plain() {
+        colored::control::set_override(false);
+        let mut diagnostic = ToolDiagnostic::new("cue", Severity::Error, "conflicting values");
+        diagnostic.location = Some(Location::new("./a.json", Some("2"), Some("8")));
+        diagnostic.related = vec![Location::new("schema.cue", Some("3"), Some("5"))];
+
+        assert_eq!(
+            diagnostic.render(),
+            "a.json:2:8: error[cue]: conflicting values\n    → schema.cue:3:5"
+        );
+    }
+}
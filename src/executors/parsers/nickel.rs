@@ -0,0 +1,90 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Nickel error output
+//!
+//! Nickel renders codespan-style reports:
+//!
+//! ```text
+//! error: contract broken by a value
+//!   ┌─ config.ncl:3:10
+//!   │
+//! 3 │   port = "80",
+//!   │          ^^^^ applied to this expression
+//! ```
+
+use regex::Regex;
+use std::sync::LazyLock;
+
+use super::{Location, Severity, ToolDiagnostic};
+
+static HEADER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(?P<severity>error|warning|note)(?:\[(?P<code>[^\]]+)\])?: (?P<message>.+)$")
+        .unwrap()
+});
+
+static POSITION: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s*(?:┌─|-->)\s*(?P<file>[^:]+):(?P<line>\d+):(?P<column>\d+)").unwrap()
+});
+
+/// Parse `nickel export`/`nickel typecheck` errors
+pub fn parse(output: &str) -> Vec<ToolDiagnostic> {
+    let mut diagnostics: Vec<ToolDiagnostic> = Vec::new();
+
+    for line in output.lines() {
+        if let Some(caps) = HEADER.captures(line) {
+            let mut diagnostic = ToolDiagnostic::new(
+                "nickel",
+                Severity::parse(&caps["severity"]),
+                caps["message"].trim().to_string(),
+            );
+            diagnostic.rule = caps.name("code").map(|c| c.as_str().to_string());
+            diagnostics.push(diagnostic);
+        } else if let Some(caps) = POSITION.captures(line) {
+            let Some(diagnostic) = diagnostics.last_mut() else {
+                continue;
+            };
+            let location = Location::new(&caps["file"], Some(&caps["line"]), Some(&caps["column"]));
+            if diagnostic.location.is_none() {
+                diagnostic.location = Some(location);
+            } else {
+                diagnostic.related.push(location);
+            }
+        }
+    }
+
+    // A bare "error: ..." line alone is too weak a signal to claim the format
+    if diagnostics.iter().all(|d| d.location.is_none()) {
+        return vec![];
+    }
+
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_parse_report() {
+        let output = "error: contract broken by a value\n  ┌─ config.ncl:3:10\n  │\n3 │   port = \"80\",\n  │          ^^^^ applied to this expression\n  ┌─ lib.ncl:1:5\n";
+        let diagnostics = parse(output);
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].message, "contract broken by a value");
+        let location = diagnostics[0].location.as_ref().unwrap();
+        assert_eq!(location.file, PathBuf::from("config.ncl"));
+        assert_eq!(location.line, Some(3));
+        assert_eq!(diagnostics[0].related.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_coded_error() {
+        let output = "error[E001]: unbound identifier `prot`\n --> main.ncl:4:2\n";
+        let diagnostics = parse(output);
+
+        assert_eq!(diagnostics[0].rule.as_deref(), Some("E001"));
+        assert_eq!(diagnostics[0].location.as_ref().unwrap().column, Some(2));
+    }
+}
@@ -0,0 +1,135 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! SARIF 2.1.0 export
+//!
+//! Produces one run per tool so code-scanning UIs attribute each result
+//! to the validator that reported it.
+
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+use super::{Location, Severity, ToolDiagnostic};
+
+const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// Build a SARIF log from diagnostics
+pub fn to_sarif(diagnostics: &[ToolDiagnostic]) -> Value {
+    let mut by_tool: BTreeMap<&str, Vec<&ToolDiagnostic>> = BTreeMap::new();
+    for diagnostic in diagnostics {
+        by_tool
+            .entry(&diagnostic.tool)
+            .or_default()
+            .push(diagnostic);
+    }
+
+    let runs: Vec<Value> = by_tool
+        .into_iter()
+        .map(|(tool, diagnostics)| {
+            let mut rules: Vec<&str> = diagnostics
+                .iter()
+                .filter_map(|d| d.rule.as_deref())
+                .collect();
+            rules.sort_unstable();
+            rules.dedup();
+
+            json!({
+                "tool": {
+                    "driver": {
+                        "name": tool,
+                        "informationUri": "https://gitlab.com/rhodium-standard/conflow",
+                        "rules": rules.iter().map(|id| json!({ "id": id })).collect::<Vec<_>>(),
+                    }
+                },
+                "results": diagnostics.iter().map(|d| result(d)).collect::<Vec<_>>(),
+            })
+        })
+        .collect();
+
+    json!({
+        "$schema": SCHEMA,
+        "version": "2.1.0",
+        "runs": runs,
+    })
+}
+
+fn result(diagnostic: &ToolDiagnostic) -> Value {
+    let level = match diagnostic.severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Info => "note",
+    };
+
+    let mut result = json!({
+        "level": level,
+        "message": { "text": diagnostic.message },
+    });
+
+    if let Some(ref rule) = diagnostic.rule {
+        result["ruleId"] = json!(rule);
+    }
+    if let Some(ref location) = diagnostic.location {
+        result["locations"] = json!([physical_location(location)]);
+    }
+    if !diagnostic.related.is_empty() {
+        result["relatedLocations"] = diagnostic
+            .related
+            .iter()
+            .enumerate()
+            .map(|(id, l)| {
+                let mut related = physical_location(l);
+                related["id"] = json!(id);
+                related
+            })
+            .collect();
+    }
+
+    result
+}
+
+fn physical_location(location: &Location) -> Value {
+    let uri = location.file.to_string_lossy().replace('\\', "/");
+    let mut physical = json!({ "artifactLocation": { "uri": uri } });
+
+    if let Some(line) = location.line {
+        let mut region = json!({ "startLine": line });
+        if let Some(column) = location.column {
+            region["startColumn"] = json!(column);
+        }
+        physical["region"] = region;
+    }
+
+    json!({ "physicalLocation": physical })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::parse_output;
+    use super::*;
+
+    #[test]
+    fn test_sarif_structure() {
+        let mut diagnostics = parse_output(
+            "yamllint",
+            "a.yaml:3:1: [warning] missing document start \"---\" (document-start)\n",
+        );
+        diagnostics.extend(parse_output(
+            "kubeconform",
+            "b.yaml - Service api is invalid: bad port\n",
+        ));
+
+        let sarif = to_sarif(&diagnostics);
+        assert_eq!(sarif["version"], "2.1.0");
+
+        let runs = sarif["runs"].as_array().unwrap();
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0]["tool"]["driver"]["name"], "kubeconform");
+
+        let yamllint = &runs[1]["results"][0];
+        assert_eq!(yamllint["level"], "warning");
+        assert_eq!(yamllint["ruleId"], "document-start");
+        let region = &yamllint["locations"][0]["physicalLocation"]["region"];
+        assert_eq!(region["startLine"], 3);
+        assert_eq!(region["startColumn"], 1);
+    }
+}
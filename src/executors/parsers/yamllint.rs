@@ -0,0 +1,83 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! yamllint output
+//!
+//! Supports both the `parsable` format
+//! (`file:line:col: [level] message (rule)`) and the default `standard`
+//! format, where each file name is followed by indented problems.
+
+use regex::Regex;
+use std::sync::LazyLock;
+
+use super::{Location, Severity, ToolDiagnostic};
+
+static PARSABLE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(?P<file>[^:]+):(?P<line>\d+):(?P<column>\d+): \[(?P<severity>error|warning)\] (?P<message>.+?)(?: \((?P<rule>[\w-]+)\))?$").unwrap()
+});
+
+static STANDARD: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s+(?P<line>\d+):(?P<column>\d+)\s+(?P<severity>error|warning)\s+(?P<message>.+?)(?:\s+\((?P<rule>[\w-]+)\))?$").unwrap()
+});
+
+/// Parse yamllint problems
+pub fn parse(output: &str) -> Vec<ToolDiagnostic> {
+    let mut diagnostics = Vec::new();
+    let mut current_file: Option<&str> = None;
+
+    for line in output.lines() {
+        let (file, caps) = if let Some(caps) = PARSABLE.captures(line) {
+            (caps.name("file").map(|f| f.as_str()), caps)
+        } else if let Some(caps) = STANDARD.captures(line) {
+            (current_file, caps)
+        } else {
+            if !line.trim().is_empty() && !line.starts_with(char::is_whitespace) {
+                current_file = Some(line.trim());
+            }
+            continue;
+        };
+
+        let Some(file) = file else {
+            continue;
+        };
+
+        let mut diagnostic = ToolDiagnostic::new(
+            "yamllint",
+            Severity::parse(&caps["severity"]),
+            caps["message"].trim().to_string(),
+        );
+        diagnostic.rule = caps.name("rule").map(|r| r.as_str().to_string());
+        diagnostic.location = Some(Location::new(
+            file,
+            Some(&caps["line"]),
+            Some(&caps["column"]),
+        ));
+        diagnostics.push(diagnostic);
+    }
+
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_parse_standard() {
+        let output = "config/app.yaml\n  3:1       error    wrong indentation: expected 2 but found 0  (indentation)\n  9:81      warning  line too long (92 > 80 characters)  (line-length)\n";
+        let diagnostics = parse(output);
+
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(
+            diagnostics[0].location.as_ref().unwrap().file,
+            PathBuf::from("config/app.yaml")
+        );
+        assert_eq!(
+            diagnostics[0].message,
+            "wrong indentation: expected 2 but found 0"
+        );
+        assert_eq!(diagnostics[1].severity, Severity::Warning);
+        assert_eq!(diagnostics[1].rule.as_deref(), Some("line-length"));
+    }
+}
@@ -0,0 +1,263 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Render executor
+//!
+//! Renders Helm chart templates and Go-templated files with sample values
+//! so the stages after it validate real YAML. Each input is written under
+//! the stage's output directory (default `.conflow/rendered/<stage>`) at its
+//! path relative to the working directory, without a `.tmpl` / `.gotmpl`
+//! extension; the rendered files are the stage's outputs. Directories,
+//! chart helpers and `NOTES.txt` are skipped, as are templates that render
+//! to nothing.
+
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use super::{resolve_inputs, ExecutionResult, Executor};
+use crate::analyzer::{
+    is_chart_helper, render_template, untemplated_path, KubeVersion, SampleValues,
+};
+use crate::errors::ConflowError;
+use crate::pipeline::{Stage, Tool};
+
+/// Executor for render stages
+pub struct RenderExecutor {
+    /// Kubernetes release templates see in `.Capabilities`
+    kube_version: Option<KubeVersion>,
+}
+
+impl RenderExecutor {
+    /// Create an executor rendering for the newest known Kubernetes release
+    pub fn new() -> Self {
+        Self { kube_version: None }
+    }
+
+    /// Render for a specific Kubernetes release
+    pub fn with_kube_version(mut self, version: Option<KubeVersion>) -> Self {
+        self.kube_version = version;
+        self
+    }
+
+    /// Where a stage writes rendered files
+    fn output_dir(stage: &Stage, working_dir: &Path) -> PathBuf {
+        match stage.output {
+            Some(ref output) => working_dir.join(output.path()),
+            None => working_dir.join(".conflow/rendered").join(&stage.name),
+        }
+    }
+
+    fn sample_values(&self, stage: &Stage, working_dir: &Path) -> SampleValues {
+        let Tool::Render {
+            values,
+            set,
+            release,
+            namespace,
+        } = &stage.tool
+        else {
+            return SampleValues::default();
+        };
+        SampleValues {
+            files: values.iter().map(|v| working_dir.join(v)).collect(),
+            set: set.clone(),
+            release: release.clone(),
+            namespace: namespace.clone(),
+            kube_version: self.kube_version,
+        }
+    }
+}
+
+impl Default for RenderExecutor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Executor for RenderExecutor {
+    async fn execute(
+        &self,
+        stage: &Stage,
+        working_dir: &Path,
+        _env: &HashMap<String, String>,
+        resolved_inputs: Option<&[PathBuf]>,
+    ) -> Result<ExecutionResult, ConflowError> {
+        let start = Instant::now();
+        self.validate_stage(stage)?;
+        let values = self.sample_values(stage, working_dir);
+        let inputs = match resolved_inputs {
+            Some(resolved) => resolved.to_vec(),
+            None => resolve_inputs(stage, working_dir)?,
+        };
+        let out_dir = Self::output_dir(stage, working_dir);
+
+        let mut outputs = Vec::new();
+        let mut errors = Vec::new();
+        let mut empty = 0;
+        let templates = inputs
+            .iter()
+            .filter(|input| input.is_file() && !is_chart_helper(input));
+        for input in templates {
+            let content =
+                std::fs::read_to_string(input).map_err(|e| ConflowError::FileReadError {
+                    path: input.clone(),
+                    error: e.to_string(),
+                })?;
+            let rendered = match render_template(input, &content, &values) {
+                Ok(rendered) => rendered,
+                Err(e) => {
+                    errors.push(e.to_string());
+                    continue;
+                }
+            };
+            if rendered.trim().is_empty() {
+                empty += 1;
+                continue;
+            }
+
+            let relative = input.strip_prefix(working_dir).unwrap_or(input);
+            let relative = match relative.is_absolute() {
+                true => PathBuf::from(relative.file_name().unwrap_or_default()),
+                false => relative.to_path_buf(),
+            };
+            let path = out_dir.join(untemplated_path(&relative));
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent).map_err(|e| ConflowError::FileWriteError {
+                    path: parent.to_path_buf(),
+                    error: e.to_string(),
+                })?;
+            }
+            std::fs::write(&path, rendered).map_err(|e| ConflowError::FileWriteError {
+                path: path.clone(),
+                error: e.to_string(),
+            })?;
+            outputs.push(path);
+        }
+
+        let shown = out_dir.strip_prefix(working_dir).unwrap_or(&out_dir);
+        let mut stdout = format!(
+            "Rendered {} file(s) into {}",
+            outputs.len(),
+            shown.display()
+        );
+        if empty > 0 {
+            stdout.push_str(&format!(" ({} rendered nothing)", empty));
+        }
+        if !errors.is_empty() {
+            let mut result = ExecutionResult::failure(errors.join("\n"), 1, start.elapsed());
+            result.stdout = stdout;
+            result.outputs = outputs;
+            return Ok(result);
+        }
+        Ok(ExecutionResult::success(stdout, start.elapsed(), outputs))
+    }
+
+    async fn check_available(&self) -> Result<bool, ConflowError> {
+        Ok(true)
+    }
+
+    async fn version(&self) -> Result<String, ConflowError> {
+        Ok(format!("built-in {}", env!("CARGO_PKG_VERSION")))
+    }
+
+    fn validate_stage(&self, stage: &Stage) -> Result<(), ConflowError> {
+        match &stage.tool {
+            Tool::Render { .. } => Ok(()),
+            _ => Err(ConflowError::InvalidStage {
+                stage: stage.name.clone(),
+                reason: "Not a render stage".to_string(),
+            }),
+        }
+    }
+
+    fn command_line(
+        &self,
+        stage: &Stage,
+        working_dir: &Path,
+        resolved_inputs: Option<&[PathBuf]>,
+    ) -> Result<Vec<String>, ConflowError> {
+        self.validate_stage(stage)?;
+        let inputs = match resolved_inputs {
+            Some(resolved) => resolved.to_vec(),
+            None => resolve_inputs(stage, working_dir)?,
+        };
+
+        // Templates are rendered in-process, so there is no command to show
+        Ok(std::iter::once("(render with sample values)".to_string())
+            .chain(inputs.iter().map(|i| i.display().to_string()))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_render_stage() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let root = temp.path();
+        let write = |path: &str, content: &str| {
+            let path = root.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        };
+        write(
+            "chart/Chart.yaml",
+            "apiVersion: v2\nname: web\nversion: 0.1.0\n",
+        );
+        write("chart/values.yaml", "replicas: 1\nmonitoring: false\n");
+        write(
+            "chart/templates/_helpers.tpl",
+            "{{ define \"name\" }}web{{ end }}",
+        );
+        write(
+            "chart/templates/deployment.yaml",
+            "name: {{ template \"name\" }}\nreplicas: {{ .Values.replicas }}\n",
+        );
+        write(
+            "chart/templates/monitor.yaml",
+            "{{- if .Values.monitoring }}\nkind: ServiceMonitor\n{{- end }}\n",
+        );
+        write("ci-values.yaml", "replicas: 3\n");
+
+        let stage: Stage = serde_yaml::from_str(
+            r#"
+name: render
+input: "chart/templates/**/*"
+output: rendered
+tool: { type: render, values: [ci-values.yaml], set: { image.tag: "1.0" } }
+"#,
+        )
+        .unwrap();
+        let executor = RenderExecutor::new();
+        let result = executor
+            .execute(&stage, root, &HashMap::new(), None)
+            .await
+            .unwrap();
+        assert!(result.success, "{}", result.stderr);
+        assert_eq!(
+            result.stdout,
+            "Rendered 1 file(s) into rendered (1 rendered nothing)"
+        );
+        assert_eq!(
+            result.outputs,
+            [root.join("rendered/chart/templates/deployment.yaml")]
+        );
+        let rendered = std::fs::read_to_string(&result.outputs[0]).unwrap();
+        assert_eq!(rendered, "name: web\nreplicas: 3\n");
+
+        write(
+            "chart/templates/bad.yaml",
+            "x: {{ required \"x is required\" .Values.x }}\n",
+        );
+        let result = executor
+            .execute(&stage, root, &HashMap::new(), None)
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result.stderr.contains("x is required"));
+    }
+}
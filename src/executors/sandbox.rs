@@ -0,0 +1,229 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Sandboxed command execution
+//!
+//! Wraps stage commands in bubblewrap (`bwrap`) so they run in fresh
+//! namespaces: no network, the filesystem mounted read-only except for
+//! the working directory and configured writable paths, and a private
+//! `/tmp`. Resource limits are applied with `setrlimit` before exec and
+//! are inherited by everything the command spawns.
+
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+
+use crate::errors::ConflowError;
+use crate::pipeline::{ResourceLimits, SandboxConfig};
+
+/// A configured sandbox
+#[derive(Debug, Clone)]
+pub struct Sandbox {
+    config: SandboxConfig,
+}
+
+impl Sandbox {
+    /// Create a sandbox from pipeline configuration
+    pub fn new(config: SandboxConfig) -> Self {
+        Self { config }
+    }
+
+    /// Check that the sandbox can be used on this system
+    pub fn check_available() -> Result<PathBuf, ConflowError> {
+        if !cfg!(target_os = "linux") {
+            return Err(ConflowError::Sandbox {
+                message: format!("not supported on {}", std::env::consts::OS),
+                help: Some("Disable 'sandbox.enabled' or run on Linux".into()),
+            });
+        }
+
+        which::which("bwrap").map_err(|_| ConflowError::Sandbox {
+            message: "bubblewrap (bwrap) not found".into(),
+            help: Some(
+                "Install bubblewrap (e.g. 'apt install bubblewrap' or 'dnf install bubblewrap')"
+                    .into(),
+            ),
+        })
+    }
+
+    /// Rebuild `cmd` to run inside the sandbox
+    pub fn wrap(&self, cmd: Command, working_dir: &Path) -> Result<Command, ConflowError> {
+        let bwrap = Self::check_available()?;
+        let std_cmd = cmd.as_std();
+
+        let mut wrapped = Command::new(bwrap);
+        wrapped.args(self.bwrap_args(working_dir));
+        wrapped.arg("--");
+        wrapped.arg(std_cmd.get_program());
+        wrapped.args(std_cmd.get_args());
+
+        for (key, value) in std_cmd.get_envs() {
+            match value {
+                Some(value) => wrapped.env(key, value),
+                None => wrapped.env_remove(key),
+            };
+        }
+        wrapped.current_dir(working_dir);
+        apply_limits(&mut wrapped, self.config.limits);
+
+        Ok(wrapped)
+    }
+
+    /// bubblewrap arguments isolating a command to `working_dir`
+    fn bwrap_args(&self, working_dir: &Path) -> Vec<OsString> {
+        let mut args: Vec<OsString> = [
+            "--die-with-parent",
+            "--new-session",
+            "--unshare-all",
+            "--ro-bind",
+            "/",
+            "/",
+            "--dev",
+            "/dev",
+            "--proc",
+            "/proc",
+            "--tmpfs",
+            "/tmp",
+        ]
+        .iter()
+        .map(OsString::from)
+        .collect();
+
+        if self.config.network {
+            args.push("--share-net".into());
+        }
+
+        let mut bind = |path: &Path| {
+            args.push("--bind".into());
+            args.push(path.into());
+            args.push(path.into());
+        };
+
+        bind(working_dir);
+        for path in &self.config.writable {
+            let path = working_dir.join(path);
+            // bwrap fails on missing bind sources
+            if path.exists() {
+                bind(&path);
+            }
+        }
+
+        args.push("--chdir".into());
+        args.push(working_dir.into());
+        args
+    }
+}
+
+/// Apply resource limits to a command before it execs
+#[cfg(unix)]
+pub(crate) fn apply_limits(cmd: &mut Command, limits: ResourceLimits) {
+    const MB: u64 = 1024 * 1024;
+
+    // The resource constant type differs between libcs, so let it infer
+    let resources = [
+        libc::RLIMIT_CPU,
+        libc::RLIMIT_AS,
+        libc::RLIMIT_FSIZE,
+        libc::RLIMIT_NOFILE,
+    ];
+    let values = [
+        limits.cpu_seconds,
+        limits.memory_mb.map(|m| m * MB),
+        limits.file_size_mb.map(|m| m * MB),
+        limits.open_files,
+    ];
+    let limits: Vec<_> = resources
+        .into_iter()
+        .zip(values)
+        .filter_map(|(resource, value)| value.map(|v| (resource, v)))
+        .collect();
+
+    if limits.is_empty() {
+        return;
+    }
+
+    // SAFETY: only calls setrlimit, which is async-signal-safe, on data
+    // prepared before fork.
+    unsafe {
+        cmd.pre_exec(move || {
+            for &(resource, value) in &limits {
+                let rlimit = libc::rlimit {
+                    rlim_cur: value as libc::rlim_t,
+                    rlim_max: value as libc::rlim_t,
+                };
+                if libc::setrlimit(resource, &rlimit) != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+            }
+            Ok(())
+        });
+    }
+}
+
+#[cfg(not(unix))]
+pub(crate) fn apply_limits(_cmd: &mut Command, _limits: ResourceLimits) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bwrap_args() {
+        let temp = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir(temp.path().join("out")).unwrap();
+
+        let sandbox = Sandbox::new(SandboxConfig {
+            enabled: true,
+            writable: vec!["out".into(), "missing".into()],
+            ..Default::default()
+        });
+        let args: Vec<String> = sandbox
+            .bwrap_args(temp.path())
+            .iter()
+            .map(|a| a.to_string_lossy().to_string())
+            .collect();
+        let joined = args.join(" ");
+
+        assert!(joined.contains("--unshare-all --ro-bind / /"));
+        assert!(!joined.contains("--share-net"));
+        let root = temp.path().display().to_string();
+        assert!(joined.contains(&format!("--bind {root} {root}")));
+        assert!(joined.contains(&format!("--bind {root}/out {root}/out")));
+        assert!(!joined.contains("missing"));
+        assert!(joined.ends_with(&format!("--chdir {root}")));
+    }
+
+    #[test]
+    fn test_network_opt_in() {
+        let sandbox = Sandbox::new(SandboxConfig {
+            enabled: true,
+            network: true,
+            ..Default::default()
+        });
+        let args = sandbox.bwrap_args(Path::new("/work"));
+        assert!(args.contains(&OsString::from("--share-net")));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_file_size_limit() {
+        let temp = tempfile::TempDir::new().unwrap();
+
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c")
+            .arg("head -c 2097152 /dev/zero > big")
+            .current_dir(temp.path());
+        apply_limits(
+            &mut cmd,
+            ResourceLimits {
+                file_size_mb: Some(1),
+                ..Default::default()
+            },
+        );
+
+        let status = cmd.status().await.unwrap();
+        assert!(!status.success());
+        let written = std::fs::metadata(temp.path().join("big")).unwrap().len();
+        assert!(written <= 1024 * 1024);
+    }
+}
@@ -8,20 +8,30 @@
 use async_trait::async_trait;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::Instant;
 use tokio::process::Command;
 
-use super::{ExecutionResult, Executor};
+use super::{ExecutionResult, Executor, Sandbox};
 use crate::errors::ConflowError;
 use crate::pipeline::{Stage, Tool};
 
 /// Shell executor
-pub struct ShellExecutor;
+pub struct ShellExecutor {
+    /// Sandbox for commands, when enabled
+    sandbox: Option<Arc<Sandbox>>,
+}
 
 impl ShellExecutor {
     /// Create a new shell executor
     pub fn new() -> Self {
-        Self
+        Self { sandbox: None }
+    }
+
+    /// Run commands in a sandbox
+    pub fn sandboxed(mut self, sandbox: Option<Arc<Sandbox>>) -> Self {
+        self.sandbox = sandbox;
+        self
     }
 }
 
@@ -49,11 +59,15 @@ impl Executor for ShellExecutor {
 
         let start = Instant::now();
 
-        let mut cmd = Command::new(shell);
-        cmd.arg("-c").arg(command);
+        let mut cmd = Command::from(super::shell_command(shell, command));
         cmd.current_dir(working_dir);
         cmd.envs(env);
 
+        if let Some(ref sandbox) = self.sandbox {
+            cmd = sandbox.wrap(cmd, working_dir)?;
+        }
+
+        tracing::debug!(command = ?super::argv(&cmd), "spawning shell");
         let output = cmd.output().await.map_err(|e| ConflowError::ToolExecutionFailed {
             tool: "shell".to_string(),
             error: e.to_string(),
@@ -61,6 +75,11 @@ impl Executor for ShellExecutor {
         })?;
 
         let duration = start.elapsed();
+        tracing::debug!(
+            exit_code = output.status.code(),
+            duration_ms = duration.as_millis() as u64,
+            "shell finished"
+        );
         let stdout = String::from_utf8_lossy(&output.stdout).to_string();
         let stderr = String::from_utf8_lossy(&output.stderr).to_string();
 
@@ -141,6 +160,22 @@ impl Executor for ShellExecutor {
 
         Ok(())
     }
+
+    fn command_line(
+        &self,
+        stage: &Stage,
+        _working_dir: &Path,
+        _resolved_inputs: Option<&[PathBuf]>,
+    ) -> Result<Vec<String>, ConflowError> {
+        let Tool::Shell { command, shell } = &stage.tool else {
+            return Err(ConflowError::InvalidStage {
+                stage: stage.name.clone(),
+                reason: "Expected Shell tool".to_string(),
+            });
+        };
+
+        Ok(super::shell_argv(shell, command))
+    }
 }
 
 #[cfg(test)]
@@ -157,11 +192,19 @@ mod tests {
                 shell: "bash".into(),
             },
             input: Input::Single("*".into()),
+            exclude: vec![],
             output: None,
+            snapshot: false,
+            contract: None,
             depends_on: vec![],
             allow_failure: false,
             env: HashMap::new(),
             condition: None,
+            env_file: None,
+            workdir: None,
+            shard: None,
+            runs_on: Vec::new(),
+            manual: false,
         }
     }
 
@@ -192,4 +235,32 @@ mod tests {
         assert!(result.success);
         assert!(result.stdout.contains("hello"));
     }
+
+    #[test]
+    fn test_shell_kinds() {
+        use crate::executors::ShellKind;
+
+        assert_eq!(ShellKind::of("bash"), ShellKind::Posix);
+        assert_eq!(ShellKind::of("/usr/bin/zsh"), ShellKind::Posix);
+        assert_eq!(ShellKind::of("pwsh"), ShellKind::PowerShell);
+        assert_eq!(ShellKind::of(r"C:\Windows\System32\PowerShell.exe"), ShellKind::PowerShell);
+        assert_eq!(ShellKind::of("cmd.exe"), ShellKind::Cmd);
+
+        let mut stage = make_shell_stage("test", "Get-ChildItem");
+        stage.tool = Tool::Shell {
+            command: "Get-ChildItem".into(),
+            shell: "pwsh".into(),
+        };
+        let argv = ShellExecutor::new()
+            .command_line(&stage, Path::new("."), None)
+            .unwrap();
+        assert_eq!(argv.first().map(String::as_str), Some("pwsh"));
+        assert_eq!(argv[argv.len() - 2], "-Command");
+
+        let path = r"C:\Program Files\app's\a.json";
+        assert_eq!(ShellKind::PowerShell.quote(r"C:\a.json"), r"C:\a.json");
+        assert_eq!(ShellKind::PowerShell.quote(path), r"'C:\Program Files\app''s\a.json'");
+        assert_eq!(ShellKind::Cmd.quote(path), r#""C:\Program Files\app's\a.json""#);
+        assert_eq!(ShellKind::Posix.quote("it's"), r"'it'\''s'");
+    }
 }
@@ -0,0 +1,228 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! C ABI
+//!
+//! A small C interface to the analyzer, pipeline validation and compliance
+//! checking, built with the `ffi` feature:
+//!
+//! ```bash
+//! cargo rustc --lib --release --features ffi --crate-type cdylib
+//! ```
+//!
+//! Every call takes UTF-8 paths and returns a JSON string that the caller
+//! releases with `conflow_string_free`. Analyses and compliance reports use
+//! the [`crate::report`] format. On failure a call returns NULL and
+//! `conflow_last_error` describes the error until the next call on the same
+//! thread. The declarations are in `include/conflow.h`; Python uses the
+//! same calls through the `conflow-py` module ([`crate::python`]).
+
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::path::{Path, PathBuf};
+
+use crate::analyzer::ConfigAnalyzer;
+use crate::errors::ConflowError;
+use crate::pipeline::{Pipeline, PipelineValidator};
+use crate::rsr::ComplianceChecker;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Library version, as a static NUL-terminated string
+#[no_mangle]
+pub extern "C" fn conflow_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr().cast()
+}
+
+/// Analyze a configuration file
+///
+/// # Safety
+///
+/// `path` must be NULL or a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn conflow_analyze(path: *const c_char) -> *mut c_char {
+    call(path, analyze)
+}
+
+/// Validate a pipeline file
+///
+/// Returns `{"valid": bool, "errors": [...], "warnings": [...]}`. Missing
+/// env files and working directories are reported as warnings.
+///
+/// # Safety
+///
+/// `path` must be NULL or a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn conflow_validate(path: *const c_char) -> *mut c_char {
+    call(path, validate)
+}
+
+/// Check a project's RSR compliance, using its `.rsr.yaml`
+///
+/// # Safety
+///
+/// `project_root` must be NULL or a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn conflow_check_compliance(project_root: *const c_char) -> *mut c_char {
+    call(project_root, check_compliance)
+}
+
+/// JSON analysis of a configuration file, as `conflow_analyze` returns it
+pub fn analyze(path: &Path) -> Result<String, ConflowError> {
+    let analysis = ConfigAnalyzer::new()
+        .analyze_files(&[path.to_path_buf()])
+        .remove(0)?;
+    crate::report::to_json(&analysis)
+}
+
+/// JSON validation result of a pipeline file, as `conflow_validate` returns
+/// it
+pub fn validate(path: &Path) -> Result<String, ConflowError> {
+    let pipeline = Pipeline::from_file(path)?;
+    let mut result = PipelineValidator::validate(&pipeline)?;
+    let base = path.parent().unwrap_or(Path::new("."));
+    result
+        .warnings
+        .extend(PipelineValidator::validate_files(&pipeline, base)?);
+
+    Ok(serde_json::to_string_pretty(&serde_json::json!({
+        "valid": result.is_valid(),
+        "errors": result.errors,
+        "warnings": result.warnings,
+    }))?)
+}
+
+/// JSON compliance report of a project, as `conflow_check_compliance`
+/// returns it
+pub fn check_compliance(project_root: &Path) -> Result<String, ConflowError> {
+    let report = ComplianceChecker::for_project(project_root)?.check(project_root)?;
+    crate::report::to_json(&report)
+}
+
+/// Message of the last error on this thread, or NULL
+///
+/// The string is owned by conflow and valid until the next call.
+#[no_mangle]
+pub extern "C" fn conflow_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| {
+        e.borrow()
+            .as_ref()
+            .map_or(std::ptr::null(), |message| message.as_ptr())
+    })
+}
+
+/// Release a string returned by conflow
+///
+/// # Safety
+///
+/// `s` must be NULL or a string returned by a conflow call, and must not be
+/// used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn conflow_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// Run `f` on a path argument, turning errors and panics into NULL
+unsafe fn call(
+    path: *const c_char,
+    f: impl FnOnce(&Path) -> Result<String, ConflowError>,
+) -> *mut c_char {
+    set_error(None);
+    if path.is_null() {
+        set_error(Some("path is NULL".to_string()));
+        return std::ptr::null_mut();
+    }
+    let path = match CStr::from_ptr(path).to_str() {
+        Ok(path) => PathBuf::from(path),
+        Err(_) => {
+            set_error(Some("path is not valid UTF-8".to_string()));
+            return std::ptr::null_mut();
+        }
+    };
+
+    // Unwinding across the C boundary is undefined behaviour
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| f(&path)));
+    let message = match result {
+        Ok(Ok(json)) => match CString::new(json) {
+            Ok(json) => return json.into_raw(),
+            Err(e) => e.to_string(),
+        },
+        Ok(Err(e)) => e.to_string(),
+        Err(_) => "conflow panicked".to_string(),
+    };
+    set_error(Some(message));
+    std::ptr::null_mut()
+}
+
+fn set_error(message: Option<String>) {
+    let message = message.map(|m| CString::new(m.replace('\0', " ")).unwrap_or_default());
+    LAST_ERROR.with(|e| *e.borrow_mut() = message);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn take(s: *mut c_char) -> Option<String> {
+        if s.is_null() {
+            return None;
+        }
+        let value = unsafe { CStr::from_ptr(s) }.to_str().unwrap().to_string();
+        unsafe { conflow_string_free(s) };
+        Some(value)
+    }
+
+    fn last_error() -> String {
+        let error = conflow_last_error();
+        assert!(!error.is_null());
+        unsafe { CStr::from_ptr(error) }
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn test_ffi_calls() {
+        let temp = tempfile::tempdir().unwrap();
+        let config = temp.path().join("config.yaml");
+        std::fs::write(&config, "name: app\nreplicas: 3\n").unwrap();
+        std::fs::write(
+            temp.path().join(".conflow.yaml"),
+            "name: app\nstages:\n  - name: check\n    tool:\n      type: shell\n      \
+             command: \"true\"\n    input: \"*.yaml\"\n",
+        )
+        .unwrap();
+
+        let path = |p: &Path| CString::new(p.to_str().unwrap()).unwrap();
+
+        let json = take(unsafe { conflow_analyze(path(&config).as_ptr()) }).unwrap();
+        let analysis: crate::analyzer::Analysis = crate::report::from_json(&json).unwrap();
+        assert_eq!(analysis.format, crate::analyzer::ConfigFormat::Yaml);
+
+        let pipeline = path(&temp.path().join(".conflow.yaml"));
+        let json = take(unsafe { conflow_validate(pipeline.as_ptr()) }).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["valid"], true);
+
+        let json = take(unsafe { conflow_check_compliance(path(temp.path()).as_ptr()) }).unwrap();
+        assert!(json.contains("\"kind\": \"compliance\""));
+        assert!(conflow_last_error().is_null());
+    }
+
+    #[test]
+    fn test_ffi_errors() {
+        assert!(take(unsafe { conflow_analyze(std::ptr::null()) }).is_none());
+        assert_eq!(last_error(), "path is NULL");
+
+        let missing = CString::new("/nonexistent/config.yaml").unwrap();
+        assert!(take(unsafe { conflow_analyze(missing.as_ptr()) }).is_none());
+        assert!(last_error().contains("/nonexistent/config.yaml"));
+
+        let version = unsafe { CStr::from_ptr(conflow_version()) };
+        assert_eq!(version.to_str().unwrap(), crate::VERSION);
+    }
+}
@@ -0,0 +1,414 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! The `Agent` service behind `conflow agent`: runs single stages for
+//! `conflow run --coordinator`, with files exchanged through its artifact
+//! store
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::io::AsyncReadExt;
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::{ReceiverStream, TcpListenerStream, UnboundedReceiverStream};
+use tonic::transport::{Identity, ServerTlsConfig};
+use tonic::{Request, Response, Status, Streaming};
+
+use super::pb::agent_server::{Agent, AgentServer};
+use super::pb::coordinator_client::CoordinatorClient;
+use super::pb::execution_event::Event;
+use super::{internal, pb, portable, status, with_key, CHUNK_SIZE};
+use crate::cache::{ArtifactStore, ArtifactWriter};
+use crate::errors::ConflowError;
+use crate::executors::create_executors;
+use crate::pipeline::Pipeline;
+use crate::server::{safe_relative, Scratch};
+
+/// Agent settings
+#[derive(Debug, Clone)]
+pub struct AgentConfig {
+    /// Labels matched against stages' `runs_on`
+    pub labels: Vec<String>,
+
+    /// Keys runners must send to use the agent; never the coordinator's API
+    /// keys
+    pub runner_keys: Vec<String>,
+
+    /// Certificate and private key the agent serves TLS with
+    pub identity: Identity,
+
+    /// Most bytes one `PutArtifacts` call may upload
+    pub max_upload: u64,
+
+    /// Where files are kept by content hash
+    pub store: ArtifactStore,
+}
+
+/// What the agent tells the coordinator and runners about itself
+pub fn describe(config: &AgentConfig, address: &str) -> pb::AgentInfo {
+    let mut tools: Vec<_> = crate::executors::create_default_executors()
+        .into_keys()
+        .collect();
+    tools.extend(
+        crate::executors::DEPLOY_TOOLS
+            .iter()
+            .filter(|tool| which::which(tool).is_ok())
+            .map(|tool| tool.to_string()),
+    );
+    tools.sort();
+    pb::AgentInfo {
+        version: crate::VERSION.to_string(),
+        labels: config.labels.clone(),
+        tools,
+        address: address.to_string(),
+        id: String::new(),
+    }
+}
+
+/// Serve the `Agent` service over TLS on `listener` until the task is
+/// dropped
+pub async fn serve(listener: TcpListener, config: AgentConfig) -> Result<(), ConflowError> {
+    let tls = ServerTlsConfig::new().identity(config.identity.clone());
+    tonic::transport::Server::builder()
+        .tls_config(tls)
+        .map_err(|e| ConflowError::Io {
+            message: format!("invalid TLS certificate or key: {}", e),
+        })?
+        .add_service(AgentServer::new(AgentService {
+            config: Arc::new(config),
+        }))
+        .serve_with_incoming(TcpListenerStream::new(listener))
+        .await
+        .map_err(|e| ConflowError::Io {
+            message: format!("gRPC server failed: {}", e),
+        })
+}
+
+/// Register with the coordinator and heartbeat until the task is dropped,
+/// registering again whenever the coordinator doesn't know the agent
+pub async fn keep_registered(coordinator: String, info: pb::AgentInfo, agent_key: String) {
+    let key = Some(agent_key.as_str());
+    let mut registered: Option<(String, Duration)> = None;
+    let mut retry = Duration::from_secs(1);
+
+    loop {
+        let attempt = async {
+            let mut client = CoordinatorClient::connect(coordinator.clone())
+                .await
+                .map_err(|e| e.to_string())?;
+            if let Some((ref id, _)) = registered {
+                let request = with_key(pb::HeartbeatRequest { id: id.clone() }, key);
+                let known = client
+                    .heartbeat(request)
+                    .await
+                    .map_err(|e| e.message().to_string())?;
+                if known.into_inner().known {
+                    return Ok(None);
+                }
+                tracing::info!("the coordinator forgot this agent; registering again");
+            }
+            let request = with_key(
+                pb::RegisterRequest {
+                    agent: Some(info.clone()),
+                },
+                key,
+            );
+            let response = client
+                .register(request)
+                .await
+                .map_err(|e| e.message().to_string())?;
+            let response = response.into_inner();
+            tracing::info!(id = %response.id, %coordinator, "registered with the coordinator");
+            Ok::<_, String>(Some((
+                response.id,
+                Duration::from_millis(response.heartbeat_interval_ms.max(100)),
+            )))
+        };
+
+        let wait = match attempt.await {
+            Ok(fresh) => {
+                if fresh.is_some() {
+                    registered = fresh;
+                }
+                retry = Duration::from_secs(1);
+                registered
+                    .as_ref()
+                    .map(|(_, interval)| *interval)
+                    .unwrap_or(retry)
+            }
+            Err(e) => {
+                tracing::warn!(error = %e, %coordinator, "can't reach the coordinator");
+                retry = (retry * 2).min(super::coordinator::HEARTBEAT_INTERVAL);
+                retry
+            }
+        };
+        tokio::time::sleep(wait).await;
+    }
+}
+
+struct AgentService {
+    config: Arc<AgentConfig>,
+}
+
+impl AgentService {
+    /// Reject requests without a runner key; an agent without runner keys
+    /// refuses everything rather than serving anyone
+    fn authorize<T>(&self, request: &Request<T>) -> Result<(), Status> {
+        if self.config.runner_keys.is_empty() {
+            return Err(Status::permission_denied("the agent has no runner keys"));
+        }
+        super::authorize(&self.config.runner_keys, request)
+    }
+}
+
+#[tonic::async_trait]
+impl Agent for AgentService {
+    async fn describe(
+        &self,
+        request: Request<pb::DescribeRequest>,
+    ) -> Result<Response<pb::AgentInfo>, Status> {
+        self.authorize(&request)?;
+        Ok(Response::new(describe(&self.config, "")))
+    }
+
+    async fn missing_artifacts(
+        &self,
+        request: Request<pb::ArtifactList>,
+    ) -> Result<Response<pb::ArtifactList>, Status> {
+        self.authorize(&request)?;
+        let hashes = request
+            .into_inner()
+            .hashes
+            .into_iter()
+            .filter(|hash| !self.config.store.contains(hash))
+            .collect();
+        Ok(Response::new(pb::ArtifactList { hashes }))
+    }
+
+    async fn put_artifacts(
+        &self,
+        request: Request<Streaming<pb::ArtifactChunk>>,
+    ) -> Result<Response<pb::PutArtifactsResponse>, Status> {
+        self.authorize(&request)?;
+        let mut chunks = request.into_inner();
+        let mut current: Option<(String, ArtifactWriter)> = None;
+        let mut stored = 0;
+        let mut received = 0u64;
+
+        while let Some(chunk) = chunks.message().await? {
+            received += chunk.data.len() as u64;
+            if received > self.config.max_upload {
+                return Err(Status::resource_exhausted(format!(
+                    "uploads are limited to {} bytes per call",
+                    self.config.max_upload
+                )));
+            }
+            let (hash, writer) = match current.take() {
+                Some((hash, writer)) if hash == chunk.hash => (hash, writer),
+                previous => {
+                    if let Some((_, writer)) = previous {
+                        writer.finish().map_err(invalid)?;
+                        stored += 1;
+                    }
+                    let writer = self.config.store.writer(&chunk.hash).map_err(invalid)?;
+                    (chunk.hash, writer)
+                }
+            };
+            let mut writer = writer;
+            writer.write(&chunk.data).map_err(internal)?;
+            current = Some((hash, writer));
+        }
+        if let Some((_, writer)) = current {
+            writer.finish().map_err(invalid)?;
+            stored += 1;
+        }
+
+        Ok(Response::new(pb::PutArtifactsResponse { stored }))
+    }
+
+    type GetArtifactStream = ReceiverStream<Result<pb::ArtifactChunk, Status>>;
+
+    async fn get_artifact(
+        &self,
+        request: Request<pb::GetArtifactRequest>,
+    ) -> Result<Response<Self::GetArtifactStream>, Status> {
+        self.authorize(&request)?;
+        let hash = request.into_inner().hash;
+        let path = self
+            .config
+            .store
+            .path(&hash)
+            .filter(|path| path.is_file())
+            .ok_or_else(|| Status::not_found(format!("no artifact {}", hash)))?;
+        Ok(Response::new(ReceiverStream::new(send_file(hash, path))))
+    }
+
+    type RunStageStream = UnboundedReceiverStream<Result<pb::ExecutionEvent, Status>>;
+
+    async fn run_stage(
+        &self,
+        request: Request<pb::RunStageRequest>,
+    ) -> Result<Response<Self::RunStageStream>, Status> {
+        self.authorize(&request)?;
+        let request = request.into_inner();
+
+        let pipeline = Pipeline::from_yaml(&request.pipeline).map_err(status)?;
+        let mut stage = pipeline
+            .get_stage(&request.stage)
+            .cloned()
+            .ok_or_else(|| Status::not_found(format!("no stage '{}'", request.stage)))?;
+        let missing: Vec<_> = stage
+            .runs_on
+            .iter()
+            .filter(|label| !self.config.labels.contains(label))
+            .cloned()
+            .collect();
+        if !missing.is_empty() {
+            return Err(Status::failed_precondition(format!(
+                "this agent doesn't offer {}",
+                missing.join(", ")
+            )));
+        }
+
+        // The scratch directory is the stage's working directory, holding
+        // its files and the output's directory; the runner already resolved
+        // its environment
+        let scratch = Scratch::new().map_err(internal)?;
+        for (name, hash) in &request.artifacts {
+            let relative = safe_relative(name)
+                .ok_or_else(|| Status::invalid_argument(format!("unsafe path: {}", name)))?;
+            self.config
+                .store
+                .copy_to(hash, &scratch.path().join(relative))
+                .map_err(|e| Status::failed_precondition(e.to_string()))?;
+        }
+        let output_dir = stage
+            .output
+            .as_ref()
+            .filter(|output| output.path().is_relative())
+            .and_then(|output| safe_relative(&portable(output.path())))
+            .and_then(|output| output.parent().map(|dir| scratch.path().join(dir)));
+        if let Some(dir) = output_dir {
+            std::fs::create_dir_all(dir).map_err(internal)?;
+        }
+        stage.workdir = None;
+        stage.env_file = None;
+
+        let executor = create_executors(&pipeline)
+            .remove(stage.tool_name())
+            .ok_or_else(|| {
+                Status::failed_precondition(format!("no {} on this agent", stage.tool_name()))
+            })?;
+        let inputs = match stage.input.references_stage() {
+            Some(_) => Some(
+                request
+                    .inputs
+                    .iter()
+                    .map(|name| {
+                        safe_relative(name)
+                            .map(|relative| scratch.path().join(relative))
+                            .ok_or_else(|| {
+                                Status::invalid_argument(format!("unsafe path: {}", name))
+                            })
+                    })
+                    .collect::<Result<Vec<_>, _>>()?,
+            ),
+            None => None,
+        };
+
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let store = self.config.store.clone();
+        tracing::info!(pipeline = %pipeline.name, stage = %stage.name, "running stage");
+        let _ = sender.send(Ok(pb::ExecutionEvent {
+            event: Some(Event::StageStarted(pb::StageStarted {
+                stage: stage.name.clone(),
+            })),
+        }));
+        tokio::spawn(async move {
+            let root = scratch.path();
+            let run = executor
+                .execute(&stage, root, &request.env, inputs.as_deref())
+                .await
+                .and_then(|result| {
+                    let artifacts = store_outputs(&store, &result.outputs, root)?;
+                    let mut result = super::stage_result(stage.name.clone(), result, root);
+                    result.output_artifacts = artifacts;
+                    Ok(result)
+                });
+            let event = run.map(|result| pb::ExecutionEvent {
+                event: Some(Event::StageFinished(result)),
+            });
+            let _ = sender.send(event.map_err(status));
+            drop(scratch);
+        });
+
+        Ok(Response::new(UnboundedReceiverStream::new(receiver)))
+    }
+}
+
+/// Add a stage's output files to the store, by path relative to `root`
+fn store_outputs(
+    store: &ArtifactStore,
+    outputs: &[PathBuf],
+    root: &Path,
+) -> Result<HashMap<String, String>, ConflowError> {
+    let mut artifacts = HashMap::new();
+    for output in outputs {
+        let path = root.join(output);
+        let Ok(relative) = path.strip_prefix(root) else {
+            continue;
+        };
+        if path.is_file() {
+            artifacts.insert(portable(relative), store.put_file(&path)?);
+        }
+    }
+    Ok(artifacts)
+}
+
+/// Stream a file in chunks; an empty file is one empty chunk
+pub(super) fn send_file(
+    hash: String,
+    path: PathBuf,
+) -> mpsc::Receiver<Result<pb::ArtifactChunk, Status>> {
+    let (sender, receiver) = mpsc::channel(4);
+    tokio::spawn(async move {
+        let mut file = match tokio::fs::File::open(&path).await {
+            Ok(file) => file,
+            Err(e) => {
+                let _ = sender.send(Err(internal(e))).await;
+                return;
+            }
+        };
+        let mut first = true;
+        loop {
+            let mut data = vec![0; CHUNK_SIZE];
+            let n = match file.read(&mut data).await {
+                Ok(n) => n,
+                Err(e) => {
+                    let _ = sender.send(Err(internal(e))).await;
+                    return;
+                }
+            };
+            if n == 0 && !first {
+                return;
+            }
+            data.truncate(n);
+            let chunk = pb::ArtifactChunk {
+                hash: hash.clone(),
+                data,
+            };
+            if sender.send(Ok(chunk)).await.is_err() || n == 0 {
+                return;
+            }
+            first = false;
+        }
+    });
+    receiver
+}
+
+fn invalid(error: ConflowError) -> Status {
+    Status::invalid_argument(error.to_string())
+}
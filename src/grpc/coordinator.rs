@@ -0,0 +1,244 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! The `Coordinator` service: agents register and heartbeat, runners look up
+//! a live agent for a stage's labels
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use tonic::{Request, Response, Status};
+
+use super::pb;
+use super::pb::coordinator_server::Coordinator;
+use crate::server::ServerConfig;
+
+/// How often agents heartbeat
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Heartbeats an agent may miss before it is dropped
+const MISSED_HEARTBEATS: u32 = 3;
+
+/// Agents known to a coordinator
+#[derive(Debug)]
+pub struct AgentRegistry {
+    agents: Mutex<Vec<Registered>>,
+    interval: Duration,
+}
+
+#[derive(Debug)]
+struct Registered {
+    info: pb::AgentInfo,
+    seen: Instant,
+}
+
+impl AgentRegistry {
+    /// A registry expecting heartbeats every `interval`
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            agents: Mutex::new(Vec::new()),
+            interval,
+        }
+    }
+
+    /// How often agents must heartbeat
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Add an agent, replacing one registered before at the same address,
+    /// and return its id
+    pub fn register(&self, mut info: pb::AgentInfo, now: Instant) -> String {
+        info.id = new_id(&info.address);
+        let id = info.id.clone();
+        let mut agents = self.lock();
+        agents.retain(|agent| agent.info.address != info.address);
+        agents.push(Registered { info, seen: now });
+        id
+    }
+
+    /// Record a heartbeat; false if the agent isn't (or no longer) known
+    pub fn heartbeat(&self, id: &str, now: Instant) -> bool {
+        let mut agents = self.lock();
+        self.expire(&mut agents, now);
+        match agents.iter_mut().find(|agent| agent.info.id == id) {
+            Some(agent) => {
+                agent.seen = now;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Live agents offering every label in `labels`, in registration order
+    pub fn live(&self, labels: &[String], now: Instant) -> Vec<pb::AgentInfo> {
+        let mut agents = self.lock();
+        self.expire(&mut agents, now);
+        agents
+            .iter()
+            .filter(|agent| labels.iter().all(|label| agent.info.labels.contains(label)))
+            .map(|agent| agent.info.clone())
+            .collect()
+    }
+
+    fn expire(&self, agents: &mut Vec<Registered>, now: Instant) {
+        let timeout = self.interval * MISSED_HEARTBEATS;
+        agents.retain(|agent| {
+            let live = now.saturating_duration_since(agent.seen) <= timeout;
+            if !live {
+                tracing::info!(id = %agent.info.id, address = %agent.info.address, "agent expired");
+            }
+            live
+        });
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<Registered>> {
+        self.agents.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Default for AgentRegistry {
+    fn default() -> Self {
+        Self::new(HEARTBEAT_INTERVAL)
+    }
+}
+
+/// An id no earlier registration had, even across coordinator restarts
+fn new_id(address: &str) -> String {
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    let seed = format!(
+        "{}|{}|{}",
+        address,
+        nanos,
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    );
+    crate::cache::hash_string(&seed)[..16].to_string()
+}
+
+/// Implementation of the `Coordinator` service
+pub struct CoordinatorService {
+    config: Arc<ServerConfig>,
+    registry: Arc<AgentRegistry>,
+}
+
+impl CoordinatorService {
+    /// Create the service with the REST server's settings
+    pub fn new(config: Arc<ServerConfig>, registry: Arc<AgentRegistry>) -> Self {
+        Self { config, registry }
+    }
+
+    /// Agents authenticate with agent keys, never with API keys; without
+    /// agent keys no agent may register
+    fn authorize_agent<T>(&self, request: &Request<T>) -> Result<(), Status> {
+        if self.config.agent_keys.is_empty() {
+            return Err(Status::permission_denied(
+                "this coordinator accepts no agents; start it with --agent-key",
+            ));
+        }
+        super::authorize(&self.config.agent_keys, request)
+    }
+}
+
+#[tonic::async_trait]
+impl Coordinator for CoordinatorService {
+    async fn register(
+        &self,
+        request: Request<pb::RegisterRequest>,
+    ) -> Result<Response<pb::RegisterResponse>, Status> {
+        self.authorize_agent(&request)?;
+        let info = request
+            .into_inner()
+            .agent
+            .filter(|agent| !agent.address.is_empty())
+            .ok_or_else(|| Status::invalid_argument("the agent's address is required"))?;
+        if !info.address.starts_with("https://") {
+            return Err(Status::invalid_argument(
+                "agents must be reached over TLS, at an https:// address",
+            ));
+        }
+        let (address, labels) = (info.address.clone(), info.labels.join(","));
+        let id = self.registry.register(info, Instant::now());
+        tracing::info!(%id, %address, %labels, "agent registered");
+        Ok(Response::new(pb::RegisterResponse {
+            id,
+            heartbeat_interval_ms: self.registry.interval().as_millis() as u64,
+        }))
+    }
+
+    async fn heartbeat(
+        &self,
+        request: Request<pb::HeartbeatRequest>,
+    ) -> Result<Response<pb::HeartbeatResponse>, Status> {
+        self.authorize_agent(&request)?;
+        let known = self
+            .registry
+            .heartbeat(&request.get_ref().id, Instant::now());
+        Ok(Response::new(pb::HeartbeatResponse { known }))
+    }
+
+    async fn list_agents(
+        &self,
+        request: Request<pb::ListAgentsRequest>,
+    ) -> Result<Response<pb::ListAgentsResponse>, Status> {
+        super::authorize(&self.config.api_keys, &request)?;
+        let agents = self
+            .registry
+            .live(&request.get_ref().labels, Instant::now());
+        Ok(Response::new(pb::ListAgentsResponse { agents }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn agent(address: &str, labels: &[&str]) -> pb::AgentInfo {
+        pb::AgentInfo {
+            address: address.to_string(),
+            labels: labels.iter().map(|l| l.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn addresses(agents: Vec<pb::AgentInfo>) -> Vec<String> {
+        agents.into_iter().map(|agent| agent.address).collect()
+    }
+
+    #[test]
+    fn test_registry() {
+        let registry = AgentRegistry::new(Duration::from_secs(10));
+        let start = Instant::now();
+        let prod = registry.register(agent("http://prod", &["cluster:prod", "gpu"]), start);
+        let dev = registry.register(agent("http://dev", &["cluster:dev"]), start);
+        assert_ne!(prod, dev);
+
+        // Agents offering every label
+        let labels = |labels: &[&str]| labels.iter().map(|l| l.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            addresses(registry.live(&labels(&["gpu"]), start)),
+            ["http://prod"]
+        );
+        assert!(registry
+            .live(&labels(&["gpu", "cluster:dev"]), start)
+            .is_empty());
+        assert_eq!(registry.live(&[], start).len(), 2);
+
+        // Three missed heartbeats drop an agent
+        let later = start + Duration::from_secs(25);
+        assert!(registry.heartbeat(&prod, later));
+        let expired = start + Duration::from_secs(31);
+        assert_eq!(addresses(registry.live(&[], expired)), ["http://prod"]);
+        assert!(!registry.heartbeat(&dev, expired));
+
+        // Registering again at the same address replaces the agent
+        let again = registry.register(agent("http://prod", &["gpu"]), expired);
+        assert!(!registry.heartbeat(&prod, expired));
+        assert!(registry.heartbeat(&again, expired));
+        assert_eq!(registry.live(&[], expired).len(), 1);
+    }
+}
@@ -0,0 +1,334 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Running stages with `runs_on` labels on agents, for `conflow run
+//! --coordinator`
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::transport::{Certificate, ClientTlsConfig, Endpoint};
+
+use super::pb::agent_client::AgentClient;
+use super::pb::coordinator_client::CoordinatorClient;
+use super::pb::execution_event::Event;
+use super::{pb, portable, with_key};
+use crate::cache::ArtifactStore;
+use crate::errors::ConflowError;
+use crate::executors::{resolve_inputs, ExecutionResult};
+use crate::pipeline::{Dispatcher, Pipeline, Stage, Tool};
+use crate::server::safe_relative;
+
+/// Sends stages to live agents offering their labels, found through a
+/// coordinator
+///
+/// Agents are only reached over TLS and authenticated with the runner key,
+/// never with the coordinator's API key. Stages whose environment holds a
+/// variable listed under `secrets:` or `redact.env` are refused unless
+/// [`with_secrets`](Self::with_secrets) allows sending them.
+#[derive(Debug, Clone)]
+pub struct RemoteDispatcher {
+    coordinator: String,
+    api_key: Option<String>,
+    runner_key: Option<String>,
+    agent_ca: Option<Certificate>,
+    send_secrets: bool,
+    store: ArtifactStore,
+}
+
+impl RemoteDispatcher {
+    /// Dispatch through the coordinator at `coordinator`, a URL such as
+    /// `http://ci-coordinator:50051`, authenticating with `api_key`
+    pub fn new(coordinator: String, api_key: Option<String>, store: ArtifactStore) -> Self {
+        Self {
+            coordinator,
+            api_key,
+            runner_key: None,
+            agent_ca: None,
+            send_secrets: false,
+            store,
+        }
+    }
+
+    /// Authenticate with agents by `key`
+    pub fn with_runner_key(mut self, key: Option<String>) -> Self {
+        self.runner_key = key;
+        self
+    }
+
+    /// Trust agents' certificates signed by this PEM CA rather than the
+    /// public web roots
+    pub fn with_agent_ca(mut self, pem: Option<Vec<u8>>) -> Self {
+        self.agent_ca = pem.map(Certificate::from_pem);
+        self
+    }
+
+    /// Send secret variables to agents
+    pub fn with_secrets(mut self, send: bool) -> Self {
+        self.send_secrets = send;
+        self
+    }
+
+    /// The first live agent offering every label of `stage`
+    async fn pick_agent(&self, stage: &Stage) -> Result<pb::AgentInfo, String> {
+        let mut coordinator = CoordinatorClient::connect(self.coordinator.clone())
+            .await
+            .map_err(|e| format!("can't reach the coordinator at {}: {}", self.coordinator, e))?;
+        let request = pb::ListAgentsRequest {
+            labels: stage.runs_on.clone(),
+        };
+        let agents = coordinator
+            .list_agents(with_key(request, self.api_key.as_deref()))
+            .await
+            .map_err(|e| format!("the coordinator refused: {}", e.message()))?
+            .into_inner()
+            .agents;
+        agents
+            .into_iter()
+            .next()
+            .ok_or_else(|| format!("no live agent offers {}", stage.runs_on.join(", ")))
+    }
+
+    async fn run(
+        &self,
+        pipeline: &Pipeline,
+        stage: &Stage,
+        working_dir: &Path,
+        env: &HashMap<String, String>,
+        resolved_inputs: Option<&[PathBuf]>,
+    ) -> Result<ExecutionResult, String> {
+        if !self.send_secrets {
+            let secrets: Vec<_> = pipeline
+                .masked_vars()
+                .into_iter()
+                .filter(|name| env.contains_key(name))
+                .collect();
+            if !secrets.is_empty() {
+                return Err(format!(
+                    "its environment holds secret {}; pass --send-secrets to send them to agents",
+                    secrets.join(", ")
+                ));
+            }
+        }
+
+        let agent = self.pick_agent(stage).await?;
+        tracing::info!(stage = %stage.name, agent = %agent.address, "dispatching stage");
+
+        // Add the stage's files to the local store, then send the agent
+        // those it doesn't hold
+        let mut artifacts = HashMap::new();
+        let mut sources = BTreeMap::new();
+        for file in stage_files(stage, working_dir, resolved_inputs).map_err(|e| e.to_string())? {
+            let name = relative(&file, working_dir)
+                .ok_or_else(|| format!("{} is outside the stage's directory", file.display()))?;
+            let hash = self.store.put_file(&file).map_err(|e| e.to_string())?;
+            sources.insert(hash.clone(), file);
+            artifacts.insert(name, hash);
+        }
+
+        let mut client = self.connect(&agent.address).await?;
+        let refused =
+            |e: tonic::Status| format!("the agent at {} failed: {}", agent.address, e.message());
+
+        let request = pb::ArtifactList {
+            hashes: sources.keys().cloned().collect(),
+        };
+        let missing = client
+            .missing_artifacts(with_key(request, self.key()))
+            .await
+            .map_err(refused)?
+            .into_inner()
+            .hashes;
+        if !missing.is_empty() {
+            let files = missing
+                .into_iter()
+                .filter_map(|hash| sources.get(&hash).map(|file| (hash, file.clone())))
+                .collect();
+            let upload = ReceiverStream::new(send_files(files));
+            client
+                .put_artifacts(with_key(upload, self.key()))
+                .await
+                .map_err(refused)?;
+        }
+
+        let inputs = resolved_inputs
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|file| relative(file, working_dir))
+            .collect();
+        // The agent gets the resolved environment alone, not the pipeline's
+        // definitions of it
+        let mut shipped = pipeline.clone();
+        shipped.env.clear();
+        shipped.env_file = None;
+        for stage in &mut shipped.stages {
+            stage.env.clear();
+            stage.env_file = None;
+        }
+        let request = pb::RunStageRequest {
+            pipeline: shipped.to_yaml().map_err(|e| e.to_string())?,
+            stage: stage.name.clone(),
+            env: env.clone(),
+            artifacts,
+            inputs,
+        };
+        let mut events = client
+            .run_stage(with_key(request, self.key()))
+            .await
+            .map_err(refused)?
+            .into_inner();
+        let mut finished = None;
+        while let Some(event) = events.message().await.map_err(refused)? {
+            if let Some(Event::StageFinished(result)) = event.event {
+                finished = Some(result);
+            }
+        }
+        let result = finished.ok_or("the agent ended the stage without a result")?;
+
+        // Fetch output files the local store doesn't hold
+        for (name, hash) in &result.output_artifacts {
+            let relative =
+                safe_relative(name).ok_or_else(|| format!("unsafe output path: {}", name))?;
+            if !self.store.contains(hash) {
+                let request = pb::GetArtifactRequest { hash: hash.clone() };
+                let mut chunks = client
+                    .get_artifact(with_key(request, self.key()))
+                    .await
+                    .map_err(refused)?
+                    .into_inner();
+                let mut writer = self.store.writer(hash).map_err(|e| e.to_string())?;
+                while let Some(chunk) = chunks.message().await.map_err(refused)? {
+                    writer.write(&chunk.data).map_err(|e| e.to_string())?;
+                }
+                writer.finish().map_err(|e| e.to_string())?;
+            }
+            self.store
+                .copy_to(hash, &working_dir.join(relative))
+                .map_err(|e| e.to_string())?;
+        }
+
+        Ok(ExecutionResult {
+            success: result.success,
+            stdout: result.stdout,
+            stderr: result.stderr,
+            exit_code: result.exit_code,
+            outputs: result.outputs.iter().map(PathBuf::from).collect(),
+            duration: Duration::from_millis(result.duration_ms),
+            cache_hit: false,
+        })
+    }
+
+    /// A TLS connection to the agent at `address`
+    async fn connect(
+        &self,
+        address: &str,
+    ) -> Result<AgentClient<tonic::transport::Channel>, String> {
+        if !address.starts_with("https://") {
+            return Err(format!("the agent at {} doesn't serve TLS", address));
+        }
+        let tls = match &self.agent_ca {
+            Some(ca) => ClientTlsConfig::new().ca_certificate(ca.clone()),
+            None => ClientTlsConfig::new().with_webpki_roots(),
+        };
+        let channel = Endpoint::from_shared(address.to_string())
+            .and_then(|endpoint| endpoint.tls_config(tls))
+            .map_err(|e| format!("invalid agent address {}: {}", address, e))?
+            .connect()
+            .await
+            .map_err(|e| format!("can't reach the agent at {}: {}", address, e))?;
+        Ok(AgentClient::new(channel))
+    }
+
+    fn key(&self) -> Option<&str> {
+        self.runner_key.as_deref()
+    }
+}
+
+#[async_trait]
+impl Dispatcher for RemoteDispatcher {
+    async fn dispatch(
+        &self,
+        pipeline: &Pipeline,
+        stage: &Stage,
+        working_dir: &Path,
+        env: &HashMap<String, String>,
+        resolved_inputs: Option<&[PathBuf]>,
+    ) -> Result<ExecutionResult, ConflowError> {
+        self.run(pipeline, stage, working_dir, env, resolved_inputs)
+            .await
+            .map_err(|message| ConflowError::Dispatch {
+                stage: stage.name.clone(),
+                message,
+            })
+    }
+}
+
+/// Files an agent needs for a stage: its input files and the files its tool
+/// names, such as CUE schemas and values files
+fn stage_files(
+    stage: &Stage,
+    working_dir: &Path,
+    resolved_inputs: Option<&[PathBuf]>,
+) -> Result<Vec<PathBuf>, ConflowError> {
+    let mut files = match resolved_inputs {
+        Some(inputs) => inputs.iter().map(|file| working_dir.join(file)).collect(),
+        None => resolve_inputs(stage, working_dir)?,
+    };
+
+    let named: Vec<&Path> = match &stage.tool {
+        Tool::Cue { schemas, .. } => schemas.iter().map(PathBuf::as_path).collect(),
+        Tool::Nickel { file, .. } => file.iter().map(PathBuf::as_path).collect(),
+        Tool::Helm { chart, values, .. } => values
+            .iter()
+            .map(PathBuf::as_path)
+            .chain([Path::new(chart)])
+            .collect(),
+        Tool::Render { values, .. } => values.iter().map(PathBuf::as_path).collect(),
+        Tool::Terraform { dir, .. } => dir.iter().map(PathBuf::as_path).collect(),
+        Tool::Cost { pricing, .. } => pricing.iter().map(PathBuf::as_path).collect(),
+        _ => Vec::new(),
+    };
+    for path in named {
+        let path = working_dir.join(path);
+        if path.is_dir() {
+            let pattern = format!("{}/**/*", glob::Pattern::escape(&path.to_string_lossy()));
+            let found = glob::glob(&pattern).map_err(|e| ConflowError::GlobPattern {
+                message: e.to_string(),
+            })?;
+            files.extend(found.flatten().filter(|file| file.is_file()));
+        } else if path.is_file() {
+            files.push(path);
+        }
+    }
+
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+/// `file` relative to `working_dir`, if it lies inside it
+fn relative(file: &Path, working_dir: &Path) -> Option<String> {
+    let file = working_dir.join(file);
+    let relative = portable(file.strip_prefix(working_dir).ok()?);
+    safe_relative(&relative).map(|_| relative)
+}
+
+/// Stream files in chunks, each tagged with its hash
+fn send_files(files: Vec<(String, PathBuf)>) -> mpsc::Receiver<pb::ArtifactChunk> {
+    let (sender, receiver) = mpsc::channel(4);
+    tokio::spawn(async move {
+        for (hash, path) in files {
+            let mut chunks = super::agent::send_file(hash, path);
+            while let Some(Ok(chunk)) = chunks.recv().await {
+                if sender.send(chunk).await.is_err() {
+                    return;
+                }
+            }
+        }
+    });
+    receiver
+}
@@ -0,0 +1,570 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! gRPC API
+//!
+//! tonic server behind `conflow serve --grpc`, generated from
+//! `proto/conflow/v1/conflow.proto`. It offers the REST API's operations (see
+//! [`crate::server`]) with the same API keys, sent as `authorization: Bearer
+//! <key>` or `x-api-key` metadata, plus `RunPipeline` for CI orchestrators
+//! that hand runs to a pool of conflow workers: the worker writes the uploaded
+//! files to a scratch directory, runs the pipeline there and streams its
+//! [`ExecutionEvent`]s as they happen.
+//!
+//! `RunPipeline` runs the uploaded pipeline's commands with the server's
+//! permissions, so only give API keys to callers trusted with that. A server
+//! without API keys (`--no-auth`) refuses it.
+//!
+//! The server is also a coordinator for remote agents (experimental): agents
+//! started with `conflow agent` register their labels and heartbeat, and
+//! `conflow run --coordinator` sends each stage with `runs_on` labels to a
+//! live agent offering them, exchanging files through [`ArtifactStore`]s.
+//! Agents register with agent keys rather than API keys, serve TLS only and
+//! accept runner keys of their own, so neither an agent nor its network sees
+//! the coordinator's API keys.
+//!
+//! [`ArtifactStore`]: crate::cache::ArtifactStore
+
+use std::path::{Component, Path};
+use std::sync::Arc;
+
+use tokio::net::TcpListener;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::Status;
+
+use crate::errors::ConflowError;
+use crate::executors::ExecutionResult;
+use crate::pipeline::ExecutionEvent;
+use crate::server::{key_accepted, ServerConfig};
+
+mod agent;
+mod coordinator;
+mod dispatch;
+mod service;
+
+pub use agent::{describe, keep_registered, serve as serve_agent, AgentConfig};
+pub use coordinator::{AgentRegistry, CoordinatorService, HEARTBEAT_INTERVAL};
+pub use dispatch::RemoteDispatcher;
+pub use service::ConflowService;
+
+/// Size of the chunks artifacts are streamed in
+const CHUNK_SIZE: usize = 1 << 20;
+
+/// Code generated from `proto/conflow/v1/conflow.proto`
+#[allow(clippy::all)]
+pub mod pb {
+    tonic::include_proto!("conflow.v1");
+}
+
+/// Serve the gRPC API on `listener` until the task is dropped
+pub async fn serve(listener: TcpListener, config: ServerConfig) -> Result<(), ConflowError> {
+    let max_body = config.max_body;
+    let coordinator = CoordinatorService::new(Arc::new(config.clone()), Arc::default());
+    let service = pb::conflow_server::ConflowServer::new(ConflowService::new(config))
+        .max_decoding_message_size(max_body);
+
+    tonic::transport::Server::builder()
+        .add_service(service)
+        .add_service(pb::coordinator_server::CoordinatorServer::new(coordinator))
+        .serve_with_incoming(TcpListenerStream::new(listener))
+        .await
+        .map_err(|e| ConflowError::Io {
+            message: format!("gRPC server failed: {}", e),
+        })
+}
+
+/// Reject requests without one of the accepted keys
+fn authorize<T>(keys: &[String], request: &tonic::Request<T>) -> Result<(), Status> {
+    let metadata = request.metadata();
+    let value = |name| metadata.get(name).and_then(|v| v.to_str().ok());
+    if key_accepted(keys, value("authorization"), value("x-api-key")) {
+        Ok(())
+    } else {
+        Err(Status::unauthenticated("missing or invalid API key"))
+    }
+}
+
+/// `message` as a request carrying `key`, if any
+fn with_key<T>(message: T, key: Option<&str>) -> tonic::Request<T> {
+    let mut request = tonic::Request::new(message);
+    if let Some(value) = key.and_then(|key| format!("Bearer {}", key).parse().ok()) {
+        request.metadata_mut().insert("authorization", value);
+    }
+    request
+}
+
+fn status(error: ConflowError) -> Status {
+    match error {
+        ConflowError::ConfigParse { .. }
+        | ConflowError::InvalidPipeline { .. }
+        | ConflowError::InvalidStage { .. }
+        | ConflowError::Yaml { .. } => Status::invalid_argument(error.to_string()),
+        ConflowError::ApprovalRequired { .. } => Status::failed_precondition(error.to_string()),
+        _ => internal(error),
+    }
+}
+
+fn upload_error(error: std::io::Error) -> Status {
+    match error.kind() {
+        std::io::ErrorKind::InvalidInput => Status::invalid_argument(error.to_string()),
+        _ => internal(error),
+    }
+}
+
+fn internal(error: impl std::fmt::Display) -> Status {
+    Status::internal(error.to_string())
+}
+
+/// A relative path with `/` separators, as sent between machines
+fn portable(path: &Path) -> String {
+    path.components()
+        .filter_map(|component| match component {
+            Component::Normal(part) => Some(part.to_string_lossy()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// An event as sent to clients, with output paths relative to `root`
+fn event(event: ExecutionEvent, root: &Path) -> pb::ExecutionEvent {
+    use pb::execution_event::Event;
+
+    let event = match event {
+        ExecutionEvent::Planned { stages } => Event::Planned(pb::Planned { stages }),
+        ExecutionEvent::StageStarted { stage } => Event::StageStarted(pb::StageStarted { stage }),
+        ExecutionEvent::StageSkipped { stage, reason } => {
+            Event::StageSkipped(pb::StageSkipped { stage, reason })
+        }
+        ExecutionEvent::StageCached { stage, result } => {
+            Event::StageCached(stage_result(stage, result, root))
+        }
+        ExecutionEvent::StageFinished { stage, result } => {
+            Event::StageFinished(stage_result(stage, result, root))
+        }
+        ExecutionEvent::PipelineFinished { success, duration } => {
+            Event::PipelineFinished(pb::PipelineFinished {
+                success,
+                duration_ms: duration.as_millis() as u64,
+            })
+        }
+    };
+    pb::ExecutionEvent { event: Some(event) }
+}
+
+fn stage_result(stage: String, result: ExecutionResult, root: &Path) -> pb::StageResult {
+    pb::StageResult {
+        stage,
+        success: result.success,
+        stdout: result.stdout,
+        stderr: result.stderr,
+        exit_code: result.exit_code,
+        outputs: result
+            .outputs
+            .iter()
+            .map(|output| {
+                let output = output.strip_prefix(root).unwrap_or(output);
+                output.display().to_string()
+            })
+            .collect(),
+        duration_ms: result.duration.as_millis() as u64,
+        cache_hit: result.cache_hit,
+        output_artifacts: Default::default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::pb::conflow_client::ConflowClient;
+    use super::pb::execution_event::Event;
+    use super::*;
+    use crate::pipeline::Dispatcher;
+    use std::collections::HashMap;
+    use tonic::transport::Channel;
+    use tonic::Code;
+
+    async fn start() -> ConflowClient<Channel> {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let config = ServerConfig {
+            api_keys: vec!["secret".to_string()],
+            ..ServerConfig::default()
+        };
+        tokio::spawn(serve(listener, config));
+        ConflowClient::connect(format!("http://{}", addr))
+            .await
+            .unwrap()
+    }
+
+    fn authorized<T>(message: T) -> tonic::Request<T> {
+        let mut request = tonic::Request::new(message);
+        request
+            .metadata_mut()
+            .insert("authorization", "Bearer secret".parse().unwrap());
+        request
+    }
+
+    #[tokio::test]
+    async fn test_authentication() {
+        let mut client = start().await;
+
+        // Health checks need no key
+        let health = client.health(pb::HealthRequest {}).await.unwrap();
+        assert_eq!(health.get_ref().version, crate::VERSION);
+
+        let status = client
+            .list_schemas(pb::ListSchemasRequest {})
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::Unauthenticated);
+
+        let mut request = tonic::Request::new(pb::ListSchemasRequest {});
+        request
+            .metadata_mut()
+            .insert("x-api-key", "secret".parse().unwrap());
+        let schemas = client.list_schemas(request).await.unwrap();
+        assert!(!schemas.get_ref().ids.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_operations() {
+        let mut client = start().await;
+
+        let pipeline = "name: app\nstages:\n  - name: check\n    tool:\n      type: shell\n      \
+                        command: \"true\"\n    input: \"*.yaml\"\n";
+        let validated = client
+            .validate(authorized(pb::ValidateRequest {
+                pipeline: pipeline.to_string(),
+            }))
+            .await
+            .unwrap();
+        assert!(validated.get_ref().valid);
+
+        let report = client
+            .analyze(authorized(pb::AnalyzeRequest {
+                filename: "config.yaml".to_string(),
+                content: "replicas: 3\n".to_string(),
+            }))
+            .await
+            .unwrap();
+        assert_eq!(report.get_ref().kind, "analysis");
+
+        let files = HashMap::from([("README.md".to_string(), "# Demo\n".to_string())]);
+        let report = client
+            .check_compliance(authorized(pb::ComplianceRequest { files }))
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_str(&report.get_ref().json).unwrap();
+        assert_eq!(json["kind"], "compliance");
+
+        let files = HashMap::from([("../escape".to_string(), String::new())]);
+        let status = client
+            .check_compliance(authorized(pb::ComplianceRequest { files }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
+
+        let status = client
+            .get_schema(authorized(pb::GetSchemaRequest {
+                id: "rsr:missing".to_string(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::NotFound);
+    }
+
+    #[tokio::test]
+    async fn test_run_pipeline_streams_events() {
+        let mut client = start().await;
+
+        let pipeline = "name: app\nstages:\n  - name: greet\n    tool:\n      type: shell\n      \
+                        command: \"cat greeting.yaml\"\n    input: \"*.yaml\"\n  \
+                        - name: fail\n    tool:\n      type: shell\n      \
+                        command: \"exit 3\"\n    input: \"*.yaml\"\n    depends_on: [greet]\n";
+        let files = HashMap::from([("greeting.yaml".to_string(), "hello: world\n".to_string())]);
+        let mut stream = client
+            .run_pipeline(authorized(pb::RunPipelineRequest {
+                pipeline: pipeline.to_string(),
+                files,
+                ..Default::default()
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+
+        let mut events = Vec::new();
+        while let Some(event) = stream.message().await.unwrap() {
+            events.push(event.event.unwrap());
+        }
+
+        let Event::Planned(planned) = &events[0] else {
+            panic!("expected the plan first, got {:?}", events[0]);
+        };
+        assert_eq!(planned.stages, ["greet", "fail"]);
+        let finished: Vec<_> = events
+            .iter()
+            .filter_map(|event| match event {
+                Event::StageFinished(result) => Some(result),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(finished.len(), 2);
+        assert!(finished[0].success);
+        assert!(finished[0].stdout.contains("hello: world"));
+        assert!(!finished[1].success);
+        assert_eq!(finished[1].exit_code, 3);
+        assert!(matches!(
+            events.last(),
+            Some(Event::PipelineFinished(pb::PipelineFinished {
+                success: false,
+                ..
+            }))
+        ));
+
+        let status = client
+            .run_pipeline(authorized(pb::RunPipelineRequest {
+                pipeline: "stages: [".to_string(),
+                ..Default::default()
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_run_pipeline_needs_api_keys() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(serve(listener, ServerConfig::default()));
+        let mut client = ConflowClient::connect(format!("http://{}", addr))
+            .await
+            .unwrap();
+
+        // Without keys the other operations stay open, but runs don't
+        let validated = client
+            .validate(pb::ValidateRequest {
+                pipeline: "stages: [".to_string(),
+            })
+            .await;
+        assert!(validated.is_ok());
+        let status = client
+            .run_pipeline(pb::RunPipelineRequest {
+                pipeline: "name: app\nstages: []\n".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::PermissionDenied);
+    }
+
+    #[tokio::test]
+    async fn test_stages_run_on_agents() {
+        use crate::cache::ArtifactStore;
+        use crate::executors::create_executors;
+        use crate::pipeline::{ExecutionOptions, Pipeline, PipelineExecutor};
+        use std::sync::Arc;
+        use tempfile::TempDir;
+
+        if which::which("openssl").is_err() {
+            return;
+        }
+        let temp = TempDir::new().unwrap();
+        let key = Some("secret".to_string());
+
+        // A self-signed certificate for the agent
+        let (cert, private_key) = (temp.path().join("agent.pem"), temp.path().join("key.pem"));
+        let generated = std::process::Command::new("openssl")
+            .args([
+                "req",
+                "-x509",
+                "-newkey",
+                "ec",
+                "-pkeyopt",
+                "ec_paramgen_curve:P-256",
+            ])
+            .args(["-nodes", "-days", "1", "-subj", "/CN=localhost"])
+            .args(["-addext", "subjectAltName=DNS:localhost"])
+            .args(["-addext", "basicConstraints=critical,CA:FALSE"])
+            .arg("-keyout")
+            .arg(&private_key)
+            .arg("-out")
+            .arg(&cert)
+            .output()
+            .unwrap();
+        assert!(generated.status.success(), "{:?}", generated);
+        let cert = std::fs::read(cert).unwrap();
+        let identity =
+            tonic::transport::Identity::from_pem(&cert, std::fs::read(private_key).unwrap());
+
+        // A coordinator, and an agent with a store of its own registered with it
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let coordinator = format!("http://{}", listener.local_addr().unwrap());
+        let config = ServerConfig {
+            api_keys: vec!["secret".to_string()],
+            agent_keys: vec!["agent-secret".to_string()],
+            ..ServerConfig::default()
+        };
+        tokio::spawn(serve(listener, config));
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let agent = AgentConfig {
+            labels: vec!["gpu".to_string()],
+            runner_keys: vec!["runner-secret".to_string()],
+            identity,
+            max_upload: 1 << 20,
+            store: ArtifactStore::new(temp.path().join("agent")),
+        };
+        let address = format!(
+            "https://localhost:{}",
+            listener.local_addr().unwrap().port()
+        );
+        let info = describe(&agent, &address);
+        tokio::spawn(serve_agent(listener, agent));
+
+        // Agents register with an agent key, not an API key, and only at
+        // https:// addresses
+        let mut client = pb::coordinator_client::CoordinatorClient::connect(coordinator.clone())
+            .await
+            .unwrap();
+        let register = |info: pb::AgentInfo, key: &str| {
+            with_key(pb::RegisterRequest { agent: Some(info) }, Some(key))
+        };
+        let error = client
+            .register(register(info.clone(), "secret"))
+            .await
+            .unwrap_err();
+        assert_eq!(error.code(), Code::Unauthenticated);
+        let plain = pb::AgentInfo {
+            address: address.replace("https://", "http://"),
+            ..info.clone()
+        };
+        let error = client
+            .register(register(plain, "agent-secret"))
+            .await
+            .unwrap_err();
+        assert_eq!(error.code(), Code::InvalidArgument);
+
+        tokio::spawn(keep_registered(
+            coordinator.clone(),
+            info,
+            "agent-secret".to_string(),
+        ));
+        for _ in 0..100 {
+            let request = authorized(pb::ListAgentsRequest::default());
+            let agents = client
+                .list_agents(request)
+                .await
+                .unwrap()
+                .into_inner()
+                .agents;
+            if !agents.is_empty() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+
+        let project = temp.path().join("project");
+        std::fs::create_dir_all(project.join("docs")).unwrap();
+        std::fs::write(project.join("docs/greeting.txt"), "hello\n").unwrap();
+        let pipeline = Pipeline::from_yaml(
+            r#"
+name: app
+stages:
+  - name: shout
+    tool:
+      type: shell
+      command: "tr a-z A-Z < docs/greeting.txt | tee out/shout.txt"
+    input: "docs/greeting.txt"
+    output: "out/shout.txt"
+    runs_on: [gpu]
+"#,
+        )
+        .unwrap();
+        let run = |dispatcher: Option<RemoteDispatcher>| {
+            let mut executor = PipelineExecutor::new().quiet();
+            for (name, exec) in create_executors(&pipeline) {
+                executor.register_executor(&name, exec);
+            }
+            if let Some(dispatcher) = dispatcher {
+                executor = executor.with_dispatcher(Arc::new(dispatcher));
+            }
+            let (pipeline, project) = (pipeline.clone(), project.clone());
+            async move {
+                executor
+                    .execute(&pipeline, &project, &ExecutionOptions::default())
+                    .await
+            }
+        };
+        std::fs::create_dir_all(project.join("out")).unwrap();
+
+        // The input goes to the agent, the output comes back
+        let runner = ArtifactStore::new(temp.path().join("runner"));
+        let dispatcher = RemoteDispatcher::new(coordinator.clone(), key.clone(), runner)
+            .with_runner_key(Some("runner-secret".to_string()))
+            .with_agent_ca(Some(cert.clone()));
+        let result = run(Some(dispatcher.clone())).await.unwrap();
+        assert!(result.success, "{:?}", result.results["shout"]);
+        assert_eq!(result.results["shout"].stdout, "HELLO\n");
+        let shouted = std::fs::read_to_string(project.join("out/shout.txt")).unwrap();
+        assert_eq!(shouted, "HELLO\n");
+
+        // No agent offers the labels, or there is no coordinator to ask
+        let mut other = pipeline.clone();
+        other.stages[0].runs_on = vec!["cluster:prod".to_string()];
+        let error = dispatcher
+            .dispatch(&other, &other.stages[0], &project, &HashMap::new(), None)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, ConflowError::Dispatch { .. }));
+        assert!(error
+            .to_string()
+            .contains("no live agent offers cluster:prod"));
+        assert!(matches!(
+            run(None).await,
+            Err(ConflowError::Dispatch { .. })
+        ));
+
+        // Secret variables stay home unless sending them is allowed
+        let mut secret = pipeline.clone();
+        secret.secrets = vec!["TOKEN".to_string()];
+        let env = HashMap::from([("TOKEN".to_string(), "hunter22".to_string())]);
+        let error = dispatcher
+            .dispatch(&secret, &secret.stages[0], &project, &env, None)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("--send-secrets"), "{}", error);
+        let sent = dispatcher
+            .clone()
+            .with_secrets(true)
+            .dispatch(&secret, &secret.stages[0], &project, &env, None)
+            .await
+            .unwrap();
+        assert!(sent.success);
+
+        // The agent only accepts runner keys, not the coordinator's API key,
+        // and runners only trust the certificates they were given
+        let stage = &pipeline.stages[0];
+        let runner = || ArtifactStore::new(temp.path().join("runner"));
+        for runner_key in [None, key.clone()] {
+            let unauthorized = RemoteDispatcher::new(coordinator.clone(), key.clone(), runner())
+                .with_runner_key(runner_key)
+                .with_agent_ca(Some(cert.clone()));
+            let error = unauthorized
+                .dispatch(&pipeline, stage, &project, &HashMap::new(), None)
+                .await
+                .unwrap_err();
+            assert!(error.to_string().contains("API key"), "{}", error);
+        }
+        let untrusted = RemoteDispatcher::new(coordinator, key, runner())
+            .with_runner_key(Some("runner-secret".to_string()));
+        let error = untrusted
+            .dispatch(&pipeline, stage, &project, &HashMap::new(), None)
+            .await
+            .unwrap_err();
+        assert!(
+            error.to_string().contains("can't reach the agent"),
+            "{}",
+            error
+        );
+    }
+}
@@ -0,0 +1,217 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! The `Conflow` service
+
+use std::sync::Arc;
+
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use tonic::{Request, Response, Status};
+
+use super::pb::conflow_server::Conflow;
+use super::{internal, pb, status, upload_error};
+use crate::analyzer::ConfigAnalyzer;
+use crate::executors::create_executors;
+use crate::pipeline::{Approvals, ExecutionOptions, Pipeline, PipelineExecutor, PipelineValidator};
+use crate::rsr::{ComplianceChecker, RsrSchemaRegistry};
+use crate::server::{upload_name, Scratch, ServerConfig};
+
+/// Name the uploaded pipeline is written under in the scratch directory
+const PIPELINE_FILE: &str = ".conflow.yaml";
+
+/// Implementation of the `Conflow` service
+pub struct ConflowService {
+    config: Arc<ServerConfig>,
+}
+
+impl ConflowService {
+    /// Create the service with the REST server's settings
+    pub fn new(config: ServerConfig) -> Self {
+        Self {
+            config: Arc::new(config),
+        }
+    }
+
+    fn authorize<T>(&self, request: &Request<T>) -> Result<(), Status> {
+        super::authorize(&self.config.api_keys, request)
+    }
+}
+
+#[tonic::async_trait]
+impl Conflow for ConflowService {
+    async fn health(
+        &self,
+        _request: Request<pb::HealthRequest>,
+    ) -> Result<Response<pb::HealthResponse>, Status> {
+        Ok(Response::new(pb::HealthResponse {
+            status: "ok".to_string(),
+            version: crate::VERSION.to_string(),
+        }))
+    }
+
+    async fn validate(
+        &self,
+        request: Request<pb::ValidateRequest>,
+    ) -> Result<Response<pb::ValidateResponse>, Status> {
+        self.authorize(&request)?;
+        let result = PipelineValidator::validate_yaml(&request.get_ref().pipeline);
+        Ok(Response::new(pb::ValidateResponse {
+            valid: result.is_valid(),
+            errors: result.errors,
+            warnings: result.warnings,
+        }))
+    }
+
+    async fn analyze(
+        &self,
+        request: Request<pb::AnalyzeRequest>,
+    ) -> Result<Response<pb::Report>, Status> {
+        self.authorize(&request)?;
+        let request = request.into_inner();
+        let analysis = blocking(move || {
+            let filename = upload_name(&request.filename)
+                .ok_or_else(|| Status::invalid_argument("filename must name a file"))?;
+            ConfigAnalyzer::new()
+                .analyze_content(filename, &request.content)
+                .map_err(|e| Status::invalid_argument(e.to_string()))
+        })
+        .await?;
+        report(&analysis)
+    }
+
+    async fn check_compliance(
+        &self,
+        request: Request<pb::ComplianceRequest>,
+    ) -> Result<Response<pb::Report>, Status> {
+        self.authorize(&request)?;
+        let request = request.into_inner();
+        let report = blocking(move || {
+            let scratch = Scratch::new().map_err(internal)?;
+            scratch.write_files(&request.files).map_err(upload_error)?;
+            ComplianceChecker::new()
+                .check(scratch.path())
+                .map_err(internal)
+        })
+        .await?;
+        self.config.metrics.record_compliance(&report);
+        self::report(&report)
+    }
+
+    async fn list_schemas(
+        &self,
+        request: Request<pb::ListSchemasRequest>,
+    ) -> Result<Response<pb::ListSchemasResponse>, Status> {
+        self.authorize(&request)?;
+        let registry = RsrSchemaRegistry::new();
+        let mut ids: Vec<_> = registry.list().map(|schema| schema.id.clone()).collect();
+        ids.sort();
+        Ok(Response::new(pb::ListSchemasResponse { ids }))
+    }
+
+    async fn get_schema(
+        &self,
+        request: Request<pb::GetSchemaRequest>,
+    ) -> Result<Response<pb::Schema>, Status> {
+        self.authorize(&request)?;
+        let id = request.into_inner().id;
+        let registry = RsrSchemaRegistry::new();
+        if registry.get(&id).is_none() {
+            return Err(Status::not_found(format!("unknown schema: {}", id)));
+        }
+        let content = registry.get_content(&id).map_err(internal)?;
+        Ok(Response::new(pb::Schema { id, content }))
+    }
+
+    type RunPipelineStream = UnboundedReceiverStream<Result<pb::ExecutionEvent, Status>>;
+
+    async fn run_pipeline(
+        &self,
+        request: Request<pb::RunPipelineRequest>,
+    ) -> Result<Response<Self::RunPipelineStream>, Status> {
+        self.authorize(&request)?;
+        // Runs execute uploaded commands, so they need a caller with a key
+        if self.config.api_keys.is_empty() {
+            return Err(Status::permission_denied(
+                "RunPipeline needs API keys; the server was started with --no-auth",
+            ));
+        }
+        let request = request.into_inner();
+
+        let scratch = Scratch::new().map_err(internal)?;
+        scratch.write_files(&request.files).map_err(upload_error)?;
+        let path = scratch.path().join(PIPELINE_FILE);
+        std::fs::write(&path, &request.pipeline).map_err(internal)?;
+        let pipeline = Pipeline::from_file(&path).map_err(status)?;
+        let validation = PipelineValidator::validate(&pipeline).map_err(status)?;
+        if !validation.is_valid() {
+            return Err(Status::invalid_argument(validation.errors.join("; ")));
+        }
+
+        let mut executor = PipelineExecutor::new();
+        for (name, exec) in create_executors(&pipeline) {
+            executor.register_executor(&name, exec);
+        }
+        let missing = executor.check_tools(&pipeline).await.map_err(status)?;
+        if !missing.is_empty() {
+            return Err(Status::failed_precondition(format!(
+                "not available on this worker: {}",
+                missing.join(", ")
+            )));
+        }
+
+        let options = ExecutionOptions {
+            stages: request.stages,
+            approvals: Approvals {
+                all: false,
+                stages: request.approve,
+            },
+            ..ExecutionOptions::default()
+        };
+
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let (events, mut received) = mpsc::unbounded_channel();
+        let executor = executor.with_events(events).quiet();
+        tokio::spawn(async move {
+            let root = scratch.path().to_path_buf();
+            let forward = {
+                let (sender, root) = (sender.clone(), root.clone());
+                tokio::spawn(async move {
+                    while let Some(event) = received.recv().await {
+                        let _ = sender.send(Ok(super::event(event, &root)));
+                    }
+                })
+            };
+
+            // The run goes on if the client leaves: its processes would
+            // otherwise be orphaned
+            let result = executor.execute(&pipeline, &root, &options).await;
+            drop(executor);
+            let _ = forward.await;
+            if let Err(e) = result {
+                let _ = sender.send(Err(status(e)));
+            }
+            drop(scratch);
+        });
+
+        Ok(Response::new(UnboundedReceiverStream::new(receiver)))
+    }
+}
+
+/// Run file walking and parsing off the async workers
+async fn blocking<T, F>(f: F) -> Result<T, Status>
+where
+    F: FnOnce() -> Result<T, Status> + Send + 'static,
+    T: Send + 'static,
+{
+    tokio::task::spawn_blocking(f).await.map_err(internal)?
+}
+
+fn report<T: crate::report::Report>(value: &T) -> Result<Response<pb::Report>, Status> {
+    let json = crate::report::to_json(value).map_err(internal)?;
+    Ok(Response::new(pb::Report {
+        kind: T::KIND.to_string(),
+        format_version: crate::report::FORMAT_VERSION,
+        json,
+    }))
+}
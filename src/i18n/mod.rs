@@ -0,0 +1,237 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Message catalogs
+//!
+//! User-facing text (run summaries, explanations, recommendation reasons and
+//! RSR remediation steps) is looked up by key in a per-locale catalog under
+//! `locales/`. The catalogs are compiled into the binary; a key missing from
+//! a translation falls back to English.
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::LazyLock;
+
+/// Environment variable that selects the locale, ahead of `LANG` and friends
+pub const LANG_ENV: &str = "CONFLOW_LANG";
+
+/// Supported message locales
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, clap::ValueEnum)]
+pub enum Locale {
+    /// English
+    #[default]
+    En,
+    /// German
+    De,
+    /// French
+    Fr,
+    /// Japanese
+    Ja,
+}
+
+impl Locale {
+    /// All supported locales
+    pub const ALL: [Locale; 4] = [Locale::En, Locale::De, Locale::Fr, Locale::Ja];
+
+    /// Language tag, as used for catalog file names
+    pub fn tag(self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::De => "de",
+            Locale::Fr => "fr",
+            Locale::Ja => "ja",
+        }
+    }
+
+    /// Parse a POSIX locale or BCP 47 tag such as `de_DE.UTF-8` or `fr-CA`
+    ///
+    /// `C` and `POSIX` mean English. Returns `None` for unsupported languages.
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let language = tag
+            .split(['_', '-', '.', '@'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match language.as_str() {
+            "en" | "c" | "posix" => Some(Locale::En),
+            "de" => Some(Locale::De),
+            "fr" => Some(Locale::Fr),
+            "ja" => Some(Locale::Ja),
+            _ => None,
+        }
+    }
+
+    /// Pick the locale from `CONFLOW_LANG`, `LC_ALL`, `LC_MESSAGES` or `LANG`
+    ///
+    /// The first variable that is set decides, as with gettext; an
+    /// unsupported language there means English.
+    pub fn detect() -> Self {
+        Self::detect_from(|name| std::env::var(name).ok())
+    }
+
+    fn detect_from(env: impl Fn(&str) -> Option<String>) -> Self {
+        [LANG_ENV, "LC_ALL", "LC_MESSAGES", "LANG"]
+            .into_iter()
+            .filter_map(env)
+            .find(|value| !value.is_empty())
+            .and_then(|value| Self::from_tag(&value))
+            .unwrap_or_default()
+    }
+
+    fn source(self) -> &'static str {
+        match self {
+            Locale::En => include_str!("../../locales/en.yaml"),
+            Locale::De => include_str!("../../locales/de.yaml"),
+            Locale::Fr => include_str!("../../locales/fr.yaml"),
+            Locale::Ja => include_str!("../../locales/ja.yaml"),
+        }
+    }
+}
+
+impl std::fmt::Display for Locale {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.tag())
+    }
+}
+
+type Catalog = HashMap<String, String>;
+
+static CATALOGS: LazyLock<HashMap<Locale, Catalog>> = LazyLock::new(|| {
+    Locale::ALL
+        .into_iter()
+        .map(|locale| {
+            let catalog = serde_yaml::from_str(locale.source())
+                .unwrap_or_else(|e| panic!("invalid message catalog '{}': {}", locale, e));
+            (locale, catalog)
+        })
+        .collect()
+});
+
+static CURRENT: AtomicU8 = AtomicU8::new(0);
+
+/// Set the locale used by [`tr`] and [`tr_with`]
+pub fn set_locale(locale: Locale) {
+    let index = Locale::ALL.iter().position(|l| *l == locale).unwrap_or(0);
+    CURRENT.store(index as u8, Ordering::Relaxed);
+}
+
+/// The locale messages are currently rendered in
+pub fn locale() -> Locale {
+    Locale::ALL
+        .get(CURRENT.load(Ordering::Relaxed) as usize)
+        .copied()
+        .unwrap_or_default()
+}
+
+/// Look up a message in the current locale
+pub fn tr(key: &str) -> String {
+    lookup(locale(), key).to_string()
+}
+
+/// Look up a message in the current locale and fill in its `{name}` placeholders
+pub fn tr_with(key: &str, args: &[(&str, &dyn Display)]) -> String {
+    format(locale(), key, args)
+}
+
+/// Look up a message, falling back to English and then to the key itself
+pub fn lookup(locale: Locale, key: &str) -> &str {
+    [locale, Locale::En]
+        .iter()
+        .find_map(|l| CATALOGS.get(l).and_then(|c| c.get(key)))
+        .map(String::as_str)
+        .unwrap_or(key)
+}
+
+/// Look up a message in `locale` and fill in its `{name}` placeholders
+pub fn format(locale: Locale, key: &str, args: &[(&str, &dyn Display)]) -> String {
+    args.iter()
+        .fold(lookup(locale, key).to_string(), |message, (name, value)| {
+            message.replace(&format!("{{{}}}", name), &value.to_string())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    fn placeholders(message: &str) -> BTreeSet<&str> {
+        message
+            .split('{')
+            .skip(1)
+            .filter_map(|rest| rest.split_once('}').map(|(name, _)| name))
+            .collect()
+    }
+
+    #[test]
+    fn test_catalogs_match_english() {
+        let english = &CATALOGS[&Locale::En];
+        for locale in Locale::ALL {
+            let catalog = &CATALOGS[&locale];
+            for (key, message) in english {
+                let translated = catalog
+                    .get(key)
+                    .unwrap_or_else(|| panic!("{} is missing '{}'", locale, key));
+                assert_eq!(
+                    placeholders(translated),
+                    placeholders(message),
+                    "{}: placeholders of '{}'",
+                    locale,
+                    key
+                );
+            }
+            for key in catalog.keys() {
+                assert!(
+                    english.contains_key(key),
+                    "{} has unknown key '{}'",
+                    locale,
+                    key
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_locale_from_tag() {
+        assert_eq!(Locale::from_tag("de_DE.UTF-8"), Some(Locale::De));
+        assert_eq!(Locale::from_tag("fr-CA"), Some(Locale::Fr));
+        assert_eq!(Locale::from_tag("ja_JP"), Some(Locale::Ja));
+        assert_eq!(Locale::from_tag("C.UTF-8"), Some(Locale::En));
+        assert_eq!(Locale::from_tag("POSIX"), Some(Locale::En));
+        assert_eq!(Locale::from_tag("pt_BR"), None);
+
+        let env = |vars: &'static [(&str, &str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(k, _)| *k == name)
+                    .map(|(_, v)| v.to_string())
+            }
+        };
+        assert_eq!(
+            Locale::detect_from(env(&[("LANG", "de_DE.UTF-8"), (LANG_ENV, "ja")])),
+            Locale::Ja
+        );
+        assert_eq!(Locale::detect_from(env(&[("LANG", "fr_FR")])), Locale::Fr);
+        assert_eq!(Locale::detect_from(env(&[("LC_ALL", "pt_BR")])), Locale::En);
+        assert_eq!(Locale::detect_from(env(&[])), Locale::En);
+    }
+
+    #[test]
+    fn test_lookup_and_format() {
+        assert_eq!(lookup(Locale::De, "run.outputs"), "Ausgaben");
+        assert_eq!(lookup(Locale::Ja, "no.such.key"), "no.such.key");
+        assert_eq!(
+            format(Locale::Fr, "run.stage_failed", &[("stage", &"build")]),
+            "build a échoué"
+        );
+        assert_eq!(
+            format(
+                Locale::En,
+                "run.progress",
+                &[("finished", &2), ("total", &5), ("eta", &"9s")]
+            ),
+            "2/5 stages, about 9s left"
+        );
+    }
+}
@@ -0,0 +1,394 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Built-in lessons
+//!
+//! Each exercise's starter files fail its checks; the task says what to
+//! change so they pass.
+
+use super::{Check, Exercise, Expect, Lesson};
+
+/// Every lesson, in the order they are best taken
+pub const LESSONS: &[Lesson] = &[CUE_BASICS, NICKEL_CONTRACTS, UNIFICATION];
+
+const CUE_BASICS: Lesson = Lesson {
+    name: "cue-basics",
+    title: "CUE basics",
+    summary: "Types, constraints and defaults in a CUE schema",
+    exercises: &[
+        Exercise {
+            slug: "read-errors",
+            title: "Reading CUE errors",
+            task: "config.yaml doesn't match the #Config schema in schema.cue. Run the \
+                   check, read CUE's error, and fix config.yaml (not the schema).",
+            hint: "Quoted values are strings in YAML, and port must be an int.",
+            files: &[
+                (
+                    "config.yaml",
+                    "name: api\n\
+                     port: \"8080\"\n",
+                ),
+                (
+                    "schema.cue",
+                    "package learn\n\
+                     \n\
+                     // A service config: a name, and a port number\n\
+                     #Config: {\n\
+                     \tname: string\n\
+                     \tport: int & >0 & <65536\n\
+                     }\n",
+                ),
+            ],
+            checks: &[Check {
+                stage: "tool:\n  \
+                        type: cue\n  \
+                        command: vet\n  \
+                        schemas: [schema.cue]\n  \
+                        flags: [-d, \"#Config\"]\n\
+                        input: config.yaml",
+                expect: Expect::Pass,
+            }],
+        },
+        Exercise {
+            slug: "constraints",
+            title: "Constraints",
+            task: "Tighten #Config in schema.cue: env must be dev, staging or prod, and \
+                   replicas between 1 and 10. config.yaml must still pass, while \
+                   bad-env.yaml and bad-replicas.yaml must be rejected.",
+            hint: "A disjunction lists the allowed values: \"dev\" | \"staging\" | \"prod\". \
+                   Bounds combine with &: int & >=1 & <=10.",
+            files: &[
+                (
+                    "schema.cue",
+                    "package learn\n\
+                     \n\
+                     #Config: {\n\
+                     \tname:     string\n\
+                     \treplicas: int\n\
+                     \tenv:      string\n\
+                     }\n",
+                ),
+                (
+                    "config.yaml",
+                    "name: api\n\
+                     replicas: 3\n\
+                     env: prod\n",
+                ),
+                (
+                    "bad-env.yaml",
+                    "name: api\n\
+                     replicas: 3\n\
+                     env: production\n",
+                ),
+                (
+                    "bad-replicas.yaml",
+                    "name: api\n\
+                     replicas: 0\n\
+                     env: dev\n",
+                ),
+            ],
+            checks: &[
+                Check {
+                    stage: "tool:\n  \
+                            type: cue\n  \
+                            command: vet\n  \
+                            schemas: [schema.cue]\n  \
+                            flags: [-d, \"#Config\"]\n\
+                            input: config.yaml",
+                    expect: Expect::Pass,
+                },
+                Check {
+                    stage: "tool:\n  \
+                            type: cue\n  \
+                            command: vet\n  \
+                            schemas: [schema.cue]\n  \
+                            flags: [-d, \"#Config\"]\n\
+                            input: bad-env.yaml",
+                    expect: Expect::Fail,
+                },
+                Check {
+                    stage: "tool:\n  \
+                            type: cue\n  \
+                            command: vet\n  \
+                            schemas: [schema.cue]\n  \
+                            flags: [-d, \"#Config\"]\n\
+                            input: bad-replicas.yaml",
+                    expect: Expect::Fail,
+                },
+            ],
+        },
+        Exercise {
+            slug: "defaults",
+            title: "Defaults",
+            task: "config.yaml only sets a name. Give #Config defaults so it exports \
+                   with port 8080 and replicas 1.",
+            hint: "A default is marked with *: port: int | *8080.",
+            files: &[
+                (
+                    "schema.cue",
+                    "package learn\n\
+                     \n\
+                     #Config: {\n\
+                     \tname:     string\n\
+                     \tport:     int\n\
+                     \treplicas: int & >=1\n\
+                     }\n",
+                ),
+                ("config.yaml", "name: api\n"),
+            ],
+            checks: &[Check {
+                stage: "tool:\n  \
+                        type: cue\n  \
+                        command: export\n  \
+                        schemas: [schema.cue]\n  \
+                        out_format: json\n  \
+                        flags: [-d, \"#Config\"]\n\
+                        input: config.yaml",
+                expect: Expect::Output(r#"{"name": "api", "port": 8080, "replicas": 1}"#),
+            }],
+        },
+    ],
+};
+
+const NICKEL_CONTRACTS: Lesson = Lesson {
+    name: "nickel-contracts",
+    title: "Nickel contracts",
+    summary: "Field contracts, record contracts and merge priorities",
+    exercises: &[
+        Exercise {
+            slug: "field-contracts",
+            title: "Field contracts",
+            task: "Each field of config.ncl has a contract after `|`, and one value \
+                   breaks its contract. Run the check, read Nickel's error, and fix \
+                   the value.",
+            hint: "\"8080\" is a String; the port contract wants a Number.",
+            files: &[(
+                "config.ncl",
+                "{\n  \
+                 name | String = \"api\",\n  \
+                 port | Number = \"8080\",\n  \
+                 debug | Bool = false,\n\
+                 }\n",
+            )],
+            checks: &[Check {
+                stage: "tool:\n  \
+                        type: nickel\n  \
+                        command: export\n  \
+                        format: json\n\
+                        input: config.ncl",
+                expect: Expect::Output(r#"{"name": "api", "port": 8080, "debug": false}"#),
+            }],
+        },
+        Exercise {
+            slug: "record-contracts",
+            title: "Record contracts",
+            task: "schema.ncl is the contract config.ncl and bad.ncl are checked \
+                   against. Tighten env so only \"dev\", \"staging\" and \"prod\" pass: \
+                   config.ncl must export, bad.ncl must be rejected.",
+            hint: "Build a contract from a predicate: env | String | \
+                   std.contract.from_predicate \
+                   (fun e => std.array.elem e [\"dev\", \"staging\", \"prod\"])",
+            files: &[
+                (
+                    "schema.ncl",
+                    "# The contract every service config satisfies\n\
+                     {\n  \
+                     name | String,\n  \
+                     replicas | Number,\n  \
+                     env | String,\n\
+                     }\n",
+                ),
+                (
+                    "config.ncl",
+                    "let Config = import \"schema.ncl\" in\n\
+                     {\n  \
+                     name = \"api\",\n  \
+                     replicas = 3,\n  \
+                     env = \"prod\",\n\
+                     } | Config\n",
+                ),
+                (
+                    "bad.ncl",
+                    "let Config = import \"schema.ncl\" in\n\
+                     {\n  \
+                     name = \"api\",\n  \
+                     replicas = 3,\n  \
+                     env = \"production\",\n\
+                     } | Config\n",
+                ),
+            ],
+            checks: &[
+                Check {
+                    stage: "tool:\n  \
+                            type: nickel\n  \
+                            command: export\n  \
+                            format: json\n\
+                            input: config.ncl",
+                    expect: Expect::Output(r#"{"name": "api", "replicas": 3, "env": "prod"}"#),
+                },
+                Check {
+                    stage: "tool:\n  \
+                            type: nickel\n  \
+                            command: export\n  \
+                            format: json\n\
+                            input: bad.ncl",
+                    expect: Expect::Fail,
+                },
+            ],
+        },
+        Exercise {
+            slug: "merge-priorities",
+            title: "Merge priorities",
+            task: "Merging with & combines records, but both sides of the merge in \
+                   config.ncl set replicas, so it fails. Make base's value a default \
+                   the override can replace.",
+            hint: "replicas | default = 1 gives the field a lower priority.",
+            files: &[(
+                "config.ncl",
+                "let base = {\n  \
+                 image = \"api:1.0\",\n  \
+                 replicas = 1,\n\
+                 }\n\
+                 in\n\
+                 base & { replicas = 3 }\n",
+            )],
+            checks: &[Check {
+                stage: "tool:\n  \
+                        type: nickel\n  \
+                        command: export\n  \
+                        format: json\n\
+                        input: config.ncl",
+                expect: Expect::Output(r#"{"image": "api:1.0", "replicas": 3}"#),
+            }],
+        },
+    ],
+};
+
+const UNIFICATION: Lesson = Lesson {
+    name: "unification",
+    title: "Unification",
+    summary: "How CUE combines values, templates and closed definitions",
+    exercises: &[
+        Exercise {
+            slug: "defaults-across-files",
+            title: "Values never override",
+            task: "Unification combines values instead of overriding them: base.cue \
+                   and prod.cue disagree on replicas, so exporting them together \
+                   fails. Make base's replicas a default (still an int) so prod.cue \
+                   wins.",
+            hint: "replicas: int | *1",
+            files: &[
+                (
+                    "base.cue",
+                    "package learn\n\
+                     \n\
+                     // Every environment starts from these values\n\
+                     replicas: 1\n\
+                     image:    \"api:1.0\"\n",
+                ),
+                (
+                    "prod.cue",
+                    "package learn\n\
+                     \n\
+                     // Production runs more replicas\n\
+                     replicas: 3\n",
+                ),
+            ],
+            checks: &[Check {
+                stage: "tool:\n  \
+                        type: cue\n  \
+                        command: export\n  \
+                        out_format: json\n\
+                        input: [base.cue, prod.cue]",
+                expect: Expect::Output(r#"{"replicas": 3, "image": "api:1.0"}"#),
+            }],
+        },
+        Exercise {
+            slug: "templates",
+            title: "Pattern constraints",
+            task: "Every field of services is unified with the [Name=_] template. \
+                   Fill it in so each service gets a name equal to its key, and \
+                   replicas defaulting to 1.",
+            hint: "services: [Name=_]: {name: Name, replicas: int | *1}",
+            files: &[(
+                "services.cue",
+                "package learn\n\
+                 \n\
+                 services: [Name=_]: {}\n\
+                 \n\
+                 services: api: {}\n\
+                 services: web: replicas: 2\n",
+            )],
+            checks: &[Check {
+                stage: "tool:\n  \
+                        type: cue\n  \
+                        command: export\n  \
+                        out_format: json\n\
+                        input: services.cue",
+                expect: Expect::Output(
+                    r#"{"services": {
+                        "api": {"name": "api", "replicas": 1},
+                        "web": {"name": "web", "replicas": 2}
+                    }}"#,
+                ),
+            }],
+        },
+        Exercise {
+            slug: "closed-definitions",
+            title: "Closed definitions",
+            task: "The `...` in schema.cue leaves #Deployment open to any field, so \
+                   typo.cue's unknown field slips through. Close the definition: \
+                   deploy.cue must still export, typo.cue must be rejected.",
+            hint: "Definitions are closed unless they end in `...`; remove it.",
+            files: &[
+                (
+                    "schema.cue",
+                    "package learn\n\
+                     \n\
+                     #Deployment: {\n\
+                     \timage:    string\n\
+                     \treplicas: int\n\
+                     \t...\n\
+                     }\n",
+                ),
+                (
+                    "deploy.cue",
+                    "package learn\n\
+                     \n\
+                     deploy: #Deployment & {\n\
+                     \timage:    \"api:1.0\"\n\
+                     \treplicas: 3\n\
+                     }\n",
+                ),
+                (
+                    "typo.cue",
+                    "package learn\n\
+                     \n\
+                     deploy: #Deployment & {\n\
+                     \timage:       \"api:1.0\"\n\
+                     \treplicas:    3\n\
+                     \timagePolicy: \"Always\"\n\
+                     }\n",
+                ),
+            ],
+            checks: &[
+                Check {
+                    stage: "tool:\n  \
+                            type: cue\n  \
+                            command: export\n  \
+                            out_format: json\n\
+                            input: [schema.cue, deploy.cue]",
+                    expect: Expect::Output(r#"{"deploy": {"image": "api:1.0", "replicas": 3}}"#),
+                },
+                Check {
+                    stage: "tool:\n  \
+                            type: cue\n  \
+                            command: export\n  \
+                            out_format: json\n\
+                            input: [schema.cue, typo.cue]",
+                    expect: Expect::Fail,
+                },
+            ],
+        },
+    ],
+};
@@ -0,0 +1,321 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Interactive lessons
+//!
+//! `conflow learn` teaches CUE and Nickel with exercises in a sandbox
+//! directory. Each exercise writes starter files that fail its checks;
+//! the checks are pipeline stages run by the real executors, so solving
+//! one means `cue` or `nickel` accepted (or rejected) exactly what the
+//! task asked for. Progress is kept in the sandbox's `progress.json`.
+
+mod lessons;
+
+pub use lessons::LESSONS;
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+
+use crate::errors::ConflowError;
+use crate::executors::{shell_quote, Executor};
+use crate::pipeline::Stage;
+
+/// Default sandbox directory, relative to where `conflow learn` runs
+pub const DEFAULT_SANDBOX: &str = "conflow-learn";
+
+/// A lesson: a few exercises on one topic
+#[derive(Debug)]
+pub struct Lesson {
+    /// Name given to `conflow learn`
+    pub name: &'static str,
+    pub title: &'static str,
+    pub summary: &'static str,
+    pub exercises: &'static [Exercise],
+}
+
+/// One exercise
+#[derive(Debug)]
+pub struct Exercise {
+    /// Directory name inside the lesson's sandbox
+    pub slug: &'static str,
+    pub title: &'static str,
+    /// What to change
+    pub task: &'static str,
+    pub hint: &'static str,
+    /// Starter files; the first is the one to edit
+    pub files: &'static [(&'static str, &'static str)],
+    /// Checks that must all hold for the exercise to be solved
+    pub checks: &'static [Check],
+}
+
+/// A pipeline stage, without its name, and what it must do
+#[derive(Debug)]
+pub struct Check {
+    /// Stage in `.conflow.yaml` syntax
+    pub stage: &'static str,
+    pub expect: Expect,
+}
+
+/// What a check's stage must do
+#[derive(Debug, Clone, Copy)]
+pub enum Expect {
+    /// Succeed
+    Pass,
+    /// Fail, rejecting its input
+    Fail,
+    /// Succeed and print this JSON
+    Output(&'static str),
+}
+
+/// The lesson called `name`
+pub fn lesson(name: &str) -> Option<&'static Lesson> {
+    LESSONS.iter().find(|lesson| lesson.name == name)
+}
+
+impl Lesson {
+    /// Directory of exercise `index` in the sandbox
+    pub fn exercise_dir(&self, sandbox: &Path, index: usize) -> PathBuf {
+        sandbox
+            .join(self.name)
+            .join(format!("{}-{}", index + 1, self.exercises[index].slug))
+    }
+}
+
+impl Exercise {
+    /// Write the starter files into `dir`, keeping files already there
+    /// unless `reset`, and record them in the audit log of `root`
+    pub fn write(
+        &self,
+        root: &Path,
+        dir: &Path,
+        reset: bool,
+    ) -> Result<Vec<PathBuf>, ConflowError> {
+        std::fs::create_dir_all(dir).map_err(|e| ConflowError::FileWriteError {
+            path: dir.to_path_buf(),
+            error: e.to_string(),
+        })?;
+        let mut written = Vec::new();
+        for (name, content) in self.files {
+            let path = dir.join(name);
+            if path.exists() && !reset {
+                continue;
+            }
+            crate::audit::write(root, &path, content, "learn").map_err(|e| {
+                ConflowError::FileWriteError {
+                    path: path.clone(),
+                    error: e.to_string(),
+                }
+            })?;
+            written.push(path);
+        }
+        Ok(written)
+    }
+
+    /// The exercise's tool, e.g. `cue`
+    pub fn tool(&self) -> Result<String, ConflowError> {
+        let stage = self.stage(0, self.checks.first().map_or("", |c| c.stage))?;
+        Ok(stage.tool_name().to_string())
+    }
+
+    fn stage(&self, index: usize, yaml: &str) -> Result<Stage, ConflowError> {
+        let yaml = format!("name: {}-{}\n{}", self.slug, index + 1, yaml);
+        serde_yaml::from_str(&yaml).map_err(|e| ConflowError::Yaml {
+            message: format!("exercise '{}': {}", self.slug, e),
+        })
+    }
+
+    /// Run every check in `dir`, returning what went wrong (nothing once
+    /// the exercise is solved)
+    pub async fn check(
+        &self,
+        dir: &Path,
+        executors: &HashMap<String, Box<dyn Executor>>,
+    ) -> Result<Vec<String>, ConflowError> {
+        let mut problems = Vec::new();
+        for (index, check) in self.checks.iter().enumerate() {
+            let stage = self.stage(index, check.stage)?;
+            let tool = stage.tool_name();
+            let executor = executors
+                .get(tool)
+                .ok_or_else(|| ConflowError::tool_not_found(tool))?;
+            let command = executor
+                .command_line(&stage, dir, None)?
+                .iter()
+                .map(|arg| shell_quote(arg))
+                .collect::<Vec<_>>()
+                .join(" ");
+            let result = executor.execute(&stage, dir, &HashMap::new(), None).await?;
+            let output = match result.stderr.trim() {
+                "" => result.stdout.trim().to_string(),
+                stderr => stderr.to_string(),
+            };
+
+            match check.expect {
+                Expect::Pass | Expect::Output(_) if !result.success => {
+                    problems.push(format!("`{}` failed:\n{}", command, output))
+                }
+                Expect::Fail if result.success => {
+                    problems.push(format!("`{}` passed, but should fail", command))
+                }
+                Expect::Output(expected) => {
+                    let expected: serde_json::Value =
+                        serde_json::from_str(expected).map_err(|e| ConflowError::Json {
+                            message: format!("exercise '{}': {}", self.slug, e),
+                        })?;
+                    let actual = serde_json::from_str::<serde_json::Value>(&result.stdout);
+                    if actual.as_ref().ok() != Some(&expected) {
+                        problems.push(format!(
+                            "`{}` printed:\n{}\nbut should print:\n{}",
+                            command,
+                            result.stdout.trim(),
+                            expected
+                        ));
+                    }
+                }
+                Expect::Pass | Expect::Fail => {}
+            }
+        }
+        Ok(problems)
+    }
+}
+
+/// Exercises done in each lesson
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Progress {
+    #[serde(default)]
+    pub lessons: BTreeMap<String, LessonProgress>,
+}
+
+/// Where a learner is in one lesson
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LessonProgress {
+    /// Exercise being worked on; past the last one when the lesson is done
+    #[serde(default)]
+    pub current: usize,
+    /// Exercises solved, skipped ones aside
+    #[serde(default)]
+    pub solved: BTreeSet<usize>,
+}
+
+impl Progress {
+    fn path(sandbox: &Path) -> PathBuf {
+        sandbox.join("progress.json")
+    }
+
+    /// Progress saved in `sandbox`, or none yet
+    pub fn load(sandbox: &Path) -> Result<Self, ConflowError> {
+        let path = Self::path(sandbox);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(&path).map_err(|e| ConflowError::FileReadError {
+            path: path.clone(),
+            error: e.to_string(),
+        })?;
+        serde_json::from_str(&content).map_err(|e| ConflowError::Json {
+            message: format!("{}: {}", path.display(), e),
+        })
+    }
+
+    pub fn save(&self, sandbox: &Path) -> Result<(), ConflowError> {
+        let path = Self::path(sandbox);
+        std::fs::create_dir_all(sandbox).map_err(|e| ConflowError::FileWriteError {
+            path: sandbox.to_path_buf(),
+            error: e.to_string(),
+        })?;
+        let json = serde_json::to_string_pretty(self).map_err(|e| ConflowError::Json {
+            message: e.to_string(),
+        })?;
+        std::fs::write(&path, json).map_err(|e| ConflowError::FileWriteError {
+            path,
+            error: e.to_string(),
+        })
+    }
+
+    /// Progress in a lesson
+    pub fn lesson(&mut self, name: &str) -> &mut LessonProgress {
+        self.lessons.entry(name.to_string()).or_default()
+    }
+
+    /// Exercises solved in a lesson
+    pub fn solved(&self, name: &str) -> usize {
+        self.lessons.get(name).map_or(0, |p| p.solved.len())
+    }
+}
+
+impl LessonProgress {
+    /// Mark the current exercise solved and move to the next
+    pub fn solve(&mut self) {
+        self.solved.insert(self.current);
+        self.current += 1;
+    }
+
+    /// Move to the next exercise without solving this one
+    pub fn skip(&mut self) {
+        self.current += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lessons() {
+        for lesson in LESSONS {
+            assert!(!lesson.exercises.is_empty(), "{}", lesson.name);
+            for exercise in lesson.exercises {
+                let tool = exercise.tool().unwrap();
+                assert!(["cue", "nickel"].contains(&tool.as_str()), "{}", tool);
+                for (index, check) in exercise.checks.iter().enumerate() {
+                    let stage = exercise.stage(index, check.stage).unwrap();
+                    for input in stage.input.patterns() {
+                        assert!(
+                            exercise.files.iter().any(|(name, _)| *name == input),
+                            "{}: no starter file {}",
+                            exercise.slug,
+                            input
+                        );
+                    }
+                    if let Expect::Output(json) = check.expect {
+                        serde_json::from_str::<serde_json::Value>(json).unwrap();
+                    }
+                }
+            }
+        }
+        assert!(lesson("unification").is_some());
+        assert!(lesson("rust").is_none());
+    }
+
+    #[test]
+    fn test_sandbox_and_progress() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let lesson = lesson("cue-basics").unwrap();
+        let dir = lesson.exercise_dir(temp.path(), 0);
+        assert!(dir.ends_with("cue-basics/1-read-errors"));
+
+        let exercise = &lesson.exercises[0];
+        assert_eq!(exercise.write(temp.path(), &dir, false).unwrap().len(), 2);
+        std::fs::write(dir.join("config.yaml"), "edited").unwrap();
+        assert!(exercise.write(temp.path(), &dir, false).unwrap().is_empty());
+        assert_eq!(
+            std::fs::read_to_string(dir.join("config.yaml")).unwrap(),
+            "edited"
+        );
+        exercise.write(temp.path(), &dir, true).unwrap();
+        assert_ne!(
+            std::fs::read_to_string(dir.join("config.yaml")).unwrap(),
+            "edited"
+        );
+
+        let mut progress = Progress::load(temp.path()).unwrap();
+        progress.lesson("cue-basics").solve();
+        progress.lesson("cue-basics").skip();
+        progress.save(temp.path()).unwrap();
+        let mut progress = Progress::load(temp.path()).unwrap();
+        assert_eq!(progress.solved("cue-basics"), 1);
+        assert_eq!(progress.lesson("cue-basics").current, 2);
+        assert_eq!(progress.solved("unification"), 0);
+    }
+}
@@ -30,20 +30,55 @@
 //! ```
 
 pub mod analyzer;
+pub mod audit;
+#[cfg(feature = "native")]
+pub mod bundle;
 pub mod cache;
+#[cfg(feature = "native")]
 pub mod cli;
+pub mod diagnostics;
 pub mod errors;
 pub mod executors;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+pub mod i18n;
+#[cfg(feature = "native")]
+pub mod learn;
+pub mod lint;
+#[cfg(feature = "native")]
+pub mod notify;
 pub mod pipeline;
+#[cfg(feature = "native")]
+pub mod plugins;
+#[cfg(feature = "conflow-py")]
+pub mod python;
+#[cfg(feature = "native")]
+pub mod release;
+pub mod report;
 pub mod rsr;
+#[cfg(feature = "native")]
+pub mod server;
+#[cfg(feature = "native")]
+pub mod telemetry;
+pub mod toolchain;
+#[cfg(feature = "native")]
+pub mod tui;
 pub mod utils;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "native")]
+pub mod workspace;
 
 // Re-export commonly used types
 pub use errors::{ConflowError, ConflowResult};
 pub use pipeline::{Pipeline, Stage};
 
 // Re-export RSR types
-pub use rsr::{ComplianceChecker, ComplianceLevel, ComplianceReport, RsrHooks};
+pub use rsr::{ComplianceChecker, ComplianceLevel, ComplianceReport};
+#[cfg(feature = "native")]
+pub use rsr::RsrHooks;
 
 /// Library version
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -0,0 +1,251 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Custom lint rules
+//!
+//! A CUE rule is a schema every linted file is vetted against with
+//! `cue vet`. A Rego rule is a policy whose `deny` rule is evaluated with
+//! `opa eval` for each document, in the style of conftest: every message it
+//! yields is a finding.
+
+use regex::Regex;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::LazyLock;
+
+use super::matches_any;
+use crate::cache::ParseCache;
+use crate::errors::ConflowError;
+use crate::executors::parsers::{parse_output, Location, Severity, ToolDiagnostic};
+use crate::pipeline::{CustomRule, LintConfig};
+use crate::utils::yaml;
+
+static PACKAGE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?m)^package\s+([\w.]+)").unwrap());
+
+/// Run the custom rules over the files they apply to
+pub(super) fn check(
+    config: &LintConfig,
+    files: &[PathBuf],
+    root: &Path,
+) -> Result<Vec<ToolDiagnostic>, ConflowError> {
+    let mut diagnostics = Vec::new();
+    for rule in &config.custom {
+        let Some(severity) = rule.level.severity() else {
+            continue;
+        };
+        let files: Vec<&PathBuf> = files
+            .iter()
+            .filter(|f| rule.files.is_empty() || matches_any(&rule.files, f))
+            .collect();
+        if files.is_empty() {
+            continue;
+        }
+
+        let tool = if rule.cue.is_some() { "cue" } else { "opa" };
+        if which::which(tool).is_err() {
+            diagnostics.push(finding(
+                rule,
+                Severity::Warning,
+                format!("custom rule skipped: `{}` is not installed", tool),
+                None,
+            ));
+            continue;
+        }
+
+        let found = match (&rule.cue, &rule.rego) {
+            (Some(schema), _) => cue(rule, schema, &files, root)?,
+            (None, Some(policy)) => rego(rule, policy, &files, root)?,
+            (None, None) => vec![],
+        };
+        diagnostics.extend(found.into_iter().map(|mut d| {
+            d.severity = severity;
+            d
+        }));
+    }
+    Ok(diagnostics)
+}
+
+fn finding(
+    rule: &CustomRule,
+    severity: Severity,
+    message: String,
+    location: Option<Location>,
+) -> ToolDiagnostic {
+    ToolDiagnostic {
+        tool: "lint".into(),
+        severity,
+        message,
+        rule: Some(rule.name.clone()),
+        location,
+        related: vec![],
+    }
+}
+
+fn cue(
+    rule: &CustomRule,
+    schema: &Path,
+    files: &[&PathBuf],
+    root: &Path,
+) -> Result<Vec<ToolDiagnostic>, ConflowError> {
+    let mut diagnostics = Vec::new();
+    for file in files {
+        let output = Command::new("cue")
+            .arg("vet")
+            .arg(schema)
+            .arg(file)
+            .current_dir(root)
+            .output()?;
+        if output.status.success() {
+            continue;
+        }
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let parsed = parse_output("cue", &stderr);
+        if parsed.is_empty() {
+            let location = Location {
+                file: file.to_path_buf(),
+                line: None,
+                column: None,
+            };
+            let message = stderr.trim().to_string();
+            diagnostics.push(finding(rule, Severity::Error, message, Some(location)));
+        }
+        diagnostics.extend(parsed.into_iter().map(|d| ToolDiagnostic {
+            tool: "lint".into(),
+            rule: Some(rule.name.clone()),
+            ..d
+        }));
+    }
+    Ok(diagnostics)
+}
+
+fn rego(
+    rule: &CustomRule,
+    policy: &Path,
+    files: &[&PathBuf],
+    root: &Path,
+) -> Result<Vec<ToolDiagnostic>, ConflowError> {
+    let source =
+        std::fs::read_to_string(root.join(policy)).map_err(|e| ConflowError::FileReadError {
+            path: root.join(policy),
+            error: e.to_string(),
+        })?;
+    let package = PACKAGE
+        .captures(&source)
+        .map(|c| c[1].to_string())
+        .ok_or_else(|| ConflowError::CheckFailed {
+            message: format!("{}: no `package` declaration", policy.display()),
+        })?;
+    let query = format!("data.{}.deny", package);
+
+    let mut diagnostics = Vec::new();
+    for file in files {
+        let content =
+            std::fs::read_to_string(root.join(file)).map_err(|e| ConflowError::FileReadError {
+                path: root.join(file),
+                error: e.to_string(),
+            })?;
+        // Files that don't parse are reported by the `parse` rule
+        let Ok(documents) = ParseCache::global().documents(file, &content) else {
+            continue;
+        };
+        let lines: Vec<usize> = match file.extension().and_then(|e| e.to_str()) {
+            Some("json") => vec![1],
+            _ => yaml::documents(&content).iter().map(|d| d.line).collect(),
+        };
+
+        for (document, line) in documents.iter().zip(lines) {
+            let location = Location {
+                file: file.to_path_buf(),
+                line: Some(line),
+                column: None,
+            };
+            for message in evaluate(policy, &query, document, root)? {
+                diagnostics.push(finding(
+                    rule,
+                    Severity::Error,
+                    message,
+                    Some(location.clone()),
+                ));
+            }
+        }
+    }
+    Ok(diagnostics)
+}
+
+/// Messages `query` yields for one document
+fn evaluate(
+    policy: &Path,
+    query: &str,
+    document: &Value,
+    root: &Path,
+) -> Result<Vec<String>, ConflowError> {
+    use std::io::Write;
+
+    let mut child = Command::new("opa")
+        .args(["eval", "--format", "json", "--stdin-input", "--data"])
+        .arg(policy)
+        .arg(query)
+        .current_dir(root)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(document.to_string().as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(ConflowError::CheckFailed {
+            message: format!(
+                "opa eval {}: {}",
+                policy.display(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+        });
+    }
+
+    let result: Value = serde_json::from_slice(&output.stdout)?;
+    Ok(messages(&result))
+}
+
+/// Messages in an `opa eval` result; `deny` may yield strings or objects
+/// with a `msg`, as conftest policies do
+fn messages(result: &Value) -> Vec<String> {
+    result
+        .pointer("/result/0/expressions/0/value")
+        .and_then(Value::as_array)
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(|v| match v {
+                    Value::String(message) => Some(message.clone()),
+                    other => other.get("msg")?.as_str().map(String::from),
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_opa_result_messages() {
+        let result = json!({"result": [{"expressions": [{
+            "value": ["replicas must be at least 2", {"msg": "no owner label"}, 3],
+            "text": "data.main.deny",
+        }]}]});
+        assert_eq!(
+            messages(&result),
+            vec!["replicas must be at least 2", "no owner label"]
+        );
+        assert!(messages(&json!({})).is_empty());
+        assert_eq!(
+            &PACKAGE.captures("# policy\npackage k8s.pods\n").unwrap()[1],
+            "k8s.pods"
+        );
+    }
+}
@@ -0,0 +1,668 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Config linting
+//!
+//! Schemas say what a config may contain; lint rules say what it should
+//! contain - pinned images, resource limits, a consistent key style. Rules
+//! are set under `lint:` in `.conflow.yaml`, with per-path overrides and
+//! custom rules written in CUE or Rego:
+//!
+//! ```yaml
+//! lint:
+//!   rules:
+//!     no-latest-tag: error
+//!     max-depth: { level: warning, max: 6 }
+//!   paths:
+//!     - files: ["k8s/**/*.yaml"]
+//!       rules:
+//!         resource-limits: error
+//!   custom:
+//!     - name: replicas
+//!       rego: policy/replicas.rego
+//! ```
+
+#[cfg(feature = "native")]
+mod custom;
+mod observability;
+mod rules;
+
+pub use rules::{rule, Options, Rule, RULES};
+
+use serde_json::Value;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::analyzer::{detect_template, stub_render, TemplateDialect};
+use crate::cache::ParseCache;
+use crate::errors::ConflowError;
+use crate::executors::parsers::{Location, Severity, ToolDiagnostic};
+use crate::pipeline::{LintConfig, RuleLevel, RuleSetting};
+use crate::utils::yaml;
+use crate::utils::yaml::edit::Editor;
+use rules::{At, Check, Context, Finding, Fix};
+
+/// Files linted when `lint.files` isn't set
+pub const DEFAULT_FILES: &[&str] = &["**/*.yaml", "**/*.yml", "**/*.json"];
+
+/// Outcome of linting
+#[derive(Debug, Clone, Default)]
+pub struct LintReport {
+    /// Number of files linted
+    pub files: usize,
+    /// Remaining findings, with locations relative to the project root
+    pub diagnostics: Vec<ToolDiagnostic>,
+    /// Files that fixes rewrite
+    pub fixes: Vec<FileFix>,
+}
+
+/// The fixes made to one file
+#[derive(Debug, Clone)]
+pub struct FileFix {
+    /// The file, relative to the project root
+    pub file: PathBuf,
+    pub original: String,
+    pub fixed: String,
+    /// Ids of the rules whose findings were fixed
+    pub rules: Vec<String>,
+}
+
+impl FileFix {
+    /// Unified diff from the original to the fixed content
+    pub fn diff(&self) -> String {
+        let file = self.file.display().to_string();
+        similar::TextDiff::from_lines(&self.original, &self.fixed)
+            .unified_diff()
+            .context_radius(3)
+            .header(&file, &file)
+            .to_string()
+    }
+}
+
+impl LintReport {
+    /// Number of findings at a severity
+    pub fn count(&self, severity: Severity) -> usize {
+        self.diagnostics
+            .iter()
+            .filter(|d| d.severity == severity)
+            .count()
+    }
+}
+
+impl RuleLevel {
+    /// Severity of findings at this level, `None` when the rule is off
+    pub fn severity(self) -> Option<Severity> {
+        match self {
+            Self::Off => None,
+            Self::Info => Some(Severity::Info),
+            Self::Warning => Some(Severity::Warning),
+            Self::Error => Some(Severity::Error),
+        }
+    }
+}
+
+/// Problems with a lint configuration, one message each
+pub fn validate(config: &LintConfig) -> Vec<String> {
+    let mut problems = Vec::new();
+    let settings = config
+        .rules
+        .iter()
+        .chain(config.paths.iter().flat_map(|o| &o.rules));
+    for (id, setting) in settings {
+        let Some(rule) = rule(id) else {
+            let mut problem = format!("'{}' is not a lint rule", id);
+            if let Some(similar) = yaml::suggest(id, RULES.iter().map(|r| r.id)) {
+                problem.push_str(&format!(" - did you mean '{}'?", similar));
+            }
+            problems.push(problem);
+            continue;
+        };
+        if let RuleSetting::Options { options, .. } = setting {
+            if let Err(e) = rule.validate(options) {
+                problems.push(format!("'{}': {}", id, e));
+            }
+        }
+    }
+
+    for custom in &config.custom {
+        if rule(&custom.name).is_some() {
+            problems.push(format!(
+                "custom rule '{}' has the name of a built-in rule",
+                custom.name
+            ));
+        }
+        if custom.cue.is_some() == custom.rego.is_some() {
+            problems.push(format!(
+                "custom rule '{}' needs exactly one of 'cue' or 'rego'",
+                custom.name
+            ));
+        }
+    }
+    problems
+}
+
+/// The files `lint.files` selects, relative to `root`
+///
+/// Hidden files and directories are skipped unless a pattern names them.
+pub fn files(config: &LintConfig, root: &Path) -> Result<Vec<PathBuf>, ConflowError> {
+    matching_files(&config.files, root)
+}
+
+/// The files matching some globs (default: [`DEFAULT_FILES`]), relative to `root`
+pub(crate) fn matching_files(
+    patterns: &[String],
+    root: &Path,
+) -> Result<Vec<PathBuf>, ConflowError> {
+    let patterns: Vec<&str> = if patterns.is_empty() {
+        DEFAULT_FILES.to_vec()
+    } else {
+        patterns.iter().map(String::as_str).collect()
+    };
+    let options = glob::MatchOptions {
+        require_literal_leading_dot: true,
+        ..Default::default()
+    };
+
+    let mut files = std::collections::BTreeSet::new();
+    for pattern in patterns {
+        let full = root.join(pattern.trim_start_matches("./"));
+        for path in glob::glob_with(&full.to_string_lossy(), options)?.flatten() {
+            if path.is_file() {
+                if let Ok(relative) = path.strip_prefix(root) {
+                    files.insert(relative.to_path_buf());
+                }
+            }
+        }
+    }
+    Ok(files.into_iter().collect())
+}
+
+/// Lint files, relative to `root`
+///
+/// With `fix`, findings that can be fixed safely are fixed in memory first:
+/// each fix is kept only if the file then parses to exactly the data it
+/// meant to produce, and the report lists the rewritten files for the
+/// caller to preview or write. Diagnostics describe the fixed content.
+pub fn lint(
+    config: &LintConfig,
+    files: &[PathBuf],
+    root: &Path,
+    fix: bool,
+) -> Result<LintReport, ConflowError> {
+    let mut report = LintReport {
+        files: files.len(),
+        ..LintReport::default()
+    };
+    let mut schemas = HashMap::new();
+
+    for file in files {
+        let path = root.join(file);
+        let content = std::fs::read_to_string(&path).map_err(|e| ConflowError::FileReadError {
+            path: path.clone(),
+            error: e.to_string(),
+        })?;
+        let settings = settings(config, file);
+        let schema = match schema_path(config, file) {
+            Some(schema) => Some(load_schema(&mut schemas, schema, root)?),
+            None => None,
+        };
+        let file = Target {
+            file,
+            settings: &settings,
+            schema: schema.as_deref(),
+            template: detect_template(&content, &path),
+        };
+
+        let content = if fix {
+            let (fixed, rules) = file.fix(&content);
+            if fixed == content {
+                content
+            } else {
+                report.fixes.push(FileFix {
+                    file: file.file.to_path_buf(),
+                    original: content,
+                    fixed: fixed.clone(),
+                    rules,
+                });
+                fixed
+            }
+        } else {
+            content
+        };
+        report.diagnostics.extend(file.check(&content));
+    }
+
+    #[cfg(feature = "native")]
+    report
+        .diagnostics
+        .extend(custom::check(config, files, root)?);
+
+    Ok(report)
+}
+
+/// Enabled rules for a file, with their severity and options
+fn settings(config: &LintConfig, file: &Path) -> Vec<(&'static Rule, Severity, Options)> {
+    let mut chosen: BTreeMap<&str, &RuleSetting> = config
+        .rules
+        .iter()
+        .map(|(id, setting)| (id.as_str(), setting))
+        .collect();
+    for entry in &config.paths {
+        if matches_any(&entry.files, file) {
+            chosen.extend(entry.rules.iter().map(|(id, s)| (id.as_str(), s)));
+        }
+    }
+
+    RULES
+        .iter()
+        .filter_map(|rule| {
+            let (level, options) = match chosen.get(rule.id) {
+                Some(RuleSetting::Level(level)) => (*level, Options::new()),
+                Some(RuleSetting::Options { level, options }) => (*level, options.clone()),
+                None => (rule.level, Options::new()),
+            };
+            Some((rule, level.severity()?, options))
+        })
+        .collect()
+}
+
+/// The JSON Schema of the last `lint.paths` entry matching a file that has one
+fn schema_path<'a>(config: &'a LintConfig, file: &Path) -> Option<&'a Path> {
+    config
+        .paths
+        .iter()
+        .rev()
+        .filter(|entry| matches_any(&entry.files, file))
+        .find_map(|entry| entry.schema.as_deref())
+}
+
+fn load_schema(
+    schemas: &mut HashMap<PathBuf, Arc<Value>>,
+    schema: &Path,
+    root: &Path,
+) -> Result<Arc<Value>, ConflowError> {
+    if let Some(loaded) = schemas.get(schema) {
+        return Ok(Arc::clone(loaded));
+    }
+    let path = root.join(schema);
+    let content = std::fs::read_to_string(&path).map_err(|_| ConflowError::FileNotFound {
+        path: path.clone(),
+        help: Some("`schema` in `lint.paths` names a JSON Schema file".into()),
+    })?;
+    let documents = ParseCache::global().documents(schema, &content)?;
+    let loaded = Arc::new(documents.first().cloned().unwrap_or_default());
+    schemas.insert(schema.to_path_buf(), Arc::clone(&loaded));
+    Ok(loaded)
+}
+
+/// Whether a path relative to the project root matches one of the globs
+pub(crate) fn matches_any(patterns: &[String], file: &Path) -> bool {
+    patterns.iter().any(|pattern| {
+        let pattern = pattern.trim_start_matches("./");
+        match glob::Pattern::new(pattern) {
+            Ok(p) => p.matches_path(file),
+            Err(_) => file == Path::new(pattern),
+        }
+    })
+}
+
+fn file_is_data(file: &Path) -> bool {
+    matches!(
+        file.extension().and_then(|e| e.to_str()),
+        Some("yaml" | "yml" | "json")
+    )
+}
+
+/// A file and the rules that apply to it
+struct Target<'a> {
+    file: &'a Path,
+    settings: &'a [(&'static Rule, Severity, Options)],
+    schema: Option<&'a Value>,
+    /// Template dialect, for files that aren't data until rendered
+    template: Option<TemplateDialect>,
+}
+
+/// A data rule's finding in one document
+struct DataFinding {
+    rule: &'static Rule,
+    severity: Severity,
+    document: usize,
+    finding: Finding,
+}
+
+impl Target<'_> {
+    /// Apply every fix that keeps the file's data as intended, returning
+    /// the new content and the ids of the rules fixed
+    fn fix(&self, content: &str) -> (String, Vec<String>) {
+        let mut text = content.to_string();
+        let mut fixed = Vec::new();
+
+        for (rule, _, _) in self.settings {
+            let Fix::Text(fix) = rule.fix else {
+                continue;
+            };
+            let candidate = fix(&text);
+            if candidate != text && self.same_data(&text, &candidate) {
+                text = candidate;
+                fixed.push(rule.id.to_string());
+            }
+        }
+
+        // Structural edits are only made to YAML, and not to templates
+        if self.template.is_some() || !matches!(
+            self.file.extension().and_then(|e| e.to_str()),
+            Some("yaml" | "yml")
+        ) {
+            return (text, fixed);
+        }
+        let findings = self.data_findings(&text).unwrap_or_default();
+        let mut editor = Editor::new(text);
+        for found in findings {
+            let Some(change) = found.finding.change else {
+                continue;
+            };
+            if editor.apply(found.document, &change) && !fixed.iter().any(|id| id == found.rule.id)
+            {
+                fixed.push(found.rule.id.to_string());
+            }
+        }
+        (editor.into_content(), fixed)
+    }
+
+    /// Whether a text fix left the data of the file unchanged
+    fn same_data(&self, before: &str, after: &str) -> bool {
+        // A template's data is what's around its tags
+        let (before, after) = match self.template {
+            Some(_) => (stub_render(before).into(), stub_render(after).into()),
+            None => (Cow::Borrowed(before), Cow::Borrowed(after)),
+        };
+        let cache = ParseCache::global();
+        match (
+            cache.documents(self.file, &before),
+            cache.documents(self.file, &after),
+        ) {
+            (Ok(before), Ok(after)) => before == after,
+            // Only whitespace changes are made to files that don't parse
+            (Err(_), _) => !file_is_data(self.file) || self.template.is_some(),
+            _ => false,
+        }
+    }
+
+    /// Findings of the data rules, or the parse error
+    fn data_findings(&self, content: &str) -> Result<Vec<DataFinding>, ConflowError> {
+        if !file_is_data(self.file) {
+            return Ok(vec![]);
+        }
+        let documents = ParseCache::global().documents(self.file, content)?;
+
+        let mut found = Vec::new();
+        for (rule, severity, options) in self.settings {
+            let Check::Data(check) = rule.check else {
+                continue;
+            };
+            let context = Context {
+                file: self.file,
+                options,
+                schema: self.schema,
+            };
+            for (index, document) in documents.iter().enumerate() {
+                found.extend(
+                    check(document, &context)
+                        .into_iter()
+                        .map(|finding| DataFinding {
+                            rule,
+                            severity: *severity,
+                            document: index,
+                            finding,
+                        }),
+                );
+            }
+        }
+        Ok(found)
+    }
+
+    fn check(&self, content: &str) -> Vec<ToolDiagnostic> {
+        let diagnostic = |rule: &str, severity, message, line| ToolDiagnostic {
+            tool: "lint".into(),
+            severity,
+            message,
+            rule: Some(rule.to_string()),
+            location: Some(Location {
+                file: self.file.to_path_buf(),
+                line,
+                column: None,
+            }),
+            related: vec![],
+        };
+
+        let mut diagnostics = Vec::new();
+        for (rule, severity, _) in self.settings {
+            let Check::Text(check) = rule.check else {
+                continue;
+            };
+            for finding in check(content) {
+                let line = match finding.at {
+                    At::Line(line) => Some(line),
+                    At::Key(_) => None,
+                };
+                diagnostics.push(diagnostic(rule.id, *severity, finding.message, line));
+            }
+        }
+
+        let data = match self.template {
+            Some(dialect) => Err(dialect),
+            None => Ok(self.data_findings(content)),
+        };
+        match data {
+            Err(dialect) => {
+                let message = format!(
+                    "{} template: not linted beyond text rules; lint the rendered output instead",
+                    dialect
+                );
+                diagnostics.push(diagnostic("template", Severity::Info, message, None));
+            }
+            Ok(Ok(found)) => {
+                // Offsets of YAML documents, in the order they were parsed
+                let offsets: Vec<usize> = match self.file.extension().and_then(|e| e.to_str()) {
+                    Some("json") => vec![0],
+                    _ => yaml::documents(content).iter().map(|d| d.offset).collect(),
+                };
+                for found in found {
+                    let line = match found.finding.at {
+                        At::Line(line) => Some(line),
+                        At::Key(ref path) => {
+                            let path: Vec<&str> = path.iter().map(String::as_str).collect();
+                            let offset = offsets.get(found.document).copied().unwrap_or(0);
+                            yaml::key_line(content, offset, &path)
+                        }
+                    };
+                    let message = found.finding.message;
+                    diagnostics.push(diagnostic(found.rule.id, found.severity, message, line));
+                }
+            }
+            Ok(Err(e)) => {
+                let message = format!("not linted beyond text rules: {}", e);
+                diagnostics.push(diagnostic("parse", Severity::Error, message, None));
+            }
+        }
+        diagnostics.sort_by_key(|d| d.location.as_ref().and_then(|l| l.line));
+        diagnostics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::Pipeline;
+
+    #[test]
+    fn test_lint_with_overrides_and_fixes() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let root = temp.path();
+        std::fs::create_dir_all(root.join("k8s")).unwrap();
+        std::fs::write(
+            root.join("k8s/web.yaml"),
+            "kind: Pod\nspec:  \n  containers:\n    - name: web\n      image: nginx\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("app.yaml"), "image: nginx:1.25\nmaxConns: 10").unwrap();
+        std::fs::write(root.join("bad.json"), "{\"a\":").unwrap();
+
+        let pipeline = Pipeline::from_yaml(
+            r#"
+name: lint
+stages: []
+lint:
+  rules:
+    no-latest-tag: error
+    key-case: { level: warning, style: snake_case }
+  paths:
+    - files: ["k8s/**"]
+      rules:
+        key-case: off
+        resource-limits: error
+"#,
+        )
+        .unwrap();
+        let config = &pipeline.lint;
+        assert!(validate(config).is_empty());
+
+        let files = files(config, root).unwrap();
+        assert_eq!(files.len(), 3);
+
+        let report = lint(config, &files, root, true).unwrap();
+        let fixed: Vec<&Path> = report.fixes.iter().map(|f| f.file.as_path()).collect();
+        assert_eq!(
+            fixed,
+            vec![Path::new("app.yaml"), Path::new("k8s/web.yaml")]
+        );
+        let found: Vec<(String, Option<usize>)> = report
+            .diagnostics
+            .iter()
+            .map(|d| {
+                let location = d.location.as_ref().unwrap();
+                let rule = d.rule.clone().unwrap();
+                (
+                    format!("{}:{}", location.file.display(), rule),
+                    location.line,
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("app.yaml:key-case".into(), Some(2)),
+                ("bad.json:parse".into(), None),
+                ("bad.json:final-newline".into(), Some(1)),
+                ("k8s/web.yaml:resource-limits".into(), Some(3)),
+                ("k8s/web.yaml:no-latest-tag".into(), Some(5)),
+            ]
+        );
+        assert_eq!(report.count(Severity::Error), 3);
+        assert!(report.fixes[1].fixed.starts_with("kind: Pod\nspec:\n"));
+        assert!(report.fixes[1].diff().contains("\n-spec:  \n+spec:\n"));
+    }
+
+    #[test]
+    fn test_structural_fixes() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let root = temp.path();
+        std::fs::write(
+            root.join("app.schema.json"),
+            r#"{"type": "object", "required": ["replicas"],
+                "properties": {"replicas": {"type": "integer", "default": 2}}}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("pod.yaml"),
+            "\
+# web pod
+apiVersion: v1
+kind: Pod
+spec:
+  serviceAccount: web  # set by ops
+  containers:
+    - name: web
+      image: nginx:1.25
+      imagePullPolicy: always
+",
+        )
+        .unwrap();
+
+        let pipeline = Pipeline::from_yaml(
+            r#"
+name: lint
+stages: []
+lint:
+  paths:
+    - files: ["*.yaml"]
+      schema: app.schema.json
+"#,
+        )
+        .unwrap();
+        let files = vec![PathBuf::from("pod.yaml")];
+        let report = lint(&pipeline.lint, &files, root, true).unwrap();
+
+        assert_eq!(
+            report.fixes[0].fixed,
+            "\
+# web pod
+apiVersion: v1
+kind: Pod
+spec:
+  serviceAccountName: web  # set by ops
+  containers:
+    - name: web
+      image: nginx:1.25
+      imagePullPolicy: Always
+replicas: 2
+"
+        );
+        assert_eq!(
+            report.fixes[0].rules,
+            vec!["deprecated-keys", "enum-case", "missing-defaults"]
+        );
+        let remaining: Vec<_> = report.diagnostics.iter().map(|d| d.rule.clone()).collect();
+        assert_eq!(remaining, vec![Some("resource-limits".to_string())]);
+
+        // Templates only get text rules and fixes
+        std::fs::write(
+            root.join("deploy.yaml"),
+            "kind: Pod\nspec:  \n  serviceAccount: {{ .Values.account }}\n",
+        )
+        .unwrap();
+        let files = vec![PathBuf::from("deploy.yaml")];
+        let report = lint(&pipeline.lint, &files, root, true).unwrap();
+        assert_eq!(report.fixes[0].rules, vec!["trailing-whitespace"]);
+        let remaining: Vec<_> = report.diagnostics.iter().map(|d| d.rule.clone()).collect();
+        assert_eq!(remaining, vec![Some("template".to_string())]);
+        assert_eq!(report.diagnostics[0].severity, Severity::Info);
+    }
+
+    #[test]
+    fn test_validate() {
+        let pipeline = Pipeline::from_yaml(
+            r#"
+name: lint
+stages: []
+lint:
+  rules:
+    max-dept: warning
+    max-depth: { level: error, limit: 3 }
+  custom:
+    - name: policy
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            validate(&pipeline.lint),
+            vec![
+                "'max-dept' is not a lint rule - did you mean 'max-depth'?",
+                "'max-depth': unknown option 'limit'",
+                "custom rule 'policy' needs exactly one of 'cue' or 'rego'",
+            ]
+        );
+    }
+}
@@ -0,0 +1,601 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Lint rules for observability configs
+//!
+//! Prometheus rule files, Alertmanager configs and Grafana dashboards are
+//! usually loaded long after they are written, and a dashboard with a
+//! dangling datasource or a route to a missing receiver fails quietly.
+//! Each rule recognizes its documents by shape and leaves others alone.
+
+use regex::Regex;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::sync::LazyLock;
+
+use super::rules::{At, Context, Finding};
+
+/// Prometheus durations, e.g. `5m` or `1h30m`
+static DURATION: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^([0-9]+y)?([0-9]+w)?([0-9]+d)?([0-9]+h)?([0-9]+m)?([0-9]+s)?([0-9]+ms)?$")
+        .unwrap()
+});
+
+static METRIC_NAME: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[a-zA-Z_:][a-zA-Z0-9_:]*$").unwrap());
+
+/// Grafana variable references: `$name`, `${name}`, `${name:format}`, `[[name]]`
+static VARIABLE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(?:\$\{([A-Za-z0-9_]+)(?::[^}]*)?\}|\$([A-Za-z0-9_]+)|\[\[([A-Za-z0-9_]+)\]\])$")
+        .unwrap()
+});
+
+fn finding(message: String, path: &[String]) -> Finding {
+    Finding {
+        message,
+        at: At::Key(path.to_vec()),
+        change: None,
+    }
+}
+
+fn child(path: &[String], key: impl ToString) -> Vec<String> {
+    let mut path = path.to_vec();
+    path.push(key.to_string());
+    path
+}
+
+/// The rule groups of a Prometheus rule file or `PrometheusRule` resource
+fn rule_groups(document: &Value) -> Option<(&Vec<Value>, Vec<String>)> {
+    let (groups, path) = if document.get("kind").and_then(Value::as_str) == Some("PrometheusRule") {
+        (
+            document.pointer("/spec/groups")?,
+            vec!["spec".into(), "groups".into()],
+        )
+    } else {
+        (document.get("groups")?, vec!["groups".into()])
+    };
+    let groups = groups.as_array()?;
+    let all_rule_groups = !groups.is_empty()
+        && groups
+            .iter()
+            .all(|g| g.is_object() && g.get("rules").is_some());
+    all_rule_groups.then_some((groups, path))
+}
+
+fn is_duration(value: &Value) -> bool {
+    value
+        .as_str()
+        .is_some_and(|d| !d.is_empty() && DURATION.is_match(d))
+}
+
+pub(super) fn prometheus_rules(document: &Value, _: &Context) -> Vec<Finding> {
+    let Some((groups, path)) = rule_groups(document) else {
+        return vec![];
+    };
+    let mut findings = Vec::new();
+    let mut names = HashSet::new();
+    for (i, group) in groups.iter().enumerate() {
+        let path = child(&path, i);
+        let name = match group.get("name").and_then(Value::as_str) {
+            Some(name) if !name.is_empty() => name,
+            _ => {
+                findings.push(finding("rule group has no name".into(), &path));
+                "?"
+            }
+        };
+        if name != "?" && !names.insert(name) {
+            let message = format!("rule group '{}' is defined more than once", name);
+            findings.push(finding(message, &child(&path, "name")));
+        }
+        if let Some(interval) = group.get("interval").filter(|d| !is_duration(d)) {
+            let message = format!("group '{}' has an invalid interval {}", name, interval);
+            findings.push(finding(message, &child(&path, "interval")));
+        }
+
+        let rules_path = child(&path, "rules");
+        let Some(rules) = group["rules"].as_array() else {
+            let message = format!("'rules' of group '{}' is not a list", name);
+            findings.push(finding(message, &rules_path));
+            continue;
+        };
+        for (j, rule) in rules.iter().enumerate() {
+            findings.extend(check_rule(name, rule, &child(&rules_path, j)));
+        }
+    }
+    findings
+}
+
+fn check_rule(group: &str, rule: &Value, path: &[String]) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let alert = rule.get("alert").and_then(Value::as_str);
+    let record = rule.get("record").and_then(Value::as_str);
+    let label = alert.or(record).unwrap_or("?");
+    match (alert, record) {
+        (None, None) => {
+            let message = format!("rule in group '{}' has neither 'alert' nor 'record'", group);
+            findings.push(finding(message, path));
+        }
+        (Some(_), Some(_)) => {
+            let message = format!("rule '{}' sets both 'alert' and 'record'", label);
+            findings.push(finding(message, path));
+        }
+        (None, Some(record)) if !METRIC_NAME.is_match(record) => {
+            let message = format!("recorded metric name '{}' is not valid", record);
+            findings.push(finding(message, &child(path, "record")));
+        }
+        (None, Some(_)) => {
+            for key in ["for", "keep_firing_for", "annotations"] {
+                if rule.get(key).is_some() {
+                    let message = format!("recording rule '{}' sets '{}'", label, key);
+                    findings.push(finding(message, &child(path, key)));
+                }
+            }
+        }
+        (Some(_), None) => {}
+    }
+
+    let expr_missing = match rule.get("expr") {
+        Some(Value::String(expr)) => expr.trim().is_empty(),
+        Some(Value::Number(_)) => false,
+        _ => true,
+    };
+    if expr_missing {
+        let message = format!("rule '{}' has no 'expr'", label);
+        findings.push(finding(message, path));
+    }
+    for key in ["for", "keep_firing_for"] {
+        if let Some(duration) = rule.get(key).filter(|d| !is_duration(d)) {
+            let message = format!("rule '{}' has an invalid '{}' {}", label, key, duration);
+            findings.push(finding(message, &child(path, key)));
+        }
+    }
+    for key in ["labels", "annotations"] {
+        let Some(Value::Object(map)) = rule.get(key) else {
+            continue;
+        };
+        for (name, value) in map {
+            if !value.is_string() {
+                let message = format!("{} '{}' of rule '{}' is not a string", key, name, label);
+                findings.push(finding(message, &child(&child(path, key), name)));
+            }
+        }
+    }
+    findings
+}
+
+/// An Alertmanager config or `AlertmanagerConfig` resource, and its path
+fn alertmanager(document: &Value) -> Option<(&Value, Vec<String>)> {
+    let (config, path) =
+        if document.get("kind").and_then(Value::as_str) == Some("AlertmanagerConfig") {
+            (document.get("spec")?, vec!["spec".to_string()])
+        } else {
+            (document, vec![])
+        };
+    (config.get("route").is_some_and(Value::is_object)
+        && config.get("receivers").is_some_and(Value::is_array))
+    .then_some((config, path))
+}
+
+/// A field of an Alertmanager config, spelled in snake_case in the config
+/// file and camelCase in the resource
+fn field<'a>(value: &'a Value, snake: &str, camel: &str) -> Option<(&'a Value, String)> {
+    [snake, camel]
+        .into_iter()
+        .find_map(|key| value.get(key).map(|v| (v, key.to_string())))
+}
+
+pub(super) fn alertmanager_routes(document: &Value, _: &Context) -> Vec<Finding> {
+    let Some((config, path)) = alertmanager(document) else {
+        return vec![];
+    };
+    let mut findings = Vec::new();
+
+    let mut receivers = HashSet::new();
+    for (i, receiver) in config["receivers"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .enumerate()
+    {
+        let path = child(&child(&path, "receivers"), i);
+        match receiver.get("name").and_then(Value::as_str) {
+            Some(name) if !receivers.insert(name) => {
+                let message = format!("receiver '{}' is defined more than once", name);
+                findings.push(finding(message, &child(&path, "name")));
+            }
+            Some(_) => {}
+            None => findings.push(finding("receiver has no name".into(), &path)),
+        }
+    }
+
+    let mut intervals = HashSet::new();
+    for (snake, camel) in [
+        ("time_intervals", "timeIntervals"),
+        ("mute_time_intervals", "muteTimeIntervals"),
+    ] {
+        if let Some((Value::Array(list), _)) = field(config, snake, camel) {
+            intervals.extend(list.iter().filter_map(|i| i["name"].as_str()));
+        }
+    }
+
+    let route_path = child(&path, "route");
+    if config["route"].get("receiver").is_none() {
+        let message = "the root route has no receiver".to_string();
+        findings.push(finding(message, &route_path));
+    }
+    check_route(
+        &config["route"],
+        &route_path,
+        &receivers,
+        &intervals,
+        &mut findings,
+    );
+    findings
+}
+
+fn check_route(
+    route: &Value,
+    path: &[String],
+    receivers: &HashSet<&str>,
+    intervals: &HashSet<&str>,
+    findings: &mut Vec<Finding>,
+) {
+    if let Some(receiver) = route.get("receiver").and_then(Value::as_str) {
+        if !receivers.contains(receiver) {
+            let message = format!(
+                "route sends to receiver '{}', which is not defined",
+                receiver
+            );
+            findings.push(finding(message, &child(path, "receiver")));
+        }
+    }
+    for (snake, camel) in [
+        ("mute_time_intervals", "muteTimeIntervals"),
+        ("active_time_intervals", "activeTimeIntervals"),
+    ] {
+        let Some((Value::Array(names), key)) = field(route, snake, camel) else {
+            continue;
+        };
+        for (i, name) in names.iter().enumerate() {
+            let Some(name) = name.as_str().filter(|n| !intervals.contains(n)) else {
+                continue;
+            };
+            let message = format!("route uses time interval '{}', which is not defined", name);
+            findings.push(finding(message, &child(&child(path, &key), i)));
+        }
+    }
+    if let Some(Value::Array(routes)) = route.get("routes") {
+        for (i, nested) in routes.iter().enumerate() {
+            let nested_path = child(&child(path, "routes"), i);
+            check_route(nested, &nested_path, receivers, intervals, findings);
+        }
+    }
+}
+
+/// A Grafana dashboard, bare or wrapped as the HTTP API returns it, and its path
+fn dashboard(document: &Value) -> Option<(&Value, Vec<String>)> {
+    let is_dashboard = |d: &Value| {
+        d.get("panels").is_some_and(Value::is_array)
+            && (d.get("schemaVersion").is_some() || d.get("uid").is_some())
+    };
+    match document.get("dashboard") {
+        Some(wrapped) if is_dashboard(wrapped) => Some((wrapped, vec!["dashboard".into()])),
+        _ => is_dashboard(document).then_some((document, vec![])),
+    }
+}
+
+/// Every panel of a dashboard with its path, including those in rows
+fn panels<'a>(dashboard: &'a Value, path: &[String]) -> Vec<(&'a Value, Vec<String>)> {
+    let mut found = Vec::new();
+    let panels_path = child(path, "panels");
+    for (i, panel) in dashboard["panels"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .enumerate()
+    {
+        let path = child(&panels_path, i);
+        if panel.get("panels").is_some_and(Value::is_array) {
+            found.extend(panels(panel, &path));
+        }
+        found.push((panel, path));
+    }
+    found
+}
+
+fn panel_name(panel: &Value) -> String {
+    match (panel["title"].as_str(), &panel["id"]) {
+        (Some(title), _) if !title.is_empty() => format!("'{}'", title),
+        (_, Value::Number(id)) => format!("#{}", id),
+        _ => "(untitled)".into(),
+    }
+}
+
+/// Grafana's grid is 24 columns wide
+const GRID_COLUMNS: u64 = 24;
+
+pub(super) fn grafana_panels(document: &Value, _: &Context) -> Vec<Finding> {
+    let Some((dashboard, path)) = dashboard(document) else {
+        return vec![];
+    };
+    let mut findings = Vec::new();
+    let mut ids = HashMap::new();
+    for (panel, path) in panels(dashboard, &path) {
+        let name = panel_name(panel);
+        if let Some(id) = panel.get("id").and_then(Value::as_u64) {
+            if let Some(first) = ids.insert(id, name.clone()) {
+                let message = format!("panel {} reuses id {} of panel {}", name, id, first);
+                findings.push(finding(message, &child(&path, "id")));
+            }
+        }
+        if !panel.get("type").is_some_and(Value::is_string) {
+            let message = format!("panel {} has no type", name);
+            findings.push(finding(message, &path));
+        }
+
+        if let Some(grid) = panel.get("gridPos") {
+            let at = child(&path, "gridPos");
+            let get = |key: &str| grid.get(key).and_then(Value::as_u64);
+            match (get("w"), get("h")) {
+                (Some(w), Some(h)) if w >= 1 && h >= 1 => {
+                    let x = get("x").unwrap_or(0);
+                    if x + w > GRID_COLUMNS {
+                        let message = format!(
+                            "panel {} is {} columns wide at x={}, past the {}-column grid",
+                            name, w, x, GRID_COLUMNS
+                        );
+                        findings.push(finding(message, &at));
+                    }
+                }
+                _ => {
+                    let message = format!("panel {} has no positive gridPos w and h", name);
+                    findings.push(finding(message, &at));
+                }
+            }
+        }
+
+        let mut ref_ids = HashSet::new();
+        for (i, target) in panel["targets"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .enumerate()
+        {
+            let Some(ref_id) = target.get("refId").and_then(Value::as_str) else {
+                continue;
+            };
+            if !ref_ids.insert(ref_id) {
+                let message = format!("panel {} has more than one query '{}'", name, ref_id);
+                let at = child(&child(&child(&path, "targets"), i), "refId");
+                findings.push(finding(message, &at));
+            }
+        }
+    }
+    findings
+}
+
+/// The variable a datasource reference names, if it is one
+fn datasource_variable(datasource: &Value) -> Option<(&str, &'static str)> {
+    let (reference, key) = match datasource {
+        Value::String(name) => (name.as_str(), ""),
+        Value::Object(map) => (map.get("uid")?.as_str()?, "uid"),
+        _ => return None,
+    };
+    let captures = VARIABLE.captures(reference)?;
+    let name = captures
+        .iter()
+        .skip(1)
+        .flatten()
+        .next()
+        .map(|m| m.as_str())?;
+    // Built-ins such as ${__all_datasources} are always there
+    (!name.starts_with("__")).then_some((name, key))
+}
+
+pub(super) fn grafana_datasources(document: &Value, _: &Context) -> Vec<Finding> {
+    let Some((dashboard, path)) = dashboard(document) else {
+        return vec![];
+    };
+
+    // Import inputs sit beside a wrapped dashboard or inside a bare one
+    let inputs = document
+        .get("__inputs")
+        .or_else(|| dashboard.get("__inputs"));
+    let mut defined: HashSet<&str> = inputs
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|i| i["name"].as_str())
+        .collect();
+    let variables = dashboard
+        .pointer("/templating/list")
+        .and_then(Value::as_array);
+    defined.extend(
+        variables
+            .into_iter()
+            .flatten()
+            .filter(|v| v["type"] == "datasource")
+            .filter_map(|v| v["name"].as_str()),
+    );
+
+    let mut findings = Vec::new();
+    let mut check = |owner: &str, datasource: &Value, at: Vec<String>| {
+        let Some((name, key)) = datasource_variable(datasource) else {
+            return;
+        };
+        if defined.contains(name) {
+            return;
+        }
+        let message = format!(
+            "{} uses datasource '${}', which is neither a dashboard input nor a datasource variable",
+            owner, name
+        );
+        let at = if key.is_empty() { at } else { child(&at, key) };
+        findings.push(finding(message, &at));
+    };
+
+    for (panel, path) in panels(dashboard, &path) {
+        let owner = format!("panel {}", panel_name(panel));
+        if let Some(datasource) = panel.get("datasource") {
+            check(&owner, datasource, child(&path, "datasource"));
+        }
+        for (i, target) in panel["targets"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .enumerate()
+        {
+            if let Some(datasource) = target.get("datasource") {
+                let at = child(&child(&child(&path, "targets"), i), "datasource");
+                check(&owner, datasource, at);
+            }
+        }
+    }
+    let list_path = child(&child(&path, "templating"), "list");
+    for (i, variable) in variables.into_iter().flatten().enumerate() {
+        let Some(datasource) = variable.get("datasource") else {
+            continue;
+        };
+        let owner = format!("variable '{}'", variable["name"].as_str().unwrap_or("?"));
+        check(
+            &owner,
+            datasource,
+            child(&child(&list_path, i), "datasource"),
+        );
+    }
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::path::Path;
+
+    fn messages(check: fn(&Value, &Context) -> Vec<Finding>, document: Value) -> Vec<String> {
+        let options = Default::default();
+        let context = Context {
+            file: Path::new("config.yaml"),
+            options: &options,
+            schema: None,
+        };
+        check(&document, &context)
+            .into_iter()
+            .map(|f| f.message)
+            .collect()
+    }
+
+    #[test]
+    fn test_prometheus_rules() {
+        let rules = json!({"groups": [
+            {"name": "api", "interval": "1m", "rules": [
+                {"alert": "HighErrors", "expr": "rate(errors[5m]) > 1", "for": "5 minutes",
+                 "labels": {"severity": "page", "team": 1}},
+                {"record": "job:errors:rate5m", "expr": "", "for": "5m"},
+                {"record": "job-errors", "expr": "sum(errors)"},
+                {"expr": "up == 0"},
+            ]},
+            {"name": "api", "rules": []},
+        ]});
+        assert_eq!(
+            messages(prometheus_rules, rules),
+            vec![
+                "rule 'HighErrors' has an invalid 'for' \"5 minutes\"",
+                "labels 'team' of rule 'HighErrors' is not a string",
+                "recording rule 'job:errors:rate5m' sets 'for'",
+                "rule 'job:errors:rate5m' has no 'expr'",
+                "recorded metric name 'job-errors' is not valid",
+                "rule in group 'api' has neither 'alert' nor 'record'",
+                "rule group 'api' is defined more than once",
+            ]
+        );
+
+        let resource = json!({"kind": "PrometheusRule", "spec": {"groups": [
+            {"name": "ok", "rules": [{"alert": "Down", "expr": "up == 0", "for": "1h30m"}]},
+        ]}});
+        assert!(messages(prometheus_rules, resource).is_empty());
+        assert!(messages(prometheus_rules, json!({"groups": ["admins"]})).is_empty());
+    }
+
+    #[test]
+    fn test_alertmanager_routes() {
+        let config = json!({
+            "route": {"receiver": "default", "routes": [
+                {"receiver": "pager", "mute_time_intervals": ["weekends", "nights"]},
+                {"receiver": "slack", "routes": [{"receiver": "email"}]},
+            ]},
+            "receivers": [{"name": "default"}, {"name": "pager"}, {"name": "pager"}, {"name": "slack"}],
+            "time_intervals": [{"name": "weekends"}],
+        });
+        assert_eq!(
+            messages(alertmanager_routes, config),
+            vec![
+                "receiver 'pager' is defined more than once",
+                "route uses time interval 'nights', which is not defined",
+                "route sends to receiver 'email', which is not defined",
+            ]
+        );
+
+        let resource = json!({"kind": "AlertmanagerConfig", "spec": {
+            "route": {"routes": [{"receiver": "ops", "activeTimeIntervals": ["office"]}]},
+            "receivers": [{"name": "ops"}],
+            "muteTimeIntervals": [{"name": "office"}],
+        }});
+        assert_eq!(
+            messages(alertmanager_routes, resource),
+            vec!["the root route has no receiver"]
+        );
+    }
+
+    #[test]
+    fn test_grafana_panels() {
+        let dashboard = json!({"schemaVersion": 39, "panels": [
+            {"id": 1, "title": "Errors", "type": "timeseries", "gridPos": {"x": 12, "y": 0, "w": 16, "h": 8},
+             "targets": [{"refId": "A"}, {"refId": "A"}]},
+            {"id": 2, "type": "row", "gridPos": {"x": 0, "y": 8, "w": 24, "h": 1}, "panels": [
+                {"id": 1, "title": "Latency", "type": "stat", "gridPos": {"x": 0, "y": 9, "w": 0, "h": 4}},
+            ]},
+            {"id": 3, "gridPos": {"x": 0, "y": 13, "w": 24, "h": 8}},
+        ]});
+        assert_eq!(
+            messages(grafana_panels, dashboard),
+            vec![
+                "panel 'Errors' is 16 columns wide at x=12, past the 24-column grid",
+                "panel 'Errors' has more than one query 'A'",
+                "panel 'Latency' reuses id 1 of panel 'Errors'",
+                "panel 'Latency' has no positive gridPos w and h",
+                "panel #3 has no type",
+            ]
+        );
+        assert!(messages(grafana_panels, json!({"panels": []})).is_empty());
+    }
+
+    #[test]
+    fn test_grafana_datasources() {
+        let dashboard = json!({
+            "__inputs": [{"name": "DS_PROMETHEUS", "type": "datasource"}],
+            "dashboard": {
+                "uid": "api",
+                "panels": [
+                    {"title": "Errors", "type": "timeseries",
+                     "datasource": {"type": "prometheus", "uid": "${DS_PROMETHEUS}"},
+                     "targets": [{"refId": "A", "datasource": {"uid": "${DS_LOKI}"}}]},
+                    {"title": "Logs", "type": "logs", "datasource": "$logs"},
+                    {"title": "All", "type": "table", "datasource": "${__all}"},
+                    {"title": "Fixed", "type": "stat", "datasource": {"uid": "P1809F7CD0C75ACF3"}},
+                ],
+                "templating": {"list": [
+                    {"name": "logs", "type": "datasource", "query": "loki"},
+                    {"name": "job", "type": "query", "datasource": "[[metrics]]"},
+                ]},
+            },
+        });
+        assert_eq!(
+            messages(grafana_datasources, dashboard),
+            vec![
+                "panel 'Errors' uses datasource '$DS_LOKI', which is neither a dashboard input nor a datasource variable",
+                "variable 'job' uses datasource '$metrics', which is neither a dashboard input nor a datasource variable",
+            ]
+        );
+    }
+}
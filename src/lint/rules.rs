@@ -0,0 +1,889 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Built-in lint rules
+//!
+//! Text rules look at the raw file and may carry a fix; data rules look at
+//! each parsed document and point at the offending key. Rules with no fix
+//! change what a config means, so they are only ever reported.
+
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use super::observability::{
+    alertmanager_routes, grafana_datasources, grafana_panels, prometheus_rules,
+};
+use crate::pipeline::RuleLevel;
+use crate::utils::yaml::edit::Change;
+
+/// Options of a rule, as written under its id in `lint.rules`
+pub type Options = BTreeMap<String, Value>;
+
+/// A built-in lint rule
+pub struct Rule {
+    /// Id used in `lint.rules` and reports
+    pub id: &'static str,
+    /// One-line description
+    pub description: &'static str,
+    /// Level when the pipeline doesn't set one
+    pub level: RuleLevel,
+    pub(super) check: Check,
+    pub(super) fix: Fix,
+    /// Check the rule's options, returning what is wrong with them
+    pub(super) options: fn(&Options) -> Result<(), String>,
+}
+
+impl Rule {
+    /// Whether `conflow lint --fix` can fix the rule's findings
+    pub fn fixable(&self) -> bool {
+        !matches!(self.fix, Fix::None)
+    }
+
+    /// Check a setting's options
+    pub fn validate(&self, options: &Options) -> Result<(), String> {
+        (self.options)(options)
+    }
+}
+
+pub(super) enum Check {
+    /// Over the file content
+    Text(fn(&str) -> Vec<Finding>),
+    /// Over each parsed document
+    Data(fn(&Value, &Context) -> Vec<Finding>),
+}
+
+/// How a rule's findings are fixed
+pub(super) enum Fix {
+    /// They aren't; fixing them would change what the config means
+    None,
+    /// By rewriting the file text
+    Text(fn(&str) -> String),
+    /// By the [`Change`] each finding carries
+    Changes,
+}
+
+/// What a data rule knows besides the document
+pub(super) struct Context<'a> {
+    /// The file, relative to the project root
+    pub file: &'a Path,
+    pub options: &'a Options,
+    /// The JSON Schema the file follows, from its `lint.paths` entry
+    pub schema: Option<&'a Value>,
+}
+
+/// A rule violation
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct Finding {
+    pub message: String,
+    pub at: At,
+    /// The change that fixes it, for `conflow lint --fix`
+    pub change: Option<Change>,
+}
+
+/// Where a finding is
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum At {
+    /// A 1-based line of the file
+    Line(usize),
+    /// A key path within the document
+    Key(Vec<String>),
+}
+
+/// Every built-in rule
+pub const RULES: &[Rule] = &[
+    Rule {
+        id: "final-newline",
+        description: "Files end with exactly one newline",
+        level: RuleLevel::Warning,
+        check: Check::Text(final_newline),
+        fix: Fix::Text(fix_final_newline),
+        options: no_options,
+    },
+    Rule {
+        id: "trailing-whitespace",
+        description: "No spaces or tabs at the end of lines",
+        level: RuleLevel::Warning,
+        check: Check::Text(trailing_whitespace),
+        fix: Fix::Text(fix_trailing_whitespace),
+        options: no_options,
+    },
+    Rule {
+        id: "no-latest-tag",
+        description: "Container images are pinned to a tag or digest other than `latest`",
+        level: RuleLevel::Warning,
+        check: Check::Data(no_latest_tag),
+        fix: Fix::None,
+        options: no_options,
+    },
+    Rule {
+        id: "resource-limits",
+        description: "Containers set resource limits (`require` lists the resources)",
+        level: RuleLevel::Warning,
+        check: Check::Data(resource_limits),
+        fix: Fix::None,
+        options: resource_limits_options,
+    },
+    Rule {
+        id: "key-case",
+        description: "Keys follow one case style (`style`: snake_case, camelCase or kebab-case)",
+        level: RuleLevel::Off,
+        check: Check::Data(key_case),
+        fix: Fix::None,
+        options: key_case_options,
+    },
+    Rule {
+        id: "max-depth",
+        description: "Documents nest no deeper than `max` levels (default 10)",
+        level: RuleLevel::Warning,
+        check: Check::Data(max_depth),
+        fix: Fix::None,
+        options: max_depth_options,
+    },
+    Rule {
+        id: "deprecated-keys",
+        description:
+            "Deprecated keys are replaced (`replacements` maps more key paths to new names)",
+        level: RuleLevel::Warning,
+        check: Check::Data(deprecated_keys),
+        fix: Fix::Changes,
+        options: deprecated_keys_options,
+    },
+    Rule {
+        id: "enum-case",
+        description: "Enum values match the case the schema or Kubernetes expects",
+        level: RuleLevel::Warning,
+        check: Check::Data(enum_case),
+        fix: Fix::Changes,
+        options: no_options,
+    },
+    Rule {
+        id: "missing-defaults",
+        description: "Required fields the schema gives a default for are set",
+        level: RuleLevel::Warning,
+        check: Check::Data(missing_defaults),
+        fix: Fix::Changes,
+        options: no_options,
+    },
+    Rule {
+        id: "prometheus-rules",
+        description: "Prometheus rules are alerts or recordings with an `expr` and valid durations",
+        level: RuleLevel::Warning,
+        check: Check::Data(prometheus_rules),
+        fix: Fix::None,
+        options: no_options,
+    },
+    Rule {
+        id: "alertmanager-routes",
+        description: "Alertmanager routes send to defined receivers and time intervals",
+        level: RuleLevel::Warning,
+        check: Check::Data(alertmanager_routes),
+        fix: Fix::None,
+        options: no_options,
+    },
+    Rule {
+        id: "grafana-panels",
+        description: "Grafana panels have a type, a unique id and a gridPos within 24 columns",
+        level: RuleLevel::Warning,
+        check: Check::Data(grafana_panels),
+        fix: Fix::None,
+        options: no_options,
+    },
+    Rule {
+        id: "grafana-datasources",
+        description: "Grafana datasource variables name a dashboard input or datasource variable",
+        level: RuleLevel::Warning,
+        check: Check::Data(grafana_datasources),
+        fix: Fix::None,
+        options: no_options,
+    },
+];
+
+/// Look up a built-in rule
+pub fn rule(id: &str) -> Option<&'static Rule> {
+    RULES.iter().find(|rule| rule.id == id)
+}
+
+fn no_options(options: &Options) -> Result<(), String> {
+    match options.keys().next() {
+        Some(key) => Err(format!("unknown option '{}'", key)),
+        None => Ok(()),
+    }
+}
+
+fn final_newline(content: &str) -> Vec<Finding> {
+    if content.is_empty() || fix_final_newline(content) == content {
+        return vec![];
+    }
+    let line = content.lines().count().max(1);
+    let message = if content.ends_with('\n') {
+        "file ends with blank lines"
+    } else {
+        "file does not end with a newline"
+    };
+    vec![Finding {
+        message: message.into(),
+        at: At::Line(line),
+        change: None,
+    }]
+}
+
+fn fix_final_newline(content: &str) -> String {
+    if content.is_empty() {
+        return String::new();
+    }
+    format!("{}\n", content.trim_end_matches(['\n', '\r']))
+}
+
+fn trailing_whitespace(content: &str) -> Vec<Finding> {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| line.ends_with([' ', '\t']))
+        .map(|(i, _)| Finding {
+            message: "trailing whitespace".into(),
+            at: At::Line(i + 1),
+            change: None,
+        })
+        .collect()
+}
+
+fn fix_trailing_whitespace(content: &str) -> String {
+    let mut fixed: String = content
+        .split_inclusive('\n')
+        .map(|line| {
+            let (text, end) = match line.strip_suffix("\r\n") {
+                Some(text) => (text, "\r\n"),
+                None => match line.strip_suffix('\n') {
+                    Some(text) => (text, "\n"),
+                    None => (line, ""),
+                },
+            };
+            format!("{}{}", text.trim_end_matches([' ', '\t']), end)
+        })
+        .collect();
+    if content.is_empty() {
+        fixed.clear();
+    }
+    fixed
+}
+
+/// Call `visit` with every value and its key path, depth first
+fn walk<'a>(value: &'a Value, path: &mut Vec<String>, visit: &mut dyn FnMut(&[String], &'a Value)) {
+    visit(path, value);
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map {
+                path.push(key.clone());
+                walk(child, path, visit);
+                path.pop();
+            }
+        }
+        Value::Array(items) => {
+            for (i, child) in items.iter().enumerate() {
+                path.push(i.to_string());
+                walk(child, path, visit);
+                path.pop();
+            }
+        }
+        _ => {}
+    }
+}
+
+fn no_latest_tag(document: &Value, _: &Context) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    walk(document, &mut vec![], &mut |path, value| {
+        let (Some(key), Value::String(image)) = (path.last(), value) else {
+            return;
+        };
+        // Templated images are resolved elsewhere
+        if key != "image" || image.contains("${") || image.contains("{{") {
+            return;
+        }
+        let message = match image_tag(image) {
+            _ if image.contains('@') => return,
+            Some("latest") => format!("image '{}' uses the 'latest' tag", image),
+            Some(_) => return,
+            None => format!("image '{}' has no tag, so it pulls 'latest'", image),
+        };
+        findings.push(Finding {
+            message,
+            at: At::Key(path.to_vec()),
+            change: None,
+        });
+    });
+    findings
+}
+
+/// The tag of an image reference, if it has one
+fn image_tag(image: &str) -> Option<&str> {
+    let name = image.rsplit('/').next().unwrap_or(image);
+    name.split_once(':').map(|(_, tag)| tag)
+}
+
+fn resource_limits(document: &Value, context: &Context) -> Vec<Finding> {
+    let required: Vec<&str> = context
+        .options
+        .get("require")
+        .and_then(Value::as_array)
+        .map(|names| names.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+
+    let mut findings = Vec::new();
+    walk(document, &mut vec![], &mut |path, value| {
+        let Some(key) = path.last() else {
+            return;
+        };
+        let (true, Value::Array(containers)) =
+            (key == "containers" || key == "initContainers", value)
+        else {
+            return;
+        };
+        for (i, container) in containers.iter().enumerate() {
+            let name = container.get("name").and_then(Value::as_str).unwrap_or("?");
+            let limits = container.pointer("/resources/limits");
+            let message = match limits.and_then(Value::as_object) {
+                Some(limits) if !limits.is_empty() => {
+                    let missing: Vec<&str> = required
+                        .iter()
+                        .copied()
+                        .filter(|resource| !limits.contains_key(*resource))
+                        .collect();
+                    if missing.is_empty() {
+                        continue;
+                    }
+                    format!("container '{}' has no {} limit", name, missing.join(" or "))
+                }
+                _ => format!("container '{}' sets no resource limits", name),
+            };
+            let mut at = path.to_vec();
+            at.push(i.to_string());
+            findings.push(Finding {
+                message,
+                at: At::Key(at),
+                change: None,
+            });
+        }
+    });
+    findings
+}
+
+fn resource_limits_options(options: &Options) -> Result<(), String> {
+    for (key, value) in options {
+        match (key.as_str(), value) {
+            ("require", Value::Array(names)) if names.iter().all(Value::is_string) => {}
+            ("require", _) => return Err("'require' must be a list of resource names".into()),
+            _ => return Err(format!("unknown option '{}'", key)),
+        }
+    }
+    Ok(())
+}
+
+/// Key case styles
+const CASE_STYLES: &[&str] = &["snake_case", "camelCase", "kebab-case"];
+
+/// Whether a key is in a case style
+fn in_case(style: &str, key: &str) -> bool {
+    let lower = |separator: char| {
+        !key.starts_with(separator)
+            && key
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == separator)
+    };
+    match style {
+        "snake_case" => lower('_'),
+        "kebab-case" => lower('-'),
+        _ => {
+            key.starts_with(|c: char| c.is_ascii_lowercase())
+                && key.chars().all(|c| c.is_ascii_alphanumeric())
+        }
+    }
+}
+
+fn key_case(document: &Value, context: &Context) -> Vec<Finding> {
+    let style = context
+        .options
+        .get("style")
+        .and_then(Value::as_str)
+        .unwrap_or("snake_case");
+
+    let mut findings = Vec::new();
+    walk(document, &mut vec![], &mut |path, _| {
+        let Some(key) = path.last() else {
+            return;
+        };
+        // List indices, and keys that aren't identifiers (label names,
+        // URLs, file names), are left alone
+        let identifier = key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+        if key.parse::<usize>().is_ok() || !identifier || in_case(style, key) {
+            return;
+        }
+        findings.push(Finding {
+            message: format!("key '{}' is not {}", key, style),
+            at: At::Key(path.to_vec()),
+            change: None,
+        });
+    });
+    findings
+}
+
+fn key_case_options(options: &Options) -> Result<(), String> {
+    for (key, value) in options {
+        match (key.as_str(), value.as_str()) {
+            ("style", Some(style)) if CASE_STYLES.contains(&style) => {}
+            ("style", _) => {
+                return Err(format!("'style' must be one of {}", CASE_STYLES.join(", ")))
+            }
+            _ => return Err(format!("unknown option '{}'", key)),
+        }
+    }
+    Ok(())
+}
+
+const DEFAULT_MAX_DEPTH: u64 = 10;
+
+fn max_depth(document: &Value, context: &Context) -> Vec<Finding> {
+    let max = context
+        .options
+        .get("max")
+        .and_then(Value::as_u64)
+        .unwrap_or(DEFAULT_MAX_DEPTH) as usize;
+
+    let mut findings = Vec::new();
+    walk(document, &mut vec![], &mut |path, _| {
+        // Only the first key past the limit on each branch
+        if path.len() == max + 1 {
+            findings.push(Finding {
+                message: format!("nested deeper than {} levels", max),
+                at: At::Key(path.to_vec()),
+                change: None,
+            });
+        }
+    });
+    findings
+}
+
+fn max_depth_options(options: &Options) -> Result<(), String> {
+    for (key, value) in options {
+        match (key.as_str(), value.as_u64()) {
+            ("max", Some(max)) if max > 0 => {}
+            ("max", _) => return Err("'max' must be a positive integer".into()),
+            _ => return Err(format!("unknown option '{}'", key)),
+        }
+    }
+    Ok(())
+}
+
+/// A key replaced by a newer one
+struct Deprecation {
+    /// Dotted path the key ends, e.g. `spec.serviceAccount`
+    path: &'static str,
+    replacement: &'static str,
+    /// Only in files with this stem
+    file: Option<&'static str>,
+}
+
+const DEPRECATIONS: &[Deprecation] = &[
+    // Pod specs, including pod templates
+    Deprecation {
+        path: "spec.serviceAccount",
+        replacement: "serviceAccountName",
+        file: None,
+    },
+    Deprecation {
+        path: "bases",
+        replacement: "resources",
+        file: Some("kustomization"),
+    },
+];
+
+/// Whether a key path ends with the dotted path `suffix`, with the whole
+/// path matching when `anchored`
+fn ends_with(path: &[String], suffix: &str, anchored: bool) -> bool {
+    let suffix: Vec<&str> = suffix.split('.').collect();
+    path.len() >= suffix.len()
+        && (!anchored || path.len() == suffix.len())
+        && path[path.len() - suffix.len()..]
+            .iter()
+            .zip(&suffix)
+            .all(|(a, b)| a == b)
+}
+
+fn deprecated_keys(document: &Value, context: &Context) -> Vec<Finding> {
+    let stem = context.file.file_stem().and_then(|s| s.to_str());
+    let mut replacements: Vec<(&str, &str, bool)> = DEPRECATIONS
+        .iter()
+        .filter(|d| d.file.is_none() || d.file == stem)
+        .map(|d| (d.path, d.replacement, d.file.is_some()))
+        .collect();
+    if let Some(Value::Object(custom)) = context.options.get("replacements") {
+        replacements.extend(
+            custom
+                .iter()
+                .filter_map(|(path, to)| Some((path.as_str(), to.as_str()?, false))),
+        );
+    }
+
+    let mut findings = Vec::new();
+    walk(document, &mut vec![], &mut |path, _| {
+        let Some(&(_, to, _)) = replacements
+            .iter()
+            .find(|(from, _, anchored)| ends_with(path, from, *anchored))
+        else {
+            return;
+        };
+        let key = &path[path.len() - 1];
+        let parent = &path[..path.len() - 1];
+        let taken = pointer(document, parent).is_some_and(|p| p.get(to).is_some());
+        let (message, change) = if taken {
+            let message = format!(
+                "'{}' is deprecated; remove it, '{}' is already set",
+                key, to
+            );
+            (message, None)
+        } else {
+            let change = Change::Rename {
+                path: path.to_vec(),
+                to: to.to_string(),
+            };
+            let message = format!("'{}' is deprecated; use '{}'", key, to);
+            (message, Some(change))
+        };
+        findings.push(Finding {
+            message,
+            at: At::Key(path.to_vec()),
+            change,
+        });
+    });
+    findings
+}
+
+fn deprecated_keys_options(options: &Options) -> Result<(), String> {
+    for (key, value) in options {
+        match (key.as_str(), value) {
+            ("replacements", Value::Object(map)) if map.values().all(Value::is_string) => {}
+            ("replacements", _) => {
+                return Err("'replacements' must map key paths to new key names".into())
+            }
+            _ => return Err(format!("unknown option '{}'", key)),
+        }
+    }
+    Ok(())
+}
+
+fn pointer<'a>(document: &'a Value, path: &[String]) -> Option<&'a Value> {
+    path.iter()
+        .try_fold(document, |value, segment| match value {
+            Value::Object(map) => map.get(segment),
+            Value::Array(items) => items.get(segment.parse::<usize>().ok()?),
+            _ => None,
+        })
+}
+
+/// Kubernetes fields with a fixed set of values
+const KUBERNETES_ENUMS: &[(&str, &[&str])] = &[
+    ("imagePullPolicy", &["Always", "IfNotPresent", "Never"]),
+    ("restartPolicy", &["Always", "OnFailure", "Never"]),
+    ("protocol", &["TCP", "UDP", "SCTP"]),
+];
+
+fn enum_case(document: &Value, context: &Context) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let mut check = |path: &[String], value: &str, allowed: Vec<&str>| {
+        if allowed.contains(&value) {
+            return;
+        }
+        let matching: Vec<&str> = allowed
+            .into_iter()
+            .filter(|a| a.eq_ignore_ascii_case(value))
+            .collect();
+        if let [expected] = matching[..] {
+            findings.push(Finding {
+                message: format!("'{}' should be written '{}'", value, expected),
+                at: At::Key(path.to_vec()),
+                change: Some(Change::Set {
+                    path: path.to_vec(),
+                    value: expected.into(),
+                }),
+            });
+        }
+    };
+
+    let kubernetes = document.get("apiVersion").is_some() && document.get("kind").is_some();
+    if kubernetes {
+        walk(document, &mut vec![], &mut |path, value| {
+            let (Some(key), Value::String(value)) = (path.last(), value) else {
+                return;
+            };
+            if let Some((_, allowed)) = KUBERNETES_ENUMS.iter().find(|(k, _)| k == key) {
+                check(path, value, allowed.to_vec());
+            }
+        });
+    }
+    if let Some(root) = context.schema {
+        walk_schema(
+            root,
+            root,
+            document,
+            &mut vec![],
+            &mut |path, value, schema| {
+                let (Value::String(value), Some(allowed)) = (value, schema["enum"].as_array())
+                else {
+                    return;
+                };
+                // Keys are plain strings; list elements can't be rewritten
+                if path.last().is_some_and(|k| k.parse::<usize>().is_err()) {
+                    check(
+                        path,
+                        value,
+                        allowed.iter().filter_map(Value::as_str).collect(),
+                    );
+                }
+            },
+        );
+    }
+    findings.dedup_by(|a, b| a.at == b.at);
+    findings
+}
+
+fn missing_defaults(document: &Value, context: &Context) -> Vec<Finding> {
+    let Some(root) = context.schema else {
+        return vec![];
+    };
+    let mut findings = Vec::new();
+    walk_schema(
+        root,
+        root,
+        document,
+        &mut vec![],
+        &mut |path, value, schema| {
+            let (Value::Object(map), Some(required)) = (value, schema["required"].as_array())
+            else {
+                return;
+            };
+            for name in required.iter().filter_map(Value::as_str) {
+                let property = &schema["properties"][name];
+                let Some(default) = resolve(root, property).get("default") else {
+                    continue;
+                };
+                if map.contains_key(name) {
+                    continue;
+                }
+                let Ok(value) = serde_yaml::to_value(default) else {
+                    continue;
+                };
+                let mut at = path.to_vec();
+                at.push(name.to_string());
+                findings.push(Finding {
+                    message: format!(
+                        "required '{}' is missing (schema default: {})",
+                        name, default
+                    ),
+                    at: At::Key(path.to_vec()),
+                    change: Some(Change::Insert { path: at, value }),
+                });
+            }
+        },
+    );
+    findings
+}
+
+/// Follow a local `$ref`
+fn resolve<'a>(root: &'a Value, mut schema: &'a Value) -> &'a Value {
+    // Bounded, in case of a reference cycle
+    for _ in 0..16 {
+        let Some(target) = schema["$ref"].as_str().and_then(|r| r.strip_prefix('#')) else {
+            break;
+        };
+        match root.pointer(target) {
+            Some(target) => schema = target,
+            None => break,
+        }
+    }
+    schema
+}
+
+/// Like [`walk`], with the schema of each value
+fn walk_schema<'a>(
+    root: &'a Value,
+    schema: &'a Value,
+    value: &'a Value,
+    path: &mut Vec<String>,
+    visit: &mut dyn FnMut(&[String], &'a Value, &'a Value),
+) {
+    let schema = resolve(root, schema);
+    visit(path, value, schema);
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map {
+                let child_schema = match &schema["properties"][key] {
+                    Value::Null => &schema["additionalProperties"],
+                    property => property,
+                };
+                if child_schema.is_object() {
+                    path.push(key.clone());
+                    walk_schema(root, child_schema, child, path, visit);
+                    path.pop();
+                }
+            }
+        }
+        Value::Array(items) if schema["items"].is_object() => {
+            for (i, child) in items.iter().enumerate() {
+                path.push(i.to_string());
+                walk_schema(root, &schema["items"], child, path, visit);
+                path.pop();
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn findings(
+        id: &str,
+        document: &Value,
+        options: Value,
+        schema: Option<&Value>,
+    ) -> Vec<Finding> {
+        let Check::Data(check) = rule(id).unwrap().check else {
+            panic!("{} is not a data rule", id);
+        };
+        let options: Options = serde_json::from_value(options).unwrap();
+        rule(id).unwrap().validate(&options).unwrap();
+        let context = Context {
+            file: Path::new("kustomization.yaml"),
+            options: &options,
+            schema,
+        };
+        check(document, &context)
+    }
+
+    fn data(id: &str, document: Value, options: Value) -> Vec<String> {
+        findings(id, &document, options, None)
+            .into_iter()
+            .map(|f| f.message)
+            .collect()
+    }
+
+    #[test]
+    fn test_text_rules() {
+        let content = "a: 1  \nb: |\n  text\t\n\n\n";
+        assert_eq!(trailing_whitespace(content).len(), 2);
+        assert_eq!(fix_trailing_whitespace(content), "a: 1\nb: |\n  text\n\n\n");
+        assert_eq!(
+            final_newline(content)[0].message,
+            "file ends with blank lines"
+        );
+        assert_eq!(fix_final_newline("a: 1"), "a: 1\n");
+        assert!(final_newline("a: 1\n").is_empty());
+    }
+
+    #[test]
+    fn test_data_rules() {
+        let pod = json!({"spec": {"containers": [
+            {"name": "web", "image": "nginx", "resources": {"limits": {"memory": "1Gi"}}},
+            {"name": "proxy", "image": "envoy:latest"},
+            {"name": "app", "image": "registry:5000/app@sha256:abc"},
+            {"name": "job", "image": "registry:5000/job:1.2"},
+        ]}});
+        assert_eq!(
+            data("no-latest-tag", pod.clone(), json!({})),
+            vec![
+                "image 'nginx' has no tag, so it pulls 'latest'",
+                "image 'envoy:latest' uses the 'latest' tag",
+            ]
+        );
+        assert_eq!(
+            data("resource-limits", pod, json!({"require": ["cpu"]})),
+            vec![
+                "container 'web' has no cpu limit",
+                "container 'proxy' sets no resource limits",
+                "container 'app' sets no resource limits",
+                "container 'job' sets no resource limits",
+            ]
+        );
+
+        let config = json!({"max_replicas": 1, "minReplicas": 2, "app.kubernetes.io/name": "x"});
+        assert_eq!(
+            data("key-case", config, json!({"style": "snake_case"})),
+            vec!["key 'minReplicas' is not snake_case"]
+        );
+        let deep = json!({"a": {"b": {"c": {"d": 1}}, "e": [[1]]}});
+        assert_eq!(data("max-depth", deep, json!({"max": 2})).len(), 2);
+
+        let options: Options = serde_json::from_value(json!({"max": 0})).unwrap();
+        assert!(rule("max-depth").unwrap().validate(&options).is_err());
+    }
+
+    #[test]
+    fn test_fixable_rules() {
+        let path = |dotted: &str| dotted.split('.').map(String::from).collect::<Vec<_>>();
+        let document = json!({
+            "bases": ["../base"],
+            "spec": {"serviceAccount": "web", "serviceAccountName": "web"},
+        });
+        let found = findings(
+            "deprecated-keys",
+            &document,
+            json!({"replacements": {"spec.serviceAccountName": "accountName"}}),
+            None,
+        );
+        assert_eq!(found.len(), 3);
+        assert_eq!(
+            found[0].change,
+            Some(Change::Rename {
+                path: path("bases"),
+                to: "resources".into()
+            })
+        );
+        assert_eq!(found[1].change, None);
+
+        let schema = json!({
+            "type": "object",
+            "required": ["tier", "replicas"],
+            "properties": {
+                "tier": {"$ref": "#/definitions/tier"},
+                "replicas": {"type": "integer", "default": 1},
+            },
+            "definitions": {"tier": {"enum": ["Frontend", "Backend"], "default": "Backend"}},
+        });
+        let config = json!({
+            "tier": "frontend",
+            "apiVersion": "v1",
+            "kind": "Pod",
+            "spec": {"containers": [{"imagePullPolicy": "ifnotpresent"}]},
+        });
+        let changes: Vec<Option<Change>> = findings("enum-case", &config, json!({}), Some(&schema))
+            .into_iter()
+            .map(|f| f.change)
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                Some(Change::Set {
+                    path: path("spec.containers.0.imagePullPolicy"),
+                    value: "IfNotPresent".into(),
+                }),
+                Some(Change::Set {
+                    path: path("tier"),
+                    value: "Frontend".into(),
+                }),
+            ]
+        );
+        let missing = findings("missing-defaults", &config, json!({}), Some(&schema));
+        assert_eq!(
+            missing[0].change,
+            Some(Change::Insert {
+                path: path("replicas"),
+                value: 1.into(),
+            })
+        );
+        assert_eq!(missing.len(), 1);
+    }
+}
@@ -6,23 +6,53 @@
 //! Intelligently orchestrate CUE, Nickel, and configuration validation workflows.
 
 use clap::Parser;
-use miette::Result;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
-
+use conflow::cli::run::RunOptions;
 use conflow::cli::{Cli, Commands};
+use conflow::errors::{self, ErrorCode, ExitCode};
+use conflow::i18n::{self, Locale};
+use conflow::pipeline::RedactConfig;
+use conflow::report;
+use conflow::utils::logging::{self, LogFormat};
+use conflow::utils::offline;
+use conflow::utils::redact::Redactor;
+use miette::Result;
+use std::collections::HashMap;
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() -> std::process::ExitCode {
+    let cli = Cli::parse();
+
+    cli.color.apply();
+    cli.annotations.apply();
+    i18n::set_locale(cli.lang.unwrap_or_else(Locale::detect));
+    report::set_stable(cli.stable);
+    offline::set_offline(cli.offline);
+
     // Initialize tracing
-    tracing_subscriber::registry()
-        .with(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "conflow=info".into()),
-        )
-        .with(tracing_subscriber::fmt::layer().with_target(false))
-        .init();
+    logging::init(cli.verbose, cli.log_format);
+    let log_format = cli.log_format;
 
-    let cli = Cli::parse();
+    match dispatch(cli).await {
+        Ok(()) => ExitCode::Success.into(),
+        Err(report) => {
+            let exit = ExitCode::of_report(&report);
+            match log_format {
+                LogFormat::Json => eprintln!("{}", errors::report_json(&report, exit)),
+                LogFormat::Pretty => {
+                    eprintln!("Error: {:?}", report);
+                    let code = report.code().map(|c| c.to_string());
+                    if let Some(error) = code.as_deref().and_then(ErrorCode::find) {
+                        eprintln!("Run 'conflow explain {}' for details.", error.code);
+                    }
+                }
+            }
+            exit.into()
+        }
+    }
+}
+
+async fn dispatch(cli: Cli) -> Result<()> {
+    let verbose = cli.verbose > 0;
 
     // Change to specified directory if provided
     if let Some(ref dir) = cli.directory {
@@ -30,31 +60,268 @@ async fn main() -> Result<()> {
             miette::miette!("Failed to change to directory '{}': {}", dir.display(), e)
         })?;
     }
+    if let Ok(cwd) = std::env::current_dir() {
+        let config = RedactConfig::load(&cwd);
+        report::set_redactor(Redactor::new(&config, &HashMap::new()));
+    }
 
     // Dispatch to command handlers
     match cli.command {
-        Commands::Init { name, template } => {
-            conflow::cli::init::run(name, template, cli.verbose).await
-        }
-        Commands::Analyze { files, format } => {
-            conflow::cli::analyze::run(files, format, cli.verbose).await
+        Commands::Init { name, template } => conflow::cli::init::run(name, template, verbose).await,
+        Commands::New {
+            name,
+            template,
+            tier,
+            ci,
+            no_ci,
+            set,
+        } => {
+            let ci = (!no_ci).then_some(ci);
+            conflow::cli::new::run(name, template, tier, ci, set, verbose).await
         }
+        Commands::Analyze {
+            files,
+            format,
+            emit,
+            emit_dir,
+            jobs,
+        } => conflow::cli::analyze::run(files, format, emit, emit_dir, jobs, verbose).await,
+        Commands::Recommend {
+            files,
+            format,
+            output,
+        } => conflow::cli::recommend::run(files, format, output).await,
+        Commands::Learn {
+            lesson,
+            dir,
+            check,
+            reset,
+        } => conflow::cli::learn::run(lesson, dir, check, reset).await,
         Commands::Run {
             pipeline,
             stage,
             no_cache,
             dry_run,
-        } => conflow::cli::run::run(pipeline, stage, no_cache, dry_run, cli.verbose).await,
-        Commands::Watch { pipeline, debounce } => {
-            conflow::cli::watch::run(pipeline, debounce, cli.verbose).await
+            since,
+            resume,
+            profile,
+            tui,
+            quiet,
+            report,
+            report_format,
+            group_by,
+            sandbox,
+            yes,
+            approve,
+            pr_comment,
+            no_lock,
+            lock_timeout,
+            fail_on,
+            from_bundle,
+            coordinator,
+            api_key,
+            runner_key,
+            agent_ca,
+            send_secrets,
+        } => {
+            let opts = RunOptions {
+                stages: stage,
+                no_cache,
+                dry_run,
+                since,
+                resume,
+                profile,
+                tui,
+                quiet,
+                report,
+                report_format,
+                group_by,
+                sandbox,
+                yes,
+                approve,
+                pr_comment,
+                no_lock,
+                lock_timeout: std::time::Duration::from_secs(lock_timeout),
+                fail_on,
+                from_bundle,
+                coordinator,
+                api_key,
+                runner_key,
+                agent_ca,
+                send_secrets,
+            };
+            conflow::cli::run::run(pipeline, opts, verbose).await
+        }
+        Commands::Watch {
+            pipeline,
+            debounce,
+            metrics_addr,
+        } => conflow::cli::watch::run(pipeline, debounce, metrics_addr, verbose).await,
+        Commands::Serve {
+            addr,
+            grpc,
+            api_keys,
+            no_auth,
+            max_body,
+            projects,
+            schedule,
+            agent_keys,
+        } => {
+            let opts = conflow::cli::serve::ServeOptions {
+                addr,
+                grpc,
+                api_keys,
+                no_auth,
+                max_body,
+                projects,
+                default_schedule: schedule,
+                agent_keys,
+            };
+            conflow::cli::serve::run(opts).await
+        }
+        Commands::Agent {
+            listen,
+            advertise,
+            tls_cert,
+            tls_key,
+            labels,
+            coordinator,
+            agent_key,
+            runner_keys,
+            max_upload,
+        } => {
+            let opts = conflow::cli::agent::AgentOptions {
+                listen,
+                advertise,
+                tls_cert,
+                tls_key,
+                labels,
+                coordinator,
+                agent_key,
+                runner_keys,
+                max_upload,
+            };
+            conflow::cli::agent::run(opts).await
+        }
+        Commands::Validate { pipeline, fail_on } => {
+            conflow::cli::validate::run(pipeline, fail_on, verbose).await
+        }
+        Commands::Lint {
+            files,
+            fix,
+            dry_run,
+            list_rules,
+            pipeline,
+            fail_on,
+            format,
+        } => {
+            if list_rules {
+                conflow::cli::lint::list_rules(format)
+            } else {
+                conflow::cli::lint::run(files, fix, dry_run, pipeline, fail_on, format, verbose)
+                    .await
+            }
         }
-        Commands::Validate { pipeline } => {
-            conflow::cli::validate::run(pipeline, cli.verbose).await
+        Commands::Deprecations {
+            files,
+            target,
+            pipeline,
+            fail_on,
+            format,
+        } => {
+            conflow::cli::deprecations::run(files, target, pipeline, fail_on, format, verbose).await
+        }
+        Commands::Render {
+            files,
+            values,
+            set,
+            release,
+            namespace,
+            kube_version,
+            output,
+        } => {
+            conflow::cli::render::run(files, values, set, release, namespace, kube_version, output)
+                .await
+        }
+        Commands::Migrate {
+            pipeline,
+            dry_run,
+            yes,
+        } => conflow::cli::migrate::run(pipeline, dry_run, yes, verbose).await,
+        Commands::Unused {
+            files,
+            schema,
+            pipeline,
+            format,
+        } => conflow::cli::unused::run(files, schema, pipeline, format, verbose).await,
+        Commands::Impact {
+            file,
+            pipeline,
+            format,
+        } => conflow::cli::impact::run(file, pipeline, format, verbose).await,
+        Commands::Inventory {
+            patterns,
+            pipeline,
+            output,
+            format,
+        } => conflow::cli::inventory::run(patterns, pipeline, output, format, verbose).await,
+        Commands::Scan {
+            patterns,
+            pipeline,
+            fail_on,
+            format,
+        } => conflow::cli::scan::run(patterns, pipeline, fail_on, format, verbose).await,
+        Commands::Why {
+            path,
+            stage,
+            files,
+            merge,
+            pipeline,
+            format,
+        } => conflow::cli::why::run(path, stage, files, merge, pipeline, format, verbose).await,
+        Commands::Cache { action } => conflow::cli::cache::run(action, verbose).await,
+        Commands::Bundle { action } => conflow::cli::bundle::run(action).await,
+        Commands::Snapshot { action } => conflow::cli::snapshot::run(action).await,
+        Commands::Release { action } => conflow::cli::release::run(action).await,
+        Commands::Version { action } => conflow::cli::version::run(action).await,
+        Commands::Explain {
+            code,
+            show_fix,
+            format,
+        } => conflow::cli::explain::run(code, show_fix, format, verbose).await,
+        Commands::Graph {
+            pipeline,
+            format,
+            configs,
+            affected,
+        } => {
+            if configs {
+                conflow::cli::graph::run_configs(format, affected, verbose).await
+            } else {
+                conflow::cli::graph::run(pipeline, format, verbose).await
+            }
         }
-        Commands::Cache { action } => conflow::cli::cache::run(action, cli.verbose).await,
-        Commands::Graph { pipeline, format } => {
-            conflow::cli::graph::run(pipeline, format, cli.verbose).await
+        Commands::Gitops { action } => conflow::cli::gitops::run(action, verbose).await,
+        Commands::Rsr { action } => conflow::cli::rsr::run(action, verbose).await,
+        Commands::Audit {
+            id,
+            path,
+            actor,
+            since,
+            verify,
+            format,
+        } => conflow::cli::audit::run(id, path, actor, since, verify, format).await,
+        Commands::Undo { id, last: _, force } => conflow::cli::undo::run(id, force).await,
+        Commands::Runs { action } => conflow::cli::runs::run(action, verbose).await,
+        Commands::Workspace { action } => conflow::cli::workspace::run(action, verbose).await,
+        Commands::Template { action } => conflow::cli::template::run(action, verbose).await,
+        Commands::Plugin { action } => conflow::cli::plugin::run(action, verbose).await,
+        Commands::Toolchain { action } => conflow::cli::toolchain::run(action, verbose).await,
+        Commands::Schema { action } => conflow::cli::schema::run(action, verbose).await,
+        Commands::Yaml { action } => conflow::cli::yaml::run(action, verbose).await,
+        Commands::Completion { shell } => conflow::cli::completion::run(shell, verbose).await,
+        Commands::Complete { kind, pipeline } => {
+            conflow::cli::completion::values(kind, pipeline).await
         }
-        Commands::Rsr { action } => conflow::cli::rsr::run(action, cli.verbose).await,
     }
 }
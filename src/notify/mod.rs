@@ -0,0 +1,726 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Run notifications
+//!
+//! Posts run results to Slack, Microsoft Teams, or generic webhooks when a
+//! run fails, regresses, recovers, or changes RSR compliance level. The
+//! outcome of the previous run is kept in `.conflow/notify-state.json` so
+//! transitions can be detected. A channel listing CODEOWNERS owners only
+//! hears about the failures those owners own.
+
+pub mod pr_comment;
+
+pub use pr_comment::{PrCommenter, PrReport, PrTarget, Provider};
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::errors::ConflowError;
+use crate::pipeline::{NotificationConfig, NotificationKind, NotifyEvent, PipelineResult};
+use crate::rsr::badges::shields_io_url;
+use crate::rsr::{ComplianceLevel, ComplianceReport};
+use crate::utils::codeowners::UNOWNED;
+use crate::utils::offline;
+
+static ENV_REF: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\$\{([A-Za-z_][A-Za-z0-9_]*)\}").unwrap());
+
+static PLACEHOLDER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\{\{\s*([a-z_]+)\s*\}\}").unwrap());
+
+/// What a [`RunSummary`] reports on
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SummaryKind {
+    /// A pipeline run
+    #[default]
+    Run,
+    /// A scheduled compliance check, which runs no stages
+    Compliance,
+}
+
+/// Outcome of a run, as reported in notifications
+#[derive(Debug, Clone)]
+pub struct RunSummary {
+    pub kind: SummaryKind,
+    /// Pipeline, or project for a compliance check
+    pub pipeline: String,
+    pub success: bool,
+    pub duration: Duration,
+    /// Stages that executed and passed
+    pub passed: usize,
+    /// Stages reused from cache
+    pub cached: usize,
+    pub failed_stages: Vec<String>,
+    /// RSR compliance level and score, when checked
+    pub compliance: Option<(ComplianceLevel, f64)>,
+    /// RSR badge image URL
+    pub badge: Option<String>,
+    /// Unmet RSR requirements that aren't waived
+    pub unmet: Vec<String>,
+    /// Requirements met on the previous compliance check but not now
+    pub regressed: Vec<String>,
+    /// Failed stages and unmet requirements by CODEOWNERS owner
+    pub owners: BTreeMap<String, Vec<String>>,
+}
+
+impl RunSummary {
+    /// Summarize a pipeline run and optional compliance report
+    pub fn new(
+        pipeline: &str,
+        result: &PipelineResult,
+        compliance: Option<&ComplianceReport>,
+    ) -> Self {
+        let mut failed_stages: Vec<String> = result
+            .results
+            .iter()
+            .filter(|(_, r)| !r.success)
+            .map(|(name, _)| name.clone())
+            .collect();
+        failed_stages.sort();
+        let (unmet, owners) = gaps(compliance);
+
+        Self {
+            kind: SummaryKind::Run,
+            pipeline: pipeline.to_string(),
+            success: result.success,
+            duration: result.duration,
+            passed: result
+                .results
+                .values()
+                .filter(|r| r.success && !r.cache_hit)
+                .count(),
+            cached: result.results.values().filter(|r| r.cache_hit).count(),
+            failed_stages,
+            compliance: compliance.map(|r| (r.level, r.score)),
+            badge: compliance.map(shields_io_url),
+            unmet,
+            regressed: Vec::new(),
+            owners,
+        }
+    }
+
+    /// Summarize a scheduled compliance check of a project
+    ///
+    /// The check fails when requirements met on the previous check, listed
+    /// in `regressed`, aren't met now.
+    pub fn compliance(
+        project: &str,
+        report: &ComplianceReport,
+        duration: Duration,
+        regressed: Vec<String>,
+    ) -> Self {
+        let (unmet, owners) = gaps(Some(report));
+        Self {
+            kind: SummaryKind::Compliance,
+            pipeline: project.to_string(),
+            success: regressed.is_empty(),
+            duration,
+            passed: 0,
+            cached: 0,
+            failed_stages: Vec::new(),
+            compliance: Some((report.level, report.score)),
+            badge: Some(shields_io_url(report)),
+            unmet,
+            regressed,
+            owners,
+        }
+    }
+
+    /// Attribute failed stages to their owners, keyed by stage
+    pub fn with_stage_owners(mut self, stages: &BTreeMap<String, Vec<String>>) -> Self {
+        for stage in &self.failed_stages {
+            let owners = stages.get(stage).map(Vec::as_slice).unwrap_or_default();
+            for owner in owners_or_unowned(owners) {
+                self.owners.entry(owner).or_default().push(stage.clone());
+            }
+        }
+        self
+    }
+
+    /// The part of the run some owners own
+    ///
+    /// Keeps only their failed stages and unmet requirements; a failed run
+    /// with none of theirs counts as a success for them.
+    pub fn for_owners(&self, owners: &[String]) -> Self {
+        let owned: BTreeMap<String, Vec<String>> = self
+            .owners
+            .iter()
+            .filter(|(owner, _)| owners.contains(owner))
+            .map(|(owner, findings)| (owner.clone(), findings.clone()))
+            .collect();
+        let is_owned = |finding: &String| owned.values().any(|f| f.contains(finding));
+
+        let mut scoped = self.clone();
+        scoped.failed_stages.retain(is_owned);
+        scoped.unmet.retain(is_owned);
+        scoped.regressed.retain(is_owned);
+        scoped.success =
+            self.success || (scoped.failed_stages.is_empty() && scoped.regressed.is_empty());
+        scoped.owners = owned;
+        scoped
+    }
+
+    /// One-line summary
+    pub fn headline(&self) -> String {
+        let (subject, status) = match (self.kind, self.success) {
+            (SummaryKind::Run, true) => ("Pipeline", "succeeded"),
+            (SummaryKind::Run, false) => ("Pipeline", "failed"),
+            (SummaryKind::Compliance, true) => ("Compliance of", "checked"),
+            (SummaryKind::Compliance, false) => ("Compliance of", "regressed"),
+        };
+        format!(
+            "{} '{}' {} in {:.2}s",
+            subject,
+            self.pipeline,
+            status,
+            self.duration.as_secs_f64()
+        )
+    }
+
+    /// Stage counts and failures
+    pub fn details(&self) -> String {
+        let mut lines = Vec::new();
+        if self.kind == SummaryKind::Run {
+            let mut counts = format!(
+                "{} passed, {} cached, {} failed",
+                self.passed,
+                self.cached,
+                self.failed_stages.len()
+            );
+            if !self.failed_stages.is_empty() {
+                counts.push_str(&format!(" ({})", self.failed_stages.join(", ")));
+            }
+            lines.push(counts);
+        }
+        if !self.regressed.is_empty() {
+            lines.push(format!("Regressed: {}", self.regressed.join(", ")));
+        }
+        if let Some((level, score)) = self.compliance {
+            lines.push(format!(
+                "RSR compliance: {} ({:.0}%)",
+                level_name(level),
+                score * 100.0
+            ));
+        }
+        if !self.unmet.is_empty() {
+            lines.push(format!("Unmet requirements: {}", self.unmet.join(", ")));
+        }
+        for (owner, findings) in &self.owners {
+            lines.push(format!("{}: {}", owner, findings.join(", ")));
+        }
+        lines.join("\n")
+    }
+}
+
+/// Outcome of the previous notified run
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NotifyState {
+    pub success: Option<bool>,
+    pub compliance: Option<String>,
+}
+
+impl NotifyState {
+    fn path(working_dir: &Path) -> PathBuf {
+        working_dir.join(".conflow").join("notify-state.json")
+    }
+
+    /// Load the previous state (empty if there is none)
+    pub fn load(working_dir: &Path) -> Self {
+        std::fs::read_to_string(Self::path(working_dir))
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    /// Record a run as the previous state
+    pub fn save(working_dir: &Path, summary: &RunSummary) -> Result<(), ConflowError> {
+        let state = Self {
+            success: Some(summary.success),
+            compliance: summary
+                .compliance
+                .map(|(level, _)| level_name(level).to_string()),
+        };
+        let path = Self::path(working_dir);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| ConflowError::FileWriteError {
+                path: parent.to_path_buf(),
+                error: e.to_string(),
+            })?;
+        }
+
+        let content = serde_json::to_string_pretty(&state)?;
+        std::fs::write(&path, content).map_err(|e| ConflowError::FileWriteError {
+            path,
+            error: e.to_string(),
+        })
+    }
+
+    /// Events a run triggers relative to this state
+    pub fn events(&self, summary: &RunSummary) -> Vec<NotifyEvent> {
+        let mut events = vec![NotifyEvent::Always];
+
+        if !summary.success {
+            events.push(NotifyEvent::Failure);
+        }
+        match (self.success, summary.success) {
+            (Some(true), false) => events.push(NotifyEvent::Regression),
+            (Some(false), true) => events.push(NotifyEvent::Recovery),
+            _ => {}
+        }
+
+        let level = summary.compliance.map(|(level, _)| level_name(level));
+        if let (Some(previous), Some(level)) = (&self.compliance, level) {
+            if previous != level {
+                events.push(NotifyEvent::ComplianceChange);
+            }
+        }
+
+        events
+    }
+}
+
+/// Sends notifications to configured channels
+pub struct Notifier {
+    channels: Vec<NotificationConfig>,
+    client: reqwest::Client,
+}
+
+impl Notifier {
+    /// Create a notifier for the given channels
+    pub fn new(channels: Vec<NotificationConfig>) -> Self {
+        Self {
+            channels,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Notify every channel subscribed to one of the events
+    ///
+    /// A channel with `owners` gets the part of the run they own, and no
+    /// failure or regression notice when none of the failures are theirs.
+    /// Returns the number of notifications sent and any delivery errors.
+    pub async fn notify(
+        &self,
+        summary: &RunSummary,
+        events: &[NotifyEvent],
+    ) -> (usize, Vec<ConflowError>) {
+        let mut sent = 0;
+        let mut errors = Vec::new();
+
+        for channel in &self.channels {
+            let summary = match channel.owners.is_empty() {
+                true => summary.clone(),
+                false => summary.for_owners(&channel.owners),
+            };
+            let matched: Vec<NotifyEvent> = channel
+                .on
+                .iter()
+                .copied()
+                .filter(|e| events.contains(e))
+                .filter(|e| {
+                    let failure = matches!(e, NotifyEvent::Failure | NotifyEvent::Regression);
+                    !failure || !summary.success
+                })
+                .collect();
+            if matched.is_empty() {
+                continue;
+            }
+
+            match self.send(channel, &summary, &matched).await {
+                Ok(()) => sent += 1,
+                Err(e) => errors.push(e),
+            }
+        }
+
+        (sent, errors)
+    }
+
+    async fn send(
+        &self,
+        channel: &NotificationConfig,
+        summary: &RunSummary,
+        events: &[NotifyEvent],
+    ) -> Result<(), ConflowError> {
+        let url = expand_env(&channel.url)?;
+        offline::guard(format!("notification to {}", url))?;
+        let body = payload(channel, summary, events);
+
+        let mut request = self
+            .client
+            .post(&url)
+            .header("Content-Type", "application/json")
+            .body(body);
+        for (key, value) in &channel.headers {
+            request = request.header(key, expand_env(value)?);
+        }
+
+        // Only the host is shown, since webhook URLs carry credentials
+        let host = reqwest::Url::parse(&url)
+            .ok()
+            .and_then(|u| u.host_str().map(String::from))
+            .unwrap_or_default();
+
+        let response = request
+            .timeout(Duration::from_secs(10))
+            .send()
+            .await
+            .map_err(|e| ConflowError::Notification {
+                message: format!("{:?} webhook ({}): {}", channel.kind, host, e.without_url()),
+            })?;
+
+        if !response.status().is_success() {
+            return Err(ConflowError::Notification {
+                message: format!(
+                    "{:?} webhook ({}) returned {}",
+                    channel.kind,
+                    host,
+                    response.status()
+                ),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Values available to templates as `{{name}}`
+pub fn template_values(
+    summary: &RunSummary,
+    events: &[NotifyEvent],
+) -> BTreeMap<&'static str, String> {
+    let events: Vec<String> = events.iter().map(ToString::to_string).collect();
+    let (compliance, score) = match summary.compliance {
+        Some((level, score)) => (
+            level_name(level).to_string(),
+            format!("{:.0}", score * 100.0),
+        ),
+        None => (String::new(), String::new()),
+    };
+
+    BTreeMap::from([
+        ("pipeline", summary.pipeline.clone()),
+        (
+            "status",
+            if summary.success {
+                "success"
+            } else {
+                "failure"
+            }
+            .to_string(),
+        ),
+        ("events", events.join(", ")),
+        ("headline", summary.headline()),
+        ("summary", summary.details()),
+        ("duration", format!("{:.2}", summary.duration.as_secs_f64())),
+        ("passed", summary.passed.to_string()),
+        ("cached", summary.cached.to_string()),
+        ("failed", summary.failed_stages.len().to_string()),
+        ("failed_stages", summary.failed_stages.join(", ")),
+        ("unmet", summary.unmet.join(", ")),
+        ("regressed", summary.regressed.join(", ")),
+        (
+            "owners",
+            summary.owners.keys().cloned().collect::<Vec<_>>().join(", "),
+        ),
+        ("compliance", compliance),
+        ("score", score),
+        ("badge", summary.badge.clone().unwrap_or_default()),
+    ])
+}
+
+/// Request body for a channel
+pub fn payload(
+    channel: &NotificationConfig,
+    summary: &RunSummary,
+    events: &[NotifyEvent],
+) -> String {
+    let values = template_values(summary, events);
+
+    if let Some(ref template) = channel.template {
+        return render_template(template, &values);
+    }
+
+    let color = if summary.success { "2eb886" } else { "d00000" };
+    let badge = summary
+        .badge
+        .as_ref()
+        .map(|url| format!("\n![RSR compliance]({})", url))
+        .unwrap_or_default();
+
+    let body = match channel.kind {
+        NotificationKind::Slack => {
+            let mut attachment = json!({
+                "color": format!("#{}", color),
+                "text": summary.details(),
+                "footer": format!("conflow · {}", values["events"]),
+            });
+            if let Some(ref url) = summary.badge {
+                attachment["image_url"] = json!(url);
+            }
+            json!({ "text": summary.headline(), "attachments": [attachment] })
+        }
+        NotificationKind::Teams => json!({
+            "@type": "MessageCard",
+            "@context": "https://schema.org/extensions",
+            "themeColor": color,
+            "summary": summary.headline(),
+            "title": summary.headline(),
+            "text": format!("{}{}", summary.details().replace('\n', "\n\n"), badge),
+        }),
+        NotificationKind::Webhook => {
+            let fields: serde_json::Map<String, Value> = values
+                .iter()
+                .map(|(k, v)| (k.to_string(), json!(v)))
+                .collect();
+            Value::Object(fields)
+        }
+    };
+
+    body.to_string()
+}
+
+/// Substitute `{{name}}` placeholders, JSON-escaping the values
+///
+/// Unknown placeholders are left as written.
+pub fn render_template(template: &str, values: &BTreeMap<&'static str, String>) -> String {
+    PLACEHOLDER
+        .replace_all(template, |caps: &regex::Captures| {
+            match values.get(&caps[1]) {
+                Some(value) => {
+                    let quoted = Value::String(value.clone()).to_string();
+                    quoted[1..quoted.len() - 1].to_string()
+                }
+                None => caps[0].to_string(),
+            }
+        })
+        .to_string()
+}
+
+/// Expand `${VAR}` references from the environment
+pub(crate) fn expand_env(value: &str) -> Result<String, ConflowError> {
+    expand_env_with(value, |var| std::env::var(var).ok())
+}
+
+fn expand_env_with(
+    value: &str,
+    env: impl Fn(&str) -> Option<String>,
+) -> Result<String, ConflowError> {
+    let mut missing = None;
+    let expanded = ENV_REF.replace_all(value, |caps: &regex::Captures| {
+        env(&caps[1]).unwrap_or_else(|| {
+            missing.get_or_insert_with(|| caps[1].to_string());
+            String::new()
+        })
+    });
+
+    match missing {
+        Some(var) => Err(ConflowError::Notification {
+            message: format!("environment variable '{}' is not set", var),
+        }),
+        None => Ok(expanded.to_string()),
+    }
+}
+
+/// Unmet, unwaived requirements of a report, and the ones each owner owns
+fn gaps(compliance: Option<&ComplianceReport>) -> (Vec<String>, BTreeMap<String, Vec<String>>) {
+    let gaps: Vec<_> = compliance
+        .map(|r| {
+            r.requirements
+                .iter()
+                .filter(|r| !r.met && !r.waived)
+                .collect()
+        })
+        .unwrap_or_default();
+    let mut owners: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for gap in &gaps {
+        for owner in owners_or_unowned(&gap.owners) {
+            owners
+                .entry(owner)
+                .or_default()
+                .push(gap.requirement_id.clone());
+        }
+    }
+    let unmet = gaps.iter().map(|r| r.requirement_id.clone()).collect();
+    (unmet, owners)
+}
+
+/// Owners, or [`UNOWNED`] when there are none
+fn owners_or_unowned(owners: &[String]) -> Vec<String> {
+    match owners.is_empty() {
+        true => vec![UNOWNED.to_string()],
+        false => owners.to_vec(),
+    }
+}
+
+fn level_name(level: ComplianceLevel) -> &'static str {
+    match level {
+        ComplianceLevel::NonCompliant => "non-compliant",
+        ComplianceLevel::Basic => "basic",
+        ComplianceLevel::Good => "good",
+        ComplianceLevel::Excellent => "excellent",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn summary(success: bool, level: ComplianceLevel) -> RunSummary {
+        RunSummary {
+            kind: SummaryKind::Run,
+            pipeline: "deploy".into(),
+            success,
+            duration: Duration::from_millis(1500),
+            passed: 2,
+            cached: 1,
+            failed_stages: if success {
+                vec![]
+            } else {
+                vec!["validate".into()]
+            },
+            compliance: Some((level, 0.75)),
+            badge: Some("https://img.shields.io/badge/RSR-good".into()),
+            unmet: vec![],
+            regressed: vec![],
+            owners: BTreeMap::new(),
+        }
+    }
+
+    fn channel(kind: NotificationKind, template: Option<&str>) -> NotificationConfig {
+        NotificationConfig {
+            kind,
+            url: "https://hooks.example.com/x".into(),
+            on: vec![NotifyEvent::Failure],
+            headers: HashMap::new(),
+            template: template.map(String::from),
+            owners: vec![],
+        }
+    }
+
+    #[test]
+    fn test_events_from_previous_state() {
+        let previous = NotifyState {
+            success: Some(true),
+            compliance: Some("good".into()),
+        };
+        let events = previous.events(&summary(false, ComplianceLevel::Basic));
+
+        assert!(events.contains(&NotifyEvent::Failure));
+        assert!(events.contains(&NotifyEvent::Regression));
+        assert!(events.contains(&NotifyEvent::ComplianceChange));
+
+        let first_run = NotifyState::default().events(&summary(true, ComplianceLevel::Good));
+        assert_eq!(first_run, vec![NotifyEvent::Always]);
+
+        let failed = NotifyState {
+            success: Some(false),
+            compliance: None,
+        };
+        assert!(failed
+            .events(&summary(true, ComplianceLevel::Good))
+            .contains(&NotifyEvent::Recovery));
+    }
+
+    #[test]
+    fn test_default_payloads() {
+        let summary = summary(false, ComplianceLevel::Good);
+        let events = [NotifyEvent::Failure];
+
+        let slack: Value = serde_json::from_str(&payload(
+            &channel(NotificationKind::Slack, None),
+            &summary,
+            &events,
+        ))
+        .unwrap();
+        assert_eq!(slack["text"], "Pipeline 'deploy' failed in 1.50s");
+        assert!(slack["attachments"][0]["text"]
+            .as_str()
+            .unwrap()
+            .contains("1 failed (validate)"));
+
+        let teams: Value = serde_json::from_str(&payload(
+            &channel(NotificationKind::Teams, None),
+            &summary,
+            &events,
+        ))
+        .unwrap();
+        assert_eq!(teams["@type"], "MessageCard");
+        assert!(teams["text"].as_str().unwrap().contains("shields.io"));
+
+        let webhook: Value = serde_json::from_str(&payload(
+            &channel(NotificationKind::Webhook, None),
+            &summary,
+            &events,
+        ))
+        .unwrap();
+        assert_eq!(webhook["status"], "failure");
+        assert_eq!(webhook["compliance"], "good");
+    }
+
+    #[test]
+    fn test_render_template_escapes_values() {
+        let template = r#"{"msg": "{{headline}}", "detail": "{{summary}}", "x": "{{nope}}"}"#;
+        let summary = summary(false, ComplianceLevel::Good);
+        let body = payload(
+            &channel(NotificationKind::Webhook, Some(template)),
+            &summary,
+            &[NotifyEvent::Failure],
+        );
+
+        let value: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(value["msg"], "Pipeline 'deploy' failed in 1.50s");
+        assert!(value["detail"]
+            .as_str()
+            .unwrap()
+            .contains("\nRSR compliance: good (75%)"));
+        assert_eq!(value["x"], "{{nope}}");
+    }
+
+    #[test]
+    fn test_summary_for_owners() {
+        let mut summary = summary(false, ComplianceLevel::Good);
+        summary.failed_stages.push("kube".into());
+        summary.unmet = vec!["RSR-K8S-001".into()];
+        let stages = BTreeMap::from([
+            ("validate".to_string(), vec!["@org/schemas".to_string()]),
+            ("kube".to_string(), vec![]),
+        ]);
+        summary.owners = BTreeMap::from([(
+            "@org/platform".to_string(),
+            vec!["RSR-K8S-001".to_string()],
+        )]);
+        let summary = summary.with_stage_owners(&stages);
+        assert_eq!(summary.owners["@org/schemas"], ["validate"]);
+        assert_eq!(summary.owners[UNOWNED], ["kube"]);
+
+        let schemas = summary.for_owners(&["@org/schemas".into()]);
+        assert!(!schemas.success);
+        assert_eq!(schemas.failed_stages, ["validate"]);
+        assert!(schemas.unmet.is_empty());
+        assert!(schemas.details().ends_with("\n@org/schemas: validate"));
+
+        // A failed run with none of their failures is a success for them
+        let platform = summary.for_owners(&["@org/platform".into()]);
+        assert!(platform.success);
+        assert!(platform.failed_stages.is_empty());
+        assert_eq!(platform.unmet, ["RSR-K8S-001"]);
+    }
+
+    #[test]
+    fn test_expand_env() {
+        let env = |var: &str| (var == "HOOK").then(|| "abc".to_string());
+        assert_eq!(
+            expand_env_with("https://h/${HOOK}", env).unwrap(),
+            "https://h/abc"
+        );
+        assert!(expand_env_with("${MISSING}", env).is_err());
+    }
+}
@@ -0,0 +1,663 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Pull request comments
+//!
+//! Posts the run summary, cost estimates, the RSR compliance diff against
+//! the target branch, and remediation suggestions as a GitHub pull request or GitLab
+//! merge request comment. The comment carries a hidden marker, so later
+//! runs update it in place instead of adding new ones.
+
+use std::fmt::Write as _;
+use std::time::Duration;
+
+use serde_json::{json, Value};
+
+use crate::analyzer::{money, CostEstimate};
+use crate::errors::ConflowError;
+use crate::executors::parsers::ToolDiagnostic;
+use crate::pipeline::{Pipeline, PipelineResult};
+use crate::rsr::diff::{ComplianceHistory, HistoryEntry, RequirementChangeType};
+use crate::rsr::ComplianceReport;
+use crate::utils::offline;
+
+/// Most diagnostics listed in a comment
+const MAX_DIAGNOSTICS: usize = 25;
+
+/// Most changed resources listed per cost estimate
+const MAX_COST_RESOURCES: usize = 10;
+
+/// Comment pages searched for an existing report
+const MAX_PAGES: usize = 10;
+
+/// Code hosting provider
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Provider {
+    GitHub,
+    GitLab,
+}
+
+/// The pull request (or merge request) to comment on
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrTarget {
+    pub provider: Provider,
+    /// API base URL
+    pub api_url: String,
+    /// `owner/repo` on GitHub, project ID on GitLab
+    pub project: String,
+    /// Pull request number or merge request IID
+    pub number: u64,
+    pub token: String,
+    /// Target branch to compare against, as a git ref
+    pub base_ref: Option<String>,
+}
+
+impl PrTarget {
+    /// Detect the pull request from GitHub Actions or GitLab CI variables
+    ///
+    /// The token is read from `CONFLOW_GITHUB_TOKEN`/`GITHUB_TOKEN` or
+    /// `CONFLOW_GITLAB_TOKEN`/`GITLAB_TOKEN`.
+    pub fn from_env() -> Result<Self, ConflowError> {
+        Self::from_env_with(|key| std::env::var(key).ok().filter(|v| !v.is_empty()))
+    }
+
+    fn from_env_with(env: impl Fn(&str) -> Option<String>) -> Result<Self, ConflowError> {
+        let missing = |what: &str, help: &str| ConflowError::PrComment {
+            message: format!("{} not found", what),
+            help: Some(help.to_string()),
+        };
+
+        if let Some(project) = env("GITHUB_REPOSITORY") {
+            let number = env("GITHUB_REF")
+                .and_then(|r| {
+                    r.strip_prefix("refs/pull/")?
+                        .split('/')
+                        .next()?
+                        .parse()
+                        .ok()
+                })
+                .or_else(|| {
+                    let event = std::fs::read_to_string(env("GITHUB_EVENT_PATH")?).ok()?;
+                    let event: Value = serde_json::from_str(&event).ok()?;
+                    event["pull_request"]["number"].as_u64()
+                })
+                .ok_or_else(|| {
+                    missing(
+                        "Pull request number",
+                        "Run on a pull_request event so GITHUB_REF points at the PR",
+                    )
+                })?;
+            let token = env("CONFLOW_GITHUB_TOKEN")
+                .or_else(|| env("GITHUB_TOKEN"))
+                .ok_or_else(|| {
+                    missing(
+                        "GitHub token",
+                        "Set GITHUB_TOKEN: ${{ secrets.GITHUB_TOKEN }} with pull-requests: write",
+                    )
+                })?;
+
+            return Ok(Self {
+                provider: Provider::GitHub,
+                api_url: env("GITHUB_API_URL").unwrap_or_else(|| "https://api.github.com".into()),
+                project,
+                number,
+                token,
+                base_ref: env("GITHUB_BASE_REF").map(|b| format!("origin/{}", b)),
+            });
+        }
+
+        if let Some(project) = env("CI_PROJECT_ID") {
+            let number = env("CI_MERGE_REQUEST_IID")
+                .and_then(|n| n.parse().ok())
+                .ok_or_else(|| {
+                    missing(
+                        "Merge request IID",
+                        "Run in a merge request pipeline (rules: if: $CI_MERGE_REQUEST_IID)",
+                    )
+                })?;
+            let token = env("CONFLOW_GITLAB_TOKEN")
+                .or_else(|| env("GITLAB_TOKEN"))
+                .ok_or_else(|| {
+                    missing(
+                        "GitLab token",
+                        "Set GITLAB_TOKEN to a project access token with the api scope",
+                    )
+                })?;
+
+            return Ok(Self {
+                provider: Provider::GitLab,
+                api_url: env("CI_API_V4_URL").unwrap_or_else(|| "https://gitlab.com/api/v4".into()),
+                project,
+                number,
+                token,
+                base_ref: env("CI_MERGE_REQUEST_TARGET_BRANCH_NAME")
+                    .map(|b| format!("origin/{}", b)),
+            });
+        }
+
+        Err(missing(
+            "Pull request context",
+            "PR comments are posted from GitHub Actions or GitLab CI merge request pipelines",
+        ))
+    }
+}
+
+/// Content of a PR comment
+pub struct PrReport<'a> {
+    pub pipeline: &'a Pipeline,
+    pub result: &'a PipelineResult,
+    /// Parsed tool diagnostics, keyed by stage
+    pub diagnostics: &'a [(String, ToolDiagnostic)],
+    /// Cost estimates, keyed by stage
+    pub costs: &'a [(String, CostEstimate)],
+    /// Compliance of the PR
+    pub compliance: Option<&'a ComplianceReport>,
+    /// Target branch ref and its compliance
+    pub base: Option<(&'a str, &'a ComplianceReport)>,
+}
+
+impl PrReport<'_> {
+    /// Hidden marker identifying this pipeline's comment
+    pub fn marker(&self) -> String {
+        format!("<!-- conflow-report: {} -->", self.pipeline.name)
+    }
+
+    /// Render the comment as Markdown
+    pub fn markdown(&self) -> String {
+        let mut out = self.marker();
+        out.push('\n');
+
+        let (icon, status) = if self.result.success {
+            ("✅", "passed")
+        } else {
+            ("❌", "failed")
+        };
+        let _ = writeln!(
+            out,
+            "## {} conflow: `{}` {} in {:.2}s\n",
+            icon,
+            self.pipeline.name,
+            status,
+            self.result.duration.as_secs_f64()
+        );
+
+        self.write_stages(&mut out);
+        self.write_costs(&mut out);
+        self.write_diagnostics(&mut out);
+        self.write_compliance(&mut out);
+        self.write_remediation(&mut out);
+
+        out
+    }
+
+    fn write_stages(&self, out: &mut String) {
+        out.push_str("| Stage | Tool | Result | Time |\n|---|---|---|---|\n");
+        for stage in &self.pipeline.stages {
+            let (result, time) = match self.result.results.get(&stage.name) {
+                Some(r) if r.cache_hit => ("♻️ cached".to_string(), String::new()),
+                Some(r) if r.success => (
+                    "✅ passed".to_string(),
+                    format!("{:.2}s", r.duration.as_secs_f64()),
+                ),
+                Some(r) => (
+                    format!("❌ failed (exit {})", r.exit_code),
+                    format!("{:.2}s", r.duration.as_secs_f64()),
+                ),
+                None => ("○ not run".to_string(), String::new()),
+            };
+            let _ = writeln!(
+                out,
+                "| {} | {} | {} | {} |",
+                stage.name,
+                stage.tool_name(),
+                result,
+                time
+            );
+        }
+        out.push('\n');
+    }
+
+    fn write_costs(&self, out: &mut String) {
+        for (stage, estimate) in self.costs {
+            let icon = match estimate.delta() {
+                Some(delta) if delta >= 0.005 => "📈",
+                Some(delta) if delta <= -0.005 => "📉",
+                _ => "💰",
+            };
+            let _ = writeln!(
+                out,
+                "{} **This change {}** _({})_\n",
+                icon,
+                estimate.summary(),
+                stage
+            );
+
+            let changed = estimate.changed();
+            if changed.is_empty() {
+                continue;
+            }
+            out.push_str("| Resource | Before | After | Change |\n|---|---|---|---|\n");
+            for resource in changed.iter().take(MAX_COST_RESOURCES) {
+                let sign = if resource.delta() > 0.0 { "+" } else { "" };
+                let _ = writeln!(
+                    out,
+                    "| `{}` | {} | {} | {}{} |",
+                    escape(&resource.name),
+                    resource.previous.map(money).unwrap_or_else(|| "–".into()),
+                    money(resource.monthly),
+                    sign,
+                    money(resource.delta())
+                );
+            }
+            if changed.len() > MAX_COST_RESOURCES {
+                let more = changed.len() - MAX_COST_RESOURCES;
+                let _ = writeln!(out, "| … {} more | | | |", more);
+            }
+            out.push('\n');
+        }
+    }
+
+    fn write_diagnostics(&self, out: &mut String) {
+        if self.diagnostics.is_empty() {
+            return;
+        }
+
+        let _ = writeln!(
+            out,
+            "<details><summary>Diagnostics ({})</summary>\n",
+            self.diagnostics.len()
+        );
+        for (stage, d) in self.diagnostics.iter().take(MAX_DIAGNOSTICS) {
+            let location = d
+                .location
+                .as_ref()
+                .map(|l| format!("`{}` ", l))
+                .unwrap_or_default();
+            let _ = writeln!(
+                out,
+                "- {}**{}** {} _({})_",
+                location,
+                d.severity,
+                escape(&d.message),
+                stage
+            );
+        }
+        if self.diagnostics.len() > MAX_DIAGNOSTICS {
+            let _ = writeln!(out, "- … {} more", self.diagnostics.len() - MAX_DIAGNOSTICS);
+        }
+        out.push_str("\n</details>\n\n");
+    }
+
+    fn write_compliance(&self, out: &mut String) {
+        let Some(report) = self.compliance else {
+            return;
+        };
+
+        let _ = writeln!(
+            out,
+            "### RSR compliance\n\n![RSR]({}) **{:?}** ({:.0}%)",
+            crate::rsr::badges::shields_io_url(report),
+            report.level,
+            report.score * 100.0
+        );
+
+        let Some((base_ref, base)) = self.base else {
+            out.push('\n');
+            return;
+        };
+
+        let diff = ComplianceHistory::diff_entries(
+            Some(&HistoryEntry::from_report(base, None)),
+            &HistoryEntry::from_report(report, None),
+        );
+        let _ = writeln!(
+            out,
+            "\nCompared to `{}`: **{:?}** ({:.0}%), {:+.0} points\n",
+            base_ref,
+            base.level,
+            base.score * 100.0,
+            diff.score_change.delta * 100.0
+        );
+
+        let mut changes: Vec<_> = diff
+            .requirement_changes
+            .iter()
+            .filter(|c| c.change_type != RequirementChangeType::Unchanged)
+            .collect();
+        changes.sort_by(|a, b| a.requirement_id.cmp(&b.requirement_id));
+
+        if !changes.is_empty() {
+            out.push_str("| Requirement | Change |\n|---|---|\n");
+            for change in changes {
+                let label = match change.change_type {
+                    RequirementChangeType::Fixed => "✅ fixed",
+                    RequirementChangeType::Regressed => "❌ regressed",
+                    RequirementChangeType::New if change.current_met => "🆕 new, passing",
+                    RequirementChangeType::New => "🆕 new, failing",
+                    RequirementChangeType::Removed => "🗑️ removed",
+                    RequirementChangeType::Unchanged => continue,
+                };
+                let _ = writeln!(out, "| {} | {} |", change.requirement_id, label);
+            }
+            out.push('\n');
+        }
+    }
+
+    fn write_remediation(&self, out: &mut String) {
+        let Some(report) = self.compliance else {
+            return;
+        };
+
+        let suggestions: Vec<_> = report
+            .requirements
+            .iter()
+            .filter(|r| !r.met)
+            .filter_map(|r| Some((&r.requirement_id, r.remediation.as_ref()?)))
+            .collect();
+        if suggestions.is_empty() {
+            return;
+        }
+
+        out.push_str("### Remediation\n\n");
+        for (id, remediation) in suggestions {
+            let _ = writeln!(out, "- **{}**: {}", id, escape(remediation));
+        }
+        out.push('\n');
+    }
+}
+
+/// Keep tool output from breaking the Markdown layout
+fn escape(text: &str) -> String {
+    text.lines()
+        .map(str::trim)
+        .collect::<Vec<_>>()
+        .join(" ")
+        .replace('|', "\\|")
+        .replace('<', "&lt;")
+}
+
+/// Creates or updates the report comment on a pull request
+pub struct PrCommenter {
+    target: PrTarget,
+    client: reqwest::Client,
+}
+
+impl PrCommenter {
+    /// Create a commenter for a pull request
+    pub fn new(target: PrTarget) -> Self {
+        Self {
+            target,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Post the comment, replacing an earlier one with the same marker
+    ///
+    /// Returns `true` when an existing comment was updated.
+    pub async fn upsert(&self, marker: &str, body: &str) -> Result<bool, ConflowError> {
+        let existing = self.find_comment(marker).await?;
+        let (method, url) = match existing {
+            Some(id) => (reqwest::Method::PATCH, self.comment_url(Some(id))),
+            None => (reqwest::Method::POST, self.comment_url(None)),
+        };
+        let method = match (self.target.provider, method) {
+            (Provider::GitLab, reqwest::Method::PATCH) => reqwest::Method::PUT,
+            (_, method) => method,
+        };
+
+        self.request(method, &url, Some(json!({ "body": body })))
+            .await?;
+        Ok(existing.is_some())
+    }
+
+    /// Find a comment containing the marker
+    async fn find_comment(&self, marker: &str) -> Result<Option<u64>, ConflowError> {
+        for page in 1..=MAX_PAGES {
+            let url = format!("{}?per_page=100&page={}", self.comment_url(None), page);
+            let comments = self.request(reqwest::Method::GET, &url, None).await?;
+            let Some(comments) = comments.as_array() else {
+                return Ok(None);
+            };
+
+            let found = comments
+                .iter()
+                .find(|c| c["body"].as_str().is_some_and(|body| body.contains(marker)));
+            if let Some(comment) = found {
+                return Ok(comment["id"].as_u64());
+            }
+            if comments.len() < 100 {
+                break;
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Comments collection, or a single comment
+    fn comment_url(&self, id: Option<u64>) -> String {
+        let t = &self.target;
+        match (t.provider, id) {
+            (Provider::GitHub, None) => format!(
+                "{}/repos/{}/issues/{}/comments",
+                t.api_url, t.project, t.number
+            ),
+            (Provider::GitHub, Some(id)) => {
+                format!("{}/repos/{}/issues/comments/{}", t.api_url, t.project, id)
+            }
+            (Provider::GitLab, None) => format!(
+                "{}/projects/{}/merge_requests/{}/notes",
+                t.api_url,
+                t.project.replace('/', "%2F"),
+                t.number
+            ),
+            (Provider::GitLab, Some(id)) => format!(
+                "{}/projects/{}/merge_requests/{}/notes/{}",
+                t.api_url,
+                t.project.replace('/', "%2F"),
+                t.number,
+                id
+            ),
+        }
+    }
+
+    async fn request(
+        &self,
+        method: reqwest::Method,
+        url: &str,
+        body: Option<Value>,
+    ) -> Result<Value, ConflowError> {
+        offline::guard(format!("PR comment {} {}", method, url))?;
+        let mut request = self
+            .client
+            .request(method, url)
+            .header("User-Agent", "conflow")
+            .timeout(Duration::from_secs(15));
+        request = match self.target.provider {
+            Provider::GitHub => request
+                .bearer_auth(&self.target.token)
+                .header("Accept", "application/vnd.github+json"),
+            Provider::GitLab => request.header("PRIVATE-TOKEN", &self.target.token),
+        };
+        if let Some(body) = body {
+            request = request.json(&body);
+        }
+
+        let response = request.send().await.map_err(|e| ConflowError::PrComment {
+            message: e.to_string(),
+            help: None,
+        })?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let help = matches!(status.as_u16(), 401 | 403)
+                .then(|| "Check that the token can write PR/MR comments".to_string());
+            return Err(ConflowError::PrComment {
+                message: format!("{} returned {}", url, status),
+                help,
+            });
+        }
+
+        Ok(response.json().await.unwrap_or(Value::Null))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executors::ExecutionResult;
+    use crate::rsr::{ComplianceLevel, ComplianceStats, RequirementResult};
+    use std::collections::HashMap;
+
+    fn env<'a>(vars: &'a [(&str, &str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        move |key| {
+            vars.iter()
+                .find(|(k, _)| *k == key)
+                .map(|(_, v)| v.to_string())
+        }
+    }
+
+    fn report(level: ComplianceLevel, score: f64, met: &[(&str, bool)]) -> ComplianceReport {
+        ComplianceReport {
+            level,
+            score,
+            requirements: met
+                .iter()
+                .map(|(id, met)| RequirementResult {
+                    requirement_id: id.to_string(),
+                    met: *met,
+                    details: vec![],
+                    remediation: (!met).then(|| format!("Fix {}", id)),
+                    blocked_by: vec![],
+                    waived: false,
+                    owners: Vec::new(),
+                })
+                .collect(),
+            stats: ComplianceStats::default(),
+            custom_level: None,
+            waivers: vec![],
+        }
+    }
+
+    #[test]
+    fn test_target_from_github_env() {
+        let target = PrTarget::from_env_with(env(&[
+            ("GITHUB_REPOSITORY", "acme/infra"),
+            ("GITHUB_REF", "refs/pull/42/merge"),
+            ("GITHUB_TOKEN", "t"),
+            ("GITHUB_BASE_REF", "main"),
+        ]))
+        .unwrap();
+
+        assert_eq!(target.provider, Provider::GitHub);
+        assert_eq!(target.number, 42);
+        assert_eq!(target.api_url, "https://api.github.com");
+        assert_eq!(target.base_ref.as_deref(), Some("origin/main"));
+
+        let commenter = PrCommenter::new(target);
+        assert_eq!(
+            commenter.comment_url(None),
+            "https://api.github.com/repos/acme/infra/issues/42/comments"
+        );
+    }
+
+    #[test]
+    fn test_target_from_gitlab_env() {
+        let target = PrTarget::from_env_with(env(&[
+            ("CI_PROJECT_ID", "7"),
+            ("CI_MERGE_REQUEST_IID", "3"),
+            ("CI_API_V4_URL", "https://git.example.com/api/v4"),
+            ("GITLAB_TOKEN", "t"),
+        ]))
+        .unwrap();
+
+        assert_eq!(target.provider, Provider::GitLab);
+        assert_eq!(
+            PrCommenter::new(target).comment_url(Some(9)),
+            "https://git.example.com/api/v4/projects/7/merge_requests/3/notes/9"
+        );
+
+        let err = PrTarget::from_env_with(env(&[("CI_PROJECT_ID", "7")])).unwrap_err();
+        assert!(err.to_string().contains("Merge request IID"));
+        assert!(PrTarget::from_env_with(env(&[])).is_err());
+    }
+
+    #[test]
+    fn test_markdown_report() {
+        let pipeline = Pipeline::from_yaml(
+            r#"
+name: "infra"
+stages:
+  - name: "validate"
+    tool: { type: shell, command: "true" }
+    input: "*"
+  - name: "export"
+    tool: { type: shell, command: "true" }
+    input: "*"
+"#,
+        )
+        .unwrap();
+        let result = PipelineResult {
+            results: HashMap::from([(
+                "validate".to_string(),
+                ExecutionResult::failure("bad".into(), 1, Duration::from_millis(250)),
+            )]),
+            duration: Duration::from_millis(300),
+            success: false,
+            profile: None,
+        };
+        let diagnostics = crate::executors::parsers::parse_output(
+            "yamllint",
+            "app.yaml:3:1: [error] duplication of key \"a\" (key-duplicates)",
+        )
+        .into_iter()
+        .map(|d| ("validate".to_string(), d))
+        .collect::<Vec<_>>();
+        let head = report(
+            ComplianceLevel::Basic,
+            0.5,
+            &[("RSR-1", true), ("RSR-2", false)],
+        );
+        let base = report(
+            ComplianceLevel::Good,
+            0.8,
+            &[("RSR-1", false), ("RSR-2", true)],
+        );
+
+        let costs = [(
+            "cost".to_string(),
+            CostEstimate {
+                monthly: 1420.0,
+                previous: Some(1000.0),
+                resources: vec![crate::analyzer::ResourceCost {
+                    name: "aws_instance.web".into(),
+                    monthly: 1420.0,
+                    previous: Some(1000.0),
+                }],
+                unpriced: vec![],
+            },
+        )];
+
+        let markdown = PrReport {
+            pipeline: &pipeline,
+            result: &result,
+            diagnostics: &diagnostics,
+            costs: &costs,
+            compliance: Some(&head),
+            base: Some(("origin/main", &base)),
+        }
+        .markdown();
+
+        assert!(markdown.starts_with("<!-- conflow-report: infra -->"));
+        assert!(markdown.contains("## ❌ conflow: `infra` failed"));
+        assert!(markdown.contains("| validate | shell | ❌ failed (exit 1) | 0.25s |"));
+        assert!(markdown.contains("| export | shell | ○ not run |  |"));
+        assert!(markdown.contains("`app.yaml:3:1` **error**"));
+        assert!(markdown
+            .contains("📈 **This change adds ~$420/mo ($1,000 → $1,420/mo)** _(cost)_"));
+        assert!(markdown.contains("| `aws_instance.web` | $1,000 | $1,420 | +$420 |"));
+        assert!(markdown.contains("Compared to `origin/main`: **Good** (80%), -30 points"));
+        assert!(markdown.contains("| RSR-1 | ✅ fixed |"));
+        assert!(markdown.contains("| RSR-2 | ❌ regressed |"));
+        assert!(markdown.contains("- **RSR-2**: Fix RSR-2"));
+    }
+}
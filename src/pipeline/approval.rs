@@ -0,0 +1,135 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Approval gates for manual stages
+//!
+//! Stages marked `manual: true` only run once approved, either
+//! interactively, with `--yes`/`--approve`, or through the
+//! `CONFLOW_APPROVE` variable in CI (`all` or a comma-separated list of
+//! stage names).
+
+use std::io::{BufRead, IsTerminal, Write};
+
+use crate::errors::ConflowError;
+use crate::pipeline::Stage;
+
+/// Environment variable holding CI approvals
+pub const APPROVE_ENV: &str = "CONFLOW_APPROVE";
+
+/// Pre-granted approvals for manual stages
+#[derive(Debug, Clone, Default)]
+pub struct Approvals {
+    /// Approve every manual stage
+    pub all: bool,
+    /// Approve these stages
+    pub stages: Vec<String>,
+}
+
+impl Approvals {
+    /// Approve every manual stage
+    pub fn all() -> Self {
+        Self {
+            all: true,
+            stages: vec![],
+        }
+    }
+
+    /// Add approvals granted through `CONFLOW_APPROVE`
+    pub fn with_env(self) -> Self {
+        match std::env::var(APPROVE_ENV) {
+            Ok(value) => self.merge(&value),
+            Err(_) => self,
+        }
+    }
+
+    /// Add approvals from an `all` or `stage,stage` token
+    fn merge(mut self, token: &str) -> Self {
+        for name in token.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            if name == "all" || name == "*" {
+                self.all = true;
+            } else {
+                self.stages.push(name.to_string());
+            }
+        }
+        self
+    }
+
+    /// Whether a stage has been approved up front
+    pub fn is_approved(&self, stage: &str) -> bool {
+        self.all || self.stages.iter().any(|s| s == stage)
+    }
+
+    /// Approve a manual stage, asking on the terminal if needed
+    ///
+    /// Fails when the stage is declined, or when no approval was given and
+    /// there is no terminal to ask on.
+    pub fn confirm(&self, stage: &Stage, interactive: bool) -> Result<(), ConflowError> {
+        if self.is_approved(&stage.name) {
+            return Ok(());
+        }
+
+        if !interactive || !std::io::stdin().is_terminal() {
+            return Err(ConflowError::ApprovalRequired {
+                stage: stage.name.clone(),
+                help: Some(format!(
+                    "Pass --yes or --approve {}, or set {}={} in CI",
+                    stage.name, APPROVE_ENV, stage.name
+                )),
+            });
+        }
+
+        let description = stage
+            .description
+            .as_deref()
+            .map(|d| format!(" ({})", d))
+            .unwrap_or_default();
+        print!(
+            "  ? Run manual stage '{}'{}? [y/N] ",
+            stage.name, description
+        );
+        std::io::stdout().flush().ok();
+
+        let mut answer = String::new();
+        std::io::stdin().lock().read_line(&mut answer).ok();
+
+        if matches!(answer.trim().to_lowercase().as_str(), "y" | "yes") {
+            Ok(())
+        } else {
+            Err(ConflowError::ApprovalRequired {
+                stage: stage.name.clone(),
+                help: Some("Approval was declined".into()),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_approval_token() {
+        let approvals = Approvals::default().merge("deploy, apply");
+        assert!(approvals.is_approved("deploy"));
+        assert!(approvals.is_approved("apply"));
+        assert!(!approvals.is_approved("destroy"));
+
+        assert!(Approvals::default().merge("all").is_approved("destroy"));
+        assert!(Approvals::all().is_approved("anything"));
+        assert!(!Approvals::default().merge("").is_approved("deploy"));
+    }
+
+    #[test]
+    fn test_unapproved_stage_fails_without_terminal() {
+        let stage: Stage = serde_yaml::from_str(
+            "name: deploy\ntool: { type: shell, command: \"true\" }\ninput: \"*\"\nmanual: true\n",
+        )
+        .unwrap();
+
+        let err = Approvals::default().confirm(&stage, false).unwrap_err();
+        assert!(
+            matches!(err, ConflowError::ApprovalRequired { ref stage, .. } if stage == "deploy")
+        );
+        assert!(Approvals::all().confirm(&stage, false).is_ok());
+    }
+}
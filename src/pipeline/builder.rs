@@ -0,0 +1,459 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Programmatic pipeline construction
+//!
+//! Lets applications embedding conflow assemble a pipeline in Rust instead
+//! of writing YAML:
+//!
+//! ```
+//! use conflow::pipeline::{CueCommand, Input, Pipeline, Tool};
+//!
+//! let pipeline = Pipeline::builder("config")
+//!     .stage("generate", Tool::shell("nickel export main.ncl"), "main.ncl")
+//!     .output("generated.json")
+//!     .stage(
+//!         "validate",
+//!         Tool::cue(CueCommand::Vet, ["schema.cue"]),
+//!         Input::from_stage("generate"),
+//!     )
+//!     .depends_on("generate")
+//!     .build()
+//!     .unwrap();
+//!
+//! assert_eq!(pipeline.stage_names(), ["generate", "validate"]);
+//! ```
+//!
+//! Stage options only exist on the [`StageBuilder`] returned by `stage`, so
+//! they can't be applied before a stage is declared. `build` runs the same
+//! checks as `conflow validate`: duplicate names, unknown dependencies and
+//! cycles are reported as their own error variants.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::PathBuf;
+
+use crate::errors::ConflowError;
+use crate::pipeline::{
+    CacheConfig, Contract, CueCommand, DagBuilder, ExecutorDefinition, Input, KubernetesConfig, LintConfig,
+    NickelCommand, Output, OutputFormat, Pipeline, PipelineValidator, SandboxConfig, Shard, Stage,
+    RedactConfig, StageCondition, TelemetryConfig, Tool, WalkConfig,
+};
+
+impl Pipeline {
+    /// Start building a pipeline in code
+    pub fn builder(name: impl Into<String>) -> PipelineBuilder {
+        PipelineBuilder::new(name)
+    }
+}
+
+/// Fluent builder for [`Pipeline`]
+#[derive(Debug, Clone)]
+pub struct PipelineBuilder {
+    pipeline: Pipeline,
+}
+
+impl PipelineBuilder {
+    /// Create a builder for an empty pipeline
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            pipeline: Pipeline {
+                version: super::CURRENT_VERSION.to_string(),
+                name: name.into(),
+                description: None,
+                stages: vec![],
+                env: HashMap::new(),
+                env_file: None,
+                secrets: vec![],
+                cache: CacheConfig::default(),
+                telemetry: TelemetryConfig::default(),
+                plugins: vec![],
+                executors: HashMap::new(),
+                toolchain: HashMap::new(),
+                sandbox: SandboxConfig::default(),
+                notifications: vec![],
+                refs: vec![],
+                lint: LintConfig::default(),
+                kubernetes: KubernetesConfig::default(),
+                walk: WalkConfig::default(),
+                redact: RedactConfig::default(),
+                versions: BTreeMap::new(),
+                templates: vec![],
+            },
+        }
+    }
+
+    /// Set the pipeline description
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.pipeline.description = Some(description.into());
+        self
+    }
+
+    /// Set a global environment variable
+    pub fn env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.pipeline.env.insert(key.into(), value.into());
+        self
+    }
+
+    /// Mark a variable as secret, masking its value in output
+    pub fn secret(mut self, name: impl Into<String>) -> Self {
+        self.pipeline.secrets.push(name.into());
+        self
+    }
+
+    /// Replace the cache configuration
+    pub fn cache(mut self, cache: CacheConfig) -> Self {
+        self.pipeline.cache = cache;
+        self
+    }
+
+    /// Replace the sandbox configuration
+    pub fn sandbox(mut self, sandbox: SandboxConfig) -> Self {
+        self.pipeline.sandbox = sandbox;
+        self
+    }
+
+    /// Declare a custom executor for `Tool::Custom` stages
+    pub fn executor(mut self, name: impl Into<String>, definition: ExecutorDefinition) -> Self {
+        self.pipeline.executors.insert(name.into(), definition);
+        self
+    }
+
+    /// Add a stage, returning a builder for its options
+    pub fn stage(
+        self,
+        name: impl Into<String>,
+        tool: Tool,
+        input: impl Into<Input>,
+    ) -> StageBuilder {
+        StageBuilder {
+            pipeline: self,
+            stage: Stage {
+                name: name.into(),
+                description: None,
+                tool,
+                input: input.into(),
+                exclude: vec![],
+                output: None,
+                snapshot: false,
+                contract: None,
+                depends_on: vec![],
+                allow_failure: false,
+                manual: false,
+                env: HashMap::new(),
+                env_file: None,
+                workdir: None,
+                shard: None,
+                runs_on: Vec::new(),
+                condition: None,
+            },
+        }
+    }
+
+    /// Validate and return the pipeline
+    pub fn build(self) -> Result<Pipeline, ConflowError> {
+        let mut pipeline = self.pipeline;
+        pipeline.resolve_executors();
+
+        let mut seen = HashSet::new();
+        if let Some(stage) = pipeline.stages.iter().find(|s| !seen.insert(&s.name)) {
+            return Err(ConflowError::InvalidStage {
+                stage: stage.name.clone(),
+                reason: "a stage with this name was already added".into(),
+            });
+        }
+
+        // Typed errors for dependency problems, before the general checks
+        DagBuilder::build(&pipeline)?;
+
+        let result = PipelineValidator::validate(&pipeline)?;
+        if !result.is_valid() {
+            return Err(ConflowError::InvalidPipeline {
+                reason: result.errors.join("; "),
+                help: None,
+            });
+        }
+
+        Ok(pipeline)
+    }
+}
+
+/// Builder for the stage most recently added to a [`PipelineBuilder`]
+#[derive(Debug, Clone)]
+pub struct StageBuilder {
+    pipeline: PipelineBuilder,
+    stage: Stage,
+}
+
+impl StageBuilder {
+    /// Set the stage description
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.stage.description = Some(description.into());
+        self
+    }
+
+    /// Run after another stage
+    pub fn depends_on(mut self, stage: impl Into<String>) -> Self {
+        self.stage.depends_on.push(stage.into());
+        self
+    }
+
+    /// Leave input files matching a `.gitignore` pattern out
+    pub fn exclude(mut self, pattern: impl Into<String>) -> Self {
+        self.stage.exclude.push(pattern.into());
+        self
+    }
+
+    /// Write the stage output to a file
+    pub fn output(mut self, path: impl Into<PathBuf>) -> Self {
+        self.stage.output = Some(Output::File(path.into()));
+        self
+    }
+
+    /// Write the stage output to a file in a given format
+    pub fn output_as(mut self, path: impl Into<PathBuf>, format: OutputFormat) -> Self {
+        self.stage.output = Some(Output::Formatted {
+            path: path.into(),
+            format,
+        });
+        self
+    }
+
+    /// Compare the stage output with a snapshot
+    pub fn snapshot(mut self) -> Self {
+        self.stage.snapshot = true;
+        self
+    }
+
+    /// Require the stage output to satisfy a schema
+    pub fn contract(mut self, contract: Contract) -> Self {
+        self.stage.contract = Some(contract);
+        self
+    }
+
+    /// Continue the pipeline if this stage fails
+    pub fn allow_failure(mut self) -> Self {
+        self.stage.allow_failure = true;
+        self
+    }
+
+    /// Require approval before running this stage
+    pub fn manual(mut self) -> Self {
+        self.stage.manual = true;
+        self
+    }
+
+    /// Set an environment variable for this stage
+    pub fn env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.stage.env.insert(key.into(), value.into());
+        self
+    }
+
+    /// Run this stage in a directory relative to the pipeline
+    pub fn workdir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.stage.workdir = Some(dir.into());
+        self
+    }
+
+    /// Split this stage's input files across parallel runs of its tool
+    pub fn shard(mut self, shard: Shard) -> Self {
+        self.stage.shard = Some(shard);
+        self
+    }
+
+    /// Only run this stage when the condition holds
+    pub fn when(mut self, condition: StageCondition) -> Self {
+        self.stage.condition = Some(condition);
+        self
+    }
+
+    /// Finish this stage and return to pipeline options
+    pub fn done(self) -> PipelineBuilder {
+        let mut pipeline = self.pipeline;
+        pipeline.pipeline.stages.push(self.stage);
+        pipeline
+    }
+
+    /// Finish this stage and add another
+    pub fn stage(
+        self,
+        name: impl Into<String>,
+        tool: Tool,
+        input: impl Into<Input>,
+    ) -> StageBuilder {
+        self.done().stage(name, tool, input)
+    }
+
+    /// Finish this stage and build the pipeline
+    pub fn build(self) -> Result<Pipeline, ConflowError> {
+        self.done().build()
+    }
+}
+
+impl Tool {
+    /// Shell command run with bash
+    pub fn shell(command: impl Into<String>) -> Self {
+        Self::Shell {
+            command: command.into(),
+            shell: super::definition::default_shell(),
+        }
+    }
+
+    /// CUE command against the given schemas
+    pub fn cue<I, P>(command: CueCommand, schemas: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<PathBuf>,
+    {
+        Self::Cue {
+            command,
+            schemas: schemas.into_iter().map(Into::into).collect(),
+            flags: vec![],
+            out_format: None,
+        }
+    }
+
+    /// Nickel command on the stage inputs
+    pub fn nickel(command: NickelCommand) -> Self {
+        Self::Nickel {
+            command,
+            file: None,
+            flags: vec![],
+            format: None,
+        }
+    }
+
+    /// Custom executor declared with [`PipelineBuilder::executor`]
+    pub fn custom(executor: impl Into<String>) -> Self {
+        Self::Custom {
+            executor: executor.into(),
+            args: vec![],
+            definition: None,
+        }
+    }
+}
+
+impl Input {
+    /// Output of another stage
+    pub fn from_stage(stage: impl Into<String>) -> Self {
+        Self::FromStage {
+            from_stage: stage.into(),
+        }
+    }
+}
+
+impl From<&str> for Input {
+    fn from(pattern: &str) -> Self {
+        Self::Single(pattern.to_string())
+    }
+}
+
+impl From<String> for Input {
+    fn from(pattern: String) -> Self {
+        Self::Single(pattern)
+    }
+}
+
+impl From<Vec<String>> for Input {
+    fn from(patterns: Vec<String>) -> Self {
+        Self::Multiple(patterns)
+    }
+}
+
+impl<const N: usize> From<[&str; N]> for Input {
+    fn from(patterns: [&str; N]) -> Self {
+        Self::Multiple(patterns.iter().map(|p| p.to_string()).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder() {
+        let pipeline = Pipeline::builder("app")
+            .description("Generate and validate")
+            .env("MODE", "ci")
+            .stage(
+                "generate",
+                Tool::nickel(NickelCommand::Export),
+                "config.ncl",
+            )
+            .output_as("out/config.json", OutputFormat::Json)
+            .stage(
+                "validate",
+                Tool::cue(CueCommand::Vet, ["schema.cue"]),
+                Input::from_stage("generate"),
+            )
+            .depends_on("generate")
+            .stage("lint", Tool::shell("yamllint ."), ["a.yaml", "b.yaml"])
+            .exclude("b.yaml")
+            .allow_failure()
+            .build()
+            .unwrap();
+
+        assert_eq!(pipeline.stage_names(), ["generate", "validate", "lint"]);
+        assert_eq!(pipeline.env["MODE"], "ci");
+        assert_eq!(
+            pipeline.get_stage("validate").unwrap().depends_on,
+            ["generate"]
+        );
+        assert!(pipeline.get_stage("lint").unwrap().allow_failure);
+
+        // Round-trips through the YAML format
+        let parsed = Pipeline::from_yaml(&pipeline.to_yaml().unwrap()).unwrap();
+        assert_eq!(parsed.stage_names(), pipeline.stage_names());
+        assert_eq!(parsed.get_stage("lint").unwrap().exclude, ["b.yaml"]);
+    }
+
+    #[test]
+    fn test_builder_rejects_invalid_graphs() {
+        let err = Pipeline::builder("app")
+            .stage("a", Tool::shell("true"), "*.yaml")
+            .depends_on("missing")
+            .build()
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ConflowError::UnknownDependency { dependency, .. } if dependency == "missing"
+        ));
+
+        let err = Pipeline::builder("app")
+            .stage("a", Tool::shell("true"), Input::from_stage("b"))
+            .stage("b", Tool::shell("true"), "*.yaml")
+            .depends_on("a")
+            .build()
+            .unwrap_err();
+        assert!(matches!(err, ConflowError::CircularDependency { .. }));
+
+        let err = Pipeline::builder("app")
+            .stage("a", Tool::shell("true"), "*.yaml")
+            .stage("a", Tool::shell("false"), "*.yaml")
+            .build()
+            .unwrap_err();
+        assert!(matches!(err, ConflowError::InvalidStage { stage, .. } if stage == "a"));
+
+        let err = Pipeline::builder("app").build().unwrap_err();
+        assert!(matches!(err, ConflowError::InvalidPipeline { .. }));
+    }
+
+    #[test]
+    fn test_builder_resolves_executors() {
+        let definition: ExecutorDefinition =
+            serde_yaml::from_str("command: jsonnet {inputs}").unwrap();
+        let pipeline = Pipeline::builder("app")
+            .executor("jsonnet", definition.clone())
+            .stage("render", Tool::custom("jsonnet"), "main.jsonnet")
+            .build()
+            .unwrap();
+
+        let Tool::Custom {
+            definition: resolved,
+            ..
+        } = &pipeline.stages[0].tool
+        else {
+            panic!("expected custom tool");
+        };
+        assert_eq!(resolved.as_ref(), Some(&definition));
+    }
+}
@@ -0,0 +1,238 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Change detection
+//!
+//! Maps a set of changed files onto the pipeline stages they affect, so
+//! large repositories only re-run the part of the pipeline touched by a diff.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::errors::ConflowError;
+use crate::pipeline::{DagBuilder, Pipeline, Stage, Tool};
+use crate::utils::ignore::Ignore;
+
+/// Compute the stages affected by a set of changed files
+///
+/// A stage is affected when a changed file matches one of its input
+/// patterns or is one of its schema/entry files. Every stage downstream of
+/// an affected stage is affected too, and stages whose output is consumed
+/// via `from_stage` are kept so inputs can still be resolved (they will
+/// usually be cache hits).
+///
+/// `changed` may hold absolute paths; anything outside `base_dir` is ignored.
+/// Stage names are returned in pipeline order.
+pub fn affected_stages(
+    pipeline: &Pipeline,
+    changed: &[PathBuf],
+    base_dir: &Path,
+) -> Result<Vec<String>, ConflowError> {
+    let dag = DagBuilder::build(pipeline)?;
+    let relative = relativize(changed, base_dir);
+
+    let mut affected: HashSet<String> = pipeline
+        .stages
+        .iter()
+        .filter(|stage| stage_touched(stage, &relative))
+        .map(|stage| stage.name.clone())
+        .collect();
+
+    // Everything downstream of a touched stage must re-run
+    let mut queue: Vec<String> = affected.iter().cloned().collect();
+    while let Some(name) = queue.pop() {
+        for dependent in dag.dependents(&name).unwrap_or_default() {
+            if affected.insert(dependent.clone()) {
+                queue.push(dependent);
+            }
+        }
+    }
+
+    Ok(with_producers(pipeline, affected))
+}
+
+/// Add the stages feeding `from_stage` inputs of the given stages
+///
+/// Those inputs can't be resolved without their producers having run in the
+/// same invocation. Stage names are returned in pipeline order.
+pub fn with_producers(
+    pipeline: &Pipeline,
+    stages: impl IntoIterator<Item = String>,
+) -> Vec<String> {
+    let mut needed: HashSet<String> = stages.into_iter().collect();
+    let mut queue: Vec<String> = needed.iter().cloned().collect();
+    while let Some(name) = queue.pop() {
+        let Some(upstream) = pipeline
+            .get_stage(&name)
+            .and_then(|s| s.input.references_stage())
+        else {
+            continue;
+        };
+
+        if needed.insert(upstream.to_string()) {
+            queue.push(upstream.to_string());
+        }
+    }
+
+    pipeline
+        .stages
+        .iter()
+        .filter(|s| needed.contains(&s.name))
+        .map(|s| s.name.clone())
+        .collect()
+}
+
+/// Check whether any of the (relative) changed files touches a stage
+pub(super) fn stage_touched(stage: &Stage, changed: &[PathBuf]) -> bool {
+    let mut patterns: Vec<String> = stage
+        .input
+        .patterns()
+        .into_iter()
+        .map(String::from)
+        .collect();
+
+    match &stage.tool {
+        Tool::Cue { schemas, .. } => {
+            patterns.extend(schemas.iter().map(|s| s.to_string_lossy().to_string()));
+        }
+        Tool::Nickel { file: Some(f), .. } => {
+            patterns.push(f.to_string_lossy().to_string());
+        }
+        Tool::Helm { values, .. } => {
+            patterns.extend(values.iter().map(|v| v.to_string_lossy().to_string()));
+        }
+        Tool::Render { values, .. } => {
+            patterns.extend(values.iter().map(|v| v.to_string_lossy().to_string()));
+            // Chart templates also read the chart's values, metadata and helpers
+            let charts: Vec<String> = stage
+                .input
+                .patterns()
+                .iter()
+                .filter_map(|p| p.split_once("templates/").map(|(chart, _)| chart))
+                .filter(|chart| chart.is_empty() || chart.ends_with('/'))
+                .map(String::from)
+                .collect();
+            for chart in charts {
+                patterns.extend(
+                    ["values.yaml", "Chart.yaml", "templates/**/*.tpl"]
+                        .map(|file| format!("{}{}", chart, file)),
+                );
+            }
+        }
+        Tool::Terraform { dir: Some(d), .. } => {
+            patterns.push(format!("{}/**/*", d.to_string_lossy()));
+        }
+        _ => {}
+    }
+
+    // Changes to files the stage excludes don't touch it
+    let exclude = Ignore::new(Path::new(""), stage.exclude.iter().map(String::as_str));
+    let changed: Vec<&PathBuf> = changed.iter().filter(|f| !exclude.is_ignored(f)).collect();
+    patterns.iter().any(|pattern| {
+        let pattern = pattern.trim_start_matches("./");
+        match glob::Pattern::new(pattern) {
+            Ok(p) => changed.iter().any(|f| p.matches_path(f)),
+            Err(_) => changed.iter().any(|f| *f == Path::new(pattern)),
+        }
+    })
+}
+
+/// Make changed paths relative to `base_dir`, dropping those outside it
+fn relativize(changed: &[PathBuf], base_dir: &Path) -> Vec<PathBuf> {
+    let canonical_base = base_dir.canonicalize().ok();
+
+    changed
+        .iter()
+        .filter_map(|path| {
+            if path.is_relative() {
+                return Some(path.clone());
+            }
+
+            path.strip_prefix(base_dir)
+                .ok()
+                .or_else(|| {
+                    canonical_base
+                        .as_ref()
+                        .and_then(|base| path.strip_prefix(base).ok())
+                })
+                .map(Path::to_path_buf)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pipeline() -> Pipeline {
+        Pipeline::from_yaml(
+            r#"
+version: "1"
+name: "monorepo"
+stages:
+  - name: "gen-api"
+    tool:
+      type: nickel
+      command: export
+      file: api/config.ncl
+    input: "api/config.ncl"
+    output: "generated/api.json"
+  - name: "validate-api"
+    tool:
+      type: cue
+      command: vet
+      schemas:
+        - schemas/api.cue
+    input:
+      from_stage: gen-api
+    depends_on: [gen-api]
+  - name: "validate-web"
+    tool:
+      type: cue
+      command: vet
+    input: "web/**/*.json"
+    exclude: ["vendor/"]
+"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_input_change_affects_downstream() {
+        let changed = vec![PathBuf::from("api/config.ncl")];
+        let affected = affected_stages(&pipeline(), &changed, Path::new(".")).unwrap();
+        assert_eq!(affected, vec!["gen-api", "validate-api"]);
+    }
+
+    #[test]
+    fn test_schema_change_keeps_from_stage_producer() {
+        let changed = vec![PathBuf::from("schemas/api.cue")];
+        let affected = affected_stages(&pipeline(), &changed, Path::new(".")).unwrap();
+        assert_eq!(affected, vec!["gen-api", "validate-api"]);
+    }
+
+    #[test]
+    fn test_unrelated_change_affects_nothing() {
+        let changed = vec![PathBuf::from("docs/README.md")];
+        let affected = affected_stages(&pipeline(), &changed, Path::new(".")).unwrap();
+        assert!(affected.is_empty());
+    }
+
+    #[test]
+    fn test_excluded_change_affects_nothing() {
+        let changed = vec![PathBuf::from("web/vendor/lib.json")];
+        let affected = affected_stages(&pipeline(), &changed, Path::new(".")).unwrap();
+        assert!(affected.is_empty());
+    }
+
+    #[test]
+    fn test_absolute_paths_outside_base_ignored() {
+        let base = Path::new("/repo/project");
+        let changed = vec![
+            PathBuf::from("/repo/project/web/app/settings.json"),
+            PathBuf::from("/repo/other/api/config.ncl"),
+        ];
+        let affected = affected_stages(&pipeline(), &changed, base).unwrap();
+        assert_eq!(affected, vec!["validate-web"]);
+    }
+}
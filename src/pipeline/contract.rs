@@ -0,0 +1,169 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Output contracts
+//!
+//! A stage with `contract:` names a schema its output must satisfy. After
+//! the stage succeeds, its JSON and YAML output files, or its standard
+//! output when it writes none, are vetted against the schema with `cue
+//! vet`; a violation fails the producing stage rather than whatever stage
+//! consumes the broken artifact later.
+
+use colored::Colorize;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use super::Stage;
+use crate::cache::DataFormat;
+use crate::errors::ConflowError;
+use crate::executors::parsers::{parse_output, ToolDiagnostic};
+use crate::executors::ExecutionResult;
+use crate::rsr::detect::{detect_file, schema_file};
+
+/// Where standard output is written to be vetted, relative to the root
+const CONTRACTS_DIR: &str = ".conflow/contracts";
+
+/// A stage output that breaks its contract
+#[derive(Debug, Clone, Serialize)]
+pub struct ContractViolation {
+    pub stage: String,
+    /// The schema broken
+    pub schema: String,
+    /// What `cue vet` reported
+    pub message: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub diagnostics: Vec<ToolDiagnostic>,
+}
+
+impl ContractViolation {
+    /// The violation, colored for a terminal
+    pub fn render(&self) -> String {
+        let mut out = format!(
+            "  {} output breaks contract {}\n",
+            "✗".red(),
+            self.schema.bold()
+        );
+        match self.diagnostics.is_empty() {
+            true => {
+                for line in self.message.lines() {
+                    out.push_str(&format!("    {}\n", line));
+                }
+            }
+            false => {
+                for diagnostic in &self.diagnostics {
+                    out.push_str(&format!("    {}\n", diagnostic.render()));
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Vet a stage's output against its contract
+///
+/// `Ok(None)` when the stage has no contract, or its output satisfies it.
+pub fn check_contract(
+    stage: &Stage,
+    result: &ExecutionResult,
+    stage_dir: &Path,
+    root: &Path,
+) -> Result<Option<ContractViolation>, ConflowError> {
+    let Some(ref contract) = stage.contract else {
+        return Ok(None);
+    };
+    let cue = which::which("cue").map_err(|_| ConflowError::tool_not_found("cue"))?;
+
+    let mut files: Vec<PathBuf> = result
+        .outputs
+        .iter()
+        .filter(|output| DataFormat::from_path(output).is_some())
+        .cloned()
+        .collect();
+    if result.outputs.is_empty() {
+        // YAML parses JSON too
+        let path = root
+            .join(CONTRACTS_DIR)
+            .join(format!("{}.yaml", stage.name));
+        let written = match path.parent() {
+            Some(parent) => std::fs::create_dir_all(parent),
+            None => Ok(()),
+        };
+        written
+            .and_then(|()| std::fs::write(&path, &result.stdout))
+            .map_err(|e| ConflowError::FileWriteError {
+                path: path.clone(),
+                error: e.to_string(),
+            })?;
+        files.push(path);
+    }
+    if files.is_empty() {
+        return Ok(None);
+    }
+
+    let schema = match contract.is_file() {
+        true => stage_dir.join(contract.schema()),
+        false => schema_file(root, contract.schema())?,
+    };
+    if !schema.exists() {
+        return Err(ConflowError::file_not_found_in_stage(schema, &stage.name));
+    }
+    // Registry schemas that only constrain definitions take the one detected
+    let definition = match contract.definition() {
+        Some(definition) => Some(definition.to_string()),
+        None if !contract.is_file() => detect_file(&files[0])?
+            .filter(|m| m.schema == contract.schema())
+            .and_then(|m| m.definition),
+        None => None,
+    };
+
+    let mut command = Command::new(cue);
+    command.arg("vet").arg(&schema).current_dir(stage_dir);
+    if let Some(ref definition) = definition {
+        command.arg("-d").arg(definition);
+    }
+    let output = command.args(&files).output()?;
+    if output.status.success() {
+        return Ok(None);
+    }
+
+    let message = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    Ok(Some(ContractViolation {
+        stage: stage.name.clone(),
+        schema: contract.schema().to_string(),
+        diagnostics: parse_output("cue", &message),
+        message,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::{Contract, CueCommand, Pipeline, Tool};
+    use std::time::Duration;
+
+    #[test]
+    fn test_contract_violation() {
+        if which::which("cue").is_err() {
+            return;
+        }
+        let temp = tempfile::TempDir::new().unwrap();
+        let root = temp.path();
+        std::fs::write(root.join("app.cue"), "replicas: int & >0\n").unwrap();
+        let pipeline = Pipeline::builder("app")
+            .stage("export", Tool::cue(CueCommand::Export, ["s.cue"]), "a.cue")
+            .contract(Contract::Schema("app.cue".into()))
+            .build()
+            .unwrap();
+        let stage = &pipeline.stages[0];
+        let run = |stdout: &str| {
+            let result = ExecutionResult::success(stdout.into(), Duration::ZERO, vec![]);
+            check_contract(stage, &result, root, root).unwrap()
+        };
+
+        assert!(run("replicas: 2\n").is_none());
+        let violation = run("replicas: 0\n").unwrap();
+        assert_eq!(violation.schema, "app.cue");
+        assert!(violation.message.contains("replicas"));
+    }
+}
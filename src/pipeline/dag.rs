@@ -11,7 +11,7 @@ use petgraph::graph::{DiGraph, NodeIndex};
 use std::collections::HashMap;
 
 use crate::errors::ConflowError;
-use crate::pipeline::{Pipeline, Stage};
+use crate::pipeline::Pipeline;
 
 /// Builder for stage dependency DAGs
 pub struct DagBuilder {
@@ -57,8 +57,9 @@ impl DagBuilder {
                 builder.graph.add_edge(*dep_node, stage_node, ());
             }
 
-            // Implicit dependencies from from_stage inputs
-            if let Some(ref_stage) = stage.input.references_stage() {
+            // Implicit dependencies from from_stage inputs and plan stages
+            let implicit = [stage.input.references_stage(), stage.tool.plan_stage()];
+            for ref_stage in implicit.into_iter().flatten() {
                 if let Some(dep_node) = builder.name_to_index.get(ref_stage) {
                     // Only add if not already present
                     if !builder.graph.contains_edge(*dep_node, stage_node) {
@@ -85,7 +86,6 @@ impl DagBuilder {
             Ok(_) => Ok(()),
             Err(cycle) => {
                 // Find cycle members
-                let cycle_start = &self.index_to_name[&cycle.node_id()];
                 let stages = self.find_cycle_members(cycle.node_id());
                 Err(ConflowError::CircularDependency { stages })
             }
@@ -182,7 +182,7 @@ impl DagBuilder {
         let mut out = String::from("graph TD\n");
 
         // Add nodes
-        for (name, _) in &self.name_to_index {
+        for name in self.name_to_index.keys() {
             out.push_str(&format!("    {}[{}]\n", name, name));
         }
 
@@ -253,7 +253,7 @@ impl Default for DagBuilder {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::pipeline::{CueCommand, Input, Tool};
+    use crate::pipeline::{CueCommand, Input, Stage, Tool};
 
     fn make_test_pipeline(stages: Vec<(&str, Vec<&str>)>) -> Pipeline {
         Pipeline {
@@ -272,15 +272,38 @@ mod tests {
                         out_format: None,
                     },
                     input: Input::Single("*.json".into()),
+                    exclude: vec![],
                     output: None,
+                    snapshot: false,
+                    contract: None,
                     depends_on: deps.into_iter().map(String::from).collect(),
                     allow_failure: false,
                     env: std::collections::HashMap::new(),
                     condition: None,
+                    env_file: None,
+                    workdir: None,
+                    shard: None,
+                    runs_on: Vec::new(),
+                    manual: false,
                 })
                 .collect(),
             env: std::collections::HashMap::new(),
+            env_file: None,
+            secrets: vec![],
             cache: crate::pipeline::CacheConfig::default(),
+            telemetry: crate::pipeline::TelemetryConfig::default(),
+            plugins: vec![],
+            executors: std::collections::HashMap::new(),
+            toolchain: std::collections::HashMap::new(),
+            sandbox: crate::pipeline::SandboxConfig::default(),
+            notifications: vec![],
+            refs: vec![],
+            lint: crate::pipeline::LintConfig::default(),
+            kubernetes: crate::pipeline::KubernetesConfig::default(),
+            walk: crate::pipeline::WalkConfig::default(),
+            templates: vec![],
+            redact: crate::pipeline::RedactConfig::default(),
+            versions: std::collections::BTreeMap::new(),
         }
     }
 
@@ -6,11 +6,15 @@
 //! Defines the schema for .conflow.yaml files.
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use crate::cache::CacheLimits;
+use crate::rsr::templates::TemplateRepo;
 
 /// Pipeline definition from .conflow.yaml
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct Pipeline {
     /// Pipeline version (for future compatibility)
     #[serde(default = "default_version")]
@@ -27,16 +31,77 @@ pub struct Pipeline {
     pub stages: Vec<Stage>,
 
     /// Global environment variables
-    #[serde(default)]
+    #[serde(default, serialize_with = "crate::report::sorted")]
     pub env: HashMap<String, String>,
 
+    /// Dotenv file with global variables (overridden by `env`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env_file: Option<PathBuf>,
+
+    /// Names of sensitive variables whose values are masked in output
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub secrets: Vec<String>,
+
     /// Cache configuration
     #[serde(default)]
     pub cache: CacheConfig,
+
+    /// Telemetry export configuration
+    #[serde(default, skip_serializing_if = "TelemetryConfig::is_empty")]
+    pub telemetry: TelemetryConfig,
+
+    /// WASM plugin modules, relative to the pipeline file
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub plugins: Vec<PathBuf>,
+
+    /// Custom executor definitions, referenced by `type: custom` stages
+    #[serde(default, skip_serializing_if = "HashMap::is_empty", serialize_with = "crate::report::sorted")]
+    pub executors: HashMap<String, ExecutorDefinition>,
+
+    /// Pinned tool versions, installed with `conflow toolchain install`
+    #[serde(default, skip_serializing_if = "HashMap::is_empty", serialize_with = "crate::report::sorted")]
+    pub toolchain: HashMap<String, ToolPin>,
+
+    /// Sandbox for stage commands (opt-in)
+    #[serde(default, skip_serializing_if = "SandboxConfig::is_default")]
+    pub sandbox: SandboxConfig,
+
+    /// Channels notified about run results
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notifications: Vec<NotificationConfig>,
+
+    /// Cross-file reference rules, checked by `conflow validate`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub refs: Vec<RefRule>,
+
+    /// Lint rules, checked by `conflow lint`
+    #[serde(default, skip_serializing_if = "LintConfig::is_default")]
+    pub lint: LintConfig,
+
+    /// Target cluster, for `conflow deprecations`
+    #[serde(default, skip_serializing_if = "KubernetesConfig::is_default")]
+    pub kubernetes: KubernetesConfig,
+
+    /// How commands walking the project treat links, submodules and vendored code
+    #[serde(default, skip_serializing_if = "WalkConfig::is_default")]
+    pub walk: WalkConfig,
+
+    /// Template repositories for `conflow template`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub templates: Vec<TemplateRepo>,
+
+    /// Values masked in diagnostics, diffs, artifacts and reports
+    #[serde(default, skip_serializing_if = "RedactConfig::is_default")]
+    pub redact: RedactConfig,
+
+    /// Files and JSONPath locations in them sharing the project's version,
+    /// for `conflow version`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub versions: BTreeMap<PathBuf, VersionPaths>,
 }
 
 fn default_version() -> String {
-    "1".to_string()
+    super::CURRENT_VERSION.to_string()
 }
 
 impl Pipeline {
@@ -49,12 +114,48 @@ impl Pipeline {
             }
         })?;
 
-        Self::from_yaml(&content)
+        Self::parse(&content, &path.display().to_string())
     }
 
     /// Parse pipeline from YAML string
     pub fn from_yaml(yaml: &str) -> Result<Self, crate::ConflowError> {
-        serde_yaml::from_str(yaml).map_err(Into::into)
+        Self::parse(yaml, ".conflow.yaml")
+    }
+
+    /// Parse strictly, reporting errors against `file`
+    fn parse(yaml: &str, file: &str) -> Result<Self, crate::ConflowError> {
+        let mut pipeline: Self = crate::utils::yaml::parse(yaml, file).map_err(|mut e| {
+            if let crate::ConflowError::ConfigParse { help, .. } = &mut e {
+                if super::needs_migration(yaml) {
+                    let hint = "this pipeline uses an older layout; \
+                                run `conflow migrate` to upgrade it";
+                    *help = Some(match help.take() {
+                        Some(help) => format!("{}\n{}", help, hint),
+                        None => hint.to_string(),
+                    });
+                }
+            }
+            e
+        })?;
+        pipeline.resolve_executors();
+        Ok(pipeline)
+    }
+
+    /// Attach executor definitions to the custom stages that use them
+    ///
+    /// Inlining the definition makes it part of the stage's cache key, so
+    /// editing an executor invalidates the stages that run it.
+    pub(super) fn resolve_executors(&mut self) {
+        for stage in &mut self.stages {
+            if let Tool::Custom {
+                executor,
+                definition: definition @ None,
+                ..
+            } = &mut stage.tool
+            {
+                *definition = self.executors.get(executor.as_str()).cloned();
+            }
+        }
     }
 
     /// Serialize pipeline to YAML
@@ -71,10 +172,18 @@ impl Pipeline {
     pub fn stage_names(&self) -> Vec<&str> {
         self.stages.iter().map(|s| s.name.as_str()).collect()
     }
+
+    /// Variables whose values are masked: `secrets` and `redact.env`
+    pub fn masked_vars(&self) -> Vec<String> {
+        let mut names = self.secrets.clone();
+        names.extend(self.redact.env.iter().cloned());
+        names
+    }
 }
 
 /// A single pipeline stage
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct Stage {
     /// Stage name (must be unique within pipeline)
     pub name: String,
@@ -89,10 +198,23 @@ pub struct Stage {
     /// Input specification
     pub input: Input,
 
+    /// Input files to leave out, as `.gitignore` patterns relative to the
+    /// stage's working directory
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<String>,
+
     /// Output specification
     #[serde(default)]
     pub output: Option<Output>,
 
+    /// Compare the output with a snapshot under `.conflow/snapshots/`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub snapshot: bool,
+
+    /// Schema the output must satisfy
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contract: Option<Contract>,
+
     /// Stage dependencies (other stage names)
     #[serde(default)]
     pub depends_on: Vec<String>,
@@ -101,12 +223,33 @@ pub struct Stage {
     #[serde(default)]
     pub allow_failure: bool,
 
+    /// Require approval before running this stage
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub manual: bool,
+
     /// Environment variables for this stage
-    #[serde(default)]
+    #[serde(default, serialize_with = "crate::report::sorted")]
     pub env: HashMap<String, String>,
 
+    /// Dotenv file for this stage, relative to the pipeline directory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env_file: Option<PathBuf>,
+
+    /// Working directory for this stage, relative to the pipeline directory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workdir: Option<PathBuf>,
+
+    /// Split the input files across parallel runs of the tool
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shard: Option<Shard>,
+
+    /// Labels an agent must offer to run this stage, e.g. `cluster:prod`;
+    /// such stages run on agents found through `conflow run --coordinator`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub runs_on: Vec<String>,
+
     /// Condition for running this stage
-    #[serde(default)]
+    #[serde(default, alias = "when", with = "serde_yaml::with::singleton_map")]
     pub condition: Option<StageCondition>,
 }
 
@@ -117,13 +260,83 @@ impl Stage {
             Tool::Cue { .. } => "cue",
             Tool::Nickel { .. } => "nickel",
             Tool::Shell { .. } => "shell",
+            Tool::Custom { executor, .. } => executor,
+            Tool::Kubectl { .. } => "kubectl",
+            Tool::Helm { .. } => "helm",
+            Tool::Terraform { .. } => "terraform",
+            Tool::Cost { .. } => "cost",
+            Tool::Render { .. } => "render",
+        }
+    }
+
+    /// Whether results can be cached
+    ///
+    /// Deployment stages depend on live state outside the inputs, and cost
+    /// stages on the git base they compare against, so they always run.
+    pub fn is_cacheable(&self) -> bool {
+        self.tool.deploy_action().is_none() && !matches!(self.tool, Tool::Cost { .. })
+    }
+}
+
+/// How a stage's input files are split across parallel runs of its tool
+///
+/// Written as `auto` or a number of shards in YAML.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "ShardSpec", into = "ShardSpec")]
+pub enum Shard {
+    /// One shard per CPU, each with at least [`Shard::AUTO_MIN_FILES`] files
+    Auto,
+    /// A fixed number of shards
+    Count(usize),
+}
+
+impl Shard {
+    /// Fewest files worth a shard of their own under `auto`
+    pub const AUTO_MIN_FILES: usize = 50;
+
+    /// How many shards to split `files` input files into
+    pub fn count(&self, files: usize) -> usize {
+        let wanted = match self {
+            Self::Auto => std::thread::available_parallelism()
+                .map_or(1, |n| n.get())
+                .min(files.div_ceil(Self::AUTO_MIN_FILES)),
+            Self::Count(n) => *n,
+        };
+        wanted.min(files).max(1)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum ShardSpec {
+    Count(usize),
+    Mode(String),
+}
+
+impl TryFrom<ShardSpec> for Shard {
+    type Error = String;
+
+    fn try_from(spec: ShardSpec) -> Result<Self, Self::Error> {
+        match spec {
+            ShardSpec::Mode(mode) if mode == "auto" => Ok(Self::Auto),
+            ShardSpec::Count(n) if n > 0 => Ok(Self::Count(n)),
+            _ => Err("shard must be `auto` or a number of shards above 0".to_string()),
+        }
+    }
+}
+
+impl From<Shard> for ShardSpec {
+    fn from(shard: Shard) -> Self {
+        match shard {
+            Shard::Auto => Self::Mode("auto".to_string()),
+            Shard::Count(n) => Self::Count(n),
         }
     }
 }
 
 /// Tool specification
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(tag = "type", rename_all = "lowercase")]
+#[serde(tag = "type", rename_all = "lowercase", deny_unknown_fields)]
 pub enum Tool {
     /// CUE tool
     Cue {
@@ -166,13 +379,244 @@ pub enum Tool {
         /// Shell command to run
         command: String,
 
-        /// Shell to use (bash, sh, etc.)
+        /// Shell to use: bash, sh and other POSIX shells, powershell, pwsh or cmd
         #[serde(default = "default_shell")]
         shell: String,
     },
+
+    /// Custom executor declared under `executors:`
+    Custom {
+        /// Executor name
+        executor: String,
+
+        /// Extra arguments substituted for `{args}`
+        #[serde(default)]
+        args: Vec<String>,
+
+        /// Executor definition (filled from `executors:` when omitted)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        definition: Option<ExecutorDefinition>,
+    },
+
+    /// Kubernetes manifests (the stage inputs) applied with kubectl
+    Kubectl {
+        /// Server-side dry run, or apply
+        action: DeployAction,
+
+        /// Target namespace
+        #[serde(default)]
+        namespace: Option<String>,
+
+        /// kubeconfig context
+        #[serde(default)]
+        context: Option<String>,
+
+        /// Additional kubectl flags
+        #[serde(default)]
+        flags: Vec<String>,
+
+        /// Plan stage whose output must still match before applying
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        plan: Option<String>,
+    },
+
+    /// Helm release installed with `helm upgrade --install`
+    Helm {
+        /// Dry run, or apply
+        action: DeployAction,
+
+        /// Release name
+        release: String,
+
+        /// Chart reference or path
+        chart: String,
+
+        /// Target namespace
+        #[serde(default)]
+        namespace: Option<String>,
+
+        /// Values files
+        #[serde(default)]
+        values: Vec<PathBuf>,
+
+        /// Additional helm flags
+        #[serde(default)]
+        flags: Vec<String>,
+
+        /// Plan stage whose output must still match before applying
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        plan: Option<String>,
+    },
+
+    /// Terraform configuration
+    Terraform {
+        /// `terraform plan`, or `terraform apply`
+        action: DeployAction,
+
+        /// Configuration directory (defaults to the working directory)
+        #[serde(default)]
+        dir: Option<PathBuf>,
+
+        /// Additional terraform flags
+        #[serde(default)]
+        flags: Vec<String>,
+
+        /// Plan stage whose saved plan is applied
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        plan: Option<String>,
+    },
+
+    /// Monthly cost estimate of a Terraform plan or Kubernetes manifests
+    Cost {
+        /// What the stage inputs are
+        source: CostSource,
+
+        /// Terraform plan stage to estimate instead of the inputs
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        plan: Option<String>,
+
+        /// Price Terraform plans with infracost instead of the pricing table
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        infracost: bool,
+
+        /// YAML or JSON prices added to the built-in pricing table
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pricing: Option<PathBuf>,
+
+        /// Git ref Kubernetes manifests are compared against (defaults to
+        /// the pull request's target branch, or HEAD)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        base: Option<String>,
+
+        /// Fail when the change adds more than this many dollars a month
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        budget: Option<f64>,
+    },
+
+    /// Helm chart templates and Go-templated files (the stage inputs)
+    /// rendered with sample values, for later stages to validate
+    Render {
+        /// Values files, layered over a chart's `values.yaml`
+        #[serde(default)]
+        values: Vec<PathBuf>,
+
+        /// Values set by dotted key, e.g. `image.tag: "1.0"`, applied last
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        set: BTreeMap<String, serde_json::Value>,
+
+        /// Release name (default: release-name)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        release: Option<String>,
+
+        /// Release namespace (default: default)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        namespace: Option<String>,
+    },
+}
+
+impl Tool {
+    /// Plan or apply, for deployment tools
+    pub fn deploy_action(&self) -> Option<DeployAction> {
+        match self {
+            Self::Kubectl { action, .. }
+            | Self::Helm { action, .. }
+            | Self::Terraform { action, .. } => Some(*action),
+            _ => None,
+        }
+    }
+
+    /// Plan stage an apply stage is checked against, or a cost stage
+    /// estimates
+    pub fn plan_stage(&self) -> Option<&str> {
+        match self {
+            Self::Kubectl { plan, .. }
+            | Self::Helm { plan, .. }
+            | Self::Terraform { plan, .. }
+            | Self::Cost { plan, .. } => plan.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+/// What a cost stage estimates
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CostSource {
+    /// Plans from `terraform show -json`
+    Terraform,
+    /// Resource requests in Kubernetes manifests
+    Kubernetes,
+}
+
+impl std::fmt::Display for CostSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Terraform => write!(f, "terraform"),
+            Self::Kubernetes => write!(f, "kubernetes"),
+        }
+    }
 }
 
-fn default_shell() -> String {
+/// What a deployment stage does
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DeployAction {
+    /// Compute and record what would change
+    Plan,
+    /// Change the target environment
+    Apply,
+}
+
+impl std::fmt::Display for DeployAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Plan => write!(f, "plan"),
+            Self::Apply => write!(f, "apply"),
+        }
+    }
+}
+
+/// A custom executor definition
+///
+/// The command template is run through the shell with `{inputs}`,
+/// `{output}`, `{args}`, and `{stage}` substituted and quoted for that shell.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExecutorDefinition {
+    /// Command template
+    pub command: String,
+
+    /// Shell used to run the command
+    #[serde(default = "default_shell")]
+    pub shell: String,
+
+    /// Executable that must be on PATH (defaults to the command's first word)
+    #[serde(default)]
+    pub requires: Option<String>,
+
+    /// Exit codes that count as success
+    #[serde(default = "default_success_exit_codes")]
+    pub success_exit_codes: Vec<i32>,
+
+    /// Regex that must match the output for the stage to succeed
+    #[serde(default)]
+    pub success_pattern: Option<String>,
+
+    /// Regex that fails the stage when it matches the output
+    #[serde(default)]
+    pub failure_pattern: Option<String>,
+
+    /// Regex extracting errors, with named groups `message` and optionally
+    /// `file`, `line`, `column`
+    #[serde(default)]
+    pub error_pattern: Option<String>,
+}
+
+fn default_success_exit_codes() -> Vec<i32> {
+    vec![0]
+}
+
+pub(super) fn default_shell() -> String {
     "bash".to_string()
 }
 
@@ -299,6 +743,47 @@ impl Output {
     }
 }
 
+/// A schema a stage's output must satisfy
+///
+/// Written as a registry schema id (`k8s:base`) or a CUE file relative to
+/// the stage's working directory, or as a map that also names the
+/// definition to vet against.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Contract {
+    /// Schema id or file
+    Schema(String),
+
+    /// Schema and definition
+    Definition {
+        schema: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        definition: Option<String>,
+    },
+}
+
+impl Contract {
+    /// The registry schema id or schema file
+    pub fn schema(&self) -> &str {
+        match self {
+            Self::Schema(schema) | Self::Definition { schema, .. } => schema,
+        }
+    }
+
+    /// The definition to vet against, if given
+    pub fn definition(&self) -> Option<&str> {
+        match self {
+            Self::Schema(_) => None,
+            Self::Definition { definition, .. } => definition.as_deref(),
+        }
+    }
+
+    /// Whether the schema is a file rather than a registry id
+    pub fn is_file(&self) -> bool {
+        self.schema().ends_with(".cue")
+    }
+}
+
 /// Output format types
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
@@ -324,18 +809,81 @@ impl std::fmt::Display for OutputFormat {
 
 /// Cache configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct CacheConfig {
     /// Enable caching
     #[serde(default = "default_true")]
     pub enabled: bool,
 
-    /// Cache directory
+    /// Cache directory (project scope)
     #[serde(default = "default_cache_dir")]
     pub directory: PathBuf,
 
+    /// Keep the cache in the project or in the shared user cache
+    #[serde(default)]
+    pub scope: CacheScope,
+
+    /// Namespace in the shared cache (default: derived from the project path)
+    #[serde(default)]
+    pub namespace: Option<String>,
+
     /// Cache invalidation strategy
     #[serde(default)]
     pub invalidation: CacheInvalidation,
+
+    /// Evict least recently used entries beyond this many megabytes
+    #[serde(default)]
+    pub max_size_mb: Option<u64>,
+
+    /// Evict entries not used for this many days
+    #[serde(default)]
+    pub max_age_days: Option<u64>,
+}
+
+impl CacheConfig {
+    /// Where this project's cache lives
+    ///
+    /// Shared caches are split into one namespace per project, so projects
+    /// don't evict or overwrite each other's entries unless they opt into
+    /// the same namespace.
+    pub fn path(&self, working_dir: &Path) -> Result<PathBuf, crate::ConflowError> {
+        match self.scope {
+            CacheScope::Project => Ok(working_dir.join(&self.directory)),
+            CacheScope::Global => {
+                let root = crate::cache::global_root().ok_or_else(|| {
+                    crate::ConflowError::CacheError {
+                        message: "No user cache directory; set CONFLOW_CACHE_DIR".into(),
+                    }
+                })?;
+                let namespace = match &self.namespace {
+                    Some(namespace)
+                        if matches!(namespace.as_str(), "" | "." | "..")
+                            || namespace.contains(['/', '\\', '\0']) =>
+                    {
+                        return Err(crate::ConflowError::CacheError {
+                            message: format!(
+                                "Invalid cache.namespace '{}': must be a single directory name",
+                                namespace
+                            ),
+                        });
+                    }
+                    Some(namespace) => namespace.clone(),
+                    None => crate::cache::project_namespace(working_dir),
+                };
+                Ok(root.join(namespace))
+            }
+        }
+    }
+
+    /// Size and age limits for the cache directory
+    pub fn limits(&self) -> CacheLimits {
+        CacheLimits {
+            max_size_bytes: self.max_size_mb.map(|mb| mb * 1024 * 1024),
+            max_age: self
+                .max_age_days
+                .map(|days| std::time::Duration::from_secs(days * 24 * 60 * 60)),
+        }
+    }
 }
 
 impl Default for CacheConfig {
@@ -343,7 +891,11 @@ impl Default for CacheConfig {
         Self {
             enabled: true,
             directory: default_cache_dir(),
+            scope: CacheScope::default(),
+            namespace: None,
             invalidation: CacheInvalidation::default(),
+            max_size_mb: None,
+            max_age_days: None,
         }
     }
 }
@@ -356,6 +908,17 @@ fn default_cache_dir() -> PathBuf {
     PathBuf::from(".conflow/cache")
 }
 
+/// Where the cache is kept
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CacheScope {
+    /// In the project, under `directory` (default)
+    #[default]
+    Project,
+    /// In the user cache (`~/.cache/conflow`), namespaced per project
+    Global,
+}
+
 /// Cache invalidation strategies
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
@@ -371,13 +934,16 @@ pub enum CacheInvalidation {
 
 /// Condition for running a stage
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "lowercase")]
+#[serde(rename_all = "lowercase", deny_unknown_fields)]
 pub enum StageCondition {
     /// Run only if file exists
+    #[serde(alias = "file_exists")]
     FileExists(PathBuf),
     /// Run only if environment variable is set
+    #[serde(alias = "env_set")]
     EnvSet(String),
     /// Run only if environment variable equals value
+    #[serde(alias = "env_equals")]
     EnvEquals { var: String, value: String },
     /// Always run (default)
     Always,
@@ -385,6 +951,557 @@ pub enum StageCondition {
     Never,
 }
 
+impl StageCondition {
+    /// Evaluate the condition
+    ///
+    /// Variables are looked up in the stage environment first, then the
+    /// process environment; files are relative to `working_dir`.
+    pub fn is_met(&self, working_dir: &std::path::Path, env: &HashMap<String, String>) -> bool {
+        let lookup = |var: &str| env.get(var).cloned().or_else(|| std::env::var(var).ok());
+
+        match self {
+            Self::FileExists(path) => working_dir.join(path).exists(),
+            Self::EnvSet(var) => lookup(var).is_some(),
+            Self::EnvEquals { var, value } => lookup(var).as_deref() == Some(value.as_str()),
+            Self::Always => true,
+            Self::Never => false,
+        }
+    }
+}
+
+impl std::fmt::Display for StageCondition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::FileExists(path) => write!(f, "file_exists({})", path.display()),
+            Self::EnvSet(var) => write!(f, "env_set({})", var),
+            Self::EnvEquals { var, value } => write!(f, "env_equals({}={})", var, value),
+            Self::Always => write!(f, "always"),
+            Self::Never => write!(f, "never"),
+        }
+    }
+}
+
+/// A notification channel
+///
+/// `url` and `headers` may reference environment variables as `${VAR}`,
+/// so webhook secrets stay out of the pipeline file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NotificationConfig {
+    /// Channel type
+    #[serde(rename = "type")]
+    pub kind: NotificationKind,
+
+    /// Webhook URL
+    pub url: String,
+
+    /// Events that trigger a notification
+    #[serde(default = "default_notify_on")]
+    pub on: Vec<NotifyEvent>,
+
+    /// Extra request headers
+    #[serde(default, skip_serializing_if = "HashMap::is_empty", serialize_with = "crate::report::sorted")]
+    pub headers: HashMap<String, String>,
+
+    /// Request body with `{{placeholder}}` values (replaces the default)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+
+    /// CODEOWNERS owners whose failures this channel hears about (default: all)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub owners: Vec<String>,
+}
+
+fn default_notify_on() -> Vec<NotifyEvent> {
+    vec![NotifyEvent::Failure, NotifyEvent::Regression]
+}
+
+/// Notification channel types
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum NotificationKind {
+    /// Slack incoming webhook
+    Slack,
+    /// Microsoft Teams incoming webhook
+    Teams,
+    /// Generic HTTP webhook (JSON POST)
+    Webhook,
+}
+
+/// Run outcomes that trigger notifications
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum NotifyEvent {
+    /// The run failed
+    Failure,
+    /// The run failed after the previous run succeeded
+    Regression,
+    /// The run succeeded after the previous run failed
+    Recovery,
+    /// The RSR compliance level changed since the previous run
+    ComplianceChange,
+    /// Every run
+    Always,
+}
+
+impl std::fmt::Display for NotifyEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Failure => write!(f, "failure"),
+            Self::Regression => write!(f, "regression"),
+            Self::Recovery => write!(f, "recovery"),
+            Self::ComplianceChange => write!(f, "compliance_change"),
+            Self::Always => write!(f, "always"),
+        }
+    }
+}
+
+/// A cross-file reference rule
+///
+/// Every value selected by `from` must equal a value selected by `to`,
+/// e.g. each ConfigMap a Deployment mounts must be defined by some
+/// manifest, or name a path in the project, e.g. the directory an ArgoCD
+/// Application deploys.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RefRule {
+    /// Rule name, shown in reports
+    pub name: String,
+
+    /// Where the references are made
+    pub from: RefSelector,
+
+    /// Where the referenced entities are defined
+    pub to: RefTarget,
+}
+
+/// What the values of a reference rule must refer to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RefTarget {
+    /// Values selected from config files
+    Values(RefSelector),
+
+    /// Paths relative to the project root
+    Paths(RefPaths),
+}
+
+impl RefTarget {
+    /// The selector, for targets defined by config files
+    pub fn selector(&self) -> Option<&RefSelector> {
+        match self {
+            Self::Values(selector) => Some(selector),
+            Self::Paths(_) => None,
+        }
+    }
+}
+
+/// Paths referenced values must name
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RefPaths {
+    /// Kind of path that must exist
+    pub exists: PathKind,
+
+    /// Dotted path to the repository URL a document's paths belong to
+    /// (e.g. `spec.source.repoURL`); documents naming another repository
+    /// are not checked
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repo: Option<String>,
+}
+
+/// Kind of filesystem path
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PathKind {
+    File,
+    Directory,
+    Any,
+}
+
+impl PathKind {
+    /// Whether `path` exists and is of this kind
+    pub fn matches(self, path: &Path) -> bool {
+        match self {
+            Self::File => path.is_file(),
+            Self::Directory => path.is_dir(),
+            Self::Any => path.exists(),
+        }
+    }
+}
+
+/// Values selected from config files
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RefSelector {
+    /// Files to read
+    pub files: Input,
+
+    /// Dotted path to the values; `*` matches every list element or
+    /// mapping value (e.g. `spec.volumes.*.configMap.name`)
+    pub path: String,
+
+    /// Select the keys of the mapping at `path` instead (e.g. compose `services`)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub keys: bool,
+
+    /// Only documents whose fields have these values (e.g. `kind: ConfigMap`)
+    #[serde(
+        default,
+        rename = "where",
+        skip_serializing_if = "HashMap::is_empty",
+        serialize_with = "crate::report::sorted"
+    )]
+    pub filter: HashMap<String, String>,
+}
+
+/// Lint configuration, checked by `conflow lint`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LintConfig {
+    /// Files to lint (default: every YAML and JSON file outside hidden directories)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<String>,
+
+    /// Built-in rule settings by rule id, over the rules' defaults
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub rules: BTreeMap<String, RuleSetting>,
+
+    /// Rule settings for some files only; later entries win
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub paths: Vec<LintOverride>,
+
+    /// Rules written in CUE or Rego
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub custom: Vec<CustomRule>,
+}
+
+impl LintConfig {
+    /// Whether nothing differs from the defaults
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// How a lint rule is applied: a level, or a level with rule options
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RuleSetting {
+    /// Just the level, e.g. `max-depth: error`
+    Level(RuleLevel),
+
+    /// A level with options, e.g. `max-depth: { level: error, max: 6 }`
+    Options {
+        level: RuleLevel,
+        #[serde(flatten)]
+        options: BTreeMap<String, serde_json::Value>,
+    },
+}
+
+impl RuleSetting {
+    /// The level the rule reports at
+    pub fn level(&self) -> RuleLevel {
+        match self {
+            Self::Level(level) | Self::Options { level, .. } => *level,
+        }
+    }
+}
+
+/// Severity of a lint rule's findings, or `off`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleLevel {
+    Off,
+    Info,
+    Warning,
+    #[default]
+    Error,
+}
+
+/// Lint rule settings for the files matching some globs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LintOverride {
+    /// Glob patterns, relative to the project root
+    pub files: Vec<String>,
+
+    /// Rule settings for those files
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub rules: BTreeMap<String, RuleSetting>,
+
+    /// JSON Schema the files follow, for the `enum-case` and
+    /// `missing-defaults` rules
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<PathBuf>,
+}
+
+/// A lint rule written as a CUE schema or a Rego policy
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CustomRule {
+    /// Rule id, shown in reports
+    pub name: String,
+
+    /// CUE file every document must unify with (run with `cue vet`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cue: Option<PathBuf>,
+
+    /// Rego policy whose `deny` rule lists violations (run with `opa eval`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rego: Option<PathBuf>,
+
+    /// Only files matching these globs (default: every linted file)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<String>,
+
+    /// Severity of the rule's findings
+    #[serde(default)]
+    pub level: RuleLevel,
+}
+
+/// The Kubernetes cluster manifests are deployed to
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct KubernetesConfig {
+    /// Cluster version to check API deprecations against, e.g. "1.29"
+    /// (default: the newest release with known removals)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+
+    /// Manifests to check (default: every YAML and JSON file outside hidden directories)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<String>,
+}
+
+impl KubernetesConfig {
+    /// Whether nothing differs from the defaults
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// How commands walking the project (analyze, scan, inventory) treat
+/// symbolic links, git submodules and vendored directories
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WalkConfig {
+    /// What to do on a symbolic link
+    #[serde(default)]
+    pub symlinks: SymlinkPolicy,
+
+    /// Walk into git submodules and nested repositories
+    #[serde(default)]
+    pub submodules: bool,
+
+    /// Directory names skipped at any depth
+    #[serde(default = "default_vendor_dirs")]
+    pub vendor: Vec<String>,
+}
+
+impl Default for WalkConfig {
+    fn default() -> Self {
+        Self {
+            symlinks: SymlinkPolicy::default(),
+            submodules: false,
+            vendor: default_vendor_dirs(),
+        }
+    }
+}
+
+impl WalkConfig {
+    /// The `walk:` section of the pipeline in `dir`, or the defaults
+    pub fn load(dir: &Path) -> Self {
+        Pipeline::from_file(&dir.join(".conflow.yaml"))
+            .map(|p| p.walk)
+            .unwrap_or_default()
+    }
+
+    /// Whether nothing differs from the defaults
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Whether a directory name is a vendored directory
+    pub fn is_vendor(&self, name: &str) -> bool {
+        self.vendor.iter().any(|v| v == name)
+    }
+}
+
+fn default_vendor_dirs() -> Vec<String> {
+    [
+        "node_modules",
+        "bower_components",
+        "vendor",
+        "third_party",
+        ".terraform",
+        ".terragrunt-cache",
+        ".venv",
+    ]
+    .map(String::from)
+    .to_vec()
+}
+
+/// Values kept out of output
+///
+/// See [`crate::utils::redact`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RedactConfig {
+    /// JSONPath locations in config documents whose values are masked
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub paths: Vec<String>,
+
+    /// Environment variables whose values are masked
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub env: Vec<String>,
+
+    /// Regexes of text to mask; with a capture group, only the group is
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub patterns: Vec<String>,
+}
+
+impl RedactConfig {
+    /// The `redact:` section of the pipeline in `dir`, or none
+    pub fn load(dir: &Path) -> Self {
+        Pipeline::from_file(&dir.join(".conflow.yaml"))
+            .map(|p| p.redact)
+            .unwrap_or_default()
+    }
+
+    /// Whether nothing is redacted
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// JSONPath locations of a version in one file: a path or a list of them
+///
+/// Read and rewritten by `conflow version`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum VersionPaths {
+    /// Single location, e.g. `$.package.version`
+    Single(String),
+
+    /// Several locations, e.g. `["$.version", "$.appVersion"]`
+    Multiple(Vec<String>),
+}
+
+impl VersionPaths {
+    /// The JSONPath locations
+    pub fn paths(&self) -> Vec<&str> {
+        match self {
+            Self::Single(path) => vec![path.as_str()],
+            Self::Multiple(paths) => paths.iter().map(|p| p.as_str()).collect(),
+        }
+    }
+}
+
+/// What walking a project does on a symbolic link
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SymlinkPolicy {
+    /// Follow it, visiting each directory once (default)
+    #[default]
+    Follow,
+    /// Leave it out
+    Skip,
+    /// Fail the command
+    Error,
+}
+
+/// Telemetry export configuration
+///
+/// Environment variables (`OTEL_EXPORTER_OTLP_ENDPOINT`, `OTEL_SERVICE_NAME`,
+/// `OTEL_EXPORTER_OTLP_HEADERS`) take precedence over these values.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TelemetryConfig {
+    /// OTLP/HTTP collector endpoint (e.g. http://localhost:4318)
+    #[serde(default)]
+    pub endpoint: Option<String>,
+
+    /// Service name reported to the collector
+    #[serde(default)]
+    pub service_name: Option<String>,
+
+    /// Extra headers sent with each export
+    #[serde(default, serialize_with = "crate::report::sorted")]
+    pub headers: HashMap<String, String>,
+}
+
+impl TelemetryConfig {
+    /// Whether nothing is configured
+    pub fn is_empty(&self) -> bool {
+        self.endpoint.is_none() && self.service_name.is_none() && self.headers.is_empty()
+    }
+}
+
+/// A pinned tool version
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ToolPin {
+    /// Version, e.g. "0.9.2" (a partial "0.9" matches the newest patch)
+    pub version: String,
+
+    /// SHA-256 of the release asset per platform (e.g. linux-x86_64)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty", serialize_with = "crate::report::sorted")]
+    pub checksums: HashMap<String, String>,
+}
+
+/// Sandbox configuration for stage commands
+///
+/// When enabled, commands run without network access, with a read-only
+/// view of the filesystem except the working directory, and under the
+/// given resource limits.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SandboxConfig {
+    /// Run stage commands in the sandbox
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Allow network access
+    #[serde(default)]
+    pub network: bool,
+
+    /// Extra writable paths, relative to the working directory
+    #[serde(default)]
+    pub writable: Vec<PathBuf>,
+
+    /// Resource limits
+    #[serde(default)]
+    pub limits: ResourceLimits,
+}
+
+impl SandboxConfig {
+    /// Whether nothing differs from the defaults
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Per-process resource limits (applied with setrlimit)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ResourceLimits {
+    /// CPU time in seconds
+    #[serde(default)]
+    pub cpu_seconds: Option<u64>,
+
+    /// Address space in megabytes
+    #[serde(default)]
+    pub memory_mb: Option<u64>,
+
+    /// Largest file a command may write, in megabytes
+    #[serde(default)]
+    pub file_size_mb: Option<u64>,
+
+    /// Open file descriptors
+    #[serde(default)]
+    pub open_files: Option<u64>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -435,6 +1552,81 @@ stages:
         }
     }
 
+    #[test]
+    fn test_parse_custom_stage() {
+        let yaml = r#"
+version: "1"
+name: "custom"
+executors:
+  policy:
+    command: "policy-check {args} {inputs}"
+    success_exit_codes: [0, 2]
+stages:
+  - name: "check"
+    tool:
+      type: custom
+      executor: policy
+      args: ["--strict"]
+    input: "*.yaml"
+"#;
+
+        let pipeline = Pipeline::from_yaml(yaml).unwrap();
+        assert_eq!(pipeline.stages[0].tool_name(), "policy");
+        match &pipeline.stages[0].tool {
+            Tool::Custom {
+                args, definition, ..
+            } => {
+                assert_eq!(args, &vec!["--strict".to_string()]);
+                let definition = definition.as_ref().unwrap();
+                assert_eq!(definition.shell, "bash");
+                assert_eq!(definition.success_exit_codes, vec![0, 2]);
+            }
+            _ => panic!("Expected custom tool"),
+        }
+    }
+
+    #[test]
+    fn test_stage_condition() {
+        let yaml = r#"
+version: "1"
+name: "conditional"
+stages:
+  - name: "deploy"
+    tool:
+      type: shell
+      command: "true"
+    input: "*.yaml"
+    when:
+      env_equals:
+        var: TARGET
+        value: prod
+  - name: "smoke"
+    tool:
+      type: shell
+      command: "true"
+    input: "*.yaml"
+    when:
+      env_set: SMOKE
+"#;
+
+        let pipeline = Pipeline::from_yaml(yaml).unwrap();
+        let condition = pipeline.stages[0].condition.as_ref().unwrap();
+        let dir = std::path::Path::new(".");
+
+        let mut env = HashMap::new();
+        assert!(!condition.is_met(dir, &env));
+        env.insert("TARGET".to_string(), "prod".to_string());
+        assert!(condition.is_met(dir, &env));
+        assert_eq!(condition.to_string(), "env_equals(TARGET=prod)");
+
+        assert!(StageCondition::FileExists("Cargo.toml".into()).is_met(dir, &env));
+        assert!(matches!(
+            pipeline.stages[1].condition,
+            Some(StageCondition::EnvSet(ref var)) if var == "SMOKE"
+        ));
+        assert!(!StageCondition::Never.is_met(dir, &env));
+    }
+
     #[test]
     fn test_parse_from_stage_input() {
         let yaml = r#"
@@ -484,14 +1676,37 @@ stages:
                     out_format: None,
                 },
                 input: Input::Single("*.json".into()),
+                exclude: vec![],
                 output: None,
+                snapshot: false,
+                contract: None,
                 depends_on: vec![],
                 allow_failure: false,
                 env: HashMap::new(),
                 condition: None,
+                env_file: None,
+                workdir: None,
+                shard: None,
+                runs_on: Vec::new(),
+                manual: false,
             }],
             env: HashMap::new(),
+            env_file: None,
+            secrets: vec![],
             cache: CacheConfig::default(),
+            telemetry: TelemetryConfig::default(),
+            plugins: vec![],
+            executors: HashMap::new(),
+            toolchain: HashMap::new(),
+            sandbox: SandboxConfig::default(),
+            notifications: vec![],
+            refs: vec![],
+            lint: LintConfig::default(),
+            kubernetes: KubernetesConfig::default(),
+            walk: WalkConfig::default(),
+            redact: RedactConfig::default(),
+            versions: BTreeMap::new(),
+            templates: vec![],
         };
 
         let yaml = pipeline.to_yaml().unwrap();
@@ -500,4 +1715,75 @@ stages:
         assert_eq!(parsed.name, pipeline.name);
         assert_eq!(parsed.stages.len(), pipeline.stages.len());
     }
+
+    #[test]
+    fn test_parse_notifications() {
+        let yaml = r#"
+name: "notify"
+notifications:
+  - type: slack
+    url: "${SLACK_WEBHOOK}"
+  - type: webhook
+    url: "https://ci.example.com/hook"
+    on: [compliance_change, recovery]
+    template: '{"text": "{{headline}}"}'
+stages:
+  - name: "check"
+    tool: { type: shell, command: "true" }
+    input: "*"
+"#;
+
+        let pipeline = Pipeline::from_yaml(yaml).unwrap();
+        let [slack, webhook] = &pipeline.notifications[..] else {
+            panic!("expected two channels");
+        };
+
+        assert_eq!(slack.kind, NotificationKind::Slack);
+        assert_eq!(slack.on, vec![NotifyEvent::Failure, NotifyEvent::Regression]);
+        assert_eq!(webhook.kind, NotificationKind::Webhook);
+        assert_eq!(
+            webhook.on,
+            vec![NotifyEvent::ComplianceChange, NotifyEvent::Recovery]
+        );
+        assert!(webhook.template.is_some());
+    }
+
+    #[test]
+    fn test_cache_scope() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let project = CacheConfig::default();
+        assert_eq!(
+            project.path(temp.path()).unwrap(),
+            temp.path().join(".conflow/cache")
+        );
+
+        let global = CacheConfig {
+            scope: CacheScope::Global,
+            ..CacheConfig::default()
+        };
+        let a = temp.path().join("a");
+        let b = temp.path().join("b");
+        std::fs::create_dir(&a).unwrap();
+        std::fs::create_dir(&b).unwrap();
+        let (path_a, path_b) = (global.path(&a).unwrap(), global.path(&b).unwrap());
+        assert_ne!(path_a, path_b);
+        assert_eq!(path_a.parent(), path_b.parent());
+        assert!(path_a.file_name().unwrap().to_string_lossy().starts_with("a-"));
+        assert_eq!(path_a, global.path(&a.join("..").join("a")).unwrap());
+
+        let shared = CacheConfig {
+            namespace: Some("schemas".into()),
+            ..global
+        };
+        assert_eq!(shared.path(&a).unwrap(), shared.path(&b).unwrap());
+        assert!(shared.path(&a).unwrap().ends_with("schemas"));
+
+        for namespace in ["", ".", "..", "../other", "a/b", "a\\b", "/tmp"] {
+            let escaping = CacheConfig {
+                namespace: Some(namespace.into()),
+                ..shared.clone()
+            };
+            assert!(escaping.path(&a).is_err(), "accepted {:?}", namespace);
+        }
+    }
 }
@@ -0,0 +1,240 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Stage environment and working directory
+//!
+//! Variables are merged with increasing precedence:
+//!
+//! 1. the process environment (inherited by every command)
+//! 2. the pipeline `env_file`
+//! 3. the pipeline `env`
+//! 4. the stage `env_file`
+//! 5. the stage `env`
+//!
+//! Values of variables listed under `secrets:` are masked wherever stage
+//! output, plans, or reports are shown.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::errors::ConflowError;
+use crate::pipeline::{Pipeline, Stage};
+
+/// Replacement shown for secret values
+pub const MASK: &str = "***";
+
+/// Secrets shorter than this are not masked, to avoid garbling output
+const MIN_SECRET_LEN: usize = 4;
+
+/// Parse dotenv content (`KEY=value`, optional `export`, quotes, comments)
+pub fn parse_env_file(content: &str) -> Result<Vec<(String, String)>, String> {
+    let mut vars = Vec::new();
+
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(format!("line {}: expected KEY=value", i + 1));
+        };
+
+        let key = key.trim();
+        if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(format!("line {}: invalid variable name '{}'", i + 1, key));
+        }
+
+        let value = value.trim();
+        let value = match value.chars().next() {
+            Some(quote @ ('"' | '\'')) if value.len() >= 2 && value.ends_with(quote) => {
+                let inner = &value[1..value.len() - 1];
+                if quote == '"' {
+                    inner.replace("\\n", "\n").replace("\\\"", "\"")
+                } else {
+                    inner.to_string()
+                }
+            }
+            // Unquoted values may carry a trailing comment
+            _ => value
+                .split(" #")
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .to_string(),
+        };
+
+        vars.push((key.to_string(), value));
+    }
+
+    Ok(vars)
+}
+
+/// Load a dotenv file relative to `base_dir`
+pub fn load_env_file(path: &Path, base_dir: &Path) -> Result<Vec<(String, String)>, ConflowError> {
+    let full = base_dir.join(path);
+    let content = std::fs::read_to_string(&full).map_err(|e| ConflowError::FileReadError {
+        path: full.clone(),
+        error: e.to_string(),
+    })?;
+
+    parse_env_file(&content).map_err(|e| ConflowError::FileReadError {
+        path: full,
+        error: e,
+    })
+}
+
+/// Variables a stage sets on top of the process environment
+pub fn stage_env(
+    pipeline: &Pipeline,
+    stage: &Stage,
+    base_dir: &Path,
+) -> Result<HashMap<String, String>, ConflowError> {
+    let mut env = HashMap::new();
+
+    if let Some(ref file) = pipeline.env_file {
+        env.extend(load_env_file(file, base_dir)?);
+    }
+    env.extend(pipeline.env.clone());
+
+    if let Some(ref file) = stage.env_file {
+        env.extend(load_env_file(file, base_dir)?);
+    }
+    env.extend(stage.env.clone());
+
+    Ok(env)
+}
+
+/// Directory a stage runs in
+pub fn stage_workdir(stage: &Stage, base_dir: &Path) -> PathBuf {
+    match stage.workdir {
+        Some(ref dir) => base_dir.join(dir),
+        None => base_dir.to_path_buf(),
+    }
+}
+
+/// Masks values of declared secret variables
+#[derive(Debug, Clone, Default)]
+pub struct SecretMasker {
+    names: Vec<String>,
+    values: Vec<String>,
+}
+
+impl SecretMasker {
+    /// Collect secret values from the stage and process environment
+    pub fn new(secrets: &[String], env: &HashMap<String, String>) -> Self {
+        let mut values: Vec<String> = secrets
+            .iter()
+            .filter_map(|name| env.get(name).cloned().or_else(|| std::env::var(name).ok()))
+            .filter(|v| v.len() >= MIN_SECRET_LEN)
+            .collect();
+        // Longest first, so a secret containing another is fully masked
+        values.sort_by_key(|v| std::cmp::Reverse(v.len()));
+        values.dedup();
+
+        Self {
+            names: secrets.to_vec(),
+            values,
+        }
+    }
+
+    /// Whether a variable is declared secret
+    pub fn is_secret(&self, name: &str) -> bool {
+        self.names.iter().any(|n| n == name)
+    }
+
+    /// Replace secret and redacted values in text
+    pub fn mask(&self, text: &str) -> String {
+        let text = self.values.iter().fold(text.to_string(), |text, secret| {
+            text.replace(secret.as_str(), MASK)
+        });
+        crate::report::redact(&text)
+    }
+
+    /// Mask a variable's value for display
+    pub fn mask_var(&self, name: &str, value: &str) -> String {
+        if self.is_secret(name) {
+            MASK.to_string()
+        } else {
+            self.mask(value)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_env_file() {
+        let content =
+            "# comment\nexport A=1\nB = \"two words\"\nC='$literal'\nD=plain # note\nEMPTY=\n";
+        let vars = parse_env_file(content).unwrap();
+
+        assert_eq!(
+            vars,
+            vec![
+                ("A".into(), "1".into()),
+                ("B".into(), "two words".into()),
+                ("C".into(), "$literal".into()),
+                ("D".into(), "plain".into()),
+                ("EMPTY".into(), "".into()),
+            ]
+        );
+        assert!(parse_env_file("not a pair").is_err());
+        assert!(parse_env_file("BAD-NAME=1").is_err());
+    }
+
+    #[test]
+    fn test_env_precedence() {
+        let temp = tempfile::TempDir::new().unwrap();
+        std::fs::write(temp.path().join("global.env"), "A=file\nB=file\nC=file\n").unwrap();
+        std::fs::write(
+            temp.path().join("stage.env"),
+            "B=stage-file\nC=stage-file\n",
+        )
+        .unwrap();
+
+        let yaml = r#"
+name: env
+env_file: global.env
+env:
+  A: pipeline
+  B: pipeline
+stages:
+  - name: s
+    tool: { type: shell, command: "true" }
+    input: "*"
+    env_file: stage.env
+    env:
+      C: stage
+    workdir: sub
+"#;
+        let pipeline = Pipeline::from_yaml(yaml).unwrap();
+        let stage = &pipeline.stages[0];
+        let env = stage_env(&pipeline, stage, temp.path()).unwrap();
+
+        assert_eq!(env["A"], "pipeline");
+        assert_eq!(env["B"], "stage-file");
+        assert_eq!(env["C"], "stage");
+        assert_eq!(stage_workdir(stage, temp.path()), temp.path().join("sub"));
+    }
+
+    #[test]
+    fn test_secret_masking() {
+        let env = HashMap::from([
+            ("TOKEN".to_string(), "s3cr3t-value".to_string()),
+            ("PIN".to_string(), "42".to_string()),
+            ("USER".to_string(), "alice".to_string()),
+        ]);
+        let masker = SecretMasker::new(&["TOKEN".into(), "PIN".into()], &env);
+
+        assert_eq!(
+            masker.mask("auth s3cr3t-value failed for alice (42)"),
+            "auth *** failed for alice (42)"
+        );
+        assert_eq!(masker.mask_var("PIN", "42"), MASK);
+        assert_eq!(masker.mask_var("USER", "alice"), "alice");
+    }
+}
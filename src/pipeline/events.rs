@@ -0,0 +1,34 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Execution events
+//!
+//! Progress notifications emitted by the pipeline executor, for frontends
+//! that render their own view of a run (e.g. the TUI dashboard).
+
+use std::time::Duration;
+
+use crate::executors::ExecutionResult;
+
+/// Progress event from a pipeline run
+#[derive(Debug, Clone)]
+pub enum ExecutionEvent {
+    /// Stages that will run, in execution order
+    Planned { stages: Vec<String> },
+    /// A stage started executing
+    StageStarted { stage: String },
+    /// A stage was skipped because its condition was not met
+    StageSkipped { stage: String, reason: String },
+    /// A stage was satisfied from cache
+    StageCached {
+        stage: String,
+        result: ExecutionResult,
+    },
+    /// A stage finished executing
+    StageFinished {
+        stage: String,
+        result: ExecutionResult,
+    },
+    /// The run finished
+    PipelineFinished { success: bool, duration: Duration },
+}
@@ -5,18 +5,30 @@
 //!
 //! Orchestrates the execution of pipeline stages in dependency order.
 
-use std::collections::HashMap;
-use std::path::Path;
-use std::sync::Arc;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
+use async_trait::async_trait;
 use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::UnboundedSender;
 use tokio::sync::RwLock;
+use tracing::Instrument;
 
-use crate::cache::Cache;
+use crate::cache::{Cache, StageTimings};
+use crate::diagnostics::render_tool_output;
 use crate::errors::ConflowError;
-use crate::executors::{ExecutionResult, Executor};
-use crate::pipeline::{DagBuilder, Pipeline, Stage};
+use crate::executors::{resolve_inputs, ExecutionResult, Executor};
+use crate::i18n::{tr, tr_with};
+use crate::pipeline::plan::skip_reason;
+use crate::pipeline::{
+    check_contract, check_snapshots, stage_env, stage_workdir, Approvals, CacheStatus, DagBuilder,
+    ExecutionEvent, ExecutionPlan, Pipeline, PlannedStage, Profile, SecretMasker, SpanKind, Stage,
+};
+use crate::utils::redact::Redactor;
+use crate::utils::{PipelineProgress, ProgressMode};
 
 /// Pipeline execution options
 #[derive(Debug, Clone, Default)]
@@ -29,25 +41,57 @@ pub struct ExecutionOptions {
     pub stages: Vec<String>,
     /// Verbose output
     pub verbose: bool,
+    /// Record a timing profile of the run
+    pub profile: bool,
+    /// Approvals for manual stages
+    pub approvals: Approvals,
 }
 
 /// Result of executing a pipeline
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PipelineResult {
     /// Results for each stage
+    #[serde(serialize_with = "crate::report::sorted")]
     pub results: HashMap<String, ExecutionResult>,
     /// Total execution time
+    #[serde(rename = "duration_ms", with = "crate::report::duration_ms")]
     pub duration: Duration,
     /// Whether all stages succeeded
     pub success: bool,
+    /// Timing profile, when requested (written separately with `--profile`)
+    #[serde(skip)]
+    pub profile: Option<Profile>,
+}
+
+/// Runs stages with `runs_on` labels somewhere else, e.g. on remote agents
+#[async_trait]
+pub trait Dispatcher: Send + Sync {
+    /// Run `stage` on a runner offering its labels, with its files in
+    /// `working_dir` and output files put back there
+    async fn dispatch(
+        &self,
+        pipeline: &Pipeline,
+        stage: &Stage,
+        working_dir: &Path,
+        env: &HashMap<String, String>,
+        resolved_inputs: Option<&[PathBuf]>,
+    ) -> Result<ExecutionResult, ConflowError>;
 }
 
 /// Pipeline executor
 pub struct PipelineExecutor {
     /// Registered executors by tool name
-    executors: HashMap<String, Box<dyn Executor>>,
+    executors: HashMap<String, Arc<dyn Executor>>,
     /// Cache layer
     cache: Option<Arc<RwLock<Box<dyn Cache>>>>,
+    /// Progress event sinks
+    events: Vec<UnboundedSender<ExecutionEvent>>,
+    /// Suppress console output
+    quiet: bool,
+    /// Stage durations of past runs, for progress estimates
+    timings: Option<Mutex<StageTimings>>,
+    /// Runner of stages with `runs_on` labels
+    dispatcher: Option<Arc<dyn Dispatcher>>,
 }
 
 impl PipelineExecutor {
@@ -56,12 +100,16 @@ impl PipelineExecutor {
         Self {
             executors: HashMap::new(),
             cache: None,
+            events: Vec::new(),
+            quiet: false,
+            timings: None,
+            dispatcher: None,
         }
     }
 
     /// Register an executor for a tool
     pub fn register_executor(&mut self, name: &str, executor: Box<dyn Executor>) {
-        self.executors.insert(name.to_string(), executor);
+        self.executors.insert(name.to_string(), Arc::from(executor));
     }
 
     /// Set the cache layer
@@ -70,7 +118,38 @@ impl PipelineExecutor {
         self
     }
 
+    /// Estimate progress from, and record durations into, past stage timings
+    pub fn with_timings(mut self, timings: StageTimings) -> Self {
+        self.timings = Some(Mutex::new(timings));
+        self
+    }
+
+    /// Run stages with `runs_on` labels through `dispatcher`
+    pub fn with_dispatcher(mut self, dispatcher: Arc<dyn Dispatcher>) -> Self {
+        self.dispatcher = Some(dispatcher);
+        self
+    }
+
+    /// Send progress events to a channel, in addition to any set before
+    pub fn with_events(mut self, sender: UnboundedSender<ExecutionEvent>) -> Self {
+        self.events.push(sender);
+        self
+    }
+
+    /// Suppress console output (for frontends consuming events)
+    pub fn quiet(mut self) -> Self {
+        self.quiet = true;
+        self
+    }
+
+    fn emit(&self, event: ExecutionEvent) {
+        for sender in &self.events {
+            let _ = sender.send(event.clone());
+        }
+    }
+
     /// Execute a pipeline
+    #[tracing::instrument(name = "pipeline", skip_all, fields(pipeline = %pipeline.name))]
     pub async fn execute(
         &self,
         pipeline: &Pipeline,
@@ -78,6 +157,10 @@ impl PipelineExecutor {
         options: &ExecutionOptions,
     ) -> Result<PipelineResult, ConflowError> {
         let start = Instant::now();
+        let mut profile = options.profile.then(Profile::new);
+        if !pipeline.redact.is_default() {
+            crate::report::set_redactor(Redactor::new(&pipeline.redact, &HashMap::new()));
+        }
 
         // Build and validate DAG
         let dag = DagBuilder::build(pipeline)?;
@@ -93,116 +176,406 @@ impl PipelineExecutor {
                 .collect()
         };
 
-        // Print execution plan
-        self.print_execution_plan(pipeline, &stages_to_run, &dag)?;
-
         if options.dry_run {
+            let plan = self.plan(pipeline, &stages_to_run, working_dir, options).await;
+            if !self.quiet {
+                plan.print(working_dir);
+            }
             return Ok(PipelineResult {
                 results: HashMap::new(),
                 duration: start.elapsed(),
                 success: true,
+                profile,
             });
         }
 
+        // Print execution plan
+        if !self.quiet {
+            self.print_execution_plan(pipeline, &stages_to_run, &dag)?;
+        }
+        self.emit(ExecutionEvent::Planned {
+            stages: stages_to_run
+                .iter()
+                .map(|&idx| pipeline.stages[idx].name.clone())
+                .collect(),
+        });
+
+        let mode = if self.quiet {
+            ProgressMode::Quiet
+        } else {
+            ProgressMode::detect()
+        };
+        let planned: Vec<_> = stages_to_run
+            .iter()
+            .map(|&idx| {
+                let name = &pipeline.stages[idx].name;
+                let estimate = self.timings.as_ref().and_then(|t| lock(t).estimate(name));
+                (name.clone(), estimate)
+            })
+            .collect();
+        let progress = PipelineProgress::new(mode, &planned);
+
         // Execute stages in order
         let mut results = HashMap::new();
+        let mut skipped = HashSet::new();
         let mut all_success = true;
 
-        // Merge global and stage environments
-        let global_env = &pipeline.env;
-
         for idx in stages_to_run {
             let stage = &pipeline.stages[idx];
-
-            // Merge environments (stage overrides global)
-            let mut env = global_env.clone();
-            env.extend(stage.env.clone());
+            let stage_start = Instant::now();
+            let span = tracing::info_span!("stage", stage = %stage.name, tool = stage.tool_name());
+
+            // Stage settings override pipeline settings
+            let env = stage_env(pipeline, stage, working_dir)?;
+            let masker = SecretMasker::new(&pipeline.masked_vars(), &env);
+            let stage_dir = stage_workdir(stage, working_dir);
+
+            if let Some(reason) = skip_reason(stage, working_dir, &env, &skipped) {
+                let note = tr_with("run.skipped", &[("reason", &reason)]);
+                progress.finish(
+                    &stage.name,
+                    format!("  {} {} {}", "○".dimmed(), stage.name.bold(), note.dimmed()),
+                );
+                tracing::debug!(parent: &span, reason, "stage skipped");
+                self.emit(ExecutionEvent::StageSkipped {
+                    stage: stage.name.clone(),
+                    reason,
+                });
+                skipped.insert(stage.name.clone());
+                continue;
+            }
 
             // Try cache first
-            if !options.no_cache {
+            if !options.no_cache && stage.is_cacheable() {
                 if let Some(ref cache) = self.cache {
+                    let lookup_start = Instant::now();
                     let cache_read = cache.read().await;
-                    if let Ok(Some(cached)) = cache_read.get(stage).await {
-                        println!("  {} {} {}", "✓".green(), stage.name.bold(), "(cached)".dimmed());
+                    let cached = cache_read.get(stage).instrument(span.clone()).await;
+                    if let Some(ref mut p) = profile {
+                        p.record("cache lookup", SpanKind::Cache, lookup_start);
+                    }
+
+                    if let Err(ref e) = cached {
+                        tracing::warn!(parent: &span, error = %e, "cache lookup failed");
+                    }
+
+                    if let Ok(Some(cached)) = cached {
+                        let (icon, note) = ("✓".green(), tr("run.cached").dimmed());
+                        progress.finish(
+                            &stage.name,
+                            format!("  {} {} {}", icon, stage.name.bold(), note),
+                        );
+                        self.emit(ExecutionEvent::StageCached {
+                            stage: stage.name.clone(),
+                            result: cached.clone(),
+                        });
+                        if let Some(ref mut p) = profile {
+                            p.record(&stage.name, SpanKind::Stage, stage_start);
+                        }
                         results.insert(stage.name.clone(), cached);
                         continue;
                     }
                 }
             }
 
+            if stage.manual {
+                progress.suspend(|| options.approvals.confirm(stage, !self.quiet))?;
+            }
+
             // Execute stage
-            print!("  {} {}...", "→".blue(), stage.name);
+            self.emit(ExecutionEvent::StageStarted {
+                stage: stage.name.clone(),
+            });
 
-            let result = self
-                .execute_stage(stage, working_dir, &env, &results)
-                .await?;
+            let process_start = Instant::now();
+            let running = self
+                .execute_stage(pipeline, stage, &stage_dir, &env, &results)
+                .instrument(span.clone());
+            let mut result = progress.run(&stage.name, running).await?;
+            result.stdout = masker.mask(&result.stdout);
+            result.stderr = masker.mask(&result.stderr);
+
+            // A broken contract or a changed snapshot fails the stage
+            let mut violation = None;
+            if result.success && stage.contract.is_some() {
+                violation = check_contract(stage, &result, &stage_dir, working_dir)?;
+                if let Some(ref violation) = violation {
+                    result.success = false;
+                    result.stderr.push_str(&violation.message);
+                }
+            }
+            let mut mismatches = Vec::new();
+            if result.success && stage.snapshot {
+                mismatches = check_snapshots(stage, &result, &stage_dir, working_dir)?;
+                if !mismatches.is_empty() {
+                    result.success = false;
+                    for mismatch in &mismatches {
+                        result.stderr.push_str(&mismatch.diff);
+                    }
+                }
+            }
+
+            if let Some(ref mut p) = profile {
+                p.record(stage.tool_name(), SpanKind::Process, process_start);
+            }
+            self.emit(ExecutionEvent::StageFinished {
+                stage: stage.name.clone(),
+                result: result.clone(),
+            });
+
+            if let Some(ref timings) = self.timings {
+                lock(timings).record(&stage.name, result.duration);
+            }
 
             // Print result
             if result.success {
-                println!(
-                    "\r  {} {} ({:.2}s)",
-                    "✓".green(),
-                    stage.name.bold(),
-                    result.duration.as_secs_f64()
+                progress.finish(
+                    &stage.name,
+                    format!(
+                        "  {} {} ({:.2}s)",
+                        "✓".green(),
+                        stage.name.bold(),
+                        result.duration.as_secs_f64()
+                    ),
                 );
 
                 // Cache successful result
-                if !options.no_cache {
+                if !options.no_cache && stage.is_cacheable() {
                     if let Some(ref cache) = self.cache {
-                        let mut cache_write = cache.write().await;
-                        let _ = cache_write.store(stage, &result).await;
+                        let store_start = Instant::now();
+                        let cache_write = cache.write().await;
+                        if let Err(e) = cache_write
+                            .store(stage, &result)
+                            .instrument(span.clone())
+                            .await
+                        {
+                            tracing::warn!(parent: &span, error = %e, "failed to store cache entry");
+                        }
+                        if let Some(ref mut p) = profile {
+                            p.record("cache store", SpanKind::Cache, store_start);
+                        }
                     }
                 }
             } else {
-                println!("\r  {} {} failed", "✗".red(), stage.name.bold());
+                progress.finish(
+                    &stage.name,
+                    format!(
+                        "  {} {}",
+                        "✗".red(),
+                        tr_with("run.stage_failed", &[("stage", &stage.name.bold())])
+                    ),
+                );
 
-                if options.verbose {
-                    if !result.stderr.is_empty() {
-                        eprintln!("{}", result.stderr.dimmed());
-                    }
+                if let Some(violation) = violation.as_ref().filter(|_| !self.quiet) {
+                    progress.suspend(|| eprint!("{}", violation.render()));
+                } else if !self.quiet && !mismatches.is_empty() {
+                    progress.suspend(|| {
+                        for mismatch in &mismatches {
+                            eprint!("{}", mismatch.render());
+                        }
+                    });
+                } else if !self.quiet && options.verbose {
+                    let output = format!("{}\n{}", result.stdout, result.stderr);
+                    progress.suspend(|| {
+                        match render_tool_output(stage.tool_name(), &output, &stage_dir) {
+                            Some(rendered) => eprintln!("{}", rendered),
+                            None if !result.stderr.is_empty() => {
+                                eprintln!("{}", result.stderr.dimmed())
+                            }
+                            None => {}
+                        }
+                    });
                 }
 
                 if !stage.allow_failure {
+                    if let Some(ref mut p) = profile {
+                        p.record(&stage.name, SpanKind::Stage, stage_start);
+                    }
                     all_success = false;
                     results.insert(stage.name.clone(), result);
                     break;
                 }
             }
 
+            if let Some(ref mut p) = profile {
+                p.record(&stage.name, SpanKind::Stage, stage_start);
+            }
             results.insert(stage.name.clone(), result);
         }
 
+        progress.clear();
+        if let Some(ref timings) = self.timings {
+            if let Err(e) = lock(timings).save() {
+                tracing::warn!(error = %e, "failed to save stage timings");
+            }
+        }
+
         let duration = start.elapsed();
+        if let Some(ref mut p) = profile {
+            p.record(&pipeline.name, SpanKind::Pipeline, start);
+        }
+
+        self.emit(ExecutionEvent::PipelineFinished {
+            success: all_success,
+            duration,
+        });
 
         // Print summary
-        println!();
-        if all_success {
-            println!(
-                "{}",
-                format!("Pipeline completed successfully in {:.2}s", duration.as_secs_f64()).green()
-            );
-        } else {
-            println!(
-                "{}",
-                format!("Pipeline failed after {:.2}s", duration.as_secs_f64()).red()
-            );
+        if !self.quiet {
+            println!();
+            if all_success {
+                let seconds = format!("{:.2}", duration.as_secs_f64());
+                println!("{}", tr_with("run.completed", &[("seconds", &seconds)]).green());
+            } else {
+                let seconds = format!("{:.2}", duration.as_secs_f64());
+                println!("{}", tr_with("run.failed_after", &[("seconds", &seconds)]).red());
+            }
         }
 
         Ok(PipelineResult {
             results,
             duration,
             success: all_success,
+            profile,
         })
     }
 
+    /// Plan a run without executing anything
+    async fn plan(
+        &self,
+        pipeline: &Pipeline,
+        stages: &[usize],
+        working_dir: &Path,
+        options: &ExecutionOptions,
+    ) -> ExecutionPlan {
+        let mut planned = Vec::new();
+        let mut skipped = HashSet::new();
+        // Outputs of stages known from cache, and stages that would execute
+        let mut cached_outputs: HashMap<String, Vec<PathBuf>> = HashMap::new();
+        let mut executing = HashSet::new();
+
+        for &idx in stages {
+            let stage = &pipeline.stages[idx];
+            let stage_dir = stage_workdir(stage, working_dir);
+            let (env, problem) = match stage_env(pipeline, stage, working_dir) {
+                Ok(env) => (env, None),
+                Err(e) => (HashMap::new(), Some(e.to_string())),
+            };
+            let masker = SecretMasker::new(&pipeline.masked_vars(), &env);
+
+            let mut entry = PlannedStage {
+                name: stage.name.clone(),
+                tool: stage.tool_name().to_string(),
+                manual: stage.manual,
+                skipped: skip_reason(stage, working_dir, &env, &skipped),
+                cache: CacheStatus::Disabled,
+                command: vec![],
+                inputs: vec![],
+                env: env
+                    .iter()
+                    .map(|(k, v)| (k.clone(), masker.mask_var(k, v)))
+                    .collect(),
+                problem,
+            };
+
+            if entry.skipped.is_some() {
+                skipped.insert(stage.name.clone());
+                planned.push(entry);
+                continue;
+            }
+
+            // Upstream outputs come from cache, or the declared output path
+            let upstream = stage.input.references_stage();
+            let resolved: Option<Vec<PathBuf>> = upstream.map(|from| {
+                cached_outputs.get(from).cloned().unwrap_or_else(|| {
+                    pipeline
+                        .get_stage(from)
+                        .and_then(|s| {
+                            let output = s.output.as_ref()?;
+                            Some(vec![stage_workdir(s, working_dir).join(output.path())])
+                        })
+                        .unwrap_or_default()
+                })
+            });
+
+            entry.inputs = match resolved {
+                Some(ref files) => files.clone(),
+                None => {
+                    resolve_inputs(stage, &stage_dir).unwrap_or_else(|e| {
+                        entry.problem.get_or_insert(e.to_string());
+                        vec![]
+                    })
+                }
+            };
+
+            entry.cache = match self.cache {
+                _ if options.no_cache || !stage.is_cacheable() => CacheStatus::Disabled,
+                None => CacheStatus::Disabled,
+                Some(_) if upstream.is_some_and(|u| executing.contains(u)) => CacheStatus::Unknown,
+                Some(ref cache) => match cache.read().await.get(stage).await {
+                    Ok(Some(hit)) => {
+                        cached_outputs.insert(stage.name.clone(), hit.outputs);
+                        CacheStatus::Hit
+                    }
+                    Ok(None) => CacheStatus::Miss,
+                    Err(e) => {
+                        entry.problem.get_or_insert(e.to_string());
+                        CacheStatus::Miss
+                    }
+                },
+            };
+
+            match self.executors.get(stage.tool_name()) {
+                Some(executor) => {
+                    match executor.command_line(stage, &stage_dir, resolved.as_deref()) {
+                        Ok(command) => {
+                            entry.command = command.iter().map(|a| masker.mask(a)).collect()
+                        }
+                        Err(e) => {
+                            entry.problem.get_or_insert(e.to_string());
+                        }
+                    }
+                }
+                None => {
+                    entry.problem = Some(format!("No executor for '{}'", stage.tool_name()));
+                }
+            }
+
+            if entry.would_execute() {
+                executing.insert(stage.name.clone());
+            }
+            planned.push(entry);
+        }
+
+        ExecutionPlan {
+            pipeline: pipeline.name.clone(),
+            stages: planned,
+        }
+    }
+
     /// Execute a single stage
     async fn execute_stage(
         &self,
+        pipeline: &Pipeline,
         stage: &Stage,
         working_dir: &Path,
         env: &HashMap<String, String>,
         previous_results: &HashMap<String, ExecutionResult>,
     ) -> Result<ExecutionResult, ConflowError> {
+        if !stage.runs_on.is_empty() {
+            let dispatcher = self.dispatcher.as_ref().ok_or_else(|| ConflowError::Dispatch {
+                stage: stage.name.clone(),
+                message: format!(
+                    "it runs on agents labelled {} and no coordinator is set",
+                    stage.runs_on.join(", ")
+                ),
+            })?;
+            let resolved_input = self.resolve_stage_input(stage, previous_results)?;
+            return dispatcher
+                .dispatch(pipeline, stage, working_dir, env, resolved_input.as_deref())
+                .await;
+        }
+
         let tool_name = stage.tool_name();
 
         let executor = self.executors.get(tool_name).ok_or_else(|| {
@@ -212,7 +585,20 @@ impl PipelineExecutor {
         })?;
 
         // Resolve stage input if it references another stage
-        let resolved_input = self.resolve_stage_input(stage, previous_results)?;
+        let mut resolved_input = self.resolve_stage_input(stage, previous_results)?;
+
+        if let Some(shard) = stage.shard {
+            let files = match resolved_input {
+                Some(files) => files,
+                None => resolve_inputs(stage, working_dir)?,
+            };
+            let shards = shard.count(files.len());
+            if shards > 1 {
+                return execute_shards(executor, stage, working_dir, env, files, shards).await;
+            }
+            resolved_input = Some(files);
+        }
+        tracing::debug!(inputs = ?resolved_input, "executing stage");
 
         executor
             .execute(stage, working_dir, env, resolved_input.as_deref())
@@ -281,8 +667,13 @@ impl PipelineExecutor {
     pub async fn check_tools(&self, pipeline: &Pipeline) -> Result<Vec<String>, ConflowError> {
         let mut missing = Vec::new();
 
-        let tool_names: std::collections::HashSet<_> =
-            pipeline.stages.iter().map(|s| s.tool_name()).collect();
+        // Agents bring the tools of stages they run
+        let tool_names: std::collections::HashSet<_> = pipeline
+            .stages
+            .iter()
+            .filter(|s| s.runs_on.is_empty())
+            .map(|s| s.tool_name())
+            .collect();
 
         for tool in tool_names {
             if let Some(executor) = self.executors.get(tool) {
@@ -300,8 +691,60 @@ impl PipelineExecutor {
     }
 }
 
+/// Run a stage's tool on `shards` slices of its input files at once
+///
+/// Shard outputs are merged in file order, so diagnostics read as if the
+/// tool had checked every file in one go.
+async fn execute_shards(
+    executor: &Arc<dyn Executor>,
+    stage: &Stage,
+    working_dir: &Path,
+    env: &HashMap<String, String>,
+    files: Vec<PathBuf>,
+    shards: usize,
+) -> Result<ExecutionResult, ConflowError> {
+    let start = Instant::now();
+    let size = files.len().div_ceil(shards);
+    tracing::debug!(files = files.len(), shards, "executing stage in shards");
+
+    let mut running = tokio::task::JoinSet::new();
+    for (index, chunk) in files.chunks(size).enumerate() {
+        let executor = Arc::clone(executor);
+        let stage = stage.clone();
+        let working_dir = working_dir.to_path_buf();
+        let env = env.clone();
+        let chunk = chunk.to_vec();
+        running.spawn(
+            async move {
+                let result = executor.execute(&stage, &working_dir, &env, Some(&chunk)).await;
+                (index, result)
+            }
+            .in_current_span(),
+        );
+    }
+
+    let mut results = Vec::new();
+    while let Some(joined) = running.join_next().await {
+        let (index, result) = joined.map_err(|e| ConflowError::ExecutionFailed {
+            message: format!("Shard of stage '{}' panicked: {}", stage.name, e),
+            help: None,
+        })?;
+        results.push((index, result?));
+    }
+    results.sort_by_key(|(index, _)| *index);
+
+    Ok(ExecutionResult::merged(
+        results.into_iter().map(|(_, result)| result).collect(),
+        start.elapsed(),
+    ))
+}
+
 impl Default for PipelineExecutor {
     fn default() -> Self {
         Self::new()
     }
 }
+
+fn lock(timings: &Mutex<StageTimings>) -> std::sync::MutexGuard<'_, StageTimings> {
+    timings.lock().unwrap_or_else(|e| e.into_inner())
+}
@@ -0,0 +1,255 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Impact analysis
+//!
+//! Everything downstream of an edit to one file: config files referencing
+//! it (through the [`ConfigGraph`]), the pipeline stages that read any of
+//! them, what those stages render, and the environments they belong to or
+//! deploy to.
+
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::path::{Component, Path, PathBuf};
+
+use super::{affected_stages, Pipeline, Tool};
+use crate::analyzer::ConfigGraph;
+use crate::errors::ConflowError;
+
+/// Directories whose subdirectories are named after environments, as in
+/// `overlays/prod` or `environments/staging.ncl`
+const ENVIRONMENT_DIRS: &[&str] = &["clusters", "environments", "envs", "overlays"];
+
+/// What an edit to a file affects
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Impact {
+    /// The edited file, relative to the project root
+    pub file: PathBuf,
+    /// Config files referencing it, directly or transitively
+    pub configs: Vec<PathBuf>,
+    /// Stages reading the file or those configs, and stages downstream of
+    /// them, in pipeline order
+    pub stages: Vec<String>,
+    /// Outputs those stages render
+    pub artifacts: Vec<PathBuf>,
+    /// Environments named by the affected files' paths
+    pub environments: Vec<String>,
+    /// Affected deployment stages and their targets
+    pub deployments: Vec<Deployment>,
+}
+
+/// A deployment stage an edit reaches
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Deployment {
+    pub stage: String,
+    /// Where it deploys, e.g. `kubectl context prod, namespace web`
+    pub target: String,
+}
+
+impl Impact {
+    /// Whether nothing but the file itself is affected
+    pub fn is_empty(&self) -> bool {
+        self.configs.is_empty() && self.stages.is_empty() && self.environments.is_empty()
+    }
+
+    /// Environments named by the file's, configs' and artifacts' paths
+    fn environments_of_paths(&self) -> Vec<String> {
+        let names: BTreeSet<String> = std::iter::once(&self.file)
+            .chain(&self.configs)
+            .chain(&self.artifacts)
+            .flat_map(|path| environment_names(path))
+            .collect();
+        names.into_iter().collect()
+    }
+}
+
+/// Environments a path belongs to: the entries under [`ENVIRONMENT_DIRS`]
+/// on its way, with `environments/prod.ncl` naming `prod` too
+pub(super) fn environment_names(path: &Path) -> Vec<String> {
+    let parts: Vec<&str> = path
+        .components()
+        .filter_map(|c| match c {
+            Component::Normal(part) => part.to_str(),
+            _ => None,
+        })
+        .collect();
+    parts
+        .windows(2)
+        .filter(|pair| ENVIRONMENT_DIRS.contains(&pair[0]))
+        .filter_map(|pair| Path::new(pair[1]).file_stem()?.to_str().map(String::from))
+        .collect()
+}
+
+/// Work out what editing `file` affects
+///
+/// `file` is relative to `root`. Without a pipeline only configs and
+/// environments are reported.
+pub fn impact(
+    file: &Path,
+    pipeline: Option<&Pipeline>,
+    root: &Path,
+) -> Result<Impact, ConflowError> {
+    let graph = ConfigGraph::scan(root)?;
+    let configs = graph.affected_by(file);
+
+    let mut impact = Impact {
+        file: file.to_path_buf(),
+        configs,
+        ..Impact::default()
+    };
+    let Some(pipeline) = pipeline else {
+        impact.environments = impact.environments_of_paths();
+        return Ok(impact);
+    };
+
+    let changed: Vec<PathBuf> = std::iter::once(&impact.file)
+        .chain(&impact.configs)
+        .map(|path| root.join(path))
+        .collect();
+    impact.stages = affected_stages(pipeline, &changed, root)?;
+
+    let mut artifacts = BTreeSet::new();
+    for stage in impact
+        .stages
+        .iter()
+        .filter_map(|name| pipeline.get_stage(name))
+    {
+        if let Some(ref output) = stage.output {
+            artifacts.insert(output.path().clone());
+        }
+        if let Some(target) = deploy_target(&stage.tool) {
+            impact.deployments.push(Deployment {
+                stage: stage.name.clone(),
+                target,
+            });
+        }
+    }
+    impact.artifacts = artifacts.into_iter().collect();
+    impact.environments = impact.environments_of_paths();
+
+    Ok(impact)
+}
+
+/// Where a deployment stage deploys to
+fn deploy_target(tool: &Tool) -> Option<String> {
+    let target = match tool {
+        Tool::Kubectl {
+            context, namespace, ..
+        } => {
+            let mut target = match context {
+                Some(context) => format!("kubectl context {}", context),
+                None => "kubectl current context".to_string(),
+            };
+            if let Some(namespace) = namespace {
+                target.push_str(&format!(", namespace {}", namespace));
+            }
+            target
+        }
+        Tool::Helm {
+            release, namespace, ..
+        } => match namespace {
+            Some(namespace) => format!("helm release {} in {}", release, namespace),
+            None => format!("helm release {}", release),
+        },
+        Tool::Terraform { dir, .. } => match dir {
+            Some(dir) => format!("terraform {}", dir.display()),
+            None => "terraform".to_string(),
+        },
+        _ => return None,
+    };
+    Some(target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_impact() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let root = temp.path();
+        let write = |path: &str, content: &str| {
+            let path = root.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        };
+        write("base/kustomization.yaml", "resources: [deployment.yaml]\n");
+        write("base/deployment.yaml", "kind: Deployment\n");
+        write(
+            "overlays/prod/kustomization.yaml",
+            "resources: [../../base]\n",
+        );
+        write(
+            "overlays/staging/kustomization.yaml",
+            "resources: [../../base]\n",
+        );
+        write("other.yaml", "a: 1\n");
+
+        let pipeline = Pipeline::from_yaml(
+            r#"
+name: impact
+stages:
+  - name: render-prod
+    tool: { type: shell, command: "kustomize build overlays/prod" }
+    input: "overlays/prod/*.yaml"
+    output: dist/prod.yaml
+  - name: deploy-prod
+    tool: { type: kubectl, action: apply, context: prod, namespace: web }
+    input: { from_stage: render-prod }
+    depends_on: [render-prod]
+  - name: lint-other
+    tool: { type: shell, command: "true" }
+    input: other.yaml
+"#,
+        )
+        .unwrap();
+
+        let base = impact(Path::new("base/deployment.yaml"), Some(&pipeline), root).unwrap();
+        assert_eq!(base.configs.len(), 3);
+        assert_eq!(base.stages, vec!["render-prod", "deploy-prod"]);
+        assert_eq!(base.artifacts, vec![PathBuf::from("dist/prod.yaml")]);
+        assert_eq!(base.environments, vec!["prod", "staging"]);
+        assert_eq!(
+            base.deployments,
+            vec![Deployment {
+                stage: "deploy-prod".into(),
+                target: "kubectl context prod, namespace web".into(),
+            }]
+        );
+
+        let other = impact(Path::new("other.yaml"), None, root).unwrap();
+        assert!(other.is_empty());
+        assert!(other.stages.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_impact_with_link_cycle() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let root = temp.path();
+        std::fs::create_dir_all(root.join("base")).unwrap();
+        std::fs::write(
+            root.join("base/kustomization.yaml"),
+            "resources: [deployment.yaml]\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("base/deployment.yaml"), "kind: Deployment\n").unwrap();
+        std::os::unix::fs::symlink(".", root.join("l1")).unwrap();
+        std::os::unix::fs::symlink("l1", root.join("l2")).unwrap();
+        std::os::unix::fs::symlink("l2", root.join("l3")).unwrap();
+
+        let pipeline = Pipeline::from_yaml(
+            r#"
+name: impact
+stages:
+  - name: render
+    tool: { type: shell, command: "kustomize build base" }
+    input: "**/*.yaml"
+"#,
+        )
+        .unwrap();
+        let base = impact(Path::new("base/deployment.yaml"), Some(&pipeline), root).unwrap();
+        assert_eq!(base.configs, vec![PathBuf::from("base/kustomization.yaml")]);
+        assert_eq!(base.stages, vec!["render"]);
+    }
+}
@@ -0,0 +1,404 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Config inventory
+//!
+//! A machine-readable list of every configuration file in a repository,
+//! in the spirit of an SBOM: its format and digest, the schemas it is
+//! checked against, the stages reading it and whether they passed on its
+//! current content, and the team owning it according to CODEOWNERS.
+
+use regex::Regex;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use super::changes::stage_touched;
+use super::impact::environment_names;
+use super::{stage_workdir, Pipeline, Tool};
+use crate::analyzer::{detect_format, ConfigFormat};
+use crate::cache::{hash_string, ParseCache};
+use crate::errors::ConflowError;
+use crate::utils::codeowners::CodeOwners;
+#[cfg(feature = "native")]
+use crate::utils::git;
+use crate::utils::ignore::Ignore;
+use crate::utils::walk::Walker;
+
+/// Version of the inventory format
+pub const INVENTORY_VERSION: u32 = 1;
+
+/// Globs of the files inventoried outside a git repository
+const CONFIG_FILES: &[&str] = &[
+    "**/*.json",
+    "**/*.yaml",
+    "**/*.yml",
+    "**/*.toml",
+    "**/*.cue",
+    "**/*.ncl",
+];
+
+/// Directory of run state: locks, cost estimates, notification state
+const STATE_DIR: &str = ".conflow";
+
+/// Schema modelines: `# yaml-language-server: $schema=...` and taplo's
+/// `#:schema ...`
+static SCHEMA_MODELINE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?m)^\s*#\s*(?:yaml-language-server:\s*\$schema=|:schema\s+)(\S+)").unwrap()
+});
+
+/// Every config file of a repository
+#[derive(Debug, Clone, Serialize)]
+pub struct Inventory {
+    /// Version of this format
+    pub version: u32,
+    /// conflow release that wrote it
+    pub generator: String,
+    /// Name of the pipeline, when there is one
+    pub pipeline: Option<String>,
+    pub artifacts: Vec<Artifact>,
+    pub summary: InventorySummary,
+}
+
+/// One config file
+#[derive(Debug, Clone, Serialize)]
+pub struct Artifact {
+    /// Path relative to the project root
+    pub path: PathBuf,
+    pub format: ConfigFormat,
+    /// BLAKE3 digest of the content
+    pub digest: String,
+    /// Schemas it is checked against: those of CUE stages reading it,
+    /// `$schema` keys and editor modelines
+    pub schemas: Vec<String>,
+    /// Stages reading it, in pipeline order
+    pub stages: Vec<String>,
+    pub validation: Validation,
+    /// Why it is invalid
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Owners from CODEOWNERS
+    pub owners: Vec<String>,
+    /// Environments named by its path
+    pub environments: Vec<String>,
+}
+
+/// Whether a config file is known to be valid
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Validation {
+    /// Every cacheable stage reading it passed on its current content
+    Valid,
+    /// It doesn't parse
+    Invalid,
+    /// Stages read it but haven't passed since it last changed
+    Pending,
+    /// No stage that could check it reads it
+    Unvalidated,
+}
+
+impl std::fmt::Display for Validation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Valid => "valid",
+            Self::Invalid => "invalid",
+            Self::Pending => "pending",
+            Self::Unvalidated => "unvalidated",
+        })
+    }
+}
+
+/// Counts across an inventory
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct InventorySummary {
+    pub total: usize,
+    pub by_format: BTreeMap<String, usize>,
+    pub by_validation: BTreeMap<Validation, usize>,
+    /// Files checked against no schema
+    pub without_schema: usize,
+    /// Files CODEOWNERS assigns no owner
+    pub unowned: usize,
+}
+
+impl InventorySummary {
+    fn new(artifacts: &[Artifact]) -> Self {
+        let mut summary = Self {
+            total: artifacts.len(),
+            ..Self::default()
+        };
+        for artifact in artifacts {
+            let format = serde_json::to_value(artifact.format).unwrap_or_default();
+            *summary
+                .by_format
+                .entry(format.as_str().unwrap_or_default().to_string())
+                .or_default() += 1;
+            *summary
+                .by_validation
+                .entry(artifact.validation)
+                .or_default() += 1;
+            summary.without_schema += usize::from(artifact.schemas.is_empty());
+            summary.unowned += usize::from(artifact.owners.is_empty());
+        }
+        summary
+    }
+}
+
+/// Inventory the config files of a project
+///
+/// `patterns` narrows the files (default: every JSON, YAML, TOML, CUE and
+/// Nickel file git doesn't ignore). `passed` names the stages with a cached
+/// successful result for their current inputs.
+pub fn inventory(
+    root: &Path,
+    patterns: &[String],
+    pipeline: Option<&Pipeline>,
+    passed: &HashSet<String>,
+) -> Result<Inventory, ConflowError> {
+    let files = config_files(root, patterns, pipeline)?;
+    let owners = CodeOwners::load(root).unwrap_or_default();
+
+    let mut artifacts = Vec::new();
+    for path in files {
+        let Ok(content) = std::fs::read_to_string(root.join(&path)) else {
+            continue;
+        };
+        let mut artifact = Artifact {
+            format: detect_format(&content, &path).unwrap_or(ConfigFormat::Unknown),
+            digest: format!("blake3:{}", hash_string(&content)),
+            schemas: Vec::new(),
+            stages: Vec::new(),
+            validation: Validation::Unvalidated,
+            error: None,
+            owners: owners.owners(&path).to_vec(),
+            environments: environment_names(&path),
+            path,
+        };
+        let mut schemas = BTreeSet::new();
+        match parse(&artifact, &content) {
+            Ok(schema) => schemas.extend(schema),
+            Err(e) => {
+                artifact.validation = Validation::Invalid;
+                artifact.error = Some(e);
+            }
+        }
+        schemas.extend(
+            SCHEMA_MODELINE
+                .captures_iter(&content)
+                .map(|c| c[1].to_string()),
+        );
+
+        let mut checked_by = 0;
+        let mut pending = false;
+        for stage in pipeline.map(|p| p.stages.as_slice()).unwrap_or_default() {
+            if !stage_touched(stage, std::slice::from_ref(&artifact.path)) {
+                continue;
+            }
+            artifact.stages.push(stage.name.clone());
+            if let Tool::Cue { schemas: cue, .. } = &stage.tool {
+                let dir = stage_workdir(stage, root);
+                schemas.extend(cue.iter().map(|s| {
+                    let schema = dir.join(s);
+                    let schema = schema.strip_prefix(root).unwrap_or(&schema);
+                    schema.display().to_string()
+                }));
+            }
+            if stage.is_cacheable() {
+                checked_by += 1;
+                pending |= !passed.contains(&stage.name);
+            }
+        }
+        // A schema file is read by the stages checking against it
+        schemas.remove(&artifact.path.display().to_string());
+        artifact.schemas = schemas.into_iter().collect();
+
+        if artifact.validation != Validation::Invalid && checked_by > 0 {
+            artifact.validation = match pending {
+                true => Validation::Pending,
+                false => Validation::Valid,
+            };
+        }
+        artifacts.push(artifact);
+    }
+
+    Ok(Inventory {
+        version: INVENTORY_VERSION,
+        generator: format!("conflow {}", env!("CARGO_PKG_VERSION")),
+        pipeline: pipeline.map(|p| p.name.clone()),
+        summary: InventorySummary::new(&artifacts),
+        artifacts,
+    })
+}
+
+/// The config files of a project, relative to `root`
+///
+/// `patterns` narrows the files (default: every JSON, YAML, TOML, CUE and
+/// Nickel file git doesn't ignore). conflow's own state and what the
+/// pipeline's `walk:` settings and `.conflowignore` exclude are left out.
+pub(super) fn config_files(
+    root: &Path,
+    patterns: &[String],
+    pipeline: Option<&Pipeline>,
+) -> Result<Vec<PathBuf>, ConflowError> {
+    let walk = pipeline.map(|p| p.walk.clone()).unwrap_or_default();
+    let walker = Walker::new(walk, Ignore::load(root));
+    let files = match patterns.is_empty() {
+        false => crate::lint::matching_files(patterns, root)?,
+        true => match tracked_files(root, walker.config().submodules) {
+            Ok(files) => files.into_iter().filter(|f| is_config(f)).collect(),
+            Err(_) => walker
+                .files(root)?
+                .into_iter()
+                .filter_map(|f| Some(f.strip_prefix(root).ok()?.to_path_buf()))
+                .filter(|f| is_config(f))
+                .collect(),
+        },
+    };
+    // conflow's own state isn't configuration
+    let state: Vec<&Path> = std::iter::once(Path::new(STATE_DIR))
+        .chain(pipeline.map(|p| p.cache.directory.as_path()))
+        .collect();
+    let mut kept = Vec::new();
+    for file in files {
+        if !state.iter().any(|dir| file.starts_with(dir)) && walker.keeps(root, &file)? {
+            kept.push(file);
+        }
+    }
+    kept.sort();
+    kept.dedup();
+    Ok(kept)
+}
+
+/// Files git tracks in `root`, and in its submodules with `submodules`
+#[cfg(feature = "native")]
+fn tracked_files(root: &Path, submodules: bool) -> Result<Vec<PathBuf>, ConflowError> {
+    let mut files = git::listed_files(root)?;
+    if submodules {
+        for module in git::submodules(root).unwrap_or_default() {
+            let listed = git::listed_files(&root.join(&module)).unwrap_or_default();
+            files.extend(listed.into_iter().map(|f| module.join(f)));
+        }
+    }
+    Ok(files)
+}
+
+/// Without git the project is walked instead
+#[cfg(not(feature = "native"))]
+fn tracked_files(_root: &Path, _submodules: bool) -> Result<Vec<PathBuf>, ConflowError> {
+    Err(ConflowError::Git {
+        message: "git is not available in this build".into(),
+    })
+}
+
+/// Whether a path has the extension of a config format
+fn is_config(path: &Path) -> bool {
+    CONFIG_FILES.iter().any(|glob| {
+        path.extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|ext| glob.ends_with(&format!(".{}", ext)))
+    })
+}
+
+/// Parse a data file, returning the `$schema` it declares
+///
+/// CUE and Nickel files aren't parsed: that needs their tools.
+fn parse(artifact: &Artifact, content: &str) -> Result<Option<String>, String> {
+    let root = match artifact.format {
+        ConfigFormat::Json | ConfigFormat::Yaml => {
+            let documents = ParseCache::global()
+                .documents(&artifact.path, content)
+                .map_err(|e| e.to_string())?;
+            documents.first().cloned().unwrap_or_default()
+        }
+        ConfigFormat::Toml => {
+            let value: toml::Value = toml::from_str(content).map_err(|e| e.to_string())?;
+            serde_json::to_value(value).unwrap_or_default()
+        }
+        _ => return Ok(None),
+    };
+    Ok(root["$schema"].as_str().map(String::from))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inventory() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let root = temp.path();
+        let write = |path: &str, content: &str| {
+            let path = root.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        };
+        write("CODEOWNERS", "*.cue @org/schemas\nconfig/ @org/platform\n");
+        write("schemas/app.cue", "#App: {replicas: int}\n");
+        write("config/app.yaml", "replicas: 2\n");
+        write(
+            "config/environments/prod.json",
+            r#"{"$schema": "app.json", "replicas": 3}"#,
+        );
+        write(
+            "deploy/values.yaml",
+            "# yaml-language-server: $schema=https://example.com/values.json\nimage: web\n",
+        );
+        write("broken.toml", "name = \n");
+
+        let pipeline = Pipeline::from_yaml(
+            r#"
+name: inventory
+stages:
+  - name: validate
+    tool: { type: cue, command: vet, schemas: [schemas/app.cue] }
+    input: "config/**/*.yaml"
+  - name: prod
+    tool: { type: cue, command: vet, schemas: [schemas/app.cue] }
+    input: "config/environments/*.json"
+"#,
+        )
+        .unwrap();
+        let passed = HashSet::from(["validate".to_string()]);
+        let patterns: Vec<String> = CONFIG_FILES.iter().map(|g| g.to_string()).collect();
+        let inventory = inventory(root, &patterns, Some(&pipeline), &passed).unwrap();
+
+        let artifact = |path: &str| {
+            inventory
+                .artifacts
+                .iter()
+                .find(|a| a.path == Path::new(path))
+                .unwrap()
+        };
+        let app = artifact("config/app.yaml");
+        assert_eq!(app.format, ConfigFormat::Yaml);
+        assert_eq!(app.schemas, ["schemas/app.cue"]);
+        assert_eq!(app.stages, ["validate"]);
+        assert_eq!(app.validation, Validation::Valid);
+        assert_eq!(app.owners, ["@org/platform"]);
+
+        let prod = artifact("config/environments/prod.json");
+        assert_eq!(prod.schemas, ["app.json", "schemas/app.cue"]);
+        assert_eq!(prod.validation, Validation::Pending);
+        assert_eq!(prod.environments, ["prod"]);
+
+        let schema = artifact("schemas/app.cue");
+        assert!(schema.schemas.is_empty());
+        assert_eq!(schema.stages, ["validate", "prod"]);
+        assert_eq!(schema.owners, ["@org/schemas"]);
+
+        let values = artifact("deploy/values.yaml");
+        assert_eq!(values.schemas, ["https://example.com/values.json"]);
+        assert_eq!(values.validation, Validation::Unvalidated);
+        assert!(values.owners.is_empty());
+
+        let broken = artifact("broken.toml");
+        assert_eq!(broken.validation, Validation::Invalid);
+        assert!(broken.error.is_some());
+
+        assert_eq!(inventory.summary.total, 5);
+        assert_eq!(inventory.summary.by_format["yaml"], 2);
+        assert_eq!(inventory.summary.by_validation[&Validation::Pending], 2);
+        assert_eq!(inventory.summary.unowned, 2);
+        assert!(inventory.artifacts[0].digest.starts_with("blake3:"));
+    }
+}
@@ -0,0 +1,139 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Run lock
+//!
+//! Serializes pipeline runs in one project, so an editor hook and a manual
+//! `conflow run` don't race on the cache and on stage outputs. The lock is
+//! advisory: it only holds back other conflow processes, and it is released
+//! when the run ends, even if the process dies.
+
+use std::fs::{File, TryLockError};
+use std::io::{Read, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::errors::ConflowError;
+
+/// Lock file, relative to the working directory
+pub const RUN_LOCK: &str = ".conflow/run.lock";
+
+/// How long a run waits for another to finish by default
+pub const DEFAULT_LOCK_TIMEOUT: Duration = Duration::from_secs(300);
+
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Exclusive hold on a project's run lock, released on drop
+#[derive(Debug)]
+pub struct RunLock {
+    file: File,
+}
+
+impl RunLock {
+    /// Take the lock if no other run holds it
+    pub fn try_acquire(working_dir: &Path) -> Result<Option<Self>, ConflowError> {
+        let path = working_dir.join(RUN_LOCK);
+        let mut file = open(&path)?;
+        match file.try_lock() {
+            Ok(()) => {
+                // Record who holds it for anyone left waiting
+                let _ = file
+                    .set_len(0)
+                    .and_then(|_| write!(file, "{}", std::process::id()));
+                Ok(Some(Self { file }))
+            }
+            Err(TryLockError::WouldBlock) => Ok(None),
+            Err(TryLockError::Error(e)) => Err(lock_error(&path, e)),
+        }
+    }
+
+    /// Take the lock, waiting up to `timeout` for another run to finish
+    ///
+    /// `on_wait` is called once, with the other run's process id if known,
+    /// when the lock is busy.
+    pub fn acquire(
+        working_dir: &Path,
+        timeout: Duration,
+        on_wait: impl FnOnce(Option<u32>),
+    ) -> Result<Self, ConflowError> {
+        if let Some(lock) = Self::try_acquire(working_dir)? {
+            return Ok(lock);
+        }
+        on_wait(Self::holder(working_dir));
+
+        let start = Instant::now();
+        loop {
+            std::thread::sleep(POLL_INTERVAL.min(timeout));
+            if let Some(lock) = Self::try_acquire(working_dir)? {
+                return Ok(lock);
+            }
+            if start.elapsed() >= timeout {
+                return Err(ConflowError::RunLocked {
+                    path: working_dir.join(RUN_LOCK),
+                    seconds: timeout.as_secs(),
+                });
+            }
+        }
+    }
+
+    /// Process id of the run holding the lock, if recorded
+    pub fn holder(working_dir: &Path) -> Option<u32> {
+        let mut content = String::new();
+        File::open(working_dir.join(RUN_LOCK))
+            .ok()?
+            .read_to_string(&mut content)
+            .ok()?;
+        content.trim().parse().ok()
+    }
+}
+
+impl Drop for RunLock {
+    fn drop(&mut self) {
+        let _ = self.file.set_len(0);
+        let _ = self.file.unlock();
+    }
+}
+
+fn open(path: &Path) -> Result<File, ConflowError> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| lock_error(path, e))?;
+    }
+    File::options()
+        .create(true)
+        .truncate(false)
+        .read(true)
+        .write(true)
+        .open(path)
+        .map_err(|e| lock_error(path, e))
+}
+
+fn lock_error(path: &Path, error: std::io::Error) -> ConflowError {
+    ConflowError::Io {
+        message: format!("Failed to lock {}: {}", path.display(), error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_second_run_waits() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let first = RunLock::try_acquire(temp.path()).unwrap().unwrap();
+        assert_eq!(RunLock::holder(temp.path()), Some(std::process::id()));
+        assert!(RunLock::try_acquire(temp.path()).unwrap().is_none());
+
+        let mut waited = None;
+        let error = RunLock::acquire(temp.path(), Duration::from_millis(50), |pid| {
+            waited = Some(pid)
+        })
+        .unwrap_err();
+        assert_eq!(waited, Some(Some(std::process::id())));
+        assert!(matches!(error, ConflowError::RunLocked { .. }));
+
+        drop(first);
+        assert_eq!(RunLock::holder(temp.path()), None);
+        let _second = RunLock::acquire(temp.path(), Duration::ZERO, |_| panic!("free")).unwrap();
+    }
+}
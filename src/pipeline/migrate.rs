@@ -0,0 +1,407 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Pipeline schema migrations
+//!
+//! Each step rewrites a `.conflow.yaml` document from one schema version to
+//! the next, so files written for an older layout can be upgraded with
+//! `conflow migrate` instead of edited by hand. Steps work on the raw YAML
+//! value because old layouts no longer deserialize into [`Pipeline`], and
+//! record each rewrite as a [`Change`] so the file can be rewritten
+//! in place, keeping its comments. Files the editor can't place a change in
+//! are re-serialized instead.
+//!
+//! [`Pipeline`]: super::Pipeline
+
+use serde_yaml::{Mapping, Value};
+
+use crate::errors::ConflowError;
+use crate::utils::yaml::edit::{Change, Editor};
+
+/// Schema version written by this conflow
+pub const CURRENT_VERSION: u32 = 1;
+
+/// A rewrite from `from` to `from + 1`
+struct Step {
+    from: u32,
+    apply: fn(&mut Mapping, &mut Vec<String>, &mut Vec<Change>),
+}
+
+/// Migration steps, oldest first
+const STEPS: &[Step] = &[Step {
+    from: 0,
+    apply: v0_to_v1,
+}];
+
+/// Result of migrating a pipeline file
+#[derive(Debug, Clone)]
+pub struct Migration {
+    /// Version the file was written for
+    pub from: u32,
+    /// Version after migration
+    pub to: u32,
+    /// Human-readable description of each rewrite
+    pub changes: Vec<String>,
+    /// Original file content
+    pub original: String,
+    /// Migrated file content
+    pub migrated: String,
+}
+
+impl Migration {
+    /// Whether the file needs rewriting
+    pub fn is_needed(&self) -> bool {
+        !self.changes.is_empty()
+    }
+
+    /// Unified diff from the original to the migrated content
+    pub fn diff(&self, file: &str) -> String {
+        similar::TextDiff::from_lines(&self.original, &self.migrated)
+            .unified_diff()
+            .context_radius(3)
+            .header(file, file)
+            .to_string()
+    }
+}
+
+/// Migrate pipeline YAML to [`CURRENT_VERSION`]
+pub fn migrate(content: &str) -> Result<Migration, ConflowError> {
+    let mut root: Mapping = serde_yaml::from_str(content)?;
+    let from = detect_version(&root)?;
+
+    if from > CURRENT_VERSION {
+        return Err(ConflowError::InvalidPipeline {
+            reason: format!(
+                "pipeline version {} is newer than this conflow supports ({})",
+                from, CURRENT_VERSION
+            ),
+            help: Some("Upgrade conflow to read this pipeline".into()),
+        });
+    }
+
+    let mut changes = Vec::new();
+    let mut edits = Vec::new();
+    for step in STEPS.iter().filter(|s| s.from >= from) {
+        (step.apply)(&mut root, &mut changes, &mut edits);
+    }
+
+    if from < CURRENT_VERSION {
+        changes.push(format!("set version to \"{}\"", CURRENT_VERSION));
+        let path = Change::path("version");
+        let value = CURRENT_VERSION.to_string().into();
+        edits.push(match root.contains_key("version") {
+            true => Change::Set { path, value },
+            false => Change::InsertFirst { path, value },
+        });
+        root = with_version(root);
+    }
+
+    let mut editor = Editor::new(content);
+    let migrated = if changes.is_empty() {
+        content.to_string()
+    } else if editor.apply_all(0, &edits) {
+        editor.into_content()
+    } else {
+        serde_yaml::to_string(&root)?
+    };
+
+    Ok(Migration {
+        from,
+        to: CURRENT_VERSION,
+        changes,
+        original: content.to_string(),
+        migrated,
+    })
+}
+
+/// Schema version declared by a pipeline
+///
+/// Files without a `version` key predate versioning, unless they already
+/// use the current layout.
+fn detect_version(root: &Mapping) -> Result<u32, ConflowError> {
+    let invalid = |v: &str| ConflowError::InvalidPipeline {
+        reason: format!("invalid pipeline version '{}'", v),
+        help: Some(format!(
+            "Use a whole number such as version: \"{}\"",
+            CURRENT_VERSION
+        )),
+    };
+
+    match root.get("version") {
+        None if is_legacy(root) => Ok(0),
+        None => Ok(CURRENT_VERSION),
+        Some(Value::Number(n)) => n
+            .as_u64()
+            .and_then(|n| u32::try_from(n).ok())
+            .ok_or_else(|| invalid(&n.to_string())),
+        Some(Value::String(s)) => {
+            // "0.2" style versions were used before the format settled
+            let major = s.split('.').next().unwrap_or(s);
+            major.trim().parse().map_err(|_| invalid(s))
+        }
+        Some(other) => Err(invalid(serde_yaml::to_string(other)?.trim())),
+    }
+}
+
+/// Whether an unversioned file uses the pre-1 top-level layout
+///
+/// Only unambiguous markers count; a stray `schema` key in a current file
+/// is a typo, not a sign of an old pipeline.
+fn is_legacy(root: &Mapping) -> bool {
+    root.contains_key("environment") || matches!(root.get("stages"), Some(Value::Mapping(_)))
+}
+
+/// Whether content is an older pipeline that `conflow migrate` can upgrade
+pub fn needs_migration(content: &str) -> bool {
+    migrate(content).is_ok_and(|m| m.from < CURRENT_VERSION)
+}
+
+/// Move `version` to the top of the document
+fn with_version(root: Mapping) -> Mapping {
+    let mut out = Mapping::new();
+    out.insert("version".into(), CURRENT_VERSION.to_string().into());
+    for (key, value) in root {
+        if key.as_str() != Some("version") {
+            out.insert(key, value);
+        }
+    }
+    out
+}
+
+/// Rename `from` to `to` in place, unless `to` is already present
+fn rename(map: &mut Mapping, from: &str, to: &str) -> bool {
+    if map.contains_key(to) || !map.contains_key(from) {
+        return false;
+    }
+    *map = std::mem::take(map)
+        .into_iter()
+        .map(|(key, value)| match key.as_str() {
+            Some(k) if k == from => (to.into(), value),
+            _ => (key, value),
+        })
+        .collect();
+    true
+}
+
+/// Version 0: the pre-1.0 layout
+///
+/// - `stages` was a mapping keyed by stage name
+/// - `environment` held the global variables now under `env`
+/// - stages used `when` for their condition
+/// - CUE tools took a single `schema` file
+fn v0_to_v1(root: &mut Mapping, changes: &mut Vec<String>, edits: &mut Vec<Change>) {
+    if rename(root, "environment", "env") {
+        changes.push("renamed 'environment' to 'env'".into());
+        edits.push(Change::Rename {
+            path: Change::path("environment"),
+            to: "env".into(),
+        });
+    }
+
+    if let Some(Value::Mapping(named)) = root.get("stages") {
+        let stages = named
+            .iter()
+            .map(|(name, stage)| {
+                let mut stage = match stage {
+                    Value::Mapping(m) => m.clone(),
+                    _ => Mapping::new(),
+                };
+                let mut out = Mapping::new();
+                out.insert("name".into(), name.clone());
+                stage.remove("name");
+                out.extend(stage);
+                Value::Mapping(out)
+            })
+            .collect();
+        root.insert("stages".into(), Value::Sequence(stages));
+        changes.push("converted 'stages' from a mapping to a list".into());
+        edits.push(Change::ToList {
+            path: Change::path("stages"),
+            key: "name".into(),
+        });
+    }
+
+    let Some(Value::Sequence(stages)) = root.get_mut("stages") else {
+        return;
+    };
+
+    for (index, stage) in stages.iter_mut().enumerate() {
+        let Some(stage) = stage.as_mapping_mut() else {
+            continue;
+        };
+        let at = |key: &str| Change::path(&format!("stages.{}.{}", index, key));
+        let name = stage
+            .get("name")
+            .and_then(Value::as_str)
+            .unwrap_or("?")
+            .to_string();
+
+        if rename(stage, "when", "condition") {
+            changes.push(format!("stage '{}': renamed 'when' to 'condition'", name));
+            edits.push(Change::Rename {
+                path: at("when"),
+                to: "condition".into(),
+            });
+        }
+
+        let Some(Value::Mapping(tool)) = stage.get_mut("tool") else {
+            continue;
+        };
+        if tool.get("type").and_then(Value::as_str) != Some("cue") || tool.contains_key("schemas") {
+            continue;
+        }
+        if let Some(schema) = tool.remove("schema") {
+            edits.push(Change::Rename {
+                path: at("tool.schema"),
+                to: "schemas".into(),
+            });
+            let schemas = match schema {
+                Value::Sequence(list) => list,
+                single => {
+                    edits.push(Change::Set {
+                        path: at("tool.schemas"),
+                        value: Value::Sequence(vec![single.clone()]),
+                    });
+                    vec![single]
+                }
+            };
+            tool.insert("schemas".into(), Value::Sequence(schemas));
+            changes.push(format!(
+                "stage '{}': replaced 'schema' with 'schemas'",
+                name
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::Pipeline;
+
+    const LEGACY: &str = r#"name: legacy
+environment:
+  CI: "true"
+stages:
+  validate:
+    tool:
+      type: cue
+      command: vet
+      schema: schema.cue
+    input: "*.yaml"
+  generate:
+    tool:
+      type: shell
+      command: make
+    input: "*"
+    depends_on: [validate]
+    when:
+      env_set: CI
+"#;
+
+    #[test]
+    fn test_migrates_legacy_layout() {
+        let migration = migrate(LEGACY).unwrap();
+
+        assert_eq!((migration.from, migration.to), (0, CURRENT_VERSION));
+        assert!(migration.is_needed());
+        assert_eq!(migration.changes.len(), 5);
+        assert!(migration.migrated.starts_with("version: '1'\n"));
+
+        let pipeline = Pipeline::from_yaml(&migration.migrated).unwrap();
+        assert_eq!(pipeline.stage_names(), vec!["validate", "generate"]);
+        assert_eq!(pipeline.env.get("CI").map(String::as_str), Some("true"));
+        assert!(pipeline.stages[1].condition.is_some());
+        match &pipeline.stages[0].tool {
+            crate::pipeline::Tool::Cue { schemas, .. } => {
+                assert_eq!(schemas, &[std::path::PathBuf::from("schema.cue")]);
+            }
+            other => panic!("unexpected tool: {:?}", other),
+        }
+
+        let diff = migration.diff(".conflow.yaml");
+        assert!(diff.contains("-environment:"));
+        assert!(diff.contains("+env:"));
+
+        // Migrating again is a no-op
+        assert!(!migrate(&migration.migrated).unwrap().is_needed());
+    }
+
+    #[test]
+    fn test_migration_keeps_comments() {
+        let legacy = "\
+# Build pipeline, owned by the platform team
+name: legacy
+environment:
+  CI: \"true\"   # set by the runner
+stages:
+  # Schema checks come first
+  validate:
+    tool: { type: shell, command: make }
+    input: \"*.yaml\"
+  generate:
+    tool:
+      type: cue
+      command: export
+      schema: gen.cue
+    input: \"*\"
+    when:
+      env_set: CI
+";
+        let migration = migrate(legacy).unwrap();
+        assert_eq!(
+            migration.migrated,
+            "\
+# Build pipeline, owned by the platform team
+version: '1'
+name: legacy
+env:
+  CI: \"true\"   # set by the runner
+stages:
+  # Schema checks come first
+  - name: validate
+    tool: { type: shell, command: make }
+    input: \"*.yaml\"
+  - name: generate
+    tool:
+      type: cue
+      command: export
+      schemas: [\"gen.cue\"]
+    input: \"*\"
+    condition:
+      env_set: CI
+"
+        );
+
+        // A rewrite inside a flow mapping can't be placed, so the file is re-serialized
+        let flow = legacy.replace(
+            "{ type: shell, command: make }",
+            "{ type: cue, command: vet, schema: schema.cue }",
+        );
+        let migration = migrate(&flow).unwrap();
+        assert!(!migration.migrated.contains('#'));
+        assert!(Pipeline::from_yaml(&migration.migrated).is_ok());
+    }
+
+    #[test]
+    fn test_current_pipeline_unchanged() {
+        let yaml = "name: p\nstages:\n  - name: a\n    tool: { type: shell, command: \"true\" }\n    input: \"*\"\n";
+        let migration = migrate(yaml).unwrap();
+
+        assert_eq!(migration.from, CURRENT_VERSION);
+        assert!(!migration.is_needed());
+        assert_eq!(migration.migrated, yaml);
+        assert!(!needs_migration(yaml));
+        assert!(needs_migration(LEGACY));
+    }
+
+    #[test]
+    fn test_version_detection() {
+        let migration = migrate("version: \"0.3\"\nname: p\nstages: []\n").unwrap();
+        assert_eq!(migration.from, 0);
+        assert_eq!(migration.changes, vec!["set version to \"1\""]);
+
+        assert!(migrate("version: 99\nname: p\nstages: []\n").is_err());
+        assert!(migrate("version: latest\nname: p\nstages: []\n").is_err());
+    }
+}
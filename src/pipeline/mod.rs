@@ -6,12 +6,65 @@
 //! This module defines the core data structures for conflow pipelines,
 //! including stages, tools, inputs, outputs, and configuration.
 
+mod approval;
+mod builder;
+mod changes;
+#[cfg(feature = "native")]
+mod contract;
 mod dag;
 mod definition;
+mod environment;
+mod events;
+mod impact;
+mod inventory;
+#[cfg(feature = "native")]
 mod executor;
+mod lock;
+mod migrate;
+mod ownership;
+mod plan;
+mod profile;
+mod refs;
+#[cfg(feature = "native")]
+mod runs;
+#[cfg(feature = "native")]
+mod scan;
+#[cfg(feature = "native")]
+mod snapshot;
 mod validation;
+mod versions;
 
+pub use approval::{Approvals, APPROVE_ENV};
+pub use builder::{PipelineBuilder, StageBuilder};
+pub use changes::{affected_stages, with_producers};
+#[cfg(feature = "native")]
+pub use contract::{check_contract, ContractViolation};
 pub use dag::DagBuilder;
 pub use definition::*;
-pub use executor::{ExecutionOptions, PipelineExecutor, PipelineResult};
+pub use environment::{parse_env_file, stage_env, stage_workdir, SecretMasker, MASK};
+pub use events::ExecutionEvent;
+pub use impact::{impact, Deployment, Impact};
+pub use inventory::{
+    inventory, Artifact, Inventory, InventorySummary, Validation, INVENTORY_VERSION,
+};
+#[cfg(feature = "native")]
+pub use executor::{Dispatcher, ExecutionOptions, PipelineExecutor, PipelineResult};
+pub use lock::{RunLock, DEFAULT_LOCK_TIMEOUT, RUN_LOCK};
+pub use migrate::{migrate, needs_migration, Migration, CURRENT_VERSION};
+pub use ownership::Ownership;
+pub use plan::{CacheStatus, ExecutionPlan, PlannedStage};
+pub use profile::{Profile, ProfileSpan, SpanKind};
+pub use refs::{check_refs, BrokenRef};
+#[cfg(feature = "native")]
+pub use runs::{RunHistory, RunRecord, StageOutcome, StageRecord, KEEP_RUNS, RUNS_DIR};
+#[cfg(feature = "native")]
+pub use scan::{scan, ScanReport, ScanStatus, ScanSummary, ScannedFile};
+#[cfg(feature = "native")]
+pub use snapshot::{
+    accept_snapshots, check_snapshots, pending_snapshots, SnapshotMismatch, SNAPSHOTS_DIR,
+};
 pub use validation::PipelineValidator;
+pub use versions::{
+    bump_versions, check_versions, read_versions, read_versions_with, version_locations,
+    VersionLocation, DEFAULT_VERSION_LOCATIONS,
+};
@@ -0,0 +1,195 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Ownership of run findings
+//!
+//! Attributes tool diagnostics and failed stages to the CODEOWNERS owners
+//! of the files they concern, so each team sees the failures it can fix.
+
+use std::path::{Path, PathBuf};
+
+use super::{stage_workdir, Pipeline};
+use crate::executors::parsers::ToolDiagnostic;
+use crate::utils::codeowners::CodeOwners;
+
+/// Who owns what a pipeline run reports
+pub struct Ownership<'a> {
+    pipeline: &'a Pipeline,
+    pipeline_file: PathBuf,
+    root: PathBuf,
+    codeowners: CodeOwners,
+}
+
+impl<'a> Ownership<'a> {
+    /// Ownership under the CODEOWNERS file of the project at `root`
+    pub fn load(pipeline: &'a Pipeline, pipeline_file: &Path, root: &Path) -> Self {
+        Self::new(
+            pipeline,
+            pipeline_file,
+            root,
+            CodeOwners::load(root).unwrap_or_default(),
+        )
+    }
+
+    /// Ownership under the given CODEOWNERS rules
+    pub fn new(
+        pipeline: &'a Pipeline,
+        pipeline_file: &Path,
+        root: &Path,
+        codeowners: CodeOwners,
+    ) -> Self {
+        Self {
+            pipeline,
+            pipeline_file: pipeline_file
+                .strip_prefix(root)
+                .unwrap_or(pipeline_file)
+                .to_path_buf(),
+            root: root.to_path_buf(),
+            codeowners,
+        }
+    }
+
+    /// Owners of the file a stage's diagnostic points at
+    ///
+    /// A diagnostic without a location belongs to the owners of the stage.
+    pub fn of_diagnostic(&self, stage: &str, diagnostic: &ToolDiagnostic) -> Vec<String> {
+        match diagnostic.location {
+            Some(ref location) => self
+                .codeowners
+                .owners(&self.relative(stage, &location.file))
+                .to_vec(),
+            None => self.of_stage(stage, &[]),
+        }
+    }
+
+    /// Owners of a failed stage
+    ///
+    /// Those of the files its diagnostics point at, or of its input files
+    /// when it reported none. A stage reading neither belongs to the owners
+    /// of the pipeline file.
+    pub fn of_stage(&self, stage: &str, diagnostics: &[(String, ToolDiagnostic)]) -> Vec<String> {
+        let mut files: Vec<PathBuf> = diagnostics
+            .iter()
+            .filter(|(name, _)| name == stage)
+            .filter_map(|(_, d)| d.location.as_ref())
+            .map(|location| self.relative(stage, &location.file))
+            .collect();
+        if files.is_empty() {
+            files = self.inputs(stage);
+        }
+        if files.is_empty() {
+            files.push(self.pipeline_file.clone());
+        }
+        self.codeowners
+            .owners_of(files.iter().map(PathBuf::as_path))
+    }
+
+    /// Input files of a stage, relative to the project root
+    fn inputs(&self, stage: &str) -> Vec<PathBuf> {
+        let Some(stage) = self.pipeline.get_stage(stage) else {
+            return Vec::new();
+        };
+        let patterns: Vec<String> = stage
+            .input
+            .patterns()
+            .into_iter()
+            .map(String::from)
+            .collect();
+        if patterns.is_empty() {
+            return Vec::new();
+        }
+        let workdir = stage_workdir(stage, &self.root);
+        crate::lint::matching_files(&patterns, &workdir)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|file| self.relative(&stage.name, &file))
+            .collect()
+    }
+
+    /// A path reported by a stage, relative to the project root
+    fn relative(&self, stage: &str, file: &Path) -> PathBuf {
+        let file = match self
+            .pipeline
+            .get_stage(stage)
+            .and_then(|s| s.workdir.as_ref())
+        {
+            Some(workdir) if file.is_relative() => workdir.join(file),
+            _ => file.to_path_buf(),
+        };
+        file.strip_prefix(&self.root).unwrap_or(&file).to_path_buf()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executors::parsers::{Location, Severity};
+
+    #[test]
+    fn test_ownership() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("k8s")).unwrap();
+        std::fs::write(dir.path().join("k8s/app.yaml"), "kind: Service\n").unwrap();
+        std::fs::create_dir_all(dir.path().join("schemas")).unwrap();
+
+        let pipeline = Pipeline::from_yaml(
+            r#"
+version: "1"
+name: test
+stages:
+  - name: kube
+    tool: { type: shell, command: "kubeconform k8s" }
+    input: "k8s/*.yaml"
+  - name: vet
+    tool: { type: cue, command: vet }
+    input: "*.cue"
+    workdir: schemas
+  - name: gen
+    tool: { type: shell, command: "true" }
+    input: { from_stage: kube }
+"#,
+        )
+        .unwrap();
+        let codeowners = CodeOwners::parse(
+            "* @org/everyone\n/k8s/ @org/platform\n/schemas/ @org/schemas\n.conflow.yaml @org/ci\n",
+        );
+        let ownership = Ownership::new(
+            &pipeline,
+            &dir.path().join(".conflow.yaml"),
+            dir.path(),
+            codeowners,
+        );
+
+        let diagnostic = ToolDiagnostic {
+            tool: "cue".into(),
+            severity: Severity::Error,
+            message: "conflict".into(),
+            rule: None,
+            location: Some(Location {
+                file: "app.cue".into(),
+                line: Some(3),
+                column: None,
+            }),
+            related: vec![],
+        };
+        let diagnostics = vec![("vet".to_string(), diagnostic.clone())];
+
+        assert_eq!(
+            ownership.of_diagnostic("vet", &diagnostic),
+            ["@org/schemas"]
+        );
+        assert_eq!(ownership.of_stage("vet", &diagnostics), ["@org/schemas"]);
+        // Without diagnostics, a stage belongs to the owners of its inputs
+        assert_eq!(ownership.of_stage("kube", &diagnostics), ["@org/platform"]);
+        assert_eq!(ownership.of_stage("gen", &diagnostics), ["@org/ci"]);
+
+        let unlocated = ToolDiagnostic {
+            location: None,
+            ..diagnostic
+        };
+        assert_eq!(
+            ownership.of_diagnostic("kube", &unlocated),
+            ["@org/platform"]
+        );
+    }
+}
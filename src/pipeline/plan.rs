@@ -0,0 +1,226 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Dry-run planning
+//!
+//! Describes what a run would do — which stages execute or come from
+//! cache, their commands, inputs and environment — without executing
+//! anything.
+
+use colored::Colorize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use crate::executors::shell_quote;
+use crate::pipeline::Stage;
+
+/// Cache outcome a stage would have
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheStatus {
+    /// A cached result would be reused
+    Hit,
+    /// The stage would execute
+    Miss,
+    /// Caching is turned off
+    Disabled,
+    /// Depends on outputs of a stage that would execute first
+    Unknown,
+}
+
+/// One stage in a dry-run plan
+#[derive(Debug, Clone)]
+pub struct PlannedStage {
+    pub name: String,
+    pub tool: String,
+    /// Whether the stage needs approval to run
+    pub manual: bool,
+    /// Why the stage would be skipped, if it would
+    pub skipped: Option<String>,
+    pub cache: CacheStatus,
+    /// Command line that would run
+    pub command: Vec<String>,
+    pub inputs: Vec<PathBuf>,
+    /// Environment set by the pipeline and stage
+    pub env: BTreeMap<String, String>,
+    /// Problem found while planning (e.g. no input files)
+    pub problem: Option<String>,
+}
+
+impl PlannedStage {
+    /// Whether the stage would execute its command
+    pub fn would_execute(&self) -> bool {
+        self.skipped.is_none() && self.cache != CacheStatus::Hit
+    }
+}
+
+/// Result of planning a run
+#[derive(Debug, Clone)]
+pub struct ExecutionPlan {
+    pub pipeline: String,
+    pub stages: Vec<PlannedStage>,
+}
+
+impl ExecutionPlan {
+    /// Print the plan
+    pub fn print(&self, working_dir: &Path) {
+        println!();
+        println!("{}: {}", "Pipeline".bold(), self.pipeline);
+        println!("{}", "═".repeat(50));
+
+        let executing = self.stages.iter().filter(|s| s.would_execute()).count();
+        let cached = self
+            .stages
+            .iter()
+            .filter(|s| s.skipped.is_none() && s.cache == CacheStatus::Hit)
+            .count();
+        let skipped = self.stages.len() - executing - cached;
+        println!(
+            "Dry run: {} would execute, {} cached, {} skipped (nothing is run)",
+            executing, cached, skipped
+        );
+        println!();
+
+        for (i, stage) in self.stages.iter().enumerate() {
+            let status = match (&stage.skipped, stage.cache) {
+                (Some(reason), _) => format!("skipped: {}", reason).dimmed(),
+                (None, CacheStatus::Hit) => "cached".green(),
+                (None, CacheStatus::Miss) => "would run (cache miss)".yellow(),
+                (None, CacheStatus::Disabled) => "would run (cache disabled)".yellow(),
+                (None, CacheStatus::Unknown) => "would run (after upstream)".yellow(),
+            };
+            let manual = if stage.manual && stage.would_execute() {
+                " [needs approval]".magenta().to_string()
+            } else {
+                String::new()
+            };
+            println!(
+                "  {}. {} ({}) {}{}",
+                i + 1,
+                stage.name.bold(),
+                stage.tool,
+                status,
+                manual
+            );
+
+            if stage.skipped.is_some() {
+                continue;
+            }
+
+            if !stage.command.is_empty() {
+                let command: Vec<String> = stage.command.iter().map(|a| shell_quote(a)).collect();
+                println!("     {} {}", "$".dimmed(), command.join(" ").cyan());
+            }
+            if !stage.inputs.is_empty() {
+                let inputs: Vec<String> = stage
+                    .inputs
+                    .iter()
+                    .map(|p| {
+                        p.strip_prefix(working_dir)
+                            .unwrap_or(p)
+                            .display()
+                            .to_string()
+                    })
+                    .collect();
+                println!("     inputs: {}", inputs.join(", "));
+            }
+            if !stage.env.is_empty() {
+                let env: Vec<String> = stage
+                    .env
+                    .iter()
+                    .map(|(k, v)| format!("{}={}", k, v))
+                    .collect();
+                println!("     env:    {}", env.join(" "));
+            }
+            if let Some(ref problem) = stage.problem {
+                println!("     {} {}", "⚠".yellow(), problem);
+            }
+        }
+
+        println!();
+    }
+}
+
+/// Why a stage should be skipped, if it should
+///
+/// A stage is skipped when its condition is not met, or when it consumes
+/// the outputs of a stage that was skipped.
+#[cfg_attr(not(feature = "native"), allow(dead_code))]
+pub(crate) fn skip_reason(
+    stage: &Stage,
+    working_dir: &Path,
+    env: &HashMap<String, String>,
+    skipped: &HashSet<String>,
+) -> Option<String> {
+    if let Some(ref condition) = stage.condition {
+        if !condition.is_met(working_dir, env) {
+            return Some(format!("condition {} not met", condition));
+        }
+    }
+
+    stage
+        .input
+        .references_stage()
+        .filter(|s| skipped.contains(*s))
+        .map(|s| format!("input stage '{}' was skipped", s))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::{Input, StageCondition, Tool};
+
+    fn stage(name: &str, input: Input, condition: Option<StageCondition>) -> Stage {
+        Stage {
+            name: name.into(),
+            description: None,
+            tool: Tool::Shell {
+                command: "true".into(),
+                shell: "sh".into(),
+            },
+            input,
+            exclude: vec![],
+            output: None,
+            snapshot: false,
+            contract: None,
+            depends_on: vec![],
+            allow_failure: false,
+            env: HashMap::new(),
+            condition,
+            env_file: None,
+            workdir: None,
+            shard: None,
+            runs_on: Vec::new(),
+            manual: false,
+        }
+    }
+
+    #[test]
+    fn test_skip_reason() {
+        let dir = Path::new(".");
+        let env = HashMap::new();
+        let mut skipped = HashSet::new();
+
+        let gated = stage(
+            "deploy",
+            Input::Single("*.yaml".into()),
+            Some(StageCondition::EnvSet("CONFLOW_TEST_UNSET_VAR".into())),
+        );
+        assert_eq!(
+            skip_reason(&gated, dir, &env, &skipped).as_deref(),
+            Some("condition env_set(CONFLOW_TEST_UNSET_VAR) not met")
+        );
+
+        let downstream = stage(
+            "notify",
+            Input::FromStage {
+                from_stage: "deploy".into(),
+            },
+            None,
+        );
+        assert_eq!(skip_reason(&downstream, dir, &env, &skipped), None);
+        skipped.insert("deploy".to_string());
+        assert!(skip_reason(&downstream, dir, &env, &skipped)
+            .unwrap()
+            .contains("'deploy' was skipped"));
+    }
+}
@@ -0,0 +1,198 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Pipeline profiling
+//!
+//! Records a timeline of stage, cache, and subprocess spans during a run.
+//! Profiles can be written in the Chrome trace event format, which loads in
+//! `chrome://tracing`, Perfetto, and speedscope.
+
+use std::path::Path;
+use std::time::{Duration, Instant, SystemTime};
+
+use serde_json::json;
+
+use crate::errors::ConflowError;
+
+/// Kind of work a profile span covers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpanKind {
+    /// Whole pipeline run
+    Pipeline,
+    /// A single stage, including cache lookups
+    Stage,
+    /// Cache lookup or store
+    Cache,
+    /// Tool subprocess execution
+    Process,
+}
+
+impl SpanKind {
+    /// Category name used in trace output
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pipeline => "pipeline",
+            Self::Stage => "stage",
+            Self::Cache => "cache",
+            Self::Process => "process",
+        }
+    }
+}
+
+/// A timed span within a profile
+#[derive(Debug, Clone)]
+pub struct ProfileSpan {
+    /// Span name (stage name, or e.g. "cache lookup")
+    pub name: String,
+    /// What the span measures
+    pub kind: SpanKind,
+    /// Offset from the start of the profile
+    pub start: Duration,
+    /// Span duration
+    pub duration: Duration,
+}
+
+/// Timeline of a pipeline run
+#[derive(Debug, Clone)]
+pub struct Profile {
+    origin: Instant,
+    started_at: SystemTime,
+    spans: Vec<ProfileSpan>,
+}
+
+impl Profile {
+    /// Start a new profile anchored at the current instant
+    pub fn new() -> Self {
+        Self {
+            origin: Instant::now(),
+            started_at: SystemTime::now(),
+            spans: Vec::new(),
+        }
+    }
+
+    /// Record a span that started at `started` and ends now
+    pub fn record(&mut self, name: impl Into<String>, kind: SpanKind, started: Instant) {
+        self.spans.push(ProfileSpan {
+            name: name.into(),
+            kind,
+            start: started.saturating_duration_since(self.origin),
+            duration: started.elapsed(),
+        });
+    }
+
+    /// Wall-clock time the profile started
+    pub fn started_at(&self) -> SystemTime {
+        self.started_at
+    }
+
+    /// All recorded spans, in the order they finished
+    pub fn spans(&self) -> &[ProfileSpan] {
+        &self.spans
+    }
+
+    /// Stage spans sorted by duration, longest first
+    pub fn slowest_stages(&self, limit: usize) -> Vec<&ProfileSpan> {
+        let mut stages: Vec<_> = self
+            .spans
+            .iter()
+            .filter(|s| s.kind == SpanKind::Stage)
+            .collect();
+        stages.sort_by_key(|s| std::cmp::Reverse(s.duration));
+        stages.truncate(limit);
+        stages
+    }
+
+    /// Total time spent in spans of a given kind
+    pub fn total(&self, kind: SpanKind) -> Duration {
+        self.spans
+            .iter()
+            .filter(|s| s.kind == kind)
+            .map(|s| s.duration)
+            .sum()
+    }
+
+    /// Render as Chrome trace event JSON
+    pub fn to_chrome_trace(&self) -> serde_json::Value {
+        let events: Vec<_> = self
+            .spans
+            .iter()
+            .map(|span| {
+                json!({
+                    "name": span.name,
+                    "cat": span.kind.as_str(),
+                    "ph": "X",
+                    "ts": span.start.as_micros() as u64,
+                    "dur": span.duration.as_micros() as u64,
+                    "pid": 1,
+                    "tid": 1,
+                })
+            })
+            .collect();
+
+        json!({
+            "traceEvents": events,
+            "displayTimeUnit": "ms",
+        })
+    }
+
+    /// Write the Chrome trace to a file
+    pub fn write_chrome_trace(&self, path: &Path) -> Result<(), ConflowError> {
+        let content = serde_json::to_string_pretty(&self.to_chrome_trace())?;
+        std::fs::write(path, content).map_err(|e| ConflowError::Io {
+            message: format!("Failed to write profile {}: {}", path.display(), e),
+        })
+    }
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(name: &str, kind: SpanKind, start_ms: u64, dur_ms: u64) -> ProfileSpan {
+        ProfileSpan {
+            name: name.into(),
+            kind,
+            start: Duration::from_millis(start_ms),
+            duration: Duration::from_millis(dur_ms),
+        }
+    }
+
+    #[test]
+    fn test_slowest_stages() {
+        let mut profile = Profile::new();
+        profile.spans = vec![
+            span("fast", SpanKind::Stage, 0, 10),
+            span("cache lookup", SpanKind::Cache, 10, 500),
+            span("slow", SpanKind::Stage, 10, 300),
+            span("medium", SpanKind::Stage, 310, 50),
+        ];
+
+        let names: Vec<_> = profile
+            .slowest_stages(2)
+            .iter()
+            .map(|s| s.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["slow", "medium"]);
+        assert_eq!(profile.total(SpanKind::Stage), Duration::from_millis(360));
+    }
+
+    #[test]
+    fn test_chrome_trace_format() {
+        let mut profile = Profile::new();
+        profile.spans = vec![span("build", SpanKind::Process, 2, 3)];
+
+        let trace = profile.to_chrome_trace();
+        let event = &trace["traceEvents"][0];
+        assert_eq!(event["name"], "build");
+        assert_eq!(event["cat"], "process");
+        assert_eq!(event["ph"], "X");
+        assert_eq!(event["ts"], 2000);
+        assert_eq!(event["dur"], 3000);
+    }
+}
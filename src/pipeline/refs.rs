@@ -0,0 +1,516 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Cross-file reference checks
+//!
+//! Each `refs:` rule selects the entities some files define (service
+//! names, ConfigMap names) and the values other files use to refer to
+//! them, and reports references to entities that don't exist. A rule can
+//! instead require its values to be paths that exist in the repository, as
+//! with the `path` of an ArgoCD Application.
+
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Component, Path, PathBuf};
+
+use super::{Input, PathKind, Pipeline, RefRule, RefSelector, RefTarget};
+use crate::analyzer::{parse_roots, BlockLocation, Segment};
+use crate::errors::ConflowError;
+use crate::executors::resolve_globs;
+#[cfg(feature = "native")]
+use crate::utils::git;
+use crate::utils::yaml;
+
+/// A reference to an entity no file defines
+#[derive(Debug, Clone)]
+pub struct BrokenRef {
+    /// Name of the rule that was broken
+    pub rule: String,
+    /// Where the reference is made
+    pub location: BlockLocation,
+    /// The name that was referenced
+    pub value: String,
+    /// A defined entity with a similar name
+    pub suggestion: Option<String>,
+}
+
+impl std::fmt::Display for BrokenRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: '{}' is not defined ({})",
+            self.location, self.value, self.rule
+        )?;
+        if let Some(ref suggestion) = self.suggestion {
+            write!(f, " - did you mean '{}'?", suggestion)?;
+        }
+        Ok(())
+    }
+}
+
+/// Check every `refs:` rule of a pipeline
+pub fn check_refs(pipeline: &Pipeline, base_dir: &Path) -> Result<Vec<BrokenRef>, ConflowError> {
+    let mut broken = Vec::new();
+    for rule in &pipeline.refs {
+        broken.extend(check_rule(rule, pipeline, base_dir)?);
+    }
+    Ok(broken)
+}
+
+fn check_rule(
+    rule: &RefRule,
+    pipeline: &Pipeline,
+    base_dir: &Path,
+) -> Result<Vec<BrokenRef>, ConflowError> {
+    let to = match &rule.to {
+        RefTarget::Values(to) => to,
+        RefTarget::Paths(paths) => {
+            let repo = paths.repo.as_deref().map(|field| {
+                let this = origin(base_dir).map(|url| normalize_url(&url));
+                move |document: &Value| {
+                    lookup(document, field).is_none_or(|url| Some(normalize_url(&url)) == this)
+                }
+            });
+            let references = select_where(&rule.from, pipeline, base_dir, |document| {
+                repo.as_ref().is_none_or(|ours| ours(document))
+            })?;
+            return Ok(missing_paths(rule, paths.exists, references, base_dir));
+        }
+    };
+    let references = select(&rule.from, pipeline, base_dir)?;
+
+    let defined: BTreeSet<String> = select(to, pipeline, base_dir)?
+        .into_iter()
+        .map(|(_, value)| value)
+        .collect();
+
+    Ok(references
+        .into_iter()
+        .filter(|(_, value)| !defined.contains(value))
+        .map(|(location, value)| BrokenRef {
+            rule: rule.name.clone(),
+            suggestion: yaml::suggest(&value, defined.iter().map(String::as_str))
+                .map(str::to_string),
+            location,
+            value,
+        })
+        .collect())
+}
+
+/// References to paths that don't exist, suggesting siblings that do
+///
+/// Values are relative to `base_dir`; a leading `./` is ignored, as in
+/// Flux paths. URLs and absolute paths are not checked, and paths that
+/// climb out of the repository with `..` count as missing.
+fn missing_paths(
+    rule: &RefRule,
+    kind: PathKind,
+    references: Vec<(BlockLocation, String)>,
+    base_dir: &Path,
+) -> Vec<BrokenRef> {
+    let base = std::fs::canonicalize(base_dir).unwrap_or_else(|_| base_dir.to_path_buf());
+    let root = repo_root(&base).unwrap_or_else(|| base.clone());
+    references
+        .into_iter()
+        .filter(|(_, value)| !value.contains("://") && !Path::new(value).is_absolute())
+        .filter(|(_, value)| {
+            let path = Path::new(value.trim_start_matches("./"));
+            !within(&base, &root, path).is_some_and(|path| kind.matches(&path))
+        })
+        .map(|(location, value)| {
+            let relative = Path::new(value.trim_start_matches("./"));
+            let parent = relative.parent().unwrap_or(Path::new(""));
+            let siblings: Vec<String> = within(&base, &root, parent)
+                .and_then(|dir| std::fs::read_dir(dir).ok())
+                .into_iter()
+                .flatten()
+                .flatten()
+                .filter(|entry| kind.matches(&entry.path()))
+                .map(|entry| parent.join(entry.file_name()).display().to_string())
+                .collect();
+            BrokenRef {
+                rule: rule.name.clone(),
+                suggestion: yaml::suggest(
+                    &relative.display().to_string(),
+                    siblings.iter().map(String::as_str),
+                )
+                .map(str::to_string),
+                location,
+                value,
+            }
+        })
+        .collect()
+}
+
+/// `base` joined with `path`, if that stays inside `root`
+fn within(base: &Path, root: &Path, path: &Path) -> Option<PathBuf> {
+    let mut joined = base.to_path_buf();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => joined.push(part),
+            Component::CurDir => {}
+            Component::ParentDir => {
+                joined.pop();
+            }
+            Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    joined.starts_with(root).then_some(joined)
+}
+
+/// The `origin` URL of the repository holding `dir`
+#[cfg(feature = "native")]
+fn origin(dir: &Path) -> Option<String> {
+    git::remote_url(dir).ok()
+}
+
+/// The root of the repository holding `dir`
+#[cfg(feature = "native")]
+fn repo_root(dir: &Path) -> Option<PathBuf> {
+    git::repo_root(dir).ok()
+}
+
+/// Without git no repository is known, so only documents not naming one
+/// are checked and paths stay inside `base_dir`
+#[cfg(not(feature = "native"))]
+fn origin(_dir: &Path) -> Option<String> {
+    None
+}
+
+#[cfg(not(feature = "native"))]
+fn repo_root(_dir: &Path) -> Option<PathBuf> {
+    None
+}
+
+/// A repository URL without its scheme, user, `.git` suffix or trailing
+/// slash, so `git@host:org/repo.git` and `https://host/org/repo` compare equal
+fn normalize_url(url: &str) -> String {
+    let url = url.trim().trim_end_matches('/');
+    let url = url.strip_suffix(".git").unwrap_or(url);
+    let url = match url.split_once("://") {
+        Some((_, rest)) => rest.to_string(),
+        None => url.replacen(':', "/", 1),
+    };
+    let url = url.split_once('@').map_or(url.as_str(), |(_, host)| host);
+    url.to_lowercase()
+}
+
+/// Values a selector picks out, with where each was found
+fn select(
+    selector: &RefSelector,
+    pipeline: &Pipeline,
+    base_dir: &Path,
+) -> Result<Vec<(BlockLocation, String)>, ConflowError> {
+    select_where(selector, pipeline, base_dir, |_| true)
+}
+
+/// Values a selector picks out of the documents `keep` accepts
+fn select_where(
+    selector: &RefSelector,
+    pipeline: &Pipeline,
+    base_dir: &Path,
+    keep: impl Fn(&Value) -> bool,
+) -> Result<Vec<(BlockLocation, String)>, ConflowError> {
+    let path: Vec<&str> = selector.path.split('.').filter(|p| !p.is_empty()).collect();
+    let mut out = Vec::new();
+
+    for file in selector_files(&selector.files, pipeline, base_dir)? {
+        let content = std::fs::read_to_string(&file).map_err(|e| ConflowError::FileReadError {
+            path: file.clone(),
+            error: e.to_string(),
+        })?;
+        for (location, document) in parse_roots(&file, &content) {
+            if matches_filter(&document, &selector.filter) && keep(&document) {
+                collect(
+                    &document,
+                    &path,
+                    selector.keys,
+                    &location,
+                    &mut Vec::new(),
+                    &mut out,
+                );
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// Files matched by a selector; a stage reference means its output
+fn selector_files(
+    files: &Input,
+    pipeline: &Pipeline,
+    base_dir: &Path,
+) -> Result<Vec<PathBuf>, ConflowError> {
+    match files.references_stage() {
+        Some(stage) => Ok(pipeline
+            .get_stage(stage)
+            .and_then(|s| s.output.as_ref())
+            .map(|output| base_dir.join(output.path()))
+            .filter(|path| path.exists())
+            .into_iter()
+            .collect()),
+        None => resolve_globs(&files.patterns(), base_dir),
+    }
+}
+
+fn matches_filter(document: &Value, filter: &HashMap<String, String>) -> bool {
+    filter
+        .iter()
+        .all(|(path, expected)| lookup(document, path).is_some_and(|actual| actual == *expected))
+}
+
+/// The scalar at a dotted path of a document
+fn lookup(document: &Value, path: &str) -> Option<String> {
+    path.split('.')
+        .try_fold(document, |value, key| value.get(key))
+        .and_then(scalar)
+}
+
+fn scalar(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+fn collect(
+    value: &Value,
+    parts: &[&str],
+    keys: bool,
+    location: &BlockLocation,
+    path: &mut Vec<Segment>,
+    out: &mut Vec<(BlockLocation, String)>,
+) {
+    let mut found = |path: &[Segment], name: String| {
+        let location = BlockLocation {
+            path: path.to_vec(),
+            ..location.clone()
+        };
+        out.push((location, name));
+    };
+
+    let Some((part, rest)) = parts.split_first() else {
+        match value {
+            Value::Object(map) if keys => {
+                for key in map.keys() {
+                    path.push(Segment::Key(key.clone()));
+                    found(path, key.clone());
+                    path.pop();
+                }
+            }
+            // A list of names, e.g. `depends_on: [db, cache]`
+            Value::Array(items) if !keys => {
+                for (i, item) in items.iter().enumerate() {
+                    if let Some(name) = scalar(item) {
+                        path.push(Segment::Index(i));
+                        found(path, name);
+                        path.pop();
+                    }
+                }
+            }
+            _ if !keys => {
+                if let Some(name) = scalar(value) {
+                    found(path, name);
+                }
+            }
+            _ => {}
+        }
+        return;
+    };
+
+    let children: Vec<(Segment, &Value)> = match (value, *part) {
+        (Value::Object(map), "*") => map
+            .iter()
+            .map(|(k, v)| (Segment::Key(k.clone()), v))
+            .collect(),
+        (Value::Array(items), "*") => items
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (Segment::Index(i), v))
+            .collect(),
+        (Value::Object(map), key) => map
+            .get(key)
+            .map(|v| (Segment::Key(key.to_string()), v))
+            .into_iter()
+            .collect(),
+        (Value::Array(items), index) => index
+            .parse()
+            .ok()
+            .and_then(|i: usize| Some((Segment::Index(i), items.get(i)?)))
+            .into_iter()
+            .collect(),
+        _ => vec![],
+    };
+    for (segment, child) in children {
+        path.push(segment);
+        collect(child, rest, keys, location, path, out);
+        path.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pipeline(refs: &str) -> Pipeline {
+        Pipeline::from_yaml(&format!(
+            "name: refs\nstages:\n  - name: a\n    tool: {{ type: shell, command: \"true\" }}\n    \
+             input: \"*\"\nrefs:\n{}",
+            refs
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_compose_services() {
+        let temp = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            temp.path().join("compose.yaml"),
+            "services:\n  web: { image: web }\n  postgres: { image: postgres }\n",
+        )
+        .unwrap();
+        std::fs::write(
+            temp.path().join("app.json"),
+            r#"{ "upstreams": [{ "service": "postgres" }, { "service": "postgress" }], "needs": ["web", "queue"] }"#,
+        )
+        .unwrap();
+
+        let pipeline = pipeline(
+            "  - name: service
+    from: { files: app.json, path: upstreams.*.service }
+    to: { files: compose.yaml, path: services, keys: true }
+  - name: needs
+    from: { files: app.json, path: needs }
+    to: { files: compose.yaml, path: services, keys: true }
+",
+        );
+        let broken = check_refs(&pipeline, temp.path()).unwrap();
+        let broken: Vec<String> = broken
+            .iter()
+            .map(|b| {
+                b.to_string()
+                    .replace(&temp.path().display().to_string(), "")
+            })
+            .collect();
+        assert_eq!(
+            broken,
+            vec![
+                "/app.json: upstreams[1].service: 'postgress' is not defined (service) - did you mean 'postgres'?",
+                "/app.json: needs[1]: 'queue' is not defined (needs)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_kubernetes_config_maps() {
+        let temp = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            temp.path().join("all.yaml"),
+            "kind: ConfigMap
+metadata: { name: web-config }
+---
+kind: Secret
+metadata: { name: redis-settings }
+---
+kind: Deployment
+metadata: { name: web }
+spec:
+  template:
+    spec:
+      volumes:
+        - configMap: { name: web-config }
+        - configMap: { name: redis-settings }
+",
+        )
+        .unwrap();
+
+        let pipeline = pipeline(
+            "  - name: configmap
+    from:
+      files: \"*.yaml\"
+      path: spec.template.spec.volumes.*.configMap.name
+      where: { kind: Deployment }
+    to: { files: \"*.yaml\", path: metadata.name, where: { kind: ConfigMap } }
+",
+        );
+        let broken = check_refs(&pipeline, temp.path()).unwrap();
+        assert_eq!(broken.len(), 1);
+        assert_eq!(broken[0].value, "redis-settings");
+        assert_eq!(broken[0].location.document, Some(2));
+        assert_eq!(
+            crate::analyzer::dotted(&broken[0].location.path),
+            "spec.template.spec.volumes[1].configMap.name"
+        );
+        assert_eq!(broken[0].suggestion.as_deref(), None);
+    }
+
+    #[test]
+    fn test_paths_exist() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let repo = temp.path().join("gitops");
+        std::fs::create_dir_all(repo.join("apps/web")).unwrap();
+        std::fs::create_dir_all(temp.path().join("outside")).unwrap();
+        let remote = std::process::Command::new("git")
+            .args(["init", "-q"])
+            .current_dir(&repo)
+            .output()
+            .is_ok_and(|o| o.status.success())
+            && std::process::Command::new("git")
+                .args(["remote", "add", "origin", "git@example.com:org/gitops.git"])
+                .current_dir(&repo)
+                .output()
+                .is_ok_and(|o| o.status.success());
+        if !remote {
+            // Skip if git is not installed
+            return;
+        }
+        std::fs::write(
+            repo.join("apps.yaml"),
+            "kind: Application
+spec: { source: { repoURL: https://example.com/org/gitops, path: apps/web } }
+---
+kind: Application
+spec: { source: { repoURL: https://example.com/org/gitops.git, path: ./apps/wbe } }
+---
+kind: Application
+spec: { source: { repoURL: https://example.com/org/other.git, path: apps/api } }
+---
+kind: Application
+spec: { source: { repoURL: git@example.com:org/gitops.git, path: ../outside } }
+---
+kind: Kustomization
+spec: { path: https://example.com/remote }
+",
+        )
+        .unwrap();
+
+        let pipeline = pipeline(
+            "  - name: app-path
+    from: { files: apps.yaml, path: spec.source.path }
+    to: { exists: directory, repo: spec.source.repoURL }
+  - name: kustomization-path
+    from: { files: apps.yaml, path: spec.path }
+    to: { exists: any }
+",
+        );
+        let broken = check_refs(&pipeline, &repo).unwrap();
+        let values: Vec<&str> = broken.iter().map(|b| b.value.as_str()).collect();
+        assert_eq!(values, ["./apps/wbe", "../outside"]);
+        assert_eq!(broken[0].location.document, Some(1));
+        assert_eq!(broken[0].suggestion.as_deref(), Some("apps/web"));
+        assert_eq!(broken[1].suggestion, None);
+    }
+
+    #[test]
+    fn test_normalize_url() {
+        for url in [
+            "https://github.com/Org/repo.git",
+            "git@github.com:org/repo",
+            "ssh://git@github.com/org/repo/",
+        ] {
+            assert_eq!(normalize_url(url), "github.com/org/repo");
+        }
+    }
+}
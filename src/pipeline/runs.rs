@@ -0,0 +1,523 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Run records
+//!
+//! Every pipeline run is recorded under `.conflow/runs/<id>.json` with its
+//! stages' outcomes, durations and cache hits, the git commit it ran on and
+//! a digest of the pipeline file, so `conflow runs compare` can show what
+//! changed since a run that worked. IDs count up from 1 per project; the
+//! newest [`KEEP_RUNS`] records are kept.
+//!
+//! The record is written when a run starts and again as each stage finishes,
+//! so a run killed part way (Ctrl-C, OOM, a CI timeout) still shows which
+//! stages passed, and `conflow run --resume` can pick up after them.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use super::{ExecutionEvent, Pipeline, PipelineResult};
+use crate::errors::ConflowError;
+use crate::executors::ExecutionResult;
+use crate::rsr::diff::rfc3339;
+use crate::utils::git;
+
+/// Directory of run records, relative to the project
+pub const RUNS_DIR: &str = ".conflow/runs";
+
+/// How many records to keep
+pub const KEEP_RUNS: usize = 100;
+
+/// Lines of a failed stage's stderr kept in its record
+const ERROR_LINES: usize = 20;
+
+/// A recorded pipeline run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunRecord {
+    pub id: u64,
+    pub pipeline: String,
+    /// When the run started, in RFC 3339
+    pub started_at: String,
+    #[serde(rename = "duration_ms", with = "crate::report::duration_ms")]
+    pub duration: Duration,
+    pub success: bool,
+    /// False while the run is in progress, and for runs that never finished
+    #[serde(default = "default_true")]
+    pub finished: bool,
+    /// Run whose passed stages this one carried over instead of re-running
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resumed_from: Option<u64>,
+    /// Commit checked out during the run
+    #[serde(default)]
+    pub git_commit: Option<String>,
+    /// Whether tracked files had uncommitted changes
+    #[serde(default)]
+    pub git_dirty: bool,
+    /// BLAKE3 digest of the pipeline file
+    #[serde(default)]
+    pub pipeline_digest: Option<String>,
+    /// conflow version that ran the pipeline
+    pub conflow_version: String,
+    /// Stages in pipeline order
+    pub stages: Vec<StageRecord>,
+}
+
+/// How a stage fared in a run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StageRecord {
+    pub name: String,
+    pub outcome: StageOutcome,
+    #[serde(rename = "duration_ms", with = "crate::report::duration_ms")]
+    pub duration: Duration,
+    #[serde(default)]
+    pub exit_code: i32,
+    /// End of stderr, for failed stages
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Outcome of a stage
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StageOutcome {
+    Passed,
+    /// Passed earlier with the same inputs
+    Cached,
+    Failed,
+    /// Not selected, or not reached after a failure
+    NotRun,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl std::fmt::Display for StageOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Passed => write!(f, "passed"),
+            Self::Cached => write!(f, "cached"),
+            Self::Failed => write!(f, "failed"),
+            Self::NotRun => write!(f, "not run"),
+        }
+    }
+}
+
+impl RunRecord {
+    /// Record of a finished run; `id` is assigned by [`RunHistory::record`]
+    pub fn new(
+        pipeline: &Pipeline,
+        pipeline_file: &Path,
+        result: &PipelineResult,
+        dir: &Path,
+    ) -> Self {
+        let finished = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let started = match crate::report::is_stable() {
+            true => Duration::ZERO,
+            false => finished.saturating_sub(result.duration),
+        };
+
+        let stages = pipeline
+            .stages
+            .iter()
+            .map(|stage| match result.results.get(&stage.name) {
+                Some(r) => StageRecord::from_result(&stage.name, r),
+                None => StageRecord {
+                    name: stage.name.clone(),
+                    outcome: StageOutcome::NotRun,
+                    duration: Duration::ZERO,
+                    exit_code: 0,
+                    error: None,
+                },
+            })
+            .collect();
+
+        Self {
+            id: 0,
+            pipeline: pipeline.name.clone(),
+            started_at: rfc3339(started.as_secs() as i64),
+            duration: result.duration,
+            success: result.success,
+            finished: true,
+            resumed_from: None,
+            git_commit: git::head_commit(dir).ok(),
+            git_dirty: git::has_changes(dir).unwrap_or(false),
+            pipeline_digest: Self::digest(pipeline_file),
+            conflow_version: crate::VERSION.to_string(),
+            stages,
+        }
+    }
+
+    /// Record of a run that is starting, before any stage has run
+    pub fn started(pipeline: &Pipeline, pipeline_file: &Path, dir: &Path) -> Self {
+        let result = PipelineResult {
+            results: HashMap::new(),
+            duration: Duration::ZERO,
+            success: false,
+            profile: None,
+        };
+        Self {
+            finished: false,
+            ..Self::new(pipeline, pipeline_file, &result, dir)
+        }
+    }
+
+    /// Note a stage that finished or came from the cache
+    ///
+    /// Returns whether the record changed.
+    pub fn apply(&mut self, event: &ExecutionEvent) -> bool {
+        let (name, result) = match event {
+            ExecutionEvent::StageCached { stage, result }
+            | ExecutionEvent::StageFinished { stage, result } => (stage, result),
+            _ => return false,
+        };
+        match self.stages.iter_mut().find(|s| &s.name == name) {
+            Some(stage) => {
+                *stage = StageRecord::from_result(name, result);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Stages that didn't pass, in pipeline order
+    pub fn pending(&self) -> Vec<String> {
+        self.stages
+            .iter()
+            .filter(|s| matches!(s.outcome, StageOutcome::Failed | StageOutcome::NotRun))
+            .map(|s| s.name.clone())
+            .collect()
+    }
+
+    /// Carry over the stages `previous` passed that this run didn't run
+    pub fn resume(&mut self, previous: &RunRecord) {
+        for stage in &mut self.stages {
+            let carried = previous.stage(&stage.name).filter(|s| {
+                matches!(s.outcome, StageOutcome::Passed | StageOutcome::Cached)
+            });
+            if let (StageOutcome::NotRun, Some(carried)) = (stage.outcome, carried) {
+                *stage = carried.clone();
+            }
+        }
+        self.resumed_from = Some(previous.id);
+    }
+
+    /// BLAKE3 digest of a pipeline file, as recorded in `pipeline_digest`
+    pub fn digest(pipeline_file: &Path) -> Option<String> {
+        std::fs::read(pipeline_file)
+            .ok()
+            .map(|content| blake3::hash(&content).to_hex().to_string())
+    }
+
+    /// A stage's record
+    pub fn stage(&self, name: &str) -> Option<&StageRecord> {
+        self.stages.iter().find(|s| s.name == name)
+    }
+
+    /// How many stages came from the cache
+    pub fn cache_hits(&self) -> usize {
+        self.stages
+            .iter()
+            .filter(|s| s.outcome == StageOutcome::Cached)
+            .count()
+    }
+}
+
+impl StageRecord {
+    fn from_result(name: &str, result: &ExecutionResult) -> Self {
+        Self {
+            name: name.to_string(),
+            outcome: match (result.success, result.cache_hit) {
+                (true, true) => StageOutcome::Cached,
+                (true, false) => StageOutcome::Passed,
+                (false, _) => StageOutcome::Failed,
+            },
+            duration: result.duration,
+            exit_code: result.exit_code,
+            error: (!result.success && !result.stderr.is_empty()).then(|| tail(&result.stderr)),
+        }
+    }
+}
+
+/// Last lines of a stage's stderr
+fn tail(stderr: &str) -> String {
+    let lines: Vec<&str> = stderr.trim_end().lines().collect();
+    lines[lines.len().saturating_sub(ERROR_LINES)..].join("\n")
+}
+
+/// The run records of a project
+pub struct RunHistory {
+    dir: PathBuf,
+}
+
+impl RunHistory {
+    /// Records of the project at `working_dir`
+    pub fn new(working_dir: &Path) -> Self {
+        Self {
+            dir: working_dir.join(RUNS_DIR),
+        }
+    }
+
+    /// Save a record under the next ID and drop the oldest beyond [`KEEP_RUNS`]
+    pub fn record(&self, mut record: RunRecord) -> Result<RunRecord, ConflowError> {
+        std::fs::create_dir_all(&self.dir).map_err(|e| ConflowError::FileWriteError {
+            path: self.dir.clone(),
+            error: e.to_string(),
+        })?;
+
+        let mut id = self.ids()?.last().copied().unwrap_or(0);
+        // Another run may take an ID between listing and writing
+        let (mut file, path) = loop {
+            id += 1;
+            let path = self.path(id);
+            match std::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&path)
+            {
+                Ok(file) => break (file, path),
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+                Err(e) => {
+                    return Err(ConflowError::FileWriteError {
+                        path,
+                        error: e.to_string(),
+                    })
+                }
+            }
+        };
+        record.id = id;
+
+        let json = crate::report::to_json(&record)?;
+        std::io::Write::write_all(&mut file, json.as_bytes()).map_err(|e| {
+            ConflowError::FileWriteError {
+                path,
+                error: e.to_string(),
+            }
+        })?;
+
+        let ids = self.ids()?;
+        for old in &ids[..ids.len().saturating_sub(KEEP_RUNS)] {
+            let _ = std::fs::remove_file(self.path(*old));
+        }
+        Ok(record)
+    }
+
+    /// Overwrite a record saved by [`RunHistory::record`]
+    ///
+    /// The file is replaced whole, so a run killed mid-save keeps the
+    /// previous version.
+    pub fn save(&self, record: &RunRecord) -> Result<(), ConflowError> {
+        let path = self.path(record.id);
+        let error = |e: std::io::Error| ConflowError::FileWriteError {
+            path: path.clone(),
+            error: e.to_string(),
+        };
+        let json = crate::report::to_json(record)?;
+        let tmp = path.with_extension(format!("{}.tmp", std::process::id()));
+        std::fs::write(&tmp, json).map_err(error)?;
+        std::fs::rename(&tmp, &path).map_err(error)
+    }
+
+    /// A run by ID
+    pub fn get(&self, id: u64) -> Result<RunRecord, ConflowError> {
+        let path = self.path(id);
+        let json = std::fs::read_to_string(&path).map_err(|_| ConflowError::FileNotFound {
+            path: path.clone(),
+            help: Some("List recorded runs with 'conflow runs list'".into()),
+        })?;
+        crate::report::from_json(&json)
+    }
+
+    /// The newest run, if any
+    pub fn latest(&self) -> Result<Option<RunRecord>, ConflowError> {
+        match self.ids()?.last() {
+            Some(id) => self.get(*id).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Recorded runs, newest first
+    ///
+    /// Records that no longer parse are skipped.
+    pub fn list(&self) -> Result<Vec<RunRecord>, ConflowError> {
+        Ok(self
+            .ids()?
+            .iter()
+            .rev()
+            .filter_map(|id| self.get(*id).ok())
+            .collect())
+    }
+
+    /// IDs of the recorded runs, oldest first
+    fn ids(&self) -> Result<Vec<u64>, ConflowError> {
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => {
+                return Err(ConflowError::FileReadError {
+                    path: self.dir.clone(),
+                    error: e.to_string(),
+                })
+            }
+        };
+        let mut ids: Vec<u64> = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let name = entry.file_name();
+                name.to_str()?.strip_suffix(".json")?.parse().ok()
+            })
+            .collect();
+        ids.sort_unstable();
+        Ok(ids)
+    }
+
+    fn path(&self, id: u64) -> PathBuf {
+        self.dir.join(format!("{}.json", id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_history() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let pipeline = Pipeline::from_yaml(
+            r#"
+version: "1"
+name: app
+stages:
+  - name: lint
+    tool: { type: shell, command: "true" }
+    input: "*.yaml"
+  - name: test
+    tool: { type: shell, command: "false" }
+    input: "*.yaml"
+  - name: deploy
+    tool: { type: shell, command: "true" }
+    input: "*.yaml"
+"#,
+        )
+        .unwrap();
+        let lint = ExecutionResult::success(String::new(), Duration::from_millis(5), vec![])
+            .with_cache_hit();
+        let result = PipelineResult {
+            results: HashMap::from([
+                ("lint".to_string(), lint),
+                (
+                    "test".to_string(),
+                    ExecutionResult::failure("one\ntwo\n".into(), 1, Duration::from_millis(40)),
+                ),
+            ]),
+            duration: Duration::from_millis(50),
+            success: false,
+            profile: None,
+        };
+
+        let history = RunHistory::new(temp.path());
+        assert!(history.latest().unwrap().is_none());
+        let pipeline_file = temp.path().join(".conflow.yaml");
+        let record = |result: &PipelineResult| {
+            history
+                .record(RunRecord::new(
+                    &pipeline,
+                    &pipeline_file,
+                    result,
+                    temp.path(),
+                ))
+                .unwrap()
+        };
+
+        let first = record(&result);
+        assert_eq!(first.id, 1);
+        let outcomes: Vec<_> = first.stages.iter().map(|s| s.outcome).collect();
+        assert_eq!(
+            outcomes,
+            [
+                StageOutcome::Cached,
+                StageOutcome::Failed,
+                StageOutcome::NotRun
+            ]
+        );
+        assert_eq!(
+            first.stage("test").unwrap().error.as_deref(),
+            Some("one\ntwo")
+        );
+        assert_eq!(first.cache_hits(), 1);
+
+        let second = record(&PipelineResult {
+            success: true,
+            ..result
+        });
+        assert_eq!(second.id, 2);
+        assert!(history.get(1).is_ok_and(|r| !r.success));
+        assert_eq!(history.latest().unwrap().unwrap().id, 2);
+        let ids: Vec<u64> = history.list().unwrap().iter().map(|r| r.id).collect();
+        assert_eq!(ids, [2, 1]);
+        assert!(history.get(3).is_err());
+    }
+
+    #[test]
+    fn test_resume() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let pipeline = Pipeline::from_yaml(
+            r#"
+version: "1"
+name: app
+stages:
+  - name: lint
+    tool: { type: shell, command: "true" }
+    input: "*.yaml"
+  - name: test
+    tool: { type: shell, command: "true" }
+    input: "*.yaml"
+"#,
+        )
+        .unwrap();
+        let pipeline_file = temp.path().join(".conflow.yaml");
+        let history = RunHistory::new(temp.path());
+        let passed = ExecutionResult::success(String::new(), Duration::from_millis(5), vec![]);
+
+        // Interrupted after the first stage
+        let mut started = history
+            .record(RunRecord::started(&pipeline, &pipeline_file, temp.path()))
+            .unwrap();
+        assert!(!started.apply(&ExecutionEvent::StageStarted {
+            stage: "lint".into()
+        }));
+        assert!(started.apply(&ExecutionEvent::StageFinished {
+            stage: "lint".into(),
+            result: passed.clone(),
+        }));
+        history.save(&started).unwrap();
+        let interrupted = history.latest().unwrap().unwrap();
+        assert!(!interrupted.finished);
+        assert_eq!(interrupted.pending(), ["test"]);
+
+        let result = PipelineResult {
+            results: HashMap::from([("test".to_string(), passed)]),
+            duration: Duration::from_millis(5),
+            success: true,
+            profile: None,
+        };
+        let mut resumed = RunRecord::new(&pipeline, &pipeline_file, &result, temp.path());
+        resumed.resume(&interrupted);
+        assert_eq!(resumed.resumed_from, Some(1));
+        assert!(resumed.pending().is_empty());
+        assert_eq!(resumed.stage("lint").unwrap().outcome, StageOutcome::Passed);
+
+        // Records from before `finished` was recorded were finished
+        let mut json: serde_json::Value =
+            serde_json::from_str(&crate::report::to_json(&resumed).unwrap()).unwrap();
+        json.as_object_mut().unwrap().remove("finished");
+        assert!(crate::report::from_json::<RunRecord>(&json.to_string())
+            .unwrap()
+            .finished);
+    }
+}
@@ -0,0 +1,265 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Zero-config scan
+//!
+//! Walks a repository without needing a pipeline, detects the built-in
+//! schema of every JSON and YAML file it recognizes, and vets the file
+//! against it with `cue vet`. Files are ranked by how many errors they
+//! have, so the worst offenders come first.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use super::inventory::config_files;
+use super::Pipeline;
+use crate::cache::{DataFormat, ParseCache};
+use crate::errors::ConflowError;
+use crate::executors::parsers::{parse_output, Location, Severity, ToolDiagnostic};
+use crate::rsr::detect::{detect_schema, schema_file};
+use crate::rsr::SchemaMatch;
+
+/// Outcome of a scan
+#[derive(Debug, Clone, Serialize)]
+pub struct ScanReport {
+    /// Scanned files, those with the most errors first
+    pub files: Vec<ScannedFile>,
+    pub summary: ScanSummary,
+}
+
+/// One scanned file
+#[derive(Debug, Clone, Serialize)]
+pub struct ScannedFile {
+    /// Path relative to the project root
+    pub path: PathBuf,
+    /// The schema detected for it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schema: Option<SchemaMatch>,
+    pub status: ScanStatus,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub diagnostics: Vec<ToolDiagnostic>,
+}
+
+impl ScannedFile {
+    /// Number of diagnostics at a severity
+    pub fn count(&self, severity: Severity) -> usize {
+        self.diagnostics
+            .iter()
+            .filter(|d| d.severity == severity)
+            .count()
+    }
+}
+
+/// What a scan found out about a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ScanStatus {
+    /// It doesn't parse, or fails its schema
+    Invalid,
+    /// It passes its schema
+    Valid,
+    /// It matches a schema, but `cue` isn't installed to check it
+    Unchecked,
+    /// It matches no known schema
+    Unrecognized,
+}
+
+impl std::fmt::Display for ScanStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Invalid => "invalid",
+            Self::Valid => "valid",
+            Self::Unchecked => "unchecked",
+            Self::Unrecognized => "unrecognized",
+        })
+    }
+}
+
+/// Counts across a scan
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ScanSummary {
+    pub total: usize,
+    pub by_status: BTreeMap<ScanStatus, usize>,
+    /// Files per detected schema
+    pub by_schema: BTreeMap<String, usize>,
+    pub errors: usize,
+    pub warnings: usize,
+}
+
+impl ScanSummary {
+    fn new(files: &[ScannedFile]) -> Self {
+        let mut summary = Self {
+            total: files.len(),
+            ..Self::default()
+        };
+        for file in files {
+            *summary.by_status.entry(file.status).or_default() += 1;
+            if let Some(ref schema) = file.schema {
+                *summary.by_schema.entry(schema.schema.clone()).or_default() += 1;
+            }
+            summary.errors += file.count(Severity::Error);
+            summary.warnings += file.count(Severity::Warning);
+        }
+        summary
+    }
+
+    /// Number of files with a status
+    pub fn count(&self, status: ScanStatus) -> usize {
+        self.by_status.get(&status).copied().unwrap_or(0)
+    }
+}
+
+/// Scan the JSON and YAML files of a project
+///
+/// `patterns` narrows the files (default: every config file git doesn't
+/// ignore). Without `cue` on the PATH recognized files are only parsed.
+pub fn scan(
+    root: &Path,
+    patterns: &[String],
+    pipeline: Option<&Pipeline>,
+) -> Result<ScanReport, ConflowError> {
+    let cue = which::which("cue").is_ok();
+    let mut files = Vec::new();
+    for path in config_files(root, patterns, pipeline)? {
+        if DataFormat::from_path(&path).is_none() {
+            continue;
+        }
+        files.push(scan_file(root, path, cue)?);
+    }
+
+    files.sort_by(|a, b| {
+        b.count(Severity::Error)
+            .cmp(&a.count(Severity::Error))
+            .then(b.count(Severity::Warning).cmp(&a.count(Severity::Warning)))
+            .then(a.status.cmp(&b.status))
+            .then(a.path.cmp(&b.path))
+    });
+    Ok(ScanReport {
+        summary: ScanSummary::new(&files),
+        files,
+    })
+}
+
+fn scan_file(root: &Path, path: PathBuf, cue: bool) -> Result<ScannedFile, ConflowError> {
+    let mut file = ScannedFile {
+        path,
+        schema: None,
+        status: ScanStatus::Unrecognized,
+        diagnostics: vec![],
+    };
+    let location = |file: &Path| Location {
+        file: file.to_path_buf(),
+        line: None,
+        column: None,
+    };
+
+    let documents = std::fs::read_to_string(root.join(&file.path))
+        .map_err(ConflowError::from)
+        .and_then(|content| ParseCache::global().documents(&file.path, &content));
+    let documents = match documents {
+        Ok(documents) => documents,
+        Err(e) => {
+            file.status = ScanStatus::Invalid;
+            file.diagnostics
+                .push(diagnostic(e.to_string(), location(&file.path)));
+            return Ok(file);
+        }
+    };
+
+    // Every document of a stream has to match the same schema
+    let mut matches = documents.iter().filter(|d| !d.is_null()).map(detect_schema);
+    let Some(Some(first)) = matches.next() else {
+        return Ok(file);
+    };
+    let same = |m: Option<SchemaMatch>| {
+        m.is_some_and(|m| m.schema == first.schema && m.definition == first.definition)
+    };
+    if !matches.all(same) {
+        return Ok(file);
+    }
+    let schema = file.schema.insert(first).clone();
+    if !cue {
+        file.status = ScanStatus::Unchecked;
+        return Ok(file);
+    }
+
+    let mut command = Command::new("cue");
+    command
+        .arg("vet")
+        .arg(schema_file(root, &schema.schema)?)
+        .current_dir(root);
+    if let Some(definition) = schema.definition {
+        command.arg("-d").arg(definition);
+    }
+    let output = command.arg(&file.path).output()?;
+    if output.status.success() {
+        file.status = ScanStatus::Valid;
+        return Ok(file);
+    }
+
+    file.status = ScanStatus::Invalid;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let parsed = parse_output("cue", &stderr);
+    if parsed.is_empty() {
+        let message = stderr.trim().to_string();
+        file.diagnostics
+            .push(diagnostic(message, location(&file.path)));
+    }
+    file.diagnostics.extend(parsed);
+    Ok(file)
+}
+
+fn diagnostic(message: String, location: Location) -> ToolDiagnostic {
+    ToolDiagnostic {
+        tool: "scan".into(),
+        severity: Severity::Error,
+        message,
+        rule: None,
+        location: Some(location),
+        related: vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan_ranks_and_detects() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let root = temp.path();
+        std::fs::create_dir_all(root.join("workflows")).unwrap();
+        std::fs::write(
+            root.join("workflows/ci.yml"),
+            "on: push\njobs:\n  test:\n    runs-on: ubuntu-latest\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("app.json"), "{\"name\": \"app\"}").unwrap();
+        std::fs::write(root.join("broken.yaml"), "a: [1, 2\n").unwrap();
+        std::fs::write(root.join("schema.cue"), "#App: {}\n").unwrap();
+
+        let patterns = vec!["**/*".to_string()];
+        let report = scan(root, &patterns, None).unwrap();
+        assert_eq!(report.summary.total, 3);
+        assert_eq!(report.files.last().unwrap().path, PathBuf::from("app.json"));
+        let file = |name: &str| {
+            report
+                .files
+                .iter()
+                .find(|f| f.path.ends_with(name))
+                .unwrap()
+        };
+
+        let broken = file("broken.yaml");
+        assert_eq!(broken.status, ScanStatus::Invalid);
+        assert_eq!(broken.count(Severity::Error), 1);
+
+        let workflow = file("ci.yml");
+        assert_eq!(workflow.schema.as_ref().unwrap().schema, "github:workflow");
+        assert_ne!(workflow.status, ScanStatus::Unrecognized);
+        assert_eq!(report.summary.by_schema["github:workflow"], 1);
+        assert_eq!(report.summary.count(ScanStatus::Unrecognized), 1);
+    }
+}
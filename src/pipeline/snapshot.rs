@@ -0,0 +1,226 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Golden-file snapshots
+//!
+//! A stage with `snapshot: true` keeps what it produced under
+//! `.conflow/snapshots/<stage>/`: each output file, or its standard output
+//! when it writes none. Later runs compare against the snapshot and fail the
+//! stage on a difference, leaving the new content beside it as `<file>.new`
+//! until `conflow snapshot update` accepts it. A missing snapshot is written
+//! and passes. Redacted values are masked in snapshots too.
+
+use colored::Colorize;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+use super::Stage;
+use crate::cache::ParseCache;
+use crate::errors::ConflowError;
+use crate::executors::ExecutionResult;
+use crate::utils::walk::Walker;
+
+/// Directory of snapshots, relative to the project root
+pub const SNAPSHOTS_DIR: &str = ".conflow/snapshots";
+
+/// Extension of snapshots waiting to be accepted
+const PENDING: &str = "new";
+
+/// Snapshot of a stage's standard output
+const STDOUT: &str = "stdout";
+
+/// A stage output that differs from its snapshot
+#[derive(Debug, Clone, Serialize)]
+pub struct SnapshotMismatch {
+    pub stage: String,
+    /// The snapshot, relative to the project root
+    pub snapshot: PathBuf,
+    /// Unified diff from the snapshot to the new output
+    pub diff: String,
+}
+
+impl SnapshotMismatch {
+    /// The diff with a line on how to accept it, colored for a terminal
+    pub fn render(&self) -> String {
+        let mut out = format!(
+            "  {} snapshot {} changed; run 'conflow snapshot update {}' to accept it\n",
+            "≠".yellow(),
+            self.snapshot.display().to_string().bold(),
+            self.stage
+        );
+        for line in self.diff.lines() {
+            let line = if line.starts_with("+++") || line.starts_with("---") {
+                line.bold()
+            } else if line.starts_with('+') {
+                line.green()
+            } else if line.starts_with('-') {
+                line.red()
+            } else if line.starts_with("@@") {
+                line.cyan()
+            } else {
+                line.normal()
+            };
+            out.push_str(&format!("    {}\n", line));
+        }
+        out
+    }
+}
+
+/// Compare a stage's output with its snapshots
+///
+/// Missing snapshots are written; pending ones of outputs that match again
+/// are removed.
+pub fn check_snapshots(
+    stage: &Stage,
+    result: &ExecutionResult,
+    stage_dir: &Path,
+    root: &Path,
+) -> Result<Vec<SnapshotMismatch>, ConflowError> {
+    let dir = Path::new(SNAPSHOTS_DIR).join(&stage.name);
+    let mut outputs: Vec<(PathBuf, String)> = Vec::new();
+    for output in &result.outputs {
+        let name = output
+            .strip_prefix(stage_dir)
+            .ok()
+            .map(Path::to_path_buf)
+            .or_else(|| output.file_name().map(PathBuf::from))
+            .unwrap_or_else(|| PathBuf::from(STDOUT));
+        let content = std::fs::read_to_string(output).map_err(|e| ConflowError::FileReadError {
+            path: output.clone(),
+            error: e.to_string(),
+        })?;
+        // Values at redacted paths are noted when the output parses
+        let _ = ParseCache::global().documents(output, &content);
+        outputs.push((dir.join(name), crate::report::redact(&content)));
+    }
+    if outputs.is_empty() {
+        outputs.push((dir.join(STDOUT), result.stdout.clone()));
+    }
+
+    let mut mismatches = Vec::new();
+    for (snapshot, content) in outputs {
+        let path = root.join(&snapshot);
+        let pending = pending_path(&path);
+        match std::fs::read_to_string(&path) {
+            Ok(expected) if expected == content => {
+                let _ = std::fs::remove_file(&pending);
+            }
+            Ok(expected) => {
+                write(&pending, &content)?;
+                let file = snapshot.display().to_string();
+                let diff = similar::TextDiff::from_lines(&expected, &content)
+                    .unified_diff()
+                    .context_radius(3)
+                    .header(&file, &file)
+                    .to_string();
+                mismatches.push(SnapshotMismatch {
+                    stage: stage.name.clone(),
+                    snapshot,
+                    diff,
+                });
+            }
+            Err(_) => write(&path, &content)?,
+        }
+    }
+    Ok(mismatches)
+}
+
+/// Snapshots waiting to be accepted, of the named stages (default: all)
+///
+/// Paths are those of the snapshots they replace, relative to the root.
+pub fn pending_snapshots(root: &Path, stages: &[String]) -> Vec<PathBuf> {
+    let dir = root.join(SNAPSHOTS_DIR);
+    let files = Walker::everything().files(&dir).unwrap_or_default();
+    let mut pending: Vec<PathBuf> = files
+        .into_iter()
+        .filter(|f| f.extension().is_some_and(|e| e == PENDING))
+        .filter_map(|f| Some(f.with_extension("").strip_prefix(root).ok()?.to_path_buf()))
+        .filter(|f| {
+            let stage = f
+                .strip_prefix(SNAPSHOTS_DIR)
+                .ok()
+                .and_then(|f| f.iter().next());
+            stages.is_empty() || stage.is_some_and(|s| stages.iter().any(|name| s == name.as_str()))
+        })
+        .collect();
+    pending.sort();
+    pending
+}
+
+/// Accept pending snapshots of the named stages (default: all)
+///
+/// Returns the snapshots replaced, relative to the root.
+pub fn accept_snapshots(root: &Path, stages: &[String]) -> Result<Vec<PathBuf>, ConflowError> {
+    let accepted = pending_snapshots(root, stages);
+    for snapshot in &accepted {
+        let path = root.join(snapshot);
+        std::fs::rename(pending_path(&path), &path).map_err(|e| ConflowError::FileWriteError {
+            path: path.clone(),
+            error: e.to_string(),
+        })?;
+    }
+    Ok(accepted)
+}
+
+/// Where the new content of a snapshot waits to be accepted
+fn pending_path(snapshot: &Path) -> PathBuf {
+    let mut name = snapshot.as_os_str().to_os_string();
+    name.push(".");
+    name.push(PENDING);
+    PathBuf::from(name)
+}
+
+fn write(path: &Path, content: &str) -> Result<(), ConflowError> {
+    let written = match path.parent() {
+        Some(parent) => std::fs::create_dir_all(parent),
+        None => Ok(()),
+    };
+    written
+        .and_then(|()| std::fs::write(path, content))
+        .map_err(|e| ConflowError::FileWriteError {
+            path: path.to_path_buf(),
+            error: e.to_string(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::{CueCommand, Pipeline, Tool};
+    use std::time::Duration;
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let root = temp.path();
+        let pipeline = Pipeline::builder("app")
+            .stage("export", Tool::cue(CueCommand::Export, ["s.cue"]), "a.cue")
+            .build()
+            .unwrap();
+        let stage = &pipeline.stages[0];
+        let run = |stdout: &str| {
+            let result = ExecutionResult::success(stdout.into(), Duration::ZERO, vec![]);
+            check_snapshots(stage, &result, root, root).unwrap()
+        };
+
+        // The first run records the snapshot
+        assert!(run("replicas: 2\n").is_empty());
+        let snapshot = Path::new(".conflow/snapshots/export/stdout");
+        assert!(root.join(snapshot).exists());
+        assert!(run("replicas: 2\n").is_empty());
+
+        let mismatches = run("replicas: 3\n");
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].snapshot, snapshot);
+        assert!(mismatches[0].diff.contains("-replicas: 2\n+replicas: 3"));
+        assert_eq!(pending_snapshots(root, &[]), [snapshot]);
+        assert!(pending_snapshots(root, &["other".into()]).is_empty());
+
+        assert_eq!(
+            accept_snapshots(root, &["export".into()]).unwrap(),
+            [snapshot]
+        );
+        assert!(pending_snapshots(root, &[]).is_empty());
+        assert!(run("replicas: 3\n").is_empty());
+    }
+}
@@ -9,7 +9,12 @@ use std::collections::HashSet;
 use std::path::Path;
 
 use crate::errors::ConflowError;
-use crate::pipeline::{DagBuilder, Input, Pipeline, Stage, Tool};
+use crate::executors::DEPLOY_TOOLS;
+use crate::pipeline::{
+    CostSource, DagBuilder, DeployAction, ExecutorDefinition, Input, Pipeline, Stage, Tool,
+};
+use crate::rsr::RsrSchemaRegistry;
+use crate::utils::redact::JsonPath;
 
 /// Pipeline validator
 pub struct PipelineValidator;
@@ -49,6 +54,85 @@ impl PipelineValidator {
             }
         }
 
+        // Validate custom executor definitions
+        for (name, definition) in &pipeline.executors {
+            Self::validate_executor_definition(name, definition, &mut result);
+        }
+
+        // Validate toolchain pins
+        for (tool, pin) in &pipeline.toolchain {
+            if !crate::toolchain::SUPPORTED_TOOLS.contains(&tool.as_str()) {
+                result.add_error(&format!(
+                    "Toolchain: '{}' is not a managed tool (supported: {})",
+                    tool,
+                    crate::toolchain::SUPPORTED_TOOLS.join(", ")
+                ));
+            } else if format!("{}@{}", tool, pin.version)
+                .parse::<crate::toolchain::ToolRequest>()
+                .is_err()
+            {
+                result.add_error(&format!(
+                    "Toolchain: invalid version '{}' for '{}'",
+                    pin.version, tool
+                ));
+            }
+        }
+
+        // Validate notification channels
+        for (i, channel) in pipeline.notifications.iter().enumerate() {
+            if channel.url.trim().is_empty() {
+                result.add_error(&format!("Notification {}: URL is empty", i + 1));
+            }
+            if channel.on.is_empty() {
+                result.add_warning(&format!(
+                    "Notification {}: No events in 'on' - it will never fire",
+                    i + 1
+                ));
+            }
+        }
+
+        // Validate reference rules
+        let mut rule_names = HashSet::new();
+        for rule in &pipeline.refs {
+            if !rule_names.insert(&rule.name) {
+                result.add_error(&format!("Duplicate ref rule name: '{}'", rule.name));
+            }
+            for selector in std::iter::once(&rule.from).chain(rule.to.selector()) {
+                if let Some(stage) = selector.files.references_stage() {
+                    if pipeline.get_stage(stage).is_none() {
+                        result.add_error(&format!(
+                            "Ref '{}': Files reference unknown stage '{}'",
+                            rule.name, stage
+                        ));
+                    }
+                }
+            }
+        }
+
+        // Validate lint rule settings
+        for problem in crate::lint::validate(&pipeline.lint) {
+            result.add_error(&format!("Lint: {}", problem));
+        }
+
+        // Validate the Kubernetes target version
+        if let Some(version) = &pipeline.kubernetes.version {
+            if let Err(e) = version.parse::<crate::analyzer::KubeVersion>() {
+                result.add_error(&format!("Kubernetes: {}", e));
+            }
+        }
+
+        // Validate redaction rules
+        for path in &pipeline.redact.paths {
+            if let Err(e) = JsonPath::parse(path) {
+                result.add_error(&format!("Redact: invalid path '{}': {}", path, e));
+            }
+        }
+        for pattern in &pipeline.redact.patterns {
+            if let Err(e) = regex::Regex::new(pattern) {
+                result.add_error(&format!("Redact: invalid pattern '{}': {}", pattern, e));
+            }
+        }
+
         // Validate each stage
         for stage in &pipeline.stages {
             Self::validate_stage(stage, pipeline, &mut result);
@@ -61,27 +145,47 @@ impl PipelineValidator {
     fn validate_stage(stage: &Stage, pipeline: &Pipeline, result: &mut ValidationResult) {
         // Validate tool configuration
         match &stage.tool {
-            Tool::Cue {
-                command,
-                schemas,
-                flags,
-                ..
-            } => {
+            Tool::Cue { schemas, .. } => {
                 Self::validate_cue_stage(stage, schemas, result);
             }
-            Tool::Nickel {
-                command,
-                file,
-                flags,
-                ..
-            } => {
+            Tool::Nickel { file, .. } => {
                 Self::validate_nickel_stage(stage, file, result);
             }
-            Tool::Shell { command, shell } => {
+            Tool::Shell { command, .. } => {
                 if command.is_empty() {
                     result.add_error(&format!("Stage '{}': Shell command is empty", stage.name));
                 }
             }
+            Tool::Custom {
+                executor,
+                definition,
+                ..
+            } => {
+                if definition.is_none() {
+                    result.add_error(&format!(
+                        "Stage '{}': Unknown executor '{}' (declare it under 'executors:')",
+                        stage.name, executor
+                    ));
+                }
+            }
+            Tool::Kubectl { .. } | Tool::Helm { .. } | Tool::Terraform { .. } => {
+                Self::validate_deploy_stage(stage, pipeline, result);
+            }
+            Tool::Cost { .. } => Self::validate_cost_stage(stage, pipeline, result),
+            Tool::Render { set, .. } => {
+                if set.keys().any(|key| key.split('.').any(str::is_empty)) {
+                    result.add_error(&format!(
+                        "Stage '{}': 'set' keys are dotted paths like image.tag",
+                        stage.name
+                    ));
+                }
+                if stage.output.as_ref().is_some_and(|o| o.format().is_some()) {
+                    result.add_error(&format!(
+                        "Stage '{}': Render output is a directory and takes no format",
+                        stage.name
+                    ));
+                }
+            }
         }
 
         // Validate input references
@@ -117,6 +221,53 @@ impl PipelineValidator {
             }
             _ => {}
         }
+
+        // Shards split the list of input files handed to the tool
+        if stage.shard.is_some() {
+            if !matches!(stage.tool, Tool::Cue { .. } | Tool::Custom { .. }) {
+                result.add_error(&format!(
+                    "Stage '{}': Only cue and custom stages can be sharded; '{}' doesn't take \
+                     a list of input files",
+                    stage.name,
+                    stage.tool_name()
+                ));
+            } else if stage.output.is_some() {
+                result.add_error(&format!(
+                    "Stage '{}': Sharded stages can't write an output file",
+                    stage.name
+                ));
+            }
+        }
+
+        // Agents are picked by label, and run the whole stage
+        if stage
+            .runs_on
+            .iter()
+            .any(|label| label.is_empty() || label.contains(char::is_whitespace))
+        {
+            result.add_error(&format!(
+                "Stage '{}': runs_on labels can't be empty or contain spaces",
+                stage.name
+            ));
+        }
+        if !stage.runs_on.is_empty() && stage.shard.is_some() {
+            result.add_error(&format!(
+                "Stage '{}': Stages that run on agents can't be sharded",
+                stage.name
+            ));
+        }
+
+        // Contracts name a registry schema or a CUE file
+        if let Some(ref contract) = stage.contract {
+            let known = RsrSchemaRegistry::new().get(contract.schema()).is_some();
+            if !contract.is_file() && !known {
+                result.add_error(&format!(
+                    "Stage '{}': Unknown contract schema '{}' (use a registry id or a .cue file)",
+                    stage.name,
+                    contract.schema()
+                ));
+            }
+        }
     }
 
     /// Validate CUE-specific stage configuration
@@ -164,11 +315,189 @@ impl PipelineValidator {
         }
     }
 
+    /// Validate plan/apply wiring of a deployment stage
+    fn validate_deploy_stage(stage: &Stage, pipeline: &Pipeline, result: &mut ValidationResult) {
+        let action = stage.tool.deploy_action();
+
+        if let Some(plan) = stage.tool.plan_stage() {
+            if action != Some(DeployAction::Apply) {
+                result.add_error(&format!(
+                    "Stage '{}': Only apply stages can reference a plan",
+                    stage.name
+                ));
+            }
+
+            match pipeline.get_stage(plan) {
+                Some(p)
+                    if p.tool_name() == stage.tool_name()
+                        && p.tool.deploy_action() == Some(DeployAction::Plan) => {}
+                Some(_) => result.add_error(&format!(
+                    "Stage '{}': Plan '{}' is not a {} plan stage",
+                    stage.name,
+                    plan,
+                    stage.tool_name()
+                )),
+                None => result.add_error(&format!(
+                    "Stage '{}': Plan references unknown stage '{}'",
+                    stage.name, plan
+                )),
+            }
+        } else if action == Some(DeployAction::Apply) {
+            result.add_warning(&format!(
+                "Stage '{}': Applies without a plan stage to check against",
+                stage.name
+            ));
+        }
+
+        if action == Some(DeployAction::Apply) && !stage.manual {
+            result.add_warning(&format!(
+                "Stage '{}': Applies changes without approval (consider 'manual: true')",
+                stage.name
+            ));
+        }
+    }
+
+    /// Validate a cost estimate stage
+    fn validate_cost_stage(stage: &Stage, pipeline: &Pipeline, result: &mut ValidationResult) {
+        let Tool::Cost {
+            source,
+            plan,
+            infracost,
+            base,
+            budget,
+            ..
+        } = &stage.tool
+        else {
+            return;
+        };
+
+        if *source == CostSource::Kubernetes && (plan.is_some() || *infracost) {
+            result.add_error(&format!(
+                "Stage '{}': 'plan' and 'infracost' only apply to Terraform estimates",
+                stage.name
+            ));
+        }
+        if *source == CostSource::Terraform && base.is_some() {
+            result.add_warning(&format!(
+                "Stage '{}': 'base' is ignored; Terraform plans carry their own baseline",
+                stage.name
+            ));
+        }
+        if budget.is_some_and(|b| b.is_nan() || b < 0.0) {
+            result.add_error(&format!(
+                "Stage '{}': Budget must be zero or more dollars",
+                stage.name
+            ));
+        }
+
+        if let Some(plan) = plan {
+            match pipeline.get_stage(plan) {
+                Some(p) if matches!(p.tool, Tool::Terraform { action: DeployAction::Plan, .. }) => {
+                }
+                Some(_) => result.add_error(&format!(
+                    "Stage '{}': Plan '{}' is not a terraform plan stage",
+                    stage.name, plan
+                )),
+                None => result.add_error(&format!(
+                    "Stage '{}': Plan references unknown stage '{}'",
+                    stage.name, plan
+                )),
+            }
+        }
+    }
+
+    /// Validate a custom executor definition
+    fn validate_executor_definition(
+        name: &str,
+        definition: &ExecutorDefinition,
+        result: &mut ValidationResult,
+    ) {
+        let builtin = ["cue", "nickel", "shell", "cost", "render"];
+        if builtin.contains(&name) || DEPLOY_TOOLS.contains(&name) {
+            result.add_error(&format!(
+                "Executor '{}': Name clashes with a built-in tool",
+                name
+            ));
+        }
+
+        if definition.command.trim().is_empty() {
+            result.add_error(&format!("Executor '{}': Command is empty", name));
+        }
+
+        if definition.success_exit_codes.is_empty() {
+            result.add_warning(&format!(
+                "Executor '{}': No success exit codes - every run will fail",
+                name
+            ));
+        }
+
+        let patterns = [
+            ("success_pattern", &definition.success_pattern),
+            ("failure_pattern", &definition.failure_pattern),
+            ("error_pattern", &definition.error_pattern),
+        ];
+        for (field, pattern) in patterns {
+            if let Some(pattern) = pattern {
+                if let Err(e) = regex::Regex::new(pattern) {
+                    result.add_error(&format!("Executor '{}': Invalid {}: {}", name, field, e));
+                }
+            }
+        }
+
+        if let Some(ref pattern) = definition.error_pattern {
+            if let Ok(re) = regex::Regex::new(pattern) {
+                if !re.capture_names().any(|n| n == Some("message")) {
+                    result.add_error(&format!(
+                        "Executor '{}': error_pattern needs a named group 'message'",
+                        name
+                    ));
+                }
+            }
+        }
+    }
+
+    /// Parse and validate pipeline YAML, reporting parse errors as validation errors
+    pub fn validate_yaml(content: &str) -> ValidationResult {
+        let mut result = ValidationResult::new();
+        match Pipeline::from_yaml(content).and_then(|pipeline| Self::validate(&pipeline)) {
+            Ok(validated) => result = validated,
+            Err(ConflowError::ConfigParse { message, .. }) => result.add_error(&message),
+            Err(e) => result.add_error(&e.to_string()),
+        }
+        result
+    }
+
     /// Check that required files exist (runtime validation)
     pub fn validate_files(pipeline: &Pipeline, base_path: &Path) -> Result<Vec<String>, ConflowError> {
         let mut missing = Vec::new();
 
+        if let Some(ref env_file) = pipeline.env_file {
+            if !base_path.join(env_file).exists() {
+                missing.push(format!("Env file not found: {}", env_file.display()));
+            }
+        }
+
         for stage in &pipeline.stages {
+            // Check env files and working directories
+            if let Some(ref env_file) = stage.env_file {
+                if !base_path.join(env_file).exists() {
+                    missing.push(format!(
+                        "Stage '{}': Env file not found: {}",
+                        stage.name,
+                        env_file.display()
+                    ));
+                }
+            }
+            if let Some(ref workdir) = stage.workdir {
+                if !base_path.join(workdir).is_dir() {
+                    missing.push(format!(
+                        "Stage '{}': Working directory not found: {}",
+                        stage.name,
+                        workdir.display()
+                    ));
+                }
+            }
+
             // Check schema files
             if let Tool::Cue { schemas, .. } = &stage.tool {
                 for schema in schemas {
@@ -232,8 +561,11 @@ impl ValidationResult {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::pipeline::{CacheConfig, CueCommand};
-    use std::collections::HashMap;
+    use crate::pipeline::{
+        CacheConfig, CueCommand, KubernetesConfig, LintConfig, SandboxConfig, Shard,
+        RedactConfig, TelemetryConfig, WalkConfig,
+    };
+    use std::collections::{BTreeMap, HashMap};
 
     #[test]
     fn test_validate_empty_pipeline() {
@@ -243,7 +575,22 @@ mod tests {
             description: None,
             stages: vec![],
             env: HashMap::new(),
+            env_file: None,
+            secrets: vec![],
             cache: CacheConfig::default(),
+            telemetry: TelemetryConfig::default(),
+            plugins: vec![],
+            executors: HashMap::new(),
+            toolchain: HashMap::new(),
+            sandbox: SandboxConfig::default(),
+            notifications: vec![],
+            refs: vec![],
+            lint: LintConfig::default(),
+            kubernetes: KubernetesConfig::default(),
+            walk: WalkConfig::default(),
+            redact: RedactConfig::default(),
+            versions: BTreeMap::new(),
+            templates: vec![],
         };
 
         let result = PipelineValidator::validate(&pipeline).unwrap();
@@ -268,11 +615,19 @@ mod tests {
                         out_format: None,
                     },
                     input: Input::Single("*.json".into()),
+                    exclude: vec![],
                     output: None,
+                    snapshot: false,
+                    contract: None,
                     depends_on: vec![],
                     allow_failure: false,
                     env: HashMap::new(),
                     condition: None,
+                    env_file: None,
+                    workdir: None,
+                    shard: None,
+                    runs_on: Vec::new(),
+                    manual: false,
                 },
                 Stage {
                     name: "dup".into(),
@@ -284,15 +639,38 @@ mod tests {
                         out_format: None,
                     },
                     input: Input::Single("*.yaml".into()),
+                    exclude: vec![],
                     output: None,
+                    snapshot: false,
+                    contract: None,
                     depends_on: vec![],
                     allow_failure: false,
                     env: HashMap::new(),
                     condition: None,
+                    env_file: None,
+                    workdir: None,
+                    shard: None,
+                    runs_on: Vec::new(),
+                    manual: false,
                 },
             ],
             env: HashMap::new(),
+            env_file: None,
+            secrets: vec![],
             cache: CacheConfig::default(),
+            telemetry: TelemetryConfig::default(),
+            plugins: vec![],
+            executors: HashMap::new(),
+            toolchain: HashMap::new(),
+            sandbox: SandboxConfig::default(),
+            notifications: vec![],
+            refs: vec![],
+            lint: LintConfig::default(),
+            kubernetes: KubernetesConfig::default(),
+            walk: WalkConfig::default(),
+            redact: RedactConfig::default(),
+            versions: BTreeMap::new(),
+            templates: vec![],
         };
 
         let result = PipelineValidator::validate(&pipeline).unwrap();
@@ -317,11 +695,19 @@ mod tests {
                         out_format: None,
                     },
                     input: Input::Single("*.json".into()),
+                    exclude: vec![],
                     output: Some(crate::pipeline::Output::File("out.json".into())),
+                    snapshot: false,
+                    contract: None,
                     depends_on: vec![],
                     allow_failure: false,
                     env: HashMap::new(),
                     condition: None,
+                    env_file: None,
+                    workdir: None,
+                    shard: None,
+                    runs_on: Vec::new(),
+                    manual: false,
                 },
                 Stage {
                     name: "second".into(),
@@ -335,15 +721,38 @@ mod tests {
                     input: Input::FromStage {
                         from_stage: "first".into(),
                     },
+                    exclude: vec![],
                     output: None,
+                    snapshot: false,
+                    contract: None,
                     depends_on: vec![], // Missing dependency declaration
                     allow_failure: false,
                     env: HashMap::new(),
                     condition: None,
+                    env_file: None,
+                    workdir: None,
+                    shard: None,
+                    runs_on: Vec::new(),
+                    manual: false,
                 },
             ],
             env: HashMap::new(),
+            env_file: None,
+            secrets: vec![],
             cache: CacheConfig::default(),
+            telemetry: TelemetryConfig::default(),
+            plugins: vec![],
+            executors: HashMap::new(),
+            toolchain: HashMap::new(),
+            sandbox: SandboxConfig::default(),
+            notifications: vec![],
+            refs: vec![],
+            lint: LintConfig::default(),
+            kubernetes: KubernetesConfig::default(),
+            walk: WalkConfig::default(),
+            redact: RedactConfig::default(),
+            versions: BTreeMap::new(),
+            templates: vec![],
         };
 
         let result = PipelineValidator::validate(&pipeline).unwrap();
@@ -352,4 +761,264 @@ mod tests {
         assert!(result.has_warnings());
         assert!(result.warnings.iter().any(|w| w.contains("implicitly")));
     }
+
+    #[test]
+    fn test_validate_custom_executors() {
+        let pipeline = Pipeline::from_yaml(
+            r#"
+name: "custom"
+executors:
+  shell:
+    command: "true"
+  lint:
+    command: "lint {inputs}"
+    error_pattern: "(?P<file>[^:]+):(?P<line>\\d+)"
+stages:
+  - name: "known"
+    tool:
+      type: custom
+      executor: lint
+    input: "*.yaml"
+  - name: "unknown"
+    tool:
+      type: custom
+      executor: missing
+    input: "*.yaml"
+"#,
+        )
+        .unwrap();
+
+        let result = PipelineValidator::validate(&pipeline).unwrap();
+        assert_eq!(result.errors.len(), 3);
+        assert!(result.errors.iter().any(|e| e.contains("clashes with a built-in")));
+        assert!(result.errors.iter().any(|e| e.contains("named group 'message'")));
+        assert!(result.errors.iter().any(|e| e.contains("Unknown executor 'missing'")));
+    }
+
+    #[test]
+    fn test_validate_shards() {
+        let pipeline = Pipeline::from_yaml(
+            r#"
+name: "shards"
+executors:
+  lint:
+    command: "lint {inputs}"
+stages:
+  - name: "vet"
+    tool: { type: cue, command: vet, schemas: [schema.cue] }
+    input: "*.yaml"
+    shard: auto
+  - name: "lint"
+    tool: { type: custom, executor: lint }
+    input: "*.yaml"
+    shard: 4
+  - name: "export"
+    tool: { type: cue, command: export }
+    input: "*.cue"
+    output: out.json
+    shard: 2
+  - name: "sh"
+    tool: { type: shell, command: "true" }
+    input: "*"
+    shard: auto
+"#,
+        )
+        .unwrap();
+        assert_eq!(pipeline.stages[1].shard, Some(Shard::Count(4)));
+        assert_eq!(Shard::Count(4).count(3), 3);
+        assert_eq!(Shard::Auto.count(Shard::AUTO_MIN_FILES), 1);
+        let stage = "{ name: a, tool: { type: shell, command: x }, input: x, shard: 0 }";
+        assert!(Pipeline::from_yaml(&format!("name: x\nstages: [{}]", stage)).is_err());
+        let stage = stage.replace("shard: 0", "shard: 1");
+        assert!(Pipeline::from_yaml(&format!("name: x\nstages: [{}]", stage)).is_ok());
+
+        let result = PipelineValidator::validate(&pipeline).unwrap();
+        assert_eq!(
+            result.errors,
+            vec![
+                "Stage 'export': Sharded stages can't write an output file",
+                "Stage 'sh': Only cue and custom stages can be sharded; 'shell' doesn't take \
+                 a list of input files",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_runs_on() {
+        let pipeline = Pipeline::from_yaml(
+            r#"
+name: "agents"
+stages:
+  - name: "deploy"
+    tool: { type: shell, command: "true" }
+    input: "*.yaml"
+    runs_on: [cluster:prod]
+  - name: "vet"
+    tool: { type: cue, command: vet }
+    input: "*.yaml"
+    runs_on: ["gpu", "two words"]
+    shard: 2
+"#,
+        )
+        .unwrap();
+        assert_eq!(pipeline.stages[0].runs_on, ["cluster:prod"]);
+
+        let result = PipelineValidator::validate(&pipeline).unwrap();
+        assert_eq!(
+            result.errors,
+            vec![
+                "Stage 'vet': runs_on labels can't be empty or contain spaces",
+                "Stage 'vet': Stages that run on agents can't be sharded",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_refs() {
+        let pipeline = Pipeline::from_yaml(
+            r#"
+name: "refs"
+stages:
+  - name: "a"
+    tool: { type: shell, command: "true" }
+    input: "*"
+refs:
+  - name: service
+    from: { files: "*.json", path: upstream }
+    to: { files: compose.yaml, path: services, keys: true }
+  - name: service
+    from: { files: { from_stage: missing }, path: name }
+    to: { files: compose.yaml, path: services, keys: true }
+"#,
+        )
+        .unwrap();
+
+        let result = PipelineValidator::validate(&pipeline).unwrap();
+        assert_eq!(result.errors.len(), 2);
+        assert!(result.errors[0].contains("Duplicate ref rule name: 'service'"));
+        assert!(result.errors[1].contains("unknown stage 'missing'"));
+    }
+
+    #[test]
+    fn test_validate_deploy_stages() {
+        let pipeline = Pipeline::from_yaml(
+            r#"
+name: "deploy"
+stages:
+  - name: "k8s-plan"
+    tool: { type: kubectl, action: plan }
+    input: "deploy/*.yaml"
+  - name: "k8s-apply"
+    tool: { type: kubectl, action: apply, plan: k8s-plan }
+    input: "deploy/*.yaml"
+    manual: true
+  - name: "tf-apply"
+    tool: { type: terraform, action: apply, plan: k8s-plan }
+    input: "*.tf"
+"#,
+        )
+        .unwrap();
+
+        let result = PipelineValidator::validate(&pipeline).unwrap();
+        assert_eq!(
+            result.errors,
+            vec!["Stage 'tf-apply': Plan 'k8s-plan' is not a terraform plan stage"]
+        );
+        assert_eq!(result.warnings.len(), 1);
+        assert!(result.warnings[0].contains("'tf-apply': Applies changes without approval"));
+
+        let dag = DagBuilder::build(&pipeline).unwrap();
+        assert!(dag.depends_on("k8s-apply", "k8s-plan"));
+    }
+
+    #[test]
+    fn test_validate_cost_stages() {
+        let pipeline = Pipeline::from_yaml(
+            r#"
+name: "costs"
+stages:
+  - name: "tf-plan"
+    tool: { type: terraform, action: plan }
+    input: "*.tf"
+  - name: "tf-cost"
+    tool: { type: cost, source: terraform, plan: tf-plan, budget: 100 }
+    input: "*.tf"
+  - name: "k8s-cost"
+    tool: { type: cost, source: kubernetes, plan: tf-cost, budget: -1 }
+    input: "k8s/*.yaml"
+"#,
+        )
+        .unwrap();
+
+        let result = PipelineValidator::validate(&pipeline).unwrap();
+        assert_eq!(
+            result.errors,
+            vec![
+                "Stage 'k8s-cost': 'plan' and 'infracost' only apply to Terraform estimates",
+                "Stage 'k8s-cost': Budget must be zero or more dollars",
+                "Stage 'k8s-cost': Plan 'tf-cost' is not a terraform plan stage",
+            ]
+        );
+
+        let dag = DagBuilder::build(&pipeline).unwrap();
+        assert!(dag.depends_on("tf-cost", "tf-plan"));
+        assert!(!pipeline.get_stage("tf-cost").unwrap().is_cacheable());
+    }
+
+    #[test]
+    fn test_validate_contracts() {
+        let pipeline = Pipeline::from_yaml(
+            r##"
+name: "contracts"
+stages:
+  - name: "render"
+    tool: { type: cue, command: export }
+    input: "app.cue"
+    contract: k8s:base
+  - name: "export"
+    tool: { type: cue, command: export }
+    input: "app.cue"
+    contract: { schema: schemas/app.cue, definition: "#App" }
+  - name: "typo"
+    tool: { type: cue, command: export }
+    input: "app.cue"
+    contract: k8s:bsae
+"##,
+        )
+        .unwrap();
+
+        let export = pipeline.get_stage("export").unwrap().contract.as_ref();
+        assert_eq!(export.and_then(|c| c.definition()), Some("#App"));
+        let result = PipelineValidator::validate(&pipeline).unwrap();
+        assert_eq!(
+            result.errors,
+            vec![
+                "Stage 'typo': Unknown contract schema 'k8s:bsae' (use a registry id or a .cue file)"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_redact() {
+        let pipeline = Pipeline::from_yaml(
+            r#"
+name: "redact"
+redact:
+  paths: ["$..password", "$.data[x]"]
+  env: [API_TOKEN]
+  patterns: ["token=(\\S+)", "("]
+stages:
+  - name: "vet"
+    tool: { type: cue, command: vet, schemas: [schema.cue] }
+    input: "*.yaml"
+"#,
+        )
+        .unwrap();
+
+        assert!(pipeline.masked_vars().contains(&"API_TOKEN".to_string()));
+        let result = PipelineValidator::validate(&pipeline).unwrap();
+        assert_eq!(result.errors.len(), 2);
+        assert!(result.errors[0].starts_with("Redact: invalid path '$.data[x]'"));
+        assert!(result.errors[1].starts_with("Redact: invalid pattern '('"));
+    }
 }
@@ -0,0 +1,376 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Version propagation
+//!
+//! The `versions:` section of a pipeline maps files to the JSONPath
+//! locations in them that hold the project's version:
+//!
+//! ```yaml
+//! versions:
+//!   Cargo.toml: $.package.version
+//!   chart/Chart.yaml: [$.version, $.appVersion]
+//!   web/package.json: $.version
+//! ```
+//!
+//! Without it, the version fields of Cargo.toml, package.json,
+//! pyproject.toml and Chart.yaml at the project root are used. `conflow
+//! version check` fails when the locations disagree, and `conflow version
+//! bump` rewrites every one of them or, when one can't be rewritten in
+//! place, none. Edits touch only the version string, so formatting and
+//! comments stay as they were.
+
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use super::{Pipeline, VersionPaths};
+use crate::audit;
+use crate::errors::ConflowError;
+use crate::utils::redact::JsonPath;
+use crate::utils::yaml::edit::{Change, Editor};
+
+/// Manifests whose version fields are used without a `versions:` section,
+/// with the locations tried in each
+pub const DEFAULT_VERSION_LOCATIONS: &[(&str, &[&str])] = &[
+    (
+        "Cargo.toml",
+        &["$.package.version", "$.workspace.package.version"],
+    ),
+    ("package.json", &["$.version"]),
+    ("pyproject.toml", &["$.project.version"]),
+    ("Chart.yaml", &["$.version"]),
+];
+
+/// A version found at one location
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct VersionLocation {
+    /// File, relative to the project root
+    pub file: PathBuf,
+    /// JSONPath location in the file
+    pub path: String,
+    pub version: String,
+}
+
+impl std::fmt::Display for VersionLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} = {}",
+            self.file.display(),
+            self.path,
+            self.version
+        )
+    }
+}
+
+/// The version locations of the project in `project_root`: its pipeline's
+/// `versions:` section, or the default manifest fields that exist
+pub fn version_locations(project_root: &Path) -> BTreeMap<PathBuf, VersionPaths> {
+    let configured = Pipeline::from_file(&project_root.join(".conflow.yaml"))
+        .map(|p| p.versions)
+        .unwrap_or_default();
+    if !configured.is_empty() {
+        return configured;
+    }
+
+    let mut defaults = BTreeMap::new();
+    for (file, paths) in DEFAULT_VERSION_LOCATIONS {
+        let Ok(content) = std::fs::read_to_string(project_root.join(file)) else {
+            continue;
+        };
+        let Ok(document) = parse(Path::new(file), &content) else {
+            continue;
+        };
+        let found = paths.iter().find(|path| {
+            JsonPath::parse(path).is_ok_and(|p| {
+                p.select(&document)
+                    .iter()
+                    .any(|v| version_string(v).is_some())
+            })
+        });
+        if let Some(path) = found {
+            defaults.insert(PathBuf::from(file), VersionPaths::Single(path.to_string()));
+        }
+    }
+    defaults
+}
+
+/// Read every version location from the files in `project_root`
+pub fn read_versions(
+    project_root: &Path,
+    locations: &BTreeMap<PathBuf, VersionPaths>,
+) -> Result<Vec<VersionLocation>, ConflowError> {
+    read_versions_with(locations, |file| {
+        std::fs::read_to_string(project_root.join(file)).ok()
+    })
+}
+
+/// Read every version location, getting file content from `read_file`
+///
+/// Lets versions be read at another revision, e.g. with
+/// [`crate::utils::git::file_at`].
+pub fn read_versions_with(
+    locations: &BTreeMap<PathBuf, VersionPaths>,
+    read_file: impl Fn(&Path) -> Option<String>,
+) -> Result<Vec<VersionLocation>, ConflowError> {
+    let mut found = vec![];
+    for (file, paths) in locations {
+        let error = |path: &str, message: String| ConflowError::VersionLocation {
+            file: file.clone(),
+            path: path.to_string(),
+            message,
+        };
+        let first = paths.paths().first().copied().unwrap_or("$");
+        let content = read_file(file).ok_or_else(|| error(first, "file not found".into()))?;
+        let document = parse(file, &content).map_err(|e| error(first, e))?;
+
+        for path in paths.paths() {
+            let json_path = JsonPath::parse(path).map_err(|e| error(path, e))?;
+            let values = json_path.select(&document);
+            if values.is_empty() {
+                return Err(error(path, "no value at this path".into()));
+            }
+            for value in values {
+                let version = version_string(value)
+                    .ok_or_else(|| error(path, format!("{} is not a version", value)))?;
+                found.push(VersionLocation {
+                    file: file.clone(),
+                    path: path.to_string(),
+                    version,
+                });
+            }
+        }
+    }
+    Ok(found)
+}
+
+/// The one version every location holds
+///
+/// Fails with [`ConflowError::VersionMismatch`] listing the locations when
+/// they disagree, and returns `None` when there are none.
+pub fn check_versions(found: &[VersionLocation]) -> Result<Option<String>, ConflowError> {
+    let Some(first) = found.first() else {
+        return Ok(None);
+    };
+    if found.iter().all(|l| l.version == first.version) {
+        return Ok(Some(first.version.clone()));
+    }
+    Err(ConflowError::VersionMismatch {
+        versions: found.iter().map(|l| l.to_string()).collect(),
+    })
+}
+
+/// Set every version location in `project_root` to `version`
+///
+/// Every file is rewritten in memory first, so when one location can't be
+/// changed in place no file is touched. Returns the files written.
+pub fn bump_versions(
+    project_root: &Path,
+    locations: &BTreeMap<PathBuf, VersionPaths>,
+    version: &str,
+) -> Result<Vec<PathBuf>, ConflowError> {
+    let mut rewritten = vec![];
+    for (file, paths) in locations {
+        let error = |path: &str, message: &str| ConflowError::VersionLocation {
+            file: file.clone(),
+            path: path.to_string(),
+            message: message.to_string(),
+        };
+        let first = paths.paths().first().copied().unwrap_or("$");
+        let original = std::fs::read_to_string(project_root.join(file))
+            .map_err(|e| error(first, &e.to_string()))?;
+
+        let mut content = original.clone();
+        for path in paths.paths() {
+            let json_path = JsonPath::parse(path).map_err(|e| error(path, &e))?;
+            let document = parse(file, &content).map_err(|e| error(path, &e))?;
+            let targets = json_path.locate(&document);
+            if targets.is_empty() {
+                return Err(error(path, "no value at this path"));
+            }
+            for target in targets {
+                content = set(file, &content, &target, version)
+                    .ok_or_else(|| error(path, "the value can't be rewritten in place"))?;
+            }
+        }
+        if content != original {
+            rewritten.push((file.clone(), original, content));
+        }
+    }
+
+    let action = format!("version bump {}", version);
+    let mut written: Vec<(PathBuf, String)> = vec![];
+    for (file, original, content) in rewritten {
+        if let Err(e) = audit::write(project_root, &file, &content, &action) {
+            // Put back what was already written, so the files still agree
+            for (done, previous) in written.iter().rev() {
+                let _ = audit::write(project_root, done, previous, &action);
+            }
+            return Err(ConflowError::Io {
+                message: format!("{}: {}", file.display(), e),
+            });
+        }
+        written.push((file, original));
+    }
+    Ok(written.into_iter().map(|(file, _)| file).collect())
+}
+
+/// A version value as a string; YAML reads a bare `1.2` as a number
+fn version_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) if !s.is_empty() => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// Parse a TOML, JSON or YAML file as JSON data
+fn parse(file: &Path, content: &str) -> Result<Value, String> {
+    match file.extension().and_then(|e| e.to_str()) {
+        Some("toml") => toml::from_str(content).map_err(|e| e.message().to_string()),
+        Some("json") => serde_json::from_str(content).map_err(|e| e.to_string()),
+        Some("yaml" | "yml") => serde_yaml::from_str(content).map_err(|e| e.to_string()),
+        _ => Err("only TOML, JSON and YAML files are supported".into()),
+    }
+}
+
+/// Set the string at `target` to `version`, changing nothing else
+fn set(file: &Path, content: &str, target: &[String], version: &str) -> Option<String> {
+    if matches!(
+        file.extension().and_then(|e| e.to_str()),
+        Some("yaml" | "yml")
+    ) {
+        let mut editor = Editor::new(content);
+        let change = Change::Set {
+            path: target.to_vec(),
+            value: serde_yaml::Value::String(version.to_string()),
+        };
+        return editor.apply(0, &change).then(|| editor.into_content());
+    }
+
+    // Replace the one quoted occurrence of the old version whose change
+    // gives exactly the expected data
+    let mut expected = parse(file, content).ok()?;
+    let pointer: String = target
+        .iter()
+        .map(|s| format!("/{}", s.replace('~', "~0").replace('/', "~1")))
+        .collect();
+    let slot = expected.pointer_mut(&pointer)?;
+    let old = slot.as_str()?.to_string();
+    *slot = Value::String(version.to_string());
+
+    for quote in ['"', '\''] {
+        let needle = format!("{quote}{old}{quote}");
+        for (start, _) in content.match_indices(&needle) {
+            let edited = format!(
+                "{}{quote}{}{quote}{}",
+                &content[..start],
+                version,
+                &content[start + needle.len()..]
+            );
+            if parse(file, &edited).ok().as_ref() == Some(&expected) {
+                return Some(edited);
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const CARGO: &str = "[package]\nname = \"demo\" # the crate\nversion = \"1.2.0\"\n\n\
+                         [dependencies]\nserde = \"1.2.0\"\n";
+    const CHART: &str = "# chart\napiVersion: v2\nname: demo\nversion: 1.2.0\n\
+                         appVersion: \"1.2.0\"  # app\n";
+    const PACKAGE: &str = "{\n  \"name\": \"demo\",\n  \"version\": \"1.1.0\"\n}\n";
+
+    fn project() -> TempDir {
+        let temp = TempDir::new().unwrap();
+        std::fs::write(temp.path().join("Cargo.toml"), CARGO).unwrap();
+        std::fs::create_dir(temp.path().join("chart")).unwrap();
+        std::fs::write(temp.path().join("chart/Chart.yaml"), CHART).unwrap();
+        std::fs::write(temp.path().join("package.json"), PACKAGE).unwrap();
+        temp
+    }
+
+    fn configured() -> BTreeMap<PathBuf, VersionPaths> {
+        BTreeMap::from([
+            (
+                "Cargo.toml".into(),
+                VersionPaths::Single("$.package.version".into()),
+            ),
+            (
+                "chart/Chart.yaml".into(),
+                VersionPaths::Multiple(vec!["$.version".into(), "$.appVersion".into()]),
+            ),
+            (
+                "package.json".into(),
+                VersionPaths::Single("$.version".into()),
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_check_versions() {
+        let temp = project();
+        let found = read_versions(temp.path(), &configured()).unwrap();
+        assert_eq!(found.len(), 4);
+        let Err(ConflowError::VersionMismatch { versions }) = check_versions(&found) else {
+            panic!("expected a mismatch");
+        };
+        assert!(versions.contains(&"package.json $.version = 1.1.0".to_string()));
+        assert_eq!(
+            check_versions(&found[..3]).unwrap().as_deref(),
+            Some("1.2.0")
+        );
+
+        // Only the root manifests, without versions:
+        let defaults = version_locations(temp.path());
+        assert_eq!(defaults.len(), 2);
+        assert_eq!(
+            defaults[Path::new("Cargo.toml")],
+            VersionPaths::Single("$.package.version".into())
+        );
+
+        let missing = BTreeMap::from([(
+            PathBuf::from("package.json"),
+            VersionPaths::Single("$.release".into()),
+        )]);
+        assert!(matches!(
+            read_versions(temp.path(), &missing),
+            Err(ConflowError::VersionLocation { .. })
+        ));
+    }
+
+    #[test]
+    fn test_bump_versions() {
+        let temp = project();
+        let written = bump_versions(temp.path(), &configured(), "1.3.0").unwrap();
+        assert_eq!(written.len(), 3);
+
+        let read_file = |file: &str| std::fs::read_to_string(temp.path().join(file)).unwrap();
+        // Only the version changes, not the dependency on the same version
+        assert_eq!(read_file("Cargo.toml"), CARGO.replacen("1.2.0", "1.3.0", 1));
+        assert_eq!(
+            read_file("chart/Chart.yaml"),
+            CHART.replace("1.2.0", "1.3.0")
+        );
+        assert_eq!(read_file("package.json"), PACKAGE.replace("1.1.0", "1.3.0"));
+        let found = read_versions(temp.path(), &configured()).unwrap();
+        assert_eq!(check_versions(&found).unwrap().as_deref(), Some("1.3.0"));
+
+        // One location that can't be changed leaves every file alone
+        let mut broken = configured();
+        broken.insert(
+            "package.json".into(),
+            VersionPaths::Single("$.name.first".into()),
+        );
+        assert!(bump_versions(temp.path(), &broken, "2.0.0").is_err());
+        assert_eq!(read_file("Cargo.toml"), CARGO.replacen("1.2.0", "1.3.0", 1));
+    }
+}
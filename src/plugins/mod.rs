@@ -0,0 +1,434 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! WASM plugins
+//!
+//! Plugins are WebAssembly modules that add analyzers, validators, and
+//! remediators. They receive the parsed configuration as JSON, so they can
+//! inspect structure rather than re-parsing text.
+//!
+//! ## Guest API (version 1)
+//!
+//! A plugin exports `memory` and the following functions. Results are
+//! returned as an `i64` packing a pointer (high 32 bits) and a length (low
+//! 32 bits) into guest memory; all payloads are UTF-8 JSON.
+//!
+//! | Export                         | Signature          | Payload                          |
+//! |--------------------------------|--------------------|----------------------------------|
+//! | `conflow_abi_version`          | `() -> i32`        | must return `1`                  |
+//! | `conflow_alloc`                | `(len) -> ptr`     | buffer for the host to write to  |
+//! | `conflow_describe`             | `() -> i64`        | [`PluginManifest`]               |
+//! | `conflow_analyze` / `_validate` / `_remediate` | `(ptr, len) -> i64` | [`PluginRequest`] in, [`PluginResponse`] out |
+//!
+//! Multi-document YAML streams are sent one document at a time, with
+//! `document` set to its 0-based index; finding lines are relative to that
+//! document.
+//!
+//! Modules run sandboxed with no imports and a bounded fuel budget.
+//! Plugins are discovered from `~/.conflow/plugins/*.wasm` and the
+//! `plugins:` list in `.conflow.yaml`. Executing them requires building
+//! with the `wasm-plugins` feature.
+
+#[cfg(feature = "wasm-plugins")]
+mod wasm;
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::analyzer::{detect_format, ConfigFormat};
+use crate::errors::ConflowError;
+use crate::utils::yaml;
+
+/// Guest API version understood by this host
+pub const ABI_VERSION: i32 = 1;
+
+/// What a plugin can do
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Capability {
+    /// Report findings about a config
+    Analyze,
+    /// Pass/fail validation with errors
+    Validate,
+    /// Propose fixed file contents
+    Remediate,
+}
+
+impl Capability {
+    /// Name of the guest export implementing this capability
+    pub fn export_name(&self) -> &'static str {
+        match self {
+            Self::Analyze => "conflow_analyze",
+            Self::Validate => "conflow_validate",
+            Self::Remediate => "conflow_remediate",
+        }
+    }
+}
+
+/// Plugin self-description returned by `conflow_describe`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginManifest {
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub capabilities: Vec<Capability>,
+}
+
+/// Input passed to a plugin
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginRequest {
+    /// Path of the config file
+    pub path: PathBuf,
+    /// Detected format (json, yaml, toml, cue, nickel, unknown)
+    pub format: String,
+    /// Raw content of the file, or of the document for YAML streams
+    pub content: String,
+    /// Parsed content for JSON/YAML/TOML, `null` otherwise
+    pub parsed: serde_json::Value,
+    /// Index of the document in a multi-document YAML stream
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub document: Option<usize>,
+    /// 1-based line of `content` within the file (host only)
+    #[serde(skip)]
+    pub first_line: usize,
+}
+
+impl PluginRequest {
+    /// Build requests from a config file on disk
+    ///
+    /// A multi-document YAML file yields one request per document.
+    pub fn from_file(path: &Path) -> Result<Vec<Self>, ConflowError> {
+        let content = std::fs::read_to_string(path).map_err(|e| ConflowError::FileReadError {
+            path: path.to_path_buf(),
+            error: e.to_string(),
+        })?;
+        let format = detect_format(&content, path)?;
+        let request = |content: &str, parsed, document, first_line| Self {
+            path: path.to_path_buf(),
+            format: format!("{:?}", format).to_lowercase(),
+            content: content.to_string(),
+            parsed,
+            document,
+            first_line,
+        };
+
+        if format == ConfigFormat::Yaml {
+            let docs = yaml::documents(&content);
+            let parsed: Vec<serde_json::Value> =
+                yaml::parse_documents(&content, &path.display().to_string())?;
+            if docs.len() > 1 {
+                return Ok(docs
+                    .iter()
+                    .zip(parsed)
+                    .map(|(doc, parsed)| request(doc.content, parsed, Some(doc.index), doc.line))
+                    .collect());
+            }
+            let parsed = parsed.into_iter().next().unwrap_or_default();
+            return Ok(vec![request(&content, parsed, None, 1)]);
+        }
+
+        let parsed = match format {
+            ConfigFormat::Json => serde_json::from_str(&content)?,
+            ConfigFormat::Toml => {
+                let value: toml::Value = toml::from_str(&content)?;
+                serde_json::to_value(value)?
+            }
+            _ => serde_json::Value::Null,
+        };
+
+        Ok(vec![request(&content, parsed, None, 1)])
+    }
+}
+
+/// Finding severity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+}
+
+/// A single plugin finding
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginFinding {
+    pub severity: Severity,
+    pub message: String,
+    #[serde(default)]
+    pub rule: Option<String>,
+    #[serde(default)]
+    pub line: Option<usize>,
+    #[serde(default)]
+    pub column: Option<usize>,
+}
+
+/// A proposed fix: replacement content for the file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginFix {
+    pub description: String,
+    pub content: String,
+}
+
+/// Output returned by a plugin
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PluginResponse {
+    #[serde(default)]
+    pub findings: Vec<PluginFinding>,
+    #[serde(default)]
+    pub fixes: Vec<PluginFix>,
+}
+
+impl PluginResponse {
+    /// Whether any finding is an error
+    pub fn has_errors(&self) -> bool {
+        self.findings.iter().any(|f| f.severity == Severity::Error)
+    }
+}
+
+/// User-level plugin directory (`~/.conflow/plugins`)
+pub fn user_plugin_dir() -> Option<PathBuf> {
+    directories::BaseDirs::new().map(|d| d.home_dir().join(".conflow").join("plugins"))
+}
+
+/// Find plugin modules
+///
+/// `configured` paths are resolved against `base_dir`; `.wasm` files in
+/// `user_dir` are appended. Duplicates are removed.
+pub fn discover(configured: &[PathBuf], base_dir: &Path, user_dir: Option<&Path>) -> Vec<PathBuf> {
+    let mut plugins: Vec<PathBuf> = configured.iter().map(|p| base_dir.join(p)).collect();
+
+    if let Some(dir) = user_dir {
+        if let Ok(entries) = std::fs::read_dir(dir) {
+            let mut found: Vec<PathBuf> = entries
+                .flatten()
+                .map(|e| e.path())
+                .filter(|p| p.extension().is_some_and(|e| e == "wasm"))
+                .collect();
+            found.sort();
+            plugins.extend(found);
+        }
+    }
+
+    let mut seen = std::collections::HashSet::new();
+    plugins.retain(|p| seen.insert(p.clone()));
+    plugins
+}
+
+/// A loaded plugin
+pub struct Plugin {
+    path: PathBuf,
+    manifest: PluginManifest,
+    #[cfg(feature = "wasm-plugins")]
+    module: wasm::WasmModule,
+}
+
+impl Plugin {
+    /// Load a plugin module and read its manifest
+    #[cfg(feature = "wasm-plugins")]
+    pub fn load(path: &Path) -> Result<Self, ConflowError> {
+        let bytes = std::fs::read(path).map_err(|e| ConflowError::FileReadError {
+            path: path.to_path_buf(),
+            error: e.to_string(),
+        })?;
+        Self::from_bytes(path, &bytes)
+    }
+
+    /// Load a plugin module and read its manifest
+    #[cfg(not(feature = "wasm-plugins"))]
+    pub fn load(path: &Path) -> Result<Self, ConflowError> {
+        Err(ConflowError::Plugin {
+            plugin: path.display().to_string(),
+            message: "WASM plugin support is not enabled".into(),
+            help: Some("Rebuild conflow with `--features wasm-plugins`".into()),
+        })
+    }
+
+    #[cfg(feature = "wasm-plugins")]
+    fn from_bytes(path: &Path, bytes: &[u8]) -> Result<Self, ConflowError> {
+        let name = path.display().to_string();
+        let err = |message: String| ConflowError::Plugin {
+            plugin: name.clone(),
+            message,
+            help: None,
+        };
+
+        let module = wasm::WasmModule::new(bytes).map_err(err)?;
+
+        let abi = module.abi_version().map_err(err)?;
+        if abi != ABI_VERSION {
+            return Err(ConflowError::Plugin {
+                plugin: name,
+                message: format!("unsupported guest API version {}", abi),
+                help: Some(format!(
+                    "This conflow supports plugin API version {}",
+                    ABI_VERSION
+                )),
+            });
+        }
+
+        let raw = module.call("conflow_describe", None).map_err(err)?;
+        let manifest: PluginManifest =
+            serde_json::from_slice(&raw).map_err(|e| err(format!("invalid manifest: {}", e)))?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            manifest,
+            module,
+        })
+    }
+
+    /// Path the plugin was loaded from
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Plugin manifest
+    pub fn manifest(&self) -> &PluginManifest {
+        &self.manifest
+    }
+
+    /// Whether the plugin implements a capability
+    pub fn supports(&self, capability: Capability) -> bool {
+        self.manifest.capabilities.contains(&capability)
+    }
+
+    #[cfg(feature = "wasm-plugins")]
+    fn invoke(&self, export: &str, input: &[u8]) -> Result<Vec<u8>, String> {
+        self.module.call(export, Some(input))
+    }
+
+    #[cfg(not(feature = "wasm-plugins"))]
+    fn invoke(&self, _export: &str, _input: &[u8]) -> Result<Vec<u8>, String> {
+        Err("WASM plugin support is not enabled".into())
+    }
+
+    /// Invoke a capability on a config file
+    pub fn call(
+        &self,
+        capability: Capability,
+        request: &PluginRequest,
+    ) -> Result<PluginResponse, ConflowError> {
+        let err = |message: String| ConflowError::Plugin {
+            plugin: self.manifest.name.clone(),
+            message,
+            help: None,
+        };
+
+        if !self.supports(capability) {
+            return Err(err(format!("does not support {:?}", capability)));
+        }
+
+        let input = serde_json::to_vec(request)?;
+
+        let raw = self.invoke(capability.export_name(), &input).map_err(err)?;
+
+        serde_json::from_slice(&raw).map_err(|e| err(format!("invalid response: {}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_discover() {
+        let temp = TempDir::new().unwrap();
+        let user = temp.path().join("user");
+        std::fs::create_dir(&user).unwrap();
+        std::fs::write(user.join("b.wasm"), b"").unwrap();
+        std::fs::write(user.join("a.wasm"), b"").unwrap();
+        std::fs::write(user.join("notes.txt"), b"").unwrap();
+
+        let found = discover(
+            &[PathBuf::from("plugins/lint.wasm")],
+            temp.path(),
+            Some(&user),
+        );
+        assert_eq!(
+            found,
+            vec![
+                temp.path().join("plugins/lint.wasm"),
+                user.join("a.wasm"),
+                user.join("b.wasm"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_request_includes_parsed_config() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("app.yaml");
+        std::fs::write(&path, "replicas: 3\nname: web\n").unwrap();
+
+        let request = PluginRequest::from_file(&path).unwrap().remove(0);
+        assert_eq!(request.format, "yaml");
+        assert_eq!(request.parsed["replicas"], 3);
+        assert_eq!(request.document, None);
+    }
+
+    #[test]
+    fn test_request_per_yaml_document() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("manifests.yaml");
+        std::fs::write(&path, "---\nkind: Service\n---\n# app\nkind: Deployment\n").unwrap();
+
+        let requests = PluginRequest::from_file(&path).unwrap();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[1].parsed["kind"], "Deployment");
+        assert_eq!(requests[1].document, Some(1));
+        assert_eq!(requests[1].first_line, 4);
+
+        let json = serde_json::to_value(&requests[0]).unwrap();
+        assert_eq!(json["document"], 0);
+        assert!(json.get("first_line").is_none());
+    }
+
+    #[test]
+    fn test_response_defaults() {
+        let response: PluginResponse =
+            serde_json::from_str(r#"{"findings":[{"severity":"warning","message":"x"}]}"#).unwrap();
+        assert!(!response.has_errors());
+        assert!(response.fixes.is_empty());
+    }
+
+    #[cfg(feature = "wasm-plugins")]
+    #[test]
+    fn test_load_and_call_wasm_plugin() {
+        let describe = r#"{"name":"demo","version":"0.1.0","capabilities":["validate"]}"#;
+        let validate = r#"{"findings":[{"severity":"error","message":"bad port","line":3}]}"#;
+        let wat = format!(
+            r#"(module
+                (memory (export "memory") 1)
+                (data (i32.const 0) "{describe_data}")
+                (data (i32.const 512) "{validate_data}")
+                (func (export "conflow_abi_version") (result i32) i32.const 1)
+                (func (export "conflow_alloc") (param i32) (result i32) i32.const 1024)
+                (func (export "conflow_describe") (result i64) i64.const {describe_len})
+                (func (export "conflow_validate") (param i32 i32) (result i64)
+                    i64.const {validate_packed}))"#,
+            describe_data = describe.replace('"', "\\\""),
+            validate_data = validate.replace('"', "\\\""),
+            describe_len = describe.len(),
+            validate_packed = (512i64 << 32) | validate.len() as i64,
+        );
+
+        let plugin = Plugin::from_bytes(Path::new("demo.wasm"), wat.as_bytes()).unwrap();
+        assert_eq!(plugin.manifest().name, "demo");
+        assert!(plugin.supports(Capability::Validate));
+        assert!(!plugin.supports(Capability::Analyze));
+
+        let request = PluginRequest {
+            path: "app.yaml".into(),
+            format: "yaml".into(),
+            content: "port: x".into(),
+            parsed: serde_json::json!({ "port": "x" }),
+            document: None,
+            first_line: 1,
+        };
+        let response = plugin.call(Capability::Validate, &request).unwrap();
+        assert!(response.has_errors());
+        assert_eq!(response.findings[0].line, Some(3));
+    }
+}
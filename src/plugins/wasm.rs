@@ -0,0 +1,167 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! wasmtime-backed plugin runtime
+//!
+//! Every call runs in a fresh instance, so plugins cannot keep state between
+//! files and a misbehaving call cannot affect the next one.
+
+use wasmtime::{
+    Config, Engine, Instance, Linker, Memory, Module, Store, StoreLimits, StoreLimitsBuilder,
+};
+
+/// Fuel budget per call, bounding runaway plugins
+const FUEL_PER_CALL: u64 = 5_000_000_000;
+
+/// Largest linear memory a plugin may grow to, in bytes
+const MAX_MEMORY: usize = 256 * 1024 * 1024;
+
+/// Largest output a plugin may return, in bytes
+const MAX_OUTPUT: usize = 16 * 1024 * 1024;
+
+/// A compiled plugin module
+pub struct WasmModule {
+    engine: Engine,
+    module: Module,
+}
+
+impl WasmModule {
+    /// Compile a module from binary or text format
+    pub fn new(bytes: &[u8]) -> Result<Self, String> {
+        let mut config = Config::new();
+        config.consume_fuel(true);
+
+        let engine = Engine::new(&config).map_err(|e| format!("{:#}", e))?;
+        let module = Module::new(&engine, bytes).map_err(|e| format!("{:#}", e))?;
+
+        Ok(Self { engine, module })
+    }
+
+    /// Read the guest API version
+    pub fn abi_version(&self) -> Result<i32, String> {
+        let (mut store, instance) = self.instantiate()?;
+        instance
+            .get_typed_func::<(), i32>(&mut store, "conflow_abi_version")
+            .and_then(|f| f.call(&mut store, ()))
+            .map_err(|e| format!("conflow_abi_version: {:#}", e))
+    }
+
+    /// Call an export, optionally passing a payload, and return its output
+    pub fn call(&self, export: &str, input: Option<&[u8]>) -> Result<Vec<u8>, String> {
+        let (mut store, instance) = self.instantiate()?;
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .ok_or("module does not export `memory`")?;
+
+        let packed = match input {
+            Some(bytes) => {
+                let alloc = instance
+                    .get_typed_func::<i32, i32>(&mut store, "conflow_alloc")
+                    .map_err(|e| format!("conflow_alloc: {:#}", e))?;
+                let ptr = alloc
+                    .call(&mut store, bytes.len() as i32)
+                    .map_err(|e| format!("conflow_alloc: {:#}", e))?;
+                memory
+                    .write(&mut store, ptr as usize, bytes)
+                    .map_err(|e| format!("writing input: {}", e))?;
+
+                instance
+                    .get_typed_func::<(i32, i32), i64>(&mut store, export)
+                    .and_then(|f| f.call(&mut store, (ptr, bytes.len() as i32)))
+                    .map_err(|e| format!("{}: {:#}", export, e))?
+            }
+            None => instance
+                .get_typed_func::<(), i64>(&mut store, export)
+                .and_then(|f| f.call(&mut store, ()))
+                .map_err(|e| format!("{}: {:#}", export, e))?,
+        };
+
+        read_packed(&store, memory, packed)
+    }
+
+    fn instantiate(&self) -> Result<(Store<StoreLimits>, Instance), String> {
+        let limits = StoreLimitsBuilder::new().memory_size(MAX_MEMORY).build();
+        let mut store = Store::new(&self.engine, limits);
+        store.limiter(|limits| limits);
+        store
+            .set_fuel(FUEL_PER_CALL)
+            .map_err(|e| format!("{:#}", e))?;
+
+        // No host imports: plugins are pure functions over their input
+        let instance = Linker::new(&self.engine)
+            .instantiate(&mut store, &self.module)
+            .map_err(|e| format!("instantiation failed: {:#}", e))?;
+
+        Ok((store, instance))
+    }
+}
+
+/// Read a (ptr << 32 | len) result out of guest memory
+///
+/// The range is checked against the memory's size before anything is
+/// allocated, so a bogus result cannot make the host reserve 4 GiB.
+fn read_packed(store: &Store<StoreLimits>, memory: Memory, packed: i64) -> Result<Vec<u8>, String> {
+    let packed = packed as u64;
+    let ptr = (packed >> 32) as usize;
+    let len = (packed & 0xffff_ffff) as usize;
+
+    if len > MAX_OUTPUT {
+        return Err(format!(
+            "output of {} bytes exceeds the {} byte limit",
+            len, MAX_OUTPUT
+        ));
+    }
+    if ptr + len > memory.data_size(store) {
+        return Err(format!(
+            "output at {}..{} lies outside guest memory ({} bytes)",
+            ptr,
+            ptr + len,
+            memory.data_size(store)
+        ));
+    }
+
+    let mut buf = vec![0u8; len];
+    memory
+        .read(store, ptr, &mut buf)
+        .map_err(|e| format!("reading output: {}", e))?;
+    Ok(buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn module(body: &str) -> WasmModule {
+        let wat = format!("(module (memory (export \"memory\") 1) {})", body);
+        WasmModule::new(wat.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_output_must_lie_in_guest_memory() {
+        let plugin = module(
+            r#"(data (i32.const 8) "ok")
+               (func (export "good") (result i64) (i64.const 0x0000000800000002))
+               (func (export "outside") (result i64) (i64.const 0x0000fff000002000))
+               (func (export "huge") (result i64) (i64.const 0x00000000ffffffff))"#,
+        );
+
+        assert_eq!(plugin.call("good", None).unwrap(), b"ok");
+        let err = plugin.call("outside", None).unwrap_err();
+        assert!(err.contains("outside guest memory"), "{}", err);
+        let err = plugin.call("huge", None).unwrap_err();
+        assert!(err.contains("byte limit"), "{}", err);
+    }
+
+    #[test]
+    fn test_memory_growth_is_capped() {
+        // Writes 1 at address 0 when growing to 512 MiB is refused
+        let plugin = module(
+            r#"(func (export "grow") (result i64)
+                 (i32.store8 (i32.const 0)
+                   (i32.eq (memory.grow (i32.const 8192)) (i32.const -1)))
+                 (i64.const 1))"#,
+        );
+
+        assert_eq!(plugin.call("grow", None).unwrap(), vec![1]);
+    }
+}
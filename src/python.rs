@@ -0,0 +1,110 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Python module
+//!
+//! The `conflow` extension module, built with the `conflow-py` feature:
+//!
+//! ```bash
+//! PYO3_BUILD_EXTENSION_MODULE=1 \
+//!   cargo rustc --lib --release --features conflow-py --crate-type cdylib
+//! cp target/release/libconflow.so conflow.so
+//! ```
+//!
+//! `analyze`, `validate` and `check_compliance` take a path and return what
+//! the [C ABI](crate::ffi) returns, as dicts and lists. Failures raise
+//! `conflow.ConflowError`. The GIL is released while conflow works.
+//!
+//! ```python
+//! import conflow
+//! conflow.analyze("config.yaml")["recommendation"]["primary"]
+//! ```
+
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+use std::path::{Path, PathBuf};
+
+use crate::ffi;
+
+pyo3::create_exception!(
+    conflow,
+    ConflowError,
+    PyException,
+    "An error reported by conflow"
+);
+
+type Call = fn(&Path) -> Result<String, crate::ConflowError>;
+
+/// Run `call` on `path` without the GIL and load its JSON
+fn load(py: Python<'_>, call: Call, path: PathBuf) -> PyResult<Bound<'_, PyAny>> {
+    let json = py
+        .detach(|| call(&path))
+        .map_err(|e| ConflowError::new_err(e.to_string()))?;
+    py.import("json")?.call_method1("loads", (json,))
+}
+
+/// Analyze a configuration file
+#[pyfunction]
+fn analyze(py: Python<'_>, path: PathBuf) -> PyResult<Bound<'_, PyAny>> {
+    load(py, ffi::analyze, path)
+}
+
+/// Validate a pipeline file
+#[pyfunction]
+fn validate(py: Python<'_>, path: PathBuf) -> PyResult<Bound<'_, PyAny>> {
+    load(py, ffi::validate, path)
+}
+
+/// Check a project's RSR compliance, using its `.rsr.yaml`
+#[pyfunction]
+fn check_compliance(py: Python<'_>, project_root: PathBuf) -> PyResult<Bound<'_, PyAny>> {
+    load(py, ffi::check_compliance, project_root)
+}
+
+/// Library version
+#[pyfunction]
+fn version() -> &'static str {
+    crate::VERSION
+}
+
+/// conflow's analyzer, pipeline validation and compliance checks
+#[pymodule]
+fn conflow(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("ConflowError", m.py().get_type::<ConflowError>())?;
+    m.add_function(wrap_pyfunction!(analyze, m)?)?;
+    m.add_function(wrap_pyfunction!(validate, m)?)?;
+    m.add_function(wrap_pyfunction!(check_compliance, m)?)?;
+    m.add_function(wrap_pyfunction!(version, m)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pyo3::types::PyDict;
+
+    #[test]
+    fn test_python_module() {
+        let temp = tempfile::tempdir().unwrap();
+        let config = temp.path().join("config.yaml");
+        std::fs::write(&config, "name: app\nreplicas: 3\n").unwrap();
+
+        Python::initialize();
+        Python::attach(|py| {
+            let module = PyModule::new(py, "conflow").unwrap();
+            conflow(&module).unwrap();
+            let call = |name: &str, path: &Path| {
+                module.getattr(name).unwrap().call1((path.to_path_buf(),))
+            };
+
+            let analysis = call("analyze", &config).unwrap();
+            let analysis = analysis.cast::<PyDict>().unwrap();
+            let format = analysis.get_item("format").unwrap().unwrap();
+            assert_eq!(format.extract::<String>().unwrap(), "yaml");
+
+            let err = call("analyze", Path::new("/nonexistent/config.yaml")).unwrap_err();
+            assert!(err.is_instance_of::<ConflowError>(py));
+            assert!(err.to_string().contains("/nonexistent/config.yaml"));
+        });
+    }
+}
@@ -0,0 +1,676 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Release readiness
+//!
+//! `conflow release check` runs the gates a release should pass and gives
+//! one go/no-go verdict:
+//!
+//! - **version**: the version locations (`versions:` in `.conflow.yaml`, or
+//!   the manifests) agree, on a version that differs from the previous
+//!   release's
+//! - **changelog**: the CHANGELOG lists the changes of that version
+//! - **schemas**: JSON Schemas changed since the previous release still
+//!   accept what they accepted before
+//! - **compliance**: RSR compliance is at least `compliance.target_level`
+//! - **pipeline**: the latest recorded run passed, on the commit released
+//!
+//! The previous release is the latest tag, unless a base is given.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+
+use crate::errors::ConflowError;
+use crate::pipeline::{
+    check_versions, read_versions, read_versions_with, version_locations, RunHistory,
+};
+use crate::rsr::compliance::{ComplianceChecker, ComplianceLevel};
+use crate::rsr::config::{RsrConfig, TargetLevel};
+use crate::rsr::release::{self, ReleaseCheck, ReleaseControl};
+use crate::rsr::requirements::RsrRequirementClass;
+use crate::utils::git;
+
+/// Pipeline whose runs the pipeline gate looks at
+const PIPELINE_FILE: &str = ".conflow.yaml";
+
+/// Outcome of a gate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GateStatus {
+    /// The gate passed
+    Pass,
+    /// The gate passed, with something to look at
+    Warn,
+    /// The gate failed, so the release is a no-go
+    Fail,
+    /// The gate doesn't apply to the project
+    Skip,
+}
+
+/// One gate of the release check
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Gate {
+    /// Gate name, e.g. `version`
+    pub name: String,
+    pub status: GateStatus,
+    /// One-line outcome
+    pub summary: String,
+    /// What failed or needs a look
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub details: Vec<String>,
+}
+
+impl Gate {
+    fn new(name: &str, status: GateStatus, summary: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            status,
+            summary: summary.into(),
+            details: vec![],
+        }
+    }
+
+    fn with_details(mut self, details: Vec<String>) -> Self {
+        self.details = details;
+        self
+    }
+}
+
+/// Verdict of a release check
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReleaseReport {
+    /// Whether no gate failed
+    pub ready: bool,
+    /// Version being released
+    pub version: Option<String>,
+    /// Previous release the project is compared with
+    pub base: Option<String>,
+    pub gates: Vec<Gate>,
+}
+
+impl ReleaseReport {
+    /// Gates that failed
+    pub fn failed(&self) -> impl Iterator<Item = &Gate> {
+        self.gates.iter().filter(|g| g.status == GateStatus::Fail)
+    }
+}
+
+/// Checks whether a project is ready to release
+pub struct ReleaseChecker {
+    project_root: PathBuf,
+    base: Option<String>,
+}
+
+impl ReleaseChecker {
+    /// Check the project at `project_root` against its latest tag
+    pub fn new(project_root: &Path) -> Self {
+        Self {
+            project_root: project_root.to_path_buf(),
+            base: None,
+        }
+    }
+
+    /// Compare with this git ref instead of the latest tag
+    pub fn with_base(mut self, base: Option<String>) -> Self {
+        self.base = base;
+        self
+    }
+
+    /// Run every gate
+    pub fn check(&self) -> Result<ReleaseReport, ConflowError> {
+        let root = &self.project_root;
+        let base = self.base.clone().or_else(|| git::latest_tag(root));
+        let version = release::current_version(root).map(|(_, v)| v);
+        let checker = ComplianceChecker::for_project(root)?;
+
+        let gates = vec![
+            self.version_gate(base.as_deref())?,
+            self.changelog_gate(&checker),
+            self.schemas_gate(base.as_deref())?,
+            self.compliance_gate(&checker)?,
+            self.pipeline_gate()?,
+        ];
+        Ok(ReleaseReport {
+            ready: gates.iter().all(|g| g.status != GateStatus::Fail),
+            version,
+            base,
+            gates,
+        })
+    }
+
+    /// The version locations agree on a version that differs from the base's
+    fn version_gate(&self, base: Option<&str>) -> Result<Gate, ConflowError> {
+        const NAME: &str = "version";
+        let root = &self.project_root;
+        let locations = version_locations(root);
+        let found = match read_versions(root, &locations) {
+            Ok(found) => found,
+            Err(e) => return Ok(Gate::new(NAME, GateStatus::Fail, e.to_string())),
+        };
+        let version = match check_versions(&found) {
+            Ok(Some(version)) => version,
+            Ok(None) => {
+                return Ok(Gate::new(
+                    NAME,
+                    GateStatus::Fail,
+                    "no version found; list its locations under versions: in .conflow.yaml",
+                ))
+            }
+            Err(_) => {
+                let listed = found.iter().map(|l| l.to_string()).collect();
+                return Ok(
+                    Gate::new(NAME, GateStatus::Fail, "locations disagree on the version")
+                        .with_details(listed),
+                );
+            }
+        };
+        let files: BTreeSet<String> = found.iter().map(|l| l.file.display().to_string()).collect();
+        let described = format!("{} in {}", version, join(&files));
+
+        let Some(base) = base else {
+            return Ok(Gate::new(
+                NAME,
+                GateStatus::Pass,
+                format!("{} (no earlier release)", described),
+            ));
+        };
+        let mut at_base = HashMap::new();
+        for file in locations.keys() {
+            if let Some(content) = git::file_at(root, base, file)? {
+                at_base.insert(file.clone(), content);
+            }
+        }
+        // Locations that didn't exist at the base count as bumped
+        let mut unchanged = vec![];
+        for (file, paths) in &locations {
+            let single = BTreeMap::from([(file.clone(), paths.clone())]);
+            let Ok(before) = read_versions_with(&single, |f| at_base.get(f).cloned()) else {
+                continue;
+            };
+            unchanged.extend(
+                before
+                    .into_iter()
+                    .filter(|l| l.version == version)
+                    .map(|l| {
+                        format!(
+                            "{} {} still has {} from {}",
+                            l.file.display(),
+                            l.path,
+                            version,
+                            base
+                        )
+                    }),
+            );
+        }
+        Ok(match unchanged.is_empty() {
+            true => Gate::new(
+                NAME,
+                GateStatus::Pass,
+                format!("{} (bumped since {})", described, base),
+            ),
+            false => Gate::new(
+                NAME,
+                GateStatus::Fail,
+                format!("{} is not bumped since {}", described, base),
+            )
+            .with_details(unchanged),
+        })
+    }
+
+    /// The CHANGELOG has an entry for the version, as RSR-RELEASE-002 checks
+    fn changelog_gate(&self, checker: &ComplianceChecker) -> Gate {
+        const NAME: &str = "changelog";
+        let root = &self.project_root;
+        // Honor a changelog file configured for the requirement
+        let configured: Vec<ReleaseCheck> = checker
+            .registry()
+            .get(&ReleaseControl::ChangelogEntry.id())
+            .map(|r| r.validation.release.clone())
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|c| c.control == ReleaseControl::ChangelogEntry)
+            .collect();
+        let checks = match configured.is_empty() {
+            true => vec![ReleaseCheck {
+                control: ReleaseControl::ChangelogEntry,
+                commits: None,
+                types: vec![],
+                file: None,
+            }],
+            false => configured,
+        };
+
+        let files: Vec<String> = checks
+            .iter()
+            .map(|c| c.changelog().display().to_string())
+            .collect();
+        let findings: Vec<String> = checks
+            .iter()
+            .flat_map(|c| c.findings(root))
+            .map(|f| f.to_string())
+            .collect();
+        match findings.is_empty() {
+            true => Gate::new(
+                NAME,
+                GateStatus::Pass,
+                format!("{} has an entry for the version", files.join(", ")),
+            ),
+            false => Gate::new(
+                NAME,
+                GateStatus::Fail,
+                format!("{} is not ready", files.join(", ")),
+            )
+            .with_details(findings),
+        }
+    }
+
+    /// JSON Schemas changed since the base accept what they accepted before
+    fn schemas_gate(&self, base: Option<&str>) -> Result<Gate, ConflowError> {
+        const NAME: &str = "schemas";
+        let root = &self.project_root;
+        let Some(base) = base else {
+            return Ok(Gate::new(
+                NAME,
+                GateStatus::Skip,
+                "no earlier release to compare with",
+            ));
+        };
+        let files = json_schemas(root)?;
+        if files.is_empty() {
+            return Ok(Gate::new(NAME, GateStatus::Skip, "no JSON Schemas"));
+        }
+
+        let mut compared = 0;
+        let mut breaks = vec![];
+        for file in &files {
+            let Some(old) = git::file_at(root, base, file)? else {
+                continue;
+            };
+            let Ok(new) = std::fs::read_to_string(root.join(file)) else {
+                breaks.push(format!("{} was removed", file.display()));
+                continue;
+            };
+            let (Ok(old), Ok(new)) = (
+                serde_json::from_str::<Value>(&old),
+                serde_json::from_str::<Value>(&new),
+            ) else {
+                breaks.push(format!("{} is not valid JSON", file.display()));
+                continue;
+            };
+            compared += 1;
+            breaks.extend(
+                schema_breaks(&old, &new)
+                    .into_iter()
+                    .map(|b| format!("{}: {}", file.display(), b)),
+            );
+        }
+        Ok(match breaks.is_empty() {
+            true => Gate::new(
+                NAME,
+                GateStatus::Pass,
+                format!("{} schema(s) backward compatible with {}", compared, base),
+            ),
+            false => Gate::new(
+                NAME,
+                GateStatus::Fail,
+                format!("breaking schema changes since {}", base),
+            )
+            .with_details(breaks),
+        })
+    }
+
+    /// RSR compliance reaches the target level (default: basic)
+    fn compliance_gate(&self, checker: &ComplianceChecker) -> Result<Gate, ConflowError> {
+        const NAME: &str = "compliance";
+        let root = &self.project_root;
+        let config = RsrConfig::load_from_project(root)?;
+        let target = match config.compliance.target_level {
+            Some(TargetLevel::Excellent) => ComplianceLevel::Excellent,
+            Some(TargetLevel::Good) => ComplianceLevel::Good,
+            Some(TargetLevel::Basic) | None => ComplianceLevel::Basic,
+        };
+        let report = checker.check(root)?;
+
+        let summary = format!(
+            "{:?} ({:.0}%), target {:?}",
+            report.level,
+            report.score * 100.0,
+            target
+        );
+        if report.level >= target {
+            return Ok(Gate::new(NAME, GateStatus::Pass, summary));
+        }
+        let unmet: Vec<String> = report
+            .requirements
+            .iter()
+            .filter(|r| !r.met && !r.waived)
+            .map(|r| {
+                let mandatory = checker
+                    .registry()
+                    .get(&r.requirement_id)
+                    .is_some_and(|req| req.class == RsrRequirementClass::Mandatory);
+                match mandatory {
+                    true => format!("{} (mandatory)", r.requirement_id),
+                    false => r.requirement_id.clone(),
+                }
+            })
+            .collect();
+        Ok(Gate::new(NAME, GateStatus::Fail, summary).with_details(unmet))
+    }
+
+    /// The latest recorded run of the pipeline passed at HEAD
+    fn pipeline_gate(&self) -> Result<Gate, ConflowError> {
+        const NAME: &str = "pipeline";
+        let root = &self.project_root;
+        if !root.join(PIPELINE_FILE).exists() {
+            return Ok(Gate::new(
+                NAME,
+                GateStatus::Skip,
+                format!("no {}", PIPELINE_FILE),
+            ));
+        }
+        let Some(run) = RunHistory::new(root).latest()? else {
+            return Ok(Gate::new(
+                NAME,
+                GateStatus::Fail,
+                "no runs recorded; run 'conflow run' first",
+            ));
+        };
+
+        if !run.finished {
+            return Ok(Gate::new(
+                NAME,
+                GateStatus::Fail,
+                format!("run #{} did not finish", run.id),
+            ));
+        }
+        if !run.success {
+            let failed = run
+                .stages
+                .iter()
+                .filter(|s| s.outcome == crate::pipeline::StageOutcome::Failed)
+                .map(|s| format!("stage '{}' failed", s.name))
+                .collect();
+            return Ok(
+                Gate::new(NAME, GateStatus::Fail, format!("run #{} failed", run.id))
+                    .with_details(failed),
+            );
+        }
+
+        let head = git::head_commit(root).ok();
+        let stale = match (&run.git_commit, &head) {
+            (Some(ran), Some(head)) => ran != head || run.git_dirty,
+            _ => false,
+        };
+        Ok(match stale {
+            true => Gate::new(
+                NAME,
+                GateStatus::Warn,
+                format!("run #{} passed, but not on HEAD as committed", run.id),
+            )
+            .with_details(vec![format!(
+                "run #{} was at {}{}",
+                run.id,
+                short(run.git_commit.as_deref().unwrap_or_default()),
+                if run.git_dirty {
+                    " with uncommitted changes"
+                } else {
+                    ""
+                }
+            )]),
+            false => Gate::new(NAME, GateStatus::Pass, format!("run #{} passed", run.id)),
+        })
+    }
+}
+
+fn short(commit: &str) -> &str {
+    &commit[..commit.len().min(12)]
+}
+
+/// JSON Schemas of the project, relative to its root: `*.schema.json`
+/// files, and the JSON schemas `.rsr.yaml` lists
+fn json_schemas(project_root: &Path) -> Result<Vec<PathBuf>, ConflowError> {
+    let mut files: BTreeSet<PathBuf> = git::listed_files(project_root)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|f| f.to_string_lossy().ends_with(".schema.json"))
+        .collect();
+    let config = RsrConfig::load_from_project(project_root)?;
+    files.extend(
+        config
+            .schemas
+            .into_iter()
+            .map(|s| s.path)
+            .filter(|p| p.extension().is_some_and(|e| e == "json")),
+    );
+    Ok(files.into_iter().collect())
+}
+
+/// Ways `new` rejects documents that `old` accepted
+///
+/// Compares properties, `items` and definitions by name: removed
+/// properties, newly required ones, narrowed types, dropped enum values and
+/// `additionalProperties` turned off. Each break is described with the JSON
+/// Pointer of the schema it's in.
+pub fn schema_breaks(old: &Value, new: &Value) -> Vec<String> {
+    let mut breaks = vec![];
+    compare(old, new, "", &mut breaks);
+    breaks
+}
+
+fn compare(old: &Value, new: &Value, pointer: &str, breaks: &mut Vec<String>) {
+    let at = if pointer.is_empty() { "/" } else { pointer };
+    let strings = |schema: &Value, key: &str| -> BTreeSet<String> {
+        match &schema[key] {
+            Value::String(s) => BTreeSet::from([s.clone()]),
+            Value::Array(items) => items
+                .iter()
+                .map(|v| {
+                    v.as_str()
+                        .map(String::from)
+                        .unwrap_or_else(|| v.to_string())
+                })
+                .collect(),
+            _ => BTreeSet::new(),
+        }
+    };
+
+    // Types accepted before must still be
+    let (old_types, new_types) = (strings(old, "type"), strings(new, "type"));
+    if !new_types.is_empty() {
+        let widened =
+            |t: &String| new_types.contains(t) || (t == "integer" && new_types.contains("number"));
+        let dropped: Vec<&String> = match old_types.is_empty() {
+            true => vec![],
+            false => old_types.iter().filter(|t| !widened(t)).collect(),
+        };
+        if old_types.is_empty() {
+            breaks.push(format!("{}: type restricted to {}", at, join(&new_types)));
+        } else if !dropped.is_empty() {
+            breaks.push(format!("{}: type {} no longer allowed", at, join(dropped)));
+        }
+    }
+
+    // Enum values accepted before must still be
+    if let (Value::Array(old_enum), Some(new_enum)) = (&old["enum"], new["enum"].as_array()) {
+        let removed: Vec<String> = old_enum
+            .iter()
+            .filter(|v| !new_enum.contains(v))
+            .map(|v| v.to_string())
+            .collect();
+        if !removed.is_empty() {
+            breaks.push(format!(
+                "{}: enum values {} removed",
+                at,
+                removed.join(", ")
+            ));
+        }
+    }
+
+    // Properties no one had to set before
+    let (old_required, new_required) = (strings(old, "required"), strings(new, "required"));
+    let added: Vec<&String> = new_required.difference(&old_required).collect();
+    if !added.is_empty() {
+        breaks.push(format!("{}: newly required {}", at, join(added)));
+    }
+
+    if new["additionalProperties"] == Value::Bool(false)
+        && old["additionalProperties"] != Value::Bool(false)
+    {
+        breaks.push(format!("{}: additional properties no longer allowed", at));
+    }
+
+    for key in ["properties", "$defs", "definitions"] {
+        let Some(old_children) = old[key].as_object() else {
+            continue;
+        };
+        let new_children = new[key].as_object();
+        for (name, old_child) in old_children {
+            let child = format!("{}/{}/{}", pointer, key, escape(name));
+            match new_children.and_then(|c| c.get(name)) {
+                Some(new_child) => compare(old_child, new_child, &child, breaks),
+                None => breaks.push(format!("{}: removed", child)),
+            }
+        }
+    }
+    if old["items"].is_object() && new["items"].is_object() {
+        compare(
+            &old["items"],
+            &new["items"],
+            &format!("{}/items", pointer),
+            breaks,
+        );
+    }
+}
+
+fn join<'a>(values: impl IntoIterator<Item = &'a String>) -> String {
+    values
+        .into_iter()
+        .map(|v| v.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Escape a JSON Pointer reference token
+fn escape(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_version_and_changelog_gates() {
+        let repo = TempDir::new().unwrap();
+        let root = repo.path();
+        let cargo = |version: &str| {
+            let manifest = format!("[package]\nname = \"demo\"\nversion = \"{}\"\n", version);
+            std::fs::write(root.join("Cargo.toml"), manifest).unwrap();
+        };
+        let run = |args: &[&str]| {
+            std::process::Command::new("git")
+                .args(args)
+                .current_dir(root)
+                .output()
+                .is_ok_and(|o| o.status.success())
+        };
+        cargo("1.0.0");
+        let committed = run(&["init", "-q"])
+            && run(&["config", "user.email", "test@example.com"])
+            && run(&["config", "user.name", "test"])
+            && run(&["add", "."])
+            && run(&["commit", "-q", "-m", "feat: start"])
+            && run(&["tag", "v1.0.0"]);
+        if !committed {
+            // Skip if git is not installed
+            return;
+        }
+
+        let checker = ReleaseChecker::new(root);
+        let compliance = ComplianceChecker::for_project(root).unwrap();
+        let gate = checker.version_gate(Some("v1.0.0")).unwrap();
+        assert_eq!(gate.status, GateStatus::Fail);
+        assert_eq!(
+            gate.details,
+            vec!["Cargo.toml $.package.version still has 1.0.0 from v1.0.0"]
+        );
+        assert_eq!(checker.changelog_gate(&compliance).status, GateStatus::Fail);
+
+        cargo("1.1.0");
+        std::fs::write(
+            root.join("package.json"),
+            r#"{ "name": "demo", "version": "1.0.0" }"#,
+        )
+        .unwrap();
+        let gate = checker.version_gate(Some("v1.0.0")).unwrap();
+        assert_eq!(gate.summary, "locations disagree on the version");
+
+        std::fs::remove_file(root.join("package.json")).unwrap();
+        std::fs::write(
+            root.join("CHANGELOG.md"),
+            "# Changelog\n\n## [1.1.0] - 2026-01-31\n\n### Added\n\n- A flag\n",
+        )
+        .unwrap();
+        assert_eq!(
+            checker.version_gate(Some("v1.0.0")).unwrap().status,
+            GateStatus::Pass
+        );
+        assert_eq!(checker.changelog_gate(&compliance).status, GateStatus::Pass);
+        assert_eq!(checker.pipeline_gate().unwrap().status, GateStatus::Skip);
+    }
+
+    #[test]
+    fn test_schema_breaks() {
+        let old = json!({
+            "type": "object",
+            "required": ["name"],
+            "properties": {
+                "name": { "type": "string" },
+                "replicas": { "type": "integer" },
+                "mode": { "enum": ["fast", "safe"] },
+                "tags": { "type": "array", "items": { "type": ["string", "number"] } }
+            }
+        });
+
+        // Widening is compatible
+        let widened = json!({
+            "type": "object",
+            "required": ["name"],
+            "properties": {
+                "name": { "type": "string" },
+                "replicas": { "type": "number" },
+                "mode": { "enum": ["fast", "safe", "auto"] },
+                "tags": { "type": "array", "items": { "type": ["string", "number", "null"] } },
+                "extra": { "type": "boolean" }
+            }
+        });
+        assert!(schema_breaks(&old, &widened).is_empty());
+
+        let narrowed = json!({
+            "type": "object",
+            "required": ["name", "mode"],
+            "additionalProperties": false,
+            "properties": {
+                "name": { "type": "string" },
+                "mode": { "enum": ["safe"] },
+                "tags": { "type": "array", "items": { "type": "string" } }
+            }
+        });
+        let breaks = schema_breaks(&old, &narrowed);
+        assert_eq!(
+            breaks,
+            vec![
+                "/: newly required mode",
+                "/: additional properties no longer allowed",
+                "/properties/mode: enum values \"fast\" removed",
+                "/properties/replicas: removed",
+                "/properties/tags/items: type number no longer allowed",
+            ]
+        );
+    }
+}
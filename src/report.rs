@@ -0,0 +1,308 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Persisted report format
+//!
+//! Compliance reports, stage and pipeline results, run records, analyses,
+//! compliance diffs, workspace compliance matrices and release checks
+//! serialize to JSON wrapped in a small envelope:
+//!
+//! ```json
+//! {
+//!   "format_version": 1,
+//!   "kind": "compliance",
+//!   "level": "Good",
+//!   "score": 0.82,
+//!   ...
+//! }
+//! ```
+//!
+//! The report's own fields sit next to `format_version` and `kind`. Within a
+//! format version fields are only ever added, and added fields default when
+//! missing, so older files keep loading. Durations are whole milliseconds in
+//! fields ending in `_ms`. [`from_json`] rejects files written by a newer
+//! format version and files holding a different kind of report.
+//!
+//! Reports are diffed in CI and compared against golden files, so they come
+//! out the same on every run: lists are sorted by file path, requirement ID
+//! or stage name and maps are written in key order. In stable mode
+//! (`--stable`) timestamps are the Unix epoch and durations zero as well.
+//!
+//! Values the pipeline's `redact:` section names are masked in every report
+//! written, as in diagnostics and stage output.
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize, Serializer};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::RwLock;
+
+use crate::analyzer::Analysis;
+use crate::errors::ConflowError;
+#[cfg(feature = "native")]
+use crate::executors::ExecutionResult;
+#[cfg(feature = "native")]
+use crate::pipeline::{PipelineResult, RunRecord};
+#[cfg(feature = "native")]
+use crate::release::ReleaseReport;
+use crate::rsr::diff::{now_rfc3339, rfc3339};
+use crate::rsr::{ComplianceDiff, ComplianceReport};
+use crate::utils::redact::Redactor;
+#[cfg(feature = "native")]
+use crate::workspace::ComplianceMatrix;
+
+/// Current version of the persisted report format
+pub const FORMAT_VERSION: u32 = 1;
+
+static STABLE: AtomicBool = AtomicBool::new(false);
+
+/// Zero timestamps and durations in everything written from now on
+pub fn set_stable(stable: bool) {
+    STABLE.store(stable, Ordering::Relaxed);
+}
+
+/// Whether timestamps and durations are zeroed
+pub fn is_stable() -> bool {
+    STABLE.load(Ordering::Relaxed)
+}
+
+static REDACTOR: RwLock<Redactor> = RwLock::new(Redactor::empty());
+
+/// Mask the values a redactor names in everything written from now on
+pub fn set_redactor(redactor: Redactor) {
+    *REDACTOR.write().unwrap_or_else(|e| e.into_inner()) = redactor;
+}
+
+/// Mask redacted values in text
+pub fn redact(text: &str) -> String {
+    let redactor = REDACTOR.read().unwrap_or_else(|e| e.into_inner());
+    match redactor.is_empty() {
+        true => text.to_string(),
+        false => redactor.redact(text),
+    }
+}
+
+/// Note the values parsed documents hold at redacted paths
+pub(crate) fn learn(documents: &[serde_json::Value]) {
+    if !REDACTOR.read().unwrap_or_else(|e| e.into_inner()).has_paths() {
+        return;
+    }
+    let mut redactor = REDACTOR.write().unwrap_or_else(|e| e.into_inner());
+    for document in documents {
+        redactor.learn(document);
+    }
+}
+
+/// The current time as written into reports, the Unix epoch in stable mode
+pub(crate) fn timestamp() -> String {
+    match is_stable() {
+        true => rfc3339(0),
+        false => now_rfc3339(),
+    }
+}
+
+/// A report type with a stable JSON shape
+pub trait Report: Serialize + DeserializeOwned {
+    /// Value of the `kind` field
+    const KIND: &'static str;
+}
+
+impl Report for ComplianceReport {
+    const KIND: &'static str = "compliance";
+}
+
+impl Report for ComplianceDiff {
+    const KIND: &'static str = "compliance_diff";
+}
+
+#[cfg(feature = "native")]
+impl Report for ExecutionResult {
+    const KIND: &'static str = "stage_result";
+}
+
+#[cfg(feature = "native")]
+impl Report for PipelineResult {
+    const KIND: &'static str = "pipeline_result";
+}
+
+#[cfg(feature = "native")]
+impl Report for RunRecord {
+    const KIND: &'static str = "run_record";
+}
+
+impl Report for Analysis {
+    const KIND: &'static str = "analysis";
+}
+
+#[cfg(feature = "native")]
+impl Report for ComplianceMatrix {
+    const KIND: &'static str = "compliance_matrix";
+}
+
+#[cfg(feature = "native")]
+impl Report for ReleaseReport {
+    const KIND: &'static str = "release_check";
+}
+
+/// A report with its format version and kind
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Versioned<T> {
+    /// Format version the report was written with
+    #[serde(default = "first_version")]
+    pub format_version: u32,
+
+    /// Kind of report
+    pub kind: String,
+
+    /// The report itself
+    #[serde(flatten)]
+    pub report: T,
+}
+
+fn first_version() -> u32 {
+    1
+}
+
+impl<T: Report> Versioned<T> {
+    /// Wrap a report with the current format version
+    pub fn new(report: T) -> Self {
+        Self {
+            format_version: FORMAT_VERSION,
+            kind: T::KIND.to_string(),
+            report,
+        }
+    }
+}
+
+/// Serialize a report with its envelope
+pub fn to_json<T: Report>(report: &T) -> Result<String, ConflowError> {
+    #[derive(Serialize)]
+    struct Envelope<'a, T> {
+        format_version: u32,
+        kind: &'static str,
+        #[serde(flatten)]
+        report: &'a T,
+    }
+
+    let json = serde_json::to_string_pretty(&Envelope {
+        format_version: FORMAT_VERSION,
+        kind: T::KIND,
+        report,
+    })?;
+    Ok(redact(&json))
+}
+
+/// Load a report written by [`to_json`]
+pub fn from_json<T: Report>(json: &str) -> Result<T, ConflowError> {
+    let versioned: Versioned<T> = serde_json::from_str(json)?;
+    if versioned.format_version > FORMAT_VERSION {
+        return Err(ConflowError::Json {
+            message: format!(
+                "report format version {} is newer than this conflow supports ({})",
+                versioned.format_version, FORMAT_VERSION
+            ),
+        });
+    }
+    if versioned.kind != T::KIND {
+        return Err(ConflowError::Json {
+            message: format!(
+                "expected a '{}' report, found '{}'",
+                T::KIND,
+                versioned.kind
+            ),
+        });
+    }
+    Ok(versioned.report)
+}
+
+/// Serialize a map in key order
+pub(crate) fn sorted<S, K, V>(map: &HashMap<K, V>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    K: Ord + Serialize,
+    V: Serialize,
+{
+    serializer.collect_map(map.iter().collect::<BTreeMap<_, _>>())
+}
+
+/// Serialize a `Duration` as whole milliseconds, zero in stable mode
+pub(crate) mod duration_ms {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        match super::is_stable() {
+            true => serializer.serialize_u64(0),
+            false => serializer.serialize_u64(duration.as_millis() as u64),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        u64::deserialize(deserializer).map(Duration::from_millis)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rsr::ComplianceChecker;
+    use std::time::Duration;
+
+    #[test]
+    fn test_round_trip() {
+        let temp = tempfile::tempdir().unwrap();
+        std::fs::write(temp.path().join("README.md"), "# demo\n").unwrap();
+        let report = ComplianceChecker::new().check(temp.path()).unwrap();
+
+        let json = to_json(&report).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["format_version"], FORMAT_VERSION);
+        assert_eq!(value["kind"], "compliance");
+        assert!(value["requirements"].is_array());
+
+        let loaded: ComplianceReport = from_json(&json).unwrap();
+        assert_eq!(loaded.level, report.level);
+        assert_eq!(loaded.requirements.len(), report.requirements.len());
+
+        let result = ExecutionResult::success("ok".into(), Duration::from_millis(1500), vec![]);
+        let json = to_json(&result).unwrap();
+        assert!(json.contains("\"duration_ms\": 1500"));
+        let loaded: ExecutionResult = from_json(&json).unwrap();
+        assert_eq!(loaded.duration, Duration::from_millis(1500));
+    }
+
+    #[test]
+    fn test_maps_in_key_order() {
+        let mut result = PipelineResult {
+            results: HashMap::new(),
+            duration: Duration::ZERO,
+            success: true,
+            profile: None,
+        };
+        for stage in ["validate", "export", "lint", "build"] {
+            let stage_result = ExecutionResult::success(String::new(), Duration::ZERO, vec![]);
+            result.results.insert(stage.to_string(), stage_result);
+        }
+        let json = to_json(&result).unwrap();
+        let positions: Vec<usize> = ["build", "export", "lint", "validate"]
+            .iter()
+            .map(|stage| json.find(&format!("\"{}\"", stage)).unwrap())
+            .collect();
+        assert!(positions.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn test_rejects_newer_or_other_reports() {
+        let result = ExecutionResult::failure("boom".into(), 1, Duration::ZERO);
+        let json = to_json(&result).unwrap();
+        assert!(from_json::<PipelineResult>(&json).is_err());
+
+        let newer = json.replace("\"format_version\": 1", "\"format_version\": 99");
+        let err = from_json::<ExecutionResult>(&newer).unwrap_err();
+        assert!(err.to_string().contains("newer"));
+
+        // Files written before the envelope had a version load as version 1
+        let legacy = json.replace("\"format_version\": 1,", "");
+        assert!(from_json::<ExecutionResult>(&legacy).is_ok());
+    }
+}
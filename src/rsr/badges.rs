@@ -3,14 +3,24 @@
 
 //! Compliance badge generation
 //!
-//! Generates SVG badges for CI pipelines showing compliance status.
+//! Generates SVG badges for CI pipelines showing compliance status, and
+//! shields.io endpoint JSON for rendering them through shields.io.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
 
 use super::compliance::{ComplianceLevel, ComplianceReport};
+use super::config::BadgeConfig;
+use super::requirements::RsrRequirementRegistry;
+use crate::ConflowError;
 
 /// Badge style
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
 pub enum BadgeStyle {
     /// Flat style (shields.io flat)
+    #[default]
     Flat,
     /// Flat square style
     FlatSquare,
@@ -31,10 +41,25 @@ impl BadgeStyle {
     }
 }
 
-impl Default for BadgeStyle {
-    fn default() -> Self {
-        Self::Flat
-    }
+/// Badge color for scores at or above `min_score`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ColorThreshold {
+    /// Lowest score (0.0 - 1.0) that gets this color
+    pub min_score: f64,
+
+    /// Color, as a hex code or shields.io color name
+    pub color: String,
+}
+
+/// A badge's text and color, rendered as SVG or shields.io endpoint JSON
+#[derive(Debug, Clone, PartialEq)]
+pub struct Badge {
+    /// File name, without extension
+    pub name: String,
+    pub label: String,
+    pub message: String,
+    pub color: String,
 }
 
 /// Badge generator
@@ -42,6 +67,8 @@ impl Default for BadgeStyle {
 pub struct BadgeGenerator {
     style: BadgeStyle,
     label: String,
+    thresholds: Vec<ColorThreshold>,
+    categories: Vec<String>,
 }
 
 impl BadgeGenerator {
@@ -50,6 +77,20 @@ impl BadgeGenerator {
         Self {
             style: BadgeStyle::default(),
             label: "RSR".into(),
+            thresholds: Vec::new(),
+            categories: Vec::new(),
+        }
+    }
+
+    /// Create a generator from the `integrations.badges` settings
+    pub fn from_config(config: &BadgeConfig) -> Self {
+        let generator = Self::new()
+            .style(config.style)
+            .thresholds(config.thresholds.clone())
+            .categories(config.categories.clone());
+        match config.label {
+            Some(ref label) => generator.label(label.clone()),
+            None => generator,
         }
     }
 
@@ -65,12 +106,126 @@ impl BadgeGenerator {
         self
     }
 
+    /// Color badges by score instead of by compliance level
+    pub fn thresholds(mut self, thresholds: Vec<ColorThreshold>) -> Self {
+        self.thresholds = thresholds;
+        self
+    }
+
+    /// Also generate a badge for the requirements with each of these tags
+    pub fn categories(mut self, categories: Vec<String>) -> Self {
+        self.categories = categories;
+        self
+    }
+
     /// Generate SVG badge from compliance report
     pub fn generate(&self, report: &ComplianceReport) -> String {
+        self.render_svg(&self.overall(report))
+    }
+
+    /// The overall badge plus one per category with checked requirements
+    pub fn badges(
+        &self,
+        report: &ComplianceReport,
+        registry: &RsrRequirementRegistry,
+    ) -> Vec<Badge> {
+        let mut badges = vec![self.overall(report)];
+
+        for category in &self.categories {
+            let results: Vec<_> = report
+                .requirements
+                .iter()
+                .filter(|r| !r.waived)
+                .filter(|r| {
+                    registry
+                        .get(&r.requirement_id)
+                        .is_some_and(|req| req.tags.contains(category))
+                })
+                .collect();
+            if results.is_empty() {
+                continue;
+            }
+
+            let passed = results.iter().filter(|r| r.met).count();
+            let score = passed as f64 / results.len() as f64;
+            badges.push(Badge {
+                name: format!("rsr-{}", category),
+                label: format!("{} {}", self.label, category),
+                message: format!("{}/{}", passed, results.len()),
+                color: self
+                    .threshold_color(score)
+                    .unwrap_or_else(|| default_color(score).into()),
+            });
+        }
+
+        badges
+    }
+
+    /// Write each badge as `<name>.svg` and `<name>.json` into `dir`
+    pub fn write_all(
+        &self,
+        report: &ComplianceReport,
+        registry: &RsrRequirementRegistry,
+        dir: &Path,
+    ) -> Result<Vec<PathBuf>, ConflowError> {
+        std::fs::create_dir_all(dir)?;
+
+        let mut written = Vec::new();
+        for badge in self.badges(report, registry) {
+            let svg = dir.join(format!("{}.svg", badge.name));
+            std::fs::write(&svg, self.render_svg(&badge))?;
+            let json = dir.join(format!("{}.json", badge.name));
+            std::fs::write(&json, self.endpoint_json(&badge))?;
+            written.extend([svg, json]);
+        }
+
+        Ok(written)
+    }
+
+    /// Render a badge as SVG in the generator's style
+    pub fn render_svg(&self, badge: &Badge) -> String {
+        self.generate_svg(&badge.label, &badge.message, &svg_color(&badge.color), None)
+    }
+
+    /// Render a badge as shields.io endpoint JSON
+    ///
+    /// Serve the file and point `https://img.shields.io/endpoint?url=...`
+    /// at it to have shields.io render the badge.
+    pub fn endpoint_json(&self, badge: &Badge) -> String {
+        let json = serde_json::json!({
+            "schemaVersion": 1,
+            "label": badge.label,
+            "message": badge.message,
+            "color": badge.color.trim_start_matches('#'),
+            "style": self.style.as_str(),
+        });
+        serde_json::to_string_pretty(&json).unwrap_or_default()
+    }
+
+    /// Badge for the report's level and score
+    fn overall(&self, report: &ComplianceReport) -> Badge {
         let (status, color) = self.level_to_status_color(report.level);
-        let score = format!("{:.0}%", report.score * 100.0);
+        Badge {
+            name: "rsr".into(),
+            label: self.label.clone(),
+            message: format!("{} ({:.0}%)", status, report.score * 100.0),
+            color: self
+                .threshold_color(report.score)
+                .unwrap_or_else(|| color.into()),
+        }
+    }
 
-        self.generate_svg(&self.label, &status, color, Some(&score))
+    /// Color of the highest threshold `score` reaches, or of the lowest
+    /// threshold when it reaches none; `None` if no thresholds are set
+    fn threshold_color(&self, score: f64) -> Option<String> {
+        let by_score =
+            |a: &&ColorThreshold, b: &&ColorThreshold| a.min_score.total_cmp(&b.min_score);
+        self.thresholds
+            .iter()
+            .filter(|t| score >= t.min_score)
+            .max_by(by_score)
+            .or_else(|| self.thresholds.iter().min_by(by_score))
+            .map(|t| t.color.clone())
     }
 
     /// Generate a simple level badge
@@ -114,6 +269,7 @@ impl BadgeGenerator {
         let total_width = label_width + status_width;
         let label_x = label_width / 2;
         let status_x = label_width + status_width / 2;
+        let (label, status, color) = (xml_escape(label), xml_escape(status), xml_escape(color));
 
         format!(
             r##"<svg xmlns="http://www.w3.org/2000/svg" width="{total_width}" height="20" role="img" aria-label="{label}: {status}">
@@ -146,6 +302,7 @@ impl BadgeGenerator {
         let total_width = label_width + status_width;
         let label_x = label_width / 2;
         let status_x = label_width + status_width / 2;
+        let (label, status, color) = (xml_escape(label), xml_escape(status), xml_escape(color));
 
         format!(
             r##"<svg xmlns="http://www.w3.org/2000/svg" width="{total_width}" height="20" role="img" aria-label="{label}: {status}">
@@ -168,6 +325,7 @@ impl BadgeGenerator {
         let total_width = label_width + status_width;
         let label_x = label_width / 2;
         let status_x = label_width + status_width / 2;
+        let (label, status, color) = (xml_escape(label), xml_escape(status), xml_escape(color));
 
         format!(
             r##"<svg xmlns="http://www.w3.org/2000/svg" width="{total_width}" height="18" role="img" aria-label="{label}: {status}">
@@ -205,6 +363,8 @@ impl BadgeGenerator {
         let status_x = label_width + status_width / 2;
         let label_text_width = label_width - 20;
         let status_text_width = status_width - 20;
+        let (label, status, color) = (xml_escape(label), xml_escape(status), xml_escape(color));
+        let (label_upper, status_upper) = (xml_escape(&label_upper), xml_escape(&status_upper));
 
         format!(
             r##"<svg xmlns="http://www.w3.org/2000/svg" width="{total_width}" height="28" role="img" aria-label="{label}: {status}">
@@ -232,6 +392,42 @@ impl BadgeGenerator {
     }
 }
 
+/// SVG fill for a color, resolving shields.io color names
+fn svg_color(color: &str) -> String {
+    let hex = match color {
+        "brightgreen" | "success" => "#4c1",
+        "green" => "#97ca00",
+        "yellowgreen" => "#a4a61d",
+        "yellow" => "#dfb317",
+        "orange" | "important" => "#fe7d37",
+        "red" | "critical" => "#e05d44",
+        "blue" | "informational" => "#007ec6",
+        "lightgrey" | "inactive" => "#9f9f9f",
+        _ if color.bytes().all(|b| b.is_ascii_hexdigit()) => return format!("#{}", color),
+        _ => color,
+    };
+    hex.to_string()
+}
+
+/// Text safe to put in SVG content and attribute values
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Color for a category score when no thresholds are configured
+fn default_color(score: f64) -> &'static str {
+    match score {
+        s if s >= 0.9 => "#4c1",
+        s if s >= 0.75 => "#97ca00",
+        s if s >= 0.5 => "#dfb317",
+        _ => "#e05d44",
+    }
+}
+
 impl Default for BadgeGenerator {
     fn default() -> Self {
         Self::new()
@@ -270,7 +466,7 @@ pub fn markdown_badge(report: &ComplianceReport, link: Option<&str>) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::rsr::compliance::ComplianceStats;
+    use crate::rsr::compliance::{ComplianceStats, RequirementResult};
 
     fn sample_report(level: ComplianceLevel, score: f64) -> ComplianceReport {
         ComplianceReport {
@@ -278,6 +474,8 @@ mod tests {
             score,
             requirements: vec![],
             stats: ComplianceStats::default(),
+            custom_level: None,
+            waivers: vec![],
         }
     }
 
@@ -324,4 +522,106 @@ mod tests {
             assert!(svg.contains("<svg"), "Style {:?} should generate SVG", style);
         }
     }
+
+    #[test]
+    fn test_svg_escapes_text_and_color() {
+        let badge = Badge {
+            name: "rsr-security".into(),
+            label: "R&D <security>".into(),
+            message: "3/4".into(),
+            color: "red\" onload=\"alert(1)".into(),
+        };
+        for style in [
+            BadgeStyle::Flat,
+            BadgeStyle::FlatSquare,
+            BadgeStyle::Plastic,
+            BadgeStyle::ForTheBadge,
+        ] {
+            let svg = BadgeGenerator::new().style(style).render_svg(&badge);
+            assert!(!svg.contains("<security>"), "{:?}", style);
+            assert!(!svg.contains("\" onload"), "{:?}", style);
+            assert!(svg.contains("&lt;") && svg.contains("&quot;"), "{:?}", style);
+        }
+    }
+
+    fn result(id: &str, met: bool) -> RequirementResult {
+        RequirementResult {
+            requirement_id: id.into(),
+            met,
+            details: vec![],
+            remediation: None,
+            blocked_by: vec![],
+            waived: false,
+            owners: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_endpoint_json_and_thresholds() {
+        let generator = BadgeGenerator::new()
+            .style(BadgeStyle::ForTheBadge)
+            .thresholds(vec![
+                ColorThreshold {
+                    min_score: 0.9,
+                    color: "brightgreen".into(),
+                },
+                ColorThreshold {
+                    min_score: 0.6,
+                    color: "#fe7d37".into(),
+                },
+            ]);
+        let badges = generator.badges(
+            &sample_report(ComplianceLevel::Basic, 0.65),
+            &RsrRequirementRegistry::new(),
+        );
+        assert_eq!(badges[0].color, "#fe7d37");
+
+        let json: serde_json::Value =
+            serde_json::from_str(&generator.endpoint_json(&badges[0])).unwrap();
+        assert_eq!(json["schemaVersion"], 1);
+        assert_eq!(json["label"], "RSR");
+        assert_eq!(json["message"], "basic (65%)");
+        assert_eq!(json["color"], "fe7d37");
+        assert_eq!(json["style"], "for-the-badge");
+
+        assert!(generator.render_svg(&badges[0]).contains(r##"fill="#fe7d37""##));
+
+        // Below every threshold, the lowest one's color is used
+        let low = generator.badges(
+            &sample_report(ComplianceLevel::NonCompliant, 0.2),
+            &RsrRequirementRegistry::new(),
+        );
+        assert_eq!(low[0].color, "#fe7d37");
+    }
+
+    #[test]
+    fn test_category_badges() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let mut report = sample_report(ComplianceLevel::Good, 0.8);
+        report.requirements = vec![
+            result("RSR-CONFIG-001", true),
+            result("RSR-CONFIG-002", false),
+            result("RSR-CONFIG-004", true),
+        ];
+        let generator = BadgeGenerator::new().categories(vec![
+            "config".into(),
+            "caching".into(),
+            "security".into(),
+        ]);
+
+        let badges = generator.badges(&report, &RsrRequirementRegistry::new());
+        let names: Vec<_> = badges.iter().map(|b| b.name.as_str()).collect();
+        // Categories without requirements get no badge
+        assert_eq!(names, ["rsr", "rsr-config", "rsr-caching"]);
+        assert_eq!(badges[1].label, "RSR config");
+        assert_eq!(badges[1].message, "2/3");
+        assert_eq!(badges[2].message, "1/1");
+
+        let written = generator
+            .write_all(&report, &RsrRequirementRegistry::new(), temp.path())
+            .unwrap();
+        assert_eq!(written.len(), 6);
+        assert!(temp.path().join("rsr-caching.svg").exists());
+        assert!(temp.path().join("rsr-caching.json").exists());
+    }
 }
@@ -0,0 +1,684 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Kubernetes security benchmark
+//!
+//! Built-in requirements for the pod security checks of the CIS Kubernetes
+//! Benchmark (section 5.2) and the NSA/CISA Kubernetes Hardening Guide,
+//! run against rendered manifests. Each control is one requirement. The
+//! controls a container's `securityContext` can satisfy come with a
+//! remediation template, the fields to set, which the remediator applies
+//! to the manifests in place.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+use super::requirements::{
+    RemediationOptions, RsrRequirement, RsrRequirementClass, ValidationChecks,
+};
+use crate::cache::ParseCache;
+use crate::errors::ConflowError;
+use crate::i18n::{tr, tr_with};
+use crate::pipeline::Pipeline;
+use crate::utils::yaml::{self, edit::Change};
+
+/// Manifests checked when neither the check nor the pipeline names any
+const DEFAULT_MANIFESTS: &[&str] = &["**/*.yaml", "**/*.yml"];
+
+/// Capabilities no workload should add
+const DANGEROUS_CAPABILITIES: &[&str] = &[
+    "ALL",
+    "SYS_ADMIN",
+    "NET_ADMIN",
+    "NET_RAW",
+    "SYS_PTRACE",
+    "SYS_MODULE",
+    "DAC_READ_SEARCH",
+];
+
+/// A pod security control
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SecurityControl {
+    /// No privileged containers (CIS 5.2.1)
+    Privileged,
+    /// No sharing the host's PID, IPC or network namespace (CIS 5.2.2-5.2.4)
+    HostNamespaces,
+    /// No hostPath volumes (CIS 5.2.12, NSA)
+    HostPath,
+    /// No privilege escalation (CIS 5.2.5)
+    PrivilegeEscalation,
+    /// Containers run as a non-root user (CIS 5.2.6, NSA)
+    RunAsNonRoot,
+    /// All capabilities dropped and none dangerous added (CIS 5.2.7-5.2.9)
+    Capabilities,
+    /// Immutable root filesystems (NSA)
+    ReadOnlyRootFilesystem,
+    /// Every container has a securityContext (NSA)
+    SecurityContext,
+}
+
+impl SecurityControl {
+    /// Every control, in requirement ID order
+    pub const ALL: [Self; 8] = [
+        Self::Privileged,
+        Self::HostNamespaces,
+        Self::HostPath,
+        Self::PrivilegeEscalation,
+        Self::RunAsNonRoot,
+        Self::Capabilities,
+        Self::ReadOnlyRootFilesystem,
+        Self::SecurityContext,
+    ];
+
+    /// Requirement ID, e.g. `RSR-K8S-001`
+    pub fn id(self) -> String {
+        let index = Self::ALL.iter().position(|c| *c == self).unwrap_or(0);
+        format!("RSR-K8S-{:03}", index + 1)
+    }
+
+    /// The control a requirement ID stands for
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.id() == id)
+    }
+
+    /// Benchmark section the control comes from
+    pub fn reference(self) -> &'static str {
+        match self {
+            Self::Privileged => "CIS 5.2.1",
+            Self::HostNamespaces => "CIS 5.2.2-5.2.4",
+            Self::HostPath => "CIS 5.2.12",
+            Self::PrivilegeEscalation => "CIS 5.2.5",
+            Self::RunAsNonRoot => "CIS 5.2.6",
+            Self::Capabilities => "CIS 5.2.7-5.2.9",
+            Self::ReadOnlyRootFilesystem => "NSA hardening",
+            Self::SecurityContext => "NSA hardening",
+        }
+    }
+
+    /// Kebab-case name, as in `.rsr.yaml`
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Privileged => "privileged",
+            Self::HostNamespaces => "host-namespaces",
+            Self::HostPath => "host-path",
+            Self::PrivilegeEscalation => "privilege-escalation",
+            Self::RunAsNonRoot => "run-as-non-root",
+            Self::Capabilities => "capabilities",
+            Self::ReadOnlyRootFilesystem => "read-only-root-filesystem",
+            Self::SecurityContext => "security-context",
+        }
+    }
+
+    fn class(self) -> RsrRequirementClass {
+        match self {
+            Self::Privileged => RsrRequirementClass::Mandatory,
+            Self::ReadOnlyRootFilesystem => RsrRequirementClass::Advisory,
+            _ => RsrRequirementClass::Preferential,
+        }
+    }
+
+    /// Container `securityContext` fields that satisfy the control, as YAML
+    ///
+    /// `None` for controls that need a change to how the workload runs.
+    pub fn template(self) -> Option<&'static str> {
+        match self {
+            Self::Privileged => Some("privileged: false"),
+            Self::PrivilegeEscalation => Some("allowPrivilegeEscalation: false"),
+            Self::RunAsNonRoot => Some("runAsNonRoot: true"),
+            Self::Capabilities => Some("capabilities:\n  drop: [ALL]"),
+            Self::ReadOnlyRootFilesystem => Some("readOnlyRootFilesystem: true"),
+            Self::SecurityContext => Some(
+                "runAsNonRoot: true\nallowPrivilegeEscalation: false\n\
+                 readOnlyRootFilesystem: true\ncapabilities:\n  drop: [ALL]",
+            ),
+            Self::HostNamespaces | Self::HostPath => None,
+        }
+    }
+
+    /// The requirement checking this control
+    pub fn requirement(self) -> RsrRequirement {
+        let key = |field: &str| format!("rsr.k8s.{}.{}", self.as_str(), field);
+        let mut manual_steps = vec![tr(&key("step"))];
+        if let Some(template) = self.template() {
+            let snippet = template.replace('\n', "\n    ");
+            manual_steps.push(tr_with("rsr.k8s.template", &[("snippet", &snippet)]));
+        }
+
+        RsrRequirement {
+            id: self.id(),
+            name: tr(&key("name")),
+            class: self.class(),
+            description: format!("{} ({})", tr(&key("description")), self.reference()),
+            validation: ValidationChecks {
+                file_exists: vec![],
+                file_absent: vec![],
+                patterns: vec![],
+                cue_validate: vec![],
+                conflow_valid: false,
+                shell_check: None,
+                manifests: vec![ManifestCheck {
+                    control: self,
+                    files: vec![],
+                }],
+                docs: vec![],
+                release: vec![],
+            },
+            remediation: RemediationOptions {
+                auto_fix: self.template().is_some(),
+                templates: vec![],
+                manual_steps,
+                docs_url: Some(format!(
+                    "https://rsr.dev/requirements/{}",
+                    self.id().trim_start_matches("RSR-").to_lowercase()
+                )),
+            },
+            related: vec![],
+            requires: vec![],
+            tags: vec!["kubernetes".into(), "security".into(), "benchmark".into()],
+        }
+    }
+}
+
+/// Built-in Kubernetes security benchmark requirements
+pub fn builtin_kubernetes_requirements() -> Vec<RsrRequirement> {
+    SecurityControl::ALL
+        .into_iter()
+        .map(SecurityControl::requirement)
+        .collect()
+}
+
+/// Check of Kubernetes manifests against a security control
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ManifestCheck {
+    /// Control the manifests must satisfy
+    pub control: SecurityControl,
+
+    /// Manifest globs (default: the pipeline's `kubernetes.files`, or every
+    /// YAML file)
+    #[serde(default)]
+    pub files: Vec<String>,
+}
+
+/// A workload breaking a control
+#[derive(Debug, Clone)]
+pub struct Violation {
+    /// Manifest file, relative to the project root
+    pub file: PathBuf,
+    /// 1-based line of the offending field
+    pub line: usize,
+    /// Index of the YAML document in the file
+    pub document: usize,
+    /// `Kind/name` of the workload
+    pub workload: String,
+    pub message: String,
+    /// Edits to the document that fix it; empty when it needs a person
+    pub fix: Vec<Change>,
+}
+
+impl std::fmt::Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}:{}: {} {}",
+            self.file.display(),
+            self.line,
+            self.workload,
+            self.message
+        )
+    }
+}
+
+impl ManifestCheck {
+    /// Manifests breaking the control in a project
+    ///
+    /// Files that don't parse, and documents that aren't workloads, are
+    /// skipped.
+    pub fn violations(&self, project_root: &Path) -> Result<Vec<Violation>, ConflowError> {
+        let patterns = match self.files.is_empty() {
+            false => self.files.clone(),
+            true => Pipeline::from_file(&project_root.join(".conflow.yaml"))
+                .map(|p| p.kubernetes.files)
+                .unwrap_or_default(),
+        };
+        let patterns = match patterns.is_empty() {
+            false => patterns,
+            true => DEFAULT_MANIFESTS.iter().map(|p| p.to_string()).collect(),
+        };
+
+        let mut violations = Vec::new();
+        for file in crate::lint::matching_files(&patterns, project_root)? {
+            let path = project_root.join(&file);
+            let Ok(content) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+            if !content.contains("apiVersion") {
+                continue;
+            }
+            violations.extend(check_file(self.control, &file, &content));
+        }
+        Ok(violations)
+    }
+}
+
+/// Violations of `control` in one manifest file
+pub fn check_file(control: SecurityControl, file: &Path, content: &str) -> Vec<Violation> {
+    let Ok(documents) = ParseCache::global().documents(file, content) else {
+        return vec![];
+    };
+    let offsets: Vec<usize> = match file.extension().and_then(|e| e.to_str()) {
+        Some("json") => vec![0],
+        _ => yaml::documents(content).iter().map(|d| d.offset).collect(),
+    };
+
+    let mut violations = Vec::new();
+    for (index, (document, offset)) in documents.iter().zip(offsets).enumerate() {
+        for (path, manifest) in workloads(document) {
+            let Some(pod) = pod_spec(manifest) else {
+                continue;
+            };
+            let workload = format!(
+                "{}/{}",
+                manifest["kind"].as_str().unwrap_or_default(),
+                manifest["metadata"]["name"].as_str().unwrap_or_default()
+            );
+            let mut found = Found {
+                content,
+                offset,
+                path: [path, pod.0].concat(),
+                issues: Vec::new(),
+            };
+            check_pod(control, pod.1, &mut found);
+            violations.extend(
+                found
+                    .issues
+                    .into_iter()
+                    .map(|(line, message, fix)| Violation {
+                        file: file.to_path_buf(),
+                        line,
+                        document: index,
+                        workload: workload.clone(),
+                        message,
+                        fix,
+                    }),
+            );
+        }
+    }
+    violations
+}
+
+/// Problems found in one pod spec
+struct Found<'a> {
+    content: &'a str,
+    offset: usize,
+    /// Path of the pod spec in the document
+    path: Vec<String>,
+    issues: Vec<(usize, String, Vec<Change>)>,
+}
+
+impl Found<'_> {
+    fn push(&mut self, at: &[&str], message: String, fix: Vec<Change>) {
+        let path: Vec<&str> = self
+            .path
+            .iter()
+            .map(String::as_str)
+            .chain(at.iter().copied())
+            .collect();
+        let line = yaml::key_line(self.content, self.offset, &path).unwrap_or(1);
+        self.issues.push((line, message, fix));
+    }
+
+    /// Report a container, at the line naming it
+    fn container(&mut self, list: &str, name: &str, message: String, fix: Vec<Change>) {
+        let path: Vec<&str> = self.path.iter().map(String::as_str).chain([list]).collect();
+        let start = yaml::key_line(self.content, self.offset, &path).unwrap_or(1);
+        let line = self
+            .content
+            .lines()
+            .enumerate()
+            .skip(start)
+            .find(|(_, line)| {
+                let line = line.trim_start().trim_start_matches("- ");
+                line.strip_prefix("name:")
+                    .map(|value| value.split('#').next().unwrap_or_default().trim())
+                    .is_some_and(|value| value.trim_matches(['"', '\'']) == name)
+            })
+            .map(|(i, _)| i + 1)
+            .unwrap_or(start);
+        self.issues.push((line, message, fix));
+    }
+
+    /// Path of a container's securityContext field
+    fn context_path(&self, list: &str, index: usize, field: &[&str]) -> Vec<String> {
+        let mut path = self.path.clone();
+        path.extend([
+            list.to_string(),
+            index.to_string(),
+            "securityContext".to_string(),
+        ]);
+        path.extend(field.iter().map(|f| f.to_string()));
+        path
+    }
+}
+
+/// A document, or the items of a `kind: List`, with their paths
+fn workloads(document: &Value) -> Vec<(Vec<String>, &Value)> {
+    match document["items"].as_array() {
+        Some(items) if document["kind"].as_str() == Some("List") => items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| (vec!["items".to_string(), i.to_string()], item))
+            .collect(),
+        _ => vec![(vec![], document)],
+    }
+}
+
+/// The pod spec of a workload, with its path in the manifest
+fn pod_spec(manifest: &Value) -> Option<(Vec<String>, &Value)> {
+    let path: &[&str] = match manifest["kind"].as_str()? {
+        "Pod" => &["spec"],
+        "Deployment"
+        | "StatefulSet"
+        | "DaemonSet"
+        | "ReplicaSet"
+        | "ReplicationController"
+        | "Job" => &["spec", "template", "spec"],
+        "CronJob" => &["spec", "jobTemplate", "spec", "template", "spec"],
+        _ => return None,
+    };
+    let spec = path
+        .iter()
+        .try_fold(manifest, |value, key| value.get(*key))?;
+    spec.is_object()
+        .then(|| (path.iter().map(|p| p.to_string()).collect(), spec))
+}
+
+fn check_pod(control: SecurityControl, pod: &Value, found: &mut Found) {
+    match control {
+        SecurityControl::HostNamespaces => {
+            for (key, namespace) in [
+                ("hostPID", "PID"),
+                ("hostIPC", "IPC"),
+                ("hostNetwork", "network"),
+            ] {
+                if pod[key].as_bool() == Some(true) {
+                    let message = tr_with(
+                        "rsr.k8s.violation.host-namespace",
+                        &[("namespace", &namespace)],
+                    );
+                    found.push(&[key], message, vec![]);
+                }
+            }
+        }
+        SecurityControl::HostPath => {
+            for volume in pod["volumes"].as_array().into_iter().flatten() {
+                let Some(host_path) = volume["hostPath"]["path"].as_str() else {
+                    continue;
+                };
+                let name = volume["name"].as_str().unwrap_or_default();
+                let message = tr_with(
+                    "rsr.k8s.violation.host-path",
+                    &[("path", &host_path), ("volume", &name)],
+                );
+                found.push(&["volumes", "hostPath"], message, vec![]);
+            }
+        }
+        _ => {
+            for list in ["initContainers", "containers"] {
+                let containers = pod[list].as_array().into_iter().flatten();
+                for (index, container) in containers.enumerate() {
+                    check_container(control, pod, list, index, container, found);
+                }
+            }
+        }
+    }
+}
+
+fn check_container(
+    control: SecurityControl,
+    pod: &Value,
+    list: &str,
+    index: usize,
+    container: &Value,
+    found: &mut Found,
+) {
+    let name = container["name"].as_str().unwrap_or_default();
+    let context = &container["securityContext"];
+    let violation = |key: &str| {
+        tr_with(
+            &format!("rsr.k8s.violation.{}", key),
+            &[("container", &name)],
+        )
+    };
+    let fix = |found: &Found| fix(control, list, index, context, found);
+
+    match control {
+        SecurityControl::Privileged if context["privileged"].as_bool() == Some(true) => {
+            found.container(list, name, violation("privileged"), fix(found));
+        }
+        SecurityControl::PrivilegeEscalation
+            if context["allowPrivilegeEscalation"].as_bool() != Some(false) =>
+        {
+            found.container(list, name, violation("privilege-escalation"), fix(found));
+        }
+        SecurityControl::RunAsNonRoot => {
+            let pod_context = &pod["securityContext"];
+            let setting = |key: &str| match &context[key] {
+                Value::Null => &pod_context[key],
+                value => value,
+            };
+            if setting("runAsUser").as_u64() == Some(0) {
+                found.container(list, name, violation("root-user"), vec![]);
+            } else if setting("runAsNonRoot").as_bool() != Some(true) {
+                found.container(list, name, violation("run-as-non-root"), fix(found));
+            }
+        }
+        SecurityControl::Capabilities => {
+            let capabilities = &context["capabilities"];
+            let added: Vec<&str> = capabilities["add"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .filter(|c| DANGEROUS_CAPABILITIES.contains(&c.trim_start_matches("CAP_")))
+                .collect();
+            if !added.is_empty() {
+                let message = tr_with(
+                    "rsr.k8s.violation.added-capabilities",
+                    &[("container", &name), ("capabilities", &added.join(", "))],
+                );
+                found.container(list, name, message, vec![]);
+            }
+            let drops_all = capabilities["drop"]
+                .as_array()
+                .is_some_and(|drop| drop.iter().any(|c| c.as_str() == Some("ALL")));
+            if !drops_all {
+                found.container(list, name, violation("capabilities"), fix(found));
+            }
+        }
+        SecurityControl::ReadOnlyRootFilesystem
+            if context["readOnlyRootFilesystem"].as_bool() != Some(true) =>
+        {
+            found.container(
+                list,
+                name,
+                violation("read-only-root-filesystem"),
+                fix(found),
+            );
+        }
+        SecurityControl::SecurityContext
+            if !context.is_object() && !pod["securityContext"].is_object() =>
+        {
+            found.container(list, name, violation("security-context"), fix(found));
+        }
+        _ => {}
+    }
+}
+
+/// Edits setting the control's template fields on a container
+///
+/// Fields already present are replaced, missing ones added, and `ALL` is
+/// appended to an existing `capabilities.drop` list.
+fn fix(
+    control: SecurityControl,
+    list: &str,
+    index: usize,
+    context: &Value,
+    found: &Found,
+) -> Vec<Change> {
+    let Some(template) = control.template() else {
+        return vec![];
+    };
+    let Ok(serde_yaml::Value::Mapping(fields)) = serde_yaml::from_str(template) else {
+        return vec![];
+    };
+    if !context.is_object() {
+        return vec![Change::Insert {
+            path: found.context_path(list, index, &[]),
+            value: serde_yaml::Value::Mapping(fields),
+        }];
+    }
+
+    let mut changes = Vec::new();
+    for (key, value) in fields {
+        let Some(key) = key.as_str() else {
+            continue;
+        };
+        let current = &context[key];
+        match (key, current) {
+            ("capabilities", Value::Object(capabilities)) => {
+                let drop = found.context_path(list, index, &["capabilities", "drop"]);
+                changes.push(match capabilities.get("drop") {
+                    Some(Value::Array(_)) => Change::Push {
+                        path: drop,
+                        value: "ALL".into(),
+                    },
+                    _ => Change::Insert {
+                        path: drop,
+                        value: value["drop"].clone(),
+                    },
+                });
+            }
+            (_, Value::Null) => changes.push(Change::Insert {
+                path: found.context_path(list, index, &[key]),
+                value,
+            }),
+            _ => changes.push(Change::Set {
+                path: found.context_path(list, index, &[key]),
+                value,
+            }),
+        }
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::yaml::edit::Editor;
+
+    const MANIFEST: &str = r#"apiVersion: apps/v1
+kind: Deployment
+metadata:
+  name: web
+spec:
+  template:
+    spec:
+      hostNetwork: true
+      volumes:
+        - name: docker
+          hostPath:
+            path: /var/run/docker.sock
+      containers:
+        - name: app
+          image: web:1.0
+          # keep this comment
+          securityContext:
+            privileged: true
+            capabilities:
+              add: [SYS_ADMIN]
+              drop:
+                - NET_BIND_SERVICE
+        - name: sidecar
+          image: proxy:1.0
+"#;
+
+    fn messages(control: SecurityControl) -> Vec<String> {
+        check_file(control, Path::new("k8s/web.yaml"), MANIFEST)
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_requirements() {
+        let requirements = builtin_kubernetes_requirements();
+        assert_eq!(requirements.len(), 8);
+        assert_eq!(requirements[0].id, "RSR-K8S-001");
+        assert_eq!(requirements[0].class, RsrRequirementClass::Mandatory);
+        assert!(requirements[0].description.ends_with("(CIS 5.2.1)"));
+        assert!(requirements[0].remediation.manual_steps[1].contains("privileged: false"));
+        assert!(!requirements[2].remediation.auto_fix);
+        assert_eq!(
+            SecurityControl::from_id("RSR-K8S-006"),
+            Some(SecurityControl::Capabilities)
+        );
+    }
+
+    #[test]
+    fn test_controls() {
+        assert_eq!(
+            messages(SecurityControl::Privileged),
+            ["k8s/web.yaml:14: Deployment/web container 'app' runs privileged"]
+        );
+        assert_eq!(
+            messages(SecurityControl::HostNamespaces),
+            ["k8s/web.yaml:8: Deployment/web shares the host's network namespace"]
+        );
+        assert_eq!(
+            messages(SecurityControl::HostPath),
+            ["k8s/web.yaml:11: Deployment/web mounts host path /var/run/docker.sock (volume 'docker')"]
+        );
+        assert_eq!(messages(SecurityControl::PrivilegeEscalation).len(), 2);
+        assert_eq!(messages(SecurityControl::Capabilities).len(), 3);
+        assert!(messages(SecurityControl::Capabilities)[0].contains("adds SYS_ADMIN"));
+        assert_eq!(
+            messages(SecurityControl::SecurityContext),
+            ["k8s/web.yaml:23: Deployment/web container 'sidecar' has no securityContext"]
+        );
+    }
+
+    #[test]
+    fn test_fixes_keep_comments() {
+        let mut editor = Editor::new(MANIFEST);
+        for control in [
+            SecurityControl::Privileged,
+            SecurityControl::Capabilities,
+            SecurityControl::RunAsNonRoot,
+        ] {
+            for violation in check_file(control, Path::new("web.yaml"), editor.content()) {
+                if !violation.fix.is_empty() {
+                    assert!(
+                        editor.apply_all(violation.document, &violation.fix),
+                        "{}",
+                        violation
+                    );
+                }
+            }
+        }
+
+        let fixed = editor.into_content();
+        assert!(fixed.contains("# keep this comment"));
+        assert!(fixed.contains("privileged: false"));
+        assert!(check_file(SecurityControl::Privileged, Path::new("web.yaml"), &fixed).is_empty());
+        assert!(
+            check_file(SecurityControl::RunAsNonRoot, Path::new("web.yaml"), &fixed).is_empty()
+        );
+        // The added SYS_ADMIN capability needs a person
+        let left = check_file(SecurityControl::Capabilities, Path::new("web.yaml"), &fixed);
+        assert_eq!(left.len(), 1);
+        assert!(left[0].fix.is_empty());
+    }
+}
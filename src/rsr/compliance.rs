@@ -3,18 +3,27 @@
 //! Checks project compliance with RSR requirements and generates reports.
 
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
 
 use crate::pipeline::{Pipeline, PipelineValidator};
+use crate::utils::codeowners::CodeOwners;
 use crate::ConflowError;
 
+use super::config::{CustomLevel, RsrConfig, ScoreFormula};
+use super::profiles;
 use super::requirements::{
     CueValidation, PatternCheck, RsrRequirement, RsrRequirementClass, RsrRequirementRegistry,
-    ValidationChecks,
 };
+use super::waivers::{self, WaiverStatus};
 
 /// Compliance level based on requirements met
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum ComplianceLevel {
     /// No compliance - mandatory requirements not met
     NonCompliant,
@@ -59,7 +68,7 @@ impl ComplianceLevel {
 }
 
 /// Result of checking a single requirement
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RequirementResult {
     /// Requirement ID
     pub requirement_id: String,
@@ -71,11 +80,44 @@ pub struct RequirementResult {
     pub details: Vec<CheckDetail>,
 
     /// Suggested remediation if not met
+    #[serde(default)]
     pub remediation: Option<String>,
+
+    /// Unmet prerequisites; a blocked requirement is not checked
+    #[serde(default)]
+    pub blocked_by: Vec<String>,
+
+    /// Whether the requirement is unmet but covered by an active waiver
+    #[serde(default)]
+    pub waived: bool,
+
+    /// CODEOWNERS owners of the files behind an unmet requirement
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub owners: Vec<String>,
+}
+
+impl RequirementResult {
+    /// Result for a requirement whose prerequisites are not met
+    fn blocked(requirement: &RsrRequirement, blocked_by: Vec<String>) -> Self {
+        Self {
+            requirement_id: requirement.id.clone(),
+            met: false,
+            details: Vec::new(),
+            remediation: Some(format!("Meet {} first", blocked_by.join(", "))),
+            blocked_by,
+            waived: false,
+            owners: Vec::new(),
+        }
+    }
+
+    /// Whether the requirement was skipped because a prerequisite is not met
+    pub fn is_blocked(&self) -> bool {
+        !self.blocked_by.is_empty()
+    }
 }
 
 /// Detail of a single check
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CheckDetail {
     /// What was checked
     pub check: String,
@@ -84,11 +126,18 @@ pub struct CheckDetail {
     pub passed: bool,
 
     /// Additional info
+    #[serde(default)]
     pub info: Option<String>,
+
+    /// Files the check looked at, relative to the project root
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<PathBuf>,
 }
 
 /// Full compliance report
-#[derive(Debug, Clone)]
+///
+/// Persisted with [`crate::report::to_json`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ComplianceReport {
     /// Overall compliance level
     pub level: ComplianceLevel,
@@ -101,14 +150,32 @@ pub struct ComplianceReport {
 
     /// Summary statistics
     pub stats: ComplianceStats,
+
+    /// Highest custom level reached, when `.rsr.yaml` defines levels
+    #[serde(default)]
+    pub custom_level: Option<String>,
+
+    /// Every recorded waiver, including expired and unapproved ones
+    #[serde(default)]
+    pub waivers: Vec<WaiverStatus>,
+}
+
+impl ComplianceReport {
+    /// Waivers that are expired or unapproved
+    pub fn invalid_waivers(&self) -> impl Iterator<Item = &WaiverStatus> {
+        self.waivers.iter().filter(|w| !w.is_active())
+    }
 }
 
 /// Summary statistics
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ComplianceStats {
     pub total: usize,
     pub passed: usize,
     pub failed: usize,
+    pub blocked: usize,
+    pub waived: usize,
     pub mandatory_total: usize,
     pub mandatory_passed: usize,
     pub preferential_total: usize,
@@ -117,36 +184,105 @@ pub struct ComplianceStats {
     pub advisory_passed: usize,
 }
 
+/// How long a single requirement's shell check may run
+pub const DEFAULT_CHECK_TIMEOUT: Duration = Duration::from_secs(60);
+
 /// Compliance checker
 pub struct ComplianceChecker {
     registry: RsrRequirementRegistry,
+    timeout: Duration,
+    formula: ScoreFormula,
+    weights: HashMap<String, f64>,
+    levels: Vec<CustomLevel>,
+    waivers: Vec<WaiverStatus>,
 }
 
 impl ComplianceChecker {
     /// Create a new compliance checker
     pub fn new() -> Self {
-        Self {
-            registry: RsrRequirementRegistry::new(),
-        }
+        Self::with_registry(RsrRequirementRegistry::new())
     }
 
     /// Create with custom registry
     pub fn with_registry(registry: RsrRequirementRegistry) -> Self {
-        Self { registry }
+        Self {
+            registry,
+            timeout: DEFAULT_CHECK_TIMEOUT,
+            formula: ScoreFormula::default(),
+            weights: HashMap::new(),
+            levels: Vec::new(),
+            waivers: Vec::new(),
+        }
+    }
+
+    /// Create a checker using the project's `.rsr.yaml`
+    pub fn for_project(project_root: &Path) -> Result<Self, ConflowError> {
+        let config = RsrConfig::load_from_project(project_root)?;
+        let profiles = profiles::load_all(&config)?;
+        let registry = RsrRequirementRegistry::from_config(&config, &profiles, project_root)?;
+        let waivers = waivers::project_waivers(project_root, &config)?;
+        Ok(Self::with_registry(registry)
+            .with_config(&config)
+            .with_waivers(waivers))
+    }
+
+    /// Apply the score formula, weights and custom levels from a config
+    pub fn with_config(mut self, config: &RsrConfig) -> Self {
+        self.formula = config.compliance.score;
+        self.weights = config.compliance.weights.clone();
+        self.levels = config.compliance.levels.clone();
+        self
+    }
+
+    /// Accept unmet requirements covered by active waivers
+    pub fn with_waivers(mut self, waivers: Vec<WaiverStatus>) -> Self {
+        self.waivers = waivers;
+        self
+    }
+
+    /// Requirements the checker checks
+    pub fn registry(&self) -> &RsrRequirementRegistry {
+        &self.registry
+    }
+
+    /// Waivers known to the checker, including expired and unapproved ones
+    pub fn waivers(&self) -> &[WaiverStatus] {
+        &self.waivers
+    }
+
+    /// Limit how long each requirement's shell check may run
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
     }
 
     /// Check compliance for a project
     pub fn check(&self, project_root: &Path) -> Result<ComplianceReport, ConflowError> {
-        let mut results = Vec::new();
-        let mut stats = ComplianceStats::default();
+        self.check_with(project_root, |_| {})
+    }
 
-        for requirement in self.registry.all() {
-            let result = self.check_requirement(requirement, project_root)?;
+    /// Check compliance, calling `on_result` as each requirement finishes
+    ///
+    /// Requirements are checked concurrently, so `on_result` sees them in
+    /// completion order; the report lists them in registry order.
+    pub fn check_with(
+        &self,
+        project_root: &Path,
+        on_result: impl FnMut(&RequirementResult),
+    ) -> Result<ComplianceReport, ConflowError> {
+        let requirements: Vec<_> = self.registry.all().collect();
+        let results = self.check_all(&requirements, project_root, on_result)?;
+        let mut stats = ComplianceStats::default();
 
+        for (requirement, result) in requirements.iter().zip(&results) {
             // Update stats
             stats.total += 1;
             if result.met {
                 stats.passed += 1;
+            } else if result.waived {
+                stats.waived += 1;
+            } else if result.is_blocked() {
+                stats.blocked += 1;
             } else {
                 stats.failed += 1;
             }
@@ -171,20 +307,123 @@ impl ComplianceChecker {
                     }
                 }
             }
-
-            results.push(result);
         }
 
-        // Calculate score
-        let score = self.calculate_score(&results);
-        let mandatory_met = stats.mandatory_passed == stats.mandatory_total;
+        // Calculate score; waived requirements count as met for the level
+        let mandatory_met = requirements
+            .iter()
+            .zip(&results)
+            .filter(|(req, _)| req.class == RsrRequirementClass::Mandatory)
+            .all(|(_, result)| result.met || result.waived);
+        let score = self.calculate_score(&results, mandatory_met);
         let level = ComplianceLevel::from_score(score, mandatory_met);
+        let custom_level = self.custom_level(&results, score, mandatory_met);
 
         Ok(ComplianceReport {
             level,
             score,
             requirements: results,
             stats,
+            custom_level,
+            waivers: self.waivers.clone(),
+        })
+    }
+
+    /// Check requirements, returning results in input order
+    ///
+    /// Requirements run in waves so that each is checked after the ones it
+    /// `requires`; one with an unmet prerequisite is reported as blocked.
+    /// Unmet requirements with an active waiver are marked as waived, and
+    /// every unmet requirement is attributed to its CODEOWNERS owners.
+    fn check_all(
+        &self,
+        requirements: &[&RsrRequirement],
+        project_root: &Path,
+        mut on_result: impl FnMut(&RequirementResult),
+    ) -> Result<Vec<RequirementResult>, ConflowError> {
+        let mut results: Vec<Option<RequirementResult>> = vec![None; requirements.len()];
+        let codeowners = CodeOwners::load(project_root).unwrap_or_default();
+
+        for wave in dependency_waves(requirements)? {
+            let mut ready = Vec::new();
+            for index in wave {
+                let unmet = |id: &String| {
+                    results
+                        .iter()
+                        .flatten()
+                        .any(|r| &r.requirement_id == id && !r.met)
+                };
+                let blocked_by: Vec<String> = requirements[index]
+                    .requires
+                    .iter()
+                    .filter(|id| unmet(id))
+                    .cloned()
+                    .collect();
+
+                if blocked_by.is_empty() {
+                    ready.push(index);
+                } else {
+                    let result =
+                        self.waive(RequirementResult::blocked(requirements[index], blocked_by));
+                    let result = attribute(result, &codeowners);
+                    on_result(&result);
+                    results[index] = Some(result);
+                }
+            }
+
+            self.check_concurrently(requirements, &ready, project_root, |index, result| {
+                let result = attribute(self.waive(result), &codeowners);
+                on_result(&result);
+                results[index] = Some(result);
+            })?;
+        }
+
+        Ok(results.into_iter().flatten().collect())
+    }
+
+    /// Mark an unmet result as waived if an active waiver covers it
+    fn waive(&self, mut result: RequirementResult) -> RequirementResult {
+        result.waived = !result.met
+            && self
+                .waivers
+                .iter()
+                .any(|w| w.is_active() && w.waiver.requirement == result.requirement_id);
+        result
+    }
+
+    /// Check `requirements[i]` for each `i` in `indices` on a pool of threads
+    fn check_concurrently(
+        &self,
+        requirements: &[&RsrRequirement],
+        indices: &[usize],
+        project_root: &Path,
+        mut on_result: impl FnMut(usize, RequirementResult),
+    ) -> Result<(), ConflowError> {
+        let workers = std::thread::available_parallelism()
+            .map_or(1, |n| n.get())
+            .min(indices.len());
+        let next = AtomicUsize::new(0);
+        let (tx, rx) = mpsc::channel();
+
+        std::thread::scope(|scope| {
+            for _ in 0..workers {
+                let tx = tx.clone();
+                let next = &next;
+                scope.spawn(move || {
+                    while let Some(&index) = indices.get(next.fetch_add(1, Ordering::Relaxed)) {
+                        let result = self.check_requirement(requirements[index], project_root);
+                        if tx.send((index, result)).is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+            drop(tx);
+
+            for (index, result) in rx {
+                on_result(index, result?);
+            }
+            Ok(())
         })
     }
 
@@ -212,6 +451,7 @@ impl ComplianceChecker {
                 } else {
                     None
                 },
+                files: vec![file.clone()],
             });
 
             if !exists {
@@ -232,6 +472,7 @@ impl ComplianceChecker {
                 } else {
                     None
                 },
+                files: vec![file.clone()],
             });
 
             if !absent {
@@ -256,6 +497,7 @@ impl ComplianceChecker {
                 ),
                 passed,
                 info: result.err().map(|e| e.to_string()),
+                files: vec![pattern_check.file.clone()],
             });
 
             if !passed {
@@ -273,6 +515,7 @@ impl ComplianceChecker {
                 check: "conflow pipeline valid".into(),
                 passed,
                 info,
+                files: vec![PathBuf::from(".conflow.yaml")],
             });
 
             if !passed {
@@ -290,6 +533,7 @@ impl ComplianceChecker {
                 check: format!("CUE validation: {}", cue_val.schema.display()),
                 passed,
                 info,
+                files: cue_val.files.clone(),
             });
 
             if !passed {
@@ -300,14 +544,87 @@ impl ComplianceChecker {
         // Check shell command
         if let Some(ref shell_check) = validation.shell_check {
             let result = self.check_shell_command(shell_check, project_root);
+            let passed = result.is_ok();
 
             details.push(CheckDetail {
                 check: format!("Shell check: {}", shell_check),
-                passed: result,
-                info: None,
+                passed,
+                info: result.err(),
+                files: Vec::new(),
+            });
+
+            if !passed {
+                all_passed = false;
+            }
+        }
+
+        // Check Kubernetes manifests
+        for manifest_check in &validation.manifests {
+            let mut files = Vec::new();
+            let (passed, info) = match manifest_check.violations(project_root) {
+                Ok(violations) if violations.is_empty() => (true, None),
+                Ok(violations) => {
+                    let mut lines: Vec<String> =
+                        violations.iter().take(5).map(ToString::to_string).collect();
+                    if violations.len() > 5 {
+                        lines.push(format!("... and {} more", violations.len() - 5));
+                    }
+                    for violation in &violations {
+                        if !files.contains(&violation.file) {
+                            files.push(violation.file.clone());
+                        }
+                    }
+                    (false, Some(lines.join("\n")))
+                }
+                Err(e) => (false, Some(e.to_string())),
+            };
+
+            details.push(CheckDetail {
+                check: format!("Kubernetes manifests: {}", manifest_check.control.as_str()),
+                passed,
+                info,
+                files,
+            });
+
+            if !passed {
+                all_passed = false;
+            }
+        }
+
+        // Check project documents, and readiness for a release
+        let docs = validation.docs.iter().map(|check| {
+            let name = format!("Documentation: {}", check.control.as_str());
+            (name, check.findings(project_root))
+        });
+        let release = validation.release.iter().map(|check| {
+            let name = format!("Release: {}", check.control.as_str());
+            (name, check.findings(project_root))
+        });
+        for (check, findings) in docs.chain(release) {
+            let passed = findings.is_empty();
+            let mut files = Vec::new();
+            for finding in &findings {
+                if !finding.file.as_os_str().is_empty() && !files.contains(&finding.file) {
+                    files.push(finding.file.clone());
+                }
+            }
+            let info = (!passed).then(|| {
+                let mut lines: Vec<String> =
+                    findings.iter().take(5).map(ToString::to_string).collect();
+                if findings.len() > 5 {
+                    lines.push(format!("... and {} more", findings.len() - 5));
+                }
+                lines.join("\n")
+            });
+
+            details.push(CheckDetail {
+                check,
+                passed,
+                info,
+                files,
             });
 
-            if !result {
+            if !passed {
                 all_passed = false;
             }
         }
@@ -316,13 +633,23 @@ impl ComplianceChecker {
         let remediation = if !all_passed {
             let mut rem = Vec::new();
 
-            if !requirement.remediation.templates.is_empty() {
+            if let Some(template) = requirement.remediation.templates.first() {
+                // `conflow init` sets up pipelines; other templates are rendered
+                let command = match template
+                    .generates
+                    .iter()
+                    .any(|f| f == Path::new(".conflow.yaml"))
+                {
+                    true => "conflow init --template",
+                    false => "conflow template render",
+                };
                 rem.push(format!(
-                    "Run: conflow init --template {}",
-                    requirement.remediation.templates[0]
+                    "Run: {} {}",
+                    command,
+                    template
                         .conflow_template
                         .as_deref()
-                        .unwrap_or(&requirement.remediation.templates[0].name)
+                        .unwrap_or(&template.name)
                 ));
             }
 
@@ -344,6 +671,9 @@ impl ComplianceChecker {
             met: all_passed,
             details,
             remediation,
+            blocked_by: Vec::new(),
+            waived: false,
+            owners: Vec::new(),
         })
     }
 
@@ -405,25 +735,57 @@ impl ComplianceChecker {
         Ok(())
     }
 
-    /// Check shell command
-    fn check_shell_command(&self, command: &str, project_root: &Path) -> bool {
-        std::process::Command::new("bash")
-            .arg("-c")
+    /// Check shell command, killing it if it outlives the timeout
+    fn check_shell_command(&self, command: &str, project_root: &Path) -> Result<(), String> {
+        let mut bash = std::process::Command::new("bash");
+        bash.arg("-c")
             .arg(command)
             .current_dir(project_root)
-            .status()
-            .map(|s| s.success())
-            .unwrap_or(false)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+        // In its own process group, so a timeout also stops what it started
+        #[cfg(unix)]
+        std::os::unix::process::CommandExt::process_group(&mut bash, 0);
+        let mut child = bash
+            .spawn()
+            .map_err(|e| format!("Failed to run bash: {}", e))?;
+
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            match child.try_wait() {
+                Ok(Some(status)) if status.success() => return Ok(()),
+                Ok(Some(status)) => return Err(format!("Exited with {}", status)),
+                Ok(None) if Instant::now() >= deadline => {
+                    kill_group(&mut child);
+                    return Err(format!("Timed out after {:?}", self.timeout));
+                }
+                Ok(None) => std::thread::sleep(Duration::from_millis(20)),
+                Err(e) => return Err(e.to_string()),
+            }
+        }
     }
 
     /// Calculate weighted score
-    fn calculate_score(&self, results: &[RequirementResult]) -> f64 {
+    fn calculate_score(&self, results: &[RequirementResult], mandatory_met: bool) -> f64 {
+        if self.formula == ScoreFormula::Strict && !mandatory_met {
+            return 0.0;
+        }
+
         let mut total_weight = 0.0;
         let mut earned_weight = 0.0;
 
-        for result in results {
+        // Waived requirements are left out of the score
+        for result in results.iter().filter(|r| !r.waived) {
             if let Some(req) = self.registry.get(&result.requirement_id) {
-                let weight = req.class.weight();
+                let weight = match self.formula {
+                    ScoreFormula::Ratio => 1.0,
+                    ScoreFormula::Weighted | ScoreFormula::Strict => self
+                        .weights
+                        .get(&req.id)
+                        .copied()
+                        .unwrap_or_else(|| req.class.weight()),
+                };
                 total_weight += weight;
                 if result.met {
                     earned_weight += weight;
@@ -438,21 +800,49 @@ impl ComplianceChecker {
         }
     }
 
+    /// Name of the highest custom level whose conditions hold
+    fn custom_level(
+        &self,
+        results: &[RequirementResult],
+        score: f64,
+        mandatory_met: bool,
+    ) -> Option<String> {
+        let met = |id: &String| {
+            results
+                .iter()
+                .any(|r| &r.requirement_id == id && (r.met || r.waived))
+        };
+
+        self.levels
+            .iter()
+            .filter(|level| score >= level.min_score)
+            .filter(|level| mandatory_met || !level.mandatory)
+            .filter(|level| level.requires.iter().all(met))
+            .max_by(|a, b| a.min_score.total_cmp(&b.min_score))
+            .map(|level| level.name.clone())
+    }
+
     /// Check specific requirements
     pub fn check_requirements(
         &self,
         requirement_ids: &[&str],
         project_root: &Path,
     ) -> Result<Vec<RequirementResult>, ConflowError> {
-        let mut results = Vec::new();
-
-        for id in requirement_ids {
-            if let Some(req) = self.registry.get(id) {
-                results.push(self.check_requirement(req, project_root)?);
-            }
-        }
+        self.check_requirements_with(requirement_ids, project_root, |_| {})
+    }
 
-        Ok(results)
+    /// Check specific requirements, calling `on_result` as each one finishes
+    pub fn check_requirements_with(
+        &self,
+        requirement_ids: &[&str],
+        project_root: &Path,
+        on_result: impl FnMut(&RequirementResult),
+    ) -> Result<Vec<RequirementResult>, ConflowError> {
+        let requirements: Vec<_> = requirement_ids
+            .iter()
+            .filter_map(|id| self.registry.get(id))
+            .collect();
+        self.check_all(&requirements, project_root, on_result)
     }
 }
 
@@ -462,6 +852,75 @@ impl Default for ComplianceChecker {
     }
 }
 
+/// Attribute an unmet requirement to the owners of its failed checks' files
+///
+/// Gaps no file points at, such as a failed shell check, go to the owners
+/// of `.rsr.yaml`.
+fn attribute(mut result: RequirementResult, codeowners: &CodeOwners) -> RequirementResult {
+    if result.met {
+        return result;
+    }
+    let files: Vec<&Path> = result
+        .details
+        .iter()
+        .filter(|d| !d.passed)
+        .flat_map(|d| d.files.iter().map(PathBuf::as_path))
+        .collect();
+    result.owners = match files.is_empty() {
+        true => codeowners.owners(Path::new(".rsr.yaml")).to_vec(),
+        false => codeowners.owners_of(files),
+    };
+    result
+}
+
+/// Group requirement indices into waves whose prerequisites are all in
+/// earlier waves; prerequisites that are not being checked are ignored
+fn dependency_waves(requirements: &[&RsrRequirement]) -> Result<Vec<Vec<usize>>, ConflowError> {
+    let positions: HashMap<&str, usize> = requirements
+        .iter()
+        .enumerate()
+        .map(|(i, r)| (r.id.as_str(), i))
+        .collect();
+    let mut done = vec![false; requirements.len()];
+    let mut remaining: Vec<usize> = (0..requirements.len()).collect();
+    let mut waves = Vec::new();
+
+    while !remaining.is_empty() {
+        let (ready, waiting): (Vec<usize>, Vec<usize>) = remaining.iter().partition(|&&i| {
+            requirements[i]
+                .requires
+                .iter()
+                .filter_map(|id| positions.get(id.as_str()))
+                .all(|&p| done[p])
+        });
+
+        if ready.is_empty() {
+            return Err(ConflowError::RequirementCycle {
+                requirements: waiting.iter().map(|&i| requirements[i].id.clone()).collect(),
+            });
+        }
+
+        for &i in &ready {
+            done[i] = true;
+        }
+        waves.push(ready);
+        remaining = waiting;
+    }
+
+    Ok(waves)
+}
+
+/// Kill a timed-out shell check together with its process group
+fn kill_group(child: &mut Child) {
+    #[cfg(all(unix, feature = "native"))]
+    // SAFETY: kill takes no pointers; the group was created for this child
+    unsafe {
+        libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL);
+    }
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -526,4 +985,255 @@ stages:
         // RSR-CONFIG-002 should pass (file exists and valid)
         assert!(results[0].met);
     }
+
+    fn shell_requirement(id: &str, command: &str, requires: &[&str]) -> RsrRequirement {
+        let mut requirement = super::super::requirements::builtin_config_requirements().remove(0);
+        requirement.id = id.to_string();
+        requirement.validation.file_exists.clear();
+        requirement.validation.patterns.clear();
+        requirement.validation.conflow_valid = false;
+        requirement.validation.shell_check = Some(command.to_string());
+        requirement.requires = requires.iter().map(|id| id.to_string()).collect();
+        requirement
+    }
+
+    #[test]
+    fn test_shell_check_timeout_and_streaming() {
+        let temp = TempDir::new().unwrap();
+        let mut registry = RsrRequirementRegistry::default();
+        registry.register(shell_requirement("TEST-HANG", "sleep 30", &[]));
+        registry.register(shell_requirement("TEST-PASS", "true", &[]));
+        registry.register(shell_requirement("TEST-FAIL", "exit 3", &[]));
+        let checker =
+            ComplianceChecker::with_registry(registry).with_timeout(Duration::from_millis(200));
+
+        let started = Instant::now();
+        let mut streamed = Vec::new();
+        let report = checker
+            .check_with(temp.path(), |result| {
+                streamed.push(result.requirement_id.clone())
+            })
+            .unwrap();
+        assert!(started.elapsed() < Duration::from_secs(10));
+
+        streamed.sort();
+        assert_eq!(streamed, ["TEST-FAIL", "TEST-HANG", "TEST-PASS"]);
+
+        let result = |id: &str| {
+            report
+                .requirements
+                .iter()
+                .find(|r| r.requirement_id == id)
+                .unwrap()
+        };
+        assert!(result("TEST-PASS").met);
+        assert!(!result("TEST-FAIL").met);
+        let hung = result("TEST-HANG");
+        assert!(!hung.met);
+        assert_eq!(hung.details[0].info.as_deref(), Some("Timed out after 200ms"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_shell_check_timeout_kills_background_processes() {
+        let temp = TempDir::new().unwrap();
+        let mut registry = RsrRequirementRegistry::default();
+        registry.register(shell_requirement(
+            "TEST-HANG",
+            "sleep 30 & echo $! > sleeper.pid; wait",
+            &[],
+        ));
+        ComplianceChecker::with_registry(registry)
+            .with_timeout(Duration::from_millis(300))
+            .check(temp.path())
+            .unwrap();
+
+        let pid = std::fs::read_to_string(temp.path().join("sleeper.pid")).unwrap();
+        let stat = format!("/proc/{}/stat", pid.trim());
+        let started = Instant::now();
+        // Gone, or a zombie waiting to be reaped by init
+        while std::fs::read_to_string(&stat)
+            .is_ok_and(|s| s.split(' ').nth(2) != Some("Z"))
+        {
+            assert!(
+                started.elapsed() < Duration::from_secs(5),
+                "background sleep survived the timeout"
+            );
+            std::thread::sleep(Duration::from_millis(20));
+        }
+    }
+
+    #[test]
+    fn test_unmet_requirements_are_attributed_to_owners() {
+        let temp = TempDir::new().unwrap();
+        std::fs::write(
+            temp.path().join("CODEOWNERS"),
+            "* @org/everyone\n/docs/ @org/docs\n.rsr.yaml @org/compliance\n",
+        )
+        .unwrap();
+        let mut registry = RsrRequirementRegistry::default();
+        let mut docs = shell_requirement("TEST-DOCS", "true", &[]);
+        docs.validation.shell_check = None;
+        docs.validation.file_exists = vec![PathBuf::from("docs/README.md")];
+        registry.register(docs);
+        registry.register(shell_requirement("TEST-SHELL", "false", &[]));
+        registry.register(shell_requirement("TEST-PASS", "true", &[]));
+
+        let report = ComplianceChecker::with_registry(registry)
+            .check(temp.path())
+            .unwrap();
+        let owners = |id: &str| {
+            let result = report.requirements.iter().find(|r| r.requirement_id == id);
+            result.unwrap().owners.join(" ")
+        };
+        assert_eq!(owners("TEST-DOCS"), "@org/docs");
+        // Gaps without files go to the owners of the RSR config
+        assert_eq!(owners("TEST-SHELL"), "@org/compliance");
+        assert_eq!(owners("TEST-PASS"), "");
+    }
+
+    #[test]
+    fn test_failing_prerequisite_blocks_dependents() {
+        let temp = TempDir::new().unwrap();
+        let mut registry = RsrRequirementRegistry::default();
+        registry.register(shell_requirement("TEST-BASE", "false", &[]));
+        registry.register(shell_requirement("TEST-CHILD", "true", &["TEST-BASE"]));
+        registry.register(shell_requirement("TEST-GRANDCHILD", "true", &["TEST-CHILD"]));
+        registry.register(shell_requirement("TEST-OTHER", "true", &["TEST-MISSING"]));
+        let checker = ComplianceChecker::with_registry(registry);
+
+        let report = checker.check(temp.path()).unwrap();
+        let result = |id: &str| {
+            report
+                .requirements
+                .iter()
+                .find(|r| r.requirement_id == id)
+                .unwrap()
+        };
+
+        assert!(!result("TEST-BASE").met);
+        assert!(!result("TEST-BASE").is_blocked());
+        assert_eq!(result("TEST-CHILD").blocked_by, ["TEST-BASE"]);
+        assert!(result("TEST-CHILD").details.is_empty());
+        assert_eq!(result("TEST-GRANDCHILD").blocked_by, ["TEST-CHILD"]);
+        // Prerequisites outside the checked set are ignored
+        assert!(result("TEST-OTHER").met);
+        assert_eq!(report.stats.failed, 1);
+        assert_eq!(report.stats.blocked, 2);
+
+        // Checking a dependent on its own runs it
+        let results = checker
+            .check_requirements(&["TEST-CHILD"], temp.path())
+            .unwrap();
+        assert!(results[0].met);
+    }
+
+    #[test]
+    fn test_requirement_cycle() {
+        let temp = TempDir::new().unwrap();
+        let mut registry = RsrRequirementRegistry::default();
+        registry.register(shell_requirement("TEST-A", "true", &["TEST-B"]));
+        registry.register(shell_requirement("TEST-B", "true", &["TEST-A"]));
+        registry.register(shell_requirement("TEST-C", "true", &[]));
+
+        let err = ComplianceChecker::with_registry(registry)
+            .check(temp.path())
+            .unwrap_err();
+        let ConflowError::RequirementCycle { mut requirements } = err else {
+            panic!("expected a cycle, got {:?}", err);
+        };
+        requirements.sort();
+        assert_eq!(requirements, ["TEST-A", "TEST-B"]);
+    }
+
+    #[test]
+    fn test_custom_weights_formula_and_levels() {
+        let temp = TempDir::new().unwrap();
+        let mut registry = RsrRequirementRegistry::default();
+        registry.register(shell_requirement("TEST-PASS", "true", &[]));
+        let mut failing = shell_requirement("TEST-FAIL", "false", &[]);
+        failing.class = RsrRequirementClass::Advisory;
+        registry.register(failing);
+        let config: RsrConfig = serde_yaml::from_str(
+            r#"
+compliance:
+  weights: { TEST-PASS: 3.0, TEST-FAIL: 1.0 }
+  levels:
+    - name: Initial
+    - name: Managed
+      min_score: 0.7
+    - name: Optimizing
+      min_score: 0.7
+      requires: [TEST-FAIL]
+"#,
+        )
+        .unwrap();
+
+        let checker = ComplianceChecker::with_registry(registry).with_config(&config);
+        let report = checker.check(temp.path()).unwrap();
+        assert_eq!(report.score, 0.75);
+        assert_eq!(report.custom_level.as_deref(), Some("Managed"));
+
+        let mut ratio = config.clone();
+        ratio.compliance.score = ScoreFormula::Ratio;
+        let report = checker.with_config(&ratio).check(temp.path()).unwrap();
+        assert_eq!(report.score, 0.5);
+        assert_eq!(report.custom_level.as_deref(), Some("Initial"));
+    }
+
+    #[test]
+    fn test_waived_requirements() {
+        let temp = TempDir::new().unwrap();
+        let mut registry = RsrRequirementRegistry::default();
+        registry.register(shell_requirement("TEST-PASS", "true", &[]));
+        registry.register(shell_requirement("TEST-WAIVED", "false", &[]));
+        registry.register(shell_requirement("TEST-EXPIRED", "false", &[]));
+        let config: RsrConfig = serde_yaml::from_str(
+            r#"
+compliance:
+  exceptions:
+    - requirement: TEST-WAIVED
+      reason: Tracked upstream
+      approved_by: alex
+"#,
+        )
+        .unwrap();
+        waivers::save(
+            temp.path(),
+            &[waivers::Waiver {
+                requirement: "TEST-EXPIRED".into(),
+                reason: "Temporary".into(),
+                approver: Some("alex".into()),
+                expires: Some("2020-01-01".into()),
+                created: None,
+                signature: None,
+            }],
+        )
+        .unwrap();
+
+        let waivers = waivers::project_waivers(temp.path(), &config).unwrap();
+        let report = ComplianceChecker::with_registry(registry)
+            .with_waivers(waivers)
+            .check(temp.path())
+            .unwrap();
+        let result = |id: &str| {
+            report
+                .requirements
+                .iter()
+                .find(|r| r.requirement_id == id)
+                .unwrap()
+        };
+
+        assert!(result("TEST-WAIVED").waived);
+        assert!(!result("TEST-EXPIRED").waived);
+        assert!(!result("TEST-PASS").waived);
+        assert_eq!(report.stats.waived, 1);
+        assert_eq!(report.stats.failed, 1);
+        // The waived requirement is left out of the score
+        assert_eq!(report.score, 0.5);
+
+        let invalid: Vec<_> = report.invalid_waivers().collect();
+        assert_eq!(invalid.len(), 1);
+        assert_eq!(invalid[0].waiver.requirement, "TEST-EXPIRED");
+    }
 }
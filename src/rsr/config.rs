@@ -10,11 +10,17 @@ use std::path::{Path, PathBuf};
 
 use serde::{Deserialize, Serialize};
 
+use super::badges::{BadgeStyle, ColorThreshold};
+use super::hooks::HookConfig;
+use super::profiles::ProfileSource;
 use super::requirements::{RsrRequirement, RsrRequirementClass};
+use super::templates::TemplateRepo;
+use crate::utils::cron::Schedule;
 use crate::ConflowError;
 
 /// RSR Configuration from .rsr.yaml
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct RsrConfig {
     /// RSR version
     #[serde(default = "default_version")]
@@ -39,6 +45,18 @@ pub struct RsrConfig {
     /// Custom schemas
     #[serde(default)]
     pub schemas: Vec<SchemaReference>,
+
+    /// Organization profiles to inherit requirements, schemas and templates from
+    #[serde(default)]
+    pub profiles: Vec<ProfileSource>,
+
+    /// Template repositories, extending the built-in templates
+    #[serde(default)]
+    pub templates: Vec<TemplateRepo>,
+
+    /// Hooks run after RSR triggers such as compliance checks
+    #[serde(default)]
+    pub hooks: Vec<HookConfig>,
 }
 
 fn default_version() -> String {
@@ -47,6 +65,7 @@ fn default_version() -> String {
 
 /// Project configuration
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct ProjectConfig {
     /// Project name
     pub name: Option<String>,
@@ -64,6 +83,7 @@ pub struct ProjectConfig {
 
 /// Requirements configuration
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct RequirementsConfig {
     /// Skip specific requirements
     #[serde(default)]
@@ -74,7 +94,7 @@ pub struct RequirementsConfig {
     pub custom: Vec<RsrRequirement>,
 
     /// Override requirement classes
-    #[serde(default)]
+    #[serde(default, serialize_with = "crate::report::sorted")]
     pub overrides: HashMap<String, RequirementOverride>,
 
     /// Import requirements from external files
@@ -84,6 +104,7 @@ pub struct RequirementsConfig {
 
 /// Override for a requirement
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct RequirementOverride {
     /// Override class
     pub class: Option<RsrRequirementClass>,
@@ -98,6 +119,7 @@ pub struct RequirementOverride {
 
 /// Integration settings
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct IntegrationsConfig {
     /// conflow integration
     #[serde(default)]
@@ -110,10 +132,15 @@ pub struct IntegrationsConfig {
     /// Notification settings
     #[serde(default)]
     pub notifications: NotificationSettings,
+
+    /// Badge settings
+    #[serde(default)]
+    pub badges: BadgeConfig,
 }
 
 /// conflow integration settings
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct ConflowIntegration {
     /// Enable conflow integration
     #[serde(default = "default_true")]
@@ -143,6 +170,7 @@ impl Default for ConflowIntegration {
 
 /// CI integration settings
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct CiIntegration {
     /// CI provider
     pub provider: Option<CiProvider>,
@@ -159,9 +187,50 @@ pub struct CiIntegration {
     pub generate_badges: bool,
 }
 
+/// Badge settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BadgeConfig {
+    /// Directory badges are written to
+    #[serde(default = "default_badge_output")]
+    pub output: PathBuf,
+
+    /// Badge style
+    #[serde(default)]
+    pub style: BadgeStyle,
+
+    /// Label text (default: RSR)
+    pub label: Option<String>,
+
+    /// Colors by score, replacing the colors of the compliance levels
+    #[serde(default)]
+    pub thresholds: Vec<ColorThreshold>,
+
+    /// Requirement tags to generate a badge each for, e.g. security
+    #[serde(default)]
+    pub categories: Vec<String>,
+}
+
+fn default_badge_output() -> PathBuf {
+    PathBuf::from(".rsr/badges")
+}
+
+impl Default for BadgeConfig {
+    fn default() -> Self {
+        Self {
+            output: default_badge_output(),
+            style: BadgeStyle::default(),
+            label: None,
+            thresholds: Vec::new(),
+            categories: Vec::new(),
+        }
+    }
+}
+
 /// CI Provider
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
 #[serde(rename_all = "lowercase")]
+#[clap(rename_all = "lowercase")]
 pub enum CiProvider {
     GitHub,
     GitLab,
@@ -173,6 +242,7 @@ pub enum CiProvider {
 
 /// Notification settings
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct NotificationSettings {
     /// Notify on regression
     #[serde(default)]
@@ -192,6 +262,7 @@ pub struct NotificationSettings {
 
 /// Compliance configuration
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct ComplianceConfig {
     /// Target compliance level
     pub target_level: Option<TargetLevel>,
@@ -206,6 +277,55 @@ pub struct ComplianceConfig {
 
     /// History file path
     pub history_file: Option<PathBuf>,
+
+    /// Cron schedule for checks by `conflow serve --project`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<Schedule>,
+
+    /// How the compliance score is calculated
+    #[serde(default)]
+    pub score: ScoreFormula,
+
+    /// Score weights by requirement ID, replacing the weight of its class
+    #[serde(default, serialize_with = "crate::report::sorted")]
+    pub weights: HashMap<String, f64>,
+
+    /// Custom levels, e.g. an internal maturity model
+    #[serde(default)]
+    pub levels: Vec<CustomLevel>,
+}
+
+/// Compliance score formula
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScoreFormula {
+    /// Share of the total weight that is met
+    #[default]
+    Weighted,
+    /// Share of requirements that are met, ignoring weights
+    Ratio,
+    /// Like `weighted`, but zero while a mandatory requirement is unmet
+    Strict,
+}
+
+/// A custom compliance level
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CustomLevel {
+    /// Level name, e.g. "L3 Managed"
+    pub name: String,
+
+    /// Lowest score (0.0 - 1.0) that reaches this level
+    #[serde(default)]
+    pub min_score: f64,
+
+    /// Whether every mandatory requirement must be met
+    #[serde(default = "default_true")]
+    pub mandatory: bool,
+
+    /// Requirements that must be met to reach this level
+    #[serde(default)]
+    pub requires: Vec<String>,
 }
 
 /// Target compliance level
@@ -219,6 +339,7 @@ pub enum TargetLevel {
 
 /// Exception to a requirement
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct ComplianceException {
     /// Requirement ID
     pub requirement: String,
@@ -235,6 +356,7 @@ pub struct ComplianceException {
 
 /// Reference to a schema
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct SchemaReference {
     /// Schema ID
     pub id: String,
@@ -257,9 +379,7 @@ impl RsrConfig {
             message: e.to_string(),
         })?;
 
-        serde_yaml::from_str(&content).map_err(|e| ConflowError::Yaml {
-            message: e.to_string(),
-        })
+        crate::utils::yaml::parse(&content, &path.display().to_string())
     }
 
     /// Load from project directory (looks for .rsr.yaml)
@@ -281,38 +401,21 @@ impl RsrConfig {
         Ok(())
     }
 
-    /// Check if a requirement should be skipped
-    pub fn should_skip(&self, requirement_id: &str) -> bool {
+    /// Check if a requirement is skipped by the skip list or an override
+    ///
+    /// Exceptions are not considered: they are checked as waivers and
+    /// reported.
+    pub fn is_skipped(&self, requirement_id: &str) -> bool {
         // Check direct skip list
         if self.requirements.skip.contains(&requirement_id.to_string()) {
             return true;
         }
 
         // Check overrides
-        if let Some(override_cfg) = self.requirements.overrides.get(requirement_id) {
-            if override_cfg.skip {
-                return true;
-            }
-        }
-
-        // Check exceptions
-        for exception in &self.compliance.exceptions {
-            if exception.requirement == requirement_id {
-                // Check if exception is still valid
-                if let Some(ref expires) = exception.expires {
-                    if let Ok(expiry) = chrono::DateTime::parse_from_rfc3339(expires) {
-                        if expiry > chrono::Utc::now() {
-                            return true;
-                        }
-                    }
-                } else {
-                    // No expiry, always valid
-                    return true;
-                }
-            }
-        }
-
-        false
+        self.requirements
+            .overrides
+            .get(requirement_id)
+            .is_some_and(|o| o.skip)
     }
 
     /// Get class override for a requirement
@@ -339,9 +442,7 @@ impl RsrConfig {
             })?;
 
             let imported: Vec<RsrRequirement> =
-                serde_yaml::from_str(&content).map_err(|e| ConflowError::Yaml {
-                    message: e.to_string(),
-                })?;
+                crate::utils::yaml::parse(&content, &full_path.display().to_string())?;
 
             requirements.extend(imported);
         }
@@ -359,12 +460,41 @@ impl Default for RsrConfig {
             integrations: IntegrationsConfig::default(),
             compliance: ComplianceConfig::default(),
             schemas: Vec::new(),
+            profiles: Vec::new(),
+            templates: Vec::new(),
+            hooks: Vec::new(),
         }
     }
 }
 
 /// Generate a default .rsr.yaml configuration
 pub fn generate_default_config(project_name: &str) -> String {
+    generate_project_config(project_name, None, None)
+}
+
+/// Generate a .rsr.yaml configuration for a project of a tier, checked in CI
+///
+/// The tier sets the target level: basic for tier 1, good for tier 2 and
+/// excellent above.
+pub fn generate_project_config(
+    project_name: &str,
+    tier: Option<u8>,
+    ci: Option<CiProvider>,
+) -> String {
+    let tier_line = match tier {
+        Some(tier) => format!("tier: {}", tier),
+        None => "# tier: 2".to_string(),
+    };
+    let provider_line = match ci.and_then(|ci| serde_yaml::to_string(&ci).ok()) {
+        Some(provider) => format!("provider: {}", provider.trim()),
+        None => "# provider: github".to_string(),
+    };
+    let target_level = match tier {
+        Some(1) => "basic",
+        Some(3..) => "excellent",
+        _ => "good",
+    };
+
     format!(
         r#"# RSR Configuration
 # See: https://rsr.dev/docs/config
@@ -374,7 +504,7 @@ version: "1"
 project:
   name: "{}"
   # description: "Project description"
-  # tier: 2  # 1-4, higher is more strict
+  {}  # 1-4, higher is more strict
 
 requirements:
   # Skip specific requirements
@@ -397,21 +527,67 @@ integrations:
     # run_before_check: false
 
   ci:
-    # provider: github
+    {}
     fail_on_noncompliant: false
     generate_badges: true
 
+  badges:
+    output: .rsr/badges
+    style: flat
+    # categories: [security, docs, testing]
+    # thresholds:
+    #   - {{ min_score: 0.9, color: brightgreen }}
+    #   - {{ min_score: 0.0, color: red }}
+
+# Inherit your organization's baseline
+profiles: []
+  # - profile: acme/backend-service@2.1
+  #   git: https://github.com/acme/rsr-profiles.git
+  #   path: backend-service
+
+# Template repositories for 'conflow template'
+templates: []
+  # - git: https://github.com/acme/conflow-templates.git
+  #   version: "1.4"
+
+# Commands or webhooks run after compliance checks
+hooks: []
+  # - name: security-alert
+  #   on: [check_compliance]
+  #   tags: [security]
+  #   when: failure
+  #   webhook:
+  #     url: https://alerts.example.com/rsr
+  #     retries: 3
+
 compliance:
-  target_level: good
+  target_level: {}
   track_history: true
   # history_file: .rsr/history.json
 
+  # Check on a cron schedule (UTC) under `conflow serve --project .`
+  # schedule: "0 * * * *"
+
+  # Score formula: weighted, ratio or strict
+  # score: weighted
+  # weights:
+  #   RSR-CONFIG-002: 3.0
+
+  # Custom levels; the highest one reached is reported
+  levels: []
+    # - name: "L1 Initial"
+    # - name: "L2 Managed"
+    #   min_score: 0.6
+    # - name: "L3 Defined"
+    #   min_score: 0.9
+    #   requires: [RSR-CONFIG-002]
+
   exceptions: []
     # - requirement: RSR-CONFIG-003
     #   reason: "Single environment project"
     #   expires: "2025-12-31T00:00:00Z"
 "#,
-        project_name
+        project_name, tier_line, provider_line, target_level
     )
 }
 
@@ -450,35 +626,8 @@ requirements:
 
         assert_eq!(config.project.name, Some("test-project".into()));
         assert_eq!(config.project.tier, Some(2));
-        assert!(config.should_skip("RSR-CONFIG-003"));
-        assert!(!config.should_skip("RSR-CONFIG-001"));
-    }
-
-    #[test]
-    fn test_exception_handling() {
-        let config = RsrConfig {
-            compliance: ComplianceConfig {
-                exceptions: vec![
-                    ComplianceException {
-                        requirement: "RSR-001".into(),
-                        reason: "Test".into(),
-                        expires: None,
-                        approved_by: None,
-                    },
-                    ComplianceException {
-                        requirement: "RSR-002".into(),
-                        reason: "Test".into(),
-                        expires: Some("2020-01-01T00:00:00Z".into()), // Expired
-                        approved_by: None,
-                    },
-                ],
-                ..Default::default()
-            },
-            ..Default::default()
-        };
-
-        assert!(config.should_skip("RSR-001")); // No expiry
-        assert!(!config.should_skip("RSR-002")); // Expired
+        assert!(config.is_skipped("RSR-CONFIG-003"));
+        assert!(!config.is_skipped("RSR-CONFIG-001"));
     }
 
     #[test]
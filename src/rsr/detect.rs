@@ -0,0 +1,400 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Schema detection
+//!
+//! Fingerprints a parsed YAML or JSON document by its distinctive keys
+//! (`apiVersion` and `kind`, `services.*.image`, `jobs.*.runs-on`, ...) and
+//! picks the registry schema it most likely follows, so a CUE `vet` stage
+//! without `schemas:` can still check its inputs against something.
+
+use serde::Serialize;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+use crate::cache::{DataFormat, ParseCache};
+use crate::errors::ConflowError;
+use crate::rsr::RsrSchemaRegistry;
+
+/// A schema picked for a document
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SchemaMatch {
+    /// Registry schema id
+    pub schema: String,
+    /// Definition to vet the document against (`cue vet -d`), if the
+    /// schema doesn't constrain top-level fields itself
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub definition: Option<String>,
+    /// How strongly the document matched; higher is better
+    pub score: u32,
+    /// Keys the match rests on, as dotted paths
+    pub evidence: Vec<String>,
+}
+
+/// What marks a document as following a schema
+struct Fingerprint {
+    schema: &'static str,
+    definition: Option<&'static str>,
+    /// `apiVersion` prefix and `kind`, for Kubernetes-style resources
+    api_version: Option<&'static str>,
+    kind: Option<&'static str>,
+    /// Dotted key paths that must all exist; `*` matches any key or index
+    keys: &'static [&'static str],
+    /// Key paths that make the match more likely
+    hints: &'static [&'static str],
+    /// Whether the keys are looked up in the items of a top-level list
+    list: bool,
+}
+
+impl Fingerprint {
+    const fn new(schema: &'static str, keys: &'static [&'static str]) -> Self {
+        Self {
+            schema,
+            definition: None,
+            api_version: None,
+            kind: None,
+            keys,
+            hints: &[],
+            list: false,
+        }
+    }
+
+    const fn hints(mut self, hints: &'static [&'static str]) -> Self {
+        self.hints = hints;
+        self
+    }
+
+    const fn resource(mut self, api_version: &'static str, kind: Option<&'static str>) -> Self {
+        self.api_version = Some(api_version);
+        self.kind = kind;
+        self
+    }
+
+    const fn definition(mut self, definition: &'static str) -> Self {
+        self.definition = Some(definition);
+        self
+    }
+
+    const fn list(mut self) -> Self {
+        self.list = true;
+        self
+    }
+
+    /// Score a document, `None` when it doesn't match
+    fn score(&self, document: &Value) -> Option<(u32, Vec<String>)> {
+        let mut evidence = Vec::new();
+        let mut score = 0;
+
+        if let Some(prefix) = self.api_version {
+            let api_version = document.get("apiVersion")?.as_str()?;
+            if !api_version.starts_with(prefix) {
+                return None;
+            }
+            evidence.push(format!("apiVersion={}", api_version));
+            score += 20;
+        }
+        if let Some(kind) = self.kind {
+            if document.get("kind")?.as_str()? != kind {
+                return None;
+            }
+            evidence.push(format!("kind={}", kind));
+            score += 20;
+        }
+
+        let items: Vec<&Value> = match (self.list, document) {
+            (true, Value::Array(items)) if !items.is_empty() => items.iter().collect(),
+            (true, _) => return None,
+            (false, _) => vec![document],
+        };
+        for key in self.keys {
+            if !items.iter().any(|item| exists(item, key)) {
+                return None;
+            }
+            evidence.push(key.to_string());
+            score += 10;
+        }
+        for key in self.hints {
+            if items.iter().any(|item| exists(item, key)) {
+                evidence.push(key.to_string());
+                score += 3;
+            }
+        }
+        Some((score, evidence))
+    }
+}
+
+/// Known fingerprints; on equal scores the earlier one wins
+const FINGERPRINTS: &[Fingerprint] = &[
+    Fingerprint::new("argocd:base", &["spec"]).resource("argoproj.io/", None),
+    Fingerprint::new("flux:base", &["spec"]).resource("kustomize.toolkit.fluxcd.io/", None),
+    Fingerprint::new("flux:base", &["spec"]).resource("helm.toolkit.fluxcd.io/", None),
+    Fingerprint::new("flux:base", &["spec"]).resource("source.toolkit.fluxcd.io/", None),
+    Fingerprint::new("k8s:base", &["spec.template.spec.containers"])
+        .resource("apps/v1", Some("Deployment"))
+        .definition("#Deployment"),
+    Fingerprint::new("prometheus:rules", &["groups.*.rules"]).hints(&["groups.*.name"]),
+    Fingerprint::new("alertmanager:config", &["route", "receivers"]).hints(&[
+        "global",
+        "inhibit_rules",
+        "route.routes",
+    ]),
+    Fingerprint::new("grafana:dashboard", &["panels", "schemaVersion"]).hints(&[
+        "templating",
+        "title",
+        "uid",
+    ]),
+    Fingerprint::new("github:workflow", &["on", "jobs.*.runs-on"]).hints(&[
+        "name",
+        "permissions",
+        "jobs.*.steps",
+    ]),
+    Fingerprint::new("circleci:config", &["version", "jobs"]).hints(&[
+        "workflows",
+        "orbs",
+        "executors",
+        "jobs.*.steps",
+        "jobs.*.docker",
+    ]),
+    Fingerprint::new("azure:pipelines", &["stages.*.stage"]).hints(&["trigger", "pool"]),
+    Fingerprint::new("azure:pipelines", &["jobs.*.job"]).hints(&["trigger", "pool"]),
+    Fingerprint::new("azure:pipelines", &["steps", "pool"]).hints(&["trigger", "variables"]),
+    Fingerprint::new("compose:spec", &["services.*.image"]).hints(&[
+        "version",
+        "volumes",
+        "networks",
+        "services.*.ports",
+    ]),
+    Fingerprint::new("compose:spec", &["services.*.build"]).hints(&[
+        "version",
+        "volumes",
+        "networks",
+        "services.*.ports",
+    ]),
+    Fingerprint::new("nomad:job", &["Job.TaskGroups"]).hints(&["Job.ID", "Job.Datacenters"]),
+    Fingerprint::new("ansible:playbook", &["hosts"])
+        .hints(&["tasks", "roles", "become", "handlers"])
+        .list(),
+    Fingerprint::new("ansible:playbook", &["import_playbook"]).list(),
+    Fingerprint::new("ansible:inventory", &["all.children"]).hints(&["all.hosts", "all.vars"]),
+    Fingerprint::new("ansible:inventory", &["all.hosts"]).hints(&["all.vars"]),
+    Fingerprint::new("rsr:pipeline", &["name", "stages.*.tool"]).hints(&["version", "cache"]),
+];
+
+/// Whether a dotted key path exists in a value
+fn exists(value: &Value, path: &str) -> bool {
+    let segments: Vec<&str> = path.split('.').collect();
+    lookup(value, &segments)
+}
+
+fn lookup(value: &Value, segments: &[&str]) -> bool {
+    let Some((segment, rest)) = segments.split_first() else {
+        return true;
+    };
+    match (value, *segment) {
+        (Value::Object(map), "*") => map.values().any(|child| lookup(child, rest)),
+        (Value::Array(items), "*") => items.iter().any(|child| lookup(child, rest)),
+        (Value::Object(map), key) => map.get(key).is_some_and(|child| lookup(child, rest)),
+        (Value::Array(items), index) => index
+            .parse::<usize>()
+            .ok()
+            .and_then(|i| items.get(i))
+            .is_some_and(|child| lookup(child, rest)),
+        _ => false,
+    }
+}
+
+/// The schema a document most likely follows
+pub fn detect_schema(document: &Value) -> Option<SchemaMatch> {
+    let mut best: Option<SchemaMatch> = None;
+    for fingerprint in FINGERPRINTS {
+        let Some((score, evidence)) = fingerprint.score(document) else {
+            continue;
+        };
+        if best.as_ref().is_some_and(|b| b.score >= score) {
+            continue;
+        }
+        best = Some(SchemaMatch {
+            schema: fingerprint.schema.into(),
+            definition: fingerprint.definition.map(String::from),
+            score,
+            evidence,
+        });
+    }
+    best
+}
+
+/// Write a registry schema to `.conflow/schemas` under `root` for `cue vet`,
+/// returning its path
+pub fn schema_file(root: &Path, schema: &str) -> Result<PathBuf, ConflowError> {
+    let file = format!("{}.cue", schema.replace(':', "-"));
+    let path = root.join(".conflow").join("schemas").join(file);
+    RsrSchemaRegistry::new().write_to_file(schema, &path)?;
+    Ok(path)
+}
+
+/// The schema a JSON or YAML file most likely follows
+///
+/// Every document of a YAML stream has to match the same schema; files of
+/// other formats match none.
+pub fn detect_file(path: &Path) -> Result<Option<SchemaMatch>, ConflowError> {
+    if DataFormat::from_path(path).is_none() {
+        return Ok(None);
+    }
+    let content = std::fs::read_to_string(path).map_err(|e| ConflowError::FileReadError {
+        path: path.to_path_buf(),
+        error: e.to_string(),
+    })?;
+    let documents = ParseCache::global().documents(path, &content)?;
+
+    let mut found: Option<SchemaMatch> = None;
+    for document in documents.iter().filter(|d| !d.is_null()) {
+        let Some(matched) = detect_schema(document) else {
+            return Ok(None);
+        };
+        match &found {
+            Some(first)
+                if first.schema != matched.schema || first.definition != matched.definition =>
+            {
+                return Ok(None)
+            }
+            Some(_) => {}
+            None => found = Some(matched),
+        }
+    }
+    Ok(found)
+}
+
+/// The one schema all of some files follow
+///
+/// `Ok(None)` when none of them matches a schema, or some aren't JSON or
+/// YAML (CUE files among the inputs bring their own constraints); an error
+/// naming the files when they match different ones, or only some match.
+pub fn detect_common(files: &[PathBuf]) -> Result<Option<SchemaMatch>, String> {
+    if files.iter().any(|f| DataFormat::from_path(f).is_none()) {
+        return Ok(None);
+    }
+    let mut matches = Vec::new();
+    for file in files {
+        let matched = detect_file(file).map_err(|e| e.to_string())?;
+        matches.push((file, matched));
+    }
+    if matches.iter().all(|(_, m)| m.is_none()) {
+        return Ok(None);
+    }
+
+    let describe = |m: &Option<SchemaMatch>| match m {
+        Some(m) => m.schema.clone(),
+        None => "no known schema".into(),
+    };
+    let (first_file, first) = &matches[0];
+    for (file, matched) in &matches[1..] {
+        let same = match (first, matched) {
+            (Some(a), Some(b)) => a.schema == b.schema && a.definition == b.definition,
+            _ => false,
+        };
+        if !same {
+            return Err(format!(
+                "{} matches {} but {} matches {}",
+                first_file.display(),
+                describe(first),
+                file.display(),
+                describe(matched)
+            ));
+        }
+    }
+    match first {
+        Some(first) => Ok(Some(first.clone())),
+        None => Err(format!("{} matches no known schema", first_file.display())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schema(document: Value) -> Option<String> {
+        detect_schema(&document).map(|m| m.schema)
+    }
+
+    #[test]
+    fn test_fingerprints_name_registry_schemas() {
+        let registry = RsrSchemaRegistry::new();
+        for fingerprint in FINGERPRINTS {
+            assert!(
+                registry.get(fingerprint.schema).is_some(),
+                "{} is not in the registry",
+                fingerprint.schema
+            );
+        }
+    }
+
+    #[test]
+    fn test_detect_schema() {
+        let deployment = json!({
+            "apiVersion": "apps/v1",
+            "kind": "Deployment",
+            "metadata": {"name": "web"},
+            "spec": {"template": {"spec": {"containers": [{"name": "web"}]}}},
+        });
+        let matched = detect_schema(&deployment).unwrap();
+        assert_eq!(matched.schema, "k8s:base");
+        assert_eq!(matched.definition.as_deref(), Some("#Deployment"));
+
+        let application =
+            json!({"apiVersion": "argoproj.io/v1alpha1", "kind": "Application", "spec": {}});
+        assert_eq!(schema(application).as_deref(), Some("argocd:base"));
+
+        let workflow =
+            json!({"on": "push", "jobs": {"test": {"runs-on": "ubuntu-latest", "steps": []}}});
+        assert_eq!(schema(workflow).as_deref(), Some("github:workflow"));
+
+        let circleci = json!({"version": 2.1, "jobs": {"test": {"docker": [], "steps": []}}});
+        assert_eq!(schema(circleci).as_deref(), Some("circleci:config"));
+
+        let compose = json!({"services": {"web": {"image": "nginx:1.25", "ports": ["80:80"]}}});
+        assert_eq!(schema(compose).as_deref(), Some("compose:spec"));
+
+        let playbook = json!([{"hosts": "all", "tasks": [{"name": "ping", "ping": null}]}]);
+        assert_eq!(schema(playbook).as_deref(), Some("ansible:playbook"));
+
+        let rules = json!({"groups": [{"name": "api", "rules": []}]});
+        assert_eq!(schema(rules).as_deref(), Some("prometheus:rules"));
+
+        assert_eq!(schema(json!({"name": "app", "replicas": 3})), None);
+        assert_eq!(
+            schema(json!({"apiVersion": "v1", "kind": "ConfigMap"})),
+            None
+        );
+    }
+
+    #[test]
+    fn test_detect_common() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let write = |name: &str, content: &str| {
+            let path = temp.path().join(name);
+            std::fs::write(&path, content).unwrap();
+            path
+        };
+        let web = write("web.yaml", "services:\n  web:\n    image: nginx:1.25\n");
+        let db = write("db.yaml", "services:\n  db:\n    build: ./db\n");
+        let ci = write(
+            "ci.yaml",
+            "on: push\njobs:\n  test:\n    runs-on: ubuntu-latest\n",
+        );
+        let plain = write("app.json", "{\"name\": \"app\"}");
+
+        let matched = detect_common(&[web.clone(), db]).unwrap().unwrap();
+        assert_eq!(matched.schema, "compose:spec");
+        assert_eq!(detect_common(std::slice::from_ref(&plain)).unwrap(), None);
+
+        let error = detect_common(&[web.clone(), ci]).unwrap_err();
+        assert!(error.contains("matches compose:spec but"));
+        assert!(error.contains("matches github:workflow"));
+        assert!(detect_common(&[web.clone(), plain])
+            .unwrap_err()
+            .contains("no known schema"));
+        let schema = write("schema.cue", "#App: {name: string}\n");
+        assert_eq!(detect_common(&[schema, web]).unwrap(), None);
+    }
+}
@@ -10,8 +10,9 @@ use std::path::Path;
 
 use serde::{Deserialize, Serialize};
 
-use super::compliance::{ComplianceLevel, ComplianceReport, RequirementResult};
+use super::compliance::{ComplianceLevel, ComplianceReport};
 use crate::ConflowError;
+use crate::utils::date::civil_from_days;
 
 /// Diff between two compliance reports
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -110,10 +111,30 @@ pub struct HistoryEntry {
     pub timestamp: String,
     pub level: ComplianceLevel,
     pub score: f64,
+    #[serde(serialize_with = "crate::report::sorted")]
     pub requirements: HashMap<String, bool>,
     pub git_commit: Option<String>,
 }
 
+impl HistoryEntry {
+    /// Snapshot a compliance report
+    pub fn from_report(report: &ComplianceReport, git_commit: Option<String>) -> Self {
+        let requirements: HashMap<String, bool> = report
+            .requirements
+            .iter()
+            .map(|r| (r.requirement_id.clone(), r.met))
+            .collect();
+
+        Self {
+            timestamp: crate::report::timestamp(),
+            level: report.level,
+            score: report.score,
+            requirements,
+            git_commit,
+        }
+    }
+}
+
 impl ComplianceHistory {
     /// Create new empty history
     pub fn new() -> Self {
@@ -156,21 +177,7 @@ impl ComplianceHistory {
 
     /// Add a new entry from a compliance report
     pub fn add_entry(&mut self, report: &ComplianceReport, git_commit: Option<String>) {
-        let requirements: HashMap<String, bool> = report
-            .requirements
-            .iter()
-            .map(|r| (r.requirement_id.clone(), r.met))
-            .collect();
-
-        let entry = HistoryEntry {
-            timestamp: chrono::Utc::now().to_rfc3339(),
-            level: report.level,
-            score: report.score,
-            requirements,
-            git_commit,
-        };
-
-        self.entries.insert(0, entry);
+        self.entries.insert(0, HistoryEntry::from_report(report, git_commit));
 
         // Keep only last 100 entries
         if self.entries.len() > 100 {
@@ -385,10 +392,35 @@ impl DiffReporter {
     }
 }
 
+/// Current UTC time as an RFC 3339 timestamp
+pub(crate) fn now_rfc3339() -> String {
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    rfc3339(secs)
+}
+
+/// A Unix time as an RFC 3339 timestamp in UTC
+pub(crate) fn rfc3339(secs: i64) -> String {
+    let (days, rem) = (secs.div_euclid(86_400), secs.rem_euclid(86_400));
+    let (year, month, day) = civil_from_days(days);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::rsr::compliance::ComplianceStats;
+    use crate::rsr::compliance::{ComplianceStats, RequirementResult};
 
     fn sample_report(level: ComplianceLevel, score: f64, requirements: Vec<(&str, bool)>) -> ComplianceReport {
         ComplianceReport {
@@ -401,9 +433,14 @@ mod tests {
                     met,
                     details: vec![],
                     remediation: None,
+                    blocked_by: vec![],
+                    waived: false,
+                    owners: Vec::new(),
                 })
                 .collect(),
             stats: ComplianceStats::default(),
+            custom_level: None,
+            waivers: vec![],
         }
     }
 
@@ -0,0 +1,814 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Documentation requirements
+//!
+//! Built-in requirements on the quality of a project's documents, beyond
+//! whether they exist: the README has the sections readers look for, the
+//! license is declared with valid SPDX identifiers, SECURITY.md says where
+//! to report a vulnerability, and the CHANGELOG follows Keep a Changelog.
+//! Each comes with a template to start from (`conflow template render`).
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use super::requirements::{
+    RemediationOptions, RemediationTemplate, RsrRequirement, RsrRequirementClass, ValidationChecks,
+};
+use super::spdx;
+use crate::utils::date;
+use crate::i18n::{tr, tr_with};
+
+/// Sections a README needs by default; `|` separates names that count alike
+pub const DEFAULT_SECTIONS: &[&str] = &[
+    "Installation|Install|Getting started|Quick start",
+    "Usage|Example",
+    "License|Licensing",
+];
+
+/// Keep a Changelog change types
+pub const CHANGE_TYPES: &[&str] = &[
+    "Added",
+    "Changed",
+    "Deprecated",
+    "Removed",
+    "Fixed",
+    "Security",
+];
+
+/// Domains reserved for examples, which no report reaches
+const PLACEHOLDER_DOMAINS: &[&str] = &["example.com", "example.org", "example.net"];
+
+/// A documentation check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DocsControl {
+    /// The README has the usual sections
+    ReadmeSections,
+    /// The license is declared with valid SPDX identifiers
+    LicenseIdentifier,
+    /// SECURITY.md names a valid contact for vulnerability reports
+    SecurityContact,
+    /// The CHANGELOG follows Keep a Changelog
+    ChangelogFormat,
+}
+
+impl DocsControl {
+    /// Every control, in requirement ID order
+    pub const ALL: [Self; 4] = [
+        Self::ReadmeSections,
+        Self::LicenseIdentifier,
+        Self::SecurityContact,
+        Self::ChangelogFormat,
+    ];
+
+    /// Requirement ID, e.g. `RSR-DOCS-001`
+    pub fn id(self) -> String {
+        let index = Self::ALL.iter().position(|c| *c == self).unwrap_or(0);
+        format!("RSR-DOCS-{:03}", index + 1)
+    }
+
+    /// The control a requirement ID stands for
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.id() == id)
+    }
+
+    /// Kebab-case name, as in `.rsr.yaml`
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::ReadmeSections => "readme-sections",
+            Self::LicenseIdentifier => "license-identifier",
+            Self::SecurityContact => "security-contact",
+            Self::ChangelogFormat => "changelog-format",
+        }
+    }
+
+    /// Files checked when the check names none, in order of preference
+    pub fn candidates(self) -> &'static [&'static str] {
+        match self {
+            Self::ReadmeSections => &[
+                "README.md",
+                "README.adoc",
+                "README.rst",
+                "README.txt",
+                "README",
+            ],
+            Self::LicenseIdentifier => &[
+                "LICENSE",
+                "LICENSE.md",
+                "LICENSE.txt",
+                "COPYING",
+                "COPYING.md",
+                "LICENSES",
+            ],
+            Self::SecurityContact => &["SECURITY.md", ".github/SECURITY.md", "docs/SECURITY.md"],
+            Self::ChangelogFormat => &["CHANGELOG.md"],
+        }
+    }
+
+    /// Built-in template that writes the document
+    pub fn template(self) -> &'static str {
+        match self {
+            Self::ReadmeSections => "readme",
+            Self::LicenseIdentifier => "license-mit",
+            Self::SecurityContact => "security-policy",
+            Self::ChangelogFormat => "changelog",
+        }
+    }
+
+    fn class(self) -> RsrRequirementClass {
+        match self {
+            Self::LicenseIdentifier => RsrRequirementClass::Mandatory,
+            Self::ChangelogFormat => RsrRequirementClass::Advisory,
+            _ => RsrRequirementClass::Preferential,
+        }
+    }
+
+    /// Whether the remediator can fix the document: it can add a README's
+    /// missing sections and start a CHANGELOG, but not pick a license or a
+    /// security contact
+    fn auto_fix(self) -> bool {
+        matches!(self, Self::ReadmeSections | Self::ChangelogFormat)
+    }
+
+    /// The requirement checking this control
+    pub fn requirement(self) -> RsrRequirement {
+        let key = |field: &str| format!("rsr.docs.{}.{}", self.as_str(), field);
+        RsrRequirement {
+            id: self.id(),
+            name: tr(&key("name")),
+            class: self.class(),
+            description: tr(&key("description")),
+            validation: ValidationChecks {
+                file_exists: vec![],
+                file_absent: vec![],
+                patterns: vec![],
+                cue_validate: vec![],
+                conflow_valid: false,
+                shell_check: None,
+                manifests: vec![],
+                docs: vec![DocsCheck {
+                    control: self,
+                    file: None,
+                    sections: vec![],
+                }],
+                release: vec![],
+            },
+            remediation: RemediationOptions {
+                auto_fix: self.auto_fix(),
+                templates: vec![RemediationTemplate {
+                    name: self.template().into(),
+                    description: tr(&key("template")),
+                    conflow_template: Some(self.template().into()),
+                    generates: vec![PathBuf::from(self.candidates()[0])],
+                }],
+                manual_steps: vec![tr(&key("step"))],
+                docs_url: Some(format!(
+                    "https://rsr.dev/requirements/{}",
+                    self.id().trim_start_matches("RSR-").to_lowercase()
+                )),
+            },
+            related: vec![],
+            requires: vec![],
+            tags: vec!["docs".into()],
+        }
+    }
+}
+
+/// Built-in documentation requirements
+pub fn builtin_docs_requirements() -> Vec<RsrRequirement> {
+    DocsControl::ALL
+        .into_iter()
+        .map(DocsControl::requirement)
+        .collect()
+}
+
+/// Check of a project document
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DocsCheck {
+    /// What the document must satisfy
+    pub control: DocsControl,
+
+    /// Document to check, relative to the project root (default: the first
+    /// of the usual names that exists, e.g. README.md)
+    #[serde(default)]
+    pub file: Option<PathBuf>,
+
+    /// Sections the README needs, `|` separating names that count alike
+    /// (default: installation, usage and license)
+    #[serde(default)]
+    pub sections: Vec<String>,
+}
+
+/// A problem with a document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    /// Document, relative to the project root; empty for Git history
+    pub file: PathBuf,
+    /// 1-based line, or 0 for the document as a whole
+    pub line: usize,
+    pub message: String,
+}
+
+impl std::fmt::Display for Finding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.line {
+            _ if self.file.as_os_str().is_empty() => write!(f, "{}", self.message),
+            0 => write!(f, "{}: {}", self.file.display(), self.message),
+            line => write!(f, "{}:{}: {}", self.file.display(), line, self.message),
+        }
+    }
+}
+
+impl DocsCheck {
+    /// The document checked, relative to the project root, if it exists
+    pub fn document(&self, project_root: &Path) -> Option<PathBuf> {
+        match self.file {
+            Some(ref file) => Some(file.clone()),
+            None => self
+                .control
+                .candidates()
+                .iter()
+                .map(PathBuf::from)
+                .find(|file| project_root.join(file).exists()),
+        }
+        .filter(|file| project_root.join(file).exists())
+    }
+
+    /// Sections the README needs
+    pub fn sections(&self) -> Vec<String> {
+        match self.sections.is_empty() {
+            true => DEFAULT_SECTIONS.iter().map(|s| s.to_string()).collect(),
+            false => self.sections.clone(),
+        }
+    }
+
+    /// Problems with the document in a project
+    pub fn findings(&self, project_root: &Path) -> Vec<Finding> {
+        let Some(file) = self.document(project_root) else {
+            let expected = match self.file {
+                Some(ref file) => file.display().to_string(),
+                None => self.control.candidates()[0].to_string(),
+            };
+            return vec![Finding {
+                file: PathBuf::from(&expected),
+                line: 0,
+                message: tr_with("rsr.docs.finding.missing-file", &[("file", &expected)]),
+            }];
+        };
+        let path = project_root.join(&file);
+        let content = match path.is_dir() {
+            // A REUSE `LICENSES/` directory holds one text per license
+            true => String::new(),
+            false => std::fs::read_to_string(&path).unwrap_or_default(),
+        };
+
+        let issues = match self.control {
+            DocsControl::ReadmeSections => check_readme(&content, &self.sections()),
+            DocsControl::LicenseIdentifier => check_license(project_root, &path, &content),
+            DocsControl::SecurityContact => check_security(&content),
+            DocsControl::ChangelogFormat => check_changelog(&content),
+        };
+        issues
+            .into_iter()
+            .map(|(line, message)| Finding {
+                file: file.clone(),
+                line,
+                message,
+            })
+            .collect()
+    }
+}
+
+/// A heading: 1-based line, level and text
+type Heading = (usize, usize, String);
+
+/// Headings of a Markdown, AsciiDoc or reStructuredText document, outside
+/// code blocks
+pub fn headings(content: &str) -> Vec<Heading> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut headings = Vec::new();
+    let mut fenced = false;
+    for (i, line) in lines.iter().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            fenced = !fenced;
+            continue;
+        }
+        if fenced {
+            continue;
+        }
+        for marker in ['#', '='] {
+            let level = trimmed.chars().take_while(|c| *c == marker).count();
+            let text = &trimmed[level..];
+            if (1..=6).contains(&level) && text.starts_with(' ') {
+                let text = text.trim().trim_end_matches(marker).trim();
+                headings.push((i + 1, level, text.to_string()));
+            }
+        }
+        // Setext and reStructuredText headings are underlined
+        let underline = lines.get(i + 1).map(|l| l.trim()).unwrap_or_default();
+        let underlined = underline.len() >= 3
+            && ['=', '-', '~', '^']
+                .iter()
+                .any(|c| underline.chars().all(|u| u == *c));
+        if underlined && !trimmed.is_empty() && !trimmed.starts_with(['#', '=', '-', '*', '|']) {
+            let level = if underline.starts_with('=') { 1 } else { 2 };
+            headings.push((i + 1, level, trimmed.to_string()));
+        }
+    }
+    headings
+}
+
+/// Sections missing from a README, by their first name
+pub fn missing_sections(content: &str, sections: &[String]) -> Vec<String> {
+    let titles: Vec<String> = headings(content)
+        .into_iter()
+        .map(|(_, _, text)| text.to_lowercase())
+        .collect();
+    sections
+        .iter()
+        .filter(|section| {
+            !section.split('|').any(|name| {
+                let name = name.trim().to_lowercase();
+                titles.iter().any(|title| title.contains(&name))
+            })
+        })
+        .map(|section| {
+            section
+                .split('|')
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .to_string()
+        })
+        .collect()
+}
+
+/// An empty section to append to a README, in the document's markup
+pub fn section_stub(file: &Path, name: &str) -> String {
+    match file.extension().and_then(|e| e.to_str()) {
+        Some("adoc") => format!("\n== {}\n\nTODO\n", name),
+        Some("rst") => format!("\n{}\n{}\n\nTODO\n", name, "-".repeat(name.len())),
+        _ => format!("\n## {}\n\nTODO\n", name),
+    }
+}
+
+/// Sections missing from a README
+fn check_readme(content: &str, sections: &[String]) -> Vec<(usize, String)> {
+    missing_sections(content, sections)
+        .into_iter()
+        .map(|section| {
+            let message = tr_with("rsr.docs.finding.missing-section", &[("section", &section)]);
+            (0, message)
+        })
+        .collect()
+}
+
+/// SPDX license expressions a project declares, with the line of each in
+/// the license file (0 when declared elsewhere)
+pub fn declared_licenses(
+    project_root: &Path,
+    license: &Path,
+    content: &str,
+) -> Vec<(usize, String)> {
+    let mut declared: Vec<(usize, String)> = content
+        .lines()
+        .enumerate()
+        .filter_map(|(i, line)| {
+            let (_, expression) = line.split_once("SPDX-License-Identifier:")?;
+            Some((
+                i + 1,
+                expression.trim().trim_end_matches("*/").trim().to_string(),
+            ))
+        })
+        .collect();
+
+    let manifest = |file: &str| std::fs::read_to_string(project_root.join(file)).ok();
+    if let Some(cargo) = manifest("Cargo.toml").and_then(|c| c.parse::<toml::Table>().ok()) {
+        let package = cargo
+            .get("package")
+            .or_else(|| cargo.get("workspace").and_then(|w| w.get("package")));
+        if let Some(expression) = package
+            .and_then(|p| p.get("license"))
+            .and_then(|l| l.as_str())
+        {
+            declared.push((0, expression.to_string()));
+        }
+    }
+    if let Some(pyproject) = manifest("pyproject.toml").and_then(|c| c.parse::<toml::Table>().ok())
+    {
+        let license = pyproject.get("project").and_then(|p| p.get("license"));
+        if let Some(expression) = license.and_then(|l| l.as_str()) {
+            declared.push((0, expression.to_string()));
+        }
+    }
+    if let Some(package) =
+        manifest("package.json").and_then(|c| serde_json::from_str::<serde_json::Value>(&c).ok())
+    {
+        if let Some(expression) = package["license"].as_str() {
+            declared.push((0, expression.to_string()));
+        }
+    }
+
+    // REUSE keeps each license in `LICENSES/<id>.txt`
+    let reuse = match license.is_dir() {
+        true => license.to_path_buf(),
+        false => project_root.join("LICENSES"),
+    };
+    if let Ok(entries) = std::fs::read_dir(reuse) {
+        let mut ids: Vec<String> = entries
+            .filter_map(|e| {
+                let path = e.ok()?.path();
+                Some(path.file_stem()?.to_string_lossy().into_owned())
+            })
+            .collect();
+        ids.sort();
+        declared.extend(ids.into_iter().map(|id| (0, id)));
+    }
+
+    declared.dedup_by(|a, b| a.1 == b.1);
+    declared
+}
+
+/// Undeclared, unknown or malformed license identifiers
+fn check_license(project_root: &Path, path: &Path, content: &str) -> Vec<(usize, String)> {
+    let declared = declared_licenses(project_root, path, content);
+    if declared.is_empty() {
+        return vec![(0, tr("rsr.docs.finding.no-license-identifier"))];
+    }
+    let mut issues = Vec::new();
+    for (line, expression) in declared {
+        match parse_license_expression(&expression) {
+            Some(unknown) => issues.extend(unknown.into_iter().map(|license| {
+                let message = tr_with("rsr.docs.finding.unknown-license", &[("license", &license)]);
+                (line, message)
+            })),
+            None => issues.push((
+                line,
+                tr_with(
+                    "rsr.docs.finding.invalid-license-expression",
+                    &[("expression", &expression)],
+                ),
+            )),
+        }
+    }
+    issues
+}
+
+/// Check an SPDX license expression such as `MIT OR Apache-2.0`
+///
+/// Returns the identifiers that aren't known SPDX licenses or exceptions,
+/// or `None` when the expression is malformed.
+pub fn parse_license_expression(expression: &str) -> Option<Vec<String>> {
+    let spaced = expression.replace('(', " ( ").replace(')', " ) ");
+    let tokens: Vec<&str> = spaced.split_whitespace().collect();
+    let mut unknown = Vec::new();
+    let mut depth = 0usize;
+    // Whether a license (or a parenthesized expression) is expected next
+    let mut operand = true;
+    let mut i = 0;
+    while i < tokens.len() {
+        let token = tokens[i];
+        let upper = token.to_ascii_uppercase();
+        match (operand, token) {
+            (true, "(") => depth += 1,
+            (true, _) if matches!(upper.as_str(), "AND" | "OR" | "WITH" | ")") => return None,
+            (true, _) => {
+                if !known(spdx::LICENSES, token.trim_end_matches('+')) {
+                    unknown.push(token.to_string());
+                }
+                if tokens
+                    .get(i + 1)
+                    .is_some_and(|t| t.eq_ignore_ascii_case("WITH"))
+                {
+                    let exception = tokens.get(i + 2)?;
+                    if matches!(*exception, "(" | ")") {
+                        return None;
+                    }
+                    if !known(spdx::EXCEPTIONS, exception) {
+                        unknown.push(exception.to_string());
+                    }
+                    i += 2;
+                }
+                operand = false;
+            }
+            (false, ")") if depth > 0 => depth -= 1,
+            (false, _) if matches!(upper.as_str(), "AND" | "OR") => operand = true,
+            (false, _) => return None,
+        }
+        i += 1;
+    }
+    match operand || depth > 0 {
+        true => None,
+        false => Some(unknown),
+    }
+}
+
+/// Whether `id` is one of `list`, or a project's own `LicenseRef-`
+fn known(list: &[&str], id: &str) -> bool {
+    id.starts_with("LicenseRef-")
+        || id.starts_with("DocumentRef-")
+        || list.iter().any(|known| known.eq_ignore_ascii_case(id))
+}
+
+/// Invalid or placeholder email addresses, or no contact at all
+fn check_security(content: &str) -> Vec<(usize, String)> {
+    static CANDIDATE: OnceLock<Regex> = OnceLock::new();
+    static EMAIL: OnceLock<Regex> = OnceLock::new();
+    static URL: OnceLock<Regex> = OnceLock::new();
+    let candidate = CANDIDATE.get_or_init(|| {
+        Regex::new(r#"[^\s<>()\[\]"'`,;*]+@[^\s<>()\[\]"'`,;*]+"#).expect("valid regex")
+    });
+    let email = EMAIL.get_or_init(|| {
+        Regex::new(
+            r"^[A-Za-z0-9!#$%&'*+=?^_{|}~-]+(\.[A-Za-z0-9!#$%&'*+=?^_{|}~-]+)*@([A-Za-z0-9]([A-Za-z0-9-]*[A-Za-z0-9])?\.)+[A-Za-z]{2,}$",
+        )
+        .expect("valid regex")
+    });
+    let url = URL.get_or_init(|| {
+        Regex::new(r"https://[^\s<>()\[\]]+\.[^\s<>()\[\]]+").expect("valid regex")
+    });
+
+    let mut issues = Vec::new();
+    let mut contact = false;
+    for (i, line) in content.lines().enumerate() {
+        contact |= url.is_match(line);
+        for found in candidate.find_iter(line) {
+            let address = found.as_str().trim_start_matches("mailto:");
+            let address = address.trim_end_matches(['.', ':', '!', '?']);
+            // Git remotes and URLs with credentials aren't addresses
+            if address.contains(['/', ':']) {
+                continue;
+            }
+            if !email.is_match(address) {
+                let message = tr_with("rsr.docs.finding.invalid-email", &[("address", &address)]);
+                issues.push((i + 1, message));
+                continue;
+            }
+            let domain = address
+                .rsplit('@')
+                .next()
+                .unwrap_or_default()
+                .to_lowercase();
+            if PLACEHOLDER_DOMAINS
+                .iter()
+                .any(|d| domain == *d || domain.ends_with(&format!(".{}", d)))
+            {
+                let message = tr_with(
+                    "rsr.docs.finding.placeholder-email",
+                    &[("address", &address)],
+                );
+                issues.push((i + 1, message));
+                continue;
+            }
+            contact = true;
+        }
+    }
+    if !contact && issues.is_empty() {
+        issues.push((0, tr("rsr.docs.finding.no-contact")));
+    }
+    issues
+}
+
+/// Departures from Keep a Changelog
+fn check_changelog(content: &str) -> Vec<(usize, String)> {
+    static RELEASE: OnceLock<Regex> = OnceLock::new();
+    let release = RELEASE.get_or_init(|| {
+        Regex::new(
+            r"^\[(\d+\.\d+\.\d+(?:-[0-9A-Za-z.-]+)?(?:\+[0-9A-Za-z.-]+)?)\](?: - (\S+))?(?: \[YANKED\])?$",
+        )
+        .expect("valid regex")
+    });
+
+    let headings = headings(content);
+    let mut issues = Vec::new();
+    match headings.first() {
+        Some((_, 1, title)) if title.eq_ignore_ascii_case("changelog") => {}
+        Some((line, _, _)) => issues.push((*line, tr("rsr.docs.finding.changelog-title"))),
+        None => issues.push((1, tr("rsr.docs.finding.changelog-title"))),
+    }
+
+    // The previous section: its name and version, None for Unreleased
+    let mut previous: Option<(String, Option<Vec<u64>>)> = None;
+    let mut sections = 0;
+    for (line, level, text) in headings.iter().skip(1) {
+        match level {
+            2 => {
+                sections += 1;
+                let (name, version) = if text.eq_ignore_ascii_case("[unreleased]") {
+                    ("Unreleased".to_string(), None)
+                } else if let Some(caps) = release.captures(text) {
+                    let version = caps[1].to_string();
+                    match caps.get(2).map(|d| d.as_str()) {
+                        Some(date) if date::is_date(date) => {}
+                        date => {
+                            let date = date.unwrap_or_default();
+                            let message = tr_with(
+                                "rsr.docs.finding.changelog-date",
+                                &[("version", &version), ("date", &date)],
+                            );
+                            issues.push((*line, message));
+                        }
+                    }
+                    let number = version_number(&version);
+                    (version, Some(number))
+                } else {
+                    let message =
+                        tr_with("rsr.docs.finding.changelog-version", &[("heading", text)]);
+                    issues.push((*line, message));
+                    continue;
+                };
+
+                if let Some((ref before, ref before_version)) = previous {
+                    // Unreleased comes first, then releases newest first
+                    let in_order = match (before_version, &version) {
+                        (None, Some(_)) => true,
+                        (Some(before), Some(version)) => version < before,
+                        (_, None) => false,
+                    };
+                    if !in_order {
+                        let message = tr_with(
+                            "rsr.docs.finding.changelog-order",
+                            &[("version", &name), ("previous", before)],
+                        );
+                        issues.push((*line, message));
+                    }
+                }
+                previous = Some((name, version));
+            }
+            3 if !CHANGE_TYPES.iter().any(|t| t.eq_ignore_ascii_case(text)) => {
+                let message = tr_with(
+                    "rsr.docs.finding.changelog-change-type",
+                    &[("heading", text)],
+                );
+                issues.push((*line, message));
+            }
+            _ => {}
+        }
+    }
+    if sections == 0 {
+        issues.push((0, tr("rsr.docs.finding.changelog-empty")));
+    }
+    issues
+}
+
+/// Numeric parts of a version, `1.2.0-rc.1` giving [1, 2, 0]
+fn version_number(version: &str) -> Vec<u64> {
+    version
+        .split(['-', '+'])
+        .next()
+        .unwrap_or_default()
+        .split('.')
+        .map(|part| part.parse().unwrap_or(0))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn messages(issues: Vec<(usize, String)>) -> Vec<String> {
+        issues
+            .into_iter()
+            .map(|(line, message)| format!("{}: {}", line, message))
+            .collect()
+    }
+
+    #[test]
+    fn test_requirements() {
+        let requirements = builtin_docs_requirements();
+        assert_eq!(requirements.len(), 4);
+        assert_eq!(requirements[1].id, "RSR-DOCS-002");
+        assert_eq!(requirements[1].class, RsrRequirementClass::Mandatory);
+        assert!(!requirements[1].remediation.auto_fix);
+        assert_eq!(requirements[3].remediation.templates[0].name, "changelog");
+        assert_eq!(
+            DocsControl::from_id("RSR-DOCS-003"),
+            Some(DocsControl::SecurityContact)
+        );
+    }
+
+    #[test]
+    fn test_readme_sections() {
+        let readme = "# app\n\n## Getting Started\n\n```sh\n# Usage\n```\n\nExamples\n--------\n";
+        let sections: Vec<String> = DEFAULT_SECTIONS.iter().map(|s| s.to_string()).collect();
+        assert_eq!(
+            messages(check_readme(readme, &sections)),
+            ["0: no 'License' section"]
+        );
+        assert_eq!(
+            messages(check_readme("= app\n\n== Deploying\n", &["Deploy".into()])),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn test_license_expressions() {
+        assert_eq!(parse_license_expression("MIT OR Apache-2.0"), Some(vec![]));
+        assert_eq!(
+            parse_license_expression(
+                "(GPL-2.0-or-later WITH Classpath-exception-2.0) AND LicenseRef-acme"
+            ),
+            Some(vec![])
+        );
+        assert_eq!(
+            parse_license_expression("CC-BY-NC-SA-4.0 OR (AGPL-1.0-only WITH Swift-exception)"),
+            Some(vec![])
+        );
+        assert_eq!(parse_license_expression("Apache 2.0"), None);
+        assert_eq!(
+            parse_license_expression("MIT/Apache-2.0"),
+            Some(vec!["MIT/Apache-2.0".to_string()])
+        );
+        assert_eq!(parse_license_expression("(MIT OR"), None);
+        assert_eq!(parse_license_expression(""), None);
+    }
+
+    #[test]
+    fn test_license_identifier() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        let check = DocsCheck {
+            control: DocsControl::LicenseIdentifier,
+            file: None,
+            sections: vec![],
+        };
+        assert_eq!(
+            check.findings(root)[0].to_string(),
+            "LICENSE: no LICENSE found"
+        );
+
+        std::fs::write(root.join("LICENSE"), "MIT License\n\nCopyright (c) 2025\n").unwrap();
+        assert_eq!(
+            check.findings(root)[0].message,
+            tr("rsr.docs.finding.no-license-identifier")
+        );
+
+        std::fs::write(
+            root.join("Cargo.toml"),
+            "[package]\nname = \"app\"\nlicense = \"MIT\"\n",
+        )
+        .unwrap();
+        assert!(check.findings(root).is_empty());
+
+        std::fs::write(root.join("LICENSE"), "SPDX-License-Identifier: Apache2\n").unwrap();
+        assert_eq!(
+            check.findings(root)[0].to_string(),
+            "LICENSE:1: 'Apache2' is not an SPDX license identifier"
+        );
+    }
+
+    #[test]
+    fn test_security_contact() {
+        let policy = "# Security\n\nEmail security@acme.io, or see @acme-bot.\n";
+        assert!(check_security(policy).is_empty());
+        assert!(
+            check_security("Report at https://github.com/acme/app/security/advisories\n")
+                .is_empty()
+        );
+        assert_eq!(
+            messages(check_security(
+                "Mail <security@acme> or git@github.com:acme/app.git\n"
+            )),
+            ["1: 'security@acme' is not a valid email address"]
+        );
+        assert_eq!(
+            messages(check_security("Mail `security@example.com`.\n")),
+            ["1: 'security@example.com' is a placeholder address"]
+        );
+        assert_eq!(
+            messages(check_security("# Security\n\nOpen an issue.\n")),
+            ["0: no contact for vulnerability reports (an email address or an https:// URL)"]
+        );
+    }
+
+    #[test]
+    fn test_changelog_format() {
+        let changelog = "# Changelog\n\n## [Unreleased]\n\n### Added\n\n- Audit log\n\n\
+            ## [1.1.0] - 2025-02-30\n\n### Fixes\n\n## [1.2.0] - 2025-03-01\n\n\
+            ## 1.0.0\n";
+        assert_eq!(
+            messages(check_changelog(changelog)),
+            [
+                "9: version 1.1.0 has an invalid date '2025-02-30' (expected YYYY-MM-DD)",
+                "11: 'Fixes' is not a change type (Added, Changed, Deprecated, Removed, Fixed or Security)",
+                "13: versions should be listed newest first, but 1.2.0 comes after 1.1.0",
+                "15: '1.0.0' is not a version heading, such as '## [1.2.0] - 2025-01-31' or '## [Unreleased]'",
+            ]
+        );
+        assert_eq!(
+            messages(check_changelog("Changes\n")),
+            [
+                "1: the first heading should be '# Changelog'",
+                "0: no [Unreleased] or release sections",
+            ]
+        );
+        let valid = "# Changelog\n\n## [1.0.0] - 2024-02-29 [YANKED]\n\n### Security\n";
+        assert!(check_changelog(valid).is_empty());
+    }
+}
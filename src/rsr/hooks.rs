@@ -2,14 +2,58 @@
 //!
 //! Provides hooks that RSR validator can use to trigger conflow operations
 //! and receive results.
+//!
+//! Hooks declared under `hooks:` in `.rsr.yaml` run after a trigger, with
+//! the trigger and its result as a JSON payload:
+//!
+//! ```yaml
+//! hooks:
+//!   - name: security-alert
+//!     on: [check_compliance]
+//!     tags: [security]
+//!     when: failure
+//!     command: ./scripts/alert.sh   # payload on stdin
+//!   - name: dashboard
+//!     async: true
+//!     webhook:
+//!       url: https://dash.example.com/rsr
+//!       headers: { Authorization: "Bearer ${DASH_TOKEN}" }
+//!       retries: 3
+//! ```
+//!
+//! `on` filters by trigger type and `tags` by requirement tag: a hook with
+//! tags only sees the requirements carrying one of them, and does not run
+//! when there are none. Async hooks run in the background; collect their
+//! outcomes with [`RsrHooks::wait`].
 
 use serde::{Deserialize, Serialize};
-use std::path::{Path, PathBuf};
+use std::collections::HashMap;
+#[cfg(feature = "native")]
+use std::path::Path;
+use std::path::PathBuf;
+#[cfg(feature = "native")]
+use std::sync::Mutex;
+#[cfg(feature = "native")]
+use std::time::Duration;
 
-use crate::pipeline::{ExecutionOptions, Pipeline, PipelineExecutor, PipelineResult};
-use crate::executors::create_default_executors;
+#[cfg(feature = "native")]
+use tokio::io::AsyncWriteExt;
+#[cfg(feature = "native")]
+use tokio::task::JoinHandle;
+
+#[cfg(feature = "native")]
 use crate::cache::FilesystemCache;
-use crate::ConflowError;
+#[cfg(feature = "native")]
+use crate::executors::create_executors;
+#[cfg(feature = "native")]
+use crate::pipeline::{
+    Approvals, ExecutionOptions, Pipeline, PipelineExecutor, RunLock, DEFAULT_LOCK_TIMEOUT,
+};
+#[cfg(feature = "native")]
+use crate::utils::offline;
+
+use super::compliance::{ComplianceReport, RequirementResult};
+use super::requirements::RsrRequirementRegistry;
 
 /// Trigger types for RSR integration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -44,6 +88,164 @@ pub enum RsrTrigger {
     },
 }
 
+impl RsrTrigger {
+    /// The trigger's type
+    pub fn kind(&self) -> TriggerKind {
+        match self {
+            RsrTrigger::ValidatePipeline { .. } => TriggerKind::ValidatePipeline,
+            RsrTrigger::RunPipeline { .. } => TriggerKind::RunPipeline,
+            RsrTrigger::CheckCompliance { .. } => TriggerKind::CheckCompliance,
+            RsrTrigger::InitFromTemplate { .. } => TriggerKind::InitFromTemplate,
+            RsrTrigger::AnalyzeConfig { .. } => TriggerKind::AnalyzeConfig,
+        }
+    }
+}
+
+/// Type of an [`RsrTrigger`], for filtering hooks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TriggerKind {
+    ValidatePipeline,
+    RunPipeline,
+    CheckCompliance,
+    InitFromTemplate,
+    AnalyzeConfig,
+}
+
+/// Outcome of a trigger that a hook runs on
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HookWhen {
+    /// Every time
+    #[default]
+    Always,
+    /// When the trigger succeeded and every requirement is met
+    Success,
+    /// When the trigger failed or a requirement is not met
+    Failure,
+}
+
+/// A hook declared under `hooks:` in `.rsr.yaml`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HookConfig {
+    /// Hook name
+    pub name: String,
+
+    /// Trigger types the hook runs on (default: all)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub on: Vec<TriggerKind>,
+
+    /// Requirement tags the hook is limited to
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+
+    /// Outcome the hook runs on
+    #[serde(default)]
+    pub when: HookWhen,
+
+    /// Run in the background instead of waiting for the hook
+    #[serde(default, rename = "async")]
+    pub run_async: bool,
+
+    /// Shell command, run in the project root with the payload on stdin
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+
+    /// Webhook the payload is posted to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook: Option<WebhookConfig>,
+}
+
+/// An external webhook called by a hook
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WebhookConfig {
+    /// URL, with `${VAR}` references expanded from the environment
+    pub url: String,
+
+    /// Extra request headers, with `${VAR}` references expanded
+    #[serde(default, skip_serializing_if = "HashMap::is_empty", serialize_with = "crate::report::sorted")]
+    pub headers: HashMap<String, String>,
+
+    /// Retries after a failed request
+    #[serde(default = "default_retries")]
+    pub retries: u32,
+
+    /// Delay before the first retry, doubled for each further one
+    #[serde(default = "default_backoff_ms")]
+    pub backoff_ms: u64,
+
+    /// Seconds to wait for a response
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+fn default_retries() -> u32 {
+    3
+}
+
+fn default_backoff_ms() -> u64 {
+    1000
+}
+
+fn default_timeout_secs() -> u64 {
+    10
+}
+
+impl HookConfig {
+    /// The result as this hook sees it, or `None` if the hook does not run
+    ///
+    /// With `tags`, only requirements carrying one of them are kept.
+    pub fn filter(&self, kind: TriggerKind, result: &RsrHookResult) -> Option<RsrHookResult> {
+        if !self.on.is_empty() && !self.on.contains(&kind) {
+            return None;
+        }
+
+        let mut result = result.clone();
+        if !self.tags.is_empty() {
+            let requirements = result
+                .data
+                .as_mut()
+                .and_then(|data| data.get_mut("requirements"))
+                .and_then(|r| r.as_array_mut())?;
+            requirements.retain(|r| {
+                r["tags"]
+                    .as_array()
+                    .is_some_and(|tags| tags.iter().any(|t| self.tags.iter().any(|s| t == s)))
+            });
+            if requirements.is_empty() {
+                return None;
+            }
+        }
+
+        let failed = !result.success || !result.unmet().is_empty();
+        let runs = match self.when {
+            HookWhen::Always => true,
+            HookWhen::Success => !failed,
+            HookWhen::Failure => failed,
+        };
+        runs.then_some(result)
+    }
+}
+
+/// Outcome of running a declared hook
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookOutcome {
+    /// Hook name
+    pub hook: String,
+
+    /// Whether the command or webhook succeeded
+    pub success: bool,
+
+    /// Error, or a short description of what ran
+    pub message: String,
+
+    /// Webhook requests made, including retries
+    #[serde(default)]
+    pub attempts: u32,
+}
+
 /// Result of an RSR hook execution
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RsrHookResult {
@@ -58,6 +260,10 @@ pub struct RsrHookResult {
 
     /// Suggestions for next steps
     pub suggestions: Vec<String>,
+
+    /// Outcomes of the declared hooks that ran synchronously
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hooks: Vec<HookOutcome>,
 }
 
 impl RsrHookResult {
@@ -67,6 +273,7 @@ impl RsrHookResult {
             message: message.into(),
             data: None,
             suggestions: vec![],
+            hooks: vec![],
         }
     }
 
@@ -76,6 +283,7 @@ impl RsrHookResult {
             message: message.into(),
             data: None,
             suggestions: vec![],
+            hooks: vec![],
         }
     }
 
@@ -88,21 +296,174 @@ impl RsrHookResult {
         self.suggestions = suggestions;
         self
     }
+
+    /// IDs of the requirements in `data` that are not met
+    pub fn unmet(&self) -> Vec<&str> {
+        self.data
+            .as_ref()
+            .and_then(|data| data.get("requirements"))
+            .and_then(|r| r.as_array())
+            .map(|requirements| {
+                requirements
+                    .iter()
+                    .filter(|r| r["met"] == false)
+                    .filter_map(|r| r["id"].as_str())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// JSON entry for a requirement result, with its tags for hook filtering
+fn requirement_json(registry: &RsrRequirementRegistry, id: &str, met: bool) -> serde_json::Value {
+    let tags = registry.get(id).map(|r| r.tags.clone()).unwrap_or_default();
+    serde_json::json!({ "id": id, "met": met, "tags": tags })
+}
+
+/// Hook result for a compliance report, as produced by `check_compliance`
+pub fn compliance_result(
+    report: &ComplianceReport,
+    registry: &RsrRequirementRegistry,
+) -> RsrHookResult {
+    RsrHookResult::success(format!(
+        "Compliance: {} ({:.0}%)",
+        report.level.description(),
+        report.score * 100.0
+    ))
+    .with_data(serde_json::json!({
+        "level": format!("{:?}", report.level),
+        "score": report.score,
+        "custom_level": report.custom_level,
+        "stats": {
+            "total": report.stats.total,
+            "passed": report.stats.passed,
+            "failed": report.stats.failed,
+        },
+        "requirements": report
+            .requirements
+            .iter()
+            .map(|r| requirement_json(registry, &r.requirement_id, r.met || r.waived))
+            .collect::<Vec<_>>(),
+    }))
+}
+
+/// Hook result for checks of specific requirements
+pub fn requirements_result(
+    results: &[RequirementResult],
+    registry: &RsrRequirementRegistry,
+) -> RsrHookResult {
+    let met = results.iter().filter(|r| r.met).count();
+    let message = if met == results.len() {
+        "All checked requirements met".to_string()
+    } else {
+        format!("{}/{} requirements met", met, results.len())
+    };
+
+    RsrHookResult::success(message).with_data(serde_json::json!({
+        "requirements": results
+            .iter()
+            .map(|r| {
+                let mut entry = requirement_json(registry, &r.requirement_id, r.met || r.waived);
+                entry["remediation"] = serde_json::json!(r.remediation);
+                entry
+            })
+            .collect::<Vec<_>>(),
+    }))
 }
 
 /// RSR Hooks handler
+#[cfg(feature = "native")]
 pub struct RsrHooks {
     working_dir: PathBuf,
+    hooks: Vec<HookConfig>,
+    client: reqwest::Client,
+    pending: Mutex<Vec<JoinHandle<HookOutcome>>>,
 }
 
+#[cfg(feature = "native")]
 impl RsrHooks {
     /// Create a new hooks handler
     pub fn new(working_dir: PathBuf) -> Self {
-        Self { working_dir }
+        Self {
+            working_dir,
+            hooks: Vec::new(),
+            client: reqwest::Client::new(),
+            pending: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Create a hooks handler running the hooks declared in `.rsr.yaml`
+    pub fn for_project(working_dir: PathBuf) -> Result<Self, crate::ConflowError> {
+        let config = super::config::RsrConfig::load_from_project(&working_dir)?;
+        Ok(Self::new(working_dir).with_hooks(config.hooks))
     }
 
-    /// Execute a trigger
+    /// Run `hooks` after each trigger
+    pub fn with_hooks(mut self, hooks: Vec<HookConfig>) -> Self {
+        self.hooks = hooks;
+        self
+    }
+
+    /// Execute a trigger, then the hooks declared for it
     pub async fn execute(&self, trigger: RsrTrigger) -> RsrHookResult {
+        let mut result = self.run_trigger(trigger.clone()).await;
+        result.hooks = self.fire(&trigger, &result).await;
+        result
+    }
+
+    /// Run the hooks matching a trigger's result
+    ///
+    /// Returns the outcomes of synchronous hooks; async ones are spawned
+    /// and collected by [`RsrHooks::wait`].
+    pub async fn fire(&self, trigger: &RsrTrigger, result: &RsrHookResult) -> Vec<HookOutcome> {
+        let mut outcomes = Vec::new();
+
+        for hook in &self.hooks {
+            let Some(seen) = hook.filter(trigger.kind(), result) else {
+                continue;
+            };
+            let payload = serde_json::json!({
+                "hook": hook.name,
+                "trigger": trigger,
+                "result": seen,
+            });
+
+            let run = run_hook(
+                hook.clone(),
+                payload,
+                self.working_dir.clone(),
+                self.client.clone(),
+            );
+            if hook.run_async {
+                let handle = tokio::spawn(run);
+                self.pending.lock().unwrap().push(handle);
+            } else {
+                outcomes.push(run.await);
+            }
+        }
+
+        outcomes
+    }
+
+    /// Wait for the async hooks started so far and return their outcomes
+    pub async fn wait(&self) -> Vec<HookOutcome> {
+        let handles = std::mem::take(&mut *self.pending.lock().unwrap());
+        let mut outcomes = Vec::new();
+        for handle in handles {
+            match handle.await {
+                Ok(outcome) => outcomes.push(outcome),
+                Err(e) => outcomes.push(HookOutcome {
+                    hook: "<async>".into(),
+                    success: false,
+                    message: format!("Hook task failed: {}", e),
+                    attempts: 0,
+                }),
+            }
+        }
+        outcomes
+    }
+
+    async fn run_trigger(&self, trigger: RsrTrigger) -> RsrHookResult {
         match trigger {
             RsrTrigger::ValidatePipeline { path } => {
                 self.validate_pipeline(&path).await
@@ -176,15 +537,28 @@ impl RsrHooks {
         };
 
         let mut executor = PipelineExecutor::new();
-        for (name, exec) in create_default_executors() {
+        for (name, exec) in create_executors(&pipeline) {
             executor.register_executor(&name, exec);
         }
 
+        // Hooks queue behind manual runs instead of racing them
+        let dir = self.working_dir.clone();
+        let lock = tokio::task::spawn_blocking(move || {
+            RunLock::acquire(&dir, DEFAULT_LOCK_TIMEOUT, |_| {})
+        })
+        .await;
+        let _lock = match lock {
+            Ok(Ok(lock)) => lock,
+            Ok(Err(e)) => return RsrHookResult::failure(e.to_string()),
+            Err(e) => return RsrHookResult::failure(format!("Failed to wait for run lock: {}", e)),
+        };
+
         if !no_cache && pipeline.cache.enabled {
-            if let Ok(cache) = FilesystemCache::new(
-                self.working_dir.join(&pipeline.cache.directory),
-                self.working_dir.clone(),
-            ) {
+            let cache_dir = pipeline.cache.path(&self.working_dir);
+            if let Ok(cache) = cache_dir
+                .and_then(|dir| FilesystemCache::new(dir, self.working_dir.clone()))
+            {
+                let cache = cache.with_limits(pipeline.cache.limits());
                 executor = executor.with_cache(Box::new(cache));
             }
         }
@@ -194,6 +568,8 @@ impl RsrHooks {
             dry_run: false,
             stages,
             verbose: false,
+            profile: false,
+            approvals: Approvals::default().with_env(),
         };
 
         match executor.execute(&pipeline, &self.working_dir, &options).await {
@@ -235,62 +611,22 @@ impl RsrHooks {
     async fn check_compliance(&self, requirements: &[String]) -> RsrHookResult {
         use super::compliance::ComplianceChecker;
 
-        let checker = ComplianceChecker::new();
+        let checker = match ComplianceChecker::for_project(&self.working_dir) {
+            Ok(checker) => checker,
+            Err(e) => return RsrHookResult::failure(format!("Compliance check failed: {}", e)),
+        };
 
         if requirements.is_empty() {
             // Check all requirements
             match checker.check(&self.working_dir) {
-                Ok(report) => {
-                    RsrHookResult::success(format!(
-                        "Compliance: {} ({:.0}%)",
-                        report.level.description(),
-                        report.score * 100.0
-                    ))
-                    .with_data(serde_json::json!({
-                        "level": format!("{:?}", report.level),
-                        "score": report.score,
-                        "stats": {
-                            "total": report.stats.total,
-                            "passed": report.stats.passed,
-                            "failed": report.stats.failed,
-                        },
-                        "requirements": report.requirements.iter().map(|r| {
-                            serde_json::json!({
-                                "id": r.requirement_id,
-                                "met": r.met,
-                            })
-                        }).collect::<Vec<_>>(),
-                    }))
-                }
+                Ok(report) => compliance_result(&report, checker.registry()),
                 Err(e) => RsrHookResult::failure(format!("Compliance check failed: {}", e)),
             }
         } else {
             // Check specific requirements
             let req_refs: Vec<&str> = requirements.iter().map(|s| s.as_str()).collect();
             match checker.check_requirements(&req_refs, &self.working_dir) {
-                Ok(results) => {
-                    let all_met = results.iter().all(|r| r.met);
-                    let message = if all_met {
-                        "All checked requirements met".to_string()
-                    } else {
-                        format!(
-                            "{}/{} requirements met",
-                            results.iter().filter(|r| r.met).count(),
-                            results.len()
-                        )
-                    };
-
-                    RsrHookResult::success(message)
-                        .with_data(serde_json::json!({
-                            "requirements": results.iter().map(|r| {
-                                serde_json::json!({
-                                    "id": r.requirement_id,
-                                    "met": r.met,
-                                    "remediation": r.remediation,
-                                })
-                            }).collect::<Vec<_>>(),
-                        }))
-                }
+                Ok(results) => requirements_result(&results, checker.registry()),
                 Err(e) => RsrHookResult::failure(format!("Compliance check failed: {}", e)),
             }
         }
@@ -339,8 +675,127 @@ impl RsrHooks {
     }
 }
 
+/// Run a hook's command or webhook with the payload
+#[cfg(feature = "native")]
+async fn run_hook(
+    hook: HookConfig,
+    payload: serde_json::Value,
+    working_dir: PathBuf,
+    client: reqwest::Client,
+) -> HookOutcome {
+    let body = payload.to_string();
+    let (result, attempts) = match (&hook.command, &hook.webhook) {
+        (Some(command), _) => (
+            run_command(command, &body, &hook.name, &working_dir).await,
+            0,
+        ),
+        (None, Some(webhook)) => post_webhook(&client, webhook, &body).await,
+        (None, None) => (Err("no 'command' or 'webhook' set".to_string()), 0),
+    };
+
+    HookOutcome {
+        success: result.is_ok(),
+        message: result.unwrap_or_else(|e| e),
+        hook: hook.name,
+        attempts,
+    }
+}
+
+#[cfg(feature = "native")]
+async fn run_command(
+    command: &str,
+    body: &str,
+    name: &str,
+    working_dir: &Path,
+) -> Result<String, String> {
+    let mut child = tokio::process::Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .current_dir(working_dir)
+        .env("CONFLOW_HOOK", name)
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run '{}': {}", command, e))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // A command that ignores its input may close stdin early
+        let _ = stdin.write_all(body.as_bytes()).await;
+    }
+
+    let output = child
+        .wait_with_output()
+        .await
+        .map_err(|e| format!("Failed to run '{}': {}", command, e))?;
+    if output.status.success() {
+        Ok(format!("ran '{}'", command))
+    } else {
+        Err(format!(
+            "'{}' failed ({}): {}",
+            command,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// POST the payload, retrying failed requests with exponential backoff
+///
+/// Returns the result and the number of requests made.
+#[cfg(feature = "native")]
+async fn post_webhook(
+    client: &reqwest::Client,
+    webhook: &WebhookConfig,
+    body: &str,
+) -> (Result<String, String>, u32) {
+    let expand = |value: &str| crate::notify::expand_env(value).map_err(|e| e.to_string());
+    let request = || -> Result<reqwest::RequestBuilder, String> {
+        let mut request = client
+            .post(expand(&webhook.url)?)
+            .header("Content-Type", "application/json")
+            .timeout(Duration::from_secs(webhook.timeout_secs))
+            .body(body.to_string());
+        for (key, value) in &webhook.headers {
+            request = request.header(key, expand(value)?);
+        }
+        Ok(request)
+    };
+
+    if let Err(e) = offline::guard(format!("webhook {}", webhook.url)) {
+        return (Err(e.to_string()), 0);
+    }
+    let mut attempts = 0;
+    let mut delay = Duration::from_millis(webhook.backoff_ms);
+    loop {
+        let request = match request() {
+            Ok(request) => request,
+            Err(e) => return (Err(e), attempts),
+        };
+        attempts += 1;
+
+        // Only the host is reported, since webhook URLs carry credentials
+        let error = match request.send().await {
+            Ok(response) if response.status().is_success() => {
+                return (
+                    Ok(format!("webhook returned {}", response.status())),
+                    attempts,
+                )
+            }
+            Ok(response) => format!("webhook returned {}", response.status()),
+            Err(e) => format!("webhook request failed: {}", e.without_url()),
+        };
+        if attempts > webhook.retries {
+            return (Err(error), attempts);
+        }
+        tokio::time::sleep(delay).await;
+        delay *= 2;
+    }
+}
+
 /// JSON-RPC style interface for external integration
 pub mod rpc {
+    #[cfg(feature = "native")]
     use super::*;
     use serde::{Deserialize, Serialize};
 
@@ -395,6 +850,7 @@ pub mod rpc {
     }
 
     /// Handle an RPC request
+    #[cfg(feature = "native")]
     pub async fn handle_request(
         hooks: &RsrHooks,
         request: RpcRequest,
@@ -503,4 +959,147 @@ stages:
 
         assert!(result.success);
     }
+
+    fn compliance(met: &[(&str, bool, &str)]) -> RsrHookResult {
+        let requirements: Vec<_> = met
+            .iter()
+            .map(|(id, met, tag)| serde_json::json!({ "id": id, "met": met, "tags": [tag] }))
+            .collect();
+        RsrHookResult::success("checked")
+            .with_data(serde_json::json!({ "requirements": requirements }))
+    }
+
+    fn hook(yaml: &str) -> HookConfig {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn test_hook_filter() {
+        let result = compliance(&[
+            ("RSR-SEC-001", false, "security"),
+            ("RSR-DOC-001", true, "docs"),
+        ]);
+        let kind = TriggerKind::CheckCompliance;
+
+        let all = hook("{ name: all }");
+        assert!(all.filter(kind, &result).is_some());
+        assert!(all.filter(TriggerKind::RunPipeline, &result).is_some());
+
+        let on = hook("{ name: on, on: [run_pipeline] }");
+        assert!(on.filter(kind, &result).is_none());
+
+        // Tags narrow the requirements the hook sees, and its outcome
+        let docs = hook("{ name: docs, tags: [docs], when: failure }");
+        assert!(docs.filter(kind, &result).is_none());
+        let security = hook("{ name: sec, tags: [security], when: failure }");
+        let seen = security.filter(kind, &result).unwrap();
+        assert_eq!(seen.unmet(), ["RSR-SEC-001"]);
+        assert_eq!(
+            seen.data.unwrap()["requirements"].as_array().unwrap().len(),
+            1
+        );
+
+        let untagged = hook("{ name: x, tags: [testing] }");
+        assert!(untagged.filter(kind, &result).is_none());
+
+        let on_success = hook("{ name: ok, when: success }");
+        assert!(on_success.filter(kind, &result).is_none());
+        assert!(on_success
+            .filter(kind, &compliance(&[("RSR-DOC-001", true, "docs")]))
+            .is_some());
+    }
+
+    #[tokio::test]
+    async fn test_command_hooks() {
+        let temp = TempDir::new().unwrap();
+        let hooks = RsrHooks::new(temp.path().to_path_buf()).with_hooks(vec![
+            hook("{ name: sync, command: 'cat > sync.json' }"),
+            hook("{ name: background, async: true, command: 'cat > async.json' }"),
+            hook("{ name: broken, command: 'exit 3' }"),
+        ]);
+        let trigger = RsrTrigger::CheckCompliance {
+            requirements: vec![],
+        };
+
+        let outcomes = hooks
+            .fire(&trigger, &compliance(&[("RSR-SEC-001", false, "security")]))
+            .await;
+        assert_eq!(outcomes.len(), 2);
+        assert!(outcomes[0].success);
+        assert!(!outcomes[1].success);
+
+        let background = hooks.wait().await;
+        assert_eq!(background.len(), 1);
+        assert!(background[0].success);
+
+        let payload: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(temp.path().join("async.json")).unwrap())
+                .unwrap();
+        assert_eq!(payload["hook"], "background");
+        assert_eq!(payload["trigger"]["type"], "check_compliance");
+        assert_eq!(
+            payload["result"]["data"]["requirements"][0]["id"],
+            "RSR-SEC-001"
+        );
+        assert!(temp.path().join("sync.json").exists());
+    }
+
+    #[tokio::test]
+    async fn test_webhook_retries() {
+        use tokio::io::AsyncReadExt;
+
+        // Fails the first request, accepts the second
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let mut bodies = Vec::new();
+            for status in ["500 Internal Server Error", "200 OK"] {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut request = Vec::new();
+                let mut buf = [0u8; 4096];
+                while !String::from_utf8_lossy(&request).contains("}}") {
+                    let n = socket.read(&mut buf).await.unwrap();
+                    if n == 0 {
+                        break;
+                    }
+                    request.extend_from_slice(&buf[..n]);
+                }
+                bodies.push(String::from_utf8_lossy(&request).to_string());
+                let response = format!(
+                    "HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                    status
+                );
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+            bodies
+        });
+
+        let webhook = WebhookConfig {
+            url: format!("http://{}/hook", addr),
+            headers: HashMap::from([("X-Token".to_string(), "secret".to_string())]),
+            retries: 2,
+            backoff_ms: 10,
+            timeout_secs: 5,
+        };
+        let (result, attempts) =
+            post_webhook(&reqwest::Client::new(), &webhook, r#"{"a":{"b":1}}"#).await;
+        assert!(result.is_ok(), "{:?}", result);
+        assert_eq!(attempts, 2);
+
+        let bodies = server.await.unwrap();
+        assert!(bodies[1].to_lowercase().contains("x-token: secret"));
+
+        // Nothing listens any more: every retry fails
+        let (result, attempts) = post_webhook(
+            &reqwest::Client::new(),
+            &WebhookConfig {
+                retries: 1,
+                ..webhook
+            },
+            "{}",
+        )
+        .await;
+        assert!(result.is_err());
+        assert_eq!(attempts, 2);
+    }
 }
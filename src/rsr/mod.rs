@@ -7,15 +7,33 @@
 //! - Integration hooks for RSR validator
 //! - Shared schema validation
 
+pub mod badges;
+pub mod benchmarks;
 pub mod compliance;
+pub mod config;
+pub mod detect;
+pub mod diff;
+pub mod docs;
 pub mod hooks;
+pub mod profiles;
+pub mod release;
+pub mod remediation;
 pub mod requirements;
+#[cfg(feature = "native")]
+pub mod schedule;
 pub mod schemas;
+pub mod spdx;
+pub mod templates;
+pub mod waivers;
 
 pub use compliance::{
     CheckDetail, ComplianceChecker, ComplianceLevel, ComplianceReport, ComplianceStats,
     RequirementResult,
 };
-pub use hooks::{RsrHooks, RsrTrigger};
+pub use detect::{detect_schema, SchemaMatch};
+pub use diff::ComplianceDiff;
+#[cfg(feature = "native")]
+pub use hooks::RsrHooks;
+pub use hooks::RsrTrigger;
 pub use requirements::{RsrRequirement, RsrRequirementClass, RsrRequirementRegistry};
 pub use schemas::RsrSchemaRegistry;
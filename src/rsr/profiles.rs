@@ -0,0 +1,624 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Organization-wide RSR profiles
+//!
+//! A profile is a named, versioned bundle of requirements, remediations,
+//! schemas and templates that every repository in an organization shares.
+//! `.rsr.yaml` references profiles as `org/name@version`:
+//!
+//! ```yaml
+//! profiles:
+//!   - profile: acme/backend-service@2.1
+//!     git: https://github.com/acme/rsr-profiles.git
+//!     path: backend-service
+//!   - profile: acme/security@1.0
+//!     registry: https://rsr.acme.dev/profiles
+//!     sha256: "<sha256 of the bundle>"
+//! ```
+//!
+//! Git profiles are cloned at the tag `<version>` (or `v<version>`). Registry
+//! profiles are downloaded from `<registry>/<org>/<name>/<version>.tar.gz`;
+//! `CONFLOW_PROFILE_REGISTRY` is the default registry. A bundle has a
+//! `profile.yaml` at its root. Fetched versions are cached in
+//! `~/.conflow/profiles/<org>/<name>/<version>/` and reused from there.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use super::config::RsrConfig;
+use super::requirements::{RemediationOptions, RsrRequirement};
+use super::schemas::{SchemaDefinition, SchemaSource};
+use crate::errors::ConflowError;
+#[cfg(feature = "native")]
+use crate::toolchain::sha256_hex;
+#[cfg(feature = "native")]
+use crate::utils::git;
+use crate::utils::offline;
+
+/// Environment variable overriding the profile cache directory
+pub const PROFILE_DIR_ENV: &str = "CONFLOW_PROFILE_DIR";
+
+/// Environment variable naming the default profile registry
+pub const REGISTRY_ENV: &str = "CONFLOW_PROFILE_REGISTRY";
+
+/// Manifest file at the root of every profile bundle
+pub const MANIFEST: &str = "profile.yaml";
+
+/// An `org/name@version` profile reference
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ProfileRef {
+    /// `org/name`
+    pub name: String,
+    pub version: String,
+}
+
+impl FromStr for ProfileRef {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid profile '{}': expected org/name@version", s);
+        let (name, version) = s.split_once('@').ok_or_else(invalid)?;
+        let (org, profile) = name.split_once('/').ok_or_else(invalid)?;
+
+        // Every part becomes a directory name in the cache
+        let safe = |part: &str| {
+            !part.is_empty()
+                && part != "."
+                && part != ".."
+                && part
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "._-".contains(c))
+        };
+        if ![org, profile, version].into_iter().all(safe) {
+            return Err(invalid());
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            version: version.to_string(),
+        })
+    }
+}
+
+impl TryFrom<String> for ProfileRef {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<ProfileRef> for String {
+    fn from(profile: ProfileRef) -> Self {
+        profile.to_string()
+    }
+}
+
+impl fmt::Display for ProfileRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}@{}", self.name, self.version)
+    }
+}
+
+/// A profile listed under `profiles:` in `.rsr.yaml`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProfileSource {
+    /// Profile reference (`org/name@version`)
+    pub profile: ProfileRef,
+
+    /// Git repository holding the profile
+    pub git: Option<String>,
+
+    /// Directory of the profile within the git repository
+    pub path: Option<PathBuf>,
+
+    /// Registry to download the profile from
+    pub registry: Option<String>,
+
+    /// Expected SHA-256 of the downloaded bundle
+    pub sha256: Option<String>,
+}
+
+/// Contents of a profile's `profile.yaml`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    /// Profile name (`org/name`)
+    pub name: String,
+
+    /// Profile version
+    pub version: String,
+
+    /// Description
+    pub description: Option<String>,
+
+    /// Requirements every project using the profile is checked against
+    #[serde(default)]
+    pub requirements: Vec<RsrRequirement>,
+
+    /// Remediation replacing that of a requirement, by requirement ID
+    #[serde(default, serialize_with = "crate::report::sorted")]
+    pub remediations: HashMap<String, RemediationOptions>,
+
+    /// Schemas, with paths relative to the bundle
+    #[serde(default)]
+    pub schemas: Vec<SchemaDefinition>,
+
+    /// Project templates for `conflow init --template`
+    #[serde(default)]
+    pub templates: Vec<ProfileTemplate>,
+
+    /// Directory the profile was loaded from
+    #[serde(skip)]
+    pub dir: PathBuf,
+}
+
+/// A project template shipped in a profile
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProfileTemplate {
+    /// Template name
+    pub name: String,
+
+    /// Description
+    #[serde(default)]
+    pub description: String,
+
+    /// Directory whose files are copied into the project
+    pub path: PathBuf,
+}
+
+impl Profile {
+    /// Look up a template by name
+    pub fn template(&self, name: &str) -> Option<&ProfileTemplate> {
+        self.templates.iter().find(|t| t.name == name)
+    }
+}
+
+/// Default profile cache (`CONFLOW_PROFILE_DIR` or `~/.conflow/profiles`)
+pub fn default_root() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os(PROFILE_DIR_ENV) {
+        return Some(PathBuf::from(dir));
+    }
+    directories::BaseDirs::new().map(|d| d.home_dir().join(".conflow").join("profiles"))
+}
+
+/// Cache of fetched profiles
+pub struct ProfileStore {
+    root: PathBuf,
+}
+
+impl ProfileStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Open the default profile cache
+    pub fn open() -> Result<Self, ConflowError> {
+        default_root()
+            .map(Self::new)
+            .ok_or_else(|| ConflowError::Io {
+                message: format!("Could not find a home directory; set {}", PROFILE_DIR_ENV),
+            })
+    }
+
+    /// Root directory
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Directory a profile version is cached in
+    pub fn dir(&self, profile: &ProfileRef) -> PathBuf {
+        let (org, name) = profile.name.split_once('/').unwrap_or(("", &profile.name));
+        self.root.join(org).join(name).join(&profile.version)
+    }
+
+    /// Whether a profile version has been fetched
+    pub fn contains(&self, profile: &ProfileRef) -> bool {
+        self.dir(profile).join(MANIFEST).is_file()
+    }
+
+    /// Load a fetched profile
+    pub fn load(&self, profile: &ProfileRef) -> Result<Profile, ConflowError> {
+        if !self.contains(profile) {
+            return Err(profile_error(
+                profile,
+                "not fetched",
+                Some("Run 'conflow rsr profiles' to fetch it"),
+            ));
+        }
+        read_bundle(profile, &self.dir(profile))
+    }
+
+    /// Fetch a profile unless it is cached, or always with `update`
+    pub async fn fetch(
+        &self,
+        source: &ProfileSource,
+        update: bool,
+    ) -> Result<Profile, ConflowError> {
+        let profile = &source.profile;
+        if self.contains(profile) && !update {
+            return self.load(profile);
+        }
+
+        let dest = self.dir(profile);
+        let partial = dest.with_file_name(format!("{}.partial", profile.version));
+        let _ = std::fs::remove_dir_all(&partial);
+        std::fs::create_dir_all(&partial)?;
+
+        let fetched = match (&source.git, registry(source)) {
+            (Some(url), _) => fetch_git(source, url, &partial),
+            (None, Some(registry)) => fetch_registry(source, &registry, &partial).await,
+            (None, None) => Err(profile_error(
+                profile,
+                "no source",
+                Some("Set 'git' or 'registry' on the profile, or CONFLOW_PROFILE_REGISTRY"),
+            )),
+        };
+        let bundle = match fetched.and_then(|dir| read_bundle(profile, &dir).map(|_| dir)) {
+            Ok(dir) => dir,
+            Err(e) => {
+                let _ = std::fs::remove_dir_all(&partial);
+                return Err(e);
+            }
+        };
+
+        let _ = std::fs::remove_dir_all(&dest);
+        std::fs::rename(&bundle, &dest)?;
+        let _ = std::fs::remove_dir_all(&partial);
+        self.load(profile)
+    }
+}
+
+/// Fetched profiles referenced by a config, in order
+pub fn load_all(config: &RsrConfig) -> Result<Vec<Profile>, ConflowError> {
+    if config.profiles.is_empty() {
+        return Ok(Vec::new());
+    }
+    let store = ProfileStore::open()?;
+    config
+        .profiles
+        .iter()
+        .map(|source| store.load(&source.profile))
+        .collect()
+}
+
+/// Fetch the profiles referenced by a config that are not cached yet
+pub async fn fetch_all(config: &RsrConfig, update: bool) -> Result<Vec<Profile>, ConflowError> {
+    if config.profiles.is_empty() {
+        return Ok(Vec::new());
+    }
+    let store = ProfileStore::open()?;
+    let fetched: Vec<String> = config
+        .profiles
+        .iter()
+        .filter(|source| update || !store.contains(&source.profile))
+        .map(|source| format!("profile {}", source.profile))
+        .collect();
+    offline::refuse(fetched)?;
+
+    let mut profiles = Vec::new();
+    for source in &config.profiles {
+        profiles.push(store.fetch(source, update).await?);
+    }
+    Ok(profiles)
+}
+
+fn registry(source: &ProfileSource) -> Option<String> {
+    source
+        .registry
+        .clone()
+        .or_else(|| std::env::var(REGISTRY_ENV).ok())
+        .filter(|r| !r.is_empty())
+}
+
+/// Clone the profile's tag and return the bundle directory inside `dest`
+#[cfg(feature = "native")]
+fn fetch_git(source: &ProfileSource, url: &str, dest: &Path) -> Result<PathBuf, ConflowError> {
+    let version = &source.profile.version;
+    let checkout = dest.join("checkout");
+    git::fetch(url, Some(version), &checkout, true).map_err(|e| {
+        profile_error(
+            &source.profile,
+            &e.to_string(),
+            Some("Check the git URL and that the repository has a tag for the version"),
+        )
+    })?;
+
+    match source.path {
+        Some(ref path) => within(&source.profile, &checkout, path),
+        None => Ok(checkout),
+    }
+}
+
+/// Clone the profile's tag and return the bundle directory inside `dest`
+#[cfg(not(feature = "native"))]
+fn fetch_git(source: &ProfileSource, _url: &str, _dest: &Path) -> Result<PathBuf, ConflowError> {
+    Err(profile_error(
+        &source.profile,
+        "git checkouts are not available in this build",
+        Some("Rebuild conflow with `--features native`"),
+    ))
+}
+
+/// Download and unpack the profile's tarball from a registry
+#[cfg(feature = "native")]
+async fn fetch_registry(
+    source: &ProfileSource,
+    registry: &str,
+    dest: &Path,
+) -> Result<PathBuf, ConflowError> {
+    let profile = &source.profile;
+    let url = format!(
+        "{}/{}/{}.tar.gz",
+        registry.trim_end_matches('/'),
+        profile.name,
+        profile.version
+    );
+    offline::guard(format!("profile {}", url))?;
+    let download_error = |e: reqwest::Error| {
+        profile_error(
+            profile,
+            &format!("download from {} failed: {}", url, e),
+            None,
+        )
+    };
+
+    let client = reqwest::Client::builder()
+        .user_agent(concat!("conflow/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .map_err(download_error)?;
+    let bytes = client
+        .get(&url)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(download_error)?
+        .bytes()
+        .await
+        .map_err(download_error)?;
+
+    unpack_bundle(profile, &bytes, source.sha256.as_deref(), dest)?;
+    Ok(dest.to_path_buf())
+}
+
+/// Download and unpack the profile's tarball from a registry
+#[cfg(not(feature = "native"))]
+async fn fetch_registry(
+    source: &ProfileSource,
+    _registry: &str,
+    _dest: &Path,
+) -> Result<PathBuf, ConflowError> {
+    Err(profile_error(
+        &source.profile,
+        "registry downloads are not available in this build",
+        Some("Use a git profile, or rebuild conflow with `--features native`"),
+    ))
+}
+
+/// Verify a gzipped bundle against its pinned checksum and unpack it
+#[cfg(feature = "native")]
+fn unpack_bundle(
+    profile: &ProfileRef,
+    bytes: &[u8],
+    expected: Option<&str>,
+    dest: &Path,
+) -> Result<(), ConflowError> {
+    let sha256 = sha256_hex(bytes);
+    if let Some(expected) = expected {
+        if !expected.eq_ignore_ascii_case(&sha256) {
+            return Err(profile_error(
+                profile,
+                &format!("checksum mismatch: expected {}, got {}", expected, sha256),
+                Some("The bundle may have been tampered with; check 'sha256' in .rsr.yaml"),
+            ));
+        }
+    }
+
+    tar::Archive::new(flate2::read::GzDecoder::new(bytes))
+        .unpack(dest)
+        .map_err(|e| profile_error(profile, &format!("invalid bundle: {}", e), None))
+}
+
+/// Read a bundle's manifest, resolving its paths against the bundle directory
+fn read_bundle(profile: &ProfileRef, dir: &Path) -> Result<Profile, ConflowError> {
+    let path = dir.join(MANIFEST);
+    let content = std::fs::read_to_string(&path)
+        .map_err(|_| profile_error(profile, &format!("bundle has no {}", MANIFEST), None))?;
+    let mut manifest: Profile = crate::utils::yaml::parse(&content, &path.display().to_string())?;
+
+    if manifest.name != profile.name || manifest.version != profile.version {
+        return Err(profile_error(
+            profile,
+            &format!("bundle is {}@{}", manifest.name, manifest.version),
+            None,
+        ));
+    }
+
+    for schema in &mut manifest.schemas {
+        if let SchemaSource::Path { ref mut path } = schema.source {
+            *path = within(profile, dir, path)?;
+        }
+    }
+    for template in &mut manifest.templates {
+        template.path = within(profile, dir, &template.path)?;
+    }
+    manifest.dir = dir.to_path_buf();
+
+    Ok(manifest)
+}
+
+/// `dir` joined with `path`, which must be relative and must not leave `dir`
+fn within(profile: &ProfileRef, dir: &Path, path: &Path) -> Result<PathBuf, ConflowError> {
+    let inside = path.components().next().is_some()
+        && path.components().all(|c| matches!(c, Component::Normal(_)));
+    if !inside {
+        return Err(profile_error(
+            profile,
+            &format!("path '{}' leaves the bundle", path.display()),
+            Some("Profile paths must be relative, without '..'"),
+        ));
+    }
+    Ok(dir.join(path))
+}
+
+fn profile_error(profile: &ProfileRef, message: &str, help: Option<&str>) -> ConflowError {
+    ConflowError::Profile {
+        profile: profile.to_string(),
+        message: message.to_string(),
+        help: help.map(String::from),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const MANIFEST_YAML: &str = r#"
+name: acme/backend
+version: "2.1"
+requirements:
+  - id: ACME-001
+    name: Has a README
+    class: mandatory
+    description: Every service documents itself
+    validation: { file_exists: [README.md] }
+    remediation: { manual_steps: [Write a README] }
+remediations:
+  RSR-CONFIG-002: { manual_steps: [Copy the ACME pipeline] }
+schemas:
+  - id: acme:service
+    schema_type: cue
+    name: ACME service
+    description: Service manifest
+    source: { path: schemas/service.cue }
+    version: "2.1"
+templates:
+  - { name: acme-service, path: templates/service }
+"#;
+
+    fn source(git: &Path) -> ProfileSource {
+        ProfileSource {
+            profile: "acme/backend@2.1".parse().unwrap(),
+            git: Some(git.display().to_string()),
+            path: Some("backend".into()),
+            registry: None,
+            sha256: None,
+        }
+    }
+
+    #[test]
+    fn test_profile_ref() {
+        let profile: ProfileRef = "acme/backend-service@2.1".parse().unwrap();
+        assert_eq!(profile.name, "acme/backend-service");
+        assert_eq!(profile.version, "2.1");
+        assert_eq!(profile.to_string(), "acme/backend-service@2.1");
+
+        for invalid in [
+            "acme/backend",
+            "backend@2.1",
+            "acme/../x@1",
+            "acme/x@",
+            "a/b/c@1",
+        ] {
+            assert!(invalid.parse::<ProfileRef>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_git_profile() {
+        let manifest = format!("backend/{}", MANIFEST);
+        let Some(repo) = git::test_repo(&[(&manifest, MANIFEST_YAML)], Some("v2.1")) else {
+            return; // git unavailable
+        };
+
+        let cache = TempDir::new().unwrap();
+        let store = ProfileStore::new(cache.path());
+        let source = source(repo.path());
+        assert!(store.load(&source.profile).is_err());
+
+        let profile = store.fetch(&source, false).await.unwrap();
+        assert!(store.contains(&source.profile));
+        assert!(!store.dir(&source.profile).join(".git").exists());
+        assert_eq!(profile.requirements[0].id, "ACME-001");
+        assert!(profile.remediations.contains_key("RSR-CONFIG-002"));
+        assert_eq!(
+            profile.template("acme-service").unwrap().path,
+            store.dir(&source.profile).join("templates/service")
+        );
+        let SchemaSource::Path { ref path } = profile.schemas[0].source else {
+            panic!("expected a path schema");
+        };
+        assert!(path.starts_with(store.dir(&source.profile)));
+
+        // A cached version is not fetched again
+        let moved = ProfileSource {
+            git: Some("/nonexistent".into()),
+            ..source
+        };
+        assert!(store.fetch(&moved, false).await.is_ok());
+        assert!(store.fetch(&moved, true).await.is_err());
+        assert!(store.contains(&moved.profile));
+    }
+
+    #[test]
+    fn test_bundle_paths_stay_inside() {
+        let profile: ProfileRef = "acme/backend@2.1".parse().unwrap();
+        let dir = TempDir::new().unwrap();
+        for (from, to) in [
+            ("schemas/service.cue", "../../.ssh/id_rsa"),
+            ("templates/service", "/etc"),
+        ] {
+            let manifest = MANIFEST_YAML.replace(from, to);
+            std::fs::write(dir.path().join(MANIFEST), manifest).unwrap();
+            let err = read_bundle(&profile, dir.path()).unwrap_err();
+            assert!(err.to_string().contains("leaves the bundle"), "{}", err);
+        }
+
+        for path in ["", ".", "..", "backend/../..", "/tmp"] {
+            assert!(within(&profile, dir.path(), Path::new(path)).is_err(), "{}", path);
+        }
+        assert_eq!(
+            within(&profile, dir.path(), Path::new("backend")).unwrap(),
+            dir.path().join("backend")
+        );
+    }
+
+    #[test]
+    fn test_unpack_bundle_checks_sha256() {
+        let mut archive = tar::Builder::new(flate2::write::GzEncoder::new(
+            Vec::new(),
+            flate2::Compression::default(),
+        ));
+        let mut header = tar::Header::new_gnu();
+        header.set_size(MANIFEST_YAML.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        archive
+            .append_data(&mut header, MANIFEST, MANIFEST_YAML.as_bytes())
+            .unwrap();
+        let bytes = archive.into_inner().unwrap().finish().unwrap();
+
+        let profile: ProfileRef = "acme/backend@2.1".parse().unwrap();
+        let dest = TempDir::new().unwrap();
+        let err = unpack_bundle(&profile, &bytes, Some("00"), dest.path()).unwrap_err();
+        assert!(err.to_string().contains("checksum mismatch"));
+
+        unpack_bundle(&profile, &bytes, Some(&sha256_hex(&bytes)), dest.path()).unwrap();
+        assert_eq!(
+            read_bundle(&profile, dest.path()).unwrap().name,
+            "acme/backend"
+        );
+
+        let other: ProfileRef = "acme/backend@3.0".parse().unwrap();
+        assert!(read_bundle(&other, dest.path()).is_err());
+    }
+}
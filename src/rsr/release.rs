@@ -0,0 +1,432 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Release readiness requirements
+//!
+//! Built-in requirements on the Git history and CHANGELOG of a project
+//! about to release: commits since the latest tag follow Conventional
+//! Commits, and the CHANGELOG has an entry for the version the project
+//! manifest declares. History is read with the `git` CLI.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use super::docs::{self, Finding};
+use super::requirements::{
+    RemediationOptions, RemediationTemplate, RsrRequirement, RsrRequirementClass, ValidationChecks,
+};
+use crate::errors::ConflowError;
+use crate::i18n::{tr, tr_with};
+use crate::pipeline::{read_versions, version_locations, DEFAULT_VERSION_LOCATIONS};
+#[cfg(feature = "native")]
+use crate::utils::git;
+
+/// Commits checked at most, when the check doesn't say
+pub const DEFAULT_COMMITS: usize = 100;
+
+/// Conventional Commits types
+pub const DEFAULT_TYPES: &[&str] = &[
+    "feat", "fix", "docs", "style", "refactor", "perf", "test", "build", "ci", "chore", "revert",
+];
+
+/// Changelog checked when the check names none
+const DEFAULT_CHANGELOG: &str = "CHANGELOG.md";
+
+/// A release readiness check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ReleaseControl {
+    /// Commits since the latest tag follow Conventional Commits
+    ConventionalCommits,
+    /// The CHANGELOG has an entry for the current version
+    ChangelogEntry,
+}
+
+impl ReleaseControl {
+    /// Every control, in requirement ID order
+    pub const ALL: [Self; 2] = [Self::ConventionalCommits, Self::ChangelogEntry];
+
+    /// Requirement ID, e.g. `RSR-RELEASE-001`
+    pub fn id(self) -> String {
+        let index = Self::ALL.iter().position(|c| *c == self).unwrap_or(0);
+        format!("RSR-RELEASE-{:03}", index + 1)
+    }
+
+    /// The control a requirement ID stands for
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.id() == id)
+    }
+
+    /// Kebab-case name, as in `.rsr.yaml`
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::ConventionalCommits => "conventional-commits",
+            Self::ChangelogEntry => "changelog-entry",
+        }
+    }
+
+    /// The requirement checking this control
+    pub fn requirement(self) -> RsrRequirement {
+        let key = |field: &str| format!("rsr.release.{}.{}", self.as_str(), field);
+        // History can't be fixed after the fact; an entry can be added
+        let (templates, related) = match self {
+            Self::ConventionalCommits => (vec![], vec![]),
+            Self::ChangelogEntry => (
+                vec![RemediationTemplate {
+                    name: "changelog".into(),
+                    description: tr("rsr.docs.changelog-format.template"),
+                    conflow_template: Some("changelog".into()),
+                    generates: vec![PathBuf::from(DEFAULT_CHANGELOG)],
+                }],
+                vec![docs::DocsControl::ChangelogFormat.id()],
+            ),
+        };
+
+        RsrRequirement {
+            id: self.id(),
+            name: tr(&key("name")),
+            class: RsrRequirementClass::Preferential,
+            description: tr(&key("description")),
+            validation: ValidationChecks {
+                file_exists: vec![],
+                file_absent: vec![],
+                patterns: vec![],
+                cue_validate: vec![],
+                conflow_valid: false,
+                shell_check: None,
+                manifests: vec![],
+                docs: vec![],
+                release: vec![ReleaseCheck {
+                    control: self,
+                    commits: None,
+                    types: vec![],
+                    file: None,
+                }],
+            },
+            remediation: RemediationOptions {
+                auto_fix: self == Self::ChangelogEntry,
+                templates,
+                manual_steps: vec![tr(&key("step"))],
+                docs_url: Some(format!(
+                    "https://rsr.dev/requirements/{}",
+                    self.id().trim_start_matches("RSR-").to_lowercase()
+                )),
+            },
+            related,
+            requires: vec![],
+            tags: vec!["release".into()],
+        }
+    }
+}
+
+/// Built-in release readiness requirements
+pub fn builtin_release_requirements() -> Vec<RsrRequirement> {
+    ReleaseControl::ALL
+        .into_iter()
+        .map(ReleaseControl::requirement)
+        .collect()
+}
+
+/// Check of a project's history or CHANGELOG before a release
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReleaseCheck {
+    /// What the project must satisfy
+    pub control: ReleaseControl,
+
+    /// Most recent commits checked (default: those since the latest tag,
+    /// at most 100)
+    #[serde(default)]
+    pub commits: Option<usize>,
+
+    /// Commit types allowed (default: those of Conventional Commits)
+    #[serde(default)]
+    pub types: Vec<String>,
+
+    /// Changelog, relative to the project root (default: CHANGELOG.md)
+    #[serde(default)]
+    pub file: Option<PathBuf>,
+}
+
+impl ReleaseCheck {
+    /// The changelog checked, relative to the project root
+    pub fn changelog(&self) -> PathBuf {
+        self.file
+            .clone()
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_CHANGELOG))
+    }
+
+    /// Problems with the project's history or CHANGELOG
+    pub fn findings(&self, project_root: &Path) -> Vec<Finding> {
+        match self.control {
+            ReleaseControl::ConventionalCommits => self.commit_findings(project_root),
+            ReleaseControl::ChangelogEntry => self.changelog_findings(project_root),
+        }
+    }
+
+    fn commit_findings(&self, project_root: &Path) -> Vec<Finding> {
+        let types: Vec<String> = match self.types.is_empty() {
+            true => DEFAULT_TYPES.iter().map(|t| t.to_string()).collect(),
+            false => self.types.clone(),
+        };
+        let finding = |message: String| Finding {
+            file: PathBuf::new(),
+            line: 0,
+            message,
+        };
+        match commit_subjects(project_root, self.commits) {
+            Ok(commits) => commits
+                .iter()
+                .filter_map(|(hash, subject)| check_subject(hash, subject, &types))
+                .map(finding)
+                .collect(),
+            Err(e) => vec![finding(e.to_string())],
+        }
+    }
+
+    fn changelog_findings(&self, project_root: &Path) -> Vec<Finding> {
+        let file = self.changelog();
+        let finding = |line: usize, message: String| Finding {
+            file: file.clone(),
+            line,
+            message,
+        };
+        let Some((_, version)) = current_version(project_root) else {
+            return vec![finding(0, tr("rsr.release.finding.no-version"))];
+        };
+        let Ok(content) = std::fs::read_to_string(project_root.join(&file)) else {
+            let name = file.display().to_string();
+            return vec![finding(
+                0,
+                tr_with("rsr.docs.finding.missing-file", &[("file", &name)]),
+            )];
+        };
+        match version_entry(&content, &version) {
+            Some((_, true)) => vec![],
+            Some((line, false)) => vec![finding(
+                line,
+                tr_with("rsr.release.finding.empty-entry", &[("version", &version)]),
+            )],
+            None => vec![finding(
+                0,
+                tr_with("rsr.release.finding.no-entry", &[("version", &version)]),
+            )],
+        }
+    }
+}
+
+/// Hashes and subjects of the last `count` commits, or else of those since
+/// the latest tag (at most [`DEFAULT_COMMITS`])
+#[cfg(feature = "native")]
+fn commit_subjects(
+    project_root: &Path,
+    count: Option<usize>,
+) -> Result<Vec<(String, String)>, ConflowError> {
+    let (range, limit) = match count {
+        Some(count) => ("HEAD".to_string(), count),
+        None => match git::latest_tag(project_root) {
+            Some(tag) => (format!("{}..HEAD", tag), DEFAULT_COMMITS),
+            None => ("HEAD".to_string(), DEFAULT_COMMITS),
+        },
+    };
+    git::commit_subjects(project_root, &range, limit)
+}
+
+/// Commit history needs the git CLI
+#[cfg(not(feature = "native"))]
+fn commit_subjects(
+    _project_root: &Path,
+    _count: Option<usize>,
+) -> Result<Vec<(String, String)>, ConflowError> {
+    Err(ConflowError::Git {
+        message: "git is not available in this build".into(),
+    })
+}
+
+/// Why a commit subject isn't a conventional commit, if it isn't
+fn check_subject(hash: &str, subject: &str, types: &[String]) -> Option<String> {
+    static CONVENTIONAL: OnceLock<Regex> = OnceLock::new();
+    let conventional = CONVENTIONAL
+        .get_or_init(|| Regex::new(r"^([A-Za-z]+)(\([^()]+\))?!?: \S").expect("valid regex"));
+    // Subjects git writes itself
+    if subject.starts_with("Revert \"") || subject.starts_with("fixup! ") {
+        return None;
+    }
+    let Some(caps) = conventional.captures(subject) else {
+        return Some(tr_with(
+            "rsr.release.finding.not-conventional",
+            &[("commit", &hash), ("subject", &subject)],
+        ));
+    };
+    let kind = &caps[1];
+    match types.iter().any(|t| t.eq_ignore_ascii_case(kind)) {
+        true => None,
+        false => Some(tr_with(
+            "rsr.release.finding.unknown-type",
+            &[
+                ("commit", &hash),
+                ("type", &kind),
+                ("types", &types.join(", ")),
+            ],
+        )),
+    }
+}
+
+/// The version the project declares, and the file declaring it
+///
+/// Reads the locations of the pipeline's `versions:` section, or else the
+/// version fields of Cargo.toml, package.json, pyproject.toml and
+/// Chart.yaml, preferring them in that order.
+pub fn current_version(project_root: &Path) -> Option<(PathBuf, String)> {
+    let mut found = read_versions(project_root, &version_locations(project_root)).ok()?;
+    found.sort_by_key(|l| {
+        DEFAULT_VERSION_LOCATIONS
+            .iter()
+            .position(|(file, _)| l.file == Path::new(file))
+            .unwrap_or(usize::MAX)
+    });
+    found.into_iter().next().map(|l| (l.file, l.version))
+}
+
+/// The heading line of a version's CHANGELOG section, and whether the
+/// section lists any changes
+pub fn version_entry(content: &str, version: &str) -> Option<(usize, bool)> {
+    let headings = docs::headings(content);
+    let bare = version.trim_start_matches('v');
+    let index = headings.iter().position(|(_, level, text)| {
+        *level == 2 && section_version(text).is_some_and(|v| v.trim_start_matches('v') == bare)
+    })?;
+    let start = headings[index].0;
+    let end = headings[index + 1..]
+        .iter()
+        .find(|(_, level, _)| *level <= 2)
+        .map(|(line, _, _)| line - 1)
+        .unwrap_or(usize::MAX);
+    let listed = content
+        .lines()
+        .enumerate()
+        .skip(start)
+        .take_while(|(i, _)| *i < end)
+        .any(|(_, line)| {
+            let line = line.trim_start();
+            line.starts_with(['-', '*', '+']) && line.len() > 1
+        });
+    Some((start, listed))
+}
+
+/// The version of a `## [1.2.0] - 2025-01-31` heading
+fn section_version(heading: &str) -> Option<&str> {
+    let rest = heading.strip_prefix('[')?;
+    let (version, _) = rest.split_once(']')?;
+    Some(version).filter(|v| !v.eq_ignore_ascii_case("unreleased"))
+}
+
+/// A CHANGELOG with its `[Unreleased]` changes released as `version`
+///
+/// `None` when there are no unreleased changes to release.
+pub fn release_unreleased(content: &str, version: &str, date: &str) -> Option<String> {
+    let headings = docs::headings(content);
+    let index = headings
+        .iter()
+        .position(|(_, level, text)| *level == 2 && text.eq_ignore_ascii_case("[unreleased]"))?;
+    let line = headings[index].0;
+    let end = headings[index + 1..]
+        .iter()
+        .find(|(_, level, _)| *level <= 2)
+        .map(|(line, _, _)| line - 1)
+        .unwrap_or(usize::MAX);
+    let pending = content
+        .lines()
+        .enumerate()
+        .skip(line)
+        .take_while(|(i, _)| *i < end)
+        .any(|(_, l)| l.trim_start().starts_with(['-', '*', '+']));
+    if !pending {
+        return None;
+    }
+
+    let mut lines: Vec<String> = content.lines().map(String::from).collect();
+    let heading = format!("## [{}] - {}", version.trim_start_matches('v'), date);
+    lines.splice(line..line, [String::new(), heading]);
+    let mut released = lines.join("\n");
+    if content.ends_with('\n') {
+        released.push('\n');
+    }
+    Some(released)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_requirements() {
+        let requirements = builtin_release_requirements();
+        assert_eq!(requirements.len(), 2);
+        assert_eq!(requirements[0].id, "RSR-RELEASE-001");
+        assert!(!requirements[0].remediation.auto_fix);
+        assert!(requirements[1].remediation.auto_fix);
+        assert_eq!(
+            ReleaseControl::from_id("RSR-RELEASE-002"),
+            Some(ReleaseControl::ChangelogEntry)
+        );
+    }
+
+    #[test]
+    fn test_commit_subjects() {
+        let types: Vec<String> = DEFAULT_TYPES.iter().map(|t| t.to_string()).collect();
+        let check = |subject: &str| check_subject("abc1234", subject, &types);
+
+        assert_eq!(check("feat(cli): add release check"), None);
+        assert_eq!(check("fix!: drop the v1 schema"), None);
+        assert_eq!(check("Revert \"feat: add x\""), None);
+        assert_eq!(
+            check("Update README").unwrap(),
+            "abc1234 'Update README' is not a conventional commit, such as 'feat(cli): add a flag'"
+        );
+        assert!(check("feature: add x").unwrap().contains("type 'feature'"));
+        assert!(check("feat:add x").is_some());
+    }
+
+    #[test]
+    fn test_changelog_entry() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        let check = ReleaseCheck {
+            control: ReleaseControl::ChangelogEntry,
+            commits: None,
+            types: vec![],
+            file: None,
+        };
+        let messages = || -> Vec<String> {
+            check
+                .findings(root)
+                .iter()
+                .map(ToString::to_string)
+                .collect()
+        };
+        assert_eq!(messages(), ["CHANGELOG.md: no version found in Cargo.toml, package.json, pyproject.toml or Chart.yaml"]);
+
+        std::fs::write(root.join("package.json"), r#"{"version": "1.4.0"}"#).unwrap();
+        assert_eq!(
+            current_version(root),
+            Some((PathBuf::from("package.json"), "1.4.0".into()))
+        );
+        let changelog = "# Changelog\n\n## [Unreleased]\n\n### Added\n\n- Release check\n\n\
+            ## [1.3.0] - 2025-01-31\n\n### Fixed\n\n- Crash\n";
+        std::fs::write(root.join("CHANGELOG.md"), changelog).unwrap();
+        assert_eq!(messages(), ["CHANGELOG.md: no entry for version 1.4.0"]);
+
+        let released = release_unreleased(changelog, "1.4.0", "2025-03-01").unwrap();
+        assert!(released.contains("## [Unreleased]\n\n## [1.4.0] - 2025-03-01\n\n### Added"));
+        std::fs::write(root.join("CHANGELOG.md"), &released).unwrap();
+        assert!(messages().is_empty());
+        assert_eq!(release_unreleased(&released, "1.5.0", "2025-04-01"), None);
+
+        let empty = "# Changelog\n\n## [1.4.0] - 2025-03-01\n\n### Added\n\n## [1.3.0] - 2025-01-31\n\n- Crash\n";
+        assert_eq!(version_entry(empty, "1.4.0"), Some((3, false)));
+    }
+}
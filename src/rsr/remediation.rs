@@ -5,12 +5,19 @@
 //!
 //! Automatically fixes failing requirements where possible.
 
-use std::path::Path;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 
+use crate::audit;
+use crate::utils::yaml::edit::{Change, Editor};
 use crate::ConflowError;
 
+use super::benchmarks::{ManifestCheck, Violation};
 use super::compliance::RequirementResult;
+use super::docs::{self, DocsCheck, DocsControl, Finding};
+use super::release::{self, ReleaseCheck, ReleaseControl};
 use super::requirements::{RsrRequirement, RsrRequirementRegistry};
+use super::templates::TemplateGenerator;
 
 /// Result of an auto-remediation attempt
 #[derive(Debug, Clone)]
@@ -165,7 +172,7 @@ package config
 }
 "#;
             if !self.dry_run {
-                std::fs::write(&schema_path, schema_content)?;
+                self.write(project_root, &schema_path, schema_content, "RSR-CONFIG-001")?;
             }
             actions.push(RemediationAction {
                 description: "Create CUE schema template".into(),
@@ -209,7 +216,7 @@ cache:
   directory: .conflow-cache
 "#;
             if !self.dry_run {
-                std::fs::write(&pipeline_path, pipeline_content)?;
+                self.write(project_root, &pipeline_path, pipeline_content, "RSR-CONFIG-002")?;
             }
             actions.push(RemediationAction {
                 description: "Create .conflow.yaml pipeline".into(),
@@ -233,9 +240,11 @@ cache:
             // Create example config
             let example_config = config_dir.join("example.yaml");
             if !self.dry_run {
-                std::fs::write(
+                self.write(
+                    project_root,
                     &example_config,
                     "# Example configuration\nversion: \"1.0\"\nname: my-app\nenvironment: development\n",
+                    "RSR-CONFIG-002",
                 )?;
             }
             actions.push(RemediationAction {
@@ -294,7 +303,7 @@ cache:
 }
 "#;
             if !self.dry_run {
-                std::fs::write(&base_path, base_content)?;
+                self.write(project_root, &base_path, base_content, "RSR-CONFIG-003")?;
             }
             actions.push(RemediationAction {
                 description: "Create base Nickel configuration".into(),
@@ -326,7 +335,7 @@ base & {{
                     }
                 );
                 if !self.dry_run {
-                    std::fs::write(&env_path, env_content)?;
+                    self.write(project_root, &env_path, env_content, "RSR-CONFIG-003")?;
                 }
                 actions.push(RemediationAction {
                     description: format!("Create {} environment config", env),
@@ -342,44 +351,42 @@ base & {{
             let content = std::fs::read_to_string(&pipeline_path)?;
             if !content.contains("generate-") {
                 // Append environment generation stages
-                let addition = r#"
-  # Generate environment-specific configs
-  - name: generate-dev
-    tool:
-      type: nickel
-      command: export
-      format: yaml
-    input: environments/development.ncl
-    output: dist/config.development.yaml
-    description: Generate development config
-
-  - name: generate-staging
-    tool:
-      type: nickel
-      command: export
-      format: yaml
-    input: environments/staging.ncl
-    output: dist/config.staging.yaml
-    description: Generate staging config
-
-  - name: generate-production
-    tool:
-      type: nickel
-      command: export
-      format: yaml
-    input: environments/production.ncl
-    output: dist/config.production.yaml
-    description: Generate production config
-"#;
-                if !self.dry_run {
-                    let new_content = content + addition;
-                    std::fs::write(&pipeline_path, new_content)?;
-                }
-                actions.push(RemediationAction {
-                    description: "Add environment generation stages to pipeline".into(),
-                    completed: true,
-                    files_affected: vec![".conflow.yaml".into()],
-                });
+                let environments = [
+                    ("dev", "development"),
+                    ("staging", "staging"),
+                    ("production", "production"),
+                ];
+                let stages: Vec<serde_yaml::Value> = environments
+                    .iter()
+                    .map(|(name, env)| {
+                        serde_yaml::from_str(&format!(
+                            r#"
+name: generate-{name}
+tool:
+  type: nickel
+  command: export
+  format: yaml
+input: environments/{env}.ncl
+output: dist/config.{env}.yaml
+description: Generate {env} config
+"#
+                        ))
+                    })
+                    .collect::<Result<_, _>>()?;
+                let changes: Vec<Change> = stages
+                    .into_iter()
+                    .map(|value| Change::Push {
+                        path: Change::path("stages"),
+                        value,
+                    })
+                    .collect();
+                actions.push(self.edit_pipeline(
+                    project_root,
+                    content,
+                    &changes,
+                    "Add environment generation stages to pipeline",
+                    "RSR-CONFIG-003",
+                )?);
             }
         }
 
@@ -395,22 +402,17 @@ base & {{
             let content = std::fs::read_to_string(&pipeline_path)?;
 
             if !content.contains("cache:") {
-                // Add cache configuration
-                let cache_config = r#"
-# Caching configuration
-cache:
-  enabled: true
-  directory: .conflow-cache
-"#;
-                if !self.dry_run {
-                    let new_content = content + cache_config;
-                    std::fs::write(&pipeline_path, new_content)?;
-                }
-                actions.push(RemediationAction {
-                    description: "Enable caching in pipeline".into(),
-                    completed: true,
-                    files_affected: vec![".conflow.yaml".into()],
-                });
+                let changes = [Change::Insert {
+                    path: Change::path("cache"),
+                    value: serde_yaml::from_str("{enabled: true, directory: .conflow-cache}")?,
+                }];
+                actions.push(self.edit_pipeline(
+                    project_root,
+                    content,
+                    &changes,
+                    "Enable caching in pipeline",
+                    "RSR-CONFIG-004",
+                )?);
             }
         }
 
@@ -427,12 +429,11 @@ cache:
         if needs_cache_entry {
             let addition = "\n# conflow cache\n.conflow-cache/\n";
             if !self.dry_run {
-                if gitignore_exists {
-                    let content = std::fs::read_to_string(&gitignore_path)?;
-                    std::fs::write(&gitignore_path, content + addition)?;
-                } else {
-                    std::fs::write(&gitignore_path, addition)?;
-                }
+                let content = match gitignore_exists {
+                    true => std::fs::read_to_string(&gitignore_path)? + addition,
+                    false => addition.to_string(),
+                };
+                self.write(project_root, &gitignore_path, content, "RSR-CONFIG-004")?;
             }
             actions.push(RemediationAction {
                 description: "Add cache directory to .gitignore".into(),
@@ -465,7 +466,7 @@ cache:
 
                 // Create empty file or use template
                 if !self.dry_run {
-                    std::fs::write(&path, "")?;
+                    self.write(project_root, &path, "", &requirement.id)?;
                 }
 
                 actions.push(RemediationAction {
@@ -481,11 +482,8 @@ cache:
             let path = project_root.join(file);
             if path.exists() {
                 if !self.dry_run {
-                    if path.is_dir() {
-                        std::fs::remove_dir_all(&path)?;
-                    } else {
-                        std::fs::remove_file(&path)?;
-                    }
+                    let action = format!("remediate {}", requirement.id);
+                    audit::remove(project_root, file, &action)?;
                 }
 
                 actions.push(RemediationAction {
@@ -496,9 +494,262 @@ cache:
             }
         }
 
+        // Apply security control templates to Kubernetes manifests
+        for check in &requirement.validation.manifests {
+            actions.extend(self.harden_manifests(check, project_root, &requirement.id)?);
+        }
+
+        // Start missing documents from their templates, complete READMEs
+        for check in &requirement.validation.docs {
+            actions.extend(self.fix_docs(check, project_root, &requirement.id)?);
+        }
+
+        // Release the CHANGELOG's unreleased changes as the current version
+        for check in &requirement.validation.release {
+            actions.extend(self.fix_release(check, project_root, &requirement.id)?);
+        }
+
+        Ok(actions)
+    }
+
+    /// Give the current version a CHANGELOG entry, from the changes listed
+    /// under `[Unreleased]`
+    ///
+    /// History can't be rewritten, so commit findings are left alone.
+    fn fix_release(
+        &self,
+        check: &ReleaseCheck,
+        project_root: &Path,
+        requirement: &str,
+    ) -> Result<Vec<RemediationAction>, ConflowError> {
+        if check.control != ReleaseControl::ChangelogEntry {
+            return Ok(vec![]);
+        }
+        let findings = check.findings(project_root);
+        if findings.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let file = check.changelog();
+        let path = project_root.join(&file);
+        let released = match (
+            release::current_version(project_root),
+            std::fs::read_to_string(&path),
+        ) {
+            (Some((_, version)), Ok(content)) => {
+                let today = &super::diff::now_rfc3339()[..10];
+                release::release_unreleased(&content, &version, today).map(|c| (version, c))
+            }
+            _ => None,
+        };
+        let Some((version, content)) = released else {
+            return Ok(findings
+                .into_iter()
+                .map(|finding| RemediationAction {
+                    description: format!("Fix by hand: {}", finding),
+                    completed: false,
+                    files_affected: vec![file.display().to_string()],
+                })
+                .collect());
+        };
+
+        if !self.dry_run {
+            self.write(project_root, &path, content, requirement)?;
+        }
+        Ok(vec![RemediationAction {
+            description: format!(
+                "Release the unreleased changes in {} as {}",
+                file.display(),
+                version
+            ),
+            completed: true,
+            files_affected: vec![file.display().to_string()],
+        }])
+    }
+
+    /// Fix a document failing a documentation check
+    ///
+    /// A missing README or CHANGELOG is rendered from its template, and a
+    /// README gets its missing sections appended as stubs. What needs a
+    /// decision, such as a license or a security contact, is reported as
+    /// actions not completed.
+    fn fix_docs(
+        &self,
+        check: &DocsCheck,
+        project_root: &Path,
+        requirement: &str,
+    ) -> Result<Vec<RemediationAction>, ConflowError> {
+        let findings = check.findings(project_root);
+        if findings.is_empty() {
+            return Ok(vec![]);
+        }
+        let fixable = matches!(
+            check.control,
+            DocsControl::ReadmeSections | DocsControl::ChangelogFormat
+        );
+        let by_hand = |findings: Vec<Finding>| {
+            findings
+                .into_iter()
+                .map(|finding| RemediationAction {
+                    description: format!("Fix by hand: {}", finding),
+                    completed: false,
+                    files_affected: vec![finding.file.display().to_string()],
+                })
+                .collect()
+        };
+        if !fixable {
+            return Ok(by_hand(findings));
+        }
+
+        let Some(file) = check.document(project_root) else {
+            let name = project_root
+                .canonicalize()
+                .ok()
+                .and_then(|root| Some(root.file_name()?.to_string_lossy().into_owned()))
+                .unwrap_or_else(|| "my-project".into());
+            let variables = [("project_name".to_string(), name)].into();
+            let rendered = TemplateGenerator::new().render(check.control.template(), &variables)?;
+            let mut files = Vec::new();
+            for (path, content) in rendered {
+                let path = match check.file {
+                    Some(ref file) => file.display().to_string(),
+                    None => path,
+                };
+                if !self.dry_run {
+                    self.write(project_root, Path::new(&path), content, requirement)?;
+                }
+                files.push(path);
+            }
+            return Ok(vec![RemediationAction {
+                description: format!(
+                    "Create {} from the {} template",
+                    files.join(", "),
+                    check.control.template()
+                ),
+                completed: true,
+                files_affected: files,
+            }]);
+        };
+
+        if check.control != DocsControl::ReadmeSections {
+            return Ok(by_hand(findings));
+        }
+        let path = project_root.join(&file);
+        let mut content = std::fs::read_to_string(&path)?;
+        let missing = docs::missing_sections(&content, &check.sections());
+        if !content.ends_with('\n') {
+            content.push('\n');
+        }
+        for section in &missing {
+            content.push_str(&docs::section_stub(&file, section));
+        }
+        if !self.dry_run {
+            self.write(project_root, &path, content, requirement)?;
+        }
+        Ok(vec![RemediationAction {
+            description: format!(
+                "Add {} section{} to {}",
+                missing.join(", "),
+                if missing.len() == 1 { "" } else { "s" },
+                file.display()
+            ),
+            completed: true,
+            files_affected: vec![file.display().to_string()],
+        }])
+    }
+
+    /// Fix the manifests breaking a security control, keeping their comments
+    ///
+    /// Violations without a fix (a hostPath volume, say) are reported as
+    /// actions not completed, with where to look.
+    fn harden_manifests(
+        &self,
+        check: &ManifestCheck,
+        project_root: &Path,
+        requirement: &str,
+    ) -> Result<Vec<RemediationAction>, ConflowError> {
+        let mut by_file: BTreeMap<PathBuf, Vec<Violation>> = BTreeMap::new();
+        for violation in check.violations(project_root)? {
+            by_file.entry(violation.file.clone()).or_default().push(violation);
+        }
+
+        let mut actions = Vec::new();
+        for (file, violations) in by_file {
+            let path = project_root.join(&file);
+            let mut editor = Editor::new(std::fs::read_to_string(&path)?);
+            let mut fixed = 0;
+            for violation in violations {
+                let fixable = !violation.fix.is_empty();
+                if fixable && editor.apply_all(violation.document, &violation.fix) {
+                    fixed += 1;
+                    continue;
+                }
+                actions.push(RemediationAction {
+                    description: format!("Fix by hand: {}", violation),
+                    completed: false,
+                    files_affected: vec![file.display().to_string()],
+                });
+            }
+            if fixed == 0 {
+                continue;
+            }
+            if !self.dry_run {
+                self.write(project_root, &path, editor.content(), requirement)?;
+            }
+            actions.push(RemediationAction {
+                description: format!(
+                    "Set {} in {} ({} container{})",
+                    check.control.as_str(),
+                    file.display(),
+                    fixed,
+                    if fixed == 1 { "" } else { "s" }
+                ),
+                completed: true,
+                files_affected: vec![file.display().to_string()],
+            });
+        }
         Ok(actions)
     }
 
+    /// Make changes to the pipeline in place, keeping its comments
+    ///
+    /// A pipeline the changes can't be placed in (a flow-style `stages`
+    /// list, say) is left alone and the action reported as not completed.
+    fn edit_pipeline(
+        &self,
+        project_root: &Path,
+        content: String,
+        changes: &[Change],
+        description: &str,
+        requirement: &str,
+    ) -> Result<RemediationAction, ConflowError> {
+        let mut editor = Editor::new(content);
+        let completed = editor.apply_all(0, changes);
+        if completed && !self.dry_run {
+            let path = Path::new(".conflow.yaml");
+            self.write(project_root, path, editor.content(), requirement)?;
+        }
+        Ok(RemediationAction {
+            description: match completed {
+                true => description.into(),
+                false => format!("{} (edit .conflow.yaml by hand)", description),
+            },
+            completed,
+            files_affected: vec![".conflow.yaml".into()],
+        })
+    }
+
+    /// Write a project file, recording it in the audit log
+    fn write(
+        &self,
+        project_root: &Path,
+        path: &Path,
+        content: impl AsRef<[u8]>,
+        requirement: &str,
+    ) -> std::io::Result<()> {
+        audit::write(project_root, path, content, &format!("remediate {}", requirement))
+    }
+
     /// Remediate multiple failing requirements
     pub fn remediate_all(
         &self,
@@ -538,6 +789,9 @@ mod tests {
             met: false,
             details: vec![],
             remediation: None,
+            blocked_by: vec![],
+            waived: false,
+            owners: Vec::new(),
         };
 
         let remediation = remediator.remediate(&result, temp.path()).unwrap();
@@ -548,6 +802,115 @@ mod tests {
         assert!(temp.path().join(".conflow.yaml").exists());
     }
 
+    #[test]
+    fn test_remediation_keeps_pipeline_comments() {
+        let temp = TempDir::new().unwrap();
+        let pipeline = temp.path().join(".conflow.yaml");
+        std::fs::write(
+            &pipeline,
+            "\
+# our pipeline
+name: app
+stages:
+  - name: validate  # keep me
+    tool: { type: cue, command: vet }
+    input: config
+",
+        )
+        .unwrap();
+        let remediator = AutoRemediator::new();
+
+        let actions = remediator.remediate_config_004(temp.path()).unwrap();
+        assert!(actions[0].completed);
+        let actions = remediator.remediate_config_003(temp.path()).unwrap();
+        assert!(actions.last().unwrap().completed);
+
+        let content = std::fs::read_to_string(&pipeline).unwrap();
+        assert!(content.starts_with("# our pipeline\n"));
+        assert!(content.contains("  - name: validate  # keep me\n"));
+        assert!(content.ends_with("cache:\n  enabled: true\n  directory: .conflow-cache\n"));
+        let parsed = crate::pipeline::Pipeline::from_yaml(&content).unwrap();
+        assert_eq!(
+            parsed.stage_names(),
+            vec!["validate", "generate-dev", "generate-staging", "generate-production"]
+        );
+
+        // A flow-style list can't be appended to in place
+        std::fs::write(&pipeline, "name: app\nstages: []\n").unwrap();
+        let actions = remediator.remediate_config_003(temp.path()).unwrap();
+        assert!(!actions.last().unwrap().completed);
+        assert_eq!(
+            std::fs::read_to_string(&pipeline).unwrap(),
+            "name: app\nstages: []\n"
+        );
+    }
+
+    #[test]
+    fn test_remediation_hardens_manifests() {
+        let temp = TempDir::new().unwrap();
+        std::fs::create_dir(temp.path().join("k8s")).unwrap();
+        let manifest = temp.path().join("k8s/pod.yaml");
+        std::fs::write(
+            &manifest,
+            "\
+apiVersion: v1
+kind: Pod
+metadata:
+  name: debug
+spec:
+  containers:
+    - name: shell  # for on-call
+      image: busybox
+      securityContext:
+        privileged: true
+",
+        )
+        .unwrap();
+        let remediator = AutoRemediator::new();
+        let requirement = remediator.registry.get("RSR-K8S-001").unwrap();
+
+        let actions = remediator.remediate_generic(requirement, temp.path()).unwrap();
+        assert_eq!(actions.len(), 1);
+        assert!(actions[0].completed);
+        assert_eq!(actions[0].files_affected, ["k8s/pod.yaml"]);
+        let content = std::fs::read_to_string(&manifest).unwrap();
+        assert!(content.contains("- name: shell  # for on-call\n"));
+        assert!(content.ends_with("        privileged: false\n"));
+
+        // Host paths need a person
+        let requirement = remediator.registry.get("RSR-K8S-003").unwrap();
+        assert!(remediator.remediate_generic(requirement, temp.path()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_remediation_fixes_docs() {
+        let temp = TempDir::new().unwrap();
+        let readme = temp.path().join("README.md");
+        std::fs::write(&readme, "# app\n\n## Usage\n\nRun it.").unwrap();
+        let remediator = AutoRemediator::new();
+        let fix = |id: &str| {
+            let requirement = remediator.registry.get(id).unwrap();
+            remediator.remediate_generic(requirement, temp.path()).unwrap()
+        };
+
+        let actions = fix("RSR-DOCS-001");
+        assert_eq!(actions[0].description, "Add Installation, License sections to README.md");
+        let content = std::fs::read_to_string(&readme).unwrap();
+        assert!(content.ends_with("Run it.\n\n## Installation\n\nTODO\n\n## License\n\nTODO\n"));
+        assert!(fix("RSR-DOCS-001").is_empty());
+
+        let actions = fix("RSR-DOCS-004");
+        assert_eq!(actions[0].files_affected, ["CHANGELOG.md"]);
+        let content = std::fs::read_to_string(temp.path().join("CHANGELOG.md")).unwrap();
+        assert!(content.contains("## [Unreleased]"));
+        assert!(fix("RSR-DOCS-004").is_empty());
+
+        // A security contact needs a person
+        let actions = fix("RSR-DOCS-003");
+        assert!(!actions[0].completed);
+        assert!(!temp.path().join("SECURITY.md").exists());
+    }
+
     #[test]
     fn test_dry_run() {
         let temp = TempDir::new().unwrap();
@@ -558,6 +921,9 @@ mod tests {
             met: false,
             details: vec![],
             remediation: None,
+            blocked_by: vec![],
+            waived: false,
+            owners: Vec::new(),
         };
 
         let remediation = remediator.remediate(&result, temp.path()).unwrap();
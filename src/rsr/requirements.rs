@@ -4,9 +4,16 @@
 //! with a focus on configuration-related requirements.
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
+use super::benchmarks::{builtin_kubernetes_requirements, ManifestCheck};
+use super::config::RsrConfig;
+use super::docs::{builtin_docs_requirements, DocsCheck};
+use super::profiles::{self, Profile};
+use super::release::{builtin_release_requirements, ReleaseCheck};
+use crate::i18n::tr;
+
 /// RSR Requirement class
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -31,6 +38,7 @@ impl RsrRequirementClass {
 
 /// RSR Requirement definition
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct RsrRequirement {
     /// Unique requirement ID (e.g., "RSR-CONFIG-002")
     pub id: String,
@@ -54,6 +62,11 @@ pub struct RsrRequirement {
     #[serde(default)]
     pub related: Vec<String>,
 
+    /// Requirements that must be met before this one is checked; while any of
+    /// them fails, this one is reported as blocked
+    #[serde(default)]
+    pub requires: Vec<String>,
+
     /// Tags for categorization
     #[serde(default)]
     pub tags: Vec<String>,
@@ -61,6 +74,7 @@ pub struct RsrRequirement {
 
 /// Validation checks for a requirement
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct ValidationChecks {
     /// Files that should exist
     #[serde(default)]
@@ -85,10 +99,23 @@ pub struct ValidationChecks {
     /// Custom shell check
     #[serde(default)]
     pub shell_check: Option<String>,
+
+    /// Kubernetes manifests should satisfy security controls
+    #[serde(default)]
+    pub manifests: Vec<ManifestCheck>,
+
+    /// Project documents should meet documentation checks
+    #[serde(default)]
+    pub docs: Vec<DocsCheck>,
+
+    /// Git history and CHANGELOG should be ready for a release
+    #[serde(default)]
+    pub release: Vec<ReleaseCheck>,
 }
 
 /// Pattern check within a file
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct PatternCheck {
     /// File to check
     pub file: PathBuf,
@@ -107,6 +134,7 @@ fn default_true() -> bool {
 
 /// CUE validation specification
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct CueValidation {
     /// Files to validate
     pub files: Vec<PathBuf>,
@@ -117,6 +145,7 @@ pub struct CueValidation {
 
 /// Remediation options for a requirement
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct RemediationOptions {
     /// Automatic fixes available
     #[serde(default)]
@@ -137,6 +166,7 @@ pub struct RemediationOptions {
 
 /// Template for remediation
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct RemediationTemplate {
     /// Template name
     pub name: String,
@@ -157,9 +187,9 @@ pub fn builtin_config_requirements() -> Vec<RsrRequirement> {
     vec![
         RsrRequirement {
             id: "RSR-CONFIG-001".into(),
-            name: "Configuration validation".into(),
+            name: tr("rsr.config-001.name"),
             class: RsrRequirementClass::Mandatory,
-            description: "Configuration files must be validated against a schema".into(),
+            description: tr("rsr.config-001.description"),
             validation: ValidationChecks {
                 file_exists: vec![],
                 file_absent: vec![],
@@ -167,12 +197,15 @@ pub fn builtin_config_requirements() -> Vec<RsrRequirement> {
                 cue_validate: vec![],
                 conflow_valid: false,
                 shell_check: None,
+                manifests: vec![],
+                docs: vec![],
+                release: vec![],
             },
             remediation: RemediationOptions {
                 auto_fix: true,
                 templates: vec![RemediationTemplate {
                     name: "cue-validation".into(),
-                    description: "Add CUE schema validation".into(),
+                    description: tr("rsr.config-001.template"),
                     conflow_template: Some("cue-validation".into()),
                     generates: vec![
                         PathBuf::from(".conflow.yaml"),
@@ -180,21 +213,20 @@ pub fn builtin_config_requirements() -> Vec<RsrRequirement> {
                     ],
                 }],
                 manual_steps: vec![
-                    "Create a CUE schema for your configuration".into(),
-                    "Add validation to your build process".into(),
+                    tr("rsr.config-001.step.1"),
+                    tr("rsr.config-001.step.2"),
                 ],
                 docs_url: Some("https://rsr.dev/requirements/config-001".into()),
             },
             related: vec!["RSR-CONFIG-002".into()],
+            requires: vec![],
             tags: vec!["config".into(), "validation".into()],
         },
         RsrRequirement {
             id: "RSR-CONFIG-002".into(),
-            name: "Configuration pipeline orchestration".into(),
+            name: tr("rsr.config-002.name"),
             class: RsrRequirementClass::Preferential,
-            description:
-                "Use conflow for configuration pipeline orchestration instead of ad-hoc scripts"
-                    .into(),
+            description: tr("rsr.config-002.description"),
             validation: ValidationChecks {
                 file_exists: vec![PathBuf::from(".conflow.yaml")],
                 file_absent: vec![],
@@ -202,45 +234,48 @@ pub fn builtin_config_requirements() -> Vec<RsrRequirement> {
                 cue_validate: vec![],
                 conflow_valid: true,
                 shell_check: None,
+                manifests: vec![],
+                docs: vec![],
+                release: vec![],
             },
             remediation: RemediationOptions {
                 auto_fix: true,
                 templates: vec![
                     RemediationTemplate {
                         name: "cue-validation".into(),
-                        description: "Simple schema validation".into(),
+                        description: tr("rsr.config-002.template.cue"),
                         conflow_template: Some("cue-validation".into()),
                         generates: vec![PathBuf::from(".conflow.yaml")],
                     },
                     RemediationTemplate {
                         name: "nickel-generation".into(),
-                        description: "Programmatic config generation".into(),
+                        description: tr("rsr.config-002.template.nickel"),
                         conflow_template: Some("nickel-generation".into()),
                         generates: vec![PathBuf::from(".conflow.yaml")],
                     },
                     RemediationTemplate {
                         name: "full-pipeline".into(),
-                        description: "Generate + validate + export".into(),
+                        description: tr("rsr.config-002.template.full"),
                         conflow_template: Some("full-pipeline".into()),
                         generates: vec![PathBuf::from(".conflow.yaml")],
                     },
                 ],
                 manual_steps: vec![
-                    "Run 'conflow init' to create a pipeline".into(),
-                    "Define stages for your config workflow".into(),
-                    "Replace ad-hoc scripts with conflow run".into(),
+                    tr("rsr.config-002.step.1"),
+                    tr("rsr.config-002.step.2"),
+                    tr("rsr.config-002.step.3"),
                 ],
                 docs_url: Some("https://rsr.dev/requirements/config-002".into()),
             },
             related: vec!["RSR-CONFIG-001".into(), "RSR-CONFIG-003".into()],
+            requires: vec![],
             tags: vec!["config".into(), "orchestration".into(), "conflow".into()],
         },
         RsrRequirement {
             id: "RSR-CONFIG-003".into(),
-            name: "Multi-environment configuration".into(),
+            name: tr("rsr.config-003.name"),
             class: RsrRequirementClass::Preferential,
-            description:
-                "Environment-specific configurations should be generated, not duplicated".into(),
+            description: tr("rsr.config-003.description"),
             validation: ValidationChecks {
                 file_exists: vec![],
                 file_absent: vec![],
@@ -252,12 +287,15 @@ pub fn builtin_config_requirements() -> Vec<RsrRequirement> {
                 cue_validate: vec![],
                 conflow_valid: true,
                 shell_check: None,
+                manifests: vec![],
+                docs: vec![],
+                release: vec![],
             },
             remediation: RemediationOptions {
                 auto_fix: true,
                 templates: vec![RemediationTemplate {
                     name: "multi-env".into(),
-                    description: "Multi-environment config generation".into(),
+                    description: tr("rsr.config-003.template"),
                     conflow_template: Some("multi-env".into()),
                     generates: vec![
                         PathBuf::from(".conflow.yaml"),
@@ -265,21 +303,21 @@ pub fn builtin_config_requirements() -> Vec<RsrRequirement> {
                     ],
                 }],
                 manual_steps: vec![
-                    "Create a base configuration in Nickel".into(),
-                    "Create environment-specific overrides".into(),
-                    "Use conflow to generate all environments".into(),
+                    tr("rsr.config-003.step.1"),
+                    tr("rsr.config-003.step.2"),
+                    tr("rsr.config-003.step.3"),
                 ],
                 docs_url: Some("https://rsr.dev/requirements/config-003".into()),
             },
             related: vec!["RSR-CONFIG-002".into()],
+            requires: vec!["RSR-CONFIG-002".into()],
             tags: vec!["config".into(), "environments".into(), "dry".into()],
         },
         RsrRequirement {
             id: "RSR-CONFIG-004".into(),
-            name: "Configuration caching".into(),
+            name: tr("rsr.config-004.name"),
             class: RsrRequirementClass::Advisory,
-            description: "Configuration generation should use caching to avoid redundant work"
-                .into(),
+            description: tr("rsr.config-004.description"),
             validation: ValidationChecks {
                 file_exists: vec![],
                 file_absent: vec![],
@@ -291,17 +329,21 @@ pub fn builtin_config_requirements() -> Vec<RsrRequirement> {
                 cue_validate: vec![],
                 conflow_valid: false,
                 shell_check: None,
+                manifests: vec![],
+                docs: vec![],
+                release: vec![],
             },
             remediation: RemediationOptions {
                 auto_fix: true,
                 templates: vec![],
                 manual_steps: vec![
-                    "Add cache configuration to .conflow.yaml".into(),
-                    "Ensure cache directory is in .gitignore".into(),
+                    tr("rsr.config-004.step.1"),
+                    tr("rsr.config-004.step.2"),
                 ],
                 docs_url: Some("https://rsr.dev/requirements/config-004".into()),
             },
             related: vec!["RSR-CONFIG-002".into()],
+            requires: vec!["RSR-CONFIG-002".into()],
             tags: vec!["config".into(), "performance".into(), "caching".into()],
         },
     ]
@@ -310,7 +352,7 @@ pub fn builtin_config_requirements() -> Vec<RsrRequirement> {
 /// Registry of all RSR requirements
 #[derive(Debug, Default)]
 pub struct RsrRequirementRegistry {
-    requirements: HashMap<String, RsrRequirement>,
+    requirements: BTreeMap<String, RsrRequirement>,
 }
 
 impl RsrRequirementRegistry {
@@ -318,13 +360,60 @@ impl RsrRequirementRegistry {
         let mut registry = Self::default();
 
         // Load built-in requirements
-        for req in builtin_config_requirements() {
+        for req in builtin_config_requirements()
+            .into_iter()
+            .chain(builtin_kubernetes_requirements())
+            .chain(builtin_docs_requirements())
+            .chain(builtin_release_requirements())
+        {
             registry.requirements.insert(req.id.clone(), req);
         }
 
         registry
     }
 
+    /// Requirements that apply to a project, as configured by its `.rsr.yaml`
+    pub fn for_project(project_root: &std::path::Path) -> Result<Self, crate::ConflowError> {
+        let config = RsrConfig::load_from_project(project_root)?;
+        let profiles = profiles::load_all(&config)?;
+        Self::from_config(&config, &profiles, project_root)
+    }
+
+    /// The built-in requirements plus those of `profiles` and the config's
+    /// own custom and imported ones, with its overrides and skips applied
+    pub fn from_config(
+        config: &RsrConfig,
+        profiles: &[Profile],
+        project_root: &std::path::Path,
+    ) -> Result<Self, crate::ConflowError> {
+        let mut registry = Self::new();
+
+        for profile in profiles {
+            for req in &profile.requirements {
+                registry.register(req.clone());
+            }
+            for (id, remediation) in &profile.remediations {
+                if let Some(req) = registry.requirements.get_mut(id) {
+                    req.remediation = remediation.clone();
+                }
+            }
+        }
+
+        let custom = config.custom_requirements().iter().cloned();
+        for req in custom.chain(config.load_imports(project_root)?) {
+            registry.register(req);
+        }
+
+        registry.requirements.retain(|id, _| !config.is_skipped(id));
+        for (id, req) in registry.requirements.iter_mut() {
+            if let Some(class) = config.class_override(id) {
+                req.class = class;
+            }
+        }
+
+        Ok(registry)
+    }
+
     /// Get a requirement by ID
     pub fn get(&self, id: &str) -> Option<&RsrRequirement> {
         self.requirements.get(id)
@@ -363,9 +452,7 @@ impl RsrRequirementRegistry {
         })?;
 
         let reqs: Vec<RsrRequirement> =
-            serde_yaml::from_str(&content).map_err(|e| crate::ConflowError::Yaml {
-                message: e.to_string(),
-            })?;
+            crate::utils::yaml::parse(&content, &path.display().to_string())?;
 
         for req in reqs {
             self.requirements.insert(req.id.clone(), req);
@@ -403,4 +490,46 @@ mod tests {
         let config_reqs = registry.by_tag("config");
         assert!(config_reqs.len() >= 4);
     }
+
+    #[test]
+    fn test_from_config() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let profile: Profile = serde_yaml::from_str(
+            r#"
+name: acme/backend
+version: "2.1"
+requirements:
+  - id: ACME-001
+    name: Has a README
+    class: mandatory
+    description: Every service documents itself
+    validation: { file_exists: [README.md] }
+    remediation: { manual_steps: [Write a README] }
+remediations:
+  RSR-CONFIG-002: { manual_steps: [Copy the ACME pipeline] }
+"#,
+        )
+        .unwrap();
+        let config: RsrConfig = serde_yaml::from_str(
+            r#"
+requirements:
+  skip: [RSR-CONFIG-004]
+  overrides: { RSR-CONFIG-003: { class: advisory } }
+"#,
+        )
+        .unwrap();
+
+        let registry =
+            RsrRequirementRegistry::from_config(&config, &[profile], temp.path()).unwrap();
+        assert!(registry.get("ACME-001").is_some());
+        assert!(registry.get("RSR-CONFIG-004").is_none());
+        assert_eq!(
+            registry.get("RSR-CONFIG-002").unwrap().remediation.manual_steps,
+            ["Copy the ACME pipeline"]
+        );
+        assert_eq!(
+            registry.get("RSR-CONFIG-003").unwrap().class,
+            RsrRequirementClass::Advisory
+        );
+    }
 }
@@ -0,0 +1,292 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Scheduled compliance checks
+//!
+//! Behind `conflow serve --project`: each project is checked on its cron
+//! schedule (`compliance.schedule` in `.rsr.yaml`), the result is added to
+//! its compliance history, and regressions are announced on the project's
+//! notification channels.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use super::config::RsrConfig;
+use super::diff::{ChangeDirection, ComplianceDiff, ComplianceHistory, RequirementChangeType};
+use super::{ComplianceChecker, ComplianceReport};
+use crate::errors::ConflowError;
+use crate::notify::{Notifier, RunSummary};
+use crate::pipeline::{NotificationConfig, NotificationKind, NotifyEvent, Pipeline};
+use crate::telemetry::Metrics;
+use crate::utils::cron::Schedule;
+use crate::utils::git;
+
+/// History file of projects that don't set `compliance.history_file`
+pub const DEFAULT_HISTORY_FILE: &str = ".rsr/history.json";
+
+/// A project checked on a schedule
+#[derive(Debug, Clone)]
+pub struct ScheduledProject {
+    /// `project.name` from `.rsr.yaml`, or the directory name
+    pub name: String,
+    pub root: PathBuf,
+    pub schedule: Schedule,
+    /// Compliance history, relative to `root`
+    pub history_file: PathBuf,
+}
+
+impl ScheduledProject {
+    /// Load a project's schedule from its `.rsr.yaml`
+    ///
+    /// `default` applies to projects without `compliance.schedule`.
+    pub fn load(root: &Path, default: Option<&Schedule>) -> Result<Self, ConflowError> {
+        let root = root.canonicalize().map_err(|e| ConflowError::Io {
+            message: format!("{}: {}", root.display(), e),
+        })?;
+        let config = RsrConfig::load_from_project(&root)?;
+
+        let schedule = config
+            .compliance
+            .schedule
+            .or_else(|| default.cloned())
+            .ok_or_else(|| ConflowError::CheckFailed {
+                message: format!(
+                    "{} has no compliance schedule; set compliance.schedule in .rsr.yaml \
+                     or pass --schedule",
+                    root.display()
+                ),
+            })?;
+        let name = config.project.name.unwrap_or_else(|| {
+            root.file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_else(|| root.display().to_string())
+        });
+
+        Ok(Self {
+            name,
+            schedule,
+            history_file: config
+                .compliance
+                .history_file
+                .unwrap_or_else(|| PathBuf::from(DEFAULT_HISTORY_FILE)),
+            root,
+        })
+    }
+
+    /// The project's compliance history
+    pub fn history(&self) -> Result<ComplianceHistory, ConflowError> {
+        ComplianceHistory::load(&self.root.join(&self.history_file))
+    }
+
+    /// Check compliance and add the result to the history
+    ///
+    /// Returns the report and how it differs from the previous check.
+    pub fn check(&self) -> Result<(ComplianceReport, ComplianceDiff), ConflowError> {
+        let report = ComplianceChecker::for_project(&self.root)?.check(&self.root)?;
+
+        let mut history = self.history()?;
+        history.add_entry(&report, git::head_commit(&self.root).ok());
+        history.save(&self.root.join(&self.history_file))?;
+
+        let diff = history
+            .diff_latest()
+            .expect("history has the entry just added");
+        Ok((report, diff))
+    }
+
+    /// Channels to notify: those of the project's pipeline, and the Slack
+    /// webhook under `integrations.notifications` in `.rsr.yaml`
+    fn channels(&self) -> Vec<NotificationConfig> {
+        let mut channels = Pipeline::from_file(&self.root.join(".conflow.yaml"))
+            .map(|pipeline| pipeline.notifications)
+            .unwrap_or_default();
+
+        let settings = RsrConfig::load_from_project(&self.root)
+            .map(|config| config.integrations.notifications)
+            .unwrap_or_default();
+        let mut on = Vec::new();
+        if settings.on_regression {
+            on.push(NotifyEvent::Regression);
+        }
+        if settings.on_improvement {
+            on.push(NotifyEvent::Recovery);
+        }
+        if let (Some(url), false) = (settings.slack_webhook, on.is_empty()) {
+            channels.push(NotificationConfig {
+                kind: NotificationKind::Slack,
+                url,
+                on,
+                headers: Default::default(),
+                template: None,
+                owners: Vec::new(),
+            });
+        }
+        channels
+    }
+}
+
+/// Requirements met on the previous check but not now
+pub fn regressed(diff: &ComplianceDiff) -> Vec<String> {
+    let mut regressed: Vec<String> = diff
+        .requirement_changes
+        .iter()
+        .filter(|c| c.change_type == RequirementChangeType::Regressed)
+        .map(|c| c.requirement_id.clone())
+        .collect();
+    regressed.sort();
+    regressed
+}
+
+/// Events a check triggers relative to the previous one
+///
+/// A regressed requirement is a failure and a regression; fixes without
+/// regressions are a recovery.
+pub fn events(diff: &ComplianceDiff) -> Vec<NotifyEvent> {
+    let mut events = vec![NotifyEvent::Always];
+    if diff.summary.regressed > 0 {
+        events.extend([NotifyEvent::Failure, NotifyEvent::Regression]);
+    } else if diff.summary.fixed > 0 {
+        events.push(NotifyEvent::Recovery);
+    }
+    if matches!(
+        diff.level_change.direction,
+        ChangeDirection::Improved | ChangeDirection::Degraded
+    ) {
+        events.push(NotifyEvent::ComplianceChange);
+    }
+    events
+}
+
+/// Check projects on their schedules until the task is dropped
+///
+/// A project whose previous check is still running skips its turn.
+pub async fn run(projects: Vec<ScheduledProject>, metrics: Arc<Metrics>) {
+    let projects: Vec<Arc<ScheduledProject>> = projects.into_iter().map(Arc::new).collect();
+    let mut running: Vec<Option<tokio::task::JoinHandle<()>>> =
+        projects.iter().map(|_| None).collect();
+    let mut now = unix_now();
+
+    loop {
+        let due: Vec<Option<u64>> = projects
+            .iter()
+            .map(|p| p.schedule.next_after(now))
+            .collect();
+        let Some(&wake) = due.iter().flatten().min() else {
+            return;
+        };
+        tokio::time::sleep(Duration::from_secs(wake.saturating_sub(unix_now()))).await;
+
+        for (i, project) in projects.iter().enumerate() {
+            if due[i] != Some(wake) {
+                continue;
+            }
+            if running[i]
+                .as_ref()
+                .is_some_and(|check| !check.is_finished())
+            {
+                tracing::warn!(project = %project.name, "previous check still running, skipped");
+                continue;
+            }
+            running[i] = Some(tokio::spawn(check_and_notify(
+                Arc::clone(project),
+                Arc::clone(&metrics),
+            )));
+        }
+        now = wake;
+    }
+}
+
+async fn check_and_notify(project: Arc<ScheduledProject>, metrics: Arc<Metrics>) {
+    let started = Instant::now();
+    let checked = {
+        let project = Arc::clone(&project);
+        tokio::task::spawn_blocking(move || project.check()).await
+    };
+    let (report, diff) = match checked {
+        Ok(Ok(checked)) => checked,
+        Ok(Err(e)) => {
+            tracing::error!(project = %project.name, error = %e, "compliance check failed");
+            return;
+        }
+        Err(e) => {
+            tracing::error!(project = %project.name, error = %e, "compliance check panicked");
+            return;
+        }
+    };
+
+    metrics.record_compliance(&report);
+    let regressed = regressed(&diff);
+    tracing::info!(
+        project = %project.name,
+        level = ?report.level,
+        score = report.score,
+        regressed = regressed.len(),
+        "compliance checked"
+    );
+
+    let summary = RunSummary::compliance(&project.name, &report, started.elapsed(), regressed);
+    let (_, errors) = Notifier::new(project.channels())
+        .notify(&summary, &events(&diff))
+        .await;
+    for error in errors {
+        tracing::warn!(project = %project.name, error = %error, "notification failed");
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scheduled_check() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let root = temp.path();
+        std::fs::write(
+            root.join(".conflow.yaml"),
+            r#"
+version: "1"
+name: web
+stages:
+  - name: lint
+    tool: { type: shell, command: "true" }
+    input: "*.yaml"
+"#,
+        )
+        .unwrap();
+        std::fs::write(
+            root.join(".rsr.yaml"),
+            "project:\n  name: web\ncompliance:\n  schedule: \"0 * * * *\"\n",
+        )
+        .unwrap();
+
+        let project = ScheduledProject::load(root, None).unwrap();
+        assert_eq!(project.name, "web");
+        assert_eq!(project.schedule.expression(), "0 * * * *");
+
+        let (_, first) = project.check().unwrap();
+        assert_eq!(first.level_change.direction, ChangeDirection::New);
+        assert_eq!(events(&first), [NotifyEvent::Always]);
+
+        std::fs::remove_file(root.join(".conflow.yaml")).unwrap();
+        let (_, second) = project.check().unwrap();
+        assert!(!regressed(&second).is_empty());
+        assert!(events(&second).contains(&NotifyEvent::Regression));
+        assert_eq!(project.history().unwrap().entries.len(), 2);
+        assert!(root.join(DEFAULT_HISTORY_FILE).exists());
+
+        // Projects without a schedule need a default
+        std::fs::write(root.join(".rsr.yaml"), "version: \"1\"\n").unwrap();
+        assert!(ScheduledProject::load(root, None).is_err());
+        let hourly: Schedule = "@hourly".parse().unwrap();
+        let project = ScheduledProject::load(root, Some(&hourly)).unwrap();
+        assert_eq!(project.schedule, hourly);
+    }
+}
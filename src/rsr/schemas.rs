@@ -3,7 +3,7 @@
 //! Provides access to RSR schemas for validation and generation.
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
 use crate::ConflowError;
@@ -62,7 +62,7 @@ pub enum SchemaSource {
 
 /// RSR Schema Registry
 pub struct RsrSchemaRegistry {
-    schemas: HashMap<String, SchemaDefinition>,
+    schemas: BTreeMap<String, SchemaDefinition>,
     cache_dir: Option<PathBuf>,
 }
 
@@ -70,7 +70,7 @@ impl RsrSchemaRegistry {
     /// Create a new schema registry
     pub fn new() -> Self {
         let mut registry = Self {
-            schemas: HashMap::new(),
+            schemas: BTreeMap::new(),
             cache_dir: None,
         };
 
@@ -152,6 +152,192 @@ impl RsrSchemaRegistry {
                 tags: vec!["kubernetes".into(), "k8s".into()],
             },
         );
+
+        // GitOps controller schemas
+        self.schemas.insert(
+            "argocd:base".into(),
+            SchemaDefinition {
+                id: "argocd:base".into(),
+                schema_type: SchemaType::Cue,
+                name: "ArgoCD Schema".into(),
+                description: "Schema for ArgoCD Application and AppProject resources".into(),
+                source: SchemaSource::Inline {
+                    content: ARGOCD_SCHEMA.into(),
+                },
+                version: "1.0.0".into(),
+                tags: vec!["gitops".into(), "argocd".into()],
+            },
+        );
+
+        self.schemas.insert(
+            "flux:base".into(),
+            SchemaDefinition {
+                id: "flux:base".into(),
+                schema_type: SchemaType::Cue,
+                name: "Flux Schema".into(),
+                description: "Schema for Flux Kustomization and HelmRelease resources".into(),
+                source: SchemaSource::Inline {
+                    content: FLUX_SCHEMA.into(),
+                },
+                version: "1.0.0".into(),
+                tags: vec!["gitops".into(), "flux".into()],
+            },
+        );
+        // Automation and orchestration schemas
+        self.schemas.insert(
+            "ansible:playbook".into(),
+            SchemaDefinition {
+                id: "ansible:playbook".into(),
+                schema_type: SchemaType::Cue,
+                name: "Ansible Playbook Schema".into(),
+                description: "Schema for Ansible playbooks: plays, tasks, handlers and roles"
+                    .into(),
+                source: SchemaSource::Inline {
+                    content: ANSIBLE_PLAYBOOK_SCHEMA.into(),
+                },
+                version: "1.0.0".into(),
+                tags: vec!["ansible".into(), "automation".into()],
+            },
+        );
+
+        self.schemas.insert(
+            "ansible:inventory".into(),
+            SchemaDefinition {
+                id: "ansible:inventory".into(),
+                schema_type: SchemaType::Cue,
+                name: "Ansible Inventory Schema".into(),
+                description: "Schema for YAML Ansible inventories of hosts, groups and vars".into(),
+                source: SchemaSource::Inline {
+                    content: ANSIBLE_INVENTORY_SCHEMA.into(),
+                },
+                version: "1.0.0".into(),
+                tags: vec!["ansible".into(), "automation".into()],
+            },
+        );
+
+        self.schemas.insert(
+            "nomad:job".into(),
+            SchemaDefinition {
+                id: "nomad:job".into(),
+                schema_type: SchemaType::Cue,
+                name: "Nomad Job Schema".into(),
+                description: "Schema for Nomad job specifications in JSON form".into(),
+                source: SchemaSource::Inline {
+                    content: NOMAD_JOB_SCHEMA.into(),
+                },
+                version: "1.0.0".into(),
+                tags: vec!["nomad".into(), "orchestration".into()],
+            },
+        );
+
+        // CI provider schemas
+        self.schemas.insert(
+            "circleci:config".into(),
+            SchemaDefinition {
+                id: "circleci:config".into(),
+                schema_type: SchemaType::Cue,
+                name: "CircleCI Config Schema".into(),
+                description: "Schema for .circleci/config.yml (version 2.1)".into(),
+                source: SchemaSource::Inline {
+                    content: CIRCLECI_SCHEMA.into(),
+                },
+                version: "1.0.0".into(),
+                tags: vec!["ci".into(), "circleci".into()],
+            },
+        );
+
+        self.schemas.insert(
+            "azure:pipelines".into(),
+            SchemaDefinition {
+                id: "azure:pipelines".into(),
+                schema_type: SchemaType::Cue,
+                name: "Azure Pipelines Schema".into(),
+                description: "Schema for azure-pipelines.yml: stages, jobs and steps".into(),
+                source: SchemaSource::Inline {
+                    content: AZURE_PIPELINES_SCHEMA.into(),
+                },
+                version: "1.0.0".into(),
+                tags: vec!["ci".into(), "azure-pipelines".into()],
+            },
+        );
+
+        self.schemas.insert(
+            "github:workflow".into(),
+            SchemaDefinition {
+                id: "github:workflow".into(),
+                schema_type: SchemaType::Cue,
+                name: "GitHub Actions Workflow Schema".into(),
+                description: "Schema for .github/workflows/*.yml: triggers, jobs and steps".into(),
+                source: SchemaSource::Inline {
+                    content: GITHUB_WORKFLOW_SCHEMA.into(),
+                },
+                version: "1.0.0".into(),
+                tags: vec!["ci".into(), "github".into()],
+            },
+        );
+
+        self.schemas.insert(
+            "compose:spec".into(),
+            SchemaDefinition {
+                id: "compose:spec".into(),
+                schema_type: SchemaType::Cue,
+                name: "Compose Schema".into(),
+                description: "Schema for Docker Compose files: services, volumes and networks"
+                    .into(),
+                source: SchemaSource::Inline {
+                    content: COMPOSE_SCHEMA.into(),
+                },
+                version: "1.0.0".into(),
+                tags: vec!["compose".into(), "orchestration".into()],
+            },
+        );
+
+        // Observability schemas
+        self.schemas.insert(
+            "prometheus:rules".into(),
+            SchemaDefinition {
+                id: "prometheus:rules".into(),
+                schema_type: SchemaType::Cue,
+                name: "Prometheus Rules Schema".into(),
+                description: "Schema for Prometheus alerting and recording rule files".into(),
+                source: SchemaSource::Inline {
+                    content: PROMETHEUS_RULES_SCHEMA.into(),
+                },
+                version: "1.0.0".into(),
+                tags: vec!["observability".into(), "prometheus".into()],
+            },
+        );
+
+        self.schemas.insert(
+            "alertmanager:config".into(),
+            SchemaDefinition {
+                id: "alertmanager:config".into(),
+                schema_type: SchemaType::Cue,
+                name: "Alertmanager Config Schema".into(),
+                description: "Schema for alertmanager.yml: routes, receivers and inhibit rules"
+                    .into(),
+                source: SchemaSource::Inline {
+                    content: ALERTMANAGER_SCHEMA.into(),
+                },
+                version: "1.0.0".into(),
+                tags: vec!["observability".into(), "alertmanager".into()],
+            },
+        );
+
+        self.schemas.insert(
+            "grafana:dashboard".into(),
+            SchemaDefinition {
+                id: "grafana:dashboard".into(),
+                schema_type: SchemaType::Cue,
+                name: "Grafana Dashboard Schema".into(),
+                description: "Schema for Grafana dashboard JSON models".into(),
+                source: SchemaSource::Inline {
+                    content: GRAFANA_DASHBOARD_SCHEMA.into(),
+                },
+                version: "1.0.0".into(),
+                tags: vec!["observability".into(), "grafana".into()],
+            },
+        );
     }
 
     /// Get a schema by ID
@@ -174,6 +360,7 @@ impl RsrSchemaRegistry {
                 })
             }
             SchemaSource::Url { url } => {
+                crate::utils::offline::guard(format!("schema {}", url))?;
                 // Would fetch from URL
                 Err(ConflowError::ExecutionFailed {
                     message: format!("URL schemas not yet implemented: {}", url),
@@ -288,8 +475,9 @@ package rsr
         docs_url?:      string
     }
 
-    related?: [...string]
-    tags?:    [...string]
+    related?:  [...string]
+    requires?: [...string]
+    tags?:     [...string]
 }
 
 #PatternCheck: {
@@ -347,8 +535,53 @@ package rsr
             provider?: "github" | "gitlab" | "jenkins"
             config?:   string
         }
+
+        badges?: {
+            output?:     string | *".rsr/badges"
+            style?:      *"flat" | "flat-square" | "plastic" | "for-the-badge"
+            label?:      string
+            thresholds?: [...{
+                min_score: number & >=0 & <=1
+                color:     string
+            }]
+            categories?: [...string]
+        }
     }
 
+    // Organization profiles (org/name@version)
+    profiles?: [...{
+        profile:   =~"^[^/@]+/[^/@]+@.+$"
+        git?:      string
+        path?:     string
+        registry?: string
+        sha256?:   string
+    }]
+
+    // Template repositories (git URL at a tag, or a local directory)
+    templates?: [...{
+        git?:     string
+        version?: string
+        path?:    string
+    }]
+
+    // Hooks run after RSR triggers
+    hooks?: [...{
+        name:     string
+        on?:      [...("validate_pipeline" | "run_pipeline" | "check_compliance" |
+                       "init_from_template" | "analyze_config")]
+        tags?:    [...string]
+        when?:    *"always" | "success" | "failure"
+        async?:   bool | *false
+        command?: string
+        webhook?: {
+            url:           string
+            headers?:      [string]: string
+            retries?:      int & >=0 | *3
+            backoff_ms?:   int & >=0 | *1000
+            timeout_secs?: int & >=0 | *10
+        }
+    }]
+
     // Compliance targets
     compliance?: {
         target_level?: "basic" | "good" | "excellent"
@@ -356,6 +589,15 @@ package rsr
             requirement: string
             reason:      string
             expires?:    string
+            approved_by?: string
+        }]
+        score?:   *"weighted" | "ratio" | "strict"
+        weights?: [string]: number & >=0
+        levels?:  [...{
+            name:       string
+            min_score?: number & >=0 & <=1
+            mandatory?: bool | *true
+            requires?:  [...string]
         }]
     }
 }
@@ -443,6 +685,663 @@ package k8s
 }
 "#;
 
+/// ArgoCD resources; documents of other kinds are left unconstrained
+pub(crate) const ARGOCD_SCHEMA: &str = r#"
+// ArgoCD Schema
+package argocd
+
+apiVersion: string
+kind:       string
+
+if apiVersion == "argoproj.io/v1alpha1" && kind == "Application" { #Application }
+if apiVersion == "argoproj.io/v1alpha1" && kind == "AppProject" { #AppProject }
+
+#Metadata: {
+    name:       string & =~"^[a-z0-9]([-a-z0-9]*[a-z0-9])?$"
+    namespace?: string
+    ...
+}
+
+#Application: {
+    apiVersion: "argoproj.io/v1alpha1"
+    kind:       "Application"
+    metadata:   #Metadata
+    spec: {
+        project:      string | *"default"
+        source?:      #Source
+        sources?:     [...#Source]
+        destination:  #Destination
+        syncPolicy?: {
+            automated?: {
+                prune?:    bool
+                selfHeal?: bool
+            }
+            syncOptions?: [...string]
+            ...
+        }
+        ...
+    }
+    ...
+}
+
+#Source: {
+    repoURL:         string & !=""
+    targetRevision?: string
+    path?:           string
+    chart?:          string
+    ...
+}
+
+#Destination: {
+    server?:    string
+    name?:      string
+    namespace?: string
+}
+
+#AppProject: {
+    apiVersion: "argoproj.io/v1alpha1"
+    kind:       "AppProject"
+    metadata:   #Metadata
+    spec: {
+        description?:  string
+        sourceRepos?:  [...string]
+        destinations?: [...#Destination]
+        ...
+    }
+    ...
+}
+"#;
+
+/// Flux resources; documents of other kinds are left unconstrained
+pub(crate) const FLUX_SCHEMA: &str = r#"
+// Flux Schema
+package flux
+
+apiVersion: string
+kind:       string
+
+if apiVersion =~ "^kustomize.toolkit.fluxcd.io/" && kind == "Kustomization" { #Kustomization }
+if apiVersion =~ "^helm.toolkit.fluxcd.io/" && kind == "HelmRelease" { #HelmRelease }
+if apiVersion =~ "^source.toolkit.fluxcd.io/" && kind == "GitRepository" { #GitRepository }
+
+#Metadata: {
+    name:       string
+    namespace?: string
+    ...
+}
+
+#Duration: string & =~"^([0-9]+(\\.[0-9]+)?(ms|s|m|h))+$"
+
+#SourceRef: {
+    kind:       "GitRepository" | "OCIRepository" | "Bucket" | "HelmRepository"
+    name:       string
+    namespace?: string
+}
+
+#Kustomization: {
+    kind:     "Kustomization"
+    metadata: #Metadata
+    spec: {
+        interval:  #Duration
+        path?:     string
+        prune:     bool
+        sourceRef: #SourceRef
+        dependsOn?: [...{name: string, namespace?: string}]
+        ...
+    }
+    ...
+}
+
+#HelmRelease: {
+    kind:     "HelmRelease"
+    metadata: #Metadata
+    spec: {
+        interval: #Duration
+        chart?: spec: {
+            chart:     string
+            version?:  string
+            sourceRef: #SourceRef
+            ...
+        }
+        chartRef?: {
+            kind: "OCIRepository" | "HelmChart"
+            name: string
+            ...
+        }
+        values?: {...}
+        ...
+    }
+    ...
+}
+
+#GitRepository: {
+    kind:     "GitRepository"
+    metadata: #Metadata
+    spec: {
+        url:      string & =~"^(https?|ssh)://"
+        interval: #Duration
+        ref?: {
+            branch?: string
+            tag?:    string
+            semver?: string
+            commit?: string
+        }
+        ...
+    }
+    ...
+}
+"#;
+
+/// Ansible playbooks: a list of plays, each an `import_playbook` or a play
+/// with `hosts`
+const ANSIBLE_PLAYBOOK_SCHEMA: &str = r#"
+// Ansible Playbook Schema
+package ansible
+
+[...#Play]
+
+#Play: #Import | #HostPlay
+
+#Import: {
+    import_playbook: string & !=""
+    name?:           string
+    when?:           #Condition
+    vars?:           {...}
+    ...
+}
+
+#HostPlay: {
+    hosts:         string | [...string]
+    name?:         string
+    become?:       bool
+    become_user?:  string
+    gather_facts?: bool
+    serial?:       int | string | [...(int | string)]
+    strategy?:     string
+    vars?:         {...}
+    vars_files?:   [...string]
+    roles?:        [...(string | #Role)]
+    pre_tasks?:    [...#Task]
+    tasks?:        [...#Task]
+    post_tasks?:   [...#Task]
+    handlers?:     [...#Task]
+    tags?:         string | [...string]
+    ...
+}
+
+#Role: {
+    role?: string
+    name?: string
+    when?: #Condition
+    vars?: {...}
+    tags?: string | [...string]
+    ...
+}
+
+#Condition: string | bool | [...(string | bool)]
+
+// Tasks name a module as an extra key; blocks group tasks
+#Task: {
+    name?:          string
+    when?:          #Condition
+    become?:        bool
+    register?:      string & =~"^[A-Za-z_][A-Za-z0-9_]*$"
+    notify?:        string | [...string]
+    loop?:          string | [...]
+    with_items?:    string | [...]
+    ignore_errors?: bool
+    changed_when?:  #Condition
+    failed_when?:   #Condition
+    tags?:          string | [...string]
+    block?:         [...#Task]
+    rescue?:        [...#Task]
+    always?:        [...#Task]
+    ...
+}
+"#;
+
+/// Ansible YAML inventories: groups of `hosts`, `vars` and `children`
+const ANSIBLE_INVENTORY_SCHEMA: &str = r#"
+// Ansible Inventory Schema
+package ansible
+
+[Name=string]: #Group
+
+#Group: {
+    hosts?:    [string]: null | {...}
+    vars?:     {...}
+    children?: [string]: null | #Group
+}
+"#;
+
+/// Nomad job specifications as produced by `nomad job run -output`
+const NOMAD_JOB_SCHEMA: &str = r#"
+// Nomad Job Schema
+package nomad
+
+Job: #Job
+
+#Job: {
+    ID?:          string & !=""
+    Name?:        string
+    Type?:        *"service" | "batch" | "system" | "sysbatch"
+    Datacenters?: [...string]
+    Namespace?:   string
+    Region?:      string
+    Priority?:    int & >=1 & <=100
+    Constraints?: [...#Constraint]
+    TaskGroups:   [...#TaskGroup] & [_, ...]
+    Update?:      {...}
+    Meta?:        [string]: string
+    ...
+}
+
+#Constraint: {
+    LTarget?: string
+    RTarget?: string
+    Operand?: string
+}
+
+#TaskGroup: {
+    Name:         string & !=""
+    Count?:       int & >=0
+    Constraints?: [...#Constraint]
+    Networks?:    [...{...}]
+    Services?:    [...#Service]
+    Tasks:        [...#Task] & [_, ...]
+    ...
+}
+
+#Task: {
+    Name:       string & !=""
+    Driver:     string & !=""
+    Config?:    {...}
+    Env?:       [string]: string
+    Resources?: {
+        CPU?:      int & >=0
+        MemoryMB?: int & >=0
+        ...
+    }
+    Services?: [...#Service]
+    ...
+}
+
+#Service: {
+    Name:       string
+    PortLabel?: string
+    Tags?:      [...string]
+    ...
+}
+"#;
+
+/// CircleCI 2.1 configuration
+const CIRCLECI_SCHEMA: &str = r#"
+// CircleCI Config Schema
+package circleci
+
+version: 2 | 2.1 | "2" | "2.1"
+
+orbs?:      [string]: string | {...}
+commands?:  [string]: #Command
+executors?: [string]: #Executor
+jobs?:      [string]: #Job
+workflows?: {
+    version?: 2
+    [string]: #Workflow | 2
+}
+parameters?: [string]: #Parameter
+setup?:      bool
+
+#Executor: {
+    docker?: [...{
+        image: string & !=""
+        ...
+    }]
+    machine?:           bool | {...}
+    macos?:             {xcode: string | number, ...}
+    resource_class?:    string
+    working_directory?: string
+    environment?:       [string]: string | number | bool
+    ...
+}
+
+#Job: #Executor & {
+    executor?:    string | {name: string, ...}
+    parallelism?: int & >=1
+    parameters?:  [string]: #Parameter
+    steps:        [...#Step]
+    ...
+}
+
+#Command: {
+    description?: string
+    parameters?:  [string]: #Parameter
+    steps:        [...#Step]
+}
+
+// "checkout", or a single-key map such as {run: ...}
+#Step: string | {[string]: _}
+
+#Parameter: {
+    type:         "string" | "boolean" | "integer" | "enum" | "executor" | "steps" | "env_var_name"
+    description?: string
+    default?:     _
+    enum?:        [...string]
+}
+
+#Workflow: {
+    when?:     _
+    unless?:   _
+    triggers?: [...{...}]
+    jobs:      [...(string | {[string]: {...}})]
+}
+"#;
+
+/// Azure Pipelines YAML; a pipeline has `stages`, `jobs` or `steps`
+const AZURE_PIPELINES_SCHEMA: &str = r#"
+// Azure Pipelines Schema
+package azure
+
+name?:       string
+trigger?:    #Trigger
+pr?:         #Trigger
+schedules?:  [...{cron: string, branches?: {...}, always?: bool, ...}]
+pool?:       #Pool
+variables?:  #Variables
+parameters?: [...{name: string, type?: string, default?: _, values?: [...], ...}]
+resources?:  {...}
+extends?:    {template: string, parameters?: {...}}
+
+stages?: [...#Stage]
+jobs?:   [...#Job]
+steps?:  [...#Step]
+
+#Trigger: "none" | [...string] | {
+    batch?:    bool
+    branches?: #Filter
+    paths?:    #Filter
+    tags?:     #Filter
+}
+
+#Filter: {
+    include?: [...string]
+    exclude?: [...string]
+}
+
+#Pool: string | {
+    name?:    string
+    vmImage?: string
+    demands?: string | [...string]
+}
+
+#Variables: {[string]: string | number | bool} | [...({name: string, value: _, readonly?: bool} | {group: string} | {template: string, ...})]
+
+#Stage: {
+    stage:        string & =~"^[A-Za-z_][A-Za-z0-9_]*$"
+    displayName?: string
+    dependsOn?:   string | [...string]
+    condition?:   string
+    variables?:   #Variables
+    pool?:        #Pool
+    jobs:         [...#Job]
+} | {template: string, parameters?: {...}}
+
+#Job: {
+    job:               string & =~"^[A-Za-z_][A-Za-z0-9_]*$"
+    displayName?:      string
+    dependsOn?:        string | [...string]
+    condition?:        string
+    pool?:             #Pool
+    variables?:        #Variables
+    timeoutInMinutes?: int & >=0
+    container?:        string | {...}
+    strategy?:         {...}
+    steps:             [...#Step]
+} | {
+    deployment:   string & =~"^[A-Za-z_][A-Za-z0-9_]*$"
+    environment:  string | {...}
+    displayName?: string
+    dependsOn?:   string | [...string]
+    pool?:        #Pool
+    strategy:     {...}
+    ...
+} | {template: string, parameters?: {...}}
+
+// script, bash, pwsh, powershell, checkout, download, publish, task or template
+#Step: {
+    displayName?:      string
+    name?:             string
+    condition?:        string
+    continueOnError?:  bool
+    enabled?:          bool
+    env?:              [string]: string
+    timeoutInMinutes?: int & >=0
+    ...
+}
+"#;
+
+/// Prometheus rule files, as loaded through `rule_files`
+const PROMETHEUS_RULES_SCHEMA: &str = r#"
+// Prometheus Rules Schema
+package prometheus
+
+groups: [...#Group]
+
+#Duration: string & =~"^([0-9]+y)?([0-9]+w)?([0-9]+d)?([0-9]+h)?([0-9]+m)?([0-9]+s)?([0-9]+ms)?$" & !=""
+
+#Group: {
+    name:          string & !=""
+    interval?:     #Duration
+    limit?:        int & >=0
+    query_offset?: #Duration
+    labels?:       [string]: string
+    rules:         [...#Rule]
+}
+
+#Rule: #Alert | #Recording
+
+#Alert: {
+    alert:            string & !=""
+    expr:             string & !="" | number
+    for?:             #Duration
+    keep_firing_for?: #Duration
+    labels?:          [string]: string
+    annotations?:     [string]: string
+}
+
+#Recording: {
+    record:  string & =~"^[a-zA-Z_:][a-zA-Z0-9_:]*$"
+    expr:    string & !="" | number
+    labels?: [string]: string
+}
+"#;
+
+/// Alertmanager configuration; receiver integrations are left open
+const ALERTMANAGER_SCHEMA: &str = r#"
+// Alertmanager Config Schema
+package alertmanager
+
+global?:              {...}
+templates?:           [...string]
+route:                #Route & {receiver: string}
+receivers:            [...#Receiver]
+inhibit_rules?:       [...#InhibitRule]
+time_intervals?:      [...#TimeInterval]
+mute_time_intervals?: [...#TimeInterval]
+
+#Duration: string & =~"^([0-9]+y)?([0-9]+w)?([0-9]+d)?([0-9]+h)?([0-9]+m)?([0-9]+s)?([0-9]+ms)?$"
+
+#Route: {
+    receiver?:              string
+    group_by?:              [...string]
+    continue?:              bool
+    matchers?:              [...string]
+    match?:                 [string]: string
+    match_re?:              [string]: string
+    group_wait?:            #Duration
+    group_interval?:        #Duration
+    repeat_interval?:       #Duration
+    mute_time_intervals?:   [...string]
+    active_time_intervals?: [...string]
+    routes?:                [...#Route]
+}
+
+#Receiver: {
+    name: string & !=""
+    ...
+}
+
+#InhibitRule: {
+    source_matchers?: [...string]
+    target_matchers?: [...string]
+    source_match?:    [string]: string
+    target_match?:    [string]: string
+    equal?:           [...string]
+}
+
+#TimeInterval: {
+    name:            string & !=""
+    time_intervals?: [...{...}]
+}
+"#;
+
+/// Grafana dashboard JSON; panel options and queries are left open
+const GRAFANA_DASHBOARD_SCHEMA: &str = r#"
+// Grafana Dashboard Schema
+package grafana
+
+uid?:           string & =~"^[a-zA-Z0-9_-]{1,40}$"
+title:          string & !=""
+schemaVersion:  int & >=16
+version?:       int
+editable?:      bool
+tags?:          [...string]
+timezone?:      string
+refresh?:       string | bool
+time?:          {from: string, to: string}
+panels?:        [...#Panel]
+templating?:    {list: [...#Variable]}
+annotations?:   {...}
+links?:         [...{...}]
+...
+
+#Datasource: null | string | {
+    type?: string
+    uid?:  string
+}
+
+#Panel: {
+    id:          int & >=0
+    type:        string & !=""
+    title?:      string
+    datasource?: #Datasource
+    gridPos?: {
+        x: int & >=0 & <24
+        y: int & >=0
+        w: int & >=1 & <=24
+        h: int & >=1
+    }
+    targets?: [...{
+        refId?:      string
+        datasource?: #Datasource
+        ...
+    }]
+    panels?: [...#Panel]
+    ...
+}
+
+#Variable: {
+    name:        string & =~"^[A-Za-z0-9_]+$"
+    type:        "query" | "custom" | "constant" | "datasource" | "interval" | "textbox" | "adhoc" | "groupby"
+    datasource?: #Datasource
+    ...
+}
+"#;
+
+/// GitHub Actions workflows
+const GITHUB_WORKFLOW_SCHEMA: &str = r#"
+// GitHub Actions Workflow Schema
+package github
+
+name?:        string
+"run-name"?:  string
+on:           string | [...string] | {...}
+permissions?: string | {[string]: string}
+env?:         [string]: string | number | bool
+defaults?:    {...}
+concurrency?: string | {...}
+jobs:         [=~"^[A-Za-z_][A-Za-z0-9_-]*$"]: #Job
+
+#Job: {
+    name?:                string
+    needs?:               string | [...string]
+    if?:                  string | bool
+    "runs-on":            string | [...string] | {...}
+    permissions?:         string | {[string]: string}
+    environment?:         string | {...}
+    env?:                 [string]: string | number | bool
+    strategy?:            {...}
+    "timeout-minutes"?:   number | string
+    "continue-on-error"?: bool | string
+    container?:           string | {...}
+    services?:            [string]: {...}
+    outputs?:             [string]: string
+    steps?:               [...#Step]
+} | {
+    uses:     string & !=""
+    with?:    {...}
+    secrets?: "inherit" | {...}
+    needs?:   string | [...string]
+    if?:      string | bool
+    ...
+}
+
+#Step: {
+    id?:                  string
+    name?:                string
+    if?:                  string | bool
+    uses?:                string
+    run?:                 string
+    with?:                {...}
+    env?:                 [string]: string | number | bool
+    shell?:               string
+    "working-directory"?: string
+    "continue-on-error"?: bool | string
+    "timeout-minutes"?:   number | string
+}
+"#;
+
+/// Docker Compose files, per the Compose specification
+const COMPOSE_SCHEMA: &str = r#"
+// Compose Schema
+package compose
+
+version?:  string
+name?:     string
+services:  [string]: #Service
+volumes?:  [string]: null | {...}
+networks?: [string]: null | {...}
+configs?:  [string]: {...}
+secrets?:  [string]: {...}
+
+#Service: {
+    image?:       string & !=""
+    build?:       string | {context?: string, dockerfile?: string, ...}
+    command?:     string | [...string]
+    entrypoint?:  string | [...string]
+    environment?: [...string] | {[string]: string | number | bool | null}
+    env_file?:    string | [...string]
+    ports?:       [...(string | number | {...})]
+    volumes?:     [...(string | {...})]
+    depends_on?:  [...string] | {[string]: {...}}
+    restart?:     "no" | "always" | "on-failure" | "unless-stopped" | =~"^on-failure:[0-9]+$"
+    networks?:    [...string] | {[string]: null | {...}}
+    healthcheck?: {...}
+    deploy?:      {...}
+    ...
+}
+"#;
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -455,6 +1354,77 @@ mod tests {
         assert!(registry.get("rsr:requirement").is_some());
         assert!(registry.get("rsr:config").is_some());
         assert!(registry.get("k8s:base").is_some());
+        assert!(registry.get("argocd:base").is_some());
+        assert!(registry.get("flux:base").is_some());
+        assert_eq!(registry.by_tag("gitops").len(), 2);
+    }
+
+    #[test]
+    fn test_ecosystem_builtins() {
+        let registry = RsrSchemaRegistry::new();
+
+        assert_eq!(registry.by_tag("ansible").len(), 2);
+        assert_eq!(registry.by_tag("ci").len(), 3);
+
+        let playbook = registry.get_content("ansible:playbook").unwrap();
+        assert!(playbook.contains("[...#Play]"));
+        assert!(playbook.contains("#Task"));
+
+        let inventory = registry.get_content("ansible:inventory").unwrap();
+        assert!(inventory.contains("children?"));
+
+        let nomad = registry.get_content("nomad:job").unwrap();
+        assert!(nomad.contains("TaskGroups:"));
+        assert_eq!(
+            registry.get("nomad:job").unwrap().tags,
+            ["nomad", "orchestration"]
+        );
+
+        let circleci = registry.get_content("circleci:config").unwrap();
+        assert!(circleci.contains("version: 2 | 2.1"));
+
+        let azure = registry.get_content("azure:pipelines").unwrap();
+        assert!(azure.contains("#Stage"));
+        assert!(azure.contains("#Job"));
+
+        for id in [
+            "ansible:playbook",
+            "ansible:inventory",
+            "nomad:job",
+            "circleci:config",
+            "azure:pipelines",
+        ] {
+            let schema = registry.get(id).unwrap();
+            assert_eq!(schema.schema_type, SchemaType::Cue);
+            assert!(registry.get_content(id).unwrap().contains("package "));
+        }
+    }
+
+    #[test]
+    fn test_observability_builtins() {
+        let registry = RsrSchemaRegistry::new();
+
+        let mut ids: Vec<_> = registry
+            .by_tag("observability")
+            .into_iter()
+            .map(|s| s.id.as_str())
+            .collect();
+        ids.sort();
+        assert_eq!(
+            ids,
+            [
+                "alertmanager:config",
+                "grafana:dashboard",
+                "prometheus:rules"
+            ]
+        );
+
+        let rules = registry.get_content("prometheus:rules").unwrap();
+        assert!(rules.contains("#Alert | #Recording"));
+        let alertmanager = registry.get_content("alertmanager:config").unwrap();
+        assert!(alertmanager.contains("routes?:                [...#Route]"));
+        let dashboard = registry.get_content("grafana:dashboard").unwrap();
+        assert!(dashboard.contains("w: int & >=1 & <=24"));
     }
 
     #[test]
@@ -4,16 +4,57 @@
 //! Template generation for compliant configurations
 //!
 //! Generate RSR-compliant configuration structures from templates.
-
-use std::collections::HashMap;
+//!
+//! File contents and paths are Go templates, rendered with
+//! [`crate::utils::gotemplate`] and the variables as `.name`, so templates
+//! can use `default`, conditionals and loops like Helm charts do. Text that
+//! must come out with braces, such as a Helm chart or a GitHub workflow,
+//! goes in a string: ``{{ `{{ .Values.image }}` }}``, `{{ "${{ github.sha }}" }}`.
+//!
+//! Besides the built-in templates, projects can list template repositories
+//! under `templates:` in `.rsr.yaml` or `.conflow.yaml`:
+//!
+//! ```yaml
+//! templates:
+//!   - git: https://github.com/acme/conflow-templates.git
+//!     version: "1.4"
+//!     path: templates
+//!   - path: ../shared/templates
+//! ```
+//!
+//! Git repositories are cloned at the tag `<version>` (or `v<version>`), or
+//! at their default branch without one, and cached in
+//! `~/.conflow/templates/`. A template with the name of an earlier one
+//! replaces it: builtins come first, then the repositories of `.conflow.yaml`
+//! and `.rsr.yaml` in listed order, then the project's `.rsr/templates`.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
 use serde::{Deserialize, Serialize};
 
+use crate::audit;
+use crate::pipeline::Pipeline;
+use crate::rsr::config::{self, CiProvider, RsrConfig};
+use crate::rsr::schemas::{ARGOCD_SCHEMA, FLUX_SCHEMA};
+#[cfg(feature = "native")]
+use crate::utils::git;
+use crate::utils::gotemplate;
 use crate::ConflowError;
 
+/// Project-local templates, relative to the project root
+pub const PROJECT_TEMPLATE_DIR: &str = ".rsr/templates";
+
+/// Environment variable overriding the template repository cache
+pub const TEMPLATE_DIR_ENV: &str = "CONFLOW_TEMPLATE_DIR";
+
+/// Source of the templates shipped with conflow
+pub const BUILTIN: &str = "builtin";
+
 /// Template type
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub enum TemplateType {
     /// Simple CUE validation pipeline
@@ -32,7 +73,12 @@ pub enum TemplateType {
     Helm,
     /// Docker Compose
     DockerCompose,
+    /// ArgoCD and Flux resources
+    Gitops,
+    /// Project documents: README, CHANGELOG, security policy, license
+    Docs,
     /// Custom template
+    #[default]
     Custom,
 }
 
@@ -47,6 +93,8 @@ impl TemplateType {
             Self::Terraform => "terraform",
             Self::Helm => "helm",
             Self::DockerCompose => "docker-compose",
+            Self::Gitops => "gitops",
+            Self::Docs => "docs",
             Self::Custom => "custom",
         }
     }
@@ -61,11 +109,35 @@ impl TemplateType {
             Self::Terraform => "Terraform configuration validation",
             Self::Helm => "Helm chart configuration",
             Self::DockerCompose => "Docker Compose configuration",
+            Self::Gitops => "ArgoCD and Flux resources",
+            Self::Docs => "Project documentation",
             Self::Custom => "Custom template",
         }
     }
 }
 
+impl FromStr for TemplateType {
+    type Err = String;
+
+    /// Parse a template type name, or a short alias such as `k8s-service`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "cue-validation" | "cue" => Self::CueValidation,
+            "nickel-generation" | "nickel" => Self::NickelGeneration,
+            "full-pipeline" | "full" => Self::FullPipeline,
+            "multi-env" => Self::MultiEnv,
+            "kubernetes" | "k8s" | "k8s-service" => Self::Kubernetes,
+            "terraform" | "tf" => Self::Terraform,
+            "helm" => Self::Helm,
+            "docker-compose" | "compose" => Self::DockerCompose,
+            "gitops" | "argocd" | "flux" => Self::Gitops,
+            "docs" | "documentation" => Self::Docs,
+            "custom" => Self::Custom,
+            _ => return Err(format!("Unknown template type: {}", s)),
+        })
+    }
+}
+
 /// Template definition
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Template {
@@ -73,18 +145,25 @@ pub struct Template {
     pub name: String,
 
     /// Template type
+    #[serde(default)]
     pub template_type: TemplateType,
 
     /// Description
     pub description: String,
 
+    /// Template version (default: the version of its repository)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+
     /// Files to generate
     pub files: Vec<TemplateFile>,
 
     /// Directories to create
+    #[serde(default)]
     pub directories: Vec<String>,
 
     /// Variables that can be customized
+    #[serde(default, serialize_with = "crate::report::sorted")]
     pub variables: HashMap<String, TemplateVariable>,
 }
 
@@ -109,6 +188,7 @@ pub struct TemplateVariable {
     pub description: String,
 
     /// Default value
+    #[serde(default)]
     pub default: String,
 
     /// Whether this variable is required
@@ -116,22 +196,195 @@ pub struct TemplateVariable {
     pub required: bool,
 }
 
+/// A template repository listed under `templates:`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TemplateRepo {
+    /// Git repository holding the templates
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git: Option<String>,
+
+    /// Tag to check out (default: the repository's default branch)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+
+    /// Directory of the templates within the git repository, or a local
+    /// directory relative to the project root
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+}
+
+impl TemplateRepo {
+    /// Directory holding the repository's templates
+    ///
+    /// Git repositories are cloned into `cache` unless they are cached
+    /// already, or always with `update`.
+    pub fn fetch(
+        &self,
+        project_root: &Path,
+        cache: &Path,
+        update: bool,
+    ) -> Result<PathBuf, ConflowError> {
+        let Some(url) = &self.git else {
+            return match &self.path {
+                Some(path) => Ok(project_root.join(path)),
+                None => Err(self.error("no source", Some("Set 'git' or 'path'"))),
+            };
+        };
+
+        let version = self.version.as_deref().unwrap_or("HEAD");
+        let safe = !version.is_empty()
+            && !version.starts_with('.')
+            && version
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "._-".contains(c));
+        if !safe {
+            return Err(self.error("invalid version", None));
+        }
+
+        let dest = repo_dir(cache, url, version);
+        self.clone(url, &dest, update).map_err(|e| {
+            self.error(
+                &e.to_string(),
+                Some("Check the git URL and that the repository has a tag for the version"),
+            )
+        })?;
+
+        Ok(match &self.path {
+            Some(path) => dest.join(path),
+            None => dest,
+        })
+    }
+
+    /// Clone the tag, or the default branch, into the cache directory `dest`
+    #[cfg(feature = "native")]
+    fn clone(&self, url: &str, dest: &Path, update: bool) -> Result<(), ConflowError> {
+        git::fetch(url, self.version.as_deref(), dest, update)
+    }
+
+    /// Clone the tag, or the default branch, into the cache directory `dest`
+    #[cfg(not(feature = "native"))]
+    fn clone(&self, _url: &str, _dest: &Path, _update: bool) -> Result<(), ConflowError> {
+        Err(ConflowError::Git {
+            message: "git is not available in this build".into(),
+        })
+    }
+
+    /// Where a git repository is cloned under `cache`
+    pub fn cache_dir(&self, cache: &Path) -> Option<PathBuf> {
+        let url = self.git.as_ref()?;
+        Some(repo_dir(cache, url, self.version.as_deref().unwrap_or("HEAD")))
+    }
+
+    fn error(&self, message: &str, help: Option<&str>) -> ConflowError {
+        ConflowError::Template {
+            template: self.to_string(),
+            message: message.to_string(),
+            help: help.map(String::from),
+        }
+    }
+}
+
+fn repo_dir(cache: &Path, url: &str, version: &str) -> PathBuf {
+    let hash = blake3::hash(url.as_bytes()).to_hex();
+    cache.join(&hash.as_str()[..16]).join(version)
+}
+
+impl fmt::Display for TemplateRepo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.git, &self.path) {
+            (Some(url), _) => {
+                write!(f, "{}", url)?;
+                if let Some(version) = &self.version {
+                    write!(f, "@{}", version)?;
+                }
+                if let Some(path) = &self.path {
+                    write!(f, "#{}", path.display())?;
+                }
+                Ok(())
+            }
+            (None, Some(path)) => write!(f, "{}", path.display()),
+            (None, None) => f.write_str("<empty>"),
+        }
+    }
+}
+
+/// Default template repository cache (`CONFLOW_TEMPLATE_DIR` or `~/.conflow/templates`)
+pub fn default_cache() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os(TEMPLATE_DIR_ENV) {
+        return Some(PathBuf::from(dir));
+    }
+    directories::BaseDirs::new().map(|d| d.home_dir().join(".conflow").join("templates"))
+}
+
+/// A new project for [`TemplateGenerator::scaffold`]
+#[derive(Debug, Clone)]
+pub struct Scaffold {
+    /// Project name, also used as the pipeline and application name
+    pub project_name: String,
+
+    /// RSR tier (1-4, higher is more strict)
+    pub tier: u8,
+
+    /// CI provider to write a workflow for
+    pub ci: Option<CiProvider>,
+}
+
 /// Template generator
 pub struct TemplateGenerator {
-    templates: HashMap<String, Template>,
+    templates: BTreeMap<String, Template>,
+
+    /// Where each template was loaded from, oldest first; the last is in use
+    sources: BTreeMap<String, Vec<String>>,
 }
 
 impl TemplateGenerator {
     /// Create a new template generator
     pub fn new() -> Self {
         let mut generator = Self {
-            templates: HashMap::new(),
+            templates: BTreeMap::new(),
+            sources: BTreeMap::new(),
         };
 
         generator.register_builtin_templates();
+        for name in generator.templates.keys() {
+            generator
+                .sources
+                .insert(name.clone(), vec![BUILTIN.to_string()]);
+        }
         generator
     }
 
+    /// Builtins plus the templates of a project's repositories and `.rsr/templates`
+    ///
+    /// Git repositories are fetched unless they are cached, or always with
+    /// `update`.
+    pub fn for_project(project_root: &Path, update: bool) -> Result<Self, ConflowError> {
+        let mut repos = Vec::new();
+        let pipeline = project_root.join(".conflow.yaml");
+        if pipeline.exists() {
+            repos.extend(Pipeline::from_file(&pipeline)?.templates);
+        }
+        repos.extend(RsrConfig::load_from_project(project_root)?.templates);
+
+        // Only git repositories need the cache
+        let cache = if repos.iter().any(|repo| repo.git.is_some()) {
+            default_cache().ok_or_else(|| ConflowError::Io {
+                message: format!("Could not find a home directory; set {}", TEMPLATE_DIR_ENV),
+            })?
+        } else {
+            PathBuf::new()
+        };
+
+        let mut generator = Self::new();
+        for repo in &repos {
+            generator.load_repo(repo, project_root, &cache, update)?;
+        }
+        generator.load_from_dir(&project_root.join(PROJECT_TEMPLATE_DIR))?;
+
+        Ok(generator)
+    }
+
     /// Register built-in templates
     fn register_builtin_templates(&mut self) {
         // CUE Validation template
@@ -141,6 +394,7 @@ impl TemplateGenerator {
                 name: "cue-validation".into(),
                 template_type: TemplateType::CueValidation,
                 description: "Simple CUE schema validation".into(),
+                version: None,
                 directories: vec!["schemas".into(), "config".into()],
                 files: vec![
                     TemplateFile {
@@ -179,6 +433,7 @@ impl TemplateGenerator {
                 name: "nickel-generation".into(),
                 template_type: TemplateType::NickelGeneration,
                 description: "Programmatic config generation with Nickel".into(),
+                version: None,
                 directories: vec!["nickel".into(), "dist".into()],
                 files: vec![
                     TemplateFile {
@@ -212,6 +467,7 @@ impl TemplateGenerator {
                 name: "full-pipeline".into(),
                 template_type: TemplateType::FullPipeline,
                 description: "Generate, validate, and export pipeline".into(),
+                version: None,
                 directories: vec!["schemas".into(), "nickel".into(), "dist".into()],
                 files: vec![
                     TemplateFile {
@@ -241,6 +497,7 @@ impl TemplateGenerator {
                 name: "multi-env".into(),
                 template_type: TemplateType::MultiEnv,
                 description: "Multi-environment configuration management".into(),
+                version: None,
                 directories: vec![
                     "environments".into(),
                     "schemas".into(),
@@ -284,6 +541,7 @@ impl TemplateGenerator {
                 name: "kubernetes".into(),
                 template_type: TemplateType::Kubernetes,
                 description: "Kubernetes manifest validation".into(),
+                version: None,
                 directories: vec!["k8s".into(), "schemas".into()],
                 files: vec![
                     TemplateFile {
@@ -311,6 +569,38 @@ impl TemplateGenerator {
                             required: true,
                         },
                     ),
+                    (
+                        "namespace".into(),
+                        TemplateVariable {
+                            description: "Namespace (default: none)".into(),
+                            default: String::new(),
+                            required: false,
+                        },
+                    ),
+                    (
+                        "image".into(),
+                        TemplateVariable {
+                            description: "Container image (default: <app_name>:latest)".into(),
+                            default: String::new(),
+                            required: false,
+                        },
+                    ),
+                    (
+                        "replicas".into(),
+                        TemplateVariable {
+                            description: "Replica count".into(),
+                            default: "1".into(),
+                            required: false,
+                        },
+                    ),
+                    (
+                        "ports".into(),
+                        TemplateVariable {
+                            description: "Container ports, comma-separated".into(),
+                            default: "8080".into(),
+                            required: false,
+                        },
+                    ),
                 ]),
             },
         );
@@ -322,6 +612,7 @@ impl TemplateGenerator {
                 name: "terraform".into(),
                 template_type: TemplateType::Terraform,
                 description: "Terraform configuration validation".into(),
+                version: None,
                 directories: vec!["terraform".into(), "schemas".into()],
                 files: vec![
                     TemplateFile {
@@ -346,6 +637,7 @@ impl TemplateGenerator {
                 name: "helm".into(),
                 template_type: TemplateType::Helm,
                 description: "Helm chart configuration".into(),
+                version: None,
                 directories: vec!["chart".into(), "schemas".into()],
                 files: vec![
                     TemplateFile {
@@ -375,6 +667,7 @@ impl TemplateGenerator {
                 name: "docker-compose".into(),
                 template_type: TemplateType::DockerCompose,
                 description: "Docker Compose configuration".into(),
+                version: None,
                 directories: vec!["schemas".into()],
                 files: vec![
                     TemplateFile {
@@ -391,6 +684,171 @@ impl TemplateGenerator {
                 variables: HashMap::new(),
             },
         );
+
+        // GitOps template
+        self.templates.insert(
+            "gitops".into(),
+            Template {
+                name: "gitops".into(),
+                template_type: TemplateType::Gitops,
+                description: "ArgoCD Applications and Flux Kustomizations checked against the repo"
+                    .into(),
+                version: None,
+                directories: vec!["argocd".into(), "clusters".into(), "schemas".into()],
+                files: vec![
+                    TemplateFile {
+                        path: ".conflow.yaml".into(),
+                        content: TEMPLATE_GITOPS_PIPELINE.into(),
+                        overwrite: false,
+                    },
+                    TemplateFile {
+                        path: "schemas/argocd.cue".into(),
+                        content: ARGOCD_SCHEMA.trim_start().into(),
+                        overwrite: false,
+                    },
+                    TemplateFile {
+                        path: "schemas/flux.cue".into(),
+                        content: FLUX_SCHEMA.trim_start().into(),
+                        overwrite: false,
+                    },
+                    TemplateFile {
+                        path: "argocd/{{ .app_name }}.yaml".into(),
+                        content: TEMPLATE_GITOPS_APPLICATION.into(),
+                        overwrite: false,
+                    },
+                    TemplateFile {
+                        path: "apps/{{ .app_name }}/kustomization.yaml".into(),
+                        content: TEMPLATE_GITOPS_KUSTOMIZATION.into(),
+                        overwrite: false,
+                    },
+                    TemplateFile {
+                        path: "clusters/production/source.yaml".into(),
+                        content: TEMPLATE_GITOPS_FLUX_SOURCE.into(),
+                        overwrite: false,
+                    },
+                    TemplateFile {
+                        path: "clusters/production/apps.yaml".into(),
+                        content: TEMPLATE_GITOPS_FLUX_APPS.into(),
+                        overwrite: false,
+                    },
+                ],
+                variables: HashMap::from([
+                    (
+                        "app_name".into(),
+                        TemplateVariable {
+                            description: "Application name".into(),
+                            default: "my-app".into(),
+                            required: true,
+                        },
+                    ),
+                    (
+                        "repo_url".into(),
+                        TemplateVariable {
+                            description: "URL of this repository".into(),
+                            default: "https://github.com/example/gitops.git".into(),
+                            required: false,
+                        },
+                    ),
+                ]),
+            },
+        );
+
+        // Documentation templates, one document each (RSR-DOCS requirements)
+        let project_name = || {
+            (
+                "project_name".to_string(),
+                TemplateVariable {
+                    description: "Project name".into(),
+                    default: "my-project".into(),
+                    required: true,
+                },
+            )
+        };
+        let docs = [
+            (
+                "readme",
+                "README with installation, usage and license sections",
+                "README.md",
+                TEMPLATE_DOCS_README,
+                HashMap::from([
+                    project_name(),
+                    (
+                        "description".into(),
+                        TemplateVariable {
+                            description: "One-line description of the project".into(),
+                            default: String::new(),
+                            required: false,
+                        },
+                    ),
+                ]),
+            ),
+            (
+                "changelog",
+                "CHANGELOG in the Keep a Changelog format",
+                "CHANGELOG.md",
+                TEMPLATE_DOCS_CHANGELOG,
+                HashMap::new(),
+            ),
+            (
+                "security-policy",
+                "SECURITY.md naming where to report vulnerabilities",
+                "SECURITY.md",
+                TEMPLATE_DOCS_SECURITY,
+                HashMap::from([
+                    project_name(),
+                    (
+                        "contact".into(),
+                        TemplateVariable {
+                            description: "Email address or https:// URL for reports".into(),
+                            default: String::new(),
+                            required: true,
+                        },
+                    ),
+                ]),
+            ),
+            (
+                "license-mit",
+                "MIT license with its SPDX identifier",
+                "LICENSE",
+                TEMPLATE_DOCS_LICENSE_MIT,
+                HashMap::from([
+                    (
+                        "holder".into(),
+                        TemplateVariable {
+                            description: "Copyright holder".into(),
+                            default: String::new(),
+                            required: true,
+                        },
+                    ),
+                    (
+                        "year".into(),
+                        TemplateVariable {
+                            description: "Year of first publication".into(),
+                            default: String::new(),
+                            required: true,
+                        },
+                    ),
+                ]),
+            ),
+        ];
+        for (name, description, path, content, variables) in docs {
+            self.templates.insert(
+                name.into(),
+                Template {
+                    name: name.into(),
+                    template_type: TemplateType::Docs,
+                    description: description.into(),
+                    version: None,
+                    directories: vec![],
+                    files: vec![TemplateFile {
+                        path: path.into(),
+                        content: content.into(),
+                        overwrite: false,
+                    }],
+                    variables,
+                },
+            );
+        }
     }
 
     /// Get a template by name
@@ -403,6 +861,65 @@ impl TemplateGenerator {
         self.templates.values()
     }
 
+    /// Render a template's files as (path, content) pairs without writing them
+    ///
+    /// Unset variables take their defaults; a required variable with neither
+    /// a value nor a default is an error.
+    pub fn render(
+        &self,
+        template_name: &str,
+        variables: &HashMap<String, String>,
+    ) -> Result<Vec<(String, String)>, ConflowError> {
+        let template = self.find(template_name)?;
+
+        let mut values: HashMap<String, String> = template
+            .variables
+            .iter()
+            .map(|(name, var)| (name.clone(), var.default.clone()))
+            .collect();
+        values.extend(variables.iter().map(|(k, v)| (k.clone(), v.clone())));
+
+        let mut missing: Vec<_> = template
+            .variables
+            .iter()
+            .filter(|(name, var)| var.required && values[*name].is_empty())
+            .map(|(name, _)| name.as_str())
+            .collect();
+        if !missing.is_empty() {
+            missing.sort();
+            return Err(ConflowError::Template {
+                template: template_name.to_string(),
+                message: format!("required variables not set: {}", missing.join(", ")),
+                help: Some(format!("Pass them with --set {}=<value>", missing[0])),
+            });
+        }
+
+        let data = serde_json::Value::Object(
+            values
+                .iter()
+                .map(|(k, v)| (k.clone(), template_value(v)))
+                .collect(),
+        );
+        template
+            .files
+            .iter()
+            .map(|file| {
+                let render = |source: &str, what: &str| {
+                    gotemplate::render(source, &data)
+                        .map(|out| self.substitute_variables(&out, &values))
+                        .map_err(|e| ConflowError::Template {
+                            template: template_name.to_string(),
+                            message: format!("{} {}", what, e),
+                            help: None,
+                        })
+                };
+                let path = render(&file.path, "path of")?;
+                let content = render(&file.content, &file.path)?;
+                Ok((path, content))
+            })
+            .collect()
+    }
+
     /// Generate template files in target directory
     pub fn generate(
         &self,
@@ -410,13 +927,19 @@ impl TemplateGenerator {
         target_dir: &Path,
         variables: &HashMap<String, String>,
     ) -> Result<GenerationResult, ConflowError> {
-        let template = self.get(template_name).ok_or_else(|| ConflowError::ExecutionFailed {
-            message: format!("Template not found: {}", template_name),
-            help: Some(format!(
-                "Available templates: {}",
-                self.templates.keys().cloned().collect::<Vec<_>>().join(", ")
-            )),
-        })?;
+        self.generate_with(template_name, target_dir, variables, false)
+    }
+
+    /// Generate template files, replacing existing ones if `force` is set
+    pub fn generate_with(
+        &self,
+        template_name: &str,
+        target_dir: &Path,
+        variables: &HashMap<String, String>,
+        force: bool,
+    ) -> Result<GenerationResult, ConflowError> {
+        let template = self.find(template_name)?;
+        let rendered = self.render(template_name, variables)?;
 
         let mut result = GenerationResult {
             template_name: template_name.to_string(),
@@ -435,52 +958,205 @@ impl TemplateGenerator {
         }
 
         // Generate files
-        for file in &template.files {
-            let path = target_dir.join(&file.path);
+        for (file, (rel_path, content)) in template.files.iter().zip(rendered) {
+            let path = target_dir.join(&rel_path);
 
-            if path.exists() && !file.overwrite {
-                result.files_skipped.push(file.path.clone());
+            if path.exists() && !file.overwrite && !force {
+                result.files_skipped.push(rel_path);
                 continue;
             }
 
-            // Apply variable substitution
-            let content = self.substitute_variables(&file.content, variables);
-
             // Create parent directories if needed
             if let Some(parent) = path.parent() {
                 std::fs::create_dir_all(parent)?;
             }
 
-            std::fs::write(&path, content)?;
-            result.files_created.push(file.path.clone());
+            audit::write(target_dir, &path, content, &format!("template {}", template.name))?;
+            result.files_created.push(rel_path);
         }
 
         Ok(result)
     }
 
-    /// Substitute variables in content
+    /// Generate a complete project in `target_dir`
+    ///
+    /// `template_name` is a template or a template type, such as
+    /// `k8s-service`. Besides the template's schemas, example configs and
+    /// pipeline, this writes `.rsr.yaml` for the project's tier and a CI
+    /// workflow. `project_name`, `app_name` and `tier` default to the
+    /// scaffold's values.
+    pub fn scaffold(
+        &self,
+        template_name: &str,
+        target_dir: &Path,
+        scaffold: &Scaffold,
+        variables: &HashMap<String, String>,
+    ) -> Result<GenerationResult, ConflowError> {
+        let name = &scaffold.project_name;
+        let valid = name.starts_with(|c: char| c.is_ascii_lowercase())
+            && name
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+        if !valid {
+            return Err(ConflowError::Template {
+                template: template_name.to_string(),
+                message: format!("invalid project name '{}'", name),
+                help: Some("Use lowercase letters, digits and dashes, e.g. billing-api".into()),
+            });
+        }
+
+        let template = match self
+            .get(template_name)
+            .or_else(|| self.of_type(template_name))
+        {
+            Some(template) => template,
+            None => self.find(template_name)?,
+        };
+
+        let mut values = variables.clone();
+        for key in ["project_name", "app_name"] {
+            values
+                .entry(key.to_string())
+                .or_insert_with(|| name.clone());
+        }
+        values
+            .entry("tier".to_string())
+            .or_insert_with(|| scaffold.tier.to_string());
+
+        // Everything that can fail is checked before the first file is written
+        let mut files = vec![(
+            ".rsr.yaml".to_string(),
+            config::generate_project_config(name, Some(scaffold.tier), scaffold.ci),
+        )];
+        if let Some(ci) = scaffold.ci {
+            let (path, source) = match ci {
+                CiProvider::GitHub => (".github/workflows/conflow.yml", TEMPLATE_CI_GITHUB),
+                CiProvider::GitLab => (".gitlab-ci.yml", TEMPLATE_CI_GITLAB),
+                other => {
+                    return Err(ConflowError::Template {
+                        template: template.name.clone(),
+                        message: format!("no CI workflow template for {:?}", other),
+                        help: Some("Use GitHub or GitLab, or leave out the CI workflow".into()),
+                    })
+                }
+            };
+            let ci_values = serde_json::json!({ "strict": scaffold.tier >= 3 });
+            let content =
+                gotemplate::render(source, &ci_values).map_err(|e| ConflowError::Template {
+                    template: path.to_string(),
+                    message: e.to_string(),
+                    help: None,
+                })?;
+            files.push((path.to_string(), content));
+        }
+
+        let mut result = self.generate_with(&template.name, target_dir, &values, false)?;
+        for (path, content) in files {
+            let target = target_dir.join(&path);
+            if target.exists() {
+                result.files_skipped.push(path);
+                continue;
+            }
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            audit::write(target_dir, &target, content, &format!("template {}", template.name))?;
+            result.files_created.push(path);
+        }
+
+        Ok(result)
+    }
+
+    /// The first template, by name, of the template type called `name`
+    fn of_type(&self, name: &str) -> Option<&Template> {
+        let kind = name.parse::<TemplateType>().ok()?;
+        if kind == TemplateType::Custom {
+            return None;
+        }
+        self.list()
+            .filter(|t| t.template_type == kind)
+            .min_by(|a, b| a.name.cmp(&b.name))
+    }
+
+    fn find(&self, template_name: &str) -> Result<&Template, ConflowError> {
+        self.get(template_name).ok_or_else(|| {
+            let mut names: Vec<_> = self.templates.keys().cloned().collect();
+            names.sort();
+            ConflowError::ExecutionFailed {
+                message: format!("Template not found: {}", template_name),
+                help: Some(format!("Available templates: {}", names.join(", "))),
+            }
+        })
+    }
+
+    /// Substitute `${var}` placeholders in content
     fn substitute_variables(&self, content: &str, variables: &HashMap<String, String>) -> String {
         let mut result = content.to_string();
 
         for (key, value) in variables {
-            let placeholder = format!("{{{{ {} }}}}", key);
+            let placeholder = format!("${{{}}}", key);
             result = result.replace(&placeholder, value);
-
-            // Also support ${var} syntax
-            let alt_placeholder = format!("${{{}}}", key);
-            result = result.replace(&alt_placeholder, value);
         }
 
         result
     }
 
+    /// Where a template was loaded from (`builtin`, a directory or a repository)
+    pub fn source(&self, name: &str) -> Option<&str> {
+        self.sources.get(name)?.last().map(String::as_str)
+    }
+
+    /// Sources of the templates a template replaced, oldest first
+    pub fn overridden(&self, name: &str) -> &[String] {
+        self.sources
+            .get(name)
+            .map(|sources| &sources[..sources.len() - 1])
+            .unwrap_or_default()
+    }
+
     /// Register a custom template
     pub fn register(&mut self, template: Template) {
+        self.register_from(template, "custom");
+    }
+
+    /// Register a template loaded from `source`, replacing any of the same name
+    pub fn register_from(&mut self, template: Template, source: &str) {
+        self.sources
+            .entry(template.name.clone())
+            .or_default()
+            .push(source.to_string());
         self.templates.insert(template.name.clone(), template);
     }
 
+    /// Load the templates of a repository, fetching it into `cache` if needed
+    pub fn load_repo(
+        &mut self,
+        repo: &TemplateRepo,
+        project_root: &Path,
+        cache: &Path,
+        update: bool,
+    ) -> Result<usize, ConflowError> {
+        let dir = repo.fetch(project_root, cache, update)?;
+        if !dir.is_dir() {
+            return Err(repo.error(
+                &format!("{} is not a directory", dir.display()),
+                Some("Check the 'path' of the template repository"),
+            ));
+        }
+        self.load(&dir, &repo.to_string(), repo.version.as_deref())
+    }
+
     /// Load templates from a directory
     pub fn load_from_dir(&mut self, dir: &Path) -> Result<usize, ConflowError> {
+        self.load(dir, &dir.display().to_string(), None)
+    }
+
+    fn load(
+        &mut self,
+        dir: &Path,
+        source: &str,
+        version: Option<&str>,
+    ) -> Result<usize, ConflowError> {
         if !dir.exists() {
             return Ok(0);
         }
@@ -493,13 +1169,15 @@ impl TemplateGenerator {
 
             if path.extension().and_then(|s| s.to_str()) == Some("yaml") {
                 let content = std::fs::read_to_string(&path)?;
-                let template: Template = serde_yaml::from_str(&content).map_err(|e| {
-                    ConflowError::Yaml {
+                let mut template: Template =
+                    serde_yaml::from_str(&content).map_err(|e| ConflowError::Yaml {
                         message: e.to_string(),
-                    }
-                })?;
+                    })?;
+                if template.version.is_none() {
+                    template.version = version.map(String::from);
+                }
 
-                self.templates.insert(template.name.clone(), template);
+                self.register_from(template, source);
                 count += 1;
             }
         }
@@ -523,13 +1201,75 @@ pub struct GenerationResult {
     pub directories_created: Vec<String>,
 }
 
+/// A variable as template data: `true`, `false` and `0` keep their meaning
+/// in conditions, everything else is a string
+fn template_value(value: &str) -> serde_json::Value {
+    match value {
+        "true" => true.into(),
+        "false" => false.into(),
+        "0" => 0.into(),
+        _ => value.into(),
+    }
+}
+
 // Template content strings
 
+const TEMPLATE_CI_GITHUB: &str = r#"# conflow pipeline and RSR compliance
+# Generated by conflow
+
+name: conflow
+
+on:
+  push:
+    branches: [main]
+  pull_request:
+
+jobs:
+  conflow:
+    runs-on: ubuntu-latest
+    steps:
+      - uses: actions/checkout@v4
+        with:
+          fetch-depth: 0
+      - uses: dtolnay/rust-toolchain@stable
+      - run: cargo install conflow --locked
+      # Install the tools the pipeline uses, e.g. pinned under 'toolchain:'
+      # with 'conflow toolchain install'
+      - run: conflow validate
+      - run: conflow run
+      {{- if .strict }}
+      - run: conflow rsr check --fail-on warning
+      {{- else }}
+      - run: conflow rsr check
+      {{- end }}
+"#;
+
+const TEMPLATE_CI_GITLAB: &str = r#"# conflow pipeline and RSR compliance
+# Generated by conflow
+
+conflow:
+  image: rust:latest
+  before_script:
+    - cargo install conflow --locked
+  script:
+    - conflow validate
+    - conflow run --report gl-code-quality-report.json --report-format code-quality
+    {{- if .strict }}
+    - conflow rsr check --fail-on warning
+    {{- else }}
+    - conflow rsr check
+    {{- end }}
+  artifacts:
+    when: always
+    reports:
+      codequality: gl-code-quality-report.json
+"#;
+
 const TEMPLATE_CUE_VALIDATION_PIPELINE: &str = r#"# CUE Validation Pipeline
 # Generated by conflow
 
 version: "1"
-name: {{ project_name }}
+name: {{ .project_name }}
 
 stages:
   - name: validate
@@ -588,7 +1328,7 @@ const TEMPLATE_NICKEL_PIPELINE: &str = r#"# Nickel Generation Pipeline
 # Generated by conflow
 
 version: "1"
-name: {{ project_name }}
+name: {{ .project_name }}
 
 stages:
   - name: generate
@@ -608,7 +1348,7 @@ cache:
 const TEMPLATE_NICKEL_CONFIG: &str = r#"# Configuration in Nickel
 {
   version = "1.0.0",
-  name = "{{ project_name }}",
+  name = "{{ .project_name }}",
 
   # Default settings
   environment = "development",
@@ -635,7 +1375,7 @@ const TEMPLATE_FULL_PIPELINE: &str = r#"# Full Pipeline: Generate, Validate, Exp
 # Generated by conflow
 
 version: "1"
-name: config-pipeline
+name: {{ .project_name | default "config-pipeline" }}
 
 stages:
   # Generate config from Nickel
@@ -678,7 +1418,7 @@ const TEMPLATE_MULTI_ENV_PIPELINE: &str = r#"# Multi-Environment Pipeline
 # Generated by conflow
 
 version: "1"
-name: multi-env-config
+name: {{ .project_name | default "multi-env-config" }}
 
 stages:
   - name: generate-dev
@@ -773,7 +1513,7 @@ const TEMPLATE_K8S_PIPELINE: &str = r#"# Kubernetes Validation Pipeline
 # Generated by conflow
 
 version: "1"
-name: k8s-validation
+name: {{ .project_name | default "k8s-validation" }}
 
 stages:
   - name: validate
@@ -854,25 +1594,32 @@ package k8s
 const TEMPLATE_K8S_DEPLOYMENT: &str = r#"apiVersion: apps/v1
 kind: Deployment
 metadata:
-  name: {{ app_name }}
+  name: {{ .app_name }}
+  {{- if .namespace }}
+  namespace: {{ .namespace }}
+  {{- end }}
   labels:
-    app: {{ app_name }}
+    app: {{ .app_name }}
 spec:
-  replicas: 1
+  replicas: {{ .replicas }}
   selector:
     matchLabels:
-      app: {{ app_name }}
+      app: {{ .app_name }}
   template:
     metadata:
-      name: {{ app_name }}
+      name: {{ .app_name }}
       labels:
-        app: {{ app_name }}
+        app: {{ .app_name }}
     spec:
       containers:
-        - name: {{ app_name }}
-          image: {{ app_name }}:latest
+        - name: {{ .app_name }}
+          image: {{ if .image }}{{ .image }}{{ else }}{{ .app_name }}:latest{{ end }}
+          {{- if .ports }}
           ports:
-            - containerPort: 8080
+          {{- range splitList "," .ports }}
+            - containerPort: {{ trim . }}
+          {{- end }}
+          {{- end }}
           resources:
             limits:
               cpu: "100m"
@@ -886,7 +1633,7 @@ const TEMPLATE_TERRAFORM_PIPELINE: &str = r#"# Terraform Validation Pipeline
 # Generated by conflow
 
 version: "1"
-name: terraform-validation
+name: {{ .project_name | default "terraform-validation" }}
 
 stages:
   - name: validate-vars
@@ -929,7 +1676,7 @@ const TEMPLATE_HELM_PIPELINE: &str = r#"# Helm Values Validation Pipeline
 # Generated by conflow
 
 version: "1"
-name: helm-validation
+name: {{ .project_name | default "helm-validation" }}
 
 stages:
   - name: validate-values
@@ -1006,7 +1753,7 @@ const TEMPLATE_COMPOSE_PIPELINE: &str = r#"# Docker Compose Validation Pipeline
 # Generated by conflow
 
 version: "1"
-name: compose-validation
+name: {{ .project_name | default "compose-validation" }}
 
 stages:
   - name: validate
@@ -1062,6 +1809,188 @@ package compose
 }
 "#;
 
+const TEMPLATE_GITOPS_PIPELINE: &str = r#"# GitOps Validation Pipeline
+# Generated by conflow
+
+version: "1"
+name: {{ .project_name | default "gitops-validation" }}
+
+stages:
+  - name: validate-argocd
+    tool:
+      type: cue
+      command: vet
+      schemas:
+        - schemas/argocd.cue
+    input:
+      - "argocd/**/*.yaml"
+    description: Validate ArgoCD Applications and AppProjects
+
+  - name: validate-flux
+    tool:
+      type: cue
+      command: vet
+      schemas:
+        - schemas/flux.cue
+    input:
+      - "clusters/**/*.yaml"
+    description: Validate Flux Kustomizations, HelmReleases and sources
+
+# Resources must point at things that exist in this repository
+refs:
+  # Applications deploying this repository name a directory in it
+  - name: argocd-source-path
+    from:
+      files: "argocd/**/*.yaml"
+      path: spec.source.path
+      where: { kind: Application }
+    to: { exists: directory, repo: spec.source.repoURL }
+
+  - name: flux-kustomization-path
+    from:
+      files: "clusters/**/*.yaml"
+      path: spec.path
+      where: { kind: Kustomization }
+    to: { exists: directory }
+
+  - name: flux-source-ref
+    from:
+      files: "clusters/**/*.yaml"
+      path: spec.sourceRef.name
+      where: { kind: Kustomization, spec.sourceRef.kind: GitRepository }
+    to:
+      files: "clusters/**/*.yaml"
+      path: metadata.name
+      where: { kind: GitRepository }
+
+  - name: flux-helmrelease-chart
+    from:
+      files: "clusters/**/*.yaml"
+      path: spec.chart.spec.chart
+      where: { kind: HelmRelease, spec.chart.spec.sourceRef.kind: GitRepository }
+    to: { exists: directory }
+
+cache:
+  enabled: true
+  directory: .conflow-cache
+"#;
+
+const TEMPLATE_GITOPS_APPLICATION: &str = r#"apiVersion: argoproj.io/v1alpha1
+kind: Application
+metadata:
+  name: {{ .app_name }}
+  namespace: argocd
+spec:
+  project: default
+  source:
+    repoURL: {{ .repo_url }}
+    targetRevision: HEAD
+    path: apps/{{ .app_name }}
+  destination:
+    server: https://kubernetes.default.svc
+    namespace: {{ .app_name }}
+  syncPolicy:
+    automated:
+      prune: true
+      selfHeal: true
+"#;
+
+const TEMPLATE_GITOPS_KUSTOMIZATION: &str = r#"apiVersion: kustomize.config.k8s.io/v1beta1
+kind: Kustomization
+namespace: {{ .app_name }}
+resources: []
+"#;
+
+const TEMPLATE_GITOPS_FLUX_SOURCE: &str = r#"apiVersion: source.toolkit.fluxcd.io/v1
+kind: GitRepository
+metadata:
+  name: {{ .project_name | default "gitops" }}
+  namespace: flux-system
+spec:
+  interval: 1m
+  url: {{ .repo_url }}
+  ref:
+    branch: main
+"#;
+
+const TEMPLATE_GITOPS_FLUX_APPS: &str = r#"apiVersion: kustomize.toolkit.fluxcd.io/v1
+kind: Kustomization
+metadata:
+  name: {{ .app_name }}
+  namespace: flux-system
+spec:
+  interval: 10m
+  path: ./apps/{{ .app_name }}
+  prune: true
+  sourceRef:
+    kind: GitRepository
+    name: {{ .project_name | default "gitops" }}
+"#;
+
+const TEMPLATE_DOCS_README: &str = r#"# {{ .project_name }}
+
+{{ .description }}
+
+## Installation
+
+TODO
+
+## Usage
+
+TODO
+
+## License
+
+See [LICENSE](LICENSE).
+"#;
+
+const TEMPLATE_DOCS_CHANGELOG: &str = r#"# Changelog
+
+All notable changes to this project are documented in this file.
+
+The format is based on [Keep a Changelog](https://keepachangelog.com/en/1.1.0/),
+and this project adheres to [Semantic Versioning](https://semver.org/spec/v2.0.0.html).
+
+## [Unreleased]
+"#;
+
+const TEMPLATE_DOCS_SECURITY: &str = r#"# Security Policy
+
+## Supported Versions
+
+Security fixes are made to the latest release of {{ .project_name }}.
+
+## Reporting a Vulnerability
+
+Please do not report vulnerabilities in public issues. Report them privately
+to {{ .contact }}, with the steps to reproduce and the versions affected.
+"#;
+
+const TEMPLATE_DOCS_LICENSE_MIT: &str = r#"SPDX-License-Identifier: MIT
+
+MIT License
+
+Copyright (c) {{ .year }} {{ .holder }}
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+"#;
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1116,4 +2045,278 @@ mod tests {
         let content = std::fs::read_to_string(temp.path().join("k8s/deployment.yaml")).unwrap();
         assert!(content.contains("my-app"));
     }
+
+    #[test]
+    fn test_generate_gitops_template() {
+        let temp = TempDir::new().unwrap();
+        let generator = TemplateGenerator::new();
+
+        let variables = HashMap::from([("app_name".to_string(), "web".to_string())]);
+        assert_eq!("argocd".parse(), Ok(TemplateType::Gitops));
+        generator
+            .generate("gitops", temp.path(), &variables)
+            .unwrap();
+        assert!(temp.path().join("argocd/web.yaml").exists());
+        assert!(temp.path().join("schemas/flux.cue").exists());
+
+        let pipeline = Pipeline::from_file(&temp.path().join(".conflow.yaml")).unwrap();
+        assert!(crate::pipeline::PipelineValidator::validate(&pipeline)
+            .unwrap()
+            .is_valid());
+        let broken = crate::pipeline::check_refs(&pipeline, temp.path()).unwrap();
+        assert!(broken.is_empty(), "{:?}", broken);
+
+        std::fs::remove_dir_all(temp.path().join("apps/web")).unwrap();
+        let broken = crate::pipeline::check_refs(&pipeline, temp.path()).unwrap();
+        let rules: Vec<_> = broken.iter().map(|b| b.rule.as_str()).collect();
+        assert_eq!(rules, vec!["flux-kustomization-path"]);
+
+        // Applications are only checked once the repository is theirs
+        let git = |args: &[&str]| {
+            std::process::Command::new("git")
+                .args(args)
+                .current_dir(temp.path())
+                .output()
+                .is_ok_and(|o| o.status.success())
+        };
+        let url = "git@github.com:example/gitops.git";
+        if !(git(&["init", "-q"]) && git(&["remote", "add", "origin", url])) {
+            // Skip if git is not installed
+            return;
+        }
+        let broken = crate::pipeline::check_refs(&pipeline, temp.path()).unwrap();
+        let rules: Vec<_> = broken.iter().map(|b| b.rule.as_str()).collect();
+        assert_eq!(rules, vec!["argocd-source-path", "flux-kustomization-path"]);
+    }
+
+    #[test]
+    fn test_render_parameters() {
+        let generator = TemplateGenerator::new();
+        let deployment = |pairs: &[(&str, &str)]| {
+            let variables = pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect();
+            let files = generator.render("kubernetes", &variables).unwrap();
+            let (_, content) = files
+                .into_iter()
+                .find(|(path, _)| path == "k8s/deployment.yaml")
+                .unwrap();
+            serde_yaml::from_str::<serde_yaml::Value>(&content).unwrap()
+        };
+
+        let defaults = deployment(&[]);
+        assert!(defaults["metadata"].get("namespace").is_none());
+        assert_eq!(defaults["spec"]["replicas"], 1);
+        let container = &defaults["spec"]["template"]["spec"]["containers"][0];
+        assert_eq!(container["image"], "my-app:latest");
+        assert_eq!(container["ports"][0]["containerPort"], 8080);
+
+        let custom = deployment(&[
+            ("app_name", "api"),
+            ("namespace", "prod"),
+            ("replicas", "3"),
+            ("image", "ghcr.io/acme/api:1.2"),
+            ("ports", "80,443"),
+        ]);
+        assert_eq!(custom["metadata"]["namespace"], "prod");
+        assert_eq!(custom["spec"]["replicas"], 3);
+        let container = &custom["spec"]["template"]["spec"]["containers"][0];
+        assert_eq!(container["image"], "ghcr.io/acme/api:1.2");
+        assert_eq!(container["ports"][1]["containerPort"], 443);
+
+        let no_ports = deployment(&[("ports", "")]);
+        assert!(no_ports["spec"]["template"]["spec"]["containers"][0]
+            .get("ports")
+            .is_none());
+    }
+
+    #[test]
+    fn test_project_template() {
+        let temp = TempDir::new().unwrap();
+        let dir = temp.path().join(PROJECT_TEMPLATE_DIR);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("service.yaml"),
+            r#"
+name: service
+description: Service manifest
+variables:
+  service: { description: Service name, required: true }
+files:
+  - path: "services/{{ .service }}.yaml"
+    content: |
+      name: {{ .service }}
+      {{- if .public }}
+      ingress: true
+      {{- end }}
+      image: {{ `{{ .Values.image }}` }}
+      sha: {{ "${{ github.sha }}" }}
+"#,
+        )
+        .unwrap();
+
+        let mut generator = TemplateGenerator::new();
+        assert_eq!(generator.load_from_dir(&dir).unwrap(), 1);
+
+        let err = generator.render("service", &HashMap::new()).unwrap_err();
+        assert!(matches!(err, ConflowError::Template { .. }));
+
+        let variables = HashMap::from([
+            ("service".to_string(), "billing".to_string()),
+            ("public".to_string(), "true".to_string()),
+        ]);
+        generator
+            .generate("service", temp.path(), &variables)
+            .unwrap();
+        let content = std::fs::read_to_string(temp.path().join("services/billing.yaml")).unwrap();
+        assert_eq!(
+            content,
+            "name: billing\ningress: true\nimage: {{ .Values.image }}\nsha: ${{ github.sha }}\n"
+        );
+
+        for public in ["false", "0", ""] {
+            let variables = HashMap::from([
+                ("service".to_string(), "billing".to_string()),
+                ("public".to_string(), public.to_string()),
+            ]);
+            let (_, content) = generator.render("service", &variables).unwrap().remove(0);
+            assert!(content.starts_with("name: billing\nimage:"), "{:?}", public);
+        }
+    }
+
+    const REPO_TEMPLATE: &str = r#"
+name: kubernetes
+description: ACME Kubernetes service
+files:
+  - path: deploy.yaml
+    content: 'app: {{ .app | default "acme" }}'
+"#;
+
+    #[test]
+    fn test_local_template_repo_overrides_builtin() {
+        let temp = TempDir::new().unwrap();
+        let shared = temp.path().join("shared");
+        std::fs::create_dir_all(&shared).unwrap();
+        std::fs::write(shared.join("kubernetes.yaml"), REPO_TEMPLATE).unwrap();
+        std::fs::write(
+            temp.path().join(".rsr.yaml"),
+            "templates: [{ path: shared }]\n",
+        )
+        .unwrap();
+
+        let generator = TemplateGenerator::for_project(temp.path(), false).unwrap();
+        assert_eq!(
+            generator.get("kubernetes").unwrap().description,
+            "ACME Kubernetes service"
+        );
+        assert_eq!(generator.source("kubernetes"), Some("shared"));
+        assert_eq!(generator.overridden("kubernetes"), [BUILTIN]);
+        assert_eq!(generator.source("helm"), Some(BUILTIN));
+        assert!(generator.overridden("helm").is_empty());
+
+        // Project templates come last
+        let project = temp.path().join(PROJECT_TEMPLATE_DIR);
+        std::fs::create_dir_all(&project).unwrap();
+        std::fs::write(project.join("kubernetes.yaml"), REPO_TEMPLATE).unwrap();
+        let generator = TemplateGenerator::for_project(temp.path(), false).unwrap();
+        assert_eq!(generator.overridden("kubernetes"), [BUILTIN, "shared"]);
+
+        std::fs::write(
+            temp.path().join(".rsr.yaml"),
+            "templates: [{ path: missing }]\n",
+        )
+        .unwrap();
+        assert!(TemplateGenerator::for_project(temp.path(), false).is_err());
+    }
+
+    #[test]
+    fn test_git_template_repo() {
+        let files = [("templates/kubernetes.yaml", REPO_TEMPLATE)];
+        let Some(repo) = git::test_repo(&files, Some("v1.2")) else {
+            return; // git unavailable
+        };
+
+        let project = TempDir::new().unwrap();
+        let cache = TempDir::new().unwrap();
+        let source = TemplateRepo {
+            git: Some(repo.path().display().to_string()),
+            version: Some("1.2".into()),
+            path: Some("templates".into()),
+        };
+
+        let mut generator = TemplateGenerator::new();
+        let loaded = generator
+            .load_repo(&source, project.path(), cache.path(), false)
+            .unwrap();
+        assert_eq!(loaded, 1);
+        let template = generator.get("kubernetes").unwrap();
+        assert_eq!(template.version.as_deref(), Some("1.2"));
+        assert_eq!(
+            generator.source("kubernetes"),
+            Some(source.to_string().as_str())
+        );
+
+        let unknown = TemplateRepo {
+            version: Some("9.9".into()),
+            ..source
+        };
+        assert!(unknown.fetch(project.path(), cache.path(), false).is_err());
+    }
+
+    #[test]
+    fn test_scaffold() {
+        let temp = TempDir::new().unwrap();
+        let generator = TemplateGenerator::new();
+        let scaffold = Scaffold {
+            project_name: "billing-api".into(),
+            tier: 3,
+            ci: Some(CiProvider::GitHub),
+        };
+
+        let result = generator
+            .scaffold("k8s-service", temp.path(), &scaffold, &HashMap::new())
+            .unwrap();
+        assert_eq!(result.template_name, "kubernetes");
+        for path in [
+            ".conflow.yaml",
+            ".rsr.yaml",
+            ".github/workflows/conflow.yml",
+            "schemas/k8s.cue",
+            "k8s/deployment.yaml",
+        ] {
+            assert!(result.files_created.iter().any(|p| p == path), "{}", path);
+        }
+
+        let pipeline = Pipeline::from_file(&temp.path().join(".conflow.yaml")).unwrap();
+        assert_eq!(pipeline.name, "billing-api");
+        let config = RsrConfig::load_from_project(temp.path()).unwrap();
+        assert_eq!(config.project.tier, Some(3));
+        assert_eq!(config.integrations.ci.provider, Some(CiProvider::GitHub));
+        let workflow =
+            std::fs::read_to_string(temp.path().join(".github/workflows/conflow.yml")).unwrap();
+        assert!(workflow.contains("conflow rsr check --fail-on warning"));
+
+        let invalid = Scaffold {
+            project_name: "Billing API".into(),
+            ..scaffold.clone()
+        };
+        assert!(generator
+            .scaffold("kubernetes", temp.path(), &invalid, &HashMap::new())
+            .is_err());
+        assert!(generator
+            .scaffold("no-such-type", temp.path(), &scaffold, &HashMap::new())
+            .is_err());
+
+        // An unsupported CI provider is rejected before anything is written
+        let empty = TempDir::new().unwrap();
+        let jenkins = Scaffold {
+            ci: Some(CiProvider::Jenkins),
+            ..scaffold.clone()
+        };
+        assert!(generator
+            .scaffold("kubernetes", empty.path(), &jenkins, &HashMap::new())
+            .is_err());
+        assert_eq!(std::fs::read_dir(empty.path()).unwrap().count(), 0);
+    }
 }
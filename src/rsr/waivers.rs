@@ -0,0 +1,428 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! RSR waivers
+//!
+//! A waiver accepts that a requirement fails, for a reason, with the name of
+//! whoever approved it and until an expiry date. `conflow rsr waive` appends
+//! waivers to `.rsr/waivers.yaml`; exceptions listed in `.rsr.yaml` are
+//! treated as waivers too. Exceptions written before approvers existed,
+//! without `approved_by`, still apply, with a detail asking for one.
+//!
+//! When `CONFLOW_WAIVER_KEY` is set, waivers are signed with a keyed BLAKE3
+//! hash of their fields, each length-prefixed so that no two waivers share a
+//! payload, and a missing or mismatched signature makes a waiver unapproved.
+//! Keep the key in CI secrets so that waivers can only be granted by someone
+//! who holds it.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::config::{ComplianceException, RsrConfig};
+use super::diff::now_rfc3339;
+use crate::audit;
+use crate::errors::ConflowError;
+use crate::utils::date;
+
+/// Waiver file, relative to the project root
+pub const WAIVER_FILE: &str = ".rsr/waivers.yaml";
+
+/// Environment variable holding the waiver signing key
+pub const WAIVER_KEY_ENV: &str = "CONFLOW_WAIVER_KEY";
+
+/// An accepted failure of a requirement
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Waiver {
+    /// Requirement ID
+    pub requirement: String,
+
+    /// Why the failure is accepted
+    pub reason: String,
+
+    /// Who approved the waiver
+    pub approver: Option<String>,
+
+    /// Date the waiver stops applying (`YYYY-MM-DD`, optionally followed by
+    /// a time, which is ignored)
+    pub expires: Option<String>,
+
+    /// When the waiver was granted
+    pub created: Option<String>,
+
+    /// Keyed BLAKE3 hash of the fields above
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+}
+
+impl Waiver {
+    /// The fields covered by the signature
+    ///
+    /// Each field is a presence byte followed by its length and content, so
+    /// a field containing a separator cannot shift text into its neighbour.
+    fn payload(&self) -> Vec<u8> {
+        let mut payload = Vec::new();
+        for field in [
+            Some(self.requirement.as_str()),
+            Some(self.reason.as_str()),
+            self.approver.as_deref(),
+            self.expires.as_deref(),
+            self.created.as_deref(),
+        ] {
+            match field {
+                Some(value) => {
+                    payload.push(1);
+                    payload.extend_from_slice(&(value.len() as u64).to_le_bytes());
+                    payload.extend_from_slice(value.as_bytes());
+                }
+                None => payload.push(0),
+            }
+        }
+        payload
+    }
+
+    fn digest(&self, key: &str) -> String {
+        let key = blake3::derive_key("conflow rsr waiver v2", key.as_bytes());
+        blake3::keyed_hash(&key, &self.payload())
+            .to_hex()
+            .to_string()
+    }
+
+    /// Sign the waiver with `key`
+    pub fn sign(&mut self, key: &str) {
+        self.signature = Some(self.digest(key));
+    }
+
+    /// Whether the waiver was signed with `key` and not changed since
+    pub fn verify(&self, key: &str) -> bool {
+        self.signature.as_deref() == Some(self.digest(key).as_str())
+    }
+
+    /// Evaluate the waiver at time `now`, checking signatures if `key` is set
+    ///
+    /// A waiver stops applying on its expiry date.
+    pub fn status(&self, now: &str, key: Option<&str>) -> WaiverStatus {
+        self.evaluate(now, key, true)
+    }
+
+    fn evaluate(&self, now: &str, key: Option<&str>, needs_approver: bool) -> WaiverStatus {
+        let unapproved = needs_approver && self.approver.as_deref().is_none_or(str::is_empty);
+        let (state, detail) = if unapproved {
+            (WaiverState::Unapproved, Some("no approver".to_string()))
+        } else if let Some(expires) = &self.expires {
+            match expired(expires, now) {
+                None => (
+                    WaiverState::Unapproved,
+                    Some(format!("invalid expiry '{}'", expires)),
+                ),
+                Some(true) => (WaiverState::Expired, Some(format!("expired {}", expires))),
+                Some(false) => self.signature_state(key),
+            }
+        } else {
+            self.signature_state(key)
+        };
+
+        WaiverStatus {
+            waiver: self.clone(),
+            state,
+            verified: state == WaiverState::Active && key.is_some(),
+            detail,
+        }
+    }
+
+    fn signature_state(&self, key: Option<&str>) -> (WaiverState, Option<String>) {
+        match key {
+            Some(_) if self.signature.is_none() => {
+                (WaiverState::Unapproved, Some("not signed".to_string()))
+            }
+            Some(key) if !self.verify(key) => (
+                WaiverState::Unapproved,
+                Some("signature does not match".to_string()),
+            ),
+            _ => (WaiverState::Active, None),
+        }
+    }
+}
+
+impl From<&ComplianceException> for Waiver {
+    fn from(exception: &ComplianceException) -> Self {
+        Self {
+            requirement: exception.requirement.clone(),
+            reason: exception.reason.clone(),
+            approver: exception.approved_by.clone(),
+            expires: exception.expires.clone(),
+            created: None,
+            signature: None,
+        }
+    }
+}
+
+/// Whether a waiver applies
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WaiverState {
+    /// Approved and not expired
+    Active,
+    /// Past its expiry date
+    Expired,
+    /// Missing an approver or a valid signature
+    Unapproved,
+}
+
+impl fmt::Display for WaiverState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            WaiverState::Active => "active",
+            WaiverState::Expired => "expired",
+            WaiverState::Unapproved => "unapproved",
+        })
+    }
+}
+
+/// A waiver and whether it applies
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WaiverStatus {
+    pub waiver: Waiver,
+    pub state: WaiverState,
+    /// Whether the signature was checked against `CONFLOW_WAIVER_KEY`
+    #[serde(default)]
+    pub verified: bool,
+    /// Why the waiver does not apply
+    #[serde(default)]
+    pub detail: Option<String>,
+}
+
+impl WaiverStatus {
+    pub fn is_active(&self) -> bool {
+        self.state == WaiverState::Active
+    }
+}
+
+/// The signing key from `CONFLOW_WAIVER_KEY`, if set
+pub fn signing_key() -> Option<String> {
+    std::env::var(WAIVER_KEY_ENV).ok().filter(|k| !k.is_empty())
+}
+
+/// Path of the waiver file in a project
+pub fn waiver_path(project_root: &Path) -> PathBuf {
+    project_root.join(WAIVER_FILE)
+}
+
+/// Waivers recorded in the project's waiver file
+pub fn load(project_root: &Path) -> Result<Vec<Waiver>, ConflowError> {
+    let path = waiver_path(project_root);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = std::fs::read_to_string(&path)?;
+    crate::utils::yaml::parse(&content, &path.display().to_string())
+}
+
+/// Write the project's waiver file
+pub fn save(project_root: &Path, waivers: &[Waiver]) -> Result<(), ConflowError> {
+    let path = waiver_path(project_root);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let content = serde_yaml::to_string(waivers).map_err(|e| ConflowError::Yaml {
+        message: e.to_string(),
+    })?;
+    audit::write(project_root, &path, content, "waive")?;
+    Ok(())
+}
+
+/// Every waiver that applies to a project, evaluated now
+///
+/// Covers the waiver file and the exceptions in `.rsr.yaml`.
+pub fn project_waivers(
+    project_root: &Path,
+    config: &RsrConfig,
+) -> Result<Vec<WaiverStatus>, ConflowError> {
+    let now = now_rfc3339();
+    let key = signing_key();
+    let exceptions = config.compliance.exceptions.iter().map(|exception| {
+        let waiver = Waiver::from(exception);
+        if waiver.approver.is_some() {
+            return waiver.status(&now, key.as_deref());
+        }
+        // Grandfathered: exceptions predate approvers
+        let mut status = waiver.evaluate(&now, key.as_deref(), false);
+        if status.is_active() {
+            status.detail = Some("no approved_by in .rsr.yaml".to_string());
+        }
+        status
+    });
+
+    let mut statuses: Vec<_> = load(project_root)?
+        .into_iter()
+        .map(|waiver| waiver.status(&now, key.as_deref()))
+        .collect();
+    statuses.extend(exceptions);
+    Ok(statuses)
+}
+
+/// Days from 1970-01-01 to the date an expiry or timestamp starts with
+///
+/// Accepts `YYYY-MM-DD` on its own or followed by `T` and a time.
+pub fn expiry_day(value: &str) -> Option<i64> {
+    let (day, time) = (value.get(..10)?, &value[10..]);
+    if !time.is_empty() && !time.starts_with('T') {
+        return None;
+    }
+    date::parse_date(day)
+}
+
+/// Whether an expiry has been reached at time `now`
+///
+/// Returns `None` when either is not a valid date.
+pub fn expired(expires: &str, now: &str) -> Option<bool> {
+    Some(expiry_day(expires)? <= expiry_day(now)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const NOW: &str = "2026-06-01T12:00:00Z";
+
+    fn waiver() -> Waiver {
+        Waiver {
+            requirement: "RSR-CONFIG-003".into(),
+            reason: "Single environment".into(),
+            approver: Some("alex".into()),
+            expires: Some("2026-12-31".into()),
+            created: Some("2026-05-01T09:00:00Z".into()),
+            signature: None,
+        }
+    }
+
+    #[test]
+    fn test_waiver_states() {
+        assert_eq!(waiver().status(NOW, None).state, WaiverState::Active);
+
+        let expired = Waiver {
+            expires: Some("2026-01-31".into()),
+            ..waiver()
+        };
+        assert_eq!(expired.status(NOW, None).state, WaiverState::Expired);
+
+        let unapproved = Waiver {
+            approver: None,
+            ..waiver()
+        };
+        let status = unapproved.status(NOW, None);
+        assert_eq!(status.state, WaiverState::Unapproved);
+        assert_eq!(status.detail.as_deref(), Some("no approver"));
+
+        for expires in ["next year", "2026-02-30", "2026-12-31 noon"] {
+            let invalid = Waiver {
+                expires: Some(expires.into()),
+                ..waiver()
+            };
+            assert_eq!(invalid.status(NOW, None).state, WaiverState::Unapproved);
+        }
+
+        // Expiry is by date: a waiver ends on the day it expires
+        let today = Waiver {
+            expires: Some("2026-06-01T23:59:59Z".into()),
+            ..waiver()
+        };
+        assert_eq!(today.status(NOW, None).state, WaiverState::Expired);
+        assert_eq!(super::expired("2026-06-02", NOW), Some(false));
+    }
+
+    #[test]
+    fn test_waiver_signature() {
+        let mut signed = waiver();
+        signed.sign("secret");
+        assert!(signed.verify("secret"));
+
+        let status = signed.status(NOW, Some("secret"));
+        assert!(status.is_active());
+        assert!(status.verified);
+
+        // Unsigned or tampered waivers are unapproved once a key is configured
+        assert_eq!(
+            waiver().status(NOW, Some("secret")).state,
+            WaiverState::Unapproved
+        );
+        let tampered = Waiver {
+            expires: Some("2099-12-31".into()),
+            ..signed.clone()
+        };
+        assert_eq!(
+            tampered.status(NOW, Some("secret")).state,
+            WaiverState::Unapproved
+        );
+        assert_eq!(
+            signed.status(NOW, Some("other")).state,
+            WaiverState::Unapproved
+        );
+
+        // Moving text across a field boundary changes the signature
+        let shifted = Waiver {
+            requirement: "RSR-CONFIG-003\nSingle".into(),
+            reason: "environment".into(),
+            ..signed.clone()
+        };
+        assert!(!shifted.verify("secret"));
+        let empty_approver = Waiver {
+            approver: Some(String::new()),
+            created: None,
+            ..waiver()
+        };
+        let no_approver = Waiver {
+            approver: None,
+            created: Some(String::new()),
+            ..waiver()
+        };
+        assert_ne!(empty_approver.payload(), no_approver.payload());
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let temp = TempDir::new().unwrap();
+        assert!(load(temp.path()).unwrap().is_empty());
+
+        save(temp.path(), &[waiver()]).unwrap();
+        assert_eq!(load(temp.path()).unwrap(), [waiver()]);
+    }
+
+    #[test]
+    fn test_legacy_exceptions() {
+        let temp = TempDir::new().unwrap();
+        let config: RsrConfig = serde_yaml::from_str(
+            r#"
+compliance:
+  exceptions:
+    - { requirement: RSR-001, reason: Legacy }
+    - { requirement: RSR-002, reason: Legacy, expires: "2020-01-01" }
+    - { requirement: RSR-003, reason: Approved, approved_by: alex }
+"#,
+        )
+        .unwrap();
+        let statuses = project_waivers(temp.path(), &config).unwrap();
+        let states: Vec<_> = statuses.iter().map(|s| s.state).collect();
+        assert_eq!(
+            states,
+            [
+                WaiverState::Active,
+                WaiverState::Expired,
+                WaiverState::Active
+            ]
+        );
+        assert_eq!(
+            statuses[0].detail.as_deref(),
+            Some("no approved_by in .rsr.yaml")
+        );
+        assert_eq!(statuses[2].detail, None);
+
+        // Unsigned once a key is configured, like any other waiver
+        let legacy = Waiver::from(&config.compliance.exceptions[0]);
+        let status = legacy.evaluate(NOW, Some("secret"), false);
+        assert_eq!(status.state, WaiverState::Unapproved);
+    }
+}
@@ -0,0 +1,810 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! REST API
+//!
+//! HTTP/1.1 server behind `conflow serve`, so developer portals and other
+//! services can validate, analyze and check configuration without installing
+//! the CLI. All endpoints except `/v1/health` require an API key, sent as
+//! `Authorization: Bearer <key>` or `X-API-Key: <key>`.
+//!
+//! | Endpoint                    | Body                                   |
+//! |-----------------------------|----------------------------------------|
+//! | `GET /v1/health`            |                                        |
+//! | `POST /v1/validate`         | pipeline YAML                          |
+//! | `POST /v1/analyze?filename=`| configuration file content             |
+//! | `POST /v1/compliance`       | `{"files": {"README.md": "...", ...}}` |
+//! | `GET /v1/schemas`           |                                        |
+//! | `GET /v1/schemas/{id}`      |                                        |
+//! | `GET /v1/projects`          |                                        |
+//! | `GET /v1/projects/{name}`   |                                        |
+//! | `GET /metrics`              |                                        |
+//!
+//! Responses are JSON; analyses and compliance reports use the
+//! [`crate::report`] format. Errors are `{"error": "..."}` with a 4xx or 5xx
+//! status. Compliance checks only run the built-in requirements: uploaded
+//! `.rsr.yaml` files could otherwise run shell checks on the server. Only
+//! the file name of `filename` is used, so analysis never reads the server's
+//! own files, and uploaded projects go to a private temporary directory.
+//!
+//! `/v1/projects` lists the projects checked on a schedule (see
+//! [`crate::rsr::schedule`]) with their latest result, and
+//! `/v1/projects/{name}` returns one project's compliance history.
+//!
+//! `/metrics` renders the server's [`Metrics`] registry in the Prometheus
+//! text format: compliance checks made over the API and on schedule. It needs
+//! an API key like the other endpoints; Prometheus sends one with
+//! `authorization: { credentials: ... }` in the scrape config.
+
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::Deserialize;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::analyzer::ConfigAnalyzer;
+use crate::errors::ConflowError;
+use crate::pipeline::PipelineValidator;
+use crate::rsr::diff::rfc3339;
+use crate::rsr::schedule::ScheduledProject;
+use crate::rsr::{ComplianceChecker, RsrSchemaRegistry};
+use crate::telemetry::Metrics;
+
+/// Default limit on request bodies, in bytes
+pub const DEFAULT_MAX_BODY: usize = 1024 * 1024;
+
+/// Limit on the request line and headers, in bytes
+const MAX_HEAD: usize = 16 * 1024;
+
+/// Default limit on connections handled at once; more wait to be accepted
+pub const DEFAULT_MAX_CONNECTIONS: usize = 64;
+
+/// How long a client may take to send its request
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Server settings
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    /// Accepted API keys; empty disables authentication
+    pub api_keys: Vec<String>,
+
+    /// Keys remote agents register with the gRPC coordinator by; empty
+    /// refuses registrations
+    pub agent_keys: Vec<String>,
+
+    /// Largest accepted request body, in bytes
+    pub max_body: usize,
+
+    /// Connections handled at once
+    pub max_connections: usize,
+
+    /// Projects checked on a schedule
+    pub projects: Vec<ScheduledProject>,
+
+    /// Registry served on `/metrics`
+    pub metrics: Arc<Metrics>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            api_keys: Vec::new(),
+            agent_keys: Vec::new(),
+            max_body: DEFAULT_MAX_BODY,
+            max_connections: DEFAULT_MAX_CONNECTIONS,
+            projects: Vec::new(),
+            metrics: Arc::new(Metrics::new()),
+        }
+    }
+}
+
+/// A parsed HTTP request
+#[derive(Debug, Default)]
+struct Request {
+    method: String,
+    path: String,
+    query: String,
+    /// Header names are lowercased
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl Request {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    fn query_param(&self, name: &str) -> Option<String> {
+        self.query.split('&').find_map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (key == name).then(|| percent_decode(value))
+        })
+    }
+}
+
+/// An HTTP response
+#[derive(Debug)]
+struct Response {
+    status: u16,
+    content_type: &'static str,
+    body: String,
+}
+
+impl Response {
+    fn json(status: u16, body: String) -> Self {
+        Self {
+            status,
+            content_type: "application/json",
+            body,
+        }
+    }
+
+    fn error(status: u16, message: impl std::fmt::Display) -> Self {
+        Self::json(
+            status,
+            serde_json::json!({ "error": message.to_string() }).to_string(),
+        )
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut head = format!(
+            "HTTP/1.1 {} {}\r\n\
+             Content-Type: {}\r\n\
+             Content-Length: {}\r\n\
+             Connection: close\r\n",
+            self.status,
+            reason(self.status),
+            self.content_type,
+            self.body.len()
+        );
+        if self.status == 401 {
+            head.push_str("WWW-Authenticate: Bearer\r\n");
+        }
+        head.push_str("\r\n");
+        let mut bytes = head.into_bytes();
+        bytes.extend_from_slice(self.body.as_bytes());
+        bytes
+    }
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        408 => "Request Timeout",
+        411 => "Length Required",
+        413 => "Payload Too Large",
+        422 => "Unprocessable Entity",
+        431 => "Request Header Fields Too Large",
+        _ => "Internal Server Error",
+    }
+}
+
+/// Serve the API on `listener` until the task is dropped
+pub async fn serve(listener: TcpListener, config: ServerConfig) -> Result<(), ConflowError> {
+    let config = Arc::new(config);
+    let slots = Arc::new(tokio::sync::Semaphore::new(config.max_connections.max(1)));
+
+    loop {
+        let slot = Arc::clone(&slots)
+            .acquire_owned()
+            .await
+            .expect("the semaphore is never closed");
+        let (socket, _) = listener.accept().await?;
+        let config = Arc::clone(&config);
+        tokio::spawn(async move {
+            handle_connection(socket, config).await;
+            drop(slot);
+        });
+    }
+}
+
+async fn handle_connection(mut socket: TcpStream, config: Arc<ServerConfig>) {
+    let response = match tokio::time::timeout(
+        READ_TIMEOUT,
+        read_request(&mut socket, config.max_body),
+    )
+    .await
+    {
+        Ok(Ok(request)) => {
+            // Analysis and compliance checks walk files and parse content
+            let config = Arc::clone(&config);
+            let (method, path) = (request.method.clone(), request.path.clone());
+            let response = tokio::task::spawn_blocking(move || route(&request, &config))
+                .await
+                .unwrap_or_else(|e| Response::error(500, e));
+            tracing::info!(method = %method, path = %path, status = response.status, "request");
+            response
+        }
+        Ok(Err(response)) => response,
+        Err(_) => Response::error(408, "request not received in time"),
+    };
+
+    let _ = socket.write_all(&response.to_bytes()).await;
+    let _ = socket.shutdown().await;
+}
+
+/// Read one request, enforcing the header and body limits
+async fn read_request(socket: &mut TcpStream, max_body: usize) -> Result<Request, Response> {
+    let mut data = Vec::new();
+    let mut buf = [0u8; 8192];
+
+    let head_end = loop {
+        if let Some(pos) = data.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos;
+        }
+        if data.len() > MAX_HEAD {
+            return Err(Response::error(431, "request headers too large"));
+        }
+        let n = socket
+            .read(&mut buf)
+            .await
+            .map_err(|e| Response::error(400, e))?;
+        if n == 0 {
+            return Err(Response::error(400, "incomplete request"));
+        }
+        data.extend_from_slice(&buf[..n]);
+    };
+
+    let mut request = parse_head(&String::from_utf8_lossy(&data[..head_end]))?;
+
+    let length = match request.header("content-length") {
+        Some(value) => value
+            .parse::<usize>()
+            .map_err(|_| Response::error(400, "invalid Content-Length"))?,
+        None if request.header("transfer-encoding").is_some() => {
+            return Err(Response::error(411, "Content-Length is required"));
+        }
+        None => 0,
+    };
+    if length > max_body {
+        return Err(Response::error(
+            413,
+            format!("request body exceeds {} bytes", max_body),
+        ));
+    }
+
+    let mut body = data.split_off(head_end + 4);
+    while body.len() < length {
+        let n = socket
+            .read(&mut buf)
+            .await
+            .map_err(|e| Response::error(400, e))?;
+        if n == 0 {
+            return Err(Response::error(400, "incomplete request body"));
+        }
+        body.extend_from_slice(&buf[..n]);
+    }
+    body.truncate(length);
+    request.body = body;
+
+    Ok(request)
+}
+
+fn parse_head(head: &str) -> Result<Request, Response> {
+    let mut lines = head.split("\r\n");
+    let mut parts = lines.next().unwrap_or_default().split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err(Response::error(400, "malformed request line"));
+    };
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+
+    let headers = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_lowercase(), value.trim().to_string()))
+        .collect();
+
+    Ok(Request {
+        method: method.to_string(),
+        path: percent_decode(path),
+        query: query.to_string(),
+        headers,
+        body: Vec::new(),
+    })
+}
+
+fn route(request: &Request, config: &ServerConfig) -> Response {
+    let method = request.method.as_str();
+    let path = request.path.trim_end_matches('/');
+
+    if path == "/v1/health" {
+        return match method {
+            "GET" => Response::json(
+                200,
+                serde_json::json!({ "status": "ok", "version": crate::VERSION }).to_string(),
+            ),
+            _ => Response::error(405, "use GET"),
+        };
+    }
+
+    if !authorized(request, &config.api_keys) {
+        return Response::error(401, "missing or invalid API key");
+    }
+
+    let result = match (method, path) {
+        ("POST", "/v1/validate") => validate(request),
+        ("POST", "/v1/analyze") => analyze(request),
+        ("POST", "/v1/compliance") => compliance(request, &config.metrics),
+        ("GET", "/v1/schemas") => list_schemas(),
+        ("GET", _) if path.starts_with("/v1/schemas/") => schema(&path["/v1/schemas/".len()..]),
+        ("GET", "/v1/projects") => list_projects(&config.projects),
+        ("GET", _) if path.starts_with("/v1/projects/") => {
+            project(&config.projects, &path["/v1/projects/".len()..])
+        }
+        ("GET", "/metrics") => Ok(Response {
+            status: 200,
+            content_type: "text/plain; version=0.0.4",
+            body: config.metrics.render(),
+        }),
+        (_, "/v1/validate" | "/v1/analyze" | "/v1/compliance") => {
+            return Response::error(405, "use POST")
+        }
+        (_, "/v1/schemas" | "/v1/projects" | "/metrics") => return Response::error(405, "use GET"),
+        _ => return Response::error(404, format!("no endpoint at {}", request.path)),
+    };
+
+    result.unwrap_or_else(|e| e)
+}
+
+/// Whether the request carries one of the accepted keys
+fn authorized(request: &Request, keys: &[String]) -> bool {
+    key_accepted(
+        keys,
+        request.header("authorization"),
+        request.header("x-api-key"),
+    )
+}
+
+/// Whether an `Authorization: Bearer` or `X-API-Key` value is one of the
+/// accepted keys; any request is accepted when there are none
+pub(crate) fn key_accepted(
+    keys: &[String],
+    authorization: Option<&str>,
+    api_key: Option<&str>,
+) -> bool {
+    if keys.is_empty() {
+        return true;
+    }
+    let presented = authorization
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .or(api_key);
+
+    presented.is_some_and(|presented| {
+        keys.iter()
+            .any(|key| constant_time_eq(key.as_bytes(), presented.trim().as_bytes()))
+    })
+}
+
+/// Compare without leaking the position of the first difference
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn body_text(request: &Request) -> Result<&str, Response> {
+    std::str::from_utf8(&request.body).map_err(|_| Response::error(400, "body is not UTF-8"))
+}
+
+fn validate(request: &Request) -> Result<Response, Response> {
+    let result = PipelineValidator::validate_yaml(body_text(request)?);
+    Ok(Response::json(
+        200,
+        serde_json::json!({
+            "valid": result.is_valid(),
+            "errors": result.errors,
+            "warnings": result.warnings,
+        })
+        .to_string(),
+    ))
+}
+
+fn analyze(request: &Request) -> Result<Response, Response> {
+    let filename = request
+        .query_param("filename")
+        .ok_or_else(|| Response::error(400, "the filename query parameter is required"))?;
+
+    let filename =
+        upload_name(&filename).ok_or_else(|| Response::error(400, "filename must name a file"))?;
+
+    let analysis = ConfigAnalyzer::new()
+        .analyze_content(filename, body_text(request)?)
+        .map_err(|e| Response::error(422, e))?;
+    report_response(&analysis)
+}
+
+/// Body of `POST /v1/compliance`
+#[derive(Debug, Deserialize)]
+struct ComplianceRequest {
+    /// Project files by relative path
+    files: std::collections::BTreeMap<String, String>,
+}
+
+fn compliance(request: &Request, metrics: &Metrics) -> Result<Response, Response> {
+    let upload: ComplianceRequest = serde_json::from_slice(&request.body)
+        .map_err(|e| Response::error(400, format!("invalid request: {}", e)))?;
+
+    let scratch = Scratch::new().map_err(|e| Response::error(500, e))?;
+    scratch
+        .write_files(&upload.files)
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::InvalidInput => Response::error(400, e),
+            _ => Response::error(500, e),
+        })?;
+
+    let report = ComplianceChecker::new()
+        .check(scratch.path())
+        .map_err(|e| Response::error(500, e))?;
+    metrics.record_compliance(&report);
+    report_response(&report)
+}
+
+fn report_response<T: crate::report::Report>(value: &T) -> Result<Response, Response> {
+    crate::report::to_json(value)
+        .map(|json| Response::json(200, json))
+        .map_err(|e| Response::error(500, e))
+}
+
+fn list_schemas() -> Result<Response, Response> {
+    let registry = RsrSchemaRegistry::new();
+    let mut ids: Vec<_> = registry.list().map(|schema| schema.id.clone()).collect();
+    ids.sort();
+    Ok(Response::json(
+        200,
+        serde_json::json!({ "schemas": ids }).to_string(),
+    ))
+}
+
+fn schema(id: &str) -> Result<Response, Response> {
+    let registry = RsrSchemaRegistry::new();
+    if registry.get(id).is_none() {
+        return Err(Response::error(404, format!("unknown schema: {}", id)));
+    }
+    let content = registry
+        .get_content(id)
+        .map_err(|e| Response::error(500, e))?;
+    Ok(Response {
+        status: 200,
+        content_type: "text/plain; charset=utf-8",
+        body: content,
+    })
+}
+
+fn list_projects(projects: &[ScheduledProject]) -> Result<Response, Response> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+
+    let mut listed = Vec::new();
+    for project in projects {
+        let history = project.history().map_err(|e| Response::error(500, e))?;
+        listed.push(serde_json::json!({
+            "name": project.name,
+            "schedule": project.schedule,
+            "next_run": project.schedule.next_after(now).map(|t| rfc3339(t as i64)),
+            "latest": history.latest(),
+        }));
+    }
+    Ok(Response::json(
+        200,
+        serde_json::json!({ "projects": listed }).to_string(),
+    ))
+}
+
+fn project(projects: &[ScheduledProject], name: &str) -> Result<Response, Response> {
+    let project = projects
+        .iter()
+        .find(|p| p.name == name)
+        .ok_or_else(|| Response::error(404, format!("unknown project: {}", name)))?;
+    let history = project.history().map_err(|e| Response::error(500, e))?;
+    Ok(Response::json(
+        200,
+        serde_json::json!({
+            "name": project.name,
+            "schedule": project.schedule,
+            "history": history.entries,
+        })
+        .to_string(),
+    ))
+}
+
+/// A relative path that stays inside the directory it is joined to
+pub(crate) fn safe_relative(name: &str) -> Option<PathBuf> {
+    let path = Path::new(name);
+    let safe = !name.is_empty() && path.components().all(|c| matches!(c, Component::Normal(_)));
+    safe.then(|| path.to_path_buf())
+}
+
+/// Decode `%XX` escapes and `+` in a URL component
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                let escaped = bytes
+                    .get(i + 1..i + 3)
+                    .and_then(|hex| std::str::from_utf8(hex).ok())
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+                if let Some(byte) = escaped {
+                    out.push(byte);
+                    i += 3;
+                    continue;
+                }
+                out.push(b'%');
+            }
+            b'+' => out.push(b' '),
+            byte => out.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// The file name of an uploaded file's `filename`, without its directories,
+/// so analysis never looks at the server's own files beside it
+pub(crate) fn upload_name(filename: &str) -> Option<&Path> {
+    Path::new(filename).file_name().map(Path::new)
+}
+
+/// Private temporary project directory, removed on drop
+pub(crate) struct Scratch(tempfile::TempDir);
+
+impl Scratch {
+    pub(crate) fn new() -> std::io::Result<Self> {
+        crate::utils::private_tempdir("conflow-serve-").map(Self)
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        self.0.path()
+    }
+
+    /// Write uploaded files by relative path; a path leaving the directory
+    /// fails with [`std::io::ErrorKind::InvalidInput`]
+    pub(crate) fn write_files<'a>(
+        &self,
+        files: impl IntoIterator<Item = (&'a String, &'a String)>,
+    ) -> std::io::Result<()> {
+        for (name, content) in files {
+            let relative = safe_relative(name).ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("file path must be relative: {}", name),
+                )
+            })?;
+            let path = self.path().join(relative);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&path, content)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> ServerConfig {
+        ServerConfig {
+            api_keys: vec!["secret".to_string()],
+            ..ServerConfig::default()
+        }
+    }
+
+    fn request(method: &str, target: &str, body: &str) -> Request {
+        let mut request = parse_head(&format!(
+            "{} {} HTTP/1.1\r\nHost: localhost\r\nAuthorization: Bearer secret",
+            method, target
+        ))
+        .unwrap();
+        request.body = body.as_bytes().to_vec();
+        request
+    }
+
+    fn json(response: &Response) -> serde_json::Value {
+        serde_json::from_str(&response.body).unwrap()
+    }
+
+    #[test]
+    fn test_authentication() {
+        let config = config();
+        let mut anonymous = request("GET", "/v1/schemas", "");
+        anonymous.headers.clear();
+        assert_eq!(route(&anonymous, &config).status, 401);
+
+        anonymous
+            .headers
+            .push(("x-api-key".into(), "secret".into()));
+        assert_eq!(route(&anonymous, &config).status, 200);
+
+        anonymous.headers = vec![("authorization".into(), "Bearer wrong".into())];
+        assert_eq!(route(&anonymous, &config).status, 401);
+
+        // Health checks need no key
+        anonymous.path = "/v1/health".into();
+        let response = route(&anonymous, &config);
+        assert_eq!(response.status, 200);
+        assert_eq!(json(&response)["version"], crate::VERSION);
+    }
+
+    #[test]
+    fn test_scratch() {
+        let (a, b) = (Scratch::new().unwrap(), Scratch::new().unwrap());
+        assert_ne!(a.path(), b.path());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(a.path()).unwrap().permissions().mode();
+            assert_eq!(mode & 0o077, 0);
+        }
+        let path = a.path().to_path_buf();
+        drop(a);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_endpoints() {
+        let config = config();
+
+        let pipeline = "name: app\nstages:\n  - name: check\n    tool:\n      type: shell\n      \
+                        command: \"true\"\n    input: \"*.yaml\"\n";
+        let response = route(&request("POST", "/v1/validate", pipeline), &config);
+        assert_eq!(json(&response)["valid"], true);
+        let response = route(&request("POST", "/v1/validate", "stages: ["), &config);
+        assert_eq!(json(&response)["valid"], false);
+
+        let target = "/v1/analyze?filename=deploy%2Fconfig.yaml";
+        let response = route(
+            &request("POST", target, "name: app\nreplicas: 3\n"),
+            &config,
+        );
+        assert_eq!(response.status, 200);
+        let analysis: crate::analyzer::Analysis = crate::report::from_json(&response.body).unwrap();
+        assert_eq!(analysis.format, crate::analyzer::ConfigFormat::Yaml);
+        let response = route(&request("POST", "/v1/analyze", "a: 1"), &config);
+        assert_eq!(response.status, 400);
+        // Only the file name counts; nothing is looked up beside it
+        assert_eq!(
+            upload_name("../../etc/chart/values.yaml"),
+            Some(Path::new("values.yaml"))
+        );
+        let response = route(&request("POST", "/v1/analyze?filename=..", "a: 1"), &config);
+        assert_eq!(response.status, 400);
+
+        let files = r##"{"files": {"README.md": "# demo\n", "docs/guide.md": "hi"}}"##;
+        let response = route(&request("POST", "/v1/compliance", files), &config);
+        assert_eq!(response.status, 200, "{}", response.body);
+        let report: crate::rsr::ComplianceReport =
+            crate::report::from_json(&response.body).unwrap();
+        assert!(!report.requirements.is_empty());
+
+        let escape = r#"{"files": {"../escape.md": "x"}}"#;
+        let response = route(&request("POST", "/v1/compliance", escape), &config);
+        assert_eq!(response.status, 400);
+
+        let response = route(&request("GET", "/v1/schemas", ""), &config);
+        let ids = json(&response)["schemas"].as_array().unwrap().clone();
+        let id = ids[0].as_str().unwrap();
+        let response = route(&request("GET", &format!("/v1/schemas/{}", id), ""), &config);
+        assert_eq!(response.status, 200);
+        assert!(!response.body.is_empty());
+        assert_eq!(
+            route(&request("GET", "/v1/schemas/rsr%3Anope", ""), &config).status,
+            404
+        );
+
+        assert_eq!(
+            route(&request("GET", "/v1/validate", ""), &config).status,
+            405
+        );
+        assert_eq!(
+            route(&request("GET", "/v2/anything", ""), &config).status,
+            404
+        );
+    }
+
+    #[test]
+    fn test_metrics() {
+        let config = config();
+        let response = route(&request("GET", "/metrics", ""), &config);
+        assert_eq!(response.status, 200);
+        assert!(response.content_type.starts_with("text/plain"));
+        assert!(response
+            .body
+            .contains("# TYPE conflow_validations_total counter"));
+        assert!(!response.body.contains("conflow_compliance_pass_rate"));
+
+        let files = r##"{"files": {"README.md": "# demo\n"}}"##;
+        route(&request("POST", "/v1/compliance", files), &config);
+        let response = route(&request("GET", "/metrics/", ""), &config);
+        assert!(response
+            .body
+            .contains("# TYPE conflow_compliance_pass_rate gauge"));
+
+        let mut anonymous = request("GET", "/metrics", "");
+        anonymous.headers.clear();
+        assert_eq!(route(&anonymous, &config).status, 401);
+        assert_eq!(route(&request("POST", "/metrics", ""), &config).status, 405);
+    }
+
+    #[test]
+    fn test_projects() {
+        let temp = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            temp.path().join(".rsr.yaml"),
+            "project:\n  name: web\ncompliance:\n  schedule: \"@daily\"\n",
+        )
+        .unwrap();
+        let project = ScheduledProject::load(temp.path(), None).unwrap();
+        project.check().unwrap();
+        let config = ServerConfig {
+            projects: vec![project],
+            ..config()
+        };
+
+        let response = route(&request("GET", "/v1/projects", ""), &config);
+        assert_eq!(response.status, 200);
+        let listed = &json(&response)["projects"][0];
+        assert_eq!(listed["name"], "web");
+        assert_eq!(listed["schedule"], "@daily");
+        assert!(listed["next_run"].as_str().unwrap().ends_with("T00:00:00Z"));
+        assert!(listed["latest"]["score"].is_number());
+
+        let response = route(&request("GET", "/v1/projects/web", ""), &config);
+        assert_eq!(json(&response)["history"].as_array().unwrap().len(), 1);
+        assert_eq!(
+            route(&request("GET", "/v1/projects/api", ""), &config).status,
+            404
+        );
+        assert_eq!(
+            route(&request("POST", "/v1/projects", ""), &config).status,
+            405
+        );
+    }
+
+    #[tokio::test]
+    async fn test_serve_limits_body_size() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(serve(
+            listener,
+            ServerConfig {
+                max_body: 64,
+                ..config()
+            },
+        ));
+
+        let client = reqwest::Client::new();
+        let response = client
+            .post(format!("http://{}/v1/validate", addr))
+            .bearer_auth("secret")
+            .body("name: app\nstages: []\n")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        let body: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(body["valid"], false);
+
+        let response = client
+            .post(format!("http://{}/v1/validate", addr))
+            .bearer_auth("secret")
+            .body("x".repeat(65))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 413);
+
+        server.abort();
+    }
+}
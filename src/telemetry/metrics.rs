@@ -0,0 +1,341 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Prometheus metrics
+//!
+//! In-process metrics registry for long-running modes, rendered in the
+//! Prometheus text exposition format and served on `/metrics`.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+use crate::errors::ConflowError;
+use crate::pipeline::PipelineResult;
+use crate::rsr::ComplianceReport;
+
+/// Upper bounds (seconds) of the stage duration histogram buckets
+const DURATION_BUCKETS: [f64; 12] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0,
+];
+
+#[derive(Debug, Default, Clone)]
+struct Histogram {
+    counts: [u64; DURATION_BUCKETS.len()],
+    count: u64,
+    sum: f64,
+}
+
+impl Histogram {
+    fn observe(&mut self, value: f64) {
+        for (i, bound) in DURATION_BUCKETS.iter().enumerate() {
+            if value <= *bound {
+                self.counts[i] += 1;
+            }
+        }
+        self.count += 1;
+        self.sum += value;
+    }
+}
+
+#[derive(Debug, Default)]
+struct State {
+    validations_succeeded: u64,
+    validations_failed: u64,
+    cache_lookups: u64,
+    cache_hits: u64,
+    /// Requirement ID -> (checks, passes)
+    compliance: BTreeMap<String, (u64, u64)>,
+    stage_durations: BTreeMap<String, Histogram>,
+}
+
+/// Metrics registry shared between the runner and the metrics endpoint
+#[derive(Debug, Default)]
+pub struct Metrics {
+    state: Mutex<State>,
+}
+
+impl Metrics {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a completed pipeline run
+    pub fn record_pipeline(&self, result: &PipelineResult) {
+        let mut state = self.state.lock().unwrap();
+
+        if result.success {
+            state.validations_succeeded += 1;
+        } else {
+            state.validations_failed += 1;
+        }
+
+        for (name, stage) in &result.results {
+            state.cache_lookups += 1;
+            if stage.cache_hit {
+                state.cache_hits += 1;
+            } else {
+                state
+                    .stage_durations
+                    .entry(name.clone())
+                    .or_default()
+                    .observe(stage.duration.as_secs_f64());
+            }
+        }
+    }
+
+    /// Record a compliance check
+    pub fn record_compliance(&self, report: &ComplianceReport) {
+        let mut state = self.state.lock().unwrap();
+
+        for requirement in &report.requirements {
+            let entry = state
+                .compliance
+                .entry(requirement.requirement_id.clone())
+                .or_default();
+            entry.0 += 1;
+            if requirement.met {
+                entry.1 += 1;
+            }
+        }
+    }
+
+    /// Render all metrics in the Prometheus text format
+    pub fn render(&self) -> String {
+        let state = self.state.lock().unwrap();
+        let mut out = String::new();
+
+        header(
+            &mut out,
+            "conflow_validations_total",
+            "counter",
+            "Pipeline runs by result",
+        );
+        let _ = writeln!(
+            out,
+            "conflow_validations_total{{result=\"success\"}} {}",
+            state.validations_succeeded
+        );
+        let _ = writeln!(
+            out,
+            "conflow_validations_total{{result=\"failure\"}} {}",
+            state.validations_failed
+        );
+
+        header(
+            &mut out,
+            "conflow_cache_lookups_total",
+            "counter",
+            "Stage cache lookups",
+        );
+        let _ = writeln!(out, "conflow_cache_lookups_total {}", state.cache_lookups);
+        header(
+            &mut out,
+            "conflow_cache_hits_total",
+            "counter",
+            "Stage cache hits",
+        );
+        let _ = writeln!(out, "conflow_cache_hits_total {}", state.cache_hits);
+        header(
+            &mut out,
+            "conflow_cache_hit_ratio",
+            "gauge",
+            "Ratio of cache hits to lookups",
+        );
+        let _ = writeln!(
+            out,
+            "conflow_cache_hit_ratio {}",
+            ratio(state.cache_hits, state.cache_lookups)
+        );
+
+        if !state.compliance.is_empty() {
+            header(
+                &mut out,
+                "conflow_compliance_pass_rate",
+                "gauge",
+                "Fraction of compliance checks passed per requirement",
+            );
+            for (id, (checks, passes)) in &state.compliance {
+                let _ = writeln!(
+                    out,
+                    "conflow_compliance_pass_rate{{requirement=\"{}\"}} {}",
+                    escape(id),
+                    ratio(*passes, *checks)
+                );
+            }
+        }
+
+        if !state.stage_durations.is_empty() {
+            header(
+                &mut out,
+                "conflow_stage_duration_seconds",
+                "histogram",
+                "Stage execution time, excluding cache hits",
+            );
+            for (stage, hist) in &state.stage_durations {
+                let stage = escape(stage);
+                for (bound, count) in DURATION_BUCKETS.iter().zip(hist.counts) {
+                    let _ = writeln!(
+                        out,
+                        "conflow_stage_duration_seconds_bucket{{stage=\"{}\",le=\"{}\"}} {}",
+                        stage, bound, count
+                    );
+                }
+                let _ = writeln!(
+                    out,
+                    "conflow_stage_duration_seconds_bucket{{stage=\"{}\",le=\"+Inf\"}} {}",
+                    stage, hist.count
+                );
+                let _ = writeln!(
+                    out,
+                    "conflow_stage_duration_seconds_sum{{stage=\"{}\"}} {}",
+                    stage, hist.sum
+                );
+                let _ = writeln!(
+                    out,
+                    "conflow_stage_duration_seconds_count{{stage=\"{}\"}} {}",
+                    stage, hist.count
+                );
+            }
+        }
+
+        out
+    }
+}
+
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+fn ratio(part: u64, whole: u64) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f64 / whole as f64
+    }
+}
+
+/// Escape a Prometheus label value
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Serve `/metrics` on `addr` until the task is dropped
+pub async fn serve(metrics: Arc<Metrics>, addr: SocketAddr) -> Result<(), ConflowError> {
+    let listener = TcpListener::bind(addr).await?;
+
+    loop {
+        let (mut socket, _) = listener.accept().await?;
+        let metrics = Arc::clone(&metrics);
+
+        tokio::spawn(async move {
+            let mut buf = [0u8; 1024];
+            let n = socket.read(&mut buf).await.unwrap_or(0);
+            let request = String::from_utf8_lossy(&buf[..n]);
+
+            let response = if request.starts_with("GET /metrics ") {
+                let body = metrics.render();
+                format!(
+                    "HTTP/1.1 200 OK\r\n\
+                     Content-Type: text/plain; version=0.0.4\r\n\
+                     Content-Length: {}\r\n\
+                     Connection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                )
+            } else {
+                "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                    .to_string()
+            };
+
+            let _ = socket.write_all(response.as_bytes()).await;
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executors::ExecutionResult;
+    use crate::rsr::compliance::{ComplianceStats, RequirementResult};
+    use crate::rsr::ComplianceLevel;
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    fn stage(duration_ms: u64, cache_hit: bool) -> ExecutionResult {
+        ExecutionResult {
+            success: true,
+            stdout: String::new(),
+            stderr: String::new(),
+            exit_code: 0,
+            outputs: vec![],
+            duration: Duration::from_millis(duration_ms),
+            cache_hit,
+        }
+    }
+
+    #[test]
+    fn test_pipeline_metrics() {
+        let metrics = Metrics::new();
+        let mut results = HashMap::new();
+        results.insert("build".to_string(), stage(200, false));
+        results.insert("lint".to_string(), stage(0, true));
+
+        metrics.record_pipeline(&PipelineResult {
+            results,
+            duration: Duration::from_millis(200),
+            success: true,
+            profile: None,
+        });
+
+        let text = metrics.render();
+        assert!(text.contains("conflow_validations_total{result=\"success\"} 1"));
+        assert!(text.contains("conflow_cache_hit_ratio 0.5"));
+        assert!(
+            text.contains("conflow_stage_duration_seconds_bucket{stage=\"build\",le=\"0.1\"} 0")
+        );
+        assert!(
+            text.contains("conflow_stage_duration_seconds_bucket{stage=\"build\",le=\"0.25\"} 1")
+        );
+        assert!(text.contains("conflow_stage_duration_seconds_count{stage=\"build\"} 1"));
+        assert!(!text.contains("stage=\"lint\""));
+    }
+
+    #[test]
+    fn test_compliance_pass_rate() {
+        let metrics = Metrics::new();
+        let requirement = |met| RequirementResult {
+            requirement_id: "RSR-001".into(),
+            met,
+            details: vec![],
+            remediation: None,
+            blocked_by: vec![],
+            waived: false,
+            owners: Vec::new(),
+        };
+
+        for met in [true, false] {
+            metrics.record_compliance(&ComplianceReport {
+                level: ComplianceLevel::Basic,
+                score: 0.5,
+                requirements: vec![requirement(met)],
+                stats: ComplianceStats::default(),
+                custom_level: None,
+                waivers: vec![],
+            });
+        }
+
+        assert!(metrics
+            .render()
+            .contains("conflow_compliance_pass_rate{requirement=\"RSR-001\"} 0.5"));
+    }
+}
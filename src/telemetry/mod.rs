@@ -0,0 +1,14 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Telemetry export
+//!
+//! Ships pipeline runs to observability backends. Traces and metrics are
+//! sent to an OpenTelemetry collector over OTLP/HTTP using the JSON encoding,
+//! and long-running modes can expose Prometheus metrics.
+
+pub mod metrics;
+mod otlp;
+
+pub use metrics::Metrics;
+pub use otlp::{OtlpExporter, OtlpSettings};
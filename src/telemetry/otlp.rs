@@ -0,0 +1,437 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! OTLP/HTTP exporter
+//!
+//! Converts a profiled pipeline run into OTLP JSON payloads: one root span
+//! for the pipeline, one child span per stage, and a small set of gauges.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde_json::{json, Value};
+
+use crate::errors::ConflowError;
+use crate::pipeline::{Pipeline, PipelineResult, Profile, SpanKind, TelemetryConfig};
+use crate::utils::offline;
+
+const SCOPE_NAME: &str = "conflow";
+
+/// Resolved OTLP export settings
+#[derive(Debug, Clone, PartialEq)]
+pub struct OtlpSettings {
+    /// Collector base endpoint (signal paths are appended)
+    pub endpoint: String,
+    /// Service name resource attribute
+    pub service_name: String,
+    /// Extra request headers
+    pub headers: HashMap<String, String>,
+}
+
+impl OtlpSettings {
+    /// Resolve settings from the environment and pipeline configuration
+    ///
+    /// Returns `None` when no endpoint is configured.
+    pub fn resolve(config: &TelemetryConfig) -> Option<Self> {
+        Self::resolve_with(config, |key| std::env::var(key).ok())
+    }
+
+    fn resolve_with(
+        config: &TelemetryConfig,
+        env: impl Fn(&str) -> Option<String>,
+    ) -> Option<Self> {
+        let endpoint = env("OTEL_EXPORTER_OTLP_ENDPOINT")
+            .filter(|e| !e.is_empty())
+            .or_else(|| config.endpoint.clone())?;
+
+        let service_name = env("OTEL_SERVICE_NAME")
+            .filter(|s| !s.is_empty())
+            .or_else(|| config.service_name.clone())
+            .unwrap_or_else(|| SCOPE_NAME.to_string());
+
+        let mut headers = config.headers.clone();
+        if let Some(raw) = env("OTEL_EXPORTER_OTLP_HEADERS") {
+            for pair in raw.split(',') {
+                if let Some((key, value)) = pair.split_once('=') {
+                    headers.insert(key.trim().to_string(), value.trim().to_string());
+                }
+            }
+        }
+
+        Some(Self {
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            service_name,
+            headers,
+        })
+    }
+}
+
+/// Exports pipeline runs to an OTLP/HTTP collector
+pub struct OtlpExporter {
+    settings: OtlpSettings,
+    client: reqwest::Client,
+}
+
+impl OtlpExporter {
+    /// Create an exporter for the given settings
+    pub fn new(settings: OtlpSettings) -> Self {
+        Self {
+            settings,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Export traces and metrics for a profiled run
+    pub async fn export(
+        &self,
+        pipeline: &Pipeline,
+        result: &PipelineResult,
+        profile: &Profile,
+    ) -> Result<(), ConflowError> {
+        self.post("/v1/traces", &self.traces(pipeline, result, profile))
+            .await?;
+        self.post("/v1/metrics", &self.metrics(pipeline, result, profile))
+            .await
+    }
+
+    async fn post(&self, path: &str, body: &Value) -> Result<(), ConflowError> {
+        let url = format!("{}{}", self.settings.endpoint, path);
+        offline::guard(format!("telemetry export to {}", url))?;
+        let mut request = self.client.post(&url).json(body);
+        for (key, value) in &self.settings.headers {
+            request = request.header(key, value);
+        }
+
+        let response = request
+            .timeout(Duration::from_secs(10))
+            .send()
+            .await
+            .map_err(|e| ConflowError::Telemetry {
+                message: format!("{}: {}", url, e),
+            })?;
+
+        if !response.status().is_success() {
+            return Err(ConflowError::Telemetry {
+                message: format!("{} returned {}", url, response.status()),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Build the OTLP trace payload
+    pub fn traces(&self, pipeline: &Pipeline, result: &PipelineResult, profile: &Profile) -> Value {
+        let trace_id = random_hex(16);
+        let root_id = random_hex(8);
+        let origin = profile.started_at();
+
+        let mut spans = Vec::new();
+        for span in profile.spans() {
+            match span.kind {
+                SpanKind::Pipeline => spans.push(json!({
+                    "traceId": trace_id,
+                    "spanId": root_id,
+                    "name": format!("pipeline {}", pipeline.name),
+                    "kind": 1,
+                    "startTimeUnixNano": unix_nanos(origin, span.start),
+                    "endTimeUnixNano": unix_nanos(origin, span.start + span.duration),
+                    "attributes": [
+                        attr("conflow.pipeline", json!({ "stringValue": pipeline.name })),
+                        attr("conflow.stage_count", json!({ "intValue": result.results.len().to_string() })),
+                    ],
+                    "status": status(result.success),
+                })),
+                SpanKind::Stage => {
+                    let mut attributes = vec![attr(
+                        "conflow.stage",
+                        json!({ "stringValue": span.name }),
+                    )];
+                    if let Some(stage) = pipeline.get_stage(&span.name) {
+                        attributes.push(attr(
+                            "conflow.tool",
+                            json!({ "stringValue": stage.tool_name() }),
+                        ));
+                    }
+                    let success = match result.results.get(&span.name) {
+                        Some(r) => {
+                            attributes.push(attr(
+                                "conflow.cache_hit",
+                                json!({ "boolValue": r.cache_hit }),
+                            ));
+                            attributes.push(attr(
+                                "process.exit_code",
+                                json!({ "intValue": r.exit_code.to_string() }),
+                            ));
+                            r.success
+                        }
+                        None => false,
+                    };
+
+                    spans.push(json!({
+                        "traceId": trace_id,
+                        "spanId": random_hex(8),
+                        "parentSpanId": root_id,
+                        "name": span.name,
+                        "kind": 1,
+                        "startTimeUnixNano": unix_nanos(origin, span.start),
+                        "endTimeUnixNano": unix_nanos(origin, span.start + span.duration),
+                        "attributes": attributes,
+                        "status": status(success),
+                    }));
+                }
+                SpanKind::Cache | SpanKind::Process => {}
+            }
+        }
+
+        json!({
+            "resourceSpans": [{
+                "resource": self.resource(),
+                "scopeSpans": [{
+                    "scope": { "name": SCOPE_NAME, "version": crate::VERSION },
+                    "spans": spans,
+                }],
+            }],
+        })
+    }
+
+    /// Build the OTLP metrics payload
+    pub fn metrics(
+        &self,
+        pipeline: &Pipeline,
+        result: &PipelineResult,
+        profile: &Profile,
+    ) -> Value {
+        let now = unix_nanos(SystemTime::now(), Duration::ZERO);
+        let pipeline_attr = attr("conflow.pipeline", json!({ "stringValue": pipeline.name }));
+
+        let stage_points: Vec<_> = profile
+            .spans()
+            .iter()
+            .filter(|s| s.kind == SpanKind::Stage)
+            .map(|span| {
+                let cache_hit = result
+                    .results
+                    .get(&span.name)
+                    .map(|r| r.cache_hit)
+                    .unwrap_or(false);
+                json!({
+                    "attributes": [
+                        pipeline_attr,
+                        attr("conflow.stage", json!({ "stringValue": span.name })),
+                        attr("conflow.cache_hit", json!({ "boolValue": cache_hit })),
+                    ],
+                    "timeUnixNano": now,
+                    "asDouble": span.duration.as_secs_f64(),
+                })
+            })
+            .collect();
+
+        let total = result.results.len();
+        let hits = result.results.values().filter(|r| r.cache_hit).count();
+        let hit_ratio = if total == 0 {
+            0.0
+        } else {
+            hits as f64 / total as f64
+        };
+
+        json!({
+            "resourceMetrics": [{
+                "resource": self.resource(),
+                "scopeMetrics": [{
+                    "scope": { "name": SCOPE_NAME, "version": crate::VERSION },
+                    "metrics": [
+                        {
+                            "name": "conflow.stage.duration",
+                            "unit": "s",
+                            "gauge": { "dataPoints": stage_points },
+                        },
+                        {
+                            "name": "conflow.pipeline.duration",
+                            "unit": "s",
+                            "gauge": { "dataPoints": [{
+                                "attributes": [
+                                    pipeline_attr,
+                                    attr("conflow.success", json!({ "boolValue": result.success })),
+                                ],
+                                "timeUnixNano": now,
+                                "asDouble": result.duration.as_secs_f64(),
+                            }] },
+                        },
+                        {
+                            "name": "conflow.cache.hit_ratio",
+                            "unit": "1",
+                            "gauge": { "dataPoints": [{
+                                "attributes": [pipeline_attr],
+                                "timeUnixNano": now,
+                                "asDouble": hit_ratio,
+                            }] },
+                        },
+                    ],
+                }],
+            }],
+        })
+    }
+
+    fn resource(&self) -> Value {
+        json!({
+            "attributes": [
+                attr("service.name", json!({ "stringValue": self.settings.service_name })),
+                attr("service.version", json!({ "stringValue": crate::VERSION })),
+            ],
+        })
+    }
+}
+
+fn attr(key: &str, value: Value) -> Value {
+    json!({ "key": key, "value": value })
+}
+
+fn status(success: bool) -> Value {
+    // OTLP status codes: 1 = OK, 2 = ERROR
+    json!({ "code": if success { 1 } else { 2 } })
+}
+
+/// Nanoseconds since the Unix epoch, as a string per the OTLP JSON mapping
+fn unix_nanos(origin: SystemTime, offset: Duration) -> String {
+    (origin + offset)
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos()
+        .to_string()
+}
+
+/// Generate a random hex identifier of `bytes` length
+fn random_hex(bytes: usize) -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(&unix_nanos(SystemTime::now(), Duration::ZERO).into_bytes());
+    hasher.update(&std::process::id().to_le_bytes());
+    hasher.update(&COUNTER.fetch_add(1, Ordering::Relaxed).to_le_bytes());
+
+    hasher.finalize().to_hex()[..bytes * 2].to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executors::ExecutionResult;
+    use std::time::Instant;
+
+    fn sample() -> (Pipeline, PipelineResult, Profile) {
+        let pipeline = Pipeline::from_yaml(
+            r#"
+name: "demo"
+stages:
+  - name: "check"
+    tool:
+      type: shell
+      command: "true"
+    input: "*.json"
+"#,
+        )
+        .unwrap();
+
+        let mut profile = Profile::new();
+        let start = Instant::now();
+        profile.record("check", SpanKind::Stage, start);
+        profile.record("demo", SpanKind::Pipeline, start);
+
+        let mut results = HashMap::new();
+        results.insert(
+            "check".to_string(),
+            ExecutionResult {
+                success: true,
+                stdout: String::new(),
+                stderr: String::new(),
+                exit_code: 0,
+                outputs: vec![],
+                duration: Duration::from_millis(5),
+                cache_hit: true,
+            },
+        );
+
+        let result = PipelineResult {
+            results,
+            duration: Duration::from_millis(5),
+            success: true,
+            profile: None,
+        };
+
+        (pipeline, result, profile)
+    }
+
+    fn exporter() -> OtlpExporter {
+        OtlpExporter::new(OtlpSettings {
+            endpoint: "http://localhost:4318".into(),
+            service_name: "test".into(),
+            headers: HashMap::new(),
+        })
+    }
+
+    #[test]
+    fn test_resolve_prefers_env() {
+        let config = TelemetryConfig {
+            endpoint: Some("http://config:4318/".into()),
+            service_name: Some("from-config".into()),
+            headers: HashMap::new(),
+        };
+
+        let settings = OtlpSettings::resolve_with(&config, |_| None).unwrap();
+        assert_eq!(settings.endpoint, "http://config:4318");
+        assert_eq!(settings.service_name, "from-config");
+
+        let settings = OtlpSettings::resolve_with(&config, |key| match key {
+            "OTEL_EXPORTER_OTLP_ENDPOINT" => Some("http://env:4318".into()),
+            "OTEL_EXPORTER_OTLP_HEADERS" => Some("x-api-key=secret, x-team = infra".into()),
+            _ => None,
+        })
+        .unwrap();
+        assert_eq!(settings.endpoint, "http://env:4318");
+        assert_eq!(settings.headers["x-api-key"], "secret");
+        assert_eq!(settings.headers["x-team"], "infra");
+    }
+
+    #[test]
+    fn test_resolve_without_endpoint() {
+        assert!(OtlpSettings::resolve_with(&TelemetryConfig::default(), |_| None).is_none());
+    }
+
+    #[test]
+    fn test_stage_span_attributes() {
+        let (pipeline, result, profile) = sample();
+        let traces = exporter().traces(&pipeline, &result, &profile);
+        let spans = traces["resourceSpans"][0]["scopeSpans"][0]["spans"]
+            .as_array()
+            .unwrap();
+
+        assert_eq!(spans.len(), 2);
+        let stage = spans.iter().find(|s| s["name"] == "check").unwrap();
+        let root = spans.iter().find(|s| s["name"] == "pipeline demo").unwrap();
+
+        assert_eq!(stage["parentSpanId"], root["spanId"]);
+        assert_eq!(stage["traceId"].as_str().unwrap().len(), 32);
+
+        let attrs = stage["attributes"].as_array().unwrap();
+        let get = |key: &str| attrs.iter().find(|a| a["key"] == key).unwrap()["value"].clone();
+        assert_eq!(get("conflow.tool")["stringValue"], "shell");
+        assert_eq!(get("conflow.cache_hit")["boolValue"], true);
+        assert_eq!(get("process.exit_code")["intValue"], "0");
+    }
+
+    #[test]
+    fn test_metrics_cache_hit_ratio() {
+        let (pipeline, result, profile) = sample();
+        let metrics = exporter().metrics(&pipeline, &result, &profile);
+        let all = metrics["resourceMetrics"][0]["scopeMetrics"][0]["metrics"]
+            .as_array()
+            .unwrap();
+
+        let ratio = all
+            .iter()
+            .find(|m| m["name"] == "conflow.cache.hit_ratio")
+            .unwrap();
+        assert_eq!(ratio["gauge"]["dataPoints"][0]["asDouble"], 1.0);
+    }
+}
@@ -0,0 +1,405 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Toolchain management
+//!
+//! Downloads pinned CUE and Nickel binaries into a conflow-managed
+//! directory (`~/.conflow/toolchain/<tool>/<version>/`). Executors prefer
+//! these over whatever is on `PATH` when `.conflow.yaml` pins a version:
+//!
+//! ```yaml
+//! toolchain:
+//!   cue:
+//!     version: "0.9.2"
+//!     checksums:
+//!       linux-x86_64: "<sha256 of the release asset>"
+//!   nickel:
+//!     version: "1.7"
+//! ```
+
+mod sources;
+
+#[cfg(feature = "native")]
+pub use sources::resolve_version;
+pub use sources::{artifact, platform, Artifact, ArtifactKind, SUPPORTED_TOOLS};
+
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+#[cfg(feature = "native")]
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::errors::ConflowError;
+use crate::pipeline::Pipeline;
+#[cfg(feature = "native")]
+use crate::utils::offline;
+
+/// A `tool@version` request from the command line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolRequest {
+    pub tool: String,
+    /// Requested version; `None` means "use the pipeline's pin"
+    pub version: Option<String>,
+}
+
+impl FromStr for ToolRequest {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (tool, version) = match s.split_once('@') {
+            Some((tool, version)) => (tool, Some(version.to_string())),
+            None => (s, None),
+        };
+
+        if !SUPPORTED_TOOLS.contains(&tool) {
+            return Err(format!(
+                "Unknown tool '{}' (supported: {})",
+                tool,
+                SUPPORTED_TOOLS.join(", ")
+            ));
+        }
+        if version
+            .as_deref()
+            .is_some_and(|v| sources::parse_version(v).is_none())
+        {
+            return Err(format!("Invalid version in '{}'", s));
+        }
+
+        Ok(Self {
+            tool: tool.to_string(),
+            version,
+        })
+    }
+}
+
+/// Result of installing a tool
+#[derive(Debug, Clone)]
+pub struct Installed {
+    pub tool: String,
+    pub version: String,
+    pub path: PathBuf,
+    /// SHA-256 of the downloaded release asset
+    pub sha256: String,
+    /// Whether the checksum was verified against a pin or upstream list
+    pub verified: bool,
+}
+
+/// Default toolchain directory (`CONFLOW_TOOLCHAIN_DIR` or `~/.conflow/toolchain`)
+pub fn default_root() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os("CONFLOW_TOOLCHAIN_DIR") {
+        return Some(PathBuf::from(dir));
+    }
+    directories::BaseDirs::new().map(|d| d.home_dir().join(".conflow").join("toolchain"))
+}
+
+/// Hex-encoded SHA-256 digest
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// The conflow-managed tool directory
+pub struct Toolchain {
+    root: PathBuf,
+}
+
+impl Toolchain {
+    /// Use a specific root directory
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Open the default toolchain directory
+    pub fn open() -> Result<Self, ConflowError> {
+        default_root()
+            .map(Self::new)
+            .ok_or_else(|| ConflowError::Toolchain {
+                tool: "toolchain".into(),
+                message: "Could not determine the home directory".into(),
+                help: Some("Set CONFLOW_TOOLCHAIN_DIR".into()),
+            })
+    }
+
+    /// Root directory
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Where a tool version's binary lives
+    pub fn binary_path(&self, tool: &str, version: &str) -> PathBuf {
+        self.root
+            .join(tool)
+            .join(version)
+            .join(format!("{}{}", tool, std::env::consts::EXE_SUFFIX))
+    }
+
+    /// Installed versions of a tool, lowest first
+    pub fn versions(&self, tool: &str) -> Vec<String> {
+        let mut versions: Vec<String> = std::fs::read_dir(self.root.join(tool))
+            .into_iter()
+            .flatten()
+            .filter_map(Result::ok)
+            .filter_map(|e| e.file_name().into_string().ok())
+            .filter(|v| self.binary_path(tool, v).is_file())
+            .collect();
+        versions.sort_by_key(|v| sources::parse_version(v));
+        versions
+    }
+
+    /// Highest installed version satisfying a (possibly partial) version
+    pub fn installed(&self, tool: &str, version: &str) -> Option<PathBuf> {
+        let versions = self.versions(tool);
+        sources::best_match(version, versions.iter().map(String::as_str))
+            .map(|v| self.binary_path(tool, v))
+    }
+
+    /// Download, verify, and install a tool version
+    ///
+    /// `expected` is a pinned SHA-256 of the release asset; without one the
+    /// upstream checksum list is used when the project publishes it.
+    #[cfg(feature = "native")]
+    pub async fn install(
+        &self,
+        tool: &str,
+        version: &str,
+        expected: Option<&str>,
+    ) -> Result<Installed, ConflowError> {
+        offline::guard(format!("{} {} download", tool, version))?;
+        let client = reqwest::Client::builder()
+            .user_agent(concat!("conflow/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .map_err(|e| sources::download_error(tool, "client", e))?;
+
+        let version = resolve_version(&client, tool, version).await?;
+        let artifact = artifact(tool, &version)?;
+
+        tracing::debug!(tool, version, url = %artifact.url, "downloading");
+        let bytes = fetch(&client, tool, &artifact.url).await?;
+        let sha256 = sha256_hex(&bytes);
+
+        let expected = match expected {
+            Some(sum) => Some(sum.to_lowercase()),
+            None => match artifact.checksums_url {
+                Some(ref url) => {
+                    let list = fetch(&client, tool, url).await?;
+                    upstream_checksum(&String::from_utf8_lossy(&list), &artifact.file_name)
+                }
+                None => None,
+            },
+        };
+
+        if let Some(ref expected) = expected {
+            if *expected != sha256 {
+                return Err(ConflowError::Toolchain {
+                    tool: tool.to_string(),
+                    message: format!(
+                        "Checksum mismatch for {}: expected {}, got {}",
+                        artifact.file_name, expected, sha256
+                    ),
+                    help: Some(
+                        "The download may be corrupted or tampered with; \
+                         check the pinned checksum in .conflow.yaml"
+                            .into(),
+                    ),
+                });
+            }
+        }
+
+        let binary = match artifact.kind {
+            ArtifactKind::Binary => bytes,
+            ArtifactKind::TarGz => extract_binary(&bytes, tool)?,
+        };
+
+        let path = self.binary_path(tool, &version);
+        write_executable(&path, &binary)?;
+
+        Ok(Installed {
+            tool: tool.to_string(),
+            version,
+            path,
+            sha256,
+            verified: expected.is_some(),
+        })
+    }
+}
+
+/// Binaries for tools pinned by the pipeline that are installed
+///
+/// Pins that aren't installed are skipped with a warning so executors fall
+/// back to `PATH`.
+pub fn pinned_binaries(pipeline: &Pipeline) -> HashMap<String, PathBuf> {
+    let mut binaries = HashMap::new();
+    if pipeline.toolchain.is_empty() {
+        return binaries;
+    }
+
+    let Ok(toolchain) = Toolchain::open() else {
+        return binaries;
+    };
+
+    for (tool, pin) in &pipeline.toolchain {
+        match toolchain.installed(tool, &pin.version) {
+            Some(path) => {
+                binaries.insert(tool.clone(), path);
+            }
+            None => tracing::warn!(
+                "{} {} is pinned but not installed; run 'conflow toolchain install'",
+                tool,
+                pin.version
+            ),
+        }
+    }
+
+    binaries
+}
+
+#[cfg(feature = "native")]
+async fn fetch(client: &reqwest::Client, tool: &str, url: &str) -> Result<Vec<u8>, ConflowError> {
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| sources::download_error(tool, url, e))?;
+
+    response
+        .bytes()
+        .await
+        .map(|b| b.to_vec())
+        .map_err(|e| sources::download_error(tool, url, e))
+}
+
+/// Find an asset's checksum in a `sha256  file` list
+#[cfg(feature = "native")]
+fn upstream_checksum(list: &str, file_name: &str) -> Option<String> {
+    list.lines().find_map(|line| {
+        let mut parts = line.split_whitespace();
+        let sum = parts.next()?;
+        let name = parts.next()?.trim_start_matches('*');
+        (name == file_name).then(|| sum.to_lowercase())
+    })
+}
+
+/// Extract the tool binary from a gzipped tarball
+#[cfg(feature = "native")]
+fn extract_binary(archive: &[u8], tool: &str) -> Result<Vec<u8>, ConflowError> {
+    let binary_name = format!("{}{}", tool, std::env::consts::EXE_SUFFIX);
+    let mut tar = tar::Archive::new(flate2::read::GzDecoder::new(archive));
+
+    for entry in tar.entries()? {
+        let mut entry = entry?;
+        let is_binary = entry
+            .path()?
+            .file_name()
+            .is_some_and(|name| name == binary_name.as_str());
+
+        if is_binary {
+            let mut binary = Vec::new();
+            entry.read_to_end(&mut binary)?;
+            return Ok(binary);
+        }
+    }
+
+    Err(ConflowError::Toolchain {
+        tool: tool.to_string(),
+        message: format!("Release archive does not contain '{}'", binary_name),
+        help: None,
+    })
+}
+
+/// Atomically write an executable file
+#[cfg(feature = "native")]
+fn write_executable(path: &Path, content: &[u8]) -> Result<(), ConflowError> {
+    let dir = path.parent().unwrap_or(Path::new("."));
+    std::fs::create_dir_all(dir)?;
+
+    let tmp = path.with_extension("partial");
+    std::fs::write(&tmp, content)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&tmp, std::fs::Permissions::from_mode(0o755))?;
+    }
+
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_tool_request() {
+        let request: ToolRequest = "cue@0.9".parse().unwrap();
+        assert_eq!(request.tool, "cue");
+        assert_eq!(request.version.as_deref(), Some("0.9"));
+
+        let request: ToolRequest = "nickel".parse().unwrap();
+        assert_eq!(request.version, None);
+
+        assert!("dhall@1.0".parse::<ToolRequest>().is_err());
+        assert!("cue@latest".parse::<ToolRequest>().is_err());
+    }
+
+    #[test]
+    fn test_installed_version_lookup() {
+        let temp = TempDir::new().unwrap();
+        let toolchain = Toolchain::new(temp.path());
+
+        for version in ["0.8.2", "0.9.0", "0.9.2"] {
+            write_executable(&toolchain.binary_path("cue", version), b"#!/bin/sh\n").unwrap();
+        }
+
+        assert_eq!(toolchain.versions("cue"), vec!["0.8.2", "0.9.0", "0.9.2"]);
+        assert_eq!(
+            toolchain.installed("cue", "0.9"),
+            Some(toolchain.binary_path("cue", "0.9.2"))
+        );
+        assert_eq!(toolchain.installed("cue", "0.10"), None);
+        assert_eq!(toolchain.installed("nickel", "1.7"), None);
+    }
+
+    #[test]
+    fn test_upstream_checksum() {
+        let list =
+            "abc123  cue_v0.9.2_linux_amd64.tar.gz\nDEF456 *cue_v0.9.2_darwin_arm64.tar.gz\n";
+        assert_eq!(
+            upstream_checksum(list, "cue_v0.9.2_darwin_arm64.tar.gz").as_deref(),
+            Some("def456")
+        );
+        assert_eq!(upstream_checksum(list, "other.tar.gz"), None);
+    }
+
+    #[test]
+    fn test_extract_binary() {
+        let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(
+            Vec::new(),
+            flate2::Compression::default(),
+        ));
+        for (name, content) in [("README.md", &b"docs"[..]), ("cue", &b"binary"[..])] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o755);
+            header.set_cksum();
+            builder.append_data(&mut header, name, content).unwrap();
+        }
+        let archive = builder.into_inner().unwrap().finish().unwrap();
+
+        assert_eq!(extract_binary(&archive, "cue").unwrap(), b"binary");
+        assert!(extract_binary(&archive, "nickel").is_err());
+    }
+
+    #[test]
+    fn test_sha256_hex() {
+        assert_eq!(
+            sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+}
@@ -0,0 +1,266 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Release sources for managed tools
+
+#[cfg(feature = "native")]
+use serde::Deserialize;
+
+use crate::errors::ConflowError;
+
+/// Tools conflow knows how to install
+pub const SUPPORTED_TOOLS: &[&str] = &["cue", "nickel"];
+
+/// How a release asset is packaged
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArtifactKind {
+    /// Gzipped tarball containing the binary
+    TarGz,
+    /// The binary itself
+    Binary,
+}
+
+/// A downloadable release asset
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Artifact {
+    pub url: String,
+    pub kind: ArtifactKind,
+    /// Upstream checksum list, when the project publishes one
+    pub checksums_url: Option<String>,
+    /// Asset file name, as listed in the checksum file
+    pub file_name: String,
+}
+
+/// Platform key used for pinned checksums, e.g. `linux-x86_64`
+pub fn platform() -> String {
+    format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH)
+}
+
+/// GitHub repository publishing a tool's releases
+fn repository(tool: &str) -> Result<&'static str, ConflowError> {
+    match tool {
+        "cue" => Ok("cue-lang/cue"),
+        "nickel" => Ok("tweag/nickel"),
+        _ => Err(unsupported(tool)),
+    }
+}
+
+/// Release tag for a version
+fn tag(tool: &str, version: &str) -> String {
+    match tool {
+        "cue" => format!("v{}", version),
+        _ => version.to_string(),
+    }
+}
+
+/// Locate the release asset for a tool version on the current platform
+pub fn artifact(tool: &str, version: &str) -> Result<Artifact, ConflowError> {
+    artifact_for(tool, version, std::env::consts::OS, std::env::consts::ARCH)
+}
+
+fn artifact_for(tool: &str, version: &str, os: &str, arch: &str) -> Result<Artifact, ConflowError> {
+    let base = format!(
+        "https://github.com/{}/releases/download/{}",
+        repository(tool)?,
+        tag(tool, version)
+    );
+    let unavailable = || ConflowError::Toolchain {
+        tool: tool.to_string(),
+        message: format!("No {} {} release for {}-{}", tool, version, os, arch),
+        help: Some("Install it manually and make sure it's on your PATH".into()),
+    };
+
+    match tool {
+        "cue" => {
+            let os = match os {
+                "linux" => "linux",
+                "macos" => "darwin",
+                _ => return Err(unavailable()),
+            };
+            let arch = match arch {
+                "x86_64" => "amd64",
+                "aarch64" => "arm64",
+                _ => return Err(unavailable()),
+            };
+            let file_name = format!("cue_v{}_{}_{}.tar.gz", version, os, arch);
+
+            Ok(Artifact {
+                url: format!("{}/{}", base, file_name),
+                kind: ArtifactKind::TarGz,
+                checksums_url: Some(format!("{}/checksums.txt", base)),
+                file_name,
+            })
+        }
+        "nickel" => {
+            let arch = match (os, arch) {
+                ("linux", "x86_64") => "x86_64",
+                ("linux", "aarch64") => "arm64",
+                _ => return Err(unavailable()),
+            };
+            let file_name = format!("nickel-{}-linux", arch);
+
+            Ok(Artifact {
+                url: format!("{}/{}", base, file_name),
+                kind: ArtifactKind::Binary,
+                checksums_url: None,
+                file_name,
+            })
+        }
+        _ => Err(unsupported(tool)),
+    }
+}
+
+/// Parse a numeric version like `0.9.2` or `v1.7`
+pub fn parse_version(version: &str) -> Option<Vec<u64>> {
+    version
+        .trim_start_matches('v')
+        .split('.')
+        .map(|part| part.parse().ok())
+        .collect()
+}
+
+/// Whether a version is fully specified (major.minor.patch)
+#[cfg(feature = "native")]
+pub fn is_exact(version: &str) -> bool {
+    parse_version(version).is_some_and(|v| v.len() >= 3)
+}
+
+/// Whether `candidate` satisfies a possibly partial `requested` version
+pub fn matches(requested: &str, candidate: &str) -> bool {
+    match (parse_version(requested), parse_version(candidate)) {
+        (Some(requested), Some(candidate)) => candidate.starts_with(&requested),
+        _ => false,
+    }
+}
+
+/// Pick the highest version satisfying `requested`
+pub fn best_match<'a>(
+    requested: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Option<&'a str> {
+    candidates
+        .into_iter()
+        .filter(|c| matches(requested, c))
+        .max_by_key(|c| parse_version(c))
+}
+
+#[cfg(feature = "native")]
+#[derive(Deserialize)]
+struct Release {
+    tag_name: String,
+    #[serde(default)]
+    prerelease: bool,
+    #[serde(default)]
+    draft: bool,
+}
+
+/// Resolve a partial version (`0.9`) to the newest matching release
+#[cfg(feature = "native")]
+pub async fn resolve_version(
+    client: &reqwest::Client,
+    tool: &str,
+    requested: &str,
+) -> Result<String, ConflowError> {
+    if is_exact(requested) {
+        return Ok(requested.trim_start_matches('v').to_string());
+    }
+    if parse_version(requested).is_none() {
+        return Err(ConflowError::Toolchain {
+            tool: tool.to_string(),
+            message: format!("Invalid version '{}'", requested),
+            help: Some("Use a version like 0.9 or 0.9.2".into()),
+        });
+    }
+
+    let url = format!(
+        "https://api.github.com/repos/{}/releases?per_page=100",
+        repository(tool)?
+    );
+    let releases: Vec<Release> = client
+        .get(&url)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| download_error(tool, &url, e))?
+        .json()
+        .await
+        .map_err(|e| download_error(tool, &url, e))?;
+
+    let tags: Vec<&str> = releases
+        .iter()
+        .filter(|r| !r.prerelease && !r.draft)
+        .map(|r| r.tag_name.as_str())
+        .collect();
+
+    best_match(requested, tags.iter().copied())
+        .map(|t| t.trim_start_matches('v').to_string())
+        .ok_or_else(|| ConflowError::Toolchain {
+            tool: tool.to_string(),
+            message: format!("No release matches version '{}'", requested),
+            help: Some(format!(
+                "See https://github.com/{}/releases",
+                repository(tool).unwrap_or_default()
+            )),
+        })
+}
+
+#[cfg(feature = "native")]
+pub(super) fn download_error(tool: &str, url: &str, e: reqwest::Error) -> ConflowError {
+    ConflowError::Toolchain {
+        tool: tool.to_string(),
+        message: format!("Failed to download {}: {}", url, e),
+        help: Some("Check your network connection and that the version exists".into()),
+    }
+}
+
+fn unsupported(tool: &str) -> ConflowError {
+    ConflowError::Toolchain {
+        tool: tool.to_string(),
+        message: format!("'{}' is not a managed tool", tool),
+        help: Some(format!("Supported tools: {}", SUPPORTED_TOOLS.join(", "))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cue_artifact() {
+        let artifact = artifact_for("cue", "0.9.2", "macos", "aarch64").unwrap();
+        assert_eq!(
+            artifact.url,
+            "https://github.com/cue-lang/cue/releases/download/v0.9.2/cue_v0.9.2_darwin_arm64.tar.gz"
+        );
+        assert_eq!(artifact.kind, ArtifactKind::TarGz);
+        assert!(artifact
+            .checksums_url
+            .unwrap()
+            .ends_with("v0.9.2/checksums.txt"));
+    }
+
+    #[test]
+    fn test_nickel_artifact() {
+        let artifact = artifact_for("nickel", "1.7.0", "linux", "x86_64").unwrap();
+        assert_eq!(
+            artifact.url,
+            "https://github.com/tweag/nickel/releases/download/1.7.0/nickel-x86_64-linux"
+        );
+        assert!(artifact_for("nickel", "1.7.0", "windows", "x86_64").is_err());
+        assert!(artifact_for("dhall", "1.0.0", "linux", "x86_64").is_err());
+    }
+
+    #[test]
+    fn test_version_matching() {
+        assert!(is_exact("0.9.2"));
+        assert!(!is_exact("0.9"));
+        assert!(matches("0.9", "v0.9.2"));
+        assert!(!matches("0.9", "v0.10.0"));
+        assert!(!matches("0.9", "v0.9.0-rc.1"));
+
+        let tags = ["v0.8.2", "v0.9.0", "v0.9.2", "v0.10.0"];
+        assert_eq!(best_match("0.9", tags), Some("v0.9.2"));
+        assert_eq!(best_match("0", tags), Some("v0.10.0"));
+        assert_eq!(best_match("1", tags), None);
+    }
+}
@@ -0,0 +1,312 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Dashboard state
+//!
+//! Folds execution events into a view model the renderer can draw.
+
+use std::time::{Duration, Instant};
+
+use crate::pipeline::{ExecutionEvent, Pipeline};
+use crate::rsr::{ComplianceLevel, ComplianceReport};
+
+/// Display status of a stage
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StageStatus {
+    /// Waiting to run
+    Pending,
+    /// Currently executing
+    Running,
+    /// Satisfied from cache
+    Cached,
+    /// Finished successfully
+    Passed,
+    /// Finished with an error
+    Failed,
+    /// Not part of this run
+    Skipped,
+}
+
+impl StageStatus {
+    /// Status icon, matching the plain CLI output
+    pub fn icon(&self) -> &'static str {
+        match self {
+            Self::Pending => "·",
+            Self::Running => "→",
+            Self::Cached | Self::Passed => "✓",
+            Self::Failed => "✗",
+            Self::Skipped => "-",
+        }
+    }
+}
+
+/// A stage row in the dashboard
+#[derive(Debug, Clone)]
+pub struct StageView {
+    pub name: String,
+    pub tool: String,
+    pub status: StageStatus,
+    pub duration: Option<Duration>,
+    /// Captured stdout and stderr lines
+    pub log: Vec<String>,
+}
+
+/// Condensed compliance report for the summary panel
+#[derive(Debug, Clone)]
+pub struct ComplianceSummary {
+    pub level: ComplianceLevel,
+    pub score: f64,
+    pub passed: usize,
+    pub total: usize,
+    /// IDs of requirements that are not met
+    pub failing: Vec<String>,
+}
+
+impl From<&ComplianceReport> for ComplianceSummary {
+    fn from(report: &ComplianceReport) -> Self {
+        Self {
+            level: report.level,
+            score: report.score,
+            passed: report.stats.passed,
+            total: report.stats.total,
+            failing: report
+                .requirements
+                .iter()
+                .filter(|r| !r.met)
+                .map(|r| r.requirement_id.clone())
+                .collect(),
+        }
+    }
+}
+
+/// Dashboard state
+pub struct App {
+    pub pipeline_name: String,
+    pub stages: Vec<StageView>,
+    pub selected: usize,
+    pub log_scroll: u16,
+    pub started: Instant,
+    /// Set once the run completes: (success, duration)
+    pub finished: Option<(bool, Duration)>,
+    /// Fatal execution error, if any
+    pub error: Option<String>,
+    pub compliance: Option<ComplianceSummary>,
+}
+
+impl App {
+    /// Create state for a pipeline, with every stage pending
+    pub fn new(pipeline: &Pipeline) -> Self {
+        Self {
+            pipeline_name: pipeline.name.clone(),
+            stages: pipeline
+                .stages
+                .iter()
+                .map(|s| StageView {
+                    name: s.name.clone(),
+                    tool: s.tool_name().to_string(),
+                    status: StageStatus::Pending,
+                    duration: None,
+                    log: Vec::new(),
+                })
+                .collect(),
+            selected: 0,
+            log_scroll: 0,
+            started: Instant::now(),
+            finished: None,
+            error: None,
+            compliance: None,
+        }
+    }
+
+    /// Apply an execution event
+    pub fn apply(&mut self, event: ExecutionEvent) {
+        match event {
+            ExecutionEvent::Planned { stages } => {
+                for view in &mut self.stages {
+                    if !stages.contains(&view.name) {
+                        view.status = StageStatus::Skipped;
+                    }
+                }
+            }
+            ExecutionEvent::StageStarted { stage } => {
+                if let Some(idx) = self.index_of(&stage) {
+                    self.stages[idx].status = StageStatus::Running;
+                    // Follow the running stage
+                    self.selected = idx;
+                    self.log_scroll = 0;
+                }
+            }
+            ExecutionEvent::StageSkipped { stage, reason } => {
+                if let Some(view) = self.stages.iter_mut().find(|s| s.name == stage) {
+                    view.status = StageStatus::Skipped;
+                    view.log = vec![reason];
+                }
+            }
+            ExecutionEvent::StageCached { stage, result }
+            | ExecutionEvent::StageFinished { stage, result } => {
+                if let Some(view) = self.stages.iter_mut().find(|s| s.name == stage) {
+                    view.status = if result.cache_hit {
+                        StageStatus::Cached
+                    } else if result.success {
+                        StageStatus::Passed
+                    } else {
+                        StageStatus::Failed
+                    };
+                    view.duration = Some(result.duration);
+                    view.log = result
+                        .stdout
+                        .lines()
+                        .chain(result.stderr.lines())
+                        .map(String::from)
+                        .collect();
+                }
+            }
+            ExecutionEvent::PipelineFinished { success, duration } => {
+                for view in &mut self.stages {
+                    if view.status == StageStatus::Pending {
+                        view.status = StageStatus::Skipped;
+                    }
+                }
+                self.finished = Some((success, duration));
+            }
+        }
+    }
+
+    /// Record a fatal execution error
+    pub fn fail(&mut self, message: impl Into<String>) {
+        self.error = Some(message.into());
+        if self.finished.is_none() {
+            self.finished = Some((false, self.started.elapsed()));
+        }
+    }
+
+    /// Number of stages in a given status
+    pub fn count(&self, status: StageStatus) -> usize {
+        self.stages.iter().filter(|s| s.status == status).count()
+    }
+
+    /// Currently selected stage
+    pub fn selected_stage(&self) -> Option<&StageView> {
+        self.stages.get(self.selected)
+    }
+
+    pub fn select_next(&mut self) {
+        if self.selected + 1 < self.stages.len() {
+            self.selected += 1;
+            self.log_scroll = 0;
+        }
+    }
+
+    pub fn select_previous(&mut self) {
+        if self.selected > 0 {
+            self.selected -= 1;
+            self.log_scroll = 0;
+        }
+    }
+
+    pub fn scroll_down(&mut self, lines: u16) {
+        let max = self
+            .selected_stage()
+            .map(|s| s.log.len().saturating_sub(1) as u16)
+            .unwrap_or(0);
+        self.log_scroll = (self.log_scroll + lines).min(max);
+    }
+
+    pub fn scroll_up(&mut self, lines: u16) {
+        self.log_scroll = self.log_scroll.saturating_sub(lines);
+    }
+
+    fn index_of(&self, stage: &str) -> Option<usize> {
+        self.stages.iter().position(|s| s.name == stage)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executors::ExecutionResult;
+
+    fn pipeline() -> Pipeline {
+        Pipeline::from_yaml(
+            r#"
+name: "demo"
+stages:
+  - name: "a"
+    tool: { type: shell, command: "true" }
+    input: "*.json"
+  - name: "b"
+    tool: { type: shell, command: "true" }
+    input: "*.json"
+  - name: "c"
+    tool: { type: shell, command: "true" }
+    input: "*.json"
+"#,
+        )
+        .unwrap()
+    }
+
+    fn result(success: bool, cache_hit: bool) -> ExecutionResult {
+        ExecutionResult {
+            success,
+            stdout: "line one\nline two".into(),
+            stderr: "warning".into(),
+            exit_code: if success { 0 } else { 1 },
+            outputs: vec![],
+            duration: Duration::from_millis(10),
+            cache_hit,
+        }
+    }
+
+    #[test]
+    fn test_apply_events() {
+        let mut app = App::new(&pipeline());
+
+        app.apply(ExecutionEvent::Planned {
+            stages: vec!["a".into(), "b".into()],
+        });
+        assert_eq!(app.stages[2].status, StageStatus::Skipped);
+
+        app.apply(ExecutionEvent::StageCached {
+            stage: "a".into(),
+            result: result(true, true),
+        });
+        app.apply(ExecutionEvent::StageStarted { stage: "b".into() });
+        assert_eq!(app.stages[1].status, StageStatus::Running);
+        assert_eq!(app.selected, 1);
+
+        app.apply(ExecutionEvent::StageFinished {
+            stage: "b".into(),
+            result: result(false, false),
+        });
+        app.apply(ExecutionEvent::PipelineFinished {
+            success: false,
+            duration: Duration::from_millis(20),
+        });
+
+        assert_eq!(app.stages[0].status, StageStatus::Cached);
+        assert_eq!(app.stages[1].status, StageStatus::Failed);
+        assert_eq!(app.stages[1].log, vec!["line one", "line two", "warning"]);
+        assert_eq!(app.finished, Some((false, Duration::from_millis(20))));
+    }
+
+    #[test]
+    fn test_navigation_and_scroll() {
+        let mut app = App::new(&pipeline());
+        app.apply(ExecutionEvent::StageFinished {
+            stage: "a".into(),
+            result: result(true, false),
+        });
+
+        app.scroll_down(10);
+        assert_eq!(app.log_scroll, 2);
+        app.scroll_up(1);
+        assert_eq!(app.log_scroll, 1);
+
+        app.select_next();
+        assert_eq!(app.selected, 1);
+        assert_eq!(app.log_scroll, 0);
+        app.select_previous();
+        app.select_previous();
+        assert_eq!(app.selected, 0);
+    }
+}
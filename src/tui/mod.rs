@@ -0,0 +1,106 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Interactive dashboard
+//!
+//! Full-screen view of a pipeline run: live stage status, per-stage logs,
+//! cache hits, and an RSR compliance summary.
+
+mod app;
+mod ui;
+
+pub use app::{App, ComplianceSummary, StageStatus, StageView};
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use tokio::sync::mpsc;
+
+use crate::errors::ConflowError;
+use crate::pipeline::{ExecutionOptions, Pipeline, PipelineExecutor, PipelineResult};
+use crate::rsr::ComplianceChecker;
+
+/// Run a pipeline under the dashboard
+///
+/// Returns once the user quits. Quitting before the run finishes cancels it.
+pub async fn run(
+    pipeline: Pipeline,
+    executor: PipelineExecutor,
+    working_dir: PathBuf,
+    options: ExecutionOptions,
+) -> Result<PipelineResult, ConflowError> {
+    let mut app = App::new(&pipeline);
+    app.compliance = ComplianceChecker::for_project(&working_dir)
+        .and_then(|checker| checker.check(&working_dir))
+        .ok()
+        .map(|report| ComplianceSummary::from(&report));
+
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let executor = executor.with_events(tx).quiet();
+    let mut handle = Some(tokio::spawn(async move {
+        executor.execute(&pipeline, &working_dir, &options).await
+    }));
+    let mut outcome = None;
+
+    let mut terminal = ratatui::init();
+    let drawn = loop {
+        while let Ok(event) = rx.try_recv() {
+            app.apply(event);
+        }
+
+        if handle.as_ref().is_some_and(|h| h.is_finished()) {
+            let joined = handle
+                .take()
+                .unwrap()
+                .await
+                .map_err(|e| ConflowError::ExecutionFailed {
+                    message: format!("Pipeline task failed: {}", e),
+                    help: None,
+                });
+            let result = joined.and_then(|r| r);
+            if let Err(ref e) = result {
+                app.fail(e.to_string());
+            }
+            outcome = Some(result);
+        }
+
+        if let Err(e) = terminal.draw(|f| ui::draw(f, &app)) {
+            break Err(e);
+        }
+
+        let key = tokio::task::block_in_place(|| -> std::io::Result<Option<KeyCode>> {
+            if event::poll(Duration::from_millis(100))? {
+                if let Event::Key(key) = event::read()? {
+                    if key.kind == KeyEventKind::Press {
+                        return Ok(Some(key.code));
+                    }
+                }
+            }
+            Ok(None)
+        });
+
+        match key {
+            Ok(Some(KeyCode::Char('q') | KeyCode::Esc)) => break Ok(()),
+            Ok(Some(KeyCode::Down | KeyCode::Char('j'))) => app.select_next(),
+            Ok(Some(KeyCode::Up | KeyCode::Char('k'))) => app.select_previous(),
+            Ok(Some(KeyCode::PageDown)) => app.scroll_down(10),
+            Ok(Some(KeyCode::PageUp)) => app.scroll_up(10),
+            Ok(_) => {}
+            Err(e) => break Err(e),
+        }
+    };
+    ratatui::restore();
+    drawn?;
+
+    if let Some(handle) = handle {
+        handle.abort();
+    }
+
+    outcome.unwrap_or_else(|| {
+        Err(ConflowError::ExecutionFailed {
+            message: "Pipeline run cancelled".into(),
+            help: None,
+        })
+    })
+}
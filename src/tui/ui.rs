@@ -0,0 +1,243 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Dashboard rendering
+
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::Frame;
+
+use super::app::{App, StageStatus};
+use crate::rsr::ComplianceLevel;
+
+/// Draw the whole dashboard
+pub fn draw(frame: &mut Frame, app: &App) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(6),
+            Constraint::Length(5),
+            Constraint::Length(1),
+        ])
+        .split(frame.area());
+
+    draw_header(frame, app, rows[0]);
+
+    let body = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(35), Constraint::Percentage(65)])
+        .split(rows[1]);
+
+    draw_stages(frame, app, body[0]);
+    draw_log(frame, app, body[1]);
+    draw_compliance(frame, app, rows[2]);
+
+    frame.render_widget(
+        Paragraph::new("↑/↓ select stage  PgUp/PgDn scroll log  q quit")
+            .style(Style::default().fg(Color::DarkGray)),
+        rows[3],
+    );
+}
+
+fn draw_header(frame: &mut Frame, app: &App, area: Rect) {
+    let done = app.count(StageStatus::Passed) + app.count(StageStatus::Cached);
+    let planned = app.stages.len() - app.count(StageStatus::Skipped);
+
+    let (state, color, elapsed) = match app.finished {
+        Some((true, d)) => ("completed", Color::Green, d),
+        Some((false, d)) => ("failed", Color::Red, d),
+        None => ("running", Color::Blue, app.started.elapsed()),
+    };
+
+    let line = Line::from(vec![
+        Span::styled(
+            state,
+            Style::default().fg(color).add_modifier(Modifier::BOLD),
+        ),
+        Span::raw(format!(
+            "  {}/{} stages  {} cached  {} failed  {:.1}s",
+            done,
+            planned,
+            app.count(StageStatus::Cached),
+            app.count(StageStatus::Failed),
+            elapsed.as_secs_f64()
+        )),
+    ]);
+
+    frame.render_widget(
+        Paragraph::new(line).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(" Pipeline: {} ", app.pipeline_name)),
+        ),
+        area,
+    );
+}
+
+fn draw_stages(frame: &mut Frame, app: &App, area: Rect) {
+    let items: Vec<ListItem> = app
+        .stages
+        .iter()
+        .map(|stage| {
+            let color = status_color(stage.status);
+            let mut spans = vec![
+                Span::styled(
+                    format!("{} ", stage.status.icon()),
+                    Style::default().fg(color),
+                ),
+                Span::raw(stage.name.clone()),
+            ];
+            if stage.status == StageStatus::Cached {
+                spans.push(Span::styled(
+                    " (cached)",
+                    Style::default().fg(Color::DarkGray),
+                ));
+            } else if let Some(d) = stage.duration {
+                spans.push(Span::styled(
+                    format!(" {:.2}s", d.as_secs_f64()),
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(" Stages "))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+
+    let mut state = ListState::default().with_selected(Some(app.selected));
+    frame.render_stateful_widget(list, area, &mut state);
+}
+
+fn draw_log(frame: &mut Frame, app: &App, area: Rect) {
+    let (title, lines): (String, Vec<Line>) = match (&app.error, app.selected_stage()) {
+        (Some(error), _) => (
+            " Error ".into(),
+            vec![Line::styled(error.clone(), Style::default().fg(Color::Red))],
+        ),
+        (None, Some(stage)) => {
+            let lines = if stage.log.is_empty() {
+                let note = match stage.status {
+                    StageStatus::Pending => "waiting…",
+                    StageStatus::Running => "running…",
+                    StageStatus::Skipped => "not part of this run",
+                    _ => "no output",
+                };
+                vec![Line::styled(note, Style::default().fg(Color::DarkGray))]
+            } else {
+                stage.log.iter().map(|l| Line::raw(l.clone())).collect()
+            };
+            (format!(" {} ({}) ", stage.name, stage.tool), lines)
+        }
+        (None, None) => (" Log ".into(), vec![]),
+    };
+
+    frame.render_widget(
+        Paragraph::new(lines)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .wrap(Wrap { trim: false })
+            .scroll((app.log_scroll, 0)),
+        area,
+    );
+}
+
+fn draw_compliance(frame: &mut Frame, app: &App, area: Rect) {
+    let lines = match &app.compliance {
+        Some(summary) => {
+            let color = match summary.level {
+                ComplianceLevel::Excellent | ComplianceLevel::Good => Color::Green,
+                ComplianceLevel::Basic => Color::Yellow,
+                ComplianceLevel::NonCompliant => Color::Red,
+            };
+            let mut lines = vec![Line::from(vec![
+                Span::styled(
+                    format!("{:?}", summary.level),
+                    Style::default().fg(color).add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(format!(
+                    "  {:.0}%  {}/{} requirements met",
+                    summary.score * 100.0,
+                    summary.passed,
+                    summary.total
+                )),
+            ])];
+            if !summary.failing.is_empty() {
+                lines.push(Line::styled(
+                    format!("Not met: {}", summary.failing.join(", ")),
+                    Style::default().fg(Color::Yellow),
+                ));
+            }
+            lines
+        }
+        None => vec![Line::styled(
+            "No compliance data",
+            Style::default().fg(Color::DarkGray),
+        )],
+    };
+
+    frame.render_widget(
+        Paragraph::new(lines)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(" RSR Compliance "),
+            )
+            .wrap(Wrap { trim: true }),
+        area,
+    );
+}
+
+fn status_color(status: StageStatus) -> Color {
+    match status {
+        StageStatus::Pending | StageStatus::Skipped => Color::DarkGray,
+        StageStatus::Running => Color::Blue,
+        StageStatus::Cached | StageStatus::Passed => Color::Green,
+        StageStatus::Failed => Color::Red,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::{ExecutionEvent, Pipeline};
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    #[test]
+    fn test_draw_dashboard() {
+        let pipeline = Pipeline::from_yaml(
+            r#"
+name: "demo"
+stages:
+  - name: "generate"
+    tool: { type: shell, command: "true" }
+    input: "*.json"
+"#,
+        )
+        .unwrap();
+
+        let mut app = App::new(&pipeline);
+        app.apply(ExecutionEvent::StageStarted {
+            stage: "generate".into(),
+        });
+
+        let mut terminal = Terminal::new(TestBackend::new(80, 20)).unwrap();
+        terminal.draw(|f| draw(f, &app)).unwrap();
+
+        let text: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|c| c.symbol())
+            .collect();
+        assert!(text.contains("Pipeline: demo"));
+        assert!(text.contains("generate"));
+        assert!(text.contains("running"));
+        assert!(text.contains("No compliance data"));
+    }
+}
@@ -0,0 +1,191 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! CODEOWNERS files
+//!
+//! Who owns a path, as GitHub and GitLab work it out: the owners of the
+//! last rule whose pattern matches. Patterns follow `.gitignore` rules. A
+//! pattern without a slash matches at any depth, and one naming a directory
+//! matches everything under it. GitLab section headers are skipped.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Owner under which findings no rule assigns are grouped
+pub const UNOWNED: &str = "(unowned)";
+
+/// Where CODEOWNERS files are looked for, in order
+const LOCATIONS: &[&str] = &[
+    ".github/CODEOWNERS",
+    "CODEOWNERS",
+    "docs/CODEOWNERS",
+    ".gitlab/CODEOWNERS",
+];
+
+/// The rules of a CODEOWNERS file
+#[derive(Debug, Clone, Default)]
+pub struct CodeOwners {
+    rules: Vec<Rule>,
+}
+
+#[derive(Debug, Clone)]
+struct Rule {
+    patterns: Vec<glob::Pattern>,
+    owners: Vec<String>,
+}
+
+impl CodeOwners {
+    /// The first CODEOWNERS file found in a repository, if there is one
+    pub fn load(root: &Path) -> Option<Self> {
+        LOCATIONS
+            .iter()
+            .find_map(|location| std::fs::read_to_string(root.join(location)).ok())
+            .map(|content| Self::parse(&content))
+    }
+
+    /// Parse CODEOWNERS content, skipping patterns that aren't valid globs
+    pub fn parse(content: &str) -> Self {
+        let rules = content
+            .lines()
+            .filter_map(|line| {
+                let line = line.split(" #").next().unwrap_or_default().trim();
+                if line.is_empty() || line.starts_with('#') || line.starts_with(['[', '^']) {
+                    return None;
+                }
+                let mut fields = line.split_whitespace();
+                let patterns = patterns(fields.next()?)?;
+                Some(Rule {
+                    patterns,
+                    owners: fields.map(String::from).collect(),
+                })
+            })
+            .collect();
+        Self { rules }
+    }
+
+    /// Owners of a path relative to the repository root
+    ///
+    /// Empty when no rule matches, or the last matching rule has no owners.
+    pub fn owners(&self, path: &Path) -> &[String] {
+        let options = glob::MatchOptions {
+            require_literal_separator: true,
+            ..Default::default()
+        };
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| {
+                rule.patterns
+                    .iter()
+                    .any(|p| p.matches_path_with(path, options))
+            })
+            .map(|rule| rule.owners.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Owners of any of several paths, in the order they're first found
+    pub fn owners_of<'a>(&self, paths: impl IntoIterator<Item = &'a Path>) -> Vec<String> {
+        let mut owners: Vec<String> = Vec::new();
+        for owner in paths.into_iter().flat_map(|path| self.owners(path)) {
+            if !owners.contains(owner) {
+                owners.push(owner.clone());
+            }
+        }
+        owners
+    }
+}
+
+/// Group items under each of their owners
+///
+/// An item with several owners is listed under each of them; one without
+/// owners is listed under [`UNOWNED`].
+pub fn group_by_owner<T>(
+    items: impl IntoIterator<Item = T>,
+    owners: impl Fn(&T) -> Vec<String>,
+) -> BTreeMap<String, Vec<T>>
+where
+    T: Clone,
+{
+    let mut groups: BTreeMap<String, Vec<T>> = BTreeMap::new();
+    for item in items {
+        let mut owners = owners(&item);
+        if owners.is_empty() {
+            owners.push(UNOWNED.to_string());
+        }
+        for owner in owners {
+            groups.entry(owner).or_default().push(item.clone());
+        }
+    }
+    groups
+}
+
+/// Globs for a CODEOWNERS pattern
+fn patterns(pattern: &str) -> Option<Vec<glob::Pattern>> {
+    let directory = pattern.ends_with('/');
+    let pattern = pattern.trim_end_matches('/');
+    let anchored = pattern.starts_with('/') || pattern.contains('/');
+    let pattern = pattern.trim_start_matches('/');
+    let base = match anchored || pattern.starts_with("**") {
+        true => pattern.to_string(),
+        false => format!("**/{}", pattern),
+    };
+
+    // A last segment without wildcards may name a directory
+    let last = base.rsplit('/').next().unwrap_or_default();
+    let mut globs = Vec::new();
+    if !directory {
+        globs.push(base.clone());
+    }
+    if directory || !last.contains(['*', '?', '[']) {
+        globs.push(format!("{}/**", base));
+    }
+    globs.iter().map(|g| glob::Pattern::new(g).ok()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_owners() {
+        let owners = CodeOwners::parse(
+            "\
+# Default owners
+*                   @org/everyone
+*.cue               @org/schemas
+/k8s/               @org/platform  # manifests
+docs/*              docs@example.com
+apps/billing        @org/billing @alice
+[Infra][2]
+terraform/          @org/infra
+k8s/generated.yaml
+",
+        );
+        let of = |path: &str| owners.owners(Path::new(path)).join(" ");
+
+        assert_eq!(of("README.md"), "@org/everyone");
+        assert_eq!(of("schemas/app.cue"), "@org/schemas");
+        assert_eq!(of("k8s/prod/app.yaml"), "@org/platform");
+        assert_eq!(of("k8s/generated.yaml"), "");
+        assert_eq!(of("docs/index.yaml"), "docs@example.com");
+        assert_eq!(of("docs/guides/index.yaml"), "@org/everyone");
+        assert_eq!(of("apps/billing/config.json"), "@org/billing @alice");
+        assert_eq!(of("terraform/main.tf"), "@org/infra");
+        // A trailing slash alone doesn't anchor a pattern
+        assert_eq!(of("modules/terraform/main.tf"), "@org/infra");
+
+        let paths = [
+            Path::new("k8s/app.yaml"),
+            Path::new("app.cue"),
+            Path::new("k8s/a.yaml"),
+        ];
+        assert_eq!(owners.owners_of(paths), ["@org/platform", "@org/schemas"]);
+
+        let groups = group_by_owner(["README.md", "k8s/generated.yaml", "apps/billing/x"], |p| {
+            owners.owners(Path::new(p)).to_vec()
+        });
+        assert_eq!(groups["@org/everyone"], ["README.md"]);
+        assert_eq!(groups[UNOWNED], ["k8s/generated.yaml"]);
+        assert_eq!(groups["@alice"], groups["@org/billing"]);
+    }
+}
@@ -5,7 +5,8 @@
 //!
 //! Provides consistent color schemes across the CLI.
 
-use colored::{Color, Colorize};
+use colored::Colorize;
+use std::io::IsTerminal;
 
 /// Style for success messages
 pub fn success(msg: &str) -> colored::ColoredString {
@@ -42,20 +43,85 @@ pub fn code(msg: &str) -> colored::ColoredString {
     msg.cyan()
 }
 
-/// Check if colors should be disabled
-pub fn should_use_colors() -> bool {
-    // Respect NO_COLOR environment variable
-    if std::env::var("NO_COLOR").is_ok() {
-        return false;
+/// When to color output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum ColorChoice {
+    /// Color when stdout is a terminal, unless `NO_COLOR` is set
+    #[default]
+    Auto,
+    /// Always emit color codes
+    Always,
+    /// Never emit color codes
+    Never,
+}
+
+impl ColorChoice {
+    /// Decide whether to color, and set up colored, console and miette to match
+    pub fn apply(self) -> bool {
+        let enabled = self.resolve(|name| std::env::var(name).ok(), stdout_is_terminal());
+        enable_ansi(enabled);
+
+        colored::control::set_override(enabled);
+        console::set_colors_enabled(enabled);
+        console::set_colors_enabled_stderr(enabled);
+        #[cfg(feature = "native")]
+        if self != Self::Auto {
+            let _ = miette::set_hook(Box::new(move |_| {
+                Box::new(miette::MietteHandlerOpts::new().color(enabled).build())
+            }));
+        }
+        enabled
+    }
+
+    /// Whether to color, given the environment and whether stdout is a terminal
+    ///
+    /// `NO_COLOR` wins over `CLICOLOR_FORCE`/`FORCE_COLOR` in auto mode, as
+    /// <https://no-color.org> asks; an explicit `--color` wins over both.
+    fn resolve(self, env: impl Fn(&str) -> Option<String>, terminal: bool) -> bool {
+        let set = |name: &str| env(name).is_some_and(|v| !v.is_empty() && v != "0");
+        match self {
+            Self::Always => true,
+            Self::Never => false,
+            Self::Auto if set("NO_COLOR") => false,
+            Self::Auto if set("CLICOLOR_FORCE") || set("FORCE_COLOR") => true,
+            Self::Auto => terminal && env("TERM").as_deref() != Some("dumb"),
+        }
     }
+}
+
+/// Turn on ANSI escape handling in the Windows console
+///
+/// Consoles older than Windows 10 can't, so colors are switched off there
+/// rather than printing raw escape codes. Does nothing on other platforms.
+fn enable_ansi(enabled: bool) {
+    #[cfg(windows)]
+    if enabled && colored::control::set_virtual_terminal(true).is_err() {
+        colored::control::set_override(false);
+    }
+    #[cfg(not(windows))]
+    let _ = enabled;
+}
+
+/// Check if colors are enabled
+pub fn should_use_colors() -> bool {
+    colored::control::SHOULD_COLORIZE.should_colorize()
+}
+
+/// Whether this looks like a CI job (`CI` is set by most CI services)
+pub fn is_ci() -> bool {
+    std::env::var("CI").is_ok_and(|v| !v.is_empty() && v != "0" && v != "false")
+}
 
-    // Check if stdout is a terminal
-    atty_check()
+/// Whether output can redraw in place, as spinners and `\r` updates do
+///
+/// False when stdout is a file or pipe, in CI logs, and on dumb terminals;
+/// progress is printed as plain lines there instead.
+pub fn is_interactive() -> bool {
+    stdout_is_terminal() && !is_ci() && std::env::var("TERM").as_deref() != Ok("dumb")
 }
 
-fn atty_check() -> bool {
-    // Simple check - could be enhanced with atty crate
-    std::env::var("TERM").is_ok()
+fn stdout_is_terminal() -> bool {
+    std::io::stdout().is_terminal()
 }
 
 /// Print a styled header
@@ -99,3 +165,38 @@ pub fn print_warning(msg: &str) {
 pub fn print_info(msg: &str) {
     println!("  {} {}", "→".blue(), msg);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolve(choice: ColorChoice, vars: &[(&str, &str)], terminal: bool) -> bool {
+        choice.resolve(
+            |name| {
+                vars.iter()
+                    .find(|(k, _)| *k == name)
+                    .map(|(_, v)| v.to_string())
+            },
+            terminal,
+        )
+    }
+
+    #[test]
+    fn test_color_choice() {
+        assert!(resolve(ColorChoice::Auto, &[], true));
+        assert!(!resolve(ColorChoice::Auto, &[], false));
+        assert!(!resolve(ColorChoice::Auto, &[("NO_COLOR", "1")], true));
+        assert!(resolve(ColorChoice::Auto, &[("NO_COLOR", "")], true));
+        assert!(!resolve(ColorChoice::Auto, &[("TERM", "dumb")], true));
+        assert!(resolve(ColorChoice::Auto, &[("FORCE_COLOR", "1")], false));
+        assert!(!resolve(ColorChoice::Auto, &[("CLICOLOR_FORCE", "0")], false));
+        assert!(!resolve(
+            ColorChoice::Auto,
+            &[("NO_COLOR", "1"), ("FORCE_COLOR", "1")],
+            true
+        ));
+
+        assert!(resolve(ColorChoice::Always, &[("NO_COLOR", "1")], false));
+        assert!(!resolve(ColorChoice::Never, &[("FORCE_COLOR", "1")], true));
+    }
+}
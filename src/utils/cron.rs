@@ -0,0 +1,262 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Cron schedules
+//!
+//! Five-field expressions (minute, hour, day of month, month, day of week)
+//! evaluated in UTC, with lists, ranges, steps, month and weekday names, and
+//! the `@hourly`, `@daily`, `@weekly`, `@monthly` and `@yearly` shorthands.
+//! As in Vixie cron, when both day fields are restricted a day matching
+//! either of them is due.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+use super::date::civil_from_days;
+
+const MONTHS: &[&str] = &[
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+const WEEKDAYS: &[&str] = &["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+/// How far ahead to look for the next run; covers schedules on February 29
+const HORIZON_DAYS: u64 = 5 * 366;
+
+/// A parsed cron expression
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Schedule {
+    expression: String,
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    /// Whether the day of month field is `*`, so only weekdays restrict days
+    any_day: bool,
+    /// Whether the day of week field is `*`
+    any_weekday: bool,
+}
+
+impl Schedule {
+    /// The expression as written
+    pub fn expression(&self) -> &str {
+        &self.expression
+    }
+
+    /// Whether the schedule is due at a Unix time, to the minute
+    pub fn matches(&self, time: u64) -> bool {
+        let at = Civil::from_unix(time);
+        self.matches_day(&at) && bit(self.hours, at.hour) && bit(self.minutes, at.minute)
+    }
+
+    /// The first minute after a Unix time when the schedule is due
+    ///
+    /// `None` for schedules that never run, such as `0 0 30 2 *`.
+    pub fn next_after(&self, time: u64) -> Option<u64> {
+        let mut next = (time / 60 + 1) * 60;
+        let limit = next + HORIZON_DAYS * 86_400;
+        while next < limit {
+            let at = Civil::from_unix(next);
+            if !self.matches_day(&at) {
+                next = next - next % 86_400 + 86_400;
+            } else if !bit(self.hours, at.hour) {
+                next = next - next % 3600 + 3600;
+            } else if !bit(self.minutes, at.minute) {
+                next += 60;
+            } else {
+                return Some(next);
+            }
+        }
+        None
+    }
+
+    fn matches_day(&self, at: &Civil) -> bool {
+        if !bit(self.months, at.month) {
+            return false;
+        }
+        let day = bit(self.days, at.day);
+        let weekday = bit(self.weekdays, at.weekday);
+        match self.any_day || self.any_weekday {
+            true => day && weekday,
+            false => day || weekday,
+        }
+    }
+}
+
+impl FromStr for Schedule {
+    type Err = String;
+
+    fn from_str(expression: &str) -> Result<Self, Self::Err> {
+        let expanded = match expression.trim() {
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            "@monthly" => "0 0 1 * *",
+            "@weekly" => "0 0 * * 0",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@hourly" => "0 * * * *",
+            other => other,
+        };
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(format!(
+                "'{}' has {} field(s); expected minute, hour, day of month, month and \
+                 day of week",
+                expression,
+                fields.len()
+            ));
+        };
+        let invalid = |e: String| format!("invalid cron expression '{}': {}", expression, e);
+
+        // Sunday is 0 or 7
+        let mut weekdays = field(weekday, 0, 7, WEEKDAYS).map_err(invalid)?;
+        if bit(weekdays, 7) {
+            weekdays |= 1;
+        }
+        Ok(Self {
+            expression: expression.trim().to_string(),
+            minutes: field(minute, 0, 59, &[]).map_err(invalid)?,
+            hours: field(hour, 0, 23, &[]).map_err(invalid)?,
+            days: field(day, 1, 31, &[]).map_err(invalid)?,
+            months: field(month, 1, 12, MONTHS).map_err(invalid)?,
+            weekdays,
+            any_day: day.starts_with('*'),
+            any_weekday: weekday.starts_with('*'),
+        })
+    }
+}
+
+impl TryFrom<String> for Schedule {
+    type Error = String;
+
+    fn try_from(expression: String) -> Result<Self, Self::Error> {
+        expression.parse()
+    }
+}
+
+impl From<Schedule> for String {
+    fn from(schedule: Schedule) -> Self {
+        schedule.expression
+    }
+}
+
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.expression)
+    }
+}
+
+/// The values a field selects, as a bit set
+///
+/// `names` are the values' names, starting at `min`.
+fn field(field: &str, min: u64, max: u64, names: &[&str]) -> Result<u64, String> {
+    let value = |s: &str| -> Result<u64, String> {
+        let value = match names.iter().position(|n| n.eq_ignore_ascii_case(s)) {
+            Some(index) => index as u64 + min,
+            None => s.parse().map_err(|_| format!("'{}' is not a number", s))?,
+        };
+        match (min..=max).contains(&value) {
+            true => Ok(value),
+            false => Err(format!("{} is outside {}-{}", value, min, max)),
+        }
+    };
+
+    let mut bits = 0;
+    for item in field.split(',') {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => match step.parse::<u64>() {
+                Ok(step) if step > 0 => (range, step),
+                _ => return Err(format!("invalid step in '{}'", item)),
+            },
+            None => (item, 1),
+        };
+        let (start, end) = match range.split_once('-') {
+            _ if range == "*" => (min, max),
+            Some((start, end)) => (value(start)?, value(end)?),
+            // `5/15` runs from 5 to the end of the range
+            None if step > 1 => (value(range)?, max),
+            None => (value(range)?, value(range)?),
+        };
+        if start > end {
+            return Err(format!("range '{}' runs backwards", range));
+        }
+        for v in (start..=end).step_by(step as usize) {
+            bits |= 1 << v;
+        }
+    }
+    Ok(bits)
+}
+
+fn bit(bits: u64, value: u64) -> bool {
+    bits & (1 << value) != 0
+}
+
+/// UTC calendar fields of a Unix time
+struct Civil {
+    month: u64,
+    day: u64,
+    weekday: u64,
+    hour: u64,
+    minute: u64,
+}
+
+impl Civil {
+    fn from_unix(time: u64) -> Self {
+        let (days, rem) = (time / 86_400, time % 86_400);
+        let (_, month, day) = civil_from_days(days as i64);
+        Self {
+            month: u64::from(month),
+            day: u64::from(day),
+            // 1970-01-01 was a Thursday
+            weekday: (days + 4) % 7,
+            hour: rem / 3600,
+            minute: rem % 3600 / 60,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2025-01-01T00:00:00Z, a Wednesday
+    const NEW_YEAR: u64 = 1_735_689_600;
+
+    #[test]
+    fn test_schedule() {
+        let next = |expression: &str, after: u64| {
+            let schedule: Schedule = expression.parse().unwrap();
+            schedule.next_after(after).map(|t| (t - NEW_YEAR) / 60)
+        };
+        let minutes = |days: u64, hours: u64, minutes: u64| (days * 24 + hours) * 60 + minutes;
+
+        assert_eq!(next("*/15 * * * *", NEW_YEAR), Some(15));
+        assert_eq!(next("@hourly", NEW_YEAR + 60), Some(60));
+        assert_eq!(next("30 9 * * mon-fri", NEW_YEAR), Some(minutes(0, 9, 30)));
+        // Saturday and Sunday are skipped
+        assert_eq!(
+            next("30 9 * * MON-FRI", NEW_YEAR + minutes(2, 10, 0) * 60),
+            Some(minutes(5, 9, 30))
+        );
+        assert_eq!(next("0 0 * * 7", NEW_YEAR), Some(minutes(4, 0, 0)));
+        assert_eq!(next("0 6 1 mar *", NEW_YEAR), Some(minutes(59, 6, 0)));
+        // Either day field matches when both are restricted
+        assert_eq!(next("0 0 3 * sun", NEW_YEAR), Some(minutes(2, 0, 0)));
+        assert_eq!(next("0 0 29 2 *", NEW_YEAR), Some(minutes(1154, 0, 0)));
+        assert_eq!(next("0 0 30 2 *", NEW_YEAR), None);
+
+        let schedule: Schedule = "5,10-20/5 * * * *".parse().unwrap();
+        assert!(schedule.matches(NEW_YEAR + 15 * 60));
+        assert!(!schedule.matches(NEW_YEAR + 25 * 60));
+
+        for invalid in [
+            "* * * *",
+            "60 * * * *",
+            "*/0 * * * *",
+            "5-1 * * * *",
+            "0 0 * foo *",
+        ] {
+            assert!(invalid.parse::<Schedule>().is_err(), "{}", invalid);
+        }
+    }
+}
@@ -0,0 +1,412 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Git helpers
+//!
+//! Thin wrappers around the `git` CLI used for change detection.
+//!
+//! These run the `git` binary rather than linking libgit2 through the `git2`
+//! crate: that keeps a C library out of the build, and the CLI picks up the
+//! user's git config, credential helpers and safe.directory rules as-is. The
+//! price is that `git` must be on `PATH`; when it isn't, every helper fails
+//! with a [`ConflowError::Git`] saying so.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use super::offline;
+use crate::errors::ConflowError;
+
+/// Run a git command in `dir` and return its stdout
+fn git(dir: &Path, args: &[&str]) -> Result<String, ConflowError> {
+    git_bytes(dir, args).map(|out| String::from_utf8_lossy(&out).to_string())
+}
+
+/// Run a git command in `dir` and return its raw stdout
+fn git_bytes(dir: &Path, args: &[&str]) -> Result<Vec<u8>, ConflowError> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .map_err(spawn_error)?;
+
+    if !output.status.success() {
+        return Err(ConflowError::Git {
+            message: format!(
+                "git {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+        });
+    }
+
+    Ok(output.stdout)
+}
+
+fn spawn_error(e: std::io::Error) -> ConflowError {
+    let message = match e.kind() {
+        std::io::ErrorKind::NotFound => {
+            "git is not installed or not on PATH; conflow runs the git CLI for change \
+             detection, so install git to use it"
+                .to_string()
+        }
+        _ => format!("Failed to run git: {}", e),
+    };
+    ConflowError::Git { message }
+}
+
+/// Get the root of the repository containing `dir`
+pub fn repo_root(dir: &Path) -> Result<PathBuf, ConflowError> {
+    let out = git(dir, &["rev-parse", "--show-toplevel"])?;
+    Ok(PathBuf::from(out.trim()))
+}
+
+/// The URL of the `origin` remote of the repository containing `dir`
+pub fn remote_url(dir: &Path) -> Result<String, ConflowError> {
+    Ok(git(dir, &["remote", "get-url", "origin"])?.trim().to_string())
+}
+
+/// The email of the git user committing in `dir`
+pub fn user_email(dir: &Path) -> Result<String, ConflowError> {
+    let email = git(dir, &["config", "user.email"])?.trim().to_string();
+    match email.is_empty() {
+        true => Err(ConflowError::Git {
+            message: "user.email is not set".into(),
+        }),
+        false => Ok(email),
+    }
+}
+
+/// Get the commit checked out in `dir`
+pub fn head_commit(dir: &Path) -> Result<String, ConflowError> {
+    Ok(git(dir, &["rev-parse", "HEAD"])?.trim().to_string())
+}
+
+/// The most recent tag reachable from the commit checked out in `dir`
+pub fn latest_tag(dir: &Path) -> Option<String> {
+    let tag = git(dir, &["describe", "--tags", "--abbrev=0"]).ok()?;
+    Some(tag.trim().to_string()).filter(|t| !t.is_empty())
+}
+
+/// Short hashes and subjects of the commits in `range`, newest first,
+/// leaving out merges
+pub fn commit_subjects(
+    dir: &Path,
+    range: &str,
+    limit: usize,
+) -> Result<Vec<(String, String)>, ConflowError> {
+    let limit = limit.to_string();
+    let log = git(
+        dir,
+        &["log", "--no-merges", "--format=%h%x09%s", "-n", &limit, range],
+    )?;
+    Ok(log
+        .lines()
+        .filter_map(|l| l.split_once('\t'))
+        .map(|(hash, subject)| (hash.to_string(), subject.to_string()))
+        .collect())
+}
+
+/// Whether tracked files in the repository have uncommitted changes
+pub fn has_changes(dir: &Path) -> Result<bool, ConflowError> {
+    let status = git(dir, &["status", "--porcelain", "--untracked-files=no"])?;
+    Ok(!status.trim().is_empty())
+}
+
+/// Get all files changed since `since`
+///
+/// Compares the working tree against the merge base of `since` and `HEAD`,
+/// so committed, staged, unstaged, and untracked changes are all included.
+/// Returned paths are absolute.
+pub fn changed_files(dir: &Path, since: &str) -> Result<Vec<PathBuf>, ConflowError> {
+    let root = repo_root(dir)?;
+    let base = git(&root, &["merge-base", since, "HEAD"])?;
+    let base = base.trim();
+
+    let mut files: Vec<PathBuf> = git(&root, &["diff", "--name-only", base])?
+        .lines()
+        .chain(git(&root, &["ls-files", "--others", "--exclude-standard"])?.lines())
+        .filter(|l| !l.is_empty())
+        .map(|l| root.join(l))
+        .collect();
+
+    files.sort();
+    files.dedup();
+
+    Ok(files)
+}
+
+/// Files in the repository under `dir`, tracked or untracked but not ignored
+///
+/// Returned paths are relative to `dir`.
+pub fn listed_files(dir: &Path) -> Result<Vec<PathBuf>, ConflowError> {
+    let listed = git(dir, &["ls-files", "--cached", "--others", "--exclude-standard"])?;
+    let mut files: Vec<PathBuf> = listed
+        .lines()
+        .filter(|l| !l.is_empty())
+        .map(PathBuf::from)
+        .filter(|path| dir.join(path).is_file())
+        .collect();
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+/// Git submodules of the repository under `dir`, relative to `dir`
+pub fn submodules(dir: &Path) -> Result<Vec<PathBuf>, ConflowError> {
+    let staged = git(dir, &["ls-files", "--stage"])?;
+    Ok(staged
+        .lines()
+        .filter(|l| l.starts_with("160000 "))
+        .filter_map(|l| l.split_once('\t'))
+        .map(|(_, path)| PathBuf::from(path))
+        .collect())
+}
+
+/// Get the files added or modified between two revisions
+///
+/// Deleted files are left out. Returned paths are absolute.
+pub fn changed_between(dir: &Path, from: &str, to: &str) -> Result<Vec<PathBuf>, ConflowError> {
+    let root = repo_root(dir)?;
+    Ok(git(&root, &["diff", "--name-only", "--diff-filter=d", from, to])?
+        .lines()
+        .filter(|l| !l.is_empty())
+        .map(|l| root.join(l))
+        .collect())
+}
+
+/// Extract the repository tree at `git_ref` into `dest`
+///
+/// Returns the directory in `dest` corresponding to `dir`.
+pub fn export_tree(dir: &Path, git_ref: &str, dest: &Path) -> Result<PathBuf, ConflowError> {
+    let root = repo_root(dir)?;
+    let archive = git_bytes(&root, &["archive", "--format=tar", git_ref])?;
+
+    tar::Archive::new(archive.as_slice())
+        .unpack(dest)
+        .map_err(|e| ConflowError::Git {
+            message: format!("Failed to extract {}: {}", git_ref, e),
+        })?;
+
+    let relative = dir
+        .canonicalize()
+        .ok()
+        .and_then(|d| Some(d.strip_prefix(root.canonicalize().ok()?).ok()?.to_path_buf()))
+        .unwrap_or_default();
+    Ok(dest.join(relative))
+}
+
+/// Contents of `path`, relative to `dir`, at `git_ref`
+///
+/// Returns `None` when the file doesn't exist at that revision.
+pub fn file_at(dir: &Path, git_ref: &str, path: &Path) -> Result<Option<String>, ConflowError> {
+    git(dir, &["rev-parse", "--verify", "--quiet", &format!("{}^{{commit}}", git_ref)])?;
+    let path = path.to_string_lossy().replace('\\', "/");
+    Ok(git(dir, &["show", &format!("{}:./{}", git_ref, path)]).ok())
+}
+
+/// Shallow-clone the tag or branch `git_ref` of `url` into `dest`
+pub fn clone_tag(url: &str, git_ref: &str, dest: &Path) -> Result<(), ConflowError> {
+    guard_clone(url)?;
+    let parent = dest.parent().unwrap_or(Path::new("."));
+    let dest = dest.to_string_lossy();
+    git(
+        parent,
+        &["clone", "--quiet", "--depth", "1", "--branch", git_ref, "--", url, &dest],
+    )
+    .map(|_| ())
+}
+
+/// Shallow-clone the default branch of `url` into `dest`
+pub fn clone_head(url: &str, dest: &Path) -> Result<(), ConflowError> {
+    guard_clone(url)?;
+    let parent = dest.parent().unwrap_or(Path::new("."));
+    let dest = dest.to_string_lossy();
+    git(parent, &["clone", "--quiet", "--depth", "1", "--", url, &dest]).map(|_| ())
+}
+
+/// Fetch `version` of `url` into the cache directory `dest`, unless it is
+/// there already or `update` is set
+///
+/// `version` is a tag, tried as given and then with a `v` prefix; without
+/// one the default branch is fetched. The clone goes to a `.partial`
+/// sibling and replaces `dest`, without its `.git` directory, only once
+/// it succeeds.
+pub fn fetch(
+    url: &str,
+    version: Option<&str>,
+    dest: &Path,
+    update: bool,
+) -> Result<(), ConflowError> {
+    if dest.is_dir() && !update {
+        return Ok(());
+    }
+    let name = dest.file_name().unwrap_or_default().to_string_lossy();
+    let partial = dest.with_file_name(format!("{}.partial", name));
+    let _ = std::fs::remove_dir_all(&partial);
+    if let Some(parent) = dest.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let cloned = match version {
+        Some(tag) => clone_tag(url, tag, &partial)
+            .or_else(|_| clone_tag(url, &format!("v{}", tag), &partial)),
+        None => clone_head(url, &partial),
+    };
+    if let Err(e) = cloned {
+        let _ = std::fs::remove_dir_all(&partial);
+        return Err(e);
+    }
+    let _ = std::fs::remove_dir_all(partial.join(".git"));
+
+    let _ = std::fs::remove_dir_all(dest);
+    std::fs::rename(&partial, dest)?;
+    Ok(())
+}
+
+fn guard_clone(url: &str) -> Result<(), ConflowError> {
+    match offline::is_remote(url) {
+        true => offline::guard(format!("git clone {}", url)),
+        false => Ok(()),
+    }
+}
+
+/// A repository in a temporary directory with `files` committed, and the
+/// commit tagged `tag`; `None` when git isn't installed
+#[cfg(test)]
+pub(crate) fn test_repo(files: &[(&str, &str)], tag: Option<&str>) -> Option<tempfile::TempDir> {
+    let temp = tempfile::TempDir::new().unwrap();
+    let run = |args: &[&str]| git(temp.path(), args).ok();
+
+    run(&["init", "-q"])?;
+    run(&["config", "user.email", "test@example.com"])?;
+    run(&["config", "user.name", "test"])?;
+    for (path, content) in files {
+        let path = temp.path().join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+    run(&["add", "."])?;
+    run(&["commit", "-q", "-m", "init"])?;
+    if let Some(tag) = tag {
+        run(&["tag", tag])?;
+    }
+
+    Some(temp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn init_repo() -> Option<TempDir> {
+        test_repo(&[("a.json", "{}")], None)
+    }
+
+    #[test]
+    fn test_missing_git_binary() {
+        let e = spawn_error(std::io::Error::from(std::io::ErrorKind::NotFound));
+        assert!(e.to_string().contains("git is not installed"));
+
+        let e = spawn_error(std::io::Error::from(std::io::ErrorKind::PermissionDenied));
+        assert!(e.to_string().starts_with("Git error: Failed to run git"));
+    }
+
+    #[test]
+    fn test_changed_files() {
+        // Skip if git is not installed
+        let Some(temp) = init_repo() else {
+            return;
+        };
+
+        std::fs::write(temp.path().join("a.json"), r#"{"x": 1}"#).unwrap();
+        std::fs::write(temp.path().join("b.yaml"), "x: 1").unwrap();
+
+        let changed = changed_files(temp.path(), "HEAD").unwrap();
+        let names: Vec<_> = changed
+            .iter()
+            .filter_map(|p| p.file_name().and_then(|n| n.to_str()))
+            .collect();
+
+        assert_eq!(names, vec!["a.json", "b.yaml"]);
+    }
+
+    #[test]
+    fn test_unknown_ref_fails() {
+        let Some(temp) = init_repo() else {
+            return;
+        };
+
+        assert!(changed_files(temp.path(), "does-not-exist").is_err());
+    }
+
+    #[test]
+    fn test_export_tree() {
+        let Some(temp) = init_repo() else {
+            return;
+        };
+        std::fs::write(temp.path().join("a.json"), r#"{"changed": true}"#).unwrap();
+
+        let dest = TempDir::new().unwrap();
+        let exported = export_tree(temp.path(), "HEAD", dest.path()).unwrap();
+
+        assert_eq!(std::fs::read_to_string(exported.join("a.json")).unwrap(), "{}");
+    }
+
+    #[test]
+    fn test_file_at() {
+        let Some(temp) = init_repo() else {
+            return;
+        };
+        std::fs::write(temp.path().join("a.json"), r#"{"changed": true}"#).unwrap();
+
+        let file_at = |path: &str| file_at(temp.path(), "HEAD", Path::new(path));
+        assert_eq!(file_at("a.json").unwrap().as_deref(), Some("{}"));
+        assert_eq!(file_at("missing.json").unwrap(), None);
+        assert!(super::file_at(temp.path(), "does-not-exist", Path::new("a.json")).is_err());
+    }
+
+    #[test]
+    fn test_fetch() {
+        let Some(temp) = test_repo(&[("a.json", "{}")], Some("v1.2")) else {
+            return;
+        };
+        let url = temp.path().display().to_string();
+        let cache = TempDir::new().unwrap();
+
+        let dest = cache.path().join("1.2");
+        fetch(&url, Some("1.2"), &dest, false).unwrap();
+        assert!(dest.join("a.json").is_file());
+        assert!(!dest.join(".git").exists());
+        assert!(!cache.path().join("1.2.partial").exists());
+
+        let head = cache.path().join("HEAD");
+        fetch(&url, None, &head, false).unwrap();
+        assert!(head.join("a.json").is_file());
+
+        // A cached version is not fetched again, and a failed update keeps it
+        std::fs::remove_dir_all(temp.path().join(".git")).unwrap();
+        assert!(fetch(&url, Some("1.2"), &dest, false).is_ok());
+        assert!(fetch(&url, Some("1.2"), &dest, true).is_err());
+        assert!(dest.join("a.json").is_file());
+        assert!(fetch(&url, Some("9.9"), &cache.path().join("9.9"), false).is_err());
+        assert!(!cache.path().join("9.9").exists());
+    }
+
+    #[test]
+    fn test_commit_subjects() {
+        let Some(temp) = init_repo() else {
+            return;
+        };
+        assert_eq!(latest_tag(temp.path()), None);
+        git(temp.path(), &["tag", "v1.0.0"]).unwrap();
+        git(temp.path(), &["commit", "-q", "--allow-empty", "-m", "feat: add b"]).unwrap();
+
+        assert_eq!(latest_tag(temp.path()).as_deref(), Some("v1.0.0"));
+        let subjects = commit_subjects(temp.path(), "v1.0.0..HEAD", 10).unwrap();
+        assert_eq!(subjects.len(), 1);
+        assert_eq!(subjects[0].1, "feat: add b");
+        assert_eq!(commit_subjects(temp.path(), "HEAD", 10).unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,1856 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Go templates
+//!
+//! A subset of Go's `text/template`, with the Sprig and Helm functions
+//! charts use most, for rendering Helm charts and Go-templated config with
+//! sample values, and for RSR templates:
+//!
+//! - `{{ pipeline }}` with `|`, parentheses, `.Field` chains, `$` and
+//!   `$var`, and string, raw string, number, `true`, `false` and `nil`
+//!   literals
+//! - `if` / `else if` / `else`, `with` / `else with`, and `range` over
+//!   lists, maps (in key order) and counts, with `break` and `continue`
+//! - `$x := pipeline` and `$x = pipeline`
+//! - `define`, `template`, `block`, and the `include` and `tpl` functions
+//! - `{{-` and `-}}` trim markers and `{{/* comments */}}`
+//!
+//! Data is JSON. Missing keys, and fields of missing values, are `nil` and
+//! render as nothing instead of failing: sample values rarely set
+//! everything a template reads. Unknown functions are errors when the
+//! template is parsed, as in Go.
+
+use serde_json::{Map, Number, Value};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Error in a template, with the line it occurred on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplateError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+type Result<T> = std::result::Result<T, TemplateError>;
+
+fn error<T>(line: usize, message: impl Into<String>) -> Result<T> {
+    Err(TemplateError {
+        line,
+        message: message.into(),
+    })
+}
+
+/// Deepest `include` / `template` nesting, against runaway recursion
+const MAX_DEPTH: usize = 100;
+
+/// Longest string, or list, one function call may build
+const MAX_OUTPUT: usize = 64 << 20;
+
+/// Functions templates can call: text/template's, then Sprig's and Helm's
+const FUNCTIONS: &str = "\
+    and or not eq ne lt le gt ge len index print printf println default empty coalesce ternary \
+    required fail quote squote upper lower title trim trimAll trimSuffix trimPrefix replace \
+    contains hasPrefix hasSuffix trunc repeat cat indent nindent toYaml toJson toPrettyJson \
+    b64enc int int64 float64 atoi toString list dict get hasKey keys append first last has \
+    join splitList until add add1 sub mul div mod max min kindIs kindOf semverCompare \
+    regexMatch regexReplaceAll lookup include tpl";
+
+fn is_function(name: &str) -> bool {
+    FUNCTIONS.split_whitespace().any(|f| f == name)
+}
+
+/// Templates sharing the blocks they `define`
+#[derive(Debug, Default)]
+pub struct TemplateSet {
+    defines: HashMap<String, Vec<Node>>,
+}
+
+impl TemplateSet {
+    /// An empty set
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the blocks `source` defines, e.g. from a chart's `_helpers.tpl`
+    pub fn define(&mut self, source: &str) -> Result<()> {
+        let (_, defines) = parse(&tokenize(source)?)?;
+        self.defines.extend(defines);
+        Ok(())
+    }
+
+    /// Render `source` with `data` as `.` and `$`
+    pub fn render(&self, source: &str, data: &Value) -> Result<String> {
+        let (nodes, defines) = parse(&tokenize(source)?)?;
+        let mut state = State {
+            set: self,
+            local: &defines,
+            vars: vec![(String::new(), data.clone())],
+            depth: 0,
+        };
+        let mut out = String::with_capacity(source.len());
+        state.walk(&nodes, data, &mut out)?;
+        Ok(out)
+    }
+}
+
+/// Render `source` with `data`
+pub fn render(source: &str, data: &Value) -> Result<String> {
+    TemplateSet::new().render(source, data)
+}
+
+/// Names of the fields a template reads with `.name` or `$.name`, in order
+/// of first use
+pub fn variables(source: &str) -> Result<Vec<String>> {
+    let mut names: Vec<String> = Vec::new();
+    for token in tokenize(source)? {
+        let Token::Action { source, line } = token else {
+            continue;
+        };
+        for item in lex(&source, line)? {
+            let name = match item {
+                Item::Field(fields) => fields.into_iter().next(),
+                Item::Var(var, fields) if var.is_empty() => fields.into_iter().next(),
+                _ => None,
+            };
+            if let Some(name) = name.filter(|name| !names.contains(name)) {
+                names.push(name);
+            }
+        }
+    }
+    Ok(names)
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Tokens
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[derive(Debug)]
+enum Token {
+    Text(String),
+    Action { source: String, line: usize },
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+    let mut line = 1;
+    let mut trim_start = false;
+
+    fn push_text(tokens: &mut Vec<Token>, text: &str, trim_start: bool, trim_end: bool) {
+        let text = if trim_start { text.trim_start() } else { text };
+        let text = if trim_end { text.trim_end() } else { text };
+        if !text.is_empty() {
+            tokens.push(Token::Text(text.to_string()));
+        }
+    }
+
+    while pos < source.len() {
+        let rest = &source[pos..];
+        let Some(offset) = rest.find("{{") else {
+            push_text(&mut tokens, rest, trim_start, false);
+            break;
+        };
+        let start = pos + offset;
+        let after = &source[start + 2..];
+        let trim_end = after.starts_with('-') && after[1..].starts_with(char::is_whitespace);
+        push_text(&mut tokens, &source[pos..start], trim_start, trim_end);
+
+        let tag_line = line + source[pos..start].matches('\n').count();
+        let inner_start = start + 2 + usize::from(trim_end);
+        let Some(len) = action_end(&source[inner_start..]) else {
+            return error(tag_line, "'{{' is not closed");
+        };
+        let mut inner = &source[inner_start..inner_start + len];
+        trim_start = false;
+        if let Some(stripped) = inner.strip_suffix('-') {
+            if stripped.ends_with(char::is_whitespace) {
+                inner = stripped;
+                trim_start = true;
+            }
+        }
+        let inner = inner.trim();
+        if !inner.starts_with("/*") {
+            tokens.push(Token::Action {
+                source: inner.to_string(),
+                line: tag_line,
+            });
+        }
+
+        let end = inner_start + len + 2;
+        line += source[pos..end].matches('\n').count();
+        pos = end;
+    }
+
+    Ok(tokens)
+}
+
+/// Offset of the `}}` closing an action, skipping quoted strings
+fn action_end(source: &str) -> Option<usize> {
+    if source.trim_start().starts_with("/*") {
+        let close = source.find("*/")?;
+        return source[close..].find("}}").map(|i| close + i);
+    }
+    let bytes = source.as_bytes();
+    let mut quote = None;
+    let mut i = 0;
+    while i < bytes.len() {
+        match (quote, bytes[i]) {
+            (Some(q), b'\\') if q != b'`' => i += 1,
+            (Some(q), b) if b == q => quote = None,
+            (Some(_), _) => {}
+            (None, b @ (b'"' | b'`' | b'\'')) => quote = Some(b),
+            (None, b'}') if bytes.get(i + 1) == Some(&b'}') => return Some(i),
+            (None, _) => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Expressions
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, PartialEq)]
+enum Item {
+    /// `.Values.name`; `.` alone is an empty chain
+    Field(Vec<String>),
+    /// `$name.field`; `$` alone has an empty name
+    Var(String, Vec<String>),
+    /// `.field` straight after `)`
+    Chain(Vec<String>),
+    Ident(String),
+    Lit(Value),
+    Pipe,
+    Open,
+    Close,
+    Declare,
+    Assign,
+    Comma,
+}
+
+fn lex(source: &str, line: usize) -> Result<Vec<Item>> {
+    let chars: Vec<char> = source.chars().collect();
+    let fail = |message: &str| error(line, format!("{} in '{}'", message, source));
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+    let mut items = Vec::new();
+    let mut i = 0;
+
+    // `.a.b` from position `i`
+    let chain = |i: &mut usize| {
+        let mut fields = Vec::new();
+        while chars.get(*i) == Some(&'.') && chars.get(*i + 1).is_some_and(|&c| is_ident(c)) {
+            *i += 1;
+            let start = *i;
+            while chars.get(*i).is_some_and(|&c| is_ident(c)) {
+                *i += 1;
+            }
+            fields.push(chars[start..*i].iter().collect());
+        }
+        fields
+    };
+
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            c if c.is_whitespace() => i += 1,
+            '|' | '(' | ')' | ',' | '=' => {
+                items.push(match c {
+                    '|' => Item::Pipe,
+                    '(' => Item::Open,
+                    ')' => Item::Close,
+                    ',' => Item::Comma,
+                    _ => Item::Assign,
+                });
+                i += 1;
+            }
+            ':' if chars.get(i + 1) == Some(&'=') => {
+                items.push(Item::Declare);
+                i += 2;
+            }
+            '"' => {
+                let mut value = String::new();
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        Some('"') => break,
+                        Some('\\') => {
+                            i += 1;
+                            match chars.get(i) {
+                                Some('n') => value.push('\n'),
+                                Some('t') => value.push('\t'),
+                                Some('r') => value.push('\r'),
+                                Some(&c) => value.push(c),
+                                None => return fail("unterminated string"),
+                            }
+                        }
+                        Some(&c) => value.push(c),
+                        None => return fail("unterminated string"),
+                    }
+                    i += 1;
+                }
+                items.push(Item::Lit(Value::String(value)));
+                i += 1;
+            }
+            '`' => {
+                let Some(len) = chars[i + 1..].iter().position(|&c| c == '`') else {
+                    return fail("unterminated raw string");
+                };
+                let value: String = chars[i + 1..i + 1 + len].iter().collect();
+                items.push(Item::Lit(Value::String(value)));
+                i += len + 2;
+            }
+            '\'' => {
+                // A character constant is its code point
+                let (Some(&c), Some('\'')) = (chars.get(i + 1), chars.get(i + 2)) else {
+                    return fail("invalid character constant");
+                };
+                items.push(Item::Lit(Value::from(c as u32)));
+                i += 3;
+            }
+            '$' => {
+                i += 1;
+                let start = i;
+                while chars.get(i).is_some_and(|&c| is_ident(c)) {
+                    i += 1;
+                }
+                let name = chars[start..i].iter().collect();
+                items.push(Item::Var(name, chain(&mut i)));
+            }
+            '.' if chars.get(i + 1).is_some_and(|c| c.is_ascii_digit()) => {
+                return fail("numbers need a leading digit");
+            }
+            '.' => {
+                let after_group = i > 0 && chars[i - 1] == ')';
+                let fields = chain(&mut i);
+                if fields.is_empty() {
+                    i += 1;
+                }
+                items.push(match after_group {
+                    true => Item::Chain(fields),
+                    false => Item::Field(fields),
+                });
+            }
+            c if c.is_ascii_digit()
+                || ((c == '-' || c == '+')
+                    && chars.get(i + 1).is_some_and(char::is_ascii_digit)) =>
+            {
+                let start = i;
+                i += 1;
+                while chars
+                    .get(i)
+                    .is_some_and(|&c| c.is_ascii_alphanumeric() || c == '.' || c == '_')
+                {
+                    i += 1;
+                }
+                let text: String = chars[start..i].iter().filter(|&&c| c != '_').collect();
+                let number = match text.parse::<i64>() {
+                    Ok(n) => Value::from(n),
+                    Err(_) => match text.parse::<f64>().ok().and_then(Number::from_f64) {
+                        Some(n) => Value::Number(n),
+                        None => return fail(&format!("bad number '{}'", text)),
+                    },
+                };
+                items.push(Item::Lit(number));
+            }
+            c if is_ident(c) => {
+                let start = i;
+                while chars.get(i).is_some_and(|&c| is_ident(c)) {
+                    i += 1;
+                }
+                let word: String = chars[start..i].iter().collect();
+                items.push(match word.as_str() {
+                    "true" => Item::Lit(Value::Bool(true)),
+                    "false" => Item::Lit(Value::Bool(false)),
+                    "nil" => Item::Lit(Value::Null),
+                    _ => Item::Ident(word),
+                });
+            }
+            c => return fail(&format!("unexpected '{}'", c)),
+        }
+    }
+
+    Ok(items)
+}
+
+#[derive(Debug, Clone)]
+enum Arg {
+    /// Field chain from `.`
+    Field(Vec<String>),
+    /// Variable and field chain
+    Var(String, Vec<String>),
+    Lit(Value),
+    Func(String),
+    /// Parenthesized pipeline, and a field chain after it
+    Group(Box<Pipeline>, Vec<String>),
+}
+
+#[derive(Debug, Clone)]
+struct Command {
+    args: Vec<Arg>,
+}
+
+#[derive(Debug, Clone)]
+struct Pipeline {
+    /// Variables declared or assigned
+    vars: Vec<String>,
+    assign: bool,
+    commands: Vec<Command>,
+    line: usize,
+}
+
+struct Items<'a> {
+    items: &'a [Item],
+    pos: usize,
+    source: &'a str,
+    line: usize,
+}
+
+impl<'a> Items<'a> {
+    fn new(items: &'a [Item], source: &'a str, line: usize) -> Self {
+        Self {
+            items,
+            pos: 0,
+            source,
+            line,
+        }
+    }
+
+    fn peek(&self) -> Option<&'a Item> {
+        self.items.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<&'a Item> {
+        let item = self.items.get(self.pos);
+        self.pos += 1;
+        item
+    }
+
+    fn fail<T>(&self, message: &str) -> Result<T> {
+        error(self.line, format!("{} in '{}'", message, self.source))
+    }
+
+    fn end(&self) -> Result<()> {
+        match self.peek() {
+            None => Ok(()),
+            Some(_) => self.fail("unexpected trailing input"),
+        }
+    }
+
+    /// `$a, $b :=` or `$a =`, if the pipeline starts with one
+    fn declaration(&mut self) -> (Vec<String>, bool) {
+        let start = self.pos;
+        let mut vars = Vec::new();
+        while let Some(Item::Var(name, fields)) = self.peek() {
+            if !fields.is_empty() {
+                break;
+            }
+            vars.push(name.clone());
+            self.pos += 1;
+            match self.next() {
+                Some(Item::Comma) => continue,
+                Some(Item::Declare) => return (vars, false),
+                Some(Item::Assign) => return (vars, true),
+                _ => break,
+            }
+        }
+        self.pos = start;
+        (Vec::new(), false)
+    }
+
+    fn pipeline(&mut self) -> Result<Pipeline> {
+        let (vars, assign) = self.declaration();
+        if vars.len() > 2 || (assign && vars.len() > 1) {
+            return self.fail("too many variables");
+        }
+        let mut commands = vec![self.command()?];
+        while self.peek() == Some(&Item::Pipe) {
+            self.pos += 1;
+            commands.push(self.command()?);
+        }
+        Ok(Pipeline {
+            vars,
+            assign,
+            commands,
+            line: self.line,
+        })
+    }
+
+    fn command(&mut self) -> Result<Command> {
+        let mut args = Vec::new();
+        while let Some(item) = self.peek() {
+            if matches!(item, Item::Pipe | Item::Close) {
+                break;
+            }
+            args.push(self.operand()?);
+        }
+        if args.is_empty() {
+            return self.fail("missing value");
+        }
+        Ok(Command { args })
+    }
+
+    fn operand(&mut self) -> Result<Arg> {
+        Ok(match self.next() {
+            Some(Item::Field(fields)) => Arg::Field(fields.clone()),
+            Some(Item::Var(name, fields)) => Arg::Var(name.clone(), fields.clone()),
+            Some(Item::Lit(value)) => Arg::Lit(value.clone()),
+            Some(Item::Ident(name)) if is_function(name) => Arg::Func(name.clone()),
+            Some(Item::Ident(name)) => {
+                return self.fail(&format!("function \"{}\" not defined", name))
+            }
+            Some(Item::Open) => {
+                let pipeline = self.pipeline()?;
+                if self.next() != Some(&Item::Close) {
+                    return self.fail("unclosed '('");
+                }
+                let fields = match self.peek() {
+                    Some(Item::Chain(fields)) => {
+                        self.pos += 1;
+                        fields.clone()
+                    }
+                    _ => Vec::new(),
+                };
+                Arg::Group(Box::new(pipeline), fields)
+            }
+            _ => return self.fail("unexpected token"),
+        })
+    }
+
+    /// The quoted template name of `template`, `define` and `block`
+    fn name(&mut self) -> Result<String> {
+        match self.next() {
+            Some(Item::Lit(Value::String(name))) => Ok(name.clone()),
+            _ => self.fail("expected a quoted template name"),
+        }
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Parsing
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Block {
+    If,
+    With,
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Text(String),
+    Action(Pipeline),
+    Branch {
+        block: Block,
+        branches: Vec<(Pipeline, Vec<Node>)>,
+        otherwise: Vec<Node>,
+    },
+    Range {
+        pipeline: Pipeline,
+        body: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+    Template {
+        name: String,
+        pipeline: Option<Pipeline>,
+        line: usize,
+    },
+    Break,
+    Continue,
+}
+
+/// A block being parsed, closed by `end`
+enum Frame {
+    Branch {
+        block: Block,
+        branches: Vec<(Pipeline, Vec<Node>)>,
+        open: Option<Pipeline>,
+        line: usize,
+    },
+    Range {
+        pipeline: Pipeline,
+        body: Option<Vec<Node>>,
+        line: usize,
+    },
+    /// `define`, or `block` with the pipeline it is called with
+    Define {
+        name: String,
+        call: Option<Pipeline>,
+        line: usize,
+    },
+}
+
+type Defines = HashMap<String, Vec<Node>>;
+
+fn parse(tokens: &[Token]) -> Result<(Vec<Node>, Defines)> {
+    let mut stack: Vec<(Frame, Vec<Node>)> = Vec::new();
+    let mut nodes = Vec::new();
+    let mut defines = HashMap::new();
+
+    for token in tokens {
+        let (source, line) = match token {
+            Token::Text(text) => {
+                nodes.push(Node::Text(text.clone()));
+                continue;
+            }
+            Token::Action { source, line } => (source, *line),
+        };
+        let lexed = lex(source, line)?;
+        let mut items = Items::new(&lexed, source, line);
+        let keyword = match items.peek() {
+            Some(Item::Ident(word)) if !is_function(word) => {
+                items.pos += 1;
+                word.as_str()
+            }
+            _ => {
+                nodes.push(Node::Action(items.pipeline()?));
+                items.end()?;
+                continue;
+            }
+        };
+
+        match keyword {
+            "if" | "with" => {
+                let block = if keyword == "if" {
+                    Block::If
+                } else {
+                    Block::With
+                };
+                let frame = Frame::Branch {
+                    block,
+                    branches: Vec::new(),
+                    open: Some(items.pipeline()?),
+                    line,
+                };
+                stack.push((frame, std::mem::take(&mut nodes)));
+            }
+            "range" => {
+                let frame = Frame::Range {
+                    pipeline: items.pipeline()?,
+                    body: None,
+                    line,
+                };
+                stack.push((frame, std::mem::take(&mut nodes)));
+            }
+            "else" => match stack.last_mut() {
+                Some((
+                    Frame::Branch {
+                        block,
+                        branches,
+                        open,
+                        ..
+                    },
+                    _,
+                )) => {
+                    let Some(pipeline) = open.take() else {
+                        return error(line, "'else' after 'else'");
+                    };
+                    branches.push((pipeline, std::mem::take(&mut nodes)));
+                    let chained = match items.peek() {
+                        Some(Item::Ident(word)) if word == "if" => Some(Block::If),
+                        Some(Item::Ident(word)) if word == "with" => Some(Block::With),
+                        _ => None,
+                    };
+                    if let Some(chained) = chained {
+                        if chained != *block {
+                            return items.fail("'else' must continue the same kind of block");
+                        }
+                        items.pos += 1;
+                        *open = Some(items.pipeline()?);
+                    }
+                }
+                Some((Frame::Range { body, .. }, _)) if body.is_none() => {
+                    *body = Some(std::mem::take(&mut nodes));
+                }
+                _ => return error(line, "'else' outside of 'if', 'with' or 'range'"),
+            },
+            "end" => {
+                let Some((frame, outer)) = stack.pop() else {
+                    return error(line, "'end' without a block to close");
+                };
+                let inner = std::mem::replace(&mut nodes, outer);
+                match frame {
+                    Frame::Branch {
+                        block,
+                        mut branches,
+                        open,
+                        ..
+                    } => {
+                        let otherwise = match open {
+                            Some(pipeline) => {
+                                branches.push((pipeline, inner));
+                                Vec::new()
+                            }
+                            None => inner,
+                        };
+                        nodes.push(Node::Branch {
+                            block,
+                            branches,
+                            otherwise,
+                        });
+                    }
+                    Frame::Range { pipeline, body, .. } => {
+                        let (body, otherwise) = match body {
+                            Some(body) => (body, inner),
+                            None => (inner, Vec::new()),
+                        };
+                        nodes.push(Node::Range {
+                            pipeline,
+                            body,
+                            otherwise,
+                        });
+                    }
+                    Frame::Define { name, call, line } => {
+                        if let Some(pipeline) = call {
+                            nodes.push(Node::Template {
+                                name: name.clone(),
+                                pipeline: Some(pipeline),
+                                line,
+                            });
+                        }
+                        defines.insert(name, inner);
+                    }
+                }
+            }
+            "define" | "block" => {
+                let name = items.name()?;
+                let call = match keyword {
+                    "block" => Some(items.pipeline()?),
+                    _ => None,
+                };
+                stack.push((
+                    Frame::Define { name, call, line },
+                    std::mem::take(&mut nodes),
+                ));
+            }
+            "template" => {
+                let name = items.name()?;
+                let pipeline = match items.peek() {
+                    Some(_) => Some(items.pipeline()?),
+                    None => None,
+                };
+                nodes.push(Node::Template {
+                    name,
+                    pipeline,
+                    line,
+                });
+            }
+            "break" | "continue" => {
+                if !stack
+                    .iter()
+                    .any(|(frame, _)| matches!(frame, Frame::Range { .. }))
+                {
+                    return error(line, format!("'{}' outside of 'range'", keyword));
+                }
+                nodes.push(match keyword {
+                    "break" => Node::Break,
+                    _ => Node::Continue,
+                });
+            }
+            other => return error(line, format!("function \"{}\" not defined", other)),
+        }
+        items.end()?;
+    }
+
+    match stack.pop() {
+        Some((
+            Frame::Branch { line, .. } | Frame::Range { line, .. } | Frame::Define { line, .. },
+            _,
+        )) => error(line, "block is not closed with 'end'"),
+        None => Ok((nodes, defines)),
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Rendering
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// What a `range` body asks of its loop
+enum Flow {
+    Next,
+    Break,
+    Continue,
+}
+
+struct State<'a> {
+    set: &'a TemplateSet,
+    /// Blocks the template being rendered defines itself
+    local: &'a Defines,
+    vars: Vec<(String, Value)>,
+    depth: usize,
+}
+
+impl State<'_> {
+    fn walk(&mut self, nodes: &[Node], dot: &Value, out: &mut String) -> Result<Flow> {
+        let scope = self.vars.len();
+        let flow = self.walk_scoped(nodes, dot, out);
+        self.vars.truncate(scope);
+        flow
+    }
+
+    fn walk_scoped(&mut self, nodes: &[Node], dot: &Value, out: &mut String) -> Result<Flow> {
+        for node in nodes {
+            match node {
+                Node::Text(text) => out.push_str(text),
+                Node::Action(pipeline) => {
+                    let value = self.pipeline(pipeline, dot)?;
+                    if pipeline.vars.is_empty() {
+                        out.push_str(&print(&value));
+                    } else {
+                        self.declare(pipeline, value)?;
+                    }
+                }
+                Node::Branch {
+                    block,
+                    branches,
+                    otherwise,
+                } => {
+                    let scope = self.vars.len();
+                    let mut taken = None;
+                    for (pipeline, body) in branches {
+                        let value = self.pipeline(pipeline, dot)?;
+                        if truthy(&value) {
+                            self.declare(pipeline, value.clone())?;
+                            taken = Some((body, value));
+                            break;
+                        }
+                    }
+                    let flow = match taken {
+                        Some((body, value)) if *block == Block::With => {
+                            self.walk(body, &value, out)
+                        }
+                        Some((body, _)) => self.walk(body, dot, out),
+                        None => self.walk(otherwise, dot, out),
+                    };
+                    self.vars.truncate(scope);
+                    match flow? {
+                        Flow::Next => {}
+                        flow => return Ok(flow),
+                    }
+                }
+                Node::Range {
+                    pipeline,
+                    body,
+                    otherwise,
+                } => {
+                    let value = self.pipeline(pipeline, dot)?;
+                    let items: Vec<(Value, Value)> = match value {
+                        Value::Array(items) => items
+                            .into_iter()
+                            .enumerate()
+                            .map(|(i, item)| (Value::from(i), item))
+                            .collect(),
+                        Value::Object(map) => {
+                            let mut entries: Vec<_> = map.into_iter().collect();
+                            entries.sort_by(|a, b| a.0.cmp(&b.0));
+                            entries
+                                .into_iter()
+                                .map(|(key, item)| (Value::String(key), item))
+                                .collect()
+                        }
+                        Value::Number(ref n) if n.is_i64() => (0..n.as_i64().unwrap_or(0))
+                            .map(|i| (Value::from(i), Value::from(i)))
+                            .collect(),
+                        Value::Null => Vec::new(),
+                        other => {
+                            let message = format!("range can't iterate over {}", print(&other));
+                            return error(pipeline.line, message);
+                        }
+                    };
+                    if items.is_empty() {
+                        self.walk(otherwise, dot, out)?;
+                        continue;
+                    }
+                    for (key, item) in items {
+                        let scope = self.vars.len();
+                        match &pipeline.vars[..] {
+                            [value] => self.vars.push((value.clone(), item.clone())),
+                            [index, value] => {
+                                self.vars.push((index.clone(), key));
+                                self.vars.push((value.clone(), item.clone()));
+                            }
+                            _ => {}
+                        }
+                        let flow = self.walk(body, &item, out);
+                        self.vars.truncate(scope);
+                        if let Flow::Break = flow? {
+                            break;
+                        }
+                    }
+                }
+                Node::Template {
+                    name,
+                    pipeline,
+                    line,
+                } => {
+                    let value = match pipeline {
+                        Some(pipeline) => self.pipeline(pipeline, dot)?,
+                        None => Value::Null,
+                    };
+                    let text = self
+                        .include(name, &value)
+                        .or_else(|message| error(*line, message))?;
+                    out.push_str(&text);
+                }
+                Node::Break => return Ok(Flow::Break),
+                Node::Continue => return Ok(Flow::Continue),
+            }
+        }
+        Ok(Flow::Next)
+    }
+
+    /// Declare or assign the variables of a pipeline that isn't a `range`
+    fn declare(&mut self, pipeline: &Pipeline, value: Value) -> Result<()> {
+        let Some(name) = pipeline.vars.first() else {
+            return Ok(());
+        };
+        if !pipeline.assign {
+            self.vars.push((name.clone(), value));
+            return Ok(());
+        }
+        match self.vars.iter_mut().rev().find(|(n, _)| n == name) {
+            Some((_, var)) => {
+                *var = value;
+                Ok(())
+            }
+            None => error(pipeline.line, format!("undefined variable: ${}", name)),
+        }
+    }
+
+    /// Value of a pipeline's commands, each piped into the next
+    fn pipeline(&mut self, pipeline: &Pipeline, dot: &Value) -> Result<Value> {
+        let mut value = None;
+        for command in &pipeline.commands {
+            let result = self.command(command, dot, value.take());
+            value = Some(result.or_else(|message| error(pipeline.line, message))?);
+        }
+        Ok(value.unwrap_or(Value::Null))
+    }
+
+    fn command(
+        &mut self,
+        command: &Command,
+        dot: &Value,
+        piped: Option<Value>,
+    ) -> std::result::Result<Value, String> {
+        let (first, rest) = command.args.split_first().ok_or("missing value")?;
+        if let Arg::Func(name) = first {
+            return self.call(name, rest, dot, piped);
+        }
+
+        let has_args = !rest.is_empty() || piped.is_some();
+        // Helm's `.Capabilities.APIVersions.Has "apps/v1"`
+        if let Arg::Field(fields) = first {
+            if has_args && fields.last().is_some_and(|f| f == "Has") {
+                let list = lookup(dot, &fields[..fields.len() - 1]);
+                let mut args = self.args(rest, dot)?;
+                args.extend(piped);
+                let wanted = args.first().map(print).unwrap_or_default();
+                let has = list.as_array().is_some_and(|versions| {
+                    versions.iter().filter_map(Value::as_str).any(|version| {
+                        wanted == version || wanted.starts_with(&format!("{}/", version))
+                    })
+                });
+                return Ok(Value::Bool(has));
+            }
+        }
+        if has_args {
+            return Err("can't give arguments to a non-function".into());
+        }
+        self.arg(first, dot)
+    }
+
+    fn args(&mut self, args: &[Arg], dot: &Value) -> std::result::Result<Vec<Value>, String> {
+        args.iter().map(|arg| self.arg(arg, dot)).collect()
+    }
+
+    fn arg(&mut self, arg: &Arg, dot: &Value) -> std::result::Result<Value, String> {
+        Ok(match arg {
+            Arg::Field(fields) => lookup(dot, fields),
+            Arg::Var(name, fields) => {
+                let Some((_, value)) = self.vars.iter().rev().find(|(n, _)| n == name) else {
+                    return Err(format!("undefined variable: ${}", name));
+                };
+                lookup(value, fields)
+            }
+            Arg::Lit(value) => value.clone(),
+            Arg::Func(name) => self.call(name, &[], dot, None)?,
+            Arg::Group(pipeline, fields) => {
+                let value = self.pipeline(pipeline, dot).map_err(|e| e.message)?;
+                lookup(&value, fields)
+            }
+        })
+    }
+
+    fn call(
+        &mut self,
+        name: &str,
+        args: &[Arg],
+        dot: &Value,
+        piped: Option<Value>,
+    ) -> std::result::Result<Value, String> {
+        let mut values = self.args(args, dot)?;
+        values.extend(piped);
+        match name {
+            "include" => {
+                let [name, data] = &values[..] else {
+                    return Err("include takes a template name and data".into());
+                };
+                self.include(&print(name), data).map(Value::String)
+            }
+            "tpl" => {
+                let [source, data] = &values[..] else {
+                    return Err("tpl takes a template and data".into());
+                };
+                let source = print(source);
+                let parsed = tokenize(&source).and_then(|tokens| parse(&tokens));
+                let (nodes, _) = parsed.map_err(|e| format!("in tpl: {}", e))?;
+                let vars = std::mem::replace(&mut self.vars, vec![(String::new(), data.clone())]);
+                let mut out = String::new();
+                let result = self.walk(&nodes, data, &mut out);
+                self.vars = vars;
+                result.map_err(|e| format!("in tpl: {}", e))?;
+                Ok(Value::String(out))
+            }
+            _ => builtin(name, values),
+        }
+    }
+
+    /// Render a defined template with `data` as `.` and `$`
+    fn include(&mut self, name: &str, data: &Value) -> std::result::Result<String, String> {
+        let Some(nodes) = self.local.get(name).or_else(|| self.set.defines.get(name)) else {
+            return Err(format!("no template \"{}\"", name));
+        };
+        if self.depth >= MAX_DEPTH {
+            return Err(format!("\"{}\" nests templates too deeply", name));
+        }
+        let vars = std::mem::replace(&mut self.vars, vec![(String::new(), data.clone())]);
+        self.depth += 1;
+        let mut out = String::new();
+        let result = self.walk(nodes, data, &mut out);
+        self.depth -= 1;
+        self.vars = vars;
+        result.map_err(|e| format!("in \"{}\": {}", name, e))?;
+        Ok(out)
+    }
+}
+
+/// Follow a field chain; missing fields are `nil`
+fn lookup(value: &Value, fields: &[String]) -> Value {
+    let mut value = value;
+    for field in fields {
+        match value.get(field) {
+            Some(next) => value = next,
+            None => return Value::Null,
+        }
+    }
+    value.clone()
+}
+
+/// Whether a value counts as true in `if`, `with`, `and`, `or` and `not`
+fn truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64().is_some_and(|n| n != 0.0),
+        Value::String(s) => !s.is_empty(),
+        Value::Array(items) => !items.is_empty(),
+        Value::Object(map) => !map.is_empty(),
+    }
+}
+
+/// A value as Go's `fmt.Sprint` prints it; `nil` prints nothing, as in Helm
+fn print(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => match (n.as_i64(), n.as_f64()) {
+            (Some(i), _) => i.to_string(),
+            (None, Some(f)) if f.fract() == 0.0 && f.abs() < 1e15 => (f as i64).to_string(),
+            _ => n.to_string(),
+        },
+        Value::String(s) => s.clone(),
+        Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(print).collect();
+            format!("[{}]", items.join(" "))
+        }
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            let entries: Vec<String> = entries
+                .iter()
+                .map(|(k, v)| format!("{}:{}", k, print(v)))
+                .collect();
+            format!("map[{}]", entries.join(" "))
+        }
+    }
+}
+
+fn number(value: &Value) -> std::result::Result<f64, String> {
+    match value {
+        Value::Number(n) => Ok(n.as_f64().unwrap_or_default()),
+        Value::String(s) => s
+            .trim()
+            .parse()
+            .map_err(|_| format!("'{}' is not a number", s)),
+        Value::Bool(b) => Ok(f64::from(u8::from(*b))),
+        Value::Null => Ok(0.0),
+        other => Err(format!("{} is not a number", print(other))),
+    }
+}
+
+fn integer(value: &Value) -> std::result::Result<i64, String> {
+    match value {
+        Value::Number(n) if n.is_i64() => Ok(n.as_i64().unwrap_or_default()),
+        Value::String(s) if s.trim().parse::<i64>().is_ok() => Ok(s.trim().parse().unwrap_or(0)),
+        other => number(other).map(|n| n as i64),
+    }
+}
+
+/// A float, or an integer when it is whole
+fn from_f64(n: f64) -> Value {
+    if n.fract() == 0.0 && n.abs() < 9e15 {
+        Value::from(n as i64)
+    } else {
+        Number::from_f64(n).map_or(Value::Null, Value::Number)
+    }
+}
+
+fn equal(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => x.as_f64() == y.as_f64(),
+        _ => a == b,
+    }
+}
+
+fn compare(a: &Value, b: &Value) -> std::result::Result<std::cmp::Ordering, String> {
+    match (a, b) {
+        (Value::Number(_), Value::Number(_)) => number(a)?
+            .partial_cmp(&number(b)?)
+            .ok_or_else(|| "can't compare NaN".to_string()),
+        (Value::String(x), Value::String(y)) => Ok(x.cmp(y)),
+        _ => Err(format!(
+            "incompatible types for comparison: {} and {}",
+            kind(a),
+            kind(b)
+        )),
+    }
+}
+
+/// Sprig's name for a value's kind
+fn kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "invalid",
+        Value::Bool(_) => "bool",
+        Value::Number(n) if n.is_f64() => "float64",
+        Value::Number(_) => "int64",
+        Value::String(_) => "string",
+        Value::Array(_) => "slice",
+        Value::Object(_) => "map",
+    }
+}
+
+fn indent(spaces: usize, text: &str) -> std::result::Result<String, String> {
+    let lines = text.split('\n').count();
+    if spaces.saturating_mul(lines).saturating_add(text.len()) > MAX_OUTPUT {
+        return Err(too_long());
+    }
+    let pad = " ".repeat(spaces);
+    Ok(text
+        .split('\n')
+        .map(|line| format!("{}{}", pad, line))
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+/// `count` copies of `unit`, refused past [`MAX_OUTPUT`] bytes
+fn repeated(unit: &str, count: usize) -> std::result::Result<String, String> {
+    match unit.len().checked_mul(count) {
+        Some(len) if len <= MAX_OUTPUT => Ok(unit.repeat(count)),
+        _ => Err(too_long()),
+    }
+}
+
+fn too_long() -> String {
+    format!("output would exceed {} bytes", MAX_OUTPUT)
+}
+
+/// A count argument, as a size
+fn count(value: &Value) -> std::result::Result<usize, String> {
+    Ok(usize::try_from(integer(value)?.max(0)).unwrap_or(usize::MAX))
+}
+
+/// Go's `%q`, close enough: a double-quoted string with JSON escapes
+fn quoted(text: &str) -> String {
+    serde_json::to_string(text).unwrap_or_default()
+}
+
+fn printf(format: &str, args: &[Value]) -> std::result::Result<String, String> {
+    let mut out = String::new();
+    let mut args = args.iter();
+    let mut chars = format.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.push(c);
+            continue;
+        }
+        let mut spec = String::new();
+        while let Some(&c) = chars.peek() {
+            if !(c.is_ascii_digit() || "-+ #.".contains(c)) {
+                break;
+            }
+            spec.push(c);
+            chars.next();
+        }
+        let Some(verb) = chars.next() else {
+            out.push_str("%!(NOVERB)");
+            break;
+        };
+        if verb == '%' {
+            out.push('%');
+            continue;
+        }
+        let Some(arg) = args.next() else {
+            out.push_str(&format!("%!{}(MISSING)", verb));
+            continue;
+        };
+        let (width, precision) = match spec
+            .trim_start_matches(['-', '+', ' ', '#'])
+            .split_once('.')
+        {
+            Some((width, precision)) => (width.parse().ok(), precision.parse().ok()),
+            None => (
+                spec.trim_start_matches(['-', '+', ' ', '#']).parse().ok(),
+                None,
+            ),
+        };
+        let precision: usize = precision.unwrap_or(6);
+        if precision > MAX_OUTPUT {
+            return Err(too_long());
+        }
+        let text = match verb {
+            'd' => integer(arg).map_or_else(|_| print(arg), |n| n.to_string()),
+            'f' => format!("{:.*}", precision, number(arg).unwrap_or_default()),
+            'q' => quoted(&print(arg)),
+            'v' if arg.is_null() => "<nil>".to_string(),
+            _ => print(arg),
+        };
+        let width: usize = width.unwrap_or(0);
+        let fill = if spec.starts_with('0') && verb == 'd' {
+            "0"
+        } else {
+            " "
+        };
+        let pad = repeated(fill, width.saturating_sub(text.chars().count()))?;
+        if spec.starts_with('-') {
+            out.push_str(&text);
+            out.push_str(&pad);
+        } else {
+            out.push_str(&pad);
+            out.push_str(&text);
+        }
+    }
+    Ok(out)
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk.iter().fold(0u32, |n, &b| n << 8 | u32::from(b)) << (8 * (3 - chunk.len()));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Whether `version` meets a Masterminds semver constraint such as
+/// `>=1.19-0`, `~1.2`, `^1.2.3`, `1.2.x` or `>=1.20 <1.25 || 2.x`
+fn semver_matches(constraint: &str, version: &str) -> std::result::Result<bool, String> {
+    fn parse(version: &str) -> Option<[Option<u64>; 3]> {
+        let core = version.trim().trim_start_matches(['v', '=']);
+        let core = core.split(['-', '+']).next().unwrap_or(core);
+        let mut parts = [None; 3];
+        for (i, part) in core.split('.').enumerate() {
+            match (i, part) {
+                (3.., _) => return None,
+                (_, "x" | "X" | "*") => break,
+                (_, part) => parts[i] = Some(part.parse().ok()?),
+            }
+        }
+        parts[0].map(|_| parts)
+    }
+    let triple = |parts: [Option<u64>; 3]| parts.map(|p| p.unwrap_or(0));
+    let invalid = || format!("invalid semver constraint '{}'", constraint);
+    let have = triple(parse(version).ok_or_else(|| format!("invalid version '{}'", version))?);
+
+    for alternative in constraint.split("||") {
+        let mut words: Vec<String> = Vec::new();
+        for word in alternative.split([' ', ',']).filter(|w| !w.is_empty()) {
+            match words.last_mut() {
+                Some(last) if last.chars().all(|c| "<>=!~^".contains(c)) => last.push_str(word),
+                _ => words.push(word.to_string()),
+            }
+        }
+        let mut all = true;
+        for word in &words {
+            let split = word
+                .find(|c: char| !"<>=!~^".contains(c))
+                .unwrap_or(word.len());
+            let (op, version) = word.split_at(split);
+            let parts = parse(version).ok_or_else(invalid)?;
+            let want = triple(parts);
+            // Upper bound of a wildcard, tilde or caret range
+            let next = |level: usize| {
+                let mut bound = want;
+                bound[level] += 1;
+                bound[level + 1..].iter_mut().for_each(|p| *p = 0);
+                bound
+            };
+            let wildcard = parts.iter().position(Option::is_none);
+            let ok = match op {
+                ">=" => have >= want,
+                ">" => have > want,
+                "<=" => have <= want,
+                "<" => have < want,
+                "!=" => have != want,
+                "~" => have >= want && have < next(if parts[1].is_some() { 1 } else { 0 }),
+                "^" => have >= want && have < next(0),
+                "" | "=" => match wildcard {
+                    Some(level) => have >= want && have < next(level - 1),
+                    None => have == want,
+                },
+                _ => return Err(invalid()),
+            };
+            all &= ok;
+        }
+        if all {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Functions that don't render templates
+fn builtin(name: &str, args: Vec<Value>) -> std::result::Result<Value, String> {
+    let arity = |n: usize| match args.len() == n {
+        true => Ok(()),
+        false => Err(format!(
+            "{} takes {} argument(s), got {}",
+            name,
+            n,
+            args.len()
+        )),
+    };
+    let text = |i: usize| args.get(i).map(print).unwrap_or_default();
+    let last_text = || args.last().map(print).unwrap_or_default();
+
+    Ok(match name {
+        "and" => args
+            .iter()
+            .find(|v| !truthy(v))
+            .or(args.last())
+            .cloned()
+            .unwrap_or(Value::Null),
+        "or" => args
+            .iter()
+            .find(|v| truthy(v))
+            .or(args.last())
+            .cloned()
+            .unwrap_or(Value::Null),
+        "not" => {
+            arity(1)?;
+            Value::Bool(!truthy(&args[0]))
+        }
+        "eq" => {
+            let (first, rest) = args.split_first().ok_or("eq takes arguments")?;
+            Value::Bool(rest.iter().any(|other| equal(first, other)))
+        }
+        "ne" => {
+            arity(2)?;
+            Value::Bool(!equal(&args[0], &args[1]))
+        }
+        "lt" | "le" | "gt" | "ge" => {
+            arity(2)?;
+            let order = compare(&args[0], &args[1])?;
+            Value::Bool(match name {
+                "lt" => order.is_lt(),
+                "le" => order.is_le(),
+                "gt" => order.is_gt(),
+                _ => order.is_ge(),
+            })
+        }
+        "len" => {
+            arity(1)?;
+            Value::from(match &args[0] {
+                Value::String(s) => s.len(),
+                Value::Array(items) => items.len(),
+                Value::Object(map) => map.len(),
+                Value::Null => 0,
+                other => return Err(format!("len of {}", kind(other))),
+            })
+        }
+        "index" => {
+            let (first, keys) = args.split_first().ok_or("index takes arguments")?;
+            let mut value = first.clone();
+            for key in keys {
+                value = match (&value, key) {
+                    (Value::Array(items), key) => {
+                        let i = integer(key)?;
+                        items.get(i as usize).cloned().unwrap_or(Value::Null)
+                    }
+                    (Value::Object(map), key) => map.get(&print(key)).cloned().unwrap_or_default(),
+                    _ => Value::Null,
+                };
+            }
+            value
+        }
+        "print" | "println" => {
+            let mut out = String::new();
+            for (i, arg) in args.iter().enumerate() {
+                // Sprint spaces operands when neither is a string
+                let spaced =
+                    name == "println" || (i > 0 && !args[i - 1].is_string() && !arg.is_string());
+                if i > 0 && spaced {
+                    out.push(' ');
+                }
+                out.push_str(&print(arg));
+            }
+            if name == "println" {
+                out.push('\n');
+            }
+            Value::String(out)
+        }
+        "printf" => {
+            let (format, rest) = args.split_first().ok_or("printf takes a format")?;
+            Value::String(printf(&print(format), rest)?)
+        }
+        "default" => match &args[..] {
+            [default] => default.clone(),
+            [default, value] if !truthy(value) => default.clone(),
+            [_, value] => value.clone(),
+            _ => return Err("default takes a default and a value".into()),
+        },
+        "empty" => {
+            arity(1)?;
+            Value::Bool(!truthy(&args[0]))
+        }
+        "coalesce" => args.into_iter().find(truthy).unwrap_or(Value::Null),
+        "ternary" => {
+            arity(3)?;
+            let [yes, no, cond] = <[Value; 3]>::try_from(args).unwrap_or_default();
+            if truthy(&cond) {
+                yes
+            } else {
+                no
+            }
+        }
+        "required" => {
+            arity(2)?;
+            match &args[1] {
+                Value::Null => return Err(text(0)),
+                Value::String(s) if s.is_empty() => return Err(text(0)),
+                value => value.clone(),
+            }
+        }
+        "fail" => return Err(text(0)),
+        "quote" | "squote" => {
+            let quoted: Vec<String> = args
+                .iter()
+                .filter(|v| !v.is_null())
+                .map(|v| match name {
+                    "quote" => quoted(&print(v)),
+                    _ => format!("'{}'", print(v)),
+                })
+                .collect();
+            Value::String(quoted.join(" "))
+        }
+        "upper" => Value::String(last_text().to_uppercase()),
+        "lower" => Value::String(last_text().to_lowercase()),
+        "title" => {
+            let mut out = String::new();
+            let mut start = true;
+            for c in last_text().chars() {
+                if start {
+                    out.extend(c.to_uppercase());
+                } else {
+                    out.push(c);
+                }
+                start = c.is_whitespace();
+            }
+            Value::String(out)
+        }
+        "trim" => Value::String(last_text().trim().to_string()),
+        "trimAll" => {
+            arity(2)?;
+            let cutset: Vec<char> = text(0).chars().collect();
+            Value::String(text(1).trim_matches(&cutset[..]).to_string())
+        }
+        "trimSuffix" => {
+            arity(2)?;
+            let (suffix, s) = (text(0), text(1));
+            Value::String(s.strip_suffix(&suffix).unwrap_or(&s).to_string())
+        }
+        "trimPrefix" => {
+            arity(2)?;
+            let (prefix, s) = (text(0), text(1));
+            Value::String(s.strip_prefix(&prefix).unwrap_or(&s).to_string())
+        }
+        "replace" => {
+            arity(3)?;
+            Value::String(text(2).replace(&text(0), &text(1)))
+        }
+        "contains" => {
+            arity(2)?;
+            Value::Bool(text(1).contains(&text(0)))
+        }
+        "hasPrefix" => {
+            arity(2)?;
+            Value::Bool(text(1).starts_with(&text(0)))
+        }
+        "hasSuffix" => {
+            arity(2)?;
+            Value::Bool(text(1).ends_with(&text(0)))
+        }
+        "trunc" => {
+            arity(2)?;
+            let n = integer(&args[0])?;
+            let chars: Vec<char> = text(1).chars().collect();
+            let kept = match n {
+                n if n >= 0 => &chars[..chars.len().min(n as usize)],
+                n => &chars[chars.len().saturating_sub(n.unsigned_abs() as usize)..],
+            };
+            Value::String(kept.iter().collect())
+        }
+        "repeat" => {
+            arity(2)?;
+            Value::String(repeated(&text(1), count(&args[0])?)?)
+        }
+        "cat" => {
+            let words: Vec<String> = args.iter().filter(|v| !v.is_null()).map(print).collect();
+            Value::String(words.join(" "))
+        }
+        "indent" | "nindent" => {
+            arity(2)?;
+            let indented = indent(count(&args[0])?, &text(1))?;
+            Value::String(match name {
+                "nindent" => format!("\n{}", indented),
+                _ => indented,
+            })
+        }
+        "toYaml" => {
+            arity(1)?;
+            let yaml = serde_yaml::to_string(&args[0]).map_err(|e| e.to_string())?;
+            Value::String(yaml.strip_suffix('\n').unwrap_or(&yaml).to_string())
+        }
+        "toJson" | "toPrettyJson" => {
+            arity(1)?;
+            let json = match name {
+                "toJson" => serde_json::to_string(&args[0]),
+                _ => serde_json::to_string_pretty(&args[0]),
+            };
+            Value::String(json.map_err(|e| e.to_string())?)
+        }
+        "b64enc" => Value::String(base64(last_text().as_bytes())),
+        "int" | "int64" | "atoi" => {
+            arity(1)?;
+            Value::from(integer(&args[0]).unwrap_or(0))
+        }
+        "float64" => {
+            arity(1)?;
+            Number::from_f64(number(&args[0]).unwrap_or(0.0)).map_or(Value::Null, Value::Number)
+        }
+        "toString" => Value::String(last_text()),
+        "list" => Value::Array(args),
+        "dict" => {
+            let mut map = Map::new();
+            for pair in args.chunks(2) {
+                map.insert(print(&pair[0]), pair.get(1).cloned().unwrap_or(Value::Null));
+            }
+            Value::Object(map)
+        }
+        "get" => {
+            arity(2)?;
+            args[0]
+                .get(text(1))
+                .cloned()
+                .unwrap_or_else(|| Value::from(""))
+        }
+        "hasKey" => {
+            arity(2)?;
+            Value::Bool(args[0].get(text(1)).is_some())
+        }
+        "keys" => {
+            let mut keys: Vec<String> = args
+                .iter()
+                .filter_map(Value::as_object)
+                .flat_map(|map| map.keys().cloned())
+                .collect();
+            keys.sort();
+            Value::from(keys)
+        }
+        "append" => {
+            arity(2)?;
+            let mut items = args[0].as_array().cloned().unwrap_or_default();
+            items.push(args[1].clone());
+            Value::Array(items)
+        }
+        "first" | "last" => {
+            arity(1)?;
+            let items = args[0].as_array();
+            let item = match name {
+                "first" => items.and_then(|items| items.first()),
+                _ => items.and_then(|items| items.last()),
+            };
+            item.cloned().unwrap_or(Value::Null)
+        }
+        "has" => {
+            arity(2)?;
+            let items = args[1].as_array();
+            Value::Bool(items.is_some_and(|items| items.iter().any(|v| equal(v, &args[0]))))
+        }
+        "join" => {
+            arity(2)?;
+            let items = match &args[1] {
+                Value::Array(items) => items.iter().map(print).collect(),
+                other => vec![print(other)],
+            };
+            Value::String(items.join(&text(0)))
+        }
+        "splitList" => {
+            arity(2)?;
+            Value::from(text(1).split(&text(0)).collect::<Vec<_>>())
+        }
+        "until" => {
+            arity(1)?;
+            let n = count(&args[0])?;
+            if n > MAX_OUTPUT {
+                return Err(too_long());
+            }
+            Value::from((0..n as u64).collect::<Vec<_>>())
+        }
+        "add" | "mul" | "max" | "min" => {
+            let numbers = args
+                .iter()
+                .map(number)
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+            let (first, rest) = numbers.split_first().ok_or("takes numbers")?;
+            from_f64(rest.iter().fold(*first, |acc, &n| match name {
+                "add" => acc + n,
+                "mul" => acc * n,
+                "max" => acc.max(n),
+                _ => acc.min(n),
+            }))
+        }
+        "add1" => {
+            arity(1)?;
+            from_f64(number(&args[0])? + 1.0)
+        }
+        "sub" | "div" | "mod" => {
+            arity(2)?;
+            let (a, b) = (number(&args[0])?, number(&args[1])?);
+            if name != "sub" && b == 0.0 {
+                return Err("division by zero".into());
+            }
+            from_f64(match name {
+                "sub" => a - b,
+                // Sprig divides integers
+                "div" => (a / b).trunc(),
+                _ => a % b,
+            })
+        }
+        "kindIs" => {
+            arity(2)?;
+            Value::Bool(kind(&args[1]) == text(0))
+        }
+        "kindOf" => {
+            arity(1)?;
+            Value::from(kind(&args[0]))
+        }
+        "semverCompare" => {
+            arity(2)?;
+            Value::Bool(semver_matches(&text(0), &text(1))?)
+        }
+        "regexMatch" | "regexReplaceAll" => {
+            let regex = regex::Regex::new(&text(0)).map_err(|e| e.to_string())?;
+            match name {
+                "regexMatch" => {
+                    arity(2)?;
+                    Value::Bool(regex.is_match(&text(1)))
+                }
+                _ => {
+                    arity(3)?;
+                    Value::String(regex.replace_all(&text(1), text(2).as_str()).into_owned())
+                }
+            }
+        }
+        // Nothing is looked up in a cluster when rendering offline
+        "lookup" => Value::Object(Map::new()),
+        other => return Err(format!("function \"{}\" not defined", other)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_variables() {
+        let source = "{{ .name }}: {{ .tier | default \"gold\" }}\n\
+            {{- range splitList \",\" .ports }}{{ trim . }}{{ $.name }}{{ end }}\n\
+            {{/* .comment */}}{{ .Values.image.tag }}";
+        assert_eq!(
+            variables(source).unwrap(),
+            ["name", "tier", "ports", "Values"]
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let data = json!({
+            "Values": {
+                "name": "web",
+                "replicas": 3,
+                "ports": [80, 443],
+                "labels": {"tier": "front", "app": "web"},
+                "tls": false,
+            },
+            "Capabilities": {"APIVersions": ["v1", "policy/v1"]},
+        });
+        let template = r#"
+{{- define "app.name" -}}
+{{ .Values.name | trunc 63 | trimSuffix "-" }}
+{{- end -}}
+name: {{ include "app.name" . | quote }}
+replicas: {{ .Values.replicas | default 1 }}
+{{- with .Values.labels }}
+labels:
+  {{- toYaml . | nindent 2 }}
+{{- end }}
+ports:
+{{- range $i, $port := .Values.ports }}
+  - {{ $i }}: {{ $port }}{{ if eq $port 443 }} # tls{{ end }}
+{{- end }}
+{{- $host := printf "%s.%s" .Values.name "example.com" }}
+host: {{ $host }}
+{{- if not .Values.tls }}
+tls: {{ .Values.tls }}{{/* off */}}
+{{- else }}
+tls: on
+{{- end }}
+pdb: {{ .Capabilities.APIVersions.Has "policy/v1/PodDisruptionBudget" }}
+missing: "{{ .Values.nothing.here }}"
+"#;
+        assert_eq!(
+            render(template, &data).unwrap(),
+            r#"name: "web"
+replicas: 3
+labels:
+  app: web
+  tier: front
+ports:
+  - 0: 80
+  - 1: 443 # tls
+host: web.example.com
+tls: false
+pdb: true
+missing: ""
+"#
+        );
+
+        let mut set = TemplateSet::new();
+        set.define(r#"{{ define "greet" }}hi {{ . }}{{ end }}"#)
+            .unwrap();
+        let out = set.render(
+            r#"{{ template "greet" "there" }}, {{ tpl "{{ .a }}" (dict "a" 1) }}"#,
+            &data,
+        );
+        assert_eq!(out.unwrap(), "hi there, 1");
+        assert_eq!(
+            render(
+                "{{ range until 5 }}{{ if eq . 3 }}{{ break }}{{ end }}{{ . }}{{ end }}",
+                &data
+            )
+            .unwrap(),
+            "012"
+        );
+    }
+
+    #[test]
+    fn test_functions() {
+        let data = Value::Null;
+        let eval = |source: &str| render(source, &data).unwrap();
+        assert_eq!(
+            eval(r#"{{ printf "%-4s|%03d|%q" "a" 7 "b" }}"#),
+            "a   |007|\"b\""
+        );
+        assert_eq!(eval(r#"{{ "hello" | b64enc }}"#), "aGVsbG8=");
+        assert_eq!(
+            eval(r#"{{ add 1 2 3 }} {{ div 7 2 }} {{ sub 1 0.5 }}"#),
+            "6 3 0.5"
+        );
+        assert_eq!(eval(r#"{{ semverCompare ">=1.19-0" "v1.29.3" }}"#), "true");
+        assert_eq!(eval(r#"{{ semverCompare "~1.2 || ^3" "1.3.0" }}"#), "false");
+        assert_eq!(eval(r#"{{ semverCompare "1.2.x" "1.2.9" }}"#), "true");
+        assert_eq!(eval(r#"{{ list 1 "a" | toJson }}"#), r#"[1,"a"]"#);
+        assert_eq!(
+            eval(r#"{{ coalesce nil "" "x" }} {{ ternary "y" "n" false }}"#),
+            "x n"
+        );
+        assert_eq!(
+            eval(r#"{{ regexReplaceAll "[^a-z]" "a.b_c" "-" }}"#),
+            "a-b-c"
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let data = json!({});
+        let err = render("a\n{{ shout . }}", &data).unwrap_err();
+        assert_eq!(err.line, 2);
+        assert!(err.message.contains("function \"shout\" not defined"));
+
+        let err = render(
+            "{{ required \"image.tag is required\" .Values.tag }}",
+            &data,
+        );
+        assert_eq!(err.unwrap_err().message, "image.tag is required");
+        assert!(render("{{ if . }}x", &data)
+            .unwrap_err()
+            .message
+            .contains("not closed"));
+        assert!(render("{{ end }}", &data).is_err());
+        assert!(render("{{ include \"nope\" . }}", &data)
+            .unwrap_err()
+            .message
+            .contains("no template \"nope\""));
+        assert!(render("{{ .a", &data).is_err());
+
+        // Huge counts fail instead of exhausting memory
+        for source in [
+            "{{ repeat 9223372036854775807 \"ab\" }}",
+            "{{ indent 9223372036854775807 \"a\" }}",
+            "{{ until 9223372036854775807 }}",
+            "{{ printf \"%999999999999d\" 1 }}",
+            "{{ printf \"%.999999999999f\" 1.0 }}",
+        ] {
+            let err = render(source, &data).unwrap_err();
+            assert!(err.message.contains("would exceed"), "{}: {}", source, err);
+        }
+    }
+}
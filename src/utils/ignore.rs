@@ -0,0 +1,179 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Ignore files
+//!
+//! Paths that commands walking a project leave out: those a
+//! `.conflowignore` at the project root lists, and those a stage's
+//! `exclude` globs match. Patterns follow `.gitignore` rules. A pattern
+//! without a slash matches at any depth, a trailing slash matches only
+//! directories, ignoring a directory ignores everything under it, and `!`
+//! re-includes what an earlier pattern left out. The last matching pattern
+//! wins.
+
+use std::path::{Component, Path, PathBuf};
+
+/// Name of the ignore file at the project root
+pub const IGNORE_FILE: &str = ".conflowignore";
+
+/// Ignore patterns, relative to a root directory
+#[derive(Debug, Clone, Default)]
+pub struct Ignore {
+    root: PathBuf,
+    rules: Vec<Rule>,
+}
+
+#[derive(Debug, Clone)]
+struct Rule {
+    negated: bool,
+    /// Only matches directories, and what's under them
+    directory: bool,
+    /// The path itself
+    path: glob::Pattern,
+    /// Everything under it
+    contents: glob::Pattern,
+}
+
+impl Ignore {
+    /// Ignore patterns from a list, skipping those that aren't valid globs
+    pub fn new<'a>(root: &Path, patterns: impl IntoIterator<Item = &'a str>) -> Self {
+        Self {
+            root: root.canonicalize().unwrap_or_else(|_| root.to_path_buf()),
+            rules: patterns.into_iter().filter_map(Rule::parse).collect(),
+        }
+    }
+
+    /// The `.conflowignore` of a project, empty when there is none
+    pub fn load(root: &Path) -> Self {
+        let content = std::fs::read_to_string(root.join(IGNORE_FILE)).unwrap_or_default();
+        Self::new(root, content.lines())
+    }
+
+    /// Whether there are no patterns
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Whether a file is ignored
+    ///
+    /// Relative paths are taken from the root; absolute paths outside it
+    /// are never ignored.
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        self.matches(path, false)
+    }
+
+    /// Whether a directory, and so everything under it, is ignored
+    pub fn is_ignored_dir(&self, path: &Path) -> bool {
+        self.matches(path, true)
+    }
+
+    fn matches(&self, path: &Path, directory: bool) -> bool {
+        if self.rules.is_empty() {
+            return false;
+        }
+        let Some(path) = self.relative(path) else {
+            return false;
+        };
+        let options = glob::MatchOptions {
+            require_literal_separator: true,
+            ..Default::default()
+        };
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| {
+                rule.contents.matches_path_with(&path, options)
+                    || ((directory || !rule.directory)
+                        && rule.path.matches_path_with(&path, options))
+            })
+            .is_some_and(|rule| !rule.negated)
+    }
+
+    /// A path relative to the root, without `.` components
+    fn relative(&self, path: &Path) -> Option<PathBuf> {
+        let path = match path.is_absolute() {
+            false => path.to_path_buf(),
+            true => match path.strip_prefix(&self.root) {
+                Ok(relative) => relative.to_path_buf(),
+                Err(_) => path
+                    .canonicalize()
+                    .ok()?
+                    .strip_prefix(&self.root)
+                    .ok()?
+                    .to_path_buf(),
+            },
+        };
+        Some(
+            path.components()
+                .filter(|c| !matches!(c, Component::CurDir))
+                .collect(),
+        )
+    }
+}
+
+impl Rule {
+    fn parse(line: &str) -> Option<Self> {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        let negated = line.starts_with('!');
+        let pattern = line.trim_start_matches('!').trim_start_matches('\\');
+
+        let directory = pattern.ends_with('/');
+        let pattern = pattern.trim_end_matches('/');
+        let anchored = pattern.starts_with('/') || pattern.contains('/');
+        let pattern = pattern.trim_start_matches('/');
+        if pattern.is_empty() {
+            return None;
+        }
+        let path = match anchored || pattern.starts_with("**") {
+            true => pattern.to_string(),
+            false => format!("**/{}", pattern),
+        };
+        Some(Self {
+            negated,
+            directory,
+            contents: glob::Pattern::new(&format!("{}/**", path)).ok()?,
+            path: glob::Pattern::new(&path).ok()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ignore() {
+        let ignore = Ignore::new(
+            Path::new("/project"),
+            "\
+# Vendored and generated
+vendor/
+/build
+*.generated.yaml
+!keep.generated.yaml
+charts/*/tmp
+"
+            .lines(),
+        );
+        let file = |path: &str| ignore.is_ignored(Path::new(path));
+
+        assert!(file("vendor/lib/config.json"));
+        assert!(file("apps/vendor/values.yaml"));
+        assert!(ignore.is_ignored_dir(Path::new("./vendor")));
+        // A trailing slash only matches directories
+        assert!(!file("vendor"));
+        assert!(file("build/out.json"));
+        assert!(!file("apps/build/out.json"));
+        assert!(file("k8s/app.generated.yaml"));
+        assert!(!file("k8s/keep.generated.yaml"));
+        assert!(file("charts/web/tmp/values.yaml"));
+        assert!(!file("charts/web/values.yaml"));
+        assert!(file("/project/build/out.json"));
+        assert!(!file("/elsewhere/build/out.json"));
+
+        assert!(Ignore::new(Path::new("."), ["", "# comment"]).is_empty());
+    }
+}
@@ -0,0 +1,105 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Diagnostic logging
+//!
+//! Sets up the `tracing` subscriber. Logs go to stderr so they never mix with
+//! command output; the colored human output stays the default renderer.
+
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+
+/// Environment variable holding per-module log filters
+pub const LOG_ENV: &str = "CONFLOW_LOG";
+
+/// Standard `tracing` filter variable, read when `CONFLOW_LOG` is unset
+pub const RUST_LOG_ENV: &str = "RUST_LOG";
+
+/// Log output format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum LogFormat {
+    /// Human-readable lines
+    #[default]
+    Pretty,
+    /// One JSON object per line
+    Json,
+}
+
+/// Default filter directive for a `-v` count
+pub fn default_filter(verbosity: u8) -> &'static str {
+    match verbosity {
+        0 => "conflow=warn",
+        1 => "conflow=info",
+        2 => "conflow=debug",
+        _ => "conflow=trace",
+    }
+}
+
+/// Filter directives from the environment: `CONFLOW_LOG`, else `RUST_LOG`
+pub fn env_filter(conflow_log: Option<String>, rust_log: Option<String>, verbosity: u8) -> String {
+    conflow_log
+        .or(rust_log)
+        .filter(|directives| !directives.trim().is_empty())
+        .unwrap_or_else(|| default_filter(verbosity).to_string())
+}
+
+/// Install the global subscriber
+///
+/// `CONFLOW_LOG` (e.g. `conflow::cache=debug,conflow=info`), or `RUST_LOG`
+/// when it is unset, overrides the level derived from `verbosity`. Without
+/// either, only warnings and errors are logged unless `-v` is given.
+pub fn init(verbosity: u8, format: LogFormat) {
+    let directives = env_filter(
+        std::env::var(LOG_ENV).ok(),
+        std::env::var(RUST_LOG_ENV).ok(),
+        verbosity,
+    );
+    let filter = EnvFilter::try_new(&directives)
+        .unwrap_or_else(|_| EnvFilter::new(default_filter(verbosity)));
+
+    let registry = tracing_subscriber::registry().with(filter);
+
+    match format {
+        LogFormat::Pretty => registry
+            .with(
+                tracing_subscriber::fmt::layer()
+                    .with_writer(std::io::stderr)
+                    .with_ansi(super::colors::should_use_colors())
+                    .with_target(verbosity > 1),
+            )
+            .init(),
+        LogFormat::Json => registry
+            .with(
+                tracing_subscriber::fmt::layer()
+                    .json()
+                    .with_current_span(true)
+                    .with_span_list(true)
+                    .with_writer(std::io::stderr),
+            )
+            .init(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_filter() {
+        assert_eq!(default_filter(0), "conflow=warn");
+        assert_eq!(default_filter(1), "conflow=info");
+        assert_eq!(default_filter(2), "conflow=debug");
+        assert_eq!(default_filter(5), "conflow=trace");
+    }
+
+    #[test]
+    fn test_env_filter() {
+        let set = |value: &str| Some(value.to_string());
+        assert_eq!(env_filter(None, None, 1), "conflow=info");
+        assert_eq!(env_filter(None, set("conflow=debug"), 0), "conflow=debug");
+        assert_eq!(
+            env_filter(set("conflow::cache=trace"), set("conflow=debug"), 0),
+            "conflow::cache=trace"
+        );
+        assert_eq!(env_filter(set(""), None, 0), "conflow=warn");
+    }
+}
@@ -5,8 +5,34 @@
 //!
 //! Common utilities for the conflow CLI.
 
+pub mod codeowners;
 pub mod colors;
+pub mod cron;
+pub mod date;
+#[cfg(feature = "native")]
+pub mod git;
+pub mod gotemplate;
+pub mod ignore;
+pub mod logging;
+pub mod offline;
+pub mod redact;
+#[cfg(feature = "native")]
 pub mod spinner;
+pub mod stream;
+pub mod walk;
+pub mod yaml;
 
 pub use colors::*;
+#[cfg(feature = "native")]
 pub use spinner::*;
+
+/// A new temporary directory only the current user can enter, removed on
+/// drop
+#[cfg(feature = "native")]
+pub fn private_tempdir(prefix: &str) -> std::io::Result<tempfile::TempDir> {
+    let mut builder = tempfile::Builder::new();
+    builder.prefix(prefix);
+    #[cfg(unix)]
+    builder.permissions(std::os::unix::fs::PermissionsExt::from_mode(0o700));
+    builder.tempdir()
+}
@@ -0,0 +1,68 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Offline mode
+//!
+//! With `--offline` (or `CONFLOW_OFFLINE`) conflow makes no network
+//! requests. Toolchain downloads, profile and template fetches, git clones,
+//! notifications, webhooks, PR comments and telemetry exports are refused
+//! with an error listing what would have been fetched; `conflow bundle`
+//! pre-fetches what a project needs so it runs without them.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::errors::ConflowError;
+
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+/// Forbid network access from now on
+pub fn set_offline(offline: bool) {
+    OFFLINE.store(offline, Ordering::Relaxed);
+}
+
+/// Whether network access is forbidden
+pub fn is_offline() -> bool {
+    OFFLINE.load(Ordering::Relaxed)
+}
+
+/// Fail in offline mode, naming the request that would have been made
+pub fn guard(request: impl Into<String>) -> Result<(), ConflowError> {
+    refuse(vec![request.into()])
+}
+
+/// Fail in offline mode when there are requests to make, naming them all
+pub fn refuse(requests: Vec<String>) -> Result<(), ConflowError> {
+    match is_offline() && !requests.is_empty() {
+        true => Err(ConflowError::Offline { requests }),
+        false => Ok(()),
+    }
+}
+
+/// Whether a git URL points at another machine
+///
+/// Local paths and `file://` URLs are cloned without the network.
+pub fn is_remote(url: &str) -> bool {
+    match url.split_once("://") {
+        Some((scheme, _)) => scheme != "file",
+        // scp-like `git@host:org/repo`
+        None => url
+            .split_once(':')
+            .is_some_and(|(host, _)| !host.is_empty() && !host.contains('/') && host.len() > 1),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_remote() {
+        assert!(is_remote("https://github.com/org/repo.git"));
+        assert!(is_remote("git@github.com:org/repo.git"));
+        assert!(!is_remote("file:///srv/git/repo"));
+        assert!(!is_remote("/srv/git/repo"));
+        assert!(!is_remote("../templates"));
+        // A Windows drive letter isn't a host
+        assert!(!is_remote("C:/repos/templates"));
+    }
+}
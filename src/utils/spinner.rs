@@ -3,13 +3,42 @@
 
 //! Progress spinner utilities
 //!
-//! Provides progress indicators for long-running operations.
+//! Provides progress indicators for long-running operations. When stdout
+//! can't redraw in place (a pipe, a file, a CI log), they fall back to
+//! plain lines.
 
-use indicatif::{ProgressBar, ProgressStyle};
-use std::time::Duration;
+use colored::Colorize;
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use super::colors::is_interactive;
+use crate::i18n::tr_with;
+
+/// How often a plain "still running" line is printed in place of a spinner
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Assumed duration of a stage with no history, when no stage has any
+const DEFAULT_STAGE_ESTIMATE: Duration = Duration::from_secs(1);
+
+/// Bar positions are per mille of the expected time
+const BAR_STEPS: u64 = 1000;
+
+/// How often bars are redrawn while a stage runs
+const BAR_TICK: Duration = Duration::from_millis(100);
 
 /// Create a spinner for indeterminate progress
+///
+/// Without a terminal, the message is printed once as a line and the
+/// spinner is hidden.
 pub fn create_spinner(message: &str) -> ProgressBar {
+    if !is_interactive() {
+        println!("  {} {}", "→".blue(), message);
+        return ProgressBar::with_draw_target(None, ProgressDrawTarget::hidden());
+    }
+
     let pb = ProgressBar::new_spinner();
     pb.set_style(
         ProgressStyle::default_spinner()
@@ -23,8 +52,14 @@ pub fn create_spinner(message: &str) -> ProgressBar {
 }
 
 /// Create a progress bar for determinate progress
+///
+/// Hidden without a terminal.
 pub fn create_progress_bar(total: u64, message: &str) -> ProgressBar {
-    let pb = ProgressBar::new(total);
+    let pb = if is_interactive() {
+        ProgressBar::new(total)
+    } else {
+        ProgressBar::with_draw_target(Some(total), ProgressDrawTarget::hidden())
+    };
     pb.set_style(
         ProgressStyle::default_bar()
             .template("{msg} [{bar:40.cyan/blue}] {pos}/{len} ({eta})")
@@ -35,15 +70,309 @@ pub fn create_progress_bar(total: u64, message: &str) -> ProgressBar {
     pb
 }
 
+/// Await `future`, calling `on_tick` with the elapsed time every `interval`
+/// until it finishes
+///
+/// Stands in for a spinner where output can't redraw, so long stages still
+/// show signs of life in CI logs.
+pub async fn heartbeat<F: Future>(
+    future: F,
+    interval: Duration,
+    mut on_tick: impl FnMut(Duration),
+) -> F::Output {
+    tokio::pin!(future);
+    let start = Instant::now();
+    let mut ticker = tokio::time::interval_at((start + interval).into(), interval);
+    loop {
+        tokio::select! {
+            output = &mut future => return output,
+            _ = ticker.tick() => on_tick(start.elapsed()),
+        }
+    }
+}
+
+/// How a pipeline run shows progress
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgressMode {
+    /// A bar for each running stage and one for the whole run
+    Bars,
+    /// A plain line per update, and "still running" lines for long stages
+    Lines,
+    /// Nothing
+    Quiet,
+}
+
+impl ProgressMode {
+    /// Bars on a terminal, plain lines otherwise
+    pub fn detect() -> Self {
+        if is_interactive() {
+            Self::Bars
+        } else {
+            Self::Lines
+        }
+    }
+}
+
+/// Expected work left in a run, for the overall percentage and ETA
+#[derive(Debug, Clone, Default)]
+pub struct ProgressEstimate {
+    expected: HashMap<String, Duration>,
+    total: Duration,
+    done: Duration,
+}
+
+impl ProgressEstimate {
+    /// Estimate a run of `stages`, each with its duration on past runs
+    ///
+    /// Stages that haven't run before are assumed to take the average of
+    /// those that have.
+    pub fn new(stages: &[(String, Option<Duration>)]) -> Self {
+        let known: Vec<Duration> = stages.iter().filter_map(|(_, d)| *d).collect();
+        let fallback = match known.len() {
+            0 => DEFAULT_STAGE_ESTIMATE,
+            n => known.iter().sum::<Duration>() / n as u32,
+        };
+        let expected: HashMap<_, _> = stages
+            .iter()
+            .map(|(name, d)| (name.clone(), d.unwrap_or(fallback)))
+            .collect();
+        let total = expected.values().sum();
+        Self {
+            expected,
+            total,
+            done: Duration::ZERO,
+        }
+    }
+
+    /// Expected duration of a stage still to finish
+    pub fn expected(&self, stage: &str) -> Option<Duration> {
+        self.expected.get(stage).copied()
+    }
+
+    /// Count a stage as done, however long it actually took
+    pub fn finish(&mut self, stage: &str) {
+        if let Some(expected) = self.expected.remove(stage) {
+            self.done += expected;
+        }
+    }
+
+    /// Fraction of the run done, counting a running stage's elapsed time up
+    /// to what it's expected to take
+    pub fn fraction(&self, running: Option<(&str, Duration)>) -> f64 {
+        if self.total.is_zero() {
+            return 1.0;
+        }
+        let done = self.done + running.map_or(Duration::ZERO, |(s, e)| self.progress(s, e));
+        (done.as_secs_f64() / self.total.as_secs_f64()).min(1.0)
+    }
+
+    /// Expected time left
+    pub fn remaining(&self, running: Option<(&str, Duration)>) -> Duration {
+        let left: Duration = self.expected.values().sum();
+        left - running.map_or(Duration::ZERO, |(s, e)| self.progress(s, e))
+    }
+
+    fn progress(&self, stage: &str, elapsed: Duration) -> Duration {
+        self.expected(stage)
+            .map_or(Duration::ZERO, |e| e.min(elapsed))
+    }
+}
+
+/// Progress of a pipeline run
+///
+/// Every method is a no-op in quiet mode, so callers don't need to check.
+pub struct PipelineProgress {
+    mode: ProgressMode,
+    multi: MultiProgress,
+    overall: ProgressBar,
+    estimate: Mutex<ProgressEstimate>,
+    stages: usize,
+    finished: Mutex<usize>,
+}
+
+impl PipelineProgress {
+    /// Start showing progress of `stages`, each with its duration on past runs
+    pub fn new(mode: ProgressMode, stages: &[(String, Option<Duration>)]) -> Self {
+        let target = match mode {
+            ProgressMode::Bars => ProgressDrawTarget::stdout(),
+            ProgressMode::Lines | ProgressMode::Quiet => ProgressDrawTarget::hidden(),
+        };
+        let multi = MultiProgress::with_draw_target(target);
+        let overall = multi.add(ProgressBar::new(BAR_STEPS));
+        overall.set_style(
+            ProgressStyle::default_bar()
+                .template("  {bar:30.cyan/blue} {percent:>3}% {msg}")
+                .expect("Invalid progress bar template")
+                .progress_chars("█▓░"),
+        );
+
+        let progress = Self {
+            mode,
+            multi,
+            overall,
+            estimate: Mutex::new(ProgressEstimate::new(stages)),
+            stages: stages.len(),
+            finished: Mutex::new(0),
+        };
+        progress.update(None);
+        progress
+    }
+
+    /// How progress is shown
+    pub fn mode(&self) -> ProgressMode {
+        self.mode
+    }
+
+    /// Print a line above the bars
+    pub fn println(&self, line: impl AsRef<str>) {
+        match self.mode {
+            ProgressMode::Bars => {
+                let _ = self.multi.println(line);
+            }
+            ProgressMode::Lines => println!("{}", line.as_ref()),
+            ProgressMode::Quiet => {}
+        }
+    }
+
+    /// Hide the bars while `f` writes to the terminal
+    pub fn suspend<R>(&self, f: impl FnOnce() -> R) -> R {
+        self.multi.suspend(f)
+    }
+
+    /// Await a stage, showing it as running until it finishes
+    pub async fn run<F: Future>(&self, stage: &str, future: F) -> F::Output {
+        match self.mode {
+            ProgressMode::Quiet => future.await,
+            ProgressMode::Lines => {
+                println!("  {} {}...", "→".blue(), stage);
+                heartbeat(future, HEARTBEAT_INTERVAL, |elapsed| {
+                    let note = tr_with("run.still_running", &[("seconds", &elapsed.as_secs())]);
+                    println!("  {} {} {}", "·".dimmed(), stage, note.dimmed());
+                })
+                .await
+            }
+            ProgressMode::Bars => {
+                let expected = self.lock().expected(stage);
+                let bar = self
+                    .multi
+                    .insert_before(&self.overall, ProgressBar::new(BAR_STEPS));
+                bar.set_style(
+                    ProgressStyle::default_bar()
+                        .template("  {spinner:.blue} {prefix} {bar:20.blue} {msg:.dim}")
+                        .expect("Invalid progress bar template")
+                        .tick_chars("⠋⠙⠹⠸⠼⠴⠦⠧⠇⠏ ")
+                        .progress_chars("█▓░"),
+                );
+                bar.set_prefix(stage.to_string());
+
+                let output = heartbeat(future, BAR_TICK, |elapsed| {
+                    let (position, message) = match expected {
+                        Some(expected) if !expected.is_zero() => {
+                            let done = elapsed.as_secs_f64() / expected.as_secs_f64();
+                            let position = (done.min(1.0) * BAR_STEPS as f64) as u64;
+                            let eta =
+                                format!("{} / ~{}", format_eta(elapsed), format_eta(expected));
+                            (position, eta)
+                        }
+                        _ => (0, format_eta(elapsed)),
+                    };
+                    bar.set_position(position);
+                    bar.set_message(message);
+                    bar.tick();
+                    self.update(Some((stage, elapsed)));
+                })
+                .await;
+
+                bar.finish_and_clear();
+                self.multi.remove(&bar);
+                output
+            }
+        }
+    }
+
+    /// Count a stage as finished, whether it ran, was cached or was skipped,
+    /// and print its result line
+    pub fn finish(&self, stage: &str, line: impl AsRef<str>) {
+        self.lock().finish(stage);
+        *self.finished.lock().unwrap_or_else(|e| e.into_inner()) += 1;
+        self.println(line);
+        self.update(None);
+    }
+
+    /// Remove the bars, before printing a summary
+    pub fn clear(&self) {
+        self.overall.finish_and_clear();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, ProgressEstimate> {
+        self.estimate.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn update(&self, running: Option<(&str, Duration)>) {
+        if self.mode != ProgressMode::Bars {
+            return;
+        }
+        let (fraction, remaining) = {
+            let estimate = self.lock();
+            (estimate.fraction(running), estimate.remaining(running))
+        };
+        let finished = *self.finished.lock().unwrap_or_else(|e| e.into_inner());
+        self.overall
+            .set_position((fraction * BAR_STEPS as f64) as u64);
+        self.overall.set_message(tr_with(
+            "run.progress",
+            &[
+                ("finished", &finished),
+                ("total", &self.stages),
+                ("eta", &format_eta(remaining)),
+            ],
+        ));
+    }
+}
+
+impl Drop for PipelineProgress {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+/// A duration as `45s`, `3m 05s` or `1h 02m`
+pub fn format_eta(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs < 60 {
+        format!("{}s", secs)
+    } else if secs < 3600 {
+        format!("{}m {:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{}h {:02}m", secs / 3600, secs % 3600 / 60)
+    }
+}
+
 /// A multi-stage progress indicator
 pub struct StageProgress {
     stages: Vec<String>,
     current: usize,
+    /// Overwrite the "→" line in place rather than printing a new one
+    redraw: bool,
 }
 
 impl StageProgress {
     pub fn new(stages: Vec<String>) -> Self {
-        Self { stages, current: 0 }
+        Self {
+            stages,
+            current: 0,
+            redraw: is_interactive(),
+        }
+    }
+
+    /// Prefix that replaces the previous line when redrawing
+    fn overwrite(&self) -> &'static str {
+        if self.redraw {
+            // Move cursor up and clear the line
+            "\x1b[1A\x1b[2K"
+        } else {
+            ""
+        }
     }
 
     pub fn start(&mut self) {
@@ -53,28 +382,23 @@ impl StageProgress {
     }
 
     pub fn complete(&mut self) {
-        use colored::Colorize;
-
         if let Some(stage) = self.stages.get(self.current) {
-            // Move cursor up and overwrite
-            println!("\x1b[1A\x1b[2K  {} {}", "✓".green(), stage);
+            println!("{}  {} {}", self.overwrite(), "✓".green(), stage);
         }
         self.current += 1;
     }
 
     pub fn fail(&mut self, error: &str) {
-        use colored::Colorize;
-
         if let Some(stage) = self.stages.get(self.current) {
-            println!("\x1b[1A\x1b[2K  {} {} - {}", "✗".red(), stage, error.dimmed());
+            let (icon, error) = ("✗".red(), error.dimmed());
+            println!("{}  {} {} - {}", self.overwrite(), icon, stage, error);
         }
     }
 
     pub fn skip(&mut self) {
-        use colored::Colorize;
-
         if let Some(stage) = self.stages.get(self.current) {
-            println!("\x1b[1A\x1b[2K  {} {} (skipped)", "○".dimmed(), stage.dimmed());
+            let (icon, stage) = ("○".dimmed(), stage.dimmed());
+            println!("{}  {} {} (skipped)", self.overwrite(), icon, stage);
         }
         self.current += 1;
     }
@@ -101,4 +425,59 @@ mod tests {
         progress.complete();
         assert!(progress.is_complete());
     }
+
+    #[tokio::test]
+    async fn test_heartbeat_ticks_until_done() {
+        let mut ticks = Vec::new();
+        let output = heartbeat(
+            async {
+                tokio::time::sleep(Duration::from_millis(75)).await;
+                "done"
+            },
+            Duration::from_millis(20),
+            |elapsed| ticks.push(elapsed),
+        )
+        .await;
+
+        assert_eq!(output, "done");
+        assert!((1..=4).contains(&ticks.len()), "{:?}", ticks);
+        assert!(ticks.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn test_estimate_from_history() {
+        let stages = vec![
+            ("generate".to_string(), Some(Duration::from_secs(10))),
+            ("validate".to_string(), Some(Duration::from_secs(30))),
+            ("export".to_string(), None),
+        ];
+        let mut estimate = ProgressEstimate::new(&stages);
+
+        // The new stage is assumed to take the average of the others
+        assert_eq!(estimate.expected("export"), Some(Duration::from_secs(20)));
+        assert_eq!(estimate.remaining(None), Duration::from_secs(60));
+        assert_eq!(estimate.fraction(None), 0.0);
+
+        estimate.finish("generate");
+        let running = Some(("validate", Duration::from_secs(15)));
+        assert_eq!(estimate.fraction(running), 25.0 / 60.0);
+        assert_eq!(estimate.remaining(running), Duration::from_secs(35));
+
+        // Overrunning a stage doesn't push the run past its own share
+        let late = Some(("validate", Duration::from_secs(90)));
+        assert_eq!(estimate.fraction(late), 40.0 / 60.0);
+        assert_eq!(estimate.remaining(late), Duration::from_secs(20));
+
+        estimate.finish("validate");
+        estimate.finish("export");
+        assert_eq!(estimate.fraction(None), 1.0);
+        assert_eq!(ProgressEstimate::new(&[]).fraction(None), 1.0);
+    }
+
+    #[test]
+    fn test_format_eta() {
+        assert_eq!(format_eta(Duration::from_millis(9_500)), "9s");
+        assert_eq!(format_eta(Duration::from_secs(185)), "3m 05s");
+        assert_eq!(format_eta(Duration::from_secs(3720)), "1h 02m");
+    }
 }
@@ -0,0 +1,487 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Streaming validation for very large config files
+//!
+//! Rendered manifests for big clusters can run to hundreds of megabytes.
+//! These checks read the file through a buffer and never build a value
+//! tree: JSON is checked token by token, and YAML event by event, so memory
+//! stays bounded however large the file or any one document in it.
+
+use miette::NamedSource;
+use serde::de::IgnoredAny;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::ffi::{c_void, CStr};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::mem::MaybeUninit;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::errors::ConflowError;
+
+/// What a streaming check read
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StreamSummary {
+    /// Non-empty documents in the file
+    pub documents: usize,
+    /// Bytes read
+    pub bytes: u64,
+    /// Size of the largest document in bytes
+    pub largest: usize,
+}
+
+/// Check that a JSON or YAML file parses, without loading it whole
+pub fn check_file(path: &Path) -> Result<StreamSummary, ConflowError> {
+    let file = File::open(path).map_err(|e| ConflowError::FileReadError {
+        path: path.to_path_buf(),
+        error: e.to_string(),
+    })?;
+    let reader = BufReader::with_capacity(1 << 16, file);
+    let name = path.display().to_string();
+
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("json") => check_json(reader, &name),
+        Some("yaml" | "yml") => check_yaml(reader, &name),
+        _ => Err(ConflowError::FormatDetectionFailed {
+            path: path.to_path_buf(),
+        }),
+    }
+}
+
+/// Check a JSON document token by token
+pub fn check_json<R: Read>(reader: R, file: &str) -> Result<StreamSummary, ConflowError> {
+    let mut reader = Counting {
+        inner: reader,
+        bytes: 0,
+    };
+    let mut de = serde_json::Deserializer::from_reader(&mut reader);
+    IgnoredAny::deserialize(&mut de)
+        .and_then(|_| de.end())
+        .map_err(|e| stream_error(file, e.to_string()))?;
+
+    Ok(StreamSummary {
+        documents: 1,
+        bytes: reader.bytes,
+        largest: reader.bytes as usize,
+    })
+}
+
+/// Check a YAML stream one document at a time
+///
+/// Documents are split at `---` lines as [`crate::utils::yaml::documents`]
+/// splits them, and each is fed to libyaml's event parser as it is read,
+/// so not even a single document is held in memory.
+pub fn check_yaml<R: BufRead>(reader: R, file: &str) -> Result<StreamSummary, ConflowError> {
+    let mut summary = StreamSummary::default();
+    let mut input = DocumentInput::new(reader);
+
+    loop {
+        let parsed = parse_document(&mut input);
+        if let Some(e) = input.error.take() {
+            return Err(stream_error(file, e.to_string()));
+        }
+        if let Err(error) = parsed {
+            // Text libyaml rejects may turn out to hold only comments and
+            // directives, which don't make a document
+            input.drain();
+            if let Some(e) = input.error.take() {
+                return Err(stream_error(file, e.to_string()));
+            }
+            if input.content {
+                let message = error.message(input.first_line);
+                let message = match input.separated {
+                    true => format!("document {}: {}", summary.documents + 1, message),
+                    false => message,
+                };
+                return Err(stream_error(file, message));
+            }
+        } else if input.content {
+            summary.documents += 1;
+            summary.largest = summary.largest.max(input.document_bytes);
+        }
+
+        if !input.next_document() {
+            break;
+        }
+    }
+    summary.bytes = input.bytes;
+    Ok(summary)
+}
+
+/// The document being read, line by line, ending at the next `---`
+struct DocumentInput<R> {
+    inner: R,
+    /// The line being handed to the parser, and how much of it has been
+    line: String,
+    offset: usize,
+    /// Lines read from the stream
+    number: usize,
+    /// Bytes read from the stream
+    bytes: u64,
+    /// What followed the `---` that ended the document, if one did
+    next: Option<String>,
+    /// Seen `...`; ignore everything up to the next `---`
+    ended: bool,
+    /// 1-based line the document starts on
+    first_line: usize,
+    /// Bytes of the document handed to the parser
+    document_bytes: usize,
+    /// Whether the document has anything but comments and directives
+    content: bool,
+    /// Whether a `---` came before the document
+    separated: bool,
+    /// A read error, reported instead of the parse error it causes
+    error: Option<std::io::Error>,
+}
+
+impl<R: BufRead> DocumentInput<R> {
+    fn new(inner: R) -> Self {
+        Self {
+            inner,
+            line: String::new(),
+            offset: 0,
+            number: 0,
+            bytes: 0,
+            next: None,
+            ended: false,
+            first_line: 1,
+            document_bytes: 0,
+            content: false,
+            separated: false,
+            error: None,
+        }
+    }
+
+    /// Move on to the document after the `---` that ended this one
+    ///
+    /// Returns false at the end of the stream.
+    fn next_document(&mut self) -> bool {
+        let Some(rest) = self.next.take() else {
+            return false;
+        };
+        self.line.clear();
+        self.offset = 0;
+        self.ended = false;
+        self.first_line = self.number + 1;
+        self.document_bytes = 0;
+        self.content = false;
+        self.separated = true;
+        // Content on the marker line itself, as in `--- !tag` or `--- {a: 1}`
+        if !rest.trim().is_empty() {
+            self.first_line = self.number;
+            self.feed(rest);
+        }
+        true
+    }
+
+    /// Hand a line of the document to the parser
+    fn feed(&mut self, line: String) {
+        let trimmed = line.trim();
+        if !(trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with('%')) {
+            self.content = true;
+        }
+        self.document_bytes += line.len();
+        self.line = line;
+        self.offset = 0;
+    }
+
+    /// Read the next line of the document; false at its end
+    fn advance(&mut self) -> bool {
+        if self.next.is_some() || self.error.is_some() {
+            return false;
+        }
+        loop {
+            let mut line = String::new();
+            let read = match self.inner.read_line(&mut line) {
+                Ok(read) => read,
+                Err(e) => {
+                    self.error = Some(e);
+                    return false;
+                }
+            };
+            if read == 0 {
+                return false;
+            }
+            self.bytes += read as u64;
+            self.number += 1;
+
+            if let Some(rest) = document_start(&line) {
+                self.next = Some(rest.to_string());
+                return false;
+            } else if is_document_end(&line) {
+                self.ended = true;
+            } else if !self.ended {
+                self.feed(line);
+                return true;
+            }
+        }
+    }
+
+    /// Read the rest of the document without parsing it
+    fn drain(&mut self) {
+        while self.advance() {}
+    }
+}
+
+impl<R: BufRead> Read for DocumentInput<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.offset == self.line.len() && !self.advance() {
+            return Ok(0);
+        }
+        let rest = &self.line.as_bytes()[self.offset..];
+        let n = rest.len().min(buf.len());
+        buf[..n].copy_from_slice(&rest[..n]);
+        self.offset += n;
+        Ok(n)
+    }
+}
+
+/// A libyaml error, with its position in the document
+struct YamlError {
+    problem: String,
+    /// 0-based line and column
+    mark: (u64, u64),
+    context: Option<String>,
+}
+
+impl YamlError {
+    /// The error, with its line counted from `first_line` of the stream
+    fn message(&self, first_line: usize) -> String {
+        let (line, column) = self.mark;
+        let mut message = format!(
+            "{} at line {} column {}",
+            self.problem,
+            first_line + line as usize,
+            column + 1
+        );
+        if let Some(context) = &self.context {
+            message.push_str(", ");
+            message.push_str(context);
+        }
+        message
+    }
+}
+
+/// Run a document through libyaml's event parser
+///
+/// Aliases must name an anchor defined earlier in the document, as when
+/// the document is deserialized.
+fn parse_document<R: BufRead>(input: &mut DocumentInput<R>) -> Result<(), YamlError> {
+    /// Owns an initialized parser, deleting it on drop
+    struct Parser(Box<MaybeUninit<unsafe_libyaml::yaml_parser_t>>);
+
+    impl Drop for Parser {
+        fn drop(&mut self) {
+            // SAFETY: the parser was initialized when this was built
+            unsafe { unsafe_libyaml::yaml_parser_delete(self.0.as_mut_ptr()) }
+        }
+    }
+
+    /// libyaml read callback; `data` is the `DocumentInput`
+    unsafe fn read<R: BufRead>(
+        data: *mut c_void,
+        buffer: *mut u8,
+        size: u64,
+        size_read: *mut u64,
+    ) -> i32 {
+        // SAFETY: libyaml passes back the input registered below, which
+        // outlives the parser, and a buffer of `size` bytes
+        let (input, buffer) = unsafe {
+            (
+                &mut *(data as *mut DocumentInput<R>),
+                std::slice::from_raw_parts_mut(buffer, size as usize),
+            )
+        };
+        match input.read(buffer) {
+            Ok(n) => {
+                // SAFETY: libyaml passes a valid pointer for the count
+                unsafe { *size_read = n as u64 };
+                1
+            }
+            Err(_) => 0,
+        }
+    }
+
+    /// The text of a NUL-terminated libyaml string
+    unsafe fn text(ptr: *const u8) -> Option<Vec<u8>> {
+        // SAFETY: libyaml strings are NUL-terminated
+        (!ptr.is_null()).then(|| unsafe { CStr::from_ptr(ptr.cast()) }.to_bytes().to_vec())
+    }
+
+    let mut uninit = Box::new(MaybeUninit::uninit());
+    // SAFETY: libyaml initializes the parser in place
+    if unsafe { unsafe_libyaml::yaml_parser_initialize(uninit.as_mut_ptr()) }.fail {
+        panic!("libyaml failed to allocate a parser");
+    }
+    let mut owned = Parser(uninit);
+    let parser = owned.0.as_mut_ptr();
+    // SAFETY: the parser is boxed, so it stays put, and `input` outlives it
+    unsafe {
+        unsafe_libyaml::yaml_parser_set_encoding(parser, unsafe_libyaml::YAML_UTF8_ENCODING);
+        unsafe_libyaml::yaml_parser_set_input(
+            parser,
+            read::<R>,
+            (input as *mut DocumentInput<R>).cast(),
+        );
+    }
+
+    let mut anchors: HashSet<Vec<u8>> = HashSet::new();
+    loop {
+        let mut event = MaybeUninit::<unsafe_libyaml::yaml_event_t>::uninit();
+        let event = event.as_mut_ptr();
+        // SAFETY: the event is only read after libyaml filled it in, and
+        // deleted before the next one
+        let (kind, mark, anchor) = unsafe {
+            if unsafe_libyaml::yaml_parser_parse(parser, event).fail {
+                let parser = &*parser;
+                return Err(YamlError {
+                    problem: text(parser.problem.cast())
+                        .map(|p| String::from_utf8_lossy(&p).into_owned())
+                        .unwrap_or_else(|| "invalid YAML".to_string()),
+                    mark: (parser.problem_mark.line, parser.problem_mark.column),
+                    context: text(parser.context.cast())
+                        .map(|c| String::from_utf8_lossy(&c).into_owned()),
+                });
+            }
+            let event = &mut *event;
+            let anchor = match event.type_ {
+                unsafe_libyaml::YAML_ALIAS_EVENT => text(event.data.alias.anchor),
+                unsafe_libyaml::YAML_SCALAR_EVENT => text(event.data.scalar.anchor),
+                unsafe_libyaml::YAML_SEQUENCE_START_EVENT => text(event.data.sequence_start.anchor),
+                unsafe_libyaml::YAML_MAPPING_START_EVENT => text(event.data.mapping_start.anchor),
+                _ => None,
+            };
+            let parsed = (event.type_, event.start_mark, anchor);
+            unsafe_libyaml::yaml_event_delete(event);
+            parsed
+        };
+
+        match (kind, anchor) {
+            (unsafe_libyaml::YAML_STREAM_END_EVENT, _) => return Ok(()),
+            (unsafe_libyaml::YAML_ALIAS_EVENT, Some(anchor)) if !anchors.contains(&anchor) => {
+                return Err(YamlError {
+                    problem: "unknown anchor".to_string(),
+                    mark: (mark.line, mark.column),
+                    context: None,
+                })
+            }
+            (unsafe_libyaml::YAML_ALIAS_EVENT, _) => {}
+            (_, Some(anchor)) => {
+                anchors.insert(anchor);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The rest of a `---` line, if it starts a document
+fn document_start(line: &str) -> Option<&str> {
+    let rest = line.strip_prefix("---")?;
+    match rest.chars().next() {
+        None | Some(' ' | '\t' | '\n' | '\r') => Some(rest),
+        _ => None,
+    }
+}
+
+fn is_document_end(line: &str) -> bool {
+    line.strip_prefix("...")
+        .is_some_and(|rest| rest.trim_end_matches(['\n', '\r']).trim().is_empty())
+}
+
+/// A parse error without source context; the file is too large to attach
+fn stream_error(file: &str, message: String) -> ConflowError {
+    ConflowError::ConfigParse {
+        file: file.to_string(),
+        message,
+        source_code: Arc::new(NamedSource::new(file, String::new())),
+        span: None,
+        help: None,
+    }
+}
+
+/// Counts the bytes read through it
+struct Counting<R> {
+    inner: R,
+    bytes: u64,
+}
+
+impl<R: Read> Read for Counting<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.bytes += n as u64;
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn yaml(content: &str) -> Result<StreamSummary, ConflowError> {
+        check_yaml(content.as_bytes(), "all.yaml")
+    }
+
+    fn message(error: ConflowError) -> String {
+        match error {
+            ConflowError::ConfigParse { message, .. } => message,
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_yaml_documents_counted_like_documents() {
+        let stream = "# leading comment\n---\nkind: A\n---\n# only a comment\n--- {kind: B}\n...\nignored: [\n---\nkind: C\n";
+        let summary = yaml(stream).unwrap();
+        assert_eq!(summary.documents, 3);
+        assert_eq!(
+            summary.documents,
+            crate::utils::yaml::documents(stream).len()
+        );
+        assert_eq!(summary.bytes, stream.len() as u64);
+    }
+
+    #[test]
+    fn test_yaml_error_points_into_stream() {
+        let stream = "kind: A\n---\nkind: B\nspec:\n  items: [1, 2\n";
+        let error = message(yaml(stream).unwrap_err());
+        assert!(error.starts_with("document 2: "), "{}", error);
+        assert!(error.contains(" at line 6 "), "{}", error);
+
+        let error = message(yaml("a: 1\nb: [\n").unwrap_err());
+        assert!(!error.starts_with("document"), "{}", error);
+    }
+
+    #[test]
+    fn test_yaml_checked_event_by_event() {
+        // One document far larger than the reader's buffer
+        let items = std::iter::repeat_n("- {name: web, ports: [80, 443]}\n", 100_000);
+        let document: String = std::iter::once("items:\n").chain(items).collect();
+        let reader = BufReader::with_capacity(64, document.as_bytes());
+        let summary = check_yaml(reader, "big.yaml").unwrap();
+        assert_eq!(summary.documents, 1);
+        assert_eq!(summary.largest, document.len());
+
+        assert_eq!(
+            yaml("%YAML 1.1\n---\na: &x 1\nb: *x\n").unwrap().documents,
+            1
+        );
+        let error = message(yaml("a: 1\n---\nb: *x\n").unwrap_err());
+        assert_eq!(error, "document 2: unknown anchor at line 3 column 4");
+    }
+
+    #[test]
+    fn test_json_checked_without_tree() {
+        let json = format!(
+            "[{}]",
+            vec![r#"{"a": [1, 2, {"b": null}]}"#; 1000].join(",")
+        );
+        let summary = check_json(json.as_bytes(), "big.json").unwrap();
+        assert_eq!(summary.documents, 1);
+        assert_eq!(summary.bytes, json.len() as u64);
+
+        let error = message(check_json(r#"{"a": 1} {"#.as_bytes(), "x.json").unwrap_err());
+        assert!(error.contains("trailing characters"), "{}", error);
+    }
+}
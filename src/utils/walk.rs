@@ -0,0 +1,237 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Project walking
+//!
+//! Lists a project's files the way its `walk:` settings ask. Hidden
+//! entries, vendored directories, paths `.conflowignore` lists and, unless
+//! enabled, git submodules are left out. Symbolic links are followed,
+//! skipped or rejected; a directory or file reached twice through links is
+//! only listed once, so link cycles can't hang a walk.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use super::ignore::Ignore;
+use crate::errors::ConflowError;
+use crate::pipeline::{SymlinkPolicy, WalkConfig};
+
+/// Walks a project's directories
+#[derive(Debug, Clone, Default)]
+pub struct Walker {
+    config: WalkConfig,
+    ignore: Ignore,
+    /// List hidden entries too
+    hidden: bool,
+}
+
+impl Walker {
+    pub fn new(config: WalkConfig, ignore: Ignore) -> Self {
+        Self {
+            config,
+            ignore,
+            hidden: false,
+        }
+    }
+
+    /// Every file, hidden, vendored and submodule ones included, for
+    /// directories taken whole such as templates and snapshots
+    pub fn everything() -> Self {
+        let config = WalkConfig {
+            symlinks: SymlinkPolicy::Follow,
+            submodules: true,
+            vendor: Vec::new(),
+        };
+        Self {
+            hidden: true,
+            ..Self::new(config, Ignore::default())
+        }
+    }
+
+    /// The project in `root`: its pipeline's `walk:` section and `.conflowignore`
+    pub fn load(root: &Path) -> Self {
+        Self::new(WalkConfig::load(root), Ignore::load(root))
+    }
+
+    /// The walk settings
+    pub fn config(&self) -> &WalkConfig {
+        &self.config
+    }
+
+    /// Files under `dir`, in path order
+    pub fn files(&self, dir: &Path) -> Result<Vec<PathBuf>, ConflowError> {
+        let mut walk = Walk {
+            walker: self,
+            directories: HashSet::new(),
+            seen: HashSet::new(),
+            files: Vec::new(),
+        };
+        walk.directories.extend(dir.canonicalize().ok());
+        walk.dir(dir)?;
+        Ok(walk.files)
+    }
+
+    /// Whether a file listed some other way (by git, by a glob) is kept
+    ///
+    /// `path` is relative to `root`. Git lists neither the contents of
+    /// submodules nor what is under linked directories, so only vendored
+    /// directories, ignored paths and a link at the file itself are checked.
+    pub fn keeps(&self, root: &Path, path: &Path) -> Result<bool, ConflowError> {
+        let vendored = path
+            .parent()
+            .into_iter()
+            .flat_map(Path::components)
+            .any(|c| {
+                c.as_os_str()
+                    .to_str()
+                    .is_some_and(|n| self.config.is_vendor(n))
+            });
+        if vendored || self.ignore.is_ignored(path) {
+            return Ok(false);
+        }
+        let link = std::fs::symlink_metadata(root.join(path)).is_ok_and(|m| m.is_symlink());
+        self.follows(link, path)
+    }
+
+    /// Whether to go on past an entry, by the symlink policy
+    fn follows(&self, link: bool, path: &Path) -> Result<bool, ConflowError> {
+        match (link, self.config.symlinks) {
+            (false, _) | (true, SymlinkPolicy::Follow) => Ok(true),
+            (true, SymlinkPolicy::Skip) => Ok(false),
+            (true, SymlinkPolicy::Error) => Err(ConflowError::SymlinkRejected {
+                path: path.to_path_buf(),
+            }),
+        }
+    }
+}
+
+/// State of one walk
+struct Walk<'a> {
+    walker: &'a Walker,
+    /// Canonical directories already walked
+    directories: HashSet<PathBuf>,
+    /// Canonical files already listed
+    seen: HashSet<PathBuf>,
+    files: Vec<PathBuf>,
+}
+
+impl Walk<'_> {
+    fn dir(&mut self, dir: &Path) -> Result<(), ConflowError> {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return Ok(());
+        };
+        let mut paths: Vec<PathBuf> = entries.filter_map(|e| Some(e.ok()?.path())).collect();
+        paths.sort();
+
+        let walker = self.walker;
+        for path in paths {
+            let name = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or_default();
+            if name.starts_with('.') && !walker.hidden {
+                continue;
+            }
+            let link = std::fs::symlink_metadata(&path).is_ok_and(|m| m.is_symlink());
+            if !walker.follows(link, &path)? {
+                continue;
+            }
+            // Links are resolved to spot what has been seen through another path
+            let Ok(canonical) = path.canonicalize() else {
+                continue;
+            };
+
+            if path.is_dir() {
+                let skipped = walker.config.is_vendor(name)
+                    || walker.ignore.is_ignored_dir(&path)
+                    || (!walker.config.submodules && path.join(".git").exists());
+                if !skipped && self.directories.insert(canonical) {
+                    self.dir(&path)?;
+                }
+            } else if !walker.ignore.is_ignored(&path) && self.seen.insert(canonical) {
+                self.files.push(path);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_walk_policy() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let root = temp.path();
+        for dir in ["app", "node_modules/lib", "module", "generated"] {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        for file in [
+            "app/config.yaml",
+            "node_modules/lib/package.json",
+            "module/values.yaml",
+            "generated/out.json",
+        ] {
+            std::fs::write(root.join(file), "{}").unwrap();
+        }
+        // A submodule checkout has a `.git` file
+        std::fs::write(root.join("module/.git"), "gitdir: ../.git/modules/module").unwrap();
+
+        let ignore = Ignore::new(root, ["generated/"]);
+        let walker = Walker::new(WalkConfig::default(), ignore.clone());
+        let relative = |files: Vec<PathBuf>| -> Vec<String> {
+            files
+                .iter()
+                .map(|f| f.strip_prefix(root).unwrap().display().to_string())
+                .collect()
+        };
+        assert_eq!(relative(walker.files(root).unwrap()), ["app/config.yaml"]);
+        assert!(!walker
+            .keeps(root, Path::new("node_modules/lib/package.json"))
+            .unwrap());
+
+        let config = WalkConfig {
+            submodules: true,
+            vendor: vec![],
+            ..WalkConfig::default()
+        };
+        let walker = Walker::new(config, ignore.clone());
+        assert_eq!(
+            relative(walker.files(root).unwrap()),
+            [
+                "app/config.yaml",
+                "module/values.yaml",
+                "node_modules/lib/package.json"
+            ]
+        );
+
+        #[cfg(unix)]
+        {
+            // A link back up is walked once; a linked file is listed once
+            std::os::unix::fs::symlink(root, root.join("app/loop")).unwrap();
+            std::os::unix::fs::symlink(root.join("app/config.yaml"), root.join("app/link.yaml"))
+                .unwrap();
+            assert_eq!(relative(walker.files(root).unwrap()).len(), 3);
+
+            let config = WalkConfig {
+                symlinks: SymlinkPolicy::Error,
+                ..WalkConfig::default()
+            };
+            let walker = Walker::new(config, ignore);
+            assert!(matches!(
+                walker.files(root),
+                Err(ConflowError::SymlinkRejected { .. })
+            ));
+            assert!(walker.keeps(root, Path::new("app/link.yaml")).is_err());
+        }
+
+        // Everything, but still each file once
+        std::fs::write(root.join("app/.env"), "A=1").unwrap();
+        let everything = relative(Walker::everything().files(root).unwrap());
+        assert!(everything.contains(&"app/.env".to_string()));
+        assert!(everything.contains(&"module/.git".to_string()));
+        assert!(everything.contains(&"generated/out.json".to_string()));
+        assert!(everything.contains(&"node_modules/lib/package.json".to_string()));
+    }
+}
@@ -0,0 +1,399 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! YAML parsing helpers
+//!
+//! conflow's config structs reject unknown keys. This turns serde's
+//! "unknown field" errors into diagnostics that point at the offending key
+//! and suggest the closest valid name.
+//!
+//! Multi-document streams (`---` separated, as Kubernetes manifests usually
+//! are) are split into [`Document`]s so each one can be parsed and reported
+//! on independently.
+
+pub mod edit;
+
+use miette::{NamedSource, SourceSpan};
+use regex::Regex;
+use serde::de::DeserializeOwned;
+use std::sync::{Arc, LazyLock};
+
+use crate::errors::ConflowError;
+
+static UNKNOWN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"unknown (field|variant) `([^`]*)`(?:, expected (.*?))?(?: at line \d+ column \d+)?$",
+    )
+    .unwrap()
+});
+
+static QUOTED: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"`([^`]*)`").unwrap());
+
+static DOCUMENT_START: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?m)^---(?:[ \t]|$)").unwrap());
+
+static DOCUMENT_END: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?m)^\.\.\.[ \t]*$").unwrap());
+
+/// One document of a YAML stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Document<'a> {
+    /// Position in the stream, counting only non-empty documents (0-based)
+    pub index: usize,
+    /// Byte offset of the document content in the stream
+    pub offset: usize,
+    /// 1-based line the document content starts on
+    pub line: usize,
+    /// Document text, without the `---` marker
+    pub content: &'a str,
+}
+
+/// Split a YAML stream into its non-empty documents
+///
+/// Documents holding only comments, directives or whitespace are skipped,
+/// so a leading `---` does not produce an empty first document.
+pub fn documents(content: &str) -> Vec<Document<'_>> {
+    let mut starts = vec![0];
+    starts.extend(DOCUMENT_START.find_iter(content).map(|m| m.start() + 3));
+
+    let mut docs = Vec::new();
+    for (i, &start) in starts.iter().enumerate() {
+        let end = starts
+            .get(i + 1)
+            .map(|&next| next - 3)
+            .unwrap_or(content.len());
+        // Content starts on the line after a bare `---`
+        let start = match content[start..end].split_once('\n') {
+            Some((rest, _)) if start > 0 && rest.trim().is_empty() => start + rest.len() + 1,
+            _ => start,
+        };
+        let mut text = &content[start..end];
+        if let Some(marker) = DOCUMENT_END.find(text) {
+            text = &text[..marker.start()];
+        }
+
+        let empty = text.lines().all(|l| {
+            let l = l.trim();
+            l.is_empty() || l.starts_with('#') || l.starts_with('%')
+        });
+        if !empty {
+            docs.push(Document {
+                index: docs.len(),
+                offset: start,
+                line: line_column(content, start).0,
+                content: text,
+            });
+        }
+    }
+    docs
+}
+
+/// Join documents into a single `---` separated stream
+pub fn join<S: AsRef<str>>(documents: &[S]) -> String {
+    let mut out = String::new();
+    for document in documents {
+        let text = document.as_ref().trim_start_matches('\n');
+        let text = text.strip_prefix("---\n").unwrap_or(text);
+        if !out.is_empty() {
+            out.push_str("---\n");
+        }
+        out.push_str(text.trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+/// Parse a config file, reporting errors against its source
+pub fn parse<T: DeserializeOwned>(content: &str, file: &str) -> Result<T, ConflowError> {
+    serde_yaml::from_str(content).map_err(|e| parse_error(&e, content, 0, None, file))
+}
+
+/// Parse every document of a YAML stream
+///
+/// Errors point into the whole stream and, when there is more than one
+/// document, name the document they occurred in.
+pub fn parse_documents<T: DeserializeOwned>(
+    content: &str,
+    file: &str,
+) -> Result<Vec<T>, ConflowError> {
+    let docs = documents(content);
+    let multiple = docs.len() > 1;
+
+    docs.iter()
+        .map(|doc| {
+            serde_yaml::from_str(doc.content).map_err(|e| {
+                let index = Some(doc.index).filter(|_| multiple);
+                parse_error(&e, content, doc.offset, index, file)
+            })
+        })
+        .collect()
+}
+
+/// Build a diagnostic for an error in the document at `base`
+fn parse_error(
+    error: &serde_yaml::Error,
+    content: &str,
+    base: usize,
+    document: Option<usize>,
+    file: &str,
+) -> ConflowError {
+    let message = error.to_string();
+    let mut offset = error.location().map(|l| base + l.index());
+    let mut help = None;
+
+    let message = match UNKNOWN.captures(&message) {
+        Some(caps) => {
+            let (kind, name) = (&caps[1], &caps[2]);
+            let expected: Vec<&str> = caps
+                .get(3)
+                .map(|m| {
+                    QUOTED
+                        .captures_iter(m.as_str())
+                        .map(|c| c.get(1).unwrap().as_str())
+                })
+                .into_iter()
+                .flatten()
+                .collect();
+
+            help = Some(match suggest(name, expected.iter().copied()) {
+                Some(candidate) => format!("did you mean `{}`?", candidate),
+                None if expected.is_empty() => format!("remove `{}`", name),
+                None => format!("expected one of {}", quote_all(&expected)),
+            });
+
+            // Tagged enums are buffered, so serde reports the enclosing
+            // mapping; point at the key itself when it can be found
+            if kind == "field" {
+                offset = offset.map(|o| find_key(content, name, o).unwrap_or(o));
+            }
+
+            let prefix = &message[..caps.get(0).unwrap().start()];
+            format!("{}unknown {} `{}`", prefix, kind, name)
+        }
+        None if message.contains("more than one document") => {
+            let second = documents(content).get(1).map(|d| d.offset);
+            offset = second.or(offset);
+            help = Some("this file holds a single document; remove the extra `---`".into());
+            "found more than one YAML document".into()
+        }
+        None => message,
+    };
+
+    let span = offset.map(|o| SourceSpan::from((o, token_len(content, o))));
+    let location = offset
+        .map(|o| {
+            let (line, column) = line_column(content, o);
+            format!(" at line {} column {}", line, column)
+        })
+        .unwrap_or_default();
+    let message = match message.find(" at line ") {
+        Some(at) => message[..at].to_string(),
+        None => message,
+    };
+
+    let message = match document {
+        Some(index) => format!("document {}: {}{}", index + 1, message, location),
+        None => format!("{}{}", message, location),
+    };
+
+    ConflowError::ConfigParse {
+        file: file.to_string(),
+        message,
+        source_code: Arc::new(NamedSource::new(file, content.to_string())),
+        span,
+        help,
+    }
+}
+
+/// Closest candidate within a small edit distance
+pub fn suggest<'a>(word: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let limit = word.chars().count().div_ceil(3);
+    candidates
+        .into_iter()
+        .map(|c| (edit_distance(word, c), c))
+        .filter(|(d, _)| *d <= limit)
+        .min_by_key(|(d, _)| *d)
+        .map(|(_, c)| c)
+}
+
+/// Levenshtein distance between two strings
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                diagonal
+            } else {
+                1 + diagonal.min(above).min(row[j])
+            };
+            diagonal = above;
+        }
+    }
+
+    row[b.len()]
+}
+
+fn quote_all(names: &[&str]) -> String {
+    names
+        .iter()
+        .map(|n| format!("`{}`", n))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// 1-based line of the key at `path`, searching from `from`
+///
+/// Follows each key in turn, which works for block and flow YAML as well
+/// as JSON; list indices are skipped, so an element resolves to its list.
+pub fn key_line(content: &str, from: usize, path: &[&str]) -> Option<usize> {
+    let mut offset = from;
+    for key in path.iter().filter(|k| k.parse::<usize>().is_err()) {
+        offset = find_key(content, key, offset)?;
+    }
+    Some(line_column(content, offset).0)
+}
+
+/// Offset of `key:` at or after `from`
+fn find_key(content: &str, key: &str, from: usize) -> Option<usize> {
+    let key = regex::escape(key);
+    let pattern = format!(r#"(?m)(?:^|[\s{{,\-])({}|'{}'|"{}")\s*:"#, key, key, key);
+    let re = Regex::new(&pattern).ok()?;
+    let rest = content.get(from..)?;
+    re.captures(rest).map(|c| from + c.get(1).unwrap().start())
+}
+
+/// Length of the word starting at `offset`
+fn token_len(content: &str, offset: usize) -> usize {
+    content
+        .get(offset..)
+        .map(|rest| {
+            rest.find(|c: char| c.is_whitespace() || matches!(c, ':' | ',' | '}' | ']'))
+                .unwrap_or(rest.len())
+        })
+        .unwrap_or(0)
+        .max(1)
+}
+
+/// 1-based line and column of a byte offset
+fn line_column(content: &str, offset: usize) -> (usize, usize) {
+    let before = &content[..offset.min(content.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.len() - before.rfind('\n').map(|i| i + 1).unwrap_or(0) + 1;
+    (line, column)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::Pipeline;
+
+    fn error(yaml: &str) -> (String, Option<String>, Option<SourceSpan>) {
+        match Pipeline::from_yaml(yaml).unwrap_err() {
+            ConflowError::ConfigParse {
+                message,
+                help,
+                span,
+                ..
+            } => (message, help, span),
+            other => panic!("unexpected error: {}", other),
+        }
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("depend_on", "depends_on"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(
+            suggest("depend_on", ["name", "depends_on"]),
+            Some("depends_on")
+        );
+        assert_eq!(suggest("zzz", ["name", "depends_on"]), None);
+    }
+
+    #[test]
+    fn test_unknown_stage_key() {
+        let yaml = "name: p\nstages:\n  - name: a\n    depend_on: [b]\n    tool: { type: shell, command: \"true\" }\n    input: \"*\"\n";
+        let (message, help, span) = error(yaml);
+
+        assert_eq!(
+            message,
+            "stages[0]: unknown field `depend_on` at line 4 column 5"
+        );
+        assert_eq!(help.as_deref(), Some("did you mean `depends_on`?"));
+        let span = span.unwrap();
+        assert_eq!(
+            &yaml[span.offset()..span.offset() + span.len()],
+            "depend_on"
+        );
+    }
+
+    #[test]
+    fn test_unknown_tool_key_points_at_key() {
+        let yaml = "name: p\nstages:\n  - name: a\n    tool:\n      type: cue\n      command: vet\n      schema: [s.cue]\n    input: \"*\"\n";
+        let (message, help, _) = error(yaml);
+
+        assert_eq!(
+            message,
+            "stages[0]: unknown field `schema` at line 7 column 7"
+        );
+        assert_eq!(help.as_deref(), Some("did you mean `schemas`?"));
+    }
+
+    #[test]
+    fn test_unknown_variant_and_top_level() {
+        let yaml = "name: p\nstages:\n  - name: a\n    tool: { type: shel, command: x }\n    input: \"*\"\n";
+        let (message, help, _) = error(yaml);
+        assert!(message.starts_with("stages[0].tool.type: unknown variant `shel`"));
+        assert_eq!(help.as_deref(), Some("did you mean `shell`?"));
+
+        let (message, help, _) = error("name: p\nstages: []\ncahce: {}\n");
+        assert_eq!(message, "unknown field `cahce` at line 3 column 1");
+        assert_eq!(help.as_deref(), Some("did you mean `cache`?"));
+    }
+
+    #[test]
+    fn test_documents() {
+        let stream = "# leading comment\n---\nkind: Service\n---\n---\nkind: Deployment\n...\n";
+        let docs = documents(stream);
+
+        assert_eq!(docs.len(), 2);
+        assert_eq!(docs[0].content.trim(), "kind: Service");
+        assert_eq!((docs[1].index, docs[1].line), (1, 6));
+        assert_eq!(docs[1].content.trim(), "kind: Deployment");
+        assert_eq!(&stream[docs[1].offset..].trim_start()[..4], "kind");
+
+        assert_eq!(documents("a: 1\n").len(), 1);
+        assert_eq!(documents("text: |\n  ---\n").len(), 1);
+        assert_eq!(join(&["a: 1", "---\nb: 2\n\n"]), "a: 1\n---\nb: 2\n");
+    }
+
+    #[test]
+    fn test_parse_documents_reports_index() {
+        let stream = "---\na: 1\n---\nb: [\n";
+        match parse_documents::<serde_yaml::Value>(stream, "k8s.yaml").unwrap_err() {
+            ConflowError::ConfigParse { message, span, .. } => {
+                assert!(message.starts_with("document 2: "), "{}", message);
+                assert!(message.ends_with("line 5 column 1"), "{}", message);
+                assert!(span.unwrap().offset() >= stream.find("b:").unwrap());
+            }
+            other => panic!("unexpected error: {}", other),
+        }
+
+        let (message, help, span) = error("name: p\nstages: []\n---\nname: q\nstages: []\n");
+        assert_eq!(
+            message,
+            "found more than one YAML document at line 4 column 1"
+        );
+        assert!(help.unwrap().contains("single document"));
+        assert_eq!(span.unwrap().offset(), 23);
+
+        let values: Vec<serde_yaml::Value> =
+            parse_documents(stream.replace('[', "2").as_str(), "k").unwrap();
+        assert_eq!(values.len(), 2);
+    }
+}
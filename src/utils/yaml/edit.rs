@@ -0,0 +1,743 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Comment-preserving YAML edits
+//!
+//! Lint fixes, pipeline migrations and remediation change config files that
+//! people maintain by hand. Re-serializing the parsed data would drop their
+//! comments, anchors, quoting, key order and blank lines, so changes are made
+//! to the file text instead: each [`Change`] is placed by a line-based walk
+//! of the block YAML and only touches the lines it has to.
+//!
+//! An [`Editor`] re-parses the stream after every edit and keeps it only if
+//! the data is exactly what applying the change to the parsed data gives.
+//! Anything the walk can't place (flow collections, block scalars, anchors,
+//! tags) is refused rather than guessed at, so callers either fall back to
+//! re-serializing or report that the change has to be made by hand.
+
+use serde_yaml::Value;
+
+use super::documents;
+
+/// A structural change to one document
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    /// Rename the key at `path`
+    Rename { path: Vec<String>, to: String },
+    /// Replace the scalar at `path`
+    Set { path: Vec<String>, value: Value },
+    /// Add the key at `path` after the other keys of its parent mapping
+    Insert { path: Vec<String>, value: Value },
+    /// Add the key at `path` before the other keys of its parent mapping
+    InsertFirst { path: Vec<String>, value: Value },
+    /// Append an item to the list at `path`
+    Push { path: Vec<String>, value: Value },
+    /// Turn the mapping at `path` into a list of its values, each starting
+    /// with `key` set to the key it had
+    ToList { path: Vec<String>, key: String },
+}
+
+impl Change {
+    /// Split a dotted path, as used by [`Change`] variants
+    pub fn path(dotted: &str) -> Vec<String> {
+        dotted.split('.').map(String::from).collect()
+    }
+
+    fn target(&self) -> &[String] {
+        match self {
+            Change::Rename { path, .. }
+            | Change::Set { path, .. }
+            | Change::Insert { path, .. }
+            | Change::InsertFirst { path, .. }
+            | Change::Push { path, .. }
+            | Change::ToList { path, .. } => path,
+        }
+    }
+
+    /// Apply the change to parsed data, returning whether it fits
+    pub fn apply(&self, document: &mut Value) -> bool {
+        let path = self.target();
+        let Some((last, parents)) = path.split_last() else {
+            return false;
+        };
+        let Some(parent) = parents
+            .iter()
+            .try_fold(document, |value, segment| match value {
+                Value::Mapping(map) => map.get_mut(segment.as_str()),
+                Value::Sequence(items) => items.get_mut(segment.parse::<usize>().ok()?),
+                _ => None,
+            })
+        else {
+            return false;
+        };
+        let Value::Mapping(map) = parent else {
+            return false;
+        };
+
+        match self {
+            Change::Rename { to, .. } if !map.contains_key(to.as_str()) => {
+                let Some(index) = map.keys().position(|k| k.as_str() == Some(last)) else {
+                    return false;
+                };
+                *map = std::mem::take(map)
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, (key, value))| match i == index {
+                        true => (Value::String(to.clone()), value),
+                        false => (key, value),
+                    })
+                    .collect();
+                true
+            }
+            Change::Set { value, .. } => match map.get_mut(last.as_str()) {
+                Some(old) if !old.is_mapping() && !old.is_sequence() => {
+                    *old = value.clone();
+                    true
+                }
+                _ => false,
+            },
+            Change::Insert { value, .. } | Change::InsertFirst { value, .. }
+                if !map.contains_key(last.as_str()) =>
+            {
+                map.insert(Value::String(last.clone()), value.clone());
+                true
+            }
+            Change::Push { value, .. } => match map.get_mut(last.as_str()) {
+                Some(Value::Sequence(items)) => {
+                    items.push(value.clone());
+                    true
+                }
+                _ => false,
+            },
+            Change::ToList { key, .. } => {
+                let Some(Value::Mapping(named)) = map.get(last.as_str()) else {
+                    return false;
+                };
+                let mut items = Vec::new();
+                for (name, value) in named {
+                    let Value::Mapping(fields) = value else {
+                        return false;
+                    };
+                    if fields.contains_key(key.as_str()) {
+                        return false;
+                    }
+                    let mut item = serde_yaml::Mapping::new();
+                    item.insert(Value::String(key.clone()), name.clone());
+                    item.extend(fields.clone());
+                    items.push(Value::Mapping(item));
+                }
+                map.insert(Value::String(last.clone()), Value::Sequence(items));
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Make the change to the document at `start..end` of `content`
+    ///
+    /// Returns the whole new content, or `None` when the change can't be
+    /// placed in the text.
+    pub fn edit(&self, content: &str, start: usize, end: usize) -> Option<String> {
+        let root = lines(content, start, end);
+        let (offset, remove, insert) = match self {
+            Change::Rename { path, to } => {
+                let Node::Key { line, key_len, .. } = resolve(content, root, path)? else {
+                    return None;
+                };
+                (line.body, key_len, format_key(to))
+            }
+            Change::Set { path, value } => {
+                let Node::Key {
+                    value: Some((from, to)),
+                    ..
+                } = resolve(content, root, path)?
+                else {
+                    return None;
+                };
+                let old = &content[from..to];
+                // Anchors, aliases, tags, block and flow values aren't rewritten
+                if old.starts_with(['&', '*', '!', '|', '>', '{', '[']) {
+                    return None;
+                }
+                let new = match value {
+                    Value::Mapping(_) | Value::Sequence(_) => serde_json::to_string(value).ok()?,
+                    _ => format_scalar(value, old)?,
+                };
+                (from, to - from, new)
+            }
+            Change::Insert { path, value } | Change::InsertFirst { path, value } => {
+                let (key, parents) = path.split_last()?;
+                let block = match resolve(content, root.clone(), parents) {
+                    _ if parents.is_empty() => root,
+                    Some(Node::Key { children, .. } | Node::Item(children)) => children,
+                    None => return None,
+                };
+                let first = block.first()?;
+                if is_item(content, first) {
+                    return None;
+                }
+                let text = entry(key, value, first.indent)?;
+                if matches!(self, Change::InsertFirst { .. }) {
+                    let at = line_start(content, first);
+                    // The first key of a list item shares the `- ` line
+                    if !content[at..first.body].trim_start_matches(' ').is_empty() {
+                        return None;
+                    }
+                    (at, 0, text)
+                } else {
+                    append(content, block.last()?, text)
+                }
+            }
+            Change::Push { path, value } => {
+                let Node::Key {
+                    value: None,
+                    children,
+                    ..
+                } = resolve(content, root, path)?
+                else {
+                    return None;
+                };
+                let first = children.first()?;
+                if !is_item(content, first) {
+                    return None;
+                }
+                let pad = " ".repeat(first.indent);
+                let text = match block(value)? {
+                    Some(lines) => {
+                        let mut text = String::new();
+                        for (i, line) in lines.lines().enumerate() {
+                            let marker = if i == 0 { "- " } else { "  " };
+                            text.push_str(&format!("{}{}{}\n", pad, marker, line));
+                        }
+                        text
+                    }
+                    None => format!("{}- {}\n", pad, format_scalar(value, "")?),
+                };
+                append(content, children.last()?, text)
+            }
+            Change::ToList { path, key } => return to_list(content, root, path, key),
+        };
+
+        let mut edited = String::with_capacity(content.len() + insert.len());
+        edited.push_str(&content[..offset]);
+        edited.push_str(&insert);
+        edited.push_str(&content[offset + remove..]);
+        Some(edited)
+    }
+}
+
+/// A YAML stream being edited
+#[derive(Debug, Clone)]
+pub struct Editor {
+    content: String,
+}
+
+impl Editor {
+    /// Start editing `content`
+    pub fn new(content: impl Into<String>) -> Self {
+        Self {
+            content: content.into(),
+        }
+    }
+
+    /// Make a change to the `index`th document of the stream
+    ///
+    /// The edit is kept only if the stream still parses and the document
+    /// holds exactly what [`Change::apply`] gives; returns whether it was.
+    pub fn apply(&mut self, index: usize, change: &Change) -> bool {
+        let Some(mut expected) = parse(&self.content) else {
+            return false;
+        };
+        if !expected.get_mut(index).is_some_and(|d| change.apply(d)) {
+            return false;
+        }
+        let Some(document) = documents(&self.content).get(index).copied() else {
+            return false;
+        };
+        let end = document.offset + document.content.len();
+        let Some(edited) = change.edit(&self.content, document.offset, end) else {
+            return false;
+        };
+        if parse(&edited).is_some_and(|parsed| parsed == expected) {
+            self.content = edited;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Make every change to one document, or none of them
+    pub fn apply_all(&mut self, index: usize, changes: &[Change]) -> bool {
+        let before = self.content.clone();
+        if changes.iter().all(|change| self.apply(index, change)) {
+            true
+        } else {
+            self.content = before;
+            false
+        }
+    }
+
+    /// The edited stream
+    pub fn content(&self) -> &str {
+        &self.content
+    }
+
+    /// Finish editing, returning the edited stream
+    pub fn into_content(self) -> String {
+        self.content
+    }
+}
+
+/// Every document of a stream, if they all parse
+fn parse(content: &str) -> Option<Vec<Value>> {
+    documents(content)
+        .iter()
+        .map(|d| serde_yaml::from_str(d.content).ok())
+        .collect()
+}
+
+/// A structural line: not blank and not only a comment
+#[derive(Debug, Clone, Copy)]
+struct Line {
+    /// Byte offset of the content, after the indentation (or after the `- `
+    /// of a list item whose first key shares the line)
+    body: usize,
+    /// Byte offset of the end of the line, before the newline
+    end: usize,
+    /// Column of `body`
+    indent: usize,
+}
+
+/// What a path leads to
+enum Node {
+    /// A mapping key, its inline value (if any) and the lines nested under it
+    Key {
+        line: Line,
+        key_len: usize,
+        value: Option<(usize, usize)>,
+        children: Vec<Line>,
+    },
+    /// A list item's lines
+    Item(Vec<Line>),
+}
+
+fn lines(content: &str, start: usize, end: usize) -> Vec<Line> {
+    let mut lines = Vec::new();
+    let mut offset = start;
+    for raw in content[start..end].split_inclusive('\n') {
+        let text = raw.trim_end_matches(['\n', '\r']);
+        let indent = text.len() - text.trim_start_matches(' ').len();
+        let body = text[indent..].trim_end();
+        if !body.is_empty() && !body.starts_with('#') {
+            lines.push(Line {
+                body: offset + indent,
+                end: offset + text.len(),
+                indent,
+            });
+        }
+        offset += raw.len();
+    }
+    lines
+}
+
+fn text<'a>(content: &'a str, line: &Line) -> &'a str {
+    &content[line.body..line.end]
+}
+
+fn is_item(content: &str, line: &Line) -> bool {
+    let text = text(content, line);
+    text == "-" || text.starts_with("- ")
+}
+
+fn line_start(content: &str, line: &Line) -> usize {
+    content[..line.body].rfind('\n').map_or(0, |i| i + 1)
+}
+
+/// Offset just past the newline ending `line`
+fn next_line(content: &str, line: &Line) -> Option<usize> {
+    match content[line.end..].strip_prefix("\r\n") {
+        Some(_) => Some(line.end + 2),
+        None if content[line.end..].starts_with('\n') => Some(line.end + 1),
+        None => None,
+    }
+}
+
+/// Place `text` on new lines after `last`
+fn append(content: &str, last: &Line, mut text: String) -> (usize, usize, String) {
+    match next_line(content, last) {
+        Some(at) => (at, 0, text),
+        None => {
+            text.insert(0, '\n');
+            text.pop();
+            (last.end, 0, text)
+        }
+    }
+}
+
+fn resolve(content: &str, mut block: Vec<Line>, path: &[String]) -> Option<Node> {
+    let mut node = None;
+    for segment in path {
+        let found = match segment.parse::<usize>() {
+            Ok(index) => item(content, &block, index)?,
+            Err(_) => key(content, &block, segment)?,
+        };
+        block = match &found {
+            Node::Key { children, .. } => children.clone(),
+            Node::Item(lines) => lines.clone(),
+        };
+        node = Some(found);
+    }
+    node
+}
+
+/// The key in a mapping block
+fn key(content: &str, block: &[Line], name: &str) -> Option<Node> {
+    let indent = block.first()?.indent;
+    let (index, line, key_len) = block.iter().enumerate().find_map(|(i, line)| {
+        let len = (line.indent == indent)
+            .then(|| key_len(text(content, line), name))
+            .flatten()?;
+        Some((i, *line, len))
+    })?;
+    Some(key_at(content, block, index, line, key_len))
+}
+
+/// The key on `block[index]`, its inline value and its nested lines
+fn key_at(content: &str, block: &[Line], index: usize, line: Line, key_len: usize) -> Node {
+    let after = &content[line.body + key_len..line.end];
+    let colon = after.find(':').map_or(after.len(), |c| c + 1);
+    let rest = &after[colon..];
+    let value_start = line.body + key_len + colon + (rest.len() - rest.trim_start().len());
+    let value = &content[value_start..line.end];
+    let value = if value.is_empty() || value.starts_with('#') {
+        None
+    } else {
+        let len = match value.find(" #") {
+            Some(comment) => value[..comment].trim_end().len(),
+            None => value.trim_end().len(),
+        };
+        Some((value_start, value_start + len))
+    };
+
+    let children = block[index + 1..]
+        .iter()
+        .take_while(|l| l.indent > line.indent || (l.indent == line.indent && is_item(content, l)))
+        .copied()
+        .collect();
+    Node::Key {
+        line,
+        key_len,
+        value,
+        children,
+    }
+}
+
+/// Length of the key token if `text` starts with the key `name`
+fn key_len(text: &str, name: &str) -> Option<usize> {
+    let quoted = [
+        name.to_string(),
+        format!("\"{}\"", name),
+        format!("'{}'", name),
+    ];
+    quoted.into_iter().find_map(|token| {
+        let rest = text.strip_prefix(token.as_str())?.trim_start_matches(' ');
+        let rest = rest.strip_prefix(':')?;
+        (rest.is_empty() || rest.starts_with([' ', '\t'])).then_some(token.len())
+    })
+}
+
+/// The lines of the `index`th item of a list block
+fn item(content: &str, block: &[Line], index: usize) -> Option<Node> {
+    let indent = block.first()?.indent;
+    let starts: Vec<usize> = block
+        .iter()
+        .enumerate()
+        .filter(|(_, l)| l.indent == indent && is_item(content, l))
+        .map(|(i, _)| i)
+        .collect();
+    let start = *starts.get(index)?;
+    let end = starts.get(index + 1).copied().unwrap_or(block.len());
+
+    let first = block[start];
+    let rest = &text(content, &first)[1..];
+    let skip = 1 + rest.len() - rest.trim_start_matches(' ').len();
+    let mut lines = Vec::new();
+    if first.body + skip < first.end {
+        lines.push(Line {
+            body: first.body + skip,
+            end: first.end,
+            indent: first.indent + skip,
+        });
+    }
+    lines.extend_from_slice(&block[start + 1..end]);
+    Some(Node::Item(lines))
+}
+
+/// Rewrite a mapping of mappings as a list, re-indenting each value under
+/// its `- key: name` line
+fn to_list(content: &str, root: Vec<Line>, path: &[String], key: &str) -> Option<String> {
+    let Node::Key {
+        value: None,
+        children,
+        ..
+    } = resolve(content, root, path)?
+    else {
+        return None;
+    };
+    let indent = children.first()?.indent;
+    let entries: Vec<usize> = (0..children.len())
+        .filter(|&i| children[i].indent == indent)
+        .collect();
+
+    let mut edited = String::with_capacity(content.len());
+    let mut copied = 0;
+    for (n, &index) in entries.iter().enumerate() {
+        let line = children[index];
+        let name_len = text(content, &line).find(':')?;
+        let Node::Key {
+            value: None,
+            children: fields,
+            ..
+        } = key_at(content, &children, index, line, name_len)
+        else {
+            return None;
+        };
+        let name = text(content, &line)[..name_len].trim_end();
+        let shift = indent + 2;
+        let from = fields.first()?.indent;
+        if from <= indent {
+            return None;
+        }
+
+        // The key line becomes the item's first line, keeping any comment
+        let start = line_start(content, &line);
+        edited.push_str(&content[copied..start]);
+        edited.push_str(&" ".repeat(indent));
+        edited.push_str(&format!("- {}: {}", format_key(key), name));
+        edited.push_str(&content[line.body + name_len + 1..line.end]);
+
+        // Fields move to the item's indentation; comments indented less
+        // than them stay where they are
+        let end = match entries.get(n + 1) {
+            Some(&next) => line_start(content, &children[next]),
+            None => next_line(content, fields.last()?).unwrap_or(content.len()),
+        };
+        let body = &content[line.end..end];
+        for raw in body.split_inclusive('\n') {
+            let spaces = raw.len() - raw.trim_start_matches(' ').len();
+            if raw.trim().is_empty() || spaces < from {
+                edited.push_str(raw);
+            } else {
+                edited.push_str(&" ".repeat(shift));
+                edited.push_str(&raw[from..]);
+            }
+        }
+        copied = end;
+    }
+    edited.push_str(&content[copied..]);
+    Some(edited)
+}
+
+/// A `key: value` entry at `indent`, with collections in block style
+fn entry(key: &str, value: &Value, indent: usize) -> Option<String> {
+    let pad = " ".repeat(indent);
+    Some(match block(value)? {
+        Some(lines) => {
+            let mut text = format!("{}{}:\n", pad, format_key(key));
+            for line in lines.lines() {
+                text.push_str(&format!("{}  {}\n", pad, line));
+            }
+            text
+        }
+        None => format!("{}{}: {}\n", pad, format_key(key), inline(value)?),
+    })
+}
+
+/// A non-empty collection as block YAML; `None` for anything else
+fn block(value: &Value) -> Option<Option<String>> {
+    match value {
+        Value::Mapping(map) if !map.is_empty() => {}
+        Value::Sequence(items) if !items.is_empty() => {}
+        _ => return Some(None),
+    }
+    serde_yaml::to_string(value).ok().map(Some)
+}
+
+/// A scalar or empty collection on one line
+fn inline(value: &Value) -> Option<String> {
+    match value {
+        Value::Mapping(_) => Some("{}".into()),
+        Value::Sequence(_) => Some("[]".into()),
+        _ => format_scalar(value, ""),
+    }
+}
+
+fn format_key(key: &str) -> String {
+    let plain = key
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '/'))
+        && !key.starts_with(['-', '.']);
+    if plain && !key.is_empty() {
+        key.to_string()
+    } else {
+        serde_json::Value::String(key.to_string()).to_string()
+    }
+}
+
+/// A scalar as YAML, quoted like `old` where that's possible
+fn format_scalar(value: &Value, old: &str) -> Option<String> {
+    match value {
+        Value::String(s) if old.starts_with('"') => serde_json::to_string(s).ok(),
+        Value::String(s) if old.starts_with('\'') => Some(format!("'{}'", s.replace('\'', "''"))),
+        Value::Mapping(_) | Value::Sequence(_) | Value::Tagged(_) => None,
+        _ => {
+            let yaml = serde_yaml::to_string(value).ok()?;
+            let yaml = yaml.trim_end();
+            if yaml.contains('\n') {
+                serde_json::to_string(value).ok()
+            } else {
+                Some(yaml.to_string())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POD: &str = "\
+# the web pod
+kind: Pod
+spec:
+  serviceAccount: web   # legacy field
+  containers:
+  - name: web
+    imagePullPolicy: \"always\"
+
+    ports: [{ containerPort: 80 }]
+  - name: proxy
+    image: envoy
+";
+
+    #[test]
+    fn test_edits_keep_comments() {
+        let rename = Change::Rename {
+            path: Change::path("spec.serviceAccount"),
+            to: "serviceAccountName".into(),
+        };
+        let edited = rename.edit(POD, 0, POD.len()).unwrap();
+        assert!(edited.contains("  serviceAccountName: web   # legacy field\n"));
+
+        let set = Change::Set {
+            path: Change::path("spec.containers.0.imagePullPolicy"),
+            value: "Always".into(),
+        };
+        let edited = set.edit(&edited, 0, edited.len()).unwrap();
+        assert!(edited.contains("    imagePullPolicy: \"Always\"\n"));
+
+        let insert = Change::Insert {
+            path: Change::path("spec.containers.1.imagePullPolicy"),
+            value: "IfNotPresent".into(),
+        };
+        let edited = insert.edit(&edited, 0, edited.len()).unwrap();
+        assert!(edited.ends_with("    image: envoy\n    imagePullPolicy: IfNotPresent\n"));
+        assert!(edited.starts_with("# the web pod\n"));
+
+        let mut document: Value = serde_yaml::from_str(POD).unwrap();
+        for change in [rename, set, insert] {
+            assert!(change.apply(&mut document));
+        }
+        let reparsed: Value = serde_yaml::from_str(&edited).unwrap();
+        assert_eq!(reparsed, document);
+
+        // Inside a flow mapping there are no lines to place the edit on
+        let flow = Change::Set {
+            path: Change::path("spec.containers.0.ports.0.containerPort"),
+            value: 8080.into(),
+        };
+        assert_eq!(flow.edit(POD, 0, POD.len()), None);
+    }
+
+    #[test]
+    fn test_editor_checks_each_edit() {
+        let content = "\
+# pipeline
+name: app   # the app
+stages:
+  # checks first
+  validate:
+    tool: { type: cue, command: vet }
+
+    input: \"*.yaml\"
+  build:
+    # compile
+    tool: { type: shell, command: make }
+    input: src
+---
+kind: Other
+";
+        let mut editor = Editor::new(content);
+        assert!(editor.apply_all(
+            0,
+            &[
+                Change::ToList {
+                    path: Change::path("stages"),
+                    key: "name".into(),
+                },
+                Change::InsertFirst {
+                    path: Change::path("version"),
+                    value: "1".into(),
+                },
+                Change::Push {
+                    path: Change::path("stages"),
+                    value: serde_yaml::from_str("{name: test, input: [a, b]}").unwrap(),
+                },
+                Change::Insert {
+                    path: Change::path("cache"),
+                    value: serde_yaml::from_str("enabled: true").unwrap(),
+                },
+            ],
+        ));
+        assert_eq!(
+            editor.content(),
+            "\
+# pipeline
+version: '1'
+name: app   # the app
+stages:
+  # checks first
+  - name: validate
+    tool: { type: cue, command: vet }
+
+    input: \"*.yaml\"
+  - name: build
+    # compile
+    tool: { type: shell, command: make }
+    input: src
+  - name: test
+    input:
+    - a
+    - b
+cache:
+  enabled: true
+---
+kind: Other
+"
+        );
+
+        // A change that doesn't fit the data leaves the stream untouched
+        let before = editor.content().to_string();
+        let missing = Change::Rename {
+            path: Change::path("spec.missing"),
+            to: "other".into(),
+        };
+        let kind = Change::Set {
+            path: Change::path("kind"),
+            value: "Pod".into(),
+        };
+        assert!(!editor.apply_all(1, &[kind, missing.clone()]));
+        assert!(!editor.apply(1, &missing));
+        assert_eq!(editor.content(), before);
+    }
+}
@@ -0,0 +1,96 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Browser bindings
+//!
+//! Exposes the analyzer, pipeline validation and the RSR schema registry to
+//! JavaScript, for a playground that checks pasted configuration without a
+//! server. Build without the `native` feature:
+//!
+//! ```bash
+//! cargo build --lib --release --target wasm32-unknown-unknown \
+//!     --no-default-features --features wasm
+//! wasm-bindgen --target web --out-dir pkg \
+//!     target/wasm32-unknown-unknown/release/conflow.wasm
+//! ```
+//!
+//! Results are JSON strings; analyses use the [`crate::report`] format.
+
+use std::path::Path;
+
+use wasm_bindgen::prelude::*;
+
+use crate::analyzer::ConfigAnalyzer;
+use crate::errors::ConflowError;
+use crate::pipeline::PipelineValidator;
+use crate::rsr::RsrSchemaRegistry;
+
+/// Library version
+#[wasm_bindgen]
+pub fn version() -> String {
+    crate::VERSION.to_string()
+}
+
+/// Analyze pasted configuration and recommend CUE or Nickel
+///
+/// `filename` is only used to detect the format from its extension.
+#[wasm_bindgen]
+pub fn analyze(filename: &str, content: &str) -> Result<String, JsError> {
+    analyze_json(filename, content).map_err(|e| JsError::new(&e.to_string()))
+}
+
+/// Validate a pasted `.conflow.yaml`
+///
+/// Returns `{"valid": bool, "errors": [...], "warnings": [...]}`; YAML and
+/// structure errors are reported in `errors` rather than thrown.
+#[wasm_bindgen(js_name = validatePipeline)]
+pub fn validate_pipeline(content: &str) -> String {
+    validate_pipeline_json(content)
+}
+
+/// Content of a schema in the RSR registry, e.g. `rsr:pipeline`
+#[wasm_bindgen]
+pub fn schema(id: &str) -> Result<String, JsError> {
+    RsrSchemaRegistry::new()
+        .get_content(id)
+        .map_err(|e| JsError::new(&e.to_string()))
+}
+
+fn analyze_json(filename: &str, content: &str) -> Result<String, ConflowError> {
+    let analysis = ConfigAnalyzer::new().analyze_content(Path::new(filename), content)?;
+    crate::report::to_json(&analysis)
+}
+
+fn validate_pipeline_json(content: &str) -> String {
+    let result = PipelineValidator::validate_yaml(content);
+    serde_json::json!({
+        "valid": result.is_valid(),
+        "errors": result.errors,
+        "warnings": result.warnings,
+    })
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_playground_calls() {
+        let json = analyze_json("config.yaml", "name: app\nreplicas: 3\n").unwrap();
+        let analysis: crate::analyzer::Analysis = crate::report::from_json(&json).unwrap();
+        assert_eq!(analysis.format, crate::analyzer::ConfigFormat::Yaml);
+
+        let valid = "name: app\nstages:\n  - name: check\n    tool:\n      type: shell\n      \
+                     command: \"true\"\n    input: \"*.yaml\"\n";
+        let value: serde_json::Value =
+            serde_json::from_str(&validate_pipeline_json(valid)).unwrap();
+        assert_eq!(value["valid"], true);
+
+        let value: serde_json::Value =
+            serde_json::from_str(&validate_pipeline_json("name: app\nstages: []\nbogus: 1\n"))
+                .unwrap();
+        assert_eq!(value["valid"], false);
+        assert!(value["errors"][0].as_str().unwrap().contains("bogus"));
+    }
+}
@@ -0,0 +1,509 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright (c) 2025 conflow contributors
+
+//! Multi-project workspaces
+//!
+//! A `conflow-workspace.yaml` lists projects, as directories or Git
+//! repositories, with settings they share, so `conflow workspace run` and
+//! `conflow workspace report` can work across all of them:
+//!
+//! ```yaml
+//! version: "1"
+//! name: platform
+//! settings:
+//!   pipeline: .conflow.yaml
+//!   env: { DEPLOY_ENV: staging }
+//! projects:
+//!   - path: services/web
+//!   - git: https://github.com/example/api.git
+//!     ref: main
+//!     stages: [validate]
+//! ```
+//!
+//! Git projects are cloned under `.conflow/workspace/` next to the
+//! workspace file.
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+use miette::NamedSource;
+use serde::{Deserialize, Serialize};
+
+use crate::errors::ConflowError;
+use crate::rsr::{ComplianceChecker, ComplianceLevel, ComplianceReport};
+use crate::utils::git;
+
+/// Default workspace file name
+pub const WORKSPACE_FILE: &str = "conflow-workspace.yaml";
+
+/// Where Git projects are cloned, relative to the workspace root
+pub const CHECKOUT_DIR: &str = ".conflow/workspace";
+
+/// Contents of a workspace file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Workspace {
+    /// Workspace file version
+    #[serde(default = "default_version")]
+    pub version: String,
+
+    /// Workspace name
+    #[serde(default)]
+    pub name: Option<String>,
+
+    /// Settings every project shares
+    #[serde(default)]
+    pub settings: WorkspaceSettings,
+
+    /// Projects in the workspace
+    pub projects: Vec<WorkspaceProject>,
+}
+
+fn default_version() -> String {
+    "1".to_string()
+}
+
+/// Settings shared by the projects of a workspace
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WorkspaceSettings {
+    /// Pipeline file, relative to each project (default: `.conflow.yaml`)
+    #[serde(default)]
+    pub pipeline: Option<PathBuf>,
+
+    /// Only run these stages
+    #[serde(default)]
+    pub stages: Vec<String>,
+
+    /// Environment variables for every run
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+}
+
+/// A project of a workspace: a directory, or a Git repository
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WorkspaceProject {
+    /// Name (default: the directory or repository name)
+    #[serde(default)]
+    pub name: Option<String>,
+
+    /// Directory, relative to the workspace file
+    #[serde(default)]
+    pub path: Option<PathBuf>,
+
+    /// Git repository URL
+    #[serde(default)]
+    pub git: Option<String>,
+
+    /// Branch or tag of the Git repository (default: its default branch)
+    #[serde(default, rename = "ref")]
+    pub git_ref: Option<String>,
+
+    /// Pipeline file, replacing the shared setting
+    #[serde(default)]
+    pub pipeline: Option<PathBuf>,
+
+    /// Stages to run, replacing the shared setting
+    #[serde(default)]
+    pub stages: Vec<String>,
+
+    /// Environment variables, added to the shared ones
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+}
+
+impl WorkspaceProject {
+    /// The project's name, or the last part of its path or URL
+    pub fn name(&self) -> String {
+        if let Some(ref name) = self.name {
+            return name.clone();
+        }
+        let source = match (&self.path, &self.git) {
+            (Some(path), _) => path.to_string_lossy().into_owned(),
+            (None, Some(url)) => url.clone(),
+            (None, None) => String::new(),
+        };
+        let last = source
+            .trim_end_matches('/')
+            .rsplit(['/', '\\', ':'])
+            .next()
+            .unwrap_or_default();
+        last.trim_end_matches(".git").to_string()
+    }
+}
+
+/// A workspace project, checked out, with the shared settings applied
+#[derive(Debug, Clone)]
+pub struct Member {
+    pub name: String,
+    /// Project directory
+    pub root: PathBuf,
+    /// Pipeline file, relative to `root`
+    pub pipeline: PathBuf,
+    pub stages: Vec<String>,
+    pub env: BTreeMap<String, String>,
+}
+
+impl Workspace {
+    /// Load and check a workspace file
+    pub fn load(path: &Path) -> Result<Self, ConflowError> {
+        let content = std::fs::read_to_string(path).map_err(|e| ConflowError::FileReadError {
+            path: path.to_path_buf(),
+            error: e.to_string(),
+        })?;
+        Self::from_yaml(&content, &path.display().to_string())
+    }
+
+    /// Parse and check a workspace file's content
+    pub fn from_yaml(yaml: &str, file: &str) -> Result<Self, ConflowError> {
+        let workspace: Self = crate::utils::yaml::parse(yaml, file)?;
+
+        let mut names = HashSet::new();
+        for project in &workspace.projects {
+            let problem = match (&project.path, &project.git) {
+                (Some(_), Some(_)) => Some("sets both 'path' and 'git'"),
+                (None, None) => Some("needs 'path' or 'git'"),
+                (Some(_), None) if project.git_ref.is_some() => Some("sets 'ref' without 'git'"),
+                _ => None,
+            };
+            let name = project.name();
+            if let Some(problem) = problem {
+                return Err(invalid(file, format!("project '{}' {}", name, problem)));
+            }
+            if name.is_empty() {
+                return Err(invalid(file, "a project has no name".to_string()));
+            }
+            if !valid_name(&name) {
+                return Err(invalid(
+                    file,
+                    format!("project name '{}' must be a single directory name", name),
+                ));
+            }
+            if !names.insert(name.clone()) {
+                return Err(invalid(
+                    file,
+                    format!("more than one project is named '{}'", name),
+                ));
+            }
+        }
+        Ok(workspace)
+    }
+
+    /// Check out the projects and apply the shared settings
+    ///
+    /// `root` is the directory of the workspace file. Git projects are
+    /// cloned when missing, and cloned afresh with `update`.
+    pub fn members(&self, root: &Path, update: bool) -> Result<Vec<Member>, ConflowError> {
+        self.projects
+            .iter()
+            .map(|project| {
+                let name = project.name();
+                if !valid_name(&name) {
+                    return Err(invalid(
+                        WORKSPACE_FILE,
+                        format!("project name '{}' must be a single directory name", name),
+                    ));
+                }
+                let dir = match (&project.path, &project.git) {
+                    (Some(path), _) => root.join(path),
+                    (None, Some(url)) => checkout(
+                        url,
+                        project.git_ref.as_deref(),
+                        &root.join(CHECKOUT_DIR).join(&name),
+                        update,
+                    )?,
+                    (None, None) => unreachable!("checked when loading"),
+                };
+                if !dir.is_dir() {
+                    return Err(ConflowError::FileNotFound {
+                        path: dir,
+                        help: Some(format!("Check the path of project '{}'", name)),
+                    });
+                }
+
+                let mut env = self.settings.env.clone();
+                env.extend(project.env.clone());
+                Ok(Member {
+                    name,
+                    root: dir,
+                    pipeline: project
+                        .pipeline
+                        .clone()
+                        .or_else(|| self.settings.pipeline.clone())
+                        .unwrap_or_else(|| PathBuf::from(".conflow.yaml")),
+                    stages: match project.stages.is_empty() {
+                        true => self.settings.stages.clone(),
+                        false => project.stages.clone(),
+                    },
+                    env,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Whether `name` can name a checkout directory: one plain path component
+fn valid_name(name: &str) -> bool {
+    let mut parts = Path::new(name).components();
+    matches!((parts.next(), parts.next()), (Some(Component::Normal(_)), None))
+}
+
+/// Clone a Git project into `dest` unless it is there already
+///
+/// The clone is made beside `dest` and renamed into place, and only a
+/// directory holding a Git checkout is ever replaced.
+fn checkout(
+    url: &str,
+    git_ref: Option<&str>,
+    dest: &Path,
+    update: bool,
+) -> Result<PathBuf, ConflowError> {
+    // A symlink is never a checkout, even to one
+    let meta = std::fs::symlink_metadata(dest).ok();
+    let checked_out = meta.as_ref().is_some_and(|m| m.is_dir()) && dest.join(".git").is_dir();
+    if meta.is_some() && !checked_out {
+        return Err(ConflowError::FileWriteError {
+            path: dest.to_path_buf(),
+            error: "exists and is not a Git checkout; move it away to clone the project".into(),
+        });
+    }
+    if checked_out && !update {
+        return Ok(dest.to_path_buf());
+    }
+
+    let parent = dest.parent().unwrap_or(Path::new("."));
+    let write_error = |e: std::io::Error| ConflowError::FileWriteError {
+        path: dest.to_path_buf(),
+        error: e.to_string(),
+    };
+    std::fs::create_dir_all(parent).map_err(write_error)?;
+    let temp = tempfile::Builder::new()
+        .prefix(".clone-")
+        .tempdir_in(parent)
+        .map_err(write_error)?;
+    let clone = temp.path().join("repo");
+    match git_ref {
+        Some(git_ref) => git::clone_tag(url, git_ref, &clone)?,
+        None => git::clone_head(url, &clone)?,
+    }
+    if checked_out {
+        std::fs::remove_dir_all(dest).map_err(write_error)?;
+    }
+    std::fs::rename(&clone, dest).map_err(write_error)?;
+    Ok(dest.to_path_buf())
+}
+
+fn invalid(file: &str, message: String) -> ConflowError {
+    ConflowError::ConfigParse {
+        file: file.to_string(),
+        message,
+        source_code: Arc::new(NamedSource::new(file, String::new())),
+        span: None,
+        help: Some("Give each project either 'path' or 'git', and a unique name".into()),
+    }
+}
+
+/// Status of a requirement in one project
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatrixCell {
+    Met,
+    Unmet,
+    /// Unmet, but covered by an active waiver
+    Waived,
+    /// The project doesn't check the requirement
+    NotChecked,
+}
+
+impl MatrixCell {
+    /// Symbol for tables
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Self::Met => "✓",
+            Self::Unmet => "✗",
+            Self::Waived => "~",
+            Self::NotChecked => "·",
+        }
+    }
+}
+
+/// Compliance level and score of one project
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectCompliance {
+    pub name: String,
+    pub level: ComplianceLevel,
+    pub score: f64,
+}
+
+/// One requirement across the projects
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatrixRow {
+    pub requirement_id: String,
+    /// Status per project, in the order of [`ComplianceMatrix::projects`]
+    pub cells: Vec<MatrixCell>,
+}
+
+/// Requirements by project, across a workspace
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComplianceMatrix {
+    pub projects: Vec<ProjectCompliance>,
+    /// Every requirement any project checks, by ID
+    pub requirements: Vec<MatrixRow>,
+}
+
+impl ComplianceMatrix {
+    /// Build the matrix from each project's report
+    pub fn new(reports: &[(String, ComplianceReport)]) -> Self {
+        let ids: BTreeSet<&str> = reports
+            .iter()
+            .flat_map(|(_, report)| &report.requirements)
+            .map(|r| r.requirement_id.as_str())
+            .collect();
+
+        let requirements = ids
+            .into_iter()
+            .map(|id| MatrixRow {
+                requirement_id: id.to_string(),
+                cells: reports
+                    .iter()
+                    .map(|(_, report)| {
+                        match report.requirements.iter().find(|r| r.requirement_id == id) {
+                            Some(r) if r.met => MatrixCell::Met,
+                            Some(r) if r.waived => MatrixCell::Waived,
+                            Some(_) => MatrixCell::Unmet,
+                            None => MatrixCell::NotChecked,
+                        }
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Self {
+            projects: reports
+                .iter()
+                .map(|(name, report)| ProjectCompliance {
+                    name: name.clone(),
+                    level: report.level,
+                    score: report.score,
+                })
+                .collect(),
+            requirements,
+        }
+    }
+
+    /// Check each member's compliance and build the matrix
+    pub fn check(members: &[Member]) -> Result<Self, ConflowError> {
+        let reports = members
+            .iter()
+            .map(|member| {
+                let report = ComplianceChecker::for_project(&member.root)?.check(&member.root)?;
+                Ok((member.name.clone(), report))
+            })
+            .collect::<Result<Vec<_>, ConflowError>>()?;
+        Ok(Self::new(&reports))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_workspace() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let root = temp.path();
+        std::fs::create_dir_all(root.join("services/web")).unwrap();
+        std::fs::create_dir_all(root.join("services/api")).unwrap();
+        std::fs::write(
+            root.join("services/web/.conflow.yaml"),
+            r#"
+version: "1"
+name: web
+stages:
+  - name: lint
+    tool: { type: shell, command: "true" }
+    input: "*.yaml"
+"#,
+        )
+        .unwrap();
+
+        let workspace = Workspace::from_yaml(
+            r#"
+name: platform
+settings:
+  stages: [lint]
+  env: { DEPLOY_ENV: staging, REGION: eu }
+projects:
+  - path: services/web
+  - path: services/api
+    name: backend
+    pipeline: ci.yaml
+    env: { REGION: us }
+"#,
+            WORKSPACE_FILE,
+        )
+        .unwrap();
+        let members = workspace.members(root, false).unwrap();
+        assert_eq!(members[0].name, "web");
+        assert_eq!(members[0].pipeline, PathBuf::from(".conflow.yaml"));
+        assert_eq!(members[0].stages, ["lint"]);
+        assert_eq!(members[1].name, "backend");
+        assert_eq!(members[1].pipeline, PathBuf::from("ci.yaml"));
+        assert_eq!(members[1].env["REGION"], "us");
+        assert_eq!(members[1].env["DEPLOY_ENV"], "staging");
+
+        let matrix = ComplianceMatrix::check(&members).unwrap();
+        assert_eq!(matrix.projects.len(), 2);
+        assert!(matrix.requirements.iter().all(|row| row.cells.len() == 2));
+        // Only web has a pipeline
+        assert!(matrix
+            .requirements
+            .iter()
+            .any(|row| row.cells == [MatrixCell::Met, MatrixCell::Unmet]));
+
+        let project = |yaml: &str| {
+            Workspace::from_yaml(&format!("projects:\n{}", yaml), WORKSPACE_FILE).map(|_| ())
+        };
+        assert!(project("  - { path: a, git: https://example.com/a.git }\n").is_err());
+        assert!(project("  - { name: a }\n").is_err());
+        assert!(project("  - { path: a, ref: main }\n").is_err());
+        assert!(project("  - path: x/a\n  - path: y/a\n").is_err());
+        assert_eq!(
+            WorkspaceProject {
+                git: Some("git@example.com:org/api.git".into()),
+                ..Default::default()
+            }
+            .name(),
+            "api"
+        );
+        assert!(workspace.members(&root.join("missing"), false).is_err());
+
+        // Names become checkout directories, so they can't leave them
+        assert!(project("  - { name: ../../victim, git: ./victim }\n").is_err());
+        assert!(project("  - git: https://example.com/org/..\n").is_err());
+        let escaping = Workspace {
+            version: default_version(),
+            name: None,
+            settings: WorkspaceSettings::default(),
+            projects: vec![WorkspaceProject {
+                name: Some("../victim".into()),
+                git: Some("./victim".into()),
+                ..Default::default()
+            }],
+        };
+        assert!(escaping.members(root, true).is_err());
+    }
+
+    #[test]
+    fn test_checkout_keeps_other_directories() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let dest = temp.path().join("api");
+        std::fs::create_dir_all(&dest).unwrap();
+        std::fs::write(dest.join("notes.txt"), "mine").unwrap();
+
+        assert!(checkout("./missing", None, &dest, true).is_err());
+        assert_eq!(std::fs::read_to_string(dest.join("notes.txt")).unwrap(), "mine");
+    }
+}